@@ -120,11 +120,14 @@ main() {
     # --- Phase 1: Basic System Configuration ---
     log_info "=== Phase 1: Basic System Configuration ==="
 
+    configure_proxy
     configure_localization
     configure_hostname
     create_user_account
     configure_sudoers
+    configure_ssh_keys
     enable_base_services
+    configure_time_sync
 
     # --- Phase 2: Bootloader & Initramfs ---
     log_info "=== Phase 2: Bootloader & Initramfs ==="
@@ -140,11 +143,17 @@ main() {
     install_desktop_environment
     install_display_manager
     install_gpu_drivers
+    install_vm_guest_tools
+    install_audio
+    configure_power_management
+    configure_initramfs_modules
+    configure_nvidia_hook
+    regenerate_initramfs
 
     # --- Phase 4: Additional Software ---
     log_info "=== Phase 4: Additional Software ==="
 
-    install_aur_helper
+    # The AUR helper was built before the chroot configuration (install.sh)
     install_flatpak
     install_additional_packages
     configure_plymouth
@@ -164,6 +173,37 @@ main() {
 # PHASE 1: BASIC SYSTEM CONFIGURATION
 # =============================================================================
 
+configure_proxy() {
+    if [[ -z "${HTTP_PROXY:-}" && -z "${HTTPS_PROXY:-}" ]]; then
+        log_info "No proxy configured"
+        return 0
+    fi
+
+    # pacman downloads with curl, which reads the lowercase names
+    export http_proxy="${HTTP_PROXY:-}"
+    export https_proxy="${HTTPS_PROXY:-${HTTP_PROXY:-}}"
+    export no_proxy="${NO_PROXY:-}"
+
+    log_info "Writing the proxy to /etc/environment"
+    local name value
+    for name in http_proxy https_proxy no_proxy; do
+        value="${!name}"
+        sed -i "/^${name}=/Id" /etc/environment 2>/dev/null || true
+        if [[ -n "$value" ]]; then
+            echo "${name}=${value}" >> /etc/environment
+            echo "${name^^}=${value}" >> /etc/environment
+        fi
+    done
+
+    # sudo resets the environment; keep the proxy for sudo pacman
+    cat > /etc/sudoers.d/10-proxy << 'EOF'
+Defaults env_keep += "http_proxy https_proxy no_proxy HTTP_PROXY HTTPS_PROXY NO_PROXY"
+EOF
+    chmod 440 /etc/sudoers.d/10-proxy
+
+    log_success "Proxy configured"
+}
+
 configure_localization() {
     log_info "Configuring localization..."
 
@@ -192,8 +232,13 @@ configure_localization() {
         fi
     fi
 
-    # Set hardware clock
-    hwclock --systohc
+    # Set hardware clock; localtime keeps Windows on a dual boot right
+    if [[ "${HARDWARE_CLOCK:-UTC}" == "localtime" ]]; then
+        log_info "Keeping the hardware clock in local time"
+        hwclock --systohc --localtime
+    else
+        hwclock --systohc --utc
+    fi
 
     # Set keymap
     if [[ -n "${KEYMAP:-}" ]]; then
@@ -265,32 +310,81 @@ configure_sudoers() {
     fi
 }
 
+configure_ssh_keys() {
+    # install.sh leaves the keys gathered from SSH_KEYS here
+    local keys=/root/authorized_keys
+    if [[ ! -s "$keys" ]]; then
+        return 0
+    fi
+
+    log_info "Authorizing SSH keys for $MAIN_USERNAME..."
+    local ssh_dir="/home/$MAIN_USERNAME/.ssh"
+    mkdir -p "$ssh_dir"
+    cat "$keys" >> "$ssh_dir/authorized_keys"
+    rm -f "$keys"
+
+    # sshd ignores keys that others can write to
+    chmod 700 "$ssh_dir"
+    chmod 600 "$ssh_dir/authorized_keys"
+    chown -R "$MAIN_USERNAME:" "$ssh_dir"
+
+    log_success "$(wc -l < "$ssh_dir/authorized_keys") SSH key(s) authorized for $MAIN_USERNAME"
+}
+
 enable_base_services() {
-    log_info "Enabling base services..."
+    local services="${SERVICES:-NetworkManager.service sshd.service fstrim.timer systemd-timesyncd.service}"
+    if [[ "$services" == "none" ]]; then
+        log_info "No services selected"
+        return 0
+    fi
 
-    # NetworkManager
-    systemctl enable NetworkManager.service 2>/dev/null || log_warn "NetworkManager service not found"
+    # systemd-timesyncd.service stands for the chosen time sync daemon
+    if [[ "${TIME_SYNC_DAEMON:-systemd-timesyncd}" == "chrony" && "${TIME_SYNC,,}" != "no" ]]; then
+        services="${services//systemd-timesyncd.service/chronyd.service}"
+    fi
 
-    # SSH (optional, but useful)
-    systemctl enable sshd.service 2>/dev/null || log_warn "sshd service not found"
+    log_info "Enabling services: $services"
 
-    # Time synchronization
-    case "${TIME_SYNC:-systemd-timesyncd}" in
-        "systemd-timesyncd"|"Yes")
-            systemctl enable systemd-timesyncd.service 2>/dev/null || true
-            ;;
-        "ntpd")
-            systemctl enable ntpd.service 2>/dev/null || true
-            ;;
-        "chrony")
-            systemctl enable chronyd.service 2>/dev/null || true
-            ;;
-    esac
+    # Packages pacstrap does not install, from the binary's service table
+    if [[ -n "${SERVICE_PACKAGES:-}" ]]; then
+        log_info "Installing packages for the selected services: $SERVICE_PACKAGES"
+        # shellcheck disable=SC2086  # Word splitting intended: space-separated package list
+        pacman -S --noconfirm --needed $SERVICE_PACKAGES
+    fi
 
-    # SSD trim timer (good for SSDs)
-    systemctl enable fstrim.timer 2>/dev/null || true
+    local unit
+    for unit in $services; do
+        # Time Sync (NTP) = No also wins over a services list that names timesyncd
+        if [[ "$unit" == "systemd-timesyncd.service" && "${TIME_SYNC,,}" == "no" ]]; then
+            continue
+        fi
+        systemctl enable "$unit" 2>/dev/null || log_warn "$unit not found"
+    done
 
-    log_success "Base services enabled"
+    log_success "Services enabled"
+}
+
+configure_time_sync() {
+    if [[ "${TIME_SYNC,,}" == "no" || -z "${NTP_SERVERS:-}" ]]; then
+        return 0
+    fi
+
+    log_info "Using NTP servers: $NTP_SERVERS"
+    local server
+    if [[ "${TIME_SYNC_DAEMON:-systemd-timesyncd}" == "chrony" ]]; then
+        # Replace the distribution pools with the chosen servers
+        if [[ -f /etc/chrony.conf ]]; then
+            sed -i -E 's/^(server|pool) /#&/' /etc/chrony.conf
+        fi
+        for server in $NTP_SERVERS; do
+            echo "server $server iburst" >> /etc/chrony.conf
+        done
+    else
+        mkdir -p /etc/systemd/timesyncd.conf.d
+        printf '[Time]\nNTP=%s\n' "$NTP_SERVERS" > /etc/systemd/timesyncd.conf.d/servers.conf
+    fi
+
+    log_success "Time sync configured"
 }
 
 # =============================================================================
@@ -300,62 +394,70 @@ enable_base_services() {
 configure_mkinitcpio() {
     log_info "Configuring mkinitcpio..."
 
-    # Build hooks list based on configuration
-    # Hook order matters! The order is:
-    # base udev autodetect modconf kms keyboard keymap consolefont block [mdadm_udev] [encrypt] [lvm2] [resume] filesystems [fsck]
-    local hooks="base udev autodetect modconf kms keyboard keymap consolefont block"
-
-    # Add RAID hook if using RAID (must come before encrypt/lvm2)
-    if [[ "${PARTITIONING_STRATEGY:-}" == *"raid"* ]]; then
-        hooks="$hooks mdadm_udev"
-        log_info "Added mdadm_udev hook for RAID"
+    # Save the RAID layout so mdadm_udev can assemble the arrays at boot
+    if [[ "${PARTITIONING_STRATEGY:-}" == *"raid"* ]] && command -v mdadm &>/dev/null; then
+        mdadm --detail --scan >> /etc/mdadm.conf 2>/dev/null || true
+    fi
 
-        # Save mdadm configuration
-        if command -v mdadm &>/dev/null; then
-            mdadm --detail --scan >> /etc/mdadm.conf 2>/dev/null || true
+    local hooks
+    if [[ -n "${INITRAMFS_HOOKS:-}" && "$INITRAMFS_HOOKS" != "auto" ]]; then
+        # Configured hooks replace the generated list; the installer has
+        # already checked their order against the storage layout
+        hooks="$INITRAMFS_HOOKS"
+        log_info "Using the configured initramfs hooks"
+    else
+        # Build hooks list based on configuration
+        # Hook order matters! The order is:
+        # base udev autodetect modconf kms keyboard keymap consolefont block [mdadm_udev] [encrypt] [lvm2] [resume] filesystems [fsck]
+        hooks="base udev autodetect modconf kms keyboard keymap consolefont block"
+
+        # Add RAID hook if using RAID (must come before encrypt/lvm2)
+        if [[ "${PARTITIONING_STRATEGY:-}" == *"raid"* ]]; then
+            hooks="$hooks mdadm_udev"
+            log_info "Added mdadm_udev hook for RAID"
         fi
-    fi
 
-    # Add encryption hook if using LUKS (must come before lvm2 for LUKS-on-LVM)
-    if [[ "${ENCRYPTION:-no}" == "yes" ]] || [[ "${PARTITIONING_STRATEGY:-}" == *"luks"* ]]; then
-        hooks="$hooks encrypt"
-        log_info "Added encrypt hook for LUKS"
-    fi
+        # Add encryption hook if using LUKS (must come before lvm2 for LUKS-on-LVM)
+        if [[ "${ENCRYPTION:-no}" == "yes" ]] || [[ "${PARTITIONING_STRATEGY:-}" == *"luks"* ]]; then
+            hooks="$hooks encrypt"
+            log_info "Added encrypt hook for LUKS"
+        fi
 
-    # Add LVM hook if using LVM (must come after encrypt for LUKS-on-LVM)
-    if [[ "${PARTITIONING_STRATEGY:-}" == *"lvm"* ]]; then
-        hooks="$hooks lvm2"
-        log_info "Added lvm2 hook"
-    fi
+        # Add LVM hook if using LVM (must come after encrypt for LUKS-on-LVM)
+        if [[ "${PARTITIONING_STRATEGY:-}" == *"lvm"* ]]; then
+            hooks="$hooks lvm2"
+            log_info "Added lvm2 hook"
+        fi
 
-    # Add Plymouth hook if enabled (must come after encrypt for plymouth-encrypt)
-    if [[ "${PLYMOUTH:-No}" == "Yes" ]]; then
-        # Use plymouth-encrypt if encryption is enabled, otherwise just plymouth
-        if [[ "${ENCRYPTION:-no}" == "yes" ]] || [[ "${PARTITIONING_STRATEGY:-}" == *"luks"* ]]; then
-            # Replace encrypt with plymouth-encrypt for integrated password prompt
-            hooks="${hooks/encrypt/plymouth-encrypt}"
-            log_info "Using plymouth-encrypt hook for encrypted Plymouth"
-        else
-            hooks="$hooks plymouth"
-            log_info "Added plymouth hook"
+        # Add Plymouth hook if enabled (must come after encrypt for plymouth-encrypt)
+        if [[ "${PLYMOUTH:-No}" == "Yes" ]]; then
+            # Use plymouth-encrypt if encryption is enabled, otherwise just plymouth
+            if [[ "${ENCRYPTION:-no}" == "yes" ]] || [[ "${PARTITIONING_STRATEGY:-}" == *"luks"* ]]; then
+                # Replace encrypt with plymouth-encrypt for integrated password prompt
+                hooks="${hooks/encrypt/plymouth-encrypt}"
+                log_info "Using plymouth-encrypt hook for encrypted Plymouth"
+            else
+                hooks="$hooks plymouth"
+                log_info "Added plymouth hook"
+            fi
         fi
-    fi
 
-    # Add resume hook for hibernation (if swap exists)
-    if [[ "${WANT_SWAP:-no}" == "yes" ]]; then
-        hooks="$hooks resume"
-        log_info "Added resume hook for hibernation support"
-    fi
+        # Add resume hook for hibernation
+        if [[ "${HIBERNATION:-No}" == "Yes" ]]; then
+            hooks="$hooks resume"
+            log_info "Added resume hook for hibernation support"
+        fi
 
-    # Final hooks - filesystems is always needed
-    hooks="$hooks filesystems"
+        # Final hooks - filesystems is always needed
+        hooks="$hooks filesystems"
 
-    # Add fsck hook only for non-Btrfs filesystems (Btrfs uses its own tools)
-    if [[ "${ROOT_FILESYSTEM_TYPE:-ext4}" != "btrfs" ]]; then
-        hooks="$hooks fsck"
-        log_info "Added fsck hook"
-    else
-        log_info "Skipping fsck hook (Btrfs uses its own check tools)"
+        # Add fsck hook only for non-Btrfs filesystems (Btrfs uses its own tools)
+        if [[ "${ROOT_FILESYSTEM_TYPE:-ext4}" != "btrfs" ]]; then
+            hooks="$hooks fsck"
+            log_info "Added fsck hook"
+        else
+            log_info "Skipping fsck hook (Btrfs uses its own check tools)"
+        fi
     fi
 
     # Update mkinitcpio.conf
@@ -373,14 +475,92 @@ configure_mkinitcpio() {
             fi
         fi
 
-        # Regenerate initramfs
-        mkinitcpio -P
-        log_success "Initramfs regenerated"
+        # bcachefs is a module too, and the root has to be mounted from the initramfs
+        if [[ "${ROOT_FILESYSTEM_TYPE:-ext4}" == "bcachefs" ]]; then
+            if ! grep -q "bcachefs" /etc/mkinitcpio.conf; then
+                sed -i 's/^MODULES=(\(.*\))/MODULES=(\1 bcachefs)/' /etc/mkinitcpio.conf
+                sed -i 's/MODULES=( /MODULES=(/' /etc/mkinitcpio.conf
+                log_info "Added bcachefs module to mkinitcpio.conf"
+            fi
+        fi
+
+        # Embed the LUKS key file so an encrypted /boot asks only once (in GRUB)
+        if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" && -f /crypto_keyfile.bin ]]; then
+            sed -i 's|^FILES=.*|FILES=(/crypto_keyfile.bin)|' /etc/mkinitcpio.conf
+            log_info "Added /crypto_keyfile.bin to the initramfs"
+        fi
+
+        # The presets are regenerated by regenerate_initramfs once the GPU
+        # drivers and the early KMS modules are in place
     else
         log_error "mkinitcpio.conf not found"
     fi
 }
 
+# Physical offset of a swap file, in pages, for resume_offset=
+swap_file_offset() {
+    local file="$1"
+    if [[ "$(findmnt -n -o FSTYPE -T "$file")" == "btrfs" ]]; then
+        btrfs inspect-internal map-swapfile -r "$file"
+    else
+        # First extent of `filefrag -v`: "0:  0..  0:  34816..  34816:  1:"
+        filefrag -v "$file" | awk '$1 == "0:" { sub(/\.\.$/, "", $4); print $4; exit }'
+    fi
+}
+
+# Kernel parameters that resume from the first swap in fstab. A swap
+# partition is named by its UUID, a swap file by the UUID of the filesystem
+# holding it and the file's offset in that filesystem.
+resume_parameters() {
+    local fstab="${1:-/etc/fstab}"
+    local source
+    source=$(awk '$1 !~ /^#/ && $3 == "swap" { print $1; exit }' "$fstab" 2>/dev/null)
+
+    case "$source" in
+        UUID=*)
+            echo "resume=$source"
+            ;;
+        /dev/*)
+            local uuid
+            uuid=$(blkid -s UUID -o value "$source")
+            [[ -n "$uuid" ]] || return 1
+            echo "resume=UUID=$uuid"
+            ;;
+        /*)
+            local uuid offset
+            uuid=$(findmnt -n -o UUID -T "$source")
+            offset=$(swap_file_offset "$source")
+            [[ -n "$uuid" && -n "$offset" ]] || return 1
+            echo "resume=UUID=$uuid resume_offset=$offset"
+            ;;
+        *)
+            return 1
+            ;;
+    esac
+}
+
+# Resume parameters when hibernation is enabled, empty otherwise; the
+# output is captured, so warnings go to stderr
+hibernation_cmdline() {
+    if [[ "${HIBERNATION:-No}" != "Yes" ]]; then
+        return 0
+    fi
+    # zram swap is in memory: the image always goes to the disk swap
+    if [[ " ${ADDITIONAL_PACKAGES:-} " == *" zram-generator "* ]]; then
+        log_warn "zram swap cannot hold the hibernation image, resuming uses the disk swap" >&2
+    fi
+    if ! resume_parameters; then
+        log_warn "No swap in /etc/fstab, hibernation will not be able to resume" >&2
+    fi
+}
+
+# Kernel parameter of the NVIDIA extras, empty otherwise
+nvidia_cmdline() {
+    if nvidia_extras; then
+        echo "nvidia_drm.modeset=1"
+    fi
+}
+
 install_bootloader() {
     log_info "Installing bootloader: ${BOOTLOADER:-grub}"
 
@@ -391,6 +571,9 @@ install_bootloader() {
         "systemd-boot")
             install_systemd_boot
             ;;
+        "u-boot")
+            install_uboot
+            ;;
         *)
             log_warn "Unknown bootloader: ${BOOTLOADER}, defaulting to GRUB"
             install_grub
@@ -398,9 +581,40 @@ install_bootloader() {
     esac
 }
 
+# GRUB only embeds its LUKS modules when cryptodisk is enabled, so this
+# has to happen before grub-install when /boot is encrypted
+enable_grub_cryptodisk() {
+    local grub_default="/etc/default/grub"
+    if [[ -f "$grub_default" ]] && ! grep -q "^GRUB_ENABLE_CRYPTODISK=y" "$grub_default"; then
+        echo "GRUB_ENABLE_CRYPTODISK=y" >> "$grub_default"
+    fi
+}
+
+# Check that GRUB's BIOS core image has somewhere to go on a disk: the gap
+# after the MBR on an MBR disk, a BIOS boot partition on a GPT disk
+check_bios_boot_disk() {
+    local disk="$1"
+    local bios_boot_guid="21686148-6449-6e6f-744e-656564454649"
+
+    if [[ "$(lsblk -dno PTTYPE "$disk" 2>/dev/null)" != "gpt" ]]; then
+        return 0
+    fi
+    if lsblk -nro PARTTYPE "$disk" 2>/dev/null | grep -qix "$bios_boot_guid"; then
+        return 0
+    fi
+    log_error "$disk has a GPT partition table but no BIOS boot partition"
+    log_error "Create a 1 MiB partition of type EF02 (BIOS boot) for GRUB, or boot in UEFI mode"
+    return 1
+}
+
 install_grub() {
     log_info "Installing GRUB..."
 
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+        log_info "Encrypted /boot: enabling GRUB cryptodisk support"
+        enable_grub_cryptodisk
+    fi
+
     if [[ "${BOOT_MODE:-UEFI}" == "UEFI" ]]; then
         # UEFI installation
         # Determine EFI directory (check both /efi and /boot/efi)
@@ -415,22 +629,47 @@ install_grub() {
         fi
 
         log_info "Installing GRUB for UEFI to $efi_dir"
-        grub-install --target=x86_64-efi --efi-directory="$efi_dir" --bootloader-id=GRUB --recheck || {
+        local target="x86_64-efi"
+        if [[ "$(target_arch)" == "aarch64" ]]; then
+            target="arm64-efi"
+        fi
+        grub-install --target="$target" --efi-directory="$efi_dir" --bootloader-id=GRUB --recheck || {
             log_error "GRUB installation failed"
             return 1
         }
+
+        # On a shared ESP the other loaders stay; GRUB only lists them with os-prober
+        if [[ -d "$efi_dir/EFI/Microsoft" && "${OS_PROBER:-Yes}" != "Yes" ]]; then
+            log_warn "Windows Boot Manager found on the ESP: enable OS Prober to add it to the GRUB menu"
+        fi
     else
-        # BIOS installation
-        log_info "Installing GRUB for BIOS to ${INSTALL_DISK:-/dev/sda}"
-        grub-install --target=i386-pc "${INSTALL_DISK:-/dev/sda}" --recheck || {
-            log_error "GRUB installation failed"
-            return 1
-        }
+        # BIOS installation: GRUB goes to the boot sector of every install
+        # disk (each RAID member), so any of them can start the system
+        local disks disk
+        IFS=',' read -ra disks <<< "${INSTALL_DISK:-/dev/sda}"
+        for disk in "${disks[@]}"; do
+            check_bios_boot_disk "$disk" || return 1
+            log_info "Installing GRUB for BIOS to $disk"
+            grub-install --target=i386-pc "$disk" --recheck || {
+                log_error "GRUB installation failed on $disk"
+                return 1
+            }
+        done
     fi
 
     log_success "GRUB installed"
 }
 
+# initrd line of the microcode image pacstrap put into /boot, which has to
+# come before the initramfs
+microcode_initrd() {
+    local microcode
+    microcode="$(microcode_package)"
+    if [[ -n "$microcode" && -f "/boot/${microcode}.img" ]]; then
+        echo "initrd  /${microcode}.img"
+    fi
+}
+
 install_systemd_boot() {
     log_info "Installing systemd-boot..."
 
@@ -439,17 +678,37 @@ install_systemd_boot() {
         return 1
     fi
 
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+        log_error "systemd-boot cannot read an encrypted /boot, use GRUB"
+        return 1
+    fi
+
     # Determine ESP path
     local esp_path="/efi"
     if [[ ! -d "$esp_path" ]]; then
         esp_path="/boot"
     fi
 
+    # bootctl replaces the removable-media fallback loader; on a shared ESP
+    # that belongs to the other system (usually a copy of Windows Boot Manager)
+    local fallback="${esp_path}/EFI/BOOT/BOOT$(efi_suffix | tr '[:lower:]' '[:upper:]').EFI"
+    local fallback_backup=""
+    if [[ "${EFI_PARTITION:-create}" != "create" && -f "$fallback" ]]; then
+        fallback_backup=$(mktemp)
+        cp "$fallback" "$fallback_backup"
+    fi
+
     bootctl install --esp-path="$esp_path" || {
         log_error "systemd-boot installation failed"
         return 1
     }
 
+    if [[ -n "$fallback_backup" ]]; then
+        log_info "Keeping the existing fallback loader on the shared ESP"
+        cp "$fallback_backup" "$fallback"
+        rm -f "$fallback_backup"
+    fi
+
     # Create boot entry
     mkdir -p "${esp_path}/loader/entries"
 
@@ -460,12 +719,18 @@ install_systemd_boot() {
         root_uuid=$(findmnt -n -o UUID /)
     fi
 
+    local resume nvidia microcode
+    resume=$(hibernation_cmdline)
+    nvidia=$(nvidia_cmdline)
+    microcode=$(microcode_initrd)
+
     # Create arch.conf entry
     cat > "${esp_path}/loader/entries/arch.conf" << EOF
 title   Arch Linux
-linux   /vmlinuz-${KERNEL:-linux}
-initrd  /initramfs-${KERNEL:-linux}.img
-options root=UUID=${root_uuid} rw quiet
+linux   /$(kernel_image "${KERNEL:-linux}")
+${microcode:+$microcode
+}initrd  /$(initramfs_image "${KERNEL:-linux}")
+options root=UUID=${root_uuid} rw quiet${resume:+ $resume}${nvidia:+ $nvidia}
 EOF
 
     # Create loader.conf
@@ -479,20 +744,55 @@ EOF
     log_success "systemd-boot installed"
 }
 
-configure_grub_settings() {
-    if [[ "${BOOTLOADER:-grub}" != "grub" ]]; then
-        return 0
+# U-Boot is already on the board (SPI flash, eMMC boot partition or the
+# start of the SD card) and is left alone; its distro boot reads
+# extlinux/extlinux.conf from the partition holding /boot
+install_uboot() {
+    log_info "Configuring U-Boot..."
+
+    if [[ "$(target_arch)" != "aarch64" ]]; then
+        log_error "U-Boot is only supported on aarch64 boards"
+        return 1
     fi
 
-    log_info "Configuring GRUB settings..."
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+        log_error "U-Boot cannot read an encrypted /boot, use GRUB"
+        return 1
+    fi
 
-    local grub_default="/etc/default/grub"
-    if [[ ! -f "$grub_default" ]]; then
-        log_warn "GRUB default config not found"
-        return 0
+    # Paths are relative to the partition U-Boot reads the file from
+    local prefix="/boot"
+    if mountpoint -q /boot; then
+        prefix=""
     fi
 
-    # Build kernel command line
+    local root_uuid="${ROOT_UUID:-}"
+    if [[ -z "$root_uuid" ]]; then
+        root_uuid=$(findmnt -n -o UUID /)
+    fi
+
+    local cmdline
+    cmdline=$(linux_cmdline)
+
+    mkdir -p /boot/extlinux
+    cat > /boot/extlinux/extlinux.conf << EOF
+DEFAULT arch
+TIMEOUT 30
+MENU TITLE Boot Menu
+
+LABEL arch
+    MENU LABEL Arch Linux ARM
+    LINUX ${prefix}/$(kernel_image "${KERNEL:-linux}")
+    INITRD ${prefix}/$(initramfs_image "${KERNEL:-linux}")
+    FDTDIR ${prefix}/dtbs
+    APPEND root=UUID=${root_uuid} rw ${cmdline}
+EOF
+
+    log_success "extlinux.conf written for U-Boot"
+}
+
+# Kernel parameters besides the root device, for GRUB and extlinux.conf
+linux_cmdline() {
     local cmdline="quiet"
 
     # Add encryption parameters if needed
@@ -505,17 +805,33 @@ configure_grub_settings() {
             fi
             cmdline="$cmdline cryptdevice=UUID=${LUKS_UUID}:${mapper_name}"
         fi
+
+        # GRUB already asked for the passphrase, unlock again with the key file
+        if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+            cmdline="$cmdline cryptkey=rootfs:/crypto_keyfile.bin"
+        fi
     fi
 
     # Add Btrfs subvolume rootflags if using Btrfs
     if [[ "${ROOT_FILESYSTEM_TYPE:-ext4}" == "btrfs" ]]; then
         cmdline="$cmdline rootflags=subvol=@"
-        log_info "Added Btrfs subvolume rootflags"
+        log_info "Added Btrfs subvolume rootflags" >&2
+    fi
+
+    # Add resume parameters for hibernation
+    local resume
+    resume=$(hibernation_cmdline)
+    if [[ -n "$resume" ]]; then
+        cmdline="$cmdline $resume"
+        log_info "Added $resume for hibernation" >&2
     fi
 
-    # Add resume parameter for hibernation if swap exists
-    if [[ "${WANT_SWAP:-no}" == "yes" && -n "${SWAP_UUID:-}" ]]; then
-        cmdline="$cmdline resume=UUID=${SWAP_UUID}"
+    # Add DRM modeset for the NVIDIA driver
+    local nvidia
+    nvidia=$(nvidia_cmdline)
+    if [[ -n "$nvidia" ]]; then
+        cmdline="$cmdline $nvidia"
+        log_info "Added $nvidia for the NVIDIA driver" >&2
     fi
 
     # Add Plymouth parameters if enabled
@@ -523,6 +839,26 @@ configure_grub_settings() {
         cmdline="$cmdline splash"
     fi
 
+    echo "$cmdline"
+}
+
+configure_grub_settings() {
+    if [[ "${BOOTLOADER:-grub}" != "grub" ]]; then
+        return 0
+    fi
+
+    log_info "Configuring GRUB settings..."
+
+    local grub_default="/etc/default/grub"
+    if [[ ! -f "$grub_default" ]]; then
+        log_warn "GRUB default config not found"
+        return 0
+    fi
+
+    # Build kernel command line
+    local cmdline
+    cmdline=$(linux_cmdline)
+
     # Update GRUB_CMDLINE_LINUX_DEFAULT
     sed -i "s/^GRUB_CMDLINE_LINUX_DEFAULT=.*/GRUB_CMDLINE_LINUX_DEFAULT=\"$cmdline\"/" "$grub_default"
 
@@ -535,12 +871,10 @@ configure_grub_settings() {
 
     # Enable GRUB_ENABLE_CRYPTODISK for encrypted /boot
     if [[ "${ENCRYPTION:-no}" == "yes" ]] || [[ "${PARTITIONING_STRATEGY:-}" == *"luks"* ]]; then
-        if ! grep -q "^GRUB_ENABLE_CRYPTODISK=y" "$grub_default"; then
-            echo "GRUB_ENABLE_CRYPTODISK=y" >> "$grub_default"
-        fi
+        enable_grub_cryptodisk
     fi
 
-    # Generate GRUB config
+    # Generate GRUB config; it loads the microcode images in /boot first
     grub-mkconfig -o /boot/grub/grub.cfg || {
         log_warn "grub-mkconfig failed, trying alternate path"
         grub-mkconfig -o /boot/grub/grub.cfg 2>/dev/null || true
@@ -589,30 +923,33 @@ configure_secure_boot() {
     log_info "Signing EFI binaries..."
 
     # Sign the kernel
-    local kernel="${KERNEL:-linux}"
-    if [[ -f "/boot/vmlinuz-${kernel}" ]]; then
-        sbctl sign -s "/boot/vmlinuz-${kernel}" 2>/dev/null || log_warn "Failed to sign vmlinuz-${kernel}"
+    local kernel
+    kernel="$(kernel_image "${KERNEL:-linux}")"
+    if [[ -f "/boot/${kernel}" ]]; then
+        sbctl sign -s "/boot/${kernel}" 2>/dev/null || log_warn "Failed to sign ${kernel}"
     fi
 
     # Sign bootloader based on type
+    local suffix
+    suffix="$(efi_suffix)"
     if [[ "${BOOTLOADER:-grub}" == "grub" ]]; then
         # Sign GRUB EFI binary
-        local grub_efi="/boot/efi/EFI/GRUB/grubx64.efi"
+        local grub_efi="/boot/efi/EFI/GRUB/grub${suffix}.efi"
         if [[ -f "$grub_efi" ]]; then
             sbctl sign -s "$grub_efi" 2>/dev/null || log_warn "Failed to sign GRUB"
         fi
         # Also try alternate path
-        grub_efi="/boot/EFI/GRUB/grubx64.efi"
+        grub_efi="/boot/EFI/GRUB/grub${suffix}.efi"
         if [[ -f "$grub_efi" ]]; then
             sbctl sign -s "$grub_efi" 2>/dev/null || log_warn "Failed to sign GRUB"
         fi
     else
         # Sign systemd-boot
-        local systemd_efi="/boot/efi/EFI/systemd/systemd-bootx64.efi"
+        local systemd_efi="/boot/efi/EFI/systemd/systemd-boot${suffix}.efi"
         if [[ -f "$systemd_efi" ]]; then
             sbctl sign -s "$systemd_efi" 2>/dev/null || log_warn "Failed to sign systemd-boot"
         fi
-        systemd_efi="/boot/EFI/systemd/systemd-bootx64.efi"
+        systemd_efi="/boot/EFI/systemd/systemd-boot${suffix}.efi"
         if [[ -f "$systemd_efi" ]]; then
             sbctl sign -s "$systemd_efi" 2>/dev/null || log_warn "Failed to sign systemd-boot"
         fi
@@ -698,43 +1035,19 @@ install_desktop_environment() {
 
     log_info "Installing desktop environment: $de"
 
-    case "$de" in
-        "kde"|"plasma")
-            install_packages "KDE Plasma" plasma kde-applications
-            ;;
-        "gnome")
-            install_packages "GNOME" gnome gnome-extra
-            ;;
-        "xfce")
-            install_packages "XFCE" xfce4 xfce4-goodies
-            ;;
-        "i3"|"i3wm")
-            install_packages "i3 Window Manager" i3-wm i3status i3lock dmenu rofi alacritty
-            ;;
-        "hyprland")
-            install_packages "Hyprland" hyprland waybar swaylock swayidle wlogout \
-                rofi-wayland grim slurp kitty xdg-desktop-portal-hyprland
-            ;;
-        "sway")
-            install_packages "Sway" sway swaylock swayidle waybar \
-                rofi-wayland grim slurp foot xdg-desktop-portal-wlr
-            ;;
-        "cinnamon")
-            install_packages "Cinnamon" cinnamon nemo-fileroller
-            ;;
-        "mate")
-            install_packages "MATE" mate mate-extra
-            ;;
-        "budgie")
-            install_packages "Budgie" budgie-desktop budgie-extras
-            ;;
-        "none"|"minimal"|"")
-            log_info "No desktop environment selected - skipping"
-            ;;
-        *)
-            log_warn "Unknown desktop environment: $de - skipping"
-            ;;
-    esac
+    if [[ "$de" == "none" || -z "$de" ]]; then
+        log_info "No desktop environment selected - skipping"
+        return 0
+    fi
+
+    # Packages come from scripts/desktops/desktops.json, expanded before the chroot
+    if [[ -z "${DESKTOP_PACKAGES:-}" ]]; then
+        log_warn "No packages known for desktop environment: $de - skipping"
+        return 0
+    fi
+
+    # shellcheck disable=SC2086  # Word splitting intended: space-separated package list
+    install_packages "$de" $DESKTOP_PACKAGES
 
     log_success "Desktop environment installation complete"
 }
@@ -784,6 +1097,8 @@ install_display_manager() {
 
 install_gpu_drivers() {
     local gpu="${GPU_DRIVERS:-Auto}"
+    local nvidia=()
+    read -r -a nvidia <<< "$(nvidia_packages)"
 
     log_info "Installing GPU drivers: $gpu"
 
@@ -792,7 +1107,7 @@ install_gpu_drivers() {
             # Auto-detect GPU
             if lspci | grep -qi nvidia; then
                 log_info "NVIDIA GPU detected"
-                pacman -S --noconfirm --needed nvidia nvidia-utils nvidia-settings || true
+                pacman -S --noconfirm --needed "${nvidia[@]}" || true
             fi
             if lspci | grep -qi "amd.*radeon\|radeon.*amd\|amd.*graphics"; then
                 log_info "AMD GPU detected"
@@ -803,11 +1118,8 @@ install_gpu_drivers() {
                 pacman -S --noconfirm --needed mesa lib32-mesa xf86-video-intel vulkan-intel || true
             fi
             ;;
-        "nvidia"|"NVIDIA")
-            pacman -S --noconfirm --needed nvidia nvidia-utils nvidia-settings
-            ;;
-        "nvidia-open")
-            pacman -S --noconfirm --needed nvidia-open nvidia-utils nvidia-settings
+        "nvidia"|"NVIDIA"|"nvidia-open")
+            pacman -S --noconfirm --needed "${nvidia[@]}"
             ;;
         "amd"|"AMD")
             pacman -S --noconfirm --needed mesa lib32-mesa xf86-video-amdgpu vulkan-radeon
@@ -829,70 +1141,295 @@ install_gpu_drivers() {
     log_success "GPU driver installation complete"
 }
 
-# =============================================================================
-# PHASE 4: ADDITIONAL SOFTWARE
-# =============================================================================
+# Whether the NVIDIA driver is installed: selected, or detected with Auto
+nvidia_selected() {
+    case "${GPU_DRIVERS:-Auto}" in
+        "nvidia"|"NVIDIA"|"nvidia-open")
+            return 0
+            ;;
+        "Auto"|"auto")
+            lspci | grep -qi nvidia
+            ;;
+        *)
+            return 1
+            ;;
+    esac
+}
+
+# Whether the NVIDIA driver gets DRM modeset, its modules in the initramfs
+# and the pacman hook rebuilding it
+nvidia_extras() {
+    [[ "${NVIDIA_EXTRAS:-Yes}" == "Yes" ]] && nvidia_selected
+}
+
+# Kernel module package of NVIDIA_DRIVER; prebuilt modules exist for linux
+# and linux-lts, other kernels build the DKMS package
+nvidia_module_package() {
+    local driver="nvidia-open"
+    if [[ "${GPU_DRIVERS:-}" != "nvidia-open" && "${NVIDIA_DRIVER:-open}" == "proprietary" ]]; then
+        driver="nvidia"
+    fi
+
+    case "${KERNEL:-linux}" in
+        "linux")
+            echo "$driver"
+            ;;
+        "linux-lts")
+            echo "${driver}-lts"
+            ;;
+        *)
+            echo "${driver}-dkms"
+            ;;
+    esac
+}
 
-install_aur_helper() {
-    local helper="${AUR_HELPER:-none}"
-    helper="${helper,,}"  # Convert to lowercase
+# Packages of the NVIDIA driver, with the headers DKMS builds against
+nvidia_packages() {
+    local module
+    module=$(nvidia_module_package)
+    if [[ "$module" == *-dkms ]]; then
+        echo "$module ${KERNEL:-linux}-headers nvidia-utils nvidia-settings"
+    else
+        echo "$module nvidia-utils nvidia-settings"
+    fi
+}
 
-    if [[ "$helper" == "none" || -z "$helper" ]]; then
-        log_info "No AUR helper selected"
+# Rebuild the initramfs when the driver or the kernel is updated, so the
+# NVIDIA modules in it always match the installed ones
+configure_nvidia_hook() {
+    if ! nvidia_extras; then
         return 0
     fi
 
-    log_info "Installing AUR helper: $helper"
+    mkdir -p /etc/pacman.d/hooks
+    cat > /etc/pacman.d/hooks/nvidia.hook << EOF
+[Trigger]
+Operation=Install
+Operation=Upgrade
+Operation=Remove
+Type=Package
+Target=$(nvidia_module_package)
+Target=${KERNEL:-linux}
 
-    # AUR helpers must be built as non-root user
-    local build_dir="/tmp/aur_build"
-    mkdir -p "$build_dir"
-    chown "$MAIN_USERNAME:$MAIN_USERNAME" "$build_dir"
+[Action]
+Description=Update NVIDIA module in initcpio
+Depends=mkinitcpio
+When=PostTransaction
+NeedsTargets
+Exec=/bin/sh -c 'while read -r trg; do case \$trg in linux*) exit 0; esac; done; /usr/bin/mkinitcpio -P'
+EOF
+    log_success "Added a pacman hook rebuilding the initramfs on NVIDIA driver updates"
+}
 
-    case "$helper" in
-        "paru")
-            # Install paru dependencies
-            pacman -S --noconfirm --needed base-devel git
+# Modules the selected GPU driver needs for early KMS
+early_kms_modules() {
+    local nvidia="nvidia nvidia_modeset nvidia_uvm nvidia_drm"
 
-            sudo -u "$MAIN_USERNAME" bash << 'AUREOF'
-cd /tmp/aur_build
-git clone https://aur.archlinux.org/paru.git
-cd paru
-makepkg -si --noconfirm
-AUREOF
+    case "${GPU_DRIVERS:-Auto}" in
+        "Auto"|"auto")
+            if lspci | grep -qi nvidia; then
+                echo "$nvidia"
+            fi
+            if lspci | grep -qi "amd.*radeon\|radeon.*amd\|amd.*graphics"; then
+                echo "amdgpu"
+            fi
+            if lspci | grep -qi "intel.*graphics\|intel.*uhd\|intel.*iris"; then
+                echo "i915"
+            fi
+            ;;
+        "nvidia"|"NVIDIA"|"nvidia-open")
+            echo "$nvidia"
+            ;;
+        "amd"|"AMD")
+            echo "amdgpu"
+            ;;
+        "intel"|"Intel")
+            echo "i915"
+            ;;
+        "nouveau")
+            echo "nouveau"
+            ;;
+    esac
+}
+
+# Add the configured modules, and the GPU modules for early KMS, to MODULES
+configure_initramfs_modules() {
+    local conf="/etc/mkinitcpio.conf"
+    local modules=()
+    local current=()
+    local module
+
+    if [[ ! -f "$conf" ]]; then
+        log_warn "mkinitcpio.conf not found, skipping initramfs modules"
+        return 0
+    fi
+
+    read -r -a modules <<< "${INITRAMFS_MODULES:-}"
+    if [[ "${EARLY_KMS:-No}" == "Yes" ]]; then
+        local kms_modules=()
+        read -r -a kms_modules <<< "$(early_kms_modules | tr '\n' ' ')"
+        if [[ ${#kms_modules[@]} -eq 0 ]]; then
+            log_warn "Early KMS: no GPU driver to load early"
+        else
+            log_info "Early KMS modules: ${kms_modules[*]}"
+            modules+=("${kms_modules[@]}")
+        fi
+    fi
+    if nvidia_extras; then
+        modules+=(nvidia nvidia_modeset nvidia_uvm nvidia_drm)
+    fi
+    # The kms hook would load nouveau ahead of the NVIDIA driver
+    if [[ " ${modules[*]} " == *" nvidia "* ]]; then
+        sed -i '/^HOOKS=/s/ kms / /' "$conf"
+        log_info "Removed the kms hook for the NVIDIA driver"
+    fi
+
+    if [[ ${#modules[@]} -eq 0 ]]; then
+        return 0
+    fi
+
+    read -r -a current <<< "$(sed -n 's/^MODULES=(\(.*\))/\1/p' "$conf")"
+    for module in "${modules[@]}"; do
+        if [[ " ${current[*]} " != *" $module "* ]]; then
+            current+=("$module")
+        fi
+    done
+    sed -i "s/^MODULES=.*/MODULES=(${current[*]})/" "$conf"
+    log_info "Updated MODULES in mkinitcpio.conf: ${current[*]}"
+}
+
+# Rebuild every kernel preset with the final hooks and modules
+regenerate_initramfs() {
+    log_info "Regenerating initramfs..."
+    if ! mkinitcpio -P; then
+        log_error "mkinitcpio failed; check HOOKS and MODULES in /etc/mkinitcpio.conf"
+        return 1
+    fi
+    log_success "Initramfs regenerated"
+}
+
+install_vm_guest_tools() {
+    local tools="${VM_GUEST_TOOLS:-None}"
+    if [[ "${tools,,}" == "none" || -z "$tools" ]]; then
+        log_info "No VM guest tools selected"
+        return 0
+    fi
+
+    # Packages and services come from the binary's GuestTools table
+    if [[ -z "${GUEST_TOOLS_PACKAGES:-}" ]]; then
+        log_warn "No packages known for VM guest tools: $tools - skipping"
+        return 0
+    fi
+
+    log_info "Installing $tools guest tools: $GUEST_TOOLS_PACKAGES"
+    # shellcheck disable=SC2086  # Word splitting intended: space-separated package list
+    pacman -S --noconfirm --needed $GUEST_TOOLS_PACKAGES
+
+    # qemu-guest-agent has no service: udev starts it when the virtio port appears
+    local service
+    for service in ${GUEST_TOOLS_SERVICES:-}; do
+        systemctl enable "$service"
+    done
+
+    log_success "VM guest tools installation complete"
+}
+
+install_audio() {
+    local audio="${AUDIO:-pipewire}"
+    if [[ "${audio,,}" == "none" || -z "$audio" ]]; then
+        log_info "No sound server selected"
+        return 0
+    fi
+
+    # Packages and user units come from the binary's AudioServer table
+    if [[ -z "${AUDIO_PACKAGES:-}" ]]; then
+        log_warn "No packages known for audio option: $audio - skipping"
+        return 0
+    fi
+
+    # Desktops pull in a sound library and sometimes a server; the one not
+    # chosen is removed first, since pipewire-pulse and pulseaudio conflict
+    local package
+    for package in ${AUDIO_REPLACES:-}; do
+        if pacman -Q "$package" &>/dev/null; then
+            log_info "Removing $package in favour of $audio"
+            pacman -Rdd --noconfirm "$package"
+        fi
+    done
+
+    log_info "Installing $audio: $AUDIO_PACKAGES"
+    # shellcheck disable=SC2086  # Word splitting intended: space-separated package list
+    pacman -S --noconfirm --needed $AUDIO_PACKAGES
+
+    # User units, started in every user session
+    local service
+    for service in ${AUDIO_USER_SERVICES:-}; do
+        systemctl --global enable "$service"
+    done
+
+    log_success "Audio setup complete"
+}
+
+configure_power_management() {
+    local power="${POWER_MANAGEMENT:-none}"
+    local lid="${LID_SWITCH:-suspend}"
+    local packages=()
+    local services=()
+
+    # tlp conflicts with power-profiles-daemon, which KDE may have pulled in
+    case "${power,,}" in
+        "tlp")
+            packages=(tlp)
+            services=(tlp.service)
+            if pacman -Q power-profiles-daemon &>/dev/null; then
+                log_info "Removing power-profiles-daemon in favour of TLP"
+                pacman -Rdd --noconfirm power-profiles-daemon
+            fi
             ;;
-        "yay")
-            # Install yay dependencies
-            pacman -S --noconfirm --needed base-devel git go
-
-            sudo -u "$MAIN_USERNAME" bash << 'AUREOF'
-cd /tmp/aur_build
-git clone https://aur.archlinux.org/yay.git
-cd yay
-makepkg -si --noconfirm
-AUREOF
+        "power-profiles-daemon")
+            packages=(power-profiles-daemon)
+            services=(power-profiles-daemon.service)
             ;;
-        "pikaur")
-            pacman -S --noconfirm --needed base-devel git python
-
-            sudo -u "$MAIN_USERNAME" bash << 'AUREOF'
-cd /tmp/aur_build
-git clone https://aur.archlinux.org/pikaur.git
-cd pikaur
-makepkg -si --noconfirm
-AUREOF
+        "none"|"")
+            log_info "No power manager selected"
             ;;
         *)
-            log_warn "Unknown AUR helper: $helper"
+            log_warn "Unknown power management option: $power"
             ;;
     esac
 
-    # Cleanup
-    rm -rf "$build_dir"
+    if [[ ${#packages[@]} -gt 0 ]]; then
+        log_info "Installing $power: ${packages[*]}"
+        pacman -S --noconfirm --needed "${packages[@]}"
+
+        local service
+        for service in "${services[@]}"; do
+            systemctl enable "$service"
+        done
+
+        # TLP switches radios itself; systemd-rfkill would undo it at boot
+        if [[ "${power,,}" == "tlp" ]]; then
+            systemctl mask systemd-rfkill.service systemd-rfkill.socket
+        fi
+    fi
+
+    # Suspending is logind's own default
+    if [[ -n "$lid" && "$lid" != "suspend" ]]; then
+        log_info "Lid close action: $lid"
+        mkdir -p /etc/systemd/logind.conf.d
+        cat > /etc/systemd/logind.conf.d/lid.conf << EOF
+[Login]
+HandleLidSwitch=$lid
+EOF
+    fi
 
-    log_success "AUR helper installation complete"
+    log_success "Power management configuration complete"
 }
 
+# =============================================================================
+# PHASE 4: ADDITIONAL SOFTWARE
+# =============================================================================
+
 install_flatpak() {
     if [[ "${FLATPAK:-No}" != "Yes" ]]; then
         log_info "Flatpak not requested"
@@ -65,13 +65,27 @@ load_config_from_json() {
     export SEPARATE_HOME="$(jq -r '.separate_home // "no"' "$config_file")"
     export ENCRYPTION="$(jq -r '.encryption // "no"' "$config_file")"
     export ENCRYPTION_PASSWORD="$(jq -r '.encryption_password // ""' "$config_file")"
+    export ENCRYPTED_BOOT="$(jq -r '.encrypted_boot // "No"' "$config_file")"
+    export EFI_PARTITION="$(jq -r '.efi_partition // "create"' "$config_file")"
+    export EXISTING_OS="$(jq -r '.existing_os // "protect"' "$config_file")"
+    export PARTITION_MOUNTS="$(jq -r '.partition_mounts // ""' "$config_file")"
     export SWAP="$(jq -r '.swap // "yes"' "$config_file")"
     export SWAP_SIZE="$(jq -r '.swap_size // "2GB"' "$config_file")"
+    export HIBERNATION="$(jq -r '.hibernation // "No"' "$config_file")"
+    export LVM_VOLUME_GROUP="$(jq -r '.lvm_volume_group // "arch"' "$config_file")"
+    export LVM_ROOT_SIZE="$(jq -r '.lvm_root_size // "50G"' "$config_file")"
+    export LVM_VAR_SIZE="$(jq -r '.lvm_var_size // "none"' "$config_file")"
+    export LVM_HOME_SIZE="$(jq -r '.lvm_home_size // "100%FREE"' "$config_file")"
+    export LVM_THIN="$(jq -r '.lvm_thin // "No"' "$config_file")"
+    export VAR_PARTITION_SIZE="$(jq -r '.var_partition_size // "none"' "$config_file")"
+    export TMP_POLICY="$(jq -r '.tmp_policy // "tmpfs"' "$config_file")"
+    export TMP_SIZE="$(jq -r '.tmp_size // "50%"' "$config_file")"
     export TIMEZONE_REGION="$(jq -r '.timezone_region // "UTC"' "$config_file")"
     export TIMEZONE="$(jq -r '.timezone // "UTC"' "$config_file")"
     export LOCALE="$(jq -r '.locale // "en_US.UTF-8"' "$config_file")"
     export KEYMAP="$(jq -r '.keymap // "us"' "$config_file")"
     export KERNEL="$(jq -r '.kernel // "linux"' "$config_file")"
+    export MICROCODE="$(jq -r '.microcode // "auto"' "$config_file")"
 
     # Use SYSTEM_HOSTNAME to avoid conflicts with shell's HOSTNAME
     export SYSTEM_HOSTNAME="$(jq -r '.hostname // "archlinux"' "$config_file")"
@@ -79,25 +93,86 @@ load_config_from_json() {
     # Use MAIN_USERNAME for the primary user account
     export MAIN_USERNAME="$(jq -r '.username // ""' "$config_file")"
     export USER_PASSWORD="$(jq -r '.user_password // ""' "$config_file")"
+    # install.sh expects MAIN_USER_PASSWORD (matches the TUI stdin protocol)
+    export MAIN_USER_PASSWORD="$USER_PASSWORD"
     export ROOT_PASSWORD="$(jq -r '.root_password // ""' "$config_file")"
+    # Omitted means the TUI's default minimum (DEFAULT_MIN_PASSWORD_LENGTH)
+    export PASSWORD_MIN_LENGTH="$(jq -r '.password_min_length // 8' "$config_file")"
+    export SSH_KEYS="$(jq -r '.ssh_keys // [] | join(" ")' "$config_file")"
 
     export MIRROR_COUNTRY="$(jq -r '.mirror_country // ""' "$config_file")"
+    # A proxy in the file wins over the one the installer was started with
+    local proxy_name proxy_value
+    for proxy_name in http_proxy https_proxy no_proxy; do
+        proxy_value="$(jq -r ".${proxy_name} // \"\"" "$config_file")"
+        if [[ -n "$proxy_value" ]]; then
+            export "${proxy_name^^}=$proxy_value"
+        fi
+    done
+    # Likewise a package cache in the file wins over --pkg-cache
+    local package_cache
+    package_cache="$(jq -r '.package_cache // ""' "$config_file")"
+    if [[ -n "$package_cache" ]]; then
+        export PKG_CACHE="$package_cache"
+    fi
     export BOOTLOADER="$(jq -r '.bootloader // "systemd-boot"' "$config_file")"
     export OS_PROBER="$(jq -r '.os_prober // "no"' "$config_file")"
     export DESKTOP_ENVIRONMENT="$(jq -r '.desktop_environment // "none"' "$config_file")"
     export DISPLAY_MANAGER="$(jq -r '.display_manager // "none"' "$config_file")"
+    local desktops="$_CONFIG_LOADER_SCRIPT_DIR/desktops/desktops.json"
+    # An older name such as plasma stands for the desktop that lists it
+    local desktop_id
+    desktop_id="$(jq -r --arg alias "${DESKTOP_ENVIRONMENT,,}" \
+        '.desktops[] | select(any(.aliases[]?; . == $alias)) | .id' "$desktops")"
+    if [[ -n "$desktop_id" ]]; then
+        export DESKTOP_ENVIRONMENT="$desktop_id"
+    fi
+    # Desktop packages come from the same catalogue the TUI uses
+    export DESKTOP_PACKAGES="$(jq -r --arg id "${DESKTOP_ENVIRONMENT,,}" \
+        '.desktops[] | select(.id == $id) | .packages | join(" ")' "$desktops")"
+    export AUDIO="$(jq -r '.audio // "pipewire"' "$config_file")"
+    export POWER_MANAGEMENT="$(jq -r '.power_management // "none"' "$config_file")"
+    export LID_SWITCH="$(jq -r '.lid_switch // "suspend"' "$config_file")"
     export ADDITIONAL_PACKAGES="$(jq -r '.additional_packages // ""' "$config_file")"
+    export PACKAGE_GROUPS="$(jq -r '.package_groups // [] | join(" ")' "$config_file")"
+    if [[ -n "$PACKAGE_GROUPS" ]]; then
+        # Expand groups from the same catalogue the TUI uses: explicit
+        # packages first, then each group's packages, without duplicates
+        local group_packages
+        group_packages="$(jq -r --arg groups "$PACKAGE_GROUPS" \
+            '($groups | split(" ")[]) as $id | .groups[] | select(.id == $id) | .packages[]' \
+            "$_CONFIG_LOADER_SCRIPT_DIR/package_groups.json")"
+        ADDITIONAL_PACKAGES="$(printf '%s\n' $ADDITIONAL_PACKAGES $group_packages | awk 'NF && !seen[$0]++' | paste -sd ' ' -)"
+        export ADDITIONAL_PACKAGES
+    fi
+    export VM_GUEST_TOOLS="$(jq -r '.vm_guest_tools // "None"' "$config_file")"
+    export NVIDIA_DRIVER="$(jq -r '.nvidia_driver // "open"' "$config_file")"
+    export NVIDIA_EXTRAS="$(jq -r '.nvidia_extras // "Yes"' "$config_file")"
+    export MULTILIB="$(jq -r '.multilib // "Yes"' "$config_file")"
+    export PARALLEL_DOWNLOADS="$(jq -r '.parallel_downloads // 5' "$config_file")"
+    export PACMAN_COLOR="$(jq -r '.pacman_color // "Yes"' "$config_file")"
+    export VERBOSE_PKG_LISTS="$(jq -r '.verbose_pkg_lists // "No"' "$config_file")"
+    # Flatten to "name|server[|key[|siglevel]]" entries, as the TUI passes them
+    export CUSTOM_REPOSITORIES="$(jq -r '[.custom_repositories // [] | .[] | [.name, .server] + (if .key_id or .sig_level then [.key_id // ""] else [] end) + (if .sig_level then [.sig_level | split(" ") | map(select(. != "")) | join(",")] else [] end) | join("|")] | join(" ")' "$config_file")"
     export ADDITIONAL_AUR_PACKAGES="$(jq -r '.additional_aur_packages // ""' "$config_file")"
     export AUR_HELPER="$(jq -r '.aur_helper // "paru"' "$config_file")"
     export PLYMOUTH="$(jq -r '.plymouth // "no"' "$config_file")"
     export PLYMOUTH_THEME="$(jq -r '.plymouth_theme // ""' "$config_file")"
     export GRUB_THEMES="$(jq -r '.grub_themes // "no"' "$config_file")"
     export GRUB_THEME_SELECTION="$(jq -r '.grub_theme_selection // ""' "$config_file")"
+    export EARLY_KMS="$(jq -r '.early_kms // "No"' "$config_file")"
+    export INITRAMFS_MODULES="$(jq -r '.initramfs_modules // [] | join(" ")' "$config_file")"
+    export INITRAMFS_HOOKS="$(jq -r '.initramfs_hooks // [] | if length == 0 then "auto" else join(" ") end' "$config_file")"
     export TIME_SYNC="$(jq -r '.time_sync // "yes"' "$config_file")"
+    export TIME_SYNC_DAEMON="$(jq -r '.time_sync_daemon // "systemd-timesyncd"' "$config_file")"
+    export NTP_SERVERS="$(jq -r '.ntp_servers // [] | join(" ")' "$config_file")"
+    export HARDWARE_CLOCK="$(jq -r '.hardware_clock // "UTC"' "$config_file")"
+    export SERVICES="$(jq -r 'if has("services") then (.services | if length == 0 then "none" else join(" ") end) else "" end' "$config_file")"
     export GIT_REPOSITORY="$(jq -r '.git_repository // "no"' "$config_file")"
     export GIT_REPOSITORY_URL="$(jq -r '.git_repository_url // ""' "$config_file")"
     export NUMLOCK_ON_BOOT="$(jq -r '.numlock_on_boot // "no"' "$config_file")"
     export SECURE_BOOT="$(jq -r '.secure_boot // "no"' "$config_file")"
+    export CUSTOM_PHASES="$(jq -r '.custom_phases // [] | join(" ")' "$config_file")"
 
     # Convert TUI variables to internal Bash variables (as done in install.sh)
     export ROOT_FILESYSTEM_TYPE="$ROOT_FILESYSTEM"
@@ -147,18 +222,193 @@ validate_configuration() {
         errors+=("Root password must be specified")
     fi
 
+    local min_length="${PASSWORD_MIN_LENGTH:-0}"
+    if [[ ! "$min_length" =~ ^[0-9]+$ ]]; then
+        errors+=("Minimum password length must be a number, not '$min_length'")
+    else
+        if [[ -n "$USER_PASSWORD" && ${#USER_PASSWORD} -lt $min_length ]]; then
+            errors+=("User password must be at least $min_length characters")
+        fi
+        if [[ -n "$ROOT_PASSWORD" && ${#ROOT_PASSWORD} -lt $min_length ]]; then
+            errors+=("Root password must be at least $min_length characters")
+        fi
+    fi
+
     # Check encryption password if encryption is enabled
     if [[ "$ENCRYPTION" == "yes" && -z "$ENCRYPTION_PASSWORD" ]]; then
         errors+=("Encryption password must be specified when encryption is enabled")
     fi
+
+    # Encrypted /boot is unlocked by GRUB, from a non-RAID LUKS container
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+        if [[ "${BOOTLOADER:-grub}" != "grub" ]]; then
+            errors+=("Encrypted /boot requires GRUB: $BOOTLOADER cannot unlock LUKS")
+        elif [[ "$PARTITIONING_STRATEGY" == *"raid"* ]]; then
+            errors+=("Encrypted /boot is not supported with RAID strategies")
+        fi
+    fi
     
+    # systemd-boot is an EFI application; BIOS installs need GRUB
+    if [[ "$BOOT_MODE" == "BIOS" && "${BOOTLOADER:-grub}" == "systemd-boot" ]]; then
+        errors+=("BIOS boot requires GRUB: $BOOTLOADER only boots on UEFI")
+    fi
+
+    # Only the non-RAID automatic strategies keep an existing ESP
+    if [[ "${EFI_PARTITION:-create}" != "create" ]]; then
+        if [[ "$BOOT_MODE" == "BIOS" ]]; then
+            errors+=("Reusing the EFI partition $EFI_PARTITION needs UEFI boot mode")
+        elif [[ "$PARTITIONING_STRATEGY" == *"raid"* || "$PARTITIONING_STRATEGY" == "manual" || "$PARTITIONING_STRATEGY" == "existing" ]]; then
+            errors+=("Reusing an EFI partition is not supported with $PARTITIONING_STRATEGY")
+        fi
+    fi
+
+    # Installing into free space needs an automatic strategy without RAID
+    case "${EXISTING_OS:-protect}" in
+        protect|erase) ;;
+        alongside)
+            if [[ "$PARTITIONING_STRATEGY" == *"raid"* || "$PARTITIONING_STRATEGY" == "manual" || "$PARTITIONING_STRATEGY" == "existing" ]]; then
+                errors+=("Installing alongside an existing OS is not supported with $PARTITIONING_STRATEGY")
+            fi
+            ;;
+        *) errors+=("Existing OS must be protect, alongside or erase, not '$EXISTING_OS'") ;;
+    esac
+
+    # Existing partitions need a root to install onto; the TUI checks the rest
+    if [[ "$PARTITIONING_STRATEGY" == "existing" ]]; then
+        if [[ -z "${PARTITION_MOUNTS:-}" ]]; then
+            errors+=("The existing partitioning strategy needs partition_mounts")
+        elif [[ ! ",${PARTITION_MOUNTS// /}" =~ ,/dev/[^,=]+=/:[a-z0-9]+(,|$) ]]; then
+            errors+=("partition_mounts must format a partition for /, e.g. /dev/sda2=/:ext4")
+        fi
+    fi
+
+    # The LVM strategies name their volume group and size their volumes
+    if [[ "$PARTITIONING_STRATEGY" == "auto_lvm" || "$PARTITIONING_STRATEGY" == "auto_luks_lvm" ]]; then
+        local vg="${LVM_VOLUME_GROUP:-arch}"
+        if [[ ! "$vg" =~ ^[A-Za-z0-9+_.][A-Za-z0-9+_.-]*$ || "$vg" == "." || "$vg" == ".." ]]; then
+            errors+=("LVM volume group '$vg' may only use letters, digits, + _ . - and cannot start with -")
+        fi
+        # Same grammar as lvm::parse_size: binary units from K to T, at least
+        # one 4 MiB extent, or a share of 1 to 100 %FREE
+        local volume size size_kib
+        for volume in "root:${LVM_ROOT_SIZE:-50G}" "var:${LVM_VAR_SIZE:-none}" "home:${LVM_HOME_SIZE:-100%FREE}"; do
+            size="${volume#*:}"
+            if [[ "${size,,}" == "none" && "${volume%%:*}" == "var" ]]; then
+                continue
+            elif [[ "$size" =~ ^([0-9]{1,3})%FREE$ ]] && (( 10#${BASH_REMATCH[1]} >= 1 && 10#${BASH_REMATCH[1]} <= 100 )); then
+                continue
+            elif [[ "$size" =~ ^([0-9]{1,12})([KkMmGgTt])([Ii]?[Bb])?$ ]]; then
+                size_kib=$((10#${BASH_REMATCH[1]}))
+                case "${BASH_REMATCH[2],,}" in
+                    m) size_kib=$((size_kib * 1024)) ;;
+                    g) size_kib=$((size_kib * 1024 * 1024)) ;;
+                    t) size_kib=$((size_kib * 1024 * 1024 * 1024)) ;;
+                esac
+                if (( size_kib < 4096 )); then
+                    errors+=("LVM ${volume%%:*} size '$size' is smaller than one 4 MiB extent")
+                fi
+                continue
+            fi
+            errors+=("LVM ${volume%%:*} size must look like 50G or 100%FREE, not '$size'")
+        done
+    fi
+
+    # The simple strategies give /var a fixed-size partition of at least 1G;
+    # /tmp is a sized tmpfs or on disk
+    if [[ "$PARTITIONING_STRATEGY" == "auto_simple" || "$PARTITIONING_STRATEGY" == "auto_simple_luks" ]]; then
+        local var_size="${VAR_PARTITION_SIZE:-none}"
+        if [[ "${var_size,,}" == "none" ]]; then
+            :
+        elif [[ ! "$var_size" =~ ^([0-9]{1,12})([KkMmGgTt])([Ii]?[Bb])?$ ]]; then
+            errors+=("/var partition size must look like 20G or be none, not '$VAR_PARTITION_SIZE'")
+        else
+            local var_mib="${BASH_REMATCH[1]}"
+            case "${BASH_REMATCH[2],,}" in
+                k) var_mib=$((10#$var_mib / 1024)) ;;
+                m) var_mib=$((10#$var_mib)) ;;
+                g) var_mib=$((10#$var_mib * 1024)) ;;
+                t) var_mib=$((10#$var_mib * 1024 * 1024)) ;;
+            esac
+            if (( var_mib < 1024 )); then
+                errors+=("The /var partition needs at least 1G, not '$VAR_PARTITION_SIZE'")
+            fi
+        fi
+    fi
+    case "${TMP_POLICY:-tmpfs}" in
+        tmpfs)
+            if [[ ! "${TMP_SIZE:-50%}" =~ ^([1-9][0-9]?%|100%|[1-9][0-9]*[KkMmGg])$ ]]; then
+                errors+=("/tmp size must look like 50% or 4G, not '$TMP_SIZE'")
+            fi
+            ;;
+        disk) ;;
+        *) errors+=("/tmp policy must be tmpfs or disk, not '$TMP_POLICY'") ;;
+    esac
+
+    # Time sync daemon and hardware clock come from fixed lists
+    case "${TIME_SYNC_DAEMON:-systemd-timesyncd}" in
+        systemd-timesyncd|chrony) ;;
+        *) errors+=("Time sync daemon must be systemd-timesyncd or chrony, not '$TIME_SYNC_DAEMON'") ;;
+    esac
+    case "${HARDWARE_CLOCK:-UTC}" in
+        UTC|localtime) ;;
+        *) errors+=("Hardware clock must be UTC or localtime, not '$HARDWARE_CLOCK'") ;;
+    esac
+
+    # Proxies are URLs curl understands; no_proxy is a comma-separated list
+    local proxy
+    for proxy in "${HTTP_PROXY:-}" "${HTTPS_PROXY:-}"; do
+        if [[ -n "$proxy" && ! "$proxy" =~ ^(https?|socks5h?)://([^[:space:]@]*@)?[^[:space:]/:@]+ ]]; then
+            errors+=("Proxy '$(redact_proxy "$proxy")' must look like http://proxy.example.com:3128")
+        fi
+    done
+    if [[ "${NO_PROXY:-}" =~ [[:space:]] ]]; then
+        errors+=("No-proxy entries are separated by commas, not spaces: '$NO_PROXY'")
+    fi
+
+    # The package cache must be readable here, where pacman uses it
+    if [[ -n "${PKG_CACHE:-}" && ! -d "$PKG_CACHE" ]]; then
+        errors+=("Package cache '$PKG_CACHE' is not a directory")
+    fi
+
+    # SSH keys come from github:USER, gitlab:USER or an absolute path
+    local key_source
+    for key_source in ${SSH_KEYS:-}; do
+        if [[ ! "${key_source,,}" =~ ^(/.+|(github|gitlab):[a-z0-9._-]+)$ ]]; then
+            errors+=("SSH key source '$key_source' must be github:USER, gitlab:USER or an absolute path")
+        fi
+    done
+
+    # Desktop environment must be one of scripts/desktops/desktops.json
+    local desktop="${DESKTOP_ENVIRONMENT:-none}"
+    if [[ "${desktop,,}" != "none" && -z "${DESKTOP_PACKAGES:-}" ]]; then
+        errors+=("Unknown desktop environment '$DESKTOP_ENVIRONMENT'")
+    fi
+
+    # Power manager and lid close action; TLP conflicts with GNOME's power-profiles-daemon
+    case "${POWER_MANAGEMENT:-none}" in
+        none|tlp|power-profiles-daemon) ;;
+        *) errors+=("Power management must be none, tlp or power-profiles-daemon, not '$POWER_MANAGEMENT'") ;;
+    esac
+    if [[ "${POWER_MANAGEMENT:-none}" == "tlp" && "${DESKTOP_ENVIRONMENT,,}" == "gnome" ]]; then
+        errors+=("TLP conflicts with power-profiles-daemon, which GNOME's power settings use: choose power-profiles-daemon or none")
+    fi
+    case "${LID_SWITCH:-suspend}" in
+        suspend|suspend-then-hibernate|hibernate|lock|poweroff|ignore) ;;
+        *) errors+=("Lid close action must be suspend, suspend-then-hibernate, hibernate, lock, poweroff or ignore, not '$LID_SWITCH'") ;;
+    esac
+
+    # GRUB cannot read bcachefs, where an encrypted /boot would live
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" && "${ROOT_FILESYSTEM:-ext4}" == "bcachefs" ]]; then
+        errors+=("Encrypted /boot needs a root filesystem GRUB can read: bcachefs is not")
+    fi
+
     # Check disk path
     if [[ -n "$INSTALL_DISK" && ! "$INSTALL_DISK" =~ ^/dev/ ]]; then
         errors+=("Install disk must be a valid device path (e.g., /dev/sda)")
     fi
     
     # Check partitioning strategy
-    local valid_strategies=("auto_simple" "auto_simple_luks" "auto_lvm" "auto_luks_lvm" "auto_raid" "auto_raid_luks" "auto_raid_lvm" "auto_raid_lvm_luks" "manual")
+    local valid_strategies=("auto_simple" "auto_simple_luks" "auto_lvm" "auto_luks_lvm" "auto_raid" "auto_raid_luks" "auto_raid_lvm" "auto_raid_lvm_luks" "manual" "existing")
     if [[ -n "$PARTITIONING_STRATEGY" ]]; then
         local is_valid=false
         for strategy in "${valid_strategies[@]}"; do
@@ -195,11 +445,28 @@ display_configuration() {
     log_info "  Home Filesystem: $HOME_FILESYSTEM"
     log_info "  Separate Home: $SEPARATE_HOME"
     log_info "  Encryption: $ENCRYPTION"
-    log_info "  Swap: $SWAP"
+    log_info "  EFI Partition: ${EFI_PARTITION:-create}"
+    log_info "  Existing OS: ${EXISTING_OS:-protect}"
+    log_info "  Swap: $SWAP (${SWAP_SIZE:-2GB}, hibernation ${HIBERNATION:-No})"
+    if [[ "$PARTITIONING_STRATEGY" == "auto_lvm" || "$PARTITIONING_STRATEGY" == "auto_luks_lvm" ]]; then
+        log_info "  LVM: ${LVM_VOLUME_GROUP:-arch} (root ${LVM_ROOT_SIZE:-50G}, var ${LVM_VAR_SIZE:-none}, home ${LVM_HOME_SIZE:-100%FREE}, thin ${LVM_THIN:-No})"
+    fi
+    if [[ "$PARTITIONING_STRATEGY" == "auto_simple" || "$PARTITIONING_STRATEGY" == "auto_simple_luks" ]]; then
+        log_info "  /var Partition: ${VAR_PARTITION_SIZE:-none}"
+    fi
+    log_info "  /tmp: ${TMP_POLICY:-tmpfs}$([[ "${TMP_POLICY:-tmpfs}" == "tmpfs" ]] && echo " (${TMP_SIZE:-50%})")"
     log_info "  Hostname: $SYSTEM_HOSTNAME"
-    log_info "  Username: $MAIN_USERNAME"
-    log_info "  Desktop Environment: $DESKTOP_ENVIRONMENT"
+    log_info "  Username: $MAIN_USERNAME${SSH_KEYS:+ (SSH keys from $SSH_KEYS)}"
+    log_info "  Desktop Environment: $DESKTOP_ENVIRONMENT${DESKTOP_PACKAGES:+ ($DESKTOP_PACKAGES)}"
     log_info "  Display Manager: $DISPLAY_MANAGER"
+    log_info "  Audio: $AUDIO"
+    log_info "  Power Management: ${POWER_MANAGEMENT:-none} (lid close: ${LID_SWITCH:-suspend})"
+    log_info "  Services: ${SERVICES:-default}"
+    log_info "  Proxy: $(redact_proxy "${HTTP_PROXY:-none}")${NO_PROXY:+ (not for $NO_PROXY)}"
+    log_info "  Time Sync: ${TIME_SYNC:-yes} (${TIME_SYNC_DAEMON:-systemd-timesyncd}, servers ${NTP_SERVERS:-default}, hardware clock ${HARDWARE_CLOCK:-UTC})"
+    log_info "  Package Cache: ${PKG_CACHE:-none}"
     log_info "  Bootloader: $BOOTLOADER"
+    log_info "  NVIDIA: ${NVIDIA_DRIVER:-open} driver, extras ${NVIDIA_EXTRAS:-Yes} (when NVIDIA drivers are installed)"
+    log_info "  Initramfs: hooks ${INITRAMFS_HOOKS:-auto}${INITRAMFS_MODULES:+, modules $INITRAMFS_MODULES}, early KMS ${EARLY_KMS:-No}"
     log_info "  AUR Helper: $AUR_HELPER"
 }
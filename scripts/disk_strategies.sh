@@ -120,6 +120,12 @@ do_manual_partitioning_guided() {
     execute_manual_partitioning
 }
 
+# Existing partitions, formatted and mounted from PARTITION_MOUNTS
+do_existing_partitioning() {
+    source_or_die "$SCRIPT_DIR/strategies/existing.sh"
+    execute_existing_partitioning
+}
+
 # --- Legacy Functions (for backward compatibility) ---
 # These map to the new modular functions
 
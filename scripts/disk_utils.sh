@@ -36,6 +36,8 @@ if [[ -z "${EFI_PARTITION_TYPE:-}" ]]; then
     readonly LUKS_PARTITION_TYPE="8309"
     readonly SWAP_PARTITION_TYPE="8200"
     readonly XBOOTLDR_PARTITION_TYPE="EA00"
+    # Partition type GUID of an existing ESP, as lsblk reports it
+    readonly ESP_PARTITION_GUID="c12a7328-f81f-11d2-ba4b-00a0c93ec93b"
 
     # Default Partition Sizes (in MiB)
     readonly BIOS_BOOT_PART_SIZE_MIB=1
@@ -43,6 +45,9 @@ if [[ -z "${EFI_PARTITION_TYPE:-}" ]]; then
     readonly DEFAULT_SWAP_SIZE_MIB=2048
     readonly DEFAULT_ROOT_SIZE_MIB=102400
     readonly DEFAULT_ESP_SIZE_MIB=512
+    # Room a reused ESP needs for the bootloader, and a kept disk for Arch
+    readonly ESP_MIN_FREE_MIB=32
+    readonly SHARED_DISK_MIN_FREE_MIB=20480
 
     # Filesystem Types
     readonly DEFAULT_ROOT_FILESYSTEM="ext4"
@@ -57,8 +62,9 @@ get_partition_path() {
     local disk="$1"
     local part_num="$2"
     
-    # Handle NVMe/MMC/Loop devices (e.g., /dev/nvme0n1 -> /dev/nvme0n1p1)
-    if [[ "$disk" =~ (nvme|mmcblk|loop) ]]; then
+    # The kernel inserts "p" when the disk name ends in a digit
+    # (NVMe namespaces, eMMC/SD, loop, md: /dev/nvme0n2 -> /dev/nvme0n2p1)
+    if [[ "$disk" =~ [0-9]$ ]]; then
         echo "${disk}p${part_num}"
     else
         # Handle SATA/SCSI/VirtIO (e.g., /dev/sda -> /dev/sda1)
@@ -89,12 +95,57 @@ get_swap_size_mib() {
     fi
 }
 
+# Installed memory in MiB (MEMINFO_FILE overrides /proc/meminfo for tests)
+get_memory_mib() {
+    awk '/^MemTotal:/ { print int($2 / 1024); exit }' "${MEMINFO_FILE:-/proc/meminfo}" 2>/dev/null
+}
+
+# Swap size in MiB for SWAP_SIZE: "4GB", "512MB", "Equal to RAM" or "Double RAM".
+# The default "2GB" is a fixed size like the others; only a missing or
+# unreadable SWAP_SIZE falls back to the RAM based rule of get_swap_size_mib.
+# Hibernation writes the memory to swap, so then the swap is at least the RAM.
+get_configured_swap_size_mib() {
+    local size="${SWAP_SIZE:-}"
+    local memory_mib
+    memory_mib=$(get_memory_mib)
+    local size_mib=""
+
+    case "$size" in
+        "Equal to RAM") size_mib="$memory_mib" ;;
+        "Double RAM") [[ -n "$memory_mib" ]] && size_mib=$((memory_mib * 2)) ;;
+        *)
+            if [[ "$size" =~ ^([0-9]+)[[:space:]]*([MmGgTt])([Ii]?[Bb])?$ ]]; then
+                local number="${BASH_REMATCH[1]}"
+                case "${BASH_REMATCH[2]}" in
+                    [Mm]) size_mib="$number" ;;
+                    [Gg]) size_mib=$((number * 1024)) ;;
+                    [Tt]) size_mib=$((number * 1024 * 1024)) ;;
+                esac
+            fi
+            ;;
+    esac
+
+    if [[ -z "$size_mib" || "$size_mib" -le 0 ]]; then
+        if [[ -n "$memory_mib" ]]; then
+            size_mib=$(get_swap_size_mib "$(( (memory_mib + 1023) / 1024 ))")
+        else
+            size_mib=$(get_swap_size_mib)
+        fi
+    fi
+
+    if [[ "${HIBERNATION:-No}" == "Yes" && -n "$memory_mib" ]] && (( size_mib < memory_mib )); then
+        log_warn "Swap of ${size_mib}MiB cannot hold ${memory_mib}MiB of RAM, growing it for hibernation"
+        size_mib="$memory_mib"
+    fi
+    echo "$size_mib"
+}
+
 # --- Disk Type Detection ---
 # Functions for detecting SSD vs HDD for appropriate wipe/optimization strategies
 # Reference: https://wiki.archlinux.org/title/Solid_state_drive
 
-# Get the base device name (strip partition numbers, handle nvme)
-# /dev/sda1 -> sda, /dev/nvme0n1p1 -> nvme0n1
+# Get the base device name (strip partition numbers, handle nvme/mmc/loop)
+# /dev/sda1 -> sda, /dev/nvme0n1p1 -> nvme0n1, /dev/mmcblk0p2 -> mmcblk0
 get_base_device_name() {
     local device="$1"
     local base_name
@@ -102,8 +153,9 @@ get_base_device_name() {
     # Remove /dev/ prefix
     base_name="${device#/dev/}"
 
-    # Handle NVMe devices: nvme0n1p1 -> nvme0n1
-    if [[ "$base_name" =~ ^(nvme[0-9]+n[0-9]+) ]]; then
+    # Disks whose name ends in a digit (NVMe namespaces, eMMC/SD, loop):
+    # nvme0n1p1 -> nvme0n1, mmcblk0p1 -> mmcblk0, loop0p1 -> loop0
+    if [[ "$base_name" =~ ^(nvme[0-9]+n[0-9]+|mmcblk[0-9]+|loop[0-9]+) ]]; then
         echo "${BASH_REMATCH[1]}"
         return
     fi
@@ -225,6 +277,170 @@ create_partition_table() {
     partprobe "$disk" || true
 }
 
+# --- Existing ESP Reuse ---
+# EFI_PARTITION is "create" or the path of an ESP to keep (dual boot)
+
+using_existing_esp() {
+    [[ -n "${EFI_PARTITION:-}" && "$EFI_PARTITION" != "create" ]]
+}
+
+# Check that a partition is a FAT ESP with room for the bootloader
+check_existing_esp() {
+    local esp="$1"
+
+    if [[ ! -b "$esp" ]]; then
+        log_error "EFI partition $esp does not exist or is not a block device"
+        return 1
+    fi
+
+    local fstype parttype
+    fstype=$(lsblk -n -d -o FSTYPE "$esp" 2>/dev/null | tr -d '[:space:]')
+    parttype=$(lsblk -n -d -o PARTTYPE "$esp" 2>/dev/null | tr -d '[:space:]' | tr '[:upper:]' '[:lower:]')
+    if [[ "$fstype" != "$EFI_FILESYSTEM" ]] || [[ "$parttype" != "$ESP_PARTITION_GUID" && "$parttype" != "0xef" ]]; then
+        log_error "$esp is not an EFI System Partition (type ${parttype:-unknown}, filesystem ${fstype:-none})"
+        return 1
+    fi
+
+    # Free space is only known with the filesystem mounted
+    local mountpoint free_mib
+    mountpoint=$(mktemp -d)
+    if ! mount -o ro "$esp" "$mountpoint"; then
+        rmdir "$mountpoint"
+        log_error "Cannot mount $esp to check its free space"
+        return 1
+    fi
+    free_mib=$(df --output=avail -BM "$mountpoint" | tail -n 1 | tr -dc '0-9')
+    umount "$mountpoint"
+    rmdir "$mountpoint"
+
+    if (( ${free_mib:-0} < ESP_MIN_FREE_MIB )); then
+        log_error "$esp has only ${free_mib:-0} MiB free, the bootloader needs ${ESP_MIN_FREE_MIB} MiB"
+        return 1
+    fi
+    log_info "Reusing EFI partition $esp (${free_mib} MiB free)"
+}
+
+# Number after the highest existing partition on a kept disk
+next_partition_number() {
+    local disk="$1"
+    local last
+    last=$(sgdisk -p "$disk" 2>/dev/null | awk '/^Number/ { table = 1; next } table && $1 ~ /^[0-9]+$/ { n = $1 } END { print n + 0 }')
+    echo $((last + 1))
+}
+
+# Check that the largest unallocated block of a kept disk fits the install
+check_disk_free_space() {
+    local disk="$1"
+    local min_mib="$2"
+
+    local first last sector_size free_mib
+    first=$(sgdisk -F "$disk" 2>/dev/null | tail -n 1)
+    last=$(sgdisk -E "$disk" 2>/dev/null | tail -n 1)
+    sector_size=$(blockdev --getss "$disk" 2>/dev/null || echo 512)
+    if [[ ! "$first" =~ ^[0-9]+$ || ! "$last" =~ ^[0-9]+$ || "$last" -le "$first" ]]; then
+        free_mib=0
+    else
+        free_mib=$(( (last - first + 1) * sector_size / 1048576 ))
+    fi
+
+    if (( free_mib < min_mib )); then
+        log_error "$disk has ${free_mib} MiB unallocated, at least ${min_mib} MiB are needed (shrink the other system first)"
+        return 1
+    fi
+    log_info "$disk has ${free_mib} MiB unallocated"
+}
+
+# --- Existing Operating Systems ---
+# EXISTING_OS is "protect" (refuse a disk holding another system),
+# "alongside" (keep its partitions, use the free space) or "erase"
+
+# Print the systems found on a disk, one per line: os-prober's labels when
+# it finds any, otherwise a guess from the partitions' filesystems
+detect_existing_systems() {
+    local disk="$1"
+    local found=""
+
+    if command -v os-prober &>/dev/null; then
+        # /dev/nvme0n1p1@/EFI/Microsoft/Boot/bootmgfw.efi:Windows Boot Manager:Windows:efi
+        found=$(os-prober 2>/dev/null | awk -F: -v disk="$disk" '
+            { split($1, device, "@") }
+            device[1] ~ ("^" disk "p?[0-9]+$") { print $2 }' | sort -u)
+    fi
+    if [[ -z "$found" ]]; then
+        found=$(lsblk -n -l -o FSTYPE "$disk" 2>/dev/null | awk '
+            $1 == "ntfs" || $1 == "BitLocker" { print "Windows" }
+            $1 == "apfs" || $1 == "hfsplus" { print "macOS" }
+            $1 ~ /^(ext[234]|btrfs|xfs|f2fs)$/ { print "Linux" }
+            $1 == "crypto_LUKS" { print "LUKS" }
+            $1 == "LVM2_member" { print "LVM" }
+            $1 == "linux_raid_member" { print "RAID" }' | sort -u)
+    fi
+    if [[ -n "$found" ]]; then
+        echo "$found"
+    fi
+}
+
+# Whether the partitions on a disk stay: installing alongside another
+# system, or reusing an ESP that lives on the disk
+keeping_existing_partitions() {
+    local disk="$1"
+
+    [[ "${EXISTING_OS:-protect}" == "alongside" ]] && return 0
+    using_existing_esp && [[ "/dev/$(lsblk -n -d -o PKNAME "$EFI_PARTITION" 2>/dev/null | tr -d '[:space:]')" == "$disk" ]]
+}
+
+# Apply EXISTING_OS to the install disk before anything is written
+check_existing_systems() {
+    local disk="$1"
+    local systems
+    systems=$(detect_existing_systems "$disk" | paste -sd, - | sed 's/,/, /g')
+
+    case "${EXISTING_OS:-protect}" in
+        alongside)
+            # sgdisk would silently convert an MBR table, breaking the other system
+            local pttype
+            pttype=$(lsblk -n -d -o PTTYPE "$disk" 2>/dev/null | tr -d '[:space:]')
+            if [[ -n "$pttype" && "$pttype" != "gpt" ]]; then
+                log_error "Installing alongside needs a GPT partition table, $disk uses $pttype"
+                return 1
+            fi
+            if [[ -n "$systems" ]]; then
+                log_info "Keeping $systems on $disk"
+            fi
+            ;;
+        erase)
+            if [[ -n "$systems" ]]; then
+                log_warn "$disk holds $systems: it will be erased"
+            fi
+            ;;
+        *)
+            if [[ -n "$systems" ]] && ! keeping_existing_partitions "$disk"; then
+                log_error "$disk holds $systems: set EXISTING_OS=alongside to install next to it or EXISTING_OS=erase to remove it"
+                return 1
+            fi
+            ;;
+    esac
+}
+
+# Wipe the install disk and create a new partition table, unless its
+# partitions are kept (installing alongside, or the reused ESP lives on it):
+# then the new ones go into the unallocated space. Sets FIRST_PART_NUM to
+# the first free number.
+prepare_install_disk() {
+    local disk="$1"
+    FIRST_PART_NUM=1
+
+    if keeping_existing_partitions "$disk"; then
+        log_warn "Keeping the partitions on $disk: Arch goes into its unallocated space"
+        check_disk_free_space "$disk" "$SHARED_DISK_MIN_FREE_MIB" || return 1
+        FIRST_PART_NUM=$(next_partition_number "$disk")
+        return 0
+    fi
+
+    wipe_disk "$disk" "CONFIRMED"
+    create_partition_table "$disk"
+}
+
 create_esp_partition() {
     local disk="$1"
     local part_num="$2"
@@ -279,6 +495,24 @@ create_swap_partition() {
     swapon "$part_device"
 }
 
+# Create and enable a swap file, for layouts without a swap partition.
+# Btrfs needs a file without copy-on-write or compression, which
+# `btrfs filesystem mkswapfile` sets up; genfstab records it once enabled.
+create_swap_file() {
+    local file="$1"
+    local size_mib="$2"
+
+    log_info "Creating swap file: $file (${size_mib}MiB)"
+    if [[ "$(findmnt -n -o FSTYPE -T "$(dirname "$file")")" == "btrfs" ]]; then
+        btrfs filesystem mkswapfile --size "${size_mib}m" "$file"
+    else
+        dd if=/dev/zero of="$file" bs=1M count="$size_mib" status=none
+        chmod 600 "$file"
+        mkswap "$file"
+    fi
+    swapon "$file"
+}
+
 create_root_partition() {
     local disk="$1"
     local part_num="$2"
@@ -315,6 +549,79 @@ create_home_partition() {
     format_filesystem "$part_device" "$filesystem"
 }
 
+# Whether the strategy puts /var on a partition of its own (VAR_PARTITION_SIZE)
+want_var_partition() {
+    [[ -n "${VAR_PARTITION_SIZE:-}" && "${VAR_PARTITION_SIZE,,}" != "none" ]]
+}
+
+# VAR_PARTITION_SIZE as sgdisk reads it: 20G, 20GB and 20GiB all become 20G
+var_partition_size_spec() {
+    local size="${VAR_PARTITION_SIZE:-}"
+    size="${size%[Bb]}"
+    size="${size%[Ii]}"
+    if [[ ! "$size" =~ ^[0-9]+[KkMmGgTt]$ ]]; then
+        log_error "VAR_PARTITION_SIZE must look like 20G, not '$VAR_PARTITION_SIZE'"
+        return 1
+    fi
+    echo "${size^^}"
+}
+
+# Create the fixed-size /var partition; it comes before root, which takes the rest
+create_var_partition() {
+    local disk="$1"
+    local part_num="$2"
+    local filesystem="${3:-$DEFAULT_ROOT_FILESYSTEM}"
+
+    local size
+    size=$(var_partition_size_spec) || return 1
+    local part_device
+    part_device=$(get_partition_path "$disk" "$part_num")
+
+    log_info "Creating /var partition: $part_device ($size, $filesystem)"
+    sgdisk -n "${part_num}:0:+${size}" -t "${part_num}:${LINUX_PARTITION_TYPE}" -c "${part_num}:VAR" "$disk"
+
+    sleep 1
+    format_filesystem "$part_device" "$filesystem"
+    capture_device_info "var" "$part_device"
+}
+
+# Set up /tmp in the target root as TMP_POLICY asks, after genfstab.
+# tmpfs gets an fstab entry with TMP_SIZE and no SELinux context options;
+# disk masks systemd's tmp.mount so /tmp stays on disk, in the btrfs @tmp
+# subvolume when genfstab found one mounted.
+configure_tmp_mount() {
+    local root="${1:-/mnt}"
+    local fstab="$root/etc/fstab"
+
+    # genfstab copies whatever is mounted at /tmp: a tmpfs is replaced by
+    # the policy, an on-disk /tmp only gives way to a tmpfs
+    if [[ -f "$fstab" ]]; then
+        if [[ "${TMP_POLICY:-tmpfs}" == "disk" ]]; then
+            sed -i '/^[^#[:space:]]\+[[:space:]]\+\/tmp[[:space:]]\+tmpfs[[:space:]]/d' "$fstab"
+        else
+            sed -i '/^[^#[:space:]]\+[[:space:]]\+\/tmp[[:space:]]/d' "$fstab"
+        fi
+    fi
+
+    case "${TMP_POLICY:-tmpfs}" in
+        tmpfs)
+            local size="${TMP_SIZE:-50%}"
+            log_info "Mounting /tmp as tmpfs (size $size)"
+            echo "tmpfs /tmp tmpfs rw,nosuid,nodev,noatime,size=$size,mode=1777 0 0" >> "$fstab"
+            ;;
+        disk)
+            log_info "Keeping /tmp on the root filesystem"
+            mkdir -p "$root/etc/systemd/system" "$root/tmp"
+            chmod 1777 "$root/tmp"
+            ln -sf /dev/null "$root/etc/systemd/system/tmp.mount"
+            ;;
+        *)
+            log_error "TMP_POLICY must be tmpfs or disk, not '$TMP_POLICY'"
+            return 1
+            ;;
+    esac
+}
+
 safe_mount() {
     local device="$1"
     local mountpoint="$2"
@@ -336,21 +643,217 @@ setup_luks_encryption() {
     
     log_info "Encrypting $partition with LUKS2"
     
+    # GRUB cannot derive Argon2 keys: an encrypted /boot needs PBKDF2 keyslots
+    local -a pbkdf_args=()
+    if [[ "${ENCRYPTED_BOOT:-No}" == "Yes" ]]; then
+        pbkdf_args=(--pbkdf pbkdf2)
+        log_info "Using PBKDF2 so GRUB can unlock $partition"
+    fi
+
     # Format with password from stdin
-    echo -n "$password" | cryptsetup luksFormat --type luks2 --batch-mode "$partition" -
+    echo -n "$password" | cryptsetup luksFormat --type luks2 "${pbkdf_args[@]}" --batch-mode "$partition" -
     
     # Open mapping
     echo -n "$password" | cryptsetup open "$partition" "$mapper_name" -
 }
 
+# Add a key file to a second keyslot so the initramfs can unlock the
+# container GRUB already opened, without asking for the passphrase again.
+# The key file lives in the encrypted root and is embedded in the initramfs.
+add_luks_keyfile() {
+    local partition="$1"
+    local password="$2"
+    local keyfile="${3:-/mnt/crypto_keyfile.bin}"
+
+    if [[ -z "$password" ]]; then
+        log_error "LUKS password is empty"
+        return 1
+    fi
+
+    log_info "Adding key file keyslot to $partition"
+
+    ( umask 077 && dd bs=512 count=4 if=/dev/urandom of="$keyfile" iflag=fullblock status=none )
+    chmod 600 "$keyfile"
+
+    echo -n "$password" | cryptsetup luksAddKey --pbkdf pbkdf2 --key-file - "$partition" "$keyfile"
+}
+
+# --- LVM Volumes ---
+# LVM_VOLUME_GROUP, LVM_ROOT_SIZE, LVM_VAR_SIZE and LVM_HOME_SIZE describe the
+# volumes of the auto_lvm strategies; LVM_THIN=Yes puts them in a thin pool.
+# Sizes are fixed (50G) or a share of the free space (100%FREE); fixed sizes
+# are created first, then shares in the order root, var, home.
+
+# Volumes to create as "name:size", one per line
+lvm_volume_plan() {
+    local home_size="none"
+    if [[ "${WANT_HOME_PARTITION:-no}" == "yes" ]]; then
+        home_size="${LVM_HOME_SIZE:-100%FREE}"
+    fi
+
+    local -a shares=()
+    local entry
+    for entry in "root:${LVM_ROOT_SIZE:-50G}" "var:${LVM_VAR_SIZE:-none}" "home:$home_size"; do
+        case "${entry#*:}" in
+            none) ;;
+            *%FREE) shares+=("$entry") ;;
+            *) echo "$entry" ;;
+        esac
+    done
+    if (( ${#shares[@]} > 0 )); then
+        printf '%s\n' "${shares[@]}"
+    fi
+}
+
+# Create the volume group on a physical volume, then format and mount its volumes
+setup_lvm_volumes() {
+    local pv="$1"
+    local vg="${LVM_VOLUME_GROUP:-arch}"
+
+    log_info "Setting up LVM volume group $vg on $pv..."
+    pvcreate "$pv" || error_exit "Failed to create physical volume."
+    vgcreate "$vg" "$pv" || error_exit "Failed to create volume group $vg."
+
+    local pool_mib=""
+    if [[ "${LVM_THIN:-No}" == "Yes" ]]; then
+        # Leave room for the pool metadata and its spare
+        lvcreate --type thin-pool -l 95%FREE -n pool "$vg" || error_exit "Failed to create thin pool."
+        pool_mib=$(lvs --noheadings --units m --nosuffix -o lv_size "$vg/pool" | tr -d ' ')
+        pool_mib="${pool_mib%%.*}"
+    fi
+
+    log_info "Creating logical volumes..."
+    local -a volumes=()
+    local name size
+    while IFS=: read -r name size; do
+        if [[ -n "$pool_mib" ]]; then
+            # Thin volumes have virtual sizes, so shares are of the pool
+            if [[ "$size" == *%FREE ]]; then
+                size="$(( pool_mib * ${size%\%FREE} / 100 ))M"
+            fi
+            lvcreate -V "$size" --thin -n "$name" "$vg/pool" || error_exit "Failed to create $name logical volume."
+        elif [[ "$size" == *%FREE ]]; then
+            lvcreate -l "$size" -n "$name" "$vg" || error_exit "Failed to create $name logical volume."
+        else
+            lvcreate -L "$size" -n "$name" "$vg" || error_exit "Failed to create $name logical volume."
+        fi
+        volumes+=("$name")
+    done < <(lvm_volume_plan)
+
+    log_info "Formatting logical volumes..."
+    format_filesystem "/dev/$vg/root" "$ROOT_FILESYSTEM_TYPE"
+    capture_device_info "root" "/dev/$vg/root"
+    safe_mount "/dev/$vg/root" "/mnt"
+
+    for name in var home; do
+        [[ " ${volumes[*]} " == *" $name "* ]] || continue
+        if [[ "$name" == "home" ]]; then
+            format_filesystem "/dev/$vg/$name" "$HOME_FILESYSTEM_TYPE"
+        else
+            format_filesystem "/dev/$vg/$name" "$ROOT_FILESYSTEM_TYPE"
+        fi
+        capture_device_info "$name" "/dev/$vg/$name"
+        safe_mount "/dev/$vg/$name" "/mnt/$name"
+    done
+}
+
+# --- Existing Partitions ---
+# PARTITION_MOUNTS lists the partitions the existing strategy installs onto as
+# device=mountpoint[:filesystem], comma-separated; a filesystem formats the
+# partition, none keeps its data, and "swap" as mountpoint enables it. The TUI
+# checks the plan against the disks before the installation starts.
+
+# Entries as "device:mountpoint:filesystem", one per line: / first, then
+# shallower mountpoints before the ones mounted inside them, swap last
+mount_plan_entries() {
+    local plan="${1:-${PARTITION_MOUNTS:-}}"
+    local -a entries=()
+    local entry device target mountpoint fs depth slashes
+    IFS=',' read -ra entries <<< "$plan"
+    for entry in "${entries[@]}"; do
+        entry="${entry//[[:space:]]/}"
+        [[ -n "$entry" ]] || continue
+        device="${entry%%=*}"
+        target="${entry#*=}"
+        mountpoint="${target%%:*}"
+        fs=""
+        [[ "$target" == *:* ]] && fs="${target#*:}"
+        case "$mountpoint" in
+            /) depth=0 ;;
+            swap) depth=99 ;;
+            *)
+                slashes="${mountpoint//[^\/]/}"
+                depth=${#slashes}
+                ;;
+        esac
+        echo "$depth $device:$mountpoint:$fs"
+    done | sort -s -n -k1,1 | cut -d' ' -f2-
+}
+
+# Format and mount the partitions of PARTITION_MOUNTS under /mnt
+apply_mount_plan() {
+    local device mountpoint fs target
+    while IFS=: read -r device mountpoint fs; do
+        if [[ "$mountpoint" == "swap" ]]; then
+            target="[SWAP]"
+        elif [[ "$mountpoint" == "/" ]]; then
+            target="/mnt"
+        else
+            target="/mnt$mountpoint"
+        fi
+
+        # Left mounted by an earlier attempt: already formatted, keep it
+        if [[ "$target" != "[SWAP]" ]] && mountpoint -q "$target" \
+            && [[ "$(findmnt -n -o SOURCE "$target")" == "$device" ]]; then
+            log_info "$device is already mounted at $target"
+        else
+            if [[ -n "$fs" ]]; then
+                log_info "Formatting $device as $fs for $mountpoint"
+                format_filesystem "$device" "$fs" || error_exit "Failed to format $device as $fs."
+            else
+                log_info "Keeping the data on $device ($mountpoint)"
+            fi
+            if [[ "$target" == "[SWAP]" ]]; then
+                swapon "$device" || error_exit "Failed to enable swap on $device."
+            else
+                safe_mount "$device" "$target" || error_exit "Failed to mount $device at $target."
+            fi
+        fi
+
+        case "$mountpoint" in
+            /)
+                capture_device_info "root" "$device"
+                # The root filesystem decides the initramfs and snapshot setup
+                ROOT_FILESYSTEM_TYPE="${fs:-$(findmnt -n -o FSTYPE /mnt)}"
+                export ROOT_FILESYSTEM_TYPE
+                ;;
+            swap) capture_device_info "swap" "$device" ;;
+            /efi|/boot/efi|/boot)
+                if [[ "$mountpoint" != "/boot" || "$(lsblk -n -o FSTYPE "$device")" == "vfat" ]]; then
+                    capture_device_info "efi" "$device"
+                fi
+                # A kept ESP holds other boot loaders, treat it as a reused one
+                if [[ -z "$fs" && "$mountpoint" == "/efi" ]]; then
+                    EFI_PARTITION="$device"
+                    export EFI_PARTITION
+                fi
+                ;;
+        esac
+    done < <(mount_plan_entries)
+}
+
 setup_btrfs_subvolumes() {
     local mountpoint="$1"
     local include_home="${2:-no}"
+    # A separate /var partition replaces the @var subvolume
+    local include_var="${3:-yes}"
     
     log_info "Creating Btrfs subvolumes at $mountpoint"
     
     btrfs subvolume create "$mountpoint/@"
-    btrfs subvolume create "$mountpoint/@var"
+    if [[ "$include_var" == "yes" ]]; then
+        btrfs subvolume create "$mountpoint/@var"
+    fi
     btrfs subvolume create "$mountpoint/@tmp"
     btrfs subvolume create "$mountpoint/@snapshots"
     
@@ -369,6 +872,7 @@ capture_device_info() {
         root) export ROOT_DEVICE="$device" ;;
         efi)  export EFI_DEVICE="$device" ;;
         swap) export SWAP_DEVICE="$device" ;;
+        var)  export VAR_DEVICE="$device" ;;
     esac
     
     log_info "Captured $type device: $device"
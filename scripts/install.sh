@@ -16,7 +16,7 @@ cleanup_on_exit() {
     echo "=== CLEANUP ON EXIT (Code: $exit_code) ==="
 
     # Try to unmount everything cleanly (in reverse order)
-    for mount_point in /mnt/home /mnt/boot /mnt/efi /mnt; do
+    for mount_point in /mnt/home /mnt/var /mnt/boot /mnt/efi /mnt; do
         if mountpoint -q "$mount_point" 2>/dev/null; then
             echo "Unmounting $mount_point..."
             umount -R "$mount_point" 2>/dev/null || true
@@ -81,6 +81,7 @@ _source_or_die() {
 _source_or_die "$SCRIPT_DIR/utils.sh"
 _source_or_die "$SCRIPT_DIR/disk_strategies.sh"
 _source_or_die "$SCRIPT_DIR/config_loader.sh"
+_source_or_die "$SCRIPT_DIR/install_report.sh"
 
 # --- Secure Password Input from TUI ---
 # SECURITY: Passwords are passed via stdin (not environment variables)
@@ -93,8 +94,9 @@ _source_or_die "$SCRIPT_DIR/config_loader.sh"
 #
 # Detection: If stdin is a pipe (not a terminal) and ARCHINSTALL_TUI is set,
 # we read passwords from stdin. Otherwise, assume headless/interactive mode.
+# Unattended installs (local or over SSH) take credentials from the config file.
 
-if [[ ! -t 0 ]] && [[ -n "${ARCHINSTALL_TUI:-}" || -p /dev/stdin ]]; then
+if [[ "${ARCHINSTALL_UNATTENDED:-}" != "1" ]] && [[ ! -t 0 ]] && [[ -n "${ARCHINSTALL_TUI:-}" || -p /dev/stdin ]]; then
     # TUI mode: read passwords from piped stdin
     echo "Reading secure credentials from TUI..."
 
@@ -140,8 +142,9 @@ export LOG_LEVEL="${LOG_LEVEL:-INFO}"
 # Perform pre-flight checks
 perform_preflight_checks
 
-# Parse command line arguments
-CONFIG_FILE=""
+# Parse command line arguments; the Rust frontend names the configuration
+# file in ARCHINSTALL_CONFIG_JSON (see src/script_env.rs), --config overrides it
+CONFIG_FILE="${ARCHINSTALL_CONFIG_JSON:-}"
 while [[ $# -gt 0 ]]; do
     case $1 in
         --config)
@@ -155,8 +158,8 @@ while [[ $# -gt 0 ]]; do
             echo "  --config FILE    Load configuration from JSON file"
             echo "  --help, -h       Show this help message"
             echo ""
-            echo "If no --config is provided, the installer expects environment variables"
-            echo "to be set by the TUI frontend."
+            echo "Without --config, the file named by ARCHINSTALL_CONFIG_JSON is loaded,"
+            echo "or else the installer expects environment variables set by the TUI frontend."
             exit 0
             ;;
         *)
@@ -167,6 +170,15 @@ while [[ $# -gt 0 ]]; do
     esac
 done
 
+# --- Unattended Mode ---
+# ARCHINSTALL_UNATTENDED=1 pre-answers every confirmation and forbids any
+# interactive fallback: stdin is closed so a stray prompt fails instead of hanging.
+if [[ "${ARCHINSTALL_UNATTENDED:-}" == "1" ]]; then
+    log_info "Unattended mode: confirmations pre-answered, on-error policy: ${ARCHINSTALL_ON_ERROR:-abort}"
+    export CONFIRM_WIPE_DISK=yes
+    exec 0</dev/null
+fi
+
 # --- Configuration Loading ---
 if [[ -n "$CONFIG_FILE" ]]; then
     log_info "Loading configuration from JSON file: $CONFIG_FILE"
@@ -181,6 +193,10 @@ else
     fi
 fi
 
+if [[ "${ARCHINSTALL_UNATTENDED:-}" == "1" && "${PARTITIONING_STRATEGY:-}" == "manual" ]]; then
+    error_exit "Manual partitioning requires interaction and cannot run unattended"
+fi
+
 # --- Configuration Variables with Defaults ---
 # Boot Configuration
 BOOT_MODE="${BOOT_MODE:-Auto}"
@@ -197,11 +213,24 @@ PARTITIONING_STRATEGY="${PARTITIONING_STRATEGY:-auto_simple}"
 export PARTITION_SCHEME="$PARTITIONING_STRATEGY"
 ENCRYPTION="${ENCRYPTION:-No}"
 ENCRYPTION_PASSWORD="${ENCRYPTION_PASSWORD:-}"
+ENCRYPTED_BOOT="${ENCRYPTED_BOOT:-No}"
+EFI_PARTITION="${EFI_PARTITION:-create}"
+EXISTING_OS="${EXISTING_OS:-protect}"
+PARTITION_MOUNTS="${PARTITION_MOUNTS:-}"
 ROOT_FILESYSTEM="${ROOT_FILESYSTEM:-ext4}"
 SEPARATE_HOME="${SEPARATE_HOME:-No}"
 HOME_FILESYSTEM="${HOME_FILESYSTEM:-ext4}"
 SWAP="${SWAP:-Yes}"
 SWAP_SIZE="${SWAP_SIZE:-2GB}"
+HIBERNATION="${HIBERNATION:-No}"
+LVM_VOLUME_GROUP="${LVM_VOLUME_GROUP:-arch}"
+LVM_ROOT_SIZE="${LVM_ROOT_SIZE:-50G}"
+LVM_VAR_SIZE="${LVM_VAR_SIZE:-none}"
+LVM_HOME_SIZE="${LVM_HOME_SIZE:-100%FREE}"
+LVM_THIN="${LVM_THIN:-No}"
+VAR_PARTITION_SIZE="${VAR_PARTITION_SIZE:-none}"
+TMP_POLICY="${TMP_POLICY:-tmpfs}"
+TMP_SIZE="${TMP_SIZE:-50%}"
 
 # Convert TUI variables to internal format
 ROOT_FILESYSTEM_TYPE="$ROOT_FILESYSTEM"
@@ -213,7 +242,10 @@ WANT_SWAP="$(echo "${SWAP:-Yes}" | tr '[:upper:]' '[:lower:]')"
 
 # Export for strategy scripts
 export ROOT_FILESYSTEM_TYPE HOME_FILESYSTEM_TYPE WANT_HOME_PARTITION WANT_SWAP
-export ENCRYPTION ENCRYPTION_PASSWORD
+export ENCRYPTION ENCRYPTION_PASSWORD ENCRYPTED_BOOT EFI_PARTITION EXISTING_OS HIBERNATION SWAP_SIZE
+export PARTITION_MOUNTS
+export LVM_VOLUME_GROUP LVM_ROOT_SIZE LVM_VAR_SIZE LVM_HOME_SIZE LVM_THIN
+export VAR_PARTITION_SIZE TMP_POLICY TMP_SIZE
 
 # Btrfs options
 BTRFS_SNAPSHOTS="${BTRFS_SNAPSHOTS:-No}"
@@ -225,19 +257,46 @@ BTRFS_ASSISTANT="${BTRFS_ASSISTANT:-No}"
 TIMEZONE_REGION="${TIMEZONE_REGION:-America}"
 TIMEZONE="${TIMEZONE:-New_York}"
 TIME_SYNC="${TIME_SYNC:-Yes}"
+TIME_SYNC_DAEMON="${TIME_SYNC_DAEMON:-systemd-timesyncd}"
+NTP_SERVERS="${NTP_SERVERS:-}"
+HARDWARE_CLOCK="${HARDWARE_CLOCK:-UTC}"
+
+# Proxy (blank for a direct connection), else the one the installer was started with
+HTTP_PROXY="${HTTP_PROXY:-${http_proxy:-}}"
+HTTPS_PROXY="${HTTPS_PROXY:-${https_proxy:-$HTTP_PROXY}}"
+NO_PROXY="${NO_PROXY:-${no_proxy:-}}"
+# curl, which pacstrap, pacman and reflector download with, reads the lowercase names
+export HTTP_PROXY HTTPS_PROXY NO_PROXY
+export http_proxy="$HTTP_PROXY" https_proxy="$HTTPS_PROXY" no_proxy="$NO_PROXY"
 
 # System Packages
 MIRROR_COUNTRY="${MIRROR_COUNTRY:-United States}"
+PKG_CACHE="${PKG_CACHE:-}"
 KERNEL="${KERNEL:-linux}"
+MICROCODE="${MICROCODE:-auto}"
 MULTILIB="${MULTILIB:-Yes}"
 ADDITIONAL_PACKAGES="${ADDITIONAL_PACKAGES:-}"
+PACKAGE_GROUPS="${PACKAGE_GROUPS:-}"
+PARALLEL_DOWNLOADS="${PARALLEL_DOWNLOADS:-5}"
+# Yes below LOW_MEMORY_THRESHOLD_MIB of RAM, decided by perform_preflight_checks
+LOW_MEMORY="${LOW_MEMORY:-No}"
+PACMAN_COLOR="${PACMAN_COLOR:-Yes}"
+VERBOSE_PKG_LISTS="${VERBOSE_PKG_LISTS:-No}"
+CUSTOM_REPOSITORIES="${CUSTOM_REPOSITORIES:-}"
 GPU_DRIVERS="${GPU_DRIVERS:-Auto}"
+NVIDIA_DRIVER="${NVIDIA_DRIVER:-open}"
+NVIDIA_EXTRAS="${NVIDIA_EXTRAS:-Yes}"
+VM_GUEST_TOOLS="${VM_GUEST_TOOLS:-None}"
+# Packages and services of the guest tools, exported by the binary
+GUEST_TOOLS_PACKAGES="${GUEST_TOOLS_PACKAGES:-}"
+GUEST_TOOLS_SERVICES="${GUEST_TOOLS_SERVICES:-}"
 
 # User Setup
 SYSTEM_HOSTNAME="${SYSTEM_HOSTNAME:-archlinux}"
 MAIN_USERNAME="${MAIN_USERNAME:-user}"
 MAIN_USER_PASSWORD="${MAIN_USER_PASSWORD:-}"
 ROOT_PASSWORD="${ROOT_PASSWORD:-}"
+SSH_KEYS="${SSH_KEYS:-}"
 
 # Package Management
 AUR_HELPER="${AUR_HELPER:-paru}"
@@ -249,10 +308,26 @@ BOOTLOADER="${BOOTLOADER:-grub}"
 OS_PROBER="${OS_PROBER:-Yes}"
 GRUB_THEME="${GRUB_THEME:-No}"
 GRUB_THEME_SELECTION="${GRUB_THEME_SELECTION:-arch}"
+EARLY_KMS="${EARLY_KMS:-No}"
+INITRAMFS_MODULES="${INITRAMFS_MODULES:-}"
+INITRAMFS_HOOKS="${INITRAMFS_HOOKS:-auto}"
 
 # Desktop Environment
 DESKTOP_ENVIRONMENT="${DESKTOP_ENVIRONMENT:-none}"
+DESKTOP_PACKAGES="${DESKTOP_PACKAGES:-}"
 DISPLAY_MANAGER="${DISPLAY_MANAGER:-sddm}"
+AUDIO="${AUDIO:-pipewire}"
+# Packages, user units and conflicting packages of the sound server, exported by the binary
+AUDIO_PACKAGES="${AUDIO_PACKAGES:-}"
+AUDIO_USER_SERVICES="${AUDIO_USER_SERVICES:-}"
+AUDIO_REPLACES="${AUDIO_REPLACES:-}"
+POWER_MANAGEMENT="${POWER_MANAGEMENT:-none}"
+LID_SWITCH="${LID_SWITCH:-suspend}"
+
+# Services enabled in the installed system (units, or "none")
+SERVICES="${SERVICES:-NetworkManager.service sshd.service fstrim.timer systemd-timesyncd.service}"
+# Packages those units need, exported by the binary
+SERVICE_PACKAGES="${SERVICE_PACKAGES:-}"
 
 # Boot Splash and Final Setup
 PLYMOUTH="${PLYMOUTH:-No}"
@@ -261,41 +336,64 @@ NUMLOCK_ON_BOOT="${NUMLOCK_ON_BOOT:-No}"
 GIT_REPOSITORY="${GIT_REPOSITORY:-No}"
 GIT_REPOSITORY_URL="${GIT_REPOSITORY_URL:-}"
 
+# Custom phases (built-in or plugins), run by the archinstall binary
+CUSTOM_PHASES="${CUSTOM_PHASES:-}"
+
 # --- Main Installation Function ---
 main() {
     echo "Starting Arch Linux installation..."
 
     # Phase 1: Validate configuration
     log_info "Phase 1: Validating configuration..."
-    validate_configuration || error_exit "Configuration validation failed"
+    run_phase critical "Configuration validation" validate_configuration
 
     # Phase 2: Prepare system
     log_info "Phase 2: Preparing system..."
-    prepare_system || error_exit "System preparation failed"
+    run_phase optional "System preparation" prepare_system
 
     # Phase 3: Check and install dependencies
     log_info "Phase 3: Installing dependencies..."
-    check_and_install_dependencies || error_exit "Dependency installation failed"
+    run_phase optional "Dependency installation" check_and_install_dependencies
 
     # Phase 4: Partition disk
     log_info "Phase 4: Partitioning disk..."
-    partition_disk || error_exit "Disk partitioning failed"
+    run_phase critical "Disk partitioning" partition_disk
 
     # Phase 5: Install base system (pacstrap)
     log_info "Phase 5: Installing base system..."
-    install_base_system || error_exit "Base system installation failed"
+    run_phase critical "Base system installation" install_base_system
 
     # Phase 6: Generate fstab
     log_info "Phase 6: Generating fstab..."
-    generate_fstab || error_exit "fstab generation failed"
+    run_phase critical "fstab generation" generate_fstab
 
     # Phase 7: Configure system in chroot
     log_info "Phase 7: Configuring system in chroot..."
-    configure_chroot || error_exit "Chroot configuration failed"
+    run_phase optional "AUR helper" install_aur_helper
+    run_phase optional "Chroot configuration" configure_chroot
 
     # Phase 8: Finalize installation
     log_info "Phase 8: Finalizing installation..."
-    finalize_installation || error_exit "Installation finalization failed"
+    run_phase optional "Installation finalization" finalize_installation
+
+    # Phase 9: Custom phases declared in the config file
+    if [[ -n "$CUSTOM_PHASES" ]]; then
+        log_info "Phase 9: Running custom phases..."
+        run_phase optional "Custom phases" run_custom_phases
+    fi
+
+    # Phase 10: Record what was installed (partitions, packages, services, config)
+    log_info "Phase 10: Generating installation report..."
+    run_phase optional "Installation report" generate_install_report
+
+    if [[ ${#FAILED_PHASES[@]} -gt 0 ]]; then
+        echo "=========================================="
+        echo "Installation finished with errors in:"
+        printf '  - %s\n' "${FAILED_PHASES[@]}"
+        echo "=========================================="
+        # Exit code 5 = completed with non-fatal phase failures
+        exit 5
+    fi
 
     echo "=========================================="
     echo "Installation complete!"
@@ -350,12 +448,100 @@ validate_configuration() {
         fi
     fi
 
+    # systemd-boot is an EFI application; BIOS installs need GRUB
+    if [[ "$BOOT_MODE" == "BIOS" && "${BOOTLOADER:-grub}" == "systemd-boot" ]]; then
+        log_error "BIOS boot requires GRUB: $BOOTLOADER only boots on UEFI"
+        return 1
+    fi
+
+    # Arch Linux ARM builds one kernel, and boards without UEFI boot with U-Boot
+    local arch
+    arch="$(target_arch)"
+    if ! kernel_package "$KERNEL" >/dev/null; then
+        log_error "KERNEL=$KERNEL is not built for $arch: Arch Linux ARM only has linux (linux-aarch64)"
+        return 1
+    fi
+    if [[ "${BOOTLOADER:-grub}" == "u-boot" && "$arch" != "aarch64" ]]; then
+        log_error "u-boot only starts aarch64 boards: use grub or systemd-boot on $arch"
+        return 1
+    fi
+    if [[ "$arch" == "aarch64" && "$BOOT_MODE" == "BIOS" && "${BOOTLOADER:-grub}" == "grub" ]]; then
+        log_error "GRUB needs UEFI firmware on aarch64: boards without it boot with u-boot"
+        return 1
+    fi
+    case "$MICROCODE" in
+        auto|none) ;;
+        intel-ucode|amd-ucode)
+            if [[ "$arch" == "aarch64" ]]; then
+                log_error "MICROCODE=$MICROCODE: there are no microcode updates for $arch"
+                return 1
+            fi
+            ;;
+        *)
+            log_error "MICROCODE must be auto, intel-ucode, amd-ucode or none, not $MICROCODE"
+            return 1
+            ;;
+    esac
+
+    # Only the non-RAID automatic strategies install into free space
+    if [[ "$EXISTING_OS" == "alongside" ]]; then
+        case "$PARTITIONING_STRATEGY" in
+            auto_raid*|manual|existing)
+                log_error "EXISTING_OS=alongside cannot be used with $PARTITIONING_STRATEGY"
+                return 1
+                ;;
+        esac
+        # Share the other system's ESP instead of adding a second one
+        if [[ "$BOOT_MODE" == "UEFI" ]] && ! using_existing_esp; then
+            local disk_esp
+            disk_esp=$(lsblk -n -l -o PATH,PARTTYPE "$INSTALL_DISK" 2>/dev/null | awk -v guid="$ESP_PARTITION_GUID" 'tolower($2) == guid { print $1; exit }')
+            if [[ -n "$disk_esp" ]]; then
+                log_info "Installing alongside: reusing the EFI partition $disk_esp"
+                EFI_PARTITION="$disk_esp"
+                export EFI_PARTITION
+            fi
+        fi
+    fi
+
+    # Detected systems on the install disk are protected unless EXISTING_OS
+    # says otherwise; manual and existing partitioning keep what the user chose
+    if [[ "$PARTITIONING_STRATEGY" != "manual" && "$PARTITIONING_STRATEGY" != "existing" ]]; then
+        check_existing_systems "$INSTALL_DISK" || return 1
+    fi
+
+    # An existing ESP is only kept by the non-RAID automatic strategies
+    if using_existing_esp; then
+        if [[ "$BOOT_MODE" != "UEFI" ]]; then
+            log_error "EFI_PARTITION=$EFI_PARTITION needs UEFI boot mode"
+            return 1
+        fi
+        case "$PARTITIONING_STRATEGY" in
+            auto_raid*|manual|existing)
+                log_error "EFI_PARTITION cannot be reused with $PARTITIONING_STRATEGY"
+                return 1
+                ;;
+        esac
+        check_existing_esp "$EFI_PARTITION" || return 1
+    fi
+
+    # Existing partitions are installed onto as PARTITION_MOUNTS assigns them
+    if [[ "$PARTITIONING_STRATEGY" == "existing" && -z "$PARTITION_MOUNTS" ]]; then
+        log_error "PARTITIONING_STRATEGY=existing needs PARTITION_MOUNTS (e.g. /dev/sda2=/:ext4)"
+        return 1
+    fi
+
     # Validate LUKS encryption has password
     if [[ "$ENCRYPTION" == "Yes" && -z "$ENCRYPTION_PASSWORD" ]]; then
         log_error "ENCRYPTION is enabled but ENCRYPTION_PASSWORD is not set"
         return 1
     fi
 
+    # Encrypted /boot is unlocked by GRUB before the kernel runs
+    if [[ "$ENCRYPTED_BOOT" == "Yes" && "$BOOTLOADER" != "grub" ]]; then
+        log_error "ENCRYPTED_BOOT requires BOOTLOADER=grub ($BOOTLOADER cannot unlock LUKS)"
+        return 1
+    fi
+
     # Validate username format
     if ! echo "$MAIN_USERNAME" | grep -qE '^[a-z_][a-z0-9_-]*$'; then
         log_error "Invalid username format: $MAIN_USERNAME (must start with lowercase letter)"
@@ -367,9 +553,34 @@ validate_configuration() {
 }
 
 # --- System Preparation ---
+# Mirror ranking and the keyring run side by side once the clock is synced,
+# since TLS downloads and key signatures need the right time; the package
+# database is synced once both are ready.
 prepare_system() {
     log_info "Preparing system..."
 
+    # Give the live system swap and download one package at a time
+    local parallel="${ARCHINSTALL_PARALLEL_PHASES:-2}"
+    if [[ "$LOW_MEMORY" == "Yes" ]]; then
+        log_info "Low memory: enabling zram swap, serial downloads and one step at a time..."
+        enable_live_zram
+        PARALLEL_DOWNLOADS=1
+        parallel=1
+    fi
+
+    ARCHINSTALL_PARALLEL_PHASES="$parallel" run_parallel_phases \
+        "Clock sync||sync_clock" \
+        "Mirror ranking|Clock sync|configure_mirrors" \
+        "Keyring|Clock sync|init_keyring" \
+        "Pacman configuration|Keyring|configure_live_pacman" \
+        "Package database|Mirror ranking,Pacman configuration|sync_package_database" \
+        || return 1
+
+    log_success "System prepared"
+    return 0
+}
+
+sync_clock() {
     # Update system clock
     log_info "Enabling NTP time synchronization..."
     timedatectl set-ntp true
@@ -377,10 +588,27 @@ prepare_system() {
     # Wait for time sync
     log_info "Waiting for time sync..."
     sleep 2
+}
 
-    # Configure mirrors
-    configure_mirrors
+# The ISO initialises the keyring at boot; make sure it is there before
+# repository keys are imported and packages are verified
+init_keyring() {
+    log_info "Initializing pacman keyring..."
+    local keyring="archlinux"
+    if [[ "$(target_arch)" == "aarch64" ]]; then
+        keyring="archlinuxarm"
+    fi
+    pacman-key --init || return 1
+    pacman-key --populate "$keyring" || return 1
+    log_success "Keyring ready"
+}
 
+# Tune pacman on the live system so pacstrap benefits too
+configure_live_pacman() {
+    configure_pacman /etc/pacman.conf
+}
+
+sync_package_database() {
     # Update package database
     log_info "Updating package database (pacman -Sy)..."
     pacman -Sy --noconfirm 2>&1 | while IFS= read -r line; do
@@ -398,24 +626,32 @@ prepare_system() {
     done
 
     if [[ ${PIPESTATUS[0]} -ne 0 ]]; then
-        log_error "pacman -Sy failed"
-        return 1
+        # Offline, the local repository is all there is
+        if [[ -n "$PKG_CACHE" && -n "$(package_cache_repository)" ]]; then
+            log_warn "Mirrors unreachable, installing from the offline repository"
+        else
+            log_error "pacman -Sy failed"
+            return 1
+        fi
     fi
-
-    log_success "System prepared"
-    return 0
 }
 
 configure_mirrors() {
     log_info "Configuring package mirrors..."
 
+    # reflector only ranks Arch Linux mirrors, which have no aarch64 packages
+    if [[ "$(target_arch)" == "aarch64" ]]; then
+        log_info "Keeping the Arch Linux ARM mirrorlist"
+        return 0
+    fi
+
     # Backup original mirrorlist
     log_info "Backing up original mirrorlist..."
     cp /etc/pacman.d/mirrorlist /etc/pacman.d/mirrorlist.backup
 
     # Use reflector if available, otherwise use default mirrors
     if command -v reflector >/dev/null 2>&1; then
-        log_info "Using reflector to rank mirrors for country: ${MIRROR_COUNTRY:-US}..."
+        log_info "Using reflector to rank mirrors for countries: ${MIRROR_COUNTRY:-US}..."
         log_info "This may take a minute while mirrors are tested..."
         reflector --country "${MIRROR_COUNTRY:-US}" --age 12 --protocol https --sort rate --save /etc/pacman.d/mirrorlist 2>&1 | while IFS= read -r line; do
             case "$line" in
@@ -439,13 +675,37 @@ configure_mirrors() {
     else
         log_info "Reflector not available, using default mirrors"
     fi
+}
+
+# Apply pacman tuning, multilib and custom repositories to a pacman.conf
+# Usage: configure_pacman <pacman.conf> [command prefix for pacman-key...]
+configure_pacman() {
+    local conf="$1"
+    shift
+    log_info "Configuring pacman ($conf)..."
 
-    # Enable multilib if requested
-    if [[ "$MULTILIB" == "Yes" ]]; then
+    set_pacman_options "$conf"
+    log_info "ParallelDownloads = $PARALLEL_DOWNLOADS, Color = $PACMAN_COLOR, VerbosePkgLists = $VERBOSE_PKG_LISTS"
+
+    # Enable multilib if requested; Arch Linux ARM has none
+    if [[ "$MULTILIB" == "Yes" && "$(target_arch)" == "aarch64" ]]; then
+        log_warn "There is no multilib repository for aarch64, skipping it"
+    elif [[ "$MULTILIB" == "Yes" ]]; then
         log_info "Enabling multilib repository..."
-        sed -i '/^#\[multilib\]/,/^#Include/s/^#//' /etc/pacman.conf
+        sed -i '/^#\[multilib\]/,/^#Include/s/^#//' "$conf"
         log_success "Multilib repository enabled"
     fi
+
+    if [[ -n "$CUSTOM_REPOSITORIES" ]]; then
+        add_custom_repositories "$conf"
+        import_repository_keys "$@" || return 1
+    fi
+
+    if [[ -n "$PKG_CACHE" ]]; then
+        use_package_cache "$conf"
+    fi
+
+    log_success "pacman configured"
 }
 
 # --- Disk Partitioning ---
@@ -494,6 +754,10 @@ partition_disk() {
             strategy_func="do_manual_partitioning_guided"
             log_info "Using manual partitioning (guided)"
             ;;
+        "existing")
+            strategy_func="do_existing_partitioning"
+            log_info "Using existing partitions"
+            ;;
         *)
             log_error "Unknown partitioning strategy: $PARTITIONING_STRATEGY"
             return 1
@@ -513,12 +777,14 @@ install_base_system() {
     log_info "Installing base system with pacstrap..."
 
     # Build package list as array
+    local kernel
+    kernel="$(kernel_package "$KERNEL")" || return 1
     local -a base_packages=(
         "base"
         "base-devel"
         "linux-firmware"
-        "$KERNEL"
-        "${KERNEL}-headers"
+        "$kernel"
+        "${kernel}-headers"
     )
 
     # Add essential packages
@@ -537,6 +803,10 @@ install_base_system() {
         "man-pages"
         "texinfo"
     )
+    # Only what a first boot needs; the rest can be installed afterwards
+    if [[ "$LOW_MEMORY" == "Yes" ]]; then
+        essential_packages=("nano" "sudo" "networkmanager" "openssh")
+    fi
 
     # Add filesystem tools based on selected filesystems
     local -a fs_packages=()
@@ -550,6 +820,12 @@ install_base_system() {
         "ext4")
             fs_packages+=("e2fsprogs")
             ;;
+        "f2fs")
+            fs_packages+=("f2fs-tools")
+            ;;
+        "bcachefs")
+            fs_packages+=("bcachefs-tools")
+            ;;
     esac
 
     # Add LUKS/LVM packages if needed
@@ -580,14 +856,19 @@ install_base_system() {
                 bootloader_packages+=("efibootmgr")
             fi
             ;;
+        "u-boot")
+            # mkimage, for boards that still read a boot.scr
+            bootloader_packages+=("uboot-tools")
+            ;;
     esac
 
-    # Detect and add CPU microcode
+    # CPU microcode, detected from the CPU unless MICROCODE names it
     local -a microcode_packages=()
-    if grep -q "GenuineIntel" /proc/cpuinfo; then
-        microcode_packages+=("intel-ucode")
-    elif grep -q "AuthenticAMD" /proc/cpuinfo; then
-        microcode_packages+=("amd-ucode")
+    local microcode
+    microcode="$(microcode_package)"
+    if [[ -n "$microcode" ]]; then
+        log_info "CPU microcode: $microcode"
+        microcode_packages+=("$microcode")
     fi
 
     # Combine all packages
@@ -601,33 +882,74 @@ install_base_system() {
 
     log_info "Total packages to install: ${#all_packages[@]}"
     log_info "Package list: ${all_packages[*]}"
+    local -a pacstrap_options=(-K)
+    if [[ -n "$PKG_CACHE" ]]; then
+        # Name what is missing up front instead of failing halfway through pacstrap
+        local missing
+        missing="$(missing_packages "${all_packages[@]}")"
+        if [[ -n "$missing" ]]; then
+            log_error "Not in the package cache or any reachable repository: ${missing//$'\n'/ }"
+            return 1
+        fi
+        # pacstrap only reads a plain package cache through the host's CacheDir
+        if [[ -z "$(package_cache_repository)" ]]; then
+            pacstrap_options+=(-c)
+        fi
+    fi
+
+    # One transaction normally; with low memory, small ones so pacman never
+    # holds the whole package set, each followed by emptying the cache
+    local -a batches=("${all_packages[*]}")
+    if [[ "$LOW_MEMORY" == "Yes" ]]; then
+        batches=(
+            "${base_packages[0]}"
+            "${base_packages[*]:1}"
+            "${all_packages[*]:${#base_packages[@]}}"
+        )
+        log_info "Low memory: installing in ${#batches[@]} batches"
+    fi
+
     log_info "Starting pacstrap - this will take several minutes..."
     log_info "Downloading and installing packages to /mnt..."
 
-    # Run pacstrap with array expansion and show output
-    pacstrap -K /mnt "${all_packages[@]}" 2>&1 | while IFS= read -r line; do
-        # Filter and format pacstrap output for readability with colors
-        case "$line" in
-            *"error"*|*"Error"*|*"ERROR"*|*"failed"*)
-                echo -e "${LOG_COLORS[ERROR]}  [pacstrap] $line${COLORS[RESET]}"
-                ;;
-            *"warning"*|*"Warning"*|*"WARNING"*)
-                echo -e "${LOG_COLORS[WARN]}  [pacstrap] $line${COLORS[RESET]}"
-                ;;
-            *"downloading"*|*"installing"*|*"Packages"*|*"Total"*|*"::"*)
-                echo -e "${LOG_COLORS[COMMAND]}  [pacstrap] $line${COLORS[RESET]}"
-                ;;
-            *)
-                # Show other lines dimmed
-                echo -e "${COLORS[DIM]}  $line${COLORS[RESET]}"
-                ;;
-        esac
+    local batch
+    local -a batch_packages
+    for batch in "${batches[@]}"; do
+        read -ra batch_packages <<< "$batch"
+        [[ ${#batch_packages[@]} -gt 0 ]] || continue
+
+        # Run pacstrap with array expansion and show output
+        pacstrap "${pacstrap_options[@]}" /mnt "${batch_packages[@]}" 2>&1 | while IFS= read -r line; do
+            # Filter and format pacstrap output for readability with colors
+            case "$line" in
+                *"error"*|*"Error"*|*"ERROR"*|*"failed"*)
+                    echo -e "${LOG_COLORS[ERROR]}  [pacstrap] $line${COLORS[RESET]}"
+                    ;;
+                *"warning"*|*"Warning"*|*"WARNING"*)
+                    echo -e "${LOG_COLORS[WARN]}  [pacstrap] $line${COLORS[RESET]}"
+                    ;;
+                *"downloading"*|*"installing"*|*"Packages"*|*"Total"*|*"::"*)
+                    echo -e "${LOG_COLORS[COMMAND]}  [pacstrap] $line${COLORS[RESET]}"
+                    ;;
+                *)
+                    # Show other lines dimmed
+                    echo -e "${COLORS[DIM]}  $line${COLORS[RESET]}"
+                    ;;
+            esac
+        done
+
+        if [[ ${PIPESTATUS[0]} -ne 0 ]]; then
+            log_error "pacstrap failed"
+            return 1
+        fi
+
+        if [[ "$LOW_MEMORY" == "Yes" && -z "$PKG_CACHE" ]]; then
+            rm -f /mnt/var/cache/pacman/pkg/*.pkg.tar.*
+        fi
     done
 
-    if [[ ${PIPESTATUS[0]} -ne 0 ]]; then
-        log_error "pacstrap failed"
-        return 1
-    fi
+    # pacstrap installs the stock pacman.conf, apply the same settings there
+    configure_pacman /mnt/etc/pacman.conf arch-chroot /mnt || return 1
 
     log_success "Base system installed successfully"
     return 0
@@ -637,6 +959,12 @@ install_base_system() {
 generate_fstab() {
     log_info "Generating fstab..."
 
+    # A reused ESP is kept, not created, so no strategy has mounted it
+    if using_existing_esp && ! mountpoint -q /mnt/efi; then
+        log_info "Mounting existing EFI partition $EFI_PARTITION at /mnt/efi"
+        safe_mount "$EFI_PARTITION" /mnt/efi
+    fi
+
     # Generate fstab using UUIDs
     genfstab -U /mnt >> /mnt/etc/fstab
 
@@ -646,6 +974,9 @@ generate_fstab() {
         return 1
     fi
 
+    # /tmp as TMP_POLICY asks: a sized tmpfs entry, or tmp.mount masked
+    configure_tmp_mount /mnt || return 1
+
     log_info "Generated fstab:"
     cat /mnt/etc/fstab
 
@@ -665,10 +996,22 @@ configure_chroot() {
     mkdir -p /mnt/root/desktops
     cp -r "$SCRIPT_DIR/desktops/"* /mnt/root/desktops/ 2>/dev/null || true
 
+    # The target's pacman.conf names the package cache at the same path
+    if [[ -n "$PKG_CACHE" ]]; then
+        mkdir -p "/mnt$PKG_CACHE"
+        mount --bind "$PKG_CACHE" "/mnt$PKG_CACHE"
+    fi
+
     # Make scripts executable
     chmod +x /mnt/root/chroot_config.sh
     chmod +x /mnt/root/utils.sh
 
+    # Keys are gathered here, where the network is up and USB sticks are mounted;
+    # chroot_config.sh hands them to the user
+    if [[ -n "$SSH_KEYS" ]]; then
+        collect_ssh_keys /mnt/root/authorized_keys || log_warn "Some SSH keys could not be imported"
+    fi
+
     # Export all configuration variables for chroot
     # Use a config file to pass variables (more reliable than env)
     cat > /mnt/root/install_config.sh << CONFIGEOF
@@ -683,8 +1026,22 @@ export TIMEZONE="$TIMEZONE"
 export LOCALE="$LOCALE"
 export KEYMAP="$KEYMAP"
 export DESKTOP_ENVIRONMENT="$DESKTOP_ENVIRONMENT"
+export DESKTOP_PACKAGES="$DESKTOP_PACKAGES"
 export DISPLAY_MANAGER="$DISPLAY_MANAGER"
+export AUDIO="$AUDIO"
+export AUDIO_PACKAGES="$AUDIO_PACKAGES"
+export AUDIO_USER_SERVICES="$AUDIO_USER_SERVICES"
+export AUDIO_REPLACES="$AUDIO_REPLACES"
+export POWER_MANAGEMENT="$POWER_MANAGEMENT"
+export LID_SWITCH="$LID_SWITCH"
+export SERVICES="$SERVICES"
+export SERVICE_PACKAGES="$SERVICE_PACKAGES"
 export GPU_DRIVERS="$GPU_DRIVERS"
+export NVIDIA_DRIVER="$NVIDIA_DRIVER"
+export NVIDIA_EXTRAS="$NVIDIA_EXTRAS"
+export VM_GUEST_TOOLS="$VM_GUEST_TOOLS"
+export GUEST_TOOLS_PACKAGES="$GUEST_TOOLS_PACKAGES"
+export GUEST_TOOLS_SERVICES="$GUEST_TOOLS_SERVICES"
 export AUR_HELPER="$AUR_HELPER"
 export ADDITIONAL_PACKAGES="$ADDITIONAL_PACKAGES"
 export ADDITIONAL_AUR_PACKAGES="$ADDITIONAL_AUR_PACKAGES"
@@ -699,17 +1056,30 @@ export BOOTLOADER="$BOOTLOADER"
 export OS_PROBER="$OS_PROBER"
 export GRUB_THEME="$GRUB_THEME"
 export GRUB_THEME_SELECTION="$GRUB_THEME_SELECTION"
+export EARLY_KMS="$EARLY_KMS"
+export INITRAMFS_MODULES="$INITRAMFS_MODULES"
+export INITRAMFS_HOOKS="$INITRAMFS_HOOKS"
 export SECURE_BOOT="$SECURE_BOOT"
 export KERNEL="$KERNEL"
+export MICROCODE="$MICROCODE"
 export MULTILIB="$MULTILIB"
 export TIME_SYNC="$TIME_SYNC"
+export TIME_SYNC_DAEMON="$TIME_SYNC_DAEMON"
+export NTP_SERVERS="$NTP_SERVERS"
+export HARDWARE_CLOCK="$HARDWARE_CLOCK"
+export HTTP_PROXY="$HTTP_PROXY"
+export HTTPS_PROXY="$HTTPS_PROXY"
+export NO_PROXY="$NO_PROXY"
 export INSTALL_DISK="$INSTALL_DISK"
 export PARTITIONING_STRATEGY="$PARTITIONING_STRATEGY"
 export ENCRYPTION="$ENCRYPTION"
+export ENCRYPTED_BOOT="$ENCRYPTED_BOOT"
+export EFI_PARTITION="$EFI_PARTITION"
 export ROOT_FILESYSTEM="$ROOT_FILESYSTEM"
 export HOME_FILESYSTEM="$HOME_FILESYSTEM"
 export BTRFS_SNAPSHOTS="$BTRFS_SNAPSHOTS"
 export SWAP="$SWAP"
+export HIBERNATION="$HIBERNATION"
 export ROOT_UUID="${ROOT_UUID:-}"
 export LUKS_UUID="${LUKS_UUID:-}"
 CONFIGEOF
@@ -762,6 +1132,14 @@ CONFIGEOF
     rm -f /mnt/root/chroot_config.sh
     rm -f /mnt/root/utils.sh
     rm -f /mnt/root/install_config.sh
+    rm -f /mnt/root/authorized_keys
+
+    # The installed system downloads from the mirrors
+    if [[ -n "$PKG_CACHE" ]]; then
+        umount "/mnt$PKG_CACHE" 2>/dev/null || true
+        rmdir "/mnt$PKG_CACHE" 2>/dev/null || true
+        forget_package_cache /mnt/etc/pacman.conf
+    fi
     rm -rf /mnt/root/desktops
 
     if [[ $chroot_exit -ne 0 ]]; then
@@ -796,5 +1174,48 @@ finalize_installation() {
     return 0
 }
 
+# --- AUR Helper ---
+# Built by the archinstall binary's aur-helper phase as a temporary build
+# user, before the chroot configuration installs ADDITIONAL_AUR_PACKAGES
+install_aur_helper() {
+    if [[ "${AUR_HELPER,,}" == "none" || -z "$AUR_HELPER" ]]; then
+        log_info "No AUR helper selected"
+        return 0
+    fi
+    if [[ -z "${ARCHINSTALL_BIN:-}" || ! -x "$ARCHINSTALL_BIN" ]]; then
+        log_error "Building the AUR helper needs the archinstall binary (ARCHINSTALL_BIN is not set)"
+        return 1
+    fi
+
+    log_info "Building AUR helper: $AUR_HELPER"
+    if ! AUR_HELPER="$AUR_HELPER" "$ARCHINSTALL_BIN" phases run --target /mnt aur-helper; then
+        log_error "AUR helper $AUR_HELPER could not be built; AUR packages will be skipped"
+        return 1
+    fi
+
+    log_success "AUR helper $AUR_HELPER installed"
+    return 0
+}
+
+# --- Custom Phases ---
+# Phases are resolved and run by the archinstall binary, which rolls back
+# the ones already applied when a phase fails.
+run_custom_phases() {
+    if [[ -z "${ARCHINSTALL_BIN:-}" || ! -x "$ARCHINSTALL_BIN" ]]; then
+        log_error "Custom phases need the archinstall binary (ARCHINSTALL_BIN is not set)"
+        return 1
+    fi
+
+    log_info "Custom phases: $CUSTOM_PHASES"
+    # shellcheck disable=SC2086 # phase names are validated, one word each
+    if ! "$ARCHINSTALL_BIN" phases run --target /mnt $CUSTOM_PHASES; then
+        log_error "Custom phases failed"
+        return 1
+    fi
+
+    log_success "Custom phases complete"
+    return 0
+}
+
 # --- Run main function ---
 main "$@"
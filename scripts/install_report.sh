@@ -0,0 +1,201 @@
+#!/bin/bash
+# install_report.sh - Installation report generation
+#
+# After a successful install, records what was actually installed in two forms:
+#   install-report.json  machine-readable (partitions, UUIDs, packages, services, config)
+#   install-report.md    the same information for humans
+# Both are written to /var/log/archinstall/ on the target system and, when
+# ARCHINSTALL_REPORT_DIR is set (--report), copied there as well.
+#
+# Pure bash: jq is only guaranteed in JSON config mode, so JSON is built by hand.
+
+# Target root the report is collected from (the mounted new system)
+REPORT_TARGET_ROOT="${REPORT_TARGET_ROOT:-/mnt}"
+
+# Report location relative to the target root
+REPORT_LOG_DIR="/var/log/archinstall"
+
+# Configuration variables recorded in the report.
+# SECURITY: passwords are deliberately not listed and never written, nor are
+# the proxies, whose URLs may carry credentials.
+REPORT_CONFIG_VARS=(
+    BOOT_MODE SECURE_BOOT LOCALE KEYMAP
+    INSTALL_DISK PARTITIONING_STRATEGY ENCRYPTION ENCRYPTED_BOOT EFI_PARTITION EXISTING_OS ROOT_FILESYSTEM
+    SEPARATE_HOME HOME_FILESYSTEM SWAP SWAP_SIZE HIBERNATION
+    LVM_VOLUME_GROUP LVM_ROOT_SIZE LVM_VAR_SIZE LVM_HOME_SIZE LVM_THIN
+    VAR_PARTITION_SIZE TMP_POLICY TMP_SIZE
+    BTRFS_SNAPSHOTS BTRFS_FREQUENCY BTRFS_KEEP_COUNT BTRFS_ASSISTANT
+    TIMEZONE_REGION TIMEZONE TIME_SYNC TIME_SYNC_DAEMON NTP_SERVERS HARDWARE_CLOCK
+    MIRROR_COUNTRY PKG_CACHE KERNEL MICROCODE MULTILIB PARALLEL_DOWNLOADS PACMAN_COLOR VERBOSE_PKG_LISTS
+    CUSTOM_REPOSITORIES CUSTOM_PHASES PACKAGE_GROUPS ADDITIONAL_PACKAGES GPU_DRIVERS
+    NVIDIA_DRIVER NVIDIA_EXTRAS VM_GUEST_TOOLS
+    SYSTEM_HOSTNAME MAIN_USERNAME SSH_KEYS
+    AUR_HELPER ADDITIONAL_AUR_PACKAGES FLATPAK
+    BOOTLOADER OS_PROBER GRUB_THEME GRUB_THEME_SELECTION EARLY_KMS INITRAMFS_MODULES INITRAMFS_HOOKS
+    DESKTOP_ENVIRONMENT DISPLAY_MANAGER AUDIO POWER_MANAGEMENT LID_SWITCH SERVICES
+    PLYMOUTH PLYMOUTH_THEME NUMLOCK_ON_BOOT GIT_REPOSITORY GIT_REPOSITORY_URL
+)
+
+# Escape a string for use inside a JSON string literal
+# Usage: report_json_escape "text"
+report_json_escape() {
+    local s="$1"
+    s="${s//\\/\\\\}"
+    s="${s//\"/\\\"}"
+    s="${s//$'\n'/\\n}"
+    s="${s//$'\r'/\\r}"
+    s="${s//$'\t'/\\t}"
+    printf '%s' "$s"
+}
+
+# Print a JSON array of strings, one element per non-empty stdin line
+# Usage: printf '%s\n' a b | report_json_array
+report_json_array() {
+    local line first=1
+    printf '['
+    while IFS= read -r line; do
+        [[ -z "$line" ]] && continue
+        if (( first )); then
+            first=0
+        else
+            printf ','
+        fi
+        printf '"%s"' "$(report_json_escape "$line")"
+    done
+    printf ']'
+}
+
+# Partition layout of the install disk as lsblk JSON ("null" if unavailable)
+report_partitions_json() {
+    local layout
+    if layout=$(lsblk -J -o NAME,PATH,SIZE,TYPE,FSTYPE,MOUNTPOINT,UUID,PARTUUID "$INSTALL_DISK" 2>/dev/null) \
+        && [[ -n "$layout" ]]; then
+        printf '%s' "$layout"
+    else
+        printf 'null'
+    fi
+}
+
+# Installed packages as "name version" lines
+report_packages() {
+    pacman --dbpath "$REPORT_TARGET_ROOT/var/lib/pacman" -Q 2>/dev/null || true
+}
+
+# Enabled systemd unit names
+report_services() {
+    systemctl --root="$REPORT_TARGET_ROOT" list-unit-files --state=enabled --no-legend 2>/dev/null \
+        | awk '{print $1}' || true
+}
+
+# Partition rows for the Markdown table: PATH SIZE FSTYPE MOUNTPOINT UUID
+# Raw lsblk output separates columns with single spaces and leaves empty
+# columns empty, so spaces become unit separators to keep empty fields.
+report_partition_rows() {
+    lsblk -rn -o PATH,SIZE,FSTYPE,MOUNTPOINT,UUID "$INSTALL_DISK" 2>/dev/null | tr ' ' '\037' || true
+}
+
+# Write the JSON report to the given file
+write_install_report_json() {
+    local out="$1"
+    local var first=1
+
+    {
+        printf '{\n'
+        printf '  "report_version": 1,\n'
+        printf '  "generated_at": "%s",\n' "$(date -u +%Y-%m-%dT%H:%M:%SZ)"
+        printf '  "install_disk": "%s",\n' "$(report_json_escape "${INSTALL_DISK:-}")"
+        printf '  "partitions": %s,\n' "$(report_partitions_json)"
+        printf '  "packages": %s,\n' "$(report_packages | report_json_array)"
+        printf '  "enabled_services": %s,\n' "$(report_services | report_json_array)"
+        printf '  "failed_phases": %s,\n' "$(printf '%s\n' "${FAILED_PHASES[@]+"${FAILED_PHASES[@]}"}" | report_json_array)"
+        printf '  "configuration": {'
+        for var in "${REPORT_CONFIG_VARS[@]}"; do
+            if (( first )); then
+                first=0
+            else
+                printf ','
+            fi
+            printf '\n    "%s": "%s"' "$var" "$(report_json_escape "${!var:-}")"
+        done
+        printf '\n  }\n}\n'
+    } > "$out"
+}
+
+# Write the Markdown report to the given file
+write_install_report_markdown() {
+    local out="$1"
+    local var path size fstype mountpoint uuid
+    local packages services
+    packages=$(report_packages)
+    services=$(report_services)
+
+    {
+        echo "# Arch Linux Installation Report"
+        echo ""
+        echo "Generated: $(date -u +%Y-%m-%dT%H:%M:%SZ)"
+        echo ""
+        echo "## Partition Layout"
+        echo ""
+        echo "| Device | Size | Filesystem | Mountpoint | UUID |"
+        echo "|--------|------|------------|------------|------|"
+        while IFS=$'\037' read -r path size fstype mountpoint uuid; do
+            [[ -z "$path" ]] && continue
+            echo "| $path | $size | ${fstype:--} | ${mountpoint:--} | ${uuid:--} |"
+        done < <(report_partition_rows)
+        echo ""
+        echo "## Enabled Services"
+        echo ""
+        if [[ -n "$services" ]]; then
+            sed 's/^/- /' <<< "$services"
+        else
+            echo "_None recorded_"
+        fi
+        echo ""
+        echo "## Configuration"
+        echo ""
+        echo "| Option | Value |"
+        echo "|--------|-------|"
+        for var in "${REPORT_CONFIG_VARS[@]}"; do
+            echo "| $var | ${!var:-} |"
+        done
+        if [[ ${#FAILED_PHASES[@]} -gt 0 ]]; then
+            echo ""
+            echo "## Failed Phases"
+            echo ""
+            printf -- '- %s\n' "${FAILED_PHASES[@]}"
+        fi
+        echo ""
+        echo "## Installed Packages ($(grep -c . <<< "$packages" || true))"
+        echo ""
+        echo '```'
+        echo "$packages"
+        echo '```'
+    } > "$out"
+}
+
+# Generate the installation report on the target and in ARCHINSTALL_REPORT_DIR
+generate_install_report() {
+    log_info "Generating installation report..."
+
+    local report_dir="$REPORT_TARGET_ROOT$REPORT_LOG_DIR"
+    if ! mkdir -p "$report_dir"; then
+        log_error "Cannot create report directory: $report_dir"
+        return 1
+    fi
+
+    write_install_report_json "$report_dir/install-report.json" || return 1
+    write_install_report_markdown "$report_dir/install-report.md" || return 1
+    log_success "Installation report saved to $REPORT_LOG_DIR/install-report.{json,md}"
+
+    if [[ -n "${ARCHINSTALL_REPORT_DIR:-}" ]]; then
+        if mkdir -p "$ARCHINSTALL_REPORT_DIR" \
+            && cp "$report_dir/install-report.json" "$report_dir/install-report.md" "$ARCHINSTALL_REPORT_DIR/"; then
+            log_success "Installation report copied to $ARCHINSTALL_REPORT_DIR"
+        else
+            log_error "Failed to copy installation report to $ARCHINSTALL_REPORT_DIR"
+            return 1
+        fi
+    fi
+
+    return 0
+}
@@ -0,0 +1,25 @@
+#!/bin/bash
+# existing.sh - Install onto existing partitions without repartitioning
+set -euo pipefail
+
+# Source common utilities via source_or_die
+SCRIPT_DIR="$(dirname "${BASH_SOURCE[0]}")"
+source_or_die "$SCRIPT_DIR/../disk_utils.sh"
+
+# Execute existing partitions strategy
+execute_existing_partitioning() {
+    echo "=== PHASE 1: Existing Partitions ==="
+    log_info "Installing onto existing partitions of $INSTALL_DISK (partition tables are kept)"
+
+    if [[ -z "${PARTITION_MOUNTS:-}" ]]; then
+        error_exit "PARTITION_MOUNTS is empty: assign the partitions to install onto first."
+    fi
+
+    apply_mount_plan
+
+    if ! mountpoint -q /mnt; then
+        error_exit "No partition was mounted at /mnt: PARTITION_MOUNTS needs a / entry."
+    fi
+
+    log_success "Existing partitions formatted and mounted"
+}
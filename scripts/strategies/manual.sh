@@ -27,6 +27,7 @@ execute_manual_partitioning() {
         echo "- Optional: Swap partition (your chosen size, formatted as swap)"
     else
         echo "For BIOS systems, you need:"
+        echo "- On a GPT disk: BIOS boot partition (1MB, type EF02, no filesystem) for GRUB"
         echo "- Boot partition (1GB, ext4, mounted to /mnt/boot)"
         echo "- Root partition (your chosen size and filesystem, mounted to /mnt)"
         echo "- Optional: Home partition (your chosen size and filesystem, mounted to /mnt/home)"
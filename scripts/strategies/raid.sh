@@ -34,18 +34,23 @@ execute_raid_partitioning() {
 
         local current_start_mib=1
 
-        # Create partition table (GPT for UEFI, MBR for BIOS)
+        # Create partition table (GPT for both UEFI and BIOS)
         create_partition_table "$disk"
 
         # 1. ESP partition on each disk (if UEFI) - NOT in RAID
         if [ "$BOOT_MODE" = "UEFI" ]; then
             sgdisk -n "$efi_part_num:0:+100M" -t "$efi_part_num:$EFI_PARTITION_TYPE" "$disk" || error_exit "Failed to create ESP partition on $disk."
             current_start_mib=$((current_start_mib + 100))
+        else
+            # BIOS with GPT: BIOS boot partition in the ESP slot, so GRUB
+            # can be installed on every member disk
+            create_bios_boot_partition "$disk" "$efi_part_num" || error_exit "Failed to create BIOS boot partition on $disk."
+            current_start_mib=$((current_start_mib + BIOS_BOOT_PART_SIZE_MIB))
         fi
 
         # 2. Boot partition on each disk (if BIOS) - NOT in RAID
         if [ "$BOOT_MODE" = "BIOS" ]; then
-            printf "n\np\n$xbootldr_part_num\n\n+1024M\nw\n" | fdisk "$disk" || error_exit "Failed to create boot partition on $disk."
+            sgdisk -n "$xbootldr_part_num:0:+1024M" -t "$xbootldr_part_num:$LINUX_PARTITION_TYPE" "$disk" || error_exit "Failed to create boot partition on $disk."
             current_start_mib=$((current_start_mib + 1024))
         fi
 
@@ -56,11 +61,7 @@ execute_raid_partitioning() {
         fi
         
         # 4. Data partition on each disk (takes rest of disk) - IN RAID
-        if [ "$BOOT_MODE" = "UEFI" ]; then
-            sgdisk -n "$data_part_num:0:0" -t "$data_part_num:$LINUX_PARTITION_TYPE" "$disk" || error_exit "Failed to create data partition on $disk."
-        else
-            printf "n\np\n$data_part_num\n\n\nw\n" | fdisk "$disk" || error_exit "Failed to create data partition on $disk."
-        fi
+        sgdisk -n "$data_part_num:0:0" -t "$data_part_num:$LINUX_PARTITION_TYPE" "$disk" || error_exit "Failed to create data partition on $disk."
         
         partprobe "$disk"
     done
@@ -119,6 +120,16 @@ execute_raid_partitioning() {
         btrfs subvolume create /mnt/@var
         btrfs subvolume create /mnt/@tmp
     fi
+
+    # Swap file on the array (if requested), there is no swap partition
+    if [ "$WANT_SWAP" = "yes" ]; then
+        local swap_file="/mnt/swapfile"
+        if [ "$ROOT_FILESYSTEM_TYPE" = "btrfs" ]; then
+            btrfs subvolume create /mnt/@swap
+            swap_file="/mnt/@swap/swapfile"
+        fi
+        create_swap_file "$swap_file" "$(get_configured_swap_size_mib)"
+    fi
     
     # Separate home partition (if requested)
     if [ "$WANT_HOME_PARTITION" = "yes" ]; then
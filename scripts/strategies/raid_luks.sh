@@ -23,8 +23,8 @@ execute_raid_luks_partitioning() {
         ESP_PARTITION_TYPE="$EFI_PARTITION_TYPE"
         XBOOTLDR_PARTITION_TYPE="$XBOOTLDR_PARTITION_TYPE"
     else
-        log_info "BIOS boot mode detected - using MBR partition tables"
-        PARTITION_TABLE="mbr"
+        log_info "BIOS boot mode detected - using GPT partition tables with a BIOS boot partition"
+        PARTITION_TABLE="bios_gpt"
         ESP_PARTITION_TYPE=""
         XBOOTLDR_PARTITION_TYPE=""
     fi
@@ -41,10 +41,11 @@ execute_raid_luks_partitioning() {
             sgdisk --new=2:0:+${XBOOTLDR_SIZE_MIB}MiB --typecode=2:"$XBOOTLDR_PARTITION_TYPE" --change-name=2:XBOOTLDR "$disk"
             sgdisk --new=3:0:0 --typecode=3:"$LUKS_PARTITION_TYPE" --change-name=3:RAID_MEMBER "$disk"
         else
-            # BIOS: MBR + RAID member
+            # BIOS: BIOS boot (for GRUB's core image) + BOOT + RAID member
             sgdisk --zap-all "$disk"
-            sgdisk --new=1:0:+${BOOT_SIZE_MIB}MiB --typecode=1:8300 --change-name=1:BOOT "$disk"
-            sgdisk --new=2:0:0 --typecode=2:"$LUKS_PARTITION_TYPE" --change-name=2:RAID_MEMBER "$disk"
+            create_bios_boot_partition "$disk" 1
+            sgdisk --new=2:0:+${BOOT_SIZE_MIB}MiB --typecode=2:8300 --change-name=2:BOOT "$disk"
+            sgdisk --new=3:0:0 --typecode=3:"$LUKS_PARTITION_TYPE" --change-name=3:RAID_MEMBER "$disk"
         fi
         
         sgdisk --print "$disk"
@@ -63,8 +64,8 @@ execute_raid_luks_partitioning() {
         DATA_PARTS=()
         
         for disk in "${INSTALL_DISKS[@]}"; do
-            XBOOTLDR_PARTS+=("${disk}2")
-            DATA_PARTS+=("${disk}3")
+            XBOOTLDR_PARTS+=("$(get_partition_path "$disk" 2)")
+            DATA_PARTS+=("$(get_partition_path "$disk" 3)")
         done
         
         # Create XBOOTLDR RAID1 array
@@ -88,8 +89,8 @@ execute_raid_luks_partitioning() {
         DATA_PARTS=()
         
         for disk in "${INSTALL_DISKS[@]}"; do
-            BOOT_PARTS+=("${disk}1")
-            DATA_PARTS+=("${disk}2")
+            BOOT_PARTS+=("$(get_partition_path "$disk" 2)")
+            DATA_PARTS+=("$(get_partition_path "$disk" 3)")
         done
         
         # Create boot RAID1 array
@@ -116,23 +117,23 @@ execute_raid_luks_partitioning() {
     log_info "Formatting encrypted RAID array"
     format_filesystem "/dev/mapper/cryptdata" "$ROOT_FILESYSTEM_TYPE"
     
-    # Create swap if requested
+    # Mount filesystems
+    log_info "Mounting filesystems"
+    mount /dev/mapper/cryptdata /mnt
+
+    # Create swap if requested: a swap file on the encrypted array, since a
+    # separate LUKS device for swap on the same RAID array is not practical
     if [[ "$WANT_SWAP" == "yes" ]]; then
         log_info "Creating swap on encrypted RAID array"
+        local swap_file="/mnt/swapfile"
         if [[ "$ROOT_FILESYSTEM_TYPE" == "btrfs" ]]; then
-            # Create swap subvolume for Btrfs
-            mount /dev/mapper/cryptdata /mnt
+            # Own subvolume, so snapshots of the root leave the swap file out
             btrfs subvolume create /mnt/@swap
-            umount /mnt
+            swap_file="/mnt/@swap/swapfile"
         fi
-        # Note: For non-btrfs, swap will be created as a file or using LVM on the encrypted volume
-        # Creating a separate LUKS device for swap on the same RAID array is not practical
+        create_swap_file "$swap_file" "$(get_configured_swap_size_mib)"
     fi
     
-    # Mount filesystems
-    log_info "Mounting filesystems"
-    mount /dev/mapper/cryptdata /mnt
-    
     if [[ "$PARTITION_TABLE" == "gpt" ]]; then
         # UEFI: Mount ESP and XBOOTLDR
         mkdir -p /mnt/efi /mnt/boot
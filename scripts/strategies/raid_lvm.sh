@@ -23,8 +23,8 @@ execute_raid_lvm_partitioning() {
         ESP_PARTITION_TYPE="$EFI_PARTITION_TYPE"
         XBOOTLDR_PARTITION_TYPE="$XBOOTLDR_PARTITION_TYPE"
     else
-        log_info "BIOS boot mode detected - using MBR partition tables"
-        PARTITION_TABLE="mbr"
+        log_info "BIOS boot mode detected - using GPT partition tables with a BIOS boot partition"
+        PARTITION_TABLE="bios_gpt"
         ESP_PARTITION_TYPE=""
         XBOOTLDR_PARTITION_TYPE=""
     fi
@@ -41,10 +41,11 @@ execute_raid_lvm_partitioning() {
             sgdisk --new=2:0:+${XBOOTLDR_SIZE_MIB}MiB --typecode=2:"$XBOOTLDR_PARTITION_TYPE" --change-name=2:XBOOTLDR "$disk"
             sgdisk --new=3:0:0 --typecode=3:"$LVM_PARTITION_TYPE" --change-name=3:RAID_MEMBER "$disk"
         else
-            # BIOS: MBR + RAID member
+            # BIOS: BIOS boot (for GRUB's core image) + BOOT + RAID member
             sgdisk --zap-all "$disk"
-            sgdisk --new=1:0:+${BOOT_SIZE_MIB}MiB --typecode=1:8300 --change-name=1:BOOT "$disk"
-            sgdisk --new=2:0:0 --typecode=2:"$LVM_PARTITION_TYPE" --change-name=2:RAID_MEMBER "$disk"
+            create_bios_boot_partition "$disk" 1
+            sgdisk --new=2:0:+${BOOT_SIZE_MIB}MiB --typecode=2:8300 --change-name=2:BOOT "$disk"
+            sgdisk --new=3:0:0 --typecode=3:"$LVM_PARTITION_TYPE" --change-name=3:RAID_MEMBER "$disk"
         fi
         
         sgdisk --print "$disk"
@@ -63,8 +64,8 @@ execute_raid_lvm_partitioning() {
         DATA_PARTS=()
         
         for disk in "${INSTALL_DISKS[@]}"; do
-            XBOOTLDR_PARTS+=("${disk}2")
-            DATA_PARTS+=("${disk}3")
+            XBOOTLDR_PARTS+=("$(get_partition_path "$disk" 2)")
+            DATA_PARTS+=("$(get_partition_path "$disk" 3)")
         done
         
         # Create XBOOTLDR RAID1 array
@@ -88,8 +89,8 @@ execute_raid_lvm_partitioning() {
         DATA_PARTS=()
         
         for disk in "${INSTALL_DISKS[@]}"; do
-            BOOT_PARTS+=("${disk}1")
-            DATA_PARTS+=("${disk}2")
+            BOOT_PARTS+=("$(get_partition_path "$disk" 2)")
+            DATA_PARTS+=("$(get_partition_path "$disk" 3)")
         done
         
         # Create boot RAID1 array
@@ -132,8 +133,9 @@ execute_raid_lvm_partitioning() {
     # Create swap logical volume if requested
     if [[ "$WANT_SWAP" == "yes" ]]; then
         log_info "Creating swap logical volume"
-        lvcreate -L "$SWAP_SIZE" -n swap archvg
+        lvcreate -L "$(get_configured_swap_size_mib)M" -n swap archvg
         mkswap /dev/archvg/swap
+        swapon /dev/archvg/swap
     fi
     
     # Create home logical volume if requested
@@ -14,20 +14,22 @@ execute_simple_partitioning() {
     # Validate requirements
     validate_partitioning_requirements
 
-    # Wipe disk with explicit confirmation
-    wipe_disk "$INSTALL_DISK" "CONFIRMED"
+    # Wipe disk and create partition table, unless it holds a reused ESP
+    prepare_install_disk "$INSTALL_DISK"
 
     local current_start_mib=1
-    local part_num=1
-    
-    # Create partition table
-    create_partition_table "$INSTALL_DISK"
+    local part_num=$FIRST_PART_NUM
     
     # ESP Partition (for UEFI only) - mounted to /efi
     if [ "$BOOT_MODE" = "UEFI" ]; then
-        create_esp_partition "$INSTALL_DISK" "$part_num" "100"
-        current_start_mib=$((current_start_mib + 100))
-        part_num=$((part_num + 1))
+        if using_existing_esp; then
+            # Existing ESP (dual boot) - mounted to /efi by install.sh
+            capture_device_info "efi" "$EFI_PARTITION"
+        else
+            create_esp_partition "$INSTALL_DISK" "$part_num" "100"
+            current_start_mib=$((current_start_mib + 100))
+            part_num=$((part_num + 1))
+        fi
 
         # XBOOTLDR Partition - mounted to /boot
         create_xbootldr_partition "$INSTALL_DISK" "$part_num" "1024"
@@ -47,12 +49,18 @@ execute_simple_partitioning() {
     
     # Swap partition (if requested)
     if [ "$WANT_SWAP" = "yes" ]; then
-        local swap_size_mib=$(get_swap_size_mib)
+        local swap_size_mib=$(get_configured_swap_size_mib)
         create_swap_partition "$INSTALL_DISK" "$part_num" "$swap_size_mib"
         current_start_mib=$((current_start_mib + swap_size_mib))
         part_num=$((part_num + 1))
     fi
     
+    # Separate /var partition (if requested), fixed size before root
+    if want_var_partition; then
+        create_var_partition "$INSTALL_DISK" "$part_num" "$ROOT_FILESYSTEM_TYPE"
+        part_num=$((part_num + 1))
+    fi
+
     # Root partition
     create_root_partition "$INSTALL_DISK" "$part_num" "$ROOT_FILESYSTEM_TYPE"
     
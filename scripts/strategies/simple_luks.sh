@@ -14,45 +14,75 @@ execute_simple_luks_partitioning() {
     # Validate requirements
     validate_partitioning_requirements
 
-    # Wipe disk with explicit confirmation
-    wipe_disk "$INSTALL_DISK" "CONFIRMED"
+    # Wipe disk and create partition table, unless it holds a reused ESP
+    prepare_install_disk "$INSTALL_DISK"
 
     local current_start_mib=1
-    local part_num=1
-    
-    # Create partition table
-    create_partition_table "$INSTALL_DISK"
+    local part_num=$FIRST_PART_NUM
     
     # ESP Partition (for UEFI only) - mounted to /efi
     if [ "$BOOT_MODE" = "UEFI" ]; then
-        create_esp_partition "$INSTALL_DISK" "$part_num" "100"
-        current_start_mib=$((current_start_mib + 100))
-        part_num=$((part_num + 1))
+        if using_existing_esp; then
+            # Existing ESP (dual boot) - mounted to /efi by install.sh
+            capture_device_info "efi" "$EFI_PARTITION"
+        else
+            create_esp_partition "$INSTALL_DISK" "$part_num" "100"
+            current_start_mib=$((current_start_mib + 100))
+            part_num=$((part_num + 1))
+        fi
 
-        # XBOOTLDR Partition - mounted to /boot
-        create_xbootldr_partition "$INSTALL_DISK" "$part_num" "1024"
-        current_start_mib=$((current_start_mib + 1024))
-        part_num=$((part_num + 1))
+        # XBOOTLDR Partition - mounted to /boot (encrypted /boot stays in root)
+        if [ "${ENCRYPTED_BOOT:-No}" != "Yes" ]; then
+            create_xbootldr_partition "$INSTALL_DISK" "$part_num" "1024"
+            current_start_mib=$((current_start_mib + 1024))
+            part_num=$((part_num + 1))
+        fi
     else
         # BIOS with GPT: Need BIOS boot partition for GRUB
         create_bios_boot_partition "$INSTALL_DISK" "$part_num"
         current_start_mib=$((current_start_mib + BIOS_BOOT_PART_SIZE_MIB))
         part_num=$((part_num + 1))
 
-        # Boot partition - mounted to /boot
-        create_boot_partition "$INSTALL_DISK" "$part_num" "1024"
-        current_start_mib=$((current_start_mib + 1024))
-        part_num=$((part_num + 1))
+        # Boot partition - mounted to /boot (encrypted /boot stays in root)
+        if [ "${ENCRYPTED_BOOT:-No}" != "Yes" ]; then
+            create_boot_partition "$INSTALL_DISK" "$part_num" "1024"
+            current_start_mib=$((current_start_mib + 1024))
+            part_num=$((part_num + 1))
+        fi
     fi
     
     # Swap partition (if requested)
     if [ "$WANT_SWAP" = "yes" ]; then
-        local swap_size_mib=$(get_swap_size_mib)
+        local swap_size_mib=$(get_configured_swap_size_mib)
         create_swap_partition "$INSTALL_DISK" "$part_num" "$swap_size_mib"
         current_start_mib=$((current_start_mib + swap_size_mib))
         part_num=$((part_num + 1))
     fi
     
+    # Separate LUKS /var partition (if requested), fixed size before root
+    local luks_var_dev=""
+    if want_var_partition; then
+        local var_size
+        var_size=$(var_partition_size_spec) || error_exit "Invalid /var partition size"
+        log_info "Creating LUKS /var partition ($var_size)..."
+
+        sgdisk -n "${part_num}:0:+${var_size}" \
+               -t "${part_num}:${LUKS_PARTITION_TYPE}" \
+               -c "${part_num}:LUKS_VAR" \
+               "$INSTALL_DISK" || error_exit "Failed to create LUKS /var partition"
+
+        sync_partitions "$INSTALL_DISK"
+        luks_var_dev=$(get_partition_path "$INSTALL_DISK" "$part_num")
+        if [[ ! -b "$luks_var_dev" ]]; then
+            error_exit "LUKS /var partition $luks_var_dev not found after creation"
+        fi
+
+        setup_luks_encryption "$luks_var_dev" "$ENCRYPTION_PASSWORD" "cryptvar"
+        format_filesystem "/dev/mapper/cryptvar" "$ROOT_FILESYSTEM_TYPE"
+        capture_device_info "var" "/dev/mapper/cryptvar"
+        part_num=$((part_num + 1))
+    fi
+
     # LUKS partition (for root and optionally home)
     log_info "Creating LUKS partition..."
 
@@ -89,11 +119,21 @@ execute_simple_luks_partitioning() {
         if [ "$WANT_HOME_PARTITION" != "yes" ]; then
             include_home="yes"
         fi
-        setup_btrfs_subvolumes "/dev/mapper/cryptroot" "$include_home"
+        # A separate /var partition replaces the @var subvolume
+        local include_var="yes"
+        if [ -n "$luks_var_dev" ]; then
+            include_var="no"
+        fi
+        setup_btrfs_subvolumes "/dev/mapper/cryptroot" "$include_home" "$include_var"
     else
         safe_mount "/dev/mapper/cryptroot" "/mnt"
     fi
 
+    # Separate /var partition, mounted once root is
+    if [ -n "$luks_var_dev" ] && mountpoint -q /mnt; then
+        safe_mount "/dev/mapper/cryptvar" "/mnt/var"
+    fi
+
     # Separate home partition (if requested)
     if [ "$WANT_HOME_PARTITION" = "yes" ]; then
         part_num=$((part_num + 1))
@@ -130,10 +170,18 @@ execute_simple_luks_partitioning() {
         safe_mount "/dev/mapper/crypthome" "/mnt/home"
     fi
     
+    # GRUB asks for the passphrase; the initramfs reuses a key file
+    if [ "${ENCRYPTED_BOOT:-No}" = "Yes" ]; then
+        add_luks_keyfile "$luks_dev" "$ENCRYPTION_PASSWORD"
+    fi
+
     # Generate crypttab entries for boot-time unlocking
     log_info "Generating crypttab entries..."
     mkdir -p /mnt/etc
     generate_crypttab "$luks_dev" "cryptroot"
+    if [ -n "$luks_var_dev" ]; then
+        generate_crypttab "$luks_var_dev" "cryptvar"
+    fi
     if [ "$WANT_HOME_PARTITION" = "yes" ]; then
         generate_crypttab "$luks_home_dev" "crypthome"
     fi
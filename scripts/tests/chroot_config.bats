@@ -175,28 +175,283 @@ teardown() {
     grep -q 'hooks=.*encrypt' "$SCRIPTS_DIR/chroot_config.sh"
 }
 
+@test "configure_mkinitcpio adds the resume hook only for hibernation" {
+    local body
+    body="$(sed -n '/^configure_mkinitcpio()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'"${HIBERNATION:-No}" == "Yes"'*'hooks="$hooks resume"'* ]]
+    # Swap alone no longer adds it
+    [[ "$body" != *'WANT_SWAP'*'hooks="$hooks resume"'* ]]
+}
+
+@test "resume_parameters names the swap partition or swap file in fstab" {
+    eval "$(sed -n '/^swap_file_offset()/,/^}/p;/^resume_parameters()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    local fstab="$BATS_TEST_TMPDIR/fstab"
+
+    printf '%s\n' "# /dev/sda2" "UUID=1111-root / ext4 rw 0 1" "UUID=2222-swap none swap defaults 0 0" > "$fstab"
+    [ "$(resume_parameters "$fstab")" = "resume=UUID=2222-swap" ]
+
+    findmnt() { case "$*" in *FSTYPE*) echo ext4 ;; *) echo 1111-root ;; esac; }
+    filefrag() { printf '%s\n' "Filesystem type is: ef53" " ext:     logical_offset:        physical_offset: length:   expected: flags:" "   0:        0..    2047:      34816..     36863:   2048:" ; }
+    printf '%s\n' "UUID=1111-root / ext4 rw 0 1" "/swapfile none swap defaults 0 0" > "$fstab"
+    [ "$(resume_parameters "$fstab")" = "resume=UUID=1111-root resume_offset=34816" ]
+
+    echo "UUID=1111-root / ext4 rw 0 1" > "$fstab"
+    run resume_parameters "$fstab"
+    [ "$status" -ne 0 ]
+}
+
+@test "both bootloaders get the resume parameters" {
+    local grub systemd_boot
+    grub="$(sed -n '/^configure_grub_settings()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    systemd_boot="$(sed -n '/^install_systemd_boot()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$grub" == *'resume=$(hibernation_cmdline)'* ]]
+    [[ "$systemd_boot" == *'resume=$(hibernation_cmdline)'*'${resume:+ $resume}'* ]]
+}
+
+@test "systemd-boot loads the microcode image before the initramfs" {
+    local systemd_boot
+    systemd_boot="$(sed -n '/^install_systemd_boot()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$systemd_boot" == *'microcode=$(microcode_initrd)'*'${microcode:+$microcode'*'initrd  /$(initramfs_image'* ]]
+}
+
+@test "configure_mkinitcpio embeds the key file for an encrypted /boot" {
+    grep -q 'FILES=(/crypto_keyfile.bin)' "$SCRIPTS_DIR/chroot_config.sh"
+}
+
+@test "configure_mkinitcpio uses INITRAMFS_HOOKS unless it is auto" {
+    local body
+    body="$(sed -n '/^configure_mkinitcpio()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'"$INITRAMFS_HOOKS" != "auto"'*'hooks="$INITRAMFS_HOOKS"'*'else'*'hooks="base udev'* ]]
+}
+
+@test "the initramfs is regenerated after the GPU drivers and modules" {
+    local body
+    body="$(sed -n '/^main()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"install_gpu_drivers"*"configure_initramfs_modules"*"regenerate_initramfs"* ]]
+}
+
+@test "early_kms_modules picks the modules of the GPU driver" {
+    eval "$(sed -n '/^early_kms_modules()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [ "$(GPU_DRIVERS=AMD early_kms_modules)" = "amdgpu" ]
+    [ "$(GPU_DRIVERS=Intel early_kms_modules)" = "i915" ]
+    [ "$(GPU_DRIVERS=NVIDIA early_kms_modules)" = "nvidia nvidia_modeset nvidia_uvm nvidia_drm" ]
+    [ -z "$(GPU_DRIVERS=none early_kms_modules)" ]
+}
+
+@test "configure_initramfs_modules drops the kms hook for NVIDIA" {
+    local body
+    body="$(sed -n '/^configure_initramfs_modules()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'*" nvidia "*'*"/^HOOKS=/s/ kms / /"* ]]
+    [[ "$body" == *'MODULES=(${current[*]})'* ]]
+}
+
+@test "nvidia_packages picks the module package of the driver and kernel" {
+    eval "$(sed -n '/^nvidia_module_package()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    eval "$(sed -n '/^nvidia_packages()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [ "$(KERNEL=linux nvidia_packages)" = "nvidia-open nvidia-utils nvidia-settings" ]
+    [ "$(KERNEL=linux-lts NVIDIA_DRIVER=proprietary nvidia_packages)" = "nvidia-lts nvidia-utils nvidia-settings" ]
+    [ "$(KERNEL=linux-zen nvidia_packages)" = "nvidia-open-dkms linux-zen-headers nvidia-utils nvidia-settings" ]
+    [ "$(GPU_DRIVERS=nvidia-open NVIDIA_DRIVER=proprietary nvidia_module_package)" = "nvidia-open" ]
+}
+
+@test "nvidia_cmdline sets DRM modeset only with the NVIDIA extras" {
+    eval "$(sed -n '/^nvidia_selected()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    eval "$(sed -n '/^nvidia_extras()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    eval "$(sed -n '/^nvidia_cmdline()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [ "$(GPU_DRIVERS=NVIDIA nvidia_cmdline)" = "nvidia_drm.modeset=1" ]
+    [ -z "$(GPU_DRIVERS=NVIDIA NVIDIA_EXTRAS=No nvidia_cmdline)" ]
+    [ -z "$(GPU_DRIVERS=AMD nvidia_cmdline)" ]
+}
+
+@test "both bootloaders pass the NVIDIA kernel parameter" {
+    local grub systemd_boot
+    grub="$(sed -n '/^configure_grub_settings()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    systemd_boot="$(sed -n '/^install_systemd_boot()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$grub" == *'nvidia=$(nvidia_cmdline)'* ]]
+    [[ "$systemd_boot" == *'nvidia=$(nvidia_cmdline)'*'${nvidia:+ $nvidia}'* ]]
+}
+
+@test "configure_nvidia_hook rebuilds the initramfs on driver and kernel updates" {
+    local body
+    body="$(sed -n '/^configure_nvidia_hook()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"nvidia_extras"*"/etc/pacman.d/hooks/nvidia.hook"* ]]
+    [[ "$body" == *'Target=$(nvidia_module_package)'*'Target=${KERNEL:-linux}'*"mkinitcpio -P"* ]]
+    grep -A1 '^    configure_initramfs_modules$' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'configure_nvidia_hook'
+}
+
+@test "install_grub enables cryptodisk before grub-install for an encrypted /boot" {
+    local body
+    body="$(sed -n '/^install_grub()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"enable_grub_cryptodisk"*"grub-install"* ]]
+}
+
+@test "install_grub checks each disk for a BIOS boot partition before grub-install" {
+    local body
+    body="$(sed -n '/^install_grub()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"check_bios_boot_disk"*"grub-install --target=i386-pc"* ]]
+}
+
+@test "check_bios_boot_disk looks for the BIOS boot partition type on GPT" {
+    grep -q '21686148-6449-6e6f-744e-656564454649' "$SCRIPTS_DIR/chroot_config.sh"
+}
+
+@test "install_systemd_boot keeps the fallback loader of a shared ESP" {
+    local body
+    body="$(sed -n '/^install_systemd_boot()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'cp "$fallback" "$fallback_backup"'*"bootctl install"*'cp "$fallback_backup" "$fallback"'* ]]
+}
+
+@test "install_grub points out Windows on a shared ESP without OS Prober" {
+    local body
+    body="$(sed -n '/^install_grub()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"EFI/Microsoft"*"enable OS Prober"* ]]
+}
+
+@test "configure_grub_settings passes cryptkey for an encrypted /boot" {
+    grep -q 'cryptkey=rootfs:/crypto_keyfile.bin' "$SCRIPTS_DIR/chroot_config.sh"
+}
+
 # =============================================================================
 # Desktop Environment Tests
 # =============================================================================
 
-@test "chroot_config.sh handles gnome desktop" {
-    grep -q 'gnome' "$SCRIPTS_DIR/chroot_config.sh"
+@test "every desktop in the catalogue has a session and packages" {
+    local desktops="$SCRIPTS_DIR/desktops/desktops.json"
+    for id in gnome kde xfce cinnamon mate hyprland sway i3; do
+        command jq -e --arg id "$id" \
+            '.desktops[] | select(.id == $id) | (.packages | length > 0) and (.session != null)' \
+            "$desktops" >/dev/null
+    done
+}
+
+@test "install_desktop_environment installs the catalogue packages" {
+    grep -q 'install_packages "$de" $DESKTOP_PACKAGES' "$SCRIPTS_DIR/chroot_config.sh"
+    grep -q 'export DESKTOP_PACKAGES=' "$SCRIPTS_DIR/install.sh"
+}
+
+# =============================================================================
+# VM Guest Tools Tests
+# =============================================================================
+
+@test "install_vm_guest_tools checks VM_GUEST_TOOLS environment variable" {
+    grep -q 'VM_GUEST_TOOLS' "$SCRIPTS_DIR/chroot_config.sh"
+}
+
+@test "install_vm_guest_tools installs the packages and services the binary exports" {
+    local body
+    body="$(sed -n '/^install_vm_guest_tools()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'pacman -S --noconfirm --needed $GUEST_TOOLS_PACKAGES'* ]]
+    [[ "$body" == *'for service in ${GUEST_TOOLS_SERVICES:-}'* ]]
+    [[ "$body" != *"open-vm-tools"* ]]
+    grep -q 'export GUEST_TOOLS_PACKAGES=' "$SCRIPTS_DIR/install.sh"
+    grep -q 'export GUEST_TOOLS_SERVICES=' "$SCRIPTS_DIR/install.sh"
+}
+
+@test "install_vm_guest_tools runs after GPU drivers" {
+    grep -A1 '^    install_gpu_drivers$' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'install_vm_guest_tools'
+}
+
+# =============================================================================
+# Audio Tests
+# =============================================================================
+
+@test "install_audio checks AUDIO environment variable" {
+    grep -q 'AUDIO:-pipewire' "$SCRIPTS_DIR/chroot_config.sh"
+}
+
+@test "install_audio installs the packages the binary exports" {
+    local body
+    body="$(sed -n '/^install_audio()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'for package in ${AUDIO_REPLACES:-}'*'pacman -S --noconfirm --needed $AUDIO_PACKAGES'* ]]
+    [[ "$body" != *"wireplumber"* ]]
+    grep -q 'export AUDIO_PACKAGES=' "$SCRIPTS_DIR/install.sh"
+}
+
+@test "install_audio enables sound server user units globally" {
+    local body
+    body="$(sed -n '/^install_audio()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'for service in ${AUDIO_USER_SERVICES:-}'*'systemctl --global enable'* ]]
+}
+
+@test "install_audio runs after the desktop environment" {
+    grep -A1 '^    install_vm_guest_tools$' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'install_audio'
+}
+
+# =============================================================================
+# Service Tests
+# =============================================================================
+
+@test "enable_base_services enables the units listed in SERVICES" {
+    local body
+    body="$(sed -n '/^enable_base_services()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'for unit in $services'*'systemctl enable "$unit"'* ]]
+    [[ "$body" == *'"none"'* ]]
+}
+
+@test "enable_base_services installs the packages the binary exports" {
+    local body
+    body="$(sed -n '/^enable_base_services()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'pacman -S --noconfirm --needed $SERVICE_PACKAGES'* ]]
+    [[ "$body" != *"bluez"* ]]
+    grep -q 'export SERVICE_PACKAGES=' "$SCRIPTS_DIR/install.sh"
+}
+
+@test "enable_base_services enables chronyd in place of systemd-timesyncd" {
+    local body
+    body="$(sed -n '/^enable_base_services()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'systemd-timesyncd.service/chronyd.service'* ]]
+}
+
+@test "configure_time_sync writes the NTP servers for either daemon" {
+    local body
+    body="$(sed -n '/^configure_time_sync()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *"/etc/systemd/timesyncd.conf.d/servers.conf"* ]]
+    [[ "$body" == *'server $server iburst'*"/etc/chrony.conf"* ]]
+}
+
+@test "configure_time_sync runs after the services are enabled" {
+    grep -A1 '^    enable_base_services$' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'configure_time_sync'
+}
+
+@test "configure_ssh_keys installs the keys with the permissions sshd requires" {
+    local body
+    body="$(sed -n '/^configure_ssh_keys()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'>> "$ssh_dir/authorized_keys"'* ]]
+    [[ "$body" == *'chmod 700 "$ssh_dir"'* ]]
+    [[ "$body" == *'chmod 600 "$ssh_dir/authorized_keys"'* ]]
+    [[ "$body" == *'chown -R "$MAIN_USERNAME:" "$ssh_dir"'* ]]
+}
+
+@test "configure_ssh_keys runs once the user exists" {
+    grep -A1 '^    configure_sudoers$' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'configure_ssh_keys'
 }
 
-@test "chroot_config.sh handles kde desktop" {
-    grep -q 'kde\|plasma' "$SCRIPTS_DIR/chroot_config.sh"
+@test "configure_proxy keeps the proxy for the installed system and sudo" {
+    local body
+    body="$(sed -n '/^configure_proxy()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'>> /etc/environment'* ]]
+    [[ "$body" == *'env_keep += "http_proxy https_proxy no_proxy'* ]]
+    [[ "$body" == *'export https_proxy="${HTTPS_PROXY:-${HTTP_PROXY:-}}"'* ]]
 }
 
-@test "chroot_config.sh handles hyprland desktop" {
-    grep -q 'hyprland' "$SCRIPTS_DIR/chroot_config.sh"
+@test "configure_proxy runs before anything is downloaded in the chroot" {
+    grep -A2 'Phase 1: Basic System Configuration' "$SCRIPTS_DIR/chroot_config.sh" | grep -q 'configure_proxy'
 }
 
-@test "chroot_config.sh handles i3 desktop" {
-    grep -q 'i3' "$SCRIPTS_DIR/chroot_config.sh"
+@test "configure_power_management installs the power manager and writes the lid action" {
+    local body
+    body="$(sed -n '/^configure_power_management()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'POWER_MANAGEMENT:-none'* ]]
+    [[ "$body" == *"pacman -Rdd --noconfirm power-profiles-daemon"* ]]
+    [[ "$body" == *"systemctl mask systemd-rfkill.service systemd-rfkill.socket"* ]]
+    [[ "$body" == *"/etc/systemd/logind.conf.d/lid.conf"* ]]
+    [[ "$body" == *'HandleLidSwitch=$lid'* ]]
 }
 
-@test "chroot_config.sh handles xfce desktop" {
-    grep -q 'xfce' "$SCRIPTS_DIR/chroot_config.sh"
+@test "configure_localization keeps the hardware clock in local time when asked" {
+    local body
+    body="$(sed -n '/^configure_localization()/,/^}/p' "$SCRIPTS_DIR/chroot_config.sh")"
+    [[ "$body" == *'"localtime"'*"hwclock --systohc --localtime"*"hwclock --systohc --utc"* ]]
 }
 
 # =============================================================================
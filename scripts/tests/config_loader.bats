@@ -118,6 +118,119 @@ teardown() {
         # Check default values are set
         [ "$LOCALE" = "en_US.UTF-8" ]
         [ "$KEYMAP" = "us" ]
+        [ "$AUDIO" = "pipewire" ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json defaults pacman tuning when omitted" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ "$PARALLEL_DOWNLOADS" = "5" ]
+        [ "$PACMAN_COLOR" = "Yes" ]
+        [ "$VERBOSE_PKG_LISTS" = "No" ]
+        [ -z "$CUSTOM_REPOSITORIES" ]
+        [ -z "$CUSTOM_PHASES" ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json flattens custom_repositories" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        jq '. + {parallel_downloads: 10, custom_repositories: [
+                {name: "mine", server: "https://repo.example/$arch", key_id: "ABCD1234"},
+                {name: "local", server: "file:///srv/repo"},
+                {name: "corp", server: "https://corp.example", sig_level: "Required DatabaseOptional"}]}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/repos.json"
+        load_config_from_json "$TEST_TMP_DIR/repos.json" 2>/dev/null || true
+        [ "$PARALLEL_DOWNLOADS" = "10" ]
+        [ "$CUSTOM_REPOSITORIES" = 'mine|https://repo.example/$arch|ABCD1234 local|file:///srv/repo corp|https://corp.example||Required,DatabaseOptional' ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json joins custom_phases" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        jq '. + {custom_phases: ["ca-certificates", "dotfiles"]}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/phases.json"
+        load_config_from_json "$TEST_TMP_DIR/phases.json" 2>/dev/null || true
+        [ "$CUSTOM_PHASES" = "ca-certificates dotfiles" ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json expands package_groups" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        jq '. + {additional_packages: "git vim", package_groups: ["office", "development"]}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/groups.json"
+        load_config_from_json "$TEST_TMP_DIR/groups.json" 2>/dev/null || true
+        [ "$PACKAGE_GROUPS" = "office development" ]
+        [[ "$ADDITIONAL_PACKAGES" == "git vim "* ]]
+        [[ "$ADDITIONAL_PACKAGES" == *"libreoffice-fresh"* ]]
+        [[ "$ADDITIONAL_PACKAGES" == *"base-devel"* ]]
+        [ "$(tr ' ' '\n' <<< "$ADDITIONAL_PACKAGES" | grep -cx git)" -eq 1 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json looks up the desktop packages" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        jq '. + {desktop_environment: "Xfce"}' "$TEST_CONFIG" > "$TEST_TMP_DIR/xfce.json"
+        load_config_from_json "$TEST_TMP_DIR/xfce.json" 2>/dev/null || true
+        [ "$DESKTOP_ENVIRONMENT" = "Xfce" ]
+        [[ "$DESKTOP_PACKAGES" == *"xfce4-goodies"* ]]
+
+        jq '. + {desktop_environment: "Unity"}' "$TEST_CONFIG" > "$TEST_TMP_DIR/unity.json"
+        run load_config_from_json "$TEST_TMP_DIR/unity.json"
+        [[ "$output" == *"Unknown desktop environment 'Unity'"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json accepts the older desktop names" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        jq '. + {desktop_environment: "plasma"}' "$TEST_CONFIG" > "$TEST_TMP_DIR/plasma.json"
+        load_config_from_json "$TEST_TMP_DIR/plasma.json" 2>/dev/null || true
+        [ "$DESKTOP_ENVIRONMENT" = "kde" ]
+        [[ "$DESKTOP_PACKAGES" == *"kde-applications"* ]]
+
+        jq '. + {desktop_environment: "i3wm"}' "$TEST_CONFIG" > "$TEST_TMP_DIR/i3wm.json"
+        load_config_from_json "$TEST_TMP_DIR/i3wm.json" 2>/dev/null || true
+        [ "$DESKTOP_ENVIRONMENT" = "i3" ]
+
+        jq '. + {desktop_environment: "budgie"}' "$TEST_CONFIG" > "$TEST_TMP_DIR/budgie.json"
+        load_config_from_json "$TEST_TMP_DIR/budgie.json" 2>/dev/null || true
+        [[ "$DESKTOP_PACKAGES" == *"budgie-desktop"* ]]
     else
         skip "jq not installed"
     fi
@@ -254,6 +367,33 @@ teardown() {
     fi
 }
 
+@test "validate_configuration enforces the minimum password length" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_simple"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="long-enough"
+        export ENCRYPTION="no"
+        export PASSWORD_MIN_LENGTH="8"
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"User password must be at least 8 characters"* ]]
+
+        export USER_PASSWORD="also-long-enough"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
 @test "validate_configuration fails when encryption enabled without password" {
     if command -v jq >/dev/null 2>&1; then
         set +euo pipefail
@@ -276,6 +416,386 @@ teardown() {
     fi
 }
 
+@test "validate_configuration rejects encrypted /boot with systemd-boot" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_luks_lvm"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export ENCRYPTED_BOOT="Yes"
+        export BOOTLOADER="systemd-boot"
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"requires GRUB"* ]]
+
+        export BOOTLOADER="grub"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration rejects BIOS boot with systemd-boot" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_lvm"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export BOOT_MODE="BIOS"
+        export BOOTLOADER="systemd-boot"
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"BIOS boot requires GRUB"* ]]
+
+        export BOOTLOADER="grub"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration rejects reusing an ESP with RAID or BIOS" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_raid"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export BOOT_MODE="UEFI"
+        export EFI_PARTITION="/dev/nvme0n1p1"
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"not supported with auto_raid"* ]]
+
+        export PARTITIONING_STRATEGY="auto_simple"
+        export BOOT_MODE="BIOS"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"needs UEFI boot mode"* ]]
+
+        export BOOT_MODE="UEFI"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration rejects installing alongside with RAID" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_raid_lvm"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export BOOT_MODE="UEFI"
+        export EXISTING_OS="alongside"
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"alongside an existing OS is not supported with auto_raid_lvm"* ]]
+
+        export PARTITIONING_STRATEGY="auto_lvm"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+
+        export EXISTING_OS="keep"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"protect, alongside or erase"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration needs partition mounts for existing partitions" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="existing"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export PARTITION_MOUNTS=""
+
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"needs partition_mounts"* ]]
+
+        export PARTITION_MOUNTS="/dev/sda2=/,/dev/sda3=/home"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"must format a partition for /"* ]]
+
+        export PARTITION_MOUNTS="/dev/sda3=/home,/dev/sda2=/:btrfs,/dev/sda1=/efi"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration checks the LVM layout" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_lvm"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export BOOT_MODE="UEFI"
+        export LVM_VOLUME_GROUP="vg0"
+        export LVM_ROOT_SIZE="40G"
+        export LVM_VAR_SIZE="20%FREE"
+
+        run validate_configuration
+        [ "$status" -eq 0 ]
+
+        export LVM_VOLUME_GROUP="-vg"
+        export LVM_ROOT_SIZE="big"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"LVM volume group '-vg'"* ]]
+        [[ "$output" == *"LVM root size must look like 50G or 100%FREE, not 'big'"* ]]
+
+        # The grammar of lvm::parse_size: K to T units, one extent, 1-100 %FREE
+        export LVM_VOLUME_GROUP="vg0"
+        export LVM_ROOT_SIZE="4096KiB"
+        export LVM_VAR_SIZE="None"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+        export LVM_ROOT_SIZE="512K"
+        export LVM_HOME_SIZE="0%FREE"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"LVM root size '512K' is smaller than one 4 MiB extent"* ]]
+        [[ "$output" == *"LVM home size must look like 50G or 100%FREE, not '0%FREE'"* ]]
+
+        # Other strategies keep their own layout
+        export PARTITIONING_STRATEGY="auto_raid_lvm"
+        run validate_configuration
+        [[ "$output" != *"LVM root size"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "validate_configuration checks the /var partition and /tmp policy" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export INSTALL_DISK="/dev/sda"
+        export PARTITIONING_STRATEGY="auto_simple"
+        export SYSTEM_HOSTNAME="test"
+        export MAIN_USERNAME="user"
+        export USER_PASSWORD="pass"
+        export ROOT_PASSWORD="root"
+        export ENCRYPTION="no"
+        export BOOT_MODE="UEFI"
+        export VAR_PARTITION_SIZE="20G"
+        export TMP_POLICY="tmpfs"
+        export TMP_SIZE="4G"
+
+        run validate_configuration
+        [ "$status" -eq 0 ]
+
+        export VAR_PARTITION_SIZE="20%FREE"
+        export TMP_SIZE="lots"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"/var partition size must look like 20G or be none, not '20%FREE'"* ]]
+        [[ "$output" == *"/tmp size must look like 50% or 4G, not 'lots'"* ]]
+
+        # Like the binary, /var takes K to T units and at least 1G
+        export TMP_SIZE="4G"
+        export VAR_PARTITION_SIZE="2097152K"
+        run validate_configuration
+        [ "$status" -eq 0 ]
+        export VAR_PARTITION_SIZE="512MiB"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"The /var partition needs at least 1G, not '512MiB'"* ]]
+
+        # The size only matters for a tmpfs, the partition for the simple strategies
+        export TMP_POLICY="disk"
+        export PARTITIONING_STRATEGY="auto_lvm"
+        run validate_configuration
+        [[ "$output" != *"/var partition size"* ]]
+        [[ "$output" != *"/tmp size"* ]]
+
+        export TMP_POLICY="ramdisk"
+        run validate_configuration
+        [ "$status" -ne 0 ]
+        [[ "$output" == *"/tmp policy must be tmpfs or disk"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json loads the time settings" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ "$TIME_SYNC_DAEMON" = "systemd-timesyncd" ]
+        [ -z "$NTP_SERVERS" ]
+        [ "$HARDWARE_CLOCK" = "UTC" ]
+
+        # The jq mock only answers -r queries
+        command jq '. + {time_sync_daemon: "chrony", ntp_servers: ["ntp.example.com", "10.0.0.1"], hardware_clock: "localtime"}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/time.json"
+        load_config_from_json "$TEST_TMP_DIR/time.json" 2>/dev/null || true
+        [ "$TIME_SYNC_DAEMON" = "chrony" ]
+        [ "$NTP_SERVERS" = "ntp.example.com 10.0.0.1" ]
+        [ "$HARDWARE_CLOCK" = "localtime" ]
+
+        export HARDWARE_CLOCK="local"
+        run validate_configuration
+        [[ "$output" == *"Hardware clock must be UTC or localtime, not 'local'"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json loads the package cache over --pkg-cache" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export PKG_CACHE="$TEST_TMP_DIR"
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ "$PKG_CACHE" = "$TEST_TMP_DIR" ]
+
+        # The jq mock only answers -r queries
+        mkdir -p "$TEST_TMP_DIR/packages"
+        command jq --arg dir "$TEST_TMP_DIR/packages" '. + {package_cache: $dir}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/cache.json"
+        load_config_from_json "$TEST_TMP_DIR/cache.json" 2>/dev/null || true
+        [ "$PKG_CACHE" = "$TEST_TMP_DIR/packages" ]
+
+        export PKG_CACHE="/nonexistent/packages"
+        run validate_configuration
+        [[ "$output" == *"Package cache '/nonexistent/packages' is not a directory"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json loads the SSH key sources" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ -z "$SSH_KEYS" ]
+
+        # The jq mock only answers -r queries
+        command jq '. + {ssh_keys: ["github:alice", "/run/media/usb/id_ed25519.pub"]}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/ssh.json"
+        load_config_from_json "$TEST_TMP_DIR/ssh.json" 2>/dev/null || true
+        [ "$SSH_KEYS" = "github:alice /run/media/usb/id_ed25519.pub" ]
+
+        export SSH_KEYS="github:alice id_rsa.pub"
+        run validate_configuration
+        [[ "$output" == *"SSH key source 'id_rsa.pub' must be github:USER"* ]]
+        [[ "$output" != *"'github:alice'"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json loads the proxy over the environment's" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        export HTTP_PROXY="http://env-proxy:8080"
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ "$HTTP_PROXY" = "http://env-proxy:8080" ]
+
+        # The jq mock only answers -r queries
+        command jq '. + {http_proxy: "http://proxy.example.com:3128", no_proxy: "localhost,.lan"}' \
+            "$TEST_CONFIG" > "$TEST_TMP_DIR/proxy.json"
+        load_config_from_json "$TEST_TMP_DIR/proxy.json" 2>/dev/null || true
+        [ "$HTTP_PROXY" = "http://proxy.example.com:3128" ]
+        [ "$NO_PROXY" = "localhost,.lan" ]
+
+        export HTTPS_PROXY="proxy.example.com:3128" NO_PROXY="localhost .lan"
+        run validate_configuration
+        [[ "$output" == *"Proxy 'proxy.example.com:3128' must look like http://"* ]]
+        [[ "$output" == *"No-proxy entries are separated by commas"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
+@test "load_config_from_json loads the power settings" {
+    if command -v jq >/dev/null 2>&1; then
+        set +euo pipefail
+        source "$SCRIPTS_DIR/config_loader.sh" 2>/dev/null || true
+        set -euo pipefail
+
+        load_config_from_json "$TEST_CONFIG" 2>/dev/null || true
+        [ "$POWER_MANAGEMENT" = "none" ]
+        [ "$LID_SWITCH" = "suspend" ]
+
+        export DESKTOP_ENVIRONMENT="gnome" POWER_MANAGEMENT="tlp" LID_SWITCH="sleep"
+        run validate_configuration
+        [[ "$output" == *"TLP conflicts with power-profiles-daemon"* ]]
+        [[ "$output" == *"Lid close action must be suspend"*"not 'sleep'"* ]]
+    else
+        skip "jq not installed"
+    fi
+}
+
 @test "validate_configuration succeeds with valid complete config" {
     if command -v jq >/dev/null 2>&1; then
         set +euo pipefail
@@ -0,0 +1,65 @@
+#!/usr/bin/env bats
+# configure_network.bats - Tests for the persistent configuration configure_network.sh writes
+
+load 'test_helper'
+
+setup() {
+    setup_test_environment
+    source "$SCRIPTS_DIR/utils.sh"
+    # The tool runs on load, so take only its configuration writers
+    local fn
+    for fn in netmask_to_prefix write_networkd_config write_networkmanager_config; do
+        eval "$(sed -n "/^$fn()/,/^}/p" "$SCRIPTS_DIR/tools/configure_network.sh")"
+    done
+    CONFIG_ROOT="$TEST_TMP_DIR/root"
+}
+
+teardown() {
+    teardown_test_environment
+}
+
+@test "netmask_to_prefix converts dotted masks" {
+    [ "$(netmask_to_prefix 255.255.255.0)" = "24" ]
+    [ "$(netmask_to_prefix 255.255.240.0)" = "20" ]
+    [ "$(netmask_to_prefix 16)" = "16" ]
+}
+
+@test "networkd unit holds both static address families" {
+    write_networkd_config enp1s0 192.168.1.10/24 192.168.1.1 \
+        static 2001:db8::10/64 fe80::1 "192.168.1.1,2001:4860:4860::8888"
+    local unit="$CONFIG_ROOT/etc/systemd/network/20-enp1s0.network"
+    grep -qx "Name=enp1s0" "$unit"
+    grep -qx "DHCP=no" "$unit"
+    grep -qx "Address=192.168.1.10/24" "$unit"
+    grep -qx "Address=2001:db8::10/64" "$unit"
+    grep -qx "Gateway=fe80::1" "$unit"
+    grep -qx "IPv6AcceptRA=no" "$unit"
+    grep -qx "DNS=2001:4860:4860::8888" "$unit"
+}
+
+@test "networkd unit uses DHCP for both families with dhcpv6" {
+    write_networkd_config enp1s0 "" "" dhcpv6 "" "" ""
+    local unit="$CONFIG_ROOT/etc/systemd/network/20-enp1s0.network"
+    grep -qx "DHCP=yes" "$unit"
+    grep -qx "IPv6AcceptRA=yes" "$unit"
+    run grep -q "^Address=" "$unit"
+    [ "$status" -ne 0 ]
+}
+
+@test "NetworkManager keyfile splits DNS by family and is private" {
+    write_networkmanager_config enp1s0 10.0.0.2/24 10.0.0.1 \
+        static 2001:db8::2/64 2001:db8::1 "10.0.0.1,2001:db8::53"
+    local keyfile="$CONFIG_ROOT/etc/NetworkManager/system-connections/enp1s0.nmconnection"
+    grep -qx "address1=10.0.0.2/24,10.0.0.1" "$keyfile"
+    grep -qx "address1=2001:db8::2/64,2001:db8::1" "$keyfile"
+    grep -qx "dns=10.0.0.1;" "$keyfile"
+    grep -qx "dns=2001:db8::53;" "$keyfile"
+    [ "$(stat -c %a "$keyfile")" = "600" ]
+}
+
+@test "NetworkManager keyfile disables IPv6 when it is off" {
+    write_networkmanager_config enp1s0 "" "" off "" "" ""
+    local keyfile="$CONFIG_ROOT/etc/NetworkManager/system-connections/enp1s0.nmconnection"
+    [ "$(sed -n '/^\[ipv4\]/,/^$/p' "$keyfile" | grep '^method=')" = "method=auto" ]
+    [ "$(sed -n '/^\[ipv6\]/,$p' "$keyfile" | grep '^method=')" = "method=disabled" ]
+}
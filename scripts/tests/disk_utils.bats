@@ -41,6 +41,20 @@ teardown() {
     [ "$output" = "/dev/loop0p1" ]
 }
 
+@test "get_partition_path returns correct path for second NVMe namespace" {
+    run get_partition_path "/dev/nvme0n2" "3"
+    [ "$status" -eq 0 ]
+    [ "$output" = "/dev/nvme0n2p3" ]
+}
+
+@test "get_base_device_name strips partition suffixes" {
+    [ "$(get_base_device_name /dev/sda2)" = "sda" ]
+    [ "$(get_base_device_name /dev/nvme1n2p3)" = "nvme1n2" ]
+    [ "$(get_base_device_name /dev/mmcblk0p1)" = "mmcblk0" ]
+    [ "$(get_base_device_name /dev/loop7p2)" = "loop7" ]
+    [ "$(get_base_device_name /dev/mmcblk1)" = "mmcblk1" ]
+}
+
 @test "get_partition_path handles multiple partitions" {
     run get_partition_path "/dev/sda" "3"
     [ "$status" -eq 0 ]
@@ -103,6 +117,40 @@ teardown() {
     [ "$output" = "2048" ]  # DEFAULT_SWAP_SIZE_MIB
 }
 
+@test "get_configured_swap_size_mib honours SWAP_SIZE" {
+    export MEMINFO_FILE="$BATS_TEST_TMPDIR/meminfo"
+    echo "MemTotal:        8388608 kB" > "$MEMINFO_FILE"
+    [ "$(SWAP_SIZE=4GB get_configured_swap_size_mib)" = "4096" ]
+    [ "$(SWAP_SIZE=512MB get_configured_swap_size_mib)" = "512" ]
+    [ "$(SWAP_SIZE='Equal to RAM' get_configured_swap_size_mib)" = "8192" ]
+    [ "$(SWAP_SIZE='Double RAM' get_configured_swap_size_mib)" = "16384" ]
+    [ "$(SWAP_SIZE=N/A get_configured_swap_size_mib)" = "8192" ]  # 1x rule for 8GB RAM
+}
+
+@test "get_configured_swap_size_mib keeps the 2GB default whatever the RAM" {
+    export MEMINFO_FILE="$BATS_TEST_TMPDIR/meminfo"
+    echo "MemTotal:        8388608 kB" > "$MEMINFO_FILE"
+    [ "$(SWAP_SIZE=2GB get_configured_swap_size_mib)" = "2048" ]
+    echo "MemTotal:        2097152 kB" > "$MEMINFO_FILE"
+    [ "$(SWAP_SIZE=2GB get_configured_swap_size_mib)" = "2048" ]
+}
+
+@test "the RAID strategies put the requested swap in a swap file" {
+    local strategy body
+    for strategy in raid raid_luks; do
+        body="$(cat "$SCRIPTS_DIR/strategies/$strategy.sh")"
+        [[ "$body" == *'"$WANT_SWAP"'*'create_swap_file "$swap_file" "$(get_configured_swap_size_mib)"'* ]]
+        [[ "$body" == *'swap_file="/mnt/@swap/swapfile"'* ]]
+    done
+}
+
+@test "get_configured_swap_size_mib grows the swap to the RAM for hibernation" {
+    export MEMINFO_FILE="$BATS_TEST_TMPDIR/meminfo"
+    echo "MemTotal:        8388608 kB" > "$MEMINFO_FILE"
+    [ "$(SWAP_SIZE=2GB HIBERNATION=Yes get_configured_swap_size_mib 2>/dev/null)" = "8192" ]
+    [ "$(SWAP_SIZE=16GB HIBERNATION=Yes get_configured_swap_size_mib)" = "16384" ]
+}
+
 # =============================================================================
 # Partition Type Constants Tests
 # =============================================================================
@@ -183,6 +231,219 @@ teardown() {
     assert_mock_called_with_pattern "sgdisk.*--zap-all.*/dev/sda"
 }
 
+# =============================================================================
+# Existing ESP Reuse Tests
+# =============================================================================
+
+@test "prepare_install_disk wipes the disk when a new ESP is created" {
+    export EFI_PARTITION="create"
+    prepare_install_disk "/dev/sda"
+    [ "$FIRST_PART_NUM" = "1" ]
+    assert_mock_called_with_pattern "wipefs.*/dev/sda"
+    assert_mock_called_with_pattern "sgdisk.*--zap-all.*/dev/sda"
+}
+
+@test "prepare_install_disk wipes the disk when the reused ESP is elsewhere" {
+    export EFI_PARTITION="/dev/nvme0n1p1"
+    lsblk() { echo "nvme0n1"; }
+    prepare_install_disk "/dev/sda"
+    [ "$FIRST_PART_NUM" = "1" ]
+    assert_mock_called_with_pattern "wipefs.*/dev/sda"
+}
+
+@test "prepare_install_disk keeps the disk holding the reused ESP" {
+    export EFI_PARTITION="/dev/nvme0n1p1"
+    lsblk() { echo "nvme0n1"; }
+    blockdev() { echo "512"; }
+    sgdisk() {
+        log_mock_call "sgdisk" "$@"
+        case "$1" in
+            -p)
+                echo "Number  Start (sector)    End (sector)  Size       Code  Name"
+                echo "   1            2048          206847   100.0 MiB   EF00  EFI system partition"
+                echo "   4       105066496       106168319   538.0 MiB   2700"
+                ;;
+            -F) echo "106168320" ;;
+            -E) echo "1000215182" ;;
+        esac
+    }
+    prepare_install_disk "/dev/nvme0n1"
+    [ "$FIRST_PART_NUM" = "5" ]
+    if grep -q "wipefs" "$MOCK_CALLS_LOG"; then return 1; fi
+    if grep -q "sgdisk.*--zap-all" "$MOCK_CALLS_LOG"; then return 1; fi
+}
+
+@test "prepare_install_disk needs unallocated space on a kept disk" {
+    export EFI_PARTITION="/dev/nvme0n1p1"
+    lsblk() { echo "nvme0n1"; }
+    blockdev() { echo "512"; }
+    sgdisk() {
+        case "$1" in
+            -F) echo "106168320" ;;
+            -E) echo "116654079" ;;
+        esac
+    }
+    run prepare_install_disk "/dev/nvme0n1"
+    [ "$status" -ne 0 ]
+    [[ "$output" == *"5120 MiB unallocated"*"shrink the other system"* ]]
+}
+
+# =============================================================================
+# Existing Operating System Tests
+# =============================================================================
+
+@test "detect_existing_systems lists os-prober entries on the disk" {
+    os-prober() {
+        echo "/dev/nvme0n1p1@/EFI/Microsoft/Boot/bootmgfw.efi:Windows Boot Manager:Windows:efi"
+        echo "/dev/sda2:Ubuntu 24.04 LTS:Ubuntu:linux"
+    }
+    run detect_existing_systems "/dev/nvme0n1"
+    [ "$status" -eq 0 ]
+    [ "$output" = "Windows Boot Manager" ]
+}
+
+@test "detect_existing_systems guesses from filesystems without os-prober" {
+    os-prober() { :; }
+    lsblk() { printf '%s\n' "" vfat ntfs ntfs; }
+    run detect_existing_systems "/dev/sda"
+    [ "$output" = "Windows" ]
+}
+
+@test "check_existing_systems protects a disk holding another system" {
+    export EFI_PARTITION="create"
+    unset EXISTING_OS
+    detect_existing_systems() { echo "Windows Boot Manager"; }
+    run check_existing_systems "/dev/nvme0n1"
+    [ "$status" -ne 0 ]
+    [[ "$output" == *"/dev/nvme0n1 holds Windows Boot Manager"*"EXISTING_OS=alongside"* ]]
+
+    export EXISTING_OS="erase"
+    run check_existing_systems "/dev/nvme0n1"
+    [ "$status" -eq 0 ]
+    [[ "$output" == *"it will be erased"* ]]
+}
+
+@test "check_existing_systems needs GPT to install alongside" {
+    export EXISTING_OS="alongside"
+    detect_existing_systems() { echo "Windows"; }
+    lsblk() { echo "dos"; }
+    run check_existing_systems "/dev/sda"
+    [ "$status" -ne 0 ]
+    [[ "$output" == *"needs a GPT partition table"* ]]
+
+    lsblk() { echo "gpt"; }
+    run check_existing_systems "/dev/sda"
+    [ "$status" -eq 0 ]
+}
+
+@test "prepare_install_disk keeps the disk when installing alongside" {
+    export EFI_PARTITION="create"
+    export EXISTING_OS="alongside"
+    blockdev() { echo "512"; }
+    sgdisk() {
+        log_mock_call "sgdisk" "$@"
+        case "$1" in
+            -p)
+                echo "Number  Start (sector)    End (sector)  Size       Code  Name"
+                echo "   1            2048          206847   100.0 MiB   EF00  EFI system partition"
+                echo "   2          206848       105066495   50.0 GiB    0700  Basic data partition"
+                ;;
+            -F) echo "105066496" ;;
+            -E) echo "1000215182" ;;
+        esac
+    }
+    prepare_install_disk "/dev/sda"
+    [ "$FIRST_PART_NUM" = "3" ]
+    if grep -q "wipefs" "$MOCK_CALLS_LOG"; then return 1; fi
+}
+
+# =============================================================================
+# LVM Volume Tests
+# =============================================================================
+
+@test "lvm_volume_plan creates fixed sizes before shares" {
+    export LVM_ROOT_SIZE="100%FREE" LVM_VAR_SIZE="20G" LVM_HOME_SIZE="200G"
+    export WANT_HOME_PARTITION="yes"
+    run lvm_volume_plan
+    [ "$status" -eq 0 ]
+    [ "$output" = $'var:20G\nhome:200G\nroot:100%FREE' ]
+
+    export WANT_HOME_PARTITION="no" LVM_VAR_SIZE="none"
+    run lvm_volume_plan
+    [ "$output" = "root:100%FREE" ]
+}
+
+@test "setup_lvm_volumes uses the configured volume group and sizes" {
+    export LVM_VOLUME_GROUP="vg0" LVM_ROOT_SIZE="40G" LVM_VAR_SIZE="10G" LVM_HOME_SIZE="100%FREE"
+    export WANT_HOME_PARTITION="yes" LVM_THIN="No"
+    export ROOT_FILESYSTEM_TYPE="ext4" HOME_FILESYSTEM_TYPE="xfs"
+    pvcreate() { log_mock_call "pvcreate" "$@"; }
+    vgcreate() { log_mock_call "vgcreate" "$@"; }
+    lvcreate() { log_mock_call "lvcreate" "$@"; }
+    safe_mount() { log_mock_call "safe_mount" "$@"; }
+    setup_lvm_volumes "/dev/sda3"
+    assert_mock_called "vgcreate" "vg0 /dev/sda3"
+    assert_mock_called "lvcreate" "-L 40G -n root vg0"
+    assert_mock_called "lvcreate" "-L 10G -n var vg0"
+    assert_mock_called "lvcreate" "-l 100%FREE -n home vg0"
+    assert_mock_called "mkfs.xfs" "-f /dev/vg0/home"
+    assert_mock_called "safe_mount" "/dev/vg0/var /mnt/var"
+    [ "$ROOT_DEVICE" = "/dev/vg0/root" ]
+}
+
+@test "setup_lvm_volumes creates thin volumes in a pool" {
+    export LVM_VOLUME_GROUP="arch" LVM_ROOT_SIZE="50G" LVM_VAR_SIZE="none" LVM_HOME_SIZE="50%FREE"
+    export WANT_HOME_PARTITION="yes" LVM_THIN="Yes"
+    export ROOT_FILESYSTEM_TYPE="ext4" HOME_FILESYSTEM_TYPE="ext4"
+    pvcreate() { :; }
+    vgcreate() { :; }
+    lvcreate() { log_mock_call "lvcreate" "$@"; }
+    lvs() { echo "  102400.00"; }
+    safe_mount() { :; }
+    setup_lvm_volumes "/dev/mapper/cryptlvm"
+    assert_mock_called "lvcreate" "--type thin-pool -l 95%FREE -n pool arch"
+    assert_mock_called "lvcreate" "-V 50G --thin -n root arch/pool"
+    assert_mock_called "lvcreate" "-V 51200M --thin -n home arch/pool"
+}
+
+@test "mount_plan_entries mounts the root first and swap last" {
+    run mount_plan_entries "/dev/sda4=swap, /dev/sda3=/home/data,/dev/sda1=/efi,/dev/sda2=/:ext4,/dev/sdb1=/home"
+    [ "$status" -eq 0 ]
+    [ "$output" = $'/dev/sda2:/:ext4\n/dev/sda1:/efi:\n/dev/sdb1:/home:\n/dev/sda3:/home/data:\n/dev/sda4:swap:' ]
+
+    run mount_plan_entries ""
+    [ -z "$output" ]
+}
+
+@test "apply_mount_plan formats only the partitions it is told to" {
+    export PARTITION_MOUNTS="/dev/sda3=/home,/dev/sda2=/:xfs,/dev/sda1=/efi,/dev/sda4=swap:swap"
+    export EFI_PARTITION="create"
+    safe_mount() { log_mock_call "safe_mount" "$@"; }
+    swapon() { log_mock_call "swapon" "$@"; }
+    mountpoint() { return 1; }
+    lsblk() { echo "vfat"; }
+    apply_mount_plan
+    assert_mock_called "mkfs.xfs" "-f /dev/sda2"
+    assert_mock_called "mkswap" "/dev/sda4"
+    assert_mock_called "swapon" "/dev/sda4"
+    assert_mock_called "safe_mount" "/dev/sda2 /mnt"
+    assert_mock_called "safe_mount" "/dev/sda3 /mnt/home"
+    assert_mock_called "safe_mount" "/dev/sda1 /mnt/efi"
+    ! grep -q "^mkfs.*sda3" "$MOCK_CALLS_LOG"
+    [ "$ROOT_DEVICE" = "/dev/sda2" ]
+    [ "$ROOT_FILESYSTEM_TYPE" = "xfs" ]
+    [ "$EFI_DEVICE" = "/dev/sda1" ]
+    [ "$SWAP_DEVICE" = "/dev/sda4" ]
+    # The kept ESP is treated like a reused one
+    [ "$EFI_PARTITION" = "/dev/sda1" ]
+}
+
+@test "check_existing_esp rejects a missing partition" {
+    run check_existing_esp "/dev/does-not-exist"
+    [ "$status" -ne 0 ]
+    [[ "$output" == *"does not exist"* ]]
+}
+
 # =============================================================================
 # ESP Partition Creation Tests
 # =============================================================================
@@ -277,6 +538,81 @@ teardown() {
     assert_mock_called_with_pattern "mkfs.btrfs"
 }
 
+# =============================================================================
+# /var Partition and /tmp Policy Tests
+# =============================================================================
+
+@test "want_var_partition follows VAR_PARTITION_SIZE" {
+    export VAR_PARTITION_SIZE="none"
+    ! want_var_partition
+    export VAR_PARTITION_SIZE="20G"
+    want_var_partition
+}
+
+@test "var_partition_size_spec normalizes units for sgdisk" {
+    export VAR_PARTITION_SIZE="20GiB"
+    [ "$(var_partition_size_spec)" = "20G" ]
+    export VAR_PARTITION_SIZE="512mb"
+    [ "$(var_partition_size_spec)" = "512M" ]
+    export VAR_PARTITION_SIZE="20%FREE"
+    run var_partition_size_spec
+    [ "$status" -ne 0 ]
+}
+
+@test "create_var_partition creates a fixed-size partition with the root filesystem" {
+    export VAR_PARTITION_SIZE="20G"
+    run create_var_partition "/dev/sda" "4" "xfs"
+    [ "$status" -eq 0 ]
+    assert_mock_called_with_pattern "sgdisk.*-n 4:0:+20G.*8300.*VAR"
+    assert_mock_called_with_pattern "mkfs.xfs"
+}
+
+@test "configure_tmp_mount writes a sized tmpfs entry without SELinux contexts" {
+    local root="$TEST_TMP_DIR/target"
+    mkdir -p "$root/etc"
+    printf 'UUID=abc / ext4 rw 0 1\ntmpfs /tmp tmpfs rw,nosuid 0 0\n' > "$root/etc/fstab"
+    export TMP_POLICY="tmpfs" TMP_SIZE="4G"
+    run configure_tmp_mount "$root"
+    [ "$status" -eq 0 ]
+    [ "$(grep -c ' /tmp ' "$root/etc/fstab")" -eq 1 ]
+    grep -q "^tmpfs /tmp tmpfs rw,nosuid,nodev,noatime,size=4G,mode=1777 0 0$" "$root/etc/fstab"
+    ! grep -q "context=" "$root/etc/fstab"
+    grep -q "^UUID=abc / ext4" "$root/etc/fstab"
+}
+
+@test "configure_tmp_mount masks tmp.mount to keep /tmp on disk" {
+    local root="$TEST_TMP_DIR/target"
+    mkdir -p "$root/etc"
+    printf 'tmpfs /tmp tmpfs rw,nosuid 0 0\n' > "$root/etc/fstab"
+    export TMP_POLICY="disk"
+    run configure_tmp_mount "$root"
+    [ "$status" -eq 0 ]
+    ! grep -q " /tmp " "$root/etc/fstab"
+    [ "$(readlink "$root/etc/systemd/system/tmp.mount")" = "/dev/null" ]
+}
+
+@test "configure_tmp_mount keeps the btrfs @tmp subvolume on disk" {
+    local root="$TEST_TMP_DIR/target"
+    mkdir -p "$root/etc"
+    printf 'UUID=abc /tmp btrfs rw,subvol=/@tmp 0 0\n' > "$root/etc/fstab"
+    export TMP_POLICY="disk"
+    run configure_tmp_mount "$root"
+    [ "$status" -eq 0 ]
+    grep -q "^UUID=abc /tmp btrfs rw,subvol=/@tmp 0 0$" "$root/etc/fstab"
+
+    export TMP_POLICY="tmpfs" TMP_SIZE="4G"
+    run configure_tmp_mount "$root"
+    [ "$status" -eq 0 ]
+    ! grep -q "subvol=/@tmp" "$root/etc/fstab"
+    grep -q "^tmpfs /tmp tmpfs" "$root/etc/fstab"
+}
+
+@test "configure_tmp_mount rejects an unknown policy" {
+    export TMP_POLICY="ramdisk"
+    run configure_tmp_mount "$TEST_TMP_DIR"
+    [ "$status" -ne 0 ]
+}
+
 # =============================================================================
 # Safe Mount Tests
 # =============================================================================
@@ -331,6 +667,32 @@ teardown() {
     assert_mock_called_with_pattern "cryptsetup.*open.*/dev/sda1.*cryptroot"
 }
 
+@test "setup_luks_encryption uses PBKDF2 for an encrypted /boot" {
+    ENCRYPTED_BOOT="Yes" run setup_luks_encryption "/dev/sda1" "testpassword" "cryptroot"
+    [ "$status" -eq 0 ]
+    assert_mock_called_with_pattern "cryptsetup.*luksFormat.*--pbkdf pbkdf2"
+}
+
+@test "setup_luks_encryption keeps the default KDF otherwise" {
+    run setup_luks_encryption "/dev/sda1" "testpassword" "cryptroot"
+    [ "$status" -eq 0 ]
+    ! grep -q "pbkdf2" "$MOCK_CALLS_LOG"
+}
+
+@test "add_luks_keyfile adds a key file keyslot" {
+    local keyfile="$TEST_TMP_DIR/crypto_keyfile.bin"
+    run add_luks_keyfile "/dev/sda2" "testpassword" "$keyfile"
+    [ "$status" -eq 0 ]
+    [ "$(stat -c %s "$keyfile")" -eq 2048 ]
+    [ "$(stat -c %a "$keyfile")" = "600" ]
+    assert_mock_called_with_pattern "cryptsetup.*luksAddKey.*/dev/sda2.*$keyfile"
+}
+
+@test "add_luks_keyfile fails with empty password" {
+    run add_luks_keyfile "/dev/sda2" "" "$TEST_TMP_DIR/crypto_keyfile.bin"
+    [ "$status" -eq 1 ]
+}
+
 # =============================================================================
 # Btrfs Subvolume Tests
 # Function signature: setup_btrfs_subvolumes(mountpoint, include_home)
@@ -352,6 +714,14 @@ teardown() {
     assert_mock_called_with_pattern "btrfs.*subvolume.*create.*/@var"
 }
 
+@test "setup_btrfs_subvolumes skips @var for a separate /var partition" {
+    local mountpoint="$TEST_TMP_DIR/mnt"
+    mkdir -p "$mountpoint"
+    run setup_btrfs_subvolumes "$mountpoint" "no" "no"
+    [ "$status" -eq 0 ]
+    ! grep -q "@var" "$MOCK_CALLS_LOG"
+}
+
 @test "setup_btrfs_subvolumes creates @tmp subvolume" {
     local mountpoint="$TEST_TMP_DIR/mnt"
     mkdir -p "$mountpoint"
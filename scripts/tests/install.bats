@@ -136,6 +136,75 @@ teardown() {
     [ -f "$SCRIPTS_DIR/strategies/manual.sh" ]
 }
 
+@test "automatic strategies create a BIOS boot partition on GPT" {
+    local strategy
+    for strategy in simple simple_luks lvm lvm_luks raid raid_luks raid_lvm raid_lvm_luks; do
+        grep -q 'create_bios_boot_partition' "$SCRIPTS_DIR/strategies/$strategy.sh"
+        if grep -q '| fdisk' "$SCRIPTS_DIR/strategies/$strategy.sh"; then return 1; fi
+    done
+}
+
+@test "non-RAID strategies can keep an existing ESP" {
+    local strategy
+    for strategy in simple simple_luks lvm lvm_luks; do
+        grep -q 'prepare_install_disk "$INSTALL_DISK"' "$SCRIPTS_DIR/strategies/$strategy.sh"
+        grep -q 'capture_device_info "efi" "$EFI_PARTITION"' "$SCRIPTS_DIR/strategies/$strategy.sh"
+        if grep -q 'wipe_disk' "$SCRIPTS_DIR/strategies/$strategy.sh"; then return 1; fi
+    done
+}
+
+@test "install.sh mounts a reused ESP before generating fstab" {
+    local body
+    body="$(sed -n '/^generate_fstab()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'safe_mount "$EFI_PARTITION" /mnt/efi'*"genfstab"* ]]
+    grep -q 'export EFI_PARTITION="$EFI_PARTITION"' "$SCRIPTS_DIR/install.sh"
+}
+
+@test "install.sh checks existing systems before partitioning" {
+    local body
+    body="$(sed -n '/^validate_configuration()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'check_existing_systems "$INSTALL_DISK"'* ]]
+    grep -q 'EXISTING_OS="${EXISTING_OS:-protect}"' "$SCRIPTS_DIR/install.sh"
+    grep -q 'keeping_existing_partitions "$disk"' "$SCRIPTS_DIR/disk_utils.sh"
+}
+
+@test "install.sh loads the configuration named by ARCHINSTALL_CONFIG_JSON" {
+    grep -q 'CONFIG_FILE="${ARCHINSTALL_CONFIG_JSON:-}"' "$SCRIPTS_DIR/install.sh"
+    grep -q 'load_config_from_json "$CONFIG_FILE"' "$SCRIPTS_DIR/install.sh"
+}
+
+@test "install.sh checks the package cache before pacstrap" {
+    local body
+    body="$(sed -n '/^install_base_system()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'missing_packages "${all_packages[@]}"'*'pacstrap "${pacstrap_options[@]}"'* ]]
+    [[ "$body" == *'pacstrap_options+=(-c)'* ]]
+}
+
+@test "install.sh installs in batches with a smaller package set on low memory" {
+    local body
+    body="$(sed -n '/^install_base_system()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'essential_packages=("nano" "sudo" "networkmanager" "openssh")'* ]]
+    [[ "$body" == *'for batch in "${batches[@]}"'*'pacstrap "${pacstrap_options[@]}" /mnt "${batch_packages[@]}"'*'rm -f /mnt/var/cache/pacman/pkg/'* ]]
+    body="$(sed -n '/^prepare_system()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'enable_live_zram'*'PARALLEL_DOWNLOADS=1'*'parallel=1'*'Pacman configuration|Keyring|configure_live_pacman'* ]]
+    body="$(sed -n '/^configure_live_pacman()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'configure_pacman /etc/pacman.conf'* ]]
+}
+
+@test "install.sh shares the package cache with the chroot only" {
+    local body
+    body="$(sed -n '/^configure_chroot()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'mount --bind "$PKG_CACHE" "/mnt$PKG_CACHE"'*'./chroot_config.sh'*'umount "/mnt$PKG_CACHE"'* ]]
+    [[ "$body" == *'forget_package_cache /mnt/etc/pacman.conf'* ]]
+}
+
+@test "install.sh gathers SSH keys on the host for the chroot" {
+    local body
+    body="$(sed -n '/^configure_chroot()/,/^}/p' "$SCRIPTS_DIR/install.sh")"
+    [[ "$body" == *'collect_ssh_keys /mnt/root/authorized_keys'*'./chroot_config.sh'* ]]
+    [[ "$body" == *'rm -f /mnt/root/authorized_keys'* ]]
+}
+
 # =============================================================================
 # Desktop Environment Script Existence Tests
 # =============================================================================
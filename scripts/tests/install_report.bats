@@ -0,0 +1,119 @@
+#!/usr/bin/env bats
+# install_report.bats - Tests for install_report.sh functions
+
+load 'test_helper'
+
+setup() {
+    setup_test_environment
+
+    export REPORT_TARGET_ROOT="$TEST_TMP_DIR/mnt"
+    mkdir -p "$REPORT_TARGET_ROOT"
+
+    source "$SCRIPTS_DIR/utils.sh"
+    source "$SCRIPTS_DIR/install_report.sh"
+
+    # Mock the commands the report is collected from
+    lsblk() {
+        log_mock_call "lsblk" "$@"
+        if [[ "$1" == "-J" ]]; then
+            echo '{"blockdevices": [{"name": "sda", "path": "/dev/sda"}]}'
+        else
+            printf '/dev/sda 20G   \n/dev/sda1 512M vfat /mnt/boot ABCD-1234\n/dev/sda2 19G ext4  1111-2222\n'
+        fi
+    }
+    pacman() {
+        log_mock_call "pacman" "$@"
+        printf 'base 3-2\nlinux 6.9.1.arch1-1\n'
+    }
+    systemctl() {
+        log_mock_call "systemctl" "$@"
+        printf 'NetworkManager.service enabled enabled\nsshd.service enabled disabled\n'
+    }
+
+    INSTALL_DISK="/dev/sda"
+    SYSTEM_HOSTNAME="archbox"
+    KERNEL="linux"
+    ROOT_PASSWORD="rootsecret"
+    MAIN_USER_PASSWORD="usersecret"
+    FAILED_PHASES=()
+}
+
+teardown() {
+    teardown_test_environment
+}
+
+# =============================================================================
+# JSON Helper Tests
+# =============================================================================
+
+@test "report_json_escape escapes quotes, backslashes and control characters" {
+    run report_json_escape $'say "hi"\\\tnow\n'
+    [ "$status" -eq 0 ]
+    [ "$output" = 'say \"hi\"\\\tnow\n' ]
+}
+
+@test "report_json_array builds an array from lines and skips blanks" {
+    run bash -c "source '$SCRIPTS_DIR/install_report.sh'; printf 'a\n\nb \"c\"\n' | report_json_array"
+    [ "$status" -eq 0 ]
+    [ "$output" = '["a","b \"c\""]' ]
+}
+
+@test "report_json_array prints an empty array for no input" {
+    run bash -c "source '$SCRIPTS_DIR/install_report.sh'; report_json_array < /dev/null"
+    [ "$output" = '[]' ]
+}
+
+# =============================================================================
+# Report Generation Tests
+# =============================================================================
+
+@test "generate_install_report writes JSON and Markdown to the target" {
+    run generate_install_report
+    [ "$status" -eq 0 ]
+    [ -f "$REPORT_TARGET_ROOT/var/log/archinstall/install-report.json" ]
+    [ -f "$REPORT_TARGET_ROOT/var/log/archinstall/install-report.md" ]
+    assert_mock_called "pacman" "--dbpath $REPORT_TARGET_ROOT/var/lib/pacman -Q"
+}
+
+@test "JSON report records partitions, packages, services and config" {
+    generate_install_report
+    local report="$REPORT_TARGET_ROOT/var/log/archinstall/install-report.json"
+
+    grep -q '"blockdevices"' "$report"
+    grep -q '"linux 6.9.1.arch1-1"' "$report"
+    grep -q '"enabled_services": \["NetworkManager.service","sshd.service"\]' "$report"
+    grep -q '"SYSTEM_HOSTNAME": "archbox"' "$report"
+    grep -q '"failed_phases": \[\]' "$report"
+    if command -v jq >/dev/null 2>&1; then
+        jq -e '.packages | length == 2' "$report"
+    fi
+}
+
+@test "Markdown report keeps empty partition columns aligned" {
+    generate_install_report
+    local report="$REPORT_TARGET_ROOT/var/log/archinstall/install-report.md"
+
+    grep -qF '| /dev/sda1 | 512M | vfat | /mnt/boot | ABCD-1234 |' "$report"
+    grep -qF '| /dev/sda2 | 19G | ext4 | - | 1111-2222 |' "$report"
+    grep -qF -- '- sshd.service' "$report"
+}
+
+@test "report never contains passwords" {
+    generate_install_report
+    ! grep -rq 'rootsecret\|usersecret' "$REPORT_TARGET_ROOT/var/log/archinstall"
+}
+
+@test "failed phases are listed in both reports" {
+    FAILED_PHASES=("Chroot configuration")
+    generate_install_report
+    grep -q '"failed_phases": \["Chroot configuration"\]' "$REPORT_TARGET_ROOT/var/log/archinstall/install-report.json"
+    grep -q '## Failed Phases' "$REPORT_TARGET_ROOT/var/log/archinstall/install-report.md"
+}
+
+@test "ARCHINSTALL_REPORT_DIR receives a copy of the report" {
+    export ARCHINSTALL_REPORT_DIR="$TEST_TMP_DIR/external/reports"
+    run generate_install_report
+    [ "$status" -eq 0 ]
+    [ -f "$ARCHINSTALL_REPORT_DIR/install-report.json" ]
+    [ -f "$ARCHINSTALL_REPORT_DIR/install-report.md" ]
+}
@@ -118,6 +118,20 @@ create_mock_commands() {
     }
     export -f mkfs.xfs
 
+    # Mock mkfs.f2fs
+    mkfs.f2fs() {
+        log_mock_call "mkfs.f2fs" "$@"
+        return 0
+    }
+    export -f mkfs.f2fs
+
+    # Mock bcachefs
+    bcachefs() {
+        log_mock_call "bcachefs" "$@"
+        return 0
+    }
+    export -f bcachefs
+
     # Mock mkfs.fat
     mkfs.fat() {
         log_mock_call "mkfs.fat" "$@"
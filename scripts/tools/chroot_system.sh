@@ -1,6 +1,15 @@
 #!/bin/bash
 # chroot_system.sh - Chroot into a mounted system
 # Usage: ./chroot_system.sh --root /mnt
+#        ./chroot_system.sh --device /dev/sda2 [--root /mnt] [--mount-only]
+#
+# With --device the system is mounted first: a LUKS container is unlocked
+# (LVM inside it is activated and its "root" volume used), the root is
+# mounted (Btrfs from its @ subvolume) and the other local filesystems from
+# its fstab, such as /boot and the ESP, are mounted below it. Everything
+# mounted or unlocked here is released again when the shell exits. With
+# --mount-only the system is mounted and left mounted without a shell, for
+# tools that work on it afterwards (the bootloader repair).
 
 set -euo pipefail
 
@@ -11,6 +20,13 @@ source_or_die "$SCRIPT_DIR/../utils.sh"
 # Default values
 ROOT_PATH="/mnt"
 MOUNT_SYSTEMS=true
+DEVICE=""
+MOUNT_ONLY=false
+
+# LUKS mapping opened for --device; the TUI releases it if the shell is killed
+RESCUE_MAPPING="rescue-root"
+OPENED_MAPPING=false
+MOUNTED_ROOT=false
 
 # Parse arguments
 while [[ $# -gt 0 ]]; do
@@ -19,14 +35,24 @@ while [[ $# -gt 0 ]]; do
             ROOT_PATH="$2"
             shift 2
             ;;
+        --device)
+            DEVICE="$2"
+            shift 2
+            ;;
         --no-mount)
             MOUNT_SYSTEMS=false
             shift
             ;;
+        --mount-only)
+            MOUNT_ONLY=true
+            shift
+            ;;
         --help)
-            echo "Usage: $0 [--root <path>] [--no-mount]"
-            echo "  --root <path>     Root directory to chroot into (default: /mnt)"
-            echo "  --no-mount        Skip mounting /proc, /sys, /dev"
+            echo "Usage: $0 [--root <path>] [--device <partition>] [--no-mount] [--mount-only]"
+            echo "  --root <path>         Root directory to chroot into (default: /mnt)"
+            echo "  --device <partition>  Unlock and mount this root filesystem at --root first"
+            echo "  --no-mount            Skip mounting /proc, /sys, /dev"
+            echo "  --mount-only          Mount the --device system and exit without a shell"
             exit 0
             ;;
         *)
@@ -36,6 +62,95 @@ while [[ $# -gt 0 ]]; do
     esac
 done
 
+# Unmount everything below the root and close the LUKS mapping we opened
+release_system() {
+    if [[ "$MOUNTED_ROOT" == true ]]; then
+        log_info "Unmounting $ROOT_PATH..."
+        umount -R "$ROOT_PATH" || log_warning "Could not unmount $ROOT_PATH"
+    fi
+    if [[ "$OPENED_MAPPING" == true ]]; then
+        local vg
+        vg=$(pvs --noheadings -o vg_name "/dev/mapper/$RESCUE_MAPPING" 2>/dev/null | xargs || true)
+        [[ -n "$vg" ]] && { vgchange -an "$vg" &>/dev/null || true; }
+        cryptsetup close "$RESCUE_MAPPING" || log_warning "Could not close $RESCUE_MAPPING"
+    fi
+}
+
+# Unlock and mount the system on DEVICE at ROOT_PATH
+mount_system() {
+    local root_device="$DEVICE"
+
+    if mountpoint -q "$ROOT_PATH" 2>/dev/null; then
+        error_exit "$ROOT_PATH is already mounted; unmount it or chroot without --device"
+    fi
+
+    if [[ "$(blkid -s TYPE -o value "$DEVICE")" == "crypto_LUKS" ]]; then
+        log_info "Unlocking $DEVICE..."
+        cryptsetup open "$DEVICE" "$RESCUE_MAPPING" || error_exit "Could not unlock $DEVICE"
+        OPENED_MAPPING=true
+        root_device="/dev/mapper/$RESCUE_MAPPING"
+
+        # LVM on LUKS: the root is the "root" logical volume
+        if [[ "$(blkid -s TYPE -o value "$root_device")" == "LVM2_member" ]]; then
+            local vg
+            vg=$(pvs --noheadings -o vg_name "$root_device" 2>/dev/null | xargs || true)
+            vgchange -ay "$vg" &>/dev/null || true
+            root_device="/dev/$vg/root"
+            [[ -b "$root_device" ]] || error_exit "No 'root' logical volume inside $DEVICE"
+        fi
+    fi
+
+    mkdir -p "$ROOT_PATH"
+    if [[ "$(blkid -s TYPE -o value "$root_device")" == "btrfs" ]]; then
+        # The installer keeps the root in the @ subvolume
+        mount -o subvol=@ "$root_device" "$ROOT_PATH" 2>/dev/null \
+            || mount "$root_device" "$ROOT_PATH" \
+            || error_exit "Could not mount $root_device"
+    else
+        mount "$root_device" "$ROOT_PATH" || error_exit "Could not mount $root_device"
+    fi
+    MOUNTED_ROOT=true
+    log_info "Mounted $root_device at $ROOT_PATH"
+
+    if [[ ! -f "$ROOT_PATH/etc/fstab" ]]; then
+        log_warning "No /etc/fstab on $root_device; only the root filesystem is mounted"
+        return 0
+    fi
+
+    # /boot, the ESP, /home, ... in fstab order so parents come first
+    local source target fstype options
+    while read -r source target fstype options; do
+        case "$target" in
+            /|none|swap) continue ;;
+        esac
+        case "$fstype" in
+            swap|tmpfs|proc|sysfs|nfs*|cifs|smb3) continue ;;
+        esac
+        [[ ",$options," == *",noauto,"* ]] && continue
+
+        mkdir -p "$ROOT_PATH$target"
+        if mount -o "$options" "$source" "$ROOT_PATH$target"; then
+            log_info "Mounted $target"
+        else
+            log_warning "Could not mount $target ($source)"
+        fi
+    done < <(findmnt --tab-file "$ROOT_PATH/etc/fstab" -n -r -o SOURCE,TARGET,FSTYPE,OPTIONS)
+}
+
+if [[ "$MOUNT_ONLY" == true ]]; then
+    [[ -n "$DEVICE" ]] || error_exit "--mount-only needs --device"
+    # Released on failure only; on success the caller owns the mounts
+    trap '[[ $? -eq 0 ]] || release_system' EXIT
+    mount_system
+    log_success "$DEVICE is mounted at $ROOT_PATH"
+    exit 0
+fi
+
+if [[ -n "$DEVICE" ]]; then
+    trap release_system EXIT
+    mount_system
+fi
+
 # Check if root path exists
 if [[ ! -d "$ROOT_PATH" ]]; then
     error_exit "Root path does not exist: $ROOT_PATH"
@@ -14,7 +14,13 @@ ACTION=""
 IP_ADDRESS=""
 NETMASK=""
 GATEWAY=""
+IP6_ADDRESS=""
+GATEWAY6=""
+IPV6_MODE=""
 DNS_SERVERS=""
+BACKEND=""
+# Persistent configuration is written below this directory (tests use a scratch one)
+CONFIG_ROOT="${NETWORK_CONFIG_ROOT:-}"
 DHCP=false
 STATIC=false
 ENABLE=false
@@ -44,6 +50,30 @@ while [[ $# -gt 0 ]]; do
             GATEWAY="$2"
             shift 2
             ;;
+        --ip6)
+            IP6_ADDRESS="$2"
+            shift 2
+            ;;
+        --gateway6)
+            GATEWAY6="$2"
+            shift 2
+            ;;
+        --ipv6-mode)
+            IPV6_MODE="$2"
+            shift 2
+            ;;
+        --backend)
+            BACKEND="$2"
+            shift 2
+            ;;
+        --config_type)
+            case "$2" in
+                dhcp) DHCP=true ;;
+                static) STATIC=true ;;
+                *) error_exit "Invalid configuration type: $2 (use: dhcp, static)" ;;
+            esac
+            shift 2
+            ;;
         --dns)
             DNS_SERVERS="$2"
             shift 2
@@ -84,15 +114,20 @@ while [[ $# -gt 0 ]]; do
             echo "Configuration Options:"
             echo "  --dhcp                Use DHCP for IP configuration"
             echo "  --static              Use static IP configuration"
-            echo "  --ip <address>        Static IP address"
-            echo "  --netmask <mask>      Network mask (e.g., 255.255.255.0)"
-            echo "  --gateway <gateway>   Default gateway"
-            echo "  --dns <servers>       DNS servers (comma-separated)"
+            echo "  --ip <address>        Static IPv4 address, optionally with /prefix"
+            echo "  --netmask <mask>      Network mask (e.g., 255.255.255.0 or 24)"
+            echo "  --gateway <gateway>   Default IPv4 gateway"
+            echo "  --ipv6-mode <mode>    IPv6: ra (router advertisements), dhcpv6, static, off"
+            echo "  --ip6 <address>       Static IPv6 address with prefix (default /64)"
+            echo "  --gateway6 <gateway>  Default IPv6 gateway"
+            echo "  --dns <servers>       DNS servers, IPv4 or IPv6 (comma-separated)"
+            echo "  --backend <backend>   Also save the configuration for networkmanager or networkd"
             echo ""
             echo "Examples:"
             echo "  $0 --interface eth0 --action status"
             echo "  $0 --interface eth0 --action configure --dhcp"
             echo "  $0 --interface eth0 --action configure --static --ip 192.168.1.100 --netmask 255.255.255.0 --gateway 192.168.1.1"
+            echo "  $0 --interface eth0 --action configure --dhcp --ipv6-mode static --ip6 2001:db8::10/64 --gateway6 fe80::1 --backend networkd"
             echo "  $0 --interface eth0 --action enable"
             echo "  $0 --interface eth0 --action info"
             echo ""
@@ -281,7 +316,7 @@ configure_dhcp() {
     # Try dhcpcd if available
     if command -v dhcpcd >/dev/null 2>&1; then
         log_info "Using dhcpcd for DHCP configuration..."
-        if dhcpcd "$iface"; then
+        if dhcpcd -4 "$iface"; then
             log_success "✅ DHCP configuration successful"
         else
             log_warning "⚠️  dhcpcd failed, trying alternative method"
@@ -307,10 +342,8 @@ configure_dhcp() {
 # Function to configure interface with static IP
 configure_static() {
     local iface="$1"
-    local ip="$2"
-    local netmask="$3"
-    local gateway="$4"
-    local dns="$5"
+    local address="$2"
+    local gateway="$3"
     
     log_info "🔧 Configuring '$iface' with static IP..."
     
@@ -318,8 +351,8 @@ configure_static() {
     ip link set "$iface" up
     
     # Configure IP address
-    log_info "Setting IP address: $ip"
-    if ip addr add "$ip/$netmask" dev "$iface"; then
+    log_info "Setting IP address: $address"
+    if ip addr add "$address" dev "$iface"; then
         log_success "✅ IP address configured"
     else
         error_exit "Failed to configure IP address"
@@ -334,21 +367,183 @@ configure_static() {
             log_warning "⚠️  Failed to configure gateway"
         fi
     fi
-    
-    # Configure DNS if provided
-    if [[ -n "$dns" ]]; then
-        log_info "Setting DNS servers: $dns"
-        # Note: This is a temporary configuration
-        # For persistent DNS, /etc/resolv.conf would need to be configured
-        echo "nameserver $(echo "$dns" | cut -d',' -f1)" > /etc/resolv.conf
-        if [[ "$dns" == *,* ]]; then
-            echo "nameserver $(echo "$dns" | cut -d',' -f2)" >> /etc/resolv.conf
-        fi
-        log_success "✅ DNS servers configured (temporary)"
+}
+
+# Function to configure IPv6 by mode: ra, dhcpv6, static or off
+configure_ipv6() {
+    local iface="$1"
+    local mode="$2"
+    local address="$3"
+    local gateway="$4"
+
+    log_info "🔧 Configuring IPv6 on '$iface' ($mode)..."
+    if [[ "$mode" == "off" ]]; then
+        sysctl -q -w "net.ipv6.conf.$iface.disable_ipv6=1"
+        log_success "✅ IPv6 disabled"
+        return 0
     fi
-    
-    # Show new configuration
-    get_interface_status "$iface"
+
+    sysctl -q -w "net.ipv6.conf.$iface.disable_ipv6=0"
+    case "$mode" in
+        static)
+            sysctl -q -w "net.ipv6.conf.$iface.accept_ra=0"
+            log_info "Setting IPv6 address: $address"
+            if ip -6 addr add "$address" dev "$iface"; then
+                log_success "✅ IPv6 address configured"
+            else
+                error_exit "Failed to configure IPv6 address"
+            fi
+            if [[ -n "$gateway" ]]; then
+                log_info "Setting IPv6 gateway: $gateway"
+                if ip -6 route add default via "$gateway" dev "$iface"; then
+                    log_success "✅ IPv6 gateway configured"
+                else
+                    log_warning "⚠️  Failed to configure IPv6 gateway"
+                fi
+            fi
+            ;;
+        dhcpv6)
+            sysctl -q -w "net.ipv6.conf.$iface.accept_ra=1"
+            if command -v dhcpcd >/dev/null 2>&1 && dhcpcd -6 "$iface"; then
+                log_success "✅ DHCPv6 configuration successful"
+            else
+                log_warning "⚠️  DHCPv6 failed; addresses from router advertisements only"
+            fi
+            ;;
+        *)
+            sysctl -q -w "net.ipv6.conf.$iface.accept_ra=1"
+            log_success "✅ IPv6 addresses from router advertisements"
+            ;;
+    esac
+}
+
+# Function to set DNS servers of either family for the live session
+configure_dns() {
+    local dns="$1"
+
+    log_info "Setting DNS servers: $dns"
+    # Note: This is a temporary configuration; --backend saves it persistently
+    local server
+    : > /etc/resolv.conf
+    for server in ${dns//,/ }; do
+        echo "nameserver $server" >> /etc/resolv.conf
+    done
+    log_success "✅ DNS servers configured (temporary)"
+}
+
+# Prefix length of a netmask given as 255.255.255.0 or 24
+netmask_to_prefix() {
+    local mask="$1"
+    if [[ "$mask" =~ ^[0-9]+$ ]]; then
+        echo "$mask"
+        return 0
+    fi
+    local prefix=0 octet
+    for octet in ${mask//./ }; do
+        while (( octet > 0 )); do
+            prefix=$(( prefix + (octet & 1) ))
+            octet=$(( octet >> 1 ))
+        done
+    done
+    echo "$prefix"
+}
+
+# Write a systemd-networkd unit for the interface
+# Usage: write_networkd_config <iface> <ipv4|""> <gateway4> <ipv6_mode> <ipv6|""> <gateway6> <dns>
+write_networkd_config() {
+    local iface="$1" ipv4="$2" gateway4="$3" mode="$4" ipv6="$5" gateway6="$6" dns="$7"
+    local dir="$CONFIG_ROOT/etc/systemd/network"
+    local file="$dir/20-$iface.network"
+    local dhcp="no" server
+
+    if [[ -z "$ipv4" && "$mode" == "dhcpv6" ]]; then
+        dhcp="yes"
+    elif [[ -z "$ipv4" ]]; then
+        dhcp="ipv4"
+    elif [[ "$mode" == "dhcpv6" ]]; then
+        dhcp="ipv6"
+    fi
+
+    mkdir -p "$dir"
+    {
+        echo "[Match]"
+        echo "Name=$iface"
+        echo ""
+        echo "[Network]"
+        echo "DHCP=$dhcp"
+        [[ -n "$ipv4" ]] && echo "Address=$ipv4"
+        [[ -n "$gateway4" ]] && echo "Gateway=$gateway4"
+        case "$mode" in
+            static)
+                echo "Address=$ipv6"
+                [[ -n "$gateway6" ]] && echo "Gateway=$gateway6"
+                echo "IPv6AcceptRA=no"
+                ;;
+            off)
+                echo "IPv6AcceptRA=no"
+                echo "LinkLocalAddressing=no"
+                ;;
+            *)
+                echo "IPv6AcceptRA=yes"
+                ;;
+        esac
+        for server in ${dns//,/ }; do
+            echo "DNS=$server"
+        done
+    } > "$file"
+    log_success "✅ Saved systemd-networkd configuration: $file"
+}
+
+# Write a NetworkManager keyfile for the interface
+# Usage: write_networkmanager_config <iface> <ipv4|""> <gateway4> <ipv6_mode> <ipv6|""> <gateway6> <dns>
+write_networkmanager_config() {
+    local iface="$1" ipv4="$2" gateway4="$3" mode="$4" ipv6="$5" gateway6="$6" dns="$7"
+    local dir="$CONFIG_ROOT/etc/NetworkManager/system-connections"
+    local file="$dir/$iface.nmconnection"
+    local dns4="" dns6="" server method6
+
+    for server in ${dns//,/ }; do
+        if [[ "$server" == *:* ]]; then
+            dns6+="$server;"
+        else
+            dns4+="$server;"
+        fi
+    done
+    case "$mode" in
+        static) method6="manual" ;;
+        dhcpv6) method6="dhcp" ;;
+        off) method6="disabled" ;;
+        *) method6="auto" ;;
+    esac
+
+    mkdir -p "$dir"
+    # NetworkManager ignores keyfiles others can read
+    (
+        umask 077
+        {
+            echo "[connection]"
+            echo "id=$iface"
+            echo "type=ethernet"
+            echo "interface-name=$iface"
+            echo ""
+            echo "[ipv4]"
+            if [[ -n "$ipv4" ]]; then
+                echo "method=manual"
+                echo "address1=$ipv4${gateway4:+,$gateway4}"
+            else
+                echo "method=auto"
+            fi
+            [[ -n "$dns4" ]] && echo "dns=$dns4"
+            echo ""
+            echo "[ipv6]"
+            echo "method=$method6"
+            [[ "$mode" == "static" ]] && echo "address1=$ipv6${gateway6:+,$gateway6}"
+            [[ -n "$dns6" ]] && echo "dns=$dns6"
+            true
+        } > "$file"
+    )
+    chmod 600 "$file"
+    log_success "✅ Saved NetworkManager configuration: $file"
 }
 
 # Main execution based on action
@@ -370,22 +565,66 @@ case "$ACTION" in
         ;;
         
     "configure")
+        IPV4_CIDR=""
         if [[ "$DHCP" == true ]]; then
             configure_dhcp "$INTERFACE"
         elif [[ "$STATIC" == true ]]; then
             if [[ -z "$IP_ADDRESS" ]]; then
                 error_exit "IP address is required for static configuration (--ip)"
             fi
-            if [[ -z "$NETMASK" ]]; then
-                # Try to determine netmask from IP class
-                log_warning "Netmask not specified, using /24"
-                NETMASK="24"
+            if [[ "$IP_ADDRESS" == */* ]]; then
+                IPV4_CIDR="$IP_ADDRESS"
+            else
+                if [[ -z "$NETMASK" ]]; then
+                    # Try to determine netmask from IP class
+                    log_warning "Netmask not specified, using /24"
+                    NETMASK="24"
+                fi
+                IPV4_CIDR="$IP_ADDRESS/$(netmask_to_prefix "$NETMASK")"
             fi
             
-            configure_static "$INTERFACE" "$IP_ADDRESS" "$NETMASK" "$GATEWAY" "$DNS_SERVERS"
+            configure_static "$INTERFACE" "$IPV4_CIDR" "$GATEWAY"
         else
             error_exit "Configuration type required (--dhcp or --static)"
         fi
+
+        # IPv6 is static with an address and follows router advertisements otherwise
+        if [[ -z "$IPV6_MODE" ]]; then
+            IPV6_MODE="ra"
+            [[ -n "$IP6_ADDRESS" ]] && IPV6_MODE="static"
+        fi
+        case "$IPV6_MODE" in
+            ra|dhcpv6|off) ;;
+            static)
+                if [[ -z "$IP6_ADDRESS" ]]; then
+                    error_exit "IPv6 address is required for static IPv6 (--ip6)"
+                fi
+                [[ "$IP6_ADDRESS" == */* ]] || IP6_ADDRESS="$IP6_ADDRESS/64"
+                ;;
+            *)
+                error_exit "Invalid IPv6 mode: $IPV6_MODE (use: ra, dhcpv6, static, off)"
+                ;;
+        esac
+        configure_ipv6 "$INTERFACE" "$IPV6_MODE" "$IP6_ADDRESS" "$GATEWAY6"
+
+        if [[ -n "$DNS_SERVERS" ]]; then
+            configure_dns "$DNS_SERVERS"
+        fi
+
+        case "$BACKEND" in
+            "") ;;
+            networkd)
+                write_networkd_config "$INTERFACE" "$IPV4_CIDR" "$GATEWAY" \
+                    "$IPV6_MODE" "$IP6_ADDRESS" "$GATEWAY6" "$DNS_SERVERS"
+                ;;
+            networkmanager)
+                write_networkmanager_config "$INTERFACE" "$IPV4_CIDR" "$GATEWAY" \
+                    "$IPV6_MODE" "$IP6_ADDRESS" "$GATEWAY6" "$DNS_SERVERS"
+                ;;
+            *)
+                error_exit "Invalid backend: $BACKEND (use: networkmanager, networkd)"
+                ;;
+        esac
         ;;
         
     *)
@@ -404,6 +643,6 @@ get_interface_status "$INTERFACE"
 log_info "Next steps:"
 log_info "  • Test connectivity: ping 8.8.8.8"
 log_info "  • Check DNS resolution: nslookup google.com"
-if [[ "$ACTION" == "configure" && "$STATIC" == true ]]; then
-    log_info "  • Configure persistent settings in /etc/netctl/ or systemd-networkd"
+if [[ "$ACTION" == "configure" && -z "$BACKEND" ]]; then
+    log_info "  • Save persistent settings with --backend networkmanager or --backend networkd"
 fi
\ No newline at end of file
@@ -1,6 +1,6 @@
 #!/bin/bash
 # format_partition.sh - Format a partition with specified filesystem
-# Usage: ./format_partition.sh --device /dev/sda1 --filesystem ext4
+# Usage: ./format_partition.sh --device /dev/sda1 --filesystem ext4 [--ext4-reserved 1]
 
 set -euo pipefail
 
@@ -13,6 +13,9 @@ DEVICE=""
 FILESYSTEM=""
 LABEL=""
 FORCE=false
+EXT4_RESERVED=""
+BTRFS_METADATA=""
+F2FS_COMPRESSION=false
 
 # Parse arguments
 while [[ $# -gt 0 ]]; do
@@ -29,13 +32,26 @@ while [[ $# -gt 0 ]]; do
             LABEL="$2"
             shift 2
             ;;
+        --ext4-reserved)
+            EXT4_RESERVED="$2"
+            shift 2
+            ;;
+        --btrfs-metadata)
+            BTRFS_METADATA="$2"
+            shift 2
+            ;;
+        --f2fs-compression)
+            F2FS_COMPRESSION=true
+            shift
+            ;;
         --force)
             FORCE=true
             shift
             ;;
         --help)
             echo "Usage: $0 --device <partition> --filesystem <fs_type> [--label <label>] [--force]"
-            echo "Supported filesystems: ext4, xfs, btrfs, fat32, ntfs"
+            echo "       [--ext4-reserved <percent>] [--btrfs-metadata single|dup] [--f2fs-compression]"
+            echo "Supported filesystems: ext4, xfs, btrfs, f2fs, fat32, exfat, ntfs"
             exit 0
             ;;
         *)
@@ -54,6 +70,24 @@ if [[ -z "$FILESYSTEM" ]]; then
     error_exit "Filesystem type is required (--filesystem ext4)"
 fi
 
+# Per-filesystem flags only apply to their own filesystem
+if [[ -n "$EXT4_RESERVED" ]]; then
+    [[ "$FILESYSTEM" == "ext4" ]] || error_exit "--ext4-reserved only applies to ext4"
+    if ! [[ "$EXT4_RESERVED" =~ ^[0-9]+$ ]] || (( EXT4_RESERVED > 50 )); then
+        error_exit "--ext4-reserved must be 0-50 percent"
+    fi
+fi
+if [[ -n "$BTRFS_METADATA" ]]; then
+    [[ "$FILESYSTEM" == "btrfs" ]] || error_exit "--btrfs-metadata only applies to btrfs"
+    case "$BTRFS_METADATA" in
+        single|dup) ;;
+        *) error_exit "--btrfs-metadata must be single or dup" ;;
+    esac
+fi
+if [[ "$F2FS_COMPRESSION" == true && "$FILESYSTEM" != "f2fs" ]]; then
+    error_exit "--f2fs-compression only applies to f2fs"
+fi
+
 # Check if device exists
 if [[ ! -b "$DEVICE" ]]; then
     error_exit "Device does not exist: $DEVICE"
@@ -80,41 +114,39 @@ fi
 log_info "Formatting $DEVICE with $FILESYSTEM filesystem..."
 
 # Format based on filesystem type
+MKFS_ARGS=()
 case "$FILESYSTEM" in
     ext4)
-        if [[ -n "$LABEL" ]]; then
-            mkfs.ext4 -L "$LABEL" "$DEVICE"
-        else
-            mkfs.ext4 "$DEVICE"
-        fi
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-L "$LABEL")
+        [[ -n "$EXT4_RESERVED" ]] && MKFS_ARGS+=(-m "$EXT4_RESERVED")
+        mkfs.ext4 "${MKFS_ARGS[@]}" "$DEVICE"
         ;;
     xfs)
-        if [[ -n "$LABEL" ]]; then
-            mkfs.xfs -L "$LABEL" "$DEVICE"
-        else
-            mkfs.xfs "$DEVICE"
-        fi
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-L "$LABEL")
+        mkfs.xfs "${MKFS_ARGS[@]}" "$DEVICE"
         ;;
     btrfs)
-        if [[ -n "$LABEL" ]]; then
-            mkfs.btrfs -L "$LABEL" "$DEVICE"
-        else
-            mkfs.btrfs "$DEVICE"
-        fi
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-L "$LABEL")
+        [[ -n "$BTRFS_METADATA" ]] && MKFS_ARGS+=(-m "$BTRFS_METADATA")
+        mkfs.btrfs "${MKFS_ARGS[@]}" "$DEVICE"
+        ;;
+    f2fs)
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-l "$LABEL")
+        # Compression needs the extra inode attributes to store its flags
+        [[ "$F2FS_COMPRESSION" == true ]] && MKFS_ARGS+=(-O extra_attr,compression)
+        mkfs.f2fs -f "${MKFS_ARGS[@]}" "$DEVICE"
         ;;
     fat32)
-        if [[ -n "$LABEL" ]]; then
-            mkfs.fat -F 32 -n "$LABEL" "$DEVICE"
-        else
-            mkfs.fat -F 32 "$DEVICE"
-        fi
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-n "$LABEL")
+        mkfs.fat -F 32 "${MKFS_ARGS[@]}" "$DEVICE"
+        ;;
+    exfat)
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-L "$LABEL")
+        mkfs.exfat "${MKFS_ARGS[@]}" "$DEVICE"
         ;;
     ntfs)
-        if [[ -n "$LABEL" ]]; then
-            mkfs.ntfs -L "$LABEL" "$DEVICE"
-        else
-            mkfs.ntfs "$DEVICE"
-        fi
+        [[ -n "$LABEL" ]] && MKFS_ARGS+=(-L "$LABEL")
+        mkfs.ntfs "${MKFS_ARGS[@]}" "$DEVICE"
         ;;
     *)
         error_exit "Unsupported filesystem type: $FILESYSTEM"
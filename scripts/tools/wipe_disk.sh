@@ -55,8 +55,8 @@ fi
 
 # --- Disk Type Detection ---
 
-# Get the base device name (strip partition numbers, handle nvme)
-# /dev/sda1 -> sda, /dev/nvme0n1p1 -> nvme0n1
+# Get the base device name (strip partition numbers, handle nvme/mmc/loop)
+# /dev/sda1 -> sda, /dev/nvme0n1p1 -> nvme0n1, /dev/mmcblk0p2 -> mmcblk0
 get_base_device_name() {
     local device="$1"
     local base_name
@@ -64,8 +64,9 @@ get_base_device_name() {
     # Remove /dev/ prefix
     base_name="${device#/dev/}"
 
-    # Handle NVMe devices: nvme0n1p1 -> nvme0n1
-    if [[ "$base_name" =~ ^(nvme[0-9]+n[0-9]+) ]]; then
+    # Disks whose name ends in a digit (NVMe namespaces, eMMC/SD, loop):
+    # nvme0n1p1 -> nvme0n1, mmcblk0p1 -> mmcblk0, loop0p1 -> loop0
+    if [[ "$base_name" =~ ^(nvme[0-9]+n[0-9]+|mmcblk[0-9]+|loop[0-9]+) ]]; then
         echo "${BASH_REMATCH[1]}"
         return
     fi
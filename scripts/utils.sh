@@ -45,6 +45,17 @@ shell_escape() {
     printf '%q' "$input"
 }
 
+# Replace the user:password@ of a proxy URL with ***@ before it is shown
+# Usage: log_info "Proxy: $(redact_proxy "$HTTP_PROXY")"
+redact_proxy() {
+    local url="$1"
+    if [[ "$url" =~ ^([a-zA-Z0-9+.-]+://)([^/]*)@(.*)$ ]]; then
+        printf '%s***@%s' "${BASH_REMATCH[1]}" "${BASH_REMATCH[3]}"
+    else
+        printf '%s' "$url"
+    fi
+}
+
 # Validate that a string contains only safe characters for filenames
 # Returns 0 if safe, 1 if contains dangerous characters
 validate_safe_string() {
@@ -159,6 +170,192 @@ log_and_continue() {
     # Do not exit
 }
 
+# Run an installation phase under the unattended on-error policy.
+# Usage: run_phase critical|optional "Description" function_name
+#   ARCHINSTALL_ON_ERROR=abort     fail on first error (default)
+#   ARCHINSTALL_ON_ERROR=retry     retry up to ARCHINSTALL_RETRIES more times, then abort
+#   ARCHINSTALL_ON_ERROR=continue  record the failure in FAILED_PHASES and keep going
+#   ARCHINSTALL_ON_ERROR=ask       print an ARCHINSTALL_RECOVERY line and wait for the
+#                                  TUI to write retry, skip or abort to ARCHINSTALL_RECOVERY_FILE
+# Critical phases (disk, base system) are never skipped - nothing after them can work.
+FAILED_PHASES=()
+run_phase() {
+    local criticality="$1"
+    local desc="$2"
+    local func="$3"
+    local policy="${ARCHINSTALL_ON_ERROR:-abort}"
+    local attempts=1
+
+    if [[ "$policy" == "retry" ]]; then
+        attempts=$(( ${ARCHINSTALL_RETRIES:-3} + 1 ))
+    fi
+
+    local attempt
+    for (( attempt = 1; attempt <= attempts; attempt++ )); do
+        if "$func"; then
+            return 0
+        fi
+        if (( attempt < attempts )); then
+            log_warn "$desc failed (attempt $attempt/$attempts), retrying..."
+            sleep "${ARCHINSTALL_RETRY_DELAY:-5}"
+        fi
+    done
+
+    if [[ "$policy" == "ask" && -n "${ARCHINSTALL_RECOVERY_FILE:-}" ]]; then
+        local decision
+        while true; do
+            rm -f "$ARCHINSTALL_RECOVERY_FILE"
+            echo "ARCHINSTALL_RECOVERY: $criticality $desc"
+            until [[ -s "$ARCHINSTALL_RECOVERY_FILE" ]]; do
+                sleep 1
+            done
+            read -r decision < "$ARCHINSTALL_RECOVERY_FILE" || true
+            rm -f "$ARCHINSTALL_RECOVERY_FILE"
+            case "$decision" in
+                retry)
+                    log_info "Retrying $desc..."
+                    if "$func"; then
+                        return 0
+                    fi
+                    ;;
+                skip)
+                    if [[ "$criticality" != "critical" ]]; then
+                        log_warn "$desc failed, skipped"
+                        FAILED_PHASES+=("$desc")
+                        return 0
+                    fi
+                    log_warn "$desc is critical and cannot be skipped"
+                    ;;
+                *)
+                    break
+                    ;;
+            esac
+        done
+    fi
+
+    if [[ "$policy" == "continue" && "$criticality" != "critical" ]]; then
+        log_warn "$desc failed, continuing (on-error policy: continue)"
+        FAILED_PHASES+=("$desc")
+        return 0
+    fi
+
+    error_exit "$desc failed"
+}
+
+# Run the steps of a phase that do not depend on each other side by side.
+# Usage: run_parallel_phases "Name|Dependency,Dependency|function_name" ...
+# A step starts once the steps it names are done, at most
+# ARCHINSTALL_PARALLEL_PHASES at a time (default 2; 1 runs them in order).
+# Steps run in subshells, so variables they set do not reach the caller.
+# The TUI draws a progress lane per step from these lines:
+#   ARCHINSTALL_LANE: wait|start|done|failed|skipped <name>
+# and the step's output, prefixed with "[<name>] ". Steps depending on a
+# failed one are skipped; returns 1 once the running steps finished.
+run_parallel_phases() {
+    local max="${ARCHINSTALL_PARALLEL_PHASES:-2}"
+    local -a names=() deps=() funcs=()
+    local -A state=() pid_step=()
+    local spec name dep_list func
+
+    for spec in "$@"; do
+        IFS='|' read -r name dep_list func <<< "$spec"
+        names+=("$name")
+        deps+=("$dep_list")
+        funcs+=("$func")
+        state[$name]=wait
+    done
+    for dep_list in "${deps[@]}"; do
+        local -a required=()
+        IFS=',' read -ra required <<< "$dep_list"
+        for name in "${required[@]}"; do
+            if [[ -z "${state[$name]:-}" ]]; then
+                log_error "Unknown step in dependencies: $name"
+                return 1
+            fi
+        done
+    done
+    (( max >= 1 )) || max=1
+
+    for name in "${names[@]}"; do
+        echo "ARCHINSTALL_LANE: wait $name"
+    done
+
+    local running=0 failed=0 i
+    while true; do
+        local progressed=1
+        while (( progressed )); do
+            progressed=0
+            for i in "${!names[@]}"; do
+                name="${names[$i]}"
+                [[ "${state[$name]}" == "wait" ]] || continue
+
+                local ready=1 blocked=0
+                local -a required=()
+                IFS=',' read -ra required <<< "${deps[$i]}"
+                for dep_list in "${required[@]}"; do
+                    case "${state[$dep_list]}" in
+                        done) ;;
+                        failed|skipped) blocked=1 ;;
+                        *) ready=0 ;;
+                    esac
+                done
+
+                if (( blocked )); then
+                    state[$name]=skipped
+                    echo "ARCHINSTALL_LANE: skipped $name"
+                    progressed=1
+                elif (( ready && running < max )); then
+                    state[$name]=start
+                    echo "ARCHINSTALL_LANE: start $name"
+                    _run_lane "$name" "${funcs[$i]}" &
+                    pid_step[$!]="$name"
+                    running=$(( running + 1 ))
+                    progressed=1
+                fi
+            done
+        done
+
+        if (( running == 0 )); then
+            break
+        fi
+
+        local finished_pid="" status=0
+        wait -n -p finished_pid "${!pid_step[@]}" || status=$?
+        name="${pid_step[$finished_pid]}"
+        unset "pid_step[$finished_pid]"
+        running=$(( running - 1 ))
+        if (( status == 0 )); then
+            state[$name]=done
+            echo "ARCHINSTALL_LANE: done $name"
+        else
+            state[$name]=failed
+            failed=1
+            echo "ARCHINSTALL_LANE: failed $name"
+            log_error "$name failed"
+        fi
+    done
+
+    for name in "${names[@]}"; do
+        if [[ "${state[$name]}" == "wait" ]]; then
+            echo "ARCHINSTALL_LANE: skipped $name"
+            log_error "Circular dependency: $name never became ready"
+            failed=1
+        fi
+    done
+    (( failed == 0 ))
+}
+
+# Run one step of run_parallel_phases with its output prefixed by its name
+_run_lane() {
+    local name="$1"
+    local func="$2"
+    set -o pipefail
+    if "$func" 2>&1 | while IFS= read -r line; do printf '[%s] %s\n' "$name" "$line"; done; then
+        exit 0
+    fi
+    exit 1
+}
+
 validate_username() {
     local user="$1"
     if [[ -z "$user" ]]; then return 1; fi
@@ -177,6 +374,320 @@ check_package_available() {
     pacman -Si "$1" >/dev/null 2>&1
 }
 
+# --- Pacman Configuration ---
+
+# Write ParallelDownloads, Color and VerbosePkgLists into [options]
+# Usage: set_pacman_options /etc/pacman.conf
+set_pacman_options() {
+    local conf="$1"
+    local parallel="${PARALLEL_DOWNLOADS:-5}"
+
+    if [[ ! "$parallel" =~ ^[0-9]+$ ]] || (( parallel < 1 || parallel > 50 )); then
+        log_warn "Invalid ParallelDownloads value '$parallel', using 5"
+        parallel=5
+    fi
+
+    local options="ParallelDownloads = $parallel"
+    if [[ "${PACMAN_COLOR:-Yes}" == "Yes" ]]; then
+        options+=$'\n'"Color"
+    fi
+    if [[ "${VERBOSE_PKG_LISTS:-No}" == "Yes" ]]; then
+        options+=$'\n'"VerbosePkgLists"
+    fi
+
+    # Drop existing (possibly commented) settings, then add ours after [options]
+    sed -i -E '/^#?(ParallelDownloads|Color|VerbosePkgLists)\b/d' "$conf"
+    awk -v opts="$options" '{ print } /^\[options\]/ { print opts }' "$conf" > "$conf.tmp"
+    mv "$conf.tmp" "$conf"
+}
+
+# Append CUSTOM_REPOSITORIES ("name|server[|key]" entries) to pacman.conf
+# Usage: add_custom_repositories /etc/pacman.conf
+add_custom_repositories() {
+    local conf="$1"
+    local -a entries=()
+    read -ra entries <<< "${CUSTOM_REPOSITORIES:-}"
+
+    local entry name server key_id sig_level
+    for entry in "${entries[@]}"; do
+        IFS='|' read -r name server key_id sig_level <<< "$entry"
+        if [[ -z "$name" || -z "$server" ]]; then
+            log_warn "Skipping malformed repository entry: $entry"
+            continue
+        fi
+        if grep -q "^\[$name\]" "$conf"; then
+            log_info "Repository [$name] already configured"
+            continue
+        fi
+
+        log_info "Adding repository [$name]"
+        # The SigLevel words are joined by commas in the entry
+        sig_level="${sig_level//,/ }"
+        if [[ -z "$key_id" && -z "$sig_level" ]]; then
+            log_warn "Repository [$name] has no signing key, packages will not be verified"
+            sig_level="Optional TrustAll"
+        fi
+        {
+            echo ""
+            echo "[$name]"
+            [[ -z "$sig_level" ]] || echo "SigLevel = $sig_level"
+            echo "Server = $server"
+        } >> "$conf"
+    done
+}
+
+# Import and locally sign the keys of CUSTOM_REPOSITORIES
+# Usage: import_repository_keys [command prefix...]  (e.g. arch-chroot /mnt)
+import_repository_keys() {
+    local -a prefix=("$@")
+    local -a entries=()
+    read -ra entries <<< "${CUSTOM_REPOSITORIES:-}"
+
+    local entry name server key_id
+    for entry in "${entries[@]}"; do
+        IFS='|' read -r name server key_id _ <<< "$entry"
+        [[ -n "$key_id" ]] || continue
+
+        log_info "Importing signing key $key_id for [$name]"
+        if ! "${prefix[@]}" pacman-key --recv-keys "$key_id" \
+            || ! "${prefix[@]}" pacman-key --lsign-key "$key_id"; then
+            log_error "Failed to import key $key_id for [$name]"
+            return 1
+        fi
+    done
+}
+
+# Lines around what use_package_cache adds to a pacman.conf
+PKG_CACHE_BEGIN="# BEGIN archinstall package cache"
+PKG_CACHE_END="# END archinstall package cache"
+
+# Name of the repository database in PKG_CACHE, empty for a plain package cache
+package_cache_repository() {
+    local db
+    for db in "${PKG_CACHE%/}"/*.db; do
+        if [[ -f "$db" ]]; then
+            basename "$db" .db
+            return 0
+        fi
+    done
+}
+
+# Make pacman prefer the packages in PKG_CACHE
+# Usage: use_package_cache <pacman.conf>
+# An offline repository (a NAME.db made by repo-add) is listed before every
+# other repository; a plain package cache becomes the first CacheDir.
+use_package_cache() {
+    local conf="$1"
+    local dir="${PKG_CACHE%/}"
+    local repo block
+    repo="$(package_cache_repository)"
+
+    forget_package_cache "$conf"
+    if [[ -n "$repo" ]]; then
+        log_info "Using offline repository [$repo] at $dir"
+        block="$PKG_CACHE_BEGIN"$'\n'"[$repo]"$'\n'"SigLevel = Optional TrustAll"$'\n'"Server = file://$dir"$'\n'"$PKG_CACHE_END"
+        BLOCK="$block" awk '!done && /^\[/ && $0 != "[options]" { print ENVIRON["BLOCK"]; done = 1 } { print }' \
+            "$conf" > "$conf.new"
+    else
+        log_info "Using package cache $dir"
+        block="$PKG_CACHE_BEGIN"$'\n'"CacheDir = $dir/"$'\n'"CacheDir = /var/cache/pacman/pkg/"$'\n'"$PKG_CACHE_END"
+        BLOCK="$block" awk '{ print } !done && $0 == "[options]" { print ENVIRON["BLOCK"]; done = 1 }' \
+            "$conf" > "$conf.new"
+    fi
+    cat "$conf.new" > "$conf"
+    rm -f "$conf.new"
+}
+
+# Remove what use_package_cache added to a pacman.conf
+forget_package_cache() {
+    sed -i "/^$PKG_CACHE_BEGIN\$/,/^$PKG_CACHE_END\$/d" "$1"
+}
+
+# Print the packages pacman finds in no repository, one per line
+# Usage: missing_packages <package>...
+missing_packages() {
+    pacman -Sp --print-format '%n' "$@" 2>&1 >/dev/null | sed -n 's/^error: target not found: //p'
+}
+
+# Gather the public keys named by SSH_KEYS into one authorized_keys file
+# Usage: collect_ssh_keys <output file>
+# Sources are github:USER, gitlab:USER or the absolute path of a key file.
+# Sources that cannot be read are reported and skipped; returns 1 if any was.
+collect_ssh_keys() {
+    local output="$1"
+    local list="${SSH_KEYS:-}"
+    local -a sources=()
+    read -ra sources <<< "${list//,/ }"
+
+    local source service user keys failed=0
+    : > "$output"
+    for source in "${sources[@]}"; do
+        if [[ "$source" == /* ]]; then
+            keys="$(cat "$source" 2>/dev/null)"
+        else
+            service="${source%%:*}"
+            user="${source#*:}"
+            case "${service,,}" in
+                github) keys="$(curl -fsSL --max-time 30 "https://github.com/$user.keys")" ;;
+                gitlab) keys="$(curl -fsSL --max-time 30 "https://gitlab.com/$user.keys")" ;;
+                *) keys="" ;;
+            esac
+        fi
+        keys="$(grep -E '^(ssh-|ecdsa-|sk-)' <<< "$keys")"
+        if [[ -z "$keys" ]]; then
+            log_error "No SSH keys found at $source"
+            failed=1
+            continue
+        fi
+        printf '%s\n' "$keys" >> "$output"
+        log_info "Imported $(wc -l <<< "$keys") SSH key(s) from $source"
+    done
+    return "$failed"
+}
+
+# Succeed when the running kernel is at least MAJOR.MINOR
+kernel_at_least() {
+    local want_major="$1"
+    local want_minor="$2"
+    local major minor
+    IFS=.- read -r major minor _ <<< "$(uname -r)"
+    minor="${minor%%[!0-9]*}"
+    (( major > want_major || (major == want_major && ${minor:-0} >= want_minor) ))
+}
+
+# --- Architecture ---
+# aarch64 installs Arch Linux ARM: its kernel package has another name, it
+# has no multilib or microcode, and boards without UEFI boot with U-Boot,
+# which reads /boot/extlinux/extlinux.conf (see src/arch.rs)
+
+# Architecture being installed, the one the installer runs on
+target_arch() {
+    echo "${TARGET_ARCH:-$(uname -m)}"
+}
+
+# Package of a KERNEL value for the target architecture; fails for kernels
+# Arch Linux ARM does not build
+kernel_package() {
+    local kernel="${1:-linux}"
+    if [[ "$(target_arch)" != "aarch64" ]]; then
+        echo "$kernel"
+    elif [[ "$kernel" == "linux" ]]; then
+        echo "linux-aarch64"
+    else
+        return 1
+    fi
+}
+
+# Kernel image and initramfs in /boot; linux-aarch64 installs Image and
+# keeps the initramfs name of linux
+kernel_image() {
+    if [[ "$(target_arch)" == "aarch64" ]]; then
+        echo "Image"
+    else
+        echo "vmlinuz-${1:-linux}"
+    fi
+}
+
+initramfs_image() {
+    if [[ "$(target_arch)" == "aarch64" ]]; then
+        echo "initramfs-linux.img"
+    else
+        echo "initramfs-${1:-linux}.img"
+    fi
+}
+
+# Suffix of the EFI binaries, as in grubx64.efi and BOOTAA64.EFI
+efi_suffix() {
+    if [[ "$(target_arch)" == "aarch64" ]]; then
+        echo "aa64"
+    else
+        echo "x64"
+    fi
+}
+
+# --- Microcode ---
+# MICROCODE names the package (intel-ucode, amd-ucode or none); auto picks
+# it from the vendor of the CPU installing the system (see src/hardware.rs)
+
+# Microcode package to install, nothing for none or an unknown CPU
+# (CPUINFO_FILE overrides /proc/cpuinfo for tests)
+microcode_package() {
+    local microcode="${MICROCODE:-auto}"
+    if [[ "$microcode" == "auto" ]]; then
+        case "$(awk -F': *' '/^vendor_id/ { print $2; exit }' "${CPUINFO_FILE:-/proc/cpuinfo}" 2>/dev/null)" in
+            GenuineIntel) microcode="intel-ucode" ;;
+            AuthenticAMD) microcode="amd-ucode" ;;
+            *) microcode="none" ;;
+        esac
+    fi
+    if [[ "$microcode" != "none" ]]; then
+        echo "$microcode"
+    fi
+}
+
+# --- Low Memory ---
+# The live ISO keeps its root filesystem in RAM, so below about 1 GiB a
+# normal install runs out of memory during pacstrap.
+
+# Below this much RAM (MiB) the low-memory strategy is used
+LOW_MEMORY_THRESHOLD_MIB=1024
+
+# Where archiso mounts the image it copied to RAM (copytoram)
+COPYTORAM_DIR="${COPYTORAM_DIR:-/run/archiso/copytoram}"
+
+# Succeed when the low-memory strategy applies: LOW_MEMORY=Yes or No
+# decides, anything else goes by the installed memory (get_memory_mib)
+low_memory_mode() {
+    case "${LOW_MEMORY:-Auto}" in
+        Yes) return 0 ;;
+        No) return 1 ;;
+    esac
+    local memory_mib
+    memory_mib="$(get_memory_mib)"
+    [[ -n "$memory_mib" ]] && (( memory_mib < LOW_MEMORY_THRESHOLD_MIB ))
+}
+
+# Succeed when the live ISO was copied to RAM at boot
+iso_copied_to_ram() {
+    [[ -n "$(ls -A "$COPYTORAM_DIR" 2>/dev/null)" ]]
+}
+
+# Checks before the configuration is read: decides LOW_MEMORY and warns
+# about what a low-memory install does differently
+perform_preflight_checks() {
+    if low_memory_mode; then
+        LOW_MEMORY=Yes
+        log_warn "Low memory ($(get_memory_mib) MiB): installing with the low-memory strategy"
+        log_warn "  zram swap for the live system, a smaller package set, one download at a time,"
+        log_warn "  and packages installed in batches with the package cache emptied in between"
+        if iso_copied_to_ram; then
+            log_warn "The ISO was copied to RAM; boot it with copytoram=n to leave that memory free"
+        fi
+    else
+        LOW_MEMORY=No
+    fi
+    export LOW_MEMORY
+}
+
+# Add swap on zram to the live system, sized to the installed memory
+enable_live_zram() {
+    if swapon --show=NAME --noheadings 2>/dev/null | grep -q '^/dev/zram'; then
+        log_info "The live system already swaps to zram"
+        return 0
+    fi
+    local device
+    if ! modprobe zram 2>/dev/null || ! device="$(zramctl --find --size "$(get_memory_mib)M")"; then
+        log_warn "Could not create a zram device, continuing without swap"
+        return 0
+    fi
+    if mkswap "$device" >/dev/null && swapon --priority 100 "$device"; then
+        log_success "Swapping to $device"
+    else
+        log_warn "Could not swap to $device, continuing without swap"
+        zramctl --reset "$device" 2>/dev/null || true
+    fi
+}
+
 format_filesystem() {
     local dev="$1"
     local fs="$2"
@@ -184,6 +695,15 @@ format_filesystem() {
         ext4) mkfs.ext4 -F "$dev" ;;
         btrfs) mkfs.btrfs -f "$dev" ;;
         xfs) mkfs.xfs -f "$dev" ;;
+        f2fs) mkfs.f2fs -f "$dev" ;;
+        bcachefs)
+            # Mainline bcachefs arrived in Linux 6.7
+            if ! kernel_at_least 6 7; then
+                log_error "bcachefs needs Linux 6.7 or newer (running $(uname -r))"
+                return 1
+            fi
+            bcachefs format -f "$dev"
+            ;;
         vfat|fat32) mkfs.fat -F32 "$dev" ;;
         swap) mkswap "$dev" ;;
         *) return 1 ;;
@@ -0,0 +1,811 @@
+//! Application actions
+//!
+//! Every key press is translated into an [`Action`] by [`Action::from_key`] and
+//! dispatched through `App::dispatch`. Actions that only change state are
+//! applied by the reducer ([`AppState::reduce`](super::AppState::reduce));
+//! the rest (running tools, starting the installer, loading files) are side
+//! effects handled by the app.
+
+use super::AppMode;
+use crate::components::floating_window::WindowChange;
+use crate::input;
+use crate::recovery::RecoveryChoice;
+use crate::tools::services::UnitAction;
+use crate::tools::smart::SelfTest;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Cursor movement within a list, menu or scrollable view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+/// Something the user asked the application to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Move the selection or scroll position
+    Navigate(Movement),
+    /// Activate the selected item
+    Select,
+    /// Return to the previous screen
+    Back,
+    /// Dismiss the current dialog without acting on it
+    Cancel,
+    /// Flip the Yes/No choice of a confirmation dialog
+    Toggle,
+    /// Exit the application
+    Quit,
+    /// Show or hide the help overlay
+    ToggleHelp,
+    /// Scroll the help overlay
+    ScrollHelp(Movement),
+    /// Show the help page of the previous configuration option
+    PreviousHelpPage,
+    /// Show the help page of the next configuration option
+    NextHelpPage,
+    /// Switch the help overlay between the option page and the keybindings
+    ToggleHelpKeys,
+    /// Switch the help overlay between the option page and its Arch Wiki article
+    ToggleWikiArticle,
+    /// Open the input dialog for the selected configuration option
+    OpenDialog,
+    /// A value was entered in the input dialog
+    SubmitValue(String),
+    /// Validate the configuration and ask to start the installation
+    StartInstall,
+    /// Restore the default value of the selected configuration option
+    ResetOption,
+    /// Ask to restore the default value of every configuration option
+    ResetAll,
+    /// Switch the guided installer between the option list and the wizard
+    ToggleWizard,
+    /// Type a character into the focused text field
+    InsertChar(char),
+    /// Delete the last character of the focused text field
+    DeleteChar,
+    /// Pick the next choice, flip the toggle or increment the number of the focused field
+    NextValue,
+    /// Pick the previous choice, flip the toggle or decrement the number of the focused field
+    PreviousValue,
+    /// Jump the file browser to the home directory
+    BrowseHome,
+    /// Jump the file browser to the filesystem root
+    BrowseRoot,
+    /// Start a SMART self-test on the inspected disk
+    SelfTest(SelfTest),
+    /// Re-read the SMART report of the inspected disk
+    RefreshDiskHealth,
+    /// Run the full reliability test script on the inspected disk
+    FullDiskTest,
+    /// Open the filter prompt of the service manager
+    FilterUnits,
+    /// Enable, disable, start, stop, mask or unmask the highlighted unit
+    ManageUnit(UnitAction),
+    /// List the units of the service manager's system again
+    RefreshServices,
+    /// Switch the service manager between the running and the installed system
+    SwitchServiceTarget,
+    /// Mount the highlighted device at its suggested mount point
+    MountDevice,
+    /// Ask where to mount the highlighted device
+    MountDeviceAt,
+    /// Unmount the highlighted device
+    UnmountDevice,
+    /// List the block devices of the mount manager again
+    RefreshMounts,
+    /// Check the manual partition layout again
+    RefreshPartitionCheck,
+    /// Give the highlighted partition the next mountpoint
+    CycleMountpoint,
+    /// Switch the highlighted partition between keeping and formatting
+    CycleFormat,
+    /// Start the program behind the highlighted tool in the embedded terminal
+    RunInteractively,
+    /// Leave the embedded terminal
+    ExitTerminal,
+    /// Suspend the TUI for a shell in the live system, /mnt or the chroot
+    OpenShell,
+    /// Open the search prompt of the installer output
+    SearchOutput,
+    /// Select the next match of the installer output search
+    NextMatch,
+    /// Select the previous match of the installer output search
+    PreviousMatch,
+    /// Show the next severity filter of the installer output
+    CycleOutputFilter,
+    /// Act on a failed installation phase
+    Recover(RecoveryChoice),
+    /// Save the installation summary next to the installation report
+    SaveInstallSummary,
+    /// Resize, maximize or dock the floating output window
+    ArrangeOutput(WindowChange),
+    /// Kill the installer command that stopped printing, optionally retrying its phase
+    KillHungCommand { retry: bool },
+}
+
+impl Action {
+    /// Translate a key press in the given mode into an action
+    ///
+    /// This is the single keymap of the application. Returns `None` for keys
+    /// that do nothing in this mode; the embedded terminal forwards those to
+    /// its PTY. Text input dialogs consume raw keys before this is consulted.
+    pub fn from_key(mode: &AppMode, help_visible: bool, key: KeyEvent) -> Option<Self> {
+        if *mode == AppMode::EmbeddedTerminal {
+            let exit =
+                key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('q');
+            return exit.then_some(Self::ExitTerminal);
+        }
+        // The shell is reachable from every other screen, under the help too
+        if Self::opens_shell(&key) {
+            return Some(Self::OpenShell);
+        }
+
+        // The help overlay swallows everything except its own browsing keys
+        if help_visible {
+            return match key.code {
+                KeyCode::Char('?') | KeyCode::F(1) | KeyCode::Esc => Some(Self::ToggleHelp),
+                KeyCode::Up => Some(Self::ScrollHelp(Movement::Up)),
+                KeyCode::Down => Some(Self::ScrollHelp(Movement::Down)),
+                KeyCode::PageUp => Some(Self::ScrollHelp(Movement::PageUp)),
+                KeyCode::PageDown => Some(Self::ScrollHelp(Movement::PageDown)),
+                KeyCode::Home => Some(Self::ScrollHelp(Movement::First)),
+                KeyCode::End => Some(Self::ScrollHelp(Movement::Last)),
+                KeyCode::Left => Some(Self::PreviousHelpPage),
+                KeyCode::Right => Some(Self::NextHelpPage),
+                KeyCode::Tab => Some(Self::ToggleHelpKeys),
+                KeyCode::Char('w') | KeyCode::Char('W') => Some(Self::ToggleWikiArticle),
+                _ => None,
+            };
+        }
+        if matches!(key.code, KeyCode::Char('?') | KeyCode::F(1)) {
+            return Some(Self::ToggleHelp);
+        }
+
+        let action = match mode {
+            AppMode::ToolDialog => match key.code {
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                // Tab and Shift+Tab move between fields like Down and Up
+                KeyCode::Tab => Self::Navigate(Movement::Down),
+                KeyCode::BackTab => Self::Navigate(Movement::Up),
+                KeyCode::Left => Self::PreviousValue,
+                KeyCode::Right => Self::NextValue,
+                KeyCode::Enter => Self::Select,
+                KeyCode::Esc => Self::Cancel,
+                KeyCode::Char(c) if input::is_text_key(&key) => Self::InsertChar(c),
+                KeyCode::Backspace => Self::DeleteChar,
+                _ => return None,
+            },
+            AppMode::Summary => match key.code {
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Enter => Self::Select,
+                KeyCode::Esc => Self::Back,
+                KeyCode::Char(c) if input::is_text_key(&key) => Self::InsertChar(c),
+                KeyCode::Backspace => Self::DeleteChar,
+                _ => return None,
+            },
+            AppMode::FloatingOutput => match key.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('b') | KeyCode::Char('B') => {
+                    Self::Back
+                }
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::Char('+') | KeyCode::Char('=') => Self::ArrangeOutput(WindowChange::Grow),
+                KeyCode::Char('-') => Self::ArrangeOutput(WindowChange::Shrink),
+                KeyCode::Char('m') | KeyCode::Char('M') => {
+                    Self::ArrangeOutput(WindowChange::Maximize)
+                }
+                KeyCode::Char('d') | KeyCode::Char('D') => Self::ArrangeOutput(WindowChange::Dock),
+                _ => return None,
+            },
+            AppMode::FileBrowser => match key.code {
+                KeyCode::Esc => Self::Cancel,
+                KeyCode::Enter => Self::Select,
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::Char('~') => Self::BrowseHome,
+                KeyCode::Char('/') => Self::BrowseRoot,
+                _ => return None,
+            },
+            AppMode::PartitionCheck => match key.code {
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Char('r') | KeyCode::Char('R') => Self::RefreshPartitionCheck,
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('b') | KeyCode::Char('B') => {
+                    Self::Back
+                }
+                _ => return None,
+            },
+            AppMode::PartitionMounts => match key.code {
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Char('m') | KeyCode::Char('M') => Self::CycleMountpoint,
+                KeyCode::Char('f') | KeyCode::Char('F') => Self::CycleFormat,
+                KeyCode::Enter => Self::Select,
+                KeyCode::Esc => Self::Back,
+                _ => return None,
+            },
+            AppMode::DiskHealth => match key.code {
+                KeyCode::Char('s') | KeyCode::Char('S') => Self::SelfTest(SelfTest::Short),
+                KeyCode::Char('l') | KeyCode::Char('L') => Self::SelfTest(SelfTest::Long),
+                KeyCode::Char('r') | KeyCode::Char('R') => Self::RefreshDiskHealth,
+                KeyCode::Char('f') | KeyCode::Char('F') => Self::FullDiskTest,
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('b') | KeyCode::Char('B') => {
+                    Self::Back
+                }
+                _ => return None,
+            },
+            AppMode::ServiceManager => match key.code {
+                KeyCode::Char('/') => Self::FilterUnits,
+                KeyCode::Char('e') | KeyCode::Char('E') => Self::ManageUnit(UnitAction::Enable),
+                KeyCode::Char('d') | KeyCode::Char('D') => Self::ManageUnit(UnitAction::Disable),
+                KeyCode::Char('s') | KeyCode::Char('S') => Self::ManageUnit(UnitAction::Start),
+                KeyCode::Char('x') | KeyCode::Char('X') => Self::ManageUnit(UnitAction::Stop),
+                KeyCode::Char('m') | KeyCode::Char('M') => Self::ManageUnit(UnitAction::Mask),
+                KeyCode::Char('u') | KeyCode::Char('U') => Self::ManageUnit(UnitAction::Unmask),
+                KeyCode::Char('r') | KeyCode::Char('R') => Self::RefreshServices,
+                KeyCode::Char('c') | KeyCode::Char('C') => Self::SwitchServiceTarget,
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Home => Self::Navigate(Movement::First),
+                KeyCode::End => Self::Navigate(Movement::Last),
+                KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('B') => Self::Back,
+                _ => return None,
+            },
+            AppMode::MountManager => match key.code {
+                KeyCode::Char('m') | KeyCode::Char('M') => Self::MountDevice,
+                KeyCode::Char('p') | KeyCode::Char('P') => Self::MountDeviceAt,
+                KeyCode::Char('u') | KeyCode::Char('U') => Self::UnmountDevice,
+                KeyCode::Char('r') | KeyCode::Char('R') => Self::RefreshMounts,
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Home => Self::Navigate(Movement::First),
+                KeyCode::End => Self::Navigate(Movement::Last),
+                KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('B') => Self::Back,
+                _ => return None,
+            },
+            AppMode::Installation => match key.code {
+                KeyCode::Char('q') => Self::Quit,
+                KeyCode::Char('b') | KeyCode::Char('B') => Self::Back,
+                KeyCode::Char('/') => Self::SearchOutput,
+                KeyCode::Char('n') => Self::NextMatch,
+                KeyCode::Char('N') => Self::PreviousMatch,
+                KeyCode::Char('f') | KeyCode::Char('F') => Self::CycleOutputFilter,
+                KeyCode::Char('k') | KeyCode::Char('K') => Self::KillHungCommand { retry: false },
+                KeyCode::Char('r') | KeyCode::Char('R') => Self::KillHungCommand { retry: true },
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Home => Self::Navigate(Movement::First),
+                KeyCode::End => Self::Navigate(Movement::Last),
+                _ => return None,
+            },
+            AppMode::Complete => match key.code {
+                KeyCode::Char('q') | KeyCode::Enter => Self::Quit,
+                KeyCode::Char('b') | KeyCode::Char('B') => Self::Back,
+                KeyCode::Char('w') | KeyCode::Char('W') => Self::SaveInstallSummary,
+                _ => return None,
+            },
+            AppMode::RecoveryDialog => match key.code {
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::Enter => Self::Select,
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Self::Recover(RecoveryChoice::from_key(c)?)
+                }
+                _ => return None,
+            },
+            AppMode::ConfirmDialog => match key.code {
+                KeyCode::Left | KeyCode::Right | KeyCode::Tab => Self::Toggle,
+                KeyCode::Enter => Self::Select,
+                KeyCode::Esc => Self::Cancel,
+                _ => return None,
+            },
+            _ => match key.code {
+                KeyCode::Char('q') => Self::Quit,
+                KeyCode::Char('b') | KeyCode::Char('B') => Self::Back,
+                KeyCode::Char(' ') if *mode == AppMode::GuidedInstaller => Self::StartInstall,
+                KeyCode::Char('r') if *mode == AppMode::GuidedInstaller => Self::ResetOption,
+                KeyCode::Char('R') if *mode == AppMode::GuidedInstaller => Self::ResetAll,
+                KeyCode::Char('w') | KeyCode::Char('W') if *mode == AppMode::GuidedInstaller => {
+                    Self::ToggleWizard
+                }
+                KeyCode::Char('i') | KeyCode::Char('I')
+                    if matches!(
+                        mode,
+                        AppMode::DiskTools
+                            | AppMode::SystemTools
+                            | AppMode::UserTools
+                            | AppMode::NetworkTools
+                            | AppMode::SnapshotTools
+                    ) =>
+                {
+                    Self::RunInteractively
+                }
+                // The wizard's Prev/Next; the option list moves the same way
+                KeyCode::Left if *mode == AppMode::GuidedInstaller => Self::Navigate(Movement::Up),
+                KeyCode::Right if *mode == AppMode::GuidedInstaller => {
+                    Self::Navigate(Movement::Down)
+                }
+                KeyCode::Up => Self::Navigate(Movement::Up),
+                KeyCode::Down => Self::Navigate(Movement::Down),
+                KeyCode::PageUp => Self::Navigate(Movement::PageUp),
+                KeyCode::PageDown => Self::Navigate(Movement::PageDown),
+                KeyCode::Home => Self::Navigate(Movement::First),
+                KeyCode::End => Self::Navigate(Movement::Last),
+                KeyCode::Enter => Self::Select,
+                _ => return None,
+            },
+        };
+        Some(action)
+    }
+
+    /// Whether the key opens a shell (Ctrl+T)
+    ///
+    /// Every screen but the embedded terminal takes it, including text
+    /// fields and prompts, which check it before they consume raw keys.
+    pub fn opens_shell(key: &KeyEvent) -> bool {
+        key.modifiers == KeyModifiers::CONTROL && matches!(key.code, KeyCode::Char('t' | 'T'))
+    }
+
+    /// Translate a key press while a search or filter prompt is open
+    ///
+    /// Used by the installer output's search and the service manager's filter.
+    pub fn from_search_key(key: KeyEvent) -> Option<Self> {
+        match key.code {
+            KeyCode::Enter => Some(Self::Select),
+            KeyCode::Esc => Some(Self::Cancel),
+            KeyCode::Char(c) if input::is_text_key(&key) => Some(Self::InsertChar(c)),
+            KeyCode::Backspace => Some(Self::DeleteChar),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_menu_keys() {
+        let mode = AppMode::MainMenu;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Up)),
+            Some(Action::Navigate(Movement::Up))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::End)),
+            Some(Action::Navigate(Movement::Last))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Select)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('B'))),
+            Some(Action::Back)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('x'))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wizard_keys() {
+        let mode = AppMode::GuidedInstaller;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('w'))),
+            Some(Action::ToggleWizard)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Right)),
+            Some(Action::Navigate(Movement::Down))
+        );
+        assert_eq!(
+            Action::from_key(&AppMode::MainMenu, false, key(KeyCode::Left)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_space_starts_install_only_in_guided_installer() {
+        assert_eq!(
+            Action::from_key(&AppMode::GuidedInstaller, false, key(KeyCode::Char(' '))),
+            Some(Action::StartInstall)
+        );
+        assert_eq!(
+            Action::from_key(&AppMode::ToolsMenu, false, key(KeyCode::Char(' '))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reset_keys_only_in_guided_installer() {
+        let mode = AppMode::GuidedInstaller;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('r'))),
+            Some(Action::ResetOption)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('R'))),
+            Some(Action::ResetAll)
+        );
+        assert_eq!(
+            Action::from_key(&AppMode::MainMenu, false, key(KeyCode::Char('r'))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_help_overlay_swallows_keys() {
+        let mode = AppMode::GuidedInstaller;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('?'))),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::Esc)),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(Action::from_key(&mode, true, key(KeyCode::Char('q'))), None);
+    }
+
+    #[test]
+    fn test_help_browser_keys() {
+        let mode = AppMode::GuidedInstaller;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::F(1))),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::F(1))),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::PageDown)),
+            Some(Action::ScrollHelp(Movement::PageDown))
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::Right)),
+            Some(Action::NextHelpPage)
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::Tab)),
+            Some(Action::ToggleHelpKeys)
+        );
+        assert_eq!(
+            Action::from_key(&mode, true, key(KeyCode::Char('W'))),
+            Some(Action::ToggleWikiArticle)
+        );
+        assert_eq!(Action::from_key(&mode, true, key(KeyCode::Enter)), None);
+    }
+
+    #[test]
+    fn test_tool_dialog_types_characters() {
+        let mode = AppMode::ToolDialog;
+        // 'q' and 'b' are text here, not quit/back
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            Some(Action::InsertChar('q'))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('b'))),
+            Some(Action::InsertChar('b'))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Backspace)),
+            Some(Action::DeleteChar)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Esc)),
+            Some(Action::Cancel)
+        );
+    }
+
+    #[test]
+    fn test_tool_dialog_form_keys() {
+        let mode = AppMode::ToolDialog;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Tab)),
+            Some(Action::Navigate(Movement::Down))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::BackTab)),
+            Some(Action::Navigate(Movement::Up))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Left)),
+            Some(Action::PreviousValue)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Right)),
+            Some(Action::NextValue)
+        );
+    }
+
+    #[test]
+    fn test_summary_types_confirmation() {
+        let mode = AppMode::Summary;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('E'))),
+            Some(Action::InsertChar('E'))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            Some(Action::InsertChar('q'))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Select)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_confirm_dialog_ignores_quit() {
+        let mode = AppMode::ConfirmDialog;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            None
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Tab)),
+            Some(Action::Toggle)
+        );
+    }
+
+    #[test]
+    fn test_embedded_terminal_only_maps_exit_chord() {
+        let mode = AppMode::EmbeddedTerminal;
+        let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(
+            Action::from_key(&mode, false, ctrl_q),
+            Some(Action::ExitTerminal)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            None
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('?'))),
+            None
+        );
+        // The shell in the terminal gets Ctrl+T
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert_eq!(Action::from_key(&mode, false, ctrl_t), None);
+    }
+
+    #[test]
+    fn test_shell_opens_from_every_other_screen() {
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        for mode in [
+            AppMode::MainMenu,
+            AppMode::GuidedInstaller,
+            AppMode::ToolDialog,
+            AppMode::ConfirmDialog,
+            AppMode::Installation,
+            AppMode::RecoveryDialog,
+        ] {
+            for help_visible in [false, true] {
+                assert_eq!(
+                    Action::from_key(&mode, help_visible, ctrl_t),
+                    Some(Action::OpenShell),
+                    "{:?}",
+                    mode
+                );
+            }
+        }
+        // Plain t is text, or the recovery dialog's shell at /mnt
+        assert!(!Action::opens_shell(&key(KeyCode::Char('t'))));
+        assert_eq!(
+            Action::from_key(&AppMode::ToolDialog, false, key(KeyCode::Char('t'))),
+            Some(Action::InsertChar('t'))
+        );
+    }
+
+    #[test]
+    fn test_run_interactively_only_in_tool_menus() {
+        assert_eq!(
+            Action::from_key(&AppMode::DiskTools, false, key(KeyCode::Char('i'))),
+            Some(Action::RunInteractively)
+        );
+        assert_eq!(
+            Action::from_key(&AppMode::NetworkTools, false, key(KeyCode::Char('I'))),
+            Some(Action::RunInteractively)
+        );
+        assert_eq!(
+            Action::from_key(&AppMode::ToolsMenu, false, key(KeyCode::Char('i'))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_installation_output_keys() {
+        let mode = AppMode::Installation;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('/'))),
+            Some(Action::SearchOutput)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('N'))),
+            Some(Action::PreviousMatch)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::End)),
+            Some(Action::Navigate(Movement::Last))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('R'))),
+            Some(Action::KillHungCommand { retry: true })
+        );
+        // The search prompt takes 'q' as text
+        assert_eq!(
+            Action::from_search_key(key(KeyCode::Char('q'))),
+            Some(Action::InsertChar('q'))
+        );
+        assert_eq!(
+            Action::from_search_key(key(KeyCode::Esc)),
+            Some(Action::Cancel)
+        );
+    }
+
+    #[test]
+    fn test_disk_health_keys() {
+        let mode = AppMode::DiskHealth;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('L'))),
+            Some(Action::SelfTest(SelfTest::Long))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_partition_check_keys() {
+        let mode = AppMode::PartitionCheck;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('r'))),
+            Some(Action::RefreshPartitionCheck)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_partition_mounts_keys() {
+        let mode = AppMode::PartitionMounts;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('m'))),
+            Some(Action::CycleMountpoint)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('F'))),
+            Some(Action::CycleFormat)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Select)
+        );
+    }
+
+    #[test]
+    fn test_service_manager_keys() {
+        let mode = AppMode::ServiceManager;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('e'))),
+            Some(Action::ManageUnit(UnitAction::Enable))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('x'))),
+            Some(Action::ManageUnit(UnitAction::Stop))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('/'))),
+            Some(Action::FilterUnits)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_mount_manager_keys() {
+        let mode = AppMode::MountManager;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('m'))),
+            Some(Action::MountDevice)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('p'))),
+            Some(Action::MountDeviceAt)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('U'))),
+            Some(Action::UnmountDevice)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_floating_output_layout_keys() {
+        let mode = AppMode::FloatingOutput;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('='))),
+            Some(Action::ArrangeOutput(WindowChange::Grow))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('D'))),
+            Some(Action::ArrangeOutput(WindowChange::Dock))
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Back)
+        );
+    }
+
+    #[test]
+    fn test_complete_keys() {
+        let mode = AppMode::Complete;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('W'))),
+            Some(Action::SaveInstallSummary)
+        );
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Enter)),
+            Some(Action::Quit)
+        );
+        assert_eq!(Action::from_key(&mode, false, key(KeyCode::Up)), None);
+    }
+
+    #[test]
+    fn test_recovery_dialog_keys() {
+        let mode = AppMode::RecoveryDialog;
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('t'))),
+            Some(Action::Recover(RecoveryChoice::Shell))
+        );
+        // Quitting would leave the installer waiting
+        assert_eq!(
+            Action::from_key(&mode, false, key(KeyCode::Char('q'))),
+            None
+        );
+        assert_eq!(Action::from_key(&mode, false, key(KeyCode::Esc)), None);
+    }
+}
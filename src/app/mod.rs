@@ -4,34 +4,90 @@
 //!
 //! # Module Structure
 //! - `state` - Application state types (AppState, AppMode, ToolDialogState, etc.)
+//! - `action` - The Action enum and the key-to-action keymap
+//! - `reducer` - State-only transitions (`AppState::reduce`)
+//! - `transition` - Allowed mode changes and the state each mode needs
+//! - `store` - State updates sent by background threads to the main loop
 //! - Main module - App struct and event loop
 
+pub mod action;
+mod reducer;
 mod state;
+mod store;
+mod transition;
 
 // Re-export state types for external use
+pub use action::Action;
 pub use state::{AppMode, AppState, ToolDialogState, ToolParam, ToolParameter};
+pub use store::{StateHandle, StateUpdate};
 
+use crate::clone;
 use crate::components::confirm_dialog::{
-    format_partition_confirm, start_install_confirm, wipe_disk_confirm,
+    automated_install_confirm, bootloader_repair_confirm, cleanup_confirm, format_partition_confirm,
+    resize_partition_confirm, restore_session_confirm, root_required_confirm, snapshot_delete_confirm,
+    snapshot_rollback_confirm, wipe_disk_confirm,
 };
+use crate::components::disk_health::DiskHealthState;
 use crate::components::floating_window::FloatingOutputState;
+use crate::components::install_summary::InstallSummaryState;
 use crate::components::keybindings::KeybindingContext;
+use crate::components::mount_manager::MountManagerState;
+use crate::components::partition_check::PartitionCheckState;
+use crate::components::partition_mounts::PartitionMountsState;
 use crate::components::pty_terminal::{PtyTerminal, PtyTerminalState};
-use crate::config::Configuration;
-use crate::error;
+use crate::components::service_manager::ServiceManagerState;
+use crate::config::options::{self, Input};
+use crate::config::{Configuration, Validity};
+use crate::error::{self, ArchInstallError};
+use crate::hardware::Hypervisor;
+use crate::help;
 use crate::input::InputHandler;
+use crate::install_metrics;
 use crate::installer::Installer;
+use crate::keylog;
+use crate::partition_check;
+use crate::preseed::{self, Preseed};
+use crate::privilege;
+use crate::recovery::{self, RecoveryChoice};
 use crate::process_guard::{ChildRegistry, CommandProcessGroup, ProcessGuard};
-use crate::ui::UiRenderer;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crate::proxy::Proxy;
+use crate::serial;
+use crate::script_env::ScriptArgs;
+use crate::self_update;
+use crate::session;
+use crate::shell::ShellContext;
+use crate::simulate;
+use crate::theme::Charset;
+use crate::tools::format::FormatOptions;
+use crate::tools::services::{self, Target, UnitAction};
+use crate::tools::interactive::{self, Interactive};
+use crate::tools::{bootrepair, cleanup, mounts, rescue, resize};
+use crate::tools::smart::{self, SelfTest};
+use crate::tools::snapshots;
+use crate::tools::wipe::{self, Progress, WipeMethod};
+use crate::types::{
+    BtrfsProfile, ExistingOsPolicy, FormatFilesystem, GuestTools, Ipv6Mode, Microcode,
+    NetworkBackend, PartitionScheme, Toggle,
+};
+use crate::ui::{menus, UiRenderer};
+use crate::watchdog::{self, TimeoutPolicy};
+use crate::wiki;
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
 use log::{debug, info};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
 use std::io::{BufRead, BufReader, Write};
 use std::process::{Command, Stdio};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+/// Shown when an installation is started without root privileges
+const INSTALL_NEEDS_ROOT: &str =
+    "Installing needs root privileges: restart with sudo archinstall-tui";
 
 /// Messages sent from tool execution threads to the main UI thread
 #[derive(Debug)]
@@ -40,6 +96,8 @@ pub enum ToolMessage {
     Stdout(String),
     /// A line of stderr output
     Stderr(String),
+    /// Bytes processed so far by a tool that reports progress
+    Progress(Progress),
     /// Tool execution completed successfully
     Complete { success: bool, exit_code: Option<i32> },
     /// Tool execution failed to start
@@ -48,7 +106,12 @@ pub enum ToolMessage {
 
 /// Main application struct
 pub struct App {
-    state: Arc<Mutex<AppState>>,
+    /// Application state, owned by the main loop
+    state: AppState,
+    /// Handle background threads send state updates through (cloned to threads)
+    state_tx: StateHandle,
+    /// State updates from background threads (applied in the main loop)
+    state_rx: Receiver<StateUpdate>,
     installer: Option<Installer>,
     ui_renderer: UiRenderer,
     input_handler: InputHandler,
@@ -61,34 +124,35 @@ pub struct App {
     tool_tx: Sender<ToolMessage>,
     /// Channel receiver for tool execution output (polled in main loop)
     tool_rx: Receiver<ToolMessage>,
+    /// Session file the guided configuration is auto-saved to (None = disabled)
+    session_path: Option<std::path::PathBuf>,
+    /// Option values as of the last session save, used to detect changes
+    session_snapshot: Vec<String>,
+    /// External directory the installation report is copied to (--report)
+    report_dir: Option<std::path::PathBuf>,
+    /// Timeouts after which a quiet installer command counts as hung
+    timeout_policy: TimeoutPolicy,
+    /// Launcher and tool to restart as root with, once the main loop exits
+    root_restart: Option<(&'static str, String)>,
+    /// Keystroke file the session is recorded to (--record)
+    recorder: Option<keylog::Recorder>,
+    /// Keystrokes fed in before the keyboard (--replay)
+    replay: Option<keylog::Replay>,
+    /// Running on a serial console (--serial)
+    serial: bool,
+    /// The screen holds output the next frame must paint over completely
+    screen_stale: bool,
     /// Process guard for child process lifecycle management
     /// Ensures all spawned bash scripts are terminated when App is dropped
     _process_guard: ProcessGuard,
 }
 
 impl App {
-    /// Helper function to safely lock the state mutex
-    fn lock_state(
-        &self,
-    ) -> Result<std::sync::MutexGuard<'_, AppState>, Box<dyn std::error::Error>> {
-        self.state
-            .lock()
-            .map_err(|e| error::general_error(format!("Mutex poisoned: {}", e)).into())
-    }
-
-    /// Helper function to safely lock the state mutex mutably
-    fn lock_state_mut(
-        &self,
-    ) -> Result<std::sync::MutexGuard<'_, AppState>, Box<dyn std::error::Error>> {
-        self.state
-            .lock()
-            .map_err(|e| error::general_error(format!("Mutex poisoned: {}", e)).into())
-    }
-
     /// Create a new application instance
     pub fn new(save_config_path: Option<std::path::PathBuf>) -> Self {
         info!("Creating new App instance");
         let (tool_tx, tool_rx) = mpsc::channel();
+        let (state_tx, state_rx) = StateHandle::channel();
 
         // ProcessGuard ensures all child processes are killed when App is dropped
         // This prevents orphaned bash scripts continuing after TUI crash
@@ -96,7 +160,9 @@ impl App {
         debug!("ProcessGuard initialized for child process tracking");
 
         Self {
-            state: Arc::new(Mutex::new(AppState::default())),
+            state: AppState::default(),
+            state_tx,
+            state_rx,
             installer: None,
             ui_renderer: UiRenderer::new(),
             input_handler: InputHandler::new(),
@@ -105,90 +171,455 @@ impl App {
             keybinding_context: KeybindingContext::new(),
             tool_tx,
             tool_rx,
+            session_path: None,
+            session_snapshot: Vec::new(),
+            report_dir: None,
+            timeout_policy: TimeoutPolicy::default(),
+            root_restart: None,
+            recorder: None,
+            replay: None,
+            serial: false,
+            screen_stale: false,
             _process_guard: process_guard,
         }
     }
 
+    /// Render with the given character set, e.g. ASCII on a serial console
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.ui_renderer = UiRenderer::with_charset(charset);
+    }
+
+    /// Tune drawing and keys for a serial console (--serial)
+    ///
+    /// See [`serial`] for what changes.
+    pub fn set_serial(&mut self, serial: bool) {
+        self.serial = serial;
+        self.keybinding_context.set_serial(serial);
+    }
+
+    /// Copy the installation report to this directory once the install finishes
+    pub fn set_report_dir(&mut self, dir: std::path::PathBuf) {
+        self.report_dir = Some(dir);
+    }
+
+    /// Offer to kill installer commands that stay quiet longer than these timeouts
+    pub fn set_timeout_policy(&mut self, policy: TimeoutPolicy) {
+        self.timeout_policy = policy;
+    }
+
+    /// Record whether the process runs as root; tools needing root are locked otherwise
+    pub fn set_privileged(&mut self, privileged: bool) {
+        let state = &mut self.state;
+        state.privileged = privileged;
+        if !privileged {
+            state
+                .status
+                .warn("Running without root: tools marked with a lock need root");
+        }
+    }
+
+    /// Open the tools submenu holding `tool` and run it, as after restarting as root
+    pub fn open_tool(&mut self, tool: &str) -> error::Result<()> {
+        let Some((mode, selection)) = menus::find_tool(tool) else {
+            self.state.status.error(format!("Unknown tool: {}", tool));
+            return Ok(());
+        };
+        {
+            let state = &mut self.state;
+            state.transition(mode.clone());
+            state.menu.tools_selection = selection;
+        }
+        self.execute_tool(&mode, selection)
+    }
+
+    /// Launcher and tool to restart as root with, if the user asked for it
+    ///
+    /// Set when the main loop exits for that reason; the caller restores
+    /// the terminal and then calls [`privilege::restart_as_root`].
+    pub fn take_root_restart(&mut self) -> Option<(&'static str, String)> {
+        self.root_restart.take()
+    }
+
+    /// Record the key presses of the session (--record)
+    pub fn record_keys(&mut self, recorder: keylog::Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Feed recorded key presses in with their timing before the keyboard (--replay)
+    pub fn replay_keys(&mut self, replay: keylog::Replay) {
+        info!("Replaying recorded keystrokes");
+        self.replay = Some(replay);
+    }
+
+    /// Preselect the guest tools matching the detected hypervisor
+    ///
+    /// On bare metal (`None`) the configuration is left untouched.
+    pub fn propose_guest_tools(&mut self, hypervisor: Option<Hypervisor>) {
+        let Some(hypervisor) = hypervisor else {
+            return;
+        };
+        let tools = hypervisor.guest_tools();
+        info!(
+            "Detected {} virtual machine, proposing {} guest tools (services: {})",
+            hypervisor,
+            tools,
+            tools.services().join(" ")
+        );
+
+        let state = &mut self.state;
+        if let Some(option) = state
+            .guided
+            .config
+            .options
+            .iter_mut()
+            .find(|opt| opt.name == "VM Guest Tools")
+        {
+            option.default_value = tools.to_string();
+        }
+        state.status.info(if tools == GuestTools::None {
+            format!("Running in a {} virtual machine", hypervisor)
+        } else {
+            format!(
+                "Running in a {} virtual machine - guest tools preselected: {}",
+                hypervisor,
+                tools.packages().join(" ")
+            )
+        });
+    }
+
+    /// Show the microcode package of the detected CPU instead of Auto
+    ///
+    /// The installer would pick the same one; the entry is there so the
+    /// choice can be seen and overridden.
+    pub fn propose_microcode(&mut self, microcode: Microcode) {
+        info!("Detected CPU microcode: {}", microcode);
+        if let Some(option) = self.state.guided.config.option_mut("CPU Microcode") {
+            option.default_value = microcode.to_string();
+        }
+    }
+
+    /// Preselect a power manager on laptops
+    ///
+    /// The proposal suits the desktop environment configured at startup.
+    pub fn propose_power_management(&mut self, laptop: bool) {
+        if !laptop {
+            return;
+        }
+        let state = &mut self.state;
+        let desktop = state
+            .guided
+            .config
+            .options
+            .iter()
+            .find(|opt| opt.name == "Desktop Environment")
+            .and_then(|opt| opt.get_value().parse().ok())
+            .unwrap_or_default();
+        let power = crate::power::proposed(desktop);
+        info!("Detected a laptop, proposing {} power management", power);
+
+        if let Some(option) = state
+            .guided
+            .config
+            .options
+            .iter_mut()
+            .find(|opt| opt.name == "Power Management")
+        {
+            option.default_value = power.to_string();
+        }
+        state.status.info(format!(
+            "Laptop detected - power management preselected: {}",
+            power
+        ));
+    }
+
+    /// Preset the proxy options to the proxy the installer was started with
+    pub fn use_proxy(&mut self, proxy: &Proxy) {
+        if proxy.is_empty() {
+            return;
+        }
+        let state = &mut self.state;
+        for (name, value) in [
+            ("HTTP Proxy", &proxy.http),
+            ("HTTPS Proxy", &proxy.https),
+            ("No Proxy", &proxy.no_proxy),
+        ] {
+            if let Some(option) = state
+                .guided
+                .config
+                .options
+                .iter_mut()
+                .find(|opt| opt.name == name)
+            {
+                option.default_value = value.clone();
+            }
+        }
+        state.status.info(format!(
+            "Downloading through proxy {}",
+            proxy.redacted().https()
+        ));
+    }
+
+    /// Look for a newer installer release in the background
+    ///
+    /// The main menu shows a banner once one is found. Failures, such as no
+    /// network yet, are only logged.
+    pub fn check_for_update(&mut self) {
+        let app_state = self.state_handle();
+        thread::spawn(move || match self_update::check() {
+            Ok(Some(release)) => {
+                info!("Installer {} is available", release.version);
+                app_state.update(move |state| {
+                    state.update = Some(release);
+                });
+            }
+            Ok(None) => debug!("Installer is up to date"),
+            Err(e) => debug!("Update check failed: {}", e),
+        });
+    }
+
+    /// Preset the Package Cache option to the directory given with `--pkg-cache`
+    pub fn use_package_cache(&mut self, dir: &std::path::Path) {
+        let state = &mut self.state;
+        if let Some(option) = state
+            .guided
+            .config
+            .options
+            .iter_mut()
+            .find(|opt| opt.name == "Package Cache")
+        {
+            option.default_value = dir.display().to_string();
+        }
+        if let Ok(source) = crate::pkg_cache::inspect(dir) {
+            state
+                .status
+                .info(format!("Installing from {}", source.describe()));
+        }
+    }
+
+    /// Open the guided installer's wizard on the options a config file leaves
+    /// unset (`--interactive-missing`)
+    ///
+    /// The values of the file are filled in and the wizard skips them, unless
+    /// they are invalid.
+    pub fn preseed(&mut self, preseed: &Preseed) {
+        let state = &mut self.state;
+        let mut preseeded = preseed::apply_preseed(&mut state.guided.config, preseed);
+        let set = preseeded.len();
+        // The Region quick setup would overwrite what the file sets
+        if preseeded
+            .iter()
+            .any(|name| crate::region::FILLS.contains(&name.as_str()))
+        {
+            preseeded.push("Region".to_string());
+        }
+        state.guided.preseeded = preseeded;
+        state.transition(AppMode::GuidedInstaller);
+        state.guided.wizard = true;
+        state.guided.scroll.set_selected(0);
+        let steps = state.guided.wizard_steps();
+        if !steps.contains(&0) {
+            state.guided.wizard_step(true);
+        }
+        // The start button is the last step
+        let summary = format!(
+            "{} option(s) set by the config file, {} left to answer",
+            set,
+            steps.len() - 1
+        );
+        info!("{}", summary);
+        if preseed.warnings.is_empty() {
+            state.status.info(summary);
+        } else {
+            for warning in &preseed.warnings {
+                log::warn!("{}", warning);
+            }
+            state
+                .status
+                .warn(format!("{}. {}", summary, preseed.warnings.join(". ")));
+        }
+    }
+
+    /// Enable auto-saving of the guided configuration to a session file
+    ///
+    /// If the file already holds a saved session, a dialog offers to restore it.
+    pub fn enable_session_persistence(&mut self, path: std::path::PathBuf) {
+        let state = &mut self.state;
+        self.session_snapshot = state
+            .guided
+            .config
+            .options
+            .iter()
+            .map(|o| o.value.clone())
+            .collect();
+
+        match session::load_session(&path) {
+            Ok(values) if !values.is_empty() => {
+                info!(
+                    "Found saved session at {:?} ({} options)",
+                    path,
+                    values.len()
+                );
+                let back_to = state.mode.clone();
+                state.open_confirm(restore_session_confirm(values.len()), back_to);
+            }
+            Ok(_) => {}
+            Err(ArchInstallError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Ignoring unreadable session file {:?}: {}", path, e),
+        }
+        self.session_path = Some(path);
+    }
+
+    /// Save the guided configuration if it changed since the last save
+    fn autosave_session(&mut self) {
+        let Some(ref path) = self.session_path else {
+            return;
+        };
+        let state = &self.state;
+        if state
+            .guided
+            .config
+            .options
+            .iter()
+            .map(|o| &o.value)
+            .eq(self.session_snapshot.iter())
+        {
+            return;
+        }
+
+        match session::save_session(&state.guided.config, path) {
+            Ok(()) => debug!("Session saved to {:?}", path),
+            Err(e) => log::warn!("Failed to save session to {:?}: {}", path, e),
+        }
+        self.session_snapshot = state
+            .guided
+            .config
+            .options
+            .iter()
+            .map(|o| o.value.clone())
+            .collect();
+    }
+
+    /// Restore the saved session into the guided configuration
+    fn restore_session(&mut self) -> error::Result<()> {
+        let Some(path) = self.session_path.clone() else {
+            return Ok(());
+        };
+        let values = session::load_session(&path)?;
+
+        let state = &mut self.state;
+        let restored = session::apply_session(&mut state.guided.config, &values);
+        state.transition(AppMode::GuidedInstaller);
+        state.status.warn(format!(
+            "Restored {} option(s) from previous session - re-enter passwords",
+            restored
+        ));
+        self.session_snapshot = state
+            .guided
+            .config
+            .options
+            .iter()
+            .map(|o| o.value.clone())
+            .collect();
+        Ok(())
+    }
+
     /// Get reference to keybinding context
     #[allow(dead_code)] // API method available for future use
     pub fn keybinding_context(&self) -> &KeybindingContext {
         &self.keybinding_context
     }
 
-    /// Toggle help overlay visibility
-    pub fn toggle_help(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            state.help_visible = !state.help_visible;
-        }
+    /// The application state
+    pub fn state(&self) -> &AppState {
+        &self.state
     }
 
-    /// Load a configuration file and start installation
-    fn load_config_file(&mut self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::config_file::InstallationConfig;
+    /// The application state, to prepare it before the main loop starts
+    pub fn state_mut(&mut self) -> &mut AppState {
+        &mut self.state
+    }
 
-        // Clear file browser state first
-        {
-            let mut state = self.lock_state_mut()?;
-            state.file_browser = None;
-        }
+    /// Handle for threads to send state updates through
+    ///
+    /// Lets installs started outside the TUI (e.g. remote installs) feed the progress screen.
+    pub fn state_handle(&self) -> StateHandle {
+        self.state_tx.clone()
+    }
 
-        // Load and validate the config file
-        match InstallationConfig::load_from_file(path) {
-            Ok(config) => {
-                match config.validate() {
-                    Ok(_) => {
-                        // Config is valid - start installation
-                        let mut state = self.lock_state_mut()?;
-                        state.status_message = format!(
-                            "Configuration loaded from: {}",
-                            path.display()
-                        );
+    /// Sender tool threads report their output and result through
+    #[allow(dead_code)] // Used by the end-to-end tests
+    pub fn tool_sender(&self) -> Sender<ToolMessage> {
+        self.tool_tx.clone()
+    }
 
-                        // Set up floating output to show config details
-                        let mut content = vec![
-                            format!("Configuration file: {}", path.display()),
-                            String::new(),
-                            "Configuration loaded successfully!".to_string(),
-                            String::new(),
-                            format!("Disk: {}", config.install_disk),
-                            format!("Hostname: {}", config.hostname),
-                            format!("Username: {}", config.username),
-                            format!("Bootloader: {}", config.bootloader),
-                            String::new(),
-                        ];
-
-                        if config.desktop_environment != crate::types::DesktopEnvironment::None {
-                            content.push(format!("Desktop: {}", config.desktop_environment));
-                        }
+    /// Apply the state updates background threads sent since the last call
+    pub fn apply_state_updates(&mut self) {
+        store::apply_updates(&mut self.state, &self.state_rx);
+    }
 
-                        content.push(String::new());
-                        content.push("Press Enter to start installation or Esc to cancel".to_string());
-
-                        state.floating_output = Some(crate::components::floating_window::FloatingOutputState {
-                            title: "Configuration Loaded".to_string(),
-                            content,
-                            scroll_offset: 0,
-                            auto_scroll: false,
-                            complete: true,
-                            progress: None,
-                            status: "Ready to install".to_string(),
-                        });
-                        state.mode = AppMode::FloatingOutput;
-                    }
-                    Err(e) => {
-                        let mut state = self.lock_state_mut()?;
-                        state.mode = AppMode::AutomatedInstall;
-                        state.status_message = format!("Config validation failed: {}", e);
-                    }
-                }
+    /// Load and validate a configuration file, then preview it before installing
+    fn load_config_file(&mut self, path: &std::path::Path) -> error::Result<()> {
+        let loaded = Self::load_install_config(path);
+
+        let state = &mut self.state;
+        state.file_browser = None;
+        match loaded {
+            Ok(config) => {
+                state.status.info(format!(
+                    "Configuration loaded from: {}",
+                    path.display()
+                ));
+                state.open_confirm(
+                    automated_install_confirm(&path.display().to_string(), &config),
+                    AppMode::AutomatedInstall,
+                );
             }
             Err(e) => {
-                let mut state = self.lock_state_mut()?;
-                state.mode = AppMode::AutomatedInstall;
-                state.status_message = format!("Failed to load config: {}", e);
+                state.transition(AppMode::AutomatedInstall);
+                state.status.error(e);
             }
         }
+        Ok(())
+    }
+
+    /// Load a configuration file with the checks of a headless install
+    fn load_install_config(
+        path: &std::path::Path,
+    ) -> Result<crate::config_file::InstallationConfig, String> {
+        let config = crate::config_file::InstallationConfig::load_from_file(path)
+            .map_err(|e| format!("Failed to load config: {:#}", e))?;
+        config
+            .validate()
+            .map_err(|e| format!("Config validation failed: {:#}", e))?;
+        Ok(config)
+    }
+
+    /// Install from a configuration file, with progress on the Installation screen
+    ///
+    /// The file is read again, so changes made while the preview was open
+    /// are not missed.
+    fn start_automated_install(&mut self, path: &std::path::Path) -> error::Result<()> {
+        if !self.state.privileged {
+            self.state.status.error(INSTALL_NEEDS_ROOT);
+            return Ok(());
+        }
+        let config = match Self::load_install_config(path) {
+            Ok(config) => config,
+            Err(e) => {
+                self.state.status.error(e);
+                return Ok(());
+            }
+        };
+        info!("Starting automated installation from {:?}", path);
 
+        self.installer = Some(
+            Installer::from_file(config, self.state_handle())
+                .with_report_dir(self.report_dir.clone())
+                .with_timeout_policy(self.timeout_policy.clone()),
+        );
+        if let Some(ref mut installer) = self.installer {
+            installer.start(&mut self.state)?;
+        }
         Ok(())
     }
 
@@ -199,9 +630,16 @@ impl App {
         args: &[&str],
         tool_name: &str,
         return_mode: AppMode,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         use crate::components::pty_terminal::{spawn_or_fallback, PtySpawnResult};
 
+        if simulate::is_enabled() {
+            let command = format!("{} {}", cmd, args.join(" "));
+            let state = &mut self.state;
+            state.status.info(simulate::skipped(command.trim_end()));
+            return Ok(());
+        }
+
         // Get terminal size
         let (cols, rows) = crossterm::terminal::size()?;
         let pty_rows = rows.saturating_sub(2); // Reserve space for nav bar
@@ -210,14 +648,15 @@ impl App {
             PtySpawnResult::Success(pty) => {
                 self.pty_terminal = Some(*pty);
 
-                let mut state = self.lock_state_mut()?;
-                let return_menu_selection = state.tools_menu_selection;
+                let state = &mut self.state;
+                let return_menu_selection = state.menu.tools_selection;
                 state.embedded_terminal = Some(PtyTerminalState {
                     tool_name: tool_name.to_string(),
                     return_mode,
                     return_menu_selection,
+                    finished: false,
                 });
-                state.mode = AppMode::EmbeddedTerminal;
+                state.transition(AppMode::EmbeddedTerminal);
                 Ok(())
             }
             PtySpawnResult::Fallback(reason) => {
@@ -234,49 +673,87 @@ impl App {
         cmd: &str,
         args: &[&str],
         return_mode: AppMode,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         use std::process::Command;
 
-        // Temporarily leave alternate screen
-        crossterm::execute!(
-            std::io::stdout(),
-            crossterm::terminal::LeaveAlternateScreen
-        )?;
-        crossterm::terminal::disable_raw_mode()?;
-
-        // Run the command
+        self.suspend_tui()?;
         let status = Command::new(cmd).args(args).status();
-
-        // Return to alternate screen
-        crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(
-            std::io::stdout(),
-            crossterm::terminal::EnterAlternateScreen
-        )?;
+        self.resume_tui()?;
 
         // Check status and return to appropriate mode
         match status {
             Ok(exit_status) => {
-                let mut state = self.lock_state_mut()?;
+                let state = &mut self.state;
                 if exit_status.success() {
-                    state.status_message = format!("{} completed successfully", cmd);
+                    state.status.info(format!("{} completed successfully", cmd));
                 } else {
-                    state.status_message = format!("{} exited with error", cmd);
+                    state.status.error(format!("{} exited with error", cmd));
                 }
-                state.mode = return_mode;
+                state.transition(return_mode);
             }
             Err(e) => {
-                let mut state = self.lock_state_mut()?;
-                state.status_message = format!("Failed to run {}: {}", cmd, e);
-                state.mode = return_mode;
+                let state = &mut self.state;
+                state.status.error(format!("Failed to run {}: {}", cmd, e));
+                state.transition(return_mode);
             }
         }
 
         Ok(())
     }
 
+    /// Hand the terminal to a program run in the foreground
+    ///
+    /// Leaves the alternate screen; serial consoles draw on the normal
+    /// screen, which the program's output overwrites.
+    fn suspend_tui(&self) -> error::Result<()> {
+        if !self.serial {
+            crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
+        }
+        crossterm::terminal::disable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Take the terminal back after [`Self::suspend_tui`]
+    fn resume_tui(&mut self) -> error::Result<()> {
+        crossterm::terminal::enable_raw_mode()?;
+        if self.serial {
+            self.screen_stale = true;
+        } else {
+            crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+        }
+        Ok(())
+    }
+
+    /// Suspend the TUI for a shell where the installation stands (Ctrl+T)
+    ///
+    /// The state is left as it is, so exiting the shell returns to the
+    /// screen it was opened from; see [`crate::shell`] for where the shell opens.
+    fn open_shell(&mut self) -> error::Result<()> {
+        let context = ShellContext::detect();
+        info!("Opening a shell in {}", context);
+
+        self.suspend_tui()?;
+        let status = std::process::Command::new("bash")
+            .args(["-c", &context.command()])
+            .status();
+        self.resume_tui()?;
+        // Whatever the shell printed is still on the screen
+        self.screen_stale = true;
+
+        let state = &mut self.state;
+        match status {
+            Ok(_) => state
+                .status
+                .info(format!("Back from the shell in {}", context)),
+            Err(e) => state
+                .status
+                .error(format!("Failed to start a shell: {}", e)),
+        }
+        Ok(())
+    }
+
     /// Exit embedded terminal and return to previous mode
-    fn exit_embedded_terminal(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn exit_embedded_terminal(&mut self) -> error::Result<()> {
         // Kill the PTY if running
         if let Some(ref mut pty) = self.pty_terminal {
             pty.kill();
@@ -284,35 +761,71 @@ impl App {
         self.pty_terminal = None;
 
         // Return to previous mode
-        let mut state = self.lock_state_mut()?;
-        if let Some(terminal_state) = state.embedded_terminal.take() {
-            state.mode = terminal_state.return_mode;
-            state.tools_menu_selection = terminal_state.return_menu_selection;
-            state.status_message = format!("{} closed", terminal_state.tool_name);
-        } else {
-            state.mode = AppMode::MainMenu;
+        let tool_name = {
+            let state = &mut self.state;
+            let Some(terminal_state) = state.embedded_terminal.take() else {
+                state.transition(AppMode::MainMenu);
+                return Ok(());
+            };
+            state.transition(terminal_state.return_mode);
+            state.menu.tools_selection = terminal_state.return_menu_selection;
+            state.status.info(format!("{} closed", terminal_state.tool_name));
+            terminal_state.tool_name
+        };
+
+        // A rescue shell closed from here could not unmount the system itself
+        if tool_name == rescue::TOOL_NAME {
+            if let Err(e) = rescue::release() {
+                let state = &mut self.state;
+                state
+                    .status
+                    .error(format!("Could not release the rescued system: {}", e));
+            }
         }
 
         Ok(())
     }
 
     /// Poll PTY output if in embedded terminal mode
-    fn poll_pty(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(ref mut pty) = self.pty_terminal {
-            // Check if PTY is still running
-            if !pty.is_running() {
-                // PTY exited, return to previous mode
-                self.exit_embedded_terminal()?;
-            }
+    ///
+    /// A program that exited stays on screen until a key is pressed, so its
+    /// last output can be read before returning to the menu.
+    fn poll_pty(&mut self) -> error::Result<()> {
+        let Some(ref mut pty) = self.pty_terminal else {
+            return Ok(());
+        };
+        if pty.is_running() {
+            return Ok(());
+        }
+        let exit_status = pty.exit_status();
+
+        let state = &mut self.state;
+        let Some(ref mut terminal) = state.embedded_terminal else {
+            return Ok(());
+        };
+        if terminal.finished {
+            return Ok(());
+        }
+        terminal.finished = true;
+        let tool_name = terminal.tool_name.clone();
+        match exit_status {
+            Some(status) if !status.success() => state.status.error(format!(
+                "{}: {} - press any key to return",
+                tool_name,
+                status.to_string().to_lowercase()
+            )),
+            _ => state
+                .status
+                .info(format!("{} finished - press any key to return", tool_name)),
         }
         Ok(())
     }
 
     /// Poll for tool execution messages from background threads
-    fn poll_tool_messages(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn poll_tool_messages(&mut self) -> error::Result<()> {
         // Process all pending messages without blocking
         while let Ok(msg) = self.tool_rx.try_recv() {
-            let mut state = self.lock_state_mut()?;
+            let state = &mut self.state;
 
             match msg {
                 ToolMessage::Stdout(line) => {
@@ -332,6 +845,16 @@ impl App {
                         }
                     }
                 }
+                ToolMessage::Progress(progress) => {
+                    if let Some(ref mut floating) = state.floating_output {
+                        floating.set_progress(progress.percent());
+                        floating.status = format!(
+                            "{} of {}",
+                            resize::format_size(progress.done),
+                            resize::format_size(progress.total)
+                        );
+                    }
+                }
                 ToolMessage::Complete { success, exit_code } => {
                     // Update status message first (before borrowing floating_output)
                     let status_msg = if success {
@@ -339,8 +862,12 @@ impl App {
                     } else {
                         format!("Tool failed with exit code: {}", exit_code.unwrap_or(-1))
                     };
-                    state.status_message = status_msg.clone();
-                    state.current_tool = None;
+                    if success {
+                        state.status.info(status_msg);
+                    } else {
+                        state.status.error(status_msg);
+                    }
+                    state.tools.current = None;
 
                     // Now update floating output
                     if let Some(ref mut floating) = state.floating_output {
@@ -359,8 +886,8 @@ impl App {
                     }
                 }
                 ToolMessage::Error(err) => {
-                    state.status_message = format!("Tool error: {}", err);
-                    state.current_tool = None;
+                    state.status.error(format!("Tool error: {}", err));
+                    state.tools.current = None;
 
                     if let Some(ref mut floating) = state.floating_output {
                         floating.append_line(format!("❌ Error: {}", err));
@@ -378,457 +905,395 @@ impl App {
     pub fn run(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         info!("Starting main application loop");
+        let poll_interval = if self.serial {
+            serial::POLL_INTERVAL
+        } else {
+            Duration::from_millis(50)
+        };
+        let mut pacer = serial::FramePacer::new(self.serial);
 
         loop {
             // Poll PTY if in embedded terminal mode
             self.poll_pty()?;
 
+            // Apply what the installer and tool threads changed
+            self.apply_state_updates();
+
             // Poll for tool execution output messages
             self.poll_tool_messages()?;
 
-            // Handle input events
-            if crossterm::event::poll(Duration::from_millis(50))? {
-                match crossterm::event::read()? {
-                    Event::Key(key_event) => {
-                        if self.handle_key_event(key_event)? {
-                            break; // Exit requested
-                        }
-                    }
-                    Event::Resize(width, height) => {
-                        // Handle window resize - update scroll state
-                        self.handle_resize(width, height)?;
-                        // Also resize PTY if active
-                        if let Some(ref mut pty) = self.pty_terminal {
-                            let _ = pty.resize(width, height.saturating_sub(2));
-                        }
-                    }
-                    _ => {}
+            // Replayed keystrokes come first, then the keyboard takes over
+            if let Some(event) = self.replay.as_mut().and_then(keylog::Replay::next_due) {
+                if self.handle_input_event(event)? {
+                    break; // Exit requested
                 }
+                pacer.input();
             }
-
-            // Check if installation is complete
+            if self
+                .replay
+                .as_ref()
+                .is_some_and(keylog::Replay::is_finished)
             {
-                let state = self
-                    .state
-                    .lock()
-                    .map_err(|e| error::general_error(format!("Mutex poisoned: {}", e)))?;
-                if state.mode == AppMode::Complete {
-                    break;
+                info!("Replay finished");
+                self.replay = None;
+            }
+
+            // Handle input events
+            if crossterm::event::poll(poll_interval)? {
+                let event = crossterm::event::read()?;
+                if self.handle_input_event(event)? {
+                    break; // Exit requested
                 }
+                pacer.input();
+            }
+
+            // The toolkit restarts as root from main()
+            if self.root_restart.is_some() {
+                break;
             }
 
             // Render UI
-            terminal.draw(|f| {
-                let mut state = match self.state.lock() {
-                    Ok(state) => state,
-                    Err(_) => {
-                        // If mutex is poisoned, we can't continue safely
-                        eprintln!("Fatal error: Mutex poisoned, cannot continue");
-                        std::process::exit(1);
-                    }
-                };
-                // Update scroll state with actual available space for config options
-                if state.mode == AppMode::GuidedInstaller {
-                    // Calculate the config area height (total height minus reserved space)
-                    let config_area_height = f.area().height.saturating_sub(17); // 17 lines reserved (includes nav bar)
-                    let visible_items = config_area_height.saturating_sub(2); // Account for borders
-                    state
-                        .config_scroll
-                        .update_visible_items(visible_items as usize);
+            self.apply_state_updates();
+            if pacer.should_draw(Instant::now()) {
+                if std::mem::take(&mut self.screen_stale) {
+                    terminal.clear()?;
                 }
-                self.ui_renderer
-                    .render_with_context(f, &state, &mut self.input_handler, &self.keybinding_context, self.pty_terminal.as_mut());
-            })?;
+                self.draw_to(terminal)?;
+            }
         }
 
         Ok(())
     }
 
-    /// Handle keyboard input events
-    fn handle_key_event(
-        &mut self,
-        key_event: KeyEvent,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        // Get current mode and help visibility
-        let (current_mode, help_visible) = {
-            if let Ok(state) = self.lock_state() {
-                (state.mode.clone(), state.help_visible)
-            } else {
-                return Ok(false);
+    /// Handle an event from the terminal or a replay, recording it if enabled
+    fn handle_input_event(&mut self, event: Event) -> error::Result<bool> {
+        let secret = self.input_is_secret();
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.record(&event, secret) {
+                log::warn!("Stopped recording keystrokes: {}", e);
+                self.recorder = None;
             }
-        };
+        }
+        self.handle_event(event)
+    }
 
-        // Handle embedded terminal mode - forward all keys except Ctrl+Q
-        if current_mode == AppMode::EmbeddedTerminal {
-            // Ctrl+Q exits the embedded terminal
-            if key_event.modifiers.contains(KeyModifiers::CONTROL)
-                && key_event.code == KeyCode::Char('q')
-            {
-                self.exit_embedded_terminal()?;
-                return Ok(false);
-            }
+    /// Whether typed characters must stay out of the key log: passwords,
+    /// proxy credentials, and anything typed into the embedded terminal
+    pub fn input_is_secret(&self) -> bool {
+        let state = &self.state;
+        self.input_handler.is_secret_active()
+            || state.mode == AppMode::EmbeddedTerminal
+            || (state.mode == AppMode::ToolDialog
+                && state
+                    .tools
+                    .dialog
+                    .as_ref()
+                    .is_some_and(ToolDialogState::editing_password))
+    }
 
-            // Forward all other keys to PTY
-            if let Some(ref mut pty) = self.pty_terminal {
-                let _ = pty.send_key(key_event);
+    /// Feed events through the app without delays, drawing after each
+    ///
+    /// Replays keystroke files in end-to-end tests on a `TestBackend`.
+    /// Returns `true` when an event requested exit; later events are skipped.
+    #[allow(dead_code)] // Used by the end-to-end tests
+    pub fn play<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        events: impl IntoIterator<Item = Event>,
+    ) -> error::Result<bool> {
+        for event in events {
+            self.apply_state_updates();
+            self.poll_tool_messages()?;
+            if self.handle_event(event)? {
+                return Ok(true);
             }
-            return Ok(false);
+            self.draw_to(terminal)?;
         }
+        Ok(false)
+    }
 
-        // Handle help overlay - ? or Esc dismisses it
-        if help_visible {
-            match key_event.code {
-                KeyCode::Char('?') | KeyCode::Esc => {
-                    self.toggle_help();
+    /// Process a single terminal event
+    ///
+    /// Returns `true` when the event requests application exit. This is the same
+    /// entry point the main loop uses, so tests can inject key presses directly.
+    /// Configuration changes are auto-saved to the session file, if enabled.
+    /// Errors the user can carry on from are shown in a dialog; only fatal
+    /// ones (see [`ArchInstallError::is_fatal`]) end the application.
+    pub fn handle_event(&mut self, event: Event) -> error::Result<bool> {
+        let exit = match event {
+            // Terminals with the enhanced keyboard protocol also report releases
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Release => false,
+            Event::Key(key_event) => match self.handle_key_event(key_event) {
+                Ok(exit) => exit,
+                Err(e) if !e.is_fatal() => {
+                    log::error!("{}", e);
+                    self.input_handler.start_error(&e);
+                    false
                 }
-                _ => {}
+                Err(e) => return Err(e),
+            },
+            Event::Resize(width, height) => {
+                // Handle window resize - update scroll state
+                self.handle_resize(width, height)?;
+                // The embedded terminal's PTY follows its area when drawn
+                false
             }
-            return Ok(false);
-        }
+            _ => false,
+        };
 
-        // Global help toggle with '?' (except in dialogs and embedded terminal)
-        if key_event.code == KeyCode::Char('?') && !self.input_handler.is_dialog_active() {
-            self.toggle_help();
-            return Ok(false);
-        }
+        self.autosave_session();
+        Ok(exit)
+    }
 
-        // Check if we're in a tool dialog
-        let is_tool_dialog = current_mode == AppMode::ToolDialog;
+    /// Render one frame to any ratatui backend
+    ///
+    /// The main loop draws to the crossterm backend; tests can pass a
+    /// `ratatui::backend::TestBackend` and inspect the resulting buffer.
+    pub fn draw_to<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+    ) -> error::Result<()> {
+        terminal.draw(|f| {
+            let state = &mut self.state;
+            // Update scroll state with actual available space for config options
+            if state.mode == AppMode::GuidedInstaller {
+                // Calculate the config area height (total height minus reserved space)
+                // 7 lines besides the header and title are reserved (includes nav bar)
+                let reserved = 7 + self.ui_renderer.header_height(f.area());
+                let config_area_height = f.area().height.saturating_sub(reserved);
+                let visible_items = config_area_height.saturating_sub(2); // Account for borders
+                state
+                    .guided
+                    .scroll
+                    .update_visible_items(visible_items as usize);
+                // Validators run again only when an option changed
+                state.guided.refresh_validity();
+            }
+            // Same for the installer output: 6 lines besides the header and title
+            // go to the progress bar, pane borders and nav bar
+            if state.mode == AppMode::Installation {
+                let reserved = 6 + self.ui_renderer.header_height(f.area());
+                let visible_lines = f.area().height.saturating_sub(reserved);
+                state
+                    .install
+                    .output
+                    .set_visible_lines(visible_lines as usize);
+            }
+            self.ui_renderer
+                .render_with_context(f, state, &mut self.input_handler, &self.keybinding_context, self.pty_terminal.as_mut());
+        })?;
 
-        if is_tool_dialog {
-            self.handle_tool_dialog_input(key_event)?;
-            return Ok(false);
-        }
+        Ok(())
+    }
 
-        // Check if we're in an input dialog
-        if self.input_handler.is_dialog_active() {
-            if let Some(value) = self.input_handler.handle_input(key_event) {
-                // Check if we're in disk selection mode for a tool
-                let current_tool = {
-                    let state = self.state.lock().map_err(|_| "Failed to lock state")?;
-                    state.current_tool.clone()
-                };
+    /// Handle keyboard input events
+    ///
+    /// Raw keys only reach the embedded terminal and text input dialogs;
+    /// everything else is translated into an [`Action`] and dispatched.
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+    ) -> error::Result<bool> {
+        // Get current mode, help visibility and whether a search is typed
+        let (current_mode, help_visible, output_search, service_filter, terminal_finished) = {
+            let state = &self.state;
+            (
+                state.mode.clone(),
+                state.help_visible,
+                state.install.output.prompt.is_some(),
+                state.service_filter_open(),
+                state
+                    .embedded_terminal
+                    .as_ref()
+                    .is_some_and(|terminal| terminal.finished),
+            )
+        };
 
-                if let Some(tool) = current_tool {
-                    match tool.as_str() {
-                        "health" => {
-                            // Handle disk selection for health tool
-                            self.execute_health_tool_with_disk(value)?;
-                            return Ok(false);
-                        }
-                        "format_partition" => {
-                            // Show confirmation dialog before formatting
-                            let mut state = self.lock_state_mut()?;
-                            state.pre_dialog_mode = Some(AppMode::DiskTools);
-                            state.confirm_dialog =
-                                Some(format_partition_confirm(&value, "ext4"));
-                            state.mode = AppMode::ConfirmDialog;
-                            return Ok(false);
-                        }
-                        "wipe_disk" => {
-                            // Show confirmation dialog before wiping
-                            let mut state = self.lock_state_mut()?;
-                            state.pre_dialog_mode = Some(AppMode::DiskTools);
-                            state.confirm_dialog = Some(wipe_disk_confirm(&value));
-                            state.mode = AppMode::ConfirmDialog;
-                            return Ok(false);
-                        }
-                        _ => {}
-                    }
-                }
+        // Any key returns from an embedded terminal whose program exited
+        if current_mode == AppMode::EmbeddedTerminal && terminal_finished {
+            return self.dispatch(Action::ExitTerminal);
+        }
 
-                // User confirmed input, update configuration
-                self.update_configuration_value(value)?;
-            }
-            return Ok(false);
+        // Control-key alternatives stand in for keys a serial client may not
+        // send; the embedded terminal's shell needs the control keys themselves
+        let key_event = if self.serial && current_mode != AppMode::EmbeddedTerminal {
+            let typing = !help_visible
+                && (self.input_handler.is_text_active()
+                    || (current_mode == AppMode::Installation && output_search)
+                    || service_filter);
+            serial::translate(key_event, typing)
+        } else {
+            key_event
+        };
+
+        // Ctrl+T opens a shell over text fields and prompts as well
+        if current_mode != AppMode::EmbeddedTerminal && Action::opens_shell(&key_event) {
+            return self.dispatch(Action::OpenShell);
         }
 
-        // Handle floating output mode
-        if current_mode == AppMode::FloatingOutput {
-            match key_event.code {
-                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('b') | KeyCode::Char('B') => {
-                    // Dismiss floating output and return to previous mode
-                    let mut state = self.lock_state_mut()?;
-                    if let Some(_output) = state.floating_output.take() {
-                        // Return to tools menu or previous mode
-                        state.mode = AppMode::ToolsMenu;
-                    }
-                }
-                KeyCode::Up => {
-                    let mut state = self.lock_state_mut()?;
-                    if let Some(ref mut output) = state.floating_output {
-                        if output.scroll_offset > 0 {
-                            output.scroll_offset -= 1;
-                        }
-                    }
-                }
-                KeyCode::Down => {
-                    let mut state = self.lock_state_mut()?;
-                    if let Some(ref mut output) = state.floating_output {
-                        if output.scroll_offset < output.content.len().saturating_sub(1) {
-                            output.scroll_offset += 1;
-                        }
-                    }
-                }
-                _ => {}
+        // Text input dialogs consume raw keys until a value is submitted
+        if !help_visible
+            && current_mode != AppMode::EmbeddedTerminal
+            && self.input_handler.is_dialog_active()
+        {
+            if let Some(value) = self.input_handler.handle_input(key_event) {
+                return self.dispatch(Action::SubmitValue(value));
             }
             return Ok(false);
         }
 
-        // Handle file browser mode
-        if current_mode == AppMode::FileBrowser {
-            let mut state = self.lock_state_mut()?;
-            if let Some(ref mut browser) = state.file_browser {
-                match key_event.code {
-                    KeyCode::Esc => {
-                        browser.cancel();
-                    }
-                    KeyCode::Enter => {
-                        browser.handle_enter();
-                    }
-                    KeyCode::Up => {
-                        browser.move_up();
-                    }
-                    KeyCode::Down => {
-                        browser.move_down();
-                    }
-                    KeyCode::Char('~') => {
-                        browser.go_home();
-                    }
-                    KeyCode::Char('/') => {
-                        browser.go_root();
-                    }
-                    _ => {}
-                }
+        // The installer output's search prompt and the service manager's
+        // filter take text until Enter or Esc
+        if !help_visible
+            && ((current_mode == AppMode::Installation && output_search) || service_filter)
+        {
+            return match Action::from_search_key(key_event) {
+                Some(action) => self.dispatch(action),
+                None => Ok(false),
+            };
+        }
 
-                // Check if file browser is complete
-                if browser.complete {
-                    if let Some(selected_path) = browser.selected_file.clone() {
-                        // Load the config file
-                        drop(state); // Release the lock before loading
-                        self.load_config_file(&selected_path)?;
-                    } else {
-                        // Cancelled - return to automated install screen
-                        state.file_browser = None;
-                        state.mode = AppMode::AutomatedInstall;
-                        state.status_message = "File selection cancelled".to_string();
+        match Action::from_key(&current_mode, help_visible, key_event) {
+            Some(action) => self.dispatch(action),
+            None => {
+                // Forward unmapped keys to the embedded terminal's PTY
+                if current_mode == AppMode::EmbeddedTerminal {
+                    if let Some(ref mut pty) = self.pty_terminal {
+                        let _ = pty.send_key(key_event);
                     }
                 }
+                Ok(false)
             }
-            return Ok(false);
         }
+    }
 
-        // Handle confirm dialog mode
-        if current_mode == AppMode::ConfirmDialog {
-            let mut state = self.lock_state_mut()?;
-            if let Some(ref mut dialog) = state.confirm_dialog {
-                match key_event.code {
-                    KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
-                        // Toggle between No (0) and Yes (1)
-                        let old_selected = dialog.selected;
-                        dialog.selected = if dialog.selected == 0 { 1 } else { 0 };
-                        log::debug!("ConfirmDialog toggle: {} -> {} (0=No/left, 1=Yes/right)",
-                            old_selected, dialog.selected);
-                    }
-                    KeyCode::Enter => {
-                        // SECURITY FIX: Use is_confirmed() method to get correct selection
-                        // selected = 0 means No/Cancel (left), selected = 1 means Yes/Confirm (right)
-                        let confirmed = dialog.is_confirmed(); // Returns true if selected == 1 (Yes on right)
-                        let action = dialog.confirm_action.clone();
-                        let data = dialog.action_data.clone();
-
-                        log::info!("ConfirmDialog Enter: selected={}, is_confirmed={}, action={}",
-                            dialog.selected, confirmed, action);
-
-                        // Clear dialog and restore previous mode
-                        state.confirm_dialog = None;
-                        if let Some(prev_mode) = state.pre_dialog_mode.take() {
-                            state.mode = prev_mode;
-                        }
-
-                        if confirmed {
-                            log::info!("Executing confirmed action: {}", action);
-                            // Drop the lock before executing action
-                            drop(state);
-                            self.execute_confirmed_action(&action, data)?;
-                        } else {
-                            log::info!("Action cancelled, returning to previous mode");
-                        }
-                    }
-                    KeyCode::Esc => {
-                        // Cancel - restore previous mode
-                        state.confirm_dialog = None;
-                        if let Some(prev_mode) = state.pre_dialog_mode.take() {
-                            state.mode = prev_mode;
-                        }
-                    }
-                    _ => {}
-                }
-            }
+    /// Perform an action
+    ///
+    /// State-only actions go through [`AppState::reduce`]; the rest are side
+    /// effects performed here. Returns `true` when the action requests exit.
+    pub fn dispatch(&mut self, action: Action) -> error::Result<bool> {
+        match &action {
+            // Typed text may be a password or a proxy's credentials
+            Action::SubmitValue(_) | Action::InsertChar(_) => debug!("Dispatching action: text"),
+            _ => debug!("Dispatching action: {:?}", action),
+        }
+        if self.state.reduce(&action) {
             return Ok(false);
         }
 
-        // Handle main application navigation
-        match key_event.code {
-            KeyCode::Char('q') => {
-                // Exit application
-                return Ok(true);
-            }
-            KeyCode::Char('b') | KeyCode::Char('B') => {
-                // Go back in menu system
-                self.handle_back_key()?;
-            }
-            KeyCode::Up => {
-                self.navigate_up();
-            }
-            KeyCode::Down => {
-                self.navigate_down();
-            }
-            KeyCode::PageUp => {
-                self.page_up();
-            }
-            KeyCode::PageDown => {
-                self.page_down();
-            }
-            KeyCode::Home => {
-                self.move_to_first();
-            }
-            KeyCode::End => {
-                self.move_to_last();
+        match action {
+            Action::Quit => return Ok(true),
+            Action::Select => self.handle_enter()?,
+            Action::OpenDialog => self.open_input_dialog()?,
+            Action::StartInstall => self.request_start_installation()?,
+            Action::ResetOption => self.reset_selected_option()?,
+            Action::SubmitValue(value) => self.submit_value(value)?,
+            Action::SelfTest(test) => self.start_disk_self_test(test)?,
+            Action::RefreshDiskHealth => self.refresh_disk_health()?,
+            Action::ManageUnit(action) => self.manage_unit(action)?,
+            Action::RefreshServices => self.refresh_services()?,
+            Action::SwitchServiceTarget => self.switch_service_target()?,
+            Action::MountDevice => self.mount_selected_device(None)?,
+            Action::MountDeviceAt => self.ask_mountpoint()?,
+            Action::UnmountDevice => self.unmount_selected_device()?,
+            Action::RefreshMounts => self.refresh_mounts()?,
+            Action::RefreshPartitionCheck => self.recheck_partitions()?,
+            Action::Recover(choice) => self.recover(choice)?,
+            Action::SaveInstallSummary => self.save_install_summary()?,
+            Action::ToggleWikiArticle => self.toggle_wiki_article()?,
+            Action::KillHungCommand { retry } => self.kill_hung_command(retry)?,
+            Action::RunInteractively => self.run_interactively()?,
+            Action::OpenShell => self.open_shell()?,
+            Action::FullDiskTest => {
+                // Fall back to the full reliability test script
+                let device = {
+                    let state = &mut self.state;
+                    state.tools.disk_health.take().map(|health| health.device)
+                };
+                if let Some(device) = device {
+                    self.execute_tool_with_device(
+                        "check_disk_health.sh",
+                        &device,
+                        &["--detailed"],
+                    )?;
+                }
             }
-            KeyCode::Enter => {
-                self.handle_enter()?;
+            Action::Back | Action::ExitTerminal => {
+                // The reducer handles Back everywhere except the embedded terminal
+                self.exit_embedded_terminal()?;
             }
             _ => {}
         }
-
         Ok(false)
     }
 
-    /// Navigate to previous option
-    fn navigate_up(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            match state.mode {
-                AppMode::MainMenu => {
-                    if state.main_menu_selection > 0 {
-                        state.main_menu_selection -= 1;
-                    }
-                }
-                AppMode::ToolsMenu
-                | AppMode::DiskTools
-                | AppMode::SystemTools
-                | AppMode::UserTools
-                | AppMode::NetworkTools => {
-                    if state.tools_menu_selection > 0 {
-                        state.tools_menu_selection -= 1;
-                    }
-                }
-                AppMode::ToolDialog => {
-                    if let Some(ref mut dialog) = state.tool_dialog {
-                        if dialog.current_param > 0 {
-                            dialog.current_param -= 1;
-                        }
-                    }
-                }
-                AppMode::GuidedInstaller => {
-                    state.config_scroll.move_up();
-                }
-                _ => {}
-            }
-        }
-    }
+    /// Apply a value entered in the input dialog
+    fn submit_value(&mut self, value: String) -> error::Result<()> {
+        // Check if we're in disk selection mode for a tool
+        let (current_tool, mode) = {
+            let state = &self.state;
+            (state.tools.current.clone(), state.mode.clone())
+        };
 
-    /// Navigate to next option
-    fn navigate_down(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            match state.mode {
-                AppMode::MainMenu => {
-                    if state.main_menu_selection < 3 {
-                        // 4 items total (0-3)
-                        state.main_menu_selection += 1;
-                    }
-                }
-                AppMode::ToolsMenu => {
-                    if state.tools_menu_selection < 4 {
-                        // 5 items total (0-4)
-                        state.tools_menu_selection += 1;
-                    }
-                }
-                AppMode::DiskTools => {
-                    if state.tools_menu_selection < 5 {
-                        // 6 items total (0-5)
-                        state.tools_menu_selection += 1;
-                    }
-                }
-                AppMode::SystemTools | AppMode::UserTools => {
-                    if state.tools_menu_selection < 5 {
-                        // 6 items total (0-5)
-                        state.tools_menu_selection += 1;
-                    }
-                }
-                AppMode::NetworkTools => {
-                    if state.tools_menu_selection < 4 {
-                        // 5 items total (0-4)
-                        state.tools_menu_selection += 1;
-                    }
-                }
-                AppMode::ToolDialog => {
-                    if let Some(ref mut dialog) = state.tool_dialog {
-                        if dialog.current_param < dialog.parameters.len() - 1 {
-                            dialog.current_param += 1;
-                        }
-                    }
-                }
-                AppMode::GuidedInstaller => {
-                    state.config_scroll.move_down();
-                }
-                _ => {}
+        match current_tool.as_deref() {
+            Some("health") => {
+                // Handle disk selection for health tool
+                self.execute_health_tool_with_disk(value)?;
             }
-        }
-    }
-
-    /// Page up in configuration list
-    fn page_up(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            if state.mode == AppMode::GuidedInstaller {
-                state.config_scroll.page_up();
+            Some("chroot") => {
+                self.launch_rescue_shell(&value)?;
             }
-        }
-    }
-
-    /// Page down in configuration list
-    fn page_down(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            if state.mode == AppMode::GuidedInstaller {
-                state.config_scroll.page_down();
+            Some(bootrepair::TOOL_NAME) => {
+                self.mount_for_repair(&value)?;
             }
-        }
-    }
-
-    /// Move to first configuration option
-    fn move_to_first(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            if state.mode == AppMode::GuidedInstaller {
-                state.config_scroll.move_to_first();
+            Some(clone::TOOL_NAME) if mode == AppMode::MainMenu => {
+                self.clone_system(&value)?;
             }
-        }
-    }
-
-    /// Move to last configuration option
-    fn move_to_last(&self) {
-        if let Ok(mut state) = self.lock_state_mut() {
-            if state.mode == AppMode::GuidedInstaller {
-                state.config_scroll.move_to_last();
+            Some("mount_at") if mode == AppMode::MountManager => {
+                self.state.tools.current = None;
+                self.mount_selected_device(Some(value))?;
+            }
+            Some("wipe_disk") => {
+                self.choose_wipe_method(&value)?;
+            }
+            Some("run_interactively") => {
+                self.state.tools.current = None;
+                let selection = self.state.menu.tools_selection;
+                if let Some(interactive) =
+                    menus::tool_name(&mode, selection).and_then(interactive::find)
+                {
+                    self.launch_interactive(interactive, Some(&value), mode)?;
+                }
+            }
+            Some("wipe_method") => {
+                // Show confirmation dialog before wiping
+                let state = &mut self.state;
+                let method = value
+                    .split_whitespace()
+                    .next()
+                    .and_then(|name| name.parse::<WipeMethod>().ok());
+                if let (Some(target), Some(method)) = (state.tools.wipe_target.take(), method) {
+                    state.open_confirm(
+                        wipe_disk_confirm(&target.disk.path, method),
+                        AppMode::DiskTools,
+                    );
+                }
+                state.tools.current = None;
+            }
+            _ => {
+                // User confirmed input, update configuration
+                self.update_configuration_value(value)?;
             }
         }
+        Ok(())
     }
 
     /// Handle Enter key press
-    fn handle_enter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn handle_enter(&mut self) -> error::Result<()> {
         let current_mode = {
-            let state = self.lock_state()?;
+            let state = &self.state;
             state.mode.clone()
         };
 
@@ -842,11 +1307,25 @@ impl App {
             AppMode::DiskTools
             | AppMode::SystemTools
             | AppMode::UserTools
-            | AppMode::NetworkTools => {
+            | AppMode::NetworkTools
+            | AppMode::SnapshotTools => {
                 self.handle_tool_selection()?;
             }
             AppMode::GuidedInstaller => {
-                self.handle_guided_installer_enter()?;
+                // One step past the last option is the green "start" button
+                let on_start_button = {
+                    let state = &self.state;
+                    state.guided.scroll.selected_index == state.guided.config.options.len()
+                };
+                let action = if on_start_button {
+                    Action::StartInstall
+                } else {
+                    Action::OpenDialog
+                };
+                self.dispatch(action)?;
+            }
+            AppMode::Summary => {
+                self.confirm_summary()?;
             }
             AppMode::AutomatedInstall => {
                 self.handle_automated_install_enter()?;
@@ -867,88 +1346,331 @@ impl App {
                 // Embedded terminal handles its own input
             }
             AppMode::FloatingOutput => {
-                // Dismiss floating output on Enter
-                let mut state = self.lock_state_mut()?;
-                if let Some(_output) = state.floating_output.take() {
-                    state.mode = AppMode::ToolsMenu;
-                }
+                // Enter is mapped to Back, which dismisses the output
             }
             AppMode::FileBrowser => {
-                // File browser handles its own Enter key
+                self.handle_file_browser_enter()?;
             }
             AppMode::ConfirmDialog => {
                 // Handle confirmation dialog selection
                 self.handle_confirm_dialog_enter()?;
             }
+            AppMode::DiskHealth => {
+                // Enter is mapped to Back, which closes the report
+            }
+            AppMode::PartitionCheck => {
+                // Enter is mapped to Back, which closes the check
+            }
+            AppMode::PartitionMounts => {
+                // The reducer saves the plan
+            }
+            AppMode::ServiceManager => {
+                // Units are acted on with their own keys
+            }
+            AppMode::MountManager => {
+                // Devices are acted on with their own keys
+            }
+            AppMode::RecoveryDialog => {
+                let choice = self
+                    .state
+                    .install
+                    .recovery_dialog
+                    .as_ref()
+                    .map(|dialog| dialog.choice());
+                if let Some(choice) = choice {
+                    self.recover(choice)?;
+                }
+            }
         }
 
         Ok(())
     }
 
-    /// Handle confirmation dialog Enter key
-    fn handle_confirm_dialog_enter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let (confirmed, action, action_data, pre_mode) = {
-            let state = self.lock_state()?;
-            if let Some(ref dialog) = state.confirm_dialog {
-                (
-                    dialog.is_confirmed(),
-                    dialog.confirm_action.clone(),
-                    dialog.action_data.clone(),
-                    state.pre_dialog_mode.clone(),
-                )
-            } else {
+    /// Show the Arch Wiki article of the option help page, or go back to the page
+    ///
+    /// The article is fetched in the background; the help overlay says so
+    /// until it arrives.
+    fn toggle_wiki_article(&mut self) -> error::Result<()> {
+        let title = {
+            let state = &mut self.state;
+            state.help_scroll = 0;
+            if state.help_wiki.take().is_some() {
                 return Ok(());
             }
+            let page = state
+                .help_page
+                .and_then(|index| state.guided.config.options.get(index))
+                .and_then(|option| help::option_help(&option.name));
+            let Some(page) = page else {
+                return Ok(());
+            };
+            state.help_wiki = Some(wiki::Article::Fetching(page.wiki.to_string()));
+            page.wiki
         };
 
-        // Clear the dialog
-        {
-            let mut state = self.lock_state_mut()?;
-            state.confirm_dialog = None;
-            // Return to previous mode
-            if let Some(mode) = pre_mode {
-                state.mode = mode;
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let article = wiki::fetch(title);
+            app_state.update(move |state| {
+                // Unless the user moved on to another page meanwhile
+                if state.help_wiki == Some(wiki::Article::Fetching(title.to_string())) {
+                    state.help_wiki = Some(article);
+                }
+            });
+        });
+        Ok(())
+    }
+
+    /// Save the installation summary with the installation report
+    fn save_install_summary(&mut self) -> error::Result<()> {
+        let dirs = install_metrics::report_dirs(self.report_dir.as_deref());
+        let state = &mut self.state;
+        let mut saved = Vec::new();
+        for dir in dirs {
+            match state.install.metrics.write_report(&dir) {
+                Ok(path) => saved.push(path.display().to_string()),
+                Err(e) => {
+                    state.status.error(format!(
+                        "Failed to save the summary to {}: {}",
+                        dir.display(),
+                        e
+                    ));
+                    return Ok(());
+                }
             }
-            state.pre_dialog_mode = None;
         }
+        state
+            .status
+            .info(format!("Summary saved to {}", saved.join(", ")));
+        Ok(())
+    }
 
-        if confirmed {
-            // Execute the confirmed action
-            match action.as_str() {
-                "wipe_disk" => {
-                    if let Some(disk) = action_data {
-                        log::info!("Confirmed: wiping disk {}", disk);
-                        // Execute wipe disk operation
-                        self.execute_wipe_disk(&disk)?;
-                    }
+    /// Kill the command the installer has been waiting on without output
+    ///
+    /// The phase running it fails; with `retry` the recovery prompt that
+    /// follows is answered with retry instead of opening the dialog.
+    fn kill_hung_command(&mut self, retry: bool) -> error::Result<()> {
+        let state = &mut self.state;
+        let Some(quiet) = state.install.watchdog.quiet.clone() else {
+            state
+                .status
+                .info("No installer command is waiting without output");
+            return Ok(());
+        };
+        match watchdog::kill(&quiet) {
+            Ok(()) => {
+                state.install.watchdog.retry_pending = retry;
+                state.install.output.push(format!(
+                    "Watchdog: stopped {} (pid {}) after it printed nothing",
+                    quiet.name, quiet.pid
+                ));
+                if retry {
+                    state.status.warn(format!("Stopped {}, retrying the phase", quiet.name));
+                } else {
+                    state.status.warn(format!("Stopped {}", quiet.name));
+                }
+            }
+            Err(e) => state.status.error(e),
+        }
+        Ok(())
+    }
+
+    /// Act on a failed installation phase
+    ///
+    /// Retry, skip and abort are handed to install.sh, which is waiting on
+    /// them; the shell and log export keep the dialog open.
+    fn recover(&mut self, choice: RecoveryChoice) -> error::Result<()> {
+        let Some(failure) = self
+            .state
+            .install
+            .recovery_dialog
+            .as_ref()
+            .map(|dialog| dialog.failure.clone())
+        else {
+            return Ok(());
+        };
+
+        match choice {
+            RecoveryChoice::Shell => {
+                self.launch_embedded_tool(
+                    "bash",
+                    &["-c", recovery::SHELL_COMMAND],
+                    recovery::SHELL_NAME,
+                    AppMode::RecoveryDialog,
+                )?;
+            }
+            RecoveryChoice::ExportLogs => {
+                let dir = self.report_dir.clone().unwrap_or_else(std::env::temp_dir);
+                let state = &mut self.state;
+                let message = match recovery::export_logs(&dir, state.install.output.lines()) {
+                    Ok(path) => format!("Installer log saved to {}", path.display()),
+                    Err(e) => format!("Failed to save the installer log: {}", e),
+                };
+                if let Some(ref mut dialog) = state.install.recovery_dialog {
+                    dialog.message = Some(message);
+                }
+            }
+            _ if !choice.is_available(&failure) => {
+                let state = &mut self.state;
+                if let Some(ref mut dialog) = state.install.recovery_dialog {
+                    dialog.message = Some(format!(
+                        "{} is critical and cannot be skipped",
+                        failure.phase
+                    ));
                 }
-                "format_partition" => {
-                    if let Some(partition) = action_data {
-                        log::info!("Confirmed: formatting partition {}", partition);
-                        // Execute format operation
+            }
+            RecoveryChoice::Retry | RecoveryChoice::Skip | RecoveryChoice::Abort => {
+                let answered =
+                    recovery::decision_file().and_then(|path| recovery::answer(&path, choice));
+                let state = &mut self.state;
+                if let Err(e) = answered {
+                    if let Some(ref mut dialog) = state.install.recovery_dialog {
+                        dialog.message = Some(format!("Failed to answer the installer: {}", e));
                     }
+                    return Ok(());
                 }
-                "start_installation" => {
-                    log::info!("Confirmed: starting installation");
-                    self.start_installation()?;
+                state.install.recovery_dialog = None;
+                state.transition(AppMode::Installation);
+                match choice {
+                    RecoveryChoice::Retry => {
+                        state.status.info(format!("Retrying {}", failure.phase))
+                    }
+                    RecoveryChoice::Skip => {
+                        state.status.warn(format!("Skipping {}", failure.phase))
+                    }
+                    _ => state
+                        .status
+                        .info("Aborting the installation and cleaning up"),
                 }
-                _ => {
-                    log::warn!("Unknown confirm action: {}", action);
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle confirmation dialog Enter key
+    fn handle_confirm_dialog_enter(&mut self) -> error::Result<()> {
+        let (confirmed, action, data) = {
+            let state = &mut self.state;
+            if state.confirm_dialog.is_none() {
+                return Ok(());
+            }
+            // Back to the screen the dialog covered, where the action runs
+            let Some(dialog) = state.close_confirm() else {
+                return Ok(());
+            };
+            // SECURITY FIX: Use is_confirmed() method to get correct selection
+            // selected = 0 means No/Cancel (left), selected = 1 means Yes/Confirm (right)
+            let confirmed = dialog.is_confirmed();
+            log::info!(
+                "ConfirmDialog Enter: selected={}, is_confirmed={}, action={}",
+                dialog.selected,
+                confirmed,
+                dialog.confirm_action
+            );
+            (confirmed, dialog.confirm_action, dialog.action_data)
+        };
+
+        if confirmed {
+            log::info!("Executing confirmed action: {}", action);
+            self.execute_confirmed_action(&action, data)?;
+        } else {
+            log::info!("Action cancelled, returning to previous mode");
+        }
+        Ok(())
+    }
+
+    /// Handle Enter in the file browser, loading the config file once one is chosen
+    fn handle_file_browser_enter(&mut self) -> error::Result<()> {
+        let selected_path = {
+            let state = &mut self.state;
+            match state.file_browser {
+                Some(ref mut browser) => {
+                    browser.handle_enter();
+                    browser.selected_file.clone().filter(|_| browser.complete)
                 }
+                None => None,
             }
+        };
+
+        if let Some(path) = selected_path {
+            self.load_config_file(&path)?;
         }
+        Ok(())
+    }
+
+    /// Offer the wipe methods a disk supports, the recommended one first
+    fn choose_wipe_method(&mut self, disk: &str) -> error::Result<()> {
+        let target = match wipe::inspect(disk) {
+            Ok(target) => target,
+            Err(e) => {
+                let state = &mut self.state;
+                state.tools.current = None;
+                state.status.error(format!("Cannot wipe {}: {}", disk, e));
+                return Ok(());
+            }
+        };
 
+        let choices = target
+            .methods()
+            .into_iter()
+            .map(|method| format!("{} - {}", method, method.description()))
+            .collect();
+        self.input_handler.start_selection(
+            format!("Wipe Method for {}", target.disk),
+            choices,
+            String::new(),
+        );
+        let state = &mut self.state;
+        state.tools.current = Some("wipe_method".to_string());
+        state.tools.wipe_target = Some(target);
+        state
+            .status
+            .info("Select how to wipe the disk (Enter to select, Esc to cancel)");
         Ok(())
     }
 
-    /// Execute wipe disk operation
-    fn execute_wipe_disk(&mut self, disk: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Show floating output for the operation
-        let mut state = self.lock_state_mut()?;
-        state.floating_output = Some(FloatingOutputState::new(&format!("Wiping {}", disk)));
-        state.floating_output.as_mut().unwrap().append_line(format!("Starting secure wipe of {}...", disk));
-        state.floating_output.as_mut().unwrap().append_line("This may take a while depending on disk size.".to_string());
-        state.mode = AppMode::FloatingOutput;
+    /// Wipe a disk in the background, showing progress in a floating window
+    fn execute_wipe_disk(&mut self, disk: &str, method: WipeMethod) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            state.floating_output = Some(FloatingOutputState {
+                title: format!("Wiping {}", disk),
+                content: vec![format!("Wiping {} ({})", disk, method.description())],
+                scroll_offset: 0,
+                auto_scroll: true,
+                complete: false,
+                progress: method.reports_progress().then_some(0),
+                status: "Running...".to_string(),
+            });
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some("wipe disk".to_string());
+        }
+
+        let tx = self.tool_tx.clone();
+        let disk = disk.to_string();
+        thread::spawn(move || {
+            // Re-inspect: the disk may have been mounted since it was picked
+            let result = wipe::inspect(&disk).and_then(|target| {
+                wipe::wipe(
+                    &target,
+                    method,
+                    |line| {
+                        let _ = tx.send(ToolMessage::Stdout(line));
+                    },
+                    |progress| {
+                        let _ = tx.send(ToolMessage::Progress(progress));
+                    },
+                )
+            });
+            let _ = tx.send(match result {
+                Ok(()) => ToolMessage::Complete {
+                    success: true,
+                    exit_code: Some(0),
+                },
+                Err(e) => ToolMessage::Error(e.to_string()),
+            });
+        });
+
         Ok(())
     }
 
@@ -957,74 +1679,113 @@ impl App {
         &mut self,
         action: &str,
         data: Option<String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         match action {
             "wipe_disk" => {
-                if let Some(disk) = data {
-                    self.execute_wipe_disk(&disk)?;
+                // data is "method:disk"
+                let params = data.as_deref().and_then(|d| d.split_once(':'));
+                if let Some((method, disk)) = params {
+                    if let Ok(method) = method.parse::<WipeMethod>() {
+                        self.execute_wipe_disk(disk, method)?;
+                    }
                 }
             }
             "format_partition" => {
-                if let Some(partition) = data {
-                    self.execute_tool_with_device("format_partition.sh", &partition, &[])?;
-                }
-            }
-            "install_bootloader" => {
-                if let Some(params) = data {
-                    // params format: "bootloader:disk"
-                    let parts: Vec<&str> = params.split(':').collect();
-                    if parts.len() == 2 {
-                        self.execute_tool_with_device(
-                            "install_bootloader.sh",
-                            parts[1],
-                            &["--bootloader", parts[0]],
-                        )?;
-                    }
+                // data is the confirmed FormatOptions as JSON
+                if let Some(options) = data
+                    .as_deref()
+                    .and_then(|d| serde_json::from_str::<FormatOptions>(d).ok())
+                {
+                    let flags = options.append_flags(ScriptArgs::new());
+                    let flags: Vec<&str> = flags.as_slice().iter().map(String::as_str).collect();
+                    self.execute_tool_with_device("format_partition.sh", &options.device, &flags)?;
+                }
+            }
+            "repair_bootloader" => {
+                // data is the proposed fixes as JSON
+                if let Some(fixes) = data
+                    .as_deref()
+                    .and_then(|d| serde_json::from_str::<Vec<bootrepair::Fix>>(d).ok())
+                {
+                    self.execute_bootloader_repair(fixes)?;
                 }
             }
-            "start_installation" => {
-                // Start the installation process
-                self.start_installation()?;
+            "restore_session" => {
+                self.restore_session()?;
+            }
+            "automated_install" => {
+                if let Some(path) = data {
+                    self.start_automated_install(std::path::Path::new(&path))?;
+                }
+            }
+            "resize_partition" => {
+                if let Some((device, size)) = data.as_deref().and_then(|d| d.split_once(':')) {
+                    self.execute_resize(device, size)?;
+                }
+            }
+            "delete_snapshot" | "rollback_snapshot" => {
+                // data is "device:number"
+                if let Some((device, number)) = data.as_deref().and_then(|d| d.split_once(':')) {
+                    self.execute_snapshot_tool(action, device, number)?;
+                }
+            }
+            "cleanup" => {
+                self.execute_cleanup()?;
+            }
+            "reset_all" => {
+                let state = &mut self.state;
+                state.guided.config.reset_all();
+                // The pre-seeded values are gone, so the wizard asks for them again
+                state.guided.preseeded.clear();
+                state.guided.region_filled = false;
+                state.status.info("All options reset to their defaults");
+            }
+            "restart_as_root" => {
+                if let (Some(tool), Some(launcher)) = (data, privilege::launcher()) {
+                    self.root_restart = Some((launcher, tool));
+                }
             }
             _ => {
                 // Unknown action
-                let mut state = self.lock_state_mut()?;
-                state.status_message = format!("Unknown action: {}", action);
+                let state = &mut self.state;
+                state.status.error(format!("Unknown action: {}", action));
             }
         }
         Ok(())
     }
 
     /// Handle main menu selection
-    fn handle_main_menu_selection(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn handle_main_menu_selection(&mut self) -> error::Result<()> {
         let selection = {
-            let state = self.lock_state()?;
-            state.main_menu_selection
+            let state = &self.state;
+            state.menu.main_selection
         };
 
         debug!("Main menu selection: {}", selection);
 
-        let mut state = self.lock_state_mut()?;
+        let state = &mut self.state;
         match selection {
             0 => {
                 // Guided Installer
-                state.mode = AppMode::GuidedInstaller;
-                state.status_message = "Starting guided installation...".to_string();
+                state.transition(AppMode::GuidedInstaller);
+                state.status.info("Starting guided installation...");
             }
             1 => {
                 // Automated Install
-                state.mode = AppMode::AutomatedInstall;
-                state.status_message =
-                    "Select configuration file for automated installation...".to_string();
+                state.transition(AppMode::AutomatedInstall);
+                state.status.info("Select configuration file for automated installation...");
             }
             2 => {
                 // Arch Linux Tools
-                state.mode = AppMode::ToolsMenu;
-                state.tools_menu_selection = 0;
-                state.status_message =
-                    "Arch Linux Tools - System repair and administration".to_string();
+                state.transition(AppMode::ToolsMenu);
+                state.menu.tools_selection = 0;
+                state.status.info("Arch Linux Tools - System repair and administration");
             }
             3 => {
+                // Clone System
+                return self.start_clone_system();
+            }
+            4 => {
                 // Quit
                 return Ok(());
             }
@@ -1034,43 +1795,49 @@ impl App {
     }
 
     /// Handle tools menu selection
-    fn handle_tools_menu_selection(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn handle_tools_menu_selection(&mut self) -> error::Result<()> {
         let selection = {
-            let state = self.lock_state()?;
-            state.tools_menu_selection
+            let state = &self.state;
+            state.menu.tools_selection
         };
 
-        let mut state = self.lock_state_mut()?;
+        let state = &mut self.state;
         match selection {
             0 => {
                 // Disk & Filesystem Tools
-                state.mode = AppMode::DiskTools;
-                state.tools_menu_selection = 0;
-                state.status_message = "Disk & Filesystem Tools".to_string();
+                state.transition(AppMode::DiskTools);
+                state.menu.tools_selection = 0;
+                state.status.info("Disk & Filesystem Tools");
             }
             1 => {
                 // System & Boot Tools
-                state.mode = AppMode::SystemTools;
-                state.tools_menu_selection = 0;
-                state.status_message = "System & Boot Tools".to_string();
+                state.transition(AppMode::SystemTools);
+                state.menu.tools_selection = 0;
+                state.status.info("System & Boot Tools");
             }
             2 => {
                 // User & Security Tools
-                state.mode = AppMode::UserTools;
-                state.tools_menu_selection = 0;
-                state.status_message = "User & Security Tools".to_string();
+                state.transition(AppMode::UserTools);
+                state.menu.tools_selection = 0;
+                state.status.info("User & Security Tools");
             }
             3 => {
                 // Network Tools
-                state.mode = AppMode::NetworkTools;
-                state.tools_menu_selection = 0;
-                state.status_message = "Network Tools".to_string();
+                state.transition(AppMode::NetworkTools);
+                state.menu.tools_selection = 0;
+                state.status.info("Network Tools");
             }
             4 => {
+                // Btrfs Snapshot Tools
+                state.transition(AppMode::SnapshotTools);
+                state.menu.tools_selection = 0;
+                state.status.info("Snapshot Tools");
+            }
+            5 => {
                 // Back to Main Menu
-                state.mode = AppMode::MainMenu;
-                state.main_menu_selection = 0;
-                state.status_message = "Welcome to Arch Linux Toolkit".to_string();
+                state.transition(AppMode::MainMenu);
+                state.menu.main_selection = 0;
+                state.status.info("Welcome to Arch Linux Toolkit");
             }
             _ => {}
         }
@@ -1078,27 +1845,26 @@ impl App {
     }
 
     /// Handle tool selection within a category
-    fn handle_tool_selection(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn handle_tool_selection(&mut self) -> error::Result<()> {
         let (current_mode, selection) = {
-            let state = self.lock_state()?;
-            (state.mode.clone(), state.tools_menu_selection)
+            let state = &self.state;
+            (state.mode.clone(), state.menu.tools_selection)
         };
 
         // Check if user selected "Back" option (last item in each menu)
         let is_back_option = match current_mode {
-            AppMode::DiskTools => selection == 5, // 6 items (0-5), back is at index 5
+            AppMode::DiskTools => selection == 7, // 8 items (0-7), back is at index 7
             AppMode::SystemTools | AppMode::UserTools => selection == 5, // 6 items (0-5), back is at index 5
-            AppMode::NetworkTools => selection == 4, // 5 items (0-4), back is at index 4
+            AppMode::NetworkTools | AppMode::SnapshotTools => selection == 4, // 5 items (0-4), back is at index 4
             _ => false,
         };
 
         if is_back_option {
             // Go back to tools menu
-            let mut state = self.lock_state_mut()?;
-            state.mode = AppMode::ToolsMenu;
-            state.tools_menu_selection = 0;
-            state.status_message =
-                "Arch Linux Tools - System repair and administration".to_string();
+            let state = &mut self.state;
+            state.transition(AppMode::ToolsMenu);
+            state.menu.tools_selection = 0;
+            state.status.info("Arch Linux Tools - System repair and administration");
         } else {
             // Execute the selected tool
             self.execute_tool(&current_mode, selection)?;
@@ -1111,7 +1877,14 @@ impl App {
         &mut self,
         mode: &AppMode,
         selection: usize,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
+        // Offer to restart as root rather than failing halfway
+        if let Some(tool) = menus::tool_name(mode, selection) {
+            if privilege::requires_root(tool) && !self.state.privileged {
+                return self.request_root(mode, tool);
+            }
+        }
+
         match mode {
             AppMode::DiskTools => {
                 match selection {
@@ -1120,42 +1893,43 @@ impl App {
                         let _ = self.launch_embedded_tool("cfdisk", &[], "cfdisk", AppMode::DiskTools);
                     }
                     1 => {
-                        // Format Partition - Use disk selection dialog
-                        self.input_handler.start_disk_selection("".to_string());
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("format_partition".to_string());
-                        state.status_message =
-                            "Select partition to format (Enter to select, Esc to cancel)"
-                                .to_string();
+                        // Format Partition - Create dialog, confirmed before formatting
+                        self.create_tool_dialog("format_partition")?;
                     }
                     2 => {
                         // Wipe Disk - Use disk selection dialog
                         self.input_handler.start_disk_selection("".to_string());
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("wipe_disk".to_string());
-                        state.status_message =
-                            "Select disk to wipe (Enter to select, Esc to cancel)".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("wipe_disk".to_string());
+                        state.status.info("Select disk to wipe (Enter to select, Esc to cancel)");
                     }
                     3 => {
                         // Check Disk Health - Use disk selection dialog
                         self.input_handler.start_disk_selection("".to_string());
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("health".to_string());
-                        state.status_message =
-                            "Select disk to check health (Enter to select, Esc to cancel)"
-                                .to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("health".to_string());
+                        state.status.info(
+                            "Select disk to check health (Enter to select, Esc to cancel)",
+                        );
                     }
                     4 => {
-                        // Mount/Unmount Partitions - Create dialog
-                        self.create_tool_dialog("mount")?;
+                        // Mount/Unmount Partitions - Device tree with mount actions
+                        self.open_mount_manager()?;
                     }
                     5 => {
+                        // Resize Partition - Create dialog, previewed before applying
+                        self.create_tool_dialog("resize_partition")?;
+                    }
+                    6 => {
+                        // Clean Up Failed Install - Confirm the detected leftovers first
+                        self.preview_cleanup()?;
+                    }
+                    7 => {
                         // Back to Tools Menu
-                        let mut state = self.lock_state_mut()?;
-                        state.mode = AppMode::ToolsMenu;
-                        state.tools_menu_selection = 0;
-                        state.status_message =
-                            "Arch Linux Tools - System repair and administration".to_string();
+                        let state = &mut self.state;
+                        state.transition(AppMode::ToolsMenu);
+                        state.menu.tools_selection = 0;
+                        state.status.info("Arch Linux Tools - System repair and administration");
                     }
                     _ => {}
                 }
@@ -1163,37 +1937,35 @@ impl App {
             AppMode::SystemTools => {
                 match selection {
                     0 => {
-                        // Install/Repair Bootloader - Create dialog
-                        self.create_tool_dialog("install_bootloader")?;
+                        // Repair Bootloader - Pick the system, then diagnose it
+                        self.start_bootloader_repair()?;
                     }
                     1 => {
                         // Generate fstab - Create dialog
                         self.create_tool_dialog("generate_fstab")?;
                     }
                     2 => {
-                        // Chroot into System - Create dialog
-                        self.create_tool_dialog("chroot")?;
+                        // Chroot into System - Pick the system to mount and enter
+                        self.start_chroot_rescue()?;
                     }
                     3 => {
-                        // Enable/Disable Services
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("manage_services".to_string());
-                        state.status_message = "Service management tool...".to_string();
+                        // Manage Services - Browse the units of this or the installed system
+                        self.open_service_manager()?;
                     }
                     4 => {
                         // System Information - Simple tool with no parameters
                         {
-                            let mut state = self.lock_state_mut()?;
-                            state.current_tool = Some("system_info".to_string());
-                            state.status_message = "Gathering system information...".to_string();
+                            let state = &mut self.state;
+                            state.tools.current = Some("system_info".to_string());
+                            state.status.info("Gathering system information...");
                         }
 
                         // Execute system info tool directly
                         if let Err(e) = self.execute_simple_tool("system_info.sh", &["--detailed"])
                         {
                             eprintln!("Failed to execute system info tool: {}", e);
-                            let mut state = self.lock_state_mut()?;
-                            state.status_message = "System info tool failed".to_string();
+                            let state = &mut self.state;
+                            state.status.error("System info tool failed");
                         }
                     }
                     _ => {}
@@ -1211,21 +1983,21 @@ impl App {
                     }
                     2 => {
                         // Manage User Groups
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("manage_groups".to_string());
-                        state.status_message = "User group management tool...".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("manage_groups".to_string());
+                        state.status.info("User group management tool...");
                     }
                     3 => {
                         // Configure SSH
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("configure_ssh".to_string());
-                        state.status_message = "SSH configuration tool...".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("configure_ssh".to_string());
+                        state.status.info("SSH configuration tool...");
                     }
                     4 => {
                         // Security Audit
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("security_audit".to_string());
-                        state.status_message = "Security audit tool...".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("security_audit".to_string());
+                        state.status.info("Security audit tool...");
                     }
                     _ => {}
                 }
@@ -1239,9 +2011,9 @@ impl App {
                     1 => {
                         // Test Network Connectivity - Simple tool
                         {
-                            let mut state = self.lock_state_mut()?;
-                            state.current_tool = Some("test_network".to_string());
-                            state.status_message = "Testing network connectivity...".to_string();
+                            let state = &mut self.state;
+                            state.tools.current = Some("test_network".to_string());
+                            state.status.info("Testing network connectivity...");
                         }
 
                         // Execute network test tool directly
@@ -1249,307 +2021,264 @@ impl App {
                             self.execute_simple_tool("test_network.sh", &["--action", "full"])
                         {
                             eprintln!("Failed to execute network test tool: {}", e);
-                            let mut state = self.lock_state_mut()?;
-                            state.status_message = "Network test tool failed".to_string();
+                            let state = &mut self.state;
+                            state.status.error("Network test tool failed");
                         }
                     }
                     2 => {
                         // Configure Firewall
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("configure_firewall".to_string());
-                        state.status_message = "Firewall configuration tool...".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("configure_firewall".to_string());
+                        state.status.info("Firewall configuration tool...");
                     }
                     3 => {
                         // Network Diagnostics
-                        let mut state = self.lock_state_mut()?;
-                        state.current_tool = Some("network_diagnostics".to_string());
-                        state.status_message = "Network diagnostics tool...".to_string();
+                        let state = &mut self.state;
+                        state.tools.current = Some("network_diagnostics".to_string());
+                        state.status.info("Network diagnostics tool...");
                     }
                     _ => {}
                 }
             }
-            _ => {}
-        }
-        Ok(())
-    }
-
-    /// Handle guided installer enter (original logic)
-    fn handle_guided_installer_enter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let (should_open_input, should_start_installation) = {
-            let state = self.lock_state()?;
-            // Check if we're on the green button (one step past the last config option)
-            if state.config_scroll.selected_index == state.config.options.len() {
-                (false, true) // Start installation
-            } else {
-                (true, false) // Open input dialog
+            AppMode::SnapshotTools => {
+                match selection {
+                    0 => {
+                        // List Snapshots - Create dialog for the btrfs device
+                        self.create_tool_dialog("list_snapshots")?;
+                    }
+                    1 => {
+                        // Create Snapshot - Create dialog
+                        self.create_tool_dialog("create_snapshot")?;
+                    }
+                    2 => {
+                        // Delete Snapshot - Create dialog, confirmed before deleting
+                        self.create_tool_dialog("delete_snapshot")?;
+                    }
+                    3 => {
+                        // Roll Back to Snapshot - Create dialog, confirmed before replacing @
+                        self.create_tool_dialog("rollback_snapshot")?;
+                    }
+                    _ => {}
+                }
             }
-        };
-
-        // Open input dialog if needed
-        if should_open_input {
-            self.open_input_dialog()?;
+            _ => {}
         }
+        Ok(())
+    }
 
-        // Start installation if needed - show confirmation dialog first
-        if should_start_installation {
-            if self.validate_configuration_for_installation() {
-                // Show confirmation dialog before starting
-                let mut state = self.lock_state_mut()?;
-                state.pre_dialog_mode = Some(AppMode::GuidedInstaller);
-                state.confirm_dialog = Some(start_install_confirm());
-                state.mode = AppMode::ConfirmDialog;
-            } else {
-                // Validation failed - status message already set in validate_configuration_for_installation
-                // User will see the error message
-            }
+    /// Start the program behind the highlighted tool in the embedded terminal
+    ///
+    /// Tools that work on a disk ask for it first; the program is started
+    /// once it is chosen (see `submit_value`).
+    fn run_interactively(&mut self) -> error::Result<()> {
+        let (mode, selection, privileged) = {
+            let state = &self.state;
+            (state.mode.clone(), state.menu.tools_selection, state.privileged)
+        };
+        let Some(tool) = menus::tool_name(&mode, selection) else {
+            return Ok(());
+        };
+        let Some(interactive) = interactive::find(tool) else {
+            self.state
+                .status
+                .warn(format!("{} cannot be run interactively", tool));
+            return Ok(());
+        };
+        // These programs change disks or the network, even for read-only tools
+        if !privileged {
+            return self.request_root(&mode, tool);
+        }
+        if !interactive.is_installed() && !simulate::is_enabled() {
+            self.state
+                .status
+                .error(format!("{} is not installed", interactive.program));
+            return Ok(());
         }
 
-        Ok(())
+        if interactive.needs_disk {
+            self.input_handler.start_disk_selection(String::new());
+            let state = &mut self.state;
+            state.tools.current = Some("run_interactively".to_string());
+            state.status.info(format!(
+                "Select disk for {} (Enter to select, Esc to cancel)",
+                interactive.program
+            ));
+            return Ok(());
+        }
+        self.launch_interactive(interactive, None, mode)
     }
 
-    /// Handle automated install enter
-    fn handle_automated_install_enter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Launch file browser for config file selection
-        let start_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("/"));
-        let file_browser = crate::components::file_browser::FileBrowserState::new(
-            &start_dir,
-            vec!["toml".to_string(), "json".to_string()],
-        );
+    /// Launch `interactive` on `disk` in the embedded terminal, returning to `mode`
+    fn launch_interactive(
+        &mut self,
+        interactive: &Interactive,
+        disk: Option<&str>,
+        mode: AppMode,
+    ) -> error::Result<()> {
+        let args = interactive.args(disk);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.launch_embedded_tool(
+            interactive.program,
+            &args,
+            &interactive.command_line(disk),
+            mode,
+        )
+    }
 
-        let mut state = self.lock_state_mut()?;
-        state.file_browser = Some(file_browser);
-        state.mode = AppMode::FileBrowser;
-        state.status_message = "Select a configuration file (.toml or .json)".to_string();
+    /// Ask to restart as root to run `tool`, or explain how to if that is impossible
+    fn request_root(
+        &mut self,
+        mode: &AppMode,
+        tool: &str,
+    ) -> error::Result<()> {
+        let state = &mut self.state;
+        match privilege::launcher() {
+            Some(launcher) => {
+                state.open_confirm(root_required_confirm(tool, launcher), mode.clone());
+            }
+            None => state.status.error(format!(
+                "{} needs root and neither sudo nor pkexec is installed: run archinstall-tui as root",
+                tool
+            )),
+        }
         Ok(())
     }
 
-    /// Handle back key navigation
-    fn handle_back_key(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let current_mode = {
-            let state = self.lock_state()?;
-            state.mode.clone()
-        };
+    /// Show the installation summary once the configuration validates
+    fn request_start_installation(&mut self) -> error::Result<()> {
+        // Only the guided installer has a configuration to install
+        if self.state.mode != AppMode::GuidedInstaller {
+            return Ok(());
+        }
+        // Saving the configuration needs no privileges, installing does
+        if self.save_config_path.is_none() && !self.state.privileged {
+            self.state.status.error(INSTALL_NEEDS_ROOT);
+            return Ok(());
+        }
 
-        let mut state = self.lock_state_mut()?;
-        match current_mode {
-            AppMode::MainMenu => {
-                // Already at top level - could show exit confirmation
-                state.status_message =
-                    "Press 'Q' to quit or use arrow keys to navigate".to_string();
-            }
-            AppMode::GuidedInstaller => {
-                // Go back to main menu from guided installer
-                state.mode = AppMode::MainMenu;
-                state.main_menu_selection = 0;
-                state.status_message = "Welcome to Arch Linux Toolkit".to_string();
-            }
-            AppMode::AutomatedInstall => {
-                // Go back to main menu
-                state.mode = AppMode::MainMenu;
-                state.main_menu_selection = 0;
-                state.status_message = "Welcome to Arch Linux Toolkit".to_string();
-            }
-            AppMode::ToolsMenu => {
-                // Go back to main menu
-                state.mode = AppMode::MainMenu;
-                state.main_menu_selection = 0;
-                state.status_message = "Welcome to Arch Linux Toolkit".to_string();
-            }
-            AppMode::DiskTools
-            | AppMode::SystemTools
-            | AppMode::UserTools
-            | AppMode::NetworkTools => {
-                // Go back to tools menu
-                state.mode = AppMode::ToolsMenu;
-                state.tools_menu_selection = 0;
-                state.status_message =
-                    "Arch Linux Tools - System repair and administration".to_string();
-            }
-            AppMode::ToolDialog => {
-                // Go back to the appropriate tool menu based on current tool
-                if let Some(ref tool_name) = state.current_tool {
-                    match tool_name.as_str() {
-                        "format_partition" | "wipe_disk" | "check_disk_health"
-                        | "mount_partitions" | "manual_partition" => {
-                            state.mode = AppMode::DiskTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Disk & Filesystem Tools".to_string();
-                        }
-                        "install_bootloader" | "generate_fstab" | "chroot_system"
-                        | "manage_services" | "system_info" => {
-                            state.mode = AppMode::SystemTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "System & Boot Tools".to_string();
-                        }
-                        "add_user" | "reset_password" | "manage_groups" | "configure_ssh"
-                        | "security_audit" => {
-                            state.mode = AppMode::UserTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "User & Security Tools".to_string();
-                        }
-                        "configure_network"
-                        | "test_network"
-                        | "configure_firewall"
-                        | "network_diagnostics" => {
-                            state.mode = AppMode::NetworkTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Network Tools".to_string();
-                        }
-                        _ => {
-                            // Fallback to tools menu
-                            state.mode = AppMode::ToolsMenu;
-                            state.tools_menu_selection = 0;
-                            state.status_message =
-                                "Arch Linux Tools - System repair and administration".to_string();
-                        }
-                    }
-                } else {
-                    // Fallback to tools menu
-                    state.mode = AppMode::ToolsMenu;
-                    state.tools_menu_selection = 0;
-                    state.status_message =
-                        "Arch Linux Tools - System repair and administration".to_string();
-                }
-                // Clear tool dialog state
-                state.tool_dialog = None;
-                state.current_tool = None;
-            }
-            AppMode::ToolExecution => {
-                // Go back to the appropriate tool menu (same logic as ToolDialog)
-                if let Some(ref tool_name) = state.current_tool {
-                    match tool_name.as_str() {
-                        "format_partition" | "wipe_disk" | "check_disk_health"
-                        | "mount_partitions" | "manual_partition" => {
-                            state.mode = AppMode::DiskTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Disk & Filesystem Tools".to_string();
-                        }
-                        "install_bootloader" | "generate_fstab" | "chroot_system"
-                        | "manage_services" | "system_info" => {
-                            state.mode = AppMode::SystemTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "System & Boot Tools".to_string();
-                        }
-                        "add_user" | "reset_password" | "manage_groups" | "configure_ssh"
-                        | "security_audit" => {
-                            state.mode = AppMode::UserTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "User & Security Tools".to_string();
-                        }
-                        "configure_network"
-                        | "test_network"
-                        | "configure_firewall"
-                        | "network_diagnostics" => {
-                            state.mode = AppMode::NetworkTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Network Tools".to_string();
-                        }
-                        _ => {
-                            // Fallback to tools menu
-                            state.mode = AppMode::ToolsMenu;
-                            state.tools_menu_selection = 0;
-                            state.status_message =
-                                "Arch Linux Tools - System repair and administration".to_string();
-                        }
-                    }
-                } else {
-                    // Fallback to tools menu
-                    state.mode = AppMode::ToolsMenu;
-                    state.tools_menu_selection = 0;
-                    state.status_message =
-                        "Arch Linux Tools - System repair and administration".to_string();
+        // On failure the status message explains what is missing
+        if self.validate_configuration_for_installation() {
+            // Manual layouts are made outside the installer and plans for
+            // existing partitions depend on the disks; both are checked here
+            let strategy = self.state.guided.config.value("Partitioning Strategy");
+            if matches!(strategy.as_str(), "manual" | "existing") && !simulate::is_enabled() {
+                let check = self.check_partitions()?;
+                if !check.is_ready() {
+                    return self.show_partition_check(check);
                 }
-                // Clear tool execution state
-                state.tool_output.clear();
-                state.current_tool = None;
-            }
-            AppMode::Installation => {
-                // During installation, go back to guided installer
-                state.mode = AppMode::GuidedInstaller;
-                state.status_message =
-                    "Installation cancelled - configure your settings".to_string();
-            }
-            AppMode::Complete => {
-                // From completion screen, go back to main menu
-                state.mode = AppMode::MainMenu;
-                state.main_menu_selection = 0;
-                state.status_message = "Welcome to Arch Linux Toolkit".to_string();
             }
-            AppMode::EmbeddedTerminal => {
-                // Embedded terminal uses Ctrl+Q to exit, but we can also handle 'b'
-                // Return to previous mode - will be handled by exit_embedded_terminal
-                drop(state);
-                self.exit_embedded_terminal()?;
+            // Disk models and existing systems make the warnings concrete
+            let disks = crate::hardware::detect_disks();
+            let state = &mut self.state;
+            if let Err(error) = existing_systems_check(&state.guided.config, &disks)
+                .and_then(|()| lvm_space_check(&state.guided.config, &disks))
+            {
+                state.status.error(error);
                 return Ok(());
             }
-            AppMode::FloatingOutput => {
-                // Dismiss floating output and return to tools menu
-                if let Some(_output) = state.floating_output.take() {
-                    state.mode = AppMode::ToolsMenu;
-                    state.tools_menu_selection = 0;
-                    state.status_message =
-                        "Arch Linux Tools - System repair and administration".to_string();
-                }
-            }
-            AppMode::FileBrowser => {
-                // Cancel file browser and return to automated install
-                state.file_browser = None;
-                state.mode = AppMode::AutomatedInstall;
-                state.status_message = "File selection cancelled".to_string();
-            }
-            AppMode::ConfirmDialog => {
-                // Cancel confirmation dialog and return to previous mode
-                state.confirm_dialog = None;
-                if let Some(mode) = state.pre_dialog_mode.take() {
-                    state.mode = mode;
-                } else {
-                    state.mode = AppMode::ToolsMenu;
-                }
-                state.status_message = "Operation cancelled".to_string();
-            }
+            state.guided.summary = Some(InstallSummaryState::new(&state.guided.config, &disks));
+            state.transition(AppMode::Summary);
+            state.status.info("Review the installation summary");
         }
         Ok(())
     }
 
-    /// Validate that all required configuration options are set and valid
-    fn validate_configuration(&self, config: &Configuration) -> bool {
-        // First check basic option validation
-        if !config.options.iter().all(|option| option.is_valid()) {
-            return false;
+    /// Check the manual partition layout of the disks in the configuration
+    fn check_partitions(&self) -> error::Result<PartitionCheckState> {
+        let config = self.state.guided.config.clone();
+        Ok(PartitionCheckState::for_config(&config))
+    }
+
+    /// Show a check of the manual partition layout
+    fn show_partition_check(&mut self, check: PartitionCheckState) -> error::Result<()> {
+        let state = &mut self.state;
+        match check.report {
+            Ok(ref report) if report.has_errors() => state.status.error(format!(
+                "The partition layout has {} error(s)",
+                report.error_count()
+            )),
+            Ok(ref report) if !report.is_mounted() => state.status.warn(format!(
+                "Mount the root partition at {} before installing",
+                partition_check::TARGET
+            )),
+            Ok(_) => state.status.info("Partition layout checked"),
+            Err(ref e) => state.status.error(format!("Partition check failed: {}", e)),
         }
+        state.guided.partition_check = Some(check);
+        state.transition(AppMode::PartitionCheck);
+        Ok(())
+    }
 
-        // Then check secure boot requirements
-        self.validate_secure_boot_requirements(config)
+    /// Check the shown partition layout again, after the user changed it
+    fn recheck_partitions(&mut self) -> error::Result<()> {
+        let Some(mut rescanned) = self.state.guided.partition_check.clone() else {
+            return Ok(());
+        };
+        rescanned.rescan();
+
+        let state = &mut self.state;
+        if let Some(ref mut check) = state.guided.partition_check {
+            *check = rescanned;
+            let message = match check.report {
+                Ok(ref report) => format!(
+                    "Partition layout checked again: {} problem(s)",
+                    report.problems.len()
+                ),
+                Err(ref e) => format!("Partition check failed: {}", e),
+            };
+            state.status.info(message);
+        }
+        Ok(())
     }
 
-    /// Validate secure boot requirements
-    fn validate_secure_boot_requirements(&self, config: &Configuration) -> bool {
-        // Find the Secure Boot option
-        if let Some(secure_boot_option) =
-            config.options.iter().find(|opt| opt.name == "Secure Boot")
+    /// Start the installation if the summary's confirmation was typed
+    fn confirm_summary(&mut self) -> error::Result<()> {
         {
-            if secure_boot_option.value.to_lowercase() == "yes" {
-                // Check if Boot Mode is UEFI
-                if let Some(boot_mode_option) =
-                    config.options.iter().find(|opt| opt.name == "Boot Mode")
-                {
-                    let boot_mode = boot_mode_option.value.to_lowercase();
-                    if boot_mode != "uefi" && boot_mode != "auto" {
-                        // Secure boot validation failed - this will be handled in the main validation
-                        return false;
-                    }
-                }
+            let state = &mut self.state;
+            let Some(ref mut summary) = state.guided.summary else {
+                return Ok(());
+            };
+            if !summary.is_confirmed() {
+                summary.error = Some(format!(
+                    "Type {} exactly to start the installation",
+                    summary.expected()
+                ));
+                return Ok(());
             }
+            state.guided.summary = None;
         }
-        true
+        self.start_installation()
     }
 
-    /// Check if secure boot warning should be shown after setting value
-    fn check_secure_boot_warning(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Secure boot warning is now always shown in the dialog itself
+    /// Handle automated install enter
+    fn handle_automated_install_enter(&mut self) -> error::Result<()> {
+        // Launch file browser for config file selection
+        let start_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("/"));
+        let file_browser = crate::components::file_browser::FileBrowserState::new(
+            &start_dir,
+            vec!["json".to_string()],
+        );
+
+        let state = &mut self.state;
+        state.file_browser = Some(file_browser);
+        state.transition(AppMode::FileBrowser);
+        state.status.info("Select a configuration file (.json)");
         Ok(())
     }
 
+    /// Validate that all required configuration options are set and valid
+    fn validate_configuration(&self, config: &Configuration) -> bool {
+        // First check basic option validation
+        if !config.options.iter().all(|option| option.is_valid()) {
+            return false;
+        }
+
+        // Then check the requirements spanning several options
+        CROSS_OPTION_CHECKS
+            .iter()
+            .all(|(check, _)| check(config).is_none())
+    }
+
     /// Check if UEFI is supported on this system
     fn is_uefi_supported(&self) -> bool {
         // Check for UEFI support by looking at /sys/firmware/efi
@@ -1587,34 +2316,18 @@ impl App {
             .filter_map(|option| option.validation_error())
             .collect();
 
-        // Add secure boot validation errors
-        if let Some(secure_boot_option) =
-            config.options.iter().find(|opt| opt.name == "Secure Boot")
-        {
-            if secure_boot_option.value.to_lowercase() == "yes" {
-                if let Some(boot_mode_option) =
-                    config.options.iter().find(|opt| opt.name == "Boot Mode")
-                {
-                    let boot_mode = boot_mode_option.value.to_lowercase();
-                    if boot_mode != "uefi" && boot_mode != "auto" {
-                        errors.push("Secure Boot requires UEFI boot mode. Please configure UEFI firmware first.".to_string());
-                    }
-                }
-            }
-        }
+        errors.extend(
+            CROSS_OPTION_CHECKS
+                .iter()
+                .filter_map(|(check, _)| check(config)),
+        );
 
         errors
     }
 
     /// Validate configuration for installation (with user feedback)
     fn validate_configuration_for_installation(&mut self) -> bool {
-        let config = {
-            let state = match self.lock_state() {
-                Ok(state) => state,
-                Err(_) => return false, // If we can't lock the state, validation fails
-            };
-            state.config.clone()
-        };
+        let config = self.state.guided.config.clone();
 
         // Check for secure boot issues first (show warning dialog)
         if let Some(secure_boot_option) =
@@ -1637,88 +2350,148 @@ impl App {
             // All validation passed - installation can proceed
             true
         } else {
-            let mut state = match self.lock_state_mut() {
-                Ok(state) => state,
-                Err(_) => return false, // If we can't lock the state, validation fails
-            };
             let errors = self.get_validation_errors(&config);
+            let state = &mut self.state;
 
             if errors.len() == 1 {
-                state.status_message = format!("❌ Cannot start installation: {}", errors[0]);
+                state.status.error(format!("❌ Cannot start installation: {}", errors[0]));
             } else {
-                state.status_message = format!(
+                state.status.error(format!(
                     "❌ Cannot start installation: {} (and {} more errors)",
                     errors[0],
                     errors.len() - 1
-                );
+                ));
             }
             false
         }
     }
 
     /// Start the installation process
-    fn start_installation(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn start_installation(&mut self) -> error::Result<()> {
         info!("Starting installation process");
 
         // Check if we need to save the config before starting
         if let Some(save_path) = &self.save_config_path {
             info!("Saving configuration to: {:?}", save_path);
-            let state = self.lock_state()?;
-            let file_config = crate::config_file::InstallationConfig::from(&state.config);
-            file_config.save_to_file(save_path)?;
+            // An option the file cannot hold is reported rather than dropped
+            let saved = crate::config_file::InstallationConfig::try_from(&self.state.guided.config)
+                .and_then(|file_config| file_config.save_to_file(save_path));
+            if let Err(e) = saved {
+                self.state.status.error(e.to_string());
+                return Ok(());
+            }
 
-            let mut state_mut = self.lock_state_mut()?;
-            state_mut.status_message = format!("✓ Config saved to {}", save_path.display());
-            drop(state_mut);
+            self.state
+                .status
+                .info(format!("✓ Config saved to {}", save_path.display()));
 
             // Give user a moment to see the save message
             std::thread::sleep(std::time::Duration::from_millis(1000));
         }
 
+        if !self.state.privileged {
+            self.state.status.error(INSTALL_NEEDS_ROOT);
+            return Ok(());
+        }
+
+        // The configuration is committed now; a stale session must not be offered again
+        if let Some(ref path) = self.session_path {
+            if let Err(e) = session::clear_session(path) {
+                log::warn!("Failed to remove session file {:?}: {}", path, e);
+            }
+        }
+
         // Update state to installation mode
         {
-            let mut state = self.lock_state_mut()?;
-            state.mode = AppMode::Installation;
-            state.status_message = "Starting installation...".to_string();
+            let state = &mut self.state;
+            state.transition(AppMode::Installation);
+            state.status.info("Starting installation...");
         }
 
         // Create installer with current configuration
         let config = {
-            let state = self.lock_state()?;
-            state.config.clone()
+            let state = &self.state;
+            state.guided.config.clone()
         };
 
-        self.installer = Some(Installer::new(config, Arc::clone(&self.state)));
+        self.installer = Some(
+            Installer::new(config, self.state_handle())
+                .with_report_dir(self.report_dir.clone())
+                .with_timeout_policy(self.timeout_policy.clone()),
+        );
 
         // Start installation in background
         if let Some(ref mut installer) = self.installer {
-            installer.start()?;
+            installer.start(&mut self.state)?;
         }
 
         Ok(())
     }
 
     /// Open input dialog for the current configuration option
-    fn open_input_dialog(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let option = {
-            let state = self.lock_state()?;
-            let current_step = state.config_scroll.selected_index;
-            state.config.options[current_step].clone()
+    ///
+    /// The dialog follows the option's entry in the option table. An option
+    /// whose requirement does not hold is not opened, unless its value has
+    /// to be fixed before installing.
+    fn open_input_dialog(&mut self) -> error::Result<()> {
+        let (option, spec, config) = {
+            let state = &mut self.state;
+            state.guided.refresh_validity();
+            let current_step = state.guided.scroll.selected_index;
+            let option = state.guided.config.options[current_step].clone();
+            let Some(spec) = options::spec(&option.name) else {
+                return Ok(());
+            };
+            if let Err(reason) = spec.requirement(&state.guided.config) {
+                let valid = state
+                    .guided
+                    .validity
+                    .get(current_step)
+                    .is_none_or(|validity| *validity == Validity::Valid);
+                if valid {
+                    state
+                        .status
+                        .warn(format!("{} does not apply: {}.", option.name, reason));
+                    return Ok(());
+                }
+            }
+            (option, spec, state.guided.config.clone())
         };
 
-        match option.name.as_str() {
-            "Boot Mode" => {
-                // Show normal selection dialog
-                let options = InputHandler::get_predefined_options(&option.name);
+        match spec.input {
+            Input::Select(_) => {
+                let options = spec.choices();
                 self.input_handler
                     .start_selection(option.name.clone(), options, option.value);
             }
-            "Secure Boot" => {
+            Input::MultiSelect { min, max, .. } => {
+                self.input_handler.start_multi_select(
+                    option.name.clone(),
+                    spec.choices(),
+                    &option.value,
+                    min,
+                    max,
+                );
+            }
+            Input::Text { placeholder, .. } => {
+                self.input_handler.start_text_input(
+                    option.name.clone(),
+                    option.value,
+                    placeholder.to_string(),
+                );
+            }
+            Input::Password { placeholder } => {
+                let policy = config.password_policy();
+                self.input_handler.start_password_input(
+                    option.name.clone(),
+                    option.value,
+                    placeholder.to_string(),
+                    policy,
+                );
+            }
+            Input::SecureBoot => {
                 // Always show selection dialog with static warning about requirements
-                let mut options = InputHandler::get_predefined_options(&option.name);
-
-                // Check if UEFI is supported
-                let uefi_supported = self.is_uefi_supported();
+                let mut options = spec.choices();
 
                 // Insert static warning at the top of the options
                 options.insert(
@@ -1734,7 +2507,7 @@ impl App {
                 options.insert(4, "".to_string());
 
                 // If UEFI is not supported, only show "No" option
-                if !uefi_supported {
+                if !self.is_uefi_supported() {
                     options = vec!["No".to_string()];
                     options.insert(
                         0,
@@ -1748,157 +2521,12 @@ impl App {
                 self.input_handler
                     .start_selection(option.name.clone(), options, option.value);
             }
-            "Encryption" => {
-                // Only allow encryption Yes/No for manual partitioning
-                let partitioning_strategy = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()), // If we can't lock the state, skip this option
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Partitioning Strategy")
-                        .map(|opt| opt.value.clone())
-                        .unwrap_or_default()
-                };
-
-                if partitioning_strategy == "manual" {
-                    let options = vec!["Yes".to_string(), "No".to_string()];
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                } else {
-                    // Show message that encryption is auto-set for non-manual strategies
-                    if let Ok(mut state) = self.lock_state_mut() {
-                        state.status_message = "Encryption is auto-set based on partitioning strategy. Use manual partitioning to control encryption.".to_string();
-                    }
-                }
-            }
-            "Swap Size" => {
-                // Only allow swap size configuration if swap is enabled
-                let swap_enabled = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Swap")
-                        .map(|opt| opt.value.to_lowercase() == "yes")
-                        .unwrap_or(false)
-                };
-
-                if swap_enabled {
-                    let options = InputHandler::get_predefined_options(&option.name);
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                } else if let Ok(mut state) = self.lock_state_mut() {
-                    state.status_message =
-                        "Swap size can only be configured when swap is enabled.".to_string();
-                }
-            }
-            "Btrfs Frequency" | "Btrfs Keep Count" | "Btrfs Assistant" => {
-                // Only allow btrfs configuration if snapshots are enabled
-                let snapshots_enabled = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Btrfs Snapshots")
-                        .map(|opt| opt.value.to_lowercase() == "yes")
-                        .unwrap_or(false)
-                };
-
-                if snapshots_enabled {
-                    let options = InputHandler::get_predefined_options(&option.name);
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                } else if let Ok(mut state) = self.lock_state_mut() {
-                    state.status_message = format!(
-                        "{} can only be configured when Btrfs snapshots are enabled.",
-                        option.name
-                    );
-                }
-            }
-            "GRUB Theme Selection" => {
-                // Only allow theme selection if GRUB themes are enabled
-                let themes_enabled = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "GRUB Theme")
-                        .map(|opt| opt.value.to_lowercase() == "yes")
-                        .unwrap_or(false)
-                };
-
-                if themes_enabled {
-                    let options = InputHandler::get_predefined_options(&option.name);
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                } else if let Ok(mut state) = self.lock_state_mut() {
-                    state.status_message =
-                        "GRUB theme selection is only available when GRUB themes are enabled."
-                            .to_string();
-                }
-            }
-            "Git Repository URL" => {
-                // Only allow URL input if git repository is enabled
-                let git_enabled = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Git Repository")
-                        .map(|opt| opt.value.to_lowercase() == "yes")
-                        .unwrap_or(false)
-                };
-
-                if git_enabled {
-                    self.input_handler.start_text_input(
-                        option.name.clone(),
-                        option.value,
-                        "Enter git repository URL".to_string(),
-                    );
-                } else if let Ok(mut state) = self.lock_state_mut() {
-                    state.status_message =
-                        "Git repository URL can only be configured when git repository is enabled."
-                            .to_string();
-                }
-            }
-            "Disk" => {
-                // Check if we need multi-disk selection
-                let state = match self.lock_state() {
-                    Ok(state) => state,
-                    Err(_) => return Ok(()),
-                };
-                let partitioning_strategy = state
-                    .config
-                    .options
-                    .iter()
-                    .find(|opt| opt.name == "Partitioning Strategy")
-                    .map(|opt| opt.value.clone())
-                    .unwrap_or_default();
-                drop(state); // Release the lock
-
+            Input::Disk => {
+                // RAID strategies, manual and existing partitioning can use several disks
+                let partitioning_strategy = config.value("Partitioning Strategy");
                 match partitioning_strategy.as_str() {
                     "auto_raid" | "auto_raid_luks" | "auto_raid_lvm" | "auto_raid_lvm_luks"
-                    | "manual" => {
+                    | "manual" | "existing" => {
                         self.input_handler
                             .start_multi_disk_selection(&partitioning_strategy);
                     }
@@ -1907,99 +2535,58 @@ impl App {
                     }
                 }
             }
-            "Username" | "Hostname" => {
-                let placeholder = match option.name.as_str() {
-                    "Username" => "Enter username",
-                    "Hostname" => "Enter hostname",
-                    _ => "Enter value",
+            Input::EfiPartition => {
+                // Offer the ESPs the bootloader still fits on
+                let mut options = spec.choices();
+                let mut too_full = Vec::new();
+                for esp in crate::esp::detect() {
+                    match crate::esp::check_free(&esp) {
+                        Ok(()) => options.push(esp.to_string()),
+                        Err(e) => too_full.push(e),
+                    }
+                }
+                if !too_full.is_empty() {
+                    let state = &mut self.state;
+                    state.status.warn(too_full.join("; "));
                 }
-                .to_string();
-
                 self.input_handler
-                    .start_text_input(option.name.clone(), option.value, placeholder);
+                    .start_selection(option.name.clone(), options, option.value);
             }
-            "User Password" | "Root Password" => {
-                let placeholder = match option.name.as_str() {
-                    "User Password" => "Enter user password",
-                    "Root Password" => "Enter root password",
-                    _ => "Enter password",
+            Input::PartitionMounts => {
+                let editor = PartitionMountsState::for_config(&config);
+                let state = &mut self.state;
+                match editor.partitions {
+                    Ok(ref partitions) if partitions.is_empty() => state
+                        .status
+                        .warn("The selected disks have no partitions to assign"),
+                    Ok(_) => state
+                        .status
+                        .info("Assign mountpoints with m, keep or format with f"),
+                    Err(ref e) => state.status.error(e.clone()),
                 }
-                .to_string();
-
-                self.input_handler.start_password_input(
-                    option.name.clone(),
-                    option.value,
-                    placeholder,
-                );
-            }
-            "Additional Pacman Packages" | "Additional AUR Packages" => {
-                self.input_handler
-                    .start_package_selection(option.name.clone(), option.value);
+                state.guided.partition_mounts = Some(editor);
+                state.transition(AppMode::PartitionMounts);
             }
-            "Timezone Region" => {
-                let options = InputHandler::get_predefined_options(&option.name);
+            Input::Timezone => {
+                let options =
+                    InputHandler::get_timezones_for_region(&config.value("Timezone Region"));
                 self.input_handler
                     .start_selection(option.name.clone(), options, option.value);
             }
-            "Timezone" => {
-                // Get timezone options based on selected region
-                let timezone_region = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Timezone Region")
-                        .map(|opt| opt.value.clone())
-                        .unwrap_or_default()
-                };
-
-                if !timezone_region.is_empty() {
-                    let options = InputHandler::get_timezones_for_region(&timezone_region);
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                } else if let Ok(mut state) = self.lock_state_mut() {
-                    state.status_message = "Please select a timezone region first.".to_string();
-                }
-            }
-            "Display Manager" => {
-                // Check if desktop environment is set to something other than "none"
-                let desktop_env = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Desktop Environment")
-                        .map(|opt| opt.value.clone())
-                        .unwrap_or_default()
-                };
-
-                if desktop_env != "none" && !desktop_env.is_empty() {
-                    // Desktop environment is selected, display manager should be auto-set
-                    if let Ok(mut state) = self.lock_state_mut() {
-                        state.status_message =
-                            "Display Manager is auto-set based on Desktop Environment selection."
-                                .to_string();
-                    }
-                } else {
-                    // No desktop environment or "none" selected, allow manual selection
-                    let options = InputHandler::get_predefined_options(&option.name);
-                    self.input_handler
-                        .start_selection(option.name.clone(), options, option.value);
-                }
+            Input::PackageGroups => {
+                self.input_handler.start_package_group_selection(option.value);
             }
-            _ => {
-                // Use predefined options for selection fields
-                let options = InputHandler::get_predefined_options(&option.name);
+            Input::Services => {
                 self.input_handler
-                    .start_selection(option.name.clone(), options, option.value);
+                    .start_service_selection(option.get_value());
+            }
+            Input::Packages => {
+                let proxy = self.state.guided.config.proxy();
+                self.input_handler.start_package_selection(
+                    option.name.clone(),
+                    option.value,
+                    proxy,
+                );
             }
         }
 
@@ -2010,47 +2597,39 @@ impl App {
     fn update_configuration_value(
         &mut self,
         value: String,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let (current_step, option_name) = {
-            let state = self
-                .state
-                .lock()
-                .map_err(|e| error::general_error(format!("Mutex poisoned: {}", e)))?;
-            if state.config_scroll.selected_index >= state.config.options.len() {
-                return Err(error::general_error("Invalid configuration option index").into());
+    ) -> error::Result<()> {
+        let (current_step, option_name, before) = {
+            let state = &self.state;
+            if state.guided.scroll.selected_index >= state.guided.config.options.len() {
+                return Err(ArchInstallError::state("Invalid configuration option index"));
             }
             (
-                state.config_scroll.selected_index,
-                state.config.options[state.config_scroll.selected_index]
+                state.guided.scroll.selected_index,
+                state.guided.config.options[state.guided.scroll.selected_index]
                     .name
                     .clone(),
+                state.guided.config.clone(),
             )
         };
 
         // Update the configuration value
         {
-            let mut state = self
-                .state
-                .lock()
-                .map_err(|e| error::general_error(format!("Mutex poisoned: {}", e)))?;
-            if current_step < state.config.options.len() {
-                // Parse disk selection to extract only device path
-                let parsed_value = if option_name == "Disk" {
-                    // Check if this is multi-disk selection by counting /dev/ occurrences
-                    // (can't just check for commas since disk info may contain commas like "VBOX HARDDISK, 512B")
-                    let dev_count = value.matches("/dev/").count();
-                    if dev_count > 1 {
-                        // Multi-disk selection - extract all disk paths
-                        // Split by /dev/ and reconstruct paths
-                        let disk_paths: Vec<String> = value
-                            .split("/dev/")
-                            .skip(1) // Skip empty first element
-                            .map(|d| format!("/dev/{}", d.split_whitespace().next().unwrap_or("")))
-                            .filter(|d| d.len() > 5) // Filter out just "/dev/"
-                            .collect();
-
+            let state = &mut self.state;
+            if current_step < state.guided.config.options.len() {
+                let input = options::spec(&option_name).map(|spec| spec.input);
+                // Normalize the disk selection to device paths
+                let parsed_value = if matches!(input, Some(Input::Disk)) {
+                    // Disk dialogs return device paths, comma-separated for multi-disk selection
+                    let disk_paths: Vec<String> = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|d| !d.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    if disk_paths.len() > 1 {
                         // Check if this is manual partitioning
                         let partitioning_strategy = state
+                            .guided
                             .config
                             .options
                             .iter()
@@ -2060,31 +2639,34 @@ impl App {
 
                         if partitioning_strategy == "manual" {
                             // For manual partitioning, show confirmation dialog
-                            drop(state); // Release the lock
                             self.input_handler
                                 .start_manual_partitioning_confirmation(&disk_paths);
                             return Ok(());
                         } else {
-                            // For RAID strategies, join with commas
+                            // For RAID and existing partitions, join with commas
                             disk_paths.join(",")
                         }
                     } else {
-                        // Single disk selection - extract just the device path from "/dev/sda (128G)" -> "/dev/sda"
-                        value
-                            .split_whitespace()
-                            .next()
-                            .unwrap_or(&value)
-                            .to_string()
+                        value.trim().to_string()
                     }
+                } else if matches!(input, Some(Input::EfiPartition)) {
+                    // "/dev/nvme0n1p1 (100.0 MiB, 72.0 MiB free)" -> the device path
+                    value
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or(crate::esp::CREATE)
+                        .to_string()
                 } else {
                     value.clone()
                 };
 
-                state.config.options[current_step].value = parsed_value.clone();
-                state.status_message = format!(
+                state.guided.config.options[current_step].value = parsed_value.clone();
+                let message = format!(
                     "Set {} to: {}",
-                    state.config.options[current_step].name, parsed_value
+                    option_name,
+                    options::shown_value(&option_name, &parsed_value)
                 );
+                state.status.info(message);
             }
         }
 
@@ -2092,18 +2674,15 @@ impl App {
         if option_name == "manual_partitioning_confirm" {
             if value == "Yes, start partitioning" {
                 // User confirmed manual partitioning
-                let state = match self.lock_state() {
-                    Ok(state) => state,
-                    Err(_) => return Ok(()),
-                };
-                let disk_value = state
+                let disk_value = self
+                    .state
+                    .guided
                     .config
                     .options
                     .iter()
                     .find(|opt| opt.name == "Disk")
                     .map(|opt| opt.value.clone())
                     .unwrap_or_default();
-                drop(state);
 
                 // Extract disk paths
                 let disk_paths: Vec<String> = disk_value
@@ -2114,292 +2693,186 @@ impl App {
 
                 // Launch partitioning tool
                 if let Err(e) = self.input_handler.launch_partitioning_tool(&disk_paths) {
-                    if let Ok(mut state) = self.lock_state_mut() {
-                        state.status_message = format!("Partitioning failed: {}", e);
-                        return Ok(());
-                    }
+                    self.state
+                        .status
+                        .error(format!("Partitioning failed: {}", e));
+                    self.input_handler.start_error(&e);
+                    return Ok(());
                 }
 
-                // Validate partitioning after user finishes
-                let boot_mode = {
-                    let state = match self.lock_state() {
-                        Ok(state) => state,
-                        Err(_) => return Ok(()),
-                    };
-                    state
-                        .config
-                        .options
-                        .iter()
-                        .find(|opt| opt.name == "Boot Mode")
-                        .map(|opt| opt.value.as_str())
-                        .unwrap_or("BIOS")
-                        .to_string()
-                };
-
-                match self
-                    .input_handler
-                    .validate_manual_partitioning(&disk_paths, &boot_mode)
-                {
-                    Ok(layout) => {
-                        if let Ok(mut state) = self.lock_state_mut() {
-                            state.status_message = format!(
-                                "Manual partitioning validated successfully! Found {} partitions with {} table",
-                                layout.partitions.len(),
-                                layout.table_type
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        if let Ok(mut state) = self.lock_state_mut() {
-                            state.status_message = format!("Partitioning validation failed: {}", e);
-                        }
-                    }
-                }
+                // Show what the user made of the disks
+                let check = self.check_partitions()?;
+                self.show_partition_check(check)?;
             }
             // If user chose "No, go back", just return (dialog will close)
             return Ok(());
         }
 
-        // Auto-set encryption based on partitioning strategy
-        if option_name == "Partitioning Strategy" {
-            self.auto_set_encryption(&value)?;
-        }
-
-        // Auto-set display manager based on desktop environment
-        if option_name == "Desktop Environment" {
-            self.auto_set_display_manager(&value)?;
-        }
-
         // Handle warning dialog acknowledgment
         if value == "acknowledged" {
             // Warning was acknowledged, proceed to show normal selection dialog
         }
 
-        // Check for secure boot warning after setting Secure Boot
-        if option_name == "Secure Boot" && value.to_lowercase() == "yes" {
-            self.check_secure_boot_warning()?;
-        }
-
         // Handle dependent option updates
-        self.handle_dependent_options(&option_name, &value)?;
+        self.handle_dependent_options(&option_name, &value, &before)?;
 
         // Move to next step
         {
-            if let Ok(mut state) = self.lock_state_mut() {
-                if state.config_scroll.selected_index < state.config.options.len() - 1 {
-                    let next_index = state.config_scroll.selected_index + 1;
-                    state.config_scroll.set_selected(next_index);
-                }
+            let state = &mut self.state;
+            let last = state.guided.config.options.len() - 1;
+            if state.guided.wizard {
+                // The wizard skips what the new value made irrelevant
+                state.guided.wizard_step(true);
+            } else if state.guided.scroll.selected_index < last {
+                let next_index = state.guided.scroll.selected_index + 1;
+                state.guided.scroll.set_selected(next_index);
             }
         }
 
         Ok(())
     } // Close the update_configuration_value function
 
-    /// Auto-set encryption based on partitioning strategy
-    fn auto_set_encryption(
-        &mut self,
-        partitioning_strategy: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Only auto-set if not manual partitioning
-        if partitioning_strategy != "manual" {
-            let encryption_value = if partitioning_strategy.contains("luks") {
-                "Yes"
-            } else {
-                "No"
+    /// Restore the default value of the highlighted option
+    ///
+    /// Options that depend on it are updated as if the default had been
+    /// chosen in its dialog.
+    fn reset_selected_option(&mut self) -> error::Result<()> {
+        let (name, value, before) = {
+            let state = &mut self.state;
+            let before = state.guided.config.clone();
+            let Some(option) = state
+                .guided
+                .config
+                .options
+                .get_mut(state.guided.scroll.selected_index)
+            else {
+                return Ok(());
             };
-
-            {
-                if let Ok(mut state) = self.lock_state_mut() {
-                    // Find encryption option (index 6)
-                    if state.config.options.len() > 6 {
-                        state.config.options[6].value = encryption_value.to_string();
-                        state.status_message = format!(
-                            "Auto-set Encryption to: {} (based on partitioning strategy)",
-                            encryption_value
-                        );
-                    }
-                }
+            if !option.is_modified() {
+                let message = format!("{} already has its default value", option.name);
+                state.status.info(message);
+                return Ok(());
             }
-        }
-
-        Ok(())
-    }
-
-    /// Auto-set display manager based on desktop environment
-    fn auto_set_display_manager(
-        &mut self,
-        desktop_env: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let display_manager = match desktop_env {
-            "kde" => "sddm",
-            "gnome" => "gdm",
-            "hyprland" => "sddm",
-            "none" => "", // Don't auto-set when "none" - let user choose
-            _ => "",
+            option.reset();
+            (option.name.clone(), option.get_value(), before)
         };
 
-        if !display_manager.is_empty() {
-            {
-                if let Ok(mut state) = self.lock_state_mut() {
-                    // Find display manager option by name
-                    if let Some(display_manager_option) = state
-                        .config
-                        .options
-                        .iter_mut()
-                        .find(|opt| opt.name == "Display Manager")
-                    {
-                        display_manager_option.value = display_manager.to_string();
-                        state.status_message = format!(
-                            "Auto-set Display Manager to: {} (based on desktop environment)",
-                            display_manager
-                        );
-                    }
-                }
-            }
-        }
-
+        self.handle_dependent_options(&name, &value, &before)?;
+        let state = &mut self.state;
+        state.status.info(if value.is_empty() {
+            format!("Reset {}", name)
+        } else {
+            format!(
+                "Reset {} to its default: {}",
+                name,
+                options::shown_value(&name, &value)
+            )
+        });
         Ok(())
     }
 
     /// Handle dependent option updates based on user selections
+    ///
+    /// `before` is the configuration before the change. Options whose
+    /// requirement stopped holding are turned off first, see
+    /// [`Configuration::apply_requirements`].
     fn handle_dependent_options(
         &mut self,
         option_name: &str,
         value: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Ok(mut state) = self.lock_state_mut() {
-            match option_name {
-                "Swap" => {
-                    if value.to_lowercase() == "no" {
-                        // Disable swap size when swap is disabled
-                        if let Some(swap_size_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Swap Size")
-                        {
-                            swap_size_option.value = "N/A".to_string();
-                        }
-                    } else if value.to_lowercase() == "yes" {
-                        // Reset swap size to default when swap is enabled
-                        if let Some(swap_size_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Swap Size")
-                        {
-                            swap_size_option.value = "2GB".to_string();
-                        }
-                    }
-                }
-                "Btrfs Snapshots" => {
-                    if value.to_lowercase() == "no" {
-                        // Disable btrfs frequency, keep count, and assistant when snapshots are disabled
-                        if let Some(freq_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Btrfs Frequency")
-                        {
-                            freq_option.value = "N/A".to_string();
-                        }
-                        if let Some(keep_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Btrfs Keep Count")
-                        {
-                            keep_option.value = "N/A".to_string();
-                        }
-                        if let Some(assistant_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Btrfs Assistant")
-                        {
-                            assistant_option.value = "No".to_string();
-                        }
-                    } else if value.to_lowercase() == "yes" {
-                        // Reset btrfs options to defaults when snapshots are enabled
-                        if let Some(freq_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Btrfs Frequency")
-                        {
-                            freq_option.value = "weekly".to_string();
-                        }
-                        if let Some(keep_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Btrfs Keep Count")
-                        {
-                            keep_option.value = "3".to_string();
-                        }
-                    }
-                }
-                "Plymouth" => {
-                    if value.to_lowercase() == "no" {
-                        // Set plymouth theme to none when plymouth is disabled
-                        if let Some(theme_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Plymouth Theme")
-                        {
-                            theme_option.value = "none".to_string();
-                        }
-                    }
-                }
-                "GRUB Theme" => {
-                    if value.to_lowercase() == "no" {
-                        // Set GRUB theme selection to none when themes are disabled
-                        if let Some(theme_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "GRUB Theme Selection")
-                        {
-                            theme_option.value = "none".to_string();
-                        }
-                    }
-                }
-                "Timezone Region" => {
-                    // Reset timezone when region changes
-                    if let Some(timezone_option) = state
+        before: &Configuration,
+    ) -> error::Result<()> {
+        let state = &mut self.state;
+        // Options that fill in others, e.g. the display manager of a desktop
+        if let Some(spec) = options::spec(option_name) {
+            if let Some(message) = spec.apply_on_set(&mut state.guided.config, value) {
+                state.status.info(message);
+            }
+        }
+        state.guided.config.apply_requirements(before);
+        match option_name {
+            "Existing OS" => {
+                let value_of = |name: &str| {
+                    state
+                        .guided
                         .config
                         .options
-                        .iter_mut()
-                        .find(|opt| opt.name == "Timezone")
-                    {
-                        timezone_option.value = "".to_string(); // Reset to empty to force selection
-                    }
-
-                    // Auto-select mirror country based on region for quality of life
-                    let mirror_country = match value {
-                        "US" => "United States",
-                        "Europe" => "Germany", // Default to Germany for Europe
-                        "Asia" => "Japan",     // Default to Japan for Asia
-                        "Australia" => "Australia",
-                        "America" => "United States", // For America region, default to US
-                        _ => "",                      // Don't auto-select for other regions
-                    };
-
-                    if !mirror_country.is_empty() {
-                        if let Some(mirror_option) = state
-                            .config
-                            .options
-                            .iter_mut()
-                            .find(|opt| opt.name == "Mirror Country")
-                        {
-                            mirror_option.value = mirror_country.to_string();
+                        .iter()
+                        .find(|opt| opt.name == name)
+                        .map(|opt| opt.value.clone())
+                        .unwrap_or_default()
+                };
+                let disk = value_of("Disk");
+                let efi_partition = value_of("EFI Partition");
+                match value.parse().unwrap_or_default() {
+                    ExistingOsPolicy::Alongside => {
+                        // The other system's ESP is shared rather than duplicated
+                        let esp = (efi_partition == crate::esp::CREATE)
+                            .then(crate::esp::detect)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .find(|esp| esp.disk == disk && esp.has_room());
+                        if let Some(esp) = esp {
+                            if let Some(option) = state
+                                .guided
+                                .config
+                                .options
+                                .iter_mut()
+                                .find(|opt| opt.name == "EFI Partition")
+                            {
+                                option.value = esp.path.clone();
+                            }
+                            state.status.info(format!(
+                                "Installing alongside: Arch goes into the free space on {} \
+                                 and reuses {}",
+                                disk, esp.path
+                            ));
+                        } else {
+                            state.status.info(
+                                "Installing alongside: existing partitions are kept and Arch \
+                                 goes into the free space",
+                            );
                         }
                     }
+                    ExistingOsPolicy::Erase => state
+                        .status
+                        .warn("Other systems on the install disk will be erased"),
+                    ExistingOsPolicy::Protect => state
+                        .status
+                        .info("Disks holding another system will not be touched"),
+                }
+            }
+            "Package Cache" if !value.is_empty() => {
+                match crate::pkg_cache::inspect(std::path::Path::new(&value)) {
+                    Ok(source) => state
+                        .status
+                        .info(format!("Package Cache: {}", source.describe())),
+                    Err(e) => state.status.warn(e),
+                }
+            }
+            "Hibernation" if value.eq_ignore_ascii_case("yes") => {
+                let packages = state
+                    .guided
+                    .config
+                    .options
+                    .iter()
+                    .find(|opt| opt.name == "Additional Pacman Packages")
+                    .map(|opt| opt.get_value())
+                    .unwrap_or_default();
+                match crate::hibernation::zram_warning(crate::hibernation::uses_zram(
+                    &packages,
+                )) {
+                    Some(warning) => state.status.warn(format!("Hibernation: {}", warning)),
+                    None => state.status.info(
+                        "Hibernation: resume= is set from the swap in fstab; a smaller swap partition is grown to the RAM size",
+                    ),
                 }
-                _ => {}
             }
+            "Region" => {
+                // Its options are still listed, but the wizard moves on to what is left
+                state.guided.region_filled = crate::region::find_region(value).is_some();
+            }
+            _ => {}
         }
 
         Ok(())
@@ -2410,62 +2883,32 @@ impl App {
         &mut self,
         _width: u16,
         height: u16,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         // Update scroll state with new visible height
-        if let Ok(mut state) = self.lock_state_mut() {
-            if state.mode == AppMode::GuidedInstaller {
-                // Calculate available height for config list
-                // Header(7) + Title(3) + Instructions(3) + Start Button(3) = 16 lines reserved
-                let available_height = (height as usize).saturating_sub(16);
-                // Use most of the available space, with a minimum of 5 lines
-                let visible_height = available_height.max(5);
-                state.config_scroll.update_visible_items(visible_height);
-            }
+        let state = &mut self.state;
+        if state.mode == AppMode::GuidedInstaller {
+            // Calculate available height for config list
+            // Header(7) + Title(3) + Instructions(3) + Start Button(3) = 16 lines reserved
+            let available_height = (height as usize).saturating_sub(16);
+            // Use most of the available space, with a minimum of 5 lines
+            let visible_height = available_height.max(5);
+            state.guided.scroll.update_visible_items(visible_height);
         }
         Ok(())
     }
 
     /// Get tool parameter definitions for a specific tool
     fn get_tool_parameters(tool_name: &str) -> Vec<ToolParam> {
+        // Every snapshot tool works on the installed system's btrfs device
+        let snapshot_device_param = || ToolParam {
+            name: "device".to_string(),
+            description: "Btrfs root device (e.g., /dev/sda2 or /dev/mapper/cryptroot)"
+                .to_string(),
+            param_type: ToolParameter::Text("".to_string()),
+            required: true,
+        };
+
         match tool_name {
-            "install_bootloader" => vec![
-                ToolParam {
-                    name: "type".to_string(),
-                    description: "Bootloader type (grub or systemd-boot)".to_string(),
-                    param_type: ToolParameter::Selection(
-                        vec!["grub".to_string(), "systemd-boot".to_string()],
-                        0,
-                    ),
-                    required: true,
-                },
-                ToolParam {
-                    name: "disk".to_string(),
-                    description: "Target disk device (e.g., /dev/sda)".to_string(),
-                    param_type: ToolParameter::Text("".to_string()),
-                    required: true,
-                },
-                ToolParam {
-                    name: "efi_path".to_string(),
-                    description: "EFI partition path (optional)".to_string(),
-                    param_type: ToolParameter::Text("".to_string()),
-                    required: false,
-                },
-                ToolParam {
-                    name: "mode".to_string(),
-                    description: "Boot mode (uefi or bios, auto-detected if empty)".to_string(),
-                    param_type: ToolParameter::Selection(
-                        vec!["".to_string(), "uefi".to_string(), "bios".to_string()],
-                        0,
-                    ),
-                    required: false,
-                },
-                ToolParam {
-                    name: "repair".to_string(),
-                    description: "Repair existing bootloader installation".to_string(),
-                    param_type: ToolParameter::Boolean(false),
-                    required: false,
-                },
-            ],
             "generate_fstab" => vec![ToolParam {
                 name: "root".to_string(),
                 description: "Root partition path (e.g., /mnt)".to_string(),
@@ -2483,13 +2926,7 @@ impl App {
                     name: "filesystem".to_string(),
                     description: "Filesystem type".to_string(),
                     param_type: ToolParameter::Selection(
-                        vec![
-                            "ext4".to_string(),
-                            "xfs".to_string(),
-                            "btrfs".to_string(),
-                            "f2fs".to_string(),
-                            "ntfs".to_string(),
-                        ],
+                        FormatFilesystem::iter().map(|fs| fs.to_string()).collect(),
                         0,
                     ),
                     required: true,
@@ -2500,97 +2937,72 @@ impl App {
                     param_type: ToolParameter::Text("".to_string()),
                     required: false,
                 },
-            ],
-            "wipe_disk" => vec![
                 ToolParam {
-                    name: "device".to_string(),
-                    description: "Disk device to wipe (e.g., /dev/sda)".to_string(),
-                    param_type: ToolParameter::Text("".to_string()),
-                    required: true,
+                    name: "ext4_reserved".to_string(),
+                    description: "Reserved blocks in percent (ext4 only)".to_string(),
+                    param_type: ToolParameter::Number(5),
+                    required: false,
                 },
                 ToolParam {
-                    name: "method".to_string(),
-                    description: "Wipe method".to_string(),
+                    name: "btrfs_metadata".to_string(),
+                    description: "Metadata profile (btrfs only)".to_string(),
                     param_type: ToolParameter::Selection(
-                        vec![
-                            "zero".to_string(),
-                            "random".to_string(),
-                            "secure".to_string(),
-                        ],
+                        std::iter::once("default".to_string())
+                            .chain(BtrfsProfile::iter().map(|profile| profile.to_string()))
+                            .collect(),
                         0,
                     ),
-                    required: true,
+                    required: false,
                 },
                 ToolParam {
-                    name: "confirm".to_string(),
-                    description: "Confirm destructive operation".to_string(),
+                    name: "f2fs_compression".to_string(),
+                    description: "Transparent compression (f2fs only)".to_string(),
                     param_type: ToolParameter::Boolean(false),
-                    required: true,
+                    required: false,
                 },
             ],
-            "health" => vec![ToolParam {
-                name: "output_level".to_string(),
-                description: "Output detail level".to_string(),
-                param_type: ToolParameter::Selection(
-                    vec!["basic".to_string(), "detailed".to_string()],
-                    0,
-                ),
-                required: false,
-            }],
-            "mount" => vec![
-                ToolParam {
-                    name: "action".to_string(),
-                    description: "Action to perform".to_string(),
-                    param_type: ToolParameter::Selection(
-                        vec![
-                            "mount".to_string(),
-                            "umount".to_string(),
-                            "list".to_string(),
-                            "info".to_string(),
-                        ],
-                        0,
-                    ),
-                    required: true,
-                },
-                ToolParam {
-                    name: "target".to_string(),
-                    description: "Device path (e.g., /dev/sda1) or mountpoint path".to_string(),
-                    param_type: ToolParameter::Text("".to_string()),
-                    required: true,
-                },
+            "list_snapshots" => vec![snapshot_device_param()],
+            "create_snapshot" => vec![
+                snapshot_device_param(),
                 ToolParam {
-                    name: "destination".to_string(),
-                    description: "Destination directory (for mount action)".to_string(),
+                    name: "description".to_string(),
+                    description: "Description shown in the snapshot list".to_string(),
                     param_type: ToolParameter::Text("".to_string()),
                     required: false,
                 },
+            ],
+            "delete_snapshot" | "rollback_snapshot" => vec![
+                snapshot_device_param(),
                 ToolParam {
-                    name: "readonly".to_string(),
-                    description: "Mount as read-only".to_string(),
-                    param_type: ToolParameter::Boolean(false),
-                    required: false,
-                },
-                ToolParam {
-                    name: "force".to_string(),
-                    description: "Force operation (unmount if busy)".to_string(),
-                    param_type: ToolParameter::Boolean(false),
-                    required: false,
+                    name: "snapshot".to_string(),
+                    description: "Snapshot number (see List Snapshots)".to_string(),
+                    param_type: ToolParameter::Number(1),
+                    required: true,
                 },
             ],
-            "chroot" => vec![
+            "resize_partition" => vec![
                 ToolParam {
-                    name: "root".to_string(),
-                    description: "Root directory to chroot into".to_string(),
-                    param_type: ToolParameter::Text("/mnt".to_string()),
+                    name: "device".to_string(),
+                    description: "Partition device (e.g., /dev/sda2)".to_string(),
+                    param_type: ToolParameter::Text("".to_string()),
                     required: true,
                 },
                 ToolParam {
-                    name: "no_mount".to_string(),
-                    description: "Skip mounting /proc, /sys, /dev".to_string(),
-                    param_type: ToolParameter::Boolean(false),
-                    required: false,
+                    name: "size".to_string(),
+                    description: "New size: 40G, +5G, -5G or max".to_string(),
+                    param_type: ToolParameter::Text("".to_string()),
+                    required: true,
                 },
             ],
+            "health" => vec![ToolParam {
+                name: "output_level".to_string(),
+                description: "Output detail level".to_string(),
+                param_type: ToolParameter::Selection(
+                    vec!["basic".to_string(), "detailed".to_string()],
+                    0,
+                ),
+                required: false,
+            }],
             "info" => vec![
                 ToolParam {
                     name: "detailed".to_string(),
@@ -2698,177 +3110,935 @@ impl App {
                     param_type: ToolParameter::Text("".to_string()),
                     required: false,
                 },
+                ToolParam {
+                    name: "ipv6_mode".to_string(),
+                    description:
+                        "IPv6: ra, dhcpv6, static or off (blank: static with an address, else ra)"
+                            .to_string(),
+                    param_type: ToolParameter::Selection(
+                        std::iter::once(String::new())
+                            .chain(Ipv6Mode::iter().map(|mode| mode.to_string()))
+                            .collect(),
+                        0,
+                    ),
+                    required: false,
+                },
+                ToolParam {
+                    name: "ip6".to_string(),
+                    description: "IPv6 address with prefix (for static IPv6, e.g. 2001:db8::10/64)"
+                        .to_string(),
+                    param_type: ToolParameter::Text("".to_string()),
+                    required: false,
+                },
+                ToolParam {
+                    name: "gateway6".to_string(),
+                    description: "IPv6 gateway (for static IPv6)".to_string(),
+                    param_type: ToolParameter::Text("".to_string()),
+                    required: false,
+                },
+                ToolParam {
+                    name: "dns".to_string(),
+                    description: "DNS servers, IPv4 or IPv6, comma-separated".to_string(),
+                    param_type: ToolParameter::Text("".to_string()),
+                    required: false,
+                },
+                ToolParam {
+                    name: "backend".to_string(),
+                    description: "Save the configuration for NetworkManager or systemd-networkd"
+                        .to_string(),
+                    param_type: ToolParameter::Selection(
+                        std::iter::once(String::new())
+                            .chain(NetworkBackend::iter().map(|backend| backend.to_string()))
+                            .collect(),
+                        0,
+                    ),
+                    required: false,
+                },
             ],
             _ => vec![],
         }
     }
 
-    /// Handle tool dialog input (navigation, parameter input, etc.)
-    fn handle_tool_dialog_input(
-        &mut self,
-        key_event: KeyEvent,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        match key_event.code {
-            KeyCode::Up => {
-                // Move to previous parameter (if not at first)
-                let mut state = self.lock_state_mut()?;
-                if let Some(ref mut dialog) = state.tool_dialog {
-                    if dialog.current_param > 0 {
-                        dialog.current_param -= 1;
-                    }
+    /// Handle tool dialog enter key
+    ///
+    /// Enter moves to the next field; on the last one the form is checked and
+    /// the tool runs, or the first field needing attention is focused.
+    fn handle_tool_dialog_enter(&mut self) -> error::Result<()> {
+        let (tool_name, param_values) = {
+            let state = &mut self.state;
+            let Some(ref mut dialog) = state.tools.dialog else {
+                return Ok(());
+            };
+            if !dialog.on_last_field() {
+                dialog.focus_next();
+                return Ok(());
+            }
+            if let Err((index, reason)) = dialog.check() {
+                dialog.current_param = index;
+                state.status.error(reason);
+                return Ok(());
+            }
+            let collected = (dialog.tool_name.clone(), dialog.param_values.clone());
+            state.transition(AppMode::ToolExecution);
+            collected
+        };
+
+        // Execute tool outside of the state lock
+        self.execute_tool_with_params(&tool_name, param_values)
+    }
+
+    /// Create a tool dialog for parameter collection
+    fn create_tool_dialog(&mut self, tool_name: &str) -> error::Result<()> {
+        let parameters = Self::get_tool_parameters(tool_name);
+
+        let state = &mut self.state;
+        state.tools.dialog = Some(ToolDialogState::new(tool_name, parameters));
+        state.transition(AppMode::ToolDialog);
+        state.status.info(format!("Configure parameters for {}", tool_name));
+
+        Ok(())
+    }
+
+    /// Check the Format Partition options and ask before formatting
+    ///
+    /// Invalid options keep the tool dialog open with the reason.
+    fn confirm_format(&mut self, params: &[String]) -> error::Result<()> {
+        let options = FormatOptions::from_dialog(params);
+
+        let state = &mut self.state;
+        match options {
+            Ok(options) => {
+                state.tools.dialog = None;
+                state.open_confirm(format_partition_confirm(&options), AppMode::DiskTools);
+            }
+            Err(e) => {
+                state.transition(AppMode::ToolDialog);
+                state.status.error(format!("Cannot format: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Plan a partition resize and show its before/after preview
+    ///
+    /// An invalid request keeps the tool dialog open with the reason.
+    fn preview_resize(
+        &mut self,
+        device: &str,
+        size: &str,
+    ) -> error::Result<()> {
+        let state = &mut self.state;
+        match resize::plan(device, size) {
+            Ok(plan) => {
+                state.tools.dialog = None;
+                state.open_confirm(resize_partition_confirm(&plan, size), AppMode::DiskTools);
+            }
+            Err(e) => {
+                state.transition(AppMode::ToolDialog);
+                state.status.error(format!("Cannot resize: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resize a partition in the background, streaming each step to the output window
+    fn execute_resize(
+        &mut self,
+        device: &str,
+        size: &str,
+    ) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            state.floating_output = Some(FloatingOutputState {
+                title: format!("Resizing {}", device),
+                content: vec![String::new()],
+                scroll_offset: 0,
+                auto_scroll: true,
+                complete: false,
+                progress: None,
+                status: "Running...".to_string(),
+            });
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some("resize partition".to_string());
+        }
+
+        let tx = self.tool_tx.clone();
+        let (device, size) = (device.to_string(), size.to_string());
+        thread::spawn(move || {
+            // Re-plan against the current disk state rather than the previewed one
+            let result = resize::plan(&device, &size).and_then(|plan| {
+                resize::apply(&plan, |line| {
+                    let _ = tx.send(ToolMessage::Stdout(line));
+                })
+            });
+            let _ = tx.send(match result {
+                Ok(()) => ToolMessage::Complete {
+                    success: true,
+                    exit_code: Some(0),
+                },
+                Err(e) => ToolMessage::Error(e.to_string()),
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Check the snapshot number and ask before deleting or rolling back
+    ///
+    /// An invalid number keeps the tool dialog open with the reason.
+    fn confirm_snapshot(
+        &mut self,
+        tool_name: &str,
+        params: &[String],
+    ) -> error::Result<()> {
+        let device = params.first().map(String::as_str).unwrap_or_default();
+        let number = params.get(1).and_then(|n| n.trim().parse::<u32>().ok());
+
+        let state = &mut self.state;
+        match number {
+            Some(number) => {
+                state.tools.dialog = None;
+                let dialog = if tool_name == "rollback_snapshot" {
+                    snapshot_rollback_confirm(device, number)
+                } else {
+                    snapshot_delete_confirm(device, number)
+                };
+                state.open_confirm(dialog, AppMode::SnapshotTools);
+            }
+            None => {
+                state.transition(AppMode::ToolDialog);
+                state
+                    .status
+                    .error("Snapshot must be a number; List Snapshots shows them");
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a snapshot tool in the background, streaming its output
+    ///
+    /// `arg` is the description for Create Snapshot and the snapshot number
+    /// for Delete and Roll Back.
+    fn execute_snapshot_tool(
+        &mut self,
+        tool_name: &str,
+        device: &str,
+        arg: &str,
+    ) -> error::Result<()> {
+        let title = match tool_name {
+            "list_snapshots" => format!("Snapshots on {}", device),
+            "create_snapshot" => format!("Snapshotting {}", device),
+            "delete_snapshot" => format!("Deleting Snapshot {}", arg),
+            _ => format!("Rolling Back to Snapshot {}", arg),
+        };
+        {
+            let state = &mut self.state;
+            state.floating_output = Some(FloatingOutputState::new(&title));
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some(tool_name.to_string());
+        }
+
+        let tx = self.tool_tx.clone();
+        let (tool_name, device, arg) = (tool_name.to_string(), device.to_string(), arg.to_string());
+        thread::spawn(move || {
+            let log = |line: String| {
+                let _ = tx.send(ToolMessage::Stdout(line));
+            };
+            let number = || {
+                arg.parse::<u32>().map_err(|_| {
+                    ArchInstallError::validation("snapshot", format!("'{}' is not a number", arg))
+                })
+            };
+            let result = match tool_name.as_str() {
+                "list_snapshots" => snapshots::list(&device).map(|list| {
+                    if list.is_empty() {
+                        log(format!("No snapshots in {}", snapshots::SNAPSHOTS_SUBVOLUME));
+                    }
+                    for snapshot in list {
+                        log(snapshot.to_string());
+                    }
+                }),
+                "create_snapshot" => {
+                    let description = if arg.trim().is_empty() {
+                        "Arch Linux Toolkit"
+                    } else {
+                        arg.trim()
+                    };
+                    snapshots::create(&device, description, log)
+                        .map(|snapshot| log(format!("Created snapshot {}", snapshot.number)))
+                }
+                "delete_snapshot" => number()
+                    .and_then(|n| snapshots::delete(&device, n, log))
+                    .map(|()| log(format!("Deleted snapshot {}", arg))),
+                _ => number()
+                    .and_then(|n| snapshots::rollback(&device, n, log))
+                    .map(|old_root| {
+                        log(format!("Rolled back to snapshot {}", arg));
+                        log(format!("The previous root is kept as {}", old_root));
+                        log("Reboot to start the rolled back system".to_string());
+                    }),
+            };
+            let _ = tx.send(match result {
+                Ok(()) => ToolMessage::Complete {
+                    success: true,
+                    exit_code: Some(0),
+                },
+                Err(e) => ToolMessage::Error(e.to_string()),
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Detect leftovers of a failed installation and ask before releasing them
+    fn preview_cleanup(&mut self) -> error::Result<()> {
+        let disks = crate::components::partition_check::selected_disks(&self.state.guided.config);
+        let leftovers = cleanup::detect(&disks);
+        let state = &mut self.state;
+        if leftovers.is_empty() {
+            state.status.info(
+                "Nothing to clean up: no mounts, swap, LVM, LUKS or RAID left active",
+            );
+            return Ok(());
+        }
+        state.open_confirm(cleanup_confirm(&leftovers), AppMode::DiskTools);
+        Ok(())
+    }
+
+    /// Offer the installed systems the rescue shell can mount and enter
+    fn start_chroot_rescue(&mut self) -> error::Result<()> {
+        let mut choices = Vec::new();
+        if rescue::root_mounted() {
+            choices.push(rescue::mounted_choice());
+        }
+        choices.extend(rescue::detect().iter().map(ToString::to_string));
+        if choices.is_empty() {
+            let state = &mut self.state;
+            state
+                .status
+                .error("No installed system found: no unmounted Linux root filesystem");
+            return Ok(());
+        }
+
+        self.input_handler.start_selection(
+            "Root Filesystem to Chroot Into".to_string(),
+            choices,
+            String::new(),
+        );
+        let state = &mut self.state;
+        state.tools.current = Some("chroot".to_string());
+        state
+            .status
+            .info("Select the installed system (Enter to select, Esc to cancel)");
+        Ok(())
+    }
+
+    /// Diagnose the bootloader of the system at /mnt, or offer the installed
+    /// systems to mount there first
+    fn start_bootloader_repair(&mut self) -> error::Result<()> {
+        if rescue::root_mounted() {
+            return self.diagnose_bootloader(None);
+        }
+        let choices: Vec<String> = rescue::detect().iter().map(ToString::to_string).collect();
+        if choices.is_empty() {
+            let state = &mut self.state;
+            state
+                .status
+                .error("No installed system found: no unmounted Linux root filesystem");
+            return Ok(());
+        }
+
+        self.input_handler.start_selection(
+            "System to Repair".to_string(),
+            choices,
+            String::new(),
+        );
+        let state = &mut self.state;
+        state.tools.current = Some(bootrepair::TOOL_NAME.to_string());
+        state
+            .status
+            .info("Select the installed system (Enter to select, Esc to cancel)");
+        Ok(())
+    }
+
+    /// Mount the chosen system at /mnt with its /boot and ESP, then diagnose it
+    ///
+    /// An encrypted root needs its passphrase, which only the rescue shell
+    /// can ask for; its mapping is offered here once it is open.
+    fn mount_for_repair(&mut self, choice: &str) -> error::Result<()> {
+        self.state.tools.current = None;
+        let device = choice.split_whitespace().next().unwrap_or_default();
+        if choice.contains("(LUKS") {
+            self.state.status.error(format!(
+                "{} is encrypted: unlock it with cryptsetup open first, then repair the opened system",
+                device
+            ));
+            return Ok(());
+        }
+
+        if !simulate::is_enabled() {
+            let output = Command::new("bash")
+                .args([
+                    "scripts/tools/chroot_system.sh",
+                    "--device",
+                    device,
+                    "--root",
+                    bootrepair::REPAIR_ROOT,
+                    "--mount-only",
+                ])
+                .stdin(Stdio::null())
+                .output();
+            let failure = match output {
+                Ok(output) if output.status.success() => None,
+                // The script's last message says why
+                Ok(output) => Some(
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .chain(String::from_utf8_lossy(&output.stderr).lines())
+                        .rfind(|line| !line.trim().is_empty())
+                        .unwrap_or("chroot_system.sh failed")
+                        .to_string(),
+                ),
+                Err(e) => Some(e.to_string()),
+            };
+            if let Some(failure) = failure {
+                self.state
+                    .status
+                    .error(format!("Could not mount {}: {}", device, failure));
+                return Ok(());
+            }
+        }
+        self.diagnose_bootloader(Some(device))
+    }
+
+    /// Show what is wrong with the bootloader at /mnt and the fixes for it
+    ///
+    /// `mounted` is the device mounted for the repair, if it was.
+    fn diagnose_bootloader(&mut self, mounted: Option<&str>) -> error::Result<()> {
+        let root = std::path::Path::new(bootrepair::REPAIR_ROOT);
+        let diagnosis = bootrepair::diagnose(root, &bootrepair::Probe::read(root));
+        let state = &mut self.state;
+        if let Some(problem) = diagnosis.problems.iter().find(|p| p.is_blocking()) {
+            let mut message = format!("Cannot repair the bootloader: {}", problem);
+            // Nothing is left to do with the system this tool mounted
+            if mounted.is_some() {
+                if let Err(e) = bootrepair::unmount() {
+                    message.push_str(&format!(" (and it stays mounted: {})", e));
+                }
+            }
+            state.status.error(message);
+            return Ok(());
+        }
+        let mut dialog = bootloader_repair_confirm(&diagnosis);
+        if let Some(device) = mounted {
+            dialog = dialog.with_detail(&format!(
+                "{} stays mounted at {} afterwards",
+                device,
+                bootrepair::REPAIR_ROOT
+            ));
+        }
+        state.open_confirm(dialog, AppMode::SystemTools);
+        Ok(())
+    }
+
+    /// Apply the confirmed bootloader fixes in the background, streaming each step
+    fn execute_bootloader_repair(&mut self, fixes: Vec<bootrepair::Fix>) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            state.floating_output = Some(FloatingOutputState::new("Repairing Bootloader"));
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some(bootrepair::TOOL_NAME.to_string());
+        }
+
+        let tx = self.tool_tx.clone();
+        thread::spawn(move || {
+            let result = bootrepair::run(&fixes, |line| {
+                let _ = tx.send(ToolMessage::Stdout(line));
+            });
+            let _ = tx.send(match result {
+                Ok(()) => ToolMessage::Complete {
+                    success: true,
+                    exit_code: Some(0),
+                },
+                Err(e) => ToolMessage::Error(e.to_string()),
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Ask for the system to clone into the guided installer
+    fn start_clone_system(&mut self) -> error::Result<()> {
+        self.input_handler.start_text_input(
+            "Source System".to_string(),
+            "/".to_string(),
+            "/ for this system, or where another one is mounted".to_string(),
+        );
+        let state = &mut self.state;
+        state.tools.current = Some(clone::TOOL_NAME.to_string());
+        state
+            .status
+            .info("Enter the root of the system to clone (Enter to confirm, Esc to cancel)");
+        Ok(())
+    }
+
+    /// Load the configuration of the system at `source` into the guided installer
+    ///
+    /// Shows what was read and what could not be carried over; dismissing
+    /// the summary opens the guided installer for review.
+    fn clone_system(&mut self, source: &str) -> error::Result<()> {
+        let result = clone::clone_system(std::path::Path::new(source));
+        let state = &mut self.state;
+        let cloned = match result {
+            Ok(cloned) => cloned,
+            Err(e) => {
+                state.tools.current = None;
+                state.status.error(e);
+                return Ok(());
+            }
+        };
+
+        let config = &cloned.config;
+        let count = |list: &str| list.split_whitespace().count();
+        let mut content = vec![
+            format!("Source system: {}", source),
+            String::new(),
+            format!("Hostname: {}", config.hostname),
+            format!("Username: {}", config.username),
+            format!("Kernel: {}", config.kernel),
+            format!("Bootloader: {}", config.bootloader),
+            format!("Layout: {}", config.partitioning_strategy),
+            format!(
+                "Filesystems: / {}{}",
+                config.root_filesystem,
+                if config.separate_home == Toggle::Yes {
+                    format!(", /home {}", config.home_filesystem)
+                } else {
+                    String::new()
+                }
+            ),
+            format!("Desktop: {}", config.desktop_environment),
+            format!("Services: {}", config.services.join(" ")),
+            format!(
+                "Packages: {} from the repositories, {} from the AUR",
+                count(&config.additional_packages),
+                count(&config.additional_aur_packages)
+            ),
+            String::new(),
+            "Review before installing:".to_string(),
+        ];
+        content.extend(cloned.notes.iter().map(|note| format!("  • {}", note)));
+        content.push(String::new());
+        content.push("Press Enter to review the configuration".to_string());
+
+        state.guided.config = Configuration::from(config);
+        state.guided.scroll.selected_index = 0;
+        state.floating_output = Some(FloatingOutputState {
+            title: "System Cloned".to_string(),
+            content,
+            scroll_offset: 0,
+            auto_scroll: false,
+            complete: true,
+            progress: None,
+            status: "Ready to review".to_string(),
+        });
+        state.transition(AppMode::FloatingOutput);
+        state
+            .status
+            .info(format!("Configuration cloned from {}", source));
+        Ok(())
+    }
+
+    /// Mount the chosen system and open arch-chroot in the embedded terminal
+    ///
+    /// The script asks for the LUKS passphrase in the terminal and releases
+    /// what it mounted when the shell exits.
+    fn launch_rescue_shell(&mut self, choice: &str) -> error::Result<()> {
+        let (script_args, mounts_system) = rescue::script_args(choice);
+        let mut args = vec!["scripts/tools/chroot_system.sh"];
+        args.extend(script_args.iter().map(String::as_str));
+        let tool_name = if mounts_system {
+            rescue::TOOL_NAME
+        } else {
+            "chroot"
+        };
+
+        self.state.tools.current = None;
+        self.launch_embedded_tool("bash", &args, tool_name, AppMode::SystemTools)
+    }
+
+    /// Release installation leftovers in the background, streaming each step
+    fn execute_cleanup(&mut self) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            state.floating_output = Some(FloatingOutputState::new("Cleaning Up"));
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some("cleanup".to_string());
+        }
+
+        let tx = self.tool_tx.clone();
+        let disks = crate::components::partition_check::selected_disks(&self.state.guided.config);
+        thread::spawn(move || {
+            // Detect again: something may have changed since the confirmation
+            let leftovers = cleanup::detect(&disks);
+            let result = cleanup::run(&leftovers, |line| {
+                let _ = tx.send(ToolMessage::Stdout(line));
+            });
+            let _ = tx.send(match result {
+                Ok(()) => ToolMessage::Complete {
+                    success: true,
+                    exit_code: Some(0),
+                },
+                Err(e) => ToolMessage::Error(e.to_string()),
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Execute health tool with selected disk
+    ///
+    /// Shows the structured SMART report; the full reliability test script
+    /// remains available from the report window.
+    fn execute_health_tool_with_disk(
+        &mut self,
+        selected_disk: String,
+    ) -> error::Result<()> {
+        // Disk selection entries look like "/dev/sda (500G) ..."
+        let device = selected_disk
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        {
+            let state = &mut self.state;
+            state.tools.disk_health = Some(DiskHealthState::loading(&device));
+            state.transition(AppMode::DiskHealth);
+            state.tools.current = None;
+            state.status.info(format!("Disk health report for {}", device));
+        }
+
+        self.refresh_disk_health()
+    }
+
+    /// Re-read SMART data for the disk in the health report (in the background)
+    fn refresh_disk_health(&mut self) -> error::Result<()> {
+        let (device, privileged) = {
+            let state = &mut self.state;
+            let privileged = state.privileged;
+            match state.tools.disk_health {
+                Some(ref mut health) => {
+                    health.report = None;
+                    (health.device.clone(), privileged)
+                }
+                None => return Ok(()),
+            }
+        };
+
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let report = smart::read_report(&device).map_err(|e| {
+                if privileged {
+                    e.to_string()
+                } else {
+                    format!("{} (reading SMART data usually needs root)", e)
+                }
+            });
+            app_state.update(move |state| {
+                if let Some(ref mut health) = state.tools.disk_health {
+                    // Ignore results for a disk the user has since moved away from
+                    if health.device == device {
+                        health.report = Some(report);
+                    }
+                }
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Start a SMART self-test on the disk in the health report
+    fn start_disk_self_test(&mut self, test: SelfTest) -> error::Result<()> {
+        let state = &mut self.state;
+        let privileged = state.privileged;
+        if let Some(ref mut health) = state.tools.disk_health {
+            if health.is_loading() {
+                return Ok(());
+            }
+            if !privileged {
+                health.message = Some(format!("Starting a {} self-test needs root", test));
+                return Ok(());
+            }
+            health.message = Some(match smart::start_self_test(&health.device, test) {
+                Ok(message) => message,
+                Err(e) => e.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Open the service manager
+    ///
+    /// Manages the installed system when one is mounted at /mnt, which is
+    /// what the toolkit is usually run for, and the running system otherwise.
+    fn open_service_manager(&mut self) -> error::Result<()> {
+        let target = if services::has_installed_system(cleanup::INSTALL_ROOT) {
+            Target::Root(cleanup::INSTALL_ROOT.to_string())
+        } else {
+            Target::Running
+        };
+
+        {
+            let state = &mut self.state;
+            state
+                .status
+                .info(format!("Service manager: units of the {}", target));
+            state.tools.services = Some(ServiceManagerState::loading(target));
+            state.transition(AppMode::ServiceManager);
+        }
+
+        self.refresh_services()
+    }
+
+    /// List the units of the service manager's system again (in the background)
+    fn refresh_services(&mut self) -> error::Result<()> {
+        let target = {
+            let state = &mut self.state;
+            match state.tools.services {
+                Some(ref mut services) if !services.busy => {
+                    services.units = None;
+                    services.target.clone()
                 }
+                _ => return Ok(()),
             }
-            KeyCode::Down => {
-                // Move to next parameter (if not at last)
-                let mut state = self.lock_state_mut()?;
-                if let Some(ref mut dialog) = state.tool_dialog {
-                    if dialog.current_param < dialog.parameters.len() - 1 {
-                        dialog.current_param += 1;
-                    }
-                }
-            }
-            KeyCode::Enter => {
-                // Execute tool with collected parameters
-                self.handle_tool_dialog_enter()?;
-            }
-            KeyCode::Esc => {
-                // Cancel tool dialog and go back
-                let mut state = self.lock_state_mut()?;
-                let current_tool = state.current_tool.clone();
-                state.tool_dialog = None;
-                state.current_tool = None;
-                // Go back to appropriate tool menu
-                if let Some(ref tool_name) = current_tool {
-                    match tool_name.as_str() {
-                        "format_partition" | "wipe_disk" | "health" | "mount"
-                        | "manual_partition" => {
-                            state.mode = AppMode::DiskTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Disk & Filesystem Tools".to_string();
-                        }
-                        "install_bootloader" | "generate_fstab" | "chroot" | "info" => {
-                            state.mode = AppMode::SystemTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "System & Boot Tools".to_string();
-                        }
-                        "reset_password" => {
-                            state.mode = AppMode::UserTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "User & Security Tools".to_string();
-                        }
-                        "configure" => {
-                            state.mode = AppMode::NetworkTools;
-                            state.tools_menu_selection = 0;
-                            state.status_message = "Network Tools".to_string();
-                        }
-                        _ => {
-                            state.mode = AppMode::ToolsMenu;
-                            state.tools_menu_selection = 0;
-                            state.status_message =
-                                "Arch Linux Tools - System repair and administration".to_string();
-                        }
-                    }
-                } else {
-                    state.mode = AppMode::ToolsMenu;
-                    state.tools_menu_selection = 0;
-                    state.status_message =
-                        "Arch Linux Tools - System repair and administration".to_string();
-                }
-            }
-            KeyCode::Char(c) => {
-                // Handle text input for current parameter
-                let mut state = self.lock_state_mut()?;
-                if let Some(ref mut dialog) = state.tool_dialog {
-                    if dialog.current_param < dialog.param_values.len() {
-                        dialog.param_values[dialog.current_param].push(c);
+        };
+
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let units = services::list_units(&target).map_err(|e| e.to_string());
+            app_state.update(move |state| {
+                if let Some(ref mut services) = state.tools.services {
+                    // Ignore results for a system the user has since switched away from
+                    if services.target == target {
+                        services.set_units(units);
                     }
                 }
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Switch the service manager between the running and the installed system
+    fn switch_service_target(&mut self) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            let Some(ref mut services) = state.tools.services else {
+                return Ok(());
+            };
+            if services.busy {
+                return Ok(());
             }
-            KeyCode::Backspace => {
-                // Handle backspace for current parameter
-                let mut state = self.lock_state_mut()?;
-                if let Some(ref mut dialog) = state.tool_dialog {
-                    if dialog.current_param < dialog.param_values.len() {
-                        dialog.param_values[dialog.current_param].pop();
-                    }
-                }
+            if services.target.is_offline() {
+                services.target = Target::Running;
+            } else if services::has_installed_system(cleanup::INSTALL_ROOT) {
+                services.target = Target::Root(cleanup::INSTALL_ROOT.to_string());
+            } else {
+                services.message = Some(format!(
+                    "No installed system is mounted at {}; mount one with the chroot tool first",
+                    cleanup::INSTALL_ROOT
+                ));
+                return Ok(());
             }
-            _ => {}
+            services.message = None;
+            services.filter.clear();
         }
-        Ok(())
+
+        self.refresh_services()
     }
 
-    /// Handle tool dialog enter key
-    fn handle_tool_dialog_enter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let (tool_name, current_param, param_values) = {
-            let state = self.lock_state()?;
-            if let Some(ref dialog) = state.tool_dialog {
-                (
-                    dialog.tool_name.clone(),
-                    dialog.current_param,
-                    dialog.param_values.clone(),
-                )
-            } else {
+    /// Apply a systemctl action to the highlighted unit (in the background)
+    ///
+    /// The unit list is read again afterwards so its state shows the result.
+    fn manage_unit(&mut self, action: UnitAction) -> error::Result<()> {
+        let (target, unit) = {
+            let state = &mut self.state;
+            let privileged = state.privileged;
+            let Some(ref mut services) = state.tools.services else {
+                return Ok(());
+            };
+            if services.busy || services.is_loading() {
+                return Ok(());
+            }
+            let Some(unit) = services.selected_unit().map(|unit| unit.name.clone()) else {
+                return Ok(());
+            };
+            if !privileged {
+                services.message = Some(format!("{} {} needs root", action, unit));
                 return Ok(());
             }
+            services.busy = true;
+            (services.target.clone(), unit)
         };
 
-        {
-            let mut state = self.lock_state_mut()?;
-            if let Some(ref mut dialog) = state.tool_dialog {
-                if current_param < dialog.parameters.len() {
-                    // Move to next parameter or execute tool
-                    if current_param == dialog.parameters.len() - 1 {
-                        // All parameters collected, execute tool
-                        state.mode = AppMode::ToolExecution;
-                    } else {
-                        // Move to next parameter
-                        dialog.current_param += 1;
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let message = match services::run(&target, action, &unit) {
+                Ok(message) => message,
+                Err(e) => e.to_string(),
+            };
+            let units = services::list_units(&target).map_err(|e| e.to_string());
+            app_state.update(move |state| {
+                if let Some(ref mut services) = state.tools.services {
+                    services.busy = false;
+                    if services.target == target {
+                        services.message = Some(message);
+                        services.set_units(units);
                     }
                 }
-            }
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Open the mount manager on the block devices of the running system
+    fn open_mount_manager(&mut self) -> error::Result<()> {
+        {
+            let state = &mut self.state;
+            let mut mounts = MountManagerState::loading();
+            if !state.privileged {
+                mounts.message = Some("Mounting and unmounting needs root".to_string());
+            }
+            state.tools.mounts = Some(mounts);
+            state.transition(AppMode::MountManager);
+            state
+                .status
+                .info("Mount manager: block devices and their mountpoints");
         }
 
-        // Execute tool outside of the state lock
-        if current_param
-            == self
-                .lock_state()?
-                .tool_dialog
-                .as_ref()
-                .map(|d| d.parameters.len() - 1)
-                .unwrap_or(0)
+        self.refresh_mounts()
+    }
+
+    /// List the block devices again (in the background)
+    fn refresh_mounts(&mut self) -> error::Result<()> {
         {
-            self.execute_tool_with_params(&tool_name, param_values)?;
+            let state = &mut self.state;
+            match state.tools.mounts {
+                Some(ref mut mounts) if !mounts.busy => mounts.devices = None,
+                _ => return Ok(()),
+            }
+        }
+
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let devices = mounts::list_devices().map_err(|e| e.to_string());
+            app_state.update(move |state| {
+                if let Some(ref mut mounts) = state.tools.mounts {
+                    mounts.set_devices(devices);
+                }
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Highlighted device of the mount manager, if it may be acted on
+    ///
+    /// Sets the manager's message and returns `None` without root.
+    fn device_to_act_on(&mut self, action: &str) -> error::Result<Option<mounts::BlockDevice>> {
+        let state = &mut self.state;
+        let privileged = state.privileged;
+        let Some(ref mut mounts) = state.tools.mounts else {
+            return Ok(None);
+        };
+        if mounts.busy || mounts.is_loading() {
+            return Ok(None);
+        }
+        let Some(device) = mounts.selected_device().cloned() else {
+            return Ok(None);
+        };
+        if !privileged {
+            mounts.message = Some(format!("{} {} needs root", action, device.path));
+            return Ok(None);
         }
+        Ok(Some(device))
+    }
 
+    /// Ask where to mount the highlighted device, suggesting a free directory
+    fn ask_mountpoint(&mut self) -> error::Result<()> {
+        let Some(device) = self.device_to_act_on("Mounting")? else {
+            return Ok(());
+        };
+        let suggestion = {
+            let state = &mut self.state;
+            state.tools.current = Some("mount_at".to_string());
+            let listed = state.tools.mounts.as_ref().map_or(&[][..], |m| m.listed());
+            mounts::suggested_mountpoint(&device, listed)
+        };
+        self.input_handler.start_text_input(
+            "Mount Point".to_string(),
+            suggestion,
+            format!("Directory to mount {} at", device.path),
+        );
         Ok(())
     }
 
-    /// Create a tool dialog for parameter collection
-    fn create_tool_dialog(&mut self, tool_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let parameters = Self::get_tool_parameters(tool_name);
-        let param_values = vec![String::new(); parameters.len()];
-
-        let mut state = self.lock_state_mut()?;
-        state.tool_dialog = Some(ToolDialogState {
-            tool_name: tool_name.to_string(),
-            parameters,
-            current_param: 0,
-            param_values,
-            is_executing: false,
-        });
-        state.mode = AppMode::ToolDialog;
-        state.status_message = format!("Configure parameters for {}", tool_name);
+    /// Mount the highlighted device (in the background)
+    ///
+    /// Without `path` it goes to the suggested mount point. The device list
+    /// is read again afterwards so the table shows the result.
+    fn mount_selected_device(&mut self, path: Option<String>) -> error::Result<()> {
+        let Some(device) = self.device_to_act_on("Mounting")? else {
+            return Ok(());
+        };
+        let path = {
+            let state = &mut self.state;
+            let Some(ref mut mounts) = state.tools.mounts else {
+                return Ok(());
+            };
+            mounts.busy = true;
+            path.unwrap_or_else(|| mounts::suggested_mountpoint(&device, mounts.listed()))
+        };
 
+        self.run_mount_action(move || mounts::mount(&device, path.trim()));
         Ok(())
     }
 
-    /// Execute health tool with selected disk
-    fn execute_health_tool_with_disk(
-        &mut self,
-        selected_disk: String,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.execute_tool_with_device("check_disk_health.sh", &selected_disk, &["--detailed"])
+    /// Unmount the highlighted device (in the background)
+    fn unmount_selected_device(&mut self) -> error::Result<()> {
+        let Some(device) = self.device_to_act_on("Unmounting")? else {
+            return Ok(());
+        };
+        if let Some(ref mut mounts) = self.state.tools.mounts {
+            mounts.busy = true;
+        }
+
+        self.run_mount_action(move || mounts::unmount(&device));
+        Ok(())
+    }
+
+    /// Run a mount or unmount in a thread, then show its result and the new tree
+    fn run_mount_action<F>(&self, action: F)
+    where
+        F: FnOnce() -> error::Result<String> + Send + 'static,
+    {
+        let app_state = self.state_handle();
+        thread::spawn(move || {
+            let message = match action() {
+                Ok(message) => message,
+                Err(e) => e.to_string(),
+            };
+            let devices = mounts::list_devices().map_err(|e| e.to_string());
+            app_state.update(move |state| {
+                if let Some(ref mut mounts) = state.tools.mounts {
+                    mounts.busy = false;
+                    mounts.message = Some(message);
+                    mounts.set_devices(devices);
+                }
+            });
+        });
     }
 
     /// Generic function to execute tools that need a device parameter (async/non-blocking)
@@ -2877,7 +4047,7 @@ impl App {
         script_name: &str,
         device: &str,
         extra_args: &[&str],
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         let mut args = Vec::new();
         args.push("--device".to_string());
         args.push(device.to_string());
@@ -2891,7 +4061,7 @@ impl App {
 
         // Set up floating output window
         {
-            let mut state = self.lock_state_mut()?;
+            let state = &mut self.state;
             state.floating_output = Some(FloatingOutputState {
                 title: format!("Running: {} on {}", tool_display, device),
                 content: vec![
@@ -2904,8 +4074,8 @@ impl App {
                 progress: None,
                 status: "Running...".to_string(),
             });
-            state.mode = AppMode::FloatingOutput;
-            state.current_tool = Some(tool_display);
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some(tool_display);
         }
 
         // Spawn the tool in a background thread
@@ -2919,14 +4089,14 @@ impl App {
         &mut self,
         script_name: &str,
         extra_args: &[&str],
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
         let args: Vec<String> = extra_args.iter().map(|s| s.to_string()).collect();
         let script_path = format!("scripts/tools/{}", script_name);
         let tool_display = script_name.replace(".sh", "").replace('_', " ");
 
         // Set up floating output window
         {
-            let mut state = self.lock_state_mut()?;
+            let state = &mut self.state;
             state.floating_output = Some(FloatingOutputState {
                 title: format!("Running: {}", tool_display),
                 content: vec![
@@ -2939,8 +4109,8 @@ impl App {
                 progress: None,
                 status: "Running...".to_string(),
             });
-            state.mode = AppMode::FloatingOutput;
-            state.current_tool = Some(tool_display.clone());
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some(tool_display.clone());
         }
 
         // Spawn the tool in a background thread
@@ -2959,7 +4129,11 @@ impl App {
         &self,
         script_path: &str,
         args: Vec<String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
+        if simulate::is_enabled() {
+            self.simulate_tool_script(script_path, &args);
+            return Ok(());
+        }
         let tx = self.tool_tx.clone();
         let script_path = script_path.to_string();
 
@@ -3057,6 +4231,18 @@ impl App {
         Ok(())
     }
 
+    /// Report a tool script as run without running it (--simulate)
+    fn simulate_tool_script(&self, script_path: &str, args: &[String]) {
+        let command = format!("bash {} {}", script_path, args.join(" "));
+        let _ = self
+            .tool_tx
+            .send(ToolMessage::Stdout(simulate::skipped(command.trim_end())));
+        let _ = self.tool_tx.send(ToolMessage::Complete {
+            success: true,
+            exit_code: Some(0),
+        });
+    }
+
     /// Spawn a tool script with optional stdin data (for secure password passing)
     /// This prevents passwords from being visible in `ps aux` or `/proc/<pid>/cmdline`
     ///
@@ -3069,7 +4255,11 @@ impl App {
         script_path: &str,
         args: Vec<String>,
         stdin_data: Option<String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
+        if simulate::is_enabled() {
+            self.simulate_tool_script(script_path, &args);
+            return Ok(());
+        }
         let tx = self.tool_tx.clone();
         let script_path = script_path.to_string();
 
@@ -3187,23 +4377,32 @@ impl App {
         &mut self,
         tool_name: &str,
         params: Vec<String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> error::Result<()> {
+        // Resizing is native: preview the plan before anything is changed
+        if tool_name == "resize_partition" && params.len() >= 2 {
+            return self.preview_resize(&params[0], &params[1]);
+        }
+        // Formatting erases the partition: confirm the options first
+        if tool_name == "format_partition" {
+            return self.confirm_format(&params);
+        }
+        // Snapshots are native; deleting and rolling back are confirmed first
+        match tool_name {
+            "list_snapshots" | "create_snapshot" => {
+                let device = params.first().cloned().unwrap_or_default();
+                let description = params.get(1).cloned().unwrap_or_default();
+                return self.execute_snapshot_tool(tool_name, &device, &description);
+            }
+            "delete_snapshot" | "rollback_snapshot" => {
+                return self.confirm_snapshot(tool_name, &params);
+            }
+            _ => {}
+        }
+
         let mut args = Vec::new();
 
         // Map tool names to their script names and build arguments
         match tool_name {
-            "Format Partition" => {
-                if params.len() >= 2 {
-                    args.push("--device".to_string());
-                    args.push(params[0].clone());
-                    args.push("--filesystem".to_string());
-                    args.push(params[1].clone());
-                    if params.len() >= 3 && !params[2].is_empty() {
-                        args.push("--label".to_string());
-                        args.push(params[2].clone());
-                    }
-                }
-            }
             "Wipe Disk" => {
                 if params.len() >= 2 {
                     args.push("--device".to_string());
@@ -3232,56 +4431,6 @@ impl App {
                     args.push("--detailed".to_string());
                 }
             }
-            "mount" => {
-                if params.len() >= 2 {
-                    args.push("--action".to_string());
-                    args.push(params[0].clone());
-
-                    // Determine if target is device or mountpoint based on action
-                    let action = &params[0];
-                    let target = &params[1];
-
-                    if action == "mount" {
-                        // For mount: target is device, destination is mountpoint
-                        args.push("--device".to_string());
-                        args.push(target.clone());
-                        if params.len() >= 3 && !params[2].is_empty() {
-                            args.push("--mountpoint".to_string());
-                            args.push(params[2].clone());
-                        }
-                    } else if action == "umount" {
-                        // For umount: determine if target is device or mountpoint
-                        if target.starts_with("/dev/") {
-                            args.push("--device".to_string());
-                            args.push(target.clone());
-                        } else {
-                            args.push("--mountpoint".to_string());
-                            args.push(target.clone());
-                        }
-                    } else {
-                        // For info/list: target is device
-                        args.push("--device".to_string());
-                        args.push(target.clone());
-                    }
-
-                    // Add flags
-                    if params.len() >= 4 && params[3] == "true" {
-                        args.push("--readonly".to_string());
-                    }
-                    if params.len() >= 5 && params[4] == "true" {
-                        args.push("--force".to_string());
-                    }
-                }
-            }
-            "chroot" => {
-                if !params.is_empty() {
-                    args.push("--root".to_string());
-                    args.push(params[0].clone());
-                    if params.len() >= 2 && params[1] == "true" {
-                        args.push("--no-mount".to_string());
-                    }
-                }
-            }
             "info" => {
                 if !params.is_empty() && params[0] == "true" {
                     args.push("--detailed".to_string());
@@ -3290,27 +4439,6 @@ impl App {
                     args.push("--json".to_string());
                 }
             }
-            "install_bootloader" => {
-                if !params.is_empty() {
-                    args.push("--type".to_string());
-                    args.push(params[0].clone());
-                }
-                if params.len() >= 2 {
-                    args.push("--disk".to_string());
-                    args.push(params[1].clone());
-                }
-                if params.len() >= 3 && !params[2].is_empty() {
-                    args.push("--efi-path".to_string());
-                    args.push(params[2].clone());
-                }
-                if params.len() >= 4 && !params[3].is_empty() {
-                    args.push("--mode".to_string());
-                    args.push(params[3].clone());
-                }
-                if params.len() >= 5 && params[4] == "true" {
-                    args.push("--repair".to_string());
-                }
-            }
             "add_user" => {
                 // Parameter order: username, password, full_name, groups, shell, system_user
                 // NOTE: Password (params[1]) is NOT passed as command-line arg for security
@@ -3343,29 +4471,42 @@ impl App {
                 }
             }
             "configure_network" => {
-                if !params.is_empty() {
-                    args.push("--interface".to_string());
-                    args.push(params[0].clone());
-                }
-                if params.len() >= 2 {
-                    args.push("--action".to_string());
-                    args.push(params[1].clone());
-                }
-                if params.len() >= 3 && !params[2].is_empty() {
-                    args.push("--config_type".to_string());
-                    args.push(params[2].clone());
-                }
-                if params.len() >= 4 && !params[3].is_empty() {
-                    args.push("--ip".to_string());
-                    args.push(params[3].clone());
-                }
-                if params.len() >= 5 && !params[4].is_empty() {
-                    args.push("--netmask".to_string());
-                    args.push(params[4].clone());
-                }
-                if params.len() >= 6 && !params[5].is_empty() {
-                    args.push("--gateway".to_string());
-                    args.push(params[5].clone());
+                // Parameter order: interface, action, config_type, ip, netmask, gateway,
+                // ipv6_mode, ip6, gateway6, dns, backend
+                let param =
+                    |index: usize| params.get(index).filter(|value| !value.is_empty()).cloned();
+                if param(1).as_deref() == Some("configure") {
+                    let static_ipv4 = param(2).as_deref() == Some("static");
+                    let settings = crate::network::NetworkSettings {
+                        interface: param(0).unwrap_or_default(),
+                        ipv4: param(3).filter(|_| static_ipv4),
+                        netmask: param(4).filter(|_| static_ipv4),
+                        gateway4: param(5).filter(|_| static_ipv4),
+                        ipv6_mode: param(6).and_then(|mode| mode.parse().ok()),
+                        ipv6: param(7),
+                        gateway6: param(8),
+                        dns: crate::network::parse_dns(&param(9).unwrap_or_default()),
+                        backend: param(10).and_then(|backend| backend.parse().ok()),
+                    };
+                    if static_ipv4 && settings.ipv4.is_none() {
+                        return Err(ArchInstallError::validation(
+                            "ip",
+                            "Static configuration needs an IP address",
+                        ));
+                    }
+                    settings
+                        .validate()
+                        .map_err(|e| ArchInstallError::validation("configure_network", e))?;
+                    args.extend(settings.script_args().as_slice().iter().cloned());
+                } else {
+                    if let Some(interface) = param(0) {
+                        args.push("--interface".to_string());
+                        args.push(interface);
+                    }
+                    if let Some(action) = param(1) {
+                        args.push("--action".to_string());
+                        args.push(action);
+                    }
                 }
             }
             _ => {
@@ -3380,39 +4521,36 @@ impl App {
 
         let script_name = match tool_name {
             "format_partition" => "format_partition.sh",
-            "wipe_disk" => "wipe_disk.sh",
-            "install_bootloader" => "install_bootloader.sh",
             "generate_fstab" => "generate_fstab.sh",
             "add_user" => "add_user.sh",
             "health" => "check_disk_health.sh",
-            "mount" => "mount_partitions.sh",
-            "chroot" => "chroot_system.sh",
             "info" => "system_info.sh",
             "reset_password" => "reset_password.sh",
             "configure_network" => "configure_network.sh",
             "manual_partition" => "manual_partition.sh",
             _ => {
-                return Err(format!("Unknown tool: {}", tool_name).into());
+                return Err(ArchInstallError::validation(
+                    "tool",
+                    format!("'{}' is not a known tool", tool_name),
+                ));
             }
         };
 
         // Interactive tools should use embedded terminal
-        let interactive_tools = ["chroot", "manual_partition"];
+        let interactive_tools = ["manual_partition"];
         if interactive_tools.contains(&tool_name) {
             let script_path = format!("scripts/tools/{}", script_name);
 
             // Determine return mode based on tool
             let return_mode = match tool_name {
-                "chroot" => AppMode::SystemTools,
                 "manual_partition" => AppMode::DiskTools,
                 _ => AppMode::ToolsMenu,
             };
 
             // Clear tool dialog state before launching
-            if let Ok(mut state) = self.lock_state_mut() {
-                state.tool_dialog = None;
-                state.current_tool = None;
-            }
+            let state = &mut self.state;
+            state.tools.dialog = None;
+            state.tools.current = None;
 
             // Build argument list for bash: ["-c", "script_path arg1 arg2 ..."]
             let full_cmd = if args.is_empty() {
@@ -3429,8 +4567,8 @@ impl App {
 
         // Non-interactive tools use floating output window with async execution
         {
-            let mut state = self.lock_state_mut()?;
-            state.tool_dialog = None;
+            let state = &mut self.state;
+            state.tools.dialog = None;
             state.floating_output = Some(FloatingOutputState {
                 title: format!("Running: {}", tool_display),
                 content: vec![
@@ -3443,8 +4581,8 @@ impl App {
                 progress: None,
                 status: "Running...".to_string(),
             });
-            state.mode = AppMode::FloatingOutput;
-            state.current_tool = Some(tool_display);
+            state.transition(AppMode::FloatingOutput);
+            state.tools.current = Some(tool_display);
         }
 
         // Spawn the tool in a background thread
@@ -3464,3 +4602,438 @@ impl App {
         Ok(())
     }
 }
+
+/// Check spanning several options, returning the error it finds
+type CrossOptionCheck = fn(&Configuration) -> Option<String>;
+
+/// Checks spanning several options, with the options each one concerns
+const CROSS_OPTION_CHECKS: &[(CrossOptionCheck, &[&str])] = &[
+    (secure_boot_error, &["Secure Boot", "Boot Mode"]),
+    (multilib_group_error, &["Multilib", "Package Groups"]),
+    (boot_mode_error, &["Boot Mode", "Bootloader"]),
+    (arch_error, &["Kernel", "Bootloader", "Boot Mode"]),
+    (
+        efi_partition_error,
+        &["EFI Partition", "Boot Mode", "Partitioning Strategy"],
+    ),
+    (existing_os_error, &["Existing OS", "Partitioning Strategy"]),
+    (
+        partition_mounts_error,
+        &["Partition Mounts", "Partitioning Strategy"],
+    ),
+    (
+        lvm_layout_error,
+        &[
+            "Partitioning Strategy",
+            "LVM Volume Group",
+            "LVM Root Size",
+            "LVM Var Size",
+            "LVM Home Size",
+            "LVM Thin Provisioning",
+        ],
+    ),
+    (
+        encrypted_boot_error,
+        &[
+            "Encrypted Boot",
+            "Bootloader",
+            "Partitioning Strategy",
+            "Encryption",
+        ],
+    ),
+    (
+        initramfs_error,
+        &["Initramfs Hooks", "Partitioning Strategy", "Encryption"],
+    ),
+    (
+        hibernation_swap_error,
+        &["Hibernation", "Swap", "Swap Size"],
+    ),
+    (hibernation_hooks_error, &["Hibernation", "Initramfs Hooks"]),
+    (
+        filesystem_error,
+        &[
+            "Root Filesystem",
+            "Home Filesystem",
+            "Btrfs Snapshots",
+            "Encrypted Boot",
+        ],
+    ),
+    (
+        power_management_error,
+        &["Power Management", "Desktop Environment"],
+    ),
+    (
+        password_length_error,
+        &["User Password", "Root Password", "Password Minimum Length"],
+    ),
+];
+
+/// Validity of every option, in configuration order
+///
+/// An option's own validator decides first; an option that is valid on its
+/// own is marked as conflicting when a check it takes part in fails.
+fn option_validity(config: &Configuration) -> Vec<Validity> {
+    let conflicts: Vec<(String, &[&str])> = CROSS_OPTION_CHECKS
+        .iter()
+        .filter_map(|(check, names)| check(config).map(|error| (error, *names)))
+        .collect();
+    config
+        .options
+        .iter()
+        .map(|option| {
+            if let Some(error) = option.validation_error() {
+                return Validity::Invalid(error);
+            }
+            conflicts
+                .iter()
+                .find(|(_, names)| names.contains(&option.name.as_str()))
+                .map_or(Validity::Valid, |(error, _)| {
+                    Validity::Conflict(error.clone())
+                })
+        })
+        .collect()
+}
+
+/// Error when Secure Boot is enabled for a non-UEFI boot mode
+fn secure_boot_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.value.to_lowercase())
+            .unwrap_or_default()
+    };
+    let boot_mode = value("Boot Mode");
+    (value("Secure Boot") == "yes" && boot_mode != "uefi" && boot_mode != "auto").then(|| {
+        "Secure Boot requires UEFI boot mode. Please configure UEFI firmware first.".to_string()
+    })
+}
+
+/// Error when a selected package group needs multilib but it is disabled
+fn multilib_group_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    if value("Multilib").eq_ignore_ascii_case("yes") {
+        return None;
+    }
+    crate::package_utils::parse_package_groups(&value("Package Groups"))
+        .ok()?
+        .into_iter()
+        .find(|group| group.requires_multilib)
+        .map(|group| format!("Package group '{}' requires Multilib", group.id))
+}
+
+/// Error when the bootloader cannot boot in the selected (or detected) mode
+fn boot_mode_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    crate::config::validate_boot_mode(
+        value("Boot Mode").parse().unwrap_or_default(),
+        value("Bootloader").parse().unwrap_or_default(),
+        Some(std::path::Path::new("/sys/firmware/efi").exists()),
+    )
+    .err()
+}
+
+/// Error when the kernel or bootloader does not exist for this architecture
+fn arch_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    crate::arch::validate(
+        crate::arch::Arch::current(),
+        value("Kernel").parse().unwrap_or_default(),
+        value("Bootloader").parse().unwrap_or_default(),
+        value("Boot Mode").parse().unwrap_or_default(),
+        Some(std::path::Path::new("/sys/firmware/efi").exists()),
+    )
+    .err()
+}
+
+/// Error when an existing ESP is to be reused by a layout that cannot keep it
+fn efi_partition_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    crate::esp::validate_reuse(
+        &value("EFI Partition"),
+        value("Boot Mode").parse().unwrap_or_default(),
+        value("Partitioning Strategy").parse().unwrap_or_default(),
+        Some(std::path::Path::new("/sys/firmware/efi").exists()),
+    )
+    .err()
+}
+
+/// Error when installing alongside another system with a layout that wipes disks
+fn existing_os_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    crate::multiboot::validate_policy(
+        value("Existing OS").parse().unwrap_or_default(),
+        value("Partitioning Strategy").parse().unwrap_or_default(),
+    )
+    .err()
+}
+
+/// Error when the existing strategy has no partitions to install onto
+fn partition_mounts_error(config: &Configuration) -> Option<String> {
+    let existing = config.value("Partitioning Strategy").parse() == Ok(PartitionScheme::Existing);
+    if existing && config.value("Partition Mounts").trim().is_empty() {
+        Some("Installing onto existing partitions needs Partition Mounts: assign / at least".to_string())
+    } else {
+        None
+    }
+}
+
+/// Refuse disks whose operating systems the Existing OS option protects
+fn existing_systems_check(
+    config: &Configuration,
+    disks: &[crate::hardware::DiskInfo],
+) -> Result<(), String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    let targets: Vec<String> = value("Disk")
+        .split(',')
+        .map(str::trim)
+        .filter(|disk| !disk.is_empty())
+        .map(str::to_string)
+        .collect();
+    crate::multiboot::check_targets(
+        value("Existing OS").parse().unwrap_or_default(),
+        value("Partitioning Strategy").parse().unwrap_or_default(),
+        &value("EFI Partition"),
+        disks,
+        &targets,
+    )
+}
+
+/// Error when the LVM layout of an LVM strategy is invalid
+fn lvm_layout_error(config: &Configuration) -> Option<String> {
+    lvm_layout_check(config, None).err()
+}
+
+/// Refuse LVM volumes that do not fit on the install disk
+fn lvm_space_check(
+    config: &Configuration,
+    disks: &[crate::hardware::DiskInfo],
+) -> Result<(), String> {
+    let disk = config
+        .options
+        .iter()
+        .find(|option| option.name == "Disk")
+        .map(|option| option.get_value())
+        .unwrap_or_default();
+    match disks.iter().find(|d| d.path == disk && d.size > 0) {
+        Some(disk) => lvm_layout_check(config, Some(disk.size)),
+        None => Ok(()),
+    }
+}
+
+fn lvm_layout_check(config: &Configuration, disk_size: Option<u64>) -> Result<(), String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    let yes = |name: &str| value(name).eq_ignore_ascii_case("yes");
+    if !crate::lvm::is_configurable(value("Partitioning Strategy").parse().unwrap_or_default()) {
+        return Ok(());
+    }
+    // Sizes relative to RAM are not known here
+    let swap = yes("Swap")
+        .then(|| crate::lvm::parse_size(&value("Swap Size")).ok().flatten())
+        .flatten()
+        .and_then(|size| match size {
+            crate::lvm::Size::Bytes(bytes) => Some(bytes),
+            crate::lvm::Size::PercentFree(_) => None,
+        });
+    let home_size = value("LVM Home Size");
+    crate::lvm::validate_layout(
+        &value("LVM Volume Group"),
+        &value("LVM Root Size"),
+        &value("LVM Var Size"),
+        yes("Separate Home Partition").then_some(home_size.as_str()),
+        yes("LVM Thin Provisioning"),
+        disk_size.map(|size| crate::lvm::available_space(size, swap)),
+    )
+}
+
+/// Error when encrypted /boot is enabled without GRUB or an encrypted layout
+fn encrypted_boot_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    if !value("Encrypted Boot").eq_ignore_ascii_case("yes") {
+        return None;
+    }
+    crate::config::validate_encrypted_boot(
+        value("Bootloader").parse().unwrap_or_default(),
+        value("Partitioning Strategy").parse().unwrap_or_default(),
+        value("Encryption").parse().unwrap_or_default(),
+    )
+    .err()
+}
+
+/// Error when custom initramfs hooks cannot assemble the storage layout
+fn initramfs_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    let hooks = crate::initramfs::parse_hooks(&value("Initramfs Hooks"))
+        .ok()
+        .flatten()?;
+    crate::initramfs::check_storage(
+        &hooks,
+        value("Partitioning Strategy").parse().unwrap_or_default(),
+        value("Encryption").parse().unwrap_or_default(),
+    )
+    .err()
+}
+
+/// Error when the swap cannot hold the memory image of this machine
+fn hibernation_swap_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    if !value("Hibernation").eq_ignore_ascii_case("yes") {
+        return None;
+    }
+    crate::hibernation::check_swap(
+        value("Swap").eq_ignore_ascii_case("yes"),
+        &value("Swap Size"),
+        crate::hibernation::total_memory(),
+        crate::hibernation::uses_zram(&value("Additional Pacman Packages")),
+    )
+    .err()
+}
+
+/// Error when custom initramfs hooks cannot resume from hibernation
+fn hibernation_hooks_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    if !value("Hibernation").eq_ignore_ascii_case("yes") {
+        return None;
+    }
+    let hooks = crate::initramfs::parse_hooks(&value("Initramfs Hooks"))
+        .ok()
+        .flatten()?;
+    crate::hibernation::check_hooks(&hooks).err()
+}
+
+/// Error when an option or the running kernel does not suit the filesystems
+fn filesystem_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    let yes = |name: &str| value(name).eq_ignore_ascii_case("yes");
+    crate::filesystem::validate_filesystems(
+        value("Root Filesystem").parse().unwrap_or_default(),
+        yes("Separate Home Partition")
+            .then(|| value("Home Filesystem").parse().unwrap_or_default()),
+        yes("Btrfs Snapshots"),
+        yes("Encrypted Boot"),
+        crate::filesystem::running_kernel().as_deref(),
+    )
+    .err()
+}
+
+/// Error when the power manager conflicts with the desktop environment
+fn power_management_error(config: &Configuration) -> Option<String> {
+    let value = |name: &str| {
+        config
+            .options
+            .iter()
+            .find(|option| option.name == name)
+            .map(|option| option.get_value())
+            .unwrap_or_default()
+    };
+    crate::power::validate(
+        value("Power Management").parse().unwrap_or_default(),
+        value("Desktop Environment").parse().unwrap_or_default(),
+    )
+    .err()
+}
+
+/// Error when a password is shorter than the configured minimum
+///
+/// Empty passwords are left to the required check.
+fn password_length_error(config: &Configuration) -> Option<String> {
+    let policy = config.password_policy();
+    ["User Password", "Root Password"]
+        .into_iter()
+        .find_map(|name| {
+            let password = config.value(name);
+            if password.is_empty() {
+                return None;
+            }
+            policy
+                .check(&password)
+                .err()
+                .map(|e| format!("{}: {}", name, e))
+        })
+}
@@ -0,0 +1,1072 @@
+//! State reducer
+//!
+//! Applies the state-only [`Action`]s to [`AppState`]: menu navigation,
+//! scrolling, dialog toggles and returning to previous screens. Nothing here
+//! spawns processes or touches the terminal, so every transition can be
+//! tested on a plain `AppState`.
+
+use super::action::{Action, Movement};
+use super::{AppMode, AppState};
+use crate::clone;
+use crate::components::confirm_dialog::reset_all_confirm;
+use crate::components::help_overlay::HelpOverlay;
+use crate::components::keybindings::KeybindingContext;
+use crate::input;
+
+/// Status shown when returning to the main menu
+const MAIN_MENU_STATUS: &str = "Welcome to Arch Linux Toolkit";
+/// Status shown when returning to the tools menu
+const TOOLS_MENU_STATUS: &str = "Arch Linux Tools - System repair and administration";
+
+/// Number of entries in a menu screen (including its Back/Quit entry)
+fn menu_len(mode: &AppMode) -> Option<usize> {
+    match mode {
+        AppMode::MainMenu => Some(5),
+        AppMode::NetworkTools | AppMode::SnapshotTools => Some(5),
+        AppMode::ToolsMenu | AppMode::SystemTools | AppMode::UserTools => Some(6),
+        AppMode::DiskTools => Some(8),
+        _ => None,
+    }
+}
+
+/// Tool category menu a tool belongs to
+///
+/// Covers both the menu tool names and the tool dialog names.
+fn tool_category(tool_name: &str) -> AppMode {
+    match tool_name {
+        "format_partition" | "wipe_disk" | "health" | "check_disk_health" | "mount"
+        | "mount_partitions" | "manual_partition" | "resize_partition" | "cleanup" => {
+            AppMode::DiskTools
+        }
+        "bootloader_repair" | "generate_fstab" | "chroot" | "chroot_system" | "info"
+        | "system_info" => AppMode::SystemTools,
+        "add_user" | "reset_password" | "manage_groups" | "configure_ssh" | "security_audit" => {
+            AppMode::UserTools
+        }
+        "configure"
+        | "configure_network"
+        | "test_network"
+        | "configure_firewall"
+        | "network_diagnostics" => AppMode::NetworkTools,
+        "list_snapshots" | "create_snapshot" | "delete_snapshot" | "rollback_snapshot" => {
+            AppMode::SnapshotTools
+        }
+        _ => AppMode::ToolsMenu,
+    }
+}
+
+impl AppState {
+    /// Apply a state-only action
+    ///
+    /// Returns `false` when the action needs side effects the app has to
+    /// perform (or does nothing in the current mode), `true` once it has been
+    /// fully handled here.
+    pub fn reduce(&mut self, action: &Action) -> bool {
+        match action {
+            Action::ToggleHelp => {
+                self.help_visible = !self.help_visible;
+                self.help_scroll = 0;
+                self.help_page = self.selected_option();
+                self.help_wiki = None;
+                true
+            }
+            Action::ScrollHelp(movement) => {
+                self.scroll_help(*movement);
+                true
+            }
+            Action::PreviousHelpPage => {
+                if let Some(page) = self.help_page {
+                    self.help_page = Some(page.saturating_sub(1));
+                    self.help_scroll = 0;
+                    self.help_wiki = None;
+                }
+                true
+            }
+            Action::NextHelpPage => {
+                if let Some(page) = self.help_page {
+                    self.help_page = Some((page + 1).min(self.guided.config.options.len() - 1));
+                    self.help_scroll = 0;
+                    self.help_wiki = None;
+                }
+                true
+            }
+            Action::ToggleHelpKeys => {
+                self.help_page = match self.help_page {
+                    Some(_) => None,
+                    None => self.selected_option(),
+                };
+                self.help_scroll = 0;
+                self.help_wiki = None;
+                true
+            }
+            Action::Navigate(movement) => {
+                self.navigate(*movement);
+                true
+            }
+            Action::ToggleWizard if self.mode == AppMode::GuidedInstaller => {
+                self.guided.wizard = !self.guided.wizard;
+                if self.guided.wizard {
+                    // Start on a step, not on an option the wizard skips
+                    let steps = self.guided.wizard_steps();
+                    if !steps.contains(&self.guided.scroll.selected_index) {
+                        self.guided.wizard_step(true);
+                    }
+                    self.status
+                        .info("Wizard: one question per screen, ←/→ to go back and forward");
+                } else {
+                    self.status.info("Showing all configuration options");
+                }
+                true
+            }
+            Action::Toggle => {
+                if let Some(ref mut dialog) = self.confirm_dialog {
+                    // Toggle between No (0) and Yes (1)
+                    dialog.selected = if dialog.selected == 0 { 1 } else { 0 };
+                    log::debug!(
+                        "ConfirmDialog toggle: selected={} (0=No/left, 1=Yes/right)",
+                        dialog.selected
+                    );
+                }
+                true
+            }
+            Action::ArrangeOutput(change) => {
+                self.floating_layout.apply(*change);
+                self.status.info(self.floating_layout.describe());
+                true
+            }
+            Action::Cancel => self.cancel(),
+            Action::Back => self.back(),
+            Action::ResetAll => {
+                let modified = self.guided.config.modified_count();
+                if modified == 0 {
+                    self.status
+                        .info("All options already have their default values");
+                } else {
+                    self.open_confirm(reset_all_confirm(modified), AppMode::GuidedInstaller);
+                }
+                true
+            }
+            Action::CycleMountpoint | Action::CycleFormat
+                if self.mode == AppMode::PartitionMounts =>
+            {
+                if let Some(ref mut editor) = self.guided.partition_mounts {
+                    if *action == Action::CycleMountpoint {
+                        editor.cycle_mountpoint();
+                    } else {
+                        editor.cycle_format();
+                    }
+                }
+                true
+            }
+            Action::Select if self.mode == AppMode::PartitionMounts => {
+                if let Some(editor) = self.guided.partition_mounts.take() {
+                    let value = editor.plan.to_string();
+                    if let Some(option) = self.guided.config.option_mut("Partition Mounts") {
+                        option.value = value.clone();
+                    }
+                    match editor.plan.validate() {
+                        Ok(()) if !editor.plan.is_empty() => {
+                            self.status.info(format!("Set Partition Mounts to: {}", value))
+                        }
+                        Ok(()) => self.status.warn("No partitions are assigned yet"),
+                        Err(e) => self.status.warn(e),
+                    }
+                }
+                self.transition(AppMode::GuidedInstaller);
+                true
+            }
+            Action::InsertChar(c) if self.output_prompt_open() => {
+                if let Some(ref mut prompt) = self.install.output.prompt {
+                    input::insert_char(prompt, *c);
+                }
+                true
+            }
+            Action::DeleteChar if self.output_prompt_open() => {
+                if let Some(ref mut prompt) = self.install.output.prompt {
+                    input::delete_last_char(prompt);
+                }
+                true
+            }
+            Action::Select if self.output_prompt_open() => {
+                if !self.install.output.confirm_search() {
+                    self.warn_no_match();
+                }
+                true
+            }
+            Action::InsertChar(c) if self.service_filter_open() => {
+                if let Some(ref mut services) = self.tools.services {
+                    input::insert_char(&mut services.filter, *c);
+                    services.apply_filter();
+                }
+                true
+            }
+            Action::DeleteChar if self.service_filter_open() => {
+                if let Some(ref mut services) = self.tools.services {
+                    input::delete_last_char(&mut services.filter);
+                    services.apply_filter();
+                }
+                true
+            }
+            Action::Select if self.service_filter_open() => {
+                if let Some(ref mut services) = self.tools.services {
+                    services.filtering = false;
+                }
+                true
+            }
+            Action::FilterUnits => {
+                if let Some(ref mut services) = self.tools.services {
+                    services.filtering = true;
+                    services.message = None;
+                }
+                true
+            }
+            Action::InsertChar(c) if self.mode == AppMode::Summary => {
+                if let Some(ref mut summary) = self.guided.summary {
+                    input::insert_char(&mut summary.typed, *c);
+                    summary.error = None;
+                }
+                true
+            }
+            Action::DeleteChar if self.mode == AppMode::Summary => {
+                if let Some(ref mut summary) = self.guided.summary {
+                    input::delete_last_char(&mut summary.typed);
+                    summary.error = None;
+                }
+                true
+            }
+            Action::InsertChar(c) => {
+                if let Some(ref mut dialog) = self.tools.dialog {
+                    dialog.insert_char(*c);
+                }
+                true
+            }
+            Action::DeleteChar => {
+                if let Some(ref mut dialog) = self.tools.dialog {
+                    dialog.delete_char();
+                }
+                true
+            }
+            Action::NextValue | Action::PreviousValue => {
+                if let Some(ref mut dialog) = self.tools.dialog {
+                    dialog.step(*action == Action::NextValue);
+                }
+                true
+            }
+            Action::BrowseHome => {
+                if let Some(ref mut browser) = self.file_browser {
+                    browser.go_home();
+                }
+                true
+            }
+            Action::BrowseRoot => {
+                if let Some(ref mut browser) = self.file_browser {
+                    browser.go_root();
+                }
+                true
+            }
+            Action::SearchOutput => {
+                self.install.output.start_search();
+                true
+            }
+            Action::NextMatch | Action::PreviousMatch => {
+                if self.install.output.search.is_none() {
+                    self.status.warn("Press / to search the installer output");
+                } else if !self.install.output.next_match(*action == Action::NextMatch) {
+                    self.warn_no_match();
+                }
+                true
+            }
+            Action::CycleOutputFilter => {
+                self.install.output.cycle_filter();
+                self.status.info(format!(
+                    "Installer output: showing {}",
+                    self.install.output.filter.label()
+                ));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Index of the configuration option highlighted in the guided installer
+    ///
+    /// `None` outside the guided installer and on the START button.
+    fn selected_option(&self) -> Option<usize> {
+        let index = self.guided.scroll.selected_index;
+        (self.mode == AppMode::GuidedInstaller && index < self.guided.config.options.len())
+            .then_some(index)
+    }
+
+    /// Whether keys are typed into the installer output's search prompt
+    fn output_prompt_open(&self) -> bool {
+        self.mode == AppMode::Installation && self.install.output.prompt.is_some()
+    }
+
+    /// Whether keys are typed into the service manager's filter
+    pub fn service_filter_open(&self) -> bool {
+        self.mode == AppMode::ServiceManager
+            && self.tools.services.as_ref().is_some_and(|s| s.filtering)
+    }
+
+    /// Report a search of the installer output that found nothing
+    fn warn_no_match(&mut self) {
+        if let Some(ref term) = self.install.output.search {
+            let message = format!("No matches for '{}' in the installer output", term);
+            self.status.warn(message);
+        }
+    }
+
+    /// Scroll the help overlay within its content
+    fn scroll_help(&mut self, movement: Movement) {
+        const PAGE: usize = 10;
+        let last = HelpOverlay::for_state(self, &KeybindingContext::new())
+            .content_len()
+            .saturating_sub(1);
+        self.help_scroll = match movement {
+            Movement::Up => self.help_scroll.saturating_sub(1),
+            Movement::Down => self.help_scroll + 1,
+            Movement::PageUp => self.help_scroll.saturating_sub(PAGE),
+            Movement::PageDown => self.help_scroll + PAGE,
+            Movement::First => 0,
+            Movement::Last => last,
+        }
+        .min(last);
+    }
+
+    /// Show a menu screen with its first entry selected
+    fn open_menu(&mut self, mode: AppMode) {
+        self.status.info(match mode {
+            AppMode::MainMenu => MAIN_MENU_STATUS,
+            AppMode::DiskTools => "Disk & Filesystem Tools",
+            AppMode::SystemTools => "System & Boot Tools",
+            AppMode::UserTools => "User & Security Tools",
+            AppMode::NetworkTools => "Network Tools",
+            AppMode::SnapshotTools => "Snapshot Tools",
+            _ => TOOLS_MENU_STATUS,
+        }
+        .to_string());
+        if mode == AppMode::MainMenu {
+            self.menu.main_selection = 0;
+        } else {
+            self.menu.tools_selection = 0;
+        }
+        self.transition(mode);
+    }
+
+    /// Move the selection or scroll position of the current screen
+    fn navigate(&mut self, movement: Movement) {
+        if let Some(len) = menu_len(&self.mode) {
+            let selection = if self.mode == AppMode::MainMenu {
+                &mut self.menu.main_selection
+            } else {
+                &mut self.menu.tools_selection
+            };
+            match movement {
+                Movement::Up => *selection = selection.saturating_sub(1),
+                Movement::Down => *selection = (*selection + 1).min(len - 1),
+                _ => {}
+            }
+            return;
+        }
+
+        match self.mode {
+            AppMode::GuidedInstaller if self.guided.wizard => match movement {
+                Movement::Up | Movement::PageUp => self.guided.wizard_step(false),
+                Movement::Down | Movement::PageDown => self.guided.wizard_step(true),
+                Movement::First | Movement::Last => {
+                    let steps = self.guided.wizard_steps();
+                    let step = match movement {
+                        Movement::First => steps.first(),
+                        _ => steps.last(),
+                    };
+                    if let Some(&step) = step {
+                        self.guided.scroll.set_selected(step);
+                    }
+                }
+            },
+            AppMode::GuidedInstaller => match movement {
+                Movement::Up => self.guided.scroll.move_up(),
+                Movement::Down => self.guided.scroll.move_down(),
+                Movement::PageUp => self.guided.scroll.page_up(),
+                Movement::PageDown => self.guided.scroll.page_down(),
+                Movement::First => self.guided.scroll.move_to_first(),
+                Movement::Last => self.guided.scroll.move_to_last(),
+            },
+            AppMode::Summary => {
+                if let Some(ref mut summary) = self.guided.summary {
+                    const PAGE: usize = 10;
+                    let last = summary.content_len().saturating_sub(1);
+                    summary.scroll = match movement {
+                        Movement::Up => summary.scroll.saturating_sub(1),
+                        Movement::Down => summary.scroll + 1,
+                        Movement::PageUp => summary.scroll.saturating_sub(PAGE),
+                        Movement::PageDown => summary.scroll + PAGE,
+                        Movement::First => 0,
+                        Movement::Last => last,
+                    }
+                    .min(last);
+                }
+            }
+            AppMode::PartitionMounts => {
+                if let Some(ref mut editor) = self.guided.partition_mounts {
+                    let table = &mut editor.table;
+                    match movement {
+                        Movement::Up => table.select_previous(),
+                        Movement::Down => table.select_next(),
+                        Movement::PageUp => table.page_up(),
+                        Movement::PageDown => table.page_down(),
+                        Movement::First => table.select_first(),
+                        Movement::Last => table.select_last(),
+                    }
+                }
+            }
+            AppMode::PartitionCheck => {
+                if let Some(ref mut check) = self.guided.partition_check {
+                    const PAGE: usize = 10;
+                    let last = check.content_len().saturating_sub(1);
+                    check.scroll = match movement {
+                        Movement::Up => check.scroll.saturating_sub(1),
+                        Movement::Down => check.scroll + 1,
+                        Movement::PageUp => check.scroll.saturating_sub(PAGE),
+                        Movement::PageDown => check.scroll + PAGE,
+                        Movement::First => 0,
+                        Movement::Last => last,
+                    }
+                    .min(last);
+                }
+            }
+            AppMode::ToolDialog => {
+                if let Some(ref mut dialog) = self.tools.dialog {
+                    match movement {
+                        Movement::Up => dialog.focus_previous(),
+                        Movement::Down => dialog.focus_next(),
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::FloatingOutput => {
+                if let Some(ref mut output) = self.floating_output {
+                    match movement {
+                        Movement::Up => {
+                            output.scroll_offset = output.scroll_offset.saturating_sub(1)
+                        }
+                        Movement::Down
+                            if output.scroll_offset < output.content.len().saturating_sub(1) =>
+                        {
+                            output.scroll_offset += 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::Installation => {
+                let log = &mut self.install.output;
+                match movement {
+                    Movement::Up => log.scroll.move_up(),
+                    Movement::Down => log.scroll.move_down(),
+                    Movement::PageUp => return log.page(true),
+                    Movement::PageDown => return log.page(false),
+                    Movement::First => log.scroll.move_to_first(),
+                    Movement::Last => log.scroll.move_to_last(),
+                }
+                log.after_scroll();
+            }
+            AppMode::FileBrowser => {
+                if let Some(ref mut browser) = self.file_browser {
+                    match movement {
+                        Movement::Up => browser.move_up(),
+                        Movement::Down => browser.move_down(),
+                        _ => {}
+                    }
+                }
+            }
+            AppMode::ServiceManager => {
+                if let Some(ref mut services) = self.tools.services {
+                    let table = &mut services.table;
+                    match movement {
+                        Movement::Up => table.select_previous(),
+                        Movement::Down => table.select_next(),
+                        Movement::PageUp => table.page_up(),
+                        Movement::PageDown => table.page_down(),
+                        Movement::First => table.select_first(),
+                        Movement::Last => table.select_last(),
+                    }
+                }
+            }
+            AppMode::MountManager => {
+                if let Some(ref mut mounts) = self.tools.mounts {
+                    let table = &mut mounts.table;
+                    match movement {
+                        Movement::Up => table.select_previous(),
+                        Movement::Down => table.select_next(),
+                        Movement::PageUp => table.page_up(),
+                        Movement::PageDown => table.page_down(),
+                        Movement::First => table.select_first(),
+                        Movement::Last => table.select_last(),
+                    }
+                }
+            }
+            AppMode::RecoveryDialog => {
+                if let Some(ref mut dialog) = self.install.recovery_dialog {
+                    match movement {
+                        Movement::Up => dialog.move_selection(false),
+                        Movement::Down => dialog.move_selection(true),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Dismiss the current dialog
+    fn cancel(&mut self) -> bool {
+        match self.mode {
+            AppMode::ConfirmDialog => {
+                self.close_confirm();
+            }
+            AppMode::ToolDialog => {
+                let category = self
+                    .tools
+                    .current
+                    .as_deref()
+                    .map_or(AppMode::ToolsMenu, tool_category);
+                self.tools.dialog = None;
+                self.tools.current = None;
+                self.open_menu(category);
+            }
+            AppMode::Installation if self.install.output.prompt.is_some() => {
+                self.install.output.prompt = None;
+            }
+            AppMode::ServiceManager if self.service_filter_open() => {
+                if let Some(ref mut services) = self.tools.services {
+                    services.filter.clear();
+                    services.filtering = false;
+                    services.apply_filter();
+                }
+            }
+            AppMode::FileBrowser => {
+                if let Some(ref mut browser) = self.file_browser {
+                    browser.cancel();
+                }
+                self.file_browser = None;
+                self.transition(AppMode::AutomatedInstall);
+                self.status.warn("File selection cancelled");
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Return to the previous screen
+    fn back(&mut self) -> bool {
+        match self.mode {
+            AppMode::MainMenu => {
+                // Already at top level
+                self.status.warn("Press 'Q' to quit or use arrow keys to navigate");
+            }
+            AppMode::GuidedInstaller
+            | AppMode::AutomatedInstall
+            | AppMode::ToolsMenu
+            | AppMode::Complete => {
+                self.open_menu(AppMode::MainMenu);
+            }
+            AppMode::DiskTools
+            | AppMode::SystemTools
+            | AppMode::UserTools
+            | AppMode::NetworkTools
+            | AppMode::SnapshotTools => {
+                self.open_menu(AppMode::ToolsMenu);
+            }
+            AppMode::ToolDialog | AppMode::ToolExecution => {
+                let category = self
+                    .tools
+                    .current
+                    .as_deref()
+                    .map_or(AppMode::ToolsMenu, tool_category);
+                self.open_menu(category);
+                self.tools.dialog = None;
+                self.tools.output.clear();
+                self.tools.current = None;
+            }
+            AppMode::Summary => {
+                self.guided.summary = None;
+                self.transition(AppMode::GuidedInstaller);
+                self.status.warn("Installation not started - review your settings");
+            }
+            AppMode::PartitionCheck => {
+                let ready = self
+                    .guided
+                    .partition_check
+                    .take()
+                    .is_some_and(|check| check.is_ready());
+                self.transition(AppMode::GuidedInstaller);
+                if ready {
+                    self.status.info("Partition layout checked - ready to install");
+                } else {
+                    self.status
+                        .warn("Fix the partition layout before starting the installation");
+                }
+            }
+            AppMode::PartitionMounts => {
+                self.guided.partition_mounts = None;
+                self.transition(AppMode::GuidedInstaller);
+                self.status.info("Partition Mounts unchanged");
+            }
+            AppMode::Installation => {
+                // During installation, go back to guided installer
+                self.transition(AppMode::GuidedInstaller);
+                self.status.warn("Installation cancelled - configure your settings");
+            }
+            AppMode::FloatingOutput => {
+                if self.floating_output.take().is_some() {
+                    if self.tools.current.as_deref() == Some(clone::TOOL_NAME) {
+                        // Review the cloned configuration
+                        self.tools.current = None;
+                        self.transition(AppMode::GuidedInstaller);
+                        self.status.info(
+                            "Review the cloned configuration, then choose the disk and passwords",
+                        );
+                    } else {
+                        self.open_menu(AppMode::ToolsMenu);
+                    }
+                }
+            }
+            AppMode::FileBrowser => return self.cancel(),
+            AppMode::ConfirmDialog => {
+                self.close_confirm();
+                self.status.warn("Operation cancelled");
+            }
+            AppMode::DiskHealth => {
+                // Close the report and return to disk tools
+                self.tools.disk_health = None;
+                self.transition(AppMode::DiskTools);
+                self.status.info("Disk Tools");
+            }
+            AppMode::ServiceManager => {
+                // Close the unit browser and return to system tools
+                self.tools.services = None;
+                self.transition(AppMode::SystemTools);
+                self.status.info("System & Boot Tools");
+            }
+            AppMode::MountManager => {
+                // Close the mount manager and return to disk tools
+                self.tools.mounts = None;
+                self.transition(AppMode::DiskTools);
+                self.status.info("Disk Tools");
+            }
+            // Leaving the embedded terminal has to tear down its PTY
+            AppMode::EmbeddedTerminal => return false,
+            // The installer waits until a recovery choice is made
+            AppMode::RecoveryDialog => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::confirm_dialog::wipe_disk_confirm;
+    use crate::components::floating_window::FloatingOutputState;
+    use crate::tools::wipe::WipeMethod;
+    use crate::wiki::Article;
+
+    fn state_in(mode: AppMode) -> AppState {
+        AppState {
+            mode,
+            ..AppState::default()
+        }
+    }
+
+    #[test]
+    fn test_menu_navigation_is_clamped() {
+        let mut state = state_in(AppMode::MainMenu);
+        assert!(state.reduce(&Action::Navigate(Movement::Up)));
+        assert_eq!(state.menu.main_selection, 0);
+        for _ in 0..10 {
+            state.reduce(&Action::Navigate(Movement::Down));
+        }
+        assert_eq!(state.menu.main_selection, 4);
+
+        let mut state = state_in(AppMode::NetworkTools);
+        for _ in 0..10 {
+            state.reduce(&Action::Navigate(Movement::Down));
+        }
+        assert_eq!(state.menu.tools_selection, 4);
+    }
+
+    #[test]
+    fn test_back_walks_up_the_menu_tree() {
+        let mut state = state_in(AppMode::DiskTools);
+        state.menu.tools_selection = 3;
+        assert!(state.reduce(&Action::Back));
+        assert_eq!(state.mode, AppMode::ToolsMenu);
+        assert_eq!(state.menu.tools_selection, 0);
+
+        assert!(state.reduce(&Action::Back));
+        assert_eq!(state.mode, AppMode::MainMenu);
+        assert_eq!(state.status.text(), MAIN_MENU_STATUS);
+    }
+
+    #[test]
+    fn test_back_from_tool_returns_to_its_category() {
+        let mut state = state_in(AppMode::ToolExecution);
+        state.tools.current = Some("test_network".to_string());
+        state.tools.output.push("ping ok".to_string());
+        assert!(state.reduce(&Action::Back));
+        assert_eq!(state.mode, AppMode::NetworkTools);
+        assert!(state.tools.output.is_empty());
+        assert!(state.tools.current.is_none());
+    }
+
+    #[test]
+    fn test_confirm_dialog_toggle_and_cancel() {
+        let mut state = state_in(AppMode::ConfirmDialog);
+        state.pre_dialog_mode = Some(AppMode::DiskTools);
+        state.confirm_dialog = Some(wipe_disk_confirm("/dev/sda", WipeMethod::Zero));
+
+        state.reduce(&Action::Toggle);
+        assert!(state.confirm_dialog.as_ref().unwrap().is_confirmed());
+
+        assert!(state.reduce(&Action::Cancel));
+        assert!(state.confirm_dialog.is_none());
+        assert_eq!(state.mode, AppMode::DiskTools);
+    }
+
+    #[test]
+    fn test_summary_typing_scroll_and_back() {
+        use crate::components::install_summary::InstallSummaryState;
+
+        let mut state = state_in(AppMode::Summary);
+        let mut summary = InstallSummaryState::new(&state.guided.config, &[]);
+        summary.error = Some("Type ERASE to start the installation".to_string());
+        state.guided.summary = Some(summary);
+
+        for c in "ERASX".chars() {
+            assert!(state.reduce(&Action::InsertChar(c)));
+        }
+        assert!(state.reduce(&Action::DeleteChar));
+        state.reduce(&Action::InsertChar('E'));
+        let summary = state.guided.summary.as_ref().unwrap();
+        assert_eq!(summary.typed, "ERASE");
+        assert!(summary.error.is_none());
+
+        state.reduce(&Action::Navigate(Movement::Last));
+        let last = state.guided.summary.as_ref().unwrap().content_len() - 1;
+        assert_eq!(state.guided.summary.as_ref().unwrap().scroll, last);
+        state.reduce(&Action::Navigate(Movement::Down));
+        assert_eq!(state.guided.summary.as_ref().unwrap().scroll, last);
+
+        // Typing here never reaches a tool dialog
+        assert!(state.tools.dialog.is_none());
+
+        assert!(state.reduce(&Action::Back));
+        assert_eq!(state.mode, AppMode::GuidedInstaller);
+        assert!(state.guided.summary.is_none());
+    }
+
+    #[test]
+    fn test_partition_check_scroll_and_back() {
+        use crate::components::partition_check::PartitionCheckState;
+        use crate::partition_check::Report;
+        use crate::types::BootMode;
+
+        let mut state = state_in(AppMode::PartitionCheck);
+        state.guided.partition_check = Some(PartitionCheckState {
+            disks: vec!["/dev/sda".to_string()],
+            boot_mode: BootMode::Bios,
+            plan: None,
+            report: Ok(Report::new(BootMode::Bios, Vec::new(), Vec::new())),
+            scroll: 0,
+        });
+        state.reduce(&Action::Navigate(Movement::PageDown));
+        let check = state.guided.partition_check.as_ref().unwrap();
+        assert_eq!(check.scroll, check.content_len() - 1);
+        assert!(!check.is_ready());
+
+        assert!(state.reduce(&Action::Back));
+        assert_eq!(state.mode, AppMode::GuidedInstaller);
+        assert!(state.guided.partition_check.is_none());
+        assert_eq!(
+            state.status.text(),
+            "Fix the partition layout before starting the installation"
+        );
+    }
+
+    #[test]
+    fn test_partition_mounts_edit_and_save() {
+        use crate::components::partition_mounts::PartitionMountsState;
+        use crate::mount_plan::MountPlan;
+        use crate::partition_check::Partition;
+
+        let partitions = ["/dev/sda1", "/dev/sda2"]
+            .iter()
+            .map(|path| Partition {
+                path: path.to_string(),
+                fstype: "ext4".to_string(),
+                ..Partition::default()
+            })
+            .collect();
+        let mut state = state_in(AppMode::PartitionMounts);
+        state.guided.partition_mounts = Some(PartitionMountsState::new(
+            Ok(partitions),
+            MountPlan::default(),
+            "ext4".to_string(),
+            "ext4".to_string(),
+        ));
+        state.reduce(&Action::Navigate(Movement::Down));
+        assert!(state.reduce(&Action::CycleMountpoint));
+        state.reduce(&Action::Navigate(Movement::Up));
+        state.reduce(&Action::CycleMountpoint);
+        state.reduce(&Action::CycleMountpoint);
+
+        assert!(state.reduce(&Action::Select));
+        assert_eq!(state.mode, AppMode::GuidedInstaller);
+        assert!(state.guided.partition_mounts.is_none());
+        assert_eq!(
+            state.guided.config.value("Partition Mounts"),
+            "/dev/sda2=/:ext4,/dev/sda1=/boot"
+        );
+    }
+
+    #[test]
+    fn test_floating_output_scroll_and_dismiss() {
+        let mut state = state_in(AppMode::FloatingOutput);
+        let mut output = FloatingOutputState::new("Test");
+        output.append_line("one".to_string());
+        output.append_line("two".to_string());
+        state.floating_output = Some(output);
+
+        state.reduce(&Action::Navigate(Movement::Down));
+        state.reduce(&Action::Navigate(Movement::Down));
+        assert_eq!(state.floating_output.as_ref().unwrap().scroll_offset, 1);
+
+        assert!(state.reduce(&Action::Back));
+        assert!(state.floating_output.is_none());
+        assert_eq!(state.mode, AppMode::ToolsMenu);
+    }
+
+    #[test]
+    fn test_floating_output_layout_outlives_the_output() {
+        use crate::components::floating_window::{WindowChange, WindowDock};
+
+        let mut state = state_in(AppMode::FloatingOutput);
+        state.floating_output = Some(FloatingOutputState::new("Test"));
+        assert!(state.reduce(&Action::ArrangeOutput(WindowChange::Dock)));
+        assert_eq!(state.status.text(), "Output window docked to the bottom");
+
+        state.reduce(&Action::Back);
+        assert!(state.floating_output.is_none());
+        assert_eq!(state.floating_layout.dock, WindowDock::Bottom);
+    }
+
+    #[test]
+    fn test_installer_output_search_prompt() {
+        let mut state = state_in(AppMode::Installation);
+        for line in ["pacstrap base", "ERROR: mkfs.ext4 failed", "genfstab"] {
+            state.install.output.push(line.to_string());
+        }
+
+        assert!(state.reduce(&Action::NextMatch));
+        assert!(state.status.text().contains("Press /"));
+
+        state.reduce(&Action::SearchOutput);
+        for c in "mkfsx".chars() {
+            assert!(state.reduce(&Action::InsertChar(c)));
+        }
+        state.reduce(&Action::DeleteChar);
+        assert!(state.reduce(&Action::Select));
+        assert_eq!(state.install.output.search.as_deref(), Some("mkfs"));
+        assert_eq!(state.install.output.scroll.selected_index, 1);
+        assert!(state.tools.dialog.is_none());
+
+        // Esc closes an open prompt but does not leave the screen
+        state.reduce(&Action::SearchOutput);
+        assert!(state.reduce(&Action::Cancel));
+        assert!(state.install.output.prompt.is_none());
+        assert!(!state.reduce(&Action::Cancel));
+        assert_eq!(state.mode, AppMode::Installation);
+
+        state.reduce(&Action::CycleOutputFilter);
+        assert_eq!(state.install.output.shown_len(), 1);
+    }
+
+    #[test]
+    fn test_help_opens_on_selected_option() {
+        let mut state = state_in(AppMode::GuidedInstaller);
+        state.guided.scroll.selected_index = 4;
+        assert!(state.reduce(&Action::ToggleHelp));
+        assert!(state.help_visible);
+        assert_eq!(state.help_page, Some(4));
+
+        state.reduce(&Action::NextHelpPage);
+        assert_eq!(state.help_page, Some(5));
+        state.reduce(&Action::ToggleHelpKeys);
+        assert_eq!(state.help_page, None);
+        state.reduce(&Action::ToggleHelpKeys);
+        assert_eq!(state.help_page, Some(4));
+
+        state.reduce(&Action::ToggleHelp);
+        assert!(!state.help_visible);
+
+        // The START button and other screens show the keybindings
+        state.guided.scroll.selected_index = state.guided.config.options.len();
+        state.reduce(&Action::ToggleHelp);
+        assert_eq!(state.help_page, None);
+        let mut state = state_in(AppMode::MainMenu);
+        state.reduce(&Action::ToggleHelp);
+        assert_eq!(state.help_page, None);
+    }
+
+    #[test]
+    fn test_help_pages_and_scroll_are_clamped() {
+        let mut state = state_in(AppMode::GuidedInstaller);
+        state.reduce(&Action::ToggleHelp);
+        state.reduce(&Action::PreviousHelpPage);
+        assert_eq!(state.help_page, Some(0));
+
+        let last = state.guided.config.options.len() - 1;
+        state.help_page = Some(last);
+        state.reduce(&Action::NextHelpPage);
+        assert_eq!(state.help_page, Some(last));
+
+        state.reduce(&Action::ScrollHelp(Movement::Up));
+        assert_eq!(state.help_scroll, 0);
+        state.reduce(&Action::ScrollHelp(Movement::Last));
+        let bottom = state.help_scroll;
+        assert!(bottom > 0);
+        state.reduce(&Action::ScrollHelp(Movement::PageDown));
+        assert_eq!(state.help_scroll, bottom);
+
+        state.reduce(&Action::PreviousHelpPage);
+        assert_eq!(state.help_scroll, 0);
+    }
+
+    #[test]
+    fn test_help_pages_close_the_wiki_article() {
+        let mut state = state_in(AppMode::GuidedInstaller);
+        state.reduce(&Action::ToggleHelp);
+        state.help_wiki = Some(Article::Fetching("Swap".to_string()));
+        // Fetching the article is a side effect
+        assert!(!state.reduce(&Action::ToggleWikiArticle));
+
+        state.reduce(&Action::NextHelpPage);
+        assert_eq!(state.help_wiki, None);
+        state.help_wiki = Some(Article::Fetching("Swap".to_string()));
+        state.reduce(&Action::ToggleHelp);
+        assert_eq!(state.help_wiki, None);
+    }
+
+    #[test]
+    fn test_service_manager_filter() {
+        use crate::components::service_manager::ServiceManagerState;
+        use crate::tools::services::{Target, Unit};
+
+        let mut state = state_in(AppMode::ServiceManager);
+        let mut services = ServiceManagerState::loading(Target::Running);
+        services.set_units(Ok(["bluetooth.service", "sshd.service", "sshd.socket"]
+            .iter()
+            .map(|name| Unit {
+                name: name.to_string(),
+                ..Unit::default()
+            })
+            .collect()));
+        state.tools.services = Some(services);
+
+        state.reduce(&Action::FilterUnits);
+        for c in "sshx".chars() {
+            state.reduce(&Action::InsertChar(c));
+        }
+        state.reduce(&Action::DeleteChar);
+        state.reduce(&Action::Select);
+        state.reduce(&Action::Navigate(Movement::Last));
+        let services = state.tools.services.as_ref().unwrap();
+        assert!(!services.filtering);
+        assert_eq!(services.visible().len(), 2);
+        assert_eq!(services.selected_unit().unwrap().name, "sshd.socket");
+
+        // Esc in the prompt clears the filter, Esc outside it leaves
+        state.reduce(&Action::FilterUnits);
+        assert!(state.reduce(&Action::Cancel));
+        assert_eq!(state.tools.services.as_ref().unwrap().visible().len(), 3);
+        state.reduce(&Action::Back);
+        assert!(state.tools.services.is_none());
+        assert_eq!(state.mode, AppMode::SystemTools);
+    }
+
+    #[test]
+    fn test_mount_manager_navigation() {
+        use crate::components::mount_manager::MountManagerState;
+        use crate::tools::mounts::BlockDevice;
+
+        let mut state = state_in(AppMode::MountManager);
+        let mut mounts = MountManagerState::loading();
+        mounts.set_devices(Ok(["sda", "sda1", "sda2"]
+            .iter()
+            .map(|name| BlockDevice {
+                name: name.to_string(),
+                path: format!("/dev/{}", name),
+                ..BlockDevice::default()
+            })
+            .collect()));
+        state.tools.mounts = Some(mounts);
+
+        state.reduce(&Action::Navigate(Movement::Last));
+        state.reduce(&Action::Navigate(Movement::Up));
+        let mounts = state.tools.mounts.as_ref().unwrap();
+        assert_eq!(mounts.selected_device().unwrap().name, "sda1");
+
+        state.reduce(&Action::Back);
+        assert!(state.tools.mounts.is_none());
+        assert_eq!(state.mode, AppMode::DiskTools);
+    }
+
+    #[test]
+    fn test_wizard_skips_irrelevant_options() {
+        let mut state = state_in(AppMode::GuidedInstaller);
+        let index = |state: &AppState, name: &str| {
+            state
+                .guided
+                .config
+                .options
+                .iter()
+                .position(|o| o.name == name)
+                .unwrap()
+        };
+        let swap = index(&state, "Swap");
+        state.guided.config.options[swap].value = "No".to_string();
+        let swap_size = index(&state, "Swap Size");
+        state.guided.config.options[swap_size].value = "N/A".to_string();
+        state.guided.scroll.set_selected(swap);
+
+        assert!(state.reduce(&Action::ToggleWizard));
+        assert!(state.guided.wizard);
+        state.reduce(&Action::Navigate(Movement::Down));
+        let next = state.guided.scroll.selected_index;
+        assert_eq!(state.guided.config.options[next].name, "Var Partition Size");
+        state.reduce(&Action::Navigate(Movement::Up));
+        assert_eq!(state.guided.scroll.selected_index, swap);
+
+        // The last step is the start button
+        state.reduce(&Action::Navigate(Movement::Last));
+        assert_eq!(
+            state.guided.scroll.selected_index,
+            state.guided.config.options.len()
+        );
+
+        state.reduce(&Action::ToggleWizard);
+        assert!(!state.guided.wizard);
+    }
+
+    #[test]
+    fn test_side_effect_actions_are_left_to_the_app() {
+        let mut state = state_in(AppMode::GuidedInstaller);
+        assert!(!state.reduce(&Action::Select));
+        assert!(!state.reduce(&Action::StartInstall));
+        assert!(!state.reduce(&Action::Quit));
+        assert!(!state_in(AppMode::EmbeddedTerminal).reduce(&Action::Back));
+        assert!(!state_in(AppMode::RecoveryDialog)
+            .reduce(&Action::Recover(crate::recovery::RecoveryChoice::Abort)));
+    }
+}
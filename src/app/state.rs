@@ -1,16 +1,35 @@
 //! Application state definitions
 //!
 //! Contains all state-related types for the application including AppState,
-//! AppMode, and tool-related state types.
+//! its per-screen parts, AppMode, and tool-related state types.
 
 #![allow(dead_code)]
 
 use crate::components::confirm_dialog::ConfirmDialogState;
+use crate::components::disk_health::DiskHealthState;
+use crate::components::install_summary::InstallSummaryState;
+use crate::components::output_log::OutputLog;
+use crate::components::partition_check::PartitionCheckState;
+use crate::components::partition_mounts::PartitionMountsState;
 use crate::components::file_browser::FileBrowserState;
-use crate::components::floating_window::FloatingOutputState;
+use crate::components::floating_window::{FloatingLayout, FloatingOutputState};
 use crate::components::pty_terminal::PtyTerminalState;
-use crate::config::Configuration;
+use crate::components::recovery_dialog::RecoveryDialogState;
+use crate::components::mount_manager::MountManagerState;
+use crate::components::service_manager::ServiceManagerState;
+use crate::components::status_bar::StatusBarState;
+use crate::config::{Configuration, Validity};
+use crate::install_metrics::InstallMetrics;
+use crate::lanes::Lanes;
+use crate::package_progress::PackageProgress;
 use crate::scrolling::ScrollState;
+use crate::self_update::Release;
+use crate::throughput::Throughput;
+use crate::tools::wipe::WipeTarget;
+use crate::wiki::Article;
+use crate::watchdog::WatchdogState;
+use crate::wizard;
+use std::time::Instant;
 
 /// Tool parameter types for input dialogs
 #[derive(Debug, Clone)]
@@ -43,43 +62,202 @@ pub struct ToolDialogState {
     pub is_executing: bool,
 }
 
+impl ToolDialogState {
+    /// Whether the focused field is a password
+    pub fn editing_password(&self) -> bool {
+        self.parameters
+            .get(self.current_param)
+            .is_some_and(|param| matches!(param.param_type, ToolParameter::Password(_)))
+    }
+}
+
 /// Main application state
+///
+/// Screen-specific state lives in [`MenuState`], [`GuidedState`],
+/// [`ToolsState`] and [`InstallState`]; what is left here is shared by every
+/// screen (mode, status bar, overlays and dialogs).
 #[derive(Debug, Clone)]
 pub struct AppState {
-    /// Current application mode
+    /// Current application mode; changed through [`AppState::transition`]
     pub mode: AppMode,
-    /// Configuration options
-    pub config: Configuration,
-    /// Scroll state for configuration list
-    pub config_scroll: ScrollState,
-    /// Status message for user feedback
-    pub status_message: String,
-    /// Installer output lines
-    pub installer_output: Vec<String>,
-    /// Installation progress percentage
-    pub installation_progress: u8,
-    /// Main menu selection state
-    pub main_menu_selection: usize,
-    /// Tools menu selection state
-    pub tools_menu_selection: usize,
-    /// Current tool being executed
-    pub current_tool: Option<String>,
-    /// Tool execution output
-    pub tool_output: Vec<String>,
-    /// Tool dialog state for parameter collection
-    pub tool_dialog: Option<ToolDialogState>,
+    /// Main and tools menu selections
+    pub menu: MenuState,
+    /// Guided installer configuration and summary
+    pub guided: GuidedState,
+    /// Tool dialogs, output and reports
+    pub tools: ToolsState,
+    /// Progress of the running installation
+    pub install: InstallState,
+    /// Status bar message for user feedback
+    pub status: StatusBarState,
     /// Whether help overlay is visible
     pub help_visible: bool,
+    /// Configuration option whose help page is shown (keybindings when `None`)
+    pub help_page: Option<usize>,
+    /// Scroll offset of the help overlay
+    pub help_scroll: usize,
+    /// Arch Wiki article shown in place of the option's help page
+    pub help_wiki: Option<Article>,
     /// Floating output window state
     pub floating_output: Option<FloatingOutputState>,
+    /// Size and place of the floating output window, kept between tools
+    pub floating_layout: FloatingLayout,
     /// Embedded terminal state
     pub embedded_terminal: Option<PtyTerminalState>,
     /// File browser state
     pub file_browser: Option<FileBrowserState>,
     /// Confirmation dialog state
     pub confirm_dialog: Option<ConfirmDialogState>,
-    /// Previous mode to return to after dialog
+    /// Screen the confirmation dialog covers, set by [`AppState::open_confirm`]
     pub pre_dialog_mode: Option<AppMode>,
+    /// Screen the installer asked for while the user was busy with input,
+    /// shown once they leave it, see [`AppState::transition_when_free`]
+    pub deferred_mode: Option<AppMode>,
+    /// Whether the process runs as root; tools needing root are locked otherwise
+    pub privileged: bool,
+    /// Newer installer release found by the startup check
+    pub update: Option<Release>,
+}
+
+/// Main menu and tools menu state
+#[derive(Debug, Clone, Default)]
+pub struct MenuState {
+    /// Main menu selection
+    pub main_selection: usize,
+    /// Tools menu selection
+    pub tools_selection: usize,
+}
+
+/// Guided installer state
+#[derive(Debug, Clone)]
+pub struct GuidedState {
+    /// Configuration options
+    pub config: Configuration,
+    /// Scroll state for configuration list
+    pub scroll: ScrollState,
+    /// Summary shown before the installation starts
+    pub summary: Option<InstallSummaryState>,
+    /// Validity of each option, refreshed by [`GuidedState::refresh_validity`]
+    pub validity: Vec<Validity>,
+    /// Option values `validity` was computed for
+    validated_values: Vec<String>,
+    /// Whether the options are asked one per screen instead of listed
+    pub wizard: bool,
+    /// Options set by the config file of `--interactive-missing`, which the
+    /// wizard does not ask for
+    pub preseeded: Vec<String>,
+    /// Whether the Region quick setup filled in its options, which the
+    /// wizard then does not ask for either
+    pub region_filled: bool,
+    /// Check of the manual partition layout, while it is shown
+    pub partition_check: Option<PartitionCheckState>,
+    /// Partition Mounts editor, while it is open
+    pub partition_mounts: Option<PartitionMountsState>,
+}
+
+impl Default for GuidedState {
+    fn default() -> Self {
+        let config = Configuration::default();
+        Self {
+            // One line per option plus the start button, 30 visible by default
+            scroll: ScrollState::new(config.options.len(), 30),
+            config,
+            summary: None,
+            validity: Vec::new(),
+            validated_values: Vec::new(),
+            wizard: false,
+            preseeded: Vec::new(),
+            region_filled: false,
+            partition_check: None,
+            partition_mounts: None,
+        }
+    }
+}
+
+impl GuidedState {
+    /// Run the option validators again if any value changed since the last run
+    pub fn refresh_validity(&mut self) {
+        if self
+            .config
+            .options
+            .iter()
+            .map(|o| &o.value)
+            .eq(self.validated_values.iter())
+        {
+            return;
+        }
+        self.validity = super::option_validity(&self.config);
+        self.validated_values = self
+            .config
+            .options
+            .iter()
+            .map(|o| o.value.clone())
+            .collect();
+    }
+
+    /// Options the wizard does not ask for while they are valid
+    pub fn wizard_skips(&self) -> Vec<String> {
+        let mut skips = self.preseeded.clone();
+        if self.region_filled {
+            skips.extend(crate::region::FILLS.iter().map(|name| name.to_string()));
+        }
+        skips
+    }
+
+    /// Stops of the wizard with the current values, see [`wizard::steps`]
+    pub fn wizard_steps(&mut self) -> Vec<usize> {
+        self.refresh_validity();
+        wizard::steps(&self.config, &self.validity, &self.wizard_skips())
+    }
+
+    /// Move the wizard to its next or previous step
+    pub fn wizard_step(&mut self, forward: bool) {
+        let steps = self.wizard_steps();
+        let step = wizard::neighbour(&steps, self.scroll.selected_index, forward);
+        self.scroll.set_selected(step);
+    }
+}
+
+/// Tools menu state: the running tool and its dialogs
+#[derive(Debug, Clone, Default)]
+pub struct ToolsState {
+    /// Current tool being executed
+    pub current: Option<String>,
+    /// Tool execution output
+    pub output: Vec<String>,
+    /// Tool dialog state for parameter collection
+    pub dialog: Option<ToolDialogState>,
+    /// SMART disk health report state
+    pub disk_health: Option<DiskHealthState>,
+    /// systemd unit browser state
+    pub services: Option<ServiceManagerState>,
+    /// Block device mount manager state
+    pub mounts: Option<MountManagerState>,
+    /// Disk picked for wiping while its wipe method is chosen
+    pub wipe_target: Option<WipeTarget>,
+}
+
+/// Installation progress state
+#[derive(Debug, Clone, Default)]
+pub struct InstallState {
+    /// When the running installation started
+    pub started: Option<Instant>,
+    /// Download and disk rates, `Some` while the installer transfers files
+    pub throughput: Option<Throughput>,
+    /// Installer output and the state of its pane
+    pub output: OutputLog,
+    /// Installation progress percentage
+    pub progress: u8,
+    /// Packages of the pacman transaction the installer is running
+    pub package_progress: PackageProgress,
+    /// Failed installation phase waiting for a recovery decision
+    pub recovery_dialog: Option<RecoveryDialogState>,
+    /// Phase durations, downloads, packages and warnings of the installation
+    pub metrics: InstallMetrics,
+    /// How long the installer has been quiet and the command it waits on
+    pub watchdog: WatchdogState,
+    /// Steps install.sh runs side by side, as progress lanes
+    pub lanes: Lanes,
 }
 
 /// Application operating modes
@@ -89,6 +267,12 @@ pub enum AppMode {
     MainMenu,
     /// Guided installer - step-by-step configuration
     GuidedInstaller,
+    /// Review of the final configuration before the installation starts
+    Summary,
+    /// Problems of the manual partition layout
+    PartitionCheck,
+    /// Mountpoints and formats of existing partitions
+    PartitionMounts,
     /// Automated install - run from configuration file
     AutomatedInstall,
     /// Tools menu - system administration tools
@@ -101,6 +285,8 @@ pub enum AppMode {
     UserTools,
     /// Network tools submenu
     NetworkTools,
+    /// Btrfs snapshot tools submenu
+    SnapshotTools,
     /// Tool parameter input dialog
     ToolDialog,
     /// Tool execution in progress
@@ -117,28 +303,38 @@ pub enum AppMode {
     FileBrowser,
     /// Confirmation dialog for destructive operations
     ConfirmDialog,
+    /// SMART disk health report
+    DiskHealth,
+    /// systemd unit browser
+    ServiceManager,
+    /// Block device tree with mount and unmount actions
+    MountManager,
+    /// Choice of how to go on after an installation phase failed
+    RecoveryDialog,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             mode: AppMode::MainMenu,
-            config: Configuration::default(),
-            config_scroll: ScrollState::new(42, 30), // 42 config options, default 30 visible
-            status_message: "Welcome to Arch Linux Toolkit".to_string(),
-            installer_output: Vec::new(),
-            installation_progress: 0,
-            main_menu_selection: 0,
-            tools_menu_selection: 0,
-            current_tool: None,
-            tool_output: Vec::new(),
-            tool_dialog: None,
+            menu: MenuState::default(),
+            guided: GuidedState::default(),
+            tools: ToolsState::default(),
+            install: InstallState::default(),
+            status: StatusBarState::new("Welcome to Arch Linux Toolkit"),
             help_visible: false,
+            help_page: None,
+            help_scroll: 0,
+            help_wiki: None,
             floating_output: None,
+            floating_layout: FloatingLayout::default(),
             embedded_terminal: None,
             file_browser: None,
             confirm_dialog: None,
             pre_dialog_mode: None,
+            deferred_mode: None,
+            privileged: true,
+            update: None,
         }
     }
 }
@@ -0,0 +1,105 @@
+//! State updates from other threads
+//!
+//! The main loop is the only owner of the [`AppState`]: key handling, the
+//! reducer and the renderer use it directly, without a lock. Threads that
+//! follow the installer, a tool or a timer send their changes through a
+//! [`StateHandle`] instead; the main loop applies them in the order they were
+//! sent before it handles input and before it draws, so a busy installer
+//! never holds up a frame.
+
+use super::AppState;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// A change to the application state, applied by the main loop
+pub type StateUpdate = Box<dyn FnOnce(&mut AppState) + Send>;
+
+/// Sends changes to the owner of the application state
+///
+/// Cheap to clone; every thread gets its own copy.
+#[derive(Clone)]
+pub struct StateHandle {
+    tx: Sender<StateUpdate>,
+}
+
+impl StateHandle {
+    /// A handle and the receiving end the owner applies updates from
+    pub fn channel() -> (Self, Receiver<StateUpdate>) {
+        let (tx, rx) = mpsc::channel();
+        (Self { tx }, rx)
+    }
+
+    /// Change the state once the main loop gets to it
+    ///
+    /// Returns `false` when the application is gone, which tells a
+    /// background thread to stop.
+    pub fn update(&self, f: impl FnOnce(&mut AppState) + Send + 'static) -> bool {
+        self.tx.send(Box::new(f)).is_ok()
+    }
+
+    /// Change or read the state and wait until the main loop has done it
+    ///
+    /// `None` when the application is dropped before it applies the change.
+    pub fn query<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut AppState) -> R + Send + 'static,
+    ) -> Option<R> {
+        let (reply, result) = mpsc::channel();
+        self.update(move |state| {
+            let _ = reply.send(f(state));
+        });
+        result.recv().ok()
+    }
+}
+
+/// Apply every update sent so far, in order; returns how many there were
+pub fn apply_updates(state: &mut AppState, updates: &Receiver<StateUpdate>) -> usize {
+    updates.try_iter().fold(0, |applied, update| {
+        update(state);
+        applied + 1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_updates_apply_in_order() {
+        let (handle, updates) = StateHandle::channel();
+        let mut state = AppState::default();
+        handle.update(|state| state.status.info("first"));
+        handle.update(|state| state.status.info("second"));
+        assert_eq!(state.status.text(), AppState::default().status.text());
+
+        assert_eq!(apply_updates(&mut state, &updates), 2);
+        assert_eq!(state.status.text(), "second");
+        assert_eq!(apply_updates(&mut state, &updates), 0);
+    }
+
+    #[test]
+    fn test_query_waits_for_the_owner() {
+        let (handle, updates) = StateHandle::channel();
+        let worker = thread::spawn(move || {
+            handle.query(|state| {
+                state.install.progress = 42;
+                state.install.progress + 1
+            })
+        });
+
+        let mut state = AppState::default();
+        while state.install.progress != 42 {
+            apply_updates(&mut state, &updates);
+            thread::yield_now();
+        }
+        assert_eq!(worker.join().unwrap(), Some(43));
+    }
+
+    #[test]
+    fn test_handles_notice_the_owner_is_gone() {
+        let (handle, updates) = StateHandle::channel();
+        drop(updates);
+        assert!(!handle.update(|_| {}));
+        assert_eq!(handle.query(|_| 1), None);
+    }
+}
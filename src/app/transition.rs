@@ -0,0 +1,314 @@
+//! Mode transitions
+//!
+//! Every change of [`AppMode`] goes through [`AppState::transition`], which
+//! checks it against the table in [`AppMode::can_transition_to`], checks that
+//! the new screen has the state it draws, and logs it. Confirmation dialogs
+//! remember the screen they cover through [`AppState::open_confirm`] and
+//! [`AppState::close_confirm`] instead of setting `pre_dialog_mode` by hand.
+
+use super::{AppMode, AppState};
+use crate::components::confirm_dialog::ConfirmDialogState;
+
+impl AppMode {
+    /// Tool category submenus
+    pub fn is_tool_menu(&self) -> bool {
+        matches!(
+            self,
+            Self::DiskTools
+                | Self::SystemTools
+                | Self::UserTools
+                | Self::NetworkTools
+                | Self::SnapshotTools
+        )
+    }
+
+    /// Screens a tool runs in, opened from its category menu
+    pub fn is_tool_screen(&self) -> bool {
+        matches!(
+            self,
+            Self::ToolDialog
+                | Self::ToolExecution
+                | Self::FloatingOutput
+                | Self::DiskHealth
+                | Self::ServiceManager
+                | Self::MountManager
+        )
+    }
+
+    /// Screens with input of their own that an installer event must not
+    /// replace: the user would lose what they typed or the program running
+    pub fn holds_input(&self) -> bool {
+        matches!(
+            self,
+            Self::ConfirmDialog
+                | Self::FileBrowser
+                | Self::ToolDialog
+                | Self::EmbeddedTerminal
+                | Self::RecoveryDialog
+        )
+    }
+
+    /// Whether the application may go from this mode to `to`
+    pub fn can_transition_to(&self, to: &AppMode) -> bool {
+        use AppMode::*;
+        match (self, to) {
+            // Staying, e.g. a tool dialog kept open to show an error
+            (from, to) if from == to => true,
+
+            // Confirmations cover any screen that is not itself waiting on
+            // the user, and go back to it
+            (ConfirmDialog | EmbeddedTerminal | RecoveryDialog, ConfirmDialog) => false,
+            (_, ConfirmDialog) | (ConfirmDialog, _) => true,
+
+            // Interactive programs return to the screen that started them
+            (from, EmbeddedTerminal) | (EmbeddedTerminal, from)
+                if from.is_tool_menu() || matches!(from, ToolExecution | RecoveryDialog) =>
+            {
+                true
+            }
+            // Left without its state
+            (EmbeddedTerminal, MainMenu) => true,
+
+            // Menus
+            (MainMenu, GuidedInstaller | AutomatedInstall | ToolsMenu) => true,
+            (GuidedInstaller | AutomatedInstall | ToolsMenu | Complete, MainMenu) => true,
+            // Tools started by name open their menu straight away
+            (MainMenu, to) if to.is_tool_menu() => true,
+            (ToolsMenu, menu) | (menu, ToolsMenu) if menu.is_tool_menu() => true,
+
+            // Tools run from their menu and go back to it
+            (from, to) if from.is_tool_menu() && to.is_tool_screen() => true,
+            (from, to) if from.is_tool_screen() && (to.is_tool_menu() || *to == ToolsMenu) => true,
+            (ToolDialog | ToolExecution, to) if to.is_tool_screen() => true,
+            // Clone System shows what it cloned, then the configuration
+            (MainMenu, FloatingOutput) | (FloatingOutput, GuidedInstaller) => true,
+
+            // Guided installer
+            (GuidedInstaller, Summary | PartitionCheck | PartitionMounts) => true,
+            (Summary | PartitionCheck | PartitionMounts, GuidedInstaller) => true,
+            (AutomatedInstall, FileBrowser) | (FileBrowser, AutomatedInstall) => true,
+
+            // Installation, started from the summary, a config file or
+            // before the TUI shows anything (remote and config installs)
+            (MainMenu | AutomatedInstall | Summary, Installation) => true,
+            (Installation, GuidedInstaller) => true,
+            (RecoveryDialog, Installation) => true,
+            // The installer asks and finishes in the background, over the
+            // screen the user went to unless that one is waiting on input
+            (from, RecoveryDialog | Complete) => !from.holds_input(),
+
+            _ => false,
+        }
+    }
+}
+
+impl AppState {
+    /// Switch to `to`
+    ///
+    /// Transitions missing from the table are logged, and panic in debug
+    /// builds, as is a screen entered without the state it draws.
+    pub fn transition(&mut self, to: AppMode) {
+        if self.mode == to {
+            self.debug_check_invariants();
+            return;
+        }
+        if self.mode.can_transition_to(&to) {
+            log::debug!("Mode {:?} -> {:?}", self.mode, to);
+        } else {
+            log::warn!("Unexpected mode transition {:?} -> {:?}", self.mode, to);
+            debug_assert!(false, "mode transition {:?} -> {:?}", self.mode, to);
+        }
+        self.mode = to;
+        self.debug_check_invariants();
+        if !self.mode.holds_input() {
+            if let Some(deferred) = self.deferred_mode.take() {
+                self.transition(deferred);
+            }
+        }
+    }
+
+    /// Switch to `to` now, or once the screen holding input is left
+    pub fn transition_when_free(&mut self, to: AppMode) {
+        if self.mode.holds_input() {
+            self.deferred_mode = Some(to);
+        } else {
+            self.transition(to);
+        }
+    }
+
+    /// Show `dialog`, going back to `back_to` when it is answered or cancelled
+    pub fn open_confirm(&mut self, dialog: ConfirmDialogState, back_to: AppMode) {
+        self.confirm_dialog = Some(dialog);
+        self.pre_dialog_mode = Some(back_to);
+        self.transition(AppMode::ConfirmDialog);
+    }
+
+    /// Close the confirmation dialog and return to the screen it covered
+    pub fn close_confirm(&mut self) -> Option<ConfirmDialogState> {
+        let dialog = self.confirm_dialog.take();
+        let back_to = self.pre_dialog_mode.take().unwrap_or(AppMode::MainMenu);
+        self.transition(back_to);
+        dialog
+    }
+
+    /// What is wrong with the state of the current mode, if anything
+    pub fn invariant_violation(&self) -> Option<&'static str> {
+        let missing = |state_missing: bool, problem| state_missing.then_some(problem);
+        match self.mode {
+            AppMode::ConfirmDialog => missing(
+                self.pre_dialog_mode.is_none(),
+                "confirmation dialog without the screen it covers",
+            )
+            .or(missing(
+                self.confirm_dialog.is_none(),
+                "confirmation dialog without its state",
+            )),
+            AppMode::FileBrowser => missing(
+                self.file_browser.is_none(),
+                "file browser without its state",
+            ),
+            AppMode::EmbeddedTerminal => missing(
+                self.embedded_terminal.is_none(),
+                "embedded terminal without its state",
+            ),
+            AppMode::FloatingOutput => missing(
+                self.floating_output.is_none(),
+                "output window without its state",
+            ),
+            AppMode::ToolDialog => missing(
+                self.tools.dialog.is_none(),
+                "tool dialog without its parameters",
+            ),
+            AppMode::DiskHealth => missing(
+                self.tools.disk_health.is_none(),
+                "disk health report without its state",
+            ),
+            AppMode::ServiceManager => missing(
+                self.tools.services.is_none(),
+                "service manager without its state",
+            ),
+            AppMode::MountManager => missing(
+                self.tools.mounts.is_none(),
+                "mount manager without its state",
+            ),
+            AppMode::Summary => missing(
+                self.guided.summary.is_none(),
+                "installation summary without its state",
+            ),
+            AppMode::PartitionCheck => missing(
+                self.guided.partition_check.is_none(),
+                "partition check without its state",
+            ),
+            AppMode::PartitionMounts => missing(
+                self.guided.partition_mounts.is_none(),
+                "Partition Mounts editor without its state",
+            ),
+            AppMode::RecoveryDialog => missing(
+                self.install.recovery_dialog.is_none(),
+                "recovery dialog without its failure",
+            ),
+            // Only a confirmation has a screen to go back to
+            _ => missing(
+                self.pre_dialog_mode.is_some(),
+                "screen to return to without a dialog",
+            ),
+        }
+    }
+
+    fn debug_check_invariants(&self) {
+        if let Some(problem) = self.invariant_violation() {
+            log::warn!("Inconsistent {:?} state: {}", self.mode, problem);
+            debug_assert!(false, "{:?}: {}", self.mode, problem);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::confirm_dialog::reset_all_confirm;
+
+    #[test]
+    fn test_transition_table() {
+        use AppMode::*;
+        assert!(MainMenu.can_transition_to(&ToolsMenu));
+        assert!(ToolsMenu.can_transition_to(&DiskTools));
+        assert!(DiskTools.can_transition_to(&DiskHealth));
+        assert!(DiskHealth.can_transition_to(&DiskTools));
+        assert!(ToolExecution.can_transition_to(&EmbeddedTerminal));
+        assert!(EmbeddedTerminal.can_transition_to(&RecoveryDialog));
+        assert!(GuidedInstaller.can_transition_to(&ConfirmDialog));
+        assert!(ConfirmDialog.can_transition_to(&AutomatedInstall));
+        assert!(Summary.can_transition_to(&Installation));
+
+        assert!(!MainMenu.can_transition_to(&Summary));
+        assert!(!GuidedInstaller.can_transition_to(&DiskHealth));
+        assert!(!DiskTools.can_transition_to(&Installation));
+        assert!(!EmbeddedTerminal.can_transition_to(&ConfirmDialog));
+        assert!(!RecoveryDialog.can_transition_to(&ConfirmDialog));
+        // The installer's end does not replace a dialog
+        assert!(Installation.can_transition_to(&Complete));
+        assert!(GuidedInstaller.can_transition_to(&Complete));
+        assert!(!FileBrowser.can_transition_to(&Complete));
+        assert!(!ToolDialog.can_transition_to(&RecoveryDialog));
+    }
+
+    #[test]
+    fn test_confirm_returns_to_the_covered_screen() {
+        let mut state = AppState {
+            mode: AppMode::GuidedInstaller,
+            ..AppState::default()
+        };
+        state.open_confirm(reset_all_confirm(2), AppMode::GuidedInstaller);
+        assert_eq!(state.mode, AppMode::ConfirmDialog);
+        assert_eq!(state.invariant_violation(), None);
+
+        assert!(state.close_confirm().is_some());
+        assert_eq!(state.mode, AppMode::GuidedInstaller);
+        assert!(state.pre_dialog_mode.is_none());
+        assert_eq!(state.invariant_violation(), None);
+    }
+
+    #[test]
+    fn test_installer_end_waits_for_the_dialog() {
+        let mut state = AppState {
+            mode: AppMode::Installation,
+            ..AppState::default()
+        };
+        state.open_confirm(reset_all_confirm(2), AppMode::Installation);
+        state.transition_when_free(AppMode::Complete);
+        assert_eq!(state.mode, AppMode::ConfirmDialog);
+
+        state.close_confirm();
+        assert_eq!(state.mode, AppMode::Complete);
+        assert!(state.deferred_mode.is_none());
+    }
+
+    #[test]
+    fn test_invariant_violations() {
+        let mut state = AppState {
+            mode: AppMode::FloatingOutput,
+            ..AppState::default()
+        };
+        assert_eq!(
+            state.invariant_violation(),
+            Some("output window without its state")
+        );
+
+        state.mode = AppMode::DiskTools;
+        assert_eq!(state.invariant_violation(), None);
+        state.pre_dialog_mode = Some(AppMode::MainMenu);
+        assert_eq!(
+            state.invariant_violation(),
+            Some("screen to return to without a dialog")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "mode transition")]
+    #[cfg(debug_assertions)]
+    fn test_unexpected_transition_asserts() {
+        let mut state = AppState::default();
+        state.transition(AppMode::DiskHealth);
+    }
+}
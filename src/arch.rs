@@ -0,0 +1,215 @@
+//! Target architecture
+//!
+//! The installer installs for the machine it runs on. On x86_64 that is Arch
+//! Linux; on aarch64 it is Arch Linux ARM, started from a running system on
+//! the board (an SD card image, or a rescue system on eMMC). Arch Linux ARM
+//! names its kernel package differently, has no multilib repository or CPU
+//! microcode, and most single board computers have no UEFI firmware: U-Boot
+//! starts the kernel from `/boot/extlinux/extlinux.conf` instead of GRUB.
+//!
+//! U-Boot itself lives in SPI flash, an eMMC boot partition or at the start
+//! of the SD card, and differs from board to board; it is expected to be
+//! there already and is never written by the installer.
+
+use crate::types::{BootMode, Bootloader, Kernel};
+use std::fmt;
+use strum::IntoEnumIterator;
+
+/// CPU architecture of the installed system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arch {
+    #[default]
+    X86_64,
+    /// 64-bit ARM, installed from Arch Linux ARM
+    Aarch64,
+}
+
+impl Arch {
+    /// Parse the machine name printed by `uname -m`
+    pub fn from_machine(machine: &str) -> Option<Self> {
+        match machine.trim() {
+            "x86_64" => Some(Self::X86_64),
+            "aarch64" | "arm64" => Some(Self::Aarch64),
+            _ => None,
+        }
+    }
+
+    /// Architecture of the machine the installer runs on
+    pub fn current() -> Self {
+        Self::from_machine(std::env::consts::ARCH).unwrap_or_default()
+    }
+
+    /// Package of `kernel`, `None` when it is not built for the architecture
+    ///
+    /// Arch Linux ARM only builds the mainline kernel, as linux-aarch64.
+    pub fn kernel_package(self, kernel: Kernel) -> Option<&'static str> {
+        match (self, kernel) {
+            (Self::X86_64, Kernel::Linux) => Some("linux"),
+            (Self::X86_64, Kernel::LinuxLts) => Some("linux-lts"),
+            (Self::X86_64, Kernel::LinuxZen) => Some("linux-zen"),
+            (Self::X86_64, Kernel::LinuxHardened) => Some("linux-hardened"),
+            (Self::Aarch64, Kernel::Linux) => Some("linux-aarch64"),
+            (Self::Aarch64, _) => None,
+        }
+    }
+
+    /// Kernels that can be installed
+    pub fn kernels(self) -> Vec<Kernel> {
+        Kernel::iter()
+            .filter(|kernel| self.kernel_package(*kernel).is_some())
+            .collect()
+    }
+
+    /// Bootloaders that can start the system
+    pub fn bootloaders(self) -> Vec<Bootloader> {
+        Bootloader::iter()
+            .filter(|bootloader| *bootloader != Bootloader::UBoot || self == Self::Aarch64)
+            .collect()
+    }
+
+    /// Bootloader proposed for the firmware the machine booted with
+    pub fn default_bootloader(self, uefi_firmware: bool) -> Bootloader {
+        match self {
+            Self::Aarch64 if !uefi_firmware => Bootloader::UBoot,
+            _ => Bootloader::Grub,
+        }
+    }
+
+    /// `grub-install` target of a UEFI install
+    pub fn grub_efi_target(self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64-efi",
+            Self::Aarch64 => "arm64-efi",
+        }
+    }
+
+    /// Suffix of the EFI binaries, as in grubx64.efi and BOOTAA64.EFI
+    pub fn efi_suffix(self) -> &'static str {
+        match self {
+            Self::X86_64 => "x64",
+            Self::Aarch64 => "aa64",
+        }
+    }
+
+    /// Whether the repositories include multilib
+    pub fn has_multilib(self) -> bool {
+        self == Self::X86_64
+    }
+
+    /// Whether the repositories have CPU microcode updates
+    pub fn has_microcode(self) -> bool {
+        self == Self::X86_64
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X86_64 => write!(f, "x86_64"),
+            Self::Aarch64 => write!(f, "aarch64"),
+        }
+    }
+}
+
+/// Check that the kernel and bootloader exist for the architecture
+///
+/// `uefi_firmware` is whether the machine booted in UEFI mode, when known;
+/// Auto installs for that mode. Without UEFI, an aarch64 board boots with
+/// U-Boot since GRUB has no BIOS target there.
+pub fn validate(
+    arch: Arch,
+    kernel: Kernel,
+    bootloader: Bootloader,
+    boot_mode: BootMode,
+    uefi_firmware: Option<bool>,
+) -> Result<(), String> {
+    if arch.kernel_package(kernel).is_none() {
+        return Err(format!(
+            "{} is not built for {}: Arch Linux ARM only has linux (linux-aarch64)",
+            kernel, arch
+        ));
+    }
+    if !arch.bootloaders().contains(&bootloader) {
+        return Err(format!(
+            "{} only starts aarch64 boards: use GRUB or systemd-boot on {}",
+            bootloader, arch
+        ));
+    }
+    let bios = match boot_mode {
+        BootMode::Bios => true,
+        BootMode::Uefi => false,
+        BootMode::Auto => uefi_firmware == Some(false),
+    };
+    if arch == Arch::Aarch64 && bios && bootloader == Bootloader::Grub {
+        return Err(
+            "GRUB needs UEFI firmware on aarch64: boards without it boot with u-boot".to_string(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_machine() {
+        assert_eq!(Arch::from_machine("x86_64\n"), Some(Arch::X86_64));
+        assert_eq!(Arch::from_machine("aarch64"), Some(Arch::Aarch64));
+        assert_eq!(Arch::from_machine("arm64"), Some(Arch::Aarch64));
+        assert_eq!(Arch::from_machine("armv7l"), None);
+    }
+
+    #[test]
+    fn test_packages_follow_the_architecture() {
+        assert_eq!(
+            Arch::X86_64.kernel_package(Kernel::LinuxZen),
+            Some("linux-zen")
+        );
+        assert_eq!(
+            Arch::Aarch64.kernel_package(Kernel::Linux),
+            Some("linux-aarch64")
+        );
+        assert_eq!(Arch::Aarch64.kernels(), vec![Kernel::Linux]);
+        assert_eq!(Arch::X86_64.kernels().len(), Kernel::iter().count());
+
+        assert!(!Arch::X86_64.bootloaders().contains(&Bootloader::UBoot));
+        assert!(Arch::Aarch64.bootloaders().contains(&Bootloader::UBoot));
+        assert_eq!(Arch::Aarch64.default_bootloader(false), Bootloader::UBoot);
+        assert_eq!(Arch::Aarch64.default_bootloader(true), Bootloader::Grub);
+        assert_eq!(Arch::X86_64.default_bootloader(false), Bootloader::Grub);
+        assert_eq!(Arch::Aarch64.grub_efi_target(), "arm64-efi");
+        assert_eq!(Arch::Aarch64.efi_suffix(), "aa64");
+        assert!(!Arch::Aarch64.has_multilib());
+    }
+
+    #[test]
+    fn test_validate_against_the_architecture() {
+        use Bootloader::*;
+
+        assert!(validate(Arch::X86_64, Kernel::LinuxLts, Grub, BootMode::Bios, None).is_ok());
+        assert!(validate(
+            Arch::Aarch64,
+            Kernel::Linux,
+            UBoot,
+            BootMode::Auto,
+            Some(false)
+        )
+        .is_ok());
+        assert!(validate(Arch::Aarch64, Kernel::Linux, Grub, BootMode::Uefi, None).is_ok());
+
+        let error =
+            validate(Arch::Aarch64, Kernel::LinuxZen, UBoot, BootMode::Auto, None).unwrap_err();
+        assert!(error.contains("linux-aarch64"), "{}", error);
+        let error = validate(Arch::X86_64, Kernel::Linux, UBoot, BootMode::Bios, None).unwrap_err();
+        assert!(error.contains("u-boot only starts aarch64"), "{}", error);
+        assert!(validate(
+            Arch::Aarch64,
+            Kernel::Linux,
+            Grub,
+            BootMode::Auto,
+            Some(false)
+        )
+        .is_err());
+    }
+}
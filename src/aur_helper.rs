@@ -0,0 +1,535 @@
+//! AUR helper bootstrap
+//!
+//! paru and yay are only in the AUR, so they are built from their PKGBUILD
+//! on the installed system before anything else can use them. The
+//! `aur-helper` phase does this from outside the target, through
+//! arch-chroot:
+//!
+//! 1. install base-devel and git
+//! 2. create a temporary system user, [`BUILD_USER`], that may only run
+//!    pacman through sudo, for makepkg to install the build dependencies
+//! 3. clone the AUR repository into the build user's home, retrying a
+//!    failed clone
+//! 4. build with makepkg as that user and install the package as root
+//! 5. run `<helper> --version` to check it works
+//!
+//! The build user, its sudo rule and the build directory are removed
+//! whether the build worked or not, so a failed build leaves nothing behind
+//! but the dependencies pacman installed. A helper that is already installed
+//! and runs is left alone. The helper to build is read from `AUR_HELPER`,
+//! like the other installation settings.
+
+use crate::error::ArchInstallError;
+use crate::phases::{InstallPhase, PhaseContext};
+use crate::types::AurHelper;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Name the phase is registered under
+pub const PHASE_NAME: &str = "aur-helper";
+
+/// Temporary user the helper is built as; makepkg refuses to run as root
+pub const BUILD_USER: &str = "aurbuild";
+
+/// Home of the build user, on disk so a tmpfs /tmp does not limit the build
+const BUILD_HOME: &str = "/var/tmp/aur-build";
+
+/// sudo rule letting the build user install build dependencies
+const SUDOERS_FILE: &str = "etc/sudoers.d/90-aur-build";
+
+const AUR_URL: &str = "https://aur.archlinux.org";
+
+/// Clones tried before giving up, for the AUR's occasional timeouts
+const CLONE_ATTEMPTS: u32 = 3;
+
+/// Command run in the target system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChrootCommand {
+    /// User to run as, root when `None`
+    pub user: Option<&'static str>,
+    pub args: Vec<String>,
+}
+
+impl ChrootCommand {
+    fn root(args: &[&str]) -> Self {
+        Self {
+            user: None,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn build_user(args: &[&str]) -> Self {
+        Self {
+            user: Some(BUILD_USER),
+            ..Self::root(args)
+        }
+    }
+
+    /// Shell-like rendering for the log
+    pub fn display(&self, target_root: &Path) -> String {
+        let user = self
+            .user
+            .map(|user| format!(" -u {}", user))
+            .unwrap_or_default();
+        format!(
+            "arch-chroot{} {} {}",
+            user,
+            target_root.display(),
+            self.args.join(" ")
+        )
+    }
+}
+
+/// Runs a command in the target and returns its stdout
+pub type Runner = Box<dyn FnMut(&Path, &ChrootCommand) -> Result<String, ArchInstallError>>;
+
+/// Package providing `helper` in the AUR, `None` for no helper
+pub fn package(helper: AurHelper) -> Option<&'static str> {
+    match helper {
+        AurHelper::Paru => Some("paru"),
+        AurHelper::Yay => Some("yay"),
+        AurHelper::None => None,
+    }
+}
+
+/// The helper named in `AUR_HELPER`; unset or unknown means none
+pub fn helper_from_env() -> AurHelper {
+    std::env::var("AUR_HELPER")
+        .ok()
+        .and_then(|value| value.trim().to_lowercase().parse().ok())
+        .unwrap_or(AurHelper::None)
+}
+
+/// Packages makepkg built, without the debug packages
+///
+/// `listing` is the output of `makepkg --packagelist`.
+pub fn built_packages(listing: &str, package: &str) -> Vec<String> {
+    let debug = format!("{}-debug-", package);
+    listing
+        .lines()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .filter(|path| {
+            Path::new(path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| !name.starts_with(&debug))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Built-in phase building and installing the AUR helper
+pub struct AurHelperPhase {
+    helper: AurHelper,
+    runner: Runner,
+    retry_delay: Duration,
+    /// What run did, for the cleanup and rollback
+    user_created: bool,
+    sudoers: Option<PathBuf>,
+    installed: bool,
+}
+
+impl AurHelperPhase {
+    /// Phase for `helper`, running its commands through arch-chroot
+    pub fn new(helper: AurHelper) -> Self {
+        Self::with_runner(helper, Box::new(arch_chroot))
+    }
+
+    /// Phase running its commands through `runner`
+    pub fn with_runner(helper: AurHelper, runner: Runner) -> Self {
+        Self {
+            helper,
+            runner,
+            retry_delay: Duration::from_secs(5),
+            user_created: false,
+            sudoers: None,
+            installed: false,
+        }
+    }
+
+    fn exec(
+        &mut self,
+        ctx: &mut PhaseContext,
+        command: ChrootCommand,
+    ) -> Result<String, ArchInstallError> {
+        ctx.log(format!("$ {}", command.display(&ctx.target_root)));
+        let stdout = (self.runner)(&ctx.target_root, &command)?;
+        for line in stdout.lines() {
+            ctx.log(format!("  {}", line));
+        }
+        Ok(stdout)
+    }
+
+    /// Whether the helper is installed and answers `--version`
+    fn works(&mut self, ctx: &mut PhaseContext, package: &str) -> Result<String, ArchInstallError> {
+        let version = self.exec(ctx, ChrootCommand::root(&[package, "--version"]))?;
+        version
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                ArchInstallError::system(format!("{} --version printed nothing", package))
+            })
+    }
+
+    fn build(&mut self, ctx: &mut PhaseContext, package: &str) -> Result<(), ArchInstallError> {
+        self.exec(
+            ctx,
+            ChrootCommand::root(&[
+                "pacman",
+                "-S",
+                "--needed",
+                "--noconfirm",
+                "base-devel",
+                "git",
+            ]),
+        )?;
+
+        // A build user left by an interrupted run is replaced
+        if has_user(&ctx.target_root, BUILD_USER) {
+            ctx.log("Removing the build user left from an earlier run");
+            self.user_created = true;
+            self.cleanup(ctx);
+        }
+        self.exec(
+            ctx,
+            ChrootCommand::root(&[
+                "useradd",
+                "--system",
+                "--create-home",
+                "--home-dir",
+                BUILD_HOME,
+                "--shell",
+                "/usr/bin/nologin",
+                BUILD_USER,
+            ]),
+        )?;
+        self.user_created = true;
+
+        let sudoers = ctx.target_root.join(SUDOERS_FILE);
+        write_sudoers(&sudoers)?;
+        self.sudoers = Some(sudoers);
+
+        let source = format!("{}/{}", BUILD_HOME, package);
+        let url = format!("{}/{}.git", AUR_URL, package);
+        let clone = ChrootCommand::build_user(&["git", "clone", "--depth", "1", &url, &source]);
+        for attempt in 1..=CLONE_ATTEMPTS {
+            match self.exec(ctx, clone.clone()) {
+                Ok(_) => break,
+                Err(e) if attempt < CLONE_ATTEMPTS => {
+                    ctx.log(format!(
+                        "Cloning {} failed ({}), retrying ({}/{})",
+                        url,
+                        e,
+                        attempt + 1,
+                        CLONE_ATTEMPTS
+                    ));
+                    self.exec(ctx, ChrootCommand::build_user(&["rm", "-rf", &source]))?;
+                    std::thread::sleep(self.retry_delay);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let in_source = |command: &str| {
+            ChrootCommand::build_user(&["sh", "-c", &format!("cd {} && {}", source, command)])
+        };
+        self.exec(ctx, in_source("makepkg --syncdeps --noconfirm"))?;
+        let listing = self.exec(ctx, in_source("makepkg --packagelist"))?;
+        let packages = built_packages(&listing, package);
+        if packages.is_empty() {
+            return Err(ArchInstallError::system(format!(
+                "makepkg built no {} package",
+                package
+            )));
+        }
+
+        let mut install = vec!["pacman", "-U", "--noconfirm"];
+        install.extend(packages.iter().map(String::as_str));
+        self.exec(ctx, ChrootCommand::root(&install))?;
+        self.installed = true;
+        Ok(())
+    }
+
+    /// Remove the build user, its sudo rule and the build directory
+    ///
+    /// Failures are logged: they leave clutter, not a broken system.
+    fn cleanup(&mut self, ctx: &mut PhaseContext) {
+        if let Some(sudoers) = self.sudoers.take() {
+            if let Err(e) = std::fs::remove_file(&sudoers) {
+                ctx.log(format!("Could not remove {}: {}", sudoers.display(), e));
+            }
+        }
+        if std::mem::take(&mut self.user_created) {
+            if let Err(e) = self.exec(
+                ctx,
+                ChrootCommand::root(&["userdel", "--remove", BUILD_USER]),
+            ) {
+                ctx.log(format!("Could not remove the build user: {}", e));
+            }
+            if let Err(e) = self.exec(ctx, ChrootCommand::root(&["rm", "-rf", BUILD_HOME])) {
+                ctx.log(format!("Could not remove {}: {}", BUILD_HOME, e));
+            }
+        }
+    }
+}
+
+impl InstallPhase for AurHelperPhase {
+    fn name(&self) -> &str {
+        PHASE_NAME
+    }
+
+    fn run(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        let Some(package) = package(self.helper) else {
+            ctx.log("No AUR helper selected");
+            return Ok(());
+        };
+        if let Ok(version) = self.works(ctx, package) {
+            ctx.log(format!("{} is already installed: {}", package, version));
+            return Ok(());
+        }
+
+        ctx.log(format!(
+            "Building {} from the AUR as {}",
+            package, BUILD_USER
+        ));
+        let built = self.build(ctx, package);
+        self.cleanup(ctx);
+        built?;
+
+        let version = self.works(ctx, package).map_err(|e| {
+            ArchInstallError::system(format!("{} was installed but does not run: {}", package, e))
+        })?;
+        ctx.log(format!("Installed {}", version));
+        Ok(())
+    }
+
+    fn rollback(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        self.cleanup(ctx);
+        if !std::mem::take(&mut self.installed) {
+            return Ok(());
+        }
+        let Some(package) = package(self.helper) else {
+            return Ok(());
+        };
+        self.exec(
+            ctx,
+            ChrootCommand::root(&["pacman", "-Rns", "--noconfirm", package]),
+        )
+        .map(drop)
+    }
+}
+
+/// Whether `/etc/passwd` in `target_root` has `user`
+fn has_user(target_root: &Path, user: &str) -> bool {
+    std::fs::read_to_string(target_root.join("etc/passwd")).is_ok_and(|passwd| {
+        passwd
+            .lines()
+            .any(|line| line.split(':').next() == Some(user))
+    })
+}
+
+/// Let the build user run pacman, and nothing else, without a password
+fn write_sudoers(path: &Path) -> Result<(), ArchInstallError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(
+        path,
+        format!("{} ALL=(root) NOPASSWD: /usr/bin/pacman\n", BUILD_USER),
+    )?;
+    // sudo ignores rules other users may write to
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o440))?;
+    Ok(())
+}
+
+/// Run `command` with arch-chroot, logging it instead in simulation
+fn arch_chroot(target_root: &Path, command: &ChrootCommand) -> Result<String, ArchInstallError> {
+    if crate::simulate::is_enabled() {
+        return Ok(crate::simulate::skipped(&command.display(target_root)));
+    }
+    let mut chroot = Command::new("arch-chroot");
+    if let Some(user) = command.user {
+        chroot.arg("-u").arg(user);
+    }
+    let output = chroot
+        .arg(target_root)
+        .args(&command.args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("arch-chroot", e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(
+            command.args.join(" "),
+            &output,
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Runner recording the commands, failing those starting with `fail`
+    /// and answering `--version` once the package was installed
+    fn recorder(journal: &Rc<RefCell<Vec<String>>>, fail: &'static [&'static str]) -> Runner {
+        let journal = Rc::clone(journal);
+        let installed = Rc::new(RefCell::new(false));
+        Box::new(move |_root, command| {
+            let line = command.args.join(" ");
+            journal.borrow_mut().push(match command.user {
+                Some(user) => format!("{}: {}", user, line),
+                None => line.clone(),
+            });
+            if fail.iter().any(|prefix| line.starts_with(prefix)) {
+                return Err(ArchInstallError::system(format!("{} failed", line)));
+            }
+            if line.starts_with("pacman -U") {
+                *installed.borrow_mut() = true;
+            }
+            Ok(match line.as_str() {
+                "paru --version" if *installed.borrow() => "paru v2.0.4 - libalpm v15.0.0\n".into(),
+                "paru --version" => return Err(ArchInstallError::system("not installed")),
+                line if line.ends_with("makepkg --packagelist") => {
+                    "/var/tmp/aur-build/paru/paru-2.0.4-1-x86_64.pkg.tar.zst\n\
+                     /var/tmp/aur-build/paru/paru-debug-2.0.4-1-x86_64.pkg.tar.zst\n"
+                        .into()
+                }
+                _ => String::new(),
+            })
+        })
+    }
+
+    fn phase(journal: &Rc<RefCell<Vec<String>>>, fail: &'static [&'static str]) -> AurHelperPhase {
+        let mut phase = AurHelperPhase::with_runner(AurHelper::Paru, recorder(journal, fail));
+        phase.retry_delay = Duration::ZERO;
+        phase
+    }
+
+    #[test]
+    fn test_built_packages_skip_debug_packages() {
+        let listing = "/build/yay/yay-12.4.2-1-x86_64.pkg.tar.zst\n\
+                       /build/yay/yay-debug-12.4.2-1-x86_64.pkg.tar.zst\n\n";
+        assert_eq!(
+            built_packages(listing, "yay"),
+            vec!["/build/yay/yay-12.4.2-1-x86_64.pkg.tar.zst"]
+        );
+        assert!(built_packages("", "yay").is_empty());
+    }
+
+    #[test]
+    fn test_builds_as_the_build_user_and_cleans_up() {
+        let target = tempfile::tempdir().unwrap();
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut phase = phase(&journal, &[]);
+        let mut lines = Vec::new();
+        let mut log = |line: String| lines.push(line);
+        let mut ctx = PhaseContext::new(target.path(), &mut log);
+        phase.run(&mut ctx).unwrap();
+
+        assert_eq!(
+            *journal.borrow(),
+            vec![
+                "paru --version",
+                "pacman -S --needed --noconfirm base-devel git",
+                "useradd --system --create-home --home-dir /var/tmp/aur-build --shell /usr/bin/nologin aurbuild",
+                "aurbuild: git clone --depth 1 https://aur.archlinux.org/paru.git /var/tmp/aur-build/paru",
+                "aurbuild: sh -c cd /var/tmp/aur-build/paru && makepkg --syncdeps --noconfirm",
+                "aurbuild: sh -c cd /var/tmp/aur-build/paru && makepkg --packagelist",
+                "pacman -U --noconfirm /var/tmp/aur-build/paru/paru-2.0.4-1-x86_64.pkg.tar.zst",
+                "userdel --remove aurbuild",
+                "rm -rf /var/tmp/aur-build",
+                "paru --version",
+            ]
+        );
+        assert!(!target.path().join(SUDOERS_FILE).exists());
+        assert!(lines.contains(&"Installed paru v2.0.4 - libalpm v15.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_failed_build_removes_the_build_user() {
+        let target = tempfile::tempdir().unwrap();
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut phase = phase(&journal, &["sh -c"]);
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new(target.path(), &mut log);
+
+        assert!(phase.run(&mut ctx).is_err());
+        let journal = journal.borrow();
+        assert!(journal.contains(&"userdel --remove aurbuild".to_string()));
+        assert!(!journal.iter().any(|line| line.starts_with("pacman -U")));
+        assert!(!target.path().join(SUDOERS_FILE).exists());
+
+        // Nothing was installed, so there is nothing to roll back
+        assert!(phase.rollback(&mut ctx).is_ok());
+    }
+
+    #[test]
+    fn test_clone_is_retried() {
+        let target = tempfile::tempdir().unwrap();
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut phase = phase(&journal, &["git clone"]);
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new(target.path(), &mut log);
+
+        assert!(phase.run(&mut ctx).is_err());
+        let clones = journal
+            .borrow()
+            .iter()
+            .filter(|line| line.contains("git clone"))
+            .count();
+        assert_eq!(clones, CLONE_ATTEMPTS as usize);
+    }
+
+    #[test]
+    fn test_rollback_removes_the_installed_helper() {
+        let target = tempfile::tempdir().unwrap();
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut phase = phase(&journal, &[]);
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new(target.path(), &mut log);
+        phase.run(&mut ctx).unwrap();
+
+        phase.rollback(&mut ctx).unwrap();
+        assert_eq!(
+            journal.borrow().last().unwrap(),
+            "pacman -Rns --noconfirm paru"
+        );
+    }
+
+    #[test]
+    fn test_nothing_to_do() {
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new("/mnt", &mut log);
+
+        let mut none = AurHelperPhase::with_runner(AurHelper::None, recorder(&journal, &[]));
+        none.run(&mut ctx).unwrap();
+        assert!(journal.borrow().is_empty());
+
+        // An installed helper that runs is kept
+        let mut installed = phase(&journal, &[]);
+        installed.installed = false;
+        let runner = recorder(&journal, &[]);
+        installed.runner = Box::new({
+            let mut runner = runner;
+            move |root, command| {
+                if command.args == ["paru", "--version"] {
+                    Ok("paru v2.0.4\n".to_string())
+                } else {
+                    runner(root, command)
+                }
+            }
+        });
+        installed.run(&mut ctx).unwrap();
+        assert_eq!(*journal.borrow(), Vec::<String>::new());
+    }
+}
@@ -1,5 +1,11 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::tools::wipe::WipeMethod;
+use crate::types::{
+    BtrfsProfile, ErrorPolicy, FormatFilesystem, Ipv6Mode, NetworkBackend, OutputFormat,
+};
 
 /// ArchInstall TUI - A friendly Arch Linux installer
 #[derive(Parser)]
@@ -9,6 +15,98 @@ use std::path::PathBuf;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Draw the interface with plain ASCII (serial/IPMI and Linux consoles);
+    /// detected from $TERM and the locale when not given
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Tune the TUI for a serial or IPMI SOL console: normal screen, fewer
+    /// redraws, ASCII, and control-key alternatives for arrows and Esc
+    #[arg(long, global = true)]
+    pub serial: bool,
+
+    /// Record the key presses of the TUI session to this file (for bug reports)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Replay key presses recorded with --record, then continue interactively
+    #[arg(long, global = true, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Proxy for downloads, e.g. http://proxy.example.com:3128 (default:
+    /// $http_proxy); a proxy in the configuration file takes precedence
+    #[arg(long, global = true, value_name = "URL")]
+    pub http_proxy: Option<String>,
+
+    /// Proxy for HTTPS downloads (default: $https_proxy, then the HTTP proxy)
+    #[arg(long, global = true, value_name = "URL")]
+    pub https_proxy: Option<String>,
+
+    /// Comma-separated hosts reached without the proxy (default: $no_proxy)
+    #[arg(long, global = true, value_name = "HOSTS")]
+    pub no_proxy: Option<String>,
+
+    /// Directory of packages, or an offline repository made with repo-add,
+    /// that pacman prefers over the mirrors (default: $PKG_CACHE); a package
+    /// cache in the configuration file takes precedence
+    #[arg(long, global = true, value_name = "DIR")]
+    pub pkg_cache: Option<PathBuf>,
+
+    /// Try the installer without touching this machine: fake disks, tools
+    /// that log their commands instead of running them, and a timed
+    /// installation that writes nothing
+    #[arg(long, global = true)]
+    pub simulate: bool,
+
+    /// Add the outcome of each installation (strategy, phase timings, the
+    /// phase that failed; nothing identifying) to a local statistics file
+    /// for bug reports; see the stats command. Nothing is sent anywhere
+    #[arg(long, global = true)]
+    pub install_stats: bool,
+
+    /// Do not ask GitHub for a newer installer release when the TUI starts
+    #[arg(long, global = true)]
+    pub no_update_check: bool,
+
+    /// More log output: -v for debug, -vv for trace (also makes the
+    /// installer scripts print their debug lines)
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        conflicts_with = "log_level"
+    )]
+    pub verbose: u8,
+
+    /// Log level: off, error, warn, info, debug or trace (default: $RUST_LOG,
+    /// then info)
+    #[arg(long, global = true, value_name = "LEVEL")]
+    pub log_level: Option<log::LevelFilter>,
+
+    /// Write the log to this file instead of standard error; it is rotated by
+    /// size, keeping FILE.1 to FILE.3
+    #[arg(long, global = true, value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Size at which the log file is rotated, in MiB [default: 8]
+    #[arg(
+        long,
+        global = true,
+        value_name = "MIB",
+        requires = "log_file",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub log_max_size: Option<u64>,
+
+    /// Log one JSON object per line instead of text
+    #[arg(long, global = true)]
+    pub log_json: bool,
+
+    /// Open this tools menu entry on start (used when restarting as root)
+    #[arg(long, global = true, hide = true, value_name = "TOOL")]
+    pub open_tool: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -22,17 +120,199 @@ pub enum Commands {
         /// Save current configuration to file and exit (after TUI configuration)
         #[arg(long)]
         save_config: Option<PathBuf>,
+
+        /// Take the options --config sets and ask for the rest in the TUI
+        #[arg(
+            long,
+            requires = "config",
+            conflicts_with_all = ["save_config", "unattended", "output"]
+        )]
+        interactive_missing: bool,
+
+        /// Run without any prompts: pre-answer confirmations and fail instead of asking
+        #[arg(short = 'y', long, visible_alias = "yes", requires = "config")]
+        unattended: bool,
+
+        /// What to do when an installation phase fails (abort, retry, continue)
+        #[arg(long, default_value = "abort", requires = "unattended")]
+        on_error: ErrorPolicy,
+
+        /// Number of retries per failed phase when --on-error=retry
+        #[arg(long, default_value = "3", requires = "unattended")]
+        retries: u8,
+
+        /// Also copy the installation report (JSON + Markdown) to this directory
+        #[arg(long, value_name = "DIR", conflicts_with = "save_config")]
+        report: Option<PathBuf>,
+
+        /// How to print progress: decorated text, or one JSON event per line
+        #[arg(long, default_value = "text", requires = "config")]
+        output: OutputFormat,
+
+        /// Seconds a command may run without output before the TUI offers to kill it
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value = "600",
+            conflicts_with = "config"
+        )]
+        command_timeout: u64,
+
+        /// Timeout for one phase or command, e.g. reflector=60 (repeatable)
+        #[arg(
+            long = "timeout",
+            value_name = "NAME=SECONDS",
+            value_parser = crate::watchdog::parse_override,
+            conflicts_with = "config"
+        )]
+        timeouts: Vec<(String, Duration)>,
+    },
+    /// Install onto a remote machine booted into the Arch ISO over SSH
+    Remote {
+        /// Target machine as [user@]host (user defaults to root)
+        target: String,
+
+        /// Path to configuration file to install with
+        #[arg(short, long)]
+        config: PathBuf,
+
+        /// SSH port on the target
+        #[arg(short, long, default_value = "22")]
+        port: u16,
+
+        /// SSH private key to authenticate with
+        #[arg(short, long)]
+        identity: Option<PathBuf>,
+
+        /// What to do when an installation phase fails (abort, retry, continue)
+        #[arg(long, default_value = "abort")]
+        on_error: ErrorPolicy,
+
+        /// Number of retries per failed phase when --on-error=retry
+        #[arg(long, default_value = "3")]
+        retries: u8,
     },
     /// Validate a configuration file
     Validate {
         /// Path to configuration file to validate
         config: PathBuf,
     },
+    /// Write a configuration file replicating an installed Arch system
+    Clone {
+        /// Root of the system to clone (/ for the running system)
+        #[arg(short, long, default_value = "/")]
+        source: PathBuf,
+
+        /// Configuration file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
     /// Arch Linux Tools - System administration and repair
     Tools {
         #[command(subcommand)]
         tool: ToolCommands,
     },
+    /// Check GitHub for a newer installer release and install it on the live ISO
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Show the installation statistics recorded with --install-stats
+    Stats {
+        /// Print the records as JSON, one per line, to attach to a bug report
+        #[arg(long, conflicts_with = "clear")]
+        json: bool,
+        /// Delete the statistics file
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Custom installation phases (built-in and plugins)
+    Phases {
+        #[command(subcommand)]
+        action: PhaseCommands,
+    },
+    /// Convert configuration files to and from the official archinstall
+    Archinstall {
+        #[command(subcommand)]
+        action: ArchinstallCommands,
+    },
+    /// Write the files other systems need to install with a configuration
+    Generate {
+        #[command(subcommand)]
+        artifact: GenerateCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GenerateCommands {
+    /// Kernel command line and iPXE script for zero-touch network installs
+    Pxe {
+        /// Configuration file to install with
+        config: PathBuf,
+
+        /// URL the machines download the configuration from
+        #[arg(long, value_name = "URL")]
+        config_url: String,
+
+        /// URL of the extracted Arch ISO, the directory holding arch/
+        #[arg(long, value_name = "URL", default_value = crate::pxe::DEFAULT_ISO_URL)]
+        iso_url: String,
+
+        /// Directory to write the configuration, cmdline.txt and boot.ipxe to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ArchinstallCommands {
+    /// Write archinstall's user_configuration.json and user_credentials.json
+    Export {
+        /// Configuration file to convert
+        config: PathBuf,
+
+        /// Directory to write archinstall's files to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Convert archinstall's JSON files into a configuration file
+    Import {
+        /// archinstall's user_configuration.json
+        config: PathBuf,
+
+        /// archinstall's user_credentials.json
+        #[arg(long)]
+        creds: Option<PathBuf>,
+
+        /// Configuration file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PhaseCommands {
+    /// List the phases that can be named in custom_phases
+    List {
+        /// Plugin directory (default: $ARCHINSTALL_PHASES_DIR or /etc/archinstall/phases)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Run phases against an installed system, rolling back on failure
+    Run {
+        /// Phases to run, in order
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Root of the installed system
+        #[arg(long, default_value = "/mnt")]
+        target: PathBuf,
+
+        /// Plugin directory (default: $ARCHINSTALL_PHASES_DIR or /etc/archinstall/phases)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -57,6 +337,20 @@ pub enum ToolCommands {
         #[command(subcommand)]
         network_tool: NetworkToolCommands,
     },
+    /// Btrfs snapshots of an installed system's root subvolume
+    Snapshot {
+        #[command(subcommand)]
+        snapshot_tool: SnapshotToolCommands,
+    },
+    /// Unmount and close everything a failed installation left behind
+    Cleanup {
+        /// Only list what would be released
+        #[arg(long)]
+        dry_run: bool,
+        /// Disk the installation used, when nothing of it is mounted at /mnt
+        #[arg(long)]
+        disk: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -66,21 +360,31 @@ pub enum DiskToolCommands {
         /// Partition device (e.g., /dev/sda1)
         #[arg(short, long)]
         device: String,
-        /// Filesystem type
+        /// Filesystem type (ext4, xfs, btrfs, f2fs, fat32, exfat, ntfs)
         #[arg(short, long)]
-        filesystem: String,
+        filesystem: FormatFilesystem,
         /// Partition label (optional)
         #[arg(short, long)]
         label: Option<String>,
+        /// Percentage of blocks reserved for root (ext4 only)
+        #[arg(long)]
+        ext4_reserved: Option<u8>,
+        /// Metadata profile (btrfs only: single, dup)
+        #[arg(long)]
+        btrfs_metadata: Option<BtrfsProfile>,
+        /// Enable transparent compression (f2fs only)
+        #[arg(long)]
+        f2fs_compression: bool,
     },
     /// Securely wipe a disk
     Wipe {
         /// Disk device to wipe (e.g., /dev/sda)
         #[arg(short, long)]
         device: String,
-        /// Wipe method (zero, random, secure)
-        #[arg(short, long, default_value = "zero")]
-        method: String,
+        /// Wipe method (quick, zero, discard, ata-secure-erase, nvme-format,
+        /// nvme-sanitize); the recommended one for the disk when omitted
+        #[arg(short, long)]
+        method: Option<WipeMethod>,
         /// Confirm destructive operation
         #[arg(short, long)]
         confirm: bool,
@@ -90,6 +394,9 @@ pub enum DiskToolCommands {
         /// Disk device to check (e.g., /dev/sda)
         #[arg(short, long)]
         device: String,
+        /// Print the parsed SMART report as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Mount or unmount partitions
     Mount {
@@ -112,6 +419,18 @@ pub enum DiskToolCommands {
         #[arg(short, long)]
         device: String,
     },
+    /// Grow or shrink a partition and its filesystem
+    Resize {
+        /// Partition device (e.g., /dev/sda2)
+        #[arg(short, long)]
+        device: String,
+        /// New size: absolute (40G), relative (+5G, -5G) or "max"
+        #[arg(short, long, allow_hyphen_values = true)]
+        size: String,
+        /// Apply the resize (without this only the preview is shown)
+        #[arg(short, long)]
+        confirm: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -151,15 +470,21 @@ pub enum SystemToolCommands {
         /// Show detailed information
         #[arg(short, long)]
         detailed: bool,
+        /// Print the information as JSON (sizes in bytes, uptime in seconds)
+        #[arg(long)]
+        json: bool,
     },
     /// Manage systemd services
     Services {
-        /// Action to perform (enable, disable, start, stop, status, list)
+        /// Action to perform (list, status, enable, disable, start, stop, mask, unmask)
         #[arg(short, long)]
         action: String,
-        /// Service name
+        /// Unit name; for list, a filter on names, descriptions and states
         #[arg(short, long)]
         service: Option<String>,
+        /// Manage the installed system mounted here instead of the running one
+        #[arg(short, long)]
+        root: Option<String>,
     },
 }
 
@@ -228,12 +553,30 @@ pub enum NetworkToolCommands {
         /// Network interface name
         #[arg(short, long)]
         interface: String,
-        /// IP address (optional)
-        #[arg(short, long)]
+        /// Static IPv4 address, e.g. 192.168.1.10/24 (DHCP without one)
+        #[arg(long)]
         ip: Option<String>,
-        /// Gateway (optional)
+        /// IPv4 netmask when the address has no prefix, e.g. 255.255.255.0
+        #[arg(long, requires = "ip")]
+        netmask: Option<String>,
+        /// IPv4 gateway (optional)
         #[arg(short, long)]
         gateway: Option<String>,
+        /// Static IPv6 address, e.g. 2001:db8::10/64
+        #[arg(long)]
+        ip6: Option<String>,
+        /// IPv6 gateway (optional)
+        #[arg(long)]
+        gateway6: Option<String>,
+        /// IPv6 mode (ra, dhcpv6, static, off); static with --ip6, else ra
+        #[arg(long)]
+        ipv6_mode: Option<Ipv6Mode>,
+        /// DNS servers of either family, comma-separated
+        #[arg(long, value_delimiter = ',')]
+        dns: Vec<String>,
+        /// Also write a persistent configuration (networkmanager, networkd)
+        #[arg(long)]
+        backend: Option<NetworkBackend>,
     },
     /// Test network connectivity
     Test {
@@ -273,6 +616,53 @@ pub enum NetworkToolCommands {
         /// Action to perform (basic, detailed, troubleshoot)
         #[arg(short, long)]
         action: String,
+        /// Print the interfaces, routes and check results as JSON; exits 1
+        /// when a check fails
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotToolCommands {
+    /// List the snapshots of the root subvolume
+    List {
+        /// Btrfs root device (e.g., /dev/sda2 or /dev/mapper/cryptroot)
+        #[arg(short, long)]
+        device: String,
+    },
+    /// Take a read-only snapshot of the root subvolume
+    Create {
+        /// Btrfs root device (e.g., /dev/sda2 or /dev/mapper/cryptroot)
+        #[arg(short, long)]
+        device: String,
+        /// Description shown in the snapshot list
+        #[arg(short = 'D', long, default_value = "Arch Linux Toolkit")]
+        description: String,
+    },
+    /// Delete a snapshot
+    Delete {
+        /// Btrfs root device (e.g., /dev/sda2 or /dev/mapper/cryptroot)
+        #[arg(short, long)]
+        device: String,
+        /// Snapshot number, as listed
+        #[arg(short, long)]
+        snapshot: u32,
+        /// Delete it (without this only the snapshot is shown)
+        #[arg(short, long)]
+        confirm: bool,
+    },
+    /// Replace the root subvolume with a writable copy of a snapshot
+    Rollback {
+        /// Btrfs root device (e.g., /dev/sda2 or /dev/mapper/cryptroot)
+        #[arg(short, long)]
+        device: String,
+        /// Snapshot number, as listed
+        #[arg(short, long)]
+        snapshot: u32,
+        /// Roll back (without this only the snapshot is shown)
+        #[arg(short, long)]
+        confirm: bool,
     },
 }
 
@@ -296,6 +686,85 @@ mod tests {
         assert!(cli.command.is_none());
     }
 
+    #[test]
+    fn test_cli_ascii_is_global() {
+        let cli = Cli::try_parse_from(["archinstall-tui", "--ascii"]).unwrap();
+        assert!(cli.ascii);
+        let cli = Cli::try_parse_from(["archinstall-tui", "install", "--ascii"]).unwrap();
+        assert!(cli.ascii);
+        assert!(!Cli::try_parse_from(["archinstall-tui"]).unwrap().ascii);
+    }
+
+    #[test]
+    fn test_cli_logging_flags() {
+        let cli = Cli::try_parse_from(["archinstall-tui", "install", "-vv"]).unwrap();
+        assert_eq!(cli.verbose, 2);
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "-v",
+            "--log-file",
+            "/tmp/archinstall.log",
+            "--log-max-size",
+            "2",
+            "--log-json",
+        ])
+        .unwrap();
+        assert_eq!(cli.verbose, 1);
+        assert_eq!(cli.log_max_size, Some(2));
+        assert!(cli.log_json);
+
+        let cli = Cli::try_parse_from(["archinstall-tui", "install", "--log-level", "warn"]);
+        assert_eq!(cli.unwrap().log_level, Some(log::LevelFilter::Warn));
+        assert!(Cli::try_parse_from(["archinstall-tui", "-v", "--log-level", "warn"]).is_err());
+        assert!(Cli::try_parse_from(["archinstall-tui", "--log-max-size", "2"]).is_err());
+        assert!(Cli::try_parse_from(["archinstall-tui", "--log-level", "loud"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_proxy_flags() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--http-proxy",
+            "http://proxy:3128",
+            "--no-proxy",
+            "localhost,.lan",
+        ])
+        .unwrap();
+        assert_eq!(cli.http_proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(cli.https_proxy, None);
+        assert_eq!(cli.no_proxy.as_deref(), Some("localhost,.lan"));
+    }
+
+    #[test]
+    fn test_cli_pkg_cache_flag() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "--pkg-cache",
+            "/run/media/usb/packages",
+            "install",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.pkg_cache,
+            Some(PathBuf::from("/run/media/usb/packages"))
+        );
+    }
+
+    #[test]
+    fn test_cli_serial_is_global() {
+        let cli = Cli::try_parse_from(["archinstall-tui", "install", "--serial"]).unwrap();
+        assert!(cli.serial);
+        assert!(!Cli::try_parse_from(["archinstall-tui"]).unwrap().serial);
+    }
+
+    #[test]
+    fn test_cli_simulate_is_global() {
+        let cli = Cli::try_parse_from(["archinstall-tui", "--simulate", "install"]).unwrap();
+        assert!(cli.simulate);
+        assert!(!Cli::try_parse_from(["archinstall-tui"]).unwrap().simulate);
+    }
+
     #[test]
     fn test_cli_install_with_config() {
         let result = Cli::try_parse_from([
@@ -314,6 +783,242 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_install_unattended() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "config.json",
+            "--yes",
+            "--on-error",
+            "retry",
+            "--retries",
+            "5",
+        ])
+        .expect("unattended install should parse");
+        match cli.command {
+            Some(Commands::Install {
+                unattended,
+                on_error,
+                retries,
+                ..
+            }) => {
+                assert!(unattended);
+                assert_eq!(on_error, ErrorPolicy::Retry);
+                assert_eq!(retries, 5);
+            }
+            _ => panic!("Expected Install command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_install_unattended_defaults() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "-c",
+            "config.json",
+            "--unattended",
+        ])
+        .expect("unattended install should parse");
+        match cli.command {
+            Some(Commands::Install {
+                on_error, retries, ..
+            }) => {
+                assert_eq!(on_error, ErrorPolicy::Abort);
+                assert_eq!(retries, 3);
+            }
+            _ => panic!("Expected Install command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_install_timeouts() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--command-timeout",
+            "300",
+            "--timeout",
+            "reflector=60",
+            "--timeout",
+            "Installing packages=3600",
+        ])
+        .expect("install with timeouts should parse");
+        match cli.command {
+            Some(Commands::Install {
+                command_timeout,
+                timeouts,
+                ..
+            }) => {
+                assert_eq!(command_timeout, 300);
+                assert_eq!(
+                    timeouts,
+                    [
+                        ("reflector".to_string(), Duration::from_secs(60)),
+                        ("Installing packages".to_string(), Duration::from_secs(3600)),
+                    ]
+                );
+            }
+            _ => panic!("Expected Install command"),
+        }
+
+        // The watchdog belongs to the TUI; headless installs have no one to ask
+        let result = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "config.json",
+            "--timeout",
+            "reflector=60",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_install_report_dir() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "config.json",
+            "--report",
+            "/srv/reports",
+        ])
+        .expect("install with --report should parse");
+        match cli.command {
+            Some(Commands::Install { report, .. }) => {
+                assert_eq!(report.unwrap().to_str().unwrap(), "/srv/reports");
+            }
+            _ => panic!("Expected Install command"),
+        }
+
+        // Saving a config does not install anything, so there is no report
+        let result = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--save-config",
+            "out.json",
+            "--report",
+            "/srv/reports",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_install_json_output() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "config.json",
+            "--output",
+            "json",
+        ])
+        .expect("install with --output json should parse");
+        match cli.command {
+            Some(Commands::Install { output, .. }) => assert_eq!(output, OutputFormat::Json),
+            _ => panic!("Expected Install command"),
+        }
+
+        // The TUI draws its own progress
+        let result = Cli::try_parse_from(["archinstall-tui", "install", "--output", "json"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_interactive_missing() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "partial.json",
+            "--interactive-missing",
+        ])
+        .expect("--interactive-missing should parse with --config");
+        match cli.command {
+            Some(Commands::Install {
+                config,
+                interactive_missing,
+                ..
+            }) => {
+                assert_eq!(config.unwrap().to_str().unwrap(), "partial.json");
+                assert!(interactive_missing);
+            }
+            _ => panic!("Expected Install command"),
+        }
+
+        let result = Cli::try_parse_from(["archinstall-tui", "install", "--interactive-missing"]);
+        assert!(result.is_err(), "--interactive-missing needs --config");
+
+        // The TUI asks, so nothing may run unattended
+        let result = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "partial.json",
+            "--interactive-missing",
+            "--unattended",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_unattended_requires_config() {
+        let result = Cli::try_parse_from(["archinstall-tui", "install", "--unattended"]);
+        assert!(result.is_err());
+
+        let result = Cli::try_parse_from([
+            "archinstall-tui",
+            "install",
+            "--config",
+            "config.json",
+            "--on-error",
+            "continue",
+        ]);
+        assert!(result.is_err(), "--on-error only makes sense with --unattended");
+    }
+
+    #[test]
+    fn test_cli_remote_command() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "remote",
+            "root@10.0.0.5",
+            "--config",
+            "fleet.json",
+            "--port",
+            "2222",
+            "-i",
+            "/root/.ssh/id_ed25519",
+        ])
+        .expect("remote should parse");
+        match cli.command {
+            Some(Commands::Remote {
+                target,
+                config,
+                port,
+                identity,
+                on_error,
+                ..
+            }) => {
+                assert_eq!(target, "root@10.0.0.5");
+                assert_eq!(config.to_str().unwrap(), "fleet.json");
+                assert_eq!(port, 2222);
+                assert_eq!(identity.unwrap().to_str().unwrap(), "/root/.ssh/id_ed25519");
+                assert_eq!(on_error, ErrorPolicy::Abort);
+            }
+            _ => panic!("Expected Remote command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_remote_requires_config() {
+        let result = Cli::try_parse_from(["archinstall-tui", "remote", "10.0.0.5"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_cli_validate_command() {
         let result = Cli::try_parse_from([
@@ -331,6 +1036,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_archinstall_import() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "archinstall",
+            "import",
+            "user_configuration.json",
+            "--creds",
+            "user_credentials.json",
+            "-o",
+            "config.json",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Archinstall {
+                action: ArchinstallCommands::Import { creds, output, .. },
+            }) => {
+                assert_eq!(creds.unwrap().to_str().unwrap(), "user_credentials.json");
+                assert_eq!(output.to_str().unwrap(), "config.json");
+            }
+            _ => panic!("Expected Archinstall Import command"),
+        }
+        // The output directory is required
+        let export = ["archinstall-tui", "archinstall", "export", "config.json"];
+        assert!(Cli::try_parse_from(export).is_err());
+    }
+
+    #[test]
+    fn test_cli_generate_pxe() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "generate",
+            "pxe",
+            "fleet.json",
+            "--config-url",
+            "http://pxe.lan/fleet.json",
+            "-o",
+            "/srv/tftp",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Generate {
+                artifact:
+                    GenerateCommands::Pxe {
+                        config,
+                        config_url,
+                        iso_url,
+                        output,
+                    },
+            }) => {
+                assert_eq!(config.to_str().unwrap(), "fleet.json");
+                assert_eq!(config_url, "http://pxe.lan/fleet.json");
+                assert_eq!(iso_url, crate::pxe::DEFAULT_ISO_URL);
+                assert_eq!(output.to_str().unwrap(), "/srv/tftp");
+            }
+            _ => panic!("Expected Generate Pxe command"),
+        }
+        // The machines have to know where the configuration is served
+        let missing_url = [
+            "archinstall-tui",
+            "generate",
+            "pxe",
+            "fleet.json",
+            "-o",
+            "out",
+        ];
+        assert!(Cli::try_parse_from(missing_url).is_err());
+    }
+
     #[test]
     fn test_cli_disk_format_tool() {
         let result = Cli::try_parse_from([
@@ -346,6 +1120,201 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_cli_disk_resize_tool() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "disk",
+            "resize",
+            "--device",
+            "/dev/sda2",
+            "--size",
+            "-5G",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::Disk {
+                        disk_tool:
+                            DiskToolCommands::Resize {
+                                device,
+                                size,
+                                confirm,
+                            },
+                    },
+            }) => {
+                assert_eq!(device, "/dev/sda2");
+                assert_eq!(size, "-5G");
+                assert!(!confirm);
+            }
+            _ => panic!("Expected disk resize command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_system_services_tool() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "system",
+            "services",
+            "--action",
+            "enable",
+            "--service",
+            "sshd.service",
+            "--root",
+            "/mnt",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::System {
+                        system_tool:
+                            SystemToolCommands::Services {
+                                action,
+                                service,
+                                root,
+                            },
+                    },
+            }) => {
+                assert_eq!(action, "enable");
+                assert_eq!(service.as_deref(), Some("sshd.service"));
+                assert_eq!(root.as_deref(), Some("/mnt"));
+            }
+            _ => panic!("Expected system services command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_snapshot_rollback_tool() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "snapshot",
+            "rollback",
+            "--device",
+            "/dev/mapper/cryptroot",
+            "--snapshot",
+            "12",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::Snapshot {
+                        snapshot_tool:
+                            SnapshotToolCommands::Rollback {
+                                device,
+                                snapshot,
+                                confirm,
+                            },
+                    },
+            }) => {
+                assert_eq!(device, "/dev/mapper/cryptroot");
+                assert_eq!(snapshot, 12);
+                assert!(!confirm);
+            }
+            _ => panic!("Expected snapshot rollback command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_tools_json_output() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "network",
+            "diagnostics",
+            "--action",
+            "troubleshoot",
+            "--json",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::Network {
+                        network_tool: NetworkToolCommands::Diagnostics { action, json },
+                    },
+            }) => {
+                assert_eq!(action, "troubleshoot");
+                assert!(json);
+            }
+            _ => panic!("Expected network diagnostics command"),
+        }
+
+        let cli = Cli::try_parse_from(["archinstall-tui", "tools", "system", "info"]).unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::System {
+                        system_tool: SystemToolCommands::Info { detailed, json },
+                    },
+            }) => assert!(!detailed && !json),
+            _ => panic!("Expected system info command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_cleanup_tool() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "cleanup",
+            "--dry-run",
+            "--disk",
+            "/dev/sda",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool: ToolCommands::Cleanup { dry_run, disk },
+            }) => {
+                assert!(dry_run);
+                assert_eq!(disk, vec!["/dev/sda"]);
+            }
+            _ => panic!("Expected cleanup command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_stats() {
+        let cli = Cli::try_parse_from(["archinstall-tui", "--install-stats", "install"]).unwrap();
+        assert!(cli.install_stats);
+
+        let cli = Cli::try_parse_from(["archinstall-tui", "stats", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Stats {
+                json: true,
+                clear: false
+            })
+        ));
+        assert!(Cli::try_parse_from(["archinstall-tui", "stats", "--json", "--clear"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_phases_run() {
+        let cli =
+            Cli::try_parse_from(["archinstall-tui", "phases", "run", "ca-certificates", "dotfiles"])
+                .unwrap();
+        match cli.command {
+            Some(Commands::Phases {
+                action: PhaseCommands::Run { names, target, dir },
+            }) => {
+                assert_eq!(names, vec!["ca-certificates", "dotfiles"]);
+                assert_eq!(target, PathBuf::from("/mnt"));
+                assert!(dir.is_none());
+            }
+            _ => panic!("Expected phases run command"),
+        }
+
+        assert!(Cli::try_parse_from(["archinstall-tui", "phases", "run"]).is_err());
+    }
+
     #[test]
     fn test_cli_system_bootloader_tool() {
         let result = Cli::try_parse_from([
@@ -386,4 +1355,81 @@ mod tests {
         ]);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_cli_network_configure_dual_stack() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "network",
+            "configure",
+            "--interface",
+            "enp1s0",
+            "--ip6",
+            "2001:db8::10/64",
+            "--ipv6-mode",
+            "static",
+            "--dns",
+            "1.1.1.1,2606:4700:4700::1111",
+            "--backend",
+            "networkd",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::Network {
+                        network_tool:
+                            NetworkToolCommands::Configure {
+                                ip6,
+                                ipv6_mode,
+                                dns,
+                                backend,
+                                ..
+                            },
+                    },
+            }) => {
+                assert_eq!(ip6.as_deref(), Some("2001:db8::10/64"));
+                assert_eq!(ipv6_mode, Some(Ipv6Mode::Static));
+                assert_eq!(dns, ["1.1.1.1", "2606:4700:4700::1111"]);
+                assert_eq!(backend, Some(NetworkBackend::Networkd));
+            }
+            _ => panic!("Expected network configure"),
+        }
+    }
+
+    #[test]
+    fn test_parse_format_flags() {
+        let cli = Cli::try_parse_from([
+            "archinstall-tui",
+            "tools",
+            "disk",
+            "format",
+            "--device",
+            "/dev/sdb1",
+            "--filesystem",
+            "f2fs",
+            "--f2fs-compression",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Tools {
+                tool:
+                    ToolCommands::Disk {
+                        disk_tool:
+                            DiskToolCommands::Format {
+                                filesystem,
+                                f2fs_compression,
+                                ext4_reserved,
+                                ..
+                            },
+                    },
+            }) => {
+                assert_eq!(filesystem, FormatFilesystem::F2fs);
+                assert!(f2fs_compression);
+                assert_eq!(ext4_reserved, None);
+            }
+            _ => panic!("Expected disk format"),
+        }
+    }
 }
@@ -0,0 +1,725 @@
+//! Cloning an existing Arch system onto a new disk
+//!
+//! Reads what makes an installed system its own — the explicitly installed
+//! packages (`pacman -Qqe`), the filesystem layout in fstab, the enabled
+//! services and the settings in /etc — and turns it into an
+//! [`InstallationConfig`] to review and install, e.g. when moving to a new
+//! SSD. The target disk and the passwords are left for the user; anything
+//! that cannot be carried over is listed in [`ClonedSystem::notes`].
+
+use crate::arch::Arch;
+use crate::config_file::InstallationConfig;
+use crate::desktop;
+use crate::services;
+use crate::types::{
+    AudioServer, AurHelper, AutoToggle, Bootloader, DisplayManager, Filesystem, GuestTools,
+    HardwareClock, Kernel, Microcode, PartitionScheme, PowerManagement, TimeSyncDaemon, Toggle,
+};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+/// `tools.current` while the source system is asked for and its summary shown
+pub const TOOL_NAME: &str = "clone_system";
+
+/// Packages the installer puts on every system
+const BASE_PACKAGES: &[&str] = &[
+    "base",
+    "base-devel",
+    "linux-firmware",
+    "sudo",
+    "networkmanager",
+    "openssh",
+    "grub",
+    "efibootmgr",
+    "os-prober",
+];
+
+/// Repositories every Arch system has; others are reported
+const STANDARD_REPOSITORIES: &[&str] = &[
+    "options",
+    "core",
+    "extra",
+    "multilib",
+    "core-testing",
+    "extra-testing",
+    "multilib-testing",
+];
+
+/// Package lists of the system being cloned
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageLists {
+    /// Explicitly installed packages from the sync repositories (`-Qqen`)
+    pub native: Vec<String>,
+    /// Explicitly installed packages from elsewhere, usually the AUR (`-Qqem`)
+    pub foreign: Vec<String>,
+    /// Every installed package, dependencies included (`-Qq`)
+    pub installed: BTreeSet<String>,
+}
+
+impl PackageLists {
+    /// Parse the one-name-per-line output of the three pacman queries
+    pub fn parse(native: &str, foreign: &str, installed: &str) -> Self {
+        let names = |output: &str| -> Vec<String> {
+            output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            native: names(native),
+            foreign: names(foreign),
+            installed: names(installed).into_iter().collect(),
+        }
+    }
+
+    fn has(&self, package: &str) -> bool {
+        self.installed.contains(package)
+    }
+}
+
+/// A configuration replicating an existing system
+#[derive(Debug, Clone)]
+pub struct ClonedSystem {
+    pub config: InstallationConfig,
+    /// What could not be carried over, for the user to review
+    pub notes: Vec<String>,
+}
+
+/// Query the package database of the system mounted at `root`
+pub fn read_packages(root: &Path) -> Result<PackageLists, String> {
+    let query = |flags: &str| -> Result<String, String> {
+        let output = Command::new("pacman")
+            .arg("--root")
+            .arg(root)
+            .arg("--dbpath")
+            .arg(root.join("var/lib/pacman"))
+            .arg(flags)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run pacman: {}", e))?;
+        // pacman exits 1 for an empty result, e.g. no foreign packages
+        if !output.status.success() && !output.stderr.is_empty() {
+            return Err(format!(
+                "pacman {} failed on {}: {}",
+                flags,
+                root.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    Ok(PackageLists::parse(
+        &query("-Qqen")?,
+        &query("-Qqem")?,
+        &query("-Qq")?,
+    ))
+}
+
+/// Clone the Arch system mounted at `root` ("/" for the running system)
+pub fn clone_system(root: &Path) -> Result<ClonedSystem, String> {
+    if !root.join("etc/arch-release").exists() && !root.join("var/lib/pacman/local").is_dir() {
+        return Err(format!(
+            "{} does not hold an Arch Linux system",
+            root.display()
+        ));
+    }
+    let packages = read_packages(root)?;
+    Ok(build(root, &packages))
+}
+
+/// Build the configuration from the files under `root` and its packages
+pub fn build(root: &Path, packages: &PackageLists) -> ClonedSystem {
+    let read = |path: &str| fs::read_to_string(root.join(path)).unwrap_or_default();
+    let mut config = InstallationConfig::default();
+    let mut notes = Vec::new();
+
+    // Settings in /etc
+    let hostname = read("etc/hostname").trim().to_string();
+    if crate::config::validate_hostname(&hostname).is_ok() {
+        config.hostname = hostname;
+    }
+    if let Some(locale) = shell_variable(&read("etc/locale.conf"), "LANG") {
+        config.locale = locale;
+    }
+    if let Some(keymap) = shell_variable(&read("etc/vconsole.conf"), "KEYMAP") {
+        config.keymap = keymap;
+    }
+    if let Some((region, city)) = fs::read_link(root.join("etc/localtime"))
+        .ok()
+        .and_then(|target| timezone_of(&target.to_string_lossy()))
+    {
+        config.timezone_region = region;
+        config.timezone = city;
+    }
+    // hwclock writes LOCAL or UTC to the third line
+    if read("etc/adjtime").lines().nth(2) == Some("LOCAL") {
+        config.hardware_clock = HardwareClock::LocalTime;
+    }
+    match first_user(&read("etc/passwd")) {
+        Some(user) => config.username = user,
+        None => notes.push("No regular user account found: set a username".to_string()),
+    }
+
+    // Filesystem layout
+    let mounts = parse_fstab(&read("etc/fstab"));
+    let filesystem_of = |mountpoint: &str| {
+        mounts
+            .iter()
+            .find(|mount| mount.mountpoint == mountpoint)
+            .map(|mount| mount.fstype.as_str())
+    };
+    match filesystem_of("/").map(|fstype| (fstype, Filesystem::from_str(fstype))) {
+        Some((_, Ok(filesystem))) => config.root_filesystem = filesystem,
+        Some((fstype, Err(_))) => notes.push(format!(
+            "Root filesystem {} is not offered: using {}",
+            fstype, config.root_filesystem
+        )),
+        None => notes.push("No root filesystem in /etc/fstab".to_string()),
+    }
+    if let Some(fstype) = filesystem_of("/home") {
+        config.separate_home = Toggle::Yes;
+        match Filesystem::from_str(fstype) {
+            Ok(filesystem) => config.home_filesystem = filesystem,
+            Err(_) => notes.push(format!(
+                "Home filesystem {} is not offered: using {}",
+                fstype, config.home_filesystem
+            )),
+        }
+    } else {
+        config.separate_home = Toggle::No;
+    }
+    config.swap = Toggle::from(mounts.iter().any(|mount| mount.fstype == "swap"));
+    if config.swap == Toggle::Yes {
+        notes.push(format!(
+            "Swap size is not cloned: using {}",
+            config.swap_size
+        ));
+    }
+
+    let hooks = mkinitcpio_hooks(&read("etc/mkinitcpio.conf"));
+    let encrypted = hooks
+        .iter()
+        .any(|hook| hook == "encrypt" || hook == "sd-encrypt");
+    let lvm = hooks.iter().any(|hook| hook == "lvm2");
+    config.encryption = if encrypted {
+        AutoToggle::Yes
+    } else {
+        AutoToggle::No
+    };
+    config.partitioning_strategy = match (encrypted, lvm) {
+        (false, false) => PartitionScheme::AutoSimple,
+        (true, false) => PartitionScheme::AutoSimpleLuks,
+        (false, true) => PartitionScheme::AutoLvm,
+        (true, true) => PartitionScheme::AutoLuksLvm,
+    };
+    if hooks.iter().any(|hook| hook.starts_with("mdadm")) {
+        notes.push(format!(
+            "RAID is not cloned: installing with {} on a single disk",
+            config.partitioning_strategy
+        ));
+    }
+
+    // Packages behind the options
+    let arch = Arch::current();
+    config.kernel = Kernel::iter()
+        .find(|kernel| {
+            arch.kernel_package(*kernel)
+                .is_some_and(|package| packages.native.iter().any(|name| name == package))
+        })
+        .unwrap_or_default();
+    config.bootloader = if packages.has("grub") {
+        Bootloader::Grub
+    } else if root.join("boot/loader/loader.conf").exists()
+        || root.join("efi/loader/loader.conf").exists()
+    {
+        Bootloader::SystemdBoot
+    } else if root.join("boot/extlinux/extlinux.conf").exists() {
+        Bootloader::UBoot
+    } else {
+        config.bootloader
+    };
+    config.desktop_environment =
+        desktop::detect(|package| packages.has(package)).unwrap_or_default();
+    config.display_manager = if packages.has("gdm") {
+        DisplayManager::Gdm
+    } else if packages.has("sddm") {
+        DisplayManager::Sddm
+    } else if packages.has("lightdm") {
+        DisplayManager::Lightdm
+    } else {
+        DisplayManager::None
+    };
+    config.audio = if packages.has("pipewire-pulse") {
+        AudioServer::PipeWire
+    } else if packages.has("pulseaudio") {
+        AudioServer::PulseAudio
+    } else {
+        AudioServer::None
+    };
+    config.power_management = if packages.has("tlp") {
+        PowerManagement::Tlp
+    } else if packages.has("power-profiles-daemon") {
+        PowerManagement::PowerProfilesDaemon
+    } else {
+        PowerManagement::None
+    };
+    config.vm_guest_tools = GuestTools::iter()
+        .find(|tools| {
+            tools
+                .packages()
+                .first()
+                .is_some_and(|package| packages.has(package))
+        })
+        .unwrap_or_default();
+    config.aur_helper = if packages.has("paru") || packages.has("paru-bin") {
+        AurHelper::Paru
+    } else if packages.has("yay") || packages.has("yay-bin") {
+        AurHelper::Yay
+    } else if packages.foreign.is_empty() {
+        AurHelper::None
+    } else {
+        config.aur_helper
+    };
+    config.flatpak = Toggle::from(packages.has("flatpak"));
+    config.plymouth = Toggle::from(packages.has("plymouth"));
+    config.btrfs_snapshots =
+        Toggle::from(config.root_filesystem == Filesystem::Btrfs && packages.has("snapper"));
+    config.btrfs_assistant = Toggle::from(packages.has("btrfs-assistant"));
+
+    // pacman.conf
+    let pacman_conf = read("etc/pacman.conf");
+    let sections = pacman_sections(&pacman_conf);
+    config.multilib = Toggle::from(sections.iter().any(|s| s == "multilib"));
+    config.pacman_color = Toggle::from(pacman_option(&pacman_conf, "Color").is_some());
+    config.verbose_pkg_lists =
+        Toggle::from(pacman_option(&pacman_conf, "VerbosePkgLists").is_some());
+    if let Some(Ok(downloads)) = pacman_option(&pacman_conf, "ParallelDownloads")
+        .map(|value| crate::pacman::validate_parallel_downloads(&value))
+    {
+        config.parallel_downloads = downloads;
+    }
+    for repository in sections
+        .iter()
+        .filter(|s| !STANDARD_REPOSITORIES.contains(&s.as_str()))
+    {
+        notes.push(format!(
+            "Repository [{}] is not cloned: add it under Custom Repositories",
+            repository
+        ));
+    }
+
+    // Services
+    let units = enabled_units(root);
+    config.services = services::SERVICES
+        .iter()
+        .filter(|service| units.contains(&service.unit.to_string()))
+        .map(|service| service.unit.to_string())
+        .collect();
+    // The services list names timesyncd's unit for either daemon
+    match TimeSyncDaemon::iter().find(|daemon| units.contains(&daemon.service().to_string())) {
+        Some(daemon) => {
+            config.time_sync = Toggle::Yes;
+            config.time_sync_daemon = daemon;
+            if !config
+                .services
+                .iter()
+                .any(|unit| unit == services::TIMESYNCD)
+            {
+                config.services.push(services::TIMESYNCD.to_string());
+            }
+        }
+        None => config.time_sync = Toggle::No,
+    }
+    let implied = implied_units(&config);
+    let other: Vec<&String> = units
+        .iter()
+        .filter(|unit| services::find_service(unit).is_none() && !implied.contains(&unit.as_str()))
+        .collect();
+    if !other.is_empty() {
+        notes.push(format!(
+            "Not enabled on the new system: {}",
+            other
+                .iter()
+                .map(|unit| unit.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    // Remaining packages
+    let provided = provided_packages(&config);
+    config.additional_packages = packages
+        .native
+        .iter()
+        .filter(|package| !provided.contains(&package.as_str()))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    config.additional_aur_packages = packages
+        .foreign
+        .iter()
+        .filter(|package| !provided.contains(&package.as_str()))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if config.aur_helper == AurHelper::None && !config.additional_aur_packages.is_empty() {
+        config.aur_helper = AurHelper::Paru;
+    }
+
+    notes.push("Passwords are not cloned: set the user and root passwords".to_string());
+    notes.push("Choose the disk to install on".to_string());
+
+    ClonedSystem { config, notes }
+}
+
+/// A line of /etc/fstab
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mount {
+    mountpoint: String,
+    fstype: String,
+}
+
+fn parse_fstab(fstab: &str) -> Vec<Mount> {
+    fstab
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            (fields.len() >= 3).then(|| Mount {
+                mountpoint: fields[1].to_string(),
+                fstype: fields[2].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Value of `NAME=value` in a shell-style file like /etc/locale.conf
+fn shell_variable(contents: &str, name: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        (key.trim() == name && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Region and city of a /etc/localtime link target
+fn timezone_of(target: &str) -> Option<(String, String)> {
+    let zone = target.split("zoneinfo/").nth(1)?;
+    let (region, city) = zone.split_once('/')?;
+    Some((region.to_string(), city.to_string()))
+}
+
+/// First regular user (UID 1000-59999 with a login shell) in /etc/passwd
+fn first_user(passwd: &str) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        let uid: u32 = fields.get(2)?.parse().ok()?;
+        let shell = fields.get(6).copied().unwrap_or_default();
+        ((1000..60000).contains(&uid) && !shell.ends_with("nologin") && !shell.ends_with("false"))
+            .then(|| fields[0].to_string())
+    })
+}
+
+/// The HOOKS array of mkinitcpio.conf
+fn mkinitcpio_hooks(conf: &str) -> Vec<String> {
+    conf.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("HOOKS="))
+        .map(|line| {
+            line.trim_start_matches("HOOKS=")
+                .trim_matches(|c| c == '(' || c == ')' || c == '"')
+                .split_whitespace()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Sections of pacman.conf that are not commented out
+fn pacman_sections(conf: &str) -> Vec<String> {
+    conf.lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix('[')?.strip_suffix(']'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Value of an active [options] setting; flags like `Color` have an empty value
+fn pacman_option(conf: &str, name: &str) -> Option<String> {
+    conf.lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "[options]")
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .find_map(|line| match line.split_once('=') {
+            Some((key, value)) if key.trim() == name => Some(value.trim().to_string()),
+            None if line == name => Some(String::new()),
+            _ => None,
+        })
+}
+
+/// Units enabled in the system's /etc/systemd/system/*.wants directories
+fn enabled_units(root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root.join("etc/systemd/system")) else {
+        return Vec::new();
+    };
+    let mut units = BTreeSet::new();
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().ends_with(".wants") {
+            continue;
+        }
+        for unit in fs::read_dir(entry.path()).into_iter().flatten().flatten() {
+            units.insert(unit.file_name().to_string_lossy().to_string());
+        }
+    }
+    units.into_iter().collect()
+}
+
+/// Units the installer enables through other options or systemd presets
+fn implied_units(config: &InstallationConfig) -> Vec<&'static str> {
+    let mut units = vec![
+        "getty@tty1.service",
+        "display-manager.service",
+        "remote-fs.target",
+        "systemd-userdbd.socket",
+        "systemd-resolved.service",
+        "NetworkManager-wait-online.service",
+        "NetworkManager-dispatcher.service",
+        "dbus-org.freedesktop.nm-dispatcher.service",
+        "p11-kit-server.socket",
+    ];
+    units.extend(config.vm_guest_tools.services());
+    units.extend(config.audio.user_services());
+    units.extend(config.power_management.services());
+    units.push(config.time_sync_daemon.service());
+    if config.btrfs_snapshots == Toggle::Yes {
+        units.extend(["snapper-timeline.timer", "snapper-cleanup.timer"]);
+    }
+    units
+}
+
+/// Packages the configuration installs without listing them
+fn provided_packages(config: &InstallationConfig) -> Vec<&'static str> {
+    let mut packages = BASE_PACKAGES.to_vec();
+    packages.extend(Arch::current().kernel_package(config.kernel));
+    // Auto installs the microcode of the target's CPU, not the source's
+    packages.extend(Microcode::iter().filter_map(|microcode| microcode.package()));
+    packages.extend(config.vm_guest_tools.packages());
+    packages.extend(config.audio.packages());
+    packages.extend(config.power_management.packages());
+    packages.extend(config.time_sync_daemon.packages());
+    packages.extend(
+        config
+            .services
+            .iter()
+            .filter_map(|unit| services::find_service(unit))
+            .flat_map(|service| service.packages.iter().copied()),
+    );
+    match config.display_manager {
+        DisplayManager::Gdm => packages.push("gdm"),
+        DisplayManager::Sddm => packages.push("sddm"),
+        DisplayManager::Lightdm => packages.extend(["lightdm", "lightdm-gtk-greeter"]),
+        DisplayManager::None => {}
+    }
+    match config.aur_helper {
+        AurHelper::Paru => packages.extend(["paru", "paru-bin"]),
+        AurHelper::Yay => packages.extend(["yay", "yay-bin"]),
+        AurHelper::None => {}
+    }
+    if config.flatpak == Toggle::Yes {
+        packages.push("flatpak");
+    }
+    if config.plymouth == Toggle::Yes {
+        packages.push("plymouth");
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DesktopEnvironment;
+    use std::path::PathBuf;
+
+    /// A fake system root with the given files; symlinks are `-> target`
+    fn system(files: &[(&str, &str)]) -> PathBuf {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "archinstall-clone-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        ));
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            match contents.strip_prefix("-> ") {
+                Some(target) => std::os::unix::fs::symlink(target, &path).unwrap(),
+                None => fs::write(&path, contents).unwrap(),
+            }
+        }
+        root
+    }
+
+    fn packages(native: &[&str], foreign: &[&str], dependencies: &[&str]) -> PackageLists {
+        PackageLists::parse(
+            &native.join("\n"),
+            &foreign.join("\n"),
+            &[native, foreign, dependencies].concat().join("\n"),
+        )
+    }
+
+    #[test]
+    fn test_clones_a_desktop_system() {
+        let root = system(&[
+            ("etc/hostname", "workstation\n"),
+            ("etc/locale.conf", "LANG=de_DE.UTF-8\n"),
+            ("etc/vconsole.conf", "KEYMAP=de-latin1\n"),
+            ("etc/localtime", "-> /usr/share/zoneinfo/Europe/Berlin"),
+            (
+                "etc/passwd",
+                "root:x:0:0::/root:/bin/bash\nnobody:x:65534:65534::/:/usr/bin/nologin\nalex:x:1000:1000::/home/alex:/bin/zsh\n",
+            ),
+            (
+                "etc/fstab",
+                "# /dev/nvme0n1p2\nUUID=1 / btrfs rw,subvol=/@ 0 0\nUUID=1 /home btrfs rw,subvol=/@home 0 0\nUUID=2 /boot vfat rw 0 2\n",
+            ),
+            ("etc/mkinitcpio.conf", "HOOKS=(base udev autodetect block encrypt filesystems fsck)\n"),
+            (
+                "etc/pacman.conf",
+                "[options]\nColor\nParallelDownloads = 8\n#VerbosePkgLists\n\n[core]\nInclude = x\n[extra]\n#[multilib]\n[chaotic-aur]\n",
+            ),
+            ("etc/systemd/system/multi-user.target.wants/sshd.service", "-> /usr/lib/systemd/system/sshd.service"),
+            ("etc/systemd/system/bluetooth.target.wants/bluetooth.service", "-> /x"),
+            ("etc/systemd/system/multi-user.target.wants/docker.service", "-> /x"),
+        ]);
+        let packages = packages(
+            &[
+                "base",
+                "linux-zen",
+                "gnome",
+                "gdm",
+                "firefox",
+                "bluez",
+                "snapper",
+            ],
+            &["paru-bin", "visual-studio-code-bin"],
+            &["gnome-shell", "pipewire-pulse", "grub"],
+        );
+
+        let cloned = build(&root, &packages);
+        let config = &cloned.config;
+        assert_eq!(config.hostname, "workstation");
+        assert_eq!(config.locale, "de_DE.UTF-8");
+        assert_eq!(config.keymap, "de-latin1");
+        assert_eq!(
+            (config.timezone_region.as_str(), config.timezone.as_str()),
+            ("Europe", "Berlin")
+        );
+        assert_eq!(config.username, "alex");
+        assert_eq!(config.root_filesystem, Filesystem::Btrfs);
+        assert_eq!(config.separate_home, Toggle::Yes);
+        assert_eq!(config.swap, Toggle::No);
+        assert_eq!(
+            config.partitioning_strategy,
+            PartitionScheme::AutoSimpleLuks
+        );
+        assert_eq!(config.kernel, Kernel::LinuxZen);
+        assert_eq!(config.bootloader, Bootloader::Grub);
+        assert_eq!(config.desktop_environment, DesktopEnvironment::Gnome);
+        assert_eq!(config.display_manager, DisplayManager::Gdm);
+        assert_eq!(config.audio, AudioServer::PipeWire);
+        assert_eq!(config.aur_helper, AurHelper::Paru);
+        assert_eq!(config.btrfs_snapshots, Toggle::Yes);
+        assert_eq!(config.multilib, Toggle::No);
+        assert_eq!(config.pacman_color, Toggle::Yes);
+        assert_eq!(config.verbose_pkg_lists, Toggle::No);
+        assert_eq!(config.parallel_downloads, 8);
+        assert_eq!(config.services, ["sshd.service", "bluetooth.service"]);
+        assert_eq!(config.time_sync, Toggle::No);
+        assert_eq!(config.additional_packages, "gnome firefox snapper");
+        assert_eq!(config.additional_aur_packages, "visual-studio-code-bin");
+        assert!(config.install_disk.is_empty());
+        assert!(config.user_password.is_empty());
+
+        let notes = cloned.notes.join("\n");
+        assert!(notes.contains("[chaotic-aur]"));
+        assert!(notes.contains("Not enabled on the new system: docker.service"));
+        assert!(notes.contains("Passwords are not cloned"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_clones_a_minimal_server() {
+        let root = system(&[
+            (
+                "etc/fstab",
+                "/dev/vg/root / ext4 rw 0 1\n/swapfile none swap defaults 0 0\n",
+            ),
+            (
+                "etc/mkinitcpio.conf",
+                "HOOKS=\"base udev lvm2 mdadm_udev filesystems\"\n",
+            ),
+            ("boot/loader/loader.conf", "default arch.conf\n"),
+            (
+                "etc/systemd/system/sysinit.target.wants/systemd-timesyncd.service",
+                "-> /x",
+            ),
+        ]);
+        let cloned = build(&root, &packages(&["base", "linux-lts", "vim"], &[], &[]));
+        let config = &cloned.config;
+        assert_eq!(config.partitioning_strategy, PartitionScheme::AutoLvm);
+        assert_eq!(config.separate_home, Toggle::No);
+        assert_eq!(config.swap, Toggle::Yes);
+        assert_eq!(config.kernel, Kernel::LinuxLts);
+        assert_eq!(config.bootloader, Bootloader::SystemdBoot);
+        assert_eq!(config.desktop_environment, DesktopEnvironment::None);
+        assert_eq!(config.audio, AudioServer::None);
+        assert_eq!(config.aur_helper, AurHelper::None);
+        assert_eq!(config.time_sync, Toggle::Yes);
+        assert_eq!(config.additional_packages, "vim");
+        assert!(config.hostname.is_empty());
+
+        let notes = cloned.notes.join("\n");
+        assert!(notes.contains("RAID is not cloned"));
+        assert!(notes.contains("No regular user account"));
+        assert!(notes.contains("Swap size is not cloned"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_clones_chrony_and_a_local_hardware_clock() {
+        let root = system(&[
+            ("etc/fstab", "/dev/sda2 / ext4 rw 0 1\n"),
+            ("etc/adjtime", "0.0 0 0\n0\nLOCAL\n"),
+            (
+                "etc/systemd/system/multi-user.target.wants/chronyd.service",
+                "-> /x",
+            ),
+        ]);
+        let cloned = build(&root, &packages(&["base", "linux", "chrony"], &[], &[]));
+        let config = &cloned.config;
+        assert_eq!(config.time_sync, Toggle::Yes);
+        assert_eq!(config.time_sync_daemon, TimeSyncDaemon::Chrony);
+        assert!(config.services.contains(&services::TIMESYNCD.to_string()));
+        assert_eq!(config.hardware_clock, HardwareClock::LocalTime);
+        assert!(config.additional_packages.is_empty());
+        assert!(!cloned.notes.join("\n").contains("chronyd"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_a_directory_without_arch() {
+        let root = system(&[("etc/hostname", "x")]);
+        assert!(clone_system(&root)
+            .unwrap_err()
+            .contains("does not hold an Arch Linux system"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
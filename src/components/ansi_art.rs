@@ -0,0 +1,292 @@
+//! Low-resolution images drawn with coloured half blocks
+//!
+//! Each terminal cell shows two pixels stacked vertically: the upper half
+//! block `▀` takes the top pixel as its foreground and the bottom pixel as
+//! its background. Images come from bundled pixel art or from thumbnails
+//! converted to binary PPM, which needs no image decoder:
+//! `magick preview.png -resize 64x32 preview.ppm`.
+//!
+//! ASCII consoles (`--ascii`, `--serial`) rarely show true colour either, so
+//! there each cell becomes a character as dense as its pixels are bright,
+//! see [`ascii_shade`].
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// Upper half block: foreground is the top pixel, background the bottom one
+pub const HALF_BLOCK: &str = "▀";
+
+/// ASCII characters from dark to bright
+const SHADES: &[&str] = &[" ", ".", ":", "-", "=", "+", "*", "#", "%", "@"];
+
+/// An RGB image small enough to draw in a dialog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiArt {
+    width: usize,
+    height: usize,
+    /// Row-major pixels
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+impl AnsiArt {
+    /// Image of `width` x `height` pixels filled with one colour
+    pub fn filled(width: usize, height: usize, rgb: (u8, u8, u8)) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![rgb; width * height],
+        }
+    }
+
+    /// Parse a binary PPM (P6) image with 8-bit channels
+    pub fn from_ppm(data: &[u8]) -> Result<Self, String> {
+        // Header: magic, width, height and maxval, separated by whitespace
+        // and possibly interleaved with # comments, then one whitespace byte
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while fields.len() < 4 {
+            match data.get(pos) {
+                None => return Err("PPM header is incomplete".to_string()),
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|&b| b != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => pos += 1,
+                Some(_) => {
+                    let start = pos;
+                    while data.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+                        pos += 1;
+                    }
+                    fields.push(String::from_utf8_lossy(&data[start..pos]).into_owned());
+                }
+            }
+        }
+        if fields[0] != "P6" {
+            return Err("Only binary PPM (P6) images are supported".to_string());
+        }
+        let number = |field: &str| {
+            field
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid PPM header value '{}'", field))
+        };
+        let (width, height, maxval) = (
+            number(&fields[1])?,
+            number(&fields[2])?,
+            number(&fields[3])?,
+        );
+        if width == 0 || height == 0 || maxval == 0 || maxval > 255 {
+            return Err(format!(
+                "Unsupported PPM image {}x{} with maxval {}",
+                width, height, maxval
+            ));
+        }
+
+        let body = data.get(pos + 1..).unwrap_or_default();
+        if body.len() < width * height * 3 {
+            return Err(format!(
+                "PPM image data is shorter than {}x{}",
+                width, height
+            ));
+        }
+        let scale = |v: u8| (v as usize * 255 / maxval) as u8;
+        let pixels = body
+            .chunks_exact(3)
+            .take(width * height)
+            .map(|p| (scale(p[0]), scale(p[1]), scale(p[2])))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Colour of a pixel, `None` outside the image
+    pub fn pixel(&self, x: usize, y: usize) -> Option<(u8, u8, u8)> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Set a pixel; pixels outside the image are ignored
+    pub fn set(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = rgb;
+        }
+    }
+
+    /// Fill a rectangle of pixels, clipped to the image
+    pub fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, rgb: (u8, u8, u8)) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + col] = rgb;
+            }
+        }
+    }
+
+    /// Scale down, keeping the aspect ratio, to fit `columns` x `rows` cells
+    ///
+    /// A cell holds one pixel across and two down. Images that already fit
+    /// are returned unchanged; nearest-neighbour sampling keeps edges sharp.
+    pub fn fit(&self, columns: u16, rows: u16) -> Self {
+        let max_width = columns as usize;
+        let max_height = rows as usize * 2;
+        if self.width <= max_width && self.height <= max_height {
+            return self.clone();
+        }
+        // Largest scale that fits both ways, as a fraction num/den
+        let (num, den) = if max_width * self.height <= max_height * self.width {
+            (max_width, self.width)
+        } else {
+            (max_height, self.height)
+        };
+        let width = (self.width * num / den).max(1);
+        let height = (self.height * num / den).max(1);
+        let pixels = (0..height)
+            .flat_map(|y| {
+                (0..width).map(move |x| {
+                    self.pixels[(y * self.height / height) * self.width + x * self.width / width]
+                })
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Lines of half blocks drawing the image, one per two pixel rows
+    ///
+    /// An odd last row is drawn over the terminal's background.
+    pub fn to_lines(&self) -> Vec<Line<'static>> {
+        (0..self.height)
+            .step_by(2)
+            .map(|y| {
+                let spans: Vec<Span> = (0..self.width)
+                    .map(|x| {
+                        let top = self.pixel(x, y).map(rgb);
+                        let bottom = self.pixel(x, y + 1).map(rgb);
+                        let mut style = Style::default();
+                        if let Some(top) = top {
+                            style = style.fg(top);
+                        }
+                        if let Some(bottom) = bottom {
+                            style = style.bg(bottom);
+                        }
+                        Span::styled(HALF_BLOCK, style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+fn rgb((r, g, b): (u8, u8, u8)) -> Color {
+    Color::Rgb(r, g, b)
+}
+
+/// ASCII character for a half block with the `top` and `bottom` colours
+///
+/// `None` unless the top one is a true colour, as in [`AnsiArt::to_lines`];
+/// a bottom without one is the odd last row and counts as the top.
+pub fn ascii_shade(top: Color, bottom: Color) -> Option<&'static str> {
+    let luma = |color| match color {
+        Color::Rgb(r, g, b) => {
+            Some((299 * r as usize + 587 * g as usize + 114 * b as usize) / 1000)
+        }
+        _ => None,
+    };
+    let top = luma(top)?;
+    let level = (top + luma(bottom).unwrap_or(top)) / 2;
+    Some(SHADES[level * SHADES.len() / 256])
+}
+
+/// Draws the image scaled to the area, centred
+impl Widget for &AnsiArt {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let image = self.fit(area.width, area.height);
+        let lines = image.to_lines();
+        let x = area.x + area.width.saturating_sub(image.width as u16) / 2;
+        let y = area.y + area.height.saturating_sub(lines.len() as u16) / 2;
+        for (row, line) in lines.iter().enumerate() {
+            buf.set_line(x, y + row as u16, line, image.width as u16);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_blocks_carry_two_pixels() {
+        let mut art = AnsiArt::filled(2, 3, (0, 0, 0));
+        art.set(0, 0, (255, 0, 0));
+        art.set(0, 1, (0, 0, 255));
+        art.set(1, 2, (0, 255, 0));
+
+        let lines = art.to_lines();
+        assert_eq!(lines.len(), 2);
+        let first = &lines[0].spans[0];
+        assert_eq!(first.content, "▀");
+        assert_eq!(first.style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(first.style.bg, Some(Color::Rgb(0, 0, 255)));
+        // The odd last row has nothing below it
+        assert_eq!(lines[1].spans[1].style.fg, Some(Color::Rgb(0, 255, 0)));
+        assert_eq!(lines[1].spans[1].style.bg, None);
+    }
+
+    #[test]
+    fn test_ascii_shades_follow_brightness() {
+        let black = Color::Rgb(0, 0, 0);
+        let white = Color::Rgb(255, 255, 255);
+        assert_eq!(ascii_shade(black, black), Some(" "));
+        assert_eq!(ascii_shade(white, white), Some("@"));
+        assert_eq!(ascii_shade(white, Color::Reset), Some("@"));
+        assert_eq!(ascii_shade(white, black), Some("="));
+        assert_eq!(ascii_shade(Color::Red, black), None);
+    }
+
+    #[test]
+    fn test_fit_keeps_the_aspect_ratio() {
+        let art = AnsiArt::filled(64, 32, (1, 2, 3));
+        let fitted = art.fit(16, 16);
+        assert_eq!((fitted.width(), fitted.height()), (16, 8));
+        let fitted = art.fit(80, 4);
+        assert_eq!((fitted.width(), fitted.height()), (16, 8));
+        assert_eq!(art.fit(100, 100), art);
+    }
+
+    #[test]
+    fn test_parses_binary_ppm() {
+        let mut data = b"P6\n# preview\n2 1\n255\n".to_vec();
+        data.extend_from_slice(&[255, 0, 0, 0, 128, 255]);
+        let art = AnsiArt::from_ppm(&data).unwrap();
+        assert_eq!((art.width(), art.height()), (2, 1));
+        assert_eq!(art.pixel(1, 0), Some((0, 128, 255)));
+
+        assert!(AnsiArt::from_ppm(b"P3\n1 1\n255\n0 0 0\n")
+            .unwrap_err()
+            .contains("P6"));
+        assert!(AnsiArt::from_ppm(b"P6\n4 4\n255\n\x00\x00\x00")
+            .unwrap_err()
+            .contains("shorter"));
+    }
+}
@@ -4,7 +4,13 @@
 
 #![allow(dead_code)]
 
+use crate::config_file::InstallationConfig;
 use crate::theme::{Styles, Theme, Severity, UiText};
+use crate::tools::bootrepair::Diagnosis;
+use crate::tools::cleanup::Leftover;
+use crate::tools::format::FormatOptions;
+use crate::tools::resize::{format_size, ResizePlan};
+use crate::tools::wipe::WipeMethod;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -206,54 +212,230 @@ impl ConfirmDialog {
 }
 
 /// Create a confirmation dialog for formatting a partition
-pub fn format_partition_confirm(partition: &str, filesystem: &str) -> ConfirmDialogState {
-    ConfirmDialogState::new(
+///
+/// The options travel as JSON action data, so the confirmed flags are
+/// exactly the ones shown.
+pub fn format_partition_confirm(options: &FormatOptions) -> ConfirmDialogState {
+    let mut dialog = ConfirmDialogState::new(
         "Format Partition",
-        &format!("Format {} with {}?", partition, filesystem),
+        &format!("Format {} with {}?", options.device, options.filesystem),
         ConfirmSeverity::Warning,
         "format_partition",
-    )
-    .with_detail("All data on this partition will be erased")
-    .with_detail("This operation cannot be undone")
-    .with_action_data(partition)
+    );
+    let flags = options.describe_flags();
+    if !flags.is_empty() {
+        dialog = dialog.with_detail(&format!("Options: {}", flags.join(", ")));
+    }
+    dialog
+        .with_detail("All data on this partition will be erased")
+        .with_detail("This operation cannot be undone")
+        .with_action_data(&serde_json::to_string(options).expect("format options always serialize"))
 }
 
 /// Create a confirmation dialog for wiping a disk
-pub fn wipe_disk_confirm(disk: &str) -> ConfirmDialogState {
+///
+/// The method is passed back with the disk as "method:disk" action data.
+pub fn wipe_disk_confirm(disk: &str, method: WipeMethod) -> ConfirmDialogState {
     ConfirmDialogState::new(
         "WIPE ENTIRE DISK",
         &format!("Permanently erase ALL data on {}?", disk),
         ConfirmSeverity::Danger,
         "wipe_disk",
     )
+    .with_detail(&format!("Method: {} - {}", method, method.description()))
     .with_detail("ALL partitions will be destroyed")
     .with_detail("ALL data will be permanently erased")
     .with_detail("This operation CANNOT be undone")
-    .with_action_data(disk)
+    .with_action_data(&format!("{}:{}", method, disk))
+}
+
+/// Create a confirmation dialog previewing a partition resize
+///
+/// `size` is the size as entered, passed back with the device as
+/// "device:size" action data so the plan is rebuilt from fresh disk state.
+pub fn resize_partition_confirm(plan: &ResizePlan, size: &str) -> ConfirmDialogState {
+    let severity = if plan.is_shrink() {
+        ConfirmSeverity::Danger
+    } else {
+        ConfirmSeverity::Warning
+    };
+    let dialog = ConfirmDialogState::new(
+        "Resize Partition",
+        &format!(
+            "Resize {} from {} to {}?",
+            plan.partition.device,
+            format_size(plan.partition.size),
+            format_size(plan.new_size)
+        ),
+        severity,
+        "resize_partition",
+    );
+    plan.preview()
+        .iter()
+        .fold(dialog, |dialog, line| dialog.with_detail(line))
+        .with_detail("Back up important data before resizing")
+        .with_action_data(&format!("{}:{}", plan.partition.device, size))
+}
+
+/// Create a confirmation dialog listing the leftovers a cleanup releases
+pub fn cleanup_confirm(leftovers: &[Leftover]) -> ConfirmDialogState {
+    let dialog = ConfirmDialogState::new(
+        "Clean Up Failed Install",
+        &format!(
+            "Release {} leftover(s) of a failed installation?",
+            leftovers.len()
+        ),
+        ConfirmSeverity::Warning,
+        "cleanup",
+    );
+    leftovers
+        .iter()
+        .fold(dialog, |dialog, leftover| dialog.with_detail(&leftover.to_string()))
+        .with_detail("No data is erased; the installation can be retried afterwards")
+}
+
+/// Create a confirmation dialog for deleting a btrfs snapshot
+pub fn snapshot_delete_confirm(device: &str, number: u32) -> ConfirmDialogState {
+    ConfirmDialogState::new(
+        "Delete Snapshot",
+        &format!("Delete snapshot {} on {}?", number, device),
+        ConfirmSeverity::Danger,
+        "delete_snapshot",
+    )
+    .with_detail("The snapshot and its snapper metadata are removed")
+    .with_detail("It can no longer be rolled back to")
+    .with_action_data(&format!("{}:{}", device, number))
+}
+
+/// Create a confirmation dialog for replacing the root subvolume with a snapshot
+pub fn snapshot_rollback_confirm(device: &str, number: u32) -> ConfirmDialogState {
+    ConfirmDialogState::new(
+        "Roll Back to Snapshot",
+        &format!("Replace the root filesystem on {} with snapshot {}?", device, number),
+        ConfirmSeverity::Danger,
+        "rollback_snapshot",
+    )
+    .with_detail("The current @ is kept as @.broken-<date>")
+    .with_detail("Changes to / since the snapshot are set aside with it")
+    .with_detail("@var and @home are not rolled back")
+    .with_action_data(&format!("{}:{}", device, number))
 }
 
-/// Create a confirmation dialog for installing bootloader
-pub fn bootloader_confirm(bootloader: &str, disk: &str) -> ConfirmDialogState {
+/// Create a confirmation dialog listing a bootloader's problems and their fixes
+///
+/// When nothing is broken it offers to reinstall the bootloader instead.
+pub fn bootloader_repair_confirm(diagnosis: &Diagnosis) -> ConfirmDialogState {
+    let bootloader = diagnosis
+        .bootloader
+        .map_or_else(|| "the bootloader".to_string(), |b| b.to_string());
+    let (fixes, dialog) = if diagnosis.problems.is_empty() {
+        (
+            diagnosis.reinstall(),
+            ConfirmDialogState::new(
+                "Repair Bootloader",
+                &format!("Nothing found broken. Reinstall {} anyway?", bootloader),
+                ConfirmSeverity::Info,
+                "repair_bootloader",
+            ),
+        )
+    } else {
+        (
+            diagnosis.fixes(),
+            ConfirmDialogState::new(
+                "Repair Bootloader",
+                &format!(
+                    "Fix {} problem(s) with {}?",
+                    diagnosis.problems.len(),
+                    bootloader
+                ),
+                ConfirmSeverity::Warning,
+                "repair_bootloader",
+            ),
+        )
+    };
+    let dialog = diagnosis
+        .summary()
+        .iter()
+        .fold(dialog, |dialog, line| dialog.with_detail(line));
+    let dialog = diagnosis.problems.iter().fold(dialog, |dialog, problem| {
+        dialog.with_detail(&format!("Broken: {}", problem))
+    });
+    fixes
+        .iter()
+        .fold(dialog, |dialog, fix| dialog.with_detail(&format!("Fix: {}", fix)))
+        .with_action_data(&serde_json::to_string(&fixes).unwrap_or_default())
+}
+
+/// Create a confirmation dialog previewing an automated install's key values
+///
+/// The path is passed back as action data so the file is loaded and
+/// validated again right before installing.
+pub fn automated_install_confirm(path: &str, config: &InstallationConfig) -> ConfirmDialogState {
     ConfirmDialogState::new(
-        "Install Bootloader",
-        &format!("Install {} to {}?", bootloader, disk),
+        "Automated Install",
+        &format!("Install Arch Linux on {} from this configuration?", config.install_disk),
         ConfirmSeverity::Warning,
-        "install_bootloader",
+        "automated_install",
+    )
+    .with_detail(&format!("File: {}", path))
+    .with_detail(&format!(
+        "Partitioning: {}, {} root, encryption {}",
+        config.partitioning_strategy, config.root_filesystem, config.encryption
+    ))
+    .with_detail(&format!(
+        "Bootloader: {}, kernel: {}",
+        config.bootloader, config.kernel
+    ))
+    .with_detail(&format!(
+        "Hostname: {}, user: {}",
+        config.hostname, config.username
+    ))
+    .with_detail(&format!("Desktop: {}", config.desktop_environment))
+    .with_detail("Partitions on the disk may be erased; back up first")
+    .with_action_data(path)
+}
+
+/// Create a dialog offering to restore a previously saved guided session
+pub fn restore_session_confirm(option_count: usize) -> ConfirmDialogState {
+    ConfirmDialogState::new(
+        "Restore Session",
+        "Restore your previous guided installer configuration?",
+        ConfirmSeverity::Info,
+        "restore_session",
     )
-    .with_detail("The boot sector will be modified")
-    .with_detail("Existing bootloader may be overwritten")
-    .with_action_data(disk)
+    .with_detail(&format!("{} configured option(s) were saved", option_count))
+    .with_detail("Passwords are not saved and must be re-entered")
+    .with_detail("Choosing No keeps the saved session until you change something")
 }
 
-/// Create a confirmation dialog for starting installation
-pub fn start_install_confirm() -> ConfirmDialogState {
+/// Create a dialog confirming that every option goes back to its default
+pub fn reset_all_confirm(modified: usize) -> ConfirmDialogState {
     ConfirmDialogState::new(
-        "Start Installation",
-        "Begin Arch Linux installation?",
+        "Reset All Options",
+        "Restore the default value of every option?",
         ConfirmSeverity::Warning,
-        "start_installation",
+        "reset_all",
+    )
+    .with_detail(&format!(
+        "{} option(s) differ from their defaults",
+        modified
+    ))
+    .with_detail("Disk, usernames and passwords must be entered again")
+}
+
+/// Create a dialog offering to restart as root to run a tool
+pub fn root_required_confirm(tool: &str, launcher: &str) -> ConfirmDialogState {
+    ConfirmDialogState::new(
+        "Root Privileges Required",
+        &format!(
+            "{} needs root. Restart the toolkit with {}?",
+            tool, launcher
+        ),
+        ConfirmSeverity::Info,
+        "restart_as_root",
     )
-    .with_detail("The target disk will be formatted")
-    .with_detail("This process may take several minutes")
-    .with_detail("Do not power off during installation")
+    .with_detail(&format!("{} may ask for your password", launcher))
+    .with_detail(&format!("The toolkit reopens with {} selected", tool))
+    .with_detail("Read-only tools keep working without root")
+    .with_action_data(tool)
 }
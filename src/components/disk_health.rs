@@ -0,0 +1,202 @@
+//! Disk health report component
+//!
+//! Shows parsed SMART data as a colour-coded report with self-test actions.
+
+use crate::theme::Colors;
+use crate::tools::smart::{HealthLevel, SmartReport};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the disk health report
+#[derive(Debug, Clone)]
+pub struct DiskHealthState {
+    /// Device being inspected (e.g. /dev/sda)
+    pub device: String,
+    /// Parsed report, or the error message; None while smartctl is running
+    pub report: Option<Result<SmartReport, String>>,
+    /// Feedback from the last action (self-test started, errors)
+    pub message: Option<String>,
+}
+
+impl DiskHealthState {
+    /// Create a state for a device whose report is still being read
+    pub fn loading(device: &str) -> Self {
+        Self {
+            device: device.to_string(),
+            report: None,
+            message: None,
+        }
+    }
+
+    /// Whether the report is still being read
+    pub fn is_loading(&self) -> bool {
+        self.report.is_none()
+    }
+}
+
+/// Colour used for a health level
+pub fn level_color(level: HealthLevel) -> Color {
+    match level {
+        HealthLevel::Good => Colors::SUCCESS,
+        HealthLevel::Warning => Colors::WARNING,
+        HealthLevel::Critical => Colors::ERROR,
+        HealthLevel::Unknown => Colors::FG_SECONDARY,
+    }
+}
+
+/// Short label for a health level
+fn level_label(level: HealthLevel) -> &'static str {
+    match level {
+        HealthLevel::Good => "HEALTHY",
+        HealthLevel::Warning => "WARNING",
+        HealthLevel::Critical => "CRITICAL",
+        HealthLevel::Unknown => "UNKNOWN",
+    }
+}
+
+/// Disk health report window
+pub struct DiskHealthView;
+
+impl DiskHealthView {
+    /// Render the disk health report
+    pub fn render(f: &mut Frame, state: &DiskHealthState) {
+        let area = f.area();
+
+        // Calculate centered area (80% width, 80% height)
+        let width = (area.width as f32 * 0.8) as u16;
+        let height = (area.height as f32 * 0.8) as u16;
+        let x = (area.width - width) / 2;
+        let y = (area.height - height) / 2;
+        let window_area = Rect::new(x, y, width, height);
+
+        f.render_widget(Clear, window_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Disk Health: {} ", state.device))
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(window_area);
+        f.render_widget(block, window_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Min(3),    // Report
+                Constraint::Length(2), // Action feedback
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        let lines = match state.report {
+            None => vec![Line::from(Span::styled(
+                format!("Reading SMART data from {}...", state.device),
+                Style::default().fg(Colors::FG_SECONDARY),
+            ))],
+            Some(Err(ref error)) => vec![
+                Line::from(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Colors::ERROR),
+                )),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Press f to run the full reliability test script instead.",
+                    Style::default().fg(Colors::FG_SECONDARY),
+                )),
+            ],
+            Some(Ok(ref report)) => Self::report_lines(report),
+        };
+        f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+
+        if let Some(ref message) = state.message {
+            let message = Paragraph::new(message.clone())
+                .style(Style::default().fg(Colors::INFO_LIGHT))
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, chunks[1]);
+        }
+
+        let help = Paragraph::new(
+            "s Short test | l Long test | r Refresh | f Full reliability test | Esc Back",
+        )
+        .style(Style::default().fg(Colors::FG_MUTED))
+        .alignment(Alignment::Center);
+        f.render_widget(help, chunks[2]);
+    }
+
+    /// Build the report body: drive identity, overall verdict and metric rows
+    fn report_lines(report: &SmartReport) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(Colors::FG_MUTED);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled(format!("{:<22}", "Model"), muted),
+                Span::raw(
+                    report
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(format!("{:<22}", "Serial"), muted),
+                Span::raw(
+                    report
+                        .serial
+                        .clone()
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(format!("{:<22}", "Type"), muted),
+                Span::raw(report.kind.to_string()),
+            ]),
+            Line::from(""),
+        ];
+
+        let overall = report.overall();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<22}", "Verdict"), muted),
+            Span::styled(
+                format!(" {} ", level_label(overall)),
+                Style::default()
+                    .fg(Colors::SELECTED_FG)
+                    .bg(level_color(overall))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        lines.push(Line::from(""));
+
+        for row in report.rows() {
+            lines.push(Line::from(vec![
+                Span::styled("● ", Style::default().fg(level_color(row.level))),
+                Span::styled(
+                    format!("{:<20}", row.label),
+                    Style::default().fg(Colors::FG_PRIMARY),
+                ),
+                Span::styled(row.value, Style::default().fg(level_color(row.level))),
+            ]));
+        }
+
+        if !report.messages.is_empty() {
+            lines.push(Line::from(""));
+            for message in &report.messages {
+                lines.push(Line::from(Span::styled(
+                    format!("smartctl: {}", message),
+                    Style::default().fg(Colors::WARNING),
+                )));
+            }
+        }
+
+        lines
+    }
+}
@@ -46,10 +46,103 @@ impl Default for FloatingWindowConfig {
     }
 }
 
+/// Where the floating output window sits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowDock {
+    /// Centered at the size of the layout
+    #[default]
+    Centered,
+    /// The whole screen
+    Maximized,
+    /// The bottom half of the screen, leaving the screen behind it readable
+    Bottom,
+}
+
+/// A change to the floating output window's size or place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowChange {
+    Grow,
+    Shrink,
+    /// Maximize, or restore a maximized window
+    Maximize,
+    /// Dock to the bottom half, or restore a docked window
+    Dock,
+}
+
+/// Size and place of the floating output window
+///
+/// Kept in the app state rather than with the output, so the window opens
+/// the way it was left for the next tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatingLayout {
+    pub dock: WindowDock,
+    pub width_percent: u16,
+    pub height_percent: u16,
+}
+
+impl Default for FloatingLayout {
+    fn default() -> Self {
+        Self {
+            dock: WindowDock::Centered,
+            width_percent: 80,
+            height_percent: 70,
+        }
+    }
+}
+
+impl FloatingLayout {
+    /// Percentage points a window grows or shrinks by
+    const STEP: u16 = 10;
+    /// Smallest size, as a percentage of the screen
+    const MIN_PERCENT: u16 = 30;
+
+    /// Apply a change; resizing a maximized or docked window centers it again
+    pub fn apply(&mut self, change: WindowChange) {
+        let resize = |percent: u16, grow: bool| {
+            if grow {
+                (percent + Self::STEP).min(100)
+            } else {
+                percent.saturating_sub(Self::STEP).max(Self::MIN_PERCENT)
+            }
+        };
+        match change {
+            WindowChange::Grow | WindowChange::Shrink => {
+                let grow = change == WindowChange::Grow;
+                self.dock = WindowDock::Centered;
+                self.width_percent = resize(self.width_percent, grow);
+                self.height_percent = resize(self.height_percent, grow);
+            }
+            WindowChange::Maximize => self.toggle(WindowDock::Maximized),
+            WindowChange::Dock => self.toggle(WindowDock::Bottom),
+        }
+    }
+
+    fn toggle(&mut self, dock: WindowDock) {
+        self.dock = if self.dock == dock {
+            WindowDock::Centered
+        } else {
+            dock
+        };
+    }
+
+    /// Short description for the status bar, e.g. "Output docked to the bottom"
+    pub fn describe(&self) -> String {
+        match self.dock {
+            WindowDock::Centered => format!(
+                "Output window at {}% x {}%",
+                self.width_percent, self.height_percent
+            ),
+            WindowDock::Maximized => "Output window maximized".to_string(),
+            WindowDock::Bottom => "Output window docked to the bottom".to_string(),
+        }
+    }
+}
+
 /// Floating window component
 pub struct FloatingWindow {
     config: FloatingWindowConfig,
     scroll_state: ScrollState,
+    dock: WindowDock,
 }
 
 impl FloatingWindow {
@@ -58,6 +151,26 @@ impl FloatingWindow {
         Self {
             config,
             scroll_state: ScrollState::new(0, 10),
+            dock: WindowDock::Centered,
+        }
+    }
+
+    /// Create a floating window sized and placed by `layout`
+    ///
+    /// Unlike other windows it may grow past the default maximum size, up to
+    /// the whole screen.
+    pub fn with_layout(title: &str, layout: FloatingLayout) -> Self {
+        let config = FloatingWindowConfig {
+            title: title.to_string(),
+            width_percent: layout.width_percent,
+            height_percent: layout.height_percent,
+            max_width: u16::MAX,
+            max_height: u16::MAX,
+            ..Default::default()
+        };
+        Self {
+            dock: layout.dock,
+            ..Self::new(config)
         }
     }
 
@@ -70,8 +183,17 @@ impl FloatingWindow {
         Self::new(config)
     }
 
-    /// Calculate the centered area for this window
+    /// Calculate the area for this window: centered unless docked or maximized
     pub fn calculate_area(&self, parent: Rect) -> Rect {
+        match self.dock {
+            WindowDock::Centered => {}
+            WindowDock::Maximized => return parent,
+            WindowDock::Bottom => {
+                let top = parent.height / 2;
+                return Rect::new(parent.x, parent.y + top, parent.width, parent.height - top);
+            }
+        }
+
         let width = ((parent.width as u32 * self.config.width_percent as u32) / 100) as u16;
         let height = ((parent.height as u32 * self.config.height_percent as u32) / 100) as u16;
 
@@ -214,7 +336,7 @@ impl FloatingWindow {
 
         // Render content
         let visible_height = inner_area.height as usize;
-        let start = self.scroll_state.offset;
+        let start = self.scroll_state.offset.min(content.len().saturating_sub(1));
         let end = (start + visible_height).min(content.len());
 
         let visible_lines: Vec<Line> = content[start..end].to_vec();
@@ -257,7 +379,7 @@ impl FloatingWindow {
             .constraints([
                 Constraint::Length(3), // Progress bar
                 Constraint::Min(1),    // Content
-                Constraint::Length(1), // Status
+                Constraint::Length(2), // Status above the bottom border
             ])
             .split(area);
 
@@ -429,3 +551,50 @@ impl FloatingOutputState {
         self.progress = Some(100);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_resizes_within_bounds() {
+        let mut layout = FloatingLayout::default();
+        for _ in 0..5 {
+            layout.apply(WindowChange::Grow);
+        }
+        assert_eq!((layout.width_percent, layout.height_percent), (100, 100));
+        for _ in 0..10 {
+            layout.apply(WindowChange::Shrink);
+        }
+        assert_eq!((layout.width_percent, layout.height_percent), (30, 30));
+    }
+
+    #[test]
+    fn test_dock_and_maximize_toggle() {
+        let parent = Rect::new(0, 0, 100, 41);
+        let mut layout = FloatingLayout::default();
+        layout.apply(WindowChange::Dock);
+        assert_eq!(
+            FloatingWindow::with_layout("Output", layout).calculate_area(parent),
+            Rect::new(0, 20, 100, 21)
+        );
+
+        layout.apply(WindowChange::Maximize);
+        assert_eq!(layout.dock, WindowDock::Maximized);
+        assert_eq!(
+            FloatingWindow::with_layout("Output", layout).calculate_area(parent),
+            parent
+        );
+        layout.apply(WindowChange::Maximize);
+        assert_eq!(layout.dock, WindowDock::Centered);
+
+        // Resizing a docked window centers it at the new size
+        layout.apply(WindowChange::Dock);
+        layout.apply(WindowChange::Grow);
+        assert_eq!(layout.dock, WindowDock::Centered);
+        assert_eq!(
+            FloatingWindow::with_layout("Output", layout).calculate_area(parent),
+            Rect::new(5, 4, 90, 32)
+        );
+    }
+}
@@ -1,13 +1,17 @@
 //! Help overlay component
 //!
-//! Displays context-sensitive help using a floating window.
+//! Displays context-sensitive help using a floating window: the keybindings
+//! of the current mode, or the documentation page of a guided installer option.
 
 #![allow(dead_code)]
 
 use super::floating_window::{FloatingWindow, FloatingWindowConfig};
 use super::keybindings::{HelpSection, KeybindingContext};
-use crate::app::AppMode;
+use crate::app::{AppMode, AppState};
+use crate::config::{options, ConfigOption};
+use crate::help;
 use crate::theme::Colors;
+use crate::wiki::Article;
 use ratatui::{
     layout::Rect,
     style::{Modifier, Style},
@@ -19,13 +23,80 @@ use ratatui::{
 pub struct HelpOverlay {
     window: FloatingWindow,
     content: Vec<Line<'static>>,
+    footer: &'static str,
 }
 
+/// Footer of the keybinding help
+const KEYS_FOOTER: &str = "Press ? or Esc to close";
+/// Footer of an option page
+const OPTION_FOOTER: &str = "←/→ Options · ↑/↓ Scroll · W Wiki · Tab Keys · Esc Close";
+/// Footer of an Arch Wiki article
+const WIKI_FOOTER: &str = "←/→ Options · ↑/↓ Scroll · W Back to option · Esc Close";
+
 impl HelpOverlay {
     /// Create a new help overlay for the given mode
     pub fn new(mode: &AppMode, keybinding_ctx: &KeybindingContext) -> Self {
-        let config = FloatingWindowConfig {
-            title: "Help".to_string(),
+        let sections = keybinding_ctx.get_help_content(mode);
+        let content = Self::build_content(&sections, mode);
+
+        Self {
+            window: FloatingWindow::new(Self::window_config("Help".to_string())),
+            content,
+            footer: KEYS_FOOTER,
+        }
+    }
+
+    /// Create the documentation page of a configuration option
+    ///
+    /// `index` and `total` place the option within the guided installer list.
+    pub fn for_option(option: &ConfigOption, index: usize, total: usize) -> Self {
+        let title = format!("Help: Option {}/{}", index + 1, total);
+
+        Self {
+            window: FloatingWindow::new(Self::window_config(title)),
+            content: Self::build_option_content(option),
+            footer: OPTION_FOOTER,
+        }
+    }
+
+    /// Create the view of an option's Arch Wiki article
+    pub fn for_article(article: &Article) -> Self {
+        Self {
+            window: FloatingWindow::new(Self::window_config("Arch Wiki".to_string())),
+            content: help::render_markdown(&article.to_markdown()),
+            footer: WIKI_FOOTER,
+        }
+    }
+
+    /// Create the help overlay the state asks for, scrolled to its position
+    pub fn for_state(state: &AppState, keybinding_ctx: &KeybindingContext) -> Self {
+        let total = state.guided.config.options.len();
+        let page = state.help_page.and_then(|index| {
+            state
+                .guided
+                .config
+                .options
+                .get(index)
+                .map(|option| (index, option))
+        });
+        let mut overlay = match (page, &state.help_wiki) {
+            (Some(_), Some(article)) => Self::for_article(article),
+            (Some((index, option)), None) => Self::for_option(option, index, total),
+            (None, _) => Self::new(&state.mode, keybinding_ctx),
+        };
+        overlay.window.set_scroll_offset(state.help_scroll);
+        overlay
+    }
+
+    /// Number of content lines, the limit for scrolling
+    pub fn content_len(&self) -> usize {
+        self.content.len()
+    }
+
+    /// Window configuration shared by all help views
+    fn window_config(title: String) -> FloatingWindowConfig {
+        FloatingWindowConfig {
+            title,
             width_percent: 60,
             height_percent: 70,
             min_width: 50,
@@ -35,15 +106,54 @@ impl HelpOverlay {
             has_border: true,
             scrollable: true,
             show_scroll_indicator: false,
-        };
+        }
+    }
 
-        let sections = keybinding_ctx.get_help_content(mode);
-        let content = Self::build_content(&sections, mode);
+    /// Build the documentation page of an option
+    fn build_option_content(option: &ConfigOption) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line<'static>> = Vec::new();
 
-        Self {
-            window: FloatingWindow::new(config),
-            content,
+        lines.push(Line::from(vec![Span::styled(
+            format!("  {}  ", option.name),
+            Style::default()
+                .fg(Colors::PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )]));
+        lines.push(Line::from(""));
+
+        let value = |label: &str, value: &str| {
+            let shown = if value.is_empty() { "(not set)" } else { value };
+            Line::from(vec![
+                Span::styled(format!("{:<10}", label), Style::default().fg(Colors::FG_MUTED)),
+                Span::styled(shown.to_string(), Style::default().fg(Colors::SECONDARY)),
+            ])
+        };
+        if options::is_secret(&option.name) {
+            let set = if option.value.is_empty() { "" } else { "********" };
+            lines.push(value("Current:", set));
+        } else {
+            let shown = |value: &str| options::shown_value(&option.name, value);
+            lines.push(value("Current:", &shown(&option.value)));
+            lines.push(value("Default:", &shown(&option.default_value)));
         }
+        lines.push(Line::from(""));
+
+        match help::option_help(&option.name) {
+            Some(page) => {
+                lines.extend(help::render_markdown(page.text));
+                lines.push(Line::from(""));
+                lines.push(Line::from(vec![
+                    Span::styled("Arch Wiki: ", Style::default().fg(Colors::FG_MUTED)),
+                    Span::styled(page.wiki_url(), Style::default().fg(Colors::PRIMARY)),
+                ]));
+            }
+            None => lines.push(Line::from(Span::styled(
+                option.description.clone(),
+                Style::default().fg(Colors::FG_PRIMARY),
+            ))),
+        }
+
+        lines
     }
 
     /// Build the help content from sections
@@ -63,12 +173,14 @@ impl HelpOverlay {
         let mode_name = match mode {
             AppMode::MainMenu => "Main Menu",
             AppMode::GuidedInstaller => "Guided Installer",
+            AppMode::Summary => "Installation Summary",
             AppMode::AutomatedInstall => "Automated Install",
             AppMode::ToolsMenu => "Tools Menu",
             AppMode::DiskTools => "Disk Tools",
             AppMode::SystemTools => "System Tools",
             AppMode::UserTools => "User Tools",
             AppMode::NetworkTools => "Network Tools",
+            AppMode::SnapshotTools => "Snapshot Tools",
             AppMode::ToolDialog => "Tool Configuration",
             AppMode::ToolExecution => "Tool Execution",
             AppMode::Installation => "Installation",
@@ -77,6 +189,12 @@ impl HelpOverlay {
             AppMode::FloatingOutput => "Output View",
             AppMode::FileBrowser => "File Browser",
             AppMode::ConfirmDialog => "Confirmation",
+            AppMode::DiskHealth => "Disk Health",
+            AppMode::ServiceManager => "Service Manager",
+            AppMode::MountManager => "Mount Manager",
+            AppMode::RecoveryDialog => "Recovery",
+            AppMode::PartitionCheck => "Partition Check",
+            AppMode::PartitionMounts => "Partition Mounts",
         };
         lines.push(Line::from(vec![
             Span::styled("Current: ", Style::default().fg(Colors::FG_MUTED)),
@@ -117,7 +235,7 @@ impl HelpOverlay {
         // Footer
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
-            "Press ? or Esc to close",
+            KEYS_FOOTER,
             Style::default().fg(Colors::FG_MUTED),
         )]));
 
@@ -130,7 +248,7 @@ impl HelpOverlay {
             f,
             parent,
             &self.content,
-            Some("Press ? or Esc to close"),
+            Some(self.footer),
         );
     }
 
@@ -0,0 +1,431 @@
+//! Installation summary component
+//!
+//! Last screen of the guided installer: every option's final value grouped by
+//! category, the destructive consequences spelled out, and a typed
+//! confirmation (the disk name or ERASE) before anything is touched.
+
+use crate::config::{options, Configuration};
+use crate::hardware::DiskInfo;
+use crate::mount_plan::MountPlan;
+use crate::multiboot;
+use crate::theme::Colors;
+use crate::types::PartitionScheme;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Word that confirms the installation regardless of the disk
+pub const CONFIRM_WORD: &str = "ERASE";
+
+/// Values of one category
+#[derive(Debug, Clone)]
+pub struct SummarySection {
+    pub title: String,
+    /// Option name and the value that will be installed
+    pub rows: Vec<(String, String)>,
+}
+
+/// State of the summary screen
+#[derive(Debug, Clone)]
+pub struct InstallSummaryState {
+    pub sections: Vec<SummarySection>,
+    /// What will be destroyed or made unrecoverable
+    pub warnings: Vec<String>,
+    /// Target disks, as device paths
+    pub disks: Vec<String>,
+    /// Confirmation typed so far
+    pub typed: String,
+    /// First visible line of the settings list
+    pub scroll: usize,
+    /// Shown when Enter was pressed without a valid confirmation
+    pub error: Option<String>,
+}
+
+impl InstallSummaryState {
+    /// Summarise a configuration; `disks` describes the detected disks
+    pub fn new(config: &Configuration, disks: &[DiskInfo]) -> Self {
+        let sections = config
+            .by_category()
+            .into_iter()
+            .map(|(title, options)| SummarySection {
+                title: title.to_string(),
+                rows: options
+                    .into_iter()
+                    .map(|option| {
+                        let value = option.get_value();
+                        let value = if value.is_empty() {
+                            "(not set)".to_string()
+                        } else {
+                            options::shown_value(&option.name, &value)
+                        };
+                        (option.name.clone(), value)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let value = |name: &str| {
+            config
+                .options
+                .iter()
+                .find(|option| option.name == name)
+                .map(|option| option.get_value())
+                .unwrap_or_default()
+        };
+        let targets: Vec<String> = value("Disk")
+            .split(',')
+            .map(str::trim)
+            .filter(|disk| !disk.is_empty())
+            .map(str::to_string)
+            .collect();
+        let scheme: PartitionScheme = value("Partitioning Strategy").parse().unwrap_or_default();
+        let policy = value("Existing OS").parse().unwrap_or_default();
+        let efi_partition = value("EFI Partition");
+
+        let mut warnings = Vec::new();
+        for disk in &targets {
+            let info = disks.iter().find(|d| &d.path == disk);
+            let what = match info {
+                Some(info) if !info.model.is_empty() => {
+                    format!("{} ({}, {})", disk, info.model, info.size_display())
+                }
+                _ => disk.clone(),
+            };
+            let kept = multiboot::keeps_partitions(policy, scheme, &efi_partition, disk);
+            if scheme == PartitionScheme::Manual {
+                warnings.push(format!(
+                    "{} will be repartitioned by hand: anything you do not keep is lost",
+                    what
+                ));
+            } else if scheme == PartitionScheme::Existing {
+                warnings.push(format!(
+                    "{} keeps its partition table: only the partitions below are touched",
+                    what
+                ));
+            } else if kept {
+                warnings.push(format!(
+                    "{} keeps its partitions: Arch is installed into its unallocated space",
+                    what
+                ));
+            } else {
+                warnings.push(format!("{} will be ERASED: all partitions and data", what));
+            }
+            if let Some(info) = info.filter(|d| !d.existing_os.is_empty()) {
+                let systems = info.existing_os.join(", ");
+                warnings.push(if kept {
+                    format!("{} on {} is kept and stays bootable", systems, disk)
+                } else {
+                    format!("{} currently holds {}", disk, systems)
+                });
+            }
+        }
+        if scheme == PartitionScheme::Existing {
+            let plan = MountPlan::parse(&value("Partition Mounts")).unwrap_or_default();
+            for assignment in &plan.assignments {
+                warnings.push(match assignment.format {
+                    Some(ref format) => format!(
+                        "{} will be FORMATTED {} for {}",
+                        assignment.device, format, assignment.mountpoint
+                    ),
+                    None => format!(
+                        "{} keeps its data and is mounted at {}",
+                        assignment.device, assignment.mountpoint
+                    ),
+                });
+            }
+        }
+        if scheme.uses_encryption() || value("Encryption").eq_ignore_ascii_case("yes") {
+            warnings.push(
+                "Data is unrecoverable if the encryption password is forgotten".to_string(),
+            );
+        }
+
+        Self {
+            sections,
+            warnings,
+            disks: targets,
+            typed: String::new(),
+            scroll: 0,
+            error: None,
+        }
+    }
+
+    /// Text the user has to type, as shown in the prompt
+    pub fn expected(&self) -> String {
+        match self.disks.as_slice() {
+            [disk] => format!("{} or {}", disk_name(disk), CONFIRM_WORD),
+            _ => CONFIRM_WORD.to_string(),
+        }
+    }
+
+    /// Whether the typed text confirms the installation
+    ///
+    /// ERASE always does; the disk's name (`sda` or `/dev/sda`) does when
+    /// there is a single target disk.
+    pub fn is_confirmed(&self) -> bool {
+        let typed = self.typed.trim();
+        typed == CONFIRM_WORD
+            || matches!(self.disks.as_slice(), [disk] if typed == disk || typed == disk_name(disk))
+    }
+
+    /// Number of lines in the settings list
+    pub fn content_len(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|section| section.rows.len() + 2)
+            .sum()
+    }
+}
+
+/// Device name without /dev/
+fn disk_name(path: &str) -> &str {
+    path.strip_prefix("/dev/").unwrap_or(path)
+}
+
+/// Installation summary screen
+pub struct InstallSummaryView;
+
+impl InstallSummaryView {
+    /// Render the summary over `area`
+    pub fn render(f: &mut Frame, area: Rect, state: &InstallSummaryState) {
+        f.render_widget(Clear, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Installation Summary ")
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(state.warnings.len() as u16 + 2), // Consequences
+                Constraint::Min(3),                                  // Settings
+                Constraint::Length(3),                               // Confirmation
+                Constraint::Length(1),                               // Help text
+            ])
+            .split(inner);
+
+        let warnings: Vec<Line> = state
+            .warnings
+            .iter()
+            .map(|warning| {
+                Line::from(Span::styled(
+                    format!("⚠ {}", warning),
+                    Style::default()
+                        .fg(Colors::ERROR)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            })
+            .collect();
+        let warnings = Paragraph::new(warnings)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Consequences ")
+                    .border_style(Style::default().fg(Colors::SEVERITY_DANGER)),
+            )
+            .style(Style::default().bg(Colors::BG_DANGER))
+            .wrap(Wrap { trim: true });
+        f.render_widget(warnings, chunks[0]);
+
+        let settings = Paragraph::new(Self::settings_lines(state)).scroll((state.scroll as u16, 0));
+        f.render_widget(settings, chunks[1]);
+
+        let (border, message) = match state.error {
+            Some(ref error) => (Colors::ERROR, error.clone()),
+            None => (
+                Colors::WARNING,
+                format!("Type {} to start the installation", state.expected()),
+            ),
+        };
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(Colors::WARNING)),
+            Span::styled(state.typed.clone(), Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("_", Style::default().fg(Colors::FG_MUTED)),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", message))
+                .border_style(Style::default().fg(border)),
+        );
+        f.render_widget(prompt, chunks[2]);
+
+        let help = Paragraph::new("Enter Start | ↑↓ Scroll | Esc Back to settings")
+            .style(Style::default().fg(Colors::FG_MUTED))
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[3]);
+    }
+
+    /// Category headings followed by aligned name/value rows
+    fn settings_lines(state: &InstallSummaryState) -> Vec<Line<'static>> {
+        let mut lines = Vec::with_capacity(state.content_len());
+        for section in &state.sections {
+            lines.push(Line::from(Span::styled(
+                section.title.clone(),
+                Style::default()
+                    .fg(Colors::CATEGORY)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            for (name, value) in &section.rows {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:<28}", name),
+                        Style::default().fg(Colors::FG_SECONDARY),
+                    ),
+                    Span::styled(value.clone(), Style::default().fg(Colors::FG_PRIMARY)),
+                ]));
+            }
+            lines.push(Line::from(""));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::DiskKind;
+
+    fn config_with(values: &[(&str, &str)]) -> Configuration {
+        let mut config = Configuration::default();
+        for (name, value) in values {
+            if let Some(option) = config.options.iter_mut().find(|o| o.name == *name) {
+                option.value = value.to_string();
+            }
+        }
+        config
+    }
+
+    fn disk(path: &str, model: &str, existing_os: &[&str]) -> DiskInfo {
+        DiskInfo {
+            path: path.to_string(),
+            size: 512_110_190_592,
+            model: model.to_string(),
+            transport: "nvme".to_string(),
+            kind: DiskKind::Nvme,
+            partitions: Vec::new(),
+            existing_os: existing_os.iter().map(|os| os.to_string()).collect(),
+            mounted: false,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_summary_warns_about_erased_disk() {
+        let config = config_with(&[
+            ("Disk", "/dev/nvme0n1"),
+            ("Partitioning Strategy", "auto_luks_lvm"),
+            ("User Password", "secret"),
+        ]);
+        let summary =
+            InstallSummaryState::new(&config, &[disk("/dev/nvme0n1", "Samsung 970", &["Windows"])]);
+
+        assert_eq!(summary.disks, vec!["/dev/nvme0n1"]);
+        assert!(summary.warnings[0].starts_with("/dev/nvme0n1 (Samsung 970, "));
+        assert!(summary.warnings[0].contains("will be ERASED"));
+        assert_eq!(summary.warnings[1], "/dev/nvme0n1 currently holds Windows");
+        assert!(summary.warnings[2].contains("encryption password"));
+
+        let users = summary
+            .sections
+            .iter()
+            .find(|s| s.title == "System and Users")
+            .unwrap();
+        assert!(users
+            .rows
+            .contains(&("User Password".to_string(), "********".to_string())));
+        assert!(users
+            .rows
+            .contains(&("Root Password".to_string(), "(not set)".to_string())));
+    }
+
+    #[test]
+    fn test_manual_partitioning_is_not_erased() {
+        let config = config_with(&[("Disk", "/dev/sda"), ("Partitioning Strategy", "manual")]);
+        let summary = InstallSummaryState::new(&config, &[]);
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].contains("repartitioned by hand"));
+    }
+
+    #[test]
+    fn test_existing_partitions_list_what_is_formatted() {
+        let config = config_with(&[
+            ("Disk", "/dev/sda"),
+            ("Partitioning Strategy", "existing"),
+            ("Partition Mounts", "/dev/sda2=/:ext4,/dev/sda3=/home"),
+        ]);
+        let summary = InstallSummaryState::new(&config, &[]);
+        assert_eq!(
+            summary.warnings,
+            [
+                "/dev/sda keeps its partition table: only the partitions below are touched",
+                "/dev/sda2 will be FORMATTED ext4 for /",
+                "/dev/sda3 keeps its data and is mounted at /home",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reused_esp_keeps_install_disk() {
+        let config = config_with(&[
+            ("Disk", "/dev/nvme0n1"),
+            ("EFI Partition", "/dev/nvme0n1p1"),
+        ]);
+        let summary = InstallSummaryState::new(&config, &[]);
+        assert!(summary.warnings[0].contains("keeps its partitions"));
+
+        // An ESP on another disk does not save the install disk
+        let config = config_with(&[("Disk", "/dev/sda"), ("EFI Partition", "/dev/nvme0n1p1")]);
+        let summary = InstallSummaryState::new(&config, &[]);
+        assert!(summary.warnings[0].contains("will be ERASED"));
+    }
+
+    #[test]
+    fn test_alongside_keeps_existing_systems() {
+        let config = config_with(&[("Disk", "/dev/nvme0n1"), ("Existing OS", "alongside")]);
+        let disks = [disk("/dev/nvme0n1", "", &["Windows Boot Manager"])];
+        let summary = InstallSummaryState::new(&config, &disks);
+        assert!(summary.warnings[0].contains("keeps its partitions"));
+        assert_eq!(
+            summary.warnings[1],
+            "Windows Boot Manager on /dev/nvme0n1 is kept and stays bootable"
+        );
+    }
+
+    #[test]
+    fn test_confirmation_requires_disk_name_or_erase() {
+        let config = config_with(&[("Disk", "/dev/sda")]);
+        let mut summary = InstallSummaryState::new(&config, &[]);
+        assert_eq!(summary.expected(), "sda or ERASE");
+        assert!(!summary.is_confirmed());
+
+        for typed in ["sda", "/dev/sda", "ERASE", " ERASE "] {
+            summary.typed = typed.to_string();
+            assert!(summary.is_confirmed(), "{:?} should confirm", typed);
+        }
+        for typed in ["sdb", "erase", "yes"] {
+            summary.typed = typed.to_string();
+            assert!(!summary.is_confirmed(), "{:?} should not confirm", typed);
+        }
+
+        // With several disks only ERASE is unambiguous
+        let config = config_with(&[("Disk", "/dev/sda,/dev/sdb")]);
+        let mut summary = InstallSummaryState::new(&config, &[]);
+        assert_eq!(summary.expected(), "ERASE");
+        summary.typed = "sda".to_string();
+        assert!(!summary.is_confirmed());
+    }
+}
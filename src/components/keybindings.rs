@@ -5,6 +5,7 @@
 #![allow(dead_code)]
 
 use crate::app::AppMode;
+use crate::serial;
 use crossterm::event::{KeyCode, KeyModifiers};
 use std::collections::HashMap;
 
@@ -22,6 +23,8 @@ pub enum KeyAction {
     Quit,
     Help,
     StartInstall,
+    Reset,
+    ResetAll,
     Confirm,
     Cancel,
     Toggle,
@@ -29,6 +32,38 @@ pub enum KeyAction {
     ScrollDown,
     Dismiss,
     ExitTerminal,
+    SelfTestShort,
+    SelfTestLong,
+    Refresh,
+    FullTest,
+    Search,
+    NextMatch,
+    Filter,
+    Retry,
+    Skip,
+    Shell,
+    ExportLogs,
+    Abort,
+    SaveSummary,
+    Grow,
+    Maximize,
+    Dock,
+    KillCommand,
+    EnableUnit,
+    DisableUnit,
+    StartUnit,
+    StopUnit,
+    MaskUnit,
+    UnmaskUnit,
+    SwitchTarget,
+    Mount,
+    MountAt,
+    Unmount,
+    AssignMountpoint,
+    ToggleFormat,
+    Wizard,
+    RunInteractive,
+    OpenShell,
 }
 
 /// A keybinding definition
@@ -77,6 +112,8 @@ pub struct KeybindingContext {
     mode_bindings: HashMap<AppMode, Vec<Keybinding>>,
     /// Global keybindings (available in all modes)
     global_bindings: Vec<Keybinding>,
+    /// List the control-key alternatives of a serial console in the help
+    serial: bool,
 }
 
 impl Default for KeybindingContext {
@@ -91,6 +128,7 @@ impl KeybindingContext {
         let mut ctx = Self {
             mode_bindings: HashMap::new(),
             global_bindings: Vec::new(),
+            serial: false,
         };
         ctx.register_defaults();
         ctx
@@ -101,7 +139,15 @@ impl KeybindingContext {
         // Global bindings (available everywhere except EmbeddedTerminal)
         self.global_bindings = vec![
             Keybinding::new(KeyCode::Char('?'), KeyAction::Help, "?", "Help"),
+            Keybinding::new(KeyCode::F(1), KeyAction::Help, "F1", "Option help"),
             Keybinding::new(KeyCode::Char('q'), KeyAction::Quit, "Q", "Quit"),
+            Keybinding::with_modifiers(
+                KeyCode::Char('t'),
+                KeyModifiers::CONTROL,
+                KeyAction::OpenShell,
+                "Ctrl+T",
+                "Shell (live system, /mnt or chroot)",
+            ),
         ];
 
         // Main Menu
@@ -124,8 +170,13 @@ impl KeybindingContext {
                 Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
                 Keybinding::new(KeyCode::Home, KeyAction::Home, "Home", "Go to first"),
                 Keybinding::new(KeyCode::End, KeyAction::End, "End", "Go to last"),
+                Keybinding::new(KeyCode::Left, KeyAction::NavigateUp, "Left", "Previous wizard step"),
+                Keybinding::new(KeyCode::Right, KeyAction::NavigateDown, "Right", "Next wizard step"),
                 Keybinding::new(KeyCode::Enter, KeyAction::Select, "Enter", "Configure"),
+                Keybinding::new(KeyCode::Char('w'), KeyAction::Wizard, "W", "Wizard"),
                 Keybinding::new(KeyCode::Char(' '), KeyAction::StartInstall, "Space", "Start install"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Reset, "r", "Reset option to default"),
+                Keybinding::new(KeyCode::Char('R'), KeyAction::ResetAll, "R", "Reset all options"),
                 Keybinding::new(KeyCode::Char('b'), KeyAction::Back, "B", "Back"),
             ],
         );
@@ -138,16 +189,30 @@ impl KeybindingContext {
             Keybinding::new(KeyCode::Char('b'), KeyAction::Back, "B", "Back"),
         ];
 
+        // The submenus list tools, some of which can run interactively
+        let mut tool_list_bindings = tools_bindings.clone();
+        tool_list_bindings.insert(
+            3,
+            Keybinding::new(
+                KeyCode::Char('i'),
+                KeyAction::RunInteractive,
+                "I",
+                "Interactive",
+            ),
+        );
+
         self.mode_bindings
             .insert(AppMode::ToolsMenu, tools_bindings.clone());
         self.mode_bindings
-            .insert(AppMode::DiskTools, tools_bindings.clone());
+            .insert(AppMode::DiskTools, tool_list_bindings.clone());
         self.mode_bindings
-            .insert(AppMode::SystemTools, tools_bindings.clone());
+            .insert(AppMode::SystemTools, tool_list_bindings.clone());
         self.mode_bindings
-            .insert(AppMode::UserTools, tools_bindings.clone());
+            .insert(AppMode::UserTools, tool_list_bindings.clone());
         self.mode_bindings
-            .insert(AppMode::NetworkTools, tools_bindings.clone());
+            .insert(AppMode::NetworkTools, tool_list_bindings.clone());
+        self.mode_bindings
+            .insert(AppMode::SnapshotTools, tool_list_bindings);
 
         // Automated Install
         self.mode_bindings.insert(
@@ -158,12 +223,74 @@ impl KeybindingContext {
             ],
         );
 
+        // Installation Summary
+        self.mode_bindings.insert(
+            AppMode::Summary,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::ScrollUp, "Up", "Scroll up"),
+                Keybinding::new(KeyCode::Down, KeyAction::ScrollDown, "Down", "Scroll down"),
+                Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
+                Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Enter, KeyAction::Confirm, "Enter", "Start install"),
+                Keybinding::new(KeyCode::Esc, KeyAction::Back, "Esc", "Back to settings"),
+            ],
+        );
+
+        // Partition Mounts editor
+        self.mode_bindings.insert(
+            AppMode::PartitionMounts,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::NavigateUp, "Up", "Previous partition"),
+                Keybinding::new(
+                    KeyCode::Down,
+                    KeyAction::NavigateDown,
+                    "Down",
+                    "Next partition",
+                ),
+                Keybinding::new(
+                    KeyCode::Char('m'),
+                    KeyAction::AssignMountpoint,
+                    "M",
+                    "Next mountpoint",
+                ),
+                Keybinding::new(
+                    KeyCode::Char('f'),
+                    KeyAction::ToggleFormat,
+                    "F",
+                    "Keep data or format",
+                ),
+                Keybinding::new(KeyCode::Enter, KeyAction::Confirm, "Enter", "Save"),
+                Keybinding::new(KeyCode::Esc, KeyAction::Cancel, "Esc", "Discard changes"),
+            ],
+        );
+
+        // Manual partitioning check
+        self.mode_bindings.insert(
+            AppMode::PartitionCheck,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::ScrollUp, "Up", "Scroll up"),
+                Keybinding::new(KeyCode::Down, KeyAction::ScrollDown, "Down", "Scroll down"),
+                Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
+                Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Refresh, "R", "Check again"),
+                Keybinding::new(KeyCode::Esc, KeyAction::Back, "Esc", "Back to settings"),
+            ],
+        );
+
         // Tool Dialog
         self.mode_bindings.insert(
             AppMode::ToolDialog,
             vec![
                 Keybinding::new(KeyCode::Up, KeyAction::NavigateUp, "Up", "Previous field"),
                 Keybinding::new(KeyCode::Down, KeyAction::NavigateDown, "Down", "Next field"),
+                Keybinding::new(KeyCode::Tab, KeyAction::NavigateDown, "Tab", "Next field"),
+                Keybinding::new(
+                    KeyCode::BackTab,
+                    KeyAction::NavigateUp,
+                    "Shift+Tab",
+                    "Previous field",
+                ),
+                Keybinding::new(KeyCode::Left, KeyAction::Toggle, "Left/Right", "Change"),
                 Keybinding::new(KeyCode::Enter, KeyAction::Confirm, "Enter", "Confirm"),
                 Keybinding::new(KeyCode::Esc, KeyAction::Cancel, "Esc", "Cancel"),
             ],
@@ -190,6 +317,13 @@ impl KeybindingContext {
                 Keybinding::new(KeyCode::Down, KeyAction::ScrollDown, "Down", "Scroll down"),
                 Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
                 Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Home, KeyAction::Home, "Home", "Oldest output"),
+                Keybinding::new(KeyCode::End, KeyAction::End, "End", "Follow new output"),
+                Keybinding::new(KeyCode::Char('/'), KeyAction::Search, "/", "Search"),
+                Keybinding::new(KeyCode::Char('n'), KeyAction::NextMatch, "n/N", "Next/previous match"),
+                Keybinding::new(KeyCode::Char('f'), KeyAction::Filter, "F", "Filter by severity"),
+                Keybinding::new(KeyCode::Char('k'), KeyAction::KillCommand, "K", "Kill hung command"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Retry, "R", "Kill hung command and retry phase"),
             ],
         );
 
@@ -197,7 +331,8 @@ impl KeybindingContext {
         self.mode_bindings.insert(
             AppMode::Complete,
             vec![
-                Keybinding::new(KeyCode::Enter, KeyAction::Dismiss, "Enter", "Continue"),
+                Keybinding::new(KeyCode::Char('w'), KeyAction::SaveSummary, "W", "Save summary"),
+                Keybinding::new(KeyCode::Enter, KeyAction::Quit, "Enter", "Exit"),
                 Keybinding::new(KeyCode::Char('b'), KeyAction::Back, "B", "Back to menu"),
             ],
         );
@@ -222,6 +357,9 @@ impl KeybindingContext {
                 Keybinding::new(KeyCode::Down, KeyAction::ScrollDown, "Down", "Scroll down"),
                 Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
                 Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Char('+'), KeyAction::Grow, "+/-", "Grow/shrink window"),
+                Keybinding::new(KeyCode::Char('m'), KeyAction::Maximize, "M", "Maximize window"),
+                Keybinding::new(KeyCode::Char('d'), KeyAction::Dock, "D", "Dock to bottom half"),
                 Keybinding::new(KeyCode::Esc, KeyAction::Dismiss, "Esc", "Close"),
                 Keybinding::new(KeyCode::Enter, KeyAction::Dismiss, "Enter", "Close"),
             ],
@@ -251,6 +389,120 @@ impl KeybindingContext {
                 Keybinding::new(KeyCode::Esc, KeyAction::Cancel, "Esc", "Cancel"),
             ],
         );
+
+        // Disk Health Report
+        self.mode_bindings.insert(
+            AppMode::DiskHealth,
+            vec![
+                Keybinding::new(
+                    KeyCode::Char('s'),
+                    KeyAction::SelfTestShort,
+                    "S",
+                    "Short self-test",
+                ),
+                Keybinding::new(
+                    KeyCode::Char('l'),
+                    KeyAction::SelfTestLong,
+                    "L",
+                    "Long self-test",
+                ),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Refresh, "R", "Refresh"),
+                Keybinding::new(
+                    KeyCode::Char('f'),
+                    KeyAction::FullTest,
+                    "F",
+                    "Full reliability test",
+                ),
+                Keybinding::new(KeyCode::Esc, KeyAction::Back, "Esc", "Back"),
+            ],
+        );
+
+        // Service manager
+        self.mode_bindings.insert(
+            AppMode::ServiceManager,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::NavigateUp, "Up", "Previous unit"),
+                Keybinding::new(KeyCode::Down, KeyAction::NavigateDown, "Down", "Next unit"),
+                Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
+                Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Char('/'), KeyAction::Search, "/", "Filter units"),
+                Keybinding::new(KeyCode::Char('e'), KeyAction::EnableUnit, "E", "Enable"),
+                Keybinding::new(KeyCode::Char('d'), KeyAction::DisableUnit, "D", "Disable"),
+                Keybinding::new(KeyCode::Char('s'), KeyAction::StartUnit, "S", "Start"),
+                Keybinding::new(KeyCode::Char('x'), KeyAction::StopUnit, "X", "Stop"),
+                Keybinding::new(KeyCode::Char('m'), KeyAction::MaskUnit, "M", "Mask"),
+                Keybinding::new(KeyCode::Char('u'), KeyAction::UnmaskUnit, "U", "Unmask"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Refresh, "R", "Refresh"),
+                Keybinding::new(
+                    KeyCode::Char('c'),
+                    KeyAction::SwitchTarget,
+                    "C",
+                    "Switch running/installed system",
+                ),
+                Keybinding::new(KeyCode::Esc, KeyAction::Back, "Esc", "Back"),
+            ],
+        );
+
+        // Mount manager
+        self.mode_bindings.insert(
+            AppMode::MountManager,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::NavigateUp, "Up", "Previous device"),
+                Keybinding::new(
+                    KeyCode::Down,
+                    KeyAction::NavigateDown,
+                    "Down",
+                    "Next device",
+                ),
+                Keybinding::new(KeyCode::PageUp, KeyAction::PageUp, "PgUp", "Page up"),
+                Keybinding::new(KeyCode::PageDown, KeyAction::PageDown, "PgDn", "Page down"),
+                Keybinding::new(KeyCode::Char('m'), KeyAction::Mount, "M", "Mount"),
+                Keybinding::new(
+                    KeyCode::Char('p'),
+                    KeyAction::MountAt,
+                    "P",
+                    "Mount at a path",
+                ),
+                Keybinding::new(KeyCode::Char('u'), KeyAction::Unmount, "U", "Unmount"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Refresh, "R", "Refresh"),
+                Keybinding::new(KeyCode::Esc, KeyAction::Back, "Esc", "Back"),
+            ],
+        );
+
+        // Recovery dialog after a failed installation phase
+        self.mode_bindings.insert(
+            AppMode::RecoveryDialog,
+            vec![
+                Keybinding::new(KeyCode::Up, KeyAction::NavigateUp, "Up", "Previous choice"),
+                Keybinding::new(
+                    KeyCode::Down,
+                    KeyAction::NavigateDown,
+                    "Down",
+                    "Next choice",
+                ),
+                Keybinding::new(KeyCode::Enter, KeyAction::Select, "Enter", "Choose"),
+                Keybinding::new(KeyCode::Char('r'), KeyAction::Retry, "R", "Retry the phase"),
+                Keybinding::new(KeyCode::Char('s'), KeyAction::Skip, "S", "Skip the phase"),
+                Keybinding::new(KeyCode::Char('t'), KeyAction::Shell, "T", "Shell at /mnt"),
+                Keybinding::new(
+                    KeyCode::Char('e'),
+                    KeyAction::ExportLogs,
+                    "E",
+                    "Export the log",
+                ),
+                Keybinding::new(
+                    KeyCode::Char('a'),
+                    KeyAction::Abort,
+                    "A",
+                    "Abort and clean up",
+                ),
+            ],
+        );
+    }
+
+    /// Show the serial console key alternatives in the help (--serial)
+    pub fn set_serial(&mut self, serial: bool) {
+        self.serial = serial;
     }
 
     /// Get keybindings for a specific mode (includes global bindings)
@@ -288,22 +540,51 @@ impl KeybindingContext {
                 KeyAction::NavigateDown,
                 KeyAction::Select,
                 KeyAction::StartInstall,
+                KeyAction::Wizard,
+                KeyAction::Back,
+                KeyAction::Help,
+                KeyAction::Quit,
+            ],
+            AppMode::ToolsMenu => vec![
+                KeyAction::NavigateUp,
+                KeyAction::NavigateDown,
+                KeyAction::Select,
                 KeyAction::Back,
                 KeyAction::Help,
                 KeyAction::Quit,
             ],
-            AppMode::ToolsMenu
-            | AppMode::DiskTools
+            AppMode::DiskTools
             | AppMode::SystemTools
             | AppMode::UserTools
-            | AppMode::NetworkTools => vec![
+            | AppMode::NetworkTools
+            | AppMode::SnapshotTools => vec![
                 KeyAction::NavigateUp,
                 KeyAction::NavigateDown,
                 KeyAction::Select,
+                KeyAction::RunInteractive,
                 KeyAction::Back,
                 KeyAction::Help,
                 KeyAction::Quit,
             ],
+            AppMode::Summary => vec![
+                KeyAction::ScrollUp,
+                KeyAction::ScrollDown,
+                KeyAction::Confirm,
+                KeyAction::Back,
+            ],
+            AppMode::PartitionMounts => vec![
+                KeyAction::NavigateUp,
+                KeyAction::AssignMountpoint,
+                KeyAction::ToggleFormat,
+                KeyAction::Confirm,
+                KeyAction::Cancel,
+            ],
+            AppMode::PartitionCheck => vec![
+                KeyAction::ScrollUp,
+                KeyAction::ScrollDown,
+                KeyAction::Refresh,
+                KeyAction::Back,
+            ],
             AppMode::EmbeddedTerminal => vec![KeyAction::ExitTerminal],
             AppMode::FloatingOutput | AppMode::ToolExecution => vec![
                 KeyAction::ScrollUp,
@@ -313,9 +594,11 @@ impl KeybindingContext {
             AppMode::Installation => vec![
                 KeyAction::ScrollUp,
                 KeyAction::ScrollDown,
+                KeyAction::Search,
+                KeyAction::Filter,
                 KeyAction::Quit,
             ],
-            AppMode::Complete => vec![KeyAction::Dismiss, KeyAction::Back, KeyAction::Quit],
+            AppMode::Complete => vec![KeyAction::SaveSummary, KeyAction::Back, KeyAction::Quit],
             AppMode::ToolDialog => vec![
                 KeyAction::NavigateUp,
                 KeyAction::NavigateDown,
@@ -339,6 +622,40 @@ impl KeybindingContext {
                 KeyAction::Confirm,
                 KeyAction::Cancel,
             ],
+            AppMode::DiskHealth => vec![
+                KeyAction::SelfTestShort,
+                KeyAction::SelfTestLong,
+                KeyAction::Refresh,
+                KeyAction::FullTest,
+                KeyAction::Back,
+            ],
+            AppMode::ServiceManager => vec![
+                KeyAction::NavigateUp,
+                KeyAction::Search,
+                KeyAction::EnableUnit,
+                KeyAction::DisableUnit,
+                KeyAction::StartUnit,
+                KeyAction::StopUnit,
+                KeyAction::Back,
+            ],
+            AppMode::MountManager => vec![
+                KeyAction::NavigateUp,
+                KeyAction::Mount,
+                KeyAction::MountAt,
+                KeyAction::Unmount,
+                KeyAction::Refresh,
+                KeyAction::Back,
+            ],
+            AppMode::RecoveryDialog => vec![
+                KeyAction::NavigateUp,
+                KeyAction::NavigateDown,
+                KeyAction::Select,
+                KeyAction::Retry,
+                KeyAction::Skip,
+                KeyAction::Shell,
+                KeyAction::ExportLogs,
+                KeyAction::Abort,
+            ],
         };
 
         // Combine Up/Down into single item for cleaner display
@@ -428,6 +745,37 @@ impl KeybindingContext {
                         | KeyAction::Toggle
                         | KeyAction::Dismiss
                         | KeyAction::ExitTerminal
+                        | KeyAction::SelfTestShort
+                        | KeyAction::SelfTestLong
+                        | KeyAction::Refresh
+                        | KeyAction::FullTest
+                        | KeyAction::Search
+                        | KeyAction::NextMatch
+                        | KeyAction::Filter
+                        | KeyAction::Retry
+                        | KeyAction::Skip
+                        | KeyAction::Shell
+                        | KeyAction::ExportLogs
+                        | KeyAction::Abort
+                        | KeyAction::SaveSummary
+                        | KeyAction::Grow
+                        | KeyAction::Maximize
+                        | KeyAction::Dock
+                        | KeyAction::KillCommand
+                        | KeyAction::EnableUnit
+                        | KeyAction::DisableUnit
+                        | KeyAction::StartUnit
+                        | KeyAction::StopUnit
+                        | KeyAction::MaskUnit
+                        | KeyAction::UnmaskUnit
+                        | KeyAction::SwitchTarget
+                        | KeyAction::Mount
+                        | KeyAction::MountAt
+                        | KeyAction::Unmount
+                        | KeyAction::AssignMountpoint
+                        | KeyAction::ToggleFormat
+                        | KeyAction::Wizard
+                        | KeyAction::RunInteractive
                 )
             })
             .collect();
@@ -446,7 +794,12 @@ impl KeybindingContext {
         let general_bindings: Vec<_> = self
             .get_bindings(mode)
             .into_iter()
-            .filter(|b| matches!(b.action, KeyAction::Back | KeyAction::Help | KeyAction::Quit))
+            .filter(|b| {
+                matches!(
+                    b.action,
+                    KeyAction::Back | KeyAction::Help | KeyAction::Quit | KeyAction::OpenShell
+                )
+            })
             .collect();
 
         if !general_bindings.is_empty() {
@@ -459,6 +812,22 @@ impl KeybindingContext {
             });
         }
 
+        // The embedded terminal passes control keys to its shell
+        if self.serial && *mode != AppMode::EmbeddedTerminal {
+            sections.push(HelpSection {
+                title: "Serial console".to_string(),
+                items: serial::KEY_ALTERNATIVES
+                    .iter()
+                    .map(|(letter, _, name)| {
+                        (
+                            format!("Ctrl+{}", letter.to_ascii_uppercase()),
+                            name.to_string(),
+                        )
+                    })
+                    .collect(),
+            });
+        }
+
         sections
     }
 }
@@ -2,10 +2,22 @@
 //!
 //! This module contains reusable UI components for the archinstall TUI.
 
+pub mod ansi_art;
 pub mod confirm_dialog;
+pub mod disk_health;
 pub mod file_browser;
 pub mod floating_window;
 pub mod help_overlay;
+pub mod install_summary;
 pub mod keybindings;
+pub mod mount_manager;
 pub mod nav_bar;
+pub mod output_log;
+pub mod partition_check;
+pub mod partition_mounts;
 pub mod pty_terminal;
+pub mod recovery_dialog;
+pub mod service_manager;
+pub mod status_bar;
+pub mod table;
+pub mod tool_form;
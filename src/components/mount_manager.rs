@@ -0,0 +1,233 @@
+//! Mount manager component
+//!
+//! Shows the block device tree with sizes, filesystems and mountpoints in a
+//! table, and the outcome of the last mount or unmount.
+
+use crate::components::table::{Column, DataTable, TableCell, TableRow, TableState};
+use crate::theme::Colors;
+use crate::tools::mounts::BlockDevice;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the mount manager
+#[derive(Debug, Clone, Default)]
+pub struct MountManagerState {
+    /// Devices in tree order, or the error message; None while lsblk is running
+    pub devices: Option<Result<Vec<BlockDevice>, String>>,
+    /// Highlighted device
+    pub table: TableState,
+    /// Feedback from the last action
+    pub message: Option<String>,
+    /// Whether a mount or unmount is running
+    pub busy: bool,
+}
+
+impl MountManagerState {
+    /// Create a state whose devices are still being listed
+    pub fn loading() -> Self {
+        Self::default()
+    }
+
+    /// Whether the devices are still being listed
+    pub fn is_loading(&self) -> bool {
+        self.devices.is_none()
+    }
+
+    /// Devices in display order
+    pub fn listed(&self) -> &[BlockDevice] {
+        match self.devices {
+            Some(Ok(ref devices)) => devices,
+            _ => &[],
+        }
+    }
+
+    /// Highlighted device
+    pub fn selected_device(&self) -> Option<&BlockDevice> {
+        self.listed().get(self.table.selected_row()?)
+    }
+
+    /// Take a fresh device list, keeping the highlight on the same device
+    pub fn set_devices(&mut self, devices: Result<Vec<BlockDevice>, String>) {
+        let selected = self.selected_device().map(|dev| dev.path.clone());
+        self.devices = Some(devices);
+        self.table = TableState::new(self.listed().len());
+        if let Some(path) = selected {
+            if let Some(row) = self.listed().iter().position(|dev| dev.path == path) {
+                self.table.select_row(row);
+            }
+        }
+    }
+}
+
+/// Device name indented by its depth in the tree
+fn tree_name(device: &BlockDevice) -> String {
+    if device.depth == 0 {
+        device.name.clone()
+    } else {
+        format!("{}└─{}", "  ".repeat(device.depth - 1), device.name)
+    }
+}
+
+/// Mount manager window
+pub struct MountManagerView;
+
+impl MountManagerView {
+    /// Render the mount manager
+    pub fn render(f: &mut Frame, state: &MountManagerState) {
+        let area = f.area();
+
+        // Calculate centered area (80% width, 80% height)
+        let width = (area.width as f32 * 0.8) as u16;
+        let height = (area.height as f32 * 0.8) as u16;
+        let x = (area.width - width) / 2;
+        let y = (area.height - height) / 2;
+        let window_area = Rect::new(x, y, width, height);
+
+        f.render_widget(Clear, window_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Mount Manager ")
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(window_area);
+        f.render_widget(block, window_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Min(3),    // Devices
+                Constraint::Length(2), // Action feedback
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        match state.devices {
+            None => f.render_widget(
+                Paragraph::new(Span::styled(
+                    "Listing block devices...",
+                    Style::default().fg(Colors::FG_SECONDARY),
+                )),
+                chunks[0],
+            ),
+            Some(Err(ref error)) => f.render_widget(
+                Paragraph::new(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Colors::ERROR),
+                ))
+                .wrap(Wrap { trim: false }),
+                chunks[0],
+            ),
+            Some(Ok(_)) => Self::render_devices(f, state, chunks[0]),
+        }
+
+        let message = if state.busy {
+            Some("Working...".to_string())
+        } else {
+            state.message.clone()
+        };
+        if let Some(message) = message {
+            let message = Paragraph::new(message)
+                .style(Style::default().fg(Colors::INFO_LIGHT))
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, chunks[1]);
+        }
+
+        let help = Paragraph::new("m Mount | p Mount at... | u Unmount | r Refresh | Esc Back")
+            .style(Style::default().fg(Colors::FG_MUTED))
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[2]);
+    }
+
+    /// Table of the device tree
+    fn render_devices(f: &mut Frame, state: &MountManagerState, area: Rect) {
+        let columns = [
+            Column::content("Device", 24).unsorted(),
+            Column::content("Size", 10).unsorted(),
+            Column::content("Type", 6).unsorted(),
+            Column::content("Filesystem", 12).unsorted(),
+            Column::content("Label", 12).unsorted(),
+            Column::fill("Mountpoints", 10).unsorted(),
+        ];
+        let rows: Vec<TableRow> = state
+            .listed()
+            .iter()
+            .map(|dev| {
+                let mountpoints = if dev.is_mounted() {
+                    TableCell::new(dev.mountpoints.join(", "))
+                        .style(Style::default().fg(Colors::SUCCESS))
+                } else {
+                    TableCell::new("-").style(Style::default().fg(Colors::FG_MUTED))
+                };
+                TableRow::new([
+                    TableCell::new(tree_name(dev)),
+                    TableCell::number(dev.size_display(), dev.size),
+                    TableCell::from(dev.kind.as_str()),
+                    TableCell::from(if dev.fstype.is_empty() {
+                        "-"
+                    } else {
+                        dev.fstype.as_str()
+                    }),
+                    TableCell::from(dev.label.as_str()),
+                    mountpoints,
+                ])
+            })
+            .collect();
+
+        let table = DataTable::new(&columns, &rows)
+            .highlight_style(
+                Style::default()
+                    .fg(Colors::SUCCESS_LIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        // The state is borrowed immutably while drawing; the table only needs
+        // its own copy to lay out the visible lines
+        let mut table_state = state.table.clone();
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, depth: usize) -> BlockDevice {
+        BlockDevice {
+            name: name.to_string(),
+            path: format!("/dev/{}", name),
+            depth,
+            ..BlockDevice::default()
+        }
+    }
+
+    #[test]
+    fn test_refresh_keeps_the_selected_device() {
+        let mut state = MountManagerState::loading();
+        state.set_devices(Ok(vec![device("sda", 0), device("sda1", 1)]));
+        state.table.select_next();
+        assert_eq!(state.selected_device().unwrap().name, "sda1");
+
+        state.set_devices(Ok(vec![
+            device("sda", 0),
+            device("sda1", 1),
+            device("sdb", 0),
+        ]));
+        state.table.select_row(0);
+        state.set_devices(Ok(vec![device("sdb", 0), device("sda", 0)]));
+        assert_eq!(state.selected_device().unwrap().name, "sda");
+
+        assert_eq!(tree_name(&device("cryptroot", 2)), "  └─cryptroot");
+    }
+}
@@ -1,6 +1,7 @@
 //! Navigation bar component
 //!
-//! A persistent bottom bar showing context-sensitive keybindings.
+//! Context-sensitive keybindings, shown in the left segment of the status
+//! bar.
 
 #![allow(dead_code)]
 
@@ -31,6 +32,20 @@ impl NavBar {
             return;
         }
 
+        let line = Line::from(self.spans());
+        let paragraph = Paragraph::new(line)
+            .block(
+                Block::default()
+                    .borders(Borders::NONE)
+                    .style(Style::default().bg(Colors::BG_SECONDARY)),
+            )
+            .style(Style::default().bg(Colors::BG_SECONDARY));
+
+        f.render_widget(paragraph, area);
+    }
+
+    /// Styled `[key] label` pairs
+    pub fn spans(&self) -> Vec<Span<'_>> {
         let mut spans = Vec::new();
 
         for (i, item) in self.items.iter().enumerate() {
@@ -67,17 +82,12 @@ impl NavBar {
                 Style::default().fg(Colors::FG_PRIMARY),
             ));
         }
+        spans
+    }
 
-        let line = Line::from(spans);
-        let paragraph = Paragraph::new(line)
-            .block(
-                Block::default()
-                    .borders(Borders::NONE)
-                    .style(Style::default().bg(Colors::BG_SECONDARY)),
-            )
-            .style(Style::default().bg(Colors::BG_SECONDARY));
-
-        f.render_widget(paragraph, area);
+    /// Columns taken by the hints
+    pub fn width(&self) -> u16 {
+        self.spans().iter().map(|span| span.width() as u16).sum()
     }
 
     /// Get the required height for the navigation bar
@@ -104,5 +114,6 @@ mod tests {
         ];
         let nav_bar = NavBar::new(items);
         assert_eq!(nav_bar.items.len(), 2);
+        assert_eq!(nav_bar.width(), "[Up/Dn] Navigate  [Enter] Select".len() as u16);
     }
 }
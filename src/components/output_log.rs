@@ -0,0 +1,499 @@
+//! Installer output log
+//!
+//! The installer prints thousands of lines on a desktop install. The last
+//! [`CAPACITY`] of them are kept in a ring buffer; the pane shows a window of
+//! the lines that pass the severity filter, scrolled with a [`ScrollState`]
+//! whose selection is the highlighted line. While following, the selection
+//! sticks to the newest line; scrolling up pauses it and End resumes it.
+
+use crate::scrolling::ScrollState;
+use crate::theme::Colors;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::collections::VecDeque;
+
+/// Lines kept before the oldest are dropped
+pub const CAPACITY: usize = 10_000;
+
+/// Prefix `monitor_installer_output` puts on lines read from stderr
+const STDERR_PREFIX: &str = "ERROR: ";
+
+/// How serious an output line is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Classify an installer output line
+    ///
+    /// log_warn and log_error both write to stderr, so a stderr line is an
+    /// error unless it is tagged as a warning. Tags are matched anywhere in
+    /// the line to also catch pacman's `warning:` and `error:`.
+    pub fn of(line: &str) -> Self {
+        let (stderr, text) = match line.strip_prefix(STDERR_PREFIX) {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let text = text.to_lowercase();
+        if text.contains("warn:") || text.contains("warning:") {
+            Severity::Warning
+        } else if stderr || text.contains("error:") {
+            Severity::Error
+        } else {
+            Severity::Info
+        }
+    }
+}
+
+/// Which lines the pane shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SeverityFilter {
+    #[default]
+    All,
+    /// Warnings and errors
+    Warnings,
+    Errors,
+}
+
+impl SeverityFilter {
+    /// Filter selected by the next press of the filter key
+    pub fn next(self) -> Self {
+        match self {
+            SeverityFilter::All => SeverityFilter::Warnings,
+            SeverityFilter::Warnings => SeverityFilter::Errors,
+            SeverityFilter::Errors => SeverityFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SeverityFilter::All => "all output",
+            SeverityFilter::Warnings => "warnings and errors",
+            SeverityFilter::Errors => "errors only",
+        }
+    }
+
+    fn passes(self, line: &str) -> bool {
+        match self {
+            SeverityFilter::All => true,
+            SeverityFilter::Warnings => Severity::of(line) >= Severity::Warning,
+            SeverityFilter::Errors => Severity::of(line) == Severity::Error,
+        }
+    }
+}
+
+/// Installer output with the scroll, filter and search state of its pane
+#[derive(Debug, Clone)]
+pub struct OutputLog {
+    lines: VecDeque<String>,
+    /// Number of lines passing the filter
+    shown: usize,
+    /// Scroll state over the lines passing the filter
+    pub scroll: ScrollState,
+    /// Whether the selection follows new output
+    pub follow: bool,
+    pub filter: SeverityFilter,
+    /// Term searched for with n/N
+    pub search: Option<String>,
+    /// Search term being typed after '/'
+    pub prompt: Option<String>,
+}
+
+impl Default for OutputLog {
+    fn default() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            shown: 0,
+            scroll: ScrollState::new(0, 10),
+            follow: true,
+            filter: SeverityFilter::All,
+            search: None,
+            prompt: None,
+        }
+    }
+}
+
+impl OutputLog {
+    /// Append a line, dropping the oldest one when the log is full
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == CAPACITY {
+            if let Some(oldest) = self.lines.pop_front() {
+                if self.filter.passes(&oldest) {
+                    self.shown -= 1;
+                    // Keep a paused view on the same line
+                    if !self.follow {
+                        self.scroll.selected_index = self.scroll.selected_index.saturating_sub(1);
+                        self.scroll.offset = self.scroll.offset.saturating_sub(1);
+                    }
+                }
+            }
+        }
+        if self.filter.passes(&line) {
+            self.shown += 1;
+        }
+        self.lines.push_back(line);
+        self.scroll.total_items = self.shown;
+        if self.follow {
+            self.scroll.set_selected(self.shown.saturating_sub(1));
+        }
+    }
+
+    /// All kept lines, oldest first
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// Lines passing the filter, oldest first
+    pub fn shown(&self) -> impl Iterator<Item = &str> {
+        let filter = self.filter;
+        self.lines().filter(move |line| filter.passes(line))
+    }
+
+    /// Number of lines passing the filter
+    pub fn shown_len(&self) -> usize {
+        self.shown
+    }
+
+    /// Resize the pane to `visible` lines
+    pub fn set_visible_lines(&mut self, visible: usize) {
+        if self.scroll.visible_items != visible {
+            self.scroll.update_visible_items(visible);
+            if self.follow {
+                self.follow_output();
+            }
+        }
+    }
+
+    /// Jump to the newest line and keep following new output
+    pub fn follow_output(&mut self) {
+        self.follow = true;
+        self.scroll.set_selected(self.shown.saturating_sub(1));
+    }
+
+    /// Scroll the view and the selection a page up or down
+    ///
+    /// Unlike [`ScrollState::page_up`], which first moves the selection to
+    /// the edge of the view, this always brings a new page into view.
+    pub fn page(&mut self, up: bool) {
+        let page = self.scroll.visible_items.saturating_sub(1).max(1);
+        let last = self.shown.saturating_sub(1);
+        let scroll = &mut self.scroll;
+        if up {
+            scroll.offset = scroll.offset.saturating_sub(page);
+            scroll.set_selected(scroll.selected_index.saturating_sub(page));
+        } else {
+            let max_offset = self.shown.saturating_sub(scroll.visible_items);
+            scroll.offset = (scroll.offset + page).min(max_offset);
+            scroll.set_selected((scroll.selected_index + page).min(last));
+        }
+        self.after_scroll();
+    }
+
+    /// Apply a scroll movement of the selection
+    ///
+    /// Moving up pauses following; reaching the last line resumes it.
+    pub fn after_scroll(&mut self) {
+        let last = self.shown.saturating_sub(1);
+        if self.scroll.selected_index >= last {
+            self.follow_output();
+        } else {
+            self.follow = false;
+        }
+    }
+
+    /// Show the next severity filter
+    ///
+    /// The selection moves to the newest line, or stays on the selected line
+    /// if it is still shown.
+    pub fn cycle_filter(&mut self) {
+        let selected = self.selected_line();
+        self.filter = self.filter.next();
+        self.shown = self.shown().count();
+        self.scroll.total_items = self.shown;
+        self.scroll.offset = 0;
+        match selected.and_then(|line| self.shown_index(line)) {
+            Some(index) if !self.follow => self.scroll.set_selected(index),
+            _ => self.follow_output(),
+        }
+    }
+
+    /// Index in the kept lines of the selected line
+    fn selected_line(&self) -> Option<usize> {
+        let filter = self.filter;
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| filter.passes(line))
+            .nth(self.scroll.selected_index)
+            .map(|(index, _)| index)
+    }
+
+    /// Position among the shown lines of a kept line, if it is shown
+    fn shown_index(&self, line: usize) -> Option<usize> {
+        let filter = self.filter;
+        self.filter.passes(self.lines.get(line)?).then(|| {
+            self.lines
+                .iter()
+                .take(line)
+                .filter(|line| filter.passes(line))
+                .count()
+        })
+    }
+
+    /// Open the search prompt
+    pub fn start_search(&mut self) {
+        self.prompt = Some(String::new());
+    }
+
+    /// Close the search prompt and search for what was typed
+    ///
+    /// An empty prompt clears the search. Searching starts at the selected
+    /// line and goes back in time, so the most recent match is found first
+    /// while following. Returns whether anything matched.
+    pub fn confirm_search(&mut self) -> bool {
+        let term = self.prompt.take().unwrap_or_default();
+        if term.is_empty() {
+            self.search = None;
+            return true;
+        }
+        self.search = Some(term);
+        let selected = self.scroll.selected_index;
+        self.find(selected, false) || self.find(selected, true)
+    }
+
+    /// Move to the next (`forward`) or previous match of the search
+    ///
+    /// Wraps around at either end. Returns whether anything matched.
+    pub fn next_match(&mut self, forward: bool) -> bool {
+        let selected = self.scroll.selected_index;
+        if forward {
+            self.find(selected + 1, true) || self.find(0, true)
+        } else {
+            (selected > 0 && self.find(selected - 1, false))
+                || self.find(self.shown.saturating_sub(1), false)
+        }
+    }
+
+    /// Select the first match at or after (`forward`) or at or before `from`
+    fn find(&mut self, from: usize, forward: bool) -> bool {
+        let Some(ref term) = self.search else {
+            return false;
+        };
+        let term = term.to_lowercase();
+        let matches = |line: &str| line.to_lowercase().contains(&term);
+        let shown: Vec<&str> = self.shown().collect();
+        let found = if forward {
+            (from..shown.len()).find(|&i| matches(shown[i]))
+        } else if from < shown.len() {
+            (0..=from).rev().find(|&i| matches(shown[i]))
+        } else {
+            None
+        };
+        match found {
+            Some(index) => {
+                self.scroll.set_selected(index);
+                self.after_scroll();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a line contains the search term
+    pub fn is_match(&self, line: &str) -> bool {
+        self.search
+            .as_ref()
+            .is_some_and(|term| line.to_lowercase().contains(&term.to_lowercase()))
+    }
+}
+
+/// Installer output pane
+pub struct OutputLogView;
+
+impl OutputLogView {
+    /// Render the log over `area`
+    pub fn render(f: &mut Frame, area: Rect, log: &OutputLog) {
+        let height = area.height.saturating_sub(2) as usize;
+        let (start, end) = log.scroll.visible_range();
+        let end = end.min(start + height);
+        let paused = !log.follow && log.shown_len() > 0;
+
+        let lines: Vec<Line> = log
+            .shown()
+            .enumerate()
+            .skip(start)
+            .take(end - start)
+            .map(|(index, line)| {
+                let mut style = match Severity::of(line) {
+                    Severity::Info => Style::default(),
+                    Severity::Warning => Style::default().fg(Colors::WARNING),
+                    Severity::Error => Style::default().fg(Colors::ERROR),
+                };
+                if log.is_match(line) {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                }
+                if paused && index == log.scroll.selected_index {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(Span::styled(line.to_string(), style))
+            })
+            .collect();
+
+        let mut title = "Installer Output".to_string();
+        if log.filter != SeverityFilter::All {
+            title.push_str(&format!(" - {}", log.filter.label()));
+        }
+        if paused {
+            title.push_str(&format!(
+                " (paused at {}/{}, End to follow)",
+                log.scroll.selected_index + 1,
+                log.shown_len()
+            ));
+        }
+        let mut block = Block::default().borders(Borders::ALL).title(title);
+        if let Some(ref prompt) = log.prompt {
+            block = block.title_bottom(Line::from(format!(" /{}_ ", prompt)));
+        } else if let Some(ref term) = log.search {
+            block = block.title_bottom(Line::from(Span::styled(
+                format!(" /{} (n/N next/previous) ", term),
+                Style::default().fg(Colors::FG_MUTED),
+            )));
+        }
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with(lines: &[&str], visible: usize) -> OutputLog {
+        let mut log = OutputLog::default();
+        log.set_visible_lines(visible);
+        for line in lines {
+            log.push(line.to_string());
+        }
+        log
+    }
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(
+            Severity::of("[2025-01-01 10:00:00] INFO: Installing"),
+            Severity::Info
+        );
+        assert_eq!(
+            Severity::of("ERROR: [2025-01-01 10:00:00] WARN: No swap"),
+            Severity::Warning
+        );
+        assert_eq!(Severity::of("warning: dependency cycle"), Severity::Warning);
+        assert_eq!(Severity::of("ERROR: mkfs failed"), Severity::Error);
+        assert_eq!(
+            Severity::of("error: failed to commit transaction"),
+            Severity::Error
+        );
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_newest_lines() {
+        let mut log = OutputLog::default();
+        for i in 0..CAPACITY + 5 {
+            log.push(format!("line {}", i));
+        }
+        assert_eq!(log.lines().count(), CAPACITY);
+        assert_eq!(log.lines().next(), Some("line 5"));
+        assert_eq!(log.scroll.selected_index, CAPACITY - 1);
+        assert!(log.follow);
+    }
+
+    #[test]
+    fn test_scrolling_pauses_and_resumes_following() {
+        let lines: Vec<String> = (0..50).map(|i| format!("line {}", i)).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut log = log_with(&lines, 10);
+        assert_eq!(log.scroll.visible_range(), (40, 50));
+
+        log.page(true);
+        assert!(!log.follow);
+        assert_eq!(log.scroll.visible_range(), (31, 41));
+        let selected = log.scroll.selected_index;
+        log.push("line 50".to_string());
+        assert_eq!(log.scroll.selected_index, selected);
+
+        log.scroll.move_to_last();
+        log.after_scroll();
+        assert!(log.follow);
+        assert_eq!(log.scroll.selected_index, 50);
+    }
+
+    #[test]
+    fn test_filter_counts_and_keeps_selection() {
+        let mut log = log_with(
+            &[
+                "INFO: one",
+                "ERROR: WARN: two",
+                "INFO: three",
+                "ERROR: four",
+                "INFO: five",
+            ],
+            10,
+        );
+        log.cycle_filter();
+        assert_eq!(log.filter, SeverityFilter::Warnings);
+        assert_eq!(
+            log.shown().collect::<Vec<_>>(),
+            ["ERROR: WARN: two", "ERROR: four"]
+        );
+        assert_eq!(log.shown_len(), 2);
+
+        // A paused selection stays on its line when it is still shown
+        log.scroll.set_selected(0);
+        log.after_scroll();
+        log.cycle_filter();
+        assert_eq!(log.filter, SeverityFilter::Errors);
+        assert_eq!(log.shown_len(), 1);
+        assert!(log.follow);
+
+        log.cycle_filter();
+        assert_eq!(log.shown_len(), 5);
+        log.push("ERROR: six".to_string());
+        assert_eq!(log.shown_len(), 6);
+    }
+
+    #[test]
+    fn test_search_wraps_around() {
+        let mut log = log_with(&["pacstrap a", "mkfs", "pacstrap b", "genfstab"], 10);
+        log.start_search();
+        log.prompt.as_mut().unwrap().push_str("PACSTRAP");
+        assert!(log.confirm_search());
+        assert!(log.prompt.is_none());
+        // Most recent match first
+        assert_eq!(log.scroll.selected_index, 2);
+        assert!(!log.follow);
+
+        assert!(log.next_match(true));
+        assert_eq!(log.scroll.selected_index, 0);
+        assert!(log.next_match(false));
+        assert_eq!(log.scroll.selected_index, 2);
+        assert!(log.next_match(false));
+        assert_eq!(log.scroll.selected_index, 0);
+
+        log.start_search();
+        log.prompt.as_mut().unwrap().push_str("grub");
+        assert!(!log.confirm_search());
+        assert_eq!(log.scroll.selected_index, 0);
+
+        log.start_search();
+        assert!(log.confirm_search());
+        assert!(log.search.is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Manual partitioning check component
+//!
+//! Shows the selected disks as lsblk sees them and every problem
+//! [`partition_check`](crate::partition_check) found with the layout, or
+//! with the [`mount_plan`](crate::mount_plan) for existing partitions.
+
+use crate::config::Configuration;
+use crate::mount_plan::MountPlan;
+use crate::partition_check::{self, Report, Severity};
+use crate::theme::Colors;
+use crate::tools::resize::format_size;
+use crate::types::{BootMode, PartitionScheme};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the manual partitioning check
+#[derive(Debug, Clone)]
+pub struct PartitionCheckState {
+    /// Disks picked for the manual layout
+    pub disks: Vec<String>,
+    /// Boot mode option, resolved by the check
+    pub boot_mode: BootMode,
+    /// Partition Mounts plan checked instead of what is mounted, with the
+    /// existing strategy
+    pub plan: Option<MountPlan>,
+    /// Layout and its problems, or why lsblk could not be read
+    pub report: Result<Report, String>,
+    /// Lines scrolled past
+    pub scroll: usize,
+}
+
+impl PartitionCheckState {
+    /// Check the layout of the disks selected in the configuration, or the
+    /// Partition Mounts plan for them with the existing strategy
+    pub fn for_config(config: &Configuration) -> Self {
+        let existing = config.value("Partitioning Strategy").parse() == Ok(PartitionScheme::Existing);
+        let plan = existing
+            .then(|| MountPlan::parse(&config.value("Partition Mounts")).unwrap_or_default());
+        let mut check = Self {
+            disks: selected_disks(config),
+            boot_mode: config.value("Boot Mode").parse().unwrap_or_default(),
+            plan,
+            report: Err(String::new()),
+            scroll: 0,
+        };
+        check.rescan();
+        check
+    }
+
+    /// Read the disks again and check the layout or plan on them
+    pub fn rescan(&mut self) {
+        self.report = match self.plan {
+            None => partition_check::scan(&self.disks, self.boot_mode),
+            Some(ref plan) => partition_check::read_layout(&self.disks).map(|(disks, _)| {
+                plan.report(partition_check::resolve_boot_mode(self.boot_mode), disks)
+            }),
+        };
+        self.scroll = 0;
+    }
+
+    /// Whether the installation can start on the layout: no errors and
+    /// the root mounted
+    pub fn is_ready(&self) -> bool {
+        self.report
+            .as_ref()
+            .is_ok_and(|report| !report.has_errors() && report.is_mounted())
+    }
+
+    /// Number of lines of the layout and its problems
+    pub fn content_len(&self) -> usize {
+        match self.report {
+            Ok(ref report) => PartitionCheckView::report_lines(report).len(),
+            Err(_) => 1,
+        }
+    }
+}
+
+/// Device paths of the disks selected in the configuration
+pub fn selected_disks(config: &Configuration) -> Vec<String> {
+    config
+        .value("Disk")
+        .split(',')
+        .filter_map(|d| d.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Manual partitioning check window
+pub struct PartitionCheckView;
+
+impl PartitionCheckView {
+    /// Render the layout and its problems
+    pub fn render(f: &mut Frame, state: &PartitionCheckState) {
+        let area = f.area();
+
+        // Calculate centered area (80% width, 80% height)
+        let width = (area.width as f32 * 0.8) as u16;
+        let height = (area.height as f32 * 0.8) as u16;
+        let x = (area.width - width) / 2;
+        let y = (area.height - height) / 2;
+        let window_area = Rect::new(x, y, width, height);
+
+        f.render_widget(Clear, window_area);
+
+        let title = match state.report {
+            Ok(ref report) => format!(" Partition Check ({}) ", report.boot_mode),
+            Err(_) => " Partition Check ".to_string(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(window_area);
+        f.render_widget(block, window_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Min(3),    // Layout and problems
+                Constraint::Length(2), // Verdict
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        let (lines, verdict) = match state.report {
+            Err(ref error) => (
+                vec![Line::from(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Colors::ERROR),
+                ))],
+                Span::styled(
+                    "The layout could not be checked",
+                    Style::default().fg(Colors::ERROR),
+                ),
+            ),
+            Ok(ref report) => (
+                Self::report_lines(report),
+                Self::verdict(report, state.plan.is_some()),
+            ),
+        };
+        let body = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((state.scroll as u16, 0));
+        f.render_widget(body, chunks[0]);
+        let verdict = Paragraph::new(Line::from(verdict)).wrap(Wrap { trim: true });
+        f.render_widget(verdict, chunks[1]);
+
+        let help = Paragraph::new("↑/↓ Scroll | r Re-check | Esc Back")
+            .style(Style::default().fg(Colors::FG_MUTED))
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[2]);
+    }
+
+    /// Disks with their partitions, then the problems
+    fn report_lines(report: &Report) -> Vec<Line<'static>> {
+        let muted = Style::default().fg(Colors::FG_MUTED);
+        let mut lines = Vec::new();
+        for disk in &report.disks {
+            let table = if disk.table.is_empty() {
+                "no partition table"
+            } else {
+                &disk.table
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    disk.path.clone(),
+                    Style::default()
+                        .fg(Colors::FG_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("  {}", table), muted),
+            ]));
+            for part in &disk.partitions {
+                let fstype = if part.fstype.is_empty() {
+                    "-"
+                } else {
+                    &part.fstype
+                };
+                let mountpoint = report.mountpoint(&part.path).unwrap_or("-");
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {:<16}", part.path)),
+                    Span::styled(format!("{:>10}  ", format_size(part.size)), muted),
+                    Span::raw(format!("{:<22}", part.type_display())),
+                    Span::raw(format!("{:<12}", fstype)),
+                    Span::styled(
+                        mountpoint.to_string(),
+                        Style::default().fg(Colors::INFO_LIGHT),
+                    ),
+                ]));
+            }
+            lines.push(Line::from(""));
+        }
+
+        // Volumes inside LUKS or LVM, and partitions of other disks
+        let others: Vec<_> = report
+            .mounts
+            .iter()
+            .filter(|mount| {
+                !report
+                    .disks
+                    .iter()
+                    .flat_map(|disk| &disk.partitions)
+                    .any(|part| part.path == mount.device)
+            })
+            .collect();
+        if !others.is_empty() {
+            lines.push(Line::from(Span::styled("Also mounted", muted)));
+            for mount in others {
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {:<28}", mount.device)),
+                    Span::raw(format!("{:<12}", mount.fstype)),
+                    Span::styled(mount.path.clone(), Style::default().fg(Colors::INFO_LIGHT)),
+                ]));
+            }
+            lines.push(Line::from(""));
+        }
+
+        if report.problems.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "✓ No problems found",
+                Style::default().fg(Colors::SUCCESS),
+            )));
+        }
+        for problem in &report.problems {
+            let (symbol, color) = match problem.severity {
+                Severity::Error => ("✗", Colors::ERROR),
+                Severity::Warning => ("⚠", Colors::WARNING),
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{} {}", symbol, problem),
+                Style::default().fg(color),
+            )));
+        }
+        lines
+    }
+
+    /// One-line summary of the problems; `planned` when they are about a
+    /// Partition Mounts plan rather than a layout mounted by hand
+    fn verdict(report: &Report, planned: bool) -> Span<'static> {
+        let errors = report.error_count();
+        let warnings = report.problems.len() - errors;
+        let fix = if planned {
+            "change Partition Mounts, or fix them from another console (Alt+F2) and press r"
+        } else {
+            "fix them from another console (Alt+F2), then press r"
+        };
+        if errors > 0 {
+            Span::styled(
+                format!(
+                    "{} error(s) must be fixed before installing; {}",
+                    errors, fix
+                ),
+                Style::default()
+                    .fg(Colors::ERROR)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else if warnings > 0 {
+            Span::styled(
+                format!("{} warning(s); the installation can start", warnings),
+                Style::default().fg(Colors::WARNING),
+            )
+        } else {
+            Span::styled(
+                "The layout is ready to install",
+                Style::default().fg(Colors::SUCCESS),
+            )
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! Partition Mounts editor component
+//!
+//! Lists the partitions of the selected disks and lets the user assign each
+//! a mountpoint and choose whether it is formatted or keeps its data; the
+//! result is the value of the Partition Mounts option, see
+//! [`mount_plan`](crate::mount_plan).
+
+use crate::components::partition_check::selected_disks;
+use crate::components::table::{Column, DataTable, TableCell, TableRow, TableState};
+use crate::config::Configuration;
+use crate::mount_plan::{self, Assignment, MountPlan, MOUNTPOINTS, SWAP};
+use crate::partition_check::{self, Partition};
+use crate::theme::Colors;
+use crate::tools::resize::format_size;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the Partition Mounts editor
+#[derive(Debug, Clone)]
+pub struct PartitionMountsState {
+    /// Partitions of the selected disks, or why lsblk could not be read
+    pub partitions: Result<Vec<Partition>, String>,
+    /// Plan being edited
+    pub plan: MountPlan,
+    /// Filesystem offered first for the root and the other system paths
+    pub root_filesystem: String,
+    /// Filesystem offered first for a /home that cannot be kept
+    pub home_filesystem: String,
+    /// Highlighted partition
+    pub table: TableState,
+}
+
+impl PartitionMountsState {
+    /// Edit the Partition Mounts option for the disks in the configuration
+    pub fn for_config(config: &Configuration) -> Self {
+        let partitions = partition_check::read_layout(&selected_disks(config)).map(|(disks, _)| {
+            disks
+                .into_iter()
+                .flat_map(|disk| disk.partitions)
+                .collect::<Vec<_>>()
+        });
+        let home_filesystem = config.value("Home Filesystem");
+        Self::new(
+            partitions,
+            MountPlan::parse(&config.value("Partition Mounts")).unwrap_or_default(),
+            config.value("Root Filesystem"),
+            home_filesystem,
+        )
+    }
+
+    pub fn new(
+        partitions: Result<Vec<Partition>, String>,
+        plan: MountPlan,
+        root_filesystem: String,
+        home_filesystem: String,
+    ) -> Self {
+        let len = partitions.as_ref().map_or(0, Vec::len);
+        Self {
+            partitions,
+            plan,
+            root_filesystem,
+            home_filesystem,
+            table: TableState::new(len),
+        }
+    }
+
+    /// Highlighted partition
+    pub fn selected(&self) -> Option<&Partition> {
+        let partitions = self.partitions.as_ref().ok()?;
+        partitions.get(self.table.selected_row()?)
+    }
+
+    /// Give the highlighted partition the next free mountpoint, and after
+    /// the last one take it out of the plan
+    pub fn cycle_mountpoint(&mut self) {
+        let Some(part) = self.selected().cloned() else {
+            return;
+        };
+        let current = self.plan.get(&part.path).map(|a| a.mountpoint.clone());
+        let start = current
+            .as_deref()
+            .and_then(|mp| MOUNTPOINTS.iter().position(|m| *m == mp))
+            .map_or(0, |index| index + 1);
+        let taken = |mountpoint: &str| {
+            mountpoint != SWAP
+                && self
+                    .plan
+                    .assignments
+                    .iter()
+                    .any(|a| a.device != part.path && a.mountpoint == mountpoint)
+        };
+        let next = MOUNTPOINTS[start.min(MOUNTPOINTS.len())..]
+            .iter()
+            .find(|mountpoint| !taken(mountpoint));
+        let assignment = next.map(|mountpoint| Assignment {
+            device: part.path.clone(),
+            mountpoint: mountpoint.to_string(),
+            format: self.default_format(&part, mountpoint),
+        });
+        self.plan.set(&part.path, assignment);
+    }
+
+    /// Switch the highlighted partition between keeping its data and the
+    /// filesystems it can be formatted with
+    pub fn cycle_format(&mut self) {
+        let Some(path) = self.selected().map(|part| part.path.clone()) else {
+            return;
+        };
+        let Some(assignment) = self.plan.get(&path).cloned() else {
+            return;
+        };
+        let mut choices: Vec<Option<String>> = mount_plan::formats_for(&assignment.mountpoint)
+            .into_iter()
+            .map(Some)
+            .collect();
+        // The root is always formatted
+        if assignment.mountpoint != "/" {
+            choices.insert(0, None);
+        }
+        let next = choices
+            .iter()
+            .position(|choice| *choice == assignment.format)
+            .map_or(0, |index| (index + 1) % choices.len());
+        self.plan.set(
+            &path,
+            Some(Assignment {
+                format: choices[next].clone(),
+                ..assignment
+            }),
+        );
+    }
+
+    /// Format a partition newly assigned to `mountpoint` gets: the root is
+    /// formatted, anything that already holds a usable filesystem is kept
+    fn default_format(&self, part: &Partition, mountpoint: &str) -> Option<String> {
+        let formats = mount_plan::formats_for(mountpoint);
+        if mountpoint != "/" && formats.contains(&part.fstype) {
+            return None;
+        }
+        let preferred = if mountpoint == "/home" {
+            &self.home_filesystem
+        } else {
+            &self.root_filesystem
+        };
+        if formats.contains(preferred) {
+            Some(preferred.clone())
+        } else {
+            formats.into_iter().next()
+        }
+    }
+}
+
+/// Partition Mounts editor window
+pub struct PartitionMountsView;
+
+impl PartitionMountsView {
+    /// Render the partitions and their assignments
+    pub fn render(f: &mut Frame, state: &PartitionMountsState) {
+        let area = f.area();
+
+        // Calculate centered area (80% width, 80% height)
+        let width = (area.width as f32 * 0.8) as u16;
+        let height = (area.height as f32 * 0.8) as u16;
+        let x = (area.width - width) / 2;
+        let y = (area.height - height) / 2;
+        let window_area = Rect::new(x, y, width, height);
+
+        f.render_widget(Clear, window_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Partition Mounts ")
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(window_area);
+        f.render_widget(block, window_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Min(3),    // Partitions
+                Constraint::Length(2), // Plan check
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        match state.partitions {
+            Err(ref error) => f.render_widget(
+                Paragraph::new(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Colors::ERROR),
+                ))
+                .wrap(Wrap { trim: false }),
+                chunks[0],
+            ),
+            Ok(ref partitions) => Self::render_partitions(f, state, partitions, chunks[0]),
+        }
+
+        let status = match state.plan.validate() {
+            Err(error) => Span::styled(error, Style::default().fg(Colors::WARNING)),
+            Ok(()) if state.plan.is_empty() => Span::styled(
+                "Assign / and the partitions to keep; nothing is formatted until the installation starts",
+                Style::default().fg(Colors::FG_SECONDARY),
+            ),
+            Ok(()) => Span::styled(
+                "The plan is complete; Enter saves it, the layout is checked before installing",
+                Style::default().fg(Colors::SUCCESS),
+            ),
+        };
+        f.render_widget(Paragraph::new(status).wrap(Wrap { trim: true }), chunks[1]);
+
+        let help =
+            Paragraph::new("↑/↓ Select | m Mountpoint | f Format/Keep | Enter Save | Esc Cancel")
+                .style(Style::default().fg(Colors::FG_MUTED))
+                .alignment(Alignment::Center);
+        f.render_widget(help, chunks[2]);
+    }
+
+    /// Table of the partitions with their mountpoint and format
+    fn render_partitions(
+        f: &mut Frame,
+        state: &PartitionMountsState,
+        partitions: &[Partition],
+        area: Rect,
+    ) {
+        let columns = [
+            Column::content("Partition", 18).unsorted(),
+            Column::content("Size", 10).unsorted(),
+            Column::content("Type", 22).unsorted(),
+            Column::content("Filesystem", 12).unsorted(),
+            Column::content("Mountpoint", 10).unsorted(),
+            Column::fill("Action", 10).unsorted(),
+        ];
+        let muted = Style::default().fg(Colors::FG_MUTED);
+        let rows: Vec<TableRow> = partitions
+            .iter()
+            .map(|part| {
+                let assignment = state.plan.get(&part.path);
+                let (mountpoint, action) = match assignment {
+                    None => (
+                        TableCell::new("-").style(muted),
+                        TableCell::new("unused").style(muted),
+                    ),
+                    Some(a) => (
+                        TableCell::new(a.mountpoint.clone())
+                            .style(Style::default().fg(Colors::INFO_LIGHT)),
+                        match a.format {
+                            Some(ref format) => TableCell::new(format!("format {}", format))
+                                .style(Style::default().fg(Colors::WARNING)),
+                            None => TableCell::new("keep data")
+                                .style(Style::default().fg(Colors::SUCCESS)),
+                        },
+                    ),
+                };
+                TableRow::new([
+                    TableCell::new(part.path.clone()),
+                    TableCell::number(format_size(part.size), part.size),
+                    TableCell::from(part.type_display()),
+                    TableCell::from(if part.fstype.is_empty() {
+                        "-"
+                    } else {
+                        part.fstype.as_str()
+                    }),
+                    mountpoint,
+                    action,
+                ])
+            })
+            .collect();
+
+        let table = DataTable::new(&columns, &rows)
+            .highlight_style(
+                Style::default()
+                    .fg(Colors::SUCCESS_LIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        let mut table_state = state.table.clone();
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition(path: &str, fstype: &str) -> Partition {
+        Partition {
+            path: path.to_string(),
+            fstype: fstype.to_string(),
+            ..Partition::default()
+        }
+    }
+
+    fn state(plan: &str) -> PartitionMountsState {
+        PartitionMountsState::new(
+            Ok(vec![
+                partition("/dev/sda1", "vfat"),
+                partition("/dev/sda2", "ext4"),
+                partition("/dev/sda3", "xfs"),
+            ]),
+            MountPlan::parse(plan).unwrap(),
+            "btrfs".to_string(),
+            "ext4".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_cycle_mountpoint_skips_taken_ones() {
+        let mut state = state("/dev/sda2=/:btrfs");
+        state.table.select_row(2);
+        state.cycle_mountpoint();
+        // / is taken, xfs can stay on /home
+        assert_eq!(state.plan.to_string(), "/dev/sda2=/:btrfs,/dev/sda3=/home");
+
+        state.table.select_row(1);
+        // From / past the taken /home to swap
+        for _ in 0..MOUNTPOINTS.len() - 2 {
+            state.cycle_mountpoint();
+        }
+        assert_eq!(
+            state.plan.get("/dev/sda2").unwrap().to_string(),
+            "/dev/sda2=swap:swap"
+        );
+        state.cycle_mountpoint();
+        assert!(state.plan.get("/dev/sda2").is_none());
+    }
+
+    #[test]
+    fn test_cycle_format() {
+        let mut state = state("/dev/sda1=/efi,/dev/sda2=/:btrfs");
+        state.table.select_row(0);
+        state.cycle_format();
+        assert_eq!(
+            state.plan.get("/dev/sda1").unwrap().format.as_deref(),
+            Some("vfat")
+        );
+        state.cycle_format();
+        assert_eq!(state.plan.get("/dev/sda1").unwrap().format, None);
+
+        // The root never goes back to keeping its data
+        state.table.select_row(1);
+        for _ in 0..10 {
+            state.cycle_format();
+            assert!(state.plan.get("/dev/sda2").unwrap().format.is_some());
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! PTY-based embedded terminal component
 //!
 //! Provides an embedded terminal widget for running interactive tools like cfdisk.
+//! The PTY is sized to the area it is drawn in, so programs see the window
+//! size change as the TUI is resized.
 
 #![allow(dead_code)]
 
@@ -62,6 +64,8 @@ pub struct PtyTerminalState {
     pub tool_name: String,
     pub return_mode: crate::app::AppMode,
     pub return_menu_selection: usize,
+    /// The program exited; the next key press returns to `return_mode`
+    pub finished: bool,
 }
 
 /// PTY-based embedded terminal
@@ -72,6 +76,8 @@ pub struct PtyTerminal {
     size: PtySize,
     /// Output buffer shared with reader thread
     output_buffer: Arc<Mutex<Vec<u8>>>,
+    /// Master side of the PTY, kept to resize it
+    master: Option<Box<dyn portable_pty::MasterPty + Send>>,
     /// Writer to send input to PTY
     writer: Option<Box<dyn Write + Send>>,
     /// Child process handle
@@ -94,6 +100,7 @@ impl PtyTerminal {
                 pixel_height: 0,
             },
             output_buffer: Arc::new(Mutex::new(Vec::new())),
+            master: None,
             writer: None,
             child: None,
             running: Arc::new(Mutex::new(false)),
@@ -140,6 +147,7 @@ impl PtyTerminal {
             .map_err(|e| PtyError::Read(e.to_string()))?;
 
         // Set up shared state
+        self.master = Some(pair.master);
         self.writer = Some(writer);
         self.child = Some(child);
         *self.running.lock().unwrap() = true;
@@ -208,14 +216,15 @@ impl PtyTerminal {
     }
 
     /// Check if the terminal is still running
+    ///
+    /// The child is reaped here, so [`exit_status`](Self::exit_status) is
+    /// known once this returns `false` after the program exited.
     pub fn is_running(&mut self) -> bool {
-        // First check our flag
-        let flag = *self.running.lock().unwrap();
-        if !flag {
+        if self.exit_status.lock().unwrap().is_some() {
             return false;
         }
 
-        // Also check child process status
+        // The reader reaching EOF does not mean the child was reaped yet
         if let Some(ref mut child) = self.child {
             // try_wait returns Ok(Some(status)) if exited, Ok(None) if still running
             match child.try_wait() {
@@ -224,7 +233,7 @@ impl PtyTerminal {
                     *self.running.lock().unwrap() = false;
                     false
                 }
-                Ok(None) => true,
+                Ok(None) => *self.running.lock().unwrap(),
                 Err(_) => {
                     *self.running.lock().unwrap() = false;
                     false
@@ -249,8 +258,12 @@ impl PtyTerminal {
             pixel_height: 0,
         };
         self.parser.set_size(rows, cols);
-        // Note: Resizing the actual PTY would require keeping a reference to the master
-        // For now, we just update the parser size
+        // The kernel signals the new size to the program (SIGWINCH)
+        if let Some(ref master) = self.master {
+            master
+                .resize(self.size)
+                .map_err(|e| PtyError::Resize(e.to_string()))?;
+        }
         Ok(())
     }
 
@@ -277,6 +290,16 @@ impl PtyTerminal {
         let inner = block.inner(area);
         f.render_widget(block, area);
 
+        // Follow the area the terminal is drawn in
+        if (inner.width, inner.height) != (self.size.cols, self.size.rows)
+            && inner.width > 0
+            && inner.height > 0
+        {
+            if let Err(e) = self.resize(inner.width, inner.height) {
+                log::warn!("{}", e);
+            }
+        }
+
         // Get the screen content from the parser
         let screen = self.parser.screen();
         let mut lines: Vec<Line> = Vec::new();
@@ -0,0 +1,169 @@
+//! Recovery dialog component
+//!
+//! Shown when an installation phase fails: retry or skip the phase, open a
+//! shell, export the log or abort.
+
+use crate::recovery::{PhaseFailure, RecoveryChoice};
+use crate::theme::Colors;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the recovery dialog
+#[derive(Debug, Clone)]
+pub struct RecoveryDialogState {
+    /// Phase that failed
+    pub failure: PhaseFailure,
+    /// Highlighted choice
+    pub selected: usize,
+    /// Feedback from the last choice (log exported, skip refused)
+    pub message: Option<String>,
+}
+
+impl RecoveryDialogState {
+    pub fn new(failure: PhaseFailure) -> Self {
+        Self {
+            failure,
+            selected: 0,
+            message: None,
+        }
+    }
+
+    /// The highlighted choice
+    pub fn choice(&self) -> RecoveryChoice {
+        RecoveryChoice::all()[self.selected]
+    }
+
+    /// Move the highlight, skipping choices not offered for this failure
+    pub fn move_selection(&mut self, down: bool) {
+        let choices = RecoveryChoice::all();
+        let mut index = self.selected;
+        loop {
+            index = match down {
+                true if index + 1 < choices.len() => index + 1,
+                false if index > 0 => index - 1,
+                _ => return,
+            };
+            if choices[index].is_available(&self.failure) {
+                self.selected = index;
+                return;
+            }
+        }
+    }
+}
+
+/// Recovery dialog window
+pub struct RecoveryDialog;
+
+impl RecoveryDialog {
+    /// Render the recovery dialog
+    pub fn render(f: &mut Frame, state: &RecoveryDialogState) {
+        let area = f.area();
+        let choices = RecoveryChoice::all();
+
+        let width = 64u16.min(area.width.saturating_sub(4));
+        let height = (choices.len() as u16 + 10).min(area.height.saturating_sub(2));
+        let dialog_area = Rect::new(
+            (area.width.saturating_sub(width)) / 2,
+            (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        );
+
+        f.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Installation phase failed ")
+            .title_style(
+                Style::default()
+                    .fg(Colors::ERROR)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::ERROR))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(dialog_area);
+        f.render_widget(block, dialog_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(2),                    // Message
+                Constraint::Length(choices.len() as u16), // Choices
+                Constraint::Min(1),                       // Feedback
+                Constraint::Length(1),                    // Help text
+            ])
+            .split(inner);
+
+        let message = format!(
+            "{} failed. The installer is waiting for your decision.",
+            state.failure.phase
+        );
+        f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(Colors::FG_PRIMARY))
+                .wrap(Wrap { trim: true }),
+            chunks[0],
+        );
+
+        let lines: Vec<Line> = choices
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| {
+                let available = choice.is_available(&state.failure);
+                let mut label = format!(" {}  {}", choice.key(), choice.label());
+                if !available {
+                    label.push_str(" (critical phase)");
+                }
+                let style = if index == state.selected {
+                    Style::default()
+                        .fg(Colors::SELECTED_FG)
+                        .bg(Colors::PRIMARY)
+                        .add_modifier(Modifier::BOLD)
+                } else if available {
+                    Style::default().fg(Colors::FG_PRIMARY)
+                } else {
+                    Style::default().fg(Colors::FG_MUTED)
+                };
+                Line::from(Span::styled(label, style))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[1]);
+
+        if let Some(ref message) = state.message {
+            let message = Paragraph::new(message.clone())
+                .style(Style::default().fg(Colors::INFO_LIGHT))
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, chunks[2]);
+        }
+
+        let help = Paragraph::new("Up/Down Select | Enter Choose | r s t e a Shortcuts")
+            .style(Style::default().fg(Colors::FG_MUTED))
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_skips_unavailable_choices() {
+        let mut state = RecoveryDialogState::new(PhaseFailure {
+            phase: "Disk partitioning".to_string(),
+            critical: true,
+        });
+        state.move_selection(true);
+        assert_eq!(state.choice(), RecoveryChoice::Shell);
+        state.move_selection(false);
+        assert_eq!(state.choice(), RecoveryChoice::Retry);
+        state.move_selection(false);
+        assert_eq!(state.choice(), RecoveryChoice::Retry);
+    }
+}
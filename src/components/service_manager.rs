@@ -0,0 +1,301 @@
+//! systemd unit browser component
+//!
+//! Lists the units of the running or an installed system in a filterable
+//! table with their unit file and activation state.
+
+use crate::components::table::{Column, DataTable, TableCell, TableRow, TableState};
+use crate::theme::Colors;
+use crate::tools::services::{self, Target, Unit};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// State for the service manager
+#[derive(Debug, Clone)]
+pub struct ServiceManagerState {
+    /// System whose units are listed
+    pub target: Target,
+    /// Units, or the error message; None while systemctl is running
+    pub units: Option<Result<Vec<Unit>, String>>,
+    /// Text the list is filtered by
+    pub filter: String,
+    /// Whether keys are typed into the filter
+    pub filtering: bool,
+    /// Highlighted unit among the filtered ones
+    pub table: TableState,
+    /// Feedback from the last action
+    pub message: Option<String>,
+    /// Whether a systemctl action is running
+    pub busy: bool,
+}
+
+impl ServiceManagerState {
+    /// Create a state for a target whose units are still being listed
+    pub fn loading(target: Target) -> Self {
+        Self {
+            target,
+            units: None,
+            filter: String::new(),
+            filtering: false,
+            table: TableState::default(),
+            message: None,
+            busy: false,
+        }
+    }
+
+    /// Whether the units are still being listed
+    pub fn is_loading(&self) -> bool {
+        self.units.is_none()
+    }
+
+    /// Units matching the filter, in display order
+    pub fn visible(&self) -> Vec<&Unit> {
+        match self.units {
+            Some(Ok(ref units)) => services::filter(units, &self.filter),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Highlighted unit
+    pub fn selected_unit(&self) -> Option<&Unit> {
+        let row = self.table.selected_row()?;
+        self.visible().get(row).copied()
+    }
+
+    /// Take a fresh unit list, keeping the highlight on the same unit
+    pub fn set_units(&mut self, units: Result<Vec<Unit>, String>) {
+        let selected = self.selected_unit().map(|unit| unit.name.clone());
+        self.units = Some(units);
+        self.apply_filter();
+        if let Some(name) = selected {
+            if let Some(row) = self.visible().iter().position(|unit| unit.name == name) {
+                self.table.select_row(row);
+            }
+        }
+    }
+
+    /// Restart the table after the filter changed
+    pub fn apply_filter(&mut self) {
+        self.table = TableState::new(self.visible().len());
+    }
+}
+
+/// Colour of a unit file state
+fn enabled_color(unit: &Unit) -> Color {
+    if unit.is_enabled() {
+        Colors::SUCCESS
+    } else if unit.enabled.starts_with("masked") {
+        Colors::ERROR
+    } else if unit.enabled == "disabled" {
+        Colors::FG_PRIMARY
+    } else {
+        Colors::FG_SECONDARY
+    }
+}
+
+/// Colour of an activation state
+fn active_color(unit: &Unit) -> Color {
+    if unit.is_failed() {
+        return Colors::ERROR;
+    }
+    match unit.active.as_str() {
+        "active" | "reloading" => Colors::SUCCESS,
+        "activating" | "deactivating" => Colors::WARNING,
+        _ => Colors::FG_SECONDARY,
+    }
+}
+
+/// Service manager window
+pub struct ServiceManagerView;
+
+impl ServiceManagerView {
+    /// Render the service manager
+    pub fn render(f: &mut Frame, state: &ServiceManagerState) {
+        let area = f.area();
+
+        // Calculate centered area (80% width, 80% height)
+        let width = (area.width as f32 * 0.8) as u16;
+        let height = (area.height as f32 * 0.8) as u16;
+        let x = (area.width - width) / 2;
+        let y = (area.height - height) / 2;
+        let window_area = Rect::new(x, y, width, height);
+
+        f.render_widget(Clear, window_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Services: {} ", state.target))
+            .title_style(
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_style(Style::default().fg(Colors::PRIMARY))
+            .style(Style::default().bg(Colors::BG_PRIMARY));
+        let inner = block.inner(window_area);
+        f.render_widget(block, window_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(1), // Filter
+                Constraint::Min(3),    // Units
+                Constraint::Length(2), // Action feedback
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        f.render_widget(Paragraph::new(Self::filter_line(state)), chunks[0]);
+
+        match state.units {
+            None => f.render_widget(
+                Paragraph::new(Span::styled(
+                    format!("Listing units of the {}...", state.target),
+                    Style::default().fg(Colors::FG_SECONDARY),
+                )),
+                chunks[1],
+            ),
+            Some(Err(ref error)) => f.render_widget(
+                Paragraph::new(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Colors::ERROR),
+                ))
+                .wrap(Wrap { trim: false }),
+                chunks[1],
+            ),
+            Some(Ok(_)) => Self::render_units(f, state, chunks[1]),
+        }
+
+        let message = match (state.busy, &state.message) {
+            (true, _) => Some("Running systemctl...".to_string()),
+            (false, Some(message)) => Some(message.clone()),
+            (false, None) if state.target.is_offline() => Some(
+                "The installed system is not running: only enable, disable, mask and unmask apply"
+                    .to_string(),
+            ),
+            (false, None) => None,
+        };
+        if let Some(message) = message {
+            let message = Paragraph::new(message)
+                .style(Style::default().fg(Colors::INFO_LIGHT))
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, chunks[2]);
+        }
+
+        let help = if state.filtering {
+            "Type to filter | Enter Keep filter | Esc Clear filter"
+        } else {
+            "/ Filter | e/d Enable/Disable | s/x Start/Stop | m/u Mask/Unmask | r Refresh | c Switch system | Esc Back"
+        };
+        let help = Paragraph::new(help)
+            .style(Style::default().fg(Colors::FG_MUTED))
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[3]);
+    }
+
+    /// Filter prompt with the number of matching units
+    fn filter_line(state: &ServiceManagerState) -> Line<'static> {
+        let muted = Style::default().fg(Colors::FG_MUTED);
+        let mut spans = vec![Span::styled("Filter: ", muted)];
+        if state.filtering {
+            spans.push(Span::styled(
+                format!("{}_", state.filter),
+                Style::default().fg(Colors::FG_PRIMARY),
+            ));
+        } else if state.filter.is_empty() {
+            spans.push(Span::styled("none (press / to filter)", muted));
+        } else {
+            spans.push(Span::styled(
+                state.filter.clone(),
+                Style::default().fg(Colors::FG_PRIMARY),
+            ));
+        }
+        if let Some(Ok(ref units)) = state.units {
+            spans.push(Span::styled(
+                format!("   {} of {} units", state.visible().len(), units.len()),
+                muted,
+            ));
+        }
+        Line::from(spans)
+    }
+
+    /// Table of the filtered units
+    fn render_units(f: &mut Frame, state: &ServiceManagerState, area: Rect) {
+        let columns = [
+            Column::content("Unit", 40).unsorted(),
+            Column::content("State", 16).unsorted(),
+            Column::content("Active", 22).unsorted(),
+            Column::fill("Description", 10).unsorted(),
+        ];
+        let rows: Vec<TableRow> = state
+            .visible()
+            .into_iter()
+            .map(|unit| {
+                TableRow::new([
+                    TableCell::from(unit.name.as_str()),
+                    TableCell::new(if unit.enabled.is_empty() {
+                        "-"
+                    } else {
+                        unit.enabled.as_str()
+                    })
+                    .style(Style::default().fg(enabled_color(unit))),
+                    TableCell::new(unit.activation())
+                        .style(Style::default().fg(active_color(unit))),
+                    TableCell::from(unit.description.as_str()),
+                ])
+            })
+            .collect();
+
+        let table = DataTable::new(&columns, &rows)
+            .highlight_style(
+                Style::default()
+                    .fg(Colors::SUCCESS_LIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        // The state is borrowed immutably while drawing; the table only needs
+        // its own copy to lay out the visible lines
+        let mut table_state = state.table.clone();
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(name: &str, enabled: &str) -> Unit {
+        Unit {
+            name: name.to_string(),
+            enabled: enabled.to_string(),
+            ..Unit::default()
+        }
+    }
+
+    #[test]
+    fn test_refresh_keeps_the_selected_unit() {
+        let mut state = ServiceManagerState::loading(Target::Running);
+        state.set_units(Ok(vec![
+            unit("bluetooth.service", "disabled"),
+            unit("sshd.service", "disabled"),
+        ]));
+        state.table.select_next();
+        assert_eq!(state.selected_unit().unwrap().name, "sshd.service");
+
+        state.set_units(Ok(vec![
+            unit("avahi-daemon.service", "disabled"),
+            unit("bluetooth.service", "disabled"),
+            unit("sshd.service", "enabled"),
+        ]));
+        assert_eq!(state.selected_unit().unwrap().name, "sshd.service");
+
+        state.filter = "blue".to_string();
+        state.apply_filter();
+        assert_eq!(state.selected_unit().unwrap().name, "bluetooth.service");
+    }
+}
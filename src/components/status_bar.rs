@@ -0,0 +1,243 @@
+//! Status bar component
+//!
+//! The bottom line of every screen, in three segments:
+//! - left: the keybinding hints of the current screen (see [`NavBar`])
+//! - middle: the last status message, coloured by severity; info and
+//!   warnings disappear after a few seconds, errors stay until replaced
+//! - right: elapsed time while installing, otherwise the running tool
+
+use super::keybindings::NavBarItem;
+use super::nav_bar::NavBar;
+use crate::theme::Colors;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use std::time::{Duration, Instant};
+
+/// How long an info message stays visible
+const INFO_TTL: Duration = Duration::from_secs(5);
+/// How long a warning stays visible
+const WARNING_TTL: Duration = Duration::from_secs(10);
+
+/// Severity of a status message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl StatusLevel {
+    /// How long a message stays in the bar, `None` until replaced
+    pub fn ttl(self) -> Option<Duration> {
+        match self {
+            Self::Info => Some(INFO_TTL),
+            Self::Warning => Some(WARNING_TTL),
+            Self::Error => None,
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Self::Info => Style::default().fg(Colors::INFO),
+            Self::Warning => Style::default().fg(Colors::WARNING),
+            Self::Error => Style::default()
+                .fg(Colors::ERROR)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+/// A message posted to the status bar
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub level: StatusLevel,
+    pub posted: Instant,
+}
+
+impl StatusMessage {
+    /// Whether the message is still shown at `now`
+    pub fn is_visible(&self, now: Instant) -> bool {
+        self.level
+            .ttl()
+            .is_none_or(|ttl| now.saturating_duration_since(self.posted) < ttl)
+    }
+}
+
+/// Last status message
+#[derive(Debug, Clone, Default)]
+pub struct StatusBarState {
+    message: Option<StatusMessage>,
+}
+
+impl StatusBarState {
+    /// State holding an info message
+    pub fn new(text: impl Into<String>) -> Self {
+        let mut state = Self::default();
+        state.info(text);
+        state
+    }
+
+    /// Post a message, replacing the previous one
+    pub fn set(&mut self, level: StatusLevel, text: impl Into<String>) {
+        self.message = Some(StatusMessage {
+            text: text.into(),
+            level,
+            posted: Instant::now(),
+        });
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.set(StatusLevel::Info, text);
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.set(StatusLevel::Warning, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.set(StatusLevel::Error, text);
+    }
+
+    /// Text of the last message, expired or not ("" when none was posted)
+    pub fn text(&self) -> &str {
+        self.message.as_ref().map_or("", |m| m.text.as_str())
+    }
+
+    /// The message to show at `now`, `None` once it expired
+    pub fn visible(&self, now: Instant) -> Option<&StatusMessage> {
+        self.message.as_ref().filter(|m| m.is_visible(now))
+    }
+}
+
+/// Elapsed time as M:SS, or H:MM:SS from one hour on
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Bottom status bar
+pub struct StatusBar<'a> {
+    hints: NavBar,
+    message: Option<&'a StatusMessage>,
+    right: Option<String>,
+}
+
+impl<'a> StatusBar<'a> {
+    /// Create a status bar with the given keybinding hints
+    pub fn new(hints: Vec<NavBarItem>) -> Self {
+        Self {
+            hints: NavBar::new(hints),
+            message: None,
+            right: None,
+        }
+    }
+
+    /// Show a message in the middle segment
+    pub fn message(mut self, message: Option<&'a StatusMessage>) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Text of the right segment
+    pub fn right(mut self, text: Option<String>) -> Self {
+        self.right = text;
+        self
+    }
+
+    /// Render the bar; hints give way to the message when space is short
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let background = Style::default().bg(Colors::BG_SECONDARY);
+        f.render_widget(Paragraph::new("").style(background), area);
+
+        let right = self.right.as_deref().unwrap_or("");
+        let right_width = if right.is_empty() {
+            0
+        } else {
+            right.chars().count() as u16 + 2
+        };
+        let message = self.message.map(|m| (m, m.text.chars().count() as u16 + 4));
+        let hints_width = match message {
+            // Keep at least half the bar for the hints
+            Some((_, needed)) => self.hints.width().min(
+                area.width
+                    .saturating_sub(right_width + needed)
+                    .max(area.width / 2),
+            ),
+            None => self.hints.width(),
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(hints_width),
+                Constraint::Min(0),
+                Constraint::Length(right_width),
+            ])
+            .split(area);
+
+        f.render_widget(Paragraph::new(Line::from(self.hints.spans())), chunks[0]);
+        if let Some((message, _)) = message {
+            let line = Line::from(vec![
+                Span::styled(" | ", Style::default().fg(Colors::FG_MUTED)),
+                Span::styled(message.text.as_str(), message.level.style()),
+            ]);
+            f.render_widget(Paragraph::new(line), chunks[1]);
+        }
+        if !right.is_empty() {
+            let line = Line::from(Span::styled(
+                format!(" {} ", right),
+                Style::default()
+                    .fg(Colors::SECONDARY)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            f.render_widget(Paragraph::new(line), chunks[2]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_expire_by_severity() {
+        let mut status = StatusBarState::new("Welcome");
+        let posted = status.visible(Instant::now()).unwrap().posted;
+        assert_eq!(status.text(), "Welcome");
+        assert!(status.visible(posted + Duration::from_secs(4)).is_some());
+        assert!(status.visible(posted + INFO_TTL).is_none());
+        // The text stays available after it left the bar
+        assert_eq!(status.text(), "Welcome");
+
+        status.warn("Check the disk");
+        let posted = status.visible(Instant::now()).unwrap().posted;
+        assert!(status.visible(posted + Duration::from_secs(9)).is_some());
+        assert!(status.visible(posted + WARNING_TTL).is_none());
+
+        status.error("Partitioning failed");
+        let message = status.visible(Instant::now()).unwrap();
+        let (posted, level) = (message.posted, message.level);
+        assert_eq!(level, StatusLevel::Error);
+        assert!(status.visible(posted + Duration::from_secs(3600)).is_some());
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(0)), "0:00");
+        assert_eq!(format_elapsed(Duration::from_secs(754)), "12:34");
+        assert_eq!(format_elapsed(Duration::from_secs(3723)), "1:02:03");
+    }
+}
@@ -0,0 +1,611 @@
+//! Scrollable table with sortable columns
+//!
+//! Dialogs that list columnar data (disks, package search results) build
+//! [`TableRow`]s in their own order and draw them with [`DataTable`] instead
+//! of padding strings by hand. [`TableState`] holds the highlighted line, the
+//! scroll position and the sort column; [`TableState::selected_row`] maps the
+//! highlighted line back to the caller's row, whatever the sort order.
+//! Sorting keeps the highlight on the same row.
+
+#![allow(dead_code)]
+
+use crate::theme::Colors;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Margin, Rect},
+    style::{Modifier, Style},
+    widgets::{
+        Block, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Table,
+        Widget,
+    },
+};
+
+/// Lines moved by Page Up/Down before the table has been drawn
+const DEFAULT_PAGE: usize = 10;
+
+/// How a column's width is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// Exactly this many cells
+    Fixed(u16),
+    /// As wide as the widest cell or title, but at most `max`
+    Content { max: u16 },
+    /// The space left over, at least `min`
+    Fill { min: u16 },
+}
+
+/// Column heading and sizing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub title: String,
+    pub width: ColumnWidth,
+    /// Whether Tab stops on this column when cycling the sort
+    pub sortable: bool,
+}
+
+impl Column {
+    pub fn fixed(title: impl Into<String>, width: u16) -> Self {
+        Self::new(title, ColumnWidth::Fixed(width))
+    }
+
+    pub fn content(title: impl Into<String>, max: u16) -> Self {
+        Self::new(title, ColumnWidth::Content { max })
+    }
+
+    pub fn fill(title: impl Into<String>, min: u16) -> Self {
+        Self::new(title, ColumnWidth::Fill { min })
+    }
+
+    fn new(title: impl Into<String>, width: ColumnWidth) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            sortable: true,
+        }
+    }
+
+    /// Leave the column out of the sort cycle, e.g. a selection mark
+    pub fn unsorted(mut self) -> Self {
+        self.sortable = false;
+        self
+    }
+}
+
+/// Value a cell sorts by
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    /// Case-insensitive text
+    Text(String),
+    /// Numbers such as sizes, which sort by value rather than by their text
+    Number(u64),
+}
+
+/// One cell: the text shown, its style and its sort key
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub text: String,
+    pub style: Style,
+    pub key: SortKey,
+}
+
+impl TableCell {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            key: SortKey::Text(text.to_lowercase()),
+            text,
+            style: Style::default(),
+        }
+    }
+
+    /// A cell showing `text` that sorts by `value`
+    pub fn number(text: impl Into<String>, value: u64) -> Self {
+        Self {
+            key: SortKey::Number(value),
+            ..Self::new(text)
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl From<&str> for TableCell {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for TableCell {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// One row of cells, in column order
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRow {
+    pub cells: Vec<TableCell>,
+    pub style: Style,
+}
+
+impl TableRow {
+    pub fn new<T: Into<TableCell>>(cells: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            cells: cells.into_iter().map(Into::into).collect(),
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// Direction of the sort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Highlight, scroll position and sort of a [`DataTable`]
+///
+/// Lines are in display order; the row order comes from the last draw, so
+/// key handlers between draws see what the user sees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableState {
+    /// Highlighted line
+    selected: usize,
+    /// First visible line
+    offset: usize,
+    /// Rows visible at once, from the last draw
+    height: usize,
+    /// Number of rows, from the last draw
+    len: usize,
+    /// Sort column and direction; `None` keeps the caller's order
+    sort: Option<(usize, SortOrder)>,
+    /// Columns Tab cycles through, from the last draw
+    sortable: Vec<usize>,
+    /// Row shown on each line
+    order: Vec<usize>,
+}
+
+impl Default for TableState {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl TableState {
+    /// State for `len` rows in the caller's order
+    pub fn new(len: usize) -> Self {
+        Self {
+            selected: 0,
+            offset: 0,
+            height: DEFAULT_PAGE,
+            len,
+            sort: None,
+            sortable: Vec::new(),
+            order: (0..len).collect(),
+        }
+    }
+
+    /// Caller's index of the highlighted row
+    pub fn selected_row(&self) -> Option<usize> {
+        self.order.get(self.selected).copied()
+    }
+
+    /// Highlight the caller's row `row`
+    pub fn select_row(&mut self, row: usize) {
+        if let Some(line) = self.order.iter().position(|&r| r == row) {
+            self.selected = line;
+            self.scroll_to_selected();
+        }
+    }
+
+    /// Highlighted line, in display order
+    pub fn selected_line(&self) -> usize {
+        self.selected
+    }
+
+    /// First visible line
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+        self.scroll_to_selected();
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.len.saturating_sub(1));
+        self.scroll_to_selected();
+    }
+
+    pub fn page_up(&mut self) {
+        self.selected = self.selected.saturating_sub(self.page());
+        self.scroll_to_selected();
+    }
+
+    pub fn page_down(&mut self) {
+        self.selected = (self.selected + self.page()).min(self.len.saturating_sub(1));
+        self.scroll_to_selected();
+    }
+
+    pub fn select_first(&mut self) {
+        self.selected = 0;
+        self.scroll_to_selected();
+    }
+
+    pub fn select_last(&mut self) {
+        self.selected = self.len.saturating_sub(1);
+        self.scroll_to_selected();
+    }
+
+    /// Sort by `column`, ascending first and reversed when already sorted by it
+    pub fn sort_by(&mut self, column: usize) {
+        self.sort = match self.sort {
+            Some((current, SortOrder::Ascending)) if current == column => {
+                Some((column, SortOrder::Descending))
+            }
+            _ => Some((column, SortOrder::Ascending)),
+        };
+    }
+
+    /// Sort by the next sortable column (Tab), back to unsorted after the last
+    pub fn sort_next_column(&mut self) {
+        let next = match self.sort {
+            None => self.sortable.first(),
+            Some((current, _)) => self.sortable.iter().find(|&&column| column > current),
+        };
+        self.sort = next.map(|&column| (column, SortOrder::Ascending));
+    }
+
+    /// Flip the direction of the current sort (Shift+Tab)
+    pub fn reverse_sort(&mut self) {
+        if let Some((column, order)) = self.sort {
+            let reversed = match order {
+                SortOrder::Ascending => SortOrder::Descending,
+                SortOrder::Descending => SortOrder::Ascending,
+            };
+            self.sort = Some((column, reversed));
+        }
+    }
+
+    fn page(&self) -> usize {
+        self.height.saturating_sub(1).max(1)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.height > 0 && self.selected >= self.offset + self.height {
+            self.offset = self.selected + 1 - self.height;
+        }
+        self.offset = self.offset.min(self.len.saturating_sub(self.height.max(1)));
+    }
+
+    /// Take the rows and size of a draw, keeping the highlight on its row
+    fn update(&mut self, columns: &[Column], rows: &[TableRow], height: usize) {
+        let highlighted = self.selected_row();
+        self.sortable = (0..columns.len())
+            .filter(|&column| columns[column].sortable)
+            .collect();
+        if self
+            .sort
+            .is_some_and(|(column, _)| !self.sortable.contains(&column))
+        {
+            self.sort = None;
+        }
+
+        self.order = (0..rows.len()).collect();
+        if let Some((column, order)) = self.sort {
+            let key = |row: usize| rows[row].cells.get(column).map(|cell| &cell.key);
+            // Stable, so equal keys keep the caller's order
+            self.order.sort_by(|&a, &b| {
+                let ordering = key(a).cmp(&key(b));
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+        }
+
+        self.len = rows.len();
+        self.height = height;
+        match highlighted.filter(|&row| row < rows.len()) {
+            Some(row) => self.select_row(row),
+            None => {
+                self.selected = self.selected.min(self.len.saturating_sub(1));
+                self.scroll_to_selected();
+            }
+        }
+    }
+}
+
+/// A table drawn from [`TableRow`]s with a [`TableState`]
+pub struct DataTable<'a> {
+    columns: &'a [Column],
+    rows: &'a [TableRow],
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+    highlight_symbol: &'a str,
+}
+
+impl<'a> DataTable<'a> {
+    pub fn new(columns: &'a [Column], rows: &'a [TableRow]) -> Self {
+        Self {
+            columns,
+            rows,
+            block: None,
+            highlight_style: Style::default()
+                .fg(Colors::SECONDARY)
+                .add_modifier(Modifier::BOLD),
+            highlight_symbol: "> ",
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn highlight_symbol(mut self, symbol: &'a str) -> Self {
+        self.highlight_symbol = symbol;
+        self
+    }
+
+    fn constraints(&self, sort: Option<(usize, SortOrder)>) -> Vec<Constraint> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| match column.width {
+                ColumnWidth::Fixed(width) => Constraint::Length(width),
+                ColumnWidth::Content { max } => {
+                    let title = heading(column, index, sort).chars().count();
+                    let widest = self
+                        .rows
+                        .iter()
+                        .filter_map(|row| row.cells.get(index))
+                        .map(|cell| cell.text.chars().count())
+                        .fold(title, usize::max);
+                    Constraint::Length(widest.min(max as usize) as u16)
+                }
+                ColumnWidth::Fill { min } => Constraint::Min(min),
+            })
+            .collect()
+    }
+}
+
+/// Column title with the sort direction marked
+fn heading(column: &Column, index: usize, sort: Option<(usize, SortOrder)>) -> String {
+    match sort {
+        Some((sorted, SortOrder::Ascending)) if sorted == index => format!("{} ▲", column.title),
+        Some((sorted, SortOrder::Descending)) if sorted == index => format!("{} ▼", column.title),
+        _ => column.title.clone(),
+    }
+}
+
+impl StatefulWidget for DataTable<'_> {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut TableState) {
+        let inner = match &self.block {
+            Some(block) => block.inner(area),
+            None => area,
+        };
+        if let Some(block) = self.block.clone() {
+            block.render(area, buf);
+        }
+        if inner.is_empty() {
+            return;
+        }
+
+        // One line for the header
+        let height = inner.height.saturating_sub(1) as usize;
+        state.update(self.columns, self.rows, height);
+
+        let header = Row::new(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| heading(column, index, state.sort)),
+        )
+        .style(
+            Style::default()
+                .fg(Colors::PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        let symbol_width = self.highlight_symbol.chars().count();
+        let lines = state
+            .order
+            .iter()
+            .enumerate()
+            .skip(state.offset)
+            .take(height)
+            .map(|(line, &row)| {
+                let row = &self.rows[row];
+                let highlighted = line == state.selected;
+                let cells = row.cells.iter().enumerate().map(|(index, cell)| {
+                    // The marker takes the start of the first column
+                    let text = match index {
+                        0 if highlighted => format!("{}{}", self.highlight_symbol, cell.text),
+                        0 => format!("{}{}", " ".repeat(symbol_width), cell.text),
+                        _ => cell.text.clone(),
+                    };
+                    Cell::from(text).style(cell.style)
+                });
+                let style = if highlighted {
+                    row.style.patch(self.highlight_style)
+                } else {
+                    row.style
+                };
+                Row::new(cells).style(style)
+            });
+
+        let mut constraints = self.constraints(state.sort);
+        if let Some(Constraint::Length(width)) = constraints.first_mut() {
+            *width += symbol_width as u16;
+        }
+        Widget::render(Table::new(lines, constraints).header(header), inner, buf);
+
+        if self.rows.len() > height {
+            let mut scrollbar =
+                ScrollbarState::new(self.rows.len().saturating_sub(height)).position(state.offset);
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .style(Style::default().fg(Colors::SCROLLBAR))
+                .render(
+                    area.inner(Margin {
+                        vertical: 1,
+                        horizontal: 0,
+                    }),
+                    buf,
+                    &mut scrollbar,
+                );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::content("Device", 12),
+            Column::content("Size", 8),
+            Column::fill("Model", 5),
+        ]
+    }
+
+    fn rows() -> Vec<TableRow> {
+        vec![
+            TableRow::new([
+                TableCell::new("/dev/sda"),
+                TableCell::number("1 TB", 1_000_000),
+                TableCell::new("Samsung"),
+            ]),
+            TableRow::new([
+                TableCell::new("/dev/nvme0n1"),
+                TableCell::number("500 GB", 500_000),
+                TableCell::new("WD"),
+            ]),
+            TableRow::new([
+                TableCell::new("/dev/sdb"),
+                TableCell::number("64 GB", 64_000),
+                TableCell::new("Kingston"),
+            ]),
+        ]
+    }
+
+    fn draw(state: &mut TableState, rows: &[TableRow], height: u16) -> Vec<String> {
+        let columns = columns();
+        let mut terminal = Terminal::new(TestBackend::new(40, height)).unwrap();
+        terminal
+            .draw(|f| {
+                f.render_stateful_widget(DataTable::new(&columns, rows), f.area(), state);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (0..40)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sorting_keeps_the_highlighted_row() {
+        let rows = rows();
+        let mut state = TableState::new(rows.len());
+        draw(&mut state, &rows, 6);
+        state.select_next();
+        assert_eq!(state.selected_row(), Some(1));
+
+        // Sizes sort by value, not by their text
+        state.sort_by(1);
+        let screen = draw(&mut state, &rows, 6);
+        assert!(screen[0].contains("Size ▲"), "{:?}", screen);
+        assert!(screen[1].contains("/dev/sdb"), "{:?}", screen);
+        assert!(screen[3].contains("/dev/sda"), "{:?}", screen);
+        assert_eq!(state.selected_row(), Some(1));
+        assert_eq!(state.selected_line(), 1);
+        assert!(screen[2].starts_with("> /dev/nvme0n1"), "{:?}", screen);
+
+        state.reverse_sort();
+        draw(&mut state, &rows, 6);
+        assert_eq!(state.sort(), Some((1, SortOrder::Descending)));
+        assert_eq!(state.selected_line(), 1);
+
+        // Tab walks the columns, then back to the caller's order
+        state.sort_next_column();
+        assert_eq!(state.sort(), Some((2, SortOrder::Ascending)));
+        state.sort_next_column();
+        assert_eq!(state.sort(), None);
+        draw(&mut state, &rows, 6);
+        assert_eq!(state.selected_row(), Some(1));
+    }
+
+    #[test]
+    fn test_scrolls_to_the_highlighted_line() {
+        let rows: Vec<TableRow> = (0..20)
+            .map(|n| TableRow::new([format!("/dev/sd{}", n), "1".into(), "x".into()]))
+            .collect();
+        let mut state = TableState::new(rows.len());
+        // Header and four rows
+        draw(&mut state, &rows, 5);
+        for _ in 0..6 {
+            state.select_next();
+        }
+        assert_eq!(state.offset(), 3);
+        let screen = draw(&mut state, &rows, 5);
+        assert!(screen[4].starts_with("> /dev/sd6"), "{:?}", screen);
+
+        state.page_down();
+        assert_eq!(state.selected_line(), 9);
+        state.select_last();
+        assert_eq!((state.selected_line(), state.offset()), (19, 16));
+        state.page_up();
+        state.select_first();
+        assert_eq!((state.selected_line(), state.offset()), (0, 0));
+    }
+
+    #[test]
+    fn test_content_columns_fit_the_widest_cell() {
+        let columns = columns();
+        let rows = rows();
+        let table = DataTable::new(&columns, &rows);
+        assert_eq!(
+            table.constraints(None),
+            [
+                Constraint::Length(12),
+                Constraint::Length(6),
+                Constraint::Min(5)
+            ]
+        );
+        assert_eq!(
+            table.constraints(Some((1, SortOrder::Ascending)))[1],
+            Constraint::Length(6)
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! Tool parameter form component
+//!
+//! The tool dialog shows one widget per parameter: a chooser for selections,
+//! a toggle for booleans, a number field with increment keys and text fields,
+//! masked for passwords. Values are kept as the strings the tools read: the
+//! chosen item, `true` or `false`, the digits of a number.
+//!
+//! Number fields start blank, which leaves the tool its own default; stepping
+//! a blank field starts from the default of the parameter.
+
+use crate::app::{ToolDialogState, ToolParam, ToolParameter};
+use crate::input;
+use crate::theme::Colors;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Shown for the empty item of a selection
+const NO_CHOICE: &str = "(none)";
+
+/// Value a parameter starts with
+fn initial_value(param: &ToolParam) -> String {
+    match &param.param_type {
+        ToolParameter::Text(value) | ToolParameter::Password(value) => value.clone(),
+        ToolParameter::Boolean(value) => value.to_string(),
+        ToolParameter::Selection(items, index) => items
+            .get(*index)
+            .or_else(|| items.first())
+            .cloned()
+            .unwrap_or_default(),
+        ToolParameter::Number(_) => String::new(),
+    }
+}
+
+impl ToolDialogState {
+    /// Form for `tool_name` with every field at its initial value
+    pub fn new(tool_name: &str, parameters: Vec<ToolParam>) -> Self {
+        let param_values = parameters.iter().map(initial_value).collect();
+        Self {
+            tool_name: tool_name.to_string(),
+            parameters,
+            current_param: 0,
+            param_values,
+            is_executing: false,
+        }
+    }
+
+    /// Whether the focused field is the last one
+    pub fn on_last_field(&self) -> bool {
+        self.current_param + 1 >= self.parameters.len()
+    }
+
+    /// Focus the next field, wrapping around to the first
+    pub fn focus_next(&mut self) {
+        if !self.parameters.is_empty() {
+            self.current_param = (self.current_param + 1) % self.parameters.len();
+        }
+    }
+
+    /// Focus the previous field, wrapping around to the last
+    pub fn focus_previous(&mut self) {
+        if !self.parameters.is_empty() {
+            self.current_param =
+                (self.current_param + self.parameters.len() - 1) % self.parameters.len();
+        }
+    }
+
+    /// Type a character into the focused field
+    ///
+    /// Text fields take any character and number fields digits, with `+` and
+    /// `-` stepping the number. Space flips a toggle and advances a chooser;
+    /// other characters jump a chooser to the next item starting with them.
+    pub fn insert_char(&mut self, c: char) {
+        let Some(param) = self.parameters.get(self.current_param) else {
+            return;
+        };
+        match &param.param_type {
+            ToolParameter::Text(_) | ToolParameter::Password(_) => {
+                if let Some(value) = self.param_values.get_mut(self.current_param) {
+                    input::insert_char(value, c);
+                }
+            }
+            ToolParameter::Number(_) => match c {
+                '+' => self.step(true),
+                '-' => self.step(false),
+                c if c.is_ascii_digit() => {
+                    if let Some(value) = self.param_values.get_mut(self.current_param) {
+                        value.push(c);
+                    }
+                }
+                _ => {}
+            },
+            ToolParameter::Boolean(_) if c == ' ' => self.step(true),
+            ToolParameter::Boolean(_) => {}
+            ToolParameter::Selection(items, _) => {
+                if c == ' ' {
+                    self.step(true);
+                    return;
+                }
+                let current = self.selected_index().unwrap_or(0);
+                let matching = (1..=items.len())
+                    .map(|offset| (current + offset) % items.len())
+                    .find(|&index| {
+                        items[index]
+                            .chars()
+                            .next()
+                            .is_some_and(|first| first.eq_ignore_ascii_case(&c))
+                    });
+                if let Some(index) = matching {
+                    self.param_values[self.current_param] = items[index].clone();
+                }
+            }
+        }
+    }
+
+    /// Delete the last character of a text or number field
+    pub fn delete_char(&mut self) {
+        let editable = self
+            .parameters
+            .get(self.current_param)
+            .is_some_and(|param| {
+                matches!(
+                    param.param_type,
+                    ToolParameter::Text(_) | ToolParameter::Password(_) | ToolParameter::Number(_)
+                )
+            });
+        if editable {
+            if let Some(value) = self.param_values.get_mut(self.current_param) {
+                input::delete_last_char(value);
+            }
+        }
+    }
+
+    /// Change the focused value: the next or previous item of a chooser, the
+    /// other state of a toggle, one more or less for a number
+    ///
+    /// Choosers wrap around; numbers stop at zero.
+    pub fn step(&mut self, forward: bool) {
+        let Some(param) = self.parameters.get(self.current_param) else {
+            return;
+        };
+        let current = self.selected_index();
+        let Some(value) = self.param_values.get_mut(self.current_param) else {
+            return;
+        };
+        match &param.param_type {
+            ToolParameter::Selection(items, _) if !items.is_empty() => {
+                let index = match (current, forward) {
+                    (Some(index), true) => (index + 1) % items.len(),
+                    (Some(index), false) => (index + items.len() - 1) % items.len(),
+                    (None, _) => 0,
+                };
+                *value = items[index].clone();
+            }
+            ToolParameter::Boolean(_) => {
+                *value = (value != "true").to_string();
+            }
+            ToolParameter::Number(default) => {
+                *value = match value.parse::<i32>() {
+                    Ok(number) if forward => number.saturating_add(1),
+                    Ok(number) => number.saturating_sub(1).max(0),
+                    Err(_) => (*default).max(0),
+                }
+                .to_string();
+            }
+            _ => {}
+        }
+    }
+
+    /// Index of the focused chooser's value among its items
+    fn selected_index(&self) -> Option<usize> {
+        let param = self.parameters.get(self.current_param)?;
+        let value = self.param_values.get(self.current_param)?;
+        match &param.param_type {
+            ToolParameter::Selection(items, _) => items.iter().position(|item| item == value),
+            _ => None,
+        }
+    }
+
+    /// Check the form before the tool runs
+    ///
+    /// Returns the first field that is required and left empty or holds
+    /// something that is not a whole number, with the reason.
+    pub fn check(&self) -> Result<(), (usize, String)> {
+        for (index, param) in self.parameters.iter().enumerate() {
+            let value = self
+                .param_values
+                .get(index)
+                .map(|value| value.trim())
+                .unwrap_or_default();
+            if param.required && value.is_empty() {
+                return Err((index, format!("{} is required", param.name)));
+            }
+            if matches!(param.param_type, ToolParameter::Number(_))
+                && !value.is_empty()
+                && value.parse::<i32>().is_err()
+            {
+                return Err((index, format!("{} must be a whole number", param.name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Widget showing a value according to the parameter type
+fn widget(param: &ToolParam, value: &str, focused: bool) -> Vec<Span<'static>> {
+    let value_style = if focused {
+        Style::default()
+            .fg(Colors::SECONDARY)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Colors::FG_PRIMARY)
+    };
+    let hint_style = Style::default().fg(Colors::FG_SECONDARY);
+    let cursor = if focused { "_" } else { "" };
+
+    match &param.param_type {
+        ToolParameter::Text(_) => vec![Span::styled(format!("{}{}", value, cursor), value_style)],
+        ToolParameter::Password(_) => vec![Span::styled(
+            format!("{}{}", input::mask(value), cursor),
+            value_style,
+        )],
+        ToolParameter::Boolean(_) => {
+            let on = value == "true";
+            vec![Span::styled(
+                format!(
+                    "[{}] {}",
+                    if on { "x" } else { " " },
+                    if on { "yes" } else { "no" }
+                ),
+                value_style,
+            )]
+        }
+        ToolParameter::Selection(items, _) => {
+            let shown = if value.is_empty() { NO_CHOICE } else { value };
+            let mut spans = vec![Span::styled(format!("◀ {} ▶", shown), value_style)];
+            if let Some(index) = items.iter().position(|item| item == value) {
+                spans.push(Span::styled(
+                    format!("  {}/{}", index + 1, items.len()),
+                    hint_style,
+                ));
+            }
+            spans
+        }
+        ToolParameter::Number(default) if value.is_empty() => vec![
+            Span::styled(cursor.to_string(), value_style),
+            Span::styled(format!("(default: {})", default), hint_style),
+        ],
+        ToolParameter::Number(_) => vec![
+            Span::styled(format!("{}{}", value, cursor), value_style),
+            Span::styled("  -/+", hint_style),
+        ],
+    }
+}
+
+/// Tool parameter form window
+pub struct ToolForm;
+
+impl ToolForm {
+    /// Render the form centered in the frame
+    pub fn render(f: &mut Frame, dialog: &ToolDialogState) {
+        let area = f.area();
+        let width = (area.width * 3 / 4).min(80);
+        let height = (area.height * 3 / 4).min(20);
+        let dialog_area = Rect::new(
+            (area.width - width) / 2,
+            (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        f.render_widget(Clear, dialog_area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Configure {}", dialog.tool_name))
+            .style(Style::default().bg(Colors::FG_MUTED));
+        let inner = block.inner(dialog_area);
+        f.render_widget(block, dialog_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(1)
+            .vertical_margin(1)
+            .constraints([
+                Constraint::Min(1),    // Fields
+                Constraint::Length(2), // Description of the focused field
+                Constraint::Length(1), // Help text
+            ])
+            .split(inner);
+
+        // Keep the focused field in view on small terminals
+        let rows = chunks[0].height.max(1) as usize;
+        let first = (dialog.current_param + 1).saturating_sub(rows);
+        let label_width = dialog
+            .parameters
+            .iter()
+            .map(|param| param.name.chars().count() + usize::from(param.required))
+            .max()
+            .unwrap_or(0);
+
+        let lines: Vec<Line> = dialog
+            .parameters
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows)
+            .map(|(index, param)| {
+                let focused = index == dialog.current_param;
+                let value = dialog.param_values.get(index).map_or("", String::as_str);
+                let label = format!("{}{}", param.name, if param.required { "*" } else { "" });
+                let mut spans = vec![
+                    Span::styled(
+                        if focused { "> " } else { "  " },
+                        Style::default().fg(Colors::SECONDARY),
+                    ),
+                    Span::styled(
+                        format!("{:<width$}  ", label, width = label_width),
+                        Style::default().fg(Colors::PRIMARY),
+                    ),
+                ];
+                spans.extend(widget(param, value, focused));
+                Line::from(spans)
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[0]);
+
+        if let Some(param) = dialog.parameters.get(dialog.current_param) {
+            f.render_widget(
+                Paragraph::new(param.description.clone())
+                    .style(Style::default().fg(Colors::FG_SECONDARY))
+                    .wrap(Wrap { trim: true }),
+                chunks[1],
+            );
+        }
+
+        let action = if dialog.on_last_field() {
+            "Run"
+        } else {
+            "Next"
+        };
+        f.render_widget(
+            Paragraph::new(format!(
+                "Tab: Next field | Left/Right: Change | Enter: {} | Esc: Cancel",
+                action
+            ))
+            .style(Style::default().fg(Colors::FG_SECONDARY))
+            .alignment(Alignment::Center),
+            chunks[2],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, param_type: ToolParameter, required: bool) -> ToolParam {
+        ToolParam {
+            name: name.to_string(),
+            description: String::new(),
+            param_type,
+            required,
+        }
+    }
+
+    fn form() -> ToolDialogState {
+        ToolDialogState::new(
+            "format_partition",
+            vec![
+                param("device", ToolParameter::Text(String::new()), true),
+                param(
+                    "filesystem",
+                    ToolParameter::Selection(
+                        vec!["ext4".to_string(), "btrfs".to_string(), "xfs".to_string()],
+                        1,
+                    ),
+                    true,
+                ),
+                param("reserved", ToolParameter::Number(5), false),
+                param("compression", ToolParameter::Boolean(false), false),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_fields_start_at_their_defaults() {
+        let form = form();
+        assert_eq!(form.param_values, vec!["", "btrfs", "", "false"]);
+    }
+
+    #[test]
+    fn test_widgets_change_by_type() {
+        let mut form = form();
+        form.current_param = 1;
+        form.step(true);
+        assert_eq!(form.param_values[1], "xfs");
+        form.step(true);
+        assert_eq!(form.param_values[1], "ext4");
+        form.step(false);
+        assert_eq!(form.param_values[1], "xfs");
+        form.insert_char('B');
+        assert_eq!(form.param_values[1], "btrfs");
+        form.delete_char();
+        assert_eq!(form.param_values[1], "btrfs");
+
+        form.current_param = 2;
+        form.step(true);
+        assert_eq!(form.param_values[2], "5");
+        form.insert_char('+');
+        assert_eq!(form.param_values[2], "6");
+        form.insert_char('x');
+        form.insert_char('0');
+        assert_eq!(form.param_values[2], "60");
+        form.param_values[2] = "0".to_string();
+        form.step(false);
+        assert_eq!(form.param_values[2], "0");
+
+        form.current_param = 3;
+        form.insert_char(' ');
+        assert_eq!(form.param_values[3], "true");
+        form.step(false);
+        assert_eq!(form.param_values[3], "false");
+    }
+
+    #[test]
+    fn test_tab_navigation_wraps() {
+        let mut form = form();
+        form.focus_previous();
+        assert_eq!(form.current_param, 3);
+        assert!(form.on_last_field());
+        form.focus_next();
+        assert_eq!(form.current_param, 0);
+    }
+
+    #[test]
+    fn test_check_finds_the_first_bad_field() {
+        let mut form = form();
+        assert_eq!(form.check(), Err((0, "device is required".to_string())));
+        form.param_values[0] = "/dev/sda1".to_string();
+        assert_eq!(form.check(), Ok(()));
+        form.param_values[2] = "99999999999".to_string();
+        assert_eq!(
+            form.check(),
+            Err((2, "reserved must be a whole number".to_string()))
+        );
+    }
+}
@@ -2,6 +2,12 @@
 //!
 //! Handles all configuration options, validation, and environment variable mapping.
 
+pub mod options;
+
+use crate::password::PasswordPolicy;
+use crate::proxy::Proxy;
+use crate::types::{AudioServer, AutoToggle, BootMode, Bootloader, GuestTools, PartitionScheme};
+use options::OptionSpec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -51,66 +57,63 @@ impl ConfigOption {
         }
     }
 
+    /// Whether the value was changed from the default
+    pub fn is_modified(&self) -> bool {
+        !self.value.is_empty() && self.value != self.default_value
+    }
+
+    /// Restore the default value
+    pub fn reset(&mut self) {
+        self.value.clear();
+    }
+
     /// Validate the current value
     pub fn is_valid(&self) -> bool {
+        self.validation_error().is_none()
+    }
+
+    /// Get validation error message if invalid
+    pub fn validation_error(&self) -> Option<String> {
         if self.required && self.value.trim().is_empty() {
-            return false;
+            return Some(format!("{} is required", self.name));
         }
-        // Add specific validation based on field type
-        match self.name.as_str() {
-            "Username" | "Hostname" => {
-                let value = self.get_value();
-                value.len() >= 3
-                    && value.len() <= 32
-                    && value.chars().next().is_some_and(|c| c.is_alphabetic())
-                    && value.chars().all(|c| c.is_alphanumeric() || c == '_')
-            }
-            "User Password" | "Root Password" => {
-                let value = self.get_value();
-                !value.is_empty() && !value.contains(char::is_whitespace)
-            }
-            "Disk" => self.get_value().starts_with("/dev/"),
-            "Git Repository URL" => {
-                let value = self.get_value();
-                let trimmed = value.trim();
-                trimmed.is_empty()
-                    || trimmed.starts_with("http://")
-                    || trimmed.starts_with("https://")
-                    || trimmed.starts_with("git://")
-                    || trimmed.starts_with("ssh://")
-            }
-            _ => true, // Default: any non-empty value is valid
+        options::spec(&self.name)?.check(&self.get_value()).err()
+    }
+}
+
+/// Validity of an option as shown next to it in the guided installer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validity {
+    /// The value is valid on its own and with the other options
+    Valid,
+    /// The value itself is invalid
+    Invalid(String),
+    /// The value is valid but conflicts with another option
+    Conflict(String),
+}
+
+impl Validity {
+    /// Marker drawn after the value
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Valid => "✓",
+            Self::Invalid(_) => "✗",
+            Self::Conflict(_) => "⚠",
         }
     }
 
-    /// Get validation error message if invalid
-    pub fn validation_error(&self) -> Option<String> {
-        if !self.is_valid() {
-            if self.required && self.value.trim().is_empty() {
-                Some(format!("{} is required", self.name))
-            } else {
-                match self.name.as_str() {
-                    "Username" | "Hostname" => {
-                        Some(format!("{} must contain only alphanumeric characters and underscores, start with a letter, and be 3-32 characters", self.name))
-                    }
-                    "User Password" | "Root Password" => {
-                        Some(format!("{} cannot be empty or contain whitespace", self.name))
-                    }
-                    "Disk" => {
-                        Some(format!("{} must be a valid device path (e.g., /dev/sda)", self.name))
-                    }
-                    "Git Repository URL" => {
-                        Some(format!("{} must be a valid URL (http://, https://, git://, or ssh://)", self.name))
-                    }
-                    _ => Some(format!("{} has an invalid value", self.name))
-                }
-            }
-        } else {
-            None
+    /// Why the option cannot be installed as it is
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Valid => None,
+            Self::Invalid(reason) | Self::Conflict(reason) => Some(reason),
         }
     }
 }
 
+/// Value of an option that does not apply to the rest of the configuration
+pub const NOT_APPLICABLE: &str = "N/A";
+
 /// Complete configuration for the installation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Configuration {
@@ -118,183 +121,121 @@ pub struct Configuration {
     pub options: Vec<ConfigOption>,
 }
 
-impl Configuration {}
-
 impl Default for Configuration {
     fn default() -> Self {
         Self {
-            options: vec![
-                // Boot Setup (0-1)
-                ConfigOption::new("Boot Mode", true, "Boot firmware type (Auto/UEFI/BIOS)", ""),
-                ConfigOption::new(
-                    "Secure Boot",
-                    false,
-                    "Enable Secure Boot (WARNING: Requires UEFI setup)",
-                    "No",
-                ),
-                // System Locale and Input (2-3)
-                ConfigOption::new("Locale", true, "System locale", "en_US.UTF-8"),
-                ConfigOption::new("Keymap", true, "Keyboard layout", "us"),
-                // Disk and Storage (4-14)
-                ConfigOption::new("Disk", true, "Target disk for installation", ""),
-                ConfigOption::new(
-                    "Partitioning Strategy",
-                    true,
-                    "How to partition the disk",
-                    "",
-                ),
-                ConfigOption::new("Encryption", false, "Enable disk encryption", "Auto"),
-                ConfigOption::new("Root Filesystem", true, "Root partition filesystem", "ext4"),
-                ConfigOption::new(
-                    "Separate Home Partition",
-                    false,
-                    "Create separate /home partition",
-                    "No",
-                ),
-                ConfigOption::new(
-                    "Home Filesystem",
-                    false,
-                    "Home partition filesystem",
-                    "ext4",
-                ),
-                ConfigOption::new("Swap", false, "Enable swap partition", "Yes"),
-                ConfigOption::new("Swap Size", false, "Swap partition size", "2GB"),
-                ConfigOption::new("Btrfs Snapshots", false, "Enable Btrfs snapshots", "No"),
-                ConfigOption::new(
-                    "Btrfs Frequency",
-                    false,
-                    "Btrfs snapshot frequency",
-                    "weekly",
-                ),
-                ConfigOption::new(
-                    "Btrfs Keep Count",
-                    false,
-                    "Number of snapshots to keep",
-                    "3",
-                ),
-                ConfigOption::new("Btrfs Assistant", false, "Use Btrfs assistant", "No"),
-                // Time and Location (15-17)
-                ConfigOption::new("Timezone Region", true, "Timezone region", "America"),
-                ConfigOption::new("Timezone", true, "Timezone city", "New_York"),
-                ConfigOption::new(
-                    "Time Sync (NTP)",
-                    false,
-                    "Enable NTP time synchronization",
-                    "Yes",
-                ),
-                // System Packages (18-22)
-                ConfigOption::new(
-                    "Mirror Country",
-                    true,
-                    "Package mirror country",
-                    "United States",
-                ),
-                ConfigOption::new("Kernel", true, "Linux kernel to install", "linux"),
-                ConfigOption::new("Multilib", false, "Enable multilib repository", "Yes"),
-                ConfigOption::new(
-                    "Additional Pacman Packages",
-                    false,
-                    "Extra packages to install",
-                    "",
-                ),
-                ConfigOption::new("GPU Drivers", false, "Graphics drivers", "Auto"),
-                // Hostname (23)
-                ConfigOption::new("Hostname", true, "System hostname", ""),
-                // User Setup (24-26)
-                ConfigOption::new("Username", true, "Primary user account", ""),
-                ConfigOption::new("User Password", true, "User account password", ""),
-                ConfigOption::new("Root Password", true, "Root account password", ""),
-                // Package Management (27-29)
-                ConfigOption::new("AUR Helper", false, "AUR package helper", "paru"),
-                ConfigOption::new("Additional AUR Packages", false, "Extra AUR packages", ""),
-                ConfigOption::new("Flatpak", false, "Enable Flatpak support", "No"),
-                // Boot Configuration (30-32)
-                ConfigOption::new("Bootloader", true, "Boot loader", "grub"),
-                ConfigOption::new("OS Prober", false, "Enable OS detection", "Yes"),
-                ConfigOption::new("GRUB Theme", false, "Enable GRUB themes", "No"),
-                ConfigOption::new("GRUB Theme Selection", false, "GRUB theme to use", "arch"),
-                // Desktop Environment (33-34)
-                ConfigOption::new("Desktop Environment", false, "Desktop environment", "KDE"),
-                ConfigOption::new("Display Manager", false, "Display manager", "sddm"),
-                // Boot Splash and Final Setup (35-38)
-                ConfigOption::new("Plymouth", false, "Boot splash screen", "Yes"),
-                ConfigOption::new("Plymouth Theme", false, "Plymouth theme", "arch-glow"),
-                ConfigOption::new("Numlock on Boot", false, "Enable numlock at boot", "Yes"),
-                ConfigOption::new(
-                    "Git Repository",
-                    false,
-                    "Clone installation repository",
-                    "No",
-                ),
-                ConfigOption::new(
-                    "Git Repository URL",
-                    false,
-                    "Git repository URL to clone",
-                    "",
-                ),
-            ],
+            options: options::OPTIONS.iter().map(OptionSpec::option).collect(),
         }
     }
 }
 
 impl Configuration {
+    /// Number of options changed from their defaults
+    pub fn modified_count(&self) -> usize {
+        self.options
+            .iter()
+            .filter(|option| option.is_modified())
+            .count()
+    }
+
+    /// Restore the default value of every option
+    pub fn reset_all(&mut self) {
+        self.options.iter_mut().for_each(ConfigOption::reset);
+    }
+
+    /// Value of the option called `name`, empty when there is none
+    pub fn value(&self, name: &str) -> String {
+        self.options
+            .iter()
+            .find(|option| option.name == name)
+            .map(ConfigOption::get_value)
+            .unwrap_or_default()
+    }
+
+    /// The option called `name`
+    pub fn option_mut(&mut self, name: &str) -> Option<&mut ConfigOption> {
+        self.options.iter_mut().find(|option| option.name == name)
+    }
+
+    /// Turn off the options whose requirement stopped holding since `before`
+    ///
+    /// They take their disabled value from the option table. An option
+    /// left at [`NOT_APPLICABLE`] gets its default back once its requirement
+    /// holds again; a "No" or "none" is a real choice and stays. Options are
+    /// visited in table order, so a chain of requirements settles in one pass.
+    pub fn apply_requirements(&mut self, before: &Configuration) {
+        for index in 0..self.options.len() {
+            let Some(spec) = options::spec(&self.options[index].name) else {
+                continue;
+            };
+            let Some(disabled) = spec.disabled_value else {
+                continue;
+            };
+            let applied = spec.requirement(before).is_ok();
+            let applies = spec.requirement(self).is_ok();
+            let option = &mut self.options[index];
+            if applied && !applies {
+                option.value = disabled.to_string();
+            } else if !applied && applies && option.value == NOT_APPLICABLE {
+                option.reset();
+            }
+        }
+    }
+
     /// Convert configuration to environment variables for the installer
     pub fn to_env_vars(&self) -> HashMap<String, String> {
         let mut env_vars = HashMap::new();
 
-        // Map configuration options to environment variables by name (more maintainable)
         for option in &self.options {
-            let env_name = match option.name.as_str() {
-                "Boot Mode" => "BOOT_MODE",
-                "Secure Boot" => "SECURE_BOOT",
-                "Locale" => "LOCALE",
-                "Keymap" => "KEYMAP",
-                "Disk" => "INSTALL_DISK",
-                "Partitioning Strategy" => "PARTITIONING_STRATEGY",
-                "Encryption" => "ENCRYPTION",
-                "Root Filesystem" => "ROOT_FILESYSTEM",
-                "Separate Home Partition" => "SEPARATE_HOME",
-                "Home Filesystem" => "HOME_FILESYSTEM",
-                "Swap" => "SWAP",
-                "Swap Size" => "SWAP_SIZE",
-                "Btrfs Snapshots" => "BTRFS_SNAPSHOTS",
-                "Btrfs Frequency" => "BTRFS_FREQUENCY",
-                "Btrfs Keep Count" => "BTRFS_KEEP_COUNT",
-                "Btrfs Assistant" => "BTRFS_ASSISTANT",
-                "Timezone Region" => "TIMEZONE_REGION",
-                "Timezone" => "TIMEZONE",
-                "Time Sync (NTP)" => "TIME_SYNC",
-                "Mirror Country" => "MIRROR_COUNTRY",
-                "Kernel" => "KERNEL",
-                "Multilib" => "MULTILIB",
-                "Additional Pacman Packages" => "ADDITIONAL_PACKAGES",
-                "GPU Drivers" => "GPU_DRIVERS",
-                "Hostname" => "SYSTEM_HOSTNAME",
-                "Username" => "MAIN_USERNAME",
-                // SECURITY: Passwords are NOT passed via environment variables
-                // They are passed via stdin to prevent /proc/<pid>/environ exposure
-                // See get_passwords() method and installer.rs for secure handling
-                "User Password" => continue,
-                "Root Password" => continue,
-                "AUR Helper" => "AUR_HELPER",
-                "Additional AUR Packages" => "ADDITIONAL_AUR_PACKAGES",
-                "Flatpak" => "FLATPAK",
-                "Bootloader" => "BOOTLOADER",
-                "OS Prober" => "OS_PROBER",
-                "GRUB Theme" => "GRUB_THEME",
-                "GRUB Theme Selection" => "GRUB_THEME_SELECTION",
-                "Desktop Environment" => "DESKTOP_ENVIRONMENT",
-                "Display Manager" => "DISPLAY_MANAGER",
-                "Plymouth" => "PLYMOUTH",
-                "Plymouth Theme" => "PLYMOUTH_THEME",
-                "Numlock on Boot" => "NUMLOCK_ON_BOOT",
-                "Git Repository" => "GIT_REPOSITORY",
-                "Git Repository URL" => "GIT_REPOSITORY_URL",
-                _ => continue, // Skip unknown options
-            };
+            // Passwords have no key, see get_passwords()
+            if let Some(key) = options::spec(&option.name).and_then(|spec| spec.key) {
+                env_vars.insert(key.to_string(), option.get_value());
+            }
+        }
+
+        // The scripts install groups as part of the additional packages
+        let groups: Vec<String> = env_vars
+            .get("PACKAGE_GROUPS")
+            .map(|ids| ids.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        if let Some(packages) = env_vars.get_mut("ADDITIONAL_PACKAGES") {
+            *packages = crate::package_utils::merge_packages(packages, &groups);
+        }
 
-            env_vars.insert(env_name.to_string(), option.get_value());
+        // chroot_config.sh installs the desktop's packages from the catalogue
+        let desktop_packages = env_vars
+            .get("DESKTOP_ENVIRONMENT")
+            .and_then(|id| crate::desktop::find_desktop(id))
+            .map(|desktop| desktop.packages.join(" "))
+            .unwrap_or_default();
+        env_vars.insert("DESKTOP_PACKAGES".to_string(), desktop_packages);
+
+        // ...and the guest tools' and sound server's packages from their types
+        let guest_tools: GuestTools = env_vars
+            .get("VM_GUEST_TOOLS")
+            .and_then(|tools| tools.parse().ok())
+            .unwrap_or_default();
+        let audio: AudioServer = env_vars
+            .get("AUDIO")
+            .and_then(|audio| audio.parse().ok())
+            .unwrap_or_default();
+        for (name, value) in guest_tools.env_vars().into_iter().chain(audio.env_vars()) {
+            env_vars.insert(name.to_string(), value);
         }
+        let time_sync = match env_vars.get("TIME_SYNC").map(String::as_str) {
+            Some("No") => None,
+            _ => Some(
+                env_vars
+                    .get("TIME_SYNC_DAEMON")
+                    .and_then(|daemon| daemon.parse().ok())
+                    .unwrap_or_default(),
+            ),
+        };
+        let service_packages = crate::services::packages(
+            env_vars.get("SERVICES").map_or("", String::as_str),
+            time_sync,
+        );
+        env_vars.insert("SERVICE_PACKAGES".to_string(), service_packages);
 
         env_vars
     }
@@ -328,12 +269,302 @@ impl Configuration {
 
         (user_password, root_password, encryption_password)
     }
+
+    /// Policy the user and root passwords must meet
+    pub fn password_policy(&self) -> PasswordPolicy {
+        PasswordPolicy::parse(&self.value("Password Minimum Length")).unwrap_or_default()
+    }
+
+    /// Proxy set in the proxy options
+    pub fn proxy(&self) -> Proxy {
+        Proxy {
+            http: self.value("HTTP Proxy"),
+            https: self.value("HTTPS Proxy"),
+            no_proxy: self.value("No Proxy"),
+        }
+    }
+
+    /// Options grouped by [`option_category`], categories in option order
+    pub fn by_category(&self) -> Vec<(&'static str, Vec<&ConfigOption>)> {
+        let mut groups: Vec<(&'static str, Vec<&ConfigOption>)> = Vec::new();
+        for option in &self.options {
+            let category = option_category(&option.name);
+            match groups.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, options)) => options.push(option),
+                None => groups.push((category, vec![option])),
+            }
+        }
+        groups
+    }
+}
+
+/// Section of the guided installer an option belongs to
+pub fn option_category(name: &str) -> &'static str {
+    options::spec(name).map_or("Final Setup", |spec| spec.category)
+}
+
+/// Longest hostname the kernel accepts (HOST_NAME_MAX)
+pub const HOSTNAME_MAX_LEN: usize = 64;
+
+/// Longest user name systemd accepts (utmp's 32-byte field minus the NUL)
+pub const USERNAME_MAX_LEN: usize = 31;
+
+/// Accounts and groups that already exist on an Arch system
+///
+/// Creating a user with one of these names fails or, worse, takes over a
+/// system account. `systemd-*` names are rejected separately.
+pub const RESERVED_USERNAMES: &[&str] = &[
+    "root", "bin", "daemon", "sys", "adm", "tty", "disk", "lp", "mail", "ftp", "http", "kmem",
+    "wheel", "log", "utmp", "audio", "video", "input", "kvm", "render", "storage", "optical",
+    "network", "power", "users", "uucp", "games", "nobody", "nogroup", "dbus", "polkitd", "uuidd",
+    "avahi", "git", "alpm",
+];
+
+/// Check a hostname against RFC 1123 and the kernel length limit
+///
+/// Dot-separated labels of 1-63 ASCII letters, digits and hyphens, not
+/// starting or ending with a hyphen, at most 64 characters in total.
+pub fn validate_hostname(hostname: &str) -> Result<(), String> {
+    if hostname.is_empty() {
+        return Err("Hostname is required".to_string());
+    }
+    if hostname.len() > HOSTNAME_MAX_LEN {
+        return Err(format!(
+            "Hostname must be at most {} characters",
+            HOSTNAME_MAX_LEN
+        ));
+    }
+    for label in hostname.split('.') {
+        if label.is_empty() {
+            return Err("Hostname cannot start or end with a dot or contain '..'".to_string());
+        }
+        if label.len() > 63 {
+            return Err("Each part of the hostname must be at most 63 characters".to_string());
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err("Hostname may only contain letters, digits, hyphens and dots".to_string());
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err("Hostname parts cannot start or end with a hyphen".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Check a user name against the portable POSIX/systemd rules
+///
+/// Starts with a lowercase letter or underscore, continues with lowercase
+/// letters, digits, underscores or hyphens, is at most 31 characters, and is
+/// not the name of a system account.
+pub fn validate_username(username: &str) -> Result<(), String> {
+    let Some(first) = username.chars().next() else {
+        return Err("Username is required".to_string());
+    };
+    if username.len() > USERNAME_MAX_LEN {
+        return Err(format!(
+            "Username must be at most {} characters",
+            USERNAME_MAX_LEN
+        ));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    {
+        return Err("Username may only contain lowercase letters, digits, '_' and '-'".to_string());
+    }
+    if !(first.is_ascii_lowercase() || first == '_') {
+        return Err("Username must start with a letter or underscore".to_string());
+    }
+    if RESERVED_USERNAMES.contains(&username) || username.starts_with("systemd-") {
+        return Err(format!("'{}' is reserved for a system account", username));
+    }
+    Ok(())
+}
+
+/// Check that /boot can live inside the LUKS container
+///
+/// Only GRUB can unlock LUKS before the kernel is loaded, so systemd-boot is
+/// ruled out. The container has to come from a non-RAID LUKS strategy, or
+/// from manual partitioning with encryption turned on.
+pub fn validate_encrypted_boot(
+    bootloader: Bootloader,
+    scheme: PartitionScheme,
+    encryption: AutoToggle,
+) -> Result<(), String> {
+    if bootloader != Bootloader::Grub {
+        return Err(format!(
+            "Encrypted /boot requires GRUB: {} cannot unlock LUKS",
+            bootloader
+        ));
+    }
+    if scheme.requires_raid() {
+        return Err("Encrypted /boot is not supported with RAID strategies".to_string());
+    }
+    let encrypted = match scheme {
+        PartitionScheme::Manual => encryption == AutoToggle::Yes,
+        _ => scheme.uses_encryption(),
+    };
+    if !encrypted {
+        return Err(
+            "Encrypted /boot needs an encrypted strategy (auto_simple_luks or auto_luks_lvm)"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Check that the bootloader can start the system in the chosen boot mode
+///
+/// `uefi_firmware` is whether the machine booted in UEFI mode, when known;
+/// Auto installs for that mode. systemd-boot is an EFI application, so a
+/// BIOS install needs GRUB (or U-Boot, see [`crate::arch`]).
+pub fn validate_boot_mode(
+    boot_mode: BootMode,
+    bootloader: Bootloader,
+    uefi_firmware: Option<bool>,
+) -> Result<(), String> {
+    let bios = match boot_mode {
+        BootMode::Bios => true,
+        BootMode::Uefi => false,
+        BootMode::Auto => uefi_firmware == Some(false),
+    };
+    if bios && bootloader == Bootloader::SystemdBoot {
+        return Err(format!(
+            "BIOS boot requires GRUB: {} only boots on UEFI",
+            bootloader
+        ));
+    }
+    Ok(())
+}
+
+/// What a BIOS install on a GPT disk needs from the partitioning strategy
+///
+/// GRUB's i386-pc core image has no post-MBR gap to live in on GPT, so it
+/// gets a 1 MiB BIOS boot partition. The automatic strategies create it;
+/// with manual partitioning it has to be made by hand, and existing
+/// partitions need one already.
+pub fn bios_gpt_note(scheme: PartitionScheme) -> String {
+    match scheme {
+        PartitionScheme::Manual => "BIOS on a GPT disk: create a 1 MiB BIOS boot partition \
+            (type EF02, no filesystem) for GRUB, or use an MBR partition table"
+            .to_string(),
+        PartitionScheme::Existing => "BIOS on a GPT disk: the disk needs a 1 MiB BIOS boot \
+            partition (type EF02, no filesystem) for GRUB already"
+            .to_string(),
+        _ if scheme.requires_raid() => "BIOS boot: every RAID disk gets a GPT table with a \
+            1 MiB BIOS boot partition and GRUB in its boot sector"
+            .to_string(),
+        _ => "BIOS boot: the disk gets a GPT table with a 1 MiB BIOS boot partition \
+            for GRUB"
+            .to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_options_grouped_by_category() {
+        let config = Configuration::default();
+        let groups = config.by_category();
+        let names: Vec<&str> = groups.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Boot Setup",
+                "Locale and Input",
+                "Disk and Storage",
+                "Time and Location",
+                "Packages",
+                "System and Users",
+                "Bootloader",
+                "Desktop",
+                "Services",
+                "Final Setup",
+            ]
+        );
+        let total: usize = groups.iter().map(|(_, options)| options.len()).sum();
+        assert_eq!(total, config.options.len());
+
+        let final_setup: Vec<&str> = groups[9].1.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(
+            final_setup,
+            vec![
+                "Plymouth",
+                "Plymouth Theme",
+                "Numlock on Boot",
+                "Git Repository",
+                "Git Repository URL"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_options_turn_off_with_their_requirement() {
+        let mut config = Configuration::default();
+        config.option_mut("Root Filesystem").unwrap().value = "btrfs".to_string();
+        config.option_mut("Btrfs Snapshots").unwrap().value = "Yes".to_string();
+
+        let before = config.clone();
+        config.option_mut("Swap").unwrap().value = "No".to_string();
+        config.option_mut("Root Filesystem").unwrap().value = "ext4".to_string();
+        config.apply_requirements(&before);
+        assert_eq!(config.value("Swap Size"), NOT_APPLICABLE);
+        assert_eq!(config.value("Btrfs Snapshots"), "No");
+        assert_eq!(config.value("Btrfs Frequency"), NOT_APPLICABLE);
+        // GRUB themes were already off and keep their selection
+        assert_eq!(config.value("GRUB Theme Selection"), "PolyDark");
+
+        let before = config.clone();
+        config.option_mut("Swap").unwrap().value = "Yes".to_string();
+        config.apply_requirements(&before);
+        assert_eq!(config.value("Swap Size"), "2GB");
+        assert!(!config.option_mut("Swap Size").unwrap().is_modified());
+    }
+
+    #[test]
+    fn test_encrypted_boot_requirements() {
+        use PartitionScheme::*;
+
+        assert!(
+            validate_encrypted_boot(Bootloader::Grub, AutoSimpleLuks, AutoToggle::Auto).is_ok()
+        );
+        assert!(validate_encrypted_boot(Bootloader::Grub, AutoLuksLvm, AutoToggle::Yes).is_ok());
+        assert!(validate_encrypted_boot(Bootloader::Grub, Manual, AutoToggle::Yes).is_ok());
+
+        let error =
+            validate_encrypted_boot(Bootloader::SystemdBoot, AutoSimpleLuks, AutoToggle::Yes)
+                .unwrap_err();
+        assert!(error.contains("systemd-boot"), "{}", error);
+        assert!(validate_encrypted_boot(Bootloader::Grub, AutoRaidLuks, AutoToggle::Yes).is_err());
+        assert!(validate_encrypted_boot(Bootloader::Grub, AutoLvm, AutoToggle::Yes).is_err());
+        assert!(validate_encrypted_boot(Bootloader::Grub, Manual, AutoToggle::No).is_err());
+    }
+
+    #[test]
+    fn test_boot_mode_requirements() {
+        use Bootloader::*;
+
+        assert!(validate_boot_mode(BootMode::Bios, Grub, Some(true)).is_ok());
+        assert!(validate_boot_mode(BootMode::Uefi, SystemdBoot, Some(false)).is_ok());
+        assert!(validate_boot_mode(BootMode::Auto, SystemdBoot, Some(true)).is_ok());
+        assert!(validate_boot_mode(BootMode::Auto, SystemdBoot, None).is_ok());
+
+        let error = validate_boot_mode(BootMode::Bios, SystemdBoot, None).unwrap_err();
+        assert!(error.contains("requires GRUB"), "{}", error);
+        assert!(validate_boot_mode(BootMode::Auto, SystemdBoot, Some(false)).is_err());
+        assert!(validate_boot_mode(BootMode::Bios, UBoot, None).is_ok());
+    }
+
+    #[test]
+    fn test_bios_gpt_note() {
+        assert!(bios_gpt_note(PartitionScheme::Manual).contains("EF02"));
+        assert!(bios_gpt_note(PartitionScheme::Existing).contains("already"));
+        assert!(bios_gpt_note(PartitionScheme::AutoLuksLvm).contains("1 MiB BIOS boot partition"));
+        assert!(bios_gpt_note(PartitionScheme::AutoRaidLvm).contains("every RAID disk"));
+    }
+
     #[test]
     fn test_config_option_new() {
         let option = ConfigOption::new("Test Option", true, "Test description", "default");
@@ -356,6 +587,28 @@ mod tests {
         assert_eq!(option.get_value(), "custom");
     }
 
+    #[test]
+    fn test_reset_to_default() {
+        let mut config = Configuration::default();
+        assert_eq!(config.modified_count(), 0);
+
+        let option = &mut config.options[0];
+        option.value = option.default_value.clone();
+        assert!(!option.is_modified());
+        option.value = "custom".to_string();
+        assert!(option.is_modified());
+        option.reset();
+        assert!(!option.is_modified());
+        assert_eq!(option.get_value(), option.default_value);
+
+        config.options[1].value = "custom".to_string();
+        config.options[2].value = "custom".to_string();
+        assert_eq!(config.modified_count(), 2);
+        config.reset_all();
+        assert_eq!(config.modified_count(), 0);
+        assert!(config.options.iter().all(|option| option.value.is_empty()));
+    }
+
     #[test]
     fn test_configuration_new() {
         let config = Configuration::default();
@@ -396,6 +649,20 @@ mod tests {
         assert!(env_vars.contains_key("INSTALL_DISK")); // At least one should be present
     }
 
+    #[test]
+    fn test_package_lists_in_env_vars() {
+        let mut config = Configuration::default();
+        config.option_mut("VM Guest Tools").unwrap().value = "VirtualBox".to_string();
+        let env_vars = config.to_env_vars();
+        assert_eq!(env_vars["GUEST_TOOLS_PACKAGES"], "virtualbox-guest-utils");
+        assert_eq!(env_vars["GUEST_TOOLS_SERVICES"], "vboxservice.service");
+        assert_eq!(
+            env_vars["AUDIO_REPLACES"],
+            "pulseaudio-bluetooth pulseaudio-alsa pulseaudio"
+        );
+        assert_eq!(env_vars["SERVICE_PACKAGES"], "");
+    }
+
     #[test]
     fn test_passwords_not_in_env_vars() {
         let mut config = Configuration::default();
@@ -445,4 +712,64 @@ mod tests {
         assert_eq!(root_pw, "root456");
         assert!(encrypt_pw.is_none()); // No encryption password set
     }
+
+    #[test]
+    fn test_validate_hostname() {
+        for valid in ["archbox", "my-pc", "1host", "host.example.org", "a"] {
+            assert!(
+                validate_hostname(valid).is_ok(),
+                "{} should be valid",
+                valid
+            );
+        }
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname("-host").is_err());
+        assert!(validate_hostname("host-").is_err());
+        assert!(validate_hostname("my_host").is_err());
+        assert!(validate_hostname("host..name").is_err());
+        assert!(validate_hostname(".host").is_err());
+        assert!(validate_hostname("my host").is_err());
+        assert!(validate_hostname(&"a".repeat(64)).is_err()); // label over 63
+        assert!(validate_hostname(&format!("{}.{}", "a".repeat(40), "b".repeat(24))).is_err());
+        assert!(validate_hostname(&format!("{}.{}", "a".repeat(40), "b".repeat(23))).is_ok());
+    }
+
+    #[test]
+    fn test_validate_username() {
+        for valid in ["alice", "_svc", "user-1", "dev_ops", "a"] {
+            assert!(
+                validate_username(valid).is_ok(),
+                "{} should be valid",
+                valid
+            );
+        }
+        assert!(validate_username("").is_err());
+        assert!(validate_username("Alice").is_err());
+        assert!(validate_username("1user").is_err());
+        assert!(validate_username("-user").is_err());
+        assert!(validate_username("user.name").is_err());
+        assert!(validate_username("user@host").is_err());
+        assert!(validate_username(&"a".repeat(32)).is_err());
+        assert!(validate_username(&"a".repeat(31)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_username_rejects_reserved_names() {
+        for reserved in ["root", "daemon", "nobody", "wheel", "systemd-network"] {
+            let err = validate_username(reserved).unwrap_err();
+            assert!(err.contains("reserved"), "{}: {}", reserved, err);
+        }
+    }
+
+    #[test]
+    fn test_option_validation_uses_hostname_rules() {
+        let mut option = ConfigOption::new("Hostname", true, "System hostname", "");
+        option.value = "my-arch-pc".to_string();
+        assert!(option.is_valid());
+
+        option.value = "bad_host".to_string();
+        assert!(!option.is_valid());
+        let error = option.validation_error().unwrap();
+        assert!(error.contains("letters, digits"));
+    }
 }
@@ -0,0 +1,1691 @@
+//! Declarative table of the guided installer's options
+//!
+//! Every option is described once in [`OPTIONS`]: its name, the variable
+//! install.sh reads it from, its section, default, how it is edited, what it
+//! depends on, how its value is checked, what choosing it fills in and
+//! whether it is a password. The default configuration, the environment
+//! passed to the scripts, the dialogs, the live validation, the wizard's
+//! steps and the session file are all built from the table, so an option is
+//! added or changed in one place.
+
+use super::{ConfigOption, Configuration, NOT_APPLICABLE};
+use crate::arch::Arch;
+use crate::types::{
+    AudioServer, AurHelper, AutoToggle, BootMode, Bootloader, DesktopEnvironment, DisplayManager,
+    ExistingOsPolicy, Filesystem, GpuDriver, GrubTheme, GuestTools, HardwareClock, LidSwitch,
+    Microcode, NvidiaDriver, PartitionScheme, PlymouthTheme, PowerManagement, SnapshotFrequency,
+    TimeSyncDaemon, TmpPolicy, Toggle,
+};
+use std::fmt::Display;
+use strum::IntoEnumIterator;
+
+/// Check of a value, returning the error to show
+pub type Validator = fn(&str) -> Result<(), String>;
+
+/// Values offered for an option
+pub type Choices = fn() -> Vec<String>;
+
+/// Check that the other options leave room for an option, returning why not
+///
+/// The reason completes "<option> does not apply: ...".
+pub type Requirement = fn(&Configuration) -> Result<(), String>;
+
+/// Fill in other options once a value is chosen, returning what was set or
+/// what the value means
+pub type OnSet = fn(&mut Configuration, &str) -> Option<String>;
+
+/// How an option is edited in the guided installer
+#[derive(Debug, Clone, Copy)]
+pub enum Input {
+    /// Pick one of the choices
+    Select(Choices),
+    /// Check between `min` and `max` of the choices, see
+    /// [`crate::input::join_multi_select`]
+    MultiSelect {
+        choices: Choices,
+        min: usize,
+        max: usize,
+    },
+    /// Free text, run through `check` while it is typed
+    Text {
+        placeholder: &'static str,
+        check: Option<Validator>,
+    },
+    /// Hidden text, typed twice
+    Password { placeholder: &'static str },
+    /// Yes or No behind a warning about the firmware it needs
+    SecureBoot,
+    /// One disk, or several for RAID and manual partitioning
+    Disk,
+    /// Create an ESP or reuse one of those detected
+    EfiPartition,
+    /// Table of the selected disks' partitions to assign mountpoints to
+    PartitionMounts,
+    /// City of the selected timezone region
+    Timezone,
+    /// Checkbox list of the curated package groups
+    PackageGroups,
+    /// Checkbox list of the service catalogue
+    Services,
+    /// Interactive package search
+    Packages,
+}
+
+/// Description of one option
+#[derive(Debug, Clone, Copy)]
+pub struct OptionSpec {
+    /// Name shown in the guided installer and used in configuration files
+    pub name: &'static str,
+    /// Environment variable install.sh reads the value from; `None` for the
+    /// passwords, which are passed on stdin
+    pub key: Option<&'static str>,
+    /// Section of the guided installer
+    pub category: &'static str,
+    pub description: &'static str,
+    /// Whether the installation cannot start without a value
+    pub required: bool,
+    /// Never shown, logged or saved to the session file
+    secret: bool,
+    /// A URL whose credentials are never shown, logged or saved
+    credentials: bool,
+    default: &'static str,
+    computed_default: Option<fn() -> String>,
+    pub input: Input,
+    requires: Option<Requirement>,
+    /// Value the option takes while its requirement does not hold
+    pub disabled_value: Option<&'static str>,
+    validate: Option<Validator>,
+    on_set: Option<OnSet>,
+}
+
+impl OptionSpec {
+    /// An optional Yes/No option without a default or requirement
+    const fn new(
+        name: &'static str,
+        key: &'static str,
+        category: &'static str,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            key: Some(key),
+            category,
+            description,
+            required: false,
+            secret: false,
+            credentials: false,
+            default: "",
+            computed_default: None,
+            input: Input::Select(all::<Toggle>),
+            requires: None,
+            disabled_value: None,
+            validate: None,
+            on_set: None,
+        }
+    }
+
+    const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Keep the value out of the environment, the screen and the session
+    const fn secret(mut self) -> Self {
+        self.key = None;
+        self.secret = true;
+        self
+    }
+
+    /// The value is a URL that may carry a password, like a proxy's
+    const fn credentials(mut self) -> Self {
+        self.credentials = true;
+        self
+    }
+
+    const fn default(mut self, default: &'static str) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Default that depends on other tables
+    const fn computed_default(mut self, default: fn() -> String) -> Self {
+        self.computed_default = Some(default);
+        self
+    }
+
+    const fn input(mut self, input: Input) -> Self {
+        self.input = input;
+        self
+    }
+
+    const fn requires(mut self, requirement: Requirement) -> Self {
+        self.requires = Some(requirement);
+        self
+    }
+
+    const fn disabled(mut self, value: &'static str) -> Self {
+        self.disabled_value = Some(value);
+        self
+    }
+
+    const fn validate(mut self, validator: Validator) -> Self {
+        self.validate = Some(validator);
+        self
+    }
+
+    const fn on_set(mut self, hook: OnSet) -> Self {
+        self.on_set = Some(hook);
+        self
+    }
+
+    /// Whether the value is a password
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    /// Whether the value is a URL that may carry a password
+    pub fn has_credentials(&self) -> bool {
+        self.credentials
+    }
+
+    /// A value as it may be shown or logged: passwords are masked and URLs
+    /// lose their credentials
+    pub fn shown(&self, value: &str) -> String {
+        if self.secret {
+            "********".to_string()
+        } else if self.credentials {
+            crate::proxy::redact_url(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// A value as it is written to the session file, `None` for passwords
+    pub fn saved(&self, value: &str) -> Option<String> {
+        if self.secret {
+            None
+        } else if self.credentials {
+            Some(crate::proxy::strip_credentials(value))
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    /// Set the options that follow from choosing `value`
+    ///
+    /// Returns a message saying what was set, `None` when nothing was.
+    pub fn apply_on_set(&self, config: &mut Configuration, value: &str) -> Option<String> {
+        self.on_set.and_then(|on_set| on_set(config, value))
+    }
+
+    /// Value the option has until one is chosen
+    pub fn default_value(&self) -> String {
+        match self.computed_default {
+            Some(default) => default(),
+            None => self.default.to_string(),
+        }
+    }
+
+    /// The option with its default value
+    pub fn option(&self) -> ConfigOption {
+        ConfigOption::new(
+            self.name,
+            self.required,
+            self.description,
+            &self.default_value(),
+        )
+    }
+
+    /// Values offered in the option's dialog
+    ///
+    /// Free text, disks and packages have no fixed choices; Yes/No is
+    /// returned for them.
+    pub fn choices(&self) -> Vec<String> {
+        match self.input {
+            Input::Select(choices) | Input::MultiSelect { choices, .. } => choices(),
+            Input::SecureBoot => no_first(),
+            Input::EfiPartition => vec![crate::esp::CREATE.to_string()],
+            Input::Timezone => vec!["Please select a timezone region first".to_string()],
+            _ => all::<Toggle>(),
+        }
+    }
+
+    /// Whether the option matters with the values of the other options
+    pub fn requirement(&self, config: &Configuration) -> Result<(), String> {
+        self.requires.map_or(Ok(()), |requires| requires(config))
+    }
+
+    /// Check a value of the option
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        self.validate.map_or(Ok(()), |validate| validate(value))
+    }
+
+    /// Error to show while the value is typed, `None` while it is empty
+    pub fn live_check(&self, value: &str) -> Option<String> {
+        match self.input {
+            Input::Text {
+                check: Some(check), ..
+            } if !value.is_empty() => check(value).err(),
+            _ => None,
+        }
+    }
+}
+
+/// The description of the option called `name`
+pub fn spec(name: &str) -> Option<&'static OptionSpec> {
+    OPTIONS.iter().find(|spec| spec.name == name)
+}
+
+/// Whether the option called `name` is a password
+pub fn is_secret(name: &str) -> bool {
+    spec(name).is_some_and(OptionSpec::is_secret)
+}
+
+/// A value of the option called `name` as it may be shown or logged, see
+/// [`OptionSpec::shown`]
+pub fn shown_value(name: &str, value: &str) -> String {
+    spec(name).map_or_else(|| value.to_string(), |spec| spec.shown(value))
+}
+
+/// Every value of a choice enum, in declaration order
+fn all<T: IntoEnumIterator + Display>() -> Vec<String> {
+    T::iter().map(|value| value.to_string()).collect()
+}
+
+/// No before Yes, for switches that are off unless asked for
+fn no_first() -> Vec<String> {
+    vec![Toggle::No.to_string(), Toggle::Yes.to_string()]
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+/// The values of a choice enum the architecture has, see [`Arch`]
+fn supported<T: Display>(values: Vec<T>) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+const BOOT_SETUP: &str = "Boot Setup";
+const LOCALE: &str = "Locale and Input";
+const STORAGE: &str = "Disk and Storage";
+const TIME: &str = "Time and Location";
+const PACKAGES: &str = "Packages";
+const USERS: &str = "System and Users";
+const BOOTLOADER: &str = "Bootloader";
+const DESKTOP: &str = "Desktop";
+const SERVICES: &str = "Services";
+const FINAL_SETUP: &str = "Final Setup";
+
+/// Locales offered in the guided installer
+pub const LOCALES: &[&str] = &[
+    "en_US.UTF-8",
+    "en_GB.UTF-8",
+    "de_DE.UTF-8",
+    "fr_FR.UTF-8",
+    "es_ES.UTF-8",
+    "it_IT.UTF-8",
+    "pt_BR.UTF-8",
+    "ru_RU.UTF-8",
+    "ja_JP.UTF-8",
+    "zh_CN.UTF-8",
+    "en_AU.UTF-8",
+    "en_CA.UTF-8",
+    "en_IE.UTF-8",
+    "en_IN.UTF-8",
+    "en_NZ.UTF-8",
+    "de_AT.UTF-8",
+    "de_CH.UTF-8",
+    "nl_NL.UTF-8",
+    "nl_BE.UTF-8",
+    "pt_PT.UTF-8",
+    "pl_PL.UTF-8",
+    "sv_SE.UTF-8",
+    "nb_NO.UTF-8",
+    "da_DK.UTF-8",
+    "fi_FI.UTF-8",
+    "ko_KR.UTF-8",
+];
+
+/// Console keymaps offered in the guided installer
+pub const KEYMAPS: &[&str] = &[
+    "us",
+    "uk",
+    "de",
+    "fr",
+    "es",
+    "it",
+    "pt",
+    "ru",
+    "jp",
+    "de_CH-latin1",
+    "be-latin1",
+    "br-abnt2",
+    "pl",
+    "sv-latin1",
+    "no",
+    "dk",
+    "fi",
+];
+
+const SWAP_SIZES: &[&str] = &[
+    "1GB",
+    "2GB",
+    "4GB",
+    "8GB",
+    "16GB",
+    "32GB",
+    "Equal to RAM",
+    "Double RAM",
+];
+
+const PARALLEL_DOWNLOADS: &[&str] = &["1", "3", "5", "10", "15", "20"];
+
+const KEEP_COUNTS: &[&str] = &["3", "5", "10", "20"];
+
+/// Top-level directories of /usr/share/zoneinfo offered as regions
+pub const TIMEZONE_REGIONS: &[&str] = &[
+    "Africa",
+    "America",
+    "Antarctica",
+    "Arctic",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Europe",
+    "Indian",
+    "Pacific",
+    "US",
+];
+
+/// Countries reflector can rank mirrors for
+pub const MIRROR_COUNTRIES: &[&str] = &[
+    "Australia",
+    "Austria",
+    "Belgium",
+    "Brazil",
+    "Bulgaria",
+    "Canada",
+    "Chile",
+    "China",
+    "Czech Republic",
+    "Denmark",
+    "Estonia",
+    "Finland",
+    "France",
+    "Germany",
+    "Greece",
+    "Hong Kong",
+    "Hungary",
+    "Iceland",
+    "India",
+    "Indonesia",
+    "Ireland",
+    "Israel",
+    "Italy",
+    "Japan",
+    "Kazakhstan",
+    "Korea",
+    "Latvia",
+    "Lithuania",
+    "Luxembourg",
+    "Netherlands",
+    "New Zealand",
+    "Norway",
+    "Poland",
+    "Portugal",
+    "Romania",
+    "Russia",
+    "Serbia",
+    "Singapore",
+    "Slovakia",
+    "Slovenia",
+    "South Africa",
+    "Spain",
+    "Sweden",
+    "Switzerland",
+    "Taiwan",
+    "Thailand",
+    "Turkey",
+    "Ukraine",
+    "United Kingdom",
+    "United States",
+];
+
+/// Every option of the guided installer, in the order they are shown
+pub static OPTIONS: &[OptionSpec] = &[
+    // Boot Setup
+    OptionSpec::new(
+        "Boot Mode",
+        "BOOT_MODE",
+        BOOT_SETUP,
+        "Boot firmware type (Auto/UEFI/BIOS)",
+    )
+    .required()
+    .input(Input::Select(all::<BootMode>))
+    .on_set(set_bootloader),
+    OptionSpec::new(
+        "Secure Boot",
+        "SECURE_BOOT",
+        BOOT_SETUP,
+        "Enable Secure Boot (WARNING: Requires UEFI setup)",
+    )
+    .default("No")
+    .input(Input::SecureBoot)
+    .requires(not_bios),
+    // Locale and Input
+    OptionSpec::new(
+        "Region",
+        "REGION",
+        LOCALE,
+        "Country that fills in locale, keymap, timezone and mirrors",
+    )
+    .input(Input::Select(crate::region::countries))
+    .validate(crate::region::validate)
+    .on_set(set_region),
+    OptionSpec::new("Locale", "LOCALE", LOCALE, "System locale")
+        .required()
+        .default("en_US.UTF-8")
+        .input(Input::Select(|| strings(LOCALES))),
+    OptionSpec::new("Keymap", "KEYMAP", LOCALE, "Keyboard layout")
+        .required()
+        .default("us")
+        .input(Input::Select(|| strings(KEYMAPS))),
+    // Disk and Storage
+    OptionSpec::new(
+        "Disk",
+        "INSTALL_DISK",
+        STORAGE,
+        "Target disk for installation",
+    )
+    .required()
+    .input(Input::Disk)
+    .validate(|value| crate::disk::validate_disks(value).map(drop)),
+    OptionSpec::new(
+        "Partitioning Strategy",
+        "PARTITIONING_STRATEGY",
+        STORAGE,
+        "How to partition the disk",
+    )
+    .required()
+    .input(Input::Select(all::<PartitionScheme>))
+    .on_set(set_encryption),
+    OptionSpec::new(
+        "Partition Mounts",
+        "PARTITION_MOUNTS",
+        STORAGE,
+        "Existing partitions to mount, and which to format",
+    )
+    .input(Input::PartitionMounts)
+    .requires(existing_partitions)
+    .validate(crate::mount_plan::validate_value),
+    OptionSpec::new(
+        "Encryption",
+        "ENCRYPTION",
+        STORAGE,
+        "Enable disk encryption",
+    )
+    .default("Auto")
+    .requires(manual_partitioning),
+    OptionSpec::new(
+        "Encrypted Boot",
+        "ENCRYPTED_BOOT",
+        STORAGE,
+        "Keep /boot inside LUKS (GRUB only)",
+    )
+    .default("No")
+    .input(Input::Select(no_first))
+    .requires(encrypted_boot_possible),
+    OptionSpec::new(
+        "EFI Partition",
+        "EFI_PARTITION",
+        STORAGE,
+        "Create an ESP or reuse an existing one (dual boot)",
+    )
+    .default(crate::esp::CREATE)
+    .input(Input::EfiPartition)
+    .requires(not_bios)
+    .validate(crate::esp::validate_value)
+    .on_set(describe_esp),
+    OptionSpec::new(
+        "Existing OS",
+        "EXISTING_OS",
+        STORAGE,
+        "Protect, install alongside or erase systems already on the disk",
+    )
+    .default("protect")
+    .input(Input::Select(all::<ExistingOsPolicy>)),
+    OptionSpec::new(
+        "Root Filesystem",
+        "ROOT_FILESYSTEM",
+        STORAGE,
+        "Root partition filesystem",
+    )
+    .required()
+    .default("ext4")
+    .input(Input::Select(all::<Filesystem>))
+    .on_set(|_, value| {
+        let root: Filesystem = value.parse().unwrap_or_default();
+        Some(format!(
+            "Root filesystem {}: {}",
+            root,
+            crate::filesystem::summary(root)
+        ))
+    }),
+    OptionSpec::new(
+        "Separate Home Partition",
+        "SEPARATE_HOME",
+        STORAGE,
+        "Create separate /home partition",
+    )
+    .default("No"),
+    OptionSpec::new(
+        "Home Filesystem",
+        "HOME_FILESYSTEM",
+        STORAGE,
+        "Home partition filesystem",
+    )
+    .default("ext4")
+    .input(Input::Select(all::<Filesystem>))
+    .requires(separate_home),
+    OptionSpec::new("Swap", "SWAP", STORAGE, "Enable swap partition").default("Yes"),
+    OptionSpec::new("Swap Size", "SWAP_SIZE", STORAGE, "Swap partition size")
+        .default("2GB")
+        .input(Input::Select(|| strings(SWAP_SIZES)))
+        .requires(swap)
+        .disabled(NOT_APPLICABLE),
+    OptionSpec::new(
+        "Hibernation",
+        "HIBERNATION",
+        STORAGE,
+        "Resume from swap after hibernating",
+    )
+    .default("No")
+    .input(Input::Select(no_first))
+    .requires(swap),
+    OptionSpec::new(
+        "Btrfs Snapshots",
+        "BTRFS_SNAPSHOTS",
+        STORAGE,
+        "Enable Btrfs snapshots",
+    )
+    .default("No")
+    .requires(snapper_root)
+    .disabled("No"),
+    OptionSpec::new(
+        "Btrfs Frequency",
+        "BTRFS_FREQUENCY",
+        STORAGE,
+        "Btrfs snapshot frequency",
+    )
+    .default("weekly")
+    .input(Input::Select(all::<SnapshotFrequency>))
+    .requires(snapshots)
+    .disabled(NOT_APPLICABLE),
+    OptionSpec::new(
+        "Btrfs Keep Count",
+        "BTRFS_KEEP_COUNT",
+        STORAGE,
+        "Number of snapshots to keep",
+    )
+    .default("3")
+    .input(Input::Select(|| strings(KEEP_COUNTS)))
+    .requires(snapshots)
+    .disabled(NOT_APPLICABLE),
+    OptionSpec::new(
+        "Btrfs Assistant",
+        "BTRFS_ASSISTANT",
+        STORAGE,
+        "Use Btrfs assistant",
+    )
+    .default("No")
+    .requires(snapshots)
+    .disabled("No"),
+    OptionSpec::new(
+        "LVM Volume Group",
+        "LVM_VOLUME_GROUP",
+        STORAGE,
+        "Volume group name (LVM strategies)",
+    )
+    .default(crate::lvm::DEFAULT_VOLUME_GROUP)
+    .input(Input::Text {
+        placeholder: "Enter volume group name",
+        check: None,
+    })
+    .requires(lvm_layout)
+    .validate(crate::lvm::validate_volume_group),
+    OptionSpec::new(
+        "LVM Root Size",
+        "LVM_ROOT_SIZE",
+        STORAGE,
+        "Root volume size, e.g. 50G or 100%FREE",
+    )
+    .default(crate::lvm::DEFAULT_ROOT_SIZE)
+    .input(LVM_SIZE)
+    .requires(lvm_layout)
+    .validate(|value| crate::lvm::parse_size(value).map(drop)),
+    OptionSpec::new(
+        "LVM Var Size",
+        "LVM_VAR_SIZE",
+        STORAGE,
+        "Separate /var volume size, or none",
+    )
+    .default(crate::lvm::DEFAULT_VAR_SIZE)
+    .input(LVM_SIZE)
+    .requires(lvm_layout)
+    .validate(|value| crate::lvm::parse_size(value).map(drop)),
+    OptionSpec::new(
+        "LVM Home Size",
+        "LVM_HOME_SIZE",
+        STORAGE,
+        "Home volume size with a separate /home",
+    )
+    .default(crate::lvm::DEFAULT_HOME_SIZE)
+    .input(LVM_SIZE)
+    .requires(lvm_layout)
+    .validate(|value| crate::lvm::parse_size(value).map(drop)),
+    OptionSpec::new(
+        "LVM Thin Provisioning",
+        "LVM_THIN",
+        STORAGE,
+        "Create the volumes in a thin pool",
+    )
+    .default("No")
+    .input(Input::Select(no_first))
+    .requires(lvm_layout),
+    OptionSpec::new(
+        "Var Partition Size",
+        "VAR_PARTITION_SIZE",
+        STORAGE,
+        "Separate /var partition size, or none",
+    )
+    .default(crate::var_tmp::DEFAULT_VAR_SIZE)
+    .input(Input::Text {
+        placeholder: "Size like 20G, or none",
+        check: Some(|value| crate::var_tmp::parse_var_size(value).map(drop)),
+    })
+    .requires(var_partition)
+    .validate(|value| crate::var_tmp::parse_var_size(value).map(drop)),
+    OptionSpec::new(
+        "Tmp Policy",
+        "TMP_POLICY",
+        STORAGE,
+        "Mount /tmp as tmpfs or keep it on disk",
+    )
+    .default("tmpfs")
+    .input(Input::Select(all::<TmpPolicy>)),
+    OptionSpec::new(
+        "Tmp Size",
+        "TMP_SIZE",
+        STORAGE,
+        "Size limit of the /tmp tmpfs",
+    )
+    .default(crate::var_tmp::DEFAULT_TMP_SIZE)
+    .input(Input::Text {
+        placeholder: "Share of the memory like 50%, or a size like 4G",
+        check: Some(crate::var_tmp::validate_tmp_size),
+    })
+    .requires(tmpfs)
+    .validate(crate::var_tmp::validate_tmp_size),
+    // Time and Location
+    OptionSpec::new(
+        "Timezone Region",
+        "TIMEZONE_REGION",
+        TIME,
+        "Timezone region",
+    )
+    .required()
+    .default("America")
+    .input(Input::Select(|| strings(TIMEZONE_REGIONS)))
+    .on_set(set_mirror_country),
+    OptionSpec::new("Timezone", "TIMEZONE", TIME, "Timezone city")
+        .required()
+        .default("New_York")
+        .input(Input::Timezone)
+        .requires(timezone_region),
+    OptionSpec::new(
+        "Time Sync (NTP)",
+        "TIME_SYNC",
+        TIME,
+        "Enable NTP time synchronization",
+    )
+    .default("Yes")
+    .on_set(set_timesyncd),
+    OptionSpec::new(
+        "Time Sync Daemon",
+        "TIME_SYNC_DAEMON",
+        TIME,
+        "Daemon keeping the clock in sync",
+    )
+    .default("systemd-timesyncd")
+    .input(Input::Select(all::<TimeSyncDaemon>))
+    .requires(time_sync)
+    .on_set(|_, value| {
+        let daemon: TimeSyncDaemon = value.parse().unwrap_or_default();
+        (daemon != TimeSyncDaemon::Timesyncd).then(|| {
+            format!(
+                "Time Sync Daemon: {} is installed and enabled instead of systemd-timesyncd",
+                daemon
+            )
+        })
+    }),
+    OptionSpec::new(
+        "NTP Servers",
+        "NTP_SERVERS",
+        TIME,
+        "NTP servers (blank for the defaults)",
+    )
+    .input(Input::Text {
+        placeholder: "Servers separated by spaces, blank for the defaults",
+        check: None,
+    })
+    .requires(time_sync)
+    .validate(|value| crate::timesync::parse_servers(value).map(drop)),
+    OptionSpec::new(
+        "Hardware Clock",
+        "HARDWARE_CLOCK",
+        TIME,
+        "Time kept by the hardware clock (localtime for Windows dual boot)",
+    )
+    .default("UTC")
+    .input(Input::Select(all::<HardwareClock>)),
+    // Packages
+    OptionSpec::new(
+        "HTTP Proxy",
+        "HTTP_PROXY",
+        PACKAGES,
+        "Proxy for downloads (blank for a direct connection)",
+    )
+    .input(PROXY_URL)
+    .credentials()
+    .validate(crate::proxy::validate_url)
+    .on_set(describe_proxy),
+    OptionSpec::new(
+        "HTTPS Proxy",
+        "HTTPS_PROXY",
+        PACKAGES,
+        "Proxy for HTTPS downloads (blank for the HTTP proxy)",
+    )
+    .input(PROXY_URL)
+    .credentials()
+    .validate(crate::proxy::validate_url)
+    .on_set(describe_proxy),
+    OptionSpec::new(
+        "No Proxy",
+        "NO_PROXY",
+        PACKAGES,
+        "Hosts reached without the proxy (comma-separated)",
+    )
+    .input(Input::Text {
+        placeholder: "localhost,127.0.0.1,.example.com",
+        check: Some(crate::proxy::validate_no_proxy),
+    })
+    .validate(crate::proxy::validate_no_proxy)
+    .on_set(describe_proxy),
+    OptionSpec::new(
+        "Mirror Country",
+        "MIRROR_COUNTRY",
+        PACKAGES,
+        "Package mirror countries",
+    )
+    .required()
+    .default("United States")
+    .input(Input::MultiSelect {
+        choices: || strings(MIRROR_COUNTRIES),
+        min: 1,
+        max: MIRROR_COUNTRIES.len(),
+    }),
+    OptionSpec::new(
+        "Package Cache",
+        "PKG_CACHE",
+        PACKAGES,
+        "Local package cache or offline repository (blank for none)",
+    )
+    .input(Input::Text {
+        placeholder: "/path/to/packages, blank to download from the mirrors",
+        // Whether it exists and holds packages is checked before installing
+        check: Some(crate::pkg_cache::validate_path),
+    })
+    .validate(crate::pkg_cache::validate),
+    OptionSpec::new("Kernel", "KERNEL", PACKAGES, "Linux kernel to install")
+        .required()
+        .default("linux")
+        .input(Input::Select(|| supported(Arch::current().kernels()))),
+    OptionSpec::new(
+        "CPU Microcode",
+        "MICROCODE",
+        PACKAGES,
+        "Microcode update loaded before the kernel (detected from the CPU)",
+    )
+    .default("auto")
+    .input(Input::Select(all::<Microcode>))
+    .requires(microcode),
+    OptionSpec::new(
+        "Multilib",
+        "MULTILIB",
+        PACKAGES,
+        "Enable multilib repository",
+    )
+    .computed_default(|| {
+        let multilib = Arch::current().has_multilib();
+        if multilib { Toggle::Yes } else { Toggle::No }.to_string()
+    })
+    .requires(multilib),
+    OptionSpec::new(
+        "Parallel Downloads",
+        "PARALLEL_DOWNLOADS",
+        PACKAGES,
+        "Packages pacman downloads at once",
+    )
+    .default("5")
+    .input(Input::Select(|| strings(PARALLEL_DOWNLOADS)))
+    .validate(|value| crate::pacman::validate_parallel_downloads(value).map(drop)),
+    OptionSpec::new(
+        "Pacman Color",
+        "PACMAN_COLOR",
+        PACKAGES,
+        "Colorized pacman output",
+    )
+    .default("Yes"),
+    OptionSpec::new(
+        "Verbose Package Lists",
+        "VERBOSE_PKG_LISTS",
+        PACKAGES,
+        "Show pacman transactions as a table",
+    )
+    .default("No"),
+    OptionSpec::new(
+        "Custom Repositories",
+        "CUSTOM_REPOSITORIES",
+        PACKAGES,
+        "Extra pacman repositories (name|server|key|siglevel)",
+    )
+    .input(Input::Text {
+        placeholder: "name|https://server/$repo/$arch|keyid|siglevel, separated by spaces",
+        check: Some(|value| crate::pacman::parse_repositories(value).map(drop)),
+    })
+    .validate(|value| crate::pacman::parse_repositories(value).map(drop)),
+    OptionSpec::new(
+        "Additional Pacman Packages",
+        "ADDITIONAL_PACKAGES",
+        PACKAGES,
+        "Extra packages to install",
+    )
+    .input(Input::Packages),
+    OptionSpec::new(
+        "Package Groups",
+        "PACKAGE_GROUPS",
+        PACKAGES,
+        "Curated package sets (development, gaming, ...)",
+    )
+    .input(Input::PackageGroups)
+    .validate(|value| crate::package_utils::parse_package_groups(value).map(drop)),
+    OptionSpec::new("GPU Drivers", "GPU_DRIVERS", PACKAGES, "Graphics drivers")
+        .default("Auto")
+        .input(Input::Select(all::<GpuDriver>)),
+    OptionSpec::new(
+        "NVIDIA Driver",
+        "NVIDIA_DRIVER",
+        PACKAGES,
+        "NVIDIA kernel modules (open for Turing and newer)",
+    )
+    .default("open")
+    .input(Input::Select(all::<NvidiaDriver>))
+    .requires(nvidia),
+    OptionSpec::new(
+        "NVIDIA Extras",
+        "NVIDIA_EXTRAS",
+        PACKAGES,
+        "DRM modeset, initramfs modules and a pacman hook",
+    )
+    .default("Yes")
+    .requires(nvidia),
+    OptionSpec::new(
+        "VM Guest Tools",
+        "VM_GUEST_TOOLS",
+        PACKAGES,
+        "Virtual machine guest packages and services",
+    )
+    .default("None")
+    .input(Input::Select(all::<GuestTools>)),
+    // System and Users
+    OptionSpec::new("Hostname", "SYSTEM_HOSTNAME", USERS, "System hostname")
+        .required()
+        .input(Input::Text {
+            placeholder: "Enter hostname",
+            check: Some(super::validate_hostname),
+        })
+        .validate(super::validate_hostname),
+    OptionSpec::new("Username", "MAIN_USERNAME", USERS, "Primary user account")
+        .required()
+        .input(Input::Text {
+            placeholder: "Enter username",
+            check: Some(super::validate_username),
+        })
+        .validate(super::validate_username),
+    OptionSpec::new(
+        "Password Minimum Length",
+        "PASSWORD_MIN_LENGTH",
+        USERS,
+        "Shortest user and root password accepted",
+    )
+    .computed_default(|| crate::password::DEFAULT_MIN_PASSWORD_LENGTH.to_string())
+    .input(Input::Text {
+        placeholder: "Number of characters",
+        check: Some(password_min_length),
+    })
+    .validate(password_min_length),
+    // SECURITY: Passwords are NOT passed via environment variables
+    // They are passed via stdin to prevent /proc/<pid>/environ exposure
+    // See Configuration::get_passwords() and installer.rs for secure handling
+    OptionSpec::new("User Password", "", USERS, "User account password")
+        .secret()
+        .required()
+        .input(Input::Password {
+            placeholder: "Enter user password",
+        })
+        .validate(password),
+    OptionSpec::new("Root Password", "", USERS, "Root account password")
+        .secret()
+        .required()
+        .input(Input::Password {
+            placeholder: "Enter root password",
+        })
+        .validate(password),
+    OptionSpec::new(
+        "SSH Keys",
+        "SSH_KEYS",
+        USERS,
+        "Authorized SSH keys (github:USER, gitlab:USER or a file)",
+    )
+    .input(Input::Text {
+        placeholder: "github:USER gitlab:USER or /path/to/keys.pub, separated by spaces",
+        check: Some(|value| crate::ssh_keys::parse_sources(value).map(drop)),
+    })
+    .validate(|value| crate::ssh_keys::parse_sources(value).map(drop))
+    .on_set(describe_ssh_keys),
+    // Packages, continued
+    OptionSpec::new("AUR Helper", "AUR_HELPER", PACKAGES, "AUR package helper")
+        .default("paru")
+        .input(Input::Select(all::<AurHelper>)),
+    OptionSpec::new(
+        "Additional AUR Packages",
+        "ADDITIONAL_AUR_PACKAGES",
+        PACKAGES,
+        "Extra AUR packages",
+    )
+    .input(Input::Packages)
+    .requires(aur_helper),
+    OptionSpec::new("Flatpak", "FLATPAK", PACKAGES, "Enable Flatpak support").default("No"),
+    // Bootloader
+    OptionSpec::new("Bootloader", "BOOTLOADER", BOOTLOADER, "Boot loader")
+        .required()
+        .computed_default(|| {
+            let uefi = std::path::Path::new("/sys/firmware/efi").exists();
+            Arch::current().default_bootloader(uefi).to_string()
+        })
+        .input(Input::Select(|| supported(Arch::current().bootloaders()))),
+    OptionSpec::new("OS Prober", "OS_PROBER", BOOTLOADER, "Enable OS detection")
+        .default("Yes")
+        .requires(grub),
+    OptionSpec::new("GRUB Theme", "GRUB_THEME", BOOTLOADER, "Enable GRUB themes")
+        .default("No")
+        .requires(grub),
+    OptionSpec::new(
+        "GRUB Theme Selection",
+        "GRUB_THEME_SELECTION",
+        BOOTLOADER,
+        "GRUB theme to use",
+    )
+    .default("PolyDark")
+    .input(Input::Select(all::<GrubTheme>))
+    .requires(grub_themes)
+    .disabled("none"),
+    OptionSpec::new(
+        "Early KMS",
+        "EARLY_KMS",
+        BOOTLOADER,
+        "Load the GPU driver from the initramfs",
+    )
+    .default("No")
+    .input(Input::Select(no_first)),
+    OptionSpec::new(
+        "Initramfs Modules",
+        "INITRAMFS_MODULES",
+        BOOTLOADER,
+        "Extra kernel modules in the initramfs",
+    )
+    .input(Input::Text {
+        placeholder: "Modules separated by spaces, e.g. amdgpu nvme",
+        check: None,
+    })
+    .validate(|value| crate::initramfs::parse_modules(value).map(drop)),
+    OptionSpec::new(
+        "Initramfs Hooks",
+        "INITRAMFS_HOOKS",
+        BOOTLOADER,
+        "mkinitcpio hooks (auto builds them from the layout)",
+    )
+    .default(crate::initramfs::AUTO)
+    .input(Input::Text {
+        placeholder: "auto, or hooks in order: base udev autodetect ... block filesystems fsck",
+        check: None,
+    })
+    .validate(|value| crate::initramfs::parse_hooks(value).map(drop)),
+    // Desktop
+    OptionSpec::new(
+        "Desktop Environment",
+        "DESKTOP_ENVIRONMENT",
+        DESKTOP,
+        "Desktop environment",
+    )
+    .default("kde")
+    .input(Input::Select(all::<DesktopEnvironment>))
+    .on_set(set_display_manager),
+    OptionSpec::new(
+        "Display Manager",
+        "DISPLAY_MANAGER",
+        DESKTOP,
+        "Display manager",
+    )
+    .default("sddm")
+    .input(Input::Select(all::<DisplayManager>))
+    .requires(desktop),
+    OptionSpec::new("Audio", "AUDIO", DESKTOP, "Sound server")
+        .default("pipewire")
+        .input(Input::Select(all::<AudioServer>))
+        .on_set(|_, value| {
+            let audio: AudioServer = value.parse().unwrap_or_default();
+            Some(if audio == AudioServer::None {
+                "Audio: no sound server added".to_string()
+            } else {
+                format!(
+                    "Audio: {} (user units: {})",
+                    audio.packages().join(" "),
+                    audio.user_services().join(" ")
+                )
+            })
+        }),
+    OptionSpec::new(
+        "Power Management",
+        "POWER_MANAGEMENT",
+        DESKTOP,
+        "Laptop power manager (tlp or power-profiles-daemon)",
+    )
+    .default("none")
+    .input(Input::Select(all::<PowerManagement>))
+    .on_set(|_, value| {
+        let power: PowerManagement = value.parse().unwrap_or_default();
+        (power != PowerManagement::None).then(|| {
+            format!(
+                "Power Management: {} (service: {})",
+                power.packages().join(" "),
+                power.services().join(" ")
+            )
+        })
+    }),
+    OptionSpec::new(
+        "Lid Close Action",
+        "LID_SWITCH",
+        DESKTOP,
+        "What closing the laptop lid does",
+    )
+    .default("suspend")
+    .input(Input::Select(all::<LidSwitch>)),
+    // Services
+    OptionSpec::new(
+        "Services",
+        "SERVICES",
+        SERVICES,
+        "systemd units enabled at boot",
+    )
+    .computed_default(crate::services::default_services)
+    .input(Input::Services)
+    .validate(|value| crate::services::parse_services(value).map(drop))
+    .on_set(set_time_sync),
+    // Final Setup
+    OptionSpec::new("Plymouth", "PLYMOUTH", FINAL_SETUP, "Boot splash screen").default("Yes"),
+    OptionSpec::new(
+        "Plymouth Theme",
+        "PLYMOUTH_THEME",
+        FINAL_SETUP,
+        "Plymouth theme",
+    )
+    .default("arch-glow")
+    .input(Input::Select(all::<PlymouthTheme>))
+    .requires(plymouth)
+    .disabled("none"),
+    OptionSpec::new(
+        "Numlock on Boot",
+        "NUMLOCK_ON_BOOT",
+        FINAL_SETUP,
+        "Enable numlock at boot",
+    )
+    .default("Yes"),
+    OptionSpec::new(
+        "Git Repository",
+        "GIT_REPOSITORY",
+        FINAL_SETUP,
+        "Clone installation repository",
+    )
+    .default("No"),
+    OptionSpec::new(
+        "Git Repository URL",
+        "GIT_REPOSITORY_URL",
+        FINAL_SETUP,
+        "Git repository URL to clone",
+    )
+    .input(Input::Text {
+        placeholder: "Enter git repository URL",
+        check: None,
+    })
+    .requires(git_repository)
+    .validate(git_url),
+];
+
+const LVM_SIZE: Input = Input::Text {
+    placeholder: "Size like 50G, share like 100%FREE, or none",
+    check: None,
+};
+
+const PROXY_URL: Input = Input::Text {
+    placeholder: "http://[user:password@]proxy:port, blank for none",
+    check: Some(crate::proxy::validate_url),
+};
+
+fn password(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        return Err("Password cannot be empty or contain whitespace".to_string());
+    }
+    Ok(())
+}
+
+fn password_min_length(value: &str) -> Result<(), String> {
+    crate::password::PasswordPolicy::parse(value).map(drop)
+}
+
+fn git_url(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty()
+        || ["http://", "https://", "git://", "ssh://"]
+            .iter()
+            .any(|scheme| value.starts_with(scheme))
+    {
+        Ok(())
+    } else {
+        Err(
+            "Git Repository URL must be a valid URL (http://, https://, git://, or ssh://)"
+                .to_string(),
+        )
+    }
+}
+
+/// Require a Yes option, explaining a No with `reason`
+fn enabled(config: &Configuration, name: &str, reason: &str) -> Result<(), String> {
+    if config.value(name).eq_ignore_ascii_case("yes") {
+        Ok(())
+    } else {
+        Err(reason.to_string())
+    }
+}
+
+fn not_bios(config: &Configuration) -> Result<(), String> {
+    match config.value("Boot Mode").parse() {
+        Ok(BootMode::Bios) => Err("the boot mode is BIOS".to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn manual_partitioning(config: &Configuration) -> Result<(), String> {
+    match config.value("Partitioning Strategy").parse() {
+        Ok(PartitionScheme::Manual) => Ok(()),
+        _ => Err("it is set from the partitioning strategy unless that is manual".to_string()),
+    }
+}
+
+fn existing_partitions(config: &Configuration) -> Result<(), String> {
+    match config.value("Partitioning Strategy").parse() {
+        Ok(PartitionScheme::Existing) => Ok(()),
+        _ => Err("only the existing partitioning strategy uses it".to_string()),
+    }
+}
+
+fn encrypted_boot_possible(config: &Configuration) -> Result<(), String> {
+    super::validate_encrypted_boot(
+        config.value("Bootloader").parse().unwrap_or_default(),
+        config
+            .value("Partitioning Strategy")
+            .parse()
+            .unwrap_or_default(),
+        config
+            .value("Encryption")
+            .parse()
+            .unwrap_or(AutoToggle::Auto),
+    )
+}
+
+fn separate_home(config: &Configuration) -> Result<(), String> {
+    enabled(
+        config,
+        "Separate Home Partition",
+        "there is no separate /home partition",
+    )
+}
+
+fn swap(config: &Configuration) -> Result<(), String> {
+    enabled(config, "Swap", "swap is disabled")
+}
+
+fn snapper_root(config: &Configuration) -> Result<(), String> {
+    let root: Filesystem = config.value("Root Filesystem").parse().unwrap_or_default();
+    if crate::filesystem::capabilities(root).snapper {
+        Ok(())
+    } else {
+        Err(format!(
+            "snapshots need a btrfs root filesystem ({} selected)",
+            root
+        ))
+    }
+}
+
+fn snapshots(config: &Configuration) -> Result<(), String> {
+    snapper_root(config)?;
+    enabled(config, "Btrfs Snapshots", "Btrfs snapshots are disabled")
+}
+
+fn lvm_layout(config: &Configuration) -> Result<(), String> {
+    let scheme: PartitionScheme = config
+        .value("Partitioning Strategy")
+        .parse()
+        .unwrap_or_default();
+    if crate::lvm::is_configurable(scheme) {
+        Ok(())
+    } else {
+        Err("only the auto_lvm and auto_luks_lvm strategies use it".to_string())
+    }
+}
+
+fn var_partition(config: &Configuration) -> Result<(), String> {
+    let scheme: PartitionScheme = config
+        .value("Partitioning Strategy")
+        .parse()
+        .unwrap_or_default();
+    if crate::var_tmp::has_var_partition(scheme) {
+        Ok(())
+    } else if crate::lvm::is_configurable(scheme) {
+        Err("the LVM strategies size /var with LVM Var Size".to_string())
+    } else {
+        Err("only the auto_simple and auto_simple_luks strategies use it".to_string())
+    }
+}
+
+fn tmpfs(config: &Configuration) -> Result<(), String> {
+    match config.value("Tmp Policy").parse() {
+        Ok(TmpPolicy::Disk) => Err("/tmp is kept on disk".to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn timezone_region(config: &Configuration) -> Result<(), String> {
+    if config.value("Timezone Region").is_empty() {
+        Err("select a timezone region first".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn time_sync(config: &Configuration) -> Result<(), String> {
+    enabled(config, "Time Sync (NTP)", "Time Sync (NTP) is disabled")
+}
+
+fn multilib(_: &Configuration) -> Result<(), String> {
+    let arch = Arch::current();
+    if arch.has_multilib() {
+        Ok(())
+    } else {
+        Err(format!("there is no multilib repository for {}", arch))
+    }
+}
+
+fn microcode(_: &Configuration) -> Result<(), String> {
+    let arch = Arch::current();
+    if arch.has_microcode() {
+        Ok(())
+    } else {
+        Err(format!("there are no microcode updates for {}", arch))
+    }
+}
+
+fn aur_helper(config: &Configuration) -> Result<(), String> {
+    if config.value("AUR Helper").eq_ignore_ascii_case("none") {
+        Err("no AUR helper is selected".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn nvidia(config: &Configuration) -> Result<(), String> {
+    match config.value("GPU Drivers").parse() {
+        Ok(GpuDriver::Amd) | Ok(GpuDriver::Intel) => {
+            Err("the GPU drivers are not NVIDIA or Auto".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn grub(config: &Configuration) -> Result<(), String> {
+    let bootloader: Bootloader = config.value("Bootloader").parse().unwrap_or_default();
+    if bootloader == Bootloader::Grub {
+        Ok(())
+    } else {
+        Err(format!("the bootloader is {}, not GRUB", bootloader))
+    }
+}
+
+fn grub_themes(config: &Configuration) -> Result<(), String> {
+    grub(config)?;
+    enabled(config, "GRUB Theme", "GRUB themes are disabled")
+}
+
+fn desktop(config: &Configuration) -> Result<(), String> {
+    if config
+        .value("Desktop Environment")
+        .eq_ignore_ascii_case("none")
+    {
+        Err("no desktop environment is selected".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn plymouth(config: &Configuration) -> Result<(), String> {
+    enabled(config, "Plymouth", "Plymouth is disabled")
+}
+
+fn git_repository(config: &Configuration) -> Result<(), String> {
+    enabled(config, "Git Repository", "Git Repository is disabled")
+}
+
+/// Encryption follows the partitioning strategy unless that is manual
+fn set_encryption(config: &mut Configuration, scheme: &str) -> Option<String> {
+    if matches!(scheme.parse(), Ok(PartitionScheme::Manual)) {
+        return None;
+    }
+    let value = if scheme.contains("luks") { "Yes" } else { "No" };
+    config.option_mut("Encryption")?.value = value.to_string();
+    Some(format!(
+        "Auto-set Encryption to: {} (based on partitioning strategy)",
+        value
+    ))
+}
+
+/// The display manager of the chosen desktop, from the desktop catalogue
+fn set_display_manager(config: &mut Configuration, desktop: &str) -> Option<String> {
+    let desktop = crate::desktop::find_desktop(desktop)?;
+    let display_manager = desktop.display_manager()?;
+    config.option_mut("Display Manager")?.value = display_manager.to_string();
+    Some(format!(
+        "Auto-set Display Manager to: {} for {}",
+        display_manager,
+        desktop.summary()
+    ))
+}
+
+/// Locale, keymap, timezone and mirrors of the chosen country
+fn set_region(config: &mut Configuration, country: &str) -> Option<String> {
+    let region = crate::region::find_region(country)?;
+    for (name, value) in crate::region::FILLS.iter().zip(region.values()) {
+        if let Some(option) = config.option_mut(name) {
+            option.value = value.to_string();
+        }
+    }
+    Some(format!(
+        "Region: {}, keymap {}, {}/{} and mirrors in {}; each can still be changed",
+        region.locale, region.keymap, region.timezone.0, region.timezone.1, region.mirror_country
+    ))
+}
+
+/// Only GRUB boots without UEFI, or U-Boot on aarch64 boards
+fn set_bootloader(config: &mut Configuration, mode: &str) -> Option<String> {
+    let bios = match mode.parse().unwrap_or_default() {
+        BootMode::Bios => true,
+        BootMode::Auto => !std::path::Path::new("/sys/firmware/efi").exists(),
+        BootMode::Uefi => false,
+    };
+    if !bios {
+        return None;
+    }
+    let bootloader = Arch::current().default_bootloader(false);
+    config.option_mut("Bootloader")?.value = bootloader.to_string();
+    let scheme = config
+        .value("Partitioning Strategy")
+        .parse()
+        .unwrap_or_default();
+    (bootloader == Bootloader::Grub).then(|| crate::config::bios_gpt_note(scheme))
+}
+
+/// Where Arch goes when an existing ESP is reused
+fn describe_esp(config: &mut Configuration, esp: &str) -> Option<String> {
+    if esp == crate::esp::CREATE {
+        return None;
+    }
+    let disk = config.value("Disk");
+    let same_disk =
+        crate::disk::split_partition(esp).is_some_and(|(esp_disk, _)| esp_disk.path() == disk);
+    Some(if same_disk {
+        format!(
+            "Reusing {}: {} is not wiped, Arch goes into its free space",
+            esp, disk
+        )
+    } else {
+        format!(
+            "Reusing {}: the bootloader is added next to the existing ones",
+            esp
+        )
+    })
+}
+
+/// A new timezone region asks for its city again and moves the mirrors near it
+fn set_mirror_country(config: &mut Configuration, region: &str) -> Option<String> {
+    // Reset to empty to force selection
+    config.option_mut("Timezone")?.value = String::new();
+    let mirror_country = match region {
+        "US" | "America" => "United States",
+        "Europe" => "Germany",
+        "Asia" => "Japan",
+        "Australia" => "Australia",
+        _ => return None,
+    };
+    config.option_mut("Mirror Country")?.value = mirror_country.to_string();
+    None
+}
+
+/// Time Sync is a shortcut for the timesyncd service
+fn set_timesyncd(config: &mut Configuration, value: &str) -> Option<String> {
+    let services = config.option_mut("Services")?;
+    services.value = crate::services::set_enabled(
+        &services.get_value(),
+        crate::services::TIMESYNCD,
+        value.eq_ignore_ascii_case("yes"),
+    );
+    None
+}
+
+/// Time Sync follows the timesyncd service, and services bring their packages
+fn set_time_sync(config: &mut Configuration, value: &str) -> Option<String> {
+    let services = crate::services::parse_services(value).unwrap_or_default();
+    let timesyncd = services
+        .iter()
+        .any(|service| service.unit == crate::services::TIMESYNCD);
+    if let Some(time_sync) = config.option_mut("Time Sync (NTP)") {
+        time_sync.value = if timesyncd { "Yes" } else { "No" }.to_string();
+    }
+    let packages: Vec<&str> = services
+        .iter()
+        .flat_map(|service| service.packages.iter().copied())
+        .collect();
+    (!packages.is_empty()).then(|| format!("Services: also installs {}", packages.join(" ")))
+}
+
+/// Package search and install.sh get the proxy options when they start
+fn describe_proxy(config: &mut Configuration, _: &str) -> Option<String> {
+    let proxy = config.proxy();
+    Some(if proxy.is_empty() {
+        "Proxy: downloads connect directly".to_string()
+    } else {
+        let shown = proxy.redacted();
+        format!("Proxy: {} (HTTPS: {})", shown.http, shown.https())
+    })
+}
+
+fn describe_ssh_keys(_: &mut Configuration, value: &str) -> Option<String> {
+    let sources = crate::ssh_keys::parse_sources(value).ok()?;
+    if sources.is_empty() {
+        return None;
+    }
+    let sources: Vec<String> = sources
+        .iter()
+        .map(|source| source.url().unwrap_or_else(|| source.to_string()))
+        .collect();
+    Some(format!(
+        "SSH Keys: keys from {} are authorized for the user",
+        sources.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_names_and_keys_are_unique() {
+        for (index, spec) in OPTIONS.iter().enumerate() {
+            for other in &OPTIONS[index + 1..] {
+                assert_ne!(spec.name, other.name);
+                if spec.key.is_some() {
+                    assert_ne!(spec.key, other.key, "{}", spec.name);
+                }
+            }
+        }
+        assert!(spec("User Password").unwrap().key.is_none());
+        assert!(is_secret("Root Password"));
+        assert!(!is_secret("Username"));
+        assert!(!is_secret("Unknown"));
+        assert_eq!(spec("Disk").unwrap().key, Some("INSTALL_DISK"));
+        assert!(spec("Unknown").is_none());
+    }
+
+    #[test]
+    fn test_defaults_are_among_the_choices() {
+        for spec in OPTIONS {
+            // Encryption stays Auto until manual partitioning asks for Yes or No
+            if let (Input::Select(choices), false) = (spec.input, spec.name == "Encryption") {
+                let default = spec.default_value();
+                assert!(
+                    default.is_empty() || choices().contains(&default),
+                    "{}: {}",
+                    spec.name,
+                    default
+                );
+            }
+            if let Some(disabled) = spec.disabled_value {
+                assert!(spec.requires.is_some(), "{}", spec.name);
+                assert!(
+                    disabled == NOT_APPLICABLE || spec.choices().iter().any(|c| c == disabled),
+                    "{}: {}",
+                    spec.name,
+                    disabled
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_defaults_pass_their_validators() {
+        for spec in OPTIONS.iter().filter(|spec| !spec.required) {
+            assert!(spec.check(&spec.default_value()).is_ok(), "{}", spec.name);
+        }
+    }
+
+    #[test]
+    fn test_requirements_follow_other_options() {
+        let mut config = Configuration::default();
+        let swap_size = spec("Swap Size").unwrap();
+        assert!(swap_size.requirement(&config).is_ok());
+
+        config.option_mut("Swap").unwrap().value = "No".to_string();
+        assert_eq!(
+            swap_size.requirement(&config),
+            Err("swap is disabled".to_string())
+        );
+
+        config.option_mut("Bootloader").unwrap().value = "systemd-boot".to_string();
+        let error = spec("GRUB Theme Selection")
+            .unwrap()
+            .requirement(&config)
+            .unwrap_err();
+        assert!(error.contains("systemd-boot"), "{}", error);
+    }
+
+    #[test]
+    fn test_live_check_only_runs_on_typed_text() {
+        let hostname = spec("Hostname").unwrap();
+        assert!(hostname.live_check("bad_host").is_some());
+        assert_eq!(hostname.live_check(""), None);
+        assert_eq!(spec("Git Repository URL").unwrap().live_check("x y"), None);
+        assert_eq!(spec("Swap").unwrap().live_check("x y"), None);
+    }
+
+    #[test]
+    fn test_choosing_a_value_sets_the_options_that_follow() {
+        let mut config = Configuration::default();
+        let scheme = spec("Partitioning Strategy").unwrap();
+        assert!(scheme
+            .apply_on_set(&mut config, "auto_simple_luks")
+            .is_some());
+        assert_eq!(config.value("Encryption"), "Yes");
+        assert!(scheme.apply_on_set(&mut config, "manual").is_none());
+        assert_eq!(config.value("Encryption"), "Yes");
+
+        let desktop = spec("Desktop Environment").unwrap();
+        let message = desktop.apply_on_set(&mut config, "gnome").unwrap();
+        assert!(
+            message.starts_with("Auto-set Display Manager to: gdm"),
+            "{}",
+            message
+        );
+        assert_eq!(config.value("Display Manager"), "gdm");
+        assert!(desktop.apply_on_set(&mut config, "none").is_none());
+        assert!(spec("Hostname")
+            .unwrap()
+            .apply_on_set(&mut config, "arch")
+            .is_none());
+
+        let region = spec("Region").unwrap();
+        assert!(region.apply_on_set(&mut config, "Japan").is_some());
+        assert_eq!(config.value("Keymap"), "jp");
+        assert_eq!(config.value("Timezone"), "Tokyo");
+        assert!(region.apply_on_set(&mut config, "").is_none());
+        assert_eq!(config.value("Locale"), "ja_JP.UTF-8");
+
+        spec("Timezone Region")
+            .unwrap()
+            .apply_on_set(&mut config, "Europe");
+        assert!(!config.option_mut("Timezone").unwrap().is_modified());
+        assert_eq!(config.value("Mirror Country"), "Germany");
+
+        spec("Time Sync (NTP)")
+            .unwrap()
+            .apply_on_set(&mut config, "No");
+        let services = config.value("Services");
+        assert!(
+            !services.contains(crate::services::TIMESYNCD),
+            "{}",
+            services
+        );
+        spec("Services")
+            .unwrap()
+            .apply_on_set(&mut config, crate::services::TIMESYNCD);
+        assert_eq!(config.value("Time Sync (NTP)"), "Yes");
+    }
+}
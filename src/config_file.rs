@@ -3,18 +3,33 @@
 //! This module uses type-safe enums instead of strings for configuration values,
 //! providing compile-time validation and preventing typos.
 
+pub mod interop;
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+use crate::config::{Configuration, NOT_APPLICABLE};
+use crate::desktop;
+use crate::facts::{self, Facts};
+use crate::package_utils;
+use crate::pacman::{self, CustomRepository};
+use crate::password::PasswordPolicy;
+use crate::phases;
+use crate::pkg_cache;
+use crate::proxy::Proxy;
+use crate::ssh_keys;
+use crate::timesync;
 use crate::types::{
-    AurHelper, AutoToggle, Bootloader, BootMode, DesktopEnvironment, DisplayManager, Filesystem,
-    GpuDriver, GrubTheme, Kernel, PartitionScheme, PlymouthTheme, SnapshotFrequency, Toggle,
+    AudioServer, AurHelper, AutoToggle, Bootloader, BootMode, DesktopEnvironment, DisplayManager,
+    ExistingOsPolicy, Filesystem, GpuDriver, GrubTheme, GuestTools, HardwareClock, Kernel,
+    LidSwitch, Microcode, NvidiaDriver, PartitionScheme, PlymouthTheme, PowerManagement,
+    SnapshotFrequency, TimeSyncDaemon, TmpPolicy, Toggle,
 };
 
 /// Installation configuration that can be saved/loaded
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InstallationConfig {
     // Boot & System
     pub boot_mode: BootMode,
@@ -27,8 +42,24 @@ pub struct InstallationConfig {
     pub home_filesystem: Filesystem,
     pub separate_home: Toggle,
     pub encryption: AutoToggle,
+    /// /boot inside the LUKS container, unlocked by GRUB; omitted means no
+    #[serde(default = "default_encrypted_boot")]
+    pub encrypted_boot: Toggle,
+    /// Existing ESP to reuse (dual boot); omitted means create a new one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub efi_partition: Option<String>,
+    /// Partitions to format and mount with the existing strategy, as
+    /// `device=mountpoint[:filesystem]` entries, see [`crate::mount_plan`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition_mounts: Option<String>,
+    /// What to do with systems already on the disk; omitted means protect
+    #[serde(default)]
+    pub existing_os: ExistingOsPolicy,
     pub swap: Toggle,
     pub swap_size: String, // Size like "2GB" - flexible format
+    /// Resume from swap after hibernating; omitted means no
+    #[serde(default = "default_hibernation")]
+    pub hibernation: Toggle,
 
     // Btrfs options
     pub btrfs_snapshots: Toggle,
@@ -36,27 +67,109 @@ pub struct InstallationConfig {
     pub btrfs_keep_count: u8,
     pub btrfs_assistant: Toggle,
 
+    // LVM layout (auto_lvm and auto_luks_lvm); omitted means the defaults
+    #[serde(default = "default_lvm_volume_group")]
+    pub lvm_volume_group: String,
+    #[serde(default = "default_lvm_root_size")]
+    pub lvm_root_size: String,
+    #[serde(default = "default_lvm_var_size")]
+    pub lvm_var_size: String,
+    #[serde(default = "default_lvm_home_size")]
+    pub lvm_home_size: String,
+    #[serde(default = "default_lvm_thin")]
+    pub lvm_thin: Toggle,
+
+    /// Separate /var partition of auto_simple and auto_simple_luks, e.g.
+    /// "20G"; omitted means none
+    #[serde(default = "default_var_partition_size")]
+    pub var_partition_size: String,
+    /// Where /tmp lives; omitted means tmpfs
+    #[serde(default)]
+    pub tmp_policy: TmpPolicy,
+    /// Size limit of a tmpfs /tmp, e.g. "50%" or "4G"
+    #[serde(default = "default_tmp_size")]
+    pub tmp_size: String,
+
     // Locale & Time
+    /// Country picked in the Region quick setup; the values it filled in are
+    /// saved in their own fields, so this only records the choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
     pub timezone_region: String, // Too many options for enum
     pub timezone: String,        // Too many options for enum
     pub locale: String,          // Too many options for enum
     pub keymap: String,          // Too many options for enum
     pub time_sync: Toggle,
+    /// Daemon used when time_sync is on; omitted means systemd-timesyncd
+    #[serde(default)]
+    pub time_sync_daemon: TimeSyncDaemon,
+    /// Servers to synchronise with; omitted means the daemon's defaults
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ntp_servers: Vec<String>,
+    /// Time the hardware clock keeps; omitted means UTC
+    #[serde(default)]
+    pub hardware_clock: HardwareClock,
 
     // Network & Mirrors
+    /// Proxy for downloads; omitted means a direct connection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS downloads; omitted means the HTTP proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts reached without the proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
     pub mirror_country: String, // Too many options for enum
-    pub hostname: String,       // User-defined
+    /// Local package cache or offline repository; omitted means the mirrors only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_cache: Option<String>,
+    pub hostname: String, // User-defined
 
     // User accounts
     pub username: String,      // User-defined
     pub user_password: String, // User-defined
     pub root_password: String, // User-defined
+    /// Sources of keys authorized for the user: github:USER, gitlab:USER or a path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ssh_keys: Vec<String>,
+    /// Minimum password length; omitted means
+    /// [`DEFAULT_MIN_PASSWORD_LENGTH`](crate::password::DEFAULT_MIN_PASSWORD_LENGTH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_min_length: Option<usize>,
 
     // Packages
     pub kernel: Kernel,
+    /// CPU microcode package; omitted means auto (from the CPU vendor)
+    #[serde(default)]
+    pub microcode: Microcode,
     pub gpu_drivers: GpuDriver,
+    /// NVIDIA kernel modules; omitted means open
+    #[serde(default)]
+    pub nvidia_driver: NvidiaDriver,
+    /// DRM modeset, initramfs modules and pacman hook for NVIDIA; omitted means yes
+    #[serde(default)]
+    pub nvidia_extras: Toggle,
+    /// Virtual machine guest tools; omitted means none
+    #[serde(default)]
+    pub vm_guest_tools: GuestTools,
     pub multilib: Toggle,
+    /// Packages pacman downloads at once; omitted means 5
+    #[serde(default = "default_parallel_downloads")]
+    pub parallel_downloads: u8,
+    /// Colorized pacman output; omitted means yes
+    #[serde(default)]
+    pub pacman_color: Toggle,
+    /// pacman's VerbosePkgLists table output; omitted means no
+    #[serde(default = "default_verbose_pkg_lists")]
+    pub verbose_pkg_lists: Toggle,
+    /// Extra repositories added to pacman.conf, keys imported by the installer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_repositories: Vec<CustomRepository>,
     pub additional_packages: String,     // Space-separated list
+    /// Curated package groups by ID, installed with the additional packages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub package_groups: Vec<String>,
     pub additional_aur_packages: String, // Space-separated list
     pub aur_helper: AurHelper,
     pub flatpak: Toggle,
@@ -66,10 +179,33 @@ pub struct InstallationConfig {
     pub os_prober: Toggle,
     pub grub_themes: Toggle,
     pub grub_theme_selection: GrubTheme,
+    /// Load the GPU driver from the initramfs; omitted means no
+    #[serde(default = "default_early_kms")]
+    pub early_kms: Toggle,
+    /// Extra modules for the initramfs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub initramfs_modules: Vec<String>,
+    /// mkinitcpio hooks; empty means the install engine builds them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub initramfs_hooks: Vec<String>,
 
     // Desktop
     pub desktop_environment: DesktopEnvironment,
     pub display_manager: DisplayManager,
+    /// Sound server; omitted means PipeWire
+    #[serde(default)]
+    pub audio: AudioServer,
+    /// Laptop power manager; omitted means none
+    #[serde(default)]
+    pub power_management: PowerManagement,
+    /// What closing the laptop lid does; omitted means suspend
+    #[serde(default)]
+    pub lid_switch: LidSwitch,
+
+    // Services
+    /// systemd units enabled at boot; omitted means the defaults
+    #[serde(default = "default_services")]
+    pub services: Vec<String>,
 
     // Final setup
     pub plymouth: Toggle,
@@ -77,6 +213,14 @@ pub struct InstallationConfig {
     pub numlock_on_boot: Toggle,
     pub git_repository: Toggle,
     pub git_repository_url: String, // User-defined URL
+    /// Extra phases run after the built-in ones, by name (see `phases`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_phases: Vec<String>,
+
+    // Notifications
+    /// Webhook or ntfy topic told when the installation ends (see `notify`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_url: Option<String>,
 }
 
 impl InstallationConfig {
@@ -86,11 +230,25 @@ impl InstallationConfig {
         Self::default()
     }
 
+    /// Serialize as the pretty-printed JSON of a config file
+    ///
+    /// [`InstallationConfig::from_json`] reads it back unchanged.
+    pub fn to_json(&self) -> Result<String> {
+        let mut json = serde_json::to_string_pretty(self)
+            .context("Failed to serialize configuration to JSON")?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    /// Parse the JSON of a config file (without template variables)
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse configuration JSON")
+    }
+
     /// Save configuration to a JSON file
     #[allow(dead_code)]
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)
-            .context("Failed to serialize configuration to JSON")?;
+        let json = self.to_json()?;
 
         fs::write(&path, json)
             .with_context(|| format!("Failed to write configuration to {:?}", path.as_ref()))?;
@@ -99,62 +257,84 @@ impl InstallationConfig {
     }
 
     /// Load configuration from a JSON file
+    ///
+    /// Template variables (`{{ serial }}`) are resolved with the facts of
+    /// this machine.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::load_template(path, || Ok(Facts::gather()))?.0)
+    }
+
+    /// Load a JSON file that may be a template
+    ///
+    /// `gather` is only called when the file uses template variables. Templates
+    /// also return their resolved JSON, which is what the install scripts have
+    /// to read instead of the file.
+    pub fn load_template<P, F>(path: P, gather: F) -> Result<(Self, Option<String>)>
+    where
+        P: AsRef<Path>,
+        F: FnOnce() -> Result<Facts>,
+    {
         let content = fs::read_to_string(&path)
             .with_context(|| format!("Failed to read configuration from {:?}", path.as_ref()))?;
+        let rendered = if facts::is_template(&content) {
+            Some(Self::render_template(&content, &gather()?)?)
+        } else {
+            None
+        };
 
-        let config: Self =
-            serde_json::from_str(&content).context("Failed to parse configuration JSON")?;
+        let config = Self::from_json(rendered.as_deref().unwrap_or(&content))?;
 
-        Ok(config)
+        Ok((config, rendered))
+    }
+
+    /// Resolve the template variables of a JSON config
+    pub fn render_template(content: &str, facts: &Facts) -> Result<String> {
+        let mut json: serde_json::Value =
+            serde_json::from_str(content).context("Failed to parse configuration JSON")?;
+        facts
+            .render_json(&mut json)
+            .map_err(|e| anyhow::anyhow!("Configuration template: {}", e))?;
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    /// Initramfs Hooks as the option stores it, `auto` when none are set
+    pub fn initramfs_hooks_value(&self) -> String {
+        if self.initramfs_hooks.is_empty() {
+            crate::initramfs::AUTO.to_string()
+        } else {
+            self.initramfs_hooks.join(" ")
+        }
+    }
+
+    /// Proxy set in the configuration
+    pub fn proxy(&self) -> Proxy {
+        Proxy {
+            http: self.http_proxy.clone().unwrap_or_default(),
+            https: self.https_proxy.clone().unwrap_or_default(),
+            no_proxy: self.no_proxy.clone().unwrap_or_default(),
+        }
     }
 
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
-        // Validate disk path
+        // Validate disk path, with the whole-disk naming rules the TUI uses
         if self.install_disk.trim().is_empty() {
             anyhow::bail!("Install disk must be specified");
         }
+        crate::disk::validate_disks(&self.install_disk).map_err(anyhow::Error::msg)?;
 
-        // Validate hostname (3-32 chars, start with letter, alphanumeric + underscore)
+        // Validate hostname (RFC 1123) and username (portable POSIX, not reserved)
         let hostname = self.hostname.trim();
         if hostname.is_empty() {
             anyhow::bail!("Hostname must be specified");
         }
-        if hostname.len() < 3 || hostname.len() > 32 {
-            anyhow::bail!("Hostname must be 3-32 characters long");
-        }
-        if let Some(first_char) = hostname.chars().next() {
-            if !first_char.is_ascii_alphabetic() {
-                anyhow::bail!("Hostname must start with a letter");
-            }
-        }
-        if !hostname
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '_')
-        {
-            anyhow::bail!("Hostname can only contain letters, numbers, and underscores");
-        }
+        crate::config::validate_hostname(hostname).map_err(anyhow::Error::msg)?;
 
-        // Validate username (3-32 chars, start with letter, alphanumeric + underscore)
         let username = self.username.trim();
         if username.is_empty() {
             anyhow::bail!("Username must be specified");
         }
-        if username.len() < 3 || username.len() > 32 {
-            anyhow::bail!("Username must be 3-32 characters long");
-        }
-        if let Some(first_char) = username.chars().next() {
-            if !first_char.is_ascii_alphabetic() {
-                anyhow::bail!("Username must start with a letter");
-            }
-        }
-        if !username
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '_')
-        {
-            anyhow::bail!("Username can only contain letters, numbers, and underscores");
-        }
+        crate::config::validate_username(username).map_err(anyhow::Error::msg)?;
 
         // Validate passwords (non-empty, no whitespace)
         if self.user_password.trim().is_empty() {
@@ -171,6 +351,110 @@ impl InstallationConfig {
             anyhow::bail!("Root password cannot contain whitespace");
         }
 
+        // Validate SSH key sources
+        ssh_keys::parse_sources(&self.ssh_keys.join(" ")).map_err(anyhow::Error::msg)?;
+
+        // Enforce the minimum length policy, the TUI's default when omitted
+        let policy = self.password_policy();
+        policy
+            .check(&self.user_password)
+            .map_err(|e| anyhow::anyhow!("User password: {}", e))?;
+        policy
+            .check(&self.root_password)
+            .map_err(|e| anyhow::anyhow!("Root password: {}", e))?;
+
+        // Validate the bootloader against the boot mode; with Auto the
+        // scripts check the firmware and architecture they run on
+        crate::config::validate_boot_mode(self.boot_mode, self.bootloader, None)
+            .map_err(anyhow::Error::msg)?;
+
+        // Validate ESP reuse against the boot mode and strategy
+        if let Some(efi_partition) = &self.efi_partition {
+            crate::esp::validate_reuse(
+                efi_partition,
+                self.boot_mode,
+                self.partitioning_strategy,
+                None,
+            )
+            .map_err(anyhow::Error::msg)?;
+        }
+
+        // Validate the partitions to install onto; the disks are read at install time
+        if let Some(partition_mounts) = &self.partition_mounts {
+            crate::mount_plan::validate_value(partition_mounts)
+                .map_err(|e| anyhow::anyhow!("Partition mounts: {}", e))?;
+        }
+        if self.partitioning_strategy == PartitionScheme::Existing
+            && self
+                .partition_mounts
+                .as_deref()
+                .is_none_or(|value| value.trim().is_empty())
+        {
+            return Err(anyhow::anyhow!(
+                "The existing partitioning strategy needs partition_mounts, e.g. \
+                 \"/dev/sda2=/:ext4,/dev/sda1=/efi\""
+            ));
+        }
+
+        // Validate installing alongside other systems against the strategy
+        crate::multiboot::validate_policy(self.existing_os, self.partitioning_strategy)
+            .map_err(anyhow::Error::msg)?;
+
+        // Validate the LVM layout; whether it fits the disk is checked at install time
+        if crate::lvm::is_configurable(self.partitioning_strategy) {
+            crate::lvm::validate_layout(
+                &self.lvm_volume_group,
+                &self.lvm_root_size,
+                &self.lvm_var_size,
+                (self.separate_home == Toggle::Yes).then_some(self.lvm_home_size.as_str()),
+                self.lvm_thin == Toggle::Yes,
+                None,
+            )
+            .map_err(anyhow::Error::msg)?;
+        }
+
+        // Validate the /var partition and /tmp policy
+        crate::var_tmp::validate(
+            self.partitioning_strategy,
+            &self.var_partition_size,
+            self.tmp_policy,
+            &self.tmp_size,
+        )
+        .map_err(anyhow::Error::msg)?;
+
+        // Validate encrypted /boot against the bootloader and strategy
+        if self.encrypted_boot == Toggle::Yes {
+            crate::config::validate_encrypted_boot(
+                self.bootloader,
+                self.partitioning_strategy,
+                self.encryption,
+            )
+            .map_err(anyhow::Error::msg)?;
+        }
+
+        // Validate filesystem-specific options and the kernel they need
+        crate::filesystem::validate_filesystems(
+            self.root_filesystem,
+            (self.separate_home == Toggle::Yes).then_some(self.home_filesystem),
+            self.btrfs_snapshots == Toggle::Yes,
+            self.encrypted_boot == Toggle::Yes,
+            crate::filesystem::running_kernel().as_deref(),
+        )
+        .map_err(anyhow::Error::msg)?;
+
+        // Validate pacman tuning
+        pacman::validate_parallel_downloads(&self.parallel_downloads.to_string())
+            .map_err(anyhow::Error::msg)?;
+        for (i, repository) in self.custom_repositories.iter().enumerate() {
+            repository.validate().map_err(anyhow::Error::msg)?;
+            if self.custom_repositories[..i]
+                .iter()
+                .any(|r| r.name == repository.name)
+            {
+                anyhow::bail!("Repository '{}' is listed twice", repository.name);
+            }
+        }
+
         // Validate Git repository URL format if enabled
         if self.git_repository == Toggle::Yes && !self.git_repository_url.trim().is_empty() {
             let url = self.git_repository_url.trim();
@@ -185,6 +469,75 @@ impl InstallationConfig {
             }
         }
 
+        // Validate package groups
+        let groups = package_utils::parse_package_groups(&self.package_groups.join(" "))
+            .map_err(anyhow::Error::msg)?;
+        if let Some(group) = groups.iter().find(|g| g.requires_multilib) {
+            if self.multilib != Toggle::Yes {
+                anyhow::bail!("Package group '{}' requires multilib", group.id);
+            }
+        }
+
+        // Validate the initramfs modules and the hooks against the layout
+        crate::initramfs::parse_modules(&self.initramfs_modules.join(" "))
+            .map_err(anyhow::Error::msg)?;
+        if !self.initramfs_hooks.is_empty() {
+            crate::initramfs::validate_hooks(&self.initramfs_hooks).map_err(anyhow::Error::msg)?;
+            crate::initramfs::check_storage(
+                &self.initramfs_hooks,
+                self.partitioning_strategy,
+                self.encryption,
+            )
+            .map_err(anyhow::Error::msg)?;
+        }
+
+        // Hibernation needs disk swap and an initramfs that resumes; the RAM
+        // of the target is checked when installing
+        if self.hibernation == Toggle::Yes {
+            crate::hibernation::check_swap(
+                self.swap == Toggle::Yes,
+                &self.swap_size,
+                None,
+                crate::hibernation::uses_zram(&self.additional_packages),
+            )
+            .map_err(anyhow::Error::msg)?;
+            if !self.initramfs_hooks.is_empty() {
+                crate::hibernation::check_hooks(&self.initramfs_hooks)
+                    .map_err(anyhow::Error::msg)?;
+            }
+        }
+
+        // Validate services
+        crate::services::parse_services(&self.services.join(" ")).map_err(anyhow::Error::msg)?;
+
+        // Validate NTP servers
+        timesync::parse_servers(&self.ntp_servers.join(" ")).map_err(anyhow::Error::msg)?;
+
+        // Validate the proxy
+        self.proxy().validate().map_err(anyhow::Error::msg)?;
+
+        // Validate the package cache path; its contents are checked where it is used
+        if let Some(dir) = &self.package_cache {
+            pkg_cache::validate_path(dir).map_err(anyhow::Error::msg)?;
+        }
+
+        // Validate the power manager against the desktop environment
+        crate::power::validate(self.power_management, self.desktop_environment)
+            .map_err(anyhow::Error::msg)?;
+
+        // Validate custom phase names; whether they exist is checked when they run
+        for (i, name) in self.custom_phases.iter().enumerate() {
+            phases::validate_phase_name(name).map_err(anyhow::Error::msg)?;
+            if self.custom_phases[..i].contains(name) {
+                anyhow::bail!("Phase '{}' is listed twice", name);
+            }
+        }
+
+        // Validate the notify URL; whether it answers is seen when the install ends
+        if let Some(url) = &self.notify_url {
+            crate::notify::validate_url(url).map_err(anyhow::Error::msg)?;
+        }
+
         // Validate RAID configuration
         if self.partitioning_strategy.requires_raid() {
             // RAID validation would check multiple disks - handled at runtime
@@ -193,10 +546,16 @@ impl InstallationConfig {
         Ok(())
     }
 
+    /// Policy the user and root passwords must meet
+    pub fn password_policy(&self) -> PasswordPolicy {
+        self.password_min_length
+            .map_or_else(PasswordPolicy::default, PasswordPolicy::new)
+    }
+
     /// Convert to environment variables for Bash scripts
     #[allow(dead_code)]
     pub fn to_env_vars(&self) -> Vec<(String, String)> {
-        vec![
+        let mut vars = vec![
             ("BOOT_MODE".to_string(), self.boot_mode.to_string()),
             ("SECURE_BOOT".to_string(), self.secure_boot.to_string()),
             ("INSTALL_DISK".to_string(), self.install_disk.clone()),
@@ -214,8 +573,24 @@ impl InstallationConfig {
             ),
             ("SEPARATE_HOME".to_string(), self.separate_home.to_string()),
             ("ENCRYPTION".to_string(), self.encryption.to_string()),
+            (
+                "ENCRYPTED_BOOT".to_string(),
+                self.encrypted_boot.to_string(),
+            ),
+            (
+                "EFI_PARTITION".to_string(),
+                self.efi_partition
+                    .clone()
+                    .unwrap_or_else(|| crate::esp::CREATE.to_string()),
+            ),
+            (
+                "PARTITION_MOUNTS".to_string(),
+                self.partition_mounts.clone().unwrap_or_default(),
+            ),
+            ("EXISTING_OS".to_string(), self.existing_os.to_string()),
             ("SWAP".to_string(), self.swap.to_string()),
             ("SWAP_SIZE".to_string(), self.swap_size.clone()),
+            ("HIBERNATION".to_string(), self.hibernation.to_string()),
             (
                 "BTRFS_SNAPSHOTS".to_string(),
                 self.btrfs_snapshots.to_string(),
@@ -232,23 +607,95 @@ impl InstallationConfig {
                 "BTRFS_ASSISTANT".to_string(),
                 self.btrfs_assistant.to_string(),
             ),
+            (
+                "LVM_VOLUME_GROUP".to_string(),
+                self.lvm_volume_group.clone(),
+            ),
+            ("LVM_ROOT_SIZE".to_string(), self.lvm_root_size.clone()),
+            ("LVM_VAR_SIZE".to_string(), self.lvm_var_size.clone()),
+            ("LVM_HOME_SIZE".to_string(), self.lvm_home_size.clone()),
+            ("LVM_THIN".to_string(), self.lvm_thin.to_string()),
+            (
+                "VAR_PARTITION_SIZE".to_string(),
+                self.var_partition_size.clone(),
+            ),
+            ("TMP_POLICY".to_string(), self.tmp_policy.to_string()),
+            ("TMP_SIZE".to_string(), self.tmp_size.clone()),
+            (
+                "REGION".to_string(),
+                self.region.clone().unwrap_or_default(),
+            ),
             ("TIMEZONE_REGION".to_string(), self.timezone_region.clone()),
             ("TIMEZONE".to_string(), self.timezone.clone()),
             ("LOCALE".to_string(), self.locale.clone()),
             ("KEYMAP".to_string(), self.keymap.clone()),
             ("TIME_SYNC".to_string(), self.time_sync.to_string()),
+            (
+                "TIME_SYNC_DAEMON".to_string(),
+                self.time_sync_daemon.to_string(),
+            ),
+            (
+                "NTP_SERVERS".to_string(),
+                timesync::format_servers(&self.ntp_servers),
+            ),
+            (
+                "HARDWARE_CLOCK".to_string(),
+                self.hardware_clock.to_string(),
+            ),
+            (
+                "HTTP_PROXY".to_string(),
+                self.http_proxy.clone().unwrap_or_default(),
+            ),
+            (
+                "HTTPS_PROXY".to_string(),
+                self.https_proxy.clone().unwrap_or_default(),
+            ),
+            (
+                "NO_PROXY".to_string(),
+                self.no_proxy.clone().unwrap_or_default(),
+            ),
             ("MIRROR_COUNTRY".to_string(), self.mirror_country.clone()),
+            (
+                "PKG_CACHE".to_string(),
+                self.package_cache.clone().unwrap_or_default(),
+            ),
             ("SYSTEM_HOSTNAME".to_string(), self.hostname.clone()),
             ("MAIN_USERNAME".to_string(), self.username.clone()),
             ("MAIN_USER_PASSWORD".to_string(), self.user_password.clone()),
             ("ROOT_PASSWORD".to_string(), self.root_password.clone()),
+            (
+                "SSH_KEYS".to_string(),
+                ssh_keys::format_sources(&self.ssh_keys),
+            ),
+            (
+                "PASSWORD_MIN_LENGTH".to_string(),
+                self.password_policy().min_length.to_string(),
+            ),
             ("KERNEL".to_string(), self.kernel.to_string()),
+            ("MICROCODE".to_string(), self.microcode.to_string()),
             ("GPU_DRIVERS".to_string(), self.gpu_drivers.to_string()),
+            ("NVIDIA_DRIVER".to_string(), self.nvidia_driver.to_string()),
+            ("NVIDIA_EXTRAS".to_string(), self.nvidia_extras.to_string()),
+            ("VM_GUEST_TOOLS".to_string(), self.vm_guest_tools.to_string()),
             ("MULTILIB".to_string(), self.multilib.to_string()),
+            (
+                "PARALLEL_DOWNLOADS".to_string(),
+                self.parallel_downloads.to_string(),
+            ),
+            ("PACMAN_COLOR".to_string(), self.pacman_color.to_string()),
+            (
+                "VERBOSE_PKG_LISTS".to_string(),
+                self.verbose_pkg_lists.to_string(),
+            ),
+            (
+                "CUSTOM_REPOSITORIES".to_string(),
+                pacman::format_repositories(&self.custom_repositories),
+            ),
             (
                 "ADDITIONAL_PACKAGES".to_string(),
-                self.additional_packages.clone(),
+                package_utils::merge_packages(&self.additional_packages, &self.package_groups),
             ),
+            ("PACKAGE_GROUPS".to_string(), self.package_groups.join(" ")),
             (
                 "ADDITIONAL_AUR_PACKAGES".to_string(),
                 self.additional_aur_packages.clone(),
@@ -262,14 +709,38 @@ impl InstallationConfig {
                 "GRUB_THEME_SELECTION".to_string(),
                 self.grub_theme_selection.to_string(),
             ),
+            ("EARLY_KMS".to_string(), self.early_kms.to_string()),
+            (
+                "INITRAMFS_MODULES".to_string(),
+                self.initramfs_modules.join(" "),
+            ),
+            ("INITRAMFS_HOOKS".to_string(), self.initramfs_hooks_value()),
             (
                 "DESKTOP_ENVIRONMENT".to_string(),
                 self.desktop_environment.to_string(),
             ),
+            (
+                "DESKTOP_PACKAGES".to_string(),
+                desktop::packages(self.desktop_environment),
+            ),
             (
                 "DISPLAY_MANAGER".to_string(),
                 self.display_manager.to_string(),
             ),
+            ("AUDIO".to_string(), self.audio.to_string()),
+            (
+                "POWER_MANAGEMENT".to_string(),
+                self.power_management.to_string(),
+            ),
+            ("LID_SWITCH".to_string(), self.lid_switch.to_string()),
+            (
+                "SERVICES".to_string(),
+                if self.services.is_empty() {
+                    crate::services::NONE.to_string()
+                } else {
+                    self.services.join(" ")
+                },
+            ),
             ("PLYMOUTH".to_string(), self.plymouth.to_string()),
             (
                 "PLYMOUTH_THEME".to_string(),
@@ -287,7 +758,26 @@ impl InstallationConfig {
                 "GIT_REPOSITORY_URL".to_string(),
                 self.git_repository_url.clone(),
             ),
-        ]
+            ("CUSTOM_PHASES".to_string(), self.custom_phases.join(" ")),
+        ];
+        vars.extend(self.package_vars());
+        vars
+    }
+
+    /// Packages and services of the chosen options, from the binary's tables
+    ///
+    /// The scripts keep no copy of these tables, so the variables are passed
+    /// next to a configuration file as well.
+    pub fn package_vars(&self) -> Vec<(String, String)> {
+        let time_sync = (self.time_sync == Toggle::Yes).then_some(self.time_sync_daemon);
+        let service_packages = crate::services::packages(&self.services.join(" "), time_sync);
+        self.vm_guest_tools
+            .env_vars()
+            .into_iter()
+            .chain(self.audio.env_vars())
+            .chain([("SERVICE_PACKAGES", service_packages)])
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
     }
 }
 
@@ -302,26 +792,58 @@ impl Default for InstallationConfig {
             home_filesystem: Filesystem::Ext4,
             separate_home: Toggle::No,
             encryption: AutoToggle::Auto,
+            encrypted_boot: Toggle::No,
+            efi_partition: None,
+            partition_mounts: None,
+            existing_os: ExistingOsPolicy::Protect,
             swap: Toggle::Yes,
             swap_size: "2GB".to_string(),
+            hibernation: Toggle::No,
             btrfs_snapshots: Toggle::No,
             btrfs_frequency: SnapshotFrequency::Weekly,
             btrfs_keep_count: 3,
             btrfs_assistant: Toggle::No,
+            lvm_volume_group: default_lvm_volume_group(),
+            lvm_root_size: default_lvm_root_size(),
+            lvm_var_size: default_lvm_var_size(),
+            lvm_home_size: default_lvm_home_size(),
+            lvm_thin: Toggle::No,
+            var_partition_size: default_var_partition_size(),
+            tmp_policy: TmpPolicy::Tmpfs,
+            tmp_size: default_tmp_size(),
+            region: None,
             timezone_region: "America".to_string(),
             timezone: "New_York".to_string(),
             locale: "en_US.UTF-8".to_string(),
             keymap: "us".to_string(),
             time_sync: Toggle::Yes,
+            time_sync_daemon: TimeSyncDaemon::Timesyncd,
+            ntp_servers: Vec::new(),
+            hardware_clock: HardwareClock::Utc,
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: None,
             mirror_country: "United States".to_string(),
+            package_cache: None,
             hostname: String::new(),
             username: String::new(),
             user_password: String::new(),
             root_password: String::new(),
+            ssh_keys: Vec::new(),
+            password_min_length: None,
             kernel: Kernel::Linux,
+            microcode: Microcode::Auto,
             gpu_drivers: GpuDriver::Auto,
+            nvidia_driver: NvidiaDriver::Open,
+            nvidia_extras: Toggle::Yes,
+            vm_guest_tools: GuestTools::None,
             multilib: Toggle::Yes,
+            parallel_downloads: pacman::DEFAULT_PARALLEL_DOWNLOADS,
+            pacman_color: Toggle::Yes,
+            verbose_pkg_lists: Toggle::No,
+            custom_repositories: Vec::new(),
             additional_packages: String::new(),
+            package_groups: Vec::new(),
             additional_aur_packages: String::new(),
             aur_helper: AurHelper::Paru,
             flatpak: Toggle::No,
@@ -329,20 +851,256 @@ impl Default for InstallationConfig {
             os_prober: Toggle::Yes,
             grub_themes: Toggle::No,
             grub_theme_selection: GrubTheme::PolyDark,
+            early_kms: Toggle::No,
+            initramfs_modules: Vec::new(),
+            initramfs_hooks: Vec::new(),
             desktop_environment: DesktopEnvironment::None,
             display_manager: DisplayManager::None,
+            audio: AudioServer::PipeWire,
+            power_management: PowerManagement::None,
+            lid_switch: LidSwitch::Suspend,
+            services: default_services(),
             plymouth: Toggle::Yes,
             plymouth_theme: PlymouthTheme::ArchGlow,
             numlock_on_boot: Toggle::Yes,
             git_repository: Toggle::No,
             git_repository_url: String::new(),
+            custom_phases: Vec::new(),
+            notify_url: None,
+        }
+    }
+}
+
+/// Convert the guided installer's options for saving to a file
+///
+/// Fails instead of silently changing an option: every value must come back
+/// unchanged when the file is loaded again. Options left empty, or "N/A"
+/// because they do not apply (e.g. the snapshot frequency without
+/// snapshots), are exempt.
+impl TryFrom<&Configuration> for InstallationConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(tui_config: &Configuration) -> Result<Self> {
+        let config = Self::from_options(tui_config);
+        let lost: Vec<String> = config
+            .option_values()
+            .into_iter()
+            .filter_map(|(name, saved)| {
+                let value = tui_config
+                    .options
+                    .iter()
+                    .find(|option| option.name == name)?
+                    .get_value();
+                let unset = value.is_empty() || value == NOT_APPLICABLE;
+                (value != saved && !unset)
+                    .then(|| format!("{} '{}' would be saved as '{}'", name, value, saved))
+            })
+            .collect();
+        if !lost.is_empty() {
+            anyhow::bail!("Cannot save the configuration: {}", lost.join("; "));
+        }
+        Ok(config)
+    }
+}
+
+/// Load a file configuration into the guided installer's options
+impl From<&InstallationConfig> for Configuration {
+    fn from(file_config: &InstallationConfig) -> Self {
+        let mut config = Self::default();
+        for (name, value) in file_config.option_values() {
+            if let Some(option) = config.options.iter_mut().find(|o| o.name == name) {
+                option.value = value;
+            }
         }
+        config
     }
 }
 
+fn default_parallel_downloads() -> u8 {
+    pacman::DEFAULT_PARALLEL_DOWNLOADS
+}
+
+fn default_verbose_pkg_lists() -> Toggle {
+    Toggle::No
+}
+
+fn default_encrypted_boot() -> Toggle {
+    Toggle::No
+}
+
+fn default_lvm_volume_group() -> String {
+    crate::lvm::DEFAULT_VOLUME_GROUP.to_string()
+}
+
+fn default_lvm_root_size() -> String {
+    crate::lvm::DEFAULT_ROOT_SIZE.to_string()
+}
+
+fn default_lvm_var_size() -> String {
+    crate::lvm::DEFAULT_VAR_SIZE.to_string()
+}
+
+fn default_var_partition_size() -> String {
+    crate::var_tmp::DEFAULT_VAR_SIZE.to_string()
+}
+
+fn default_tmp_size() -> String {
+    crate::var_tmp::DEFAULT_TMP_SIZE.to_string()
+}
+
+fn default_lvm_home_size() -> String {
+    crate::lvm::DEFAULT_HOME_SIZE.to_string()
+}
+
+fn default_lvm_thin() -> Toggle {
+    Toggle::No
+}
+
+fn default_hibernation() -> Toggle {
+    Toggle::No
+}
+
+fn default_early_kms() -> Toggle {
+    Toggle::No
+}
+
+fn default_services() -> Vec<String> {
+    crate::services::default_services()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
 /// Convert from TUI Configuration to InstallationConfig
-impl From<&crate::config::Configuration> for InstallationConfig {
-    fn from(tui_config: &crate::config::Configuration) -> Self {
+impl InstallationConfig {
+    /// Guided installer option values this configuration corresponds to
+    ///
+    /// The inverse of converting a [`Configuration`]: every option the TUI
+    /// shows, by name, with the value it displays.
+    pub fn option_values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Boot Mode", self.boot_mode.to_string()),
+            ("Secure Boot", self.secure_boot.to_string()),
+            ("Region", self.region.clone().unwrap_or_default()),
+            ("Locale", self.locale.clone()),
+            ("Keymap", self.keymap.clone()),
+            ("Disk", self.install_disk.clone()),
+            (
+                "Partitioning Strategy",
+                self.partitioning_strategy.to_string(),
+            ),
+            ("Encryption", self.encryption.to_string()),
+            ("Encrypted Boot", self.encrypted_boot.to_string()),
+            (
+                "EFI Partition",
+                self.efi_partition
+                    .clone()
+                    .unwrap_or_else(|| crate::esp::CREATE.to_string()),
+            ),
+            (
+                "Partition Mounts",
+                self.partition_mounts.clone().unwrap_or_default(),
+            ),
+            ("Existing OS", self.existing_os.to_string()),
+            ("Root Filesystem", self.root_filesystem.to_string()),
+            ("Separate Home Partition", self.separate_home.to_string()),
+            ("Home Filesystem", self.home_filesystem.to_string()),
+            ("Swap", self.swap.to_string()),
+            ("Swap Size", self.swap_size.clone()),
+            ("Hibernation", self.hibernation.to_string()),
+            ("Btrfs Snapshots", self.btrfs_snapshots.to_string()),
+            ("Btrfs Frequency", self.btrfs_frequency.to_string()),
+            ("Btrfs Keep Count", self.btrfs_keep_count.to_string()),
+            ("Btrfs Assistant", self.btrfs_assistant.to_string()),
+            ("LVM Volume Group", self.lvm_volume_group.clone()),
+            ("LVM Root Size", self.lvm_root_size.clone()),
+            ("LVM Var Size", self.lvm_var_size.clone()),
+            ("LVM Home Size", self.lvm_home_size.clone()),
+            ("LVM Thin Provisioning", self.lvm_thin.to_string()),
+            ("Var Partition Size", self.var_partition_size.clone()),
+            ("Tmp Policy", self.tmp_policy.to_string()),
+            ("Tmp Size", self.tmp_size.clone()),
+            ("Timezone Region", self.timezone_region.clone()),
+            ("Timezone", self.timezone.clone()),
+            ("Time Sync (NTP)", self.time_sync.to_string()),
+            ("Time Sync Daemon", self.time_sync_daemon.to_string()),
+            ("NTP Servers", timesync::format_servers(&self.ntp_servers)),
+            ("Hardware Clock", self.hardware_clock.to_string()),
+            ("HTTP Proxy", self.http_proxy.clone().unwrap_or_default()),
+            ("HTTPS Proxy", self.https_proxy.clone().unwrap_or_default()),
+            ("No Proxy", self.no_proxy.clone().unwrap_or_default()),
+            ("Mirror Country", self.mirror_country.clone()),
+            (
+                "Package Cache",
+                self.package_cache.clone().unwrap_or_default(),
+            ),
+            ("Kernel", self.kernel.to_string()),
+            ("CPU Microcode", self.microcode.to_string()),
+            ("Multilib", self.multilib.to_string()),
+            ("Parallel Downloads", self.parallel_downloads.to_string()),
+            ("Pacman Color", self.pacman_color.to_string()),
+            ("Verbose Package Lists", self.verbose_pkg_lists.to_string()),
+            (
+                "Custom Repositories",
+                pacman::format_repositories(&self.custom_repositories),
+            ),
+            (
+                "Additional Pacman Packages",
+                self.additional_packages.clone(),
+            ),
+            ("Package Groups", self.package_groups.join(" ")),
+            ("GPU Drivers", self.gpu_drivers.to_string()),
+            ("NVIDIA Driver", self.nvidia_driver.to_string()),
+            ("NVIDIA Extras", self.nvidia_extras.to_string()),
+            ("VM Guest Tools", self.vm_guest_tools.to_string()),
+            ("Hostname", self.hostname.clone()),
+            ("Username", self.username.clone()),
+            ("User Password", self.user_password.clone()),
+            ("Root Password", self.root_password.clone()),
+            ("SSH Keys", ssh_keys::format_sources(&self.ssh_keys)),
+            (
+                "Password Minimum Length",
+                self.password_policy().min_length.to_string(),
+            ),
+            ("AUR Helper", self.aur_helper.to_string()),
+            (
+                "Additional AUR Packages",
+                self.additional_aur_packages.clone(),
+            ),
+            ("Flatpak", self.flatpak.to_string()),
+            ("Bootloader", self.bootloader.to_string()),
+            ("OS Prober", self.os_prober.to_string()),
+            ("GRUB Theme", self.grub_themes.to_string()),
+            (
+                "GRUB Theme Selection",
+                self.grub_theme_selection.to_string(),
+            ),
+            ("Early KMS", self.early_kms.to_string()),
+            ("Initramfs Modules", self.initramfs_modules.join(" ")),
+            ("Initramfs Hooks", self.initramfs_hooks_value()),
+            ("Desktop Environment", self.desktop_environment.to_string()),
+            ("Display Manager", self.display_manager.to_string()),
+            ("Audio", self.audio.to_string()),
+            ("Power Management", self.power_management.to_string()),
+            ("Lid Close Action", self.lid_switch.to_string()),
+            (
+                "Services",
+                if self.services.is_empty() {
+                    crate::services::NONE.to_string()
+                } else {
+                    self.services.join(" ")
+                },
+            ),
+            ("Plymouth", self.plymouth.to_string()),
+            ("Plymouth Theme", self.plymouth_theme.to_string()),
+            ("Numlock on Boot", self.numlock_on_boot.to_string()),
+            ("Git Repository", self.git_repository.to_string()),
+            ("Git Repository URL", self.git_repository_url.clone()),
+        ]
+    }
+
+    /// Read the guided installer's options, defaulting values that do not parse
+    fn from_options(tui_config: &Configuration) -> Self {
         use std::str::FromStr;
 
         // Helper closure to find a value by name
@@ -369,26 +1127,74 @@ impl From<&crate::config::Configuration> for InstallationConfig {
             home_filesystem: parse_or_default(&get_value("Home Filesystem")),
             separate_home: parse_or_default(&get_value("Separate Home Partition")),
             encryption: parse_or_default(&get_value("Encryption")),
+            encrypted_boot: get_value("Encrypted Boot").parse().unwrap_or(Toggle::No),
+            efi_partition: Some(get_value("EFI Partition"))
+                .filter(|value| !value.is_empty() && value != crate::esp::CREATE),
+            partition_mounts: Some(get_value("Partition Mounts")).filter(|value| !value.is_empty()),
+            existing_os: parse_or_default(&get_value("Existing OS")),
             swap: parse_or_default(&get_value("Swap")),
             swap_size: get_value("Swap Size"),
+            hibernation: parse_or_default(&get_value("Hibernation")),
             btrfs_snapshots: parse_or_default(&get_value("Btrfs Snapshots")),
             btrfs_frequency: parse_or_default(&get_value("Btrfs Frequency")),
             btrfs_keep_count: get_value("Btrfs Keep Count").parse().unwrap_or(3),
             btrfs_assistant: parse_or_default(&get_value("Btrfs Assistant")),
+            lvm_volume_group: get_value("LVM Volume Group"),
+            lvm_root_size: get_value("LVM Root Size"),
+            lvm_var_size: get_value("LVM Var Size"),
+            lvm_home_size: get_value("LVM Home Size"),
+            lvm_thin: get_value("LVM Thin Provisioning")
+                .parse()
+                .unwrap_or(Toggle::No),
+            var_partition_size: get_value("Var Partition Size"),
+            tmp_policy: parse_or_default(&get_value("Tmp Policy")),
+            tmp_size: get_value("Tmp Size"),
             timezone_region: get_value("Timezone Region"),
             timezone: get_value("Timezone"),
             locale: get_value("Locale"),
             keymap: get_value("Keymap"),
+            region: Some(get_value("Region")).filter(|value| !value.is_empty()),
             time_sync: parse_or_default(&get_value("Time Sync (NTP)")),
+            time_sync_daemon: parse_or_default(&get_value("Time Sync Daemon")),
+            ntp_servers: timesync::parse_servers(&get_value("NTP Servers")).unwrap_or_default(),
+            hardware_clock: parse_or_default(&get_value("Hardware Clock")),
+            http_proxy: Some(get_value("HTTP Proxy")).filter(|value| !value.is_empty()),
+            https_proxy: Some(get_value("HTTPS Proxy")).filter(|value| !value.is_empty()),
+            no_proxy: Some(get_value("No Proxy")).filter(|value| !value.is_empty()),
             mirror_country: get_value("Mirror Country"),
+            package_cache: Some(get_value("Package Cache")).filter(|value| !value.is_empty()),
             hostname: get_value("Hostname"),
             username: get_value("Username"),
             user_password: get_value("User Password"),
             root_password: get_value("Root Password"),
+            ssh_keys: ssh_keys::parse_sources(&get_value("SSH Keys"))
+                .map(|sources| sources.iter().map(ToString::to_string).collect())
+                .unwrap_or_default(),
+            // The default is left out of the file, like an untouched option
+            password_min_length: Some(tui_config.password_policy())
+                .filter(|policy| *policy != PasswordPolicy::default())
+                .map(|policy| policy.min_length),
             kernel: parse_or_default(&get_value("Kernel")),
+            microcode: parse_or_default(&get_value("CPU Microcode")),
             gpu_drivers: parse_or_default(&get_value("GPU Drivers")),
+            nvidia_driver: parse_or_default(&get_value("NVIDIA Driver")),
+            nvidia_extras: parse_or_default(&get_value("NVIDIA Extras")),
+            vm_guest_tools: parse_or_default(&get_value("VM Guest Tools")),
             multilib: parse_or_default(&get_value("Multilib")),
+            parallel_downloads: get_value("Parallel Downloads")
+                .parse()
+                .unwrap_or(pacman::DEFAULT_PARALLEL_DOWNLOADS),
+            pacman_color: parse_or_default(&get_value("Pacman Color")),
+            verbose_pkg_lists: get_value("Verbose Package Lists")
+                .parse()
+                .unwrap_or(Toggle::No),
+            custom_repositories: pacman::parse_repositories(&get_value("Custom Repositories"))
+                .unwrap_or_default(),
             additional_packages: get_value("Additional Pacman Packages"),
+            package_groups: get_value("Package Groups")
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
             additional_aur_packages: get_value("Additional AUR Packages"),
             aur_helper: parse_or_default(&get_value("AUR Helper")),
             flatpak: parse_or_default(&get_value("Flatpak")),
@@ -396,13 +1202,32 @@ impl From<&crate::config::Configuration> for InstallationConfig {
             os_prober: parse_or_default(&get_value("OS Prober")),
             grub_themes: parse_or_default(&get_value("GRUB Theme")),
             grub_theme_selection: parse_or_default(&get_value("GRUB Theme Selection")),
+            early_kms: parse_or_default(&get_value("Early KMS")),
+            initramfs_modules: get_value("Initramfs Modules")
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            initramfs_hooks: crate::initramfs::parse_hooks(&get_value("Initramfs Hooks"))
+                .ok()
+                .flatten()
+                .unwrap_or_default(),
             desktop_environment: parse_or_default(&get_value("Desktop Environment")),
             display_manager: parse_or_default(&get_value("Display Manager")),
+            audio: parse_or_default(&get_value("Audio")),
+            power_management: parse_or_default(&get_value("Power Management")),
+            lid_switch: parse_or_default(&get_value("Lid Close Action")),
+            services: get_value("Services")
+                .split_whitespace()
+                .filter(|unit| *unit != crate::services::NONE)
+                .map(str::to_string)
+                .collect(),
             plymouth: parse_or_default(&get_value("Plymouth")),
             plymouth_theme: parse_or_default(&get_value("Plymouth Theme")),
             numlock_on_boot: parse_or_default(&get_value("Numlock on Boot")),
             git_repository: parse_or_default(&get_value("Git Repository")),
             git_repository_url: get_value("Git Repository URL"),
+            custom_phases: Vec::new(),
+            notify_url: None,
         }
     }
 }
@@ -453,6 +1278,47 @@ mod tests {
         assert!(env_vars.contains(&("ROOT_FILESYSTEM".to_string(), "ext4".to_string())));
     }
 
+    #[test]
+    fn test_validate_accepts_a_disk_list() {
+        let raid = InstallationConfig {
+            install_disk: "/dev/nvme0n1,/dev/nvme1n1".to_string(),
+            ..create_test_config()
+        };
+        assert!(raid.validate().is_ok());
+    }
+
+    #[test]
+    fn test_package_vars_come_from_the_type_tables() {
+        let config = InstallationConfig {
+            vm_guest_tools: GuestTools::Vmware,
+            audio: AudioServer::PulseAudio,
+            services: vec![
+                "bluetooth.service".to_string(),
+                crate::services::TIMESYNCD.to_string(),
+            ],
+            time_sync_daemon: TimeSyncDaemon::Chrony,
+            ..create_test_config()
+        };
+        let vars = config.package_vars();
+        assert!(vars.contains(&(
+            "GUEST_TOOLS_PACKAGES".to_string(),
+            "open-vm-tools".to_string()
+        )));
+        assert!(vars.contains(&(
+            "GUEST_TOOLS_SERVICES".to_string(),
+            "vmtoolsd.service vmware-vmblock-fuse.service".to_string()
+        )));
+        assert!(vars.contains(&(
+            "AUDIO_USER_SERVICES".to_string(),
+            "pulseaudio.socket".to_string()
+        )));
+        assert!(vars.contains(&(
+            "SERVICE_PACKAGES".to_string(),
+            "bluez bluez-utils chrony".to_string()
+        )));
+        assert!(config.to_env_vars().ends_with(&vars));
+    }
+
     #[test]
     fn test_save_and_load_json_config() {
         let config = create_test_config();
@@ -510,7 +1376,7 @@ mod tests {
     #[test]
     fn test_validation_invalid_hostname() {
         let mut config = create_test_config();
-        config.hostname = "1invalid".to_string(); // Starts with number
+        config.hostname = "-invalid".to_string(); // Starts with hyphen
         assert!(config.validate().is_err());
     }
 
@@ -625,30 +1491,30 @@ mod tests {
     }
 
     #[test]
-    fn test_validation_hostname_too_short() {
+    fn test_validation_hostname_empty_label() {
         let mut config = create_test_config();
-        config.hostname = "ab".to_string(); // Only 2 chars
+        config.hostname = "host..name".to_string(); // Empty label between dots
         let result = config.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("3-32"));
+        assert!(result.unwrap_err().to_string().contains("'..'"));
     }
 
     #[test]
     fn test_validation_hostname_too_long() {
         let mut config = create_test_config();
-        config.hostname = "a".repeat(33); // 33 chars
+        config.hostname = format!("{}.{}", "a".repeat(32), "b".repeat(32)); // 65 chars
         let result = config.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("3-32"));
+        assert!(result.unwrap_err().to_string().contains("at most 64"));
     }
 
     #[test]
     fn test_validation_hostname_special_chars() {
         let mut config = create_test_config();
-        config.hostname = "host-name".to_string(); // Contains hyphen
+        config.hostname = "host_name".to_string(); // Underscore is not allowed by RFC 1123
         let result = config.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("letters, numbers"));
+        assert!(result.unwrap_err().to_string().contains("letters, digits"));
     }
 
     #[test]
@@ -669,6 +1535,23 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("start with a letter"));
     }
 
+    #[test]
+    fn test_validation_username_reserved() {
+        let mut config = create_test_config();
+        config.username = "root".to_string();
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("reserved"));
+    }
+
+    #[test]
+    fn test_validation_accepts_hyphenated_names() {
+        let mut config = create_test_config();
+        config.hostname = "arch-desktop".to_string();
+        config.username = "jane-doe".to_string();
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_validation_empty_user_password() {
         let mut config = create_test_config();
@@ -705,6 +1588,624 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("whitespace"));
     }
 
+    #[test]
+    fn test_validation_password_min_length() {
+        let mut config = create_test_config();
+        config.password_min_length = Some(12);
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("User password"));
+
+        config.user_password = "long-enough-pass".to_string();
+        config.root_password = "also-long-enough".to_string();
+        assert!(config.validate().is_ok());
+
+        // Omitted, the TUI's default applies
+        config.password_min_length = None;
+        config.root_password = "short".to_string();
+        let result = config.validate();
+        assert!(result.unwrap_err().to_string().contains("at least 8"));
+    }
+
+    #[test]
+    fn test_password_min_length_survives_the_guided_installer() {
+        let mut config = create_test_config();
+        config.password_min_length = Some(12);
+        let tui_config = Configuration::from(&config);
+        assert_eq!(tui_config.value("Password Minimum Length"), "12");
+        assert_eq!(tui_config.password_policy().min_length, 12);
+        let saved = InstallationConfig::try_from(&tui_config).unwrap();
+        assert_eq!(saved.password_min_length, Some(12));
+
+        // The default stays out of the file
+        let saved = InstallationConfig::try_from(&Configuration::default()).unwrap();
+        assert_eq!(saved.password_min_length, None);
+    }
+
+    #[test]
+    fn test_password_min_length_optional_in_json() {
+        let config = create_test_config();
+        let json = serde_json::to_string(&config).expect("Should serialize");
+        assert!(!json.contains("password_min_length"));
+
+        let loaded: InstallationConfig = serde_json::from_str(&json).expect("Should deserialize");
+        assert_eq!(loaded.password_min_length, None);
+    }
+
+    #[test]
+    fn test_pacman_tuning_defaults_when_omitted() {
+        let config = create_test_config();
+        let mut json: serde_json::Value = serde_json::to_value(&config).unwrap();
+        let object = json.as_object_mut().unwrap();
+        for key in ["parallel_downloads", "pacman_color", "verbose_pkg_lists"] {
+            object.remove(key);
+        }
+        assert!(!object.contains_key("custom_repositories"));
+
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.parallel_downloads, 5);
+        assert_eq!(loaded.pacman_color, Toggle::Yes);
+        assert_eq!(loaded.verbose_pkg_lists, Toggle::No);
+        assert!(loaded.custom_repositories.is_empty());
+    }
+
+    #[test]
+    fn test_validation_pacman_tuning() {
+        let mut config = create_test_config();
+        config.parallel_downloads = 0;
+        assert!(config.validate().is_err());
+
+        config.parallel_downloads = 10;
+        config.custom_repositories = vec![CustomRepository {
+            name: "extra".to_string(),
+            server: "https://repo.example/$arch".to_string(),
+            key_id: None,
+            sig_level: None,
+        }];
+        let result = config.validate();
+        assert!(result.unwrap_err().to_string().contains("pacman.conf section"));
+
+        config.custom_repositories[0].name = "mine".to_string();
+        assert!(config.validate().is_ok());
+        let env_vars = config.to_env_vars();
+        assert!(env_vars.contains(&(
+            "CUSTOM_REPOSITORIES".to_string(),
+            "mine|https://repo.example/$arch".to_string()
+        )));
+
+        config.custom_repositories[0].sig_level = Some("Required Sometimes".to_string());
+        assert!(config.validate().is_err());
+        config.custom_repositories[0].sig_level = Some("Never".to_string());
+        assert!(config.to_env_vars().contains(&(
+            "CUSTOM_REPOSITORIES".to_string(),
+            "mine|https://repo.example/$arch||Never".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_package_groups() {
+        let mut config = create_test_config();
+        assert!(!serde_json::to_value(&config)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("package_groups"));
+
+        config.additional_packages = "git htop".to_string();
+        config.package_groups = vec!["development".to_string()];
+        assert!(config.validate().is_ok());
+        let env_vars = config.to_env_vars();
+        assert!(env_vars.contains(&("PACKAGE_GROUPS".to_string(), "development".to_string())));
+        let packages = env_vars
+            .iter()
+            .find(|(name, _)| name == "ADDITIONAL_PACKAGES")
+            .map(|(_, value)| value.clone())
+            .unwrap();
+        assert!(packages.starts_with("git htop base-devel"));
+        assert_eq!(packages.matches("git").count(), 1);
+
+        config.package_groups.push("bogus".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("bogus"));
+
+        config.package_groups = vec!["gaming".to_string()];
+        config.multilib = Toggle::No;
+        assert!(config.validate().unwrap_err().to_string().contains("multilib"));
+    }
+
+    #[test]
+    fn test_services() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("services");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            loaded.services.join(" "),
+            crate::services::default_services()
+        );
+
+        config.services = vec!["bluetooth.service".to_string(), "sshd.service".to_string()];
+        assert!(config.validate().is_ok());
+        assert!(config.to_env_vars().contains(&(
+            "SERVICES".to_string(),
+            "bluetooth.service sshd.service".to_string()
+        )));
+
+        config.services.clear();
+        assert!(config
+            .to_env_vars()
+            .contains(&("SERVICES".to_string(), "none".to_string())));
+
+        config.services.push("sshd".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("sshd"));
+    }
+
+    #[test]
+    fn test_initramfs() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("early_kms");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.early_kms, Toggle::No);
+        assert!(config
+            .to_env_vars()
+            .contains(&("INITRAMFS_HOOKS".to_string(), "auto".to_string())));
+
+        config.partitioning_strategy = PartitionScheme::AutoLuksLvm;
+        config.initramfs_modules = vec!["amdgpu".to_string()];
+        config.initramfs_hooks =
+            "base udev autodetect modconf kms keyboard keymap block encrypt lvm2 filesystems fsck"
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+        assert!(config.validate().is_ok());
+        assert!(config
+            .to_env_vars()
+            .contains(&("INITRAMFS_MODULES".to_string(), "amdgpu".to_string())));
+
+        config.initramfs_hooks.retain(|hook| hook != "lvm2");
+        assert!(config.validate().unwrap_err().to_string().contains("lvm2"));
+
+        config.initramfs_hooks.swap(7, 8);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("must come after"));
+    }
+
+    #[test]
+    fn test_nvidia() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("nvidia_driver");
+        json.as_object_mut().unwrap().remove("nvidia_extras");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.nvidia_driver, NvidiaDriver::Open);
+        assert_eq!(loaded.nvidia_extras, Toggle::Yes);
+
+        config.gpu_drivers = GpuDriver::Nvidia;
+        config.nvidia_driver = NvidiaDriver::Proprietary;
+        config.nvidia_extras = Toggle::No;
+        let env = config.to_env_vars();
+        assert!(env.contains(&("NVIDIA_DRIVER".to_string(), "proprietary".to_string())));
+        assert!(env.contains(&("NVIDIA_EXTRAS".to_string(), "No".to_string())));
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["nvidia_driver"],
+            "proprietary"
+        );
+    }
+
+    #[test]
+    fn test_hibernation() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("hibernation");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.hibernation, Toggle::No);
+
+        config.hibernation = Toggle::Yes;
+        config.swap = Toggle::Yes;
+        config.swap_size = "Equal to RAM".to_string();
+        assert!(config.validate().is_ok());
+        assert!(config
+            .to_env_vars()
+            .contains(&("HIBERNATION".to_string(), "Yes".to_string())));
+
+        config.initramfs_hooks = "base udev autodetect block filesystems fsck"
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("resume"));
+
+        config.initramfs_hooks.clear();
+        config.swap = Toggle::No;
+        config.additional_packages = "zram-generator".to_string();
+        assert!(config.validate().unwrap_err().to_string().contains("zram"));
+    }
+
+    #[test]
+    fn test_encrypted_boot() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("encrypted_boot");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.encrypted_boot, Toggle::No);
+
+        config.encrypted_boot = Toggle::Yes;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("encrypted strategy"));
+
+        config.partitioning_strategy = PartitionScheme::AutoLuksLvm;
+        assert!(config.validate().is_ok());
+        assert!(config
+            .to_env_vars()
+            .contains(&("ENCRYPTED_BOOT".to_string(), "Yes".to_string())));
+
+        config.bootloader = Bootloader::SystemdBoot;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("requires GRUB"));
+    }
+
+    #[test]
+    fn test_existing_os_policy() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("existing_os");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.existing_os, ExistingOsPolicy::Protect);
+
+        config.existing_os = ExistingOsPolicy::Alongside;
+        assert!(config.validate().is_ok());
+        assert!(config
+            .to_env_vars()
+            .contains(&("EXISTING_OS".to_string(), "alongside".to_string())));
+        config.partitioning_strategy = PartitionScheme::AutoRaid;
+        assert!(config.validate().unwrap_err().to_string().contains("RAID"));
+    }
+
+    #[test]
+    fn test_lvm_layout() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("lvm_volume_group");
+        json.as_object_mut().unwrap().remove("lvm_thin");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.lvm_volume_group, "arch");
+        assert_eq!(loaded.lvm_thin, Toggle::No);
+
+        // Only the LVM strategies use the layout
+        config.lvm_root_size = "huge".to_string();
+        assert!(config.validate().is_ok());
+        config.partitioning_strategy = PartitionScheme::AutoLvm;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid LVM size 'huge'"));
+
+        config.lvm_root_size = "40G".to_string();
+        config.lvm_volume_group = "vg0".to_string();
+        assert!(config.validate().is_ok());
+        assert!(config
+            .to_env_vars()
+            .contains(&("LVM_VOLUME_GROUP".to_string(), "vg0".to_string())));
+    }
+
+    #[test]
+    fn test_time_settings() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("ntp_servers").is_none());
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("time_sync_daemon");
+        json.as_object_mut().unwrap().remove("hardware_clock");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.time_sync_daemon, TimeSyncDaemon::Timesyncd);
+        assert_eq!(loaded.hardware_clock, HardwareClock::Utc);
+
+        config.time_sync_daemon = TimeSyncDaemon::Chrony;
+        config.ntp_servers = vec!["ntp.example.com".to_string(), "10.0.0.1".to_string()];
+        config.hardware_clock = HardwareClock::LocalTime;
+        assert!(config.validate().is_ok());
+        let env = config.to_env_vars();
+        for (name, value) in [
+            ("TIME_SYNC_DAEMON", "chrony"),
+            ("NTP_SERVERS", "ntp.example.com 10.0.0.1"),
+            ("HARDWARE_CLOCK", "localtime"),
+        ] {
+            assert!(
+                env.contains(&(name.to_string(), value.to_string())),
+                "{}",
+                name
+            );
+        }
+
+        config.ntp_servers.push("ntp_1".to_string());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("NTP server"));
+    }
+
+    #[test]
+    fn test_ssh_keys() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("ssh_keys").is_none());
+
+        config.ssh_keys = vec![
+            "github:alice".to_string(),
+            "/run/media/usb/id_ed25519.pub".to_string(),
+        ];
+        assert!(config.validate().is_ok());
+        assert!(config.to_env_vars().contains(&(
+            "SSH_KEYS".to_string(),
+            "github:alice /run/media/usb/id_ed25519.pub".to_string()
+        )));
+
+        config.ssh_keys.push("id_rsa.pub".to_string());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("absolute path"));
+    }
+
+    #[test]
+    fn test_proxy_settings() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("http_proxy").is_none());
+        assert!(config.proxy().is_empty());
+
+        config.http_proxy = Some("http://proxy.example.com:3128".to_string());
+        config.no_proxy = Some("localhost,.example.com".to_string());
+        assert!(config.validate().is_ok());
+        let env = config.to_env_vars();
+        assert!(env.contains(&(
+            "HTTP_PROXY".to_string(),
+            "http://proxy.example.com:3128".to_string()
+        )));
+        assert!(env.contains(&("HTTPS_PROXY".to_string(), String::new())));
+        assert_eq!(config.proxy().https(), "http://proxy.example.com:3128");
+
+        config.https_proxy = Some("proxy.example.com:3128".to_string());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("must start with http://"));
+    }
+
+    #[test]
+    fn test_package_cache() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("package_cache").is_none());
+
+        config.package_cache = Some("/run/media/usb/packages".to_string());
+        assert!(config.validate().is_ok());
+        assert!(config.to_env_vars().contains(&(
+            "PKG_CACHE".to_string(),
+            "/run/media/usb/packages".to_string()
+        )));
+
+        config.package_cache = Some("packages".to_string());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("absolute path"));
+    }
+
+    #[test]
+    fn test_power_settings() {
+        let mut config = create_test_config();
+        let mut json = serde_json::to_value(&config).unwrap();
+        json.as_object_mut().unwrap().remove("power_management");
+        json.as_object_mut().unwrap().remove("lid_switch");
+        let loaded: InstallationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.power_management, PowerManagement::None);
+        assert_eq!(loaded.lid_switch, LidSwitch::Suspend);
+
+        config.desktop_environment = DesktopEnvironment::Hyprland;
+        config.power_management = PowerManagement::Tlp;
+        config.lid_switch = LidSwitch::SuspendThenHibernate;
+        assert!(config.validate().is_ok());
+        let env = config.to_env_vars();
+        assert!(env.contains(&("POWER_MANAGEMENT".to_string(), "tlp".to_string())));
+        assert!(env.contains(&(
+            "LID_SWITCH".to_string(),
+            "suspend-then-hibernate".to_string()
+        )));
+
+        config.desktop_environment = DesktopEnvironment::Gnome;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("power-profiles-daemon"));
+    }
+
+    #[test]
+    fn test_existing_partitions_need_mounts() {
+        let mut config = create_test_config();
+        config.partitioning_strategy = PartitionScheme::Existing;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("partition_mounts"));
+
+        config.partition_mounts = Some("/dev/sda3=/home".to_string());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("No partition is assigned to /"));
+
+        config.partition_mounts = Some("/dev/sda2=/:ext4,/dev/sda3=/home".to_string());
+        assert!(config.validate().is_ok());
+        assert!(config.to_env_vars().contains(&(
+            "PARTITION_MOUNTS".to_string(),
+            "/dev/sda2=/:ext4,/dev/sda3=/home".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_var_partition_and_tmp_policy() {
+        let mut config = create_test_config();
+        config.var_partition_size = "20%FREE".to_string();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("/var partition size"));
+
+        config.var_partition_size = "20G".to_string();
+        config.tmp_policy = TmpPolicy::Tmpfs;
+        config.tmp_size = "lots".to_string();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("/tmp size"));
+
+        config.tmp_size = "4G".to_string();
+        assert!(config.validate().is_ok());
+        let env = config.to_env_vars();
+        assert!(env.contains(&("VAR_PARTITION_SIZE".to_string(), "20G".to_string())));
+        assert!(env.contains(&("TMP_POLICY".to_string(), "tmpfs".to_string())));
+        assert!(env.contains(&("TMP_SIZE".to_string(), "4G".to_string())));
+
+        let parsed: InstallationConfig =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(parsed.var_partition_size, "20G");
+        assert_eq!(parsed.tmp_policy, TmpPolicy::Tmpfs);
+    }
+
+    #[test]
+    fn test_efi_partition_reuse() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("efi_partition").is_none());
+        assert!(config
+            .to_env_vars()
+            .contains(&("EFI_PARTITION".to_string(), "create".to_string())));
+
+        config.efi_partition = Some("/dev/nvme0n1p1".to_string());
+        assert!(config.validate().is_ok());
+        let loaded: InstallationConfig =
+            serde_json::from_value(serde_json::to_value(&config).unwrap()).unwrap();
+        assert_eq!(loaded.efi_partition.as_deref(), Some("/dev/nvme0n1p1"));
+
+        config.partitioning_strategy = PartitionScheme::AutoRaid;
+        assert!(config.validate().unwrap_err().to_string().contains("RAID"));
+        config.partitioning_strategy = PartitionScheme::AutoSimple;
+        config.boot_mode = BootMode::Bios;
+        assert!(config.validate().unwrap_err().to_string().contains("UEFI"));
+    }
+
+    #[test]
+    fn test_filesystem_options_validated() {
+        let mut config = create_test_config();
+        config.btrfs_snapshots = Toggle::Yes;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("btrfs root"));
+        config.root_filesystem = Filesystem::Btrfs;
+        assert!(config.validate().is_ok());
+
+        config.btrfs_snapshots = Toggle::No;
+        config.root_filesystem = Filesystem::Bcachefs;
+        config.partitioning_strategy = PartitionScheme::AutoSimpleLuks;
+        config.encrypted_boot = Toggle::Yes;
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("GRUB can read"));
+    }
+
+    #[test]
+    fn test_custom_phases() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(!json.as_object().unwrap().contains_key("custom_phases"));
+
+        config.custom_phases = vec!["ca-certificates".to_string(), "Dotfiles".to_string()];
+        assert!(config.validate().is_err());
+
+        config.custom_phases[1] = "ca-certificates".to_string();
+        assert!(config.validate().unwrap_err().to_string().contains("twice"));
+
+        config.custom_phases[1] = "dotfiles".to_string();
+        assert!(config.validate().is_ok());
+        assert!(config.to_env_vars().contains(&(
+            "CUSTOM_PHASES".to_string(),
+            "ca-certificates dotfiles".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_notify_url() {
+        let mut config = create_test_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(!json.as_object().unwrap().contains_key("notify_url"));
+
+        config.notify_url = Some("ntfy.sh/installs".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("https://"));
+
+        config.notify_url = Some("https://ntfy.sh/installs".to_string());
+        assert!(config.validate().is_ok());
+        let reloaded = InstallationConfig::from_json(&config.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.notify_url, config.notify_url);
+    }
+
+    #[test]
+    fn test_load_template() {
+        let mut config = serde_json::to_value(create_test_config()).unwrap();
+        config["hostname"] = "lab-{{ mac_last4 }}".into();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config.to_string().as_bytes()).unwrap();
+
+        let facts = || Ok(Facts::from_pairs([("mac", "52:54:00:12:ab:cd")]));
+        let (loaded, json) = InstallationConfig::load_template(temp_file.path(), facts).unwrap();
+        assert_eq!(loaded.hostname, "lab-abcd");
+        assert!(json.unwrap().contains("\"lab-abcd\""));
+        assert!(loaded.validate().is_ok());
+
+        // Plain files are passed through without gathering facts
+        let plain = NamedTempFile::new().unwrap();
+        fs::write(
+            plain.path(),
+            serde_json::to_string(&create_test_config()).unwrap(),
+        )
+        .unwrap();
+        let gather = || -> Result<Facts> { panic!("facts gathered for a plain config") };
+        let (_, json) = InstallationConfig::load_template(plain.path(), gather).unwrap();
+        assert!(json.is_none());
+
+        let missing = || Ok(Facts::default());
+        let error = InstallationConfig::load_template(temp_file.path(), missing).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("hostname: template variable 'mac_last4'"));
+    }
+
     #[test]
     fn test_validation_git_url_invalid_scheme() {
         let mut config = create_test_config();
@@ -754,6 +2255,7 @@ mod tests {
         assert_eq!(find_var("ROOT_FILESYSTEM"), "ext4");
         assert_eq!(find_var("BOOTLOADER"), "grub");
         assert_eq!(find_var("DESKTOP_ENVIRONMENT"), "gnome");
+        assert_eq!(find_var("DESKTOP_PACKAGES"), "gnome gnome-extra");
     }
 
     #[test]
@@ -857,8 +2359,8 @@ mod tests {
         config.install_disk = "/dev/sda".to_string();
         config.hostname = "testhost".to_string();
         config.username = "testuser".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
         config.additional_packages = String::new();
         config.additional_aur_packages = String::new();
 
@@ -877,8 +2379,8 @@ mod tests {
         config.install_disk = "/dev/sda".to_string();
         config.hostname = "   ".to_string(); // Whitespace only
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         assert!(config.validate().is_err(), "Whitespace-only hostname should be invalid");
     }
@@ -889,8 +2391,8 @@ mod tests {
         config.install_disk = "/dev/sda".to_string();
         config.hostname = "host".to_string();
         config.username = "\t\n".to_string(); // Whitespace only
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         assert!(config.validate().is_err(), "Whitespace-only username should be invalid");
     }
@@ -899,13 +2401,13 @@ mod tests {
     fn test_very_long_hostname_invalid() {
         let mut config = InstallationConfig::default();
         config.install_disk = "/dev/sda".to_string();
-        // Hostnames longer than 32 chars are invalid per implementation
-        config.hostname = "a".repeat(33);
+        // Hostnames longer than 64 chars (HOST_NAME_MAX) are invalid
+        config.hostname = format!("{}.{}", "a".repeat(40), "b".repeat(24));
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
-        assert!(config.validate().is_err(), "Hostname > 32 chars should be invalid");
+        assert!(config.validate().is_err(), "Hostname > 64 chars should be invalid");
     }
 
     #[test]
@@ -915,8 +2417,8 @@ mod tests {
         // 32 chars is the maximum valid hostname length per implementation
         config.hostname = "a".repeat(32);
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         assert!(config.validate().is_ok(), "Hostname of 32 chars should be valid");
     }
@@ -927,11 +2429,11 @@ mod tests {
         config.install_disk = "/dev/sda1".to_string(); // Partition, not disk
         config.hostname = "host".to_string();
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
-        // This should be valid - validation just checks it starts with /dev/
-        assert!(config.validate().is_ok());
+        // A partition is rejected, as the TUI's Disk option does
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -940,8 +2442,8 @@ mod tests {
         config.install_disk = "/dev/nvme0n1".to_string();
         config.hostname = "host".to_string();
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         assert!(config.validate().is_ok(), "NVMe disk path should be valid");
     }
@@ -994,8 +2496,8 @@ mod tests {
         config.install_disk = "/dev/sda".to_string();
         config.hostname = "host".to_string();
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         // Serialize twice, should produce identical output
         let json1 = serde_json::to_string(&config).expect("Should serialize");
@@ -1010,8 +2512,8 @@ mod tests {
         config.install_disk = "/dev/sda".to_string();
         config.hostname = "host".to_string();
         config.username = "user".to_string();
-        config.user_password = "pass".to_string();
-        config.root_password = "root".to_string();
+        config.user_password = "password123".to_string();
+        config.root_password = "rootpass".to_string();
 
         // Pretty print JSON
         let pretty_json =
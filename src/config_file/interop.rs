@@ -0,0 +1,976 @@
+//! Interoperability with the official archinstall (Python) installer
+//!
+//! archinstall reads its settings from `user_configuration.json` and the
+//! passwords from `user_credentials.json`. [`export`] writes both from an
+//! [`InstallationConfig`], and [`import`] reads them back as well as it can.
+//! The two installers do not cover the same ground: options one of them has
+//! no equivalent for are reported as warnings instead of being dropped
+//! silently, and should be reviewed before installing.
+//!
+//! The files follow archinstall 2.8; the credentials written by 3.x, which
+//! only holds password hashes, are recognised when importing.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use strum::IntoEnumIterator;
+
+use super::InstallationConfig;
+use crate::types::{
+    AudioServer, AutoToggle, BootMode, Bootloader, DesktopEnvironment, DisplayManager, Filesystem,
+    GpuDriver, GuestTools, Kernel, NvidiaDriver, PartitionScheme, Toggle,
+};
+
+/// archinstall release whose file format is written
+pub const ARCHINSTALL_VERSION: &str = "2.8.6";
+
+/// File name archinstall reads its settings from (`--config`)
+pub const CONFIGURATION_FILE: &str = "user_configuration.json";
+
+/// File name archinstall reads the passwords from (`--creds`)
+pub const CREDENTIALS_FILE: &str = "user_credentials.json";
+
+/// Size of the EFI system partition in the exported layout
+const ESP_SIZE_MIB: u64 = 1024;
+
+/// Btrfs subvolumes of the exported layout, as the install engine creates them
+const BTRFS_SUBVOLUMES: &[(&str, &str)] = &[
+    ("@", "/"),
+    ("@home", "/home"),
+    ("@log", "/var/log"),
+    ("@pkg", "/var/cache/pacman/pkg"),
+];
+
+/// Settings of `user_configuration.json` that are imported
+const IMPORTED_KEYS: &[&str] = &[
+    "additional-repositories",
+    "audio_config",
+    "bootloader",
+    "disk_config",
+    "hostname",
+    "kernels",
+    "locale_config",
+    "mirror_config",
+    "ntp",
+    "packages",
+    "parallel downloads",
+    "profile_config",
+    "services",
+    "swap",
+    "timezone",
+    "uki",
+];
+
+/// Settings with nothing to carry over, ignored without a warning
+const IGNORED_KEYS: &[&str] = &[
+    "archinstall-language",
+    "config_version",
+    "debug",
+    "dry_run",
+    "network_config",
+    "no_pkg_lookups",
+    "offline",
+    "save_config",
+    "script",
+    "silent",
+    "skip_ntp",
+    "version",
+];
+
+/// An archinstall configuration made from an [`InstallationConfig`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exported {
+    /// Contents of `user_configuration.json`
+    pub configuration: Value,
+    /// Contents of `user_credentials.json`
+    pub credentials: Value,
+    /// Options archinstall has no equivalent for, for the user to review
+    pub warnings: Vec<String>,
+}
+
+/// An [`InstallationConfig`] read from an archinstall configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imported {
+    pub config: InstallationConfig,
+    /// Settings that could not be carried over, for the user to review
+    pub warnings: Vec<String>,
+}
+
+/// Convert `config` to archinstall's configuration and credentials
+pub fn export(config: &InstallationConfig) -> Exported {
+    let mut warnings = Vec::new();
+
+    let mut configuration = Map::new();
+    configuration.insert("version".into(), json!(ARCHINSTALL_VERSION));
+    configuration.insert("archinstall-language".into(), json!("English"));
+    configuration.insert("hostname".into(), json!(config.hostname));
+    configuration.insert("kernels".into(), json!([config.kernel.to_string()]));
+    let bootloader = match config.bootloader {
+        Bootloader::Grub => "Grub",
+        Bootloader::SystemdBoot => "Systemd-boot",
+        Bootloader::UBoot => {
+            warnings.push(
+                "Bootloader u-boot: archinstall only installs x86_64, using Grub".to_string(),
+            );
+            "Grub"
+        }
+    };
+    configuration.insert("bootloader".into(), json!(bootloader));
+    configuration.insert("uki".into(), json!(false));
+    configuration.insert("timezone".into(), json!(timezone(config)));
+    let (language, encoding) = config
+        .locale
+        .split_once('.')
+        .unwrap_or((config.locale.as_str(), "UTF-8"));
+    configuration.insert(
+        "locale_config".into(),
+        json!({ "kb_layout": config.keymap, "sys_lang": language, "sys_enc": encoding }),
+    );
+    configuration.insert("ntp".into(), json!(config.time_sync == Toggle::Yes));
+    configuration.insert(
+        "mirror_config".into(),
+        json!({ "custom_mirrors": [], "mirror_regions": mirror_regions(&config.mirror_country) }),
+    );
+    configuration.insert(
+        "network_config".into(),
+        json!({ "type": if has_service(config, "NetworkManager") { "nm" } else { "iso" } }),
+    );
+    configuration.insert(
+        "audio_config".into(),
+        match config.audio {
+            AudioServer::None => Value::Null,
+            audio => json!({ "audio": audio.to_string() }),
+        },
+    );
+    configuration.insert(
+        "packages".into(),
+        json!(config
+            .additional_packages
+            .split_whitespace()
+            .collect::<Vec<_>>()),
+    );
+    let repositories: Vec<&str> = (config.multilib == Toggle::Yes)
+        .then_some("multilib")
+        .into_iter()
+        .collect();
+    configuration.insert("additional-repositories".into(), json!(repositories));
+    configuration.insert(
+        "parallel downloads".into(),
+        json!(config.parallel_downloads),
+    );
+    configuration.insert("services".into(), json!(config.services));
+    configuration.insert("profile_config".into(), profile(config));
+    configuration.insert("disk_config".into(), disk_layout(config, &mut warnings));
+
+    // archinstall's swap is zram, sized by itself
+    configuration.insert("swap".into(), json!(config.swap == Toggle::Yes));
+    if config.swap == Toggle::Yes {
+        warnings.push(format!(
+            "Swap: archinstall uses zram instead of a swap partition ({})",
+            config.swap_size
+        ));
+    }
+
+    let mut users = Vec::new();
+    if !config.username.is_empty() {
+        users.push(json!({
+            "username": config.username,
+            "!password": config.user_password,
+            "sudo": true,
+        }));
+    }
+    let credentials = json!({
+        "!root-password": config.root_password,
+        "!users": users,
+    });
+
+    warnings.extend(unsupported(config));
+    Exported {
+        configuration: Value::Object(configuration),
+        credentials,
+        warnings,
+    }
+}
+
+/// Read archinstall's configuration, and optionally its credentials
+///
+/// Both are JSON text. Credentials may also be part of the configuration, as
+/// older archinstall releases saved them.
+pub fn import(configuration: &str, credentials: Option<&str>) -> Result<Imported> {
+    let configuration: Value =
+        serde_json::from_str(configuration).context("Failed to parse archinstall configuration")?;
+    let Value::Object(mut settings) = configuration else {
+        anyhow::bail!("archinstall configuration is not a JSON object");
+    };
+    if let Some(credentials) = credentials {
+        let credentials: Value =
+            serde_json::from_str(credentials).context("Failed to parse archinstall credentials")?;
+        let Value::Object(credentials) = credentials else {
+            anyhow::bail!("archinstall credentials are not a JSON object");
+        };
+        settings.extend(credentials);
+    }
+
+    let mut config = InstallationConfig::default();
+    let mut warnings = Vec::new();
+
+    if let Some(hostname) = settings.get("hostname").and_then(Value::as_str) {
+        config.hostname = hostname.to_string();
+    }
+    import_kernels(&settings, &mut config, &mut warnings);
+    import_bootloader(&settings, &mut config, &mut warnings);
+    if let Some(timezone) = settings.get("timezone").and_then(Value::as_str) {
+        match timezone.split_once('/') {
+            Some((region, city)) => {
+                config.timezone_region = region.to_string();
+                config.timezone = city.to_string();
+            }
+            None => {
+                config.timezone_region = timezone.to_string();
+                config.timezone = String::new();
+            }
+        }
+    }
+    if let Some(locale) = settings.get("locale_config") {
+        if let Some(layout) = locale.get("kb_layout").and_then(Value::as_str) {
+            config.keymap = layout.to_string();
+        }
+        if let Some(language) = locale.get("sys_lang").and_then(Value::as_str) {
+            let encoding = locale
+                .get("sys_enc")
+                .and_then(Value::as_str)
+                .unwrap_or("UTF-8");
+            config.locale = format!("{}.{}", language, encoding);
+        }
+    }
+    if let Some(ntp) = settings.get("ntp").and_then(Value::as_bool) {
+        config.time_sync = toggle(ntp);
+    }
+    if let Some(swap) = settings.get("swap").and_then(Value::as_bool) {
+        config.swap = toggle(swap);
+        if swap {
+            warnings.push(format!(
+                "Swap: a swap partition ({}) is created instead of archinstall's zram",
+                config.swap_size
+            ));
+        }
+    }
+    import_mirrors(&settings, &mut config, &mut warnings);
+    import_audio(&settings, &mut config, &mut warnings);
+    if let Some(packages) = settings.get("packages").and_then(Value::as_array) {
+        config.additional_packages = strings(packages).join(" ");
+    }
+    if let Some(repositories) = settings
+        .get("additional-repositories")
+        .and_then(Value::as_array)
+    {
+        let repositories = strings(repositories);
+        config.multilib = toggle(repositories.contains(&"multilib"));
+        for repository in repositories.iter().filter(|r| **r != "multilib") {
+            warnings.push(format!(
+                "Repository '{}' is not known here; add it as a custom repository",
+                repository
+            ));
+        }
+    }
+    if let Some(downloads) = settings.get("parallel downloads").and_then(Value::as_u64) {
+        // archinstall's 0 means pacman's default
+        if downloads > 0 {
+            config.parallel_downloads = u8::try_from(downloads).unwrap_or(u8::MAX);
+        }
+    }
+    if let Some(services) = settings.get("services").and_then(Value::as_array) {
+        for service in strings(services) {
+            if !config.services.iter().any(|s| same_unit(s, service)) {
+                config.services.push(service.to_string());
+            }
+        }
+    }
+    import_profile(&settings, &mut config, &mut warnings);
+    import_disk_layout(&settings, &mut config, &mut warnings);
+    import_credentials(&settings, &mut config, &mut warnings);
+
+    for key in settings.keys() {
+        let known = IMPORTED_KEYS.contains(&key.as_str())
+            || IGNORED_KEYS.contains(&key.as_str())
+            || is_credential(key);
+        if !known {
+            warnings.push(format!("'{}' is not supported and was ignored", key));
+        }
+    }
+
+    Ok(Imported { config, warnings })
+}
+
+/// archinstall's time zone, e.g. "America/New_York"
+fn timezone(config: &InstallationConfig) -> String {
+    if config.timezone.is_empty() {
+        config.timezone_region.clone()
+    } else {
+        format!("{}/{}", config.timezone_region, config.timezone)
+    }
+}
+
+/// archinstall's mirror regions, one per country of `MIRROR_COUNTRY`
+fn mirror_regions(countries: &str) -> Map<String, Value> {
+    crate::input::split_multi_select(countries)
+        .into_iter()
+        .map(|country| (country.to_string(), json!([])))
+        .collect()
+}
+
+/// Whether `config` enables the systemd unit `name` (with or without suffix)
+fn has_service(config: &InstallationConfig, name: &str) -> bool {
+    config
+        .services
+        .iter()
+        .any(|service| same_unit(service, name))
+}
+
+/// Whether two unit names are the same, ".service" being optional
+fn same_unit(a: &str, b: &str) -> bool {
+    a.trim_end_matches(".service") == b.trim_end_matches(".service")
+}
+
+fn toggle(on: bool) -> Toggle {
+    if on {
+        Toggle::Yes
+    } else {
+        Toggle::No
+    }
+}
+
+fn strings(values: &[Value]) -> Vec<&str> {
+    values.iter().filter_map(Value::as_str).collect()
+}
+
+/// Whether `key` belongs in `user_credentials.json`
+fn is_credential(key: &str) -> bool {
+    key.starts_with('!') || matches!(key, "root_enc_password" | "users" | "encryption_password")
+}
+
+/// archinstall's names of the desktop profiles
+fn desktop_profile(desktop: DesktopEnvironment) -> Option<&'static str> {
+    match desktop {
+        DesktopEnvironment::None => None,
+        DesktopEnvironment::Gnome => Some("GNOME"),
+        DesktopEnvironment::Kde => Some("KDE Plasma"),
+        DesktopEnvironment::Xfce => Some("Xfce4"),
+        DesktopEnvironment::Cinnamon => Some("Cinnamon"),
+        DesktopEnvironment::Mate => Some("Mate"),
+        DesktopEnvironment::Budgie => Some("Budgie"),
+        DesktopEnvironment::Hyprland => Some("Hyprland"),
+        DesktopEnvironment::Sway => Some("Sway"),
+        DesktopEnvironment::I3 => Some("i3-wm"),
+    }
+}
+
+/// archinstall's names of the display managers ("greeters")
+fn greeter(display_manager: DisplayManager) -> Option<&'static str> {
+    match display_manager {
+        DisplayManager::None => None,
+        DisplayManager::Gdm => Some("gdm"),
+        DisplayManager::Sddm => Some("sddm"),
+        DisplayManager::Lightdm => Some("lightdm-gtk-greeter"),
+    }
+}
+
+/// archinstall's names of the graphics drivers
+fn graphics_driver(driver: GpuDriver, nvidia: NvidiaDriver) -> &'static str {
+    match (driver, nvidia) {
+        (GpuDriver::Auto, _) => "All open-source",
+        (GpuDriver::Nvidia, NvidiaDriver::Open) => {
+            "Nvidia (open kernel module for newer GPUs, Turing+)"
+        }
+        (GpuDriver::Nvidia, NvidiaDriver::Proprietary) => "Nvidia (proprietary)",
+        (GpuDriver::Amd, _) => "AMD / ATI (open-source)",
+        (GpuDriver::Intel, _) => "Intel (open-source)",
+    }
+}
+
+/// `profile_config`: the desktop, its greeter and the graphics driver
+fn profile(config: &InstallationConfig) -> Value {
+    let Some(desktop) = desktop_profile(config.desktop_environment) else {
+        return Value::Null;
+    };
+    json!({
+        "gfx_driver": graphics_driver(config.gpu_drivers, config.nvidia_driver),
+        "greeter": greeter(config.display_manager),
+        "profile": {
+            "main": "Desktop",
+            "details": [desktop],
+            "custom_settings": { desktop: {} },
+        },
+    })
+}
+
+/// A size or offset of archinstall's disk layout
+fn size(value: u64, unit: &str) -> Value {
+    json!({ "value": value, "unit": unit, "sector_size": null })
+}
+
+/// `disk_config`: the install disk with an ESP and a root partition
+///
+/// archinstall describes every partition explicitly, so only the plain
+/// layout is written; LVM, RAID and a separate /home are left to be set up
+/// in archinstall.
+fn disk_layout(config: &InstallationConfig, warnings: &mut Vec<String>) -> Value {
+    if config.partitioning_strategy == PartitionScheme::Manual {
+        return json!({ "config_type": "pre_mounted_config", "mountpoint": "/mnt" });
+    }
+    if config.partitioning_strategy == PartitionScheme::Existing {
+        warnings.push(
+            "Partition Mounts: archinstall cannot reuse partitions this way, it will ask for \
+             the disk layout"
+                .to_string(),
+        );
+        return Value::Null;
+    }
+    if config.install_disk.is_empty() {
+        warnings.push("Disk: no install disk is set, archinstall will ask for one".to_string());
+        return Value::Null;
+    }
+
+    match config.partitioning_strategy {
+        PartitionScheme::AutoSimple | PartitionScheme::AutoSimpleLuks => {}
+        scheme => warnings.push(format!(
+            "Partitioning: {} is exported as a plain ESP + root layout",
+            scheme
+        )),
+    }
+    if config.separate_home == Toggle::Yes {
+        warnings.push("Separate home partition: not part of the exported layout".to_string());
+    }
+
+    let mut partitions = Vec::new();
+    let mut start = 1;
+    if config.boot_mode != BootMode::Bios {
+        partitions.push(json!({
+            "obj_id": "efi",
+            "status": "create",
+            "type": "primary",
+            "start": size(start, "MiB"),
+            "size": size(ESP_SIZE_MIB, "MiB"),
+            "fs_type": "fat32",
+            "mountpoint": "/boot",
+            "mount_options": [],
+            "flags": ["Boot", "ESP"],
+            "btrfs": [],
+        }));
+        start += ESP_SIZE_MIB;
+    }
+    let btrfs = config.root_filesystem == Filesystem::Btrfs;
+    let subvolumes: Vec<Value> = if btrfs {
+        BTRFS_SUBVOLUMES
+            .iter()
+            .map(|(name, mountpoint)| json!({ "name": name, "mountpoint": mountpoint }))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    partitions.push(json!({
+        "obj_id": "root",
+        "status": "create",
+        "type": "primary",
+        "start": size(start, "MiB"),
+        "size": size(100, "Percent"),
+        "fs_type": config.root_filesystem.to_string(),
+        // Btrfs mounts its subvolumes instead of the partition
+        "mountpoint": if btrfs { Value::Null } else { json!("/") },
+        "mount_options": if btrfs { json!(["compress=zstd"]) } else { json!([]) },
+        "flags": if config.boot_mode == BootMode::Bios { json!(["Boot"]) } else { json!([]) },
+        "btrfs": subvolumes,
+    }));
+
+    let mut disk_config = json!({
+        "config_type": "default_layout",
+        "device_modifications": [{
+            "device": config.install_disk,
+            "wipe": true,
+            "partitions": partitions,
+        }],
+    });
+    let encrypted = config.encryption == AutoToggle::Yes
+        || matches!(
+            config.partitioning_strategy,
+            PartitionScheme::AutoSimpleLuks
+                | PartitionScheme::AutoLuksLvm
+                | PartitionScheme::AutoRaidLuks
+                | PartitionScheme::AutoRaidLvmLuks
+        );
+    if encrypted {
+        disk_config["disk_encryption"] = json!({
+            "encryption_type": "luks",
+            "partitions": ["root"],
+        });
+        warnings.push(
+            "Encryption: archinstall asks for the encryption password, which is not exported"
+                .to_string(),
+        );
+    }
+    disk_config
+}
+
+/// Options archinstall has no equivalent for, when they are not the default
+fn unsupported(config: &InstallationConfig) -> Vec<String> {
+    let default = InstallationConfig::default();
+    let mut warnings = Vec::new();
+    let mut check = |set: bool, option: &str| {
+        if set {
+            warnings.push(format!("{}: not supported by archinstall", option));
+        }
+    };
+    check(config.secure_boot == Toggle::Yes, "Secure Boot");
+    check(config.encrypted_boot == Toggle::Yes, "Encrypted Boot");
+    check(config.efi_partition.is_some(), "EFI Partition");
+    check(config.existing_os != default.existing_os, "Existing OS");
+    check(config.hibernation == Toggle::Yes, "Hibernation");
+    check(config.btrfs_snapshots == Toggle::Yes, "Btrfs Snapshots");
+    check(config.btrfs_assistant == Toggle::Yes, "Btrfs Assistant");
+    check(
+        config.time_sync_daemon != default.time_sync_daemon,
+        "Time Sync Daemon",
+    );
+    check(!config.ntp_servers.is_empty(), "NTP Servers");
+    check(
+        config.hardware_clock != default.hardware_clock,
+        "Hardware Clock",
+    );
+    check(
+        config.http_proxy.is_some() || config.https_proxy.is_some(),
+        "Proxy",
+    );
+    check(config.package_cache.is_some(), "Package Cache");
+    check(!config.ssh_keys.is_empty(), "SSH Keys");
+    check(
+        config.password_min_length.is_some(),
+        "Password Minimum Length",
+    );
+    check(config.vm_guest_tools != GuestTools::None, "VM Guest Tools");
+    // archinstall installs the microcode of the CPU it runs on, like auto
+    check(config.microcode != default.microcode, "CPU Microcode");
+    check(config.pacman_color != default.pacman_color, "Pacman Color");
+    check(
+        config.verbose_pkg_lists == Toggle::Yes,
+        "Verbose Package Lists",
+    );
+    check(
+        !config.custom_repositories.is_empty(),
+        "Custom Repositories",
+    );
+    check(!config.package_groups.is_empty(), "Package Groups");
+    check(
+        !config.additional_aur_packages.trim().is_empty(),
+        "AUR Packages",
+    );
+    check(config.flatpak == Toggle::Yes, "Flatpak");
+    check(
+        config.bootloader == Bootloader::Grub && config.grub_themes == Toggle::Yes,
+        "GRUB Themes",
+    );
+    check(config.early_kms == Toggle::Yes, "Early KMS");
+    check(
+        !config.initramfs_modules.is_empty() || !config.initramfs_hooks.is_empty(),
+        "Initramfs",
+    );
+    check(
+        config.power_management != default.power_management,
+        "Power Management",
+    );
+    check(config.lid_switch != default.lid_switch, "Lid Switch");
+    check(config.plymouth == Toggle::Yes, "Plymouth");
+    check(config.git_repository == Toggle::Yes, "Git Repository");
+    check(!config.custom_phases.is_empty(), "Custom Phases");
+    check(config.notify_url.is_some(), "Notify URL");
+    warnings
+}
+
+fn import_kernels(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    let Some(kernels) = settings.get("kernels").and_then(Value::as_array) else {
+        return;
+    };
+    let kernels = strings(kernels);
+    match kernels.first().map(|kernel| kernel.parse::<Kernel>()) {
+        Some(Ok(kernel)) => config.kernel = kernel,
+        Some(Err(_)) => warnings.push(format!(
+            "Kernel '{}' is not supported, using {}",
+            kernels[0], config.kernel
+        )),
+        None => {}
+    }
+    for kernel in kernels.iter().skip(1) {
+        warnings.push(format!("Kernel '{}': only one kernel is installed", kernel));
+    }
+}
+
+fn import_bootloader(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    match settings.get("bootloader").and_then(Value::as_str) {
+        Some("Grub") => config.bootloader = Bootloader::Grub,
+        Some("Systemd-boot") => config.bootloader = Bootloader::SystemdBoot,
+        Some(other) => warnings.push(format!(
+            "Bootloader '{}' is not supported, using {}",
+            other, config.bootloader
+        )),
+        None => {}
+    }
+    if settings.get("uki").and_then(Value::as_bool) == Some(true) {
+        warnings.push("Unified kernel images: not supported".to_string());
+    }
+}
+
+fn import_mirrors(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    let Some(mirrors) = settings.get("mirror_config") else {
+        return;
+    };
+    if let Some(regions) = mirrors.get("mirror_regions").and_then(Value::as_object) {
+        if !regions.is_empty() {
+            let countries = regions.keys().map(String::as_str);
+            config.mirror_country = crate::input::join_multi_select(countries);
+        }
+    }
+    let custom = mirrors
+        .get("custom_mirrors")
+        .and_then(Value::as_array)
+        .is_some_and(|custom| !custom.is_empty());
+    if custom {
+        warnings.push("Custom mirrors: not supported".to_string());
+    }
+}
+
+fn import_audio(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    match settings.get("audio_config") {
+        Some(Value::Null) => config.audio = AudioServer::None,
+        Some(audio) => {
+            let server = audio
+                .get("audio")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            match server.to_lowercase().parse::<AudioServer>() {
+                Ok(server) => config.audio = server,
+                Err(_) => warnings.push(format!(
+                    "Audio server '{}' is not supported, using {}",
+                    server, config.audio
+                )),
+            }
+        }
+        None => {}
+    }
+}
+
+fn import_profile(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    let Some(profile_config) = settings.get("profile_config").filter(|p| !p.is_null()) else {
+        return;
+    };
+    let profile = &profile_config["profile"];
+    let details = profile["details"]
+        .as_array()
+        .map(|d| strings(d))
+        .unwrap_or_default();
+    match profile["main"].as_str() {
+        Some("Desktop") => {
+            for (i, name) in details.iter().enumerate() {
+                let desktop = [
+                    DesktopEnvironment::Gnome,
+                    DesktopEnvironment::Kde,
+                    DesktopEnvironment::Xfce,
+                    DesktopEnvironment::Cinnamon,
+                    DesktopEnvironment::Mate,
+                    DesktopEnvironment::Hyprland,
+                    DesktopEnvironment::Sway,
+                    DesktopEnvironment::I3,
+                ]
+                .into_iter()
+                .find(|desktop| desktop_profile(*desktop) == Some(*name));
+                match desktop {
+                    Some(desktop) if i == 0 => config.desktop_environment = desktop,
+                    Some(_) => {
+                        warnings.push(format!("Desktop '{}': only one desktop is installed", name))
+                    }
+                    None => warnings.push(format!("Desktop '{}' is not supported", name)),
+                }
+            }
+        }
+        Some("Minimal") | None => {}
+        Some(other) => warnings.push(format!("Profile '{}' is not supported", other)),
+    }
+
+    if let Some(name) = profile_config["greeter"].as_str() {
+        match name {
+            "gdm" => config.display_manager = DisplayManager::Gdm,
+            "sddm" => config.display_manager = DisplayManager::Sddm,
+            // lightdm-gtk-greeter and lightdm-slick-greeter
+            name if name.starts_with("lightdm") => config.display_manager = DisplayManager::Lightdm,
+            name => warnings.push(format!("Greeter '{}' is not supported", name)),
+        }
+    }
+    if let Some(name) = profile_config["gfx_driver"].as_str() {
+        let driver = [
+            GpuDriver::Auto,
+            GpuDriver::Nvidia,
+            GpuDriver::Amd,
+            GpuDriver::Intel,
+        ]
+        .into_iter()
+        .flat_map(|driver| NvidiaDriver::iter().map(move |nvidia| (driver, nvidia)))
+        .find(|(driver, nvidia)| graphics_driver(*driver, *nvidia) == name);
+        match driver {
+            Some((driver, nvidia)) => {
+                config.gpu_drivers = driver;
+                if driver == GpuDriver::Nvidia {
+                    config.nvidia_driver = nvidia;
+                }
+            }
+            None => warnings.push(format!(
+                "Graphics driver '{}' is not supported, using {}",
+                name, config.gpu_drivers
+            )),
+        }
+    }
+}
+
+fn import_disk_layout(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    let Some(disk_config) = settings.get("disk_config").filter(|d| !d.is_null()) else {
+        return;
+    };
+    if disk_config["config_type"].as_str() == Some("pre_mounted_config") {
+        config.partitioning_strategy = PartitionScheme::Manual;
+        return;
+    }
+
+    let devices = disk_config["device_modifications"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if let Some(device) = devices.first() {
+        if let Some(path) = device["device"].as_str() {
+            config.install_disk = path.to_string();
+        }
+        let partitions = device["partitions"].as_array().cloned().unwrap_or_default();
+        let mounted_at = |mountpoint: &str| {
+            partitions.iter().find(|partition| {
+                partition["mountpoint"].as_str() == Some(mountpoint)
+                    || partition["btrfs"].as_array().is_some_and(|subvolumes| {
+                        subvolumes
+                            .iter()
+                            .any(|subvolume| subvolume["mountpoint"].as_str() == Some(mountpoint))
+                    })
+            })
+        };
+        if let Some(root) = mounted_at("/") {
+            let fs_type = root["fs_type"].as_str().unwrap_or_default();
+            match fs_type.parse::<Filesystem>() {
+                Ok(filesystem) => config.root_filesystem = filesystem,
+                Err(_) => warnings.push(format!(
+                    "Root filesystem '{}' is not supported, using {}",
+                    fs_type, config.root_filesystem
+                )),
+            }
+            if let Some(home) = mounted_at("/home").filter(|home| home != &root) {
+                config.separate_home = Toggle::Yes;
+                if let Ok(filesystem) = home["fs_type"].as_str().unwrap_or_default().parse() {
+                    config.home_filesystem = filesystem;
+                }
+            }
+        }
+        warnings.push(
+            "Disk layout: partition sizes are not imported, the install engine's layout is used"
+                .to_string(),
+        );
+    }
+    for device in devices.iter().skip(1) {
+        warnings.push(format!(
+            "Disk {}: only one disk is installed to",
+            device["device"].as_str().unwrap_or("?")
+        ));
+    }
+
+    let lvm = !disk_config["lvm_config"].is_null();
+    let encrypted = !disk_config["disk_encryption"].is_null();
+    config.encryption = if encrypted {
+        AutoToggle::Yes
+    } else {
+        AutoToggle::No
+    };
+    config.partitioning_strategy = match (lvm, encrypted) {
+        (false, false) => PartitionScheme::AutoSimple,
+        (false, true) => PartitionScheme::AutoSimpleLuks,
+        (true, false) => PartitionScheme::AutoLvm,
+        (true, true) => PartitionScheme::AutoLuksLvm,
+    };
+}
+
+fn import_credentials(
+    settings: &Map<String, Value>,
+    config: &mut InstallationConfig,
+    warnings: &mut Vec<String>,
+) {
+    if let Some(password) = settings.get("!root-password").and_then(Value::as_str) {
+        config.root_password = password.to_string();
+    } else if settings.contains_key("root_enc_password") {
+        warnings.push("Root password: only a hash is stored, set the password again".to_string());
+    }
+
+    let users = settings
+        .get("!users")
+        .or_else(|| settings.get("users"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if let Some(user) = users.first() {
+        if let Some(username) = user["username"].as_str() {
+            config.username = username.to_string();
+        }
+        if let Some(password) = user["!password"].as_str() {
+            config.user_password = password.to_string();
+        } else if !user["enc_password"].is_null() {
+            warnings.push(format!(
+                "User '{}': only a password hash is stored, set the password again",
+                config.username
+            ));
+        }
+    }
+    for user in users.iter().skip(1) {
+        warnings.push(format!(
+            "User '{}': only one user account is created",
+            user["username"].as_str().unwrap_or("?")
+        ));
+    }
+    if settings.contains_key("!encryption-password") || settings.contains_key("encryption_password")
+    {
+        warnings.push("Encryption password: asked for when the installation starts".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> InstallationConfig {
+        InstallationConfig {
+            install_disk: "/dev/sda".to_string(),
+            hostname: "archtest".to_string(),
+            username: "testuser".to_string(),
+            user_password: "password123".to_string(),
+            root_password: "rootpass".to_string(),
+            root_filesystem: Filesystem::Btrfs,
+            kernel: Kernel::LinuxLts,
+            gpu_drivers: GpuDriver::Nvidia,
+            nvidia_driver: NvidiaDriver::Proprietary,
+            bootloader: Bootloader::SystemdBoot,
+            desktop_environment: DesktopEnvironment::Kde,
+            display_manager: DisplayManager::Sddm,
+            mirror_country: "France,Germany".to_string(),
+            additional_packages: "vim git".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_export_writes_archinstall_settings() {
+        let exported = export(&config());
+        let configuration = &exported.configuration;
+        assert_eq!(configuration["hostname"], "archtest");
+        assert_eq!(configuration["kernels"], json!(["linux-lts"]));
+        assert_eq!(configuration["bootloader"], "Systemd-boot");
+        assert_eq!(configuration["timezone"], "America/New_York");
+        assert_eq!(configuration["locale_config"]["sys_lang"], "en_US");
+        assert_eq!(configuration["packages"], json!(["vim", "git"]));
+        assert_eq!(
+            configuration["mirror_config"]["mirror_regions"],
+            json!({ "France": [], "Germany": [] })
+        );
+        assert_eq!(
+            configuration["profile_config"]["profile"]["details"],
+            json!(["KDE Plasma"])
+        );
+        assert_eq!(
+            configuration["profile_config"]["gfx_driver"],
+            "Nvidia (proprietary)"
+        );
+        let partitions = &configuration["disk_config"]["device_modifications"][0]["partitions"];
+        assert_eq!(partitions[0]["fs_type"], "fat32");
+        assert_eq!(partitions[1]["btrfs"][0]["mountpoint"], "/");
+        assert_eq!(exported.credentials["!users"][0]["username"], "testuser");
+        // The defaults include Plymouth, which archinstall lacks
+        assert!(exported.warnings.iter().any(|w| w.starts_with("Plymouth")));
+        assert!(exported.warnings.iter().any(|w| w.starts_with("Swap")));
+    }
+
+    #[test]
+    fn test_import_reads_exported_configuration() {
+        let original = config();
+        let exported = export(&original);
+        let imported = import(
+            &exported.configuration.to_string(),
+            Some(&exported.credentials.to_string()),
+        )
+        .unwrap();
+        let config = imported.config;
+        assert_eq!(config.hostname, original.hostname);
+        assert_eq!(config.kernel, original.kernel);
+        assert_eq!(config.bootloader, original.bootloader);
+        assert_eq!(config.install_disk, original.install_disk);
+        assert_eq!(config.root_filesystem, original.root_filesystem);
+        assert_eq!(config.desktop_environment, original.desktop_environment);
+        assert_eq!(config.display_manager, original.display_manager);
+        assert_eq!(config.gpu_drivers, original.gpu_drivers);
+        assert_eq!(config.nvidia_driver, original.nvidia_driver);
+        assert_eq!(config.locale, original.locale);
+        assert_eq!(config.mirror_country, original.mirror_country);
+        assert_eq!(config.user_password, original.user_password);
+        assert_eq!(config.root_password, original.root_password);
+        assert_eq!(config.additional_packages, original.additional_packages);
+        assert_eq!(config.services, original.services);
+    }
+
+    #[test]
+    fn test_import_warns_about_unsupported_settings() {
+        let configuration = r#"{
+            "kernels": ["linux", "linux-zen"],
+            "bootloader": "Limine",
+            "disk_config": {
+                "config_type": "default_layout",
+                "device_modifications": [],
+                "lvm_config": {"config_type": "default"},
+                "disk_encryption": {"encryption_type": "luks"}
+            },
+            "custom_commands": ["echo hi"]
+        }"#;
+        let credentials = r#"{"root_enc_password": "$y$j9T$...", "users": []}"#;
+        let imported = import(configuration, Some(credentials)).unwrap();
+        assert_eq!(
+            imported.config.partitioning_strategy,
+            PartitionScheme::AutoLuksLvm
+        );
+        assert_eq!(imported.config.bootloader, Bootloader::Grub);
+        let warned = |prefix: &str| imported.warnings.iter().any(|w| w.starts_with(prefix));
+        assert!(warned("Kernel 'linux-zen'"));
+        assert!(warned("Bootloader 'Limine'"));
+        assert!(warned("Root password"));
+        assert!(warned("'custom_commands'"));
+        assert!(import("[]", None).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Catalogue of desktop environments and window managers
+//!
+//! Each choice of the Desktop Environment option is described in
+//! `scripts/desktops/desktops.json`: its packages, the display manager that
+//! suits it, and whether it runs as a Wayland or X11 session. The Bash config
+//! loader reads the same file, so the TUI and config-file installs agree on
+//! what gets installed; chroot_config.sh receives the packages as
+//! `DESKTOP_PACKAGES`.
+
+use crate::types::{DesktopEnvironment, DisplayManager};
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Display server protocol of a desktop's default session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Session {
+    Wayland,
+    X11,
+}
+
+/// One entry of the desktop catalogue
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Desktop {
+    /// Option value, matching [`DesktopEnvironment`]'s lowercase name
+    pub id: String,
+    /// Display name, e.g. "KDE Plasma"
+    pub name: String,
+    pub description: String,
+    /// Default session; `None` when nothing graphical is installed
+    #[serde(default)]
+    pub session: Option<Session>,
+    /// Display manager chosen with the desktop; `None` leaves the option alone
+    #[serde(default)]
+    pub display_manager: Option<String>,
+    /// Package whose presence on a running system identifies the desktop
+    #[serde(default)]
+    pub detect: Option<String>,
+    /// Session notes shown when the desktop is selected
+    #[serde(default)]
+    pub notes: String,
+    /// Older names configuration files may use, e.g. `plasma`
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub packages: Vec<String>,
+}
+
+impl Desktop {
+    /// The display manager selected along with this desktop
+    pub fn display_manager(&self) -> Option<DisplayManager> {
+        self.display_manager
+            .as_deref()
+            .and_then(|dm| DisplayManager::from_str(dm).ok())
+    }
+
+    /// One-line summary for the status bar, e.g. "XFCE (X11): Runs on X11 only"
+    pub fn summary(&self) -> String {
+        let name = match self.session {
+            Some(Session::Wayland) => format!("{} (Wayland)", self.name),
+            Some(Session::X11) => format!("{} (X11)", self.name),
+            None => self.name.clone(),
+        };
+        if self.notes.is_empty() {
+            name
+        } else {
+            format!("{}: {}", name, self.notes)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DesktopFile {
+    desktops: Vec<Desktop>,
+}
+
+/// All desktops, in display order
+pub fn desktops() -> &'static [Desktop] {
+    static DESKTOPS: OnceLock<Vec<Desktop>> = OnceLock::new();
+    DESKTOPS.get_or_init(|| {
+        serde_json::from_str::<DesktopFile>(include_str!("../scripts/desktops/desktops.json"))
+            .expect("scripts/desktops/desktops.json is valid")
+            .desktops
+    })
+}
+
+/// Look up a desktop by its option value, e.g. `xfce`, or one of its aliases
+pub fn find_desktop(id: &str) -> Option<&'static Desktop> {
+    desktops()
+        .iter()
+        .find(|desktop| desktop.id == id || desktop.aliases.iter().any(|alias| alias == id))
+}
+
+/// The catalogue entry of a desktop environment
+pub fn desktop(de: DesktopEnvironment) -> &'static Desktop {
+    find_desktop(&de.to_string()).expect("every desktop environment is in desktops.json")
+}
+
+/// Space-separated packages of a desktop, as exported in `DESKTOP_PACKAGES`
+pub fn packages(de: DesktopEnvironment) -> String {
+    desktop(de).packages.join(" ")
+}
+
+/// Identify the desktop installed on a system by its marker package
+pub fn detect(is_installed: impl Fn(&str) -> bool) -> Option<DesktopEnvironment> {
+    desktops()
+        .iter()
+        .filter(|desktop| desktop.detect.as_deref().is_some_and(&is_installed))
+        .find_map(|desktop| DesktopEnvironment::from_str(&desktop.id).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_catalogue_covers_every_desktop_environment() {
+        for de in DesktopEnvironment::iter() {
+            let entry = desktop(de);
+            assert_eq!(entry.id, de.to_string());
+            if de == DesktopEnvironment::None {
+                assert!(entry.packages.is_empty());
+                assert_eq!(entry.session, None);
+            } else {
+                assert!(!entry.packages.is_empty(), "{} has no packages", de);
+                assert!(entry.session.is_some(), "{} has no session", de);
+                assert!(entry.display_manager().is_some(), "{} has no DM", de);
+            }
+        }
+        assert_eq!(desktops().len(), DesktopEnvironment::iter().count());
+    }
+
+    #[test]
+    fn test_display_manager_and_session_mapping() {
+        let xfce = desktop(DesktopEnvironment::Xfce);
+        assert_eq!(xfce.display_manager(), Some(DisplayManager::Lightdm));
+        assert_eq!(xfce.session, Some(Session::X11));
+        assert_eq!(
+            desktop(DesktopEnvironment::Gnome).display_manager(),
+            Some(DisplayManager::Gdm)
+        );
+        assert_eq!(
+            desktop(DesktopEnvironment::Sway).display_manager(),
+            Some(DisplayManager::None)
+        );
+        assert_eq!(desktop(DesktopEnvironment::None).display_manager(), None);
+        assert!(desktop(DesktopEnvironment::Sway)
+            .summary()
+            .starts_with("Sway (Wayland): "));
+        assert!(packages(DesktopEnvironment::I3).contains("i3-wm"));
+    }
+
+    #[test]
+    fn test_detects_installed_desktop() {
+        let installed = ["base", "mate-panel", "lightdm"];
+        assert_eq!(
+            detect(|package| installed.contains(&package)),
+            Some(DesktopEnvironment::Mate)
+        );
+        assert_eq!(
+            detect(|package| package == "plasma-desktop"),
+            Some(DesktopEnvironment::Kde)
+        );
+        assert_eq!(detect(|package| package == "vim"), None);
+    }
+
+    #[test]
+    fn test_older_desktop_names_are_accepted() {
+        assert_eq!(find_desktop("plasma").map(|d| d.id.as_str()), Some("kde"));
+        assert_eq!(find_desktop("i3wm").map(|d| d.id.as_str()), Some("i3"));
+        assert_eq!(
+            serde_json::from_str::<DesktopEnvironment>("\"plasma\"").unwrap(),
+            DesktopEnvironment::Kde
+        );
+        assert_eq!(
+            serde_json::from_str::<DesktopEnvironment>("\"minimal\"").unwrap(),
+            DesktopEnvironment::None
+        );
+    }
+}
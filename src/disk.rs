@@ -0,0 +1,254 @@
+//! Block device naming
+//!
+//! Whole disks and their partitions follow the kernel's naming: a partition
+//! is the disk name plus its number, with a `p` in between when the disk
+//! name already ends in a digit. That covers SCSI/SATA/USB (`sda1`),
+//! virtio (`vda1`), Xen (`xvda1`), NVMe namespaces (`nvme0n2p1`), eMMC and
+//! SD cards (`mmcblk0p1`) and loop devices (`loop0p1`).
+//!
+//! The bash side mirrors this in `get_partition_path` (disk_utils.sh).
+
+use std::fmt;
+
+/// Family of a whole-disk device name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskClass {
+    /// SCSI, SATA, SAS and USB disks: sda, sdb, ..., sdaa
+    Scsi,
+    /// virtio-blk: vda
+    Virtio,
+    /// Xen virtual block device: xvda
+    Xen,
+    /// NVMe namespace: nvme0n1, nvme0n2, nvme1n1
+    Nvme,
+    /// eMMC or SD card: mmcblk0
+    Mmc,
+    /// Loop device backed by an image file: loop0
+    Loop,
+}
+
+impl DiskClass {
+    /// Classify a whole-disk name (without /dev/)
+    ///
+    /// Partitions, NVMe controllers (`nvme0`) and the eMMC boot and RPMB
+    /// areas (`mmcblk0boot0`, `mmcblk0rpmb`) are not whole disks.
+    pub fn of(name: &str) -> Option<Self> {
+        let letters = |rest: &str| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_lowercase());
+        let number = |rest: &str| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit());
+
+        if let Some(rest) = name.strip_prefix("xvd") {
+            letters(rest).then_some(Self::Xen)
+        } else if let Some(rest) = name.strip_prefix("sd") {
+            letters(rest).then_some(Self::Scsi)
+        } else if let Some(rest) = name.strip_prefix("vd") {
+            letters(rest).then_some(Self::Virtio)
+        } else if let Some(rest) = name.strip_prefix("nvme") {
+            let (controller, namespace) = rest.split_once('n')?;
+            (number(controller) && number(namespace)).then_some(Self::Nvme)
+        } else if let Some(rest) = name.strip_prefix("mmcblk") {
+            number(rest).then_some(Self::Mmc)
+        } else if let Some(rest) = name.strip_prefix("loop") {
+            number(rest).then_some(Self::Loop)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for DiskClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Scsi => write!(f, "SCSI/SATA"),
+            Self::Virtio => write!(f, "virtio"),
+            Self::Xen => write!(f, "Xen"),
+            Self::Nvme => write!(f, "NVMe"),
+            Self::Mmc => write!(f, "eMMC/SD"),
+            Self::Loop => write!(f, "loop"),
+        }
+    }
+}
+
+/// A whole disk that can hold a partition table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskDevice {
+    /// Kernel name, e.g. nvme0n1
+    name: String,
+    class: DiskClass,
+}
+
+impl DiskDevice {
+    /// Parse a disk path (`/dev/sda`) or kernel name (`sda`)
+    ///
+    /// Errors for partitions and names that are not whole disks, saying
+    /// which disk was probably meant.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let name = path.trim();
+        let name = name.strip_prefix("/dev/").unwrap_or(name);
+
+        if let Some(class) = DiskClass::of(name) {
+            return Ok(Self {
+                name: name.to_string(),
+                class,
+            });
+        }
+        if let Some((disk, number)) = split_partition(name) {
+            return Err(format!(
+                "{} is partition {} of {}, select the whole disk",
+                path.trim(),
+                number,
+                disk.path()
+            ));
+        }
+        if name.starts_with("mmcblk") && (name.contains("boot") || name.ends_with("rpmb")) {
+            return Err(format!(
+                "{} is an eMMC boot or RPMB area, select the main mmcblk device",
+                path.trim()
+            ));
+        }
+        Err(format!(
+            "{} is not a supported disk (expected e.g. /dev/sda, /dev/nvme0n1, /dev/mmcblk0 or /dev/loop0)",
+            path.trim()
+        ))
+    }
+
+    /// Device path, e.g. /dev/nvme0n1
+    pub fn path(&self) -> String {
+        format!("/dev/{}", self.name)
+    }
+
+    pub fn class(&self) -> DiskClass {
+        self.class
+    }
+
+    /// Path of partition `number`: /dev/sda1, /dev/nvme0n1p1, /dev/mmcblk0p1
+    pub fn partition(&self, number: u32) -> String {
+        if self.name.ends_with(|c: char| c.is_ascii_digit()) {
+            format!("/dev/{}p{}", self.name, number)
+        } else {
+            format!("/dev/{}{}", self.name, number)
+        }
+    }
+}
+
+impl fmt::Display for DiskDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/dev/{}", self.name)
+    }
+}
+
+/// Split a partition path into its disk and partition number
+///
+/// Returns `None` for whole disks and unrecognised names.
+pub fn split_partition(path: &str) -> Option<(DiskDevice, u32)> {
+    let name = path.trim();
+    let name = name.strip_prefix("/dev/").unwrap_or(name);
+
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let number: u32 = name[stem.len()..].parse().ok()?;
+    // nvme0n1p2 -> nvme0n1, but sdp2 -> sdp
+    let disk = match stem.strip_suffix('p') {
+        Some(disk) if disk.ends_with(|c: char| c.is_ascii_digit()) => disk,
+        _ => stem,
+    };
+    // Digit-suffixed disks always separate the number with "p"
+    if disk == stem && disk.ends_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let disk = DiskDevice::parse(disk).ok()?;
+    (disk.partition(number) == format!("/dev/{}", name)).then_some((disk, number))
+}
+
+/// Check a comma-separated disk selection, as stored in the Disk option
+pub fn validate_disks(value: &str) -> Result<Vec<DiskDevice>, String> {
+    let disks = value
+        .split(',')
+        .map(str::trim)
+        .filter(|disk| !disk.is_empty())
+        .map(DiskDevice::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if disks.is_empty() {
+        return Err("No disk selected".to_string());
+    }
+    Ok(disks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_disk_classes() {
+        for (name, class) in [
+            ("sda", DiskClass::Scsi),
+            ("sdaa", DiskClass::Scsi),
+            ("vdb", DiskClass::Virtio),
+            ("xvda", DiskClass::Xen),
+            ("nvme0n1", DiskClass::Nvme),
+            ("nvme1n3", DiskClass::Nvme),
+            ("mmcblk0", DiskClass::Mmc),
+            ("loop12", DiskClass::Loop),
+        ] {
+            assert_eq!(DiskClass::of(name), Some(class), "{}", name);
+        }
+        for name in [
+            "sda1",
+            "nvme0",
+            "nvme0n1p1",
+            "mmcblk0boot0",
+            "mmcblk0rpmb",
+            "loop",
+            "zram0",
+            "sr0",
+        ] {
+            assert_eq!(DiskClass::of(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_partition_paths() {
+        let path = |disk: &str, n| DiskDevice::parse(disk).unwrap().partition(n);
+        assert_eq!(path("/dev/sda", 1), "/dev/sda1");
+        assert_eq!(path("/dev/vda", 3), "/dev/vda3");
+        assert_eq!(path("/dev/nvme0n2", 1), "/dev/nvme0n2p1");
+        assert_eq!(path("mmcblk0", 2), "/dev/mmcblk0p2");
+        assert_eq!(path("/dev/loop0", 1), "/dev/loop0p1");
+    }
+
+    #[test]
+    fn test_split_partition() {
+        let split = |path: &str| split_partition(path).map(|(disk, n)| (disk.path(), n));
+        assert_eq!(split("/dev/sda2"), Some(("/dev/sda".to_string(), 2)));
+        assert_eq!(split("/dev/sdp2"), Some(("/dev/sdp".to_string(), 2)));
+        assert_eq!(
+            split("/dev/nvme1n2p10"),
+            Some(("/dev/nvme1n2".to_string(), 10))
+        );
+        assert_eq!(
+            split("/dev/mmcblk0p1"),
+            Some(("/dev/mmcblk0".to_string(), 1))
+        );
+        assert_eq!(split("/dev/loop3p1"), Some(("/dev/loop3".to_string(), 1)));
+        assert_eq!(split("/dev/nvme0n1"), None);
+        assert_eq!(split("/dev/mmcblk0"), None);
+        assert_eq!(split("/dev/sda"), None);
+    }
+
+    #[test]
+    fn test_parse_explains_rejections() {
+        assert!(DiskDevice::parse("/dev/nvme0n1p2")
+            .unwrap_err()
+            .contains("partition 2 of /dev/nvme0n1"));
+        assert!(DiskDevice::parse("/dev/mmcblk0boot1")
+            .unwrap_err()
+            .contains("eMMC boot"));
+        assert!(DiskDevice::parse("/dev/zram0")
+            .unwrap_err()
+            .contains("not a supported disk"));
+
+        let disks = validate_disks("/dev/nvme0n1, /dev/nvme0n2").unwrap();
+        assert_eq!(disks[1].path(), "/dev/nvme0n2");
+        assert!(validate_disks("/dev/sda,/dev/sda1").is_err());
+        assert!(validate_disks("").is_err());
+    }
+}
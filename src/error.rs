@@ -2,11 +2,18 @@
 //!
 //! Provides centralized error handling with proper error types using thiserror.
 //! All errors in the application should use these types for consistency.
+//! Errors keep what went wrong in structured fields (the command and its exit
+//! status, the field that failed validation) so that [`ArchInstallError::dialog`]
+//! can explain them to the user instead of showing a bare message.
 
 #![allow(dead_code)] // Error variants and helpers are available for future use
 
+use std::process::Output;
 use thiserror::Error;
 
+/// Lines of a failed command's stderr shown in the error dialog
+const STDERR_DIALOG_LINES: usize = 5;
+
 /// Main error type for the ArchInstall TUI
 #[derive(Error, Debug)]
 pub enum ArchInstallError {
@@ -22,9 +29,27 @@ pub enum ArchInstallError {
     #[error("Script execution failed: {0}")]
     Script(String),
 
+    /// A command could not be started (not installed, not executable)
+    #[error("Failed to run {cmd}: {source}")]
+    CommandNotRun {
+        cmd: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A command ran and exited unsuccessfully
+    #[error("{cmd} failed with {}{}", exit_status(*status), stderr_suffix(stderr))]
+    CommandFailed {
+        cmd: String,
+        /// Exit code, `None` when killed by a signal
+        status: Option<i32>,
+        /// Trimmed standard error of the command
+        stderr: String,
+    },
+
     /// Validation errors (user input, config values)
-    #[error("Validation error: {0}")]
-    Validation(String),
+    #[error("Invalid {field}: {reason}")]
+    Validation { field: String, reason: String },
 
     /// System errors (commands, processes)
     #[error("System error: {0}")]
@@ -34,6 +59,10 @@ pub enum ArchInstallError {
     #[error("Terminal error: {0}")]
     Terminal(String),
 
+    /// Embedded terminal (PTY) errors
+    #[error("Embedded terminal error: {0}")]
+    Pty(String),
+
     /// State errors (mutex poisoning, invalid state)
     #[error("State error: {0}")]
     State(String),
@@ -70,9 +99,29 @@ impl ArchInstallError {
         Self::Script(msg.into())
     }
 
-    /// Create a validation error
-    pub fn validation(msg: impl Into<String>) -> Self {
-        Self::Validation(msg.into())
+    /// Create a validation error for `field`
+    pub fn validation(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Validation {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an error for a command that could not be started
+    pub fn command_not_run(cmd: impl Into<String>, source: std::io::Error) -> Self {
+        Self::CommandNotRun {
+            cmd: cmd.into(),
+            source,
+        }
+    }
+
+    /// Create an error for a command that exited unsuccessfully
+    pub fn command_failed(cmd: impl Into<String>, output: &Output) -> Self {
+        Self::CommandFailed {
+            cmd: cmd.into(),
+            status: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
     }
 
     /// Create a system error
@@ -85,6 +134,11 @@ impl ArchInstallError {
         Self::Terminal(msg.into())
     }
 
+    /// Create an embedded terminal error
+    pub fn pty(msg: impl Into<String>) -> Self {
+        Self::Pty(msg.into())
+    }
+
     /// Create a state error
     pub fn state(msg: impl Into<String>) -> Self {
         Self::State(msg.into())
@@ -106,6 +160,103 @@ impl ArchInstallError {
     }
 }
 
+impl ArchInstallError {
+    /// Whether the TUI cannot go on after this error
+    ///
+    /// Other errors are shown in a dialog and the user carries on.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Terminal(_) | Self::State(_))
+    }
+
+    /// Title and lines of the dialog explaining this error to the user
+    pub fn dialog(&self) -> (&'static str, Vec<String>) {
+        match self {
+            Self::Io(e) => match e.kind() {
+                std::io::ErrorKind::PermissionDenied => (
+                    "Permission Denied",
+                    vec![
+                        e.to_string(),
+                        String::new(),
+                        "Run the installer as root: sudo archinstall-tui".to_string(),
+                    ],
+                ),
+                std::io::ErrorKind::NotFound => (
+                    "Not Found",
+                    vec![
+                        e.to_string(),
+                        String::new(),
+                        "Check that the file or device still exists.".to_string(),
+                    ],
+                ),
+                _ => ("Error", vec![e.to_string()]),
+            },
+            Self::CommandNotRun { cmd, source } => {
+                let mut lines = vec![format!("{} could not be started: {}", cmd, source)];
+                if source.kind() == std::io::ErrorKind::NotFound {
+                    lines.push(String::new());
+                    lines.push(format!(
+                        "Is it installed? Look for the package providing it: pacman -F {}",
+                        cmd.split_whitespace().next().unwrap_or(cmd)
+                    ));
+                }
+                ("Command Not Available", lines)
+            }
+            Self::CommandFailed {
+                cmd,
+                status,
+                stderr,
+            } => {
+                let mut lines = vec![format!("{} failed with {}.", cmd, exit_status(*status))];
+                let tail: Vec<&str> = stderr.lines().collect();
+                if !tail.is_empty() {
+                    lines.push(String::new());
+                    let start = tail.len().saturating_sub(STDERR_DIALOG_LINES);
+                    lines.extend(tail[start..].iter().map(|line| line.to_string()));
+                }
+                ("Command Failed", lines)
+            }
+            Self::Validation { field, reason } => {
+                ("Invalid Value", vec![format!("{}: {}", field, reason)])
+            }
+            Self::Config(msg) => ("Configuration Problem", vec![msg.clone()]),
+            Self::Pty(msg) => (
+                "Embedded Terminal",
+                vec![
+                    msg.clone(),
+                    String::new(),
+                    "The tool can still be run from a shell.".to_string(),
+                ],
+            ),
+            Self::Script(msg) => ("Installer Script", vec![msg.clone()]),
+            Self::Json(e) => ("Invalid JSON", vec![e.to_string()]),
+            other => ("Error", vec![other.to_string()]),
+        }
+    }
+}
+
+impl From<crate::components::pty_terminal::PtyError> for ArchInstallError {
+    fn from(e: crate::components::pty_terminal::PtyError) -> Self {
+        Self::Pty(e.to_string())
+    }
+}
+
+/// "exit status 2", or "a signal" for a command killed by one
+fn exit_status(status: Option<i32>) -> String {
+    match status {
+        Some(code) => format!("exit status {}", code),
+        None => "a signal".to_string(),
+    }
+}
+
+/// ": <stderr>" when the command printed any
+fn stderr_suffix(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", stderr)
+    }
+}
+
 /// Helper function to create general errors (for backward compatibility)
 pub fn general_error(msg: impl Into<String>) -> ArchInstallError {
     ArchInstallError::General(msg.into())
@@ -120,8 +271,8 @@ mod tests {
         let err = ArchInstallError::config("invalid hostname");
         assert_eq!(err.to_string(), "Configuration error: invalid hostname");
 
-        let err = ArchInstallError::validation("password too short");
-        assert_eq!(err.to_string(), "Validation error: password too short");
+        let err = ArchInstallError::validation("password", "too short");
+        assert_eq!(err.to_string(), "Invalid password: too short");
     }
 
     #[test]
@@ -139,4 +290,31 @@ mod tests {
         let err = ArchInstallError::system("command not found");
         assert!(matches!(err, ArchInstallError::System(_)));
     }
+
+    #[test]
+    fn test_command_errors() {
+        let output = std::process::Command::new("sh")
+            .args(["-c", "echo one >&2; echo two >&2; exit 3"])
+            .output()
+            .unwrap();
+        let err = ArchInstallError::command_failed("mkfs.ext4 /dev/sda2", &output);
+        assert_eq!(
+            err.to_string(),
+            "mkfs.ext4 /dev/sda2 failed with exit status 3: one\ntwo"
+        );
+        let (title, lines) = err.dialog();
+        assert_eq!(title, "Command Failed");
+        assert_eq!(lines.last().map(String::as_str), Some("two"));
+
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "No such file");
+        let err = ArchInstallError::command_not_run("smartctl", missing);
+        assert_eq!(err.to_string(), "Failed to run smartctl: No such file");
+        assert!(err
+            .dialog()
+            .1
+            .iter()
+            .any(|line| line.contains("pacman -F smartctl")));
+        assert!(!err.is_fatal());
+        assert!(ArchInstallError::state("poisoned").is_fatal());
+    }
 }
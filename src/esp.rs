@@ -0,0 +1,327 @@
+//! Reusing an existing EFI System Partition
+//!
+//! A disk that already boots another system (typically Windows) has an ESP,
+//! and firmware tends to cope badly with two of them. The EFI Partition
+//! option is either [`CREATE`] or the path of an existing ESP: the automatic
+//! strategies then keep that partition (and, when it lives on the install
+//! disk, the rest of the disk) instead of wiping it, install.sh mounts it at
+//! /efi and the bootloader is added next to what is already there.
+
+use crate::disk;
+use crate::tools::resize::format_size;
+use crate::types::{BootMode, PartitionScheme};
+use serde_json::Value;
+use std::fmt;
+use std::process::{Command, Stdio};
+
+/// Option value for creating a fresh ESP
+pub const CREATE: &str = "create";
+
+/// GPT partition type GUID of an ESP
+pub const ESP_GUID: &str = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+
+/// MBR partition type of an ESP
+pub const ESP_MBR_TYPE: &str = "0xef";
+
+/// Free space the bootloader needs on a shared ESP
+///
+/// Kernels and initramfs images stay on /boot, so only the EFI binaries and
+/// loader entries go there. Windows creates 100 MiB ESPs that are often
+/// mostly full already.
+pub const MIN_FREE: u64 = 32 * 1024 * 1024;
+
+/// EFI System Partition found on a disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingEsp {
+    /// Partition path, e.g. /dev/nvme0n1p1
+    pub path: String,
+    /// Disk the partition is on
+    pub disk: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Free space in bytes, if the filesystem could be read
+    pub free: Option<u64>,
+}
+
+impl ExistingEsp {
+    /// Whether the bootloader fits; unknown free space counts as enough
+    /// because install.sh checks again before anything is written
+    pub fn has_room(&self) -> bool {
+        self.free.is_none_or(|free| free >= MIN_FREE)
+    }
+}
+
+impl fmt::Display for ExistingEsp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.path, format_size(self.size))?;
+        if let Some(free) = self.free {
+            write!(f, ", {} free", format_size(free))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Parse `lsblk -J -b -o PATH,PKNAME,PARTTYPE,FSTYPE,SIZE,FSAVAIL,MOUNTPOINT`
+///
+/// Partitions with the ESP type and a FAT filesystem qualify. FSAVAIL is
+/// only reported for mounted filesystems; [`detect`] fills in the rest.
+pub fn parse_lsblk(json: &str) -> Result<Vec<ExistingEsp>, serde_json::Error> {
+    let value: Value = serde_json::from_str(json)?;
+    let mut found = Vec::new();
+    for device in value["blockdevices"].as_array().into_iter().flatten() {
+        collect(device, &mut found);
+    }
+    Ok(found)
+}
+
+fn collect(device: &Value, found: &mut Vec<ExistingEsp>) {
+    let text = |key: &str| {
+        device[key]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    };
+    // Older lsblk versions print numbers as strings
+    let number = |key: &str| {
+        device[key]
+            .as_u64()
+            .or_else(|| device[key].as_str().and_then(|s| s.parse().ok()))
+    };
+
+    let parttype = text("parttype");
+    if (parttype == ESP_GUID || parttype == ESP_MBR_TYPE) && text("fstype") == "vfat" {
+        let path = device["path"].as_str().unwrap_or_default().to_string();
+        let disk = match disk::split_partition(&path) {
+            Some((disk, _)) => disk.path(),
+            None => format!("/dev/{}", text("pkname")),
+        };
+        found.push(ExistingEsp {
+            path,
+            disk,
+            size: number("size").unwrap_or(0),
+            free: number("fsavail"),
+        });
+    }
+    for child in device["children"].as_array().into_iter().flatten() {
+        collect(child, found);
+    }
+}
+
+/// Free bytes on an unmounted FAT filesystem, via a temporary read-only mount
+fn free_space(path: &str) -> Option<u64> {
+    let mountpoint = std::env::temp_dir().join(format!("archinstall-esp-{}", std::process::id()));
+    std::fs::create_dir_all(&mountpoint).ok()?;
+    let quiet = |cmd: &mut Command| {
+        cmd.stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+    };
+
+    let free = quiet(
+        Command::new("mount")
+            .args(["-o", "ro"])
+            .arg(path)
+            .arg(&mountpoint),
+    )
+    .and_then(|_| {
+        let df = quiet(
+            Command::new("df")
+                .args(["--output=avail", "-B1"])
+                .arg(&mountpoint),
+        );
+        quiet(Command::new("umount").arg(&mountpoint));
+        df
+    })
+    .and_then(|output| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .nth(1)
+            .and_then(|line| line.trim().parse().ok())
+    });
+    let _ = std::fs::remove_dir(&mountpoint);
+    free
+}
+
+/// EFI System Partitions on this machine, with their free space
+pub fn detect() -> Vec<ExistingEsp> {
+    let mut found = Command::new("lsblk")
+        .args([
+            "-J",
+            "-b",
+            "-o",
+            "PATH,PKNAME,PARTTYPE,FSTYPE,SIZE,FSAVAIL,MOUNTPOINT",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|output| parse_lsblk(&String::from_utf8_lossy(&output.stdout)).ok())
+        .unwrap_or_default();
+    for esp in found.iter_mut().filter(|esp| esp.free.is_none()) {
+        esp.free = free_space(&esp.path);
+    }
+    found
+}
+
+/// Check that an EFI Partition value is [`CREATE`] or a partition path
+pub fn validate_value(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() || value == CREATE || disk::split_partition(value).is_some() {
+        return Ok(());
+    }
+    Err(format!(
+        "EFI Partition must be '{}' or a partition such as /dev/sda1, not '{}'",
+        CREATE, value
+    ))
+}
+
+/// Check an EFI Partition value against the rest of the configuration
+///
+/// Only the automatic strategies without RAID know how to keep an ESP;
+/// manual partitioning mounts whatever the user prepared. `uefi_firmware`
+/// is whether the machine booted in UEFI mode, when known.
+pub fn validate_reuse(
+    value: &str,
+    boot_mode: BootMode,
+    scheme: PartitionScheme,
+    uefi_firmware: Option<bool>,
+) -> Result<(), String> {
+    validate_value(value)?;
+    let value = value.trim();
+    if value.is_empty() || value == CREATE {
+        return Ok(());
+    }
+    let bios = match boot_mode {
+        BootMode::Bios => true,
+        BootMode::Uefi => false,
+        BootMode::Auto => uefi_firmware == Some(false),
+    };
+    if bios {
+        return Err(format!(
+            "Reusing the EFI partition {} needs UEFI boot mode",
+            value
+        ));
+    }
+    if scheme.requires_raid() {
+        return Err(format!(
+            "Reusing an EFI partition is not supported with {}: every RAID disk gets its own",
+            scheme
+        ));
+    }
+    if scheme == PartitionScheme::Manual {
+        return Err(format!(
+            "With manual partitioning mount {} at /mnt/efi yourself and keep EFI Partition at '{}'",
+            value, CREATE
+        ));
+    }
+    if scheme == PartitionScheme::Existing {
+        return Err(format!(
+            "With existing partitions assign {} to /efi in Partition Mounts and keep EFI \
+             Partition at '{}'",
+            value, CREATE
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a detected ESP has room for the bootloader
+pub fn check_free(esp: &ExistingEsp) -> Result<(), String> {
+    if esp.has_room() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} has only {} free, the bootloader needs {}",
+        esp.path,
+        format_size(esp.free.unwrap_or(0)),
+        format_size(MIN_FREE)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSBLK: &str = r#"{
+       "blockdevices": [
+          {"path":"/dev/nvme0n1", "pkname":null, "parttype":null, "fstype":null, "size":512110190592, "fsavail":null, "mountpoint":null,
+             "children": [
+                {"path":"/dev/nvme0n1p1", "pkname":"nvme0n1", "parttype":"C12A7328-F81F-11D2-BA4B-00A0C93EC93B", "fstype":"vfat", "size":104857600, "fsavail":null, "mountpoint":null},
+                {"path":"/dev/nvme0n1p2", "pkname":"nvme0n1", "parttype":"e3c9e316-0b5c-4db8-817d-f92df00215ae", "fstype":null, "size":16777216, "fsavail":null, "mountpoint":null},
+                {"path":"/dev/nvme0n1p3", "pkname":"nvme0n1", "parttype":"ebd0a0a2-b9e5-4433-87c0-68b6b72699c7", "fstype":"ntfs", "size":255013683200, "fsavail":null, "mountpoint":null}
+             ]
+          },
+          {"path":"/dev/sda", "pkname":null, "parttype":null, "fstype":null, "size":64023257088, "fsavail":null, "mountpoint":null,
+             "children": [
+                {"path":"/dev/sda1", "pkname":"sda", "parttype":"0xef", "fstype":"vfat", "size":"268435456", "fsavail":"20971520", "mountpoint":"/run/media/esp"},
+                {"path":"/dev/sda2", "pkname":"sda", "parttype":"0x0c", "fstype":"vfat", "size":"63753772032", "fsavail":null, "mountpoint":null}
+             ]
+          }
+       ]
+    }"#;
+
+    #[test]
+    fn test_parse_lsblk_finds_esps() {
+        let found = parse_lsblk(LSBLK).unwrap();
+        let paths: Vec<&str> = found.iter().map(|esp| esp.path.as_str()).collect();
+        // Not the Windows data partitions or a FAT partition of another type
+        assert_eq!(paths, ["/dev/nvme0n1p1", "/dev/sda1"]);
+
+        assert_eq!(found[0].disk, "/dev/nvme0n1");
+        assert_eq!(found[0].to_string(), "/dev/nvme0n1p1 (100.0 MiB)");
+        assert!(found[0].has_room());
+
+        assert_eq!(found[1].free, Some(20 * 1024 * 1024));
+        assert_eq!(found[1].to_string(), "/dev/sda1 (256.0 MiB, 20.0 MiB free)");
+        assert_eq!(
+            check_free(&found[1]).unwrap_err(),
+            "/dev/sda1 has only 20.0 MiB free, the bootloader needs 32.0 MiB"
+        );
+
+        assert!(parse_lsblk(r#"{"blockdevices": []}"#).unwrap().is_empty());
+        assert!(parse_lsblk("not json").is_err());
+    }
+
+    #[test]
+    fn test_validate_reuse() {
+        let simple = PartitionScheme::AutoSimple;
+        assert!(validate_reuse(CREATE, BootMode::Bios, PartitionScheme::Manual, None).is_ok());
+        assert!(validate_reuse("/dev/nvme0n1p1", BootMode::Uefi, simple, None).is_ok());
+        assert!(validate_reuse(
+            "/dev/sda1",
+            BootMode::Auto,
+            PartitionScheme::AutoLuksLvm,
+            None
+        )
+        .is_ok());
+
+        assert!(validate_reuse("/dev/sda", BootMode::Uefi, simple, None)
+            .unwrap_err()
+            .contains("a partition such as"));
+        assert!(validate_reuse("/dev/sda1", BootMode::Bios, simple, None)
+            .unwrap_err()
+            .contains("needs UEFI"));
+        assert!(
+            validate_reuse("/dev/sda1", BootMode::Auto, simple, Some(false))
+                .unwrap_err()
+                .contains("needs UEFI")
+        );
+        assert!(
+            validate_reuse("/dev/sda1", BootMode::Uefi, PartitionScheme::AutoRaid, None)
+                .unwrap_err()
+                .contains("RAID")
+        );
+        assert!(
+            validate_reuse("/dev/sda1", BootMode::Uefi, PartitionScheme::Manual, None)
+                .unwrap_err()
+                .contains("/mnt/efi")
+        );
+        assert!(
+            validate_reuse("/dev/sda1", BootMode::Uefi, PartitionScheme::Existing, None)
+                .unwrap_err()
+                .contains("Partition Mounts")
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Machine-readable installer events
+//!
+//! `install --config FILE --output json` prints one JSON object per line
+//! instead of decorated text, so provisioning tools (Ansible, Terraform
+//! provisioners) can follow an installation. Each object has an `"event"`
+//! field: `config_loaded`, `stage`, `log` and, last, `finished`.
+//!
+//! ```text
+//! {"event":"stage","stage":"partitioning_disk","description":"Partitioning disk","progress":25}
+//! {"event":"log","level":"warn","message":"No mirrors for XX, using worldwide"}
+//! {"event":"finished","success":true,"exit_code":0,"message":"Installation completed successfully"}
+//! ```
+
+use crate::install_state::InstallStage;
+use serde::Serialize;
+
+/// Severity of a log event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Success,
+    Warn,
+    Error,
+    /// Unlabelled output of the script or a program it runs
+    Output,
+}
+
+/// One event of a headless installation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum InstallEvent {
+    /// The configuration was loaded and validated
+    ConfigLoaded {
+        path: String,
+        /// Whether template variables were resolved
+        template: bool,
+    },
+    /// The installer entered a stage
+    Stage {
+        stage: &'static str,
+        description: &'static str,
+        progress: u8,
+    },
+    /// A line of installer output
+    Log { level: LogLevel, message: String },
+    /// The installation ended; always the last event
+    Finished {
+        success: bool,
+        exit_code: i32,
+        message: String,
+    },
+}
+
+impl InstallEvent {
+    pub fn stage(stage: InstallStage) -> Self {
+        Self::Stage {
+            stage: stage.id(),
+            description: stage.description(),
+            progress: stage.progress_percent(),
+        }
+    }
+
+    pub fn log(level: LogLevel, message: impl Into<String>) -> Self {
+        Self::Log {
+            level,
+            message: message.into(),
+        }
+    }
+
+    /// Event for a line of installer output
+    ///
+    /// Phase markers become stage events; log lines keep the level utils.sh
+    /// gave them, without colour codes and timestamp. Other lines on stderr
+    /// count as errors.
+    pub fn from_output_line(line: &str, stderr: bool) -> Self {
+        let line = strip_ansi(line);
+        if let Some(stage) = InstallStage::from_output_line(&line) {
+            return Self::stage(stage);
+        }
+
+        // "[2024-05-01 10:00:00] WARN: message" as written by utils.sh, with
+        // "[Step] " in front for steps running in parallel lanes
+        let mut message = line.trim_end();
+        while let Some((_, rest)) = message
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+        {
+            message = rest;
+            if !message.starts_with('[') {
+                break;
+            }
+        }
+        for (prefix, level) in [
+            ("DEBUG: ", LogLevel::Debug),
+            ("INFO: ", LogLevel::Info),
+            ("SUCCESS: ", LogLevel::Success),
+            ("WARN: ", LogLevel::Warn),
+            ("ERROR: ", LogLevel::Error),
+        ] {
+            if let Some(text) = message.strip_prefix(prefix) {
+                return Self::log(level, text);
+            }
+        }
+        if let Some(phase) = message
+            .strip_prefix("=== ")
+            .and_then(|m| m.strip_suffix(" ==="))
+        {
+            return Self::log(LogLevel::Info, phase);
+        }
+
+        let level = if stderr {
+            LogLevel::Error
+        } else {
+            LogLevel::Output
+        };
+        Self::log(level, message)
+    }
+
+    /// The event as a single line of JSON
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("install events always serialize")
+    }
+
+    /// Print the event as a JSON line on stdout
+    pub fn emit(&self) {
+        println!("{}", self.to_json_line());
+    }
+}
+
+/// Remove ANSI colour sequences (ESC [ ... letter)
+pub(crate) fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_lines_become_events() {
+        assert_eq!(
+            InstallEvent::from_output_line(
+                "\x1b[0m[2024-05-01 10:00:00] INFO: Phase 5: Installing base system...\x1b[0m",
+                false
+            ),
+            InstallEvent::stage(InstallStage::InstallingBaseSystem)
+        );
+        assert_eq!(
+            InstallEvent::from_output_line(
+                "\x1b[1;33m[2024-05-01 10:00:00] WARN: Reflector failed\x1b[0m",
+                true
+            ),
+            InstallEvent::log(LogLevel::Warn, "Reflector failed")
+        );
+        assert_eq!(
+            InstallEvent::from_output_line(
+                "[Mirror ranking] \x1b[1;33m[2024-05-01 10:00:00] WARN: Reflector failed\x1b[0m",
+                false
+            ),
+            InstallEvent::log(LogLevel::Warn, "Reflector failed")
+        );
+        assert_eq!(
+            InstallEvent::from_output_line("[2024-05-01 10:00:00] === Bootloader ===", false),
+            InstallEvent::log(LogLevel::Info, "Bootloader")
+        );
+        assert_eq!(
+            InstallEvent::from_output_line("(3/120) installing linux", false),
+            InstallEvent::log(LogLevel::Output, "(3/120) installing linux")
+        );
+        assert_eq!(
+            InstallEvent::from_output_line("mount: /mnt: wrong fs type", true),
+            InstallEvent::log(LogLevel::Error, "mount: /mnt: wrong fs type")
+        );
+    }
+
+    #[test]
+    fn test_events_serialize_as_tagged_json_lines() {
+        assert_eq!(
+            InstallEvent::stage(InstallStage::PartitioningDisk).to_json_line(),
+            r#"{"event":"stage","stage":"partitioning_disk","description":"Partitioning disk","progress":25}"#
+        );
+        assert_eq!(
+            InstallEvent::log(LogLevel::Success, "done").to_json_line(),
+            r#"{"event":"log","level":"success","message":"done"}"#
+        );
+        let finished = InstallEvent::Finished {
+            success: false,
+            exit_code: 3,
+            message: "Hostname must be specified".to_string(),
+        };
+        assert_eq!(
+            finished.to_json_line(),
+            r#"{"event":"finished","success":false,"exit_code":3,"message":"Hostname must be specified"}"#
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Machine facts for config templates
+//!
+//! A config file can name facts about the machine it is installed on, e.g.
+//! `"hostname": "lab-{{ serial }}"`, so one template provisions many
+//! machines. Facts come from SMBIOS (via /sys/class/dmi/id) and the first
+//! physical network interface, read locally or over SSH for remote installs.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// SMBIOS facts and the sysfs file each is read from
+const DMI_FACTS: &[(&str, &str)] = &[
+    ("serial", "/sys/class/dmi/id/product_serial"),
+    ("uuid", "/sys/class/dmi/id/product_uuid"),
+    ("vendor", "/sys/class/dmi/id/sys_vendor"),
+    ("product", "/sys/class/dmi/id/product_name"),
+];
+
+/// Network interfaces; physical ones have a `device` link
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+/// Every variable a template can use
+pub const FACT_NAMES: &[&str] = &["serial", "uuid", "vendor", "product", "mac", "mac_last4"];
+
+/// Placeholders firmware vendors leave in unset SMBIOS fields
+const DMI_PLACEHOLDERS: &[&str] = &[
+    "to be filled by o.e.m.",
+    "default string",
+    "system serial number",
+    "not specified",
+    "not applicable",
+    "none",
+    "0",
+];
+
+/// Config keys never rendered: passwords may contain `{{` literally
+const SECRET_SUFFIX: &str = "_password";
+
+/// Facts about one machine, by variable name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Facts {
+    values: BTreeMap<String, String>,
+}
+
+impl Facts {
+    /// Facts from raw `name=value` pairs (DMI strings and `mac`)
+    ///
+    /// Values are trimmed, firmware placeholders dropped and the derived
+    /// facts (`mac_last4`) filled in.
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut values = BTreeMap::new();
+        for (name, value) in pairs {
+            let value = value.trim();
+            if value.is_empty() || DMI_PLACEHOLDERS.contains(&value.to_lowercase().as_str()) {
+                continue;
+            }
+            let value = if name == "mac" {
+                value.to_lowercase()
+            } else {
+                value.to_string()
+            };
+            values.insert(name.to_string(), value);
+        }
+        if let Some(mac) = values.get("mac") {
+            let digits: String = mac.chars().filter(char::is_ascii_hexdigit).collect();
+            if digits.len() >= 4 {
+                let last4 = digits[digits.len() - 4..].to_string();
+                values.insert("mac_last4".to_string(), last4);
+            }
+        }
+        Self { values }
+    }
+
+    /// Parse `name=value` lines, as printed by [`remote_command`]
+    pub fn parse(output: &str) -> Self {
+        Self::from_pairs(output.lines().filter_map(|line| line.split_once('=')))
+    }
+
+    /// Facts of the machine this runs on
+    ///
+    /// SMBIOS serials are only readable by root; unreadable facts are left
+    /// out and reported when a template uses them.
+    pub fn gather() -> Self {
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+        let mut pairs: Vec<(&str, String)> = DMI_FACTS
+            .iter()
+            .map(|(name, path)| (*name, read(path)))
+            .collect();
+        if let Some(mac) = primary_mac(Path::new(SYS_CLASS_NET)) {
+            pairs.push(("mac", mac));
+        }
+        Self::from_pairs(pairs.iter().map(|(name, value)| (*name, value.as_str())))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Replace every `{{ name }}` in `template`
+    ///
+    /// Braces around anything but a plain lowercase name are left as they
+    /// are. Unknown names and facts missing on this machine are errors.
+    pub fn render(&self, template: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            out.push_str(&rest[..start]);
+            if is_variable(name) {
+                out.push_str(self.lookup(name)?);
+            } else {
+                out.push_str(&rest[start..start + len + 4]);
+            }
+            rest = &rest[start + len + 4..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Render every string in a JSON config, passwords excepted
+    pub fn render_json(&self, value: &mut Value) -> Result<(), String> {
+        match value {
+            Value::String(text) => *text = self.render(text)?,
+            Value::Array(items) => {
+                for item in items {
+                    self.render_json(item)?;
+                }
+            }
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    if key.ends_with(SECRET_SUFFIX) {
+                        continue;
+                    }
+                    self.render_json(field)
+                        .map_err(|e| format!("{}: {}", key, e))?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Result<&str, String> {
+        if !FACT_NAMES.contains(&name) {
+            return Err(format!(
+                "unknown template variable '{}' (available: {})",
+                name,
+                FACT_NAMES.join(", ")
+            ));
+        }
+        self.get(name).ok_or_else(|| {
+            format!(
+                "template variable '{}' is not available on this machine",
+                name
+            )
+        })
+    }
+}
+
+/// Whether a config file uses template variables
+pub fn is_template(content: &str) -> bool {
+    content.split("{{").skip(1).any(|after| {
+        after
+            .split_once("}}")
+            .is_some_and(|(name, _)| is_variable(name.trim()))
+    })
+}
+
+/// Shell command printing the target's facts as `name=value` lines
+///
+/// Reads the same files as [`Facts::gather`], for remote installs.
+pub fn remote_command() -> String {
+    let mut command: Vec<String> = DMI_FACTS
+        .iter()
+        .map(|(name, path)| format!("printf '{}=%s\\n' \"$(cat {} 2>/dev/null)\"", name, path))
+        .collect();
+    command.push(format!(
+        "for i in {}/*; do [ -e \"$i/device\" ] && printf 'mac=%s\\n' \"$(cat \"$i/address\")\" && break; done",
+        SYS_CLASS_NET
+    ));
+    command.push("true".to_string());
+    command.join("; ")
+}
+
+fn is_variable(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// MAC address of the first physical interface, by name
+fn primary_mac(net: &Path) -> Option<String> {
+    let mut interfaces: Vec<_> = fs::read_dir(net)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.join("device").exists())
+        .collect();
+    interfaces.sort();
+    interfaces
+        .iter()
+        .find_map(|path| fs::read_to_string(path.join("address")).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts() -> Facts {
+        Facts::from_pairs([
+            ("serial", "PF2ABCDE\n"),
+            ("vendor", "LENOVO"),
+            ("product", "To Be Filled By O.E.M."),
+            ("mac", "52:54:00:AB:12:CD\n"),
+        ])
+    }
+
+    #[test]
+    fn test_facts_are_normalised() {
+        let facts = facts();
+        assert_eq!(facts.get("serial"), Some("PF2ABCDE"));
+        assert_eq!(facts.get("mac"), Some("52:54:00:ab:12:cd"));
+        assert_eq!(facts.get("mac_last4"), Some("12cd"));
+        // Firmware placeholders are not facts
+        assert_eq!(facts.get("product"), None);
+    }
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let facts = facts();
+        assert_eq!(facts.render("lab-{{ serial }}").unwrap(), "lab-PF2ABCDE");
+        assert_eq!(
+            facts.render("{{vendor}}-{{ mac_last4 }}").unwrap(),
+            "LENOVO-12cd"
+        );
+        // Not variables: left alone
+        assert_eq!(
+            facts.render("{{ Not A Var }} {{").unwrap(),
+            "{{ Not A Var }} {{"
+        );
+
+        assert!(facts
+            .render("{{ hostname }}")
+            .unwrap_err()
+            .contains("unknown template variable 'hostname'"));
+        assert!(facts
+            .render("{{ product }}")
+            .unwrap_err()
+            .contains("not available on this machine"));
+    }
+
+    #[test]
+    fn test_render_json_skips_passwords() {
+        let mut config = serde_json::json!({
+            "hostname": "lab-{{ serial }}",
+            "user_password": "p{{ serial }}",
+            "additional_packages": ["{{ vendor }}"],
+            "parallel_downloads": 5
+        });
+        facts().render_json(&mut config).unwrap();
+        assert_eq!(config["hostname"], "lab-PF2ABCDE");
+        assert_eq!(config["user_password"], "p{{ serial }}");
+        assert_eq!(config["additional_packages"][0], "LENOVO");
+
+        let mut config = serde_json::json!({ "hostname": "{{ uuid }}" });
+        assert!(facts()
+            .render_json(&mut config)
+            .unwrap_err()
+            .starts_with("hostname: "));
+    }
+
+    #[test]
+    fn test_is_template_and_remote_output() {
+        assert!(is_template(r#"{"hostname": "lab-{{ serial }}"}"#));
+        assert!(!is_template(r#"{"user_password": "{{ x y }}"}"#));
+        assert!(!is_template(r#"{"hostname": "lab"}"#));
+
+        let facts = Facts::parse("serial=ABC123\nuuid=\nvendor=Dell Inc.\nmac=aa:bb:cc:dd:ee:ff\n");
+        assert_eq!(facts.get("serial"), Some("ABC123"));
+        assert_eq!(facts.get("uuid"), None);
+        assert_eq!(facts.get("mac_last4"), Some("eeff"));
+        assert!(remote_command().contains("/sys/class/dmi/id/product_serial"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Filesystem capability matrix
+//!
+//! What each root/home filesystem can do, so options that only make sense on
+//! some of them (automatic snapshots, an encrypted /boot read by GRUB) are
+//! offered and validated against the selected filesystem. The bash side
+//! mirrors the formatting and kernel check in `format_filesystem` (utils.sh).
+
+use crate::types::Filesystem;
+use std::fs;
+
+/// Release of the running kernel
+const PROC_OSRELEASE: &str = "/proc/sys/kernel/osrelease";
+
+/// Features of one filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Native copy-on-write snapshots
+    pub snapshots: bool,
+    /// Automatic snapshots with snapper and grub-btrfs (the Btrfs options)
+    pub snapper: bool,
+    /// Transparent compression
+    pub compression: bool,
+    /// Can be shrunk, e.g. to make room for another system later
+    pub shrink: bool,
+    /// GRUB can read kernels from it, needed when /boot lives on it
+    pub grub_readable: bool,
+    /// Oldest kernel (major, minor) that can mount it
+    pub min_kernel: Option<(u32, u32)>,
+}
+
+/// Capabilities of `fs`
+pub fn capabilities(fs: Filesystem) -> Capabilities {
+    let base = Capabilities {
+        snapshots: false,
+        snapper: false,
+        compression: false,
+        shrink: false,
+        grub_readable: true,
+        min_kernel: None,
+    };
+    match fs {
+        Filesystem::Ext4 => Capabilities {
+            shrink: true,
+            ..base
+        },
+        Filesystem::Xfs => base,
+        Filesystem::Btrfs => Capabilities {
+            snapshots: true,
+            snapper: true,
+            compression: true,
+            shrink: true,
+            ..base
+        },
+        Filesystem::F2fs => Capabilities {
+            compression: true,
+            ..base
+        },
+        Filesystem::Bcachefs => Capabilities {
+            snapshots: true,
+            compression: true,
+            grub_readable: false,
+            min_kernel: Some((6, 7)),
+            ..base
+        },
+    }
+}
+
+/// Short feature list, e.g. "snapshots, compression, needs Linux 6.7+"
+pub fn summary(fs: Filesystem) -> String {
+    let caps = capabilities(fs);
+    let mut features: Vec<String> = [
+        (caps.snapshots, "snapshots"),
+        (caps.compression, "compression"),
+        (caps.shrink, "shrinkable"),
+    ]
+    .iter()
+    .filter(|(has, _)| *has)
+    .map(|(_, name)| name.to_string())
+    .collect();
+    if !caps.grub_readable {
+        features.push("not readable by GRUB".to_string());
+    }
+    if let Some((major, minor)) = caps.min_kernel {
+        features.push(format!("needs Linux {}.{}+", major, minor));
+    }
+    if features.is_empty() {
+        "no snapshots or compression".to_string()
+    } else {
+        features.join(", ")
+    }
+}
+
+/// Release of the running kernel, e.g. "6.9.7-arch1-1"
+pub fn running_kernel() -> Option<String> {
+    fs::read_to_string(PROC_OSRELEASE)
+        .ok()
+        .map(|release| release.trim().to_string())
+}
+
+/// (major, minor) of a kernel release string
+pub fn kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()?
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok()?;
+    Some((major, minor))
+}
+
+/// Check that the running kernel (release `kernel`) can format and mount `fs`
+///
+/// An unknown kernel release is not an error: the scripts check again.
+pub fn check_kernel(fs: Filesystem, kernel: Option<&str>) -> Result<(), String> {
+    let (Some((major, minor)), Some(release)) = (capabilities(fs).min_kernel, kernel) else {
+        return Ok(());
+    };
+    match kernel_version(release) {
+        Some(running) if running < (major, minor) => Err(format!(
+            "{} needs Linux {}.{} or newer (running {})",
+            fs, major, minor, release
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check the filesystem choices against the options that depend on them
+///
+/// `home` is the home filesystem when /home is separate. Encrypted /boot
+/// lives on the root filesystem, so GRUB has to be able to read it.
+pub fn validate_filesystems(
+    root: Filesystem,
+    home: Option<Filesystem>,
+    snapshots: bool,
+    encrypted_boot: bool,
+    kernel: Option<&str>,
+) -> Result<(), String> {
+    let caps = capabilities(root);
+    if snapshots && !caps.snapper {
+        return Err(format!(
+            "Btrfs snapshots need a btrfs root filesystem, not {}",
+            root
+        ));
+    }
+    if encrypted_boot && !caps.grub_readable {
+        return Err(format!(
+            "Encrypted /boot needs a root filesystem GRUB can read: {} is not",
+            root
+        ));
+    }
+    check_kernel(root, kernel)?;
+    if let Some(home) = home {
+        check_kernel(home, kernel)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_capability_matrix() {
+        let snapshots: Vec<Filesystem> = Filesystem::iter()
+            .filter(|fs| capabilities(*fs).snapshots)
+            .collect();
+        assert_eq!(snapshots, [Filesystem::Btrfs, Filesystem::Bcachefs]);
+        assert!(Filesystem::iter()
+            .filter(|fs| *fs != Filesystem::Bcachefs)
+            .all(|fs| capabilities(fs).grub_readable && capabilities(fs).min_kernel.is_none()));
+
+        assert_eq!(
+            summary(Filesystem::Bcachefs),
+            "snapshots, compression, not readable by GRUB, needs Linux 6.7+"
+        );
+        assert_eq!(summary(Filesystem::Xfs), "no snapshots or compression");
+    }
+
+    #[test]
+    fn test_kernel_requirement() {
+        assert_eq!(kernel_version("6.6.30-2-lts"), Some((6, 6)));
+        assert_eq!(kernel_version("6.10.0-rc3"), Some((6, 10)));
+        assert_eq!(kernel_version("6.7"), Some((6, 7)));
+        assert_eq!(kernel_version("garbage"), None);
+
+        assert!(check_kernel(Filesystem::Bcachefs, Some("6.9.7-arch1-1")).is_ok());
+        assert_eq!(
+            check_kernel(Filesystem::Bcachefs, Some("6.6.30-2-lts")).unwrap_err(),
+            "bcachefs needs Linux 6.7 or newer (running 6.6.30-2-lts)"
+        );
+        assert!(check_kernel(Filesystem::Bcachefs, None).is_ok());
+        assert!(check_kernel(Filesystem::Ext4, Some("5.10.0")).is_ok());
+    }
+
+    #[test]
+    fn test_validate_filesystems() {
+        let old = Some("6.1.0");
+        assert!(validate_filesystems(Filesystem::Btrfs, None, true, true, old).is_ok());
+        assert!(
+            validate_filesystems(Filesystem::Ext4, None, true, false, None)
+                .unwrap_err()
+                .contains("btrfs root")
+        );
+        assert!(
+            validate_filesystems(Filesystem::Bcachefs, None, false, true, None)
+                .unwrap_err()
+                .contains("GRUB can read")
+        );
+        assert!(validate_filesystems(
+            Filesystem::Ext4,
+            Some(Filesystem::Bcachefs),
+            false,
+            false,
+            old
+        )
+        .unwrap_err()
+        .contains("Linux 6.7"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Previews of the themes offered by GRUB Theme Selection
+//!
+//! The selection dialog shows a low-resolution picture of each theme's boot
+//! menu so the choice is not made by name alone. Every theme has bundled
+//! pixel art of its menu in the theme's colours. A thumbnail converted from a
+//! real screenshot, `<Theme>.ppm` in [`PREVIEW_DIR`], is shown instead when
+//! present (see [`crate::components::ansi_art`] for the conversion).
+
+use crate::components::ansi_art::AnsiArt;
+use crate::types::GrubTheme;
+use std::path::Path;
+use std::sync::OnceLock;
+use strum::IntoEnumIterator;
+
+/// Directory of converted theme thumbnails, one `<Theme>.ppm` per theme
+pub const PREVIEW_DIR: &str = "/usr/share/archinstall-tui/grub-previews";
+
+/// Size of the bundled art in pixels; a cell shows two pixels stacked
+const WIDTH: usize = 64;
+const HEIGHT: usize = 32;
+
+/// Pixel rows of the menu entries, and their text length in pixels
+const ENTRIES: &[(usize, usize)] = &[(12, 26), (15, 18), (18, 30), (21, 14)];
+
+type Rgb = (u8, u8, u8);
+
+/// Colours of a theme's menu
+struct Palette {
+    /// Background at the top and bottom of the screen
+    top: Rgb,
+    bottom: Rgb,
+    /// Background artwork
+    decoration: Rgb,
+    /// Menu border
+    menu: Rgb,
+    /// Bar behind the selected entry
+    highlight: Rgb,
+    text: Rgb,
+    selected_text: Rgb,
+}
+
+/// Background artwork of a theme
+enum Decoration {
+    Plain,
+    /// Low-poly facets
+    Polygons,
+    /// CRT scanlines
+    Scanlines,
+    /// Neon perspective grid across the bottom
+    Grid,
+    /// Soft waves
+    Waves,
+}
+
+fn style(theme: GrubTheme) -> (Palette, Decoration) {
+    match theme {
+        GrubTheme::PolyDark => (
+            Palette {
+                top: (35, 38, 46),
+                bottom: (18, 20, 26),
+                decoration: (50, 54, 66),
+                menu: (90, 96, 110),
+                highlight: (64, 120, 200),
+                text: (200, 204, 212),
+                selected_text: (255, 255, 255),
+            },
+            Decoration::Polygons,
+        ),
+        GrubTheme::CyberExs => (
+            Palette {
+                top: (10, 16, 34),
+                bottom: (4, 6, 14),
+                decoration: (18, 42, 72),
+                menu: (0, 200, 255),
+                highlight: (0, 110, 160),
+                text: (140, 230, 255),
+                selected_text: (255, 255, 255),
+            },
+            Decoration::Scanlines,
+        ),
+        GrubTheme::CyberPunk => (
+            Palette {
+                top: (44, 8, 64),
+                bottom: (12, 2, 22),
+                decoration: (255, 0, 170),
+                menu: (0, 240, 255),
+                highlight: (255, 230, 0),
+                text: (255, 90, 205),
+                selected_text: (24, 0, 36),
+            },
+            Decoration::Grid,
+        ),
+        GrubTheme::HyperFluent => (
+            Palette {
+                top: (0, 94, 164),
+                bottom: (0, 42, 94),
+                decoration: (52, 140, 214),
+                menu: (200, 226, 246),
+                highlight: (130, 196, 244),
+                text: (240, 248, 255),
+                selected_text: (0, 42, 94),
+            },
+            Decoration::Waves,
+        ),
+        // GRUB's own text menu: grey on black, the selection inverted
+        GrubTheme::None => (
+            Palette {
+                top: (0, 0, 0),
+                bottom: (0, 0, 0),
+                decoration: (0, 0, 0),
+                menu: (170, 170, 170),
+                highlight: (170, 170, 170),
+                text: (170, 170, 170),
+                selected_text: (0, 0, 0),
+            },
+            Decoration::Plain,
+        ),
+    }
+}
+
+fn blend(from: Rgb, to: Rgb, step: usize, steps: usize) -> Rgb {
+    let mix = |a: u8, b: u8| (a as usize * (steps - step) + b as usize * step) / steps;
+    (
+        mix(from.0, to.0) as u8,
+        mix(from.1, to.1) as u8,
+        mix(from.2, to.2) as u8,
+    )
+}
+
+/// The bundled picture of a theme's boot menu
+pub fn bundled(theme: GrubTheme) -> AnsiArt {
+    let (palette, decoration) = style(theme);
+    let mut art = AnsiArt::filled(WIDTH, HEIGHT, palette.top);
+
+    for y in 0..HEIGHT {
+        let background = blend(palette.top, palette.bottom, y, HEIGHT - 1);
+        for x in 0..WIDTH {
+            let decorated = match decoration {
+                Decoration::Plain => false,
+                Decoration::Polygons => (x * 3 + y * 5) % 29 < 9 && (x + 2 * y) % 37 < 20,
+                Decoration::Scanlines => y % 3 == 0,
+                Decoration::Grid => {
+                    // Lines converge on the horizon at the screen's centre
+                    let depth = y.saturating_sub(HEIGHT * 2 / 3);
+                    let offset = x.abs_diff(WIDTH / 2) * 4 / (depth + 1);
+                    depth > 0 && (y % 3 == 0 || offset % 6 == 0)
+                }
+                Decoration::Waves => {
+                    let wave = ((x as f32 / 6.0).sin() * 2.5) as isize;
+                    let row = y as isize - wave;
+                    row == 6 || row == 26 || row == 28
+                }
+            };
+            art.set(
+                x,
+                y,
+                if decorated {
+                    palette.decoration
+                } else {
+                    background
+                },
+            );
+        }
+    }
+
+    // Title above the menu
+    art.fill(22, 4, 20, 1, palette.text);
+
+    // Menu border
+    let (left, right, top, bottom) = (10, WIDTH - 10, 9, 25);
+    art.fill(left, top, right - left, 1, palette.menu);
+    art.fill(left, bottom - 1, right - left, 1, palette.menu);
+    art.fill(left, top, 1, bottom - top, palette.menu);
+    art.fill(right - 1, top, 1, bottom - top, palette.menu);
+
+    // Entries, the first selected
+    for (index, &(row, length)) in ENTRIES.iter().enumerate() {
+        let text = if index == 0 {
+            art.fill(left + 2, row - 1, right - left - 4, 3, palette.highlight);
+            palette.selected_text
+        } else {
+            palette.text
+        };
+        art.fill(left + 4, row, length, 1, text);
+    }
+    art
+}
+
+/// The converted thumbnail of a theme in `dir`, else its bundled picture
+pub fn load(theme: GrubTheme, dir: &Path) -> AnsiArt {
+    let path = dir.join(format!("{}.ppm", theme));
+    match std::fs::read(&path) {
+        Ok(data) => AnsiArt::from_ppm(&data).unwrap_or_else(|e| {
+            log::warn!("Ignoring GRUB theme preview {}: {}", path.display(), e);
+            bundled(theme)
+        }),
+        Err(_) => bundled(theme),
+    }
+}
+
+/// Preview of a theme, loaded once for all themes
+pub fn preview(theme: GrubTheme) -> &'static AnsiArt {
+    static PREVIEWS: OnceLock<Vec<(GrubTheme, AnsiArt)>> = OnceLock::new();
+    let previews = PREVIEWS.get_or_init(|| {
+        GrubTheme::iter()
+            .map(|theme| (theme, load(theme, Path::new(PREVIEW_DIR))))
+            .collect()
+    });
+    previews
+        .iter()
+        .find(|(candidate, _)| *candidate == theme)
+        .map(|(_, art)| art)
+        .expect("every theme has a preview")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_previews_show_the_theme_colours() {
+        for theme in GrubTheme::iter() {
+            let (palette, _) = style(theme);
+            let art = bundled(theme);
+            assert_eq!((art.width(), art.height()), (WIDTH, HEIGHT));
+            // Selected entry on the highlight bar, the next one on the background
+            assert_eq!(art.pixel(20, 11), Some(palette.highlight));
+            assert_eq!(art.pixel(14, 12), Some(palette.selected_text));
+            assert_eq!(art.pixel(14, 15), Some(palette.text));
+            assert_eq!(art.pixel(10, 9), Some(palette.menu));
+        }
+        assert_ne!(bundled(GrubTheme::PolyDark), bundled(GrubTheme::CyberPunk));
+    }
+
+    #[test]
+    fn test_converted_thumbnail_replaces_bundled_art() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = b"P6\n1 2\n255\n".to_vec();
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+        std::fs::write(dir.path().join("CyberPunk.ppm"), data).unwrap();
+        std::fs::write(dir.path().join("PolyDark.ppm"), "not an image").unwrap();
+
+        assert_eq!(
+            load(GrubTheme::CyberPunk, dir.path()).pixel(0, 1),
+            Some((4, 5, 6))
+        );
+        assert_eq!(
+            load(GrubTheme::PolyDark, dir.path()),
+            bundled(GrubTheme::PolyDark)
+        );
+        assert_eq!(
+            load(GrubTheme::HyperFluent, dir.path()),
+            bundled(GrubTheme::HyperFluent)
+        );
+    }
+}
@@ -0,0 +1,684 @@
+//! Hardware detection
+//!
+//! Looks at the machine the installer runs on so hardware-specific choices
+//! can be proposed instead of asked for. Detects virtual machines the way
+//! `systemd-detect-virt --vm` does: ask systemd if it is available, otherwise
+//! read the DMI vendor strings the hypervisor exposes. Laptops are told apart
+//! by their DMI chassis type, and the CPU vendor decides the microcode
+//! package. Also describes the disks an installation could
+//! target, so the wrong drive is not wiped.
+
+use crate::disk::{DiskClass, DiskDevice};
+use crate::tools::resize::format_size;
+use crate::types::{GuestTools, Microcode};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::process::{Command, Stdio};
+
+/// DMI strings set by the hypervisor
+const DMI_SYS_VENDOR: &str = "/sys/class/dmi/id/sys_vendor";
+const DMI_PRODUCT_NAME: &str = "/sys/class/dmi/id/product_name";
+
+/// CPU description of the kernel, with the vendor of every CPU
+const CPUINFO: &str = "/proc/cpuinfo";
+
+/// SMBIOS chassis type of the machine
+const DMI_CHASSIS_TYPE: &str = "/sys/class/dmi/id/chassis_type";
+
+/// Chassis types running on battery: portable, laptop, notebook, hand held,
+/// sub notebook, tablet, convertible and detachable
+const PORTABLE_CHASSIS_TYPES: &[u8] = &[8, 9, 10, 11, 14, 30, 31, 32];
+
+/// Disks smaller than this are never offered as installation targets
+const MIN_INSTALL_DISK_SIZE: u64 = 1 << 30;
+
+/// Block devices listed by the kernel, read when lsblk is unavailable
+const SYS_BLOCK: &str = "/sys/block";
+
+/// Virtual machine platform the installer runs on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hypervisor {
+    /// QEMU, with or without KVM acceleration
+    Qemu,
+    VirtualBox,
+    Vmware,
+    HyperV,
+    /// A hypervisor without dedicated guest tools (Xen, Parallels, ...)
+    Other(String),
+}
+
+impl Hypervisor {
+    /// Parse the identifier printed by `systemd-detect-virt --vm`
+    pub fn from_detect_virt(id: &str) -> Option<Self> {
+        match id.trim() {
+            "" | "none" => None,
+            "qemu" | "kvm" => Some(Self::Qemu),
+            "oracle" => Some(Self::VirtualBox),
+            "vmware" => Some(Self::Vmware),
+            "microsoft" => Some(Self::HyperV),
+            other => Some(Self::Other(other.to_string())),
+        }
+    }
+
+    /// Recognise a hypervisor from the DMI system vendor and product name
+    pub fn from_dmi(sys_vendor: &str, product_name: &str) -> Option<Self> {
+        let vendor = sys_vendor.trim();
+        let product = product_name.trim();
+
+        if vendor == "QEMU" || product.contains("KVM") {
+            Some(Self::Qemu)
+        } else if vendor == "innotek GmbH" || product == "VirtualBox" {
+            Some(Self::VirtualBox)
+        } else if vendor.starts_with("VMware") {
+            Some(Self::Vmware)
+        } else if vendor == "Microsoft Corporation" && product == "Virtual Machine" {
+            Some(Self::HyperV)
+        } else {
+            None
+        }
+    }
+
+    /// Guest tools matching this hypervisor
+    pub fn guest_tools(&self) -> GuestTools {
+        match self {
+            Self::Qemu => GuestTools::Qemu,
+            Self::VirtualBox => GuestTools::VirtualBox,
+            Self::Vmware => GuestTools::Vmware,
+            Self::HyperV => GuestTools::HyperV,
+            Self::Other(_) => GuestTools::None,
+        }
+    }
+}
+
+impl fmt::Display for Hypervisor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Qemu => write!(f, "QEMU/KVM"),
+            Self::VirtualBox => write!(f, "VirtualBox"),
+            Self::Vmware => write!(f, "VMware"),
+            Self::HyperV => write!(f, "Hyper-V"),
+            Self::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Detect the hypervisor of the running system, `None` on bare metal
+pub fn detect_hypervisor() -> Option<Hypervisor> {
+    // systemd-detect-virt exits non-zero and prints "none" on bare metal
+    let output = Command::new("systemd-detect-virt")
+        .arg("--vm")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    if let Ok(output) = output {
+        return Hypervisor::from_detect_virt(&String::from_utf8_lossy(&output.stdout));
+    }
+
+    let read = |path| fs::read_to_string(path).unwrap_or_default();
+    Hypervisor::from_dmi(&read(DMI_SYS_VENDOR), &read(DMI_PRODUCT_NAME))
+}
+
+/// Whether an SMBIOS chassis type (e.g. "10\n") is a laptop or similar
+pub fn is_portable_chassis(chassis_type: &str) -> bool {
+    chassis_type
+        .trim()
+        .parse()
+        .is_ok_and(|chassis: u8| PORTABLE_CHASSIS_TYPES.contains(&chassis))
+}
+
+/// Whether the installer runs on a laptop
+pub fn detect_laptop() -> bool {
+    fs::read_to_string(DMI_CHASSIS_TYPE).is_ok_and(|chassis| is_portable_chassis(&chassis))
+}
+
+/// Microcode package for the CPU described by /proc/cpuinfo
+///
+/// `None` for other vendors and for ARM, whose cpuinfo has no `vendor_id`.
+pub fn cpu_microcode(cpuinfo: &str) -> Microcode {
+    let vendor = cpuinfo
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim() == "vendor_id")
+        .map(|(_, vendor)| vendor.trim());
+    match vendor {
+        Some("GenuineIntel") => Microcode::Intel,
+        Some("AuthenticAMD") => Microcode::Amd,
+        _ => Microcode::None,
+    }
+}
+
+/// Microcode package for the CPU the installer runs on
+pub fn detect_microcode() -> Microcode {
+    cpu_microcode(&fs::read_to_string(CPUINFO).unwrap_or_default())
+}
+
+/// Storage technology of a disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskKind {
+    Nvme,
+    Ssd,
+    Hdd,
+    /// eMMC module or SD card
+    Mmc,
+    /// Loop device backed by an image file
+    Loop,
+    /// lsblk was unavailable
+    Unknown,
+}
+
+impl fmt::Display for DiskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nvme => write!(f, "NVMe"),
+            Self::Ssd => write!(f, "SSD"),
+            Self::Hdd => write!(f, "HDD"),
+            Self::Mmc => write!(f, "eMMC/SD"),
+            Self::Loop => write!(f, "Loop"),
+            Self::Unknown => write!(f, "-"),
+        }
+    }
+}
+
+/// A whole disk and what is already on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskInfo {
+    /// Device path, e.g. /dev/nvme0n1
+    pub path: String,
+    /// Size in bytes, 0 when unknown
+    pub size: u64,
+    /// Model string reported by the drive
+    pub model: String,
+    /// Transport: sata, nvme, usb, virtio, ...
+    pub transport: String,
+    pub kind: DiskKind,
+    /// Device paths of the partitions on the disk
+    pub partitions: Vec<String>,
+    /// Operating systems or data found on the partitions
+    pub existing_os: Vec<String>,
+    /// Whether anything on the disk is mounted (e.g. the live ISO)
+    pub mounted: bool,
+    /// Whether the kernel reports the disk read-only
+    pub read_only: bool,
+}
+
+impl DiskInfo {
+    /// Disk known only by its path, used when lsblk is unavailable
+    fn from_path(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            size: 0,
+            model: String::new(),
+            transport: String::new(),
+            kind: DiskKind::Unknown,
+            partitions: Vec::new(),
+            existing_os: Vec::new(),
+            mounted: false,
+            read_only: false,
+        }
+    }
+
+    /// Whether the disk may be offered as an installation target
+    ///
+    /// Read-only disks, USB drives and anything below 1 GiB are most likely
+    /// the installation medium. Names that are not whole disks (zram, eMMC
+    /// boot areas) are never offered.
+    pub fn is_install_candidate(&self) -> bool {
+        !self.read_only
+            && self.transport != "usb"
+            && DiskDevice::parse(&self.path).is_ok()
+            && ((self.size == 0 && self.kind == DiskKind::Unknown)
+                || self.size >= MIN_INSTALL_DISK_SIZE)
+    }
+
+    /// Human-readable size, "-" when unknown
+    pub fn size_display(&self) -> String {
+        if self.size == 0 {
+            "-".to_string()
+        } else {
+            format_size(self.size)
+        }
+    }
+
+    /// Short summary of the disk contents for the selection table
+    pub fn contents(&self) -> String {
+        if !self.existing_os.is_empty() {
+            self.existing_os.join(", ")
+        } else if self.partitions.is_empty() {
+            "empty".to_string()
+        } else {
+            "unknown data".to_string()
+        }
+    }
+}
+
+impl fmt::Display for DiskInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.path, self.size_display())?;
+        if !self.model.is_empty() {
+            write!(f, " {}", self.model)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse `lsblk -J -b` output into the whole disks it lists
+///
+/// Expects the columns PATH, SIZE, TYPE, RO, TRAN, ROTA, MODEL, FSTYPE and
+/// MOUNTPOINT. The FSTYPE of each partition (probed by blkid) is used to
+/// guess what is on the disk; [`apply_os_prober`] refines that.
+pub fn parse_lsblk_disks(json: &str) -> Result<Vec<DiskInfo>, serde_json::Error> {
+    let value: Value = serde_json::from_str(json)?;
+    let devices = value["blockdevices"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(devices
+        .iter()
+        .filter(|dev| matches!(dev["type"].as_str(), Some("disk" | "loop")))
+        .map(|dev| {
+            let text = |key: &str| dev[key].as_str().unwrap_or_default().trim().to_string();
+            let transport = text("tran");
+            let kind = disk_kind(&text("path"), &transport, lsblk_flag(&dev["rota"]));
+
+            let children = dev["children"].as_array().cloned().unwrap_or_default();
+            let partitions: Vec<String> = children
+                .iter()
+                .filter(|child| child["type"].as_str() == Some("part"))
+                .filter_map(|child| child["path"].as_str().map(str::to_string))
+                .collect();
+
+            let mut existing_os = Vec::new();
+            for fstype in children.iter().filter_map(|c| c["fstype"].as_str()) {
+                if let Some(os) = os_from_fstype(fstype) {
+                    if !existing_os.contains(&os.to_string()) {
+                        existing_os.push(os.to_string());
+                    }
+                }
+            }
+
+            DiskInfo {
+                path: text("path"),
+                // Older lsblk versions print numbers as strings
+                size: dev["size"]
+                    .as_u64()
+                    .or_else(|| dev["size"].as_str().and_then(|s| s.parse().ok()))
+                    .unwrap_or(0),
+                model: text("model"),
+                transport,
+                kind,
+                partitions,
+                existing_os,
+                mounted: is_mounted(dev),
+                read_only: lsblk_flag(&dev["ro"]),
+            }
+        })
+        .collect())
+}
+
+/// Storage technology from the device name, transport and rotational flag
+fn disk_kind(path: &str, transport: &str, rotational: bool) -> DiskKind {
+    let class = DiskDevice::parse(path).ok().map(|disk| disk.class());
+    if transport == "nvme" || class == Some(DiskClass::Nvme) {
+        DiskKind::Nvme
+    } else if transport == "mmc" || class == Some(DiskClass::Mmc) {
+        DiskKind::Mmc
+    } else if class == Some(DiskClass::Loop) {
+        DiskKind::Loop
+    } else if rotational {
+        DiskKind::Hdd
+    } else {
+        DiskKind::Ssd
+    }
+}
+
+/// Describe a whole disk from its /sys/block attributes
+///
+/// `size` is in 512-byte sectors; `ro` and `rotational` are "0" or "1".
+/// Returns `None` for names that are not whole disks (partitions, zram,
+/// device-mapper).
+pub fn parse_sys_block(name: &str, size: &str, ro: &str, rotational: &str) -> Option<DiskInfo> {
+    let disk = DiskDevice::parse(name).ok()?;
+    let path = disk.path();
+    Some(DiskInfo {
+        size: size.trim().parse::<u64>().unwrap_or(0) * 512,
+        kind: disk_kind(&path, "", rotational.trim() == "1"),
+        read_only: ro.trim() == "1",
+        ..DiskInfo::from_path(&path)
+    })
+}
+
+/// Read a boolean column, printed as `true`/`false` or `"1"`/`"0"` by lsblk
+fn lsblk_flag(value: &Value) -> bool {
+    match value {
+        Value::Bool(flag) => *flag,
+        Value::Number(n) => n.as_u64() == Some(1),
+        Value::String(s) => s == "1",
+        _ => false,
+    }
+}
+
+/// Whether the device or anything below it (partitions, LUKS, LVM) is mounted
+fn is_mounted(dev: &Value) -> bool {
+    dev["mountpoint"].as_str().is_some_and(|m| !m.is_empty())
+        || dev["children"]
+            .as_array()
+            .is_some_and(|children| children.iter().any(is_mounted))
+}
+
+/// Guess what a partition holds from its filesystem type
+fn os_from_fstype(fstype: &str) -> Option<&'static str> {
+    match fstype {
+        "ntfs" | "BitLocker" => Some("Windows"),
+        "ext2" | "ext3" | "ext4" | "btrfs" | "xfs" | "f2fs" | "bcachefs" => Some("Linux"),
+        "apfs" | "hfsplus" => Some("macOS"),
+        "crypto_LUKS" => Some("LUKS"),
+        "LVM2_member" => Some("LVM"),
+        "linux_raid_member" => Some("RAID"),
+        _ => None,
+    }
+}
+
+/// Parse `os-prober` output into (partition, OS name) pairs
+///
+/// Lines look like `/dev/sda1@/efi/Microsoft/Boot/bootmgfw.efi:Windows Boot Manager:Windows:efi`.
+pub fn parse_os_prober(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let partition = fields.next()?.split('@').next()?.trim();
+            let name = fields.next()?.trim();
+            (!partition.is_empty() && !name.is_empty())
+                .then(|| (partition.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Replace filesystem guesses with the systems os-prober recognised
+pub fn apply_os_prober(disks: &mut [DiskInfo], found: &[(String, String)]) {
+    for disk in disks.iter_mut() {
+        let names: Vec<String> = found
+            .iter()
+            .filter(|(partition, _)| disk.partitions.contains(partition))
+            .map(|(_, name)| name.clone())
+            .collect();
+        if !names.is_empty() {
+            disk.existing_os = names;
+        }
+    }
+}
+
+/// Detect the disks an installation could target
+///
+/// In simulation these are [`crate::simulate::fake_disks`].
+pub fn detect_disks() -> Vec<DiskInfo> {
+    if crate::simulate::is_enabled() {
+        return crate::simulate::fake_disks();
+    }
+    let output = Command::new("lsblk")
+        .args([
+            "-J",
+            "-b",
+            "-o",
+            "PATH,SIZE,TYPE,RO,TRAN,ROTA,MODEL,FSTYPE,MOUNTPOINT",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    let mut disks: Vec<DiskInfo> = output
+        .ok()
+        .and_then(|output| parse_lsblk_disks(&String::from_utf8_lossy(&output.stdout)).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(DiskInfo::is_install_candidate)
+        .collect();
+
+    if disks.is_empty() {
+        let read = |name: &str, attribute: &str| {
+            fs::read_to_string(format!("{}/{}/{}", SYS_BLOCK, name, attribute)).unwrap_or_default()
+        };
+        let mut disks: Vec<DiskInfo> = fs::read_dir(SYS_BLOCK)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        parse_sys_block(
+                            &name,
+                            &read(&name, "size"),
+                            &read(&name, "ro"),
+                            &read(&name, "queue/rotational"),
+                        )
+                    })
+                    .filter(DiskInfo::is_install_candidate)
+                    .collect()
+            })
+            .unwrap_or_default();
+        disks.sort_by(|a, b| a.path.cmp(&b.path));
+        return disks;
+    }
+
+    // os-prober is not on every live medium and needs root; guesses remain
+    if let Ok(output) = Command::new("os-prober")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    {
+        let found = parse_os_prober(&String::from_utf8_lossy(&output.stdout));
+        apply_os_prober(&mut disks, &found);
+    }
+
+    disks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSBLK_JSON: &str = r#"{
+       "blockdevices": [
+          {"path":"/dev/loop0", "size":838860800, "type":"loop", "ro":true, "tran":null,
+           "rota":false, "model":null, "fstype":"squashfs", "mountpoint":"/run/archiso/airootfs"},
+          {"path":"/dev/sda", "size":"1000204886016", "type":"disk", "ro":"0", "tran":"sata",
+           "rota":"1", "model":"WDC WD10EZEX-08W", "fstype":null, "mountpoint":null,
+           "children": [
+              {"path":"/dev/sda1", "size":104857600, "type":"part", "ro":false, "tran":null,
+               "rota":true, "model":null, "fstype":"vfat", "mountpoint":null},
+              {"path":"/dev/sda2", "size":999000000000, "type":"part", "ro":false, "tran":null,
+               "rota":true, "model":null, "fstype":"ntfs", "mountpoint":null}
+           ]
+          },
+          {"path":"/dev/nvme0n1", "size":512110190592, "type":"disk", "ro":false, "tran":"nvme",
+           "rota":false, "model":"Samsung SSD 980 PRO 500GB", "fstype":null, "mountpoint":null,
+           "children": [
+              {"path":"/dev/nvme0n1p1", "size":536870912, "type":"part", "ro":false, "tran":"nvme",
+               "rota":false, "model":null, "fstype":"crypto_LUKS", "mountpoint":null,
+               "children": [
+                  {"path":"/dev/mapper/root", "size":536870912, "type":"crypt", "ro":false,
+                   "tran":null, "rota":false, "model":null, "fstype":"ext4", "mountpoint":"/mnt"}
+               ]
+              }
+           ]
+          },
+          {"path":"/dev/sdb", "size":32010928128, "type":"disk", "ro":false, "tran":"usb",
+           "rota":false, "model":"Ultra Fit", "fstype":"iso9660", "mountpoint":null}
+       ]
+    }"#;
+
+    #[test]
+    fn test_parse_lsblk_disks() {
+        let disks = parse_lsblk_disks(LSBLK_JSON).unwrap();
+        assert_eq!(disks.len(), 4);
+
+        // The live system's squashfs; read-only, so never a candidate
+        let airootfs = &disks[0];
+        assert_eq!(airootfs.kind, DiskKind::Loop);
+        assert!(airootfs.read_only);
+
+        let hdd = &disks[1];
+        assert_eq!(hdd.path, "/dev/sda");
+        assert_eq!(hdd.size, 1_000_204_886_016);
+        assert_eq!(hdd.kind, DiskKind::Hdd);
+        assert_eq!(hdd.partitions, vec!["/dev/sda1", "/dev/sda2"]);
+        assert_eq!(hdd.existing_os, vec!["Windows"]);
+        assert!(!hdd.mounted);
+
+        let nvme = &disks[2];
+        assert_eq!(nvme.kind, DiskKind::Nvme);
+        assert_eq!(nvme.model, "Samsung SSD 980 PRO 500GB");
+        assert_eq!(nvme.existing_os, vec!["LUKS"]);
+        assert!(nvme.mounted);
+        assert_eq!(
+            nvme.to_string(),
+            "/dev/nvme0n1 (476.9 GiB) Samsung SSD 980 PRO 500GB"
+        );
+
+        let usb = &disks[3];
+        assert_eq!(usb.kind, DiskKind::Ssd);
+        assert_eq!(usb.contents(), "empty");
+    }
+
+    #[test]
+    fn test_install_candidates_skip_usb_and_tiny_disks() {
+        let disks = parse_lsblk_disks(LSBLK_JSON).unwrap();
+        let candidates: Vec<_> = disks
+            .iter()
+            .filter(|d| d.is_install_candidate())
+            .map(|d| d.path.as_str())
+            .collect();
+        assert_eq!(candidates, vec!["/dev/sda", "/dev/nvme0n1"]);
+
+        let tiny = DiskInfo {
+            size: 512 << 20,
+            ..DiskInfo::from_path("/dev/vdb")
+        };
+        assert!(!tiny.is_install_candidate());
+        assert!(DiskInfo::from_path("/dev/vda").is_install_candidate());
+        assert!(!DiskInfo::from_path("/dev/zram0").is_install_candidate());
+    }
+
+    #[test]
+    fn test_sys_block_fallback_accepts_emmc_nvme_namespaces_and_loop() {
+        let emmc = parse_sys_block("mmcblk0", "61071360\n", "0\n", "0\n").unwrap();
+        assert_eq!(emmc.path, "/dev/mmcblk0");
+        assert_eq!(emmc.kind, DiskKind::Mmc);
+        assert_eq!(emmc.size, 61_071_360 * 512);
+        assert!(emmc.is_install_candidate());
+
+        let namespace = parse_sys_block("nvme0n2", "209715200", "0", "0").unwrap();
+        assert_eq!(namespace.kind, DiskKind::Nvme);
+        assert!(namespace.is_install_candidate());
+
+        let image = parse_sys_block("loop3", "41943040", "0", "0").unwrap();
+        assert_eq!(image.kind, DiskKind::Loop);
+        assert!(image.is_install_candidate());
+
+        // Unattached loop devices are empty; boot areas and zram are skipped
+        assert!(!parse_sys_block("loop7", "0", "0", "0")
+            .unwrap()
+            .is_install_candidate());
+        assert!(parse_sys_block("mmcblk0boot0", "8192", "1", "0").is_none());
+        assert!(parse_sys_block("zram0", "8388608", "0", "0").is_none());
+        assert!(parse_sys_block("sda1", "2048", "0", "1").is_none());
+    }
+
+    #[test]
+    fn test_os_prober_replaces_guesses() {
+        let output =
+            "/dev/sda1@/efi/Microsoft/Boot/bootmgfw.efi:Windows Boot Manager:Windows:efi\n\
+                      /dev/sdc2:Ubuntu 24.04 LTS:Ubuntu:linux\n";
+        let found = parse_os_prober(output);
+        assert_eq!(
+            found,
+            vec![
+                ("/dev/sda1".to_string(), "Windows Boot Manager".to_string()),
+                ("/dev/sdc2".to_string(), "Ubuntu 24.04 LTS".to_string()),
+            ]
+        );
+
+        let mut disks = parse_lsblk_disks(LSBLK_JSON).unwrap();
+        apply_os_prober(&mut disks, &found);
+        assert_eq!(disks[1].contents(), "Windows Boot Manager");
+        assert_eq!(disks[2].contents(), "LUKS");
+    }
+
+    #[test]
+    fn test_from_detect_virt() {
+        assert_eq!(Hypervisor::from_detect_virt("none\n"), None);
+        assert_eq!(Hypervisor::from_detect_virt("kvm\n"), Some(Hypervisor::Qemu));
+        assert_eq!(
+            Hypervisor::from_detect_virt("oracle"),
+            Some(Hypervisor::VirtualBox)
+        );
+        assert_eq!(
+            Hypervisor::from_detect_virt("microsoft"),
+            Some(Hypervisor::HyperV)
+        );
+        assert_eq!(
+            Hypervisor::from_detect_virt("xen"),
+            Some(Hypervisor::Other("xen".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_dmi() {
+        assert_eq!(
+            Hypervisor::from_dmi("QEMU\n", "Standard PC (Q35 + ICH9, 2009)\n"),
+            Some(Hypervisor::Qemu)
+        );
+        assert_eq!(
+            Hypervisor::from_dmi("innotek GmbH", "VirtualBox"),
+            Some(Hypervisor::VirtualBox)
+        );
+        assert_eq!(
+            Hypervisor::from_dmi("VMware, Inc.", "VMware Virtual Platform"),
+            Some(Hypervisor::Vmware)
+        );
+        assert_eq!(
+            Hypervisor::from_dmi("Microsoft Corporation", "Virtual Machine"),
+            Some(Hypervisor::HyperV)
+        );
+        // A Surface laptop is Microsoft hardware, not Hyper-V
+        assert_eq!(
+            Hypervisor::from_dmi("Microsoft Corporation", "Surface Laptop 5"),
+            None
+        );
+        assert_eq!(Hypervisor::from_dmi("Dell Inc.", "XPS 13 9310"), None);
+    }
+
+    #[test]
+    fn test_portable_chassis() {
+        assert!(is_portable_chassis("10\n"));
+        assert!(is_portable_chassis("31"));
+        // Desktop, tower and rack mount chassis
+        assert!(!is_portable_chassis("3\n"));
+        assert!(!is_portable_chassis("7"));
+        assert!(!is_portable_chassis("23"));
+        assert!(!is_portable_chassis(""));
+    }
+
+    #[test]
+    fn test_cpu_microcode() {
+        let intel = "processor\t: 0\nvendor_id\t: GenuineIntel\ncpu family\t: 6\n";
+        assert_eq!(cpu_microcode(intel), Microcode::Intel);
+        let amd = "processor\t: 0\nvendor_id\t: AuthenticAMD\n\nprocessor\t: 1\n";
+        assert_eq!(cpu_microcode(amd), Microcode::Amd);
+        // aarch64 names the implementer instead
+        let arm = "processor\t: 0\nCPU implementer\t: 0x41\nCPU part\t: 0xd08\n";
+        assert_eq!(cpu_microcode(arm), Microcode::None);
+        assert_eq!(cpu_microcode(""), Microcode::None);
+    }
+
+    #[test]
+    fn test_guest_tools_mapping() {
+        assert_eq!(Hypervisor::Qemu.guest_tools(), GuestTools::Qemu);
+        assert_eq!(Hypervisor::Vmware.guest_tools(), GuestTools::Vmware);
+        assert_eq!(
+            Hypervisor::Other("xen".to_string()).guest_tools(),
+            GuestTools::None
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Hibernation (suspend to disk)
+//!
+//! Hibernating writes the memory image to disk swap and the initramfs reads
+//! it back on the next boot, so the option needs a swap partition or file
+//! at least as large as the RAM, a `resume=` kernel parameter pointing at it
+//! (plus `resume_offset=` for a swap file) and an initramfs that resumes:
+//! the `resume` hook, or the `systemd` hook which resumes on its own. zram
+//! swap lives in RAM and cannot hold the image. chroot_config.sh finds the
+//! swap in the new fstab and writes the parameters for GRUB and
+//! systemd-boot.
+
+use crate::tools::resize::format_size;
+use std::fs;
+
+/// Swap Size values relative to the installed memory
+pub const EQUAL_TO_RAM: &str = "Equal to RAM";
+pub const DOUBLE_RAM: &str = "Double RAM";
+
+/// Packages that set up swap on zram
+pub const ZRAM_PACKAGES: &[&str] = &["zram-generator", "zramswap", "systemd-swap"];
+
+const MEMINFO: &str = "/proc/meminfo";
+
+/// Total memory in bytes from the contents of /proc/meminfo
+pub fn parse_meminfo(content: &str) -> Option<u64> {
+    let line = content.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    kib.checked_mul(1024)
+}
+
+/// Memory of the machine the toolkit runs on
+pub fn total_memory() -> Option<u64> {
+    parse_meminfo(&fs::read_to_string(MEMINFO).ok()?)
+}
+
+/// Swap Size in bytes, `None` when it is not a size or the memory is unknown
+pub fn swap_bytes(swap_size: &str, memory: Option<u64>) -> Option<u64> {
+    match swap_size.trim() {
+        EQUAL_TO_RAM => memory,
+        DOUBLE_RAM => memory?.checked_mul(2),
+        size => match crate::lvm::parse_size(size) {
+            Ok(Some(crate::lvm::Size::Bytes(bytes))) => Some(bytes),
+            _ => None,
+        },
+    }
+}
+
+/// Whether the packages set up swap on zram
+pub fn uses_zram(packages: &str) -> bool {
+    packages
+        .split_whitespace()
+        .any(|package| ZRAM_PACKAGES.contains(&package))
+}
+
+/// Check that the swap can hold the memory image
+///
+/// `memory` is the RAM of the target machine when it is known; the install
+/// engine grows a smaller swap partition to the RAM size anyway, so without
+/// it only the presence of disk swap is checked.
+pub fn check_swap(
+    swap: bool,
+    swap_size: &str,
+    memory: Option<u64>,
+    zram: bool,
+) -> Result<(), String> {
+    if !swap {
+        return Err(if zram {
+            "Hibernation needs disk swap: zram swap is in RAM and cannot hold the image, \
+             enable Swap"
+                .to_string()
+        } else {
+            "Hibernation needs a swap partition: enable Swap".to_string()
+        });
+    }
+    match (swap_bytes(swap_size, memory), memory) {
+        (Some(size), Some(memory)) if size < memory => Err(format!(
+            "Hibernation needs swap at least as large as the RAM: Swap Size {} is \
+             smaller than {}",
+            swap_size.trim(),
+            format_size(memory)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that custom initramfs hooks can resume from swap
+pub fn check_hooks(hooks: &[String]) -> Result<(), String> {
+    if hooks
+        .iter()
+        .any(|hook| hook == "resume" || hook == "systemd")
+    {
+        Ok(())
+    } else {
+        Err("Hibernation needs the resume hook (or the systemd hook)".to_string())
+    }
+}
+
+/// Warning for a swap setup that works but may surprise, if any
+pub fn zram_warning(zram: bool) -> Option<&'static str> {
+    zram.then_some(
+        "zram swap is used first while running, but the image is always written to disk swap",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    #[test]
+    fn test_parse_meminfo_and_sizes() {
+        let meminfo = "MemTotal:       16318004 kB\nMemFree:         1048576 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(16318004 * 1024));
+        assert_eq!(parse_meminfo("MemFree: 1 kB\n"), None);
+
+        assert_eq!(swap_bytes("8GB", None), Some(8 * GIB));
+        assert_eq!(swap_bytes(EQUAL_TO_RAM, Some(4 * GIB)), Some(4 * GIB));
+        assert_eq!(swap_bytes(DOUBLE_RAM, Some(4 * GIB)), Some(8 * GIB));
+        assert_eq!(swap_bytes(DOUBLE_RAM, None), None);
+        assert_eq!(swap_bytes("N/A", Some(GIB)), None);
+    }
+
+    #[test]
+    fn test_check_swap() {
+        let memory = Some(16 * GIB);
+        assert!(check_swap(true, "16GB", memory, false).is_ok());
+        assert!(check_swap(true, EQUAL_TO_RAM, memory, false).is_ok());
+        assert!(check_swap(true, "8GB", memory, false)
+            .unwrap_err()
+            .contains("16.0 GiB"));
+        // Unknown memory: the engine sizes the partition
+        assert!(check_swap(true, "2GB", None, false).is_ok());
+
+        assert!(check_swap(false, "N/A", memory, true)
+            .unwrap_err()
+            .contains("zram"));
+        assert!(uses_zram("htop zram-generator"));
+        assert!(!uses_zram("htop zramctl-gui"));
+    }
+
+    #[test]
+    fn test_check_hooks() {
+        let hooks =
+            |list: &str| -> Vec<String> { list.split_whitespace().map(str::to_string).collect() };
+        assert!(check_hooks(&hooks("base udev block resume filesystems")).is_ok());
+        assert!(check_hooks(&hooks("base systemd block filesystems")).is_ok());
+        assert!(check_hooks(&hooks("base udev block filesystems")).is_err());
+    }
+}
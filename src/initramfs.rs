@@ -0,0 +1,245 @@
+//! Initramfs hooks and modules
+//!
+//! The Initramfs Hooks option replaces the HOOKS array chroot_config.sh
+//! writes to mkinitcpio.conf; `auto` keeps the list the install engine
+//! builds from the storage layout. [`validate_hooks`] enforces the orderings
+//! mkinitcpio relies on (block devices before the hooks that assemble them,
+//! encrypt before lvm2, everything before filesystems) and [`check_storage`]
+//! makes sure the hooks can unlock and assemble the chosen layout.
+//! Initramfs Modules and Early KMS add to the MODULES array once the GPU
+//! drivers are installed, and the engine regenerates the presets after.
+
+use crate::types::{AutoToggle, PartitionScheme};
+
+/// Option value that leaves the hook list to the install engine
+pub const AUTO: &str = "auto";
+
+/// Hooks that unlock LUKS devices; only one of them may be used
+const ENCRYPT_HOOKS: &[&str] = &["encrypt", "sd-encrypt", "plymouth-encrypt"];
+
+/// Hooks that have to run before others, as (earlier, later) groups
+const ORDER: &[(&[&str], &[&str])] = &[
+    (
+        &["block"],
+        &[
+            "mdadm_udev",
+            "encrypt",
+            "sd-encrypt",
+            "plymouth-encrypt",
+            "lvm2",
+            "resume",
+            "filesystems",
+        ],
+    ),
+    (
+        &["keyboard", "keymap", "sd-vconsole", "plymouth"],
+        ENCRYPT_HOOKS,
+    ),
+    (
+        &["mdadm_udev"],
+        &["encrypt", "sd-encrypt", "plymouth-encrypt", "lvm2"],
+    ),
+    (ENCRYPT_HOOKS, &["lvm2", "resume", "filesystems"]),
+    (&["lvm2"], &["resume", "filesystems"]),
+    (&["resume"], &["filesystems"]),
+    (&["filesystems"], &["fsck"]),
+];
+
+/// Hooks of the Initramfs Hooks option, `None` when the engine builds them
+pub fn parse_hooks(value: &str) -> Result<Option<Vec<String>>, String> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case(AUTO) {
+        return Ok(None);
+    }
+    let hooks: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+    validate_hooks(&hooks)?;
+    Ok(Some(hooks))
+}
+
+/// Check hook names and the orderings mkinitcpio depends on
+pub fn validate_hooks(hooks: &[String]) -> Result<(), String> {
+    for (index, hook) in hooks.iter().enumerate() {
+        if !hook
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        {
+            return Err(format!("'{}' is not a valid hook name", hook));
+        }
+        if hooks[..index].contains(hook) {
+            return Err(format!("Hook '{}' is listed twice", hook));
+        }
+    }
+    let position = |hook: &str| hooks.iter().position(|h| h == hook);
+
+    if let Some(index) = position("base") {
+        if index != 0 {
+            return Err("The base hook must come first".to_string());
+        }
+    }
+    match (position("udev"), position("systemd")) {
+        (Some(_), Some(_)) => {
+            return Err("Use either the udev or the systemd hook, not both".to_string())
+        }
+        (None, None) => return Err("The hooks need udev or systemd".to_string()),
+        _ => {}
+    }
+    if position("filesystems").is_none() {
+        return Err("The filesystems hook is required to mount the root".to_string());
+    }
+
+    let encrypt: Vec<&str> = ENCRYPT_HOOKS
+        .iter()
+        .copied()
+        .filter(|hook| position(hook).is_some())
+        .collect();
+    if encrypt.len() > 1 {
+        return Err(format!("Use only one of {}", encrypt.join(", ")));
+    }
+    if let Some(hook) = encrypt.first() {
+        let needs = if *hook == "sd-encrypt" {
+            "systemd"
+        } else {
+            "udev"
+        };
+        if position(needs).is_none() {
+            return Err(format!("The {} hook needs the {} hook", hook, needs));
+        }
+    }
+
+    for (earlier, later) in ORDER {
+        for before in earlier.iter() {
+            let Some(before_index) = position(before) else {
+                continue;
+            };
+            for after in later.iter() {
+                if position(after).is_some_and(|after_index| after_index < before_index) {
+                    return Err(format!("Hook '{}' must come after '{}'", after, before));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the hooks can unlock and assemble the storage layout
+///
+/// Manual partitioning counts as encrypted when Encryption is on; whether
+/// it uses LVM or RAID is not known, so those hooks are up to the user.
+pub fn check_storage(
+    hooks: &[String],
+    scheme: PartitionScheme,
+    encryption: AutoToggle,
+) -> Result<(), String> {
+    let has = |hook: &str| hooks.iter().any(|h| h == hook);
+    let encrypted = match scheme {
+        PartitionScheme::Manual => encryption == AutoToggle::Yes,
+        _ => scheme.uses_encryption(),
+    };
+    if encrypted && !ENCRYPT_HOOKS.iter().any(|hook| has(hook)) {
+        return Err(format!(
+            "{} is encrypted: add encrypt, sd-encrypt or plymouth-encrypt",
+            scheme
+        ));
+    }
+    if scheme.uses_lvm() && !has("lvm2") {
+        return Err(format!("{} uses LVM: add the lvm2 hook", scheme));
+    }
+    if scheme.requires_raid() && !has("mdadm_udev") {
+        return Err(format!("{} uses RAID: add the mdadm_udev hook", scheme));
+    }
+    Ok(())
+}
+
+/// Kernel modules of the Initramfs Modules option
+///
+/// A trailing `?` marks a module mkinitcpio may skip when it is missing.
+pub fn parse_modules(value: &str) -> Result<Vec<String>, String> {
+    let mut modules: Vec<String> = Vec::new();
+    for module in value.split_whitespace() {
+        let name = module.strip_suffix('?').unwrap_or(module);
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("'{}' is not a valid module name", module));
+        }
+        if modules.iter().any(|m| m == module) {
+            return Err(format!("Module '{}' is listed twice", module));
+        }
+        modules.push(module.to_string());
+    }
+    Ok(modules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks(value: &str) -> Vec<String> {
+        value.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        assert_eq!(parse_hooks("auto"), Ok(None));
+        assert_eq!(parse_hooks(""), Ok(None));
+        let default = "base udev autodetect microcode modconf kms keyboard keymap consolefont block filesystems fsck";
+        assert_eq!(parse_hooks(default), Ok(Some(hooks(default))));
+        let luks_lvm =
+            "base udev autodetect modconf kms keyboard keymap block encrypt lvm2 filesystems fsck";
+        assert!(parse_hooks(luks_lvm).is_ok());
+        let systemd = "base systemd autodetect modconf kms keyboard sd-vconsole block sd-encrypt lvm2 filesystems fsck";
+        assert!(parse_hooks(systemd).is_ok());
+
+        assert_eq!(
+            parse_hooks("base udev block lvm2 encrypt filesystems"),
+            Err("Hook 'lvm2' must come after 'encrypt'".to_string())
+        );
+        assert_eq!(
+            parse_hooks("base udev encrypt block filesystems"),
+            Err("Hook 'encrypt' must come after 'block'".to_string())
+        );
+        assert_eq!(
+            parse_hooks("base udev block fsck filesystems"),
+            Err("Hook 'fsck' must come after 'filesystems'".to_string())
+        );
+        assert!(parse_hooks("udev base block filesystems").is_err());
+        assert!(parse_hooks("base udev block").is_err());
+        assert!(parse_hooks("base udev systemd block filesystems").is_err());
+        assert!(parse_hooks("base systemd block encrypt filesystems").is_err());
+        assert!(parse_hooks("base udev block block filesystems").is_err());
+        assert!(parse_hooks("base udev block Filesystems").is_err());
+    }
+
+    #[test]
+    fn test_check_storage() {
+        let plain = hooks("base udev block filesystems");
+        let luks_lvm = hooks("base udev block encrypt lvm2 filesystems");
+        assert!(check_storage(&plain, PartitionScheme::AutoSimple, AutoToggle::No).is_ok());
+        assert!(check_storage(&luks_lvm, PartitionScheme::AutoLuksLvm, AutoToggle::No).is_ok());
+        assert!(check_storage(&plain, PartitionScheme::AutoSimpleLuks, AutoToggle::No).is_err());
+        assert!(check_storage(&plain, PartitionScheme::AutoLvm, AutoToggle::No).is_err());
+        assert!(
+            check_storage(&luks_lvm, PartitionScheme::AutoRaidLvmLuks, AutoToggle::No).is_err()
+        );
+        assert!(check_storage(&plain, PartitionScheme::Manual, AutoToggle::Yes).is_err());
+        assert!(check_storage(&plain, PartitionScheme::Manual, AutoToggle::No).is_ok());
+    }
+
+    #[test]
+    fn test_parse_modules() {
+        assert_eq!(parse_modules(""), Ok(Vec::new()));
+        assert_eq!(
+            parse_modules("amdgpu nvme? crc32c-intel"),
+            Ok(vec![
+                "amdgpu".to_string(),
+                "nvme?".to_string(),
+                "crc32c-intel".to_string()
+            ])
+        );
+        assert!(parse_modules("i915 i915").is_err());
+        assert!(parse_modules("../evil").is_err());
+        assert!(parse_modules("?").is_err());
+    }
+}
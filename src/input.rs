@@ -2,35 +2,12 @@
 //!
 //! Handles different types of user input including popups, text input, and selection dialogs.
 
+use crate::components::table::TableState;
+use crate::config::options;
 use crate::config::Package;
-use crate::types::{
-    AurHelper, AutoToggle, Bootloader, BootMode, DesktopEnvironment, DisplayManager, Filesystem,
-    GpuDriver, GrubTheme, Kernel, PartitionScheme, PlymouthTheme, SnapshotFrequency, Toggle,
-};
-use ratatui::widgets::ListState;
-use strum::IntoEnumIterator;
-
-/// Information about a partition
-#[derive(Debug, Clone)]
-pub struct PartitionInfo {
-    #[allow(dead_code)]
-    pub name: String,
-    #[allow(dead_code)]
-    pub size: String,
-}
-
-/// Layout information for manually partitioned disks
-#[derive(Debug, Clone)]
-pub struct PartitionLayout {
-    pub partitions: Vec<PartitionInfo>,
-    pub table_type: String,
-    #[allow(dead_code)]
-    pub has_esp: bool,
-    #[allow(dead_code)]
-    pub has_boot: bool,
-    #[allow(dead_code)]
-    pub has_root: bool,
-}
+use crate::error::{self, ArchInstallError};
+use crate::hardware::{detect_disks, DiskInfo};
+use crate::proxy::Proxy;
 
 /// Types of input dialogs
 #[derive(Debug, Clone, PartialEq)]
@@ -40,6 +17,8 @@ pub enum InputType {
         field_name: String,
         current_value: String,
         placeholder: String,
+        /// Why the current value is invalid, updated on every key press
+        error: Option<String>,
     },
     /// Selection from predefined options
     Selection {
@@ -50,17 +29,46 @@ pub enum InputType {
     /// Disk selection with detection
     DiskSelection {
         current_value: String,
-        available_disks: Vec<String>,
-        scroll_state: crate::scrolling::ScrollState,
+        available_disks: Vec<DiskInfo>,
+        table: TableState,
     },
     /// Multi-disk selection for RAID and manual partitioning
     MultiDiskSelection {
+        /// Device paths of the selected disks
         selected_disks: Vec<String>,
-        available_disks: Vec<String>,
-        scroll_state: crate::scrolling::ScrollState,
+        available_disks: Vec<DiskInfo>,
+        table: TableState,
         min_disks: usize,
         max_disks: usize,
     },
+    /// Curated package group toggles
+    PackageGroupSelection {
+        /// Ids of the selected groups, in selection order
+        selected: Vec<String>,
+        scroll_state: crate::scrolling::ScrollState,
+        /// Whether the highlighted group's packages are listed
+        show_packages: bool,
+    },
+    /// Service toggles
+    ServiceSelection {
+        /// Units of the selected services
+        selected: Vec<String>,
+        scroll_state: crate::scrolling::ScrollState,
+    },
+    /// Checkboxes over fixed choices, submitted as one value joined by
+    /// [`join_multi_select`]
+    MultiSelect {
+        field_name: String,
+        options: Vec<String>,
+        /// Whether each option is checked, by position in `options`
+        checked: Vec<bool>,
+        scroll_state: crate::scrolling::ScrollState,
+        /// Fewest and most options that may be checked
+        min: usize,
+        max: usize,
+        /// Why the last toggle or submit was refused
+        error: Option<String>,
+    },
     /// Package selection (for additional packages)
     PackageSelection {
         field_name: String,
@@ -69,8 +77,10 @@ pub enum InputType {
         scroll_offset: usize,
         package_list: String,
         is_pacman: bool,
+        /// Proxy the searches download through
+        proxy: Proxy,
         search_results: Vec<Package>,
-        list_state: ListState,
+        results: TableState,
         show_search_results: bool,
     },
     /// Warning dialog with information and acknowledgment
@@ -79,11 +89,21 @@ pub enum InputType {
         message: Vec<String>,
         acknowledged: bool,
     },
-    /// Password input with obscuring
+    /// Error explained to the user; closing it submits nothing
+    Error { message: Vec<String> },
+    /// Password input with obscuring, strength estimate and confirmation
     PasswordInput {
         field_name: String,
         current_value: String,
         placeholder: String,
+        /// Second entry that must match `current_value`
+        confirm_value: String,
+        /// True once the first entry was accepted and the confirmation is being typed
+        confirming: bool,
+        /// Minimum length policy applied before the confirmation step
+        min_length: usize,
+        /// Error from the last submit attempt (policy or mismatch)
+        error: Option<String>,
     },
 }
 
@@ -111,18 +131,26 @@ impl InputDialog {
     /// Handle keyboard input for the dialog
     pub fn handle_input(&mut self, key_event: crossterm::event::KeyEvent) -> InputResult {
         match &mut self.input_type {
-            InputType::TextInput { current_value, .. } => match key_event.code {
-                crossterm::event::KeyCode::Enter => {
+            InputType::TextInput {
+                field_name,
+                current_value,
+                error,
+                ..
+            } => match key_event.code {
+                // An invalid value keeps the dialog open with its error shown
+                crossterm::event::KeyCode::Enter if error.is_none() => {
                     return InputResult::Confirm(current_value.clone());
                 }
                 crossterm::event::KeyCode::Esc => {
                     return InputResult::Cancel;
                 }
                 crossterm::event::KeyCode::Backspace => {
-                    current_value.pop();
+                    delete_last_char(current_value);
+                    *error = validate_text_field(field_name, current_value);
                 }
-                crossterm::event::KeyCode::Char(c) => {
-                    current_value.push(c);
+                crossterm::event::KeyCode::Char(c) if is_text_key(&key_event) => {
+                    insert_char(current_value, c);
+                    *error = validate_text_field(field_name, current_value);
                 }
                 _ => {}
             },
@@ -166,47 +194,42 @@ impl InputDialog {
                 _ => {}
             },
             InputType::DiskSelection {
-                scroll_state,
+                table,
                 available_disks,
                 ..
             } => match key_event.code {
-                crossterm::event::KeyCode::Up => {
-                    scroll_state.move_up();
-                }
-                crossterm::event::KeyCode::Down => {
-                    scroll_state.move_down();
-                }
                 crossterm::event::KeyCode::Enter => {
-                    return InputResult::Confirm(
-                        available_disks[scroll_state.selected_index].clone(),
-                    );
+                    if let Some(disk) = table
+                        .selected_row()
+                        .and_then(|row| available_disks.get(row))
+                    {
+                        return InputResult::Confirm(disk.path.clone());
+                    }
                 }
                 crossterm::event::KeyCode::Esc => {
                     return InputResult::Cancel;
                 }
-                _ => {}
+                code => navigate_table(table, code),
             },
             InputType::MultiDiskSelection {
                 selected_disks,
                 available_disks,
-                scroll_state,
+                table,
                 min_disks,
                 max_disks,
                 ..
             } => match key_event.code {
-                crossterm::event::KeyCode::Up => {
-                    scroll_state.move_up();
-                }
-                crossterm::event::KeyCode::Down => {
-                    scroll_state.move_down();
-                }
                 crossterm::event::KeyCode::Char(' ') => {
                     // Toggle selection
-                    let selected_disk = &available_disks[scroll_state.selected_index];
-                    if selected_disks.contains(selected_disk) {
-                        selected_disks.retain(|d| d != selected_disk);
-                    } else if selected_disks.len() < *max_disks {
-                        selected_disks.push(selected_disk.clone());
+                    if let Some(disk) = table
+                        .selected_row()
+                        .and_then(|row| available_disks.get(row))
+                    {
+                        if selected_disks.contains(&disk.path) {
+                            selected_disks.retain(|d| d != &disk.path);
+                        } else if selected_disks.len() < *max_disks {
+                            selected_disks.push(disk.path.clone());
+                        }
                     }
                 }
                 crossterm::event::KeyCode::Enter => {
@@ -231,28 +254,147 @@ impl InputDialog {
                 crossterm::event::KeyCode::Esc => {
                     return InputResult::Cancel;
                 }
+                code => navigate_table(table, code),
+            },
+            InputType::PackageGroupSelection {
+                selected,
+                scroll_state,
+                show_packages,
+            } => match key_event.code {
+                crossterm::event::KeyCode::Up => {
+                    scroll_state.move_up();
+                }
+                crossterm::event::KeyCode::Down => {
+                    scroll_state.move_down();
+                }
+                crossterm::event::KeyCode::Char(' ') => {
+                    if let Some(group) =
+                        crate::package_utils::package_groups().get(scroll_state.selected_index)
+                    {
+                        if selected.contains(&group.id) {
+                            selected.retain(|id| id != &group.id);
+                        } else {
+                            selected.push(group.id.clone());
+                        }
+                    }
+                }
+                crossterm::event::KeyCode::Tab | crossterm::event::KeyCode::Right => {
+                    *show_packages = !*show_packages;
+                }
+                crossterm::event::KeyCode::Enter => {
+                    return InputResult::Confirm(selected.join(" "));
+                }
+                crossterm::event::KeyCode::Esc => {
+                    return InputResult::Cancel;
+                }
                 _ => {}
             },
+            InputType::ServiceSelection {
+                selected,
+                scroll_state,
+            } => match key_event.code {
+                crossterm::event::KeyCode::Up => {
+                    scroll_state.move_up();
+                }
+                crossterm::event::KeyCode::Down => {
+                    scroll_state.move_down();
+                }
+                crossterm::event::KeyCode::Char(' ') => {
+                    if let Some(service) =
+                        crate::services::SERVICES.get(scroll_state.selected_index)
+                    {
+                        let enabled = !selected.iter().any(|unit| unit == service.unit);
+                        let list = crate::services::set_enabled(
+                            &selected.join(" "),
+                            service.unit,
+                            enabled,
+                        );
+                        *selected = list
+                            .split_whitespace()
+                            .filter(|unit| *unit != crate::services::NONE)
+                            .map(str::to_string)
+                            .collect();
+                    }
+                }
+                crossterm::event::KeyCode::Enter => {
+                    if selected.is_empty() {
+                        return InputResult::Confirm(crate::services::NONE.to_string());
+                    }
+                    return InputResult::Confirm(selected.join(" "));
+                }
+                crossterm::event::KeyCode::Esc => {
+                    return InputResult::Cancel;
+                }
+                _ => {}
+            },
+            InputType::MultiSelect {
+                options,
+                checked,
+                scroll_state,
+                min,
+                max,
+                error,
+                ..
+            } => {
+                let count = checked.iter().filter(|&&c| c).count();
+                match key_event.code {
+                    crossterm::event::KeyCode::Up => scroll_state.move_up(),
+                    crossterm::event::KeyCode::Down => scroll_state.move_down(),
+                    crossterm::event::KeyCode::PageUp => scroll_state.page_up(),
+                    crossterm::event::KeyCode::PageDown => scroll_state.page_down(),
+                    crossterm::event::KeyCode::Char(' ') => {
+                        if let Some(c) = checked.get_mut(scroll_state.selected_index) {
+                            if !*c && count >= *max {
+                                *error = Some(format!("At most {} can be selected", max));
+                            } else {
+                                *c = !*c;
+                                *error = None;
+                            }
+                        }
+                    }
+                    crossterm::event::KeyCode::Char('a') => {
+                        if options.len() > *max {
+                            *error = Some(format!("At most {} can be selected", max));
+                        } else {
+                            checked.fill(true);
+                            *error = None;
+                        }
+                    }
+                    crossterm::event::KeyCode::Char('n') => {
+                        checked.fill(false);
+                        *error = None;
+                    }
+                    crossterm::event::KeyCode::Enter => {
+                        if count < *min {
+                            *error = Some(format!("Select at least {}", min));
+                        } else {
+                            return InputResult::Confirm(join_multi_select(checked_options(
+                                options, checked,
+                            )));
+                        }
+                    }
+                    crossterm::event::KeyCode::Esc => {
+                        return InputResult::Cancel;
+                    }
+                    _ => {}
+                }
+            }
             InputType::PackageSelection {
                 current_input,
                 output_lines,
                 scroll_offset,
                 package_list,
                 is_pacman,
+                proxy,
                 search_results,
-                list_state,
+                results,
                 show_search_results,
                 ..
             } => {
                 match key_event.code {
                     crossterm::event::KeyCode::Up => {
                         if *show_search_results && !search_results.is_empty() {
-                            // Handle list navigation for search results
-                            if let Some(selected) = list_state.selected() {
-                                if selected > 0 {
-                                    list_state.select(Some(selected - 1));
-                                }
-                            }
+                            results.select_previous();
                         } else {
                             // Handle command mode scrolling
                             if *scroll_offset > 0 {
@@ -263,14 +405,7 @@ impl InputDialog {
                     }
                     crossterm::event::KeyCode::Down => {
                         if *show_search_results && !search_results.is_empty() {
-                            // Handle list navigation for search results
-                            if let Some(selected) = list_state.selected() {
-                                if selected < search_results.len() - 1 {
-                                    list_state.select(Some(selected + 1));
-                                }
-                            } else if !search_results.is_empty() {
-                                list_state.select(Some(0));
-                            }
+                            results.select_next();
                         } else {
                             // Handle command mode scrolling - ensure input prompt stays visible
                             let max_visible: usize = 15; // Approximate visible lines in content area
@@ -287,7 +422,7 @@ impl InputDialog {
                     crossterm::event::KeyCode::Enter => {
                         if *show_search_results {
                             // Toggle package selection
-                            if let Some(selected) = list_state.selected() {
+                            if let Some(selected) = results.selected_row() {
                                 if let Some(selected_result) = search_results.get(selected) {
                                     // Extract package name from search result
                                     let package_name = &selected_result.name;
@@ -347,9 +482,9 @@ impl InputDialog {
                                 search_results.clear();
 
                                 let result = if *is_pacman {
-                                    crate::package_utils::search_pacman_packages(search_term)
+                                    crate::package_utils::search_pacman_packages(search_term, proxy)
                                 } else {
-                                    crate::package_utils::search_aur_packages(search_term)
+                                    crate::package_utils::search_aur_packages(search_term, proxy)
                                 };
 
                                 match result {
@@ -369,7 +504,7 @@ impl InputDialog {
                                 }
 
                                 if !search_results.is_empty() {
-                                    output_lines.push(format!("Found {} packages. Use ↑↓ to navigate, Enter to add, Esc to exit", search_results.len()));
+                                    output_lines.push(format!("Found {} packages. Use ↑↓ to navigate, Tab to sort, Enter to add, Esc to exit", search_results.len()));
                                     // Auto-scroll to show latest content
                                     let max_visible: usize = 15;
                                     if output_lines.len() > max_visible.saturating_sub(2) {
@@ -378,7 +513,7 @@ impl InputDialog {
                                             .saturating_sub(max_visible.saturating_sub(2));
                                     }
                                     *show_search_results = true;
-                                    list_state.select(Some(0)); // Select first result
+                                    *results = TableState::new(search_results.len());
                                 }
                             } else if let Some(package_name) = command.strip_prefix("add ") {
                                 if package_list.is_empty() {
@@ -443,14 +578,18 @@ impl InputDialog {
                         }
                     }
                     crossterm::event::KeyCode::PageUp => {
-                        if !*show_search_results {
+                        if *show_search_results {
+                            results.page_up();
+                        } else {
                             // Page up in command mode
                             *scroll_offset = scroll_offset.saturating_sub(10);
                         }
                         return InputResult::Continue;
                     }
                     crossterm::event::KeyCode::PageDown => {
-                        if !*show_search_results {
+                        if *show_search_results {
+                            results.page_down();
+                        } else {
                             // Page down in command mode
                             let max_visible: usize = 15; // Approximate visible lines in content area
                             *scroll_offset = (*scroll_offset + 10).min(
@@ -466,17 +605,23 @@ impl InputDialog {
                             // Exit search results view
                             *show_search_results = false;
                             search_results.clear();
-                            list_state.select(None);
+                            *results = TableState::default();
                             return InputResult::Continue;
                         } else {
                             return InputResult::Cancel;
                         }
                     }
-                    crossterm::event::KeyCode::Char(c) => {
-                        current_input.push(c);
+                    crossterm::event::KeyCode::Tab if *show_search_results => {
+                        results.sort_next_column();
+                    }
+                    crossterm::event::KeyCode::BackTab if *show_search_results => {
+                        results.reverse_sort();
+                    }
+                    crossterm::event::KeyCode::Char(c) if is_text_key(&key_event) => {
+                        insert_char(current_input, c);
                     }
                     crossterm::event::KeyCode::Backspace => {
-                        current_input.pop();
+                        delete_last_char(current_input);
                     }
                     _ => {}
                 }
@@ -491,18 +636,64 @@ impl InputDialog {
                 }
                 _ => {}
             },
-            InputType::PasswordInput { current_value, .. } => match key_event.code {
+            InputType::Error { .. } => {
+                if matches!(
+                    key_event.code,
+                    crossterm::event::KeyCode::Enter | crossterm::event::KeyCode::Esc
+                ) {
+                    return InputResult::Cancel;
+                }
+            }
+            InputType::PasswordInput {
+                current_value,
+                confirm_value,
+                confirming,
+                min_length,
+                error,
+                ..
+            } => match key_event.code {
                 crossterm::event::KeyCode::Enter => {
-                    return InputResult::Confirm(current_value.clone());
+                    if !*confirming {
+                        let policy = crate::password::PasswordPolicy::new(*min_length);
+                        match policy.check(current_value) {
+                            Ok(()) => {
+                                *confirming = true;
+                                *error = None;
+                            }
+                            Err(e) => *error = Some(e),
+                        }
+                    } else if confirm_value == current_value {
+                        return InputResult::Confirm(current_value.clone());
+                    } else {
+                        // Mismatch: start over so both entries are retyped
+                        current_value.clear();
+                        confirm_value.clear();
+                        *confirming = false;
+                        *error = Some("Passwords do not match, please try again".to_string());
+                    }
                 }
                 crossterm::event::KeyCode::Esc => {
-                    return InputResult::Cancel;
+                    if *confirming {
+                        // Step back to the first entry instead of discarding it
+                        confirm_value.clear();
+                        *confirming = false;
+                    } else {
+                        return InputResult::Cancel;
+                    }
                 }
                 crossterm::event::KeyCode::Backspace => {
-                    current_value.pop();
+                    if *confirming {
+                        delete_last_char(confirm_value);
+                    } else {
+                        delete_last_char(current_value);
+                    }
                 }
-                crossterm::event::KeyCode::Char(c) => {
-                    current_value.push(c);
+                crossterm::event::KeyCode::Char(c) if is_text_key(&key_event) => {
+                    if *confirming {
+                        insert_char(confirm_value, c);
+                    } else {
+                        insert_char(current_value, c);
+                    }
                 }
                 _ => {}
             },
@@ -533,12 +724,13 @@ impl InputDialog {
                 .cloned()
                 .unwrap_or_default(),
             InputType::DiskSelection {
-                scroll_state,
+                table,
                 available_disks,
                 ..
-            } => available_disks
-                .get(scroll_state.selected_index)
-                .cloned()
+            } => table
+                .selected_row()
+                .and_then(|row| available_disks.get(row))
+                .map(|disk| disk.path.clone())
                 .unwrap_or_default(),
             InputType::MultiDiskSelection { selected_disks, .. } => {
                 if selected_disks.is_empty() {
@@ -551,17 +743,45 @@ impl InputDialog {
                     )
                 }
             }
+            InputType::PackageGroupSelection { selected, .. } => {
+                if selected.is_empty() {
+                    "No groups selected".to_string()
+                } else {
+                    selected.join(" ")
+                }
+            }
+            InputType::ServiceSelection { selected, .. } => {
+                if selected.is_empty() {
+                    "No services enabled".to_string()
+                } else {
+                    selected.join(" ")
+                }
+            }
+            InputType::MultiSelect {
+                options, checked, ..
+            } => {
+                let value = join_multi_select(checked_options(options, checked));
+                if value.is_empty() {
+                    "Nothing selected".to_string()
+                } else {
+                    value
+                }
+            }
             InputType::PackageSelection { package_list, .. } => package_list.clone(),
             InputType::Warning { .. } => "Press Enter to acknowledge".to_string(),
+            InputType::Error { .. } => "Press Enter to close".to_string(),
             InputType::PasswordInput {
                 current_value,
                 placeholder,
+                confirm_value,
+                confirming,
                 ..
             } => {
-                if current_value.is_empty() {
+                let value = if *confirming { confirm_value } else { current_value };
+                if value.is_empty() {
                     placeholder.clone()
                 } else {
-                    "*".repeat(current_value.len())
+                    mask(value)
                 }
             }
         }
@@ -573,14 +793,121 @@ impl InputType {
     pub fn get_selected_index(&self) -> usize {
         match self {
             InputType::Selection { scroll_state, .. } => scroll_state.selected_index,
-            InputType::DiskSelection { scroll_state, .. } => scroll_state.selected_index,
-            InputType::MultiDiskSelection { scroll_state, .. } => scroll_state.selected_index,
-            InputType::PackageSelection { list_state, .. } => list_state.selected().unwrap_or(0),
+            InputType::DiskSelection { table, .. } => table.selected_row().unwrap_or(0),
+            InputType::MultiDiskSelection { table, .. } => table.selected_row().unwrap_or(0),
+            InputType::PackageGroupSelection { scroll_state, .. } => scroll_state.selected_index,
+            InputType::ServiceSelection { scroll_state, .. } => scroll_state.selected_index,
+            InputType::MultiSelect { scroll_state, .. } => scroll_state.selected_index,
+            InputType::PackageSelection { results, .. } => results.selected_row().unwrap_or(0),
             _ => 0,
         }
     }
 }
 
+/// Value of a multi-select option: the checked choices in the order they
+/// are offered, separated by commas as choices may contain spaces; nothing
+/// checked is ""
+pub fn join_multi_select<'a>(values: impl IntoIterator<Item = &'a str>) -> String {
+    values.into_iter().collect::<Vec<_>>().join(",")
+}
+
+/// Checked choices of a multi-select value
+pub fn split_multi_select(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|choice| !choice.is_empty())
+        .collect()
+}
+
+/// The checked entries of a multi-select dialog, in order
+fn checked_options<'a>(
+    options: &'a [String],
+    checked: &'a [bool],
+) -> impl Iterator<Item = &'a str> {
+    options
+        .iter()
+        .zip(checked)
+        .filter(|(_, &c)| c)
+        .map(|(option, _)| option.as_str())
+}
+
+/// Move the highlight or change the sort of a table dialog
+///
+/// Tab sorts by the next column and Shift+Tab reverses the sort.
+fn navigate_table(table: &mut TableState, code: crossterm::event::KeyCode) {
+    use crossterm::event::KeyCode;
+    match code {
+        KeyCode::Up => table.select_previous(),
+        KeyCode::Down => table.select_next(),
+        KeyCode::PageUp => table.page_up(),
+        KeyCode::PageDown => table.page_down(),
+        KeyCode::Home => table.select_first(),
+        KeyCode::End => table.select_last(),
+        KeyCode::Tab => table.sort_next_column(),
+        KeyCode::BackTab => table.reverse_sort(),
+        _ => {}
+    }
+}
+
+/// Live validation for text fields with naming rules
+///
+/// An empty value is not flagged while typing; required fields are reported
+/// by the configuration check before installation.
+pub fn validate_text_field(field_name: &str, value: &str) -> Option<String> {
+    options::spec(field_name)?.live_check(value)
+}
+
+/// Whether a key event types into a text field
+///
+/// Key releases (reported by terminals with the enhanced keyboard protocol)
+/// and Ctrl shortcuts are not text. AltGr arrives as Ctrl+Alt on some
+/// terminals, so characters with both modifiers are kept.
+pub fn is_text_key(key: &crossterm::event::KeyEvent) -> bool {
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+    key.kind != KeyEventKind::Release
+        && (!key.modifiers.contains(KeyModifiers::CONTROL)
+            || key.modifiers.contains(KeyModifiers::ALT))
+}
+
+/// Append a typed character to a text value
+///
+/// Dead keys and input methods normally deliver one precomposed character,
+/// but some terminals send the letter and a combining accent separately;
+/// the pair is joined into its NFC form so the value matches what the
+/// console produces at login. Control characters are dropped.
+pub fn insert_char(value: &mut String, c: char) {
+    if c.is_control() {
+        return;
+    }
+    if let Some(composed) = value
+        .chars()
+        .next_back()
+        .and_then(|base| unicode_normalization::char::compose(base, c))
+    {
+        value.pop();
+        value.push(composed);
+    } else {
+        value.push(c);
+    }
+}
+
+/// Remove the last character as the user sees it, with its accents
+pub fn delete_last_char(value: &mut String) {
+    use unicode_segmentation::UnicodeSegmentation;
+    let keep = value
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index);
+    value.truncate(keep);
+}
+
+/// Asterisks standing for a secret value, one per visible character
+pub fn mask(value: &str) -> String {
+    use unicode_segmentation::UnicodeSegmentation;
+    "*".repeat(value.graphemes(true).count())
+}
+
 /// Result of input handling
 #[derive(Debug, Clone)]
 pub enum InputResult {
@@ -1086,23 +1413,40 @@ impl InputHandler {
         ));
     }
 
+    /// Explain an error in a dialog the user closes with Enter or Esc
+    pub fn start_error(&mut self, error: &ArchInstallError) {
+        let (title, message) = error.dialog();
+        self.current_dialog = Some(InputDialog::new(
+            InputType::Error { message },
+            title.to_string(),
+            "Press Enter or Esc to close".to_string(),
+        ));
+    }
+
     /// Start a password input dialog
+    ///
+    /// The password must satisfy `policy` and be typed twice before it is accepted.
     pub fn start_password_input(
         &mut self,
         field_name: String,
         current_value: String,
         placeholder: String,
+        policy: crate::password::PasswordPolicy,
     ) {
         let input_type = InputType::PasswordInput {
             field_name: field_name.clone(),
             current_value,
             placeholder,
+            confirm_value: String::new(),
+            confirming: false,
+            min_length: policy.min_length,
+            error: None,
         };
 
         self.current_dialog = Some(InputDialog::new(
             input_type,
             format!("Configure {}", field_name),
-            "Type the password and press Enter to confirm, Esc to cancel".to_string(),
+            "Type the password, press Enter, then retype it to confirm. Esc to cancel".to_string(),
         ));
     }
 
@@ -1113,10 +1457,12 @@ impl InputHandler {
         current_value: String,
         placeholder: String,
     ) {
+        let error = validate_text_field(&field_name, &current_value);
         let input_type = InputType::TextInput {
             field_name: field_name.clone(),
             current_value,
             placeholder,
+            error,
         };
 
         self.current_dialog = Some(InputDialog::new(
@@ -1167,30 +1513,117 @@ impl InputHandler {
 
     /// Start a disk selection dialog
     pub fn start_disk_selection(&mut self, current_value: String) {
-        let available_disks = Self::detect_available_disks();
+        let available_disks = detect_disks();
         let selected_index = available_disks
             .iter()
-            .position(|disk| disk == &current_value)
+            .position(|disk| disk.path == current_value)
             .unwrap_or(0);
 
-        let mut scroll_state = crate::scrolling::ScrollState::new(available_disks.len(), 10);
-        scroll_state.set_selected(selected_index);
+        let mut table = TableState::new(available_disks.len());
+        table.select_row(selected_index);
 
         let input_type = InputType::DiskSelection {
             current_value,
             available_disks,
-            scroll_state,
+            table,
         };
 
         self.current_dialog = Some(InputDialog::new(
             input_type,
             "Select Installation Disk".to_string(),
-            "Use ↑↓ to navigate, Enter to select, Esc to cancel".to_string(),
+            "Use ↑↓ to navigate, Tab to sort, Enter to select, Esc to cancel".to_string(),
         ));
     }
 
-    /// Start a package selection dialog
-    pub fn start_package_selection(&mut self, field_name: String, current_packages: String) {
+    /// Start the package group selection dialog
+    ///
+    /// `current` is the space-separated list of selected group ids; unknown
+    /// ids are dropped.
+    pub fn start_package_group_selection(&mut self, current: String) {
+        let groups = crate::package_utils::package_groups();
+        let selected = current
+            .split_whitespace()
+            .filter(|id| groups.iter().any(|group| group.id == *id))
+            .map(str::to_string)
+            .collect();
+
+        let input_type = InputType::PackageGroupSelection {
+            selected,
+            scroll_state: crate::scrolling::ScrollState::new(groups.len(), 10),
+            show_packages: false,
+        };
+
+        self.current_dialog = Some(InputDialog::new(
+            input_type,
+            "Select Package Groups".to_string(),
+            "Use ↑↓ to navigate, Space to toggle, Tab to show packages, Enter to confirm, Esc to cancel"
+                .to_string(),
+        ));
+    }
+
+    /// Start the service selection dialog
+    pub fn start_service_selection(&mut self, current: String) {
+        let selected = crate::services::SERVICES
+            .iter()
+            .filter(|service| current.split_whitespace().any(|unit| unit == service.unit))
+            .map(|service| service.unit.to_string())
+            .collect();
+
+        let input_type = InputType::ServiceSelection {
+            selected,
+            scroll_state: crate::scrolling::ScrollState::new(crate::services::SERVICES.len(), 10),
+        };
+
+        self.current_dialog = Some(InputDialog::new(
+            input_type,
+            "Select Services".to_string(),
+            "Use ↑↓ to navigate, Space to toggle, Enter to confirm, Esc to cancel".to_string(),
+        ));
+    }
+
+    /// Start a checkbox list over `options`
+    ///
+    /// `current` is the joined value (see [`join_multi_select`]); values that
+    /// are not among the options are dropped.
+    pub fn start_multi_select(
+        &mut self,
+        field_name: String,
+        options: Vec<String>,
+        current: &str,
+        min: usize,
+        max: usize,
+    ) {
+        let current = split_multi_select(current);
+        let checked = options
+            .iter()
+            .map(|option| current.contains(&option.as_str()))
+            .collect();
+
+        let input_type = InputType::MultiSelect {
+            field_name: field_name.clone(),
+            scroll_state: crate::scrolling::ScrollState::new(options.len(), 14),
+            options,
+            checked,
+            min,
+            max,
+            error: None,
+        };
+
+        self.current_dialog = Some(InputDialog::new(
+            input_type,
+            format!("Select {}", field_name),
+            "Use ↑↓ to navigate, Space to toggle, A for all, N for none, Enter to confirm, Esc to cancel"
+                .to_string(),
+        ));
+    }
+
+    /// Start a package selection dialog searching through `proxy`
+    pub fn start_package_selection(
+        &mut self,
+        field_name: String,
+        current_packages: String,
+        proxy: Proxy,
+    ) {
         let is_pacman = field_name.contains("Pacman");
         let output_lines = vec![
             "Available commands:".to_string(),
@@ -1209,8 +1642,9 @@ impl InputHandler {
             scroll_offset: 0,
             package_list: current_packages,
             is_pacman,
+            proxy,
             search_results: Vec::new(),
-            list_state: ListState::default(),
+            results: TableState::default(),
             show_search_results: false,
         };
 
@@ -1249,226 +1683,38 @@ impl InputHandler {
         self.current_dialog.is_some()
     }
 
-    /// Get predefined options for common configuration fields
-    ///
-    /// Uses enum iteration where possible for type-safe option generation.
-    /// Options that have too many values (locales, timezones, countries) remain as static lists.
-    pub fn get_predefined_options(field_name: &str) -> Vec<String> {
-        match field_name {
-            // Type-safe enum-based options
-            "Boot Mode" => BootMode::iter().map(|v| v.to_string()).collect(),
-            "Secure Boot" => Toggle::iter().rev().map(|v| v.to_string()).collect(), // No first
-            "Partitioning Strategy" => PartitionScheme::iter().map(|v| v.to_string()).collect(),
-            "Encryption" => AutoToggle::iter().map(|v| v.to_string()).collect(),
-            "Root Filesystem" => Filesystem::iter().map(|v| v.to_string()).collect(),
-            "Home Filesystem" => Filesystem::iter().map(|v| v.to_string()).collect(),
-            "Separate Home Partition" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Swap" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Btrfs Snapshots" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Btrfs Frequency" => SnapshotFrequency::iter().map(|v| v.to_string()).collect(),
-            "Btrfs Assistant" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Time Sync (NTP)" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Kernel" => Kernel::iter().map(|v| v.to_string()).collect(),
-            "Multilib" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "GPU Drivers" => GpuDriver::iter().map(|v| v.to_string()).collect(),
-            "AUR Helper" => AurHelper::iter().map(|v| v.to_string()).collect(),
-            "Flatpak" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Bootloader" => Bootloader::iter().map(|v| v.to_string()).collect(),
-            "OS Prober" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "GRUB Theme" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "GRUB Theme Selection" => GrubTheme::iter().map(|v| v.to_string()).collect(),
-            "Desktop Environment" => DesktopEnvironment::iter().map(|v| v.to_string()).collect(),
-            "Display Manager" => DisplayManager::iter().map(|v| v.to_string()).collect(),
-            "Plymouth" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Plymouth Theme" => PlymouthTheme::iter().map(|v| v.to_string()).collect(),
-            "Numlock on Boot" => Toggle::iter().map(|v| v.to_string()).collect(),
-            "Git Repository" => Toggle::iter().map(|v| v.to_string()).collect(),
-
-            // Static lists for options with too many values to enumerate
-            "Locale" => vec![
-                "en_US.UTF-8".to_string(),
-                "en_GB.UTF-8".to_string(),
-                "de_DE.UTF-8".to_string(),
-                "fr_FR.UTF-8".to_string(),
-                "es_ES.UTF-8".to_string(),
-                "it_IT.UTF-8".to_string(),
-                "pt_BR.UTF-8".to_string(),
-                "ru_RU.UTF-8".to_string(),
-                "ja_JP.UTF-8".to_string(),
-                "zh_CN.UTF-8".to_string(),
-            ],
-            "Keymap" => vec![
-                "us".to_string(),
-                "uk".to_string(),
-                "de".to_string(),
-                "fr".to_string(),
-                "es".to_string(),
-                "it".to_string(),
-                "pt".to_string(),
-                "ru".to_string(),
-                "jp".to_string(),
-            ],
-            "Swap Size" => vec![
-                "1GB".to_string(),
-                "2GB".to_string(),
-                "4GB".to_string(),
-                "8GB".to_string(),
-                "16GB".to_string(),
-                "32GB".to_string(),
-                "Equal to RAM".to_string(),
-                "Double RAM".to_string(),
-            ],
-            "Btrfs Keep Count" => vec![
-                "3".to_string(),
-                "5".to_string(),
-                "10".to_string(),
-                "20".to_string(),
-            ],
-            "Timezone Region" => vec![
-                "Africa".to_string(),
-                "America".to_string(),
-                "Antarctica".to_string(),
-                "Arctic".to_string(),
-                "Asia".to_string(),
-                "Atlantic".to_string(),
-                "Australia".to_string(),
-                "Europe".to_string(),
-                "Indian".to_string(),
-                "Pacific".to_string(),
-                "US".to_string(),
-            ],
-            "Timezone" => {
-                // Dynamically populated based on selected region
-                vec!["Please select a timezone region first".to_string()]
-            }
-            "Mirror Country" => vec![
-                "Australia".to_string(),
-                "Austria".to_string(),
-                "Belgium".to_string(),
-                "Brazil".to_string(),
-                "Bulgaria".to_string(),
-                "Canada".to_string(),
-                "Chile".to_string(),
-                "China".to_string(),
-                "Czech Republic".to_string(),
-                "Denmark".to_string(),
-                "Estonia".to_string(),
-                "Finland".to_string(),
-                "France".to_string(),
-                "Germany".to_string(),
-                "Greece".to_string(),
-                "Hong Kong".to_string(),
-                "Hungary".to_string(),
-                "Iceland".to_string(),
-                "India".to_string(),
-                "Indonesia".to_string(),
-                "Ireland".to_string(),
-                "Israel".to_string(),
-                "Italy".to_string(),
-                "Japan".to_string(),
-                "Kazakhstan".to_string(),
-                "Korea".to_string(),
-                "Latvia".to_string(),
-                "Lithuania".to_string(),
-                "Luxembourg".to_string(),
-                "Netherlands".to_string(),
-                "New Zealand".to_string(),
-                "Norway".to_string(),
-                "Poland".to_string(),
-                "Portugal".to_string(),
-                "Romania".to_string(),
-                "Russia".to_string(),
-                "Serbia".to_string(),
-                "Singapore".to_string(),
-                "Slovakia".to_string(),
-                "Slovenia".to_string(),
-                "South Africa".to_string(),
-                "Spain".to_string(),
-                "Sweden".to_string(),
-                "Switzerland".to_string(),
-                "Taiwan".to_string(),
-                "Thailand".to_string(),
-                "Turkey".to_string(),
-                "Ukraine".to_string(),
-                "United Kingdom".to_string(),
-                "United States".to_string(),
-            ],
-
-            // Default fallback for unknown fields
-            _ => Toggle::iter().map(|v| v.to_string()).collect(),
-        }
+    /// Whether the open dialog types keys into a text value
+    pub fn is_text_active(&self) -> bool {
+        self.current_dialog.as_ref().is_some_and(|dialog| {
+            matches!(
+                dialog.input_type,
+                InputType::TextInput { .. }
+                    | InputType::PasswordInput { .. }
+                    | InputType::PackageSelection { .. }
+            )
+        })
     }
 
-    /// Detect available disks on the system
-    fn detect_available_disks() -> Vec<String> {
-        use std::process::Command;
-
-        let mut disks = Vec::new();
-
-        // Try to use lsblk to get disk information with more details
-        if let Ok(output) = Command::new("lsblk")
-            .args(["-d", "-n", "-o", "NAME,SIZE,TYPE,RO,TRAN"])
-            .output()
-        {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 5 && parts[2] == "disk" {
-                    let disk_name = format!("/dev/{}", parts[0]);
-                    let disk_size = parts[1];
-                    let is_readonly = parts[3] == "1";
-                    let transport = parts[4];
-
-                    // Safety check: Skip read-only disks (likely live ISO)
-                    if is_readonly {
-                        continue;
-                    }
-
-                    // Safety check: Skip removable media (USB, CD-ROM)
-                    if transport == "usb"
-                        || transport == "sata"
-                            && disk_size.parse::<u64>().unwrap_or(0) < 1_073_741_824
-                    {
-                        // Skip disks smaller than 1GB (likely live ISO)
-                        continue;
-                    }
-
-                    // Get additional disk info for RAID compatibility
-                    let disk_info = Self::get_disk_info(&disk_name);
-                    disks.push(format!("{} ({}) {}", disk_name, disk_size, disk_info));
-                }
-            }
-        }
-
-        // Fallback to common disk paths if lsblk fails
-        if disks.is_empty() {
-            let common_disks = vec![
-                "/dev/sda",
-                "/dev/sdb",
-                "/dev/sdc",
-                "/dev/sdd",
-                "/dev/nvme0n1",
-                "/dev/nvme1n1",
-                "/dev/nvme2n1",
-                "/dev/vda",
-                "/dev/vdb",
-                "/dev/vdc",
-            ];
-
-            for disk in common_disks {
-                if std::path::Path::new(disk).exists() {
-                    let disk_info = Self::get_disk_info(disk);
-                    disks.push(format!("{} {}", disk, disk_info));
-                }
-            }
-        }
+    /// Whether a password is being typed
+    pub fn is_password_active(&self) -> bool {
+        self.current_dialog
+            .as_ref()
+            .is_some_and(|dialog| matches!(dialog.input_type, InputType::PasswordInput { .. }))
+    }
 
-        disks
+    /// Whether a password, or a URL that may carry one, is being typed
+    pub fn is_secret_active(&self) -> bool {
+        self.is_password_active()
+            || self.current_dialog.as_ref().is_some_and(|dialog| {
+                matches!(&dialog.input_type, InputType::TextInput { field_name, .. }
+                    if crate::config::options::spec(field_name)
+                        .is_some_and(|spec| spec.has_credentials()))
+            })
     }
 
-    /// Start multi-disk selection for RAID or manual partitioning
+    /// Start multi-disk selection for RAID, manual or existing partitioning
     pub fn start_multi_disk_selection(&mut self, partitioning_strategy: &str) {
-        let available_disks = Self::detect_available_disks();
+        let available_disks = detect_disks();
 
         // Determine disk requirements based on partitioning strategy
         let (min_disks, max_disks, title) = match partitioning_strategy {
@@ -1480,6 +1726,7 @@ impl InputHandler {
                 // Manual partitioning can use 1+ disks
                 (1, 8, "Select Disks for Manual Partitioning")
             }
+            "existing" => (1, 8, "Select Disks with the Partitions to Use"),
             _ => {
                 // Default to single disk
                 (1, 1, "Select Disk")
@@ -1503,12 +1750,12 @@ impl InputHandler {
             return;
         }
 
-        let scroll_state = crate::scrolling::ScrollState::new(available_disks.len(), 10);
+        let table = TableState::new(available_disks.len());
 
         let input_type = InputType::MultiDiskSelection {
             selected_disks: Vec::new(),
             available_disks,
-            scroll_state,
+            table,
             min_disks,
             max_disks,
         };
@@ -1516,15 +1763,18 @@ impl InputHandler {
         self.current_dialog = Some(InputDialog::new(
             input_type,
             title.to_string(),
-            "Use ↑↓ to navigate, Space to select/deselect, Enter to confirm, Esc to cancel"
+            "Use ↑↓ to navigate, Space to select/deselect, Tab to sort, Enter to confirm, Esc to cancel"
                 .to_string(),
         ));
     }
 
     /// Validate RAID disk compatibility
-    fn validate_raid_disks(disks: &[String]) -> Result<(), String> {
+    fn validate_raid_disks(disks: &[String]) -> error::Result<()> {
         if disks.len() < 2 {
-            return Err("RAID requires at least 2 disks".to_string());
+            return Err(ArchInstallError::validation(
+                "RAID disks",
+                "RAID requires at least 2 disks",
+            ));
         }
 
         // Extract disk paths from the formatted strings
@@ -1553,9 +1803,12 @@ impl InputHandler {
                 let size = Self::parse_disk_size(size_str);
                 let tolerance = (first_size as f64 * 0.1) as u64; // 10% tolerance
                 if (size as i64 - first_size as i64).abs() > tolerance as i64 {
-                    return Err(format!(
-                        "RAID disks should be similar sizes. Found: {} vs {}",
-                        disk_sizes[0], size_str
+                    return Err(ArchInstallError::validation(
+                        "RAID disks",
+                        format!(
+                            "RAID disks should be similar sizes. Found: {} vs {}",
+                            disk_sizes[0], size_str
+                        ),
                     ));
                 }
             }
@@ -1590,7 +1843,7 @@ impl InputHandler {
     }
 
     /// Launch partitioning tool for manual partitioning
-    pub fn launch_partitioning_tool(&mut self, disks: &[String]) -> Result<(), String> {
+    pub fn launch_partitioning_tool(&mut self, disks: &[String]) -> error::Result<()> {
         use std::process::{Command, Stdio};
 
         // Extract disk paths from the formatted strings
@@ -1601,13 +1854,19 @@ impl InputHandler {
             .collect();
 
         if disk_paths.is_empty() {
-            return Err("No valid disks selected".to_string());
+            return Err(ArchInstallError::validation(
+                "Disk",
+                "No valid disks selected",
+            ));
         }
 
         // Validate disk paths to prevent command injection
         for disk in &disk_paths {
             if !disk.starts_with("/dev/") || disk.contains("..") || disk.contains(" ") {
-                return Err(format!("Invalid disk path: {}", disk));
+                return Err(ArchInstallError::validation(
+                    "Disk",
+                    format!("'{}' is not a disk path", disk),
+                ));
             }
         }
 
@@ -1624,17 +1883,16 @@ impl InputHandler {
                 .stdin(Stdio::inherit())
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit())
-                .status();
-
-            match status {
-                Ok(exit_status) => {
-                    if !exit_status.success() {
-                        return Err(format!("cfdisk failed for {}", disk));
-                    }
-                }
-                Err(e) => {
-                    return Err(format!("Failed to launch cfdisk for {}: {}", disk, e));
-                }
+                .status()
+                .map_err(|e| ArchInstallError::command_not_run("cfdisk", e))?;
+
+            if !status.success() {
+                // cfdisk wrote its errors to the terminal it was given
+                return Err(ArchInstallError::CommandFailed {
+                    cmd: format!("cfdisk {}", disk),
+                    status: status.code(),
+                    stderr: String::new(),
+                });
             }
         }
 
@@ -1667,167 +1925,327 @@ impl InputHandler {
             message,
         ));
     }
+}
 
-    /// Validate manual partitioning setup
-    pub fn validate_manual_partitioning(
-        &self,
-        disks: &[String],
-        boot_mode: &str,
-    ) -> Result<PartitionLayout, String> {
-        use std::process::Command;
-
-        // Extract disk paths
-        let disk_paths: Vec<String> = disks
-            .iter()
-            .map(|d| d.split(' ').next().unwrap_or("").to_string())
-            .filter(|d| !d.is_empty())
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::options::Input;
+    use crate::password::PasswordPolicy;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-        let mut partitions = Vec::new();
-        let mut has_root = false;
-        let mut has_esp = false;
-        let mut has_boot = false;
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
 
-        // Scan all partitions on selected disks
-        for disk in &disk_paths {
-            if let Ok(output) = Command::new("lsblk")
-                .args(["-n", "-o", "NAME,TYPE,SIZE", disk])
-                .output()
-            {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                for line in output_str.lines() {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2 && parts[1] == "part" {
-                        let partition_name = format!("/dev/{}", parts[0]);
-                        let partition_size = if parts.len() > 2 { parts[2] } else { "unknown" };
-                        partitions.push(PartitionInfo {
-                            name: partition_name,
-                            size: partition_size.to_string(),
-                        });
-                    }
-                }
-            }
+    fn type_text(handler: &mut InputHandler, text: &str) {
+        for c in text.chars() {
+            assert!(handler.handle_input(key(KeyCode::Char(c))).is_none());
         }
+    }
 
-        // Validate required partitions based on boot mode
-        if boot_mode.to_lowercase() == "uefi" {
-            // Check for GPT partition table and ESP partition
-            for disk in &disk_paths {
-                if let Ok(output) = Command::new("fdisk").args(["-l", disk]).output() {
-                    let output_str = String::from_utf8_lossy(&output.stdout);
-                    if output_str.contains("GPT") {
-                        // Good, GPT partition table
-                    } else if output_str.contains("MBR") || output_str.contains("DOS") {
-                        return Err(format!("UEFI requires GPT partition table on {}", disk));
-                    }
-                }
-            }
+    fn start(handler: &mut InputHandler, min_length: usize) {
+        handler.start_password_input(
+            "Root Password".to_string(),
+            String::new(),
+            "Enter root password".to_string(),
+            PasswordPolicy::new(min_length),
+        );
+    }
 
-            // Check for ESP partition (EF00 type)
-            for disk in &disk_paths {
-                if let Ok(output) = Command::new("fdisk").args(["-l", disk]).output() {
-                    let output_str = String::from_utf8_lossy(&output.stdout);
-                    if output_str.contains("EF00") || output_str.contains("EFI System") {
-                        has_esp = true;
-                        break;
-                    }
-                }
-            }
+    #[test]
+    fn test_password_requires_matching_confirmation() {
+        let mut handler = InputHandler::new();
+        start(&mut handler, 4);
 
-            if !has_esp {
-                return Err(
-                    "UEFI requires an EFI System Partition (ESP) with type EF00".to_string()
-                );
-            }
-        } else {
-            // BIOS mode - check for MBR or GPT with BIOS Boot Partition
-            for disk in &disk_paths {
-                if let Ok(output) = Command::new("fdisk").args(["-l", disk]).output() {
-                    let output_str = String::from_utf8_lossy(&output.stdout);
-                    if output_str.contains("MBR") || output_str.contains("DOS") {
-                        has_boot = true; // MBR has boot sector
-                        break;
-                    } else if output_str.contains("GPT") && output_str.contains("EF02") {
-                        has_boot = true; // GPT with BIOS Boot Partition
-                        break;
-                    }
-                }
-            }
+        type_text(&mut handler, "s3cret!");
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+        assert!(handler.is_dialog_active());
+
+        type_text(&mut handler, "s3cret!");
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("s3cret!".to_string())
+        );
+        assert!(!handler.is_dialog_active());
+    }
+
+    #[test]
+    fn test_password_mismatch_restarts_entry() {
+        let mut handler = InputHandler::new();
+        start(&mut handler, 4);
 
-            if !has_boot {
-                return Err(
-                    "BIOS requires MBR partition table or GPT with BIOS Boot Partition".to_string(),
-                );
+        type_text(&mut handler, "first-try");
+        handler.handle_input(key(KeyCode::Enter));
+        type_text(&mut handler, "different");
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+
+        let dialog = handler.current_dialog.as_ref().expect("dialog stays open");
+        match &dialog.input_type {
+            InputType::PasswordInput {
+                current_value,
+                confirm_value,
+                confirming,
+                error,
+                ..
+            } => {
+                assert!(current_value.is_empty());
+                assert!(confirm_value.is_empty());
+                assert!(!confirming);
+                assert!(error.as_deref().unwrap_or("").contains("do not match"));
             }
+            other => panic!("unexpected input type: {:?}", other),
         }
+    }
 
-        // Check for root partition (Linux filesystem)
-        for disk in &disk_paths {
-            if let Ok(output) = Command::new("fdisk").args(["-l", disk]).output() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if output_str.contains("8300") || output_str.contains("Linux filesystem") {
-                    has_root = true;
-                    break;
-                }
+    #[test]
+    fn test_password_min_length_blocks_confirmation_step() {
+        let mut handler = InputHandler::new();
+        start(&mut handler, 8);
+
+        type_text(&mut handler, "short");
+        handler.handle_input(key(KeyCode::Enter));
+
+        let dialog = handler.current_dialog.as_ref().expect("dialog stays open");
+        match &dialog.input_type {
+            InputType::PasswordInput {
+                confirming, error, ..
+            } => {
+                assert!(!confirming);
+                assert!(error.as_deref().unwrap_or("").contains("at least 8"));
             }
+            other => panic!("unexpected input type: {:?}", other),
         }
+    }
 
-        if !has_root {
-            return Err("No Linux filesystem partition found. You need at least one root partition (type 8300)".to_string());
-        }
+    #[test]
+    fn test_password_esc_in_confirmation_returns_to_first_entry() {
+        let mut handler = InputHandler::new();
+        start(&mut handler, 0);
 
-        Ok(PartitionLayout {
-            partitions,
-            table_type: if boot_mode.to_lowercase() == "uefi" {
-                "GPT".to_string()
-            } else {
-                "MBR".to_string()
-            },
-            has_esp,
-            has_boot,
-            has_root,
-        })
+        type_text(&mut handler, "abc");
+        handler.handle_input(key(KeyCode::Enter));
+        handler.handle_input(key(KeyCode::Esc));
+        assert!(handler.is_dialog_active());
+
+        handler.handle_input(key(KeyCode::Esc));
+        assert!(!handler.is_dialog_active());
     }
 
-    /// Get additional disk information for RAID compatibility
-    fn get_disk_info(disk: &str) -> String {
-        use std::process::Command;
+    #[test]
+    fn test_password_with_accents_matches_however_it_was_typed() {
+        let mut handler = InputHandler::new();
+        start(&mut handler, 4);
+
+        // Precomposed from a dead key, then letter plus combining accent
+        type_text(&mut handler, "Grüße-été");
+        let dialog = handler.current_dialog.as_ref().expect("dialog open");
+        assert_eq!(dialog.get_display_value(), "*********");
+        handler.handle_input(key(KeyCode::Enter));
+        type_text(&mut handler, "Gru\u{308}ße-e\u{301}te\u{301}");
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("Grüße-été".to_string())
+        );
+    }
 
-        let mut info_parts = Vec::<String>::new();
+    #[test]
+    fn test_text_input_deletes_whole_characters() {
+        let mut handler = InputHandler::new();
+        handler.start_text_input(
+            "Git Repository URL".to_string(),
+            String::new(),
+            String::new(),
+        );
+        // No precomposed q with a tilde: the mark stays with its letter
+        type_text(&mut handler, "日本q\u{303}");
+        handler.handle_input(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        handler.handle_input(KeyEvent::new(
+            KeyCode::Char('€'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        ));
+        handler.handle_input(key(KeyCode::Backspace));
+        handler.handle_input(key(KeyCode::Backspace));
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("日本".to_string())
+        );
+    }
 
-        // Check if disk is mounted (safety check)
-        if let Ok(output) = Command::new("mount").output() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            if output_str.contains(disk) {
-                info_parts.push("MOUNTED".to_string());
-            }
+    #[test]
+    fn test_insert_char_composes_as_nfc() {
+        let mut value = String::new();
+        for c in "c\u{30C}o\u{30B}a\u{328}x\u{301}".chars() {
+            insert_char(&mut value, c);
         }
+        assert_eq!(value, "čőąx\u{301}");
+    }
 
-        // Get disk model for RAID compatibility
-        if let Ok(output) = Command::new("lsblk")
-            .args(["-d", "-n", "-o", "MODEL", disk])
-            .output()
-        {
-            let model = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !model.is_empty() && model != "disk" {
-                info_parts.push(model);
-            }
+    fn text_error(handler: &InputHandler) -> Option<String> {
+        let dialog = handler.current_dialog.as_ref().expect("dialog open");
+        match &dialog.input_type {
+            InputType::TextInput { error, .. } => error.clone(),
+            other => panic!("unexpected input type: {:?}", other),
         }
+    }
 
-        // Get sector size for RAID compatibility
-        if let Ok(output) = Command::new("blockdev").args(["--getss", disk]).output() {
-            if let Ok(sector_size) = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .parse::<u32>()
-            {
-                info_parts.push(format!("{}B", sector_size));
+    #[test]
+    fn test_hostname_is_validated_while_typing() {
+        let mut handler = InputHandler::new();
+        handler.start_text_input(
+            "Hostname".to_string(),
+            String::new(),
+            "Enter hostname".to_string(),
+        );
+        assert_eq!(text_error(&handler), None);
+
+        type_text(&mut handler, "my_pc");
+        assert!(text_error(&handler).is_some());
+        // Enter is refused while the value is invalid
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+        assert!(handler.is_dialog_active());
+
+        handler.handle_input(key(KeyCode::Backspace));
+        handler.handle_input(key(KeyCode::Backspace));
+        handler.handle_input(key(KeyCode::Backspace));
+        type_text(&mut handler, "-pc");
+        assert_eq!(text_error(&handler), None);
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("my-pc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reserved_username_is_rejected_inline() {
+        let mut handler = InputHandler::new();
+        handler.start_text_input(
+            "Username".to_string(),
+            String::new(),
+            "Enter username".to_string(),
+        );
+        type_text(&mut handler, "root");
+        assert!(text_error(&handler).unwrap().contains("reserved"));
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+    }
+
+    #[test]
+    fn test_custom_repositories_are_validated_while_typing() {
+        assert!(validate_text_field("Custom Repositories", "mine").is_some());
+        assert_eq!(
+            validate_text_field("Custom Repositories", "mine|https://repo.example/$arch"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_other_text_fields_are_not_validated() {
+        assert_eq!(validate_text_field("Hostname", ""), None);
+        assert_eq!(validate_text_field("Git Repository URL", "any thing"), None);
+    }
+
+    #[test]
+    fn test_package_group_selection_toggles_groups() {
+        let mut handler = InputHandler::new();
+        handler.start_package_group_selection("gaming bogus".to_string());
+
+        // First entry is "development"; Tab only changes the view
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert!(handler.handle_input(key(KeyCode::Tab)).is_none());
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("gaming development".to_string())
+        );
+    }
+
+    #[test]
+    fn test_service_selection_toggles_units() {
+        let mut handler = InputHandler::new();
+        handler.start_service_selection("fstrim.timer bogus NetworkManager.service".to_string());
+
+        // NetworkManager is first: turn it off, then sshd on
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert!(handler.handle_input(key(KeyCode::Down)).is_none());
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("sshd.service fstrim.timer".to_string())
+        );
+    }
+
+    #[test]
+    fn test_multi_select_limits_and_value() {
+        let options: Vec<String> = ["linux", "linux-lts", "linux-zen", "linux-hardened"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut handler = InputHandler::new();
+        handler.start_multi_select(
+            "Kernels".to_string(),
+            options,
+            "linux-zen,bogus, linux",
+            1,
+            2,
+        );
+        let error = |handler: &InputHandler| match &handler.current_dialog {
+            Some(InputDialog {
+                input_type: InputType::MultiSelect { error, .. },
+                ..
+            }) => error.clone(),
+            other => panic!("unexpected dialog: {:?}", other),
+        };
+
+        // Two of two are checked: neither a third nor all can be added
+        assert!(handler.handle_input(key(KeyCode::Down)).is_none());
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert!(error(&handler).unwrap().contains("At most 2"));
+        assert!(handler.handle_input(key(KeyCode::Char('a'))).is_none());
+
+        // None checked is below the minimum
+        assert!(handler.handle_input(key(KeyCode::Char('n'))).is_none());
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+        assert!(error(&handler).unwrap().contains("at least 1"));
+
+        // Values come back in the order they are offered
+        assert!(handler.handle_input(key(KeyCode::Down)).is_none());
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert!(handler.handle_input(key(KeyCode::Up)).is_none());
+        assert!(handler.handle_input(key(KeyCode::Char(' '))).is_none());
+        assert_eq!(error(&handler), None);
+        assert_eq!(
+            handler.handle_input(key(KeyCode::Enter)),
+            Some("linux-lts,linux-zen".to_string())
+        );
+
+        assert_eq!(
+            split_multi_select("United States, Germany,"),
+            vec!["United States", "Germany"]
+        );
+        assert_eq!(join_multi_select(Vec::<&str>::new()), "");
+        assert!(matches!(
+            options::spec("Mirror Country").unwrap().input,
+            Input::MultiSelect { .. }
+        ));
+    }
+
+    #[test]
+    fn test_error_dialog_closes_without_a_value() {
+        let mut handler = InputHandler::new();
+        handler.start_error(&ArchInstallError::validation(
+            "Disk",
+            "No valid disks selected",
+        ));
+
+        let dialog = handler.current_dialog.as_ref().expect("dialog is open");
+        assert_eq!(dialog.title, "Invalid Value");
+        match &dialog.input_type {
+            InputType::Error { message } => {
+                assert_eq!(message, &["Disk: No valid disks selected".to_string()])
             }
+            other => panic!("unexpected input type: {:?}", other),
         }
 
-        if info_parts.is_empty() {
-            "OK".to_string()
-        } else {
-            info_parts.join(", ")
-        }
+        assert!(handler.handle_input(key(KeyCode::Enter)).is_none());
+        assert!(!handler.is_dialog_active());
     }
 }
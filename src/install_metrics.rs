@@ -0,0 +1,248 @@
+//! Metrics of a finished installation
+//!
+//! While the installer runs, the output monitor times each phase it
+//! announces, counts the packages pacman installs and collects the warnings
+//! install.sh logs; the throughput sampler adds the bytes downloaded during
+//! transfer phases. Once the installation succeeds the Installation Complete
+//! screen shows them, and `W` saves them as `install-summary.{json,md}` next
+//! to the installation report.
+
+use crate::components::status_bar::format_elapsed;
+use crate::events::{InstallEvent, LogLevel};
+use crate::tools::resize::format_size;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Where install.sh writes the installation report on the mounted target
+pub const TARGET_REPORT_DIR: &str = "/mnt/var/log/archinstall";
+
+/// Time spent in one installer phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// Durations, transfers and warnings of an installation
+#[derive(Debug, Clone, Default)]
+pub struct InstallMetrics {
+    /// Finished phases, in the order they ran
+    pub phases: Vec<PhaseTiming>,
+    /// Phase running now and when it began
+    current: Option<(String, Instant)>,
+    /// Bytes received during transfer phases; `None` when nothing sampled them
+    pub downloaded: Option<u64>,
+    /// Packages installed by all pacman transactions
+    pub packages_installed: usize,
+    /// Warnings logged by the installer
+    pub warnings: Vec<String>,
+    /// Duration of the whole installation, set when it succeeded
+    pub total: Option<Duration>,
+}
+
+impl InstallMetrics {
+    /// Start timing a phase, ending the one before it
+    pub fn begin_phase(&mut self, name: &str, now: Instant) {
+        self.end_phase(now);
+        self.current = Some((name.to_string(), now));
+    }
+
+    /// Phase being timed, the one an installation failed in if it ended now
+    pub fn current_phase(&self) -> Option<&str> {
+        self.current.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    fn end_phase(&mut self, now: Instant) {
+        if let Some((name, started)) = self.current.take() {
+            self.phases.push(PhaseTiming {
+                name,
+                duration: now.saturating_duration_since(started),
+            });
+        }
+    }
+
+    /// Collect a warning from a line of installer output
+    pub fn follow_line(&mut self, line: &str) {
+        if let InstallEvent::Log {
+            level: LogLevel::Warn,
+            message,
+        } = InstallEvent::from_output_line(line, false)
+        {
+            self.warnings.push(message.trim().to_string());
+        }
+    }
+
+    /// Count bytes received during a transfer phase
+    pub fn add_downloaded(&mut self, bytes: u64) {
+        *self.downloaded.get_or_insert(0) += bytes;
+    }
+
+    /// Record a successful installation that began at `started`
+    pub fn finish(&mut self, started: Instant, now: Instant) {
+        self.end_phase(now);
+        self.total = Some(now.saturating_duration_since(started));
+    }
+
+    /// Whether the installation succeeded
+    pub fn is_finished(&self) -> bool {
+        self.total.is_some()
+    }
+
+    /// Downloaded bytes for display
+    pub fn downloaded_text(&self) -> String {
+        self.downloaded
+            .map_or_else(|| "not measured".to_string(), format_size)
+    }
+
+    /// The summary as Markdown, in the register of install-report.md
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Arch Linux Installation Summary\n\n");
+        out.push_str(&format!(
+            "- Total time: {}\n- Downloaded: {}\n- Packages installed: {}\n- Warnings: {}\n\n",
+            self.total.map_or_else(|| "-".to_string(), format_elapsed),
+            self.downloaded_text(),
+            self.packages_installed,
+            self.warnings.len()
+        ));
+        out.push_str("## Phases\n\n| Phase | Duration |\n|-------|----------|\n");
+        for phase in &self.phases {
+            out.push_str(&format!(
+                "| {} | {} |\n",
+                phase.name,
+                format_elapsed(phase.duration)
+            ));
+        }
+        if !self.warnings.is_empty() {
+            out.push_str("\n## Warnings\n\n");
+            for warning in &self.warnings {
+                out.push_str(&format!("- {}\n", warning));
+            }
+        }
+        out
+    }
+
+    /// The summary as JSON; durations are whole seconds
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Phase<'a> {
+            name: &'a str,
+            seconds: u64,
+        }
+        #[derive(Serialize)]
+        struct Summary<'a> {
+            summary_version: u32,
+            total_seconds: Option<u64>,
+            phases: Vec<Phase<'a>>,
+            downloaded_bytes: Option<u64>,
+            packages_installed: usize,
+            warnings: &'a [String],
+        }
+        let summary = Summary {
+            summary_version: 1,
+            total_seconds: self.total.map(|total| total.as_secs()),
+            phases: self
+                .phases
+                .iter()
+                .map(|phase| Phase {
+                    name: &phase.name,
+                    seconds: phase.duration.as_secs(),
+                })
+                .collect(),
+            downloaded_bytes: self.downloaded,
+            packages_installed: self.packages_installed,
+            warnings: &self.warnings,
+        };
+        serde_json::to_string_pretty(&summary).expect("install summary always serializes")
+    }
+
+    /// Write `install-summary.json` and `install-summary.md` to `dir`,
+    /// returning the Markdown file
+    pub fn write_report(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("install-summary.json"), self.to_json() + "\n")?;
+        let path = dir.join("install-summary.md");
+        fs::write(&path, self.to_markdown())?;
+        Ok(path)
+    }
+}
+
+/// Directories the summary is saved to: next to the report on the target
+/// when it is still mounted, and the external report directory (--report)
+///
+/// With neither, the temporary directory.
+pub fn report_dirs(external: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if Path::new(TARGET_REPORT_DIR).is_dir() {
+        dirs.push(PathBuf::from(TARGET_REPORT_DIR));
+    }
+    dirs.extend(external.map(Path::to_path_buf));
+    if dirs.is_empty() {
+        dirs.push(std::env::temp_dir());
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics() -> InstallMetrics {
+        let start = Instant::now();
+        let mut metrics = InstallMetrics::default();
+        metrics.begin_phase("Partitioning disk", start);
+        metrics.follow_line("\x1b[1;33m[2025-01-01 10:00:00] WARN: No swap configured\x1b[0m");
+        metrics.follow_line("[2025-01-01 10:00:01] INFO: Creating partitions");
+        metrics.begin_phase("Installing base system", start + Duration::from_secs(12));
+        metrics.add_downloaded(600 << 20);
+        metrics.add_downloaded(24 << 20);
+        metrics.packages_installed = 420;
+        metrics.finish(start, start + Duration::from_secs(612));
+        metrics
+    }
+
+    #[test]
+    fn test_times_phases_and_collects_warnings() {
+        let metrics = metrics();
+        assert!(metrics.is_finished());
+        assert_eq!(metrics.total, Some(Duration::from_secs(612)));
+        assert_eq!(
+            metrics.phases,
+            [
+                PhaseTiming {
+                    name: "Partitioning disk".to_string(),
+                    duration: Duration::from_secs(12),
+                },
+                PhaseTiming {
+                    name: "Installing base system".to_string(),
+                    duration: Duration::from_secs(600),
+                },
+            ]
+        );
+        assert_eq!(metrics.warnings, ["No swap configured"]);
+        assert_eq!(metrics.downloaded, Some(624 << 20));
+        assert_eq!(InstallMetrics::default().downloaded_text(), "not measured");
+    }
+
+    #[test]
+    fn test_writes_summary_next_to_the_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = metrics().write_report(dir.path()).unwrap();
+
+        let markdown = fs::read_to_string(path).unwrap();
+        assert!(markdown.contains("- Total time: 10:12\n"));
+        assert!(markdown.contains("| Installing base system | 10:00 |"));
+        assert!(markdown.contains("## Warnings\n\n- No swap configured\n"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("install-summary.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["total_seconds"], 612);
+        assert_eq!(json["phases"][0]["seconds"], 12);
+        assert_eq!(json["downloaded_bytes"], 624 << 20);
+        assert_eq!(json["packages_installed"], 420);
+    }
+}
@@ -151,6 +151,41 @@ impl InstallStage {
         }
     }
 
+    /// Returns a machine-readable identifier, e.g. "installing_base_system"
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::NotStarted => "not_started",
+            Self::ValidatingConfig => "validating_config",
+            Self::PreparingSystem => "preparing_system",
+            Self::InstallingDependencies => "installing_dependencies",
+            Self::PartitioningDisk => "partitioning_disk",
+            Self::InstallingBaseSystem => "installing_base_system",
+            Self::GeneratingFstab => "generating_fstab",
+            Self::ConfiguringChroot => "configuring_chroot",
+            Self::Finalizing => "finalizing",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Returns the stage announced by a line of install.sh output
+    ///
+    /// install.sh numbers its phases ("Phase 5: Installing base system...")
+    /// in stage order; the custom phases and the report (9, 10) are part of
+    /// finalizing.
+    pub fn from_output_line(line: &str) -> Option<Self> {
+        if line.contains("Installation complete") {
+            return Some(Self::Completed);
+        }
+        let rest = &line[line.find("Phase ")? + "Phase ".len()..];
+        let number: usize = rest[..rest.find(':')?].trim().parse().ok()?;
+        match number {
+            1..=8 => Some(Self::all_stages()[number]),
+            9 | 10 => Some(Self::Finalizing),
+            _ => None,
+        }
+    }
+
     /// Returns the approximate progress percentage for this stage
     pub const fn progress_percent(self) -> u8 {
         match self {
@@ -495,6 +530,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stage_from_output_line() {
+        assert_eq!(
+            InstallStage::from_output_line(
+                "[2024-05-01 10:00:00] INFO: Phase 4: Partitioning disk..."
+            ),
+            Some(InstallStage::PartitioningDisk)
+        );
+        assert_eq!(
+            InstallStage::from_output_line("Phase 10: Generating installation report..."),
+            Some(InstallStage::Finalizing)
+        );
+        assert_eq!(
+            InstallStage::from_output_line("Installation complete!"),
+            Some(InstallStage::Completed)
+        );
+        assert_eq!(InstallStage::from_output_line("Phase 11: unknown"), None);
+        assert_eq!(InstallStage::from_output_line("Partitioning disk"), None);
+        assert_eq!(
+            InstallStage::InstallingBaseSystem.id(),
+            "installing_base_system"
+        );
+    }
+
     #[test]
     fn test_stage_next_forms_chain() {
         let mut current = InstallStage::NotStarted;
@@ -0,0 +1,362 @@
+//! Local installation statistics
+//!
+//! With `--install-stats` the outcome of every installation is appended to
+//! `install-stats.jsonl` in the [cache directory](crate::session::cache_dir):
+//! the partitioning strategy, boot mode and root filesystem, how long each
+//! phase took and which phase failed or was skipped. Host names, user names,
+//! disks, addresses and log text are never recorded, and nothing is sent
+//! anywhere; `archinstall-tui stats` prints the file for a bug report, so
+//! recurring failures of one phase or strategy stand out.
+
+use crate::config::Configuration;
+use crate::config_file::InstallationConfig;
+use crate::notify::Outcome;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Record format version written to the file
+const RECORD_VERSION: u32 = 1;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Record the installations of this run
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether `--install-stats` was given
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Statistics file, `install-stats.jsonl` in the cache directory
+pub fn default_path() -> Option<PathBuf> {
+    Some(crate::session::cache_dir()?.join("install-stats.jsonl"))
+}
+
+/// How an installation was started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallSource {
+    /// Guided installer
+    #[default]
+    Guided,
+    /// Configuration file, from the TUI or `install --config`
+    Config,
+    /// `remote` over SSH
+    Remote,
+}
+
+/// The settings an installation is recorded with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallSettings {
+    pub source: InstallSource,
+    pub strategy: String,
+    pub boot_mode: String,
+    pub root_filesystem: String,
+}
+
+impl InstallSettings {
+    /// Settings of the guided installer
+    pub fn guided(config: &Configuration) -> Self {
+        Self {
+            source: InstallSource::Guided,
+            strategy: config.value("Partitioning Strategy"),
+            boot_mode: config.value("Boot Mode"),
+            root_filesystem: config.value("Root Filesystem"),
+        }
+    }
+
+    /// Settings of a configuration file
+    pub fn for_config(config: &InstallationConfig, source: InstallSource) -> Self {
+        Self {
+            source,
+            strategy: config.partitioning_strategy.to_string(),
+            boot_mode: config.boot_mode.to_string(),
+            root_filesystem: config.root_filesystem.to_string(),
+        }
+    }
+}
+
+/// Time spent in one phase
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseRecord {
+    pub name: String,
+    pub seconds: u64,
+}
+
+/// One line of the statistics file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub record_version: u32,
+    /// Seconds since the epoch when the installation ended
+    pub recorded_at: u64,
+    /// Version of the installer
+    pub version: String,
+    #[serde(flatten)]
+    pub settings: InstallSettings,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    /// Whole installation, when it ran to the end
+    pub duration_seconds: Option<u64>,
+    /// Finished phases, in the order they ran
+    pub phases: Vec<PhaseRecord>,
+    /// Phase that was running when the installation failed
+    pub failed_phase: Option<String>,
+    /// Phases that failed and were skipped or continued past
+    #[serde(default)]
+    pub skipped_phases: Vec<String>,
+    /// Number of warnings logged; their text is not recorded
+    pub warnings: usize,
+}
+
+impl InstallRecord {
+    /// Record of an installation that ended with `outcome`
+    pub fn new(settings: InstallSettings, outcome: &Outcome) -> Self {
+        let summary = outcome.summary.as_ref();
+        Self {
+            record_version: RECORD_VERSION,
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            settings,
+            success: outcome.success,
+            exit_code: outcome.exit_code,
+            duration_seconds: summary.and_then(|s| s.total).map(|total| total.as_secs()),
+            phases: summary
+                .map(|s| {
+                    s.phases
+                        .iter()
+                        .map(|phase| PhaseRecord {
+                            name: phase.name.clone(),
+                            seconds: phase.duration.as_secs(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            failed_phase: summary
+                .filter(|_| !outcome.success)
+                .and_then(|s| s.current_phase())
+                .map(str::to_string),
+            skipped_phases: summary
+                .map(|s| s.warnings.iter().filter_map(|w| skipped_phase(w)).collect())
+                .unwrap_or_default(),
+            warnings: summary.map_or(0, |s| s.warnings.len()),
+        }
+    }
+}
+
+/// Phase named by run_phase's warning that it failed and was skipped
+fn skipped_phase(warning: &str) -> Option<String> {
+    let (phase, rest) = warning.split_once(" failed, ")?;
+    (rest.starts_with("skipped") || rest.starts_with("continuing")).then(|| phase.to_string())
+}
+
+/// Append a record to the statistics file
+pub fn append(path: &Path, record: &InstallRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Records of the statistics file; lines that do not parse are skipped
+pub fn load(path: &Path) -> io::Result<Vec<InstallRecord>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Record an installation when the statistics are enabled
+///
+/// `log` receives a line saying where it was recorded, or why not.
+pub fn record(settings: &InstallSettings, outcome: &Outcome, mut log: impl FnMut(String)) {
+    if !is_enabled() {
+        return;
+    }
+    let Some(path) = default_path() else {
+        log("WARNING: No cache directory for the installation statistics".to_string());
+        return;
+    };
+    let record = InstallRecord::new(settings.clone(), outcome);
+    match append(&path, &record) {
+        Ok(()) => log(format!(
+            "Recorded the installation in {} (nothing is sent anywhere)",
+            path.display()
+        )),
+        Err(e) => log(format!(
+            "WARNING: Failed to record the installation statistics: {}",
+            e
+        )),
+    }
+}
+
+/// Text for a bug report: totals, then failures by phase and by strategy
+pub fn summary(records: &[InstallRecord]) -> String {
+    let succeeded = records.iter().filter(|r| r.success).count();
+    let mut out = format!(
+        "{} installation(s) recorded, {} succeeded, {} failed\n",
+        records.len(),
+        succeeded,
+        records.len() - succeeded
+    );
+
+    let mut failures: Vec<(String, usize)> = Vec::new();
+    let mut count = |name: String| match failures.iter_mut().find(|(n, _)| *n == name) {
+        Some((_, count)) => *count += 1,
+        None => failures.push((name, 1)),
+    };
+    for record in records {
+        if let Some(phase) = &record.failed_phase {
+            count(phase.clone());
+        }
+        for phase in &record.skipped_phases {
+            count(format!("{} (skipped)", phase));
+        }
+    }
+    if !failures.is_empty() {
+        failures.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        out.push_str("\nFailures by phase:\n");
+        for (phase, count) in &failures {
+            out.push_str(&format!("  {:<40} {}\n", phase, count));
+        }
+    }
+
+    let mut strategies: Vec<&str> = records
+        .iter()
+        .map(|r| r.settings.strategy.as_str())
+        .collect();
+    strategies.sort_unstable();
+    strategies.dedup();
+    if !strategies.is_empty() {
+        out.push_str("\nBy strategy:\n");
+        for strategy in strategies {
+            let runs: Vec<_> = records
+                .iter()
+                .filter(|r| r.settings.strategy == strategy)
+                .collect();
+            let failed = runs.iter().filter(|r| !r.success).count();
+            out.push_str(&format!(
+                "  {:<40} {} ({} failed)\n",
+                strategy,
+                runs.len(),
+                failed
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install_metrics::InstallMetrics;
+    use std::time::{Duration, Instant};
+
+    fn settings(strategy: &str) -> InstallSettings {
+        InstallSettings {
+            source: InstallSource::Config,
+            strategy: strategy.to_string(),
+            boot_mode: "UEFI".to_string(),
+            root_filesystem: "ext4".to_string(),
+        }
+    }
+
+    fn failed() -> Outcome {
+        let start = Instant::now();
+        let mut summary = InstallMetrics::default();
+        summary.begin_phase("Partitioning disk", start);
+        summary.follow_line("[2025-01-01 10:00:00] WARN: Chroot configuration failed, skipped");
+        summary.follow_line("[2025-01-01 10:00:01] WARN: No mirrors for XX on archbox");
+        summary.begin_phase("Installing base system", start + Duration::from_secs(30));
+        Outcome {
+            success: false,
+            exit_code: Some(1),
+            message: "Installation failed with exit code: 1".to_string(),
+            summary: Some(summary),
+        }
+    }
+
+    #[test]
+    fn test_record_keeps_phases_not_text() {
+        let record = InstallRecord::new(settings("auto_simple"), &failed());
+        assert!(!record.success);
+        assert_eq!(record.duration_seconds, None);
+        assert_eq!(
+            record.phases,
+            [PhaseRecord {
+                name: "Partitioning disk".to_string(),
+                seconds: 30,
+            }]
+        );
+        assert_eq!(
+            record.failed_phase.as_deref(),
+            Some("Installing base system")
+        );
+        assert_eq!(record.skipped_phases, ["Chroot configuration"]);
+        assert_eq!(record.warnings, 2);
+        // The warning text, and the host name in it, stay out of the file
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("archbox"));
+        assert!(json.contains(r#""strategy":"auto_simple""#));
+
+        let succeeded = InstallRecord::new(
+            settings("auto_simple"),
+            &Outcome {
+                success: true,
+                ..failed()
+            },
+        );
+        assert_eq!(succeeded.failed_phase, None);
+    }
+
+    #[test]
+    fn test_append_load_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats/install-stats.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        append(
+            &path,
+            &InstallRecord::new(settings("auto_simple"), &failed()),
+        )
+        .unwrap();
+        append(&path, &InstallRecord::new(settings("auto_lvm"), &failed())).unwrap();
+        let success = Outcome {
+            success: true,
+            exit_code: Some(0),
+            ..Outcome::default()
+        };
+        append(
+            &path,
+            &InstallRecord::new(settings("auto_simple"), &success),
+        )
+        .unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "not a record\n").unwrap();
+
+        let records = load(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        let summary = summary(&records);
+        assert!(summary.starts_with("3 installation(s) recorded, 1 succeeded, 2 failed\n"));
+        assert!(summary.contains("  Installing base system                   2\n"));
+        assert!(summary.contains("  Chroot configuration (skipped)           2\n"));
+        assert!(summary.contains("  auto_simple                              2 (1 failed)\n"));
+    }
+}
@@ -2,63 +2,238 @@
 //!
 //! Handles the execution of the bash installation script and communication with the TUI.
 
-use crate::app::AppState;
+use crate::app::{AppState, StateHandle};
+use crate::components::recovery_dialog::RecoveryDialogState;
 use crate::config::Configuration;
+use crate::config_file::InstallationConfig;
+use crate::error::{self, ArchInstallError};
+use crate::install_stats::InstallSettings;
+use crate::lanes;
+use crate::notify::{Notifier, Outcome};
+use crate::package_progress::PackageProgress;
+use crate::recovery::{self, PhaseFailure, RecoveryChoice};
+use crate::script_env::ScriptEnv;
+use crate::throughput::{self, ThroughputSampler};
+use crate::watchdog::{TimeoutPolicy, Watchdog};
 use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use std::thread;
+use std::time::Instant;
+
+/// Phase markers printed by install.sh, the progress each starts at and the
+/// status shown
+const PHASES: &[(&str, u8, &str)] = &[
+    (
+        "Starting Arch Linux installation",
+        10,
+        "Installation started",
+    ),
+    ("Preparing system", 15, "Preparing system"),
+    ("Starting disk partitioning", 25, "Partitioning disk"),
+    ("Installing base system", 40, "Installing base system"),
+    ("Configuring system", 60, "Configuring system"),
+    ("Installing packages", 75, "Installing packages"),
+    ("Configuring bootloader", 85, "Configuring bootloader"),
+    ("Finalizing installation", 95, "Finalizing installation"),
+    (
+        "Installation complete",
+        100,
+        "Installation completed successfully!",
+    ),
+];
+
+/// Where an installation takes its configuration from
+enum Source {
+    /// The guided installer's options, passed as variables
+    Guided(Configuration),
+    /// A configuration file, passed whole like the headless install does
+    File(Box<InstallationConfig>),
+}
 
 /// Installer instance
 pub struct Installer {
-    config: Configuration,
-    app_state: Arc<Mutex<AppState>>,
+    source: Source,
+    app_state: StateHandle,
+    /// External directory the installation report is copied to (--report)
+    report_dir: Option<PathBuf>,
+    /// How long commands may run without output before they count as hung
+    timeout_policy: TimeoutPolicy,
 }
 
 impl Installer {
     /// Create a new installer instance
-    pub fn new(config: Configuration, app_state: Arc<Mutex<AppState>>) -> Self {
-        Self { config, app_state }
+    pub fn new(config: Configuration, app_state: StateHandle) -> Self {
+        Self::with_source(Source::Guided(config), app_state)
+    }
+
+    /// Create an installer for a configuration file (automated install)
+    pub fn from_file(config: InstallationConfig, app_state: StateHandle) -> Self {
+        Self::with_source(Source::File(Box::new(config)), app_state)
+    }
+
+    fn with_source(source: Source, app_state: StateHandle) -> Self {
+        Self {
+            source,
+            app_state,
+            report_dir: None,
+            timeout_policy: TimeoutPolicy::default(),
+        }
+    }
+
+    /// Copy the installation report to an external directory after install
+    pub fn with_report_dir(mut self, report_dir: Option<PathBuf>) -> Self {
+        self.report_dir = report_dir;
+        self
+    }
+
+    /// Watch for hung commands with these timeouts
+    pub fn with_timeout_policy(mut self, timeout_policy: TimeoutPolicy) -> Self {
+        self.timeout_policy = timeout_policy;
+        self
+    }
+
+    /// Validate the installation configuration, naming the first invalid option
+    fn validate_configuration(&self) -> error::Result<()> {
+        let config = match self.source {
+            Source::Guided(ref config) => config,
+            Source::File(ref config) => {
+                return config
+                    .validate()
+                    .map_err(|e| ArchInstallError::config(format!("{:#}", e)));
+            }
+        };
+        match config.options.iter().find(|option| !option.is_valid()) {
+            Some(option) => Err(ArchInstallError::validation(
+                option.name.clone(),
+                option
+                    .validation_error()
+                    .unwrap_or_else(|| format!("'{}' is not allowed", option.value)),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Script environment with the configuration
+    ///
+    /// A configuration file reaches the script the way the headless install
+    /// passes it, so both installs read exactly the same settings.
+    fn config_env(&self) -> error::Result<ScriptEnv> {
+        match self.source {
+            Source::Guided(ref config) => Ok(ScriptEnv::new()
+                .config_vars(config.to_env_vars())
+                .proxy(&config.proxy())),
+            Source::File(ref config) if crate::simulate::is_enabled() => {
+                Ok(ScriptEnv::new().config_vars(config.to_env_vars()))
+            }
+            Source::File(ref config) => {
+                let json = config
+                    .to_json()
+                    .map_err(|e| ArchInstallError::config(e.to_string()))?;
+                Ok(ScriptEnv::new()
+                    .config_json(&json)?
+                    .config_vars(config.package_vars()))
+            }
+        }
+    }
+
+    /// Who hears about the end of the installation
+    fn notifier(&self) -> Notifier {
+        match self.source {
+            Source::Guided(ref config) => Notifier {
+                hostname: Some(config.value("Hostname")).filter(|name| !name.is_empty()),
+                url: None,
+                stats: Some(InstallSettings::guided(config)),
+            },
+            Source::File(ref config) => Notifier::for_config(config),
+        }
     }
 
-    /// Validate the installation configuration
-    fn validate_configuration(&self) -> bool {
-        self.config.options.iter().all(|option| option.is_valid())
+    /// User, root and encryption passwords, written to the script's stdin
+    fn passwords(&self) -> (String, String, Option<String>) {
+        match self.source {
+            Source::Guided(ref config) => config.get_passwords(),
+            Source::File(ref config) => (
+                config.user_password.clone(),
+                config.root_password.clone(),
+                None,
+            ),
+        }
+    }
+
+    /// Target disks, comma-separated in the configuration
+    fn disks(&self) -> Vec<String> {
+        let disks = match self.source {
+            Source::Guided(ref config) => config
+                .options
+                .iter()
+                .find(|option| option.name == "Disk")
+                .map(|option| option.get_value())
+                .unwrap_or_default(),
+            Source::File(ref config) => config.install_disk.clone(),
+        };
+        disks.split(',').map(str::to_string).collect()
     }
 
     /// Start the installation process
-    pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// `state` is switched to the progress screen right away; the threads
+    /// following the installer update it through the installer's handle.
+    pub fn start(&self, state: &mut AppState) -> error::Result<()> {
         // Validate configuration before starting
-        if !self.validate_configuration() {
-            return Err("Configuration validation failed".into());
-        }
+        self.validate_configuration()?;
+
+        // Below 1 GiB the script installs with its low-memory strategy
+        let low_memory = crate::low_memory::detect();
 
         // Update app state to installation mode
         {
-            let mut state = self.app_state.lock().unwrap();
-            state.mode = crate::app::AppMode::Installation;
-            state.status_message = "Starting installation...".to_string();
-            state.installation_progress = 10;
+            state.transition(crate::app::AppMode::Installation);
+            state.status.info("Starting installation...");
+            // install.sh logs the details
+            if let Some(warning) = low_memory.first() {
+                state.status.warn(warning.clone());
+            }
+            state.install.started = Some(Instant::now());
+            state.install.metrics = Default::default();
+            state.install.lanes = Default::default();
+            state.install.watchdog = Default::default();
+            state.install.watchdog.note_output(Instant::now());
+            state.install.progress = 10;
 
             // Add initial debug output
             state
-                .installer_output
+                .install
+                .output
                 .push("=== INSTALLATION ENGINE STARTED ===".to_string());
             state
-                .installer_output
+                .install
+                .output
                 .push("Script: scripts/install.sh".to_string());
-            state.installer_output.push("Mode: TUI-only".to_string());
+            state.install.output.push(match self.source {
+                Source::Guided(_) => "Mode: TUI-only".to_string(),
+                Source::File(_) => "Mode: Configuration file".to_string(),
+            });
             state
-                .installer_output
+                .install
+                .output
                 .push("==========================================".to_string());
         }
 
-        // Prepare environment variables (excludes passwords for security)
-        let env_vars = self.config.to_env_vars();
+        // Prepare environment variables (excludes passwords for security);
+        // a failed phase waits for a choice in the recovery dialog
+        let decision_file = recovery::decision_file()?;
+        let _ = std::fs::remove_file(&decision_file);
+        let env = self
+            .config_env()?
+            .report_dir(self.report_dir.as_deref())
+            .installer_bin()
+            .low_memory(!low_memory.is_empty())
+            .recovery_file(&decision_file);
 
         // SECURITY: Extract passwords separately for stdin passing
         // This prevents password exposure in /proc/<pid>/environ
-        let (user_password, root_password, encryption_password) = self.config.get_passwords();
+        let (user_password, root_password, encryption_password) = self.passwords();
 
         // Serialize passwords for stdin protocol:
         // Format: USER_PASS\nROOT_PASS\nENCRYPT_PASS\n
@@ -73,11 +248,16 @@ impl Installer {
         let script_path = std::env::var("ARCHINSTALL_SCRIPTS_DIR")
             .map(|dir| format!("{}/install_wrapper.sh", dir))
             .unwrap_or_else(|_| "./scripts/install_wrapper.sh".to_string());
+        let mut command = Command::new("bash");
+        if crate::simulate::is_enabled() {
+            command.arg("-c").arg(crate::simulate::install_script());
+        } else {
+            command.arg(&script_path);
+        }
 
         // Launch the installation script with piped stdin for secure password passing
-        let mut child = Command::new("bash")
-            .arg(&script_path)
-            .envs(&env_vars)
+        env.apply(&mut command);
+        let mut child = command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::piped()) // Changed: piped for password passing
@@ -93,110 +273,251 @@ impl Installer {
             // stdin is dropped here, closing the pipe
         }
 
-        // Handle stdout in separate thread
-        if let Some(stdout) = child.stdout.take() {
-            let app_state = Arc::clone(&self.app_state);
-
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().map_while(Result::ok) {
-                    let mut state = app_state.lock().unwrap();
-                    state.installer_output.push(line.clone());
-
-                    // Keep only last 100 lines
-                    if state.installer_output.len() > 100 {
-                        state.installer_output.remove(0);
-                    }
-
-                    // Update progress based on output content
-                    if line.contains("Starting Arch Linux installation") {
-                        state.installation_progress = 10;
-                        state.status_message = "Installation started".to_string();
-                    } else if line.contains("Preparing system") {
-                        state.installation_progress = 15;
-                        state.status_message = "Preparing system".to_string();
-                    } else if line.contains("Starting disk partitioning") {
-                        state.installation_progress = 25;
-                        state.status_message = "Partitioning disk".to_string();
-                    } else if line.contains("Installing base system") {
-                        state.installation_progress = 40;
-                        state.status_message = "Installing base system".to_string();
-                    } else if line.contains("Configuring system") {
-                        state.installation_progress = 60;
-                        state.status_message = "Configuring system".to_string();
-                    } else if line.contains("Installing packages") {
-                        state.installation_progress = 75;
-                        state.status_message = "Installing packages".to_string();
-                    } else if line.contains("Configuring bootloader") {
-                        state.installation_progress = 85;
-                        state.status_message = "Configuring bootloader".to_string();
-                    } else if line.contains("Finalizing installation") {
-                        state.installation_progress = 95;
-                        state.status_message = "Finalizing installation".to_string();
-                    } else if line.contains("Installation complete") {
-                        state.installation_progress = 100;
-                        state.status_message = "Installation completed successfully!".to_string();
-                    }
-                }
-            });
-        }
+        // Rates of the local target disks; a remote install has no /proc to read
+        let disks = self.disks();
+        let sampler = ThroughputSampler::start(self.app_state.clone(), &disks);
+        let watchdog = Watchdog::start(
+            self.app_state.clone(),
+            child.id(),
+            self.timeout_policy.clone(),
+        );
 
-        // Handle stderr in separate thread
-        if let Some(stderr) = child.stderr.take() {
-            let app_state = Arc::clone(&self.app_state);
+        monitor_installer_output(
+            child,
+            self.app_state.clone(),
+            Some(sampler),
+            Some(watchdog),
+            Some(env),
+            self.notifier(),
+        );
 
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines().map_while(Result::ok) {
-                    let mut state = app_state.lock().unwrap();
-                    state.installer_output.push(format!("ERROR: {}", line));
+        Ok(())
+    }
+}
 
-                    // Keep only last 100 lines
-                    if state.installer_output.len() > 100 {
-                        state.installer_output.remove(0);
-                    }
+/// Stream installer stdout/stderr into the app state and track completion
+///
+/// Shared by local and remote installs: progress is derived from the same
+/// phase markers and pacman package counts regardless of where the script
+/// runs. With a `sampler`, download and disk rates are shown while a transfer
+/// phase runs; with a `watchdog`, commands that stay quiet too long can be
+/// killed. Both stop when the installer exits, and `env` is dropped then, so
+/// a configuration file it wrote stays readable for the whole install. When
+/// the installer ends, successfully or not, `notifier` announces it.
+pub fn monitor_installer_output(
+    mut child: Child,
+    app_state: StateHandle,
+    sampler: Option<ThroughputSampler>,
+    watchdog: Option<Watchdog>,
+    env: Option<ScriptEnv>,
+    notifier: Notifier,
+) {
+    // Handle stdout in separate thread
+    if let Some(stdout) = child.stdout.take() {
+        let app_state = app_state.clone();
+        let sampled = sampler.is_some();
 
-                    // Update app state
-                    state.status_message = format!("Error: {}", line);
+        thread::spawn(move || {
+            let reader = BufReader::new(stdout);
+            let mut phase = 0;
+            for line in reader.lines().map_while(Result::ok) {
+                // Phase markers, but not in lane or failure lines
+                let marker = if line.starts_with(lanes::MARKER)
+                    || PhaseFailure::from_output_line(&line).is_some()
+                {
+                    None
+                } else {
+                    PHASES
+                        .iter()
+                        .position(|(marker, _, _)| line.contains(marker))
+                };
+                if let Some(index) = marker {
+                    phase = index;
                 }
-            });
-        }
+                // The hung command was killed to retry its phase: answer
+                // install.sh's recovery prompt here, not on the UI thread
+                let retried = PhaseFailure::from_output_line(&line).is_some()
+                    && app_state
+                        .query(|state| std::mem::take(&mut state.install.watchdog.retry_pending))
+                        .unwrap_or_default()
+                    && recovery::decision_file()
+                        .and_then(|path| recovery::answer(&path, RecoveryChoice::Retry))
+                        .is_ok();
+                let followed = app_state.update(move |state| {
+                    follow_line(state, &line, marker, phase, sampled, retried)
+                });
+                if !followed {
+                    break;
+                }
+            }
+        });
+    }
 
-        // Wait for installation completion in separate thread
-        let app_state = Arc::clone(&self.app_state);
+    // Handle stderr in separate thread
+    if let Some(stderr) = child.stderr.take() {
+        let app_state = app_state.clone();
 
-        thread::spawn(move || match child.wait() {
-            Ok(status) => {
-                let mut state = app_state.lock().unwrap();
+        thread::spawn(move || {
+            let reader = BufReader::new(stderr);
+            for line in reader.lines().map_while(Result::ok) {
+                let sent = app_state.update(move |state| {
+                    state.install.output.push(format!("ERROR: {}", line));
+                    state.install.metrics.follow_line(&line);
+                    state.install.watchdog.note_output(Instant::now());
 
-                if status.success() {
-                    state.installation_progress = 100;
-                    state.mode = crate::app::AppMode::Complete;
-                    state.status_message = "Installation completed successfully!".to_string();
-                    state
-                        .installer_output
-                        .push("Installation completed successfully!".to_string());
-                } else {
-                    state.status_message = format!(
-                        "Installation failed with exit code: {}",
-                        status.code().unwrap_or(-1)
-                    );
-                    state.installer_output.push(format!(
-                        "Installation failed with exit code: {}",
-                        status.code().unwrap_or(-1)
-                    ));
+                    // Update app state
+                    state.status.error(format!("Error: {}", line));
+                });
+                if !sent {
+                    break;
                 }
             }
-            Err(e) => {
-                let mut state = app_state.lock().unwrap();
+        });
+    }
 
-                state
-                    .installer_output
-                    .push(format!("ERROR: Failed to wait for installer: {}", e));
-                state.status_message = format!("Installation error: {}", e);
-            }
+    // Wait for installation completion in separate thread
+    thread::spawn(move || {
+        let outcome = wait_for_installer(child, &app_state, sampler, watchdog, env);
+        notifier.notify(&outcome, |line| {
+            app_state.update(move |state| state.install.output.push(line));
         });
+    });
+}
 
-        Ok(())
+/// Show a line of installer output; `marker` is the phase it starts, if any,
+/// `phase` the one running, and `retried` whether a failure it reports was
+/// already answered with retry
+fn follow_line(
+    state: &mut AppState,
+    line: &str,
+    marker: Option<usize>,
+    phase: usize,
+    sampled: bool,
+    retried: bool,
+) {
+    state.install.watchdog.note_output(Instant::now());
+    // Steps running side by side report in their own lanes; their markers
+    // are neither shown in the log nor counted in the metrics
+    state.install.lanes.follow_line(line, Instant::now());
+    if line.starts_with(lanes::MARKER) {
+        return;
+    }
+    state.install.output.push(line.to_string());
+    state.install.metrics.follow_line(line);
+    let installed_before = state.install.package_progress.installed;
+
+    // install.sh waits for a recovery choice after a failed phase
+    if let Some(failure) = PhaseFailure::from_output_line(line) {
+        if retried {
+            state.status.info(format!("Retrying {}", failure.phase));
+            return;
+        }
+        state.status.error(format!("{} failed", failure.phase));
+        state.install.recovery_dialog = Some(RecoveryDialogState::new(failure));
+        // Not over a dialog or a program in the embedded terminal
+        if !state.mode.holds_input() {
+            state.transition(crate::app::AppMode::RecoveryDialog);
+        }
+        return;
+    }
+
+    // Phase markers set the progress, pacman's package counts
+    // move it towards the next phase
+    if let Some(index) = marker {
+        let (_, progress, status) = PHASES[index];
+        state.install.progress = progress;
+        state.install.package_progress = PackageProgress::default();
+        state.status.info(status);
+        if progress < 100 {
+            state.install.metrics.begin_phase(status, Instant::now());
+            state.install.watchdog.phase = Some(status.to_string());
+        }
+        // A retry asked for a killed command is over once a phase starts,
+        // whether the kill failed the phase or not
+        state.install.watchdog.retry_pending = false;
+    } else if state.install.package_progress.update(line) {
+        let installed = state.install.package_progress.installed;
+        state.install.metrics.packages_installed += installed.saturating_sub(installed_before);
+        let start = PHASES[phase].1;
+        let end = PHASES.get(phase + 1).map_or(100, |next| next.1);
+        let progress =
+            start + ((end - start) as f64 * state.install.package_progress.fraction()) as u8;
+        // Later transactions of a phase start over at zero
+        state.install.progress = state.install.progress.max(progress);
+        let summary = state.install.package_progress.summary();
+        state.status.info(summary);
+    }
+
+    if sampled {
+        match throughput::transfer_phase(line) {
+            Some(true) if state.install.throughput.is_none() => {
+                state.install.throughput = Some(Default::default())
+            }
+            Some(false) => state.install.throughput = None,
+            _ => {}
+        }
+    }
+}
+
+/// Wait for the installer to exit and show how it ended
+fn wait_for_installer(
+    mut child: Child,
+    app_state: &StateHandle,
+    sampler: Option<ThroughputSampler>,
+    watchdog: Option<Watchdog>,
+    env: Option<ScriptEnv>,
+) -> Outcome {
+    let status = child.wait();
+    drop(sampler);
+    drop(watchdog);
+    drop(env);
+
+    // The status message and the line the output ends with
+    let (success, exit_code, message, last_line) = match status {
+        Ok(status) => {
+            let message = if status.success() {
+                "Installation completed successfully!".to_string()
+            } else {
+                format!(
+                    "Installation failed with exit code: {}",
+                    status.code().unwrap_or(-1)
+                )
+            };
+            (status.success(), status.code(), message.clone(), message)
+        }
+        Err(e) => (
+            false,
+            None,
+            format!("Installation error: {}", e),
+            format!("ERROR: Failed to wait for installer: {}", e),
+        ),
+    };
+
+    let shown = message.clone();
+    let summary = app_state.query(move |state| {
+        state.install.throughput = None;
+        state.install.lanes = Default::default();
+        state.install.package_progress = PackageProgress::default();
+        state.install.watchdog = Default::default();
+
+        if success {
+            state.install.progress = 100;
+            if let Some(started) = state.install.started {
+                state.install.metrics.finish(started, Instant::now());
+            }
+            // Over a dialog or a program in the embedded terminal, once it is left
+            state.transition_when_free(crate::app::AppMode::Complete);
+            state.status.info(shown);
+        } else {
+            state.status.error(shown);
+        }
+        state.install.output.push(last_line);
+        state.install.metrics.clone()
+    });
+    Outcome {
+        success,
+        exit_code,
+        message,
+        summary,
     }
 }
@@ -0,0 +1,309 @@
+//! Recording and replaying keystrokes
+//!
+//! `--record FILE` writes every key press and resize of a TUI session to a
+//! plain text file; `--replay FILE` feeds such a file back in with the same
+//! timing, after which the keyboard takes over. A recording attached to a
+//! bug report reproduces the path that led to it, and tests replay files
+//! without the delays on a `TestBackend` (see `App::play`).
+//!
+//! One event per line, the delay since the previous event first:
+//!
+//! ```text
+//! # archinstall-tui keystrokes
+//! 0 Down
+//! 420 Enter
+//! 130 Ctrl+q
+//! 900 resize 120x40
+//! ```
+//!
+//! Characters typed into password fields, proxy URLs and the embedded
+//! terminal are recorded as `x`.
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// First line of a recording
+const HEADER: &str = "# archinstall-tui keystrokes";
+
+/// Character recorded in place of password characters
+const SECRET: char = 'x';
+
+/// Named keys; single characters are written as themselves
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Char(' '), "Space"),
+];
+
+const MODIFIER_NAMES: &[(KeyModifiers, &str)] = &[
+    (KeyModifiers::CONTROL, "Ctrl+"),
+    (KeyModifiers::ALT, "Alt+"),
+    (KeyModifiers::SHIFT, "Shift+"),
+];
+
+/// Writes the events of a session to a keystroke file
+pub struct Recorder {
+    file: BufWriter<File>,
+    last: Option<Instant>,
+}
+
+impl Recorder {
+    /// Create (or truncate) the keystroke file at `path`
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+        file.flush()?;
+        Ok(Self { file, last: None })
+    }
+
+    /// Append an event; `secret` replaces typed characters (password fields)
+    ///
+    /// Events that cannot be replayed (key releases, mouse, focus) are skipped.
+    pub fn record(&mut self, event: &Event, secret: bool) -> std::io::Result<()> {
+        let Some(line) = event_line(event, secret) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let delay = self
+            .last
+            .map_or(0, |last| now.duration_since(last).as_millis());
+        self.last = Some(now);
+        writeln!(self.file, "{} {}", delay, line)?;
+        // Flushed per event so a crash still leaves the steps that led to it
+        self.file.flush()
+    }
+}
+
+/// Events of a keystroke file, fed in as they fall due
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    events: Vec<(Duration, Event)>,
+    next: usize,
+    due: Option<Instant>,
+}
+
+impl Replay {
+    /// Read a keystroke file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse the contents of a keystroke file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = parse_line(line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            events.push(event);
+        }
+        Ok(Self {
+            events,
+            ..Self::default()
+        })
+    }
+
+    /// All events in order, without their delays
+    #[allow(dead_code)] // Used by the end-to-end tests
+    pub fn events(&self) -> impl Iterator<Item = Event> + '_ {
+        self.events.iter().map(|(_, event)| event.clone())
+    }
+
+    /// The next event if its delay has passed
+    ///
+    /// The first call starts the clock.
+    pub fn next_due(&mut self) -> Option<Event> {
+        let (delay, event) = self.events.get(self.next)?;
+        let now = Instant::now();
+        let due = *self.due.get_or_insert(now + *delay);
+        if now < due {
+            return None;
+        }
+        self.next += 1;
+        self.due = self.events.get(self.next).map(|(delay, _)| due + *delay);
+        Some(event.clone())
+    }
+
+    /// Whether every event has been fed in
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+}
+
+/// The recorded form of an event, without the delay
+fn event_line(event: &Event, secret: bool) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => {
+            let code = match key.code {
+                KeyCode::Char(c) if secret && c != ' ' => KeyCode::Char(SECRET),
+                code => code,
+            };
+            key_name(code, key.modifiers)
+        }
+        Event::Resize(cols, rows) => Some(format!("resize {}x{}", cols, rows)),
+        _ => None,
+    }
+}
+
+fn key_name(code: KeyCode, modifiers: KeyModifiers) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        code => KEY_NAMES
+            .iter()
+            .find(|(known, _)| *known == code)
+            .map(|(_, name)| name.to_string())?,
+    };
+    let prefix: String = MODIFIER_NAMES
+        .iter()
+        .filter(|(modifier, _)| modifiers.contains(*modifier))
+        .map(|(_, prefix)| *prefix)
+        .collect();
+    Some(prefix + &name)
+}
+
+fn parse_line(line: &str) -> Result<(Duration, Event), String> {
+    let (delay, spec) = line
+        .split_once(' ')
+        .ok_or_else(|| format!("expected '<delay ms> <key>', got '{}'", line))?;
+    let delay: u64 = delay
+        .parse()
+        .map_err(|_| format!("invalid delay '{}'", delay))?;
+    let event = match spec.strip_prefix("resize ") {
+        Some(size) => {
+            let (cols, rows) = size
+                .split_once('x')
+                .and_then(|(cols, rows)| Some((cols.parse().ok()?, rows.parse().ok()?)))
+                .ok_or_else(|| format!("invalid size '{}'", size))?;
+            Event::Resize(cols, rows)
+        }
+        None => Event::Key(parse_key(spec)?),
+    };
+    Ok((Duration::from_millis(delay), event))
+}
+
+fn parse_key(spec: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = spec;
+    while let Some((modifier, rest)) = MODIFIER_NAMES.iter().find_map(|(modifier, prefix)| {
+        name.strip_prefix(prefix)
+            .filter(|rest| !rest.is_empty())
+            .map(|rest| (*modifier, rest))
+    }) {
+        modifiers |= modifier;
+        name = rest;
+    }
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+            Some(n) => KeyCode::F(n),
+            None => KEY_NAMES
+                .iter()
+                .find(|(_, known)| *known == name)
+                .map(|(code, _)| *code)
+                .ok_or_else(|| format!("unknown key '{}'", spec))?,
+        },
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_events_round_trip() {
+        let events = [
+            key(KeyCode::Down, KeyModifiers::NONE),
+            key(KeyCode::Char(' '), KeyModifiers::NONE),
+            key(KeyCode::Char('+'), KeyModifiers::CONTROL),
+            key(KeyCode::Char('R'), KeyModifiers::SHIFT),
+            key(KeyCode::F(1), KeyModifiers::NONE),
+            key(KeyCode::Char('F'), KeyModifiers::NONE),
+            Event::Resize(120, 40),
+        ];
+        let text: String = events
+            .iter()
+            .map(|event| format!("10 {}\n", event_line(event, false).unwrap()))
+            .collect();
+        assert_eq!(
+            text,
+            "10 Down\n10 Space\n10 Ctrl++\n10 Shift+R\n10 F1\n10 F\n10 resize 120x40\n"
+        );
+        let replay = Replay::parse(&format!("{}\n\n{}", HEADER, text)).unwrap();
+        assert_eq!(replay.events().collect::<Vec<_>>(), events);
+    }
+
+    #[test]
+    fn test_secrets_are_not_recorded() {
+        let typed = key(KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(event_line(&typed, true).unwrap(), "x");
+        let enter = key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(event_line(&enter, true).unwrap(), "Enter");
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let error = Replay::parse("0 Down\n5 Hyper\n").unwrap_err();
+        assert_eq!(error, "line 2: unknown key 'Hyper'");
+        assert!(Replay::parse("soon Enter")
+            .unwrap_err()
+            .contains("invalid delay"));
+        assert!(Replay::parse("0 resize 80")
+            .unwrap_err()
+            .contains("invalid size"));
+    }
+
+    #[test]
+    fn test_replay_waits_for_each_delay() {
+        let mut replay = Replay::parse("0 a\n60000 b\n").unwrap();
+        assert!(replay.next_due().is_some());
+        assert!(replay.next_due().is_none(), "second event is a minute away");
+        assert!(!replay.is_finished());
+    }
+
+    #[test]
+    fn test_recorder_writes_delays() {
+        let path = std::env::temp_dir().join(format!("archinstall-keys-{}", std::process::id()));
+        let mut recorder = Recorder::create(&path).unwrap();
+        recorder
+            .record(&key(KeyCode::Enter, KeyModifiers::NONE), false)
+            .unwrap();
+        let mut release = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        recorder.record(&Event::Key(release), false).unwrap();
+        drop(recorder);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, format!("{}\n0 Enter\n", HEADER));
+        assert_eq!(Replay::load(&path).unwrap().events().count(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+//! Progress lanes for installer steps running side by side
+//!
+//! Some steps of a phase do not depend on each other: once the clock is
+//! synced, ranking mirrors and initialising the pacman keyring can happen at
+//! once while the system is prepared. install.sh runs such steps with
+//! `run_parallel_phases`, which starts a step as soon as the steps it depends
+//! on are done, at most `ARCHINSTALL_PARALLEL_PHASES` at a time (default 2).
+//! Every change of a step is announced with a [`MARKER`] line and the step's
+//! output is prefixed with its name:
+//!
+//! ```text
+//! ARCHINSTALL_LANE: wait Package database
+//! ARCHINSTALL_LANE: start Mirror ranking
+//! [Mirror ranking] [2025-01-01 10:00:00] INFO: Configuring package mirrors...
+//! ARCHINSTALL_LANE: done Mirror ranking
+//! ```
+//!
+//! While a group of steps runs, the Installation screen draws one lane per
+//! step with its state, how long it ran and its last line.
+
+use crate::events::strip_ansi;
+use std::time::{Duration, Instant};
+
+/// Start of the line install.sh prints when a step changes state
+pub const MARKER: &str = "ARCHINSTALL_LANE:";
+
+/// Where a step stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaneState {
+    /// Waiting for the steps it depends on or for a free slot
+    Waiting,
+    Running,
+    Done,
+    Failed,
+    /// Not run because a step it depends on failed
+    Skipped,
+}
+
+impl LaneState {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "wait" => Some(Self::Waiting),
+            "start" => Some(Self::Running),
+            "done" => Some(Self::Done),
+            "failed" => Some(Self::Failed),
+            "skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+
+    /// Whether the step has yet to finish
+    pub fn is_pending(self) -> bool {
+        matches!(self, Self::Waiting | Self::Running)
+    }
+
+    /// Status word shown in the lane
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// One step of a parallel group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lane {
+    pub name: String,
+    pub state: LaneState,
+    /// Last line the step printed, without colour codes
+    pub last_line: String,
+    started: Option<Instant>,
+    finished: Option<Instant>,
+}
+
+impl Lane {
+    /// Time the step has been running, or ran
+    pub fn elapsed(&self, now: Instant) -> Option<Duration> {
+        let started = self.started?;
+        Some(self.finished.unwrap_or(now).saturating_duration_since(started))
+    }
+}
+
+/// Steps of the parallel group install.sh runs now, or ran last
+#[derive(Debug, Clone, Default)]
+pub struct Lanes {
+    pub lanes: Vec<Lane>,
+}
+
+impl Lanes {
+    /// Follow a line of installer output
+    ///
+    /// Returns `true` for marker lines and output of a known step. A marker
+    /// arriving after every step finished begins a new group.
+    pub fn follow_line(&mut self, line: &str, now: Instant) -> bool {
+        if let Some(rest) = line.trim().strip_prefix(MARKER) {
+            let Some((state, name)) = rest.trim_start().split_once(' ') else {
+                return false;
+            };
+            let Some(state) = LaneState::parse(state) else {
+                return false;
+            };
+            if !self.is_active() {
+                self.lanes.clear();
+            }
+            let name = name.trim();
+            let index = match self.lanes.iter().position(|lane| lane.name == name) {
+                Some(index) => index,
+                None => {
+                    self.lanes.push(Lane {
+                        name: name.to_string(),
+                        state,
+                        last_line: String::new(),
+                        started: None,
+                        finished: None,
+                    });
+                    self.lanes.len() - 1
+                }
+            };
+            let lane = &mut self.lanes[index];
+            lane.state = state;
+            match state {
+                LaneState::Waiting => {}
+                LaneState::Running => lane.started = Some(now),
+                _ => lane.finished = Some(now),
+            }
+            return true;
+        }
+
+        let Some((name, output)) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+        else {
+            return false;
+        };
+        match self.lanes.iter_mut().find(|lane| lane.name == name) {
+            Some(lane) => {
+                let output = strip_ansi(output);
+                if !output.trim().is_empty() {
+                    lane.last_line = output.trim().to_string();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a group has steps left to finish
+    pub fn is_active(&self) -> bool {
+        self.lanes.iter().any(|lane| lane.state.is_pending())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lanes_follow_markers_and_output() {
+        let mut lanes = Lanes::default();
+        let now = Instant::now();
+        for line in [
+            "ARCHINSTALL_LANE: wait Package database",
+            "ARCHINSTALL_LANE: start Mirror ranking",
+            "ARCHINSTALL_LANE: start Keyring",
+        ] {
+            assert!(lanes.follow_line(line, now));
+        }
+        assert!(lanes.follow_line(
+            "[Mirror ranking] \x1b[0m[2025-01-01 10:00:00] INFO: Ranking mirrors\x1b[0m",
+            now
+        ));
+        assert!(!lanes.follow_line("[2025-01-01 10:00:00] INFO: Preparing system", now));
+
+        assert_eq!(lanes.lanes.len(), 3);
+        assert_eq!(lanes.lanes[0].state, LaneState::Waiting);
+        assert_eq!(
+            lanes.lanes[1].last_line,
+            "[2025-01-01 10:00:00] INFO: Ranking mirrors"
+        );
+        assert_eq!(lanes.lanes[0].elapsed(now), None);
+
+        let later = now + Duration::from_secs(5);
+        lanes.follow_line("ARCHINSTALL_LANE: done Mirror ranking", later);
+        lanes.follow_line("ARCHINSTALL_LANE: failed Keyring", later);
+        assert!(lanes.is_active());
+        lanes.follow_line("ARCHINSTALL_LANE: skipped Package database", later);
+        assert!(!lanes.is_active());
+        assert_eq!(
+            lanes.lanes[1].elapsed(later + Duration::from_secs(60)),
+            Some(Duration::from_secs(5))
+        );
+
+        // The next group replaces the finished one
+        lanes.follow_line("ARCHINSTALL_LANE: start Locale generation", later);
+        assert_eq!(lanes.lanes.len(), 1);
+        assert_eq!(lanes.lanes[0].state, LaneState::Running);
+    }
+
+    #[test]
+    fn test_malformed_markers_are_ignored() {
+        let mut lanes = Lanes::default();
+        let now = Instant::now();
+        assert!(!lanes.follow_line("ARCHINSTALL_LANE: start", now));
+        assert!(!lanes.follow_line("ARCHINSTALL_LANE: paused Keyring", now));
+        assert!(lanes.lanes.is_empty());
+    }
+}
@@ -3,21 +3,75 @@
 //! This library provides the core functionality for the Arch Linux TUI installer.
 
 pub mod app;
+pub mod arch;
+pub mod aur_helper;
 pub mod cli;
+pub mod clone;
 pub mod components;
 pub mod config;
 pub mod config_file;
+pub mod desktop;
+pub mod disk;
+pub mod esp;
 pub mod error;
+pub mod events;
+pub mod facts;
+pub mod filesystem;
+pub mod grub_theme;
+pub mod hardware;
+pub mod help;
+pub mod hibernation;
+pub mod initramfs;
 pub mod input;
+pub mod install_metrics;
+pub mod install_stats;
 pub mod install_state;
 pub mod installer;
+pub mod keylog;
+pub mod lanes;
+pub mod logging;
+pub mod low_memory;
+pub mod lvm;
+pub mod mount_plan;
+pub mod multiboot;
+pub mod network;
+pub mod notify;
+pub mod package_progress;
 pub mod package_utils;
+pub mod pacman;
+pub mod partition_check;
+pub mod phases;
+pub mod pkg_cache;
+pub mod password;
+pub mod power;
+pub mod preseed;
+pub mod privilege;
 pub mod process_guard;
+pub mod proxy;
+pub mod pxe;
+pub mod recovery;
+pub mod region;
+pub mod remote;
+pub mod script_env;
 pub mod script_manifest;
 pub mod scrolling;
+pub mod self_update;
+pub mod serial;
+pub mod services;
+pub mod session;
+pub mod shell;
+pub mod simulate;
+pub mod ssh_keys;
 pub mod theme;
+pub mod throughput;
+pub mod timesync;
+pub mod tools;
 pub mod types;
 pub mod ui;
+pub mod var_tmp;
+pub mod watchdog;
+pub mod wiki;
+pub mod wizard;
 
 // Re-export main types for convenience
 pub use config::{ConfigOption, Configuration, Package};
@@ -30,6 +84,8 @@ pub use script_manifest::{
     ValidatedExecution,
 };
 pub use types::{
-    AurHelper, AutoToggle, Bootloader, BootMode, DesktopEnvironment, DisplayManager, Filesystem,
-    GpuDriver, GrubTheme, Kernel, PartitionScheme, PlymouthTheme, SnapshotFrequency, Toggle,
+    AudioServer, AurHelper, AutoToggle, Bootloader, BootMode, DesktopEnvironment, DisplayManager,
+    ErrorPolicy, ExistingOsPolicy, Filesystem, GpuDriver, GrubTheme, GuestTools, HardwareClock,
+    Kernel, LidSwitch, Microcode, PartitionScheme, PlymouthTheme, PowerManagement,
+    SnapshotFrequency, TimeSyncDaemon, Toggle,
 };
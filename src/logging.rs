@@ -0,0 +1,236 @@
+//! Logging setup
+//!
+//! The level comes from `-v`/`-vv` or `--log-level`, falling back to
+//! `RUST_LOG` and then `info`. Records go to standard error unless
+//! `--log-file` names a file; `--log-json` writes one JSON object per line
+//! instead of text. Files are rotated by size, because on the ISO they live
+//! in RAM and a long rescue session must not fill it: at `--log-max-size`
+//! the file is renamed to `FILE.1` (shifting older ones up to `FILE.3`) and
+//! a new one is started.
+//!
+//! From `debug` on, `LOG_LEVEL=DEBUG` is exported so the installer scripts
+//! print their debug lines too.
+
+use env_logger::{Builder, Target};
+use log::LevelFilter;
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Rotated files kept next to the log file
+pub const KEEP: usize = 3;
+
+/// Default size of the log file before it is rotated, in MiB
+pub const DEFAULT_MAX_SIZE_MIB: u64 = 8;
+
+/// Variable the installer scripts read their log level from
+const SCRIPT_LEVEL_VAR: &str = "LOG_LEVEL";
+
+/// Where and how to log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Level given on the command line; `None` defers to RUST_LOG
+    pub level: Option<LevelFilter>,
+    /// File to log to instead of standard error
+    pub file: Option<PathBuf>,
+    /// Write JSON lines instead of text
+    pub json: bool,
+    /// Size at which the log file is rotated, in bytes
+    pub max_size: u64,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            level: None,
+            file: None,
+            json: false,
+            max_size: DEFAULT_MAX_SIZE_MIB * 1024 * 1024,
+        }
+    }
+}
+
+/// Level asked for with `-v` repeated `verbose` times
+pub fn verbosity(verbose: u8) -> Option<LevelFilter> {
+    match verbose {
+        0 => None,
+        1 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+/// Install the global logger
+///
+/// Fails when the log file cannot be opened.
+pub fn init(options: &LogOptions) -> io::Result<()> {
+    let mut builder = Builder::new();
+    builder.filter_level(LevelFilter::Info);
+    // RUST_LOG may still tune single modules below the command line level
+    builder.parse_default_env();
+    if let Some(level) = options.level {
+        builder.filter_level(level);
+    }
+
+    if options.json {
+        builder.format(|buf, record| {
+            let line = json_line(
+                &buf.timestamp().to_string(),
+                record.level(),
+                record.target(),
+                &record.args().to_string(),
+            );
+            writeln!(buf, "{}", line)
+        });
+    } else {
+        builder.format(|buf, record| {
+            writeln!(
+                buf,
+                "[{} {}:{}] {}",
+                record.level(),
+                record.file().unwrap_or("unknown"),
+                record.line().unwrap_or(0),
+                record.args()
+            )
+        });
+    }
+
+    if let Some(ref path) = options.file {
+        let file = RotatingFile::open(path, options.max_size)?;
+        builder.target(Target::Pipe(Box::new(file)));
+    }
+    builder.init();
+
+    if options.level >= Some(LevelFilter::Debug) {
+        std::env::set_var(SCRIPT_LEVEL_VAR, "DEBUG");
+    }
+    Ok(())
+}
+
+/// One log record as a JSON line
+fn json_line(timestamp: &str, level: log::Level, target: &str, message: &str) -> String {
+    json!({
+        "timestamp": timestamp,
+        "level": level.as_str(),
+        "target": target,
+        "message": message,
+    })
+    .to_string()
+}
+
+/// Log file that is rotated once it reaches a size
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, rotating it first if it is already full
+    pub fn open(path: &Path, max_size: u64) -> io::Result<Self> {
+        let mut log = Self {
+            path: path.to_path_buf(),
+            max_size,
+            file: Self::append(path)?,
+            written: fs::metadata(path)?.len(),
+        };
+        if log.written >= log.max_size {
+            log.rotate()?;
+        }
+        Ok(log)
+    }
+
+    fn append(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    /// Path of the `index`th rotated file
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Shift FILE.1..FILE.KEEP-1 up by one, move FILE to FILE.1, start anew
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for index in (1..KEEP).rev() {
+            let from = self.rotated(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))?;
+        self.file = Self::append(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A record is never split; one larger than the limit gets a file of its own
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity() {
+        assert_eq!(verbosity(0), None);
+        assert_eq!(verbosity(1), Some(LevelFilter::Debug));
+        assert_eq!(verbosity(5), Some(LevelFilter::Trace));
+    }
+
+    #[test]
+    fn test_json_line() {
+        let line = json_line(
+            "2026-01-01T00:00:00Z",
+            log::Level::Warn,
+            "archinstall_tui::pacman",
+            "mirror \"a\" is slow",
+        );
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "archinstall_tui::pacman");
+        assert_eq!(value["message"], "mirror \"a\" is slow");
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_newest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archinstall.log");
+        let mut log = RotatingFile::open(&path, 10).unwrap();
+        for record in ["first\n", "second\n", "third\n", "fourth\n", "fifth\n"] {
+            log.write_all(record.as_bytes()).unwrap();
+        }
+        log.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("archinstall.log"), "fifth\n");
+        assert_eq!(read("archinstall.log.1"), "fourth\n");
+        assert_eq!(read("archinstall.log.3"), "second\n");
+        assert!(!dir.path().join("archinstall.log.4").exists());
+
+        // A full file left by an earlier run is rotated on open
+        drop(log);
+        fs::write(&path, "0123456789").unwrap();
+        RotatingFile::open(&path, 10).unwrap();
+        assert_eq!(read("archinstall.log"), "");
+        assert_eq!(read("archinstall.log.1"), "0123456789");
+    }
+}
@@ -0,0 +1,82 @@
+//! Low-memory installs
+//!
+//! The live ISO keeps its root filesystem in RAM, so with less than about
+//! 1 GiB pacstrap runs out of memory. Below [`THRESHOLD`] install.sh uses a
+//! low-memory strategy: zram swap for the live system, a smaller package
+//! set, one download at a time, and packages installed in batches with the
+//! package cache emptied in between. The script decides for itself (see
+//! `perform_preflight_checks` in scripts/utils.sh); a frontend that has
+//! already measured the memory passes [`LOW_MEMORY_VAR`] so both agree.
+//! An ISO booted with `copytoram` holds a copy of its image in RAM as well.
+
+use crate::tools::resize::format_size;
+use std::fs;
+
+/// Below this much memory the low-memory strategy is used
+pub const THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// Variable telling install.sh to use the low-memory strategy (`Yes`)
+pub const LOW_MEMORY_VAR: &str = "LOW_MEMORY";
+
+/// Where archiso keeps the image it copied to RAM
+const COPYTORAM_DIR: &str = "/run/archiso/copytoram";
+
+/// Whether `memory` bytes call for the low-memory strategy
+pub fn is_low(memory: Option<u64>) -> bool {
+    memory.is_some_and(|memory| memory < THRESHOLD)
+}
+
+/// Whether the live ISO was copied to RAM at boot
+pub fn copied_to_ram() -> bool {
+    fs::read_dir(COPYTORAM_DIR).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// What a machine with `memory` bytes should know before installing,
+/// nothing when it has enough
+pub fn warnings(memory: Option<u64>, copied_to_ram: bool) -> Vec<String> {
+    let Some(memory) = memory.filter(|&memory| is_low(Some(memory))) else {
+        return Vec::new();
+    };
+    let mut warnings = vec![
+        format!(
+            "Low memory ({}): installing with the low-memory strategy",
+            format_size(memory)
+        ),
+        "zram swap for the live system, a smaller package set and one download at a time"
+            .to_string(),
+        "Packages are installed in batches, emptying the package cache in between".to_string(),
+    ];
+    if copied_to_ram {
+        warnings.push(
+            "The ISO was copied to RAM; boot it with copytoram=n to leave that memory free"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+/// Warnings for the machine the toolkit runs on
+pub fn detect() -> Vec<String> {
+    warnings(crate::hibernation::total_memory(), copied_to_ram())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_memory_warnings() {
+        const MIB: u64 = 1024 * 1024;
+        assert!(is_low(Some(768 * MIB)));
+        assert!(!is_low(Some(THRESHOLD)));
+        assert!(!is_low(None));
+
+        let low = warnings(Some(768 * MIB), true);
+        assert_eq!(low.len(), 4);
+        assert!(low[0].starts_with("Low memory (768.0 MiB)"));
+        assert!(low[3].contains("copytoram=n"));
+        assert_eq!(warnings(Some(768 * MIB), false).len(), 3);
+        assert!(warnings(Some(4096 * MIB), true).is_empty());
+        assert!(warnings(None, true).is_empty());
+    }
+}
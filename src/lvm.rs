@@ -0,0 +1,274 @@
+//! LVM layout of the automatic LVM strategies
+//!
+//! auto_lvm and auto_luks_lvm put one volume group on the install disk
+//! holding a root volume and, when asked for, /var and /home volumes. A size
+//! is either fixed (`50G`, `512M`, `1T`) or a share of the space still free
+//! in the volume group (`100%FREE`). Fixed sizes are created first, then the
+//! shares in the order root, var, home, so `root = 50G, home = 100%FREE`
+//! gives /home everything but 50 GiB.
+//!
+//! With thin provisioning the volume group holds one thin pool and the
+//! volumes are thin volumes in it: shares are of the pool, and fixed sizes
+//! may add up to more than the pool as long as each one fits.
+
+use crate::tools::resize::format_size;
+use crate::types::PartitionScheme;
+
+pub const DEFAULT_VOLUME_GROUP: &str = "arch";
+pub const DEFAULT_ROOT_SIZE: &str = "50G";
+pub const DEFAULT_VAR_SIZE: &str = NONE;
+pub const DEFAULT_HOME_SIZE: &str = "100%FREE";
+
+/// Size of a volume that is not created
+pub const NONE: &str = "none";
+
+/// Space the strategies put before the LVM partition: ESP, /boot and alignment
+const RESERVED_BYTES: u64 = (1 + 100 + 1024) * MIB;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Longest volume group name LVM accepts
+const MAX_NAME_LEN: usize = 127;
+
+/// Size of a logical volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    /// Fixed size in bytes
+    Bytes(u64),
+    /// Percentage of the free space when the volume is created
+    PercentFree(u8),
+}
+
+/// Whether the strategy uses the configurable layout
+///
+/// The RAID strategies build their volume group on the array and keep their
+/// own layout.
+pub fn is_configurable(scheme: PartitionScheme) -> bool {
+    matches!(
+        scheme,
+        PartitionScheme::AutoLvm | PartitionScheme::AutoLuksLvm
+    )
+}
+
+/// Check a volume group name against LVM's naming rules
+pub fn validate_volume_group(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("LVM volume group name cannot be empty".to_string());
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "LVM volume group name is longer than {} characters",
+            MAX_NAME_LEN
+        ));
+    }
+    if name == "." || name == ".." || name.starts_with('-') {
+        return Err(format!("'{}' is not a valid LVM volume group name", name));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !"+_.-".contains(*c))
+    {
+        return Err(format!(
+            "LVM volume group name cannot contain '{}': use letters, digits, + _ . -",
+            c
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a volume size such as `50G`, `512MiB` or `100%FREE`
+///
+/// Units are binary, as lvcreate reads them. Returns `None` for `none`.
+pub fn parse_size(value: &str) -> Result<Option<Size>, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case(NONE) {
+        return Ok(None);
+    }
+    let invalid = || {
+        format!(
+            "Invalid LVM size '{}': use a size like 50G or a share like 100%FREE",
+            value
+        )
+    };
+    if let Some(percent) = value.strip_suffix("%FREE") {
+        return match percent.parse() {
+            Ok(percent @ 1..=100) => Ok(Some(Size::PercentFree(percent))),
+            _ => Err(invalid()),
+        };
+    }
+
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = &value[digits.len()..];
+    let number: u64 = digits.parse().map_err(|_| invalid())?;
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return Err(invalid()),
+    };
+    match number.checked_mul(1 << shift) {
+        Some(bytes) if bytes >= 4 * MIB => Ok(Some(Size::Bytes(bytes))),
+        Some(_) => Err(format!(
+            "LVM size '{}' is smaller than one 4 MiB extent",
+            value
+        )),
+        None => Err(invalid()),
+    }
+}
+
+/// Space left for the volume group on a disk of `disk_size` bytes
+///
+/// `swap` is the size of the swap partition when it is known.
+pub fn available_space(disk_size: u64, swap: Option<u64>) -> u64 {
+    disk_size.saturating_sub(RESERVED_BYTES + swap.unwrap_or(0))
+}
+
+/// Check the layout and, when `available` bytes are known, that it fits
+///
+/// `home` is `None` without a separate /home volume.
+pub fn validate_layout(
+    volume_group: &str,
+    root: &str,
+    var: &str,
+    home: Option<&str>,
+    thin: bool,
+    available: Option<u64>,
+) -> Result<(), String> {
+    validate_volume_group(volume_group)?;
+    let root = parse_size(root)?.ok_or("The root volume needs a size, not 'none'")?;
+    let home = match home.map(parse_size).transpose()? {
+        Some(None) => return Err("A separate /home needs a home volume size, not 'none'".into()),
+        home => home.flatten(),
+    };
+    let volumes: Vec<(&str, Size)> = [
+        ("root", Some(root)),
+        ("var", parse_size(var)?),
+        ("home", home),
+    ]
+    .into_iter()
+    .filter_map(|(name, size)| Some((name, size?)))
+    .collect();
+
+    // A volume after one taking all the free space would get nothing
+    let shares: Vec<&(&str, Size)> = volumes
+        .iter()
+        .filter(|(_, size)| matches!(size, Size::PercentFree(_)))
+        .collect();
+    if let Some(pair) = shares
+        .windows(2)
+        .find(|pair| pair[0].1 == Size::PercentFree(100))
+    {
+        return Err(format!(
+            "The {} volume takes 100%FREE, leaving nothing for {}",
+            pair[0].0, pair[1].0
+        ));
+    }
+
+    let Some(available) = available else {
+        return Ok(());
+    };
+    let fixed = volumes.iter().filter_map(|(name, size)| match size {
+        Size::Bytes(bytes) => Some((*name, *bytes)),
+        Size::PercentFree(_) => None,
+    });
+    if thin {
+        // Thin volumes may overcommit the pool, but not one by itself
+        if let Some((name, bytes)) = fixed.clone().find(|(_, bytes)| *bytes > available) {
+            return Err(format!(
+                "The {} volume ({}) is larger than the thin pool (about {})",
+                name,
+                format_size(bytes),
+                format_size(available)
+            ));
+        }
+    } else {
+        let total: u64 = fixed.map(|(_, bytes)| bytes).sum();
+        if total > available {
+            return Err(format!(
+                "LVM volumes need {} but the disk leaves about {} for them",
+                format_size(total),
+                format_size(available)
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    #[test]
+    fn test_volume_group_names() {
+        assert!(validate_volume_group("arch").is_ok());
+        assert!(validate_volume_group("vg_data-1.0+x").is_ok());
+        assert!(validate_volume_group("").is_err());
+        assert!(validate_volume_group("-vg").is_err());
+        assert!(validate_volume_group("..").is_err());
+        assert!(validate_volume_group("my vg")
+            .unwrap_err()
+            .contains("cannot contain ' '"));
+        assert!(validate_volume_group(&"v".repeat(128)).is_err());
+    }
+
+    #[test]
+    fn test_parse_sizes() {
+        assert_eq!(parse_size("50G").unwrap(), Some(Size::Bytes(50 * GIB)));
+        assert_eq!(parse_size("512MiB").unwrap(), Some(Size::Bytes(512 * MIB)));
+        assert_eq!(parse_size("2gb").unwrap(), Some(Size::Bytes(2 * GIB)));
+        assert_eq!(
+            parse_size("100%FREE").unwrap(),
+            Some(Size::PercentFree(100))
+        );
+        assert_eq!(parse_size("none").unwrap(), None);
+        assert!(parse_size("0%FREE").is_err());
+        assert!(parse_size("150%FREE").is_err());
+        assert!(parse_size("50").is_err());
+        assert!(parse_size("1M").unwrap_err().contains("extent"));
+        assert!(parse_size("huge").is_err());
+    }
+
+    #[test]
+    fn test_layout_fits_the_disk() {
+        let available = available_space(128 * GIB, Some(8 * GIB));
+        assert!(validate_layout(
+            "arch",
+            "50G",
+            "20G",
+            Some("100%FREE"),
+            false,
+            Some(available)
+        )
+        .is_ok());
+        let error =
+            validate_layout("arch", "100G", "30G", None, false, Some(available)).unwrap_err();
+        assert!(error.contains("need 130.0 GiB"), "{}", error);
+
+        // Thin volumes overcommit, but each must fit the pool
+        assert!(validate_layout("arch", "100G", "30G", None, true, Some(available)).is_ok());
+        assert!(
+            validate_layout("arch", "200G", NONE, None, true, Some(available))
+                .unwrap_err()
+                .contains("larger than the thin pool")
+        );
+        // Without a disk size only the values are checked
+        assert!(validate_layout("arch", "10T", NONE, None, false, None).is_ok());
+    }
+
+    #[test]
+    fn test_layout_values() {
+        assert!(validate_layout("arch", NONE, NONE, None, false, None)
+            .unwrap_err()
+            .contains("root volume"));
+        assert!(validate_layout("arch", "50G", NONE, Some(NONE), false, None).is_err());
+        assert_eq!(
+            validate_layout("arch", "100%FREE", NONE, Some("50%FREE"), false, None).unwrap_err(),
+            "The root volume takes 100%FREE, leaving nothing for home"
+        );
+        // Fixed sizes are created before shares
+        assert!(validate_layout("arch", "100%FREE", "20G", None, false, None).is_ok());
+    }
+}
@@ -3,53 +3,127 @@
 //! A clean, modular TUI for Arch Linux installation with proper separation of concerns.
 
 mod app;
+mod arch;
+mod aur_helper;
 mod cli;
+mod clone;
 mod components;
 mod config;
 mod config_file;
+mod desktop;
+mod disk;
+mod esp;
 mod error;
+mod events;
+mod facts;
+mod filesystem;
+mod grub_theme;
+mod hardware;
+mod help;
+mod hibernation;
+mod initramfs;
 mod input;
+mod install_metrics;
+mod install_stats;
+// Only the stage names are used here, for --output json events
+#[allow(dead_code)]
+mod install_state;
 mod installer;
+mod keylog;
+mod lanes;
+mod logging;
+mod low_memory;
+mod lvm;
+mod mount_plan;
+mod multiboot;
+mod network;
+mod notify;
+mod package_progress;
 mod package_utils;
+mod pacman;
+mod partition_check;
+mod phases;
+mod pkg_cache;
+mod password;
+mod power;
+mod preseed;
+mod privilege;
 mod process_guard;
+mod proxy;
+mod pxe;
+mod recovery;
+mod region;
+mod remote;
+mod script_env;
 mod scrolling;
+mod self_update;
+mod serial;
+mod services;
+mod session;
+mod shell;
+mod simulate;
+mod ssh_keys;
 mod theme;
+mod throughput;
+mod timesync;
+mod tools;
 mod types;
 mod ui;
+mod var_tmp;
+mod watchdog;
+mod wiki;
+mod wizard;
 
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use log::{debug, error, info};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, ClearType};
+use log::{debug, error, info, warn};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io::stdout;
 
 use crate::cli::Cli;
 use crate::config_file::InstallationConfig;
+use crate::events::InstallEvent;
+use crate::proxy::Proxy;
+use crate::script_env::ScriptArgs;
+use crate::theme::Charset;
+use crate::types::{ErrorPolicy, OutputFormat};
 
-/// Initialize the logger with appropriate settings
-fn init_logger() {
-    use env_logger::Builder;
-    use std::io::Write;
+/// Process exit codes for headless installs, stable for provisioning systems
+mod exit_code {
+    /// Installation failed
+    pub const INSTALL_FAILED: i32 = 1;
+    /// Configuration file could not be loaded or failed validation
+    pub const CONFIG_INVALID: i32 = 3;
+    /// Installer script could not be started
+    pub const SPAWN_FAILED: i32 = 4;
+    /// Installation finished but optional phases failed (--on-error=continue)
+    pub const COMPLETED_WITH_ERRORS: i32 = 5;
+    /// Remote target could not be reached or prepared
+    pub const REMOTE_UNREACHABLE: i32 = 6;
+}
 
-    Builder::from_default_env()
-        .format(|buf, record| {
-            writeln!(
-                buf,
-                "[{} {}:{}] {}",
-                record.level(),
-                record.file().unwrap_or("unknown"),
-                record.line().unwrap_or(0),
-                record.args()
-            )
-        })
-        .filter_level(log::LevelFilter::Info)
-        .parse_default_env() // Allows RUST_LOG env var to override
-        .init();
+/// Options for a fully non-interactive install
+#[derive(Debug, Clone, Copy)]
+struct UnattendedOptions {
+    on_error: ErrorPolicy,
+    retries: u8,
 }
 
 /// Main application entry point
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse_args();
+
     // Initialize logging first
-    init_logger();
+    let log_options = logging::LogOptions {
+        level: cli.log_level.or(logging::verbosity(cli.verbose)),
+        file: cli.log_file.clone(),
+        json: cli.log_json,
+        max_size: cli.log_max_size.unwrap_or(logging::DEFAULT_MAX_SIZE_MIB) * 1024 * 1024,
+    };
+    if let Err(e) = logging::init(&log_options) {
+        let file = log_options.file.unwrap_or_default();
+        eprintln!("✗ Cannot write the log to {}: {}", file.display(), e);
+        std::process::exit(exit_code::CONFIG_INVALID);
+    }
     info!("ArchInstall TUI starting up");
 
     // Initialize signal handlers for graceful child process cleanup
@@ -60,8 +134,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     debug!("Signal handlers initialized");
 
-    let cli = Cli::parse_args();
-    debug!("CLI arguments parsed");
+    // Serial consoles get ASCII whatever $TERM claims
+    let display = Display {
+        charset: if cli.ascii || cli.serial {
+            Charset::Ascii
+        } else {
+            Charset::detect()
+        },
+        serial: cli.serial,
+    };
+    debug!("Using {:?} character set", display.charset);
+    let open_tool = cli.open_tool.as_deref();
+    let keystrokes = Keystrokes::open(cli.record.as_deref(), cli.replay.as_deref())?;
+
+    // Set before anything is detected or spawned; scripts inherit it
+    if cli.simulate {
+        info!("Simulation: fake disks, nothing is written to this machine");
+        simulate::enable();
+    }
+    if cli.install_stats {
+        install_stats::enable();
+    }
+
+    // Exported before anything is spawned, so package search, reflector and
+    // install.sh all download through it
+    let proxy = Proxy::from_env().with_overrides(cli.http_proxy, cli.https_proxy, cli.no_proxy);
+    if let Err(e) = proxy.validate() {
+        error!("Invalid proxy: {}", e);
+        eprintln!("✗ {}", e);
+        std::process::exit(exit_code::CONFIG_INVALID);
+    }
+    proxy.apply();
+
+    // Exported the same way, so install.sh finds it for a configuration file too
+    if let Some(dir) = &cli.pkg_cache {
+        if let Err(e) = pkg_cache::validate(&dir.display().to_string()) {
+            error!("Invalid package cache: {}", e);
+            eprintln!("✗ {}", e);
+            std::process::exit(exit_code::CONFIG_INVALID);
+        }
+        std::env::set_var(pkg_cache::ENV_VAR, dir);
+    }
+
+    // Exported so a TUI restarted as root skips the check as well
+    if cli.no_update_check {
+        std::env::set_var(self_update::DISABLE_VAR, "1");
+    }
 
     match cli.command {
         Some(crate::cli::Commands::Validate { config }) => {
@@ -85,97 +203,459 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Some(crate::cli::Commands::Clone { source, output }) => {
+            info!("Cloning system at {:?} into {:?}", source, output);
+            let cloned = match clone::clone_system(&source) {
+                Ok(cloned) => cloned,
+                Err(e) => {
+                    error!("Failed to clone system: {}", e);
+                    eprintln!("✗ Failed to clone system: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if let Err(e) = cloned.config.save_to_file(&output) {
+                error!("Failed to write configuration file: {}", e);
+                eprintln!("✗ Failed to write configuration file: {}", e);
+                std::process::exit(1);
+            }
+            println!("✓ Configuration written to {}", output.display());
+            for note in &cloned.notes {
+                println!("  • {}", note);
+            }
+        }
         Some(crate::cli::Commands::Install {
             config,
             save_config,
+            interactive_missing,
+            unattended,
+            on_error,
+            retries,
+            report,
+            output,
+            command_timeout,
+            timeouts,
         }) => {
-            if let Some(config_path) = config {
+            // The script may run from another directory, so pass an absolute path
+            let report_dir = report.map(std::path::absolute).transpose()?;
+            let policy = watchdog::TimeoutPolicy {
+                default: std::time::Duration::from_secs(command_timeout),
+                ..Default::default()
+            }
+            .with_overrides(&timeouts);
+            if let Some(config_path) = config.as_ref().filter(|_| interactive_missing) {
+                info!("Running TUI installer for the options {:?} leaves unset", config_path);
+                let preseed = match preseed::load_preseed(config_path) {
+                    Ok(preseed) => preseed,
+                    Err(e) => {
+                        error!("Failed to load configuration: {}", e);
+                        eprintln!("✗ {}", e);
+                        std::process::exit(1);
+                    }
+                };
+                run_tui_installer(
+                    report_dir,
+                    policy,
+                    Some(&preseed),
+                    display,
+                    open_tool,
+                    keystrokes,
+                )?;
+            } else if let Some(config_path) = config {
                 info!("Running headless installation with config: {:?}", config_path);
-                run_installer_with_config(&config_path)?;
+                let unattended = unattended.then_some(UnattendedOptions { on_error, retries });
+                run_installer_with_config(&config_path, unattended, report_dir.as_deref(), output)?;
             } else if let Some(save_path) = save_config {
                 info!("Running TUI installer with config save path: {:?}", save_path);
-                run_tui_installer_with_save(&save_path)?;
+                run_tui_installer_with_save(&save_path, display, open_tool, keystrokes)?;
             } else {
                 info!("Running TUI installer in interactive mode");
-                run_tui_installer()?;
+                run_tui_installer(report_dir, policy, None, display, open_tool, keystrokes)?;
             }
         }
+        Some(crate::cli::Commands::Remote {
+            target,
+            config,
+            port,
+            identity,
+            on_error,
+            retries,
+        }) => {
+            if simulate::is_enabled() {
+                error!("Remote installations cannot be simulated");
+                eprintln!("✗ --simulate only works for local installations");
+                std::process::exit(exit_code::CONFIG_INVALID);
+            }
+            info!(
+                "Running remote installation on {} with config: {:?}",
+                target, config
+            );
+            let options = UnattendedOptions { on_error, retries };
+            run_remote_installer(&target, port, identity, &config, options, display)?;
+        }
         Some(crate::cli::Commands::Tools { tool }) => {
             debug!("Running tool command");
             run_tool_command(&tool)?;
         }
-        None => {
-            info!("No command specified, launching TUI installer");
-            run_tui_installer()?;
+        Some(crate::cli::Commands::SelfUpdate { check }) => {
+            run_self_update(check)?;
+        }
+        Some(crate::cli::Commands::Stats { json, clear }) => {
+            run_stats_command(json, clear)?;
+        }
+        Some(crate::cli::Commands::Phases { action }) => {
+            run_phase_command(&action)?;
+        }
+        Some(crate::cli::Commands::Archinstall { action }) => {
+            run_archinstall_command(&action)?;
+        }
+        Some(crate::cli::Commands::Generate { artifact }) => {
+            run_generate_command(&artifact)?;
         }
+        None => match pxe::boot_config_url() {
+            Some(url) => {
+                info!("Kernel command line names config {}, installing unattended", url);
+                run_pxe_install(&url)?;
+            }
+            None => {
+                info!("No command specified, launching TUI installer");
+                run_tui_installer(None, Default::default(), None, display, open_tool, keystrokes)?;
+            }
+        },
     }
 
     Ok(())
 }
 
+/// Keystroke files given with --record and --replay, opened before the TUI starts
+struct Keystrokes {
+    recorder: Option<keylog::Recorder>,
+    replay: Option<keylog::Replay>,
+}
+
+impl Keystrokes {
+    fn open(
+        record: Option<&std::path::Path>,
+        replay: Option<&std::path::Path>,
+    ) -> error::Result<Self> {
+        let recorder = record
+            .map(|path| {
+                keylog::Recorder::create(path).map_err(|e| {
+                    error::general_error(format!("Failed to create {}: {}", path.display(), e))
+                })
+            })
+            .transpose()?;
+        let replay = replay
+            .map(|path| keylog::Replay::load(path).map_err(error::ArchInstallError::config))
+            .transpose()?;
+        Ok(Self { recorder, replay })
+    }
+
+    fn attach(self, app: &mut app::App) {
+        if let Some(recorder) = self.recorder {
+            app.record_keys(recorder);
+        }
+        if let Some(replay) = self.replay {
+            app.replay_keys(replay);
+        }
+    }
+}
+
+/// How the TUI draws: its character set and whether on a serial console
+#[derive(Clone, Copy)]
+struct Display {
+    charset: Charset,
+    serial: bool,
+}
+
+impl Display {
+    /// Put the terminal into raw mode and clear a screen to draw on
+    ///
+    /// Serial consoles draw on the normal screen (see [`serial`]).
+    fn enter(self) -> error::Result<()> {
+        enable_raw_mode().map_err(|e| {
+            error::ArchInstallError::terminal(format!("Failed to enable raw mode: {}", e))
+        })?;
+        if self.serial {
+            crossterm::execute!(stdout(), crossterm::terminal::Clear(ClearType::All)).map_err(
+                |e| error::ArchInstallError::terminal(format!("Failed to clear the screen: {}", e)),
+            )?;
+        } else {
+            crossterm::execute!(stdout(), crossterm::terminal::EnterAlternateScreen).map_err(
+                |e| {
+                    error::ArchInstallError::terminal(format!(
+                        "Failed to enter alternate screen: {}",
+                        e
+                    ))
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Leave raw mode and the screen the TUI drew on
+    fn leave(self) {
+        let _ = disable_raw_mode();
+        if self.serial {
+            let _ = crossterm::execute!(
+                stdout(),
+                crossterm::terminal::Clear(ClearType::All),
+                crossterm::cursor::MoveTo(0, 0)
+            );
+        } else {
+            let _ = crossterm::execute!(stdout(), crossterm::terminal::LeaveAlternateScreen);
+        }
+    }
+
+    fn attach(self, app: &mut app::App) {
+        app.set_charset(self.charset);
+        app.set_serial(self.serial);
+    }
+}
+
 /// Run the TUI installer
-fn run_tui_installer() -> Result<(), Box<dyn std::error::Error>> {
+///
+/// With a `preseed` the guided installer opens on the options it leaves
+/// unset, and no earlier session is offered for restore.
+fn run_tui_installer(
+    report_dir: Option<std::path::PathBuf>,
+    timeout_policy: watchdog::TimeoutPolicy,
+    preseed: Option<&preseed::Preseed>,
+    display: Display,
+    open_tool: Option<&str>,
+    keystrokes: Keystrokes,
+) -> error::Result<()> {
     debug!("Initializing terminal for TUI mode");
 
     // Initialize terminal
-    enable_raw_mode()
-        .map_err(|e| error::general_error(format!("Failed to enable raw mode: {}", e)))?;
-    crossterm::execute!(stdout(), crossterm::terminal::EnterAlternateScreen)
-        .map_err(|e| error::general_error(format!("Failed to enter alternate screen: {}", e)))?;
+    display.enter()?;
 
     // Create terminal backend
     let backend = CrosstermBackend::new(stdout());
-    let mut terminal = Terminal::new(backend)
-        .map_err(|e| error::general_error(format!("Failed to create terminal: {}", e)))?;
+    let mut terminal = Terminal::new(backend).map_err(|e| {
+        error::ArchInstallError::terminal(format!("Failed to create terminal: {}", e))
+    })?;
 
     // Create and run application
     let mut app = app::App::new(None);
-    let result = app.run(&mut terminal);
+    display.attach(&mut app);
+    // Nothing in a simulation needs root
+    app.set_privileged(privilege::is_root() || simulate::is_enabled());
+    app.propose_guest_tools(hardware::detect_hypervisor());
+    app.propose_microcode(hardware::detect_microcode());
+    app.propose_power_management(hardware::detect_laptop());
+    app.use_proxy(&Proxy::from_env());
+    if self_update::startup_check_enabled() {
+        app.check_for_update();
+    }
+    if let Some(dir) = pkg_cache::from_env() {
+        app.use_package_cache(&dir);
+    }
+    if let Some(preseed) = preseed {
+        app.preseed(preseed);
+    } else if let Some(session_path) = session::default_session_path() {
+        app.enable_session_persistence(session_path);
+    }
+    if let Some(dir) = report_dir {
+        app.set_report_dir(dir);
+    }
+    app.set_timeout_policy(timeout_policy);
+    keystrokes.attach(&mut app);
+    let result = open_tool
+        .map_or(Ok(()), |tool| app.open_tool(tool))
+        .and_then(|_| app.run(&mut terminal));
 
     // Cleanup terminal (always attempt cleanup, even if app failed)
-    let _ = disable_raw_mode();
-    let _ = crossterm::execute!(stdout(), crossterm::terminal::LeaveAlternateScreen);
+    display.leave();
+
+    result.and_then(|_| restart_as_root_if_requested(&mut app))
+}
+
+/// Restart as root when the user chose a locked tool and agreed to restart
+///
+/// Only returns if sudo or pkexec could not be started.
+fn restart_as_root_if_requested(app: &mut app::App) -> error::Result<()> {
+    let Some((launcher, tool)) = app.take_root_restart() else {
+        return Ok(());
+    };
+    let e = privilege::restart_as_root(launcher, &tool);
+    Err(error::ArchInstallError::command_not_run(launcher, e))
+}
+
+/// End a headless install that failed before or while running the scripts
+///
+/// In JSON mode the failure is the final `finished` event on stdout.
+fn exit_headless(output: OutputFormat, code: i32, message: String) -> ! {
+    match output {
+        OutputFormat::Text => eprintln!("✗ {}", message),
+        OutputFormat::Json => InstallEvent::Finished {
+            success: false,
+            exit_code: code,
+            message,
+        }
+        .emit(),
+    }
+    std::process::exit(code);
+}
+
+/// Load and validate a config for a headless install, exiting with
+/// [`exit_code::CONFIG_INVALID`] if it cannot be used
+///
+/// Templates are resolved with the facts from `gather` and also return their
+/// resolved JSON, which the scripts must read instead of the file.
+fn load_headless_config(
+    config_path: &std::path::Path,
+    unattended: bool,
+    output: OutputFormat,
+    gather: impl FnOnce() -> anyhow::Result<facts::Facts>,
+) -> (InstallationConfig, Option<String>) {
+    info!("Loading configuration from: {:?}", config_path);
+
+    let (config, rendered) = match InstallationConfig::load_template(config_path, gather)
+        .and_then(|loaded| loaded.0.validate().map(|_| loaded))
+    {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!("Configuration error: {}", e);
+            let message = format!("Configuration error: {:#}", e);
+            exit_headless(output, exit_code::CONFIG_INVALID, message);
+        }
+    };
+
+    if unattended && config.partitioning_strategy == types::PartitionScheme::Manual {
+        error!("Manual partitioning cannot run unattended");
+        let message = "Manual partitioning requires interaction and cannot run unattended";
+        exit_headless(output, exit_code::CONFIG_INVALID, message.to_string());
+    }
+    if rendered.is_some() {
+        info!("Configuration template resolved for this machine");
+    }
 
-    result
+    (config, rendered)
 }
 
 /// Run installer with configuration file (headless mode)
+///
+/// Exits the process with a code from [`exit_code`] on failure so callers can
+/// tell configuration errors from installation errors.
 fn run_installer_with_config(
     config_path: &std::path::Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+    unattended: Option<UnattendedOptions>,
+    report_dir: Option<&std::path::Path>,
+    output: OutputFormat,
+) -> error::Result<()> {
     use std::io::{BufRead, BufReader};
     use std::process::{Command, Stdio};
 
-    info!("Loading configuration from: {:?}", config_path);
-
-    // Load and validate configuration
-    let config = InstallationConfig::load_from_file(config_path)?;
-    config.validate()?;
+    let json = output == OutputFormat::Json;
+    let (loaded, rendered) =
+        load_headless_config(config_path, unattended.is_some(), output, || {
+            Ok(facts::Facts::gather())
+        });
+    let template = rendered.is_some();
 
     info!("Configuration validated successfully");
-    println!("✓ Configuration loaded and validated");
-    println!("🚀 Starting installation with configuration file...");
+    if json {
+        InstallEvent::ConfigLoaded {
+            path: config_path.display().to_string(),
+            template,
+        }
+        .emit();
+    } else {
+        println!("✓ Configuration loaded and validated");
+        println!("🚀 Starting installation with configuration file...");
+    }
 
     let script_path = "./scripts/install.sh";
     info!("Spawning installer script: {}", script_path);
 
-    let mut child = Command::new("bash")
-        .arg(script_path)
-        .arg("--config")
-        .arg(config_path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| {
+    // The validated configuration reaches the script as a private file,
+    // removed when the environment is dropped
+    let config_json = loaded
+        .to_json()
+        .map_err(|e| error::general_error(e.to_string()))?;
+    let mut env = if simulate::is_enabled() {
+        script_env::ScriptEnv::new().config_vars(loaded.to_env_vars())
+    } else {
+        script_env::ScriptEnv::new()
+            .config_json(&config_json)?
+            .config_vars(loaded.package_vars())
+    };
+
+    let mut command = Command::new("bash");
+    if simulate::is_enabled() {
+        info!("Simulating the installation instead");
+        command.arg("-c").arg(simulate::install_script());
+    } else {
+        command.arg(script_path);
+    }
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    if let Some(options) = unattended {
+        info!(
+            "Unattended mode: on-error={}, retries={}",
+            options.on_error, options.retries
+        );
+        // No stdin at all: any prompt the script reaches fails instead of hanging
+        command.stdin(Stdio::null());
+        env = env.unattended(options.on_error, options.retries);
+    }
+
+    if let Some(dir) = report_dir {
+        info!("Installation report will be copied to {:?}", dir);
+    }
+    // Below 1 GiB the script installs with its low-memory strategy (and says so)
+    let low_memory = low_memory::detect();
+    if let Some(warning) = low_memory.first() {
+        warn!("{}", warning);
+    }
+    // Custom phases are run by calling back into this binary
+    env = env
+        .report_dir(report_dir)
+        .installer_bin()
+        .low_memory(!low_memory.is_empty());
+    env.apply(&mut command);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
             error!("Failed to spawn installer script: {}", e);
-            error::ArchInstallError::script(format!("Failed to spawn installer: {}", e))
-        })?;
+            drop(env);
+            let message = format!("Failed to spawn installer: {}", e);
+            exit_headless(output, exit_code::SPAWN_FAILED, message);
+        }
+    };
+
+    // Phases and warnings for the notification and the statistics
+    let started = std::time::Instant::now();
+    let mut metrics = install_metrics::InstallMetrics::default();
+
+    // In JSON mode stderr becomes events as it arrives instead of a summary
+    let stderr_events = match child.stderr.take() {
+        Some(stderr) if json => Some(std::thread::spawn(move || {
+            let mut lines = Vec::new();
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                InstallEvent::from_output_line(&line, true).emit();
+                lines.push(line);
+            }
+            lines
+        })),
+        stderr => {
+            child.stderr = stderr;
+            None
+        }
+    };
 
     // Capture and print stdout in real-time
     if let Some(stdout) = child.stdout.take() {
         let reader = BufReader::new(stdout);
         for line in reader.lines() {
+            if let Ok(ref line_content) = line {
+                follow_stage(&mut metrics, line_content);
+            }
             match line {
+                Ok(line_content) if json => {
+                    InstallEvent::from_output_line(&line_content, false).emit()
+                }
                 Ok(line_content) => println!("{}", line_content),
                 Err(e) => {
                     // If there's an error reading stdout, still wait for the child
@@ -188,10 +668,33 @@ fn run_installer_with_config(
 
     // Always wait for the child process to finish
     let output = child.wait_with_output()?;
+    let stderr_lines = match stderr_events {
+        Some(handle) => handle.join().unwrap_or_default(),
+        None => String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    };
+    for line in &stderr_lines {
+        metrics.follow_line(line);
+    }
+    // Skipped phases are in the warnings; the installation still ran to the end
+    if output.status.success() || output.status.code() == Some(exit_code::COMPLETED_WITH_ERRORS) {
+        metrics.finish(started, std::time::Instant::now());
+    }
+    drop(env);
+    notify_finished(&loaded, output.status.code(), metrics, json);
 
+    if json {
+        return finish_json(output.status.code());
+    }
     if output.status.success() {
         info!("Installation completed successfully");
         println!("\n✓ Installation completed successfully!");
+    } else if output.status.code() == Some(exit_code::COMPLETED_WITH_ERRORS) {
+        warn!("Installation completed with non-fatal errors");
+        eprintln!("\n⚠ Installation completed, but some phases failed (see log above)");
+        std::process::exit(exit_code::COMPLETED_WITH_ERRORS);
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         error!("Installation failed. Exit code: {:?}", output.status.code());
@@ -203,16 +706,177 @@ fn run_installer_with_config(
             eprintln!("--- Errors ---");
             eprintln!("{}", stderr);
         }
-        std::process::exit(1);
+        std::process::exit(exit_code::INSTALL_FAILED);
     }
 
     Ok(())
 }
 
+/// Exit code and final message of a headless install from the script's exit code
+fn install_result(code: Option<i32>) -> (i32, &'static str) {
+    match code {
+        Some(0) => (0, "Installation completed successfully"),
+        Some(exit_code::COMPLETED_WITH_ERRORS) => (
+            exit_code::COMPLETED_WITH_ERRORS,
+            "Installation completed, but some phases failed",
+        ),
+        _ => (exit_code::INSTALL_FAILED, "Installation failed"),
+    }
+}
+
+/// Time the stage a line of headless installer output announces
+fn follow_stage(metrics: &mut install_metrics::InstallMetrics, line: &str) {
+    if let InstallEvent::Stage {
+        description,
+        progress,
+        ..
+    } = InstallEvent::from_output_line(line, false)
+    {
+        // Phases 9 and 10 are both part of finalizing
+        if progress < 100 && metrics.current_phase() != Some(description) {
+            metrics.begin_phase(description, std::time::Instant::now());
+        }
+    }
+}
+
+/// Ring the terminal and tell the config's notify URL how the install ended
+fn notify_finished(
+    config: &InstallationConfig,
+    code: Option<i32>,
+    metrics: install_metrics::InstallMetrics,
+    json: bool,
+) {
+    let (exit_code, message) = install_result(code);
+    let outcome = notify::Outcome {
+        success: exit_code == 0,
+        exit_code: code,
+        message: message.to_string(),
+        summary: Some(metrics),
+    };
+    notify::Notifier::for_config(config).notify(&outcome, |line| {
+        // JSON mode keeps stdout to events
+        info!("{}", line);
+        if !json {
+            println!("{}", line);
+        }
+    });
+}
+
+/// Emit the final event of a JSON-mode install and exit like text mode
+fn finish_json(code: Option<i32>) -> error::Result<()> {
+    let (exit_code, message) = install_result(code);
+    if exit_code == 0 {
+        info!("{}", message);
+    } else {
+        error!("{} (script exit code {:?})", message, code);
+    }
+    InstallEvent::Finished {
+        success: exit_code == 0,
+        exit_code,
+        message: message.to_string(),
+    }
+    .emit();
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+/// Install onto a remote machine over SSH, showing progress in the TUI
+fn run_remote_installer(
+    target: &str,
+    port: u16,
+    identity: Option<std::path::PathBuf>,
+    config_path: &std::path::Path,
+    options: UnattendedOptions,
+    display: Display,
+) -> error::Result<()> {
+    let target = match remote::RemoteTarget::parse(target, port, identity) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            std::process::exit(exit_code::CONFIG_INVALID);
+        }
+    };
+
+    println!("🔌 Connecting to {}...", target.destination());
+    if let Err(e) = target.check_connection() {
+        error!("Remote preparation failed: {}", e);
+        eprintln!("✗ {}", e);
+        std::process::exit(exit_code::REMOTE_UNREACHABLE);
+    }
+
+    // Remote installs have no terminal on the far side, so they are always
+    // unattended; templates are resolved with the target's facts
+    let gather = || Ok(target.facts()?);
+    let (loaded, rendered) = load_headless_config(config_path, true, OutputFormat::Text, gather);
+    println!("✓ Configuration loaded and validated");
+
+    let config = match rendered {
+        Some(json) => json.into_bytes(),
+        None => std::fs::read(config_path)?,
+    };
+    let scripts_dir =
+        std::env::var("ARCHINSTALL_SCRIPTS_DIR").unwrap_or_else(|_| "./scripts".to_string());
+    if let Err(e) = target.push(std::path::Path::new(&scripts_dir), &config) {
+        error!("Remote preparation failed: {}", e);
+        eprintln!("✗ {}", e);
+        std::process::exit(exit_code::REMOTE_UNREACHABLE);
+    }
+    println!("✓ Installer and configuration copied to {}", target.host);
+
+    // Initialize terminal
+    display.enter()?;
+
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend).map_err(|e| {
+        error::ArchInstallError::terminal(format!("Failed to create terminal: {}", e))
+    })?;
+
+    let mut app = app::App::new(None);
+    display.attach(&mut app);
+    let state = app.state_handle();
+    let result = target
+        .start_install(
+            options.on_error,
+            options.retries,
+            app.state_mut(),
+            state,
+            notify::Notifier {
+                stats: Some(install_stats::InstallSettings::for_config(
+                    &loaded,
+                    install_stats::InstallSource::Remote,
+                )),
+                ..notify::Notifier::for_config(&loaded)
+            },
+        )
+        .and_then(|_| app.run(&mut terminal));
+
+    // Cleanup terminal (always attempt cleanup, even if app failed)
+    display.leave();
+
+    result?;
+
+    let completed = app.state().install.metrics.is_finished();
+    if completed {
+        println!(
+            "✓ Remote installation on {} completed successfully!",
+            target.host
+        );
+        Ok(())
+    } else {
+        eprintln!("✗ Remote installation on {} did not complete", target.host);
+        std::process::exit(exit_code::INSTALL_FAILED);
+    }
+}
+
 /// Run TUI installer and save configuration when done
 fn run_tui_installer_with_save(
     save_path: &std::path::Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+    display: Display,
+    open_tool: Option<&str>,
+    keystrokes: Keystrokes,
+) -> error::Result<()> {
     println!(
         "🎯 TUI installer will save configuration to: {}",
         save_path.display()
@@ -225,49 +889,76 @@ fn run_tui_installer_with_save(
     println!();
 
     // Run TUI with save path
-    run_tui_installer_with_save_path(save_path)
+    run_tui_installer_with_save_path(save_path, display, open_tool, keystrokes)
 }
 
 /// Run TUI installer with save path
 fn run_tui_installer_with_save_path(
     save_path: &std::path::Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+    display: Display,
+    open_tool: Option<&str>,
+    keystrokes: Keystrokes,
+) -> error::Result<()> {
     // Initialize terminal
-    enable_raw_mode()
-        .map_err(|e| error::general_error(format!("Failed to enable raw mode: {}", e)))?;
-    crossterm::execute!(stdout(), crossterm::terminal::EnterAlternateScreen)
-        .map_err(|e| error::general_error(format!("Failed to enter alternate screen: {}", e)))?;
+    display.enter()?;
 
     // Create terminal backend
     let backend = CrosstermBackend::new(stdout());
-    let mut terminal = Terminal::new(backend)
-        .map_err(|e| error::general_error(format!("Failed to create terminal: {}", e)))?;
+    let mut terminal = Terminal::new(backend).map_err(|e| {
+        error::ArchInstallError::terminal(format!("Failed to create terminal: {}", e))
+    })?;
 
     // Create and run application with save path
     let mut app = app::App::new(Some(save_path.to_path_buf()));
-    let result = app.run(&mut terminal);
+    display.attach(&mut app);
+    // Nothing in a simulation needs root
+    app.set_privileged(privilege::is_root() || simulate::is_enabled());
+    app.propose_guest_tools(hardware::detect_hypervisor());
+    app.propose_microcode(hardware::detect_microcode());
+    app.propose_power_management(hardware::detect_laptop());
+    app.use_proxy(&Proxy::from_env());
+    if self_update::startup_check_enabled() {
+        app.check_for_update();
+    }
+    if let Some(dir) = pkg_cache::from_env() {
+        app.use_package_cache(&dir);
+    }
+    if let Some(session_path) = session::default_session_path() {
+        app.enable_session_persistence(session_path);
+    }
+    keystrokes.attach(&mut app);
+    let result = open_tool
+        .map_or(Ok(()), |tool| app.open_tool(tool))
+        .and_then(|_| app.run(&mut terminal));
 
     // Cleanup terminal (always attempt cleanup, even if app failed)
-    let _ = disable_raw_mode();
-    let _ = crossterm::execute!(stdout(), crossterm::terminal::LeaveAlternateScreen);
+    display.leave();
 
-    result
+    result.and_then(|_| restart_as_root_if_requested(&mut app))
 }
 
 /// Run tool command
-fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::error::Error>> {
+fn run_tool_command(tool: &crate::cli::ToolCommands) -> error::Result<()> {
     match tool {
         crate::cli::ToolCommands::Disk { disk_tool } => match disk_tool {
             crate::cli::DiskToolCommands::Format {
                 device,
                 filesystem,
                 label,
+                ext4_reserved,
+                btrfs_metadata,
+                f2fs_compression,
             } => {
-                let mut args = vec!["--device", device, "--filesystem", filesystem];
-                if let Some(label) = label {
-                    args.extend(&["--label", label]);
-                }
-                execute_tool_script("format_partition.sh", &args)?;
+                let options = tools::format::FormatOptions {
+                    device: device.clone(),
+                    filesystem: *filesystem,
+                    label: label.clone(),
+                    ext4_reserved: *ext4_reserved,
+                    btrfs_metadata: *btrfs_metadata,
+                    f2fs_compression: *f2fs_compression,
+                };
+                options.validate().map_err(error::general_error)?;
+                execute_tool_script("format_partition.sh", &options.script_args())?;
             }
             crate::cli::DiskToolCommands::Wipe {
                 device,
@@ -278,11 +969,33 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                     eprintln!("❌ Wipe operation requires --confirm flag");
                     std::process::exit(1);
                 }
-                let args = vec!["--device", device, "--method", method, "--confirm"];
-                execute_tool_script("wipe_disk.sh", &args)?;
+                let target = tools::wipe::inspect(device)?;
+                let method = method.unwrap_or_else(|| target.recommended());
+                println!("Wiping {} ({})", target.disk, method.description());
+                tools::wipe::wipe(
+                    &target,
+                    method,
+                    |line| println!("{}", line),
+                    |progress| {
+                        print!(
+                            "\r{:>3}% {} of {}",
+                            progress.percent(),
+                            tools::resize::format_size(progress.done),
+                            tools::resize::format_size(progress.total)
+                        );
+                        let _ = std::io::Write::flush(&mut std::io::stdout());
+                        if progress.done == progress.total {
+                            println!();
+                        }
+                    },
+                )?;
+                println!("✅ {} wiped", device);
+            }
+            crate::cli::DiskToolCommands::Health { device, json: true } => {
+                print_json(&tools::smart::read_report(device)?.to_json())?;
             }
-            crate::cli::DiskToolCommands::Health { device } => {
-                let args = vec!["--device", device];
+            crate::cli::DiskToolCommands::Health { device, .. } => {
+                let args = ScriptArgs::new().value("--device", device);
                 execute_tool_script("check_disk_health.sh", &args)?;
             }
             crate::cli::DiskToolCommands::Mount {
@@ -291,19 +1004,39 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 mountpoint,
                 filesystem,
             } => {
-                let mut args = vec!["--action", action, "--device", device];
-                if let Some(mountpoint) = mountpoint {
-                    args.extend(&["--mountpoint", mountpoint]);
-                }
-                if let Some(filesystem) = filesystem {
-                    args.extend(&["--filesystem", filesystem]);
-                }
+                let args = ScriptArgs::new()
+                    .value("--action", action)
+                    .value("--device", device)
+                    .optional("--mountpoint", mountpoint.as_ref())
+                    .optional("--filesystem", filesystem.as_ref());
                 execute_tool_script("mount_partitions.sh", &args)?;
             }
             crate::cli::DiskToolCommands::Manual { device } => {
-                let args = vec!["--device", device];
+                let args = ScriptArgs::new().value("--device", device);
                 execute_tool_script("manual_partition.sh", &args)?;
             }
+            crate::cli::DiskToolCommands::Resize {
+                device,
+                size,
+                confirm,
+            } => {
+                let plan = tools::resize::plan(device, size)?;
+                for line in plan.preview() {
+                    println!("{}", line);
+                }
+                println!();
+                for step in &plan.steps {
+                    println!("  {}", step);
+                }
+                if !confirm {
+                    println!();
+                    println!("Preview only. Re-run with --confirm to resize.");
+                    return Ok(());
+                }
+                println!();
+                tools::resize::apply(&plan, |line| println!("{}", line))?;
+                println!("✅ {} resized", device);
+            }
         },
         crate::cli::ToolCommands::System { system_tool } => match system_tool {
             crate::cli::SystemToolCommands::Bootloader {
@@ -312,36 +1045,85 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 efi_path,
                 mode,
             } => {
-                let mut args = vec!["--type", r#type, "--disk", disk, "--mode", mode];
-                if let Some(efi_path) = efi_path {
-                    args.extend(&["--efi-path", efi_path]);
-                }
+                let args = ScriptArgs::new()
+                    .value("--type", r#type)
+                    .value("--disk", disk)
+                    .value("--mode", mode)
+                    .optional("--efi-path", efi_path.as_ref());
                 execute_tool_script("install_bootloader.sh", &args)?;
             }
             crate::cli::SystemToolCommands::Fstab { root } => {
-                let args = vec!["--root", root];
+                let args = ScriptArgs::new().value("--root", root);
                 execute_tool_script("generate_fstab.sh", &args)?;
             }
             crate::cli::SystemToolCommands::Chroot { root, no_mount } => {
-                let mut args = vec!["--root", root];
-                if *no_mount {
-                    args.push("--no-mount");
-                }
+                let args = ScriptArgs::new()
+                    .value("--root", root)
+                    .switch("--no-mount", *no_mount);
                 execute_tool_script("chroot_system.sh", &args)?;
             }
-            crate::cli::SystemToolCommands::Info { detailed } => {
-                let mut args = vec![];
-                if *detailed {
-                    args.push("--detailed");
-                }
+            crate::cli::SystemToolCommands::Info {
+                detailed,
+                json: true,
+            } => {
+                print_json(&tools::sysinfo::read(*detailed))?;
+            }
+            crate::cli::SystemToolCommands::Info { detailed, .. } => {
+                let args = ScriptArgs::new().switch("--detailed", *detailed);
                 execute_tool_script("system_info.sh", &args)?;
             }
-            crate::cli::SystemToolCommands::Services { action, service } => {
-                let mut args = vec!["--action", action];
-                if let Some(svc) = service {
-                    args.extend(&["--service", svc]);
+            crate::cli::SystemToolCommands::Services {
+                action,
+                service,
+                root,
+            } => {
+                let target = match root {
+                    Some(root) => tools::services::Target::Root(root.clone()),
+                    None => tools::services::Target::Running,
+                };
+                match action.as_str() {
+                    "list" => {
+                        let units = tools::services::list_units(&target)?;
+                        let filter = service.as_deref().unwrap_or_default();
+                        for unit in tools::services::filter(&units, filter) {
+                            println!(
+                                "{:<40} {:<16} {:<22} {}",
+                                unit.name,
+                                unit.enabled,
+                                unit.activation(),
+                                unit.description
+                            );
+                        }
+                    }
+                    "status" if target.is_offline() => {
+                        return Err(error::ArchInstallError::validation(
+                            "service action",
+                            "status needs the running system, not --root",
+                        ));
+                    }
+                    "status" => {
+                        let unit = service.as_deref().ok_or_else(|| {
+                            error::ArchInstallError::validation("service", "status needs --service")
+                        })?;
+                        tools::services::print_status(unit)?;
+                    }
+                    verb => {
+                        let unit_action =
+                            verb.parse::<tools::services::UnitAction>().map_err(|_| {
+                                error::ArchInstallError::validation(
+                                    "service action",
+                                    format!("unknown action '{}'", verb),
+                                )
+                            })?;
+                        let unit = service.as_deref().ok_or_else(|| {
+                            error::ArchInstallError::validation(
+                                "service",
+                                format!("{} needs --service", verb),
+                            )
+                        })?;
+                        println!("✅ {}", tools::services::run(&target, unit_action, unit)?);
+                    }
                 }
-                execute_tool_script("manage_services.sh", &args)?;
             }
         },
         crate::cli::ToolCommands::User { user_tool } => match user_tool {
@@ -351,17 +1133,15 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 groups,
                 shell,
             } => {
-                let mut args = vec!["--username", username, "--shell", shell];
-                if let Some(full_name) = full_name {
-                    args.extend(&["--full-name", full_name]);
-                }
-                if let Some(groups) = groups {
-                    args.extend(&["--groups", groups]);
-                }
+                let args = ScriptArgs::new()
+                    .value("--username", username)
+                    .value("--shell", shell)
+                    .optional("--full-name", full_name.as_ref())
+                    .optional("--groups", groups.as_ref());
                 execute_tool_script("add_user.sh", &args)?;
             }
             crate::cli::UserToolCommands::ResetPassword { username } => {
-                let args = vec!["--username", username];
+                let args = ScriptArgs::new().value("--username", username);
                 execute_tool_script("reset_password.sh", &args)?;
             }
             crate::cli::UserToolCommands::Groups {
@@ -369,13 +1149,10 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 user,
                 group,
             } => {
-                let mut args = vec!["--action", action];
-                if let Some(u) = user {
-                    args.extend(&["--user", u]);
-                }
-                if let Some(g) = group {
-                    args.extend(&["--group", g]);
-                }
+                let args = ScriptArgs::new()
+                    .value("--action", action)
+                    .optional("--user", user.as_ref())
+                    .optional("--group", group.as_ref());
                 execute_tool_script("manage_groups.sh", &args)?;
             }
             crate::cli::UserToolCommands::Ssh {
@@ -384,30 +1161,19 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 root_login,
                 password_auth,
             } => {
-                let mut args: Vec<String> = vec!["--action".to_string(), action.to_string()];
-                if let Some(p) = port {
-                    args.push("--port".to_string());
-                    args.push(p.to_string());
-                }
-                if let Some(rl) = root_login {
-                    if *rl {
-                        args.push("--enable-root-login".to_string());
-                    } else {
-                        args.push("--disable-root-login".to_string());
-                    }
-                }
-                if let Some(pa) = password_auth {
-                    if *pa {
-                        args.push("--enable-password-auth".to_string());
-                    } else {
-                        args.push("--disable-password-auth".to_string());
-                    }
-                }
-                let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-                execute_tool_script("configure_ssh.sh", &args_refs)?;
+                let args = ScriptArgs::new()
+                    .value("--action", action)
+                    .optional("--port", *port)
+                    .toggle(*root_login, "--enable-root-login", "--disable-root-login")
+                    .toggle(
+                        *password_auth,
+                        "--enable-password-auth",
+                        "--disable-password-auth",
+                    );
+                execute_tool_script("configure_ssh.sh", &args)?;
             }
             crate::cli::UserToolCommands::Security { action } => {
-                let args = vec!["--action", action];
+                let args = ScriptArgs::new().value("--action", action);
                 execute_tool_script("security_audit.sh", &args)?;
             }
         },
@@ -415,31 +1181,38 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
             crate::cli::NetworkToolCommands::Configure {
                 interface,
                 ip,
+                netmask,
                 gateway,
+                ip6,
+                gateway6,
+                ipv6_mode,
+                dns,
+                backend,
             } => {
-                let mut args = vec!["--interface", interface];
-                if let Some(ip) = ip {
-                    args.extend(&["--ip", ip]);
-                }
-                if let Some(gateway) = gateway {
-                    args.extend(&["--gateway", gateway]);
-                }
-                execute_tool_script("configure_network.sh", &args)?;
+                let settings = network::NetworkSettings {
+                    interface: interface.clone(),
+                    ipv4: ip.clone(),
+                    netmask: netmask.clone(),
+                    gateway4: gateway.clone(),
+                    ipv6: ip6.clone(),
+                    gateway6: gateway6.clone(),
+                    ipv6_mode: *ipv6_mode,
+                    dns: dns.clone(),
+                    backend: *backend,
+                };
+                settings.validate().map_err(error::general_error)?;
+                execute_tool_script("configure_network.sh", &settings.script_args())?;
             }
             crate::cli::NetworkToolCommands::Test {
                 action,
                 host,
                 timeout,
             } => {
-                let mut args: Vec<String> = vec!["--action".to_string(), action.to_string()];
-                if let Some(h) = host {
-                    args.push("--host".to_string());
-                    args.push(h.to_string());
-                }
-                args.push("--timeout".to_string());
-                args.push(timeout.to_string());
-                let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-                execute_tool_script("test_network.sh", &args_refs)?;
+                let args = ScriptArgs::new()
+                    .value("--action", action)
+                    .optional("--host", host.as_ref())
+                    .value("--timeout", timeout);
+                execute_tool_script("test_network.sh", &args)?;
             }
             crate::cli::NetworkToolCommands::Firewall {
                 action,
@@ -449,40 +1222,370 @@ fn run_tool_command(tool: &crate::cli::ToolCommands) -> Result<(), Box<dyn std::
                 allow,
                 deny,
             } => {
-                let mut args: Vec<String> = vec![
-                    "--action".to_string(),
-                    action.to_string(),
-                    "--type".to_string(),
-                    r#type.to_string(),
-                ];
-                if let Some(p) = port {
-                    args.push("--port".to_string());
-                    args.push(p.to_string());
+                let args = ScriptArgs::new()
+                    .value("--action", action)
+                    .value("--type", r#type)
+                    .optional("--port", *port)
+                    .value("--protocol", protocol)
+                    .switch("--allow", *allow)
+                    .switch("--deny", *deny);
+                execute_tool_script("configure_firewall.sh", &args)?;
+            }
+            crate::cli::NetworkToolCommands::Diagnostics { action, json: true } => {
+                let depth = action.parse::<tools::netdiag::Depth>().map_err(|_| {
+                    error::ArchInstallError::validation(
+                        "diagnostics action",
+                        format!("unknown action '{}'", action),
+                    )
+                })?;
+                let diagnostics = tools::netdiag::run(depth)?;
+                print_json(&diagnostics)?;
+                if !diagnostics.all_ok() {
+                    std::process::exit(1);
+                }
+            }
+            crate::cli::NetworkToolCommands::Diagnostics { action, .. } => {
+                let args = ScriptArgs::new().value("--action", action);
+                execute_tool_script("network_diagnostics.sh", &args)?;
+            }
+        },
+        crate::cli::ToolCommands::Snapshot { snapshot_tool } => match snapshot_tool {
+            crate::cli::SnapshotToolCommands::List { device } => {
+                let snapshots = tools::snapshots::list(device)?;
+                if snapshots.is_empty() {
+                    println!("No snapshots on {}", device);
                 }
-                args.push("--protocol".to_string());
-                args.push(protocol.to_string());
-                if *allow {
-                    args.push("--allow".to_string());
+                for snapshot in &snapshots {
+                    println!("{}", snapshot);
                 }
-                if *deny {
-                    args.push("--deny".to_string());
+            }
+            crate::cli::SnapshotToolCommands::Create {
+                device,
+                description,
+            } => {
+                let snapshot =
+                    tools::snapshots::create(device, description, |line| println!("{}", line))?;
+                println!("✅ Created snapshot {}", snapshot.number);
+            }
+            crate::cli::SnapshotToolCommands::Delete {
+                device,
+                snapshot,
+                confirm,
+            } => {
+                if !confirm {
+                    print_snapshot(device, *snapshot)?;
+                    println!("Re-run with --confirm to delete it.");
+                    return Ok(());
                 }
-                let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-                execute_tool_script("configure_firewall.sh", &args_refs)?;
+                tools::snapshots::delete(device, *snapshot, |line| println!("{}", line))?;
+                println!("✅ Deleted snapshot {}", snapshot);
             }
-            crate::cli::NetworkToolCommands::Diagnostics { action } => {
-                let args = vec!["--action", action];
-                execute_tool_script("network_diagnostics.sh", &args)?;
+            crate::cli::SnapshotToolCommands::Rollback {
+                device,
+                snapshot,
+                confirm,
+            } => {
+                if !confirm {
+                    print_snapshot(device, *snapshot)?;
+                    println!("Re-run with --confirm to make it the root subvolume.");
+                    return Ok(());
+                }
+                let old_root =
+                    tools::snapshots::rollback(device, *snapshot, |line| println!("{}", line))?;
+                println!("✅ Rolled back to snapshot {}", snapshot);
+                println!("The previous root is kept as {}; reboot to use the snapshot", old_root);
             }
         },
+        crate::cli::ToolCommands::Cleanup { dry_run, disk } => {
+            let leftovers = tools::cleanup::detect(disk);
+            if leftovers.is_empty() {
+                println!("Nothing to clean up");
+                return Ok(());
+            }
+            for leftover in &leftovers {
+                println!("  {}", leftover);
+            }
+            if *dry_run {
+                println!();
+                println!("Dry run. Re-run without --dry-run to release them.");
+                return Ok(());
+            }
+            println!();
+            tools::cleanup::run(&leftovers, |line| println!("{}", line))?;
+            println!("✅ Cleanup complete, the installation can be retried");
+        }
+    }
+    Ok(())
+}
+
+/// Show the snapshot a destructive snapshot command would act on
+fn print_snapshot(device: &str, number: u32) -> error::Result<()> {
+    let snapshot = tools::snapshots::list(device)?
+        .into_iter()
+        .find(|snapshot| snapshot.number == number)
+        .ok_or_else(|| {
+            error::ArchInstallError::validation("snapshot", format!("snapshot {} does not exist", number))
+        })?;
+    println!("{}", snapshot);
+    println!();
+    Ok(())
+}
+
+/// Print or delete the local installation statistics
+fn run_stats_command(json: bool, clear: bool) -> error::Result<()> {
+    let path = install_stats::default_path()
+        .ok_or_else(|| error::general_error("No cache directory for the statistics"))?;
+    if clear {
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => println!("✓ Removed {}", path.display()),
+        }
+        return Ok(());
+    }
+    let records = install_stats::load(&path)?;
+    if json {
+        for record in &records {
+            println!(
+                "{}",
+                serde_json::to_string(record).map_err(|e| error::general_error(e.to_string()))?
+            );
+        }
+    } else if records.is_empty() {
+        println!("No installations recorded in {}", path.display());
+        println!("Run installations with --install-stats to record them");
+    } else {
+        print!("{}", install_stats::summary(&records));
+        println!("\nRecords: {} (attach it to a bug report)", path.display());
+    }
+    Ok(())
+}
+
+/// List or run custom installation phases
+fn run_phase_command(action: &crate::cli::PhaseCommands) -> error::Result<()> {
+    match action {
+        crate::cli::PhaseCommands::List { dir } => {
+            let dir = dir.clone().unwrap_or_else(phases::plugin_dir);
+            for (name, source) in phases::PhaseRegistry::load(&dir).list() {
+                println!("{:<24} {}", name, source);
+            }
+        }
+        crate::cli::PhaseCommands::Run { names, target, dir } => {
+            for name in names {
+                phases::validate_phase_name(name).map_err(error::general_error)?;
+            }
+            let dir = dir.clone().unwrap_or_else(phases::plugin_dir);
+            let registry = phases::PhaseRegistry::load(&dir);
+            let mut selected = registry.resolve(names)?;
+
+            info!("Running custom phases {:?} against {:?}", names, target);
+            let mut log = |line: String| println!("{}", line);
+            let mut ctx = phases::PhaseContext::new(target, &mut log);
+            phases::run_phases(&mut selected, &mut ctx)?;
+            println!("✅ Custom phases complete");
+        }
+    }
+    Ok(())
+}
+
+/// Print a tool's results for scripts
+fn print_json(value: &impl serde::Serialize) -> error::Result<()> {
+    let json =
+        serde_json::to_string_pretty(value).map_err(|e| error::general_error(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Write the artifacts for installs started elsewhere
+fn run_generate_command(artifact: &crate::cli::GenerateCommands) -> error::Result<()> {
+    let crate::cli::GenerateCommands::Pxe {
+        config,
+        config_url,
+        iso_url,
+        output,
+    } = artifact;
+
+    // Templates are checked as this machine would resolve them, and served as
+    // they are so every machine resolves its own
+    let content = std::fs::read_to_string(config).map_err(|e| {
+        error::general_error(format!("Failed to read {}: {}", config.display(), e))
+    })?;
+    let checked = InstallationConfig::load_from_file(config)
+        .and_then(|loaded| loaded.validate().map(|_| loaded));
+    if let Err(e) = checked {
+        error!("Configuration validation failed: {}", e);
+        eprintln!("✗ Configuration validation failed: {}", e);
+        std::process::exit(exit_code::CONFIG_INVALID);
     }
+    let artifacts = match pxe::generate(iso_url, config_url) {
+        Ok(artifacts) => artifacts,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            std::process::exit(exit_code::CONFIG_INVALID);
+        }
+    };
+
+    std::fs::create_dir_all(output)?;
+    let file_name = config
+        .file_name()
+        .map_or_else(|| "config.json".into(), |name| name.to_os_string());
+    for (name, text) in [
+        (file_name.as_os_str(), content.as_str()),
+        ("cmdline.txt".as_ref(), artifacts.cmdline.as_str()),
+        ("boot.ipxe".as_ref(), artifacts.ipxe.as_str()),
+    ] {
+        let path = output.join(name);
+        std::fs::write(&path, text).map_err(|e| {
+            error::general_error(format!("Failed to write {}: {}", path.display(), e))
+        })?;
+        println!("✓ Wrote {}", path.display());
+    }
+    println!(
+        "  Serve {} at {}; it holds the passwords, so only on the provisioning network",
+        file_name.to_string_lossy(),
+        config_url
+    );
+    println!("  The live system has to start archinstall-tui, which then installs unattended");
+    Ok(())
+}
+
+/// Download the config named on the kernel command line and install with it
+///
+/// Runs like `install --config <file> --yes`: a failed phase aborts.
+fn run_pxe_install(url: &str) -> error::Result<()> {
+    let path = std::path::Path::new(pxe::BOOT_CONFIG_PATH);
+    println!("Downloading the configuration from {}", url);
+    if let Err(e) = pxe::download_config(url, path) {
+        error!("{}", e);
+        eprintln!("✗ {}", e);
+        std::process::exit(exit_code::CONFIG_INVALID);
+    }
+    let unattended = UnattendedOptions {
+        on_error: ErrorPolicy::Abort,
+        retries: 0,
+    };
+    run_installer_with_config(path, Some(unattended), None, OutputFormat::Text)
+}
+
+/// Convert configuration files to and from archinstall's JSON
+fn run_archinstall_command(action: &crate::cli::ArchinstallCommands) -> error::Result<()> {
+    use config_file::interop;
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    // A private file is created readable by root only, and an existing one
+    // made so before anything is written to it
+    let write = |path: &std::path::Path, json: &serde_json::Value, private: bool| {
+        let mut content =
+            serde_json::to_string_pretty(json).map_err(|e| error::general_error(e.to_string()))?;
+        content.push('\n');
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        if private {
+            options.mode(0o600);
+        }
+        options
+            .open(path)
+            .and_then(|mut file| {
+                if private {
+                    file.set_permissions(PermissionsExt::from_mode(0o600))?;
+                }
+                file.write_all(content.as_bytes())
+            })
+            .map_err(|e| error::general_error(format!("Failed to write {}: {}", path.display(), e)))
+    };
+    let read = |path: &std::path::Path| {
+        std::fs::read_to_string(path)
+            .map_err(|e| error::general_error(format!("Failed to read {}: {}", path.display(), e)))
+    };
+
+    let warnings = match action {
+        crate::cli::ArchinstallCommands::Export { config, output } => {
+            let config = InstallationConfig::load_from_file(config)
+                .map_err(|e| error::general_error(e.to_string()))?;
+            let exported = interop::export(&config);
+            std::fs::create_dir_all(output)?;
+            let configuration = output.join(interop::CONFIGURATION_FILE);
+            let credentials = output.join(interop::CREDENTIALS_FILE);
+            write(&configuration, &exported.configuration, false)?;
+            // The credentials hold the passwords in clear text
+            write(&credentials, &exported.credentials, true)?;
+            println!(
+                "✓ Wrote {} and {}",
+                configuration.display(),
+                credentials.display()
+            );
+            println!(
+                "  Install with: archinstall --config {} --creds {}",
+                configuration.display(),
+                credentials.display()
+            );
+            exported.warnings
+        }
+        crate::cli::ArchinstallCommands::Import {
+            config,
+            creds,
+            output,
+        } => {
+            let credentials = creds.as_deref().map(read).transpose()?;
+            let imported = interop::import(&read(config)?, credentials.as_deref())
+                .map_err(|e| error::general_error(format!("{:#}", e)))?;
+            imported
+                .config
+                .save_to_file(output)
+                .map_err(|e| error::general_error(e.to_string()))?;
+            println!("✓ Configuration written to {}", output.display());
+            if let Err(e) = imported.config.validate() {
+                println!("  Not ready to install yet: {}", e);
+            }
+            imported.warnings
+        }
+    };
+
+    if !warnings.is_empty() {
+        println!("Not carried over (review before installing):");
+        for warning in &warnings {
+            println!("  • {}", warning);
+        }
+    }
+    Ok(())
+}
+
+/// Check for a newer installer release and, on the live ISO, install it
+fn run_self_update(check_only: bool) -> error::Result<()> {
+    let current = self_update::Version::current();
+    let Some(release) = self_update::check().map_err(error::general_error)? else {
+        println!("✓ archinstall-tui {} is the latest release", current);
+        return Ok(());
+    };
+    println!(
+        "archinstall-tui {} is available (running {})",
+        release.version, current
+    );
+    if check_only {
+        println!("  {}", release.page);
+        return Ok(());
+    }
+    if !self_update::is_live_iso() {
+        println!("Not on the Arch live ISO: update with the package manager that installed it,");
+        println!("or download it from {}", release.page);
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()?;
+    info!("Updating {} to {}", exe.display(), release.version);
+    self_update::install(&release, &exe).map_err(error::general_error)?;
+    println!(
+        "✓ Updated to {}; restart archinstall-tui to use it",
+        release.version
+    );
     Ok(())
 }
 
 /// Execute a tool script with arguments
-fn execute_tool_script(script_name: &str, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+fn execute_tool_script(script_name: &str, args: &ScriptArgs) -> error::Result<()> {
     use std::process::{Command, Stdio};
 
+    let args = args.as_slice();
     let script_path = format!("scripts/tools/{}", script_name);
     info!("Executing tool script: {} with args: {:?}", script_path, args);
     println!("🔧 Executing: {} {}", script_path, args.join(" "));
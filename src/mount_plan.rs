@@ -0,0 +1,502 @@
+//! Installing onto existing partitions
+//!
+//! The `existing` partitioning strategy leaves the partition tables alone.
+//! The Partition Mounts option maps partitions of the selected disks to
+//! mountpoints and says which of them are formatted, e.g.
+//! `/dev/sda2=/:ext4,/dev/sda3=/home,/dev/sda1=/efi` reformats the root as
+//! ext4 and keeps /home and the ESP as they are. install.sh formats and
+//! mounts the partitions under /mnt (`apply_mount_plan` in disk_utils.sh)
+//! and genfstab writes the fstab from what ends up mounted there.
+//!
+//! This module parses the option and checks it twice: on its own while it is
+//! typed, and against the partitions lsblk reports before installing.
+
+use crate::partition_check::{self, Disk, Mount, Problem, Report, Severity};
+use crate::types::{BootMode, Filesystem};
+use std::fmt;
+use strum::IntoEnumIterator;
+
+/// Mountpoint of a swap partition
+pub const SWAP: &str = "swap";
+
+/// Mountpoints offered in the Partition Mounts editor, in cycling order
+pub const MOUNTPOINTS: &[&str] = &["/", "/home", "/boot", "/efi", "/var", "/srv", "/opt", SWAP];
+
+/// Mountpoints the ESP can be assigned to
+const ESP_MOUNTPOINTS: &[&str] = &["/efi", "/boot", "/boot/efi"];
+
+/// Mountpoints of the live system's own filesystems
+const RESERVED: &[&str] = &["/proc", "/sys", "/dev", "/run", "/tmp"];
+
+/// Signatures of partitions that hold other volumes, not a filesystem
+const CONTAINERS: &[&str] = &[
+    "crypto_LUKS",
+    "LVM2_member",
+    "linux_raid_member",
+    "zfs_member",
+];
+
+/// One partition of the plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub device: String,
+    /// Absolute path in the installed system, or [`SWAP`]
+    pub mountpoint: String,
+    /// Filesystem the partition is formatted with; `None` keeps its data
+    pub format: Option<String>,
+}
+
+impl Assignment {
+    pub fn is_swap(&self) -> bool {
+        self.mountpoint == SWAP
+    }
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.device, self.mountpoint)?;
+        if let Some(format) = &self.format {
+            write!(f, ":{}", format)?;
+        }
+        Ok(())
+    }
+}
+
+/// Partitions to mount, in the order they were assigned
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MountPlan {
+    pub assignments: Vec<Assignment>,
+}
+
+impl MountPlan {
+    /// Parse a Partition Mounts value; an empty value is an empty plan
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut assignments = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (device, target) = entry.split_once('=').ok_or_else(|| {
+                format!(
+                    "'{}' must be device=mountpoint, e.g. /dev/sda2=/:ext4",
+                    entry
+                )
+            })?;
+            let (mountpoint, format) = match target.split_once(':') {
+                Some((mountpoint, format)) => (mountpoint, Some(format.trim().to_string())),
+                None => (target, None),
+            };
+            assignments.push(Assignment {
+                device: device.trim().to_string(),
+                mountpoint: mountpoint.trim().to_string(),
+                format,
+            });
+        }
+        Ok(Self { assignments })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assignments.is_empty()
+    }
+
+    /// Assignment of a partition
+    pub fn get(&self, device: &str) -> Option<&Assignment> {
+        self.assignments.iter().find(|a| a.device == device)
+    }
+
+    /// Assign a partition, or drop it from the plan with `None`
+    pub fn set(&mut self, device: &str, assignment: Option<Assignment>) {
+        match (
+            self.assignments.iter().position(|a| a.device == device),
+            assignment,
+        ) {
+            (Some(index), Some(assignment)) => self.assignments[index] = assignment,
+            (Some(index), None) => {
+                self.assignments.remove(index);
+            }
+            (None, Some(assignment)) => self.assignments.push(assignment),
+            (None, None) => {}
+        }
+    }
+
+    /// Check the plan on its own: paths, formats and a formatted root
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, a) in self.assignments.iter().enumerate() {
+            if !a.device.starts_with("/dev/") || a.device.contains(char::is_whitespace) {
+                return Err(format!("'{}' is not a device path", a.device));
+            }
+            if a.mountpoint.is_empty() {
+                return Err(format!("{} has no mountpoint", a.device));
+            }
+            if !a.is_swap() {
+                validate_mountpoint(&a.mountpoint)?;
+            }
+            if let Some(format) = &a.format {
+                if !formats_for(&a.mountpoint).contains(format) {
+                    return Err(format!(
+                        "{} cannot be formatted {}; use {}",
+                        a.mountpoint,
+                        format,
+                        formats_for(&a.mountpoint).join(", ")
+                    ));
+                }
+            }
+            let earlier = &self.assignments[..index];
+            if earlier.iter().any(|b| b.device == a.device) {
+                return Err(format!("{} is assigned twice", a.device));
+            }
+            if !a.is_swap() && earlier.iter().any(|b| b.mountpoint == a.mountpoint) {
+                return Err(format!(
+                    "{} is assigned to more than one partition",
+                    a.mountpoint
+                ));
+            }
+        }
+        match self.assignments.iter().find(|a| a.mountpoint == "/") {
+            None if !self.is_empty() => Err("No partition is assigned to /".to_string()),
+            Some(root) if root.format.is_none() => Err(format!(
+                "The root {} must be formatted; only other partitions can keep their data",
+                root.device
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Filesystems the plan leaves mounted below [`partition_check::TARGET`],
+    /// with the signatures they will have once formatted
+    pub fn mounts(&self, disks: &[Disk]) -> Vec<Mount> {
+        self.assignments
+            .iter()
+            .filter(|a| !a.is_swap())
+            .map(|a| Mount {
+                device: a.device.clone(),
+                fstype: a.format.clone().unwrap_or_else(|| {
+                    find_partition(disks, &a.device)
+                        .map(|part| part.fstype.clone())
+                        .unwrap_or_default()
+                }),
+                path: a.mountpoint.clone(),
+            })
+            .collect()
+    }
+
+    /// Everything that keeps the plan from installing on the selected disks
+    pub fn problems(&self, boot_mode: BootMode, disks: &[Disk]) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut error = |device: &str, message: String| {
+            problems.push(Problem {
+                severity: Severity::Error,
+                device: Some(device.to_string()),
+                message,
+            })
+        };
+        for a in &self.assignments {
+            let Some(part) = find_partition(disks, &a.device) else {
+                error(
+                    &a.device,
+                    "is not a partition of the selected disks".to_string(),
+                );
+                continue;
+            };
+            if CONTAINERS.contains(&part.fstype.as_str()) {
+                error(
+                    &a.device,
+                    format!(
+                        "holds {}, not a filesystem; open it and use the manual strategy",
+                        part.fstype
+                    ),
+                );
+                continue;
+            }
+            if a.format.is_none() {
+                match part.fstype.as_str() {
+                    "" => error(
+                        &a.device,
+                        format!(
+                            "has no filesystem to keep at {}; choose one to format it with",
+                            a.mountpoint
+                        ),
+                    ),
+                    "swap" if !a.is_swap() => error(
+                        &a.device,
+                        format!(
+                            "is a swap partition and cannot be mounted at {}",
+                            a.mountpoint
+                        ),
+                    ),
+                    fstype if a.is_swap() && fstype != "swap" => error(
+                        &a.device,
+                        format!("holds {}; format it as swap to use it for swap", fstype),
+                    ),
+                    _ => {}
+                }
+            }
+            // Left mounted by an earlier attempt is fine, anywhere else is
+            // not; swap is formatted again, so it must be off
+            let planned = (!a.is_swap()).then(|| {
+                Mount {
+                    path: a.mountpoint.clone(),
+                    ..Mount::default()
+                }
+                .live_path()
+            });
+            if let Some(other) = part
+                .mountpoints
+                .iter()
+                .find(|m| Some(m.as_str()) != planned.as_deref())
+            {
+                error(
+                    &a.device,
+                    if other == "[SWAP]" {
+                        "is in use as swap; swapoff it first".to_string()
+                    } else {
+                        format!("is mounted at {}; unmount it first", other)
+                    },
+                );
+            }
+        }
+
+        // The layout as it will be once formatted
+        let mut disks = disks.to_vec();
+        for part in disks.iter_mut().flat_map(|disk| &mut disk.partitions) {
+            if let Some(format) = self.get(&part.path).and_then(|a| a.format.clone()) {
+                part.fstype = format;
+            }
+        }
+        let mounts = self.mounts(&disks);
+        let esps: Vec<&str> = disks
+            .iter()
+            .flat_map(|disk| &disk.partitions)
+            .filter(|part| part.is_esp())
+            .map(|part| part.path.as_str())
+            .collect();
+        let esp_assigned = mounts.iter().any(|mount| {
+            esps.contains(&mount.device.as_str()) && ESP_MOUNTPOINTS.contains(&mount.path.as_str())
+        });
+        if boot_mode == BootMode::Uefi && !esps.is_empty() && !esp_assigned {
+            problems.push(Problem {
+                severity: Severity::Error,
+                device: Some(esps[0].to_string()),
+                message: "EFI System Partition is not assigned; mount it at /efi or /boot"
+                    .to_string(),
+            });
+        }
+        // Layout-wide warnings are about mounting by hand, which the plan does
+        problems.extend(
+            partition_check::check(boot_mode, &disks, &mounts)
+                .into_iter()
+                .filter(|p| p.device.is_some() || p.severity == Severity::Error),
+        );
+        problems
+    }
+
+    /// Check the plan against the selected disks, as the Partition Check
+    /// screen shows it
+    pub fn report(&self, boot_mode: BootMode, disks: Vec<Disk>) -> Report {
+        let mut problems = self.problems(boot_mode, &disks);
+        problems.sort_by_key(|problem| problem.severity);
+        Report {
+            boot_mode,
+            mounts: self.mounts(&disks),
+            disks,
+            problems,
+        }
+    }
+}
+
+impl fmt::Display for MountPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self.assignments.iter().map(ToString::to_string).collect();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+/// Check a Partition Mounts value without looking at the disks
+pub fn validate_value(value: &str) -> Result<(), String> {
+    MountPlan::parse(value)?.validate()
+}
+
+/// Filesystems a partition at `mountpoint` can be formatted with, the usual
+/// one first
+pub fn formats_for(mountpoint: &str) -> Vec<String> {
+    if mountpoint == SWAP {
+        return vec!["swap".to_string()];
+    }
+    if ["/efi", "/boot/efi"].contains(&mountpoint) {
+        return vec!["vfat".to_string()];
+    }
+    let mut formats: Vec<String> = Filesystem::iter().map(|fs| fs.to_string()).collect();
+    if mountpoint == "/boot" {
+        formats.insert(0, "vfat".to_string());
+    }
+    formats
+}
+
+fn validate_mountpoint(mountpoint: &str) -> Result<(), String> {
+    if !mountpoint.starts_with('/')
+        || mountpoint.contains(|c: char| c.is_whitespace() || ",=:".contains(c))
+        || mountpoint
+            .split('/')
+            .any(|part| part == "." || part == "..")
+        || (mountpoint.len() > 1 && mountpoint.ends_with('/'))
+    {
+        return Err(format!("'{}' is not a valid mountpoint", mountpoint));
+    }
+    if RESERVED
+        .iter()
+        .any(|r| mountpoint == *r || mountpoint.starts_with(&format!("{}/", r)))
+    {
+        return Err(format!(
+            "{} is provided by the running system and cannot be a partition",
+            mountpoint
+        ));
+    }
+    Ok(())
+}
+
+fn find_partition<'a>(disks: &'a [Disk], device: &str) -> Option<&'a partition_check::Partition> {
+    disks
+        .iter()
+        .flat_map(|disk| &disk.partitions)
+        .find(|part| part.path == device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::esp::ESP_GUID;
+    use crate::partition_check::Partition;
+
+    const LINUX: &str = "0fc63daf-8483-4772-8e79-3d69d8477de4";
+
+    fn partition(path: &str, part_type: &str, fstype: &str) -> Partition {
+        Partition {
+            path: path.to_string(),
+            size: 50 * 1024 * 1024 * 1024,
+            part_type: part_type.to_string(),
+            fstype: fstype.to_string(),
+            ..Partition::default()
+        }
+    }
+
+    fn disk() -> Disk {
+        Disk {
+            path: "/dev/sda".to_string(),
+            table: "gpt".to_string(),
+            partitions: vec![
+                partition("/dev/sda1", ESP_GUID, "vfat"),
+                partition("/dev/sda2", LINUX, "ext4"),
+                partition("/dev/sda3", LINUX, "btrfs"),
+                partition("/dev/sda4", LINUX, "swap"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let value = "/dev/sda2=/:ext4,/dev/sda3=/home,/dev/sda1=/efi,/dev/sda4=swap";
+        let plan = MountPlan::parse(value).unwrap();
+        assert_eq!(plan.assignments.len(), 4);
+        assert_eq!(plan.assignments[0].format.as_deref(), Some("ext4"));
+        assert_eq!(plan.get("/dev/sda3").unwrap().format, None);
+        assert!(plan.assignments[3].is_swap());
+        assert_eq!(plan.to_string(), value);
+        assert_eq!(MountPlan::parse("").unwrap(), MountPlan::default());
+        assert!(MountPlan::parse("/dev/sda2").is_err());
+    }
+
+    #[test]
+    fn test_set_replaces_and_removes() {
+        let mut plan = MountPlan::parse("/dev/sda2=/:ext4").unwrap();
+        let home = Assignment {
+            device: "/dev/sda3".to_string(),
+            mountpoint: "/home".to_string(),
+            format: None,
+        };
+        plan.set("/dev/sda3", Some(home));
+        plan.set("/dev/sda2", None);
+        assert_eq!(plan.to_string(), "/dev/sda3=/home");
+    }
+
+    #[test]
+    fn test_validate_value() {
+        assert!(validate_value("").is_ok());
+        assert!(validate_value("/dev/sda2=/:btrfs,/dev/sda3=/home,/dev/sda4=swap:swap").is_ok());
+        for (value, error) in [
+            ("/dev/sda3=/home", "No partition is assigned to /"),
+            ("/dev/sda2=/", "must be formatted"),
+            ("sda2=/:ext4", "not a device path"),
+            ("/dev/sda2=/:ext4,/dev/sda3=/", "more than one partition"),
+            ("/dev/sda2=/:ext4,/dev/sda2=/home", "assigned twice"),
+            ("/dev/sda2=/:ntfs", "cannot be formatted ntfs"),
+            (
+                "/dev/sda2=/:ext4,/dev/sda1=/efi:ext4",
+                "cannot be formatted ext4",
+            ),
+            ("/dev/sda2=/:ext4,/dev/sda3=/proc", "running system"),
+            ("/dev/sda2=/:ext4,/dev/sda3=home", "not a valid mountpoint"),
+            (
+                "/dev/sda2=/:ext4,/dev/sda3=/srv/../etc",
+                "not a valid mountpoint",
+            ),
+        ] {
+            let result = validate_value(value);
+            assert!(
+                result.as_ref().is_err_and(|e| e.contains(error)),
+                "{}: {:?}",
+                value,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_keeping_home_and_the_esp_passes() {
+        let plan =
+            MountPlan::parse("/dev/sda2=/:ext4,/dev/sda3=/home,/dev/sda1=/efi,/dev/sda4=swap")
+                .unwrap();
+        assert_eq!(plan.problems(BootMode::Uefi, &[disk()]), []);
+        let report = plan.report(BootMode::Uefi, vec![disk()]);
+        assert!(report.is_mounted());
+        assert_eq!(report.mountpoint("/dev/sda3"), Some("/home"));
+    }
+
+    #[test]
+    fn test_problems_with_the_partitions() {
+        let mut disk = disk();
+        disk.partitions[2].fstype = "crypto_LUKS".to_string();
+        disk.partitions[3].mountpoints = vec!["[SWAP]".to_string()];
+        disk.partitions.push(partition("/dev/sda5", LINUX, ""));
+        let plan = MountPlan::parse(
+            "/dev/sda2=/:ext4,/dev/sda3=/home,/dev/sda4=swap,/dev/sda5=/srv,/dev/sdb1=/var",
+        )
+        .unwrap();
+        let problems: Vec<String> = plan
+            .problems(BootMode::Uefi, &[disk])
+            .iter()
+            .map(Problem::to_string)
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                "/dev/sda3: holds crypto_LUKS, not a filesystem; open it and use the manual strategy",
+                "/dev/sda4: is in use as swap; swapoff it first",
+                "/dev/sda5: has no filesystem to keep at /srv; choose one to format it with",
+                "/dev/sdb1: is not a partition of the selected disks",
+                "/dev/sda1: EFI System Partition is not assigned; mount it at /efi or /boot",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_formatting_fixes_the_layout_checks() {
+        let mut disk = disk();
+        disk.partitions[0].fstype = String::new();
+        let plan = MountPlan::parse("/dev/sda2=/:ext4,/dev/sda1=/efi:vfat").unwrap();
+        assert_eq!(plan.problems(BootMode::Uefi, &[disk.clone()]), []);
+
+        // A kept btrfs partition cannot be the ESP
+        let plan = MountPlan::parse("/dev/sda2=/:ext4,/dev/sda3=/efi").unwrap();
+        let problems = plan.problems(BootMode::Uefi, &[disk]);
+        assert!(problems
+            .iter()
+            .any(|p| p.to_string().contains("is not an EFI System Partition")));
+    }
+}
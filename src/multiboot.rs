@@ -0,0 +1,160 @@
+//! Protecting operating systems already on the install disks
+//!
+//! [`crate::hardware::detect_disks`] runs os-prober and blkid, so every disk
+//! knows what it holds. The Existing OS option decides what happens to such a
+//! disk: the default refuses to install on it, `alongside` keeps its
+//! partitions and puts Arch into the unallocated space, and `erase` wipes it
+//! after the usual confirmation.
+
+use crate::disk;
+use crate::hardware::DiskInfo;
+use crate::types::{ExistingOsPolicy, PartitionScheme};
+
+/// Whether the automatic strategy keeps the partitions already on `disk`
+///
+/// Installing alongside keeps them, and so does reusing an ESP on that disk.
+/// Manual partitioning and RAID are not automatic in this sense: the user
+/// decides, or every member disk is wiped. Installing onto existing
+/// partitions always keeps the partition table.
+pub fn keeps_partitions(
+    policy: ExistingOsPolicy,
+    scheme: PartitionScheme,
+    efi_partition: &str,
+    disk: &str,
+) -> bool {
+    if scheme == PartitionScheme::Existing {
+        return true;
+    }
+    if scheme == PartitionScheme::Manual || scheme.requires_raid() {
+        return false;
+    }
+    policy == ExistingOsPolicy::Alongside
+        || disk::split_partition(efi_partition.trim())
+            .is_some_and(|(esp_disk, _)| esp_disk.path() == disk)
+}
+
+/// Check the Existing OS option against the partitioning strategy
+pub fn validate_policy(policy: ExistingOsPolicy, scheme: PartitionScheme) -> Result<(), String> {
+    if policy != ExistingOsPolicy::Alongside {
+        return Ok(());
+    }
+    if scheme.requires_raid() {
+        return Err(format!(
+            "Existing OS 'alongside' is not supported with {}: RAID needs whole disks",
+            scheme
+        ));
+    }
+    if scheme == PartitionScheme::Manual || scheme == PartitionScheme::Existing {
+        return Err(format!(
+            "Existing OS 'alongside' only applies to automatic partitioning: with {} \
+             partitioning keep the partitions you need yourself",
+            scheme
+        ));
+    }
+    Ok(())
+}
+
+/// Check that no target disk holding another system would be wiped
+///
+/// `targets` are the install disk paths and `disks` the detected disks.
+/// Under [`ExistingOsPolicy::Protect`] a disk that holds an operating system
+/// and is not kept is an error; manual and existing partitioning are left to
+/// the user.
+pub fn check_targets(
+    policy: ExistingOsPolicy,
+    scheme: PartitionScheme,
+    efi_partition: &str,
+    disks: &[DiskInfo],
+    targets: &[String],
+) -> Result<(), String> {
+    validate_policy(policy, scheme)?;
+    if policy != ExistingOsPolicy::Protect
+        || matches!(scheme, PartitionScheme::Manual | PartitionScheme::Existing)
+    {
+        return Ok(());
+    }
+    let endangered: Vec<String> = targets
+        .iter()
+        .filter(|target| !keeps_partitions(policy, scheme, efi_partition, target))
+        .filter_map(|target| disks.iter().find(|d| &d.path == target))
+        .filter(|d| !d.existing_os.is_empty())
+        .map(|d| format!("{} holds {}", d.path, d.existing_os.join(", ")))
+        .collect();
+    if endangered.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{}: set Existing OS to 'alongside' to install next to it or 'erase' to remove it",
+        endangered.join("; ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::DiskKind;
+
+    fn disk(path: &str, existing_os: &[&str]) -> DiskInfo {
+        DiskInfo {
+            path: path.to_string(),
+            size: 512 * 1024 * 1024 * 1024,
+            model: String::new(),
+            transport: "nvme".to_string(),
+            kind: DiskKind::Ssd,
+            partitions: vec![format!("{}p1", path)],
+            existing_os: existing_os.iter().map(|os| os.to_string()).collect(),
+            mounted: false,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_keeps_partitions() {
+        use ExistingOsPolicy::*;
+        use PartitionScheme::*;
+
+        assert!(keeps_partitions(
+            Alongside, AutoSimple, "create", "/dev/sda"
+        ));
+        assert!(keeps_partitions(Protect, AutoLvm, "/dev/sda1", "/dev/sda"));
+        assert!(!keeps_partitions(Protect, AutoLvm, "/dev/sdb1", "/dev/sda"));
+        assert!(!keeps_partitions(Erase, AutoSimple, "create", "/dev/sda"));
+        assert!(!keeps_partitions(Alongside, AutoRaid, "create", "/dev/sda"));
+        assert!(!keeps_partitions(Alongside, Manual, "create", "/dev/sda"));
+    }
+
+    #[test]
+    fn test_alongside_needs_an_automatic_strategy() {
+        use ExistingOsPolicy::*;
+
+        assert!(validate_policy(Alongside, PartitionScheme::AutoSimpleLuks).is_ok());
+        assert!(validate_policy(Alongside, PartitionScheme::AutoRaidLvm)
+            .unwrap_err()
+            .contains("RAID"));
+        assert!(validate_policy(Alongside, PartitionScheme::Manual).is_err());
+        assert!(validate_policy(Alongside, PartitionScheme::Existing).is_err());
+        assert!(validate_policy(Erase, PartitionScheme::AutoRaid).is_ok());
+    }
+
+    #[test]
+    fn test_protect_refuses_disks_with_an_os() {
+        use ExistingOsPolicy::*;
+        use PartitionScheme::*;
+
+        let disks = [
+            disk("/dev/nvme0n1", &["Windows Boot Manager"]),
+            disk("/dev/sda", &[]),
+        ];
+        let nvme = vec!["/dev/nvme0n1".to_string()];
+        let error = check_targets(Protect, AutoSimple, "create", &disks, &nvme).unwrap_err();
+        assert!(error.contains("/dev/nvme0n1 holds Windows Boot Manager"));
+        assert!(error.contains("'alongside'"));
+
+        assert!(check_targets(Protect, AutoSimple, "/dev/nvme0n1p1", &disks, &nvme).is_ok());
+        assert!(check_targets(Alongside, AutoSimple, "create", &disks, &nvme).is_ok());
+        assert!(check_targets(Erase, AutoSimple, "create", &disks, &nvme).is_ok());
+        assert!(check_targets(Protect, Manual, "create", &disks, &nvme).is_ok());
+        let sda = vec!["/dev/sda".to_string()];
+        assert!(check_targets(Protect, AutoSimple, "create", &disks, &sda).is_ok());
+    }
+}
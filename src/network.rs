@@ -0,0 +1,307 @@
+//! Dual-stack settings for the network tool
+//!
+//! `tools network configure` and the Configure Network dialog take IPv4 and
+//! IPv6 settings side by side. [`NetworkSettings`] checks them before
+//! configure_network.sh applies them with `ip`. When a backend is named, the
+//! script also writes them to a NetworkManager keyfile or a systemd-networkd
+//! unit so they outlive the live session.
+//!
+//! IPv4 is static when an address is given and DHCP otherwise. IPv6 follows
+//! its [`Ipv6Mode`]: router advertisements unless an address is given.
+
+use crate::script_env::ScriptArgs;
+use crate::types::{Ipv6Mode, NetworkBackend};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Settings of one interface
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkSettings {
+    pub interface: String,
+    /// Static IPv4 address, optionally with a `/prefix`
+    pub ipv4: Option<String>,
+    /// IPv4 netmask, dotted or as a prefix length, when the address has none
+    pub netmask: Option<String>,
+    pub gateway4: Option<String>,
+    /// Static IPv6 address, optionally with a `/prefix` (64 when left out)
+    pub ipv6: Option<String>,
+    pub gateway6: Option<String>,
+    /// IPv6 mode; `None` picks static with an address, else router advertisements
+    pub ipv6_mode: Option<Ipv6Mode>,
+    /// DNS servers of either family
+    pub dns: Vec<String>,
+    pub backend: Option<NetworkBackend>,
+}
+
+impl NetworkSettings {
+    /// The IPv6 mode in effect
+    pub fn ipv6_mode(&self) -> Ipv6Mode {
+        self.ipv6_mode.unwrap_or(if self.ipv6.is_some() {
+            Ipv6Mode::Static
+        } else {
+            Ipv6Mode::Ra
+        })
+    }
+
+    /// Check every address, naming the first one that is wrong
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interface.trim().is_empty() {
+            return Err("An interface is required".to_string());
+        }
+        if let Some(ref address) = self.ipv4 {
+            let (_, prefix) = parse_address::<Ipv4Addr>(address, 32, "IPv4 address")?;
+            if prefix.is_some() && self.netmask.is_some() {
+                return Err(format!(
+                    "IPv4 address '{}' has a prefix: leave the netmask out",
+                    address
+                ));
+            }
+        }
+        if let Some(ref netmask) = self.netmask {
+            if self.ipv4.is_none() {
+                return Err("A netmask needs a static IPv4 address".to_string());
+            }
+            netmask_prefix(netmask)?;
+        }
+        if let Some(ref gateway) = self.gateway4 {
+            if self.ipv4.is_none() {
+                return Err("An IPv4 gateway needs a static IPv4 address".to_string());
+            }
+            parse_gateway::<Ipv4Addr>(gateway, "IPv4 gateway")?;
+        }
+
+        let mode = self.ipv6_mode();
+        match (&self.ipv6, mode) {
+            (Some(address), Ipv6Mode::Static) => {
+                parse_address::<Ipv6Addr>(address, 128, "IPv6 address")?;
+            }
+            (None, Ipv6Mode::Static) => {
+                return Err("Static IPv6 needs an IPv6 address".to_string());
+            }
+            (Some(address), _) => {
+                return Err(format!(
+                    "IPv6 address '{}' is only used in static mode, not {}",
+                    address, mode
+                ));
+            }
+            (None, _) => {}
+        }
+        if let Some(ref gateway) = self.gateway6 {
+            if mode != Ipv6Mode::Static {
+                return Err(format!(
+                    "An IPv6 gateway is only used in static mode, not {}",
+                    mode
+                ));
+            }
+            parse_gateway::<Ipv6Addr>(gateway, "IPv6 gateway")?;
+        }
+
+        for server in &self.dns {
+            if server.parse::<IpAddr>().is_err() {
+                return Err(format!("DNS server '{}' is not an IP address", server));
+            }
+        }
+        Ok(())
+    }
+
+    /// Arguments of configure_network.sh's configure action
+    pub fn script_args(&self) -> ScriptArgs {
+        let dns = (!self.dns.is_empty()).then(|| self.dns.join(","));
+        ScriptArgs::new()
+            .value("--interface", &self.interface)
+            .value("--action", "configure")
+            .switch("--static", self.ipv4.is_some())
+            .switch("--dhcp", self.ipv4.is_none())
+            .optional("--ip", self.ipv4.as_ref())
+            .optional("--netmask", self.netmask.as_ref())
+            .optional("--gateway", self.gateway4.as_ref())
+            .value("--ipv6-mode", self.ipv6_mode())
+            .optional("--ip6", self.ipv6.as_ref())
+            .optional("--gateway6", self.gateway6.as_ref())
+            .optional("--dns", dns)
+            .optional("--backend", self.backend)
+    }
+}
+
+/// Parse DNS servers separated by commas or spaces
+pub fn parse_dns(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|server| !server.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Prefix length of a netmask given as `255.255.255.0` or `24`
+pub fn netmask_prefix(netmask: &str) -> Result<u8, String> {
+    if let Ok(prefix) = netmask.parse::<u8>() {
+        if prefix <= 32 {
+            return Ok(prefix);
+        }
+    }
+    let mask = netmask
+        .parse::<Ipv4Addr>()
+        .map(u32::from)
+        .map_err(|_| format!("Netmask '{}' is not a mask or prefix length", netmask))?;
+    if mask.leading_ones() + mask.trailing_zeros() != 32 {
+        return Err(format!("Netmask '{}' is not contiguous", netmask));
+    }
+    Ok(mask.leading_ones() as u8)
+}
+
+fn parse_address<A: std::str::FromStr>(
+    value: &str,
+    max_prefix: u8,
+    what: &str,
+) -> Result<(A, Option<u8>), String> {
+    let (address, prefix) = match value.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (value, None),
+    };
+    let address = address
+        .parse::<A>()
+        .map_err(|_| format!("{} '{}' is not valid", what, value))?;
+    let prefix = prefix
+        .map(|prefix| match prefix.parse::<u8>() {
+            Ok(prefix) if prefix <= max_prefix => Ok(prefix),
+            _ => Err(format!(
+                "{} '{}' needs a prefix length up to {}",
+                what, value, max_prefix
+            )),
+        })
+        .transpose()?;
+    Ok((address, prefix))
+}
+
+fn parse_gateway<A: std::str::FromStr>(value: &str, what: &str) -> Result<A, String> {
+    value
+        .parse::<A>()
+        .map_err(|_| format!("{} '{}' is not valid", what, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> NetworkSettings {
+        NetworkSettings {
+            interface: "enp1s0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dual_stack_static() {
+        let settings = NetworkSettings {
+            ipv4: Some("192.168.1.10/24".to_string()),
+            gateway4: Some("192.168.1.1".to_string()),
+            ipv6: Some("2001:db8::10/64".to_string()),
+            gateway6: Some("fe80::1".to_string()),
+            dns: parse_dns("192.168.1.1, 2001:4860:4860::8888"),
+            backend: Some(NetworkBackend::Networkd),
+            ..settings()
+        };
+        assert_eq!(settings.ipv6_mode(), Ipv6Mode::Static);
+        settings.validate().unwrap();
+        assert_eq!(
+            settings.script_args().as_slice(),
+            [
+                "--interface",
+                "enp1s0",
+                "--action",
+                "configure",
+                "--static",
+                "--ip",
+                "192.168.1.10/24",
+                "--gateway",
+                "192.168.1.1",
+                "--ipv6-mode",
+                "static",
+                "--ip6",
+                "2001:db8::10/64",
+                "--gateway6",
+                "fe80::1",
+                "--dns",
+                "192.168.1.1,2001:4860:4860::8888",
+                "--backend",
+                "networkd",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dhcp_with_ipv6_modes() {
+        let dhcp = settings();
+        dhcp.validate().unwrap();
+        assert_eq!(dhcp.ipv6_mode(), Ipv6Mode::Ra);
+        assert!(dhcp
+            .script_args()
+            .as_slice()
+            .contains(&"--dhcp".to_string()));
+
+        let dhcpv6 = NetworkSettings {
+            ipv6_mode: Some(Ipv6Mode::Dhcpv6),
+            ..settings()
+        };
+        dhcpv6.validate().unwrap();
+
+        let misplaced = NetworkSettings {
+            ipv6: Some("2001:db8::10".to_string()),
+            ipv6_mode: Some(Ipv6Mode::Dhcpv6),
+            ..settings()
+        };
+        assert!(misplaced
+            .validate()
+            .unwrap_err()
+            .contains("only used in static mode"));
+        let missing = NetworkSettings {
+            ipv6_mode: Some(Ipv6Mode::Static),
+            ..settings()
+        };
+        assert_eq!(
+            missing.validate().unwrap_err(),
+            "Static IPv6 needs an IPv6 address"
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_families_and_prefixes() {
+        let check = |settings: NetworkSettings| settings.validate().unwrap_err();
+        assert!(check(NetworkSettings {
+            ipv4: Some("2001:db8::10".to_string()),
+            ..settings()
+        })
+        .contains("IPv4 address"));
+        assert!(check(NetworkSettings {
+            ipv6: Some("2001:db8::10/129".to_string()),
+            ..settings()
+        })
+        .contains("prefix length up to 128"));
+        assert!(check(NetworkSettings {
+            ipv4: Some("10.0.0.2".to_string()),
+            gateway4: Some("fe80::1".to_string()),
+            ..settings()
+        })
+        .contains("IPv4 gateway"));
+        assert!(check(NetworkSettings {
+            dns: parse_dns("1.1.1.1 dns.example"),
+            ..settings()
+        })
+        .contains("'dns.example'"));
+        assert!(check(NetworkSettings {
+            gateway4: Some("10.0.0.1".to_string()),
+            ..settings()
+        })
+        .contains("needs a static IPv4 address"));
+    }
+
+    #[test]
+    fn test_netmask_prefix() {
+        assert_eq!(netmask_prefix("255.255.255.0"), Ok(24));
+        assert_eq!(netmask_prefix("255.255.240.0"), Ok(20));
+        assert_eq!(netmask_prefix("16"), Ok(16));
+        assert!(netmask_prefix("255.0.255.0")
+            .unwrap_err()
+            .contains("not contiguous"));
+        assert!(netmask_prefix("33").is_err());
+    }
+}
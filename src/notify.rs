@@ -0,0 +1,326 @@
+//! Notifications when an installation ends
+//!
+//! Installs take long enough that nobody watches them finish. When one ends,
+//! successfully or not, the terminal rings its bell and receives an OSC 9
+//! notification, which terminals like kitty, WezTerm, iTerm2 and Windows
+//! Terminal show on the desktop; others ignore it. With `notify_url` in the
+//! config file the outcome is also POSTed there: ntfy topics (hosts named
+//! `ntfy.*`) get a readable message with a title, any other URL gets a JSON
+//! document with the installation summary. Posting goes through curl and so
+//! through the proxy. With `--install-stats` the outcome is also added to the
+//! local [statistics file](crate::install_stats).
+
+use crate::config_file::InstallationConfig;
+use crate::install_metrics::InstallMetrics;
+use crate::install_stats::{self, InstallSettings, InstallSource};
+use crate::simulate;
+use serde_json::{json, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Seconds a webhook may take before the notification is given up
+const POST_TIMEOUT_SECS: u32 = 20;
+
+/// How an installation ended
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    pub success: bool,
+    /// Exit code of the installer; `None` when it was killed or never waited on
+    pub exit_code: Option<i32>,
+    /// Final status line, as the installer output shows it
+    pub message: String,
+    /// Phases, downloads and warnings, when the output was followed
+    pub summary: Option<InstallMetrics>,
+}
+
+/// Where the end of an installation is announced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Notifier {
+    /// Host name of the installed system, naming it in the notification
+    pub hostname: Option<String>,
+    /// Webhook or ntfy topic the outcome is POSTed to
+    pub url: Option<String>,
+    /// Settings recorded with the outcome when statistics are enabled
+    pub stats: Option<InstallSettings>,
+}
+
+impl Notifier {
+    /// Notifier for an installation from a config file
+    pub fn for_config(config: &InstallationConfig) -> Self {
+        Self {
+            hostname: Some(config.hostname.trim().to_string()).filter(|name| !name.is_empty()),
+            url: config.notify_url.clone(),
+            stats: Some(InstallSettings::for_config(config, InstallSource::Config)),
+        }
+    }
+
+    /// One-line title of the notification
+    pub fn title(&self, outcome: &Outcome) -> String {
+        let result = if outcome.success {
+            "Arch Linux installation finished"
+        } else {
+            "Arch Linux installation failed"
+        };
+        match &self.hostname {
+            Some(hostname) => format!("{} on {}", result, hostname),
+            None => result.to_string(),
+        }
+    }
+
+    /// Ring the terminal, record the outcome in the statistics and POST it
+    /// to the notify URL, if any
+    ///
+    /// `log` receives a line saying whether the webhook got the outcome.
+    pub fn notify(&self, outcome: &Outcome, mut log: impl FnMut(String)) {
+        ring(&self.title(outcome));
+        // A simulated installation says nothing about how installs go
+        if let Some(settings) = self.stats.as_ref().filter(|_| !simulate::is_enabled()) {
+            install_stats::record(settings, outcome, &mut log);
+        }
+
+        let Some(url) = &self.url else {
+            return;
+        };
+        if simulate::is_enabled() {
+            log(simulate::skipped("curl -X POST <notify_url>"));
+            return;
+        }
+        // The URL is not logged: an ntfy topic name is all it takes to read it
+        match self.post(url, outcome) {
+            Ok(()) => log("Sent the installation status to the notify URL".to_string()),
+            Err(e) => log(format!(
+                "WARNING: Failed to send the installation status to the notify URL: {}",
+                e
+            )),
+        }
+    }
+
+    fn post(&self, url: &str, outcome: &Outcome) -> Result<(), String> {
+        let mut command = Command::new("curl");
+        command
+            .args(["-fsS", "-X", "POST", "--max-time"])
+            .arg(POST_TIMEOUT_SECS.to_string());
+        let body = if is_ntfy(url) {
+            let (tags, priority) = if outcome.success {
+                ("white_check_mark", "default")
+            } else {
+                ("x", "high")
+            };
+            command
+                .arg("-H")
+                .arg(format!("Title: {}", self.title(outcome)))
+                .arg("-H")
+                .arg(format!("Tags: {}", tags))
+                .arg("-H")
+                .arg(format!("Priority: {}", priority));
+            text(outcome)
+        } else {
+            command.args(["-H", "Content-Type: application/json"]);
+            self.to_json(outcome).to_string()
+        };
+
+        let mut child = command
+            .args(["--data-binary", "@-", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(body.as_bytes())
+                .map_err(|e| format!("Failed to send the request body: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// The outcome as the JSON document webhooks receive
+    pub fn to_json(&self, outcome: &Outcome) -> Value {
+        let mut document = json!({
+            "event": "installation_finished",
+            "success": outcome.success,
+            "exit_code": outcome.exit_code,
+            "hostname": self.hostname,
+            "title": self.title(outcome),
+            "message": outcome.message,
+        });
+        if let Some(summary) = &outcome.summary {
+            let summary: Value =
+                serde_json::from_str(&summary.to_json()).expect("install summary is JSON");
+            document["summary"] = summary;
+        }
+        document
+    }
+}
+
+/// The outcome as the message text of an ntfy notification
+pub fn text(outcome: &Outcome) -> String {
+    let mut text = outcome.message.clone();
+    if let Some(summary) = &outcome.summary {
+        if let Some(total) = summary.total {
+            text.push_str(&format!(
+                "\nTotal time: {}",
+                crate::components::status_bar::format_elapsed(total)
+            ));
+        }
+        text.push_str(&format!(
+            "\nDownloaded: {}\nPackages installed: {}\nWarnings: {}",
+            summary.downloaded_text(),
+            summary.packages_installed,
+            summary.warnings.len()
+        ));
+    }
+    text
+}
+
+/// Whether `url` is an ntfy server, by its host name
+pub fn is_ntfy(url: &str) -> bool {
+    host(url).is_some_and(|host| host.split('.').next() == Some("ntfy"))
+}
+
+/// Host of an http:// or https:// URL
+pub fn host(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    Some(host).filter(|host| !host.is_empty())
+}
+
+/// Check a notify URL: http or https with a host, nothing curl could misread
+pub fn validate_url(url: &str) -> Result<(), String> {
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("Notify URL cannot contain whitespace".to_string());
+    }
+    if host(url).is_none() {
+        return Err(format!(
+            "Notify URL '{}' must be an http:// or https:// URL with a host",
+            url
+        ));
+    }
+    Ok(())
+}
+
+/// Terminal bell followed by an OSC 9 notification with `title`
+pub fn terminal_sequence(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x07\x1b]9;{}\x07", title)
+}
+
+/// Ring the controlling terminal, which the TUI and headless output share;
+/// without one (an install started from a script) nothing is rung
+fn ring(title: &str) {
+    let rung = OpenOptions::new()
+        .write(true)
+        .open("/dev/tty")
+        .and_then(|mut tty| {
+            tty.write_all(terminal_sequence(title).as_bytes())?;
+            tty.flush()
+        });
+    if let Err(e) = rung {
+        log::debug!("No terminal to notify: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn notifier(url: Option<&str>) -> Notifier {
+        Notifier {
+            hostname: Some("archbox".to_string()),
+            url: url.map(str::to_string),
+            stats: None,
+        }
+    }
+
+    fn failed() -> Outcome {
+        let mut summary = InstallMetrics::default();
+        summary.begin_phase("Installing base system", std::time::Instant::now());
+        summary.packages_installed = 12;
+        Outcome {
+            success: false,
+            exit_code: Some(1),
+            message: "Installation failed with exit code: 1".to_string(),
+            summary: Some(summary),
+        }
+    }
+
+    #[test]
+    fn test_title_names_the_host() {
+        let outcome = Outcome {
+            success: true,
+            ..Outcome::default()
+        };
+        assert_eq!(
+            notifier(None).title(&outcome),
+            "Arch Linux installation finished on archbox"
+        );
+        assert_eq!(
+            Notifier::default().title(&failed()),
+            "Arch Linux installation failed"
+        );
+    }
+
+    #[test]
+    fn test_terminal_sequence_rings_and_notifies() {
+        assert_eq!(
+            terminal_sequence("Done\x07\x1b]0;x"),
+            "\x07\x1b]9;Done]0;x\x07"
+        );
+    }
+
+    #[test]
+    fn test_json_carries_the_summary() {
+        let mut outcome = failed();
+        let json = notifier(Some("https://example.com/hook")).to_json(&outcome);
+        assert_eq!(json["event"], "installation_finished");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(json["hostname"], "archbox");
+        assert_eq!(json["summary"]["packages_installed"], 12);
+
+        outcome.summary = None;
+        let json = Notifier::default().to_json(&outcome);
+        assert!(json["hostname"].is_null());
+        assert!(json.get("summary").is_none());
+    }
+
+    #[test]
+    fn test_text_for_ntfy() {
+        let mut outcome = failed();
+        outcome.summary.as_mut().unwrap().total = Some(Duration::from_secs(75));
+        assert_eq!(
+            text(&outcome),
+            "Installation failed with exit code: 1\nTotal time: 1:15\n\
+             Downloaded: not measured\nPackages installed: 12\nWarnings: 0"
+        );
+        outcome.summary = None;
+        assert_eq!(text(&outcome), "Installation failed with exit code: 1");
+    }
+
+    #[test]
+    fn test_ntfy_and_url_validation() {
+        assert!(is_ntfy("https://ntfy.sh/my-installs"));
+        assert!(is_ntfy("http://user:pw@ntfy.example.org:8080/topic"));
+        assert!(!is_ntfy("https://hooks.example.com/ntfy"));
+        assert!(!is_ntfy("ntfy.sh/topic"));
+
+        assert!(validate_url("https://ntfy.sh/my-installs").is_ok());
+        assert!(validate_url("https://example.com/hook?token=abc").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("https://").is_err());
+        assert!(validate_url("https://example.com/a b").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Package progress from pacman output
+//!
+//! pacstrap and the chroot's `pacman -S` calls print the size of each
+//! transaction ("Packages (420) base-3-2 ..."), a line per download
+//! ("firefox-131.0-1-x86_64 downloading...") and a counter per installed
+//! package ("(143/420) installing firefox"). Following them lets the
+//! Installation screen name the package being installed and move the progress
+//! bar through a phase by package count instead of jumping between phases.
+
+/// Installer output prefixes pacman lines with the tool that ran it and
+/// colors them; both are stripped before parsing
+const TOOL_PREFIXES: &[&str] = &["[pacstrap]", "[pacman]"];
+
+/// Transaction verbs that count towards the installed packages
+const INSTALL_VERBS: &[&str] = &["installing", "upgrading", "reinstalling", "downgrading"];
+
+/// Packages downloaded and installed by the running pacman transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageProgress {
+    /// Packages in the transaction; 0 until pacman has listed them
+    pub total: usize,
+    /// Packages downloaded so far
+    pub downloaded: usize,
+    /// Packages installed so far
+    pub installed: usize,
+    /// Package last downloaded or installed
+    pub package: String,
+}
+
+impl PackageProgress {
+    /// Whether a transaction is running
+    pub fn is_active(&self) -> bool {
+        self.total > 0
+    }
+
+    /// Follow one installer output line; returns whether the progress changed
+    pub fn update(&mut self, line: &str) -> bool {
+        let line = strip_colors(line);
+        let mut line = line.trim();
+        for prefix in TOOL_PREFIXES {
+            if let Some(rest) = line.strip_prefix(prefix) {
+                line = rest.trim_start();
+            }
+        }
+
+        if let Some(total) = transaction_size(line) {
+            *self = Self {
+                total,
+                ..Self::default()
+            };
+            return true;
+        }
+        // Databases are downloaded before the transaction is listed
+        if !self.is_active() {
+            return false;
+        }
+        if let Some((current, package)) = installed_package(line) {
+            self.downloaded = self.total;
+            self.installed = current.min(self.total);
+            self.package = package.to_string();
+            return true;
+        }
+        if let Some(package) = downloaded_package(line) {
+            if self.installed == 0 && self.downloaded < self.total {
+                self.downloaded += 1;
+                self.package = package.to_string();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// How far the transaction is, from 0.0 to 1.0
+    ///
+    /// Downloads make up the first half and installs the second; packages
+    /// already in the cache are not downloaded, so installing counts every
+    /// download as done.
+    pub fn fraction(&self) -> f64 {
+        if !self.is_active() {
+            return 0.0;
+        }
+        let done = self.downloaded + self.installed;
+        (done as f64 / (2 * self.total) as f64).min(1.0)
+    }
+
+    /// Status line, e.g. "Installing package 143/420: firefox"
+    pub fn summary(&self) -> String {
+        if self.installed > 0 {
+            format!(
+                "Installing package {}/{}: {}",
+                self.installed, self.total, self.package
+            )
+        } else if self.downloaded > 0 {
+            format!(
+                "Downloading package {}/{}: {}",
+                self.downloaded, self.total, self.package
+            )
+        } else {
+            format!("Preparing {} packages", self.total)
+        }
+    }
+}
+
+/// Remove ANSI color sequences such as `\x1b[1;32m`
+fn strip_colors(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// "Packages (420) ..." or, with VerbosePkgLists, the "Package (420)" header
+fn transaction_size(line: &str) -> Option<usize> {
+    let rest = line
+        .strip_prefix("Packages (")
+        .or_else(|| line.strip_prefix("Package ("))?;
+    let (count, _) = rest.split_once(')')?;
+    count.parse().ok().filter(|count| *count > 0)
+}
+
+/// "(143/420) installing firefox", where pacman pads the counter with spaces
+fn installed_package(line: &str) -> Option<(usize, &str)> {
+    let rest = line.strip_prefix('(')?;
+    let (counter, rest) = rest.split_once(')')?;
+    let (current, _) = counter.split_once('/')?;
+    let current = current.trim().parse().ok()?;
+    let mut words = rest.split_whitespace();
+    let verb = words.next()?;
+    if !INSTALL_VERBS.contains(&verb) {
+        return None;
+    }
+    Some((current, words.next()?))
+}
+
+/// "firefox-131.0-1-x86_64 downloading...", named without version and arch
+fn downloaded_package(line: &str) -> Option<&str> {
+    let (file, _) = line.split_once(" downloading...")?;
+    let file = file.trim();
+    // name-version-release-arch; names may contain dashes themselves
+    let parts: Vec<&str> = file.rsplitn(4, '-').collect();
+    Some(if parts.len() == 4 { parts[3] } else { file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follows_a_pacstrap_transaction() {
+        let mut progress = PackageProgress::default();
+        assert!(!progress.update("  [pacstrap]  core downloading..."));
+        assert!(!progress.is_active());
+
+        assert!(progress.update(
+            "\x1b[0;36m  [pacstrap] Packages (420) acl-2.3.2-1  archlinux-keyring-20241015-1\x1b[0m"
+        ));
+        assert_eq!(progress.total, 420);
+        assert_eq!(progress.summary(), "Preparing 420 packages");
+
+        assert!(progress.update("  [pacstrap]  xdg-utils-1.2.1-1-any downloading..."));
+        assert_eq!(progress.summary(), "Downloading package 1/420: xdg-utils");
+        assert_eq!(progress.fraction(), 1.0 / 840.0);
+
+        assert!(progress.update("  [pacstrap] (143/420) installing firefox"));
+        assert_eq!(progress.summary(), "Installing package 143/420: firefox");
+        assert_eq!(progress.fraction(), (420.0 + 143.0) / 840.0);
+
+        // Other counted steps don't advance the install count
+        assert!(!progress.update("( 12/420) checking keys in keyring"));
+        assert!(!progress.update("  [pacstrap]  zstd-1.5.6-1-x86_64 downloading..."));
+
+        assert!(progress.update("  [pacman] Packages (2) gdm-47.0-1  gnome-shell-1:47.0-1"));
+        assert_eq!(progress.installed, 0);
+        assert_eq!(progress.total, 2);
+    }
+
+    #[test]
+    fn test_pads_and_verbose_package_lists() {
+        assert_eq!(transaction_size("Package (12)   New Version"), Some(12));
+        assert_eq!(transaction_size("Packages: base linux"), None);
+        assert_eq!(
+            installed_package("(  7/420) upgrading linux-firmware"),
+            Some((7, "linux-firmware"))
+        );
+        assert_eq!(installed_package("(1/2) checking package integrity"), None);
+        assert_eq!(
+            downloaded_package(" linux-firmware-20241017-1-any downloading..."),
+            Some("linux-firmware")
+        );
+    }
+}
@@ -1,8 +1,84 @@
 use crate::config::Package;
+use crate::proxy::Proxy;
+use serde::Deserialize;
 use std::process::Command;
+use std::sync::OnceLock;
+
+/// Curated package set that can be installed as a whole
+///
+/// The groups are defined in `scripts/package_groups.json`, which the Bash
+/// config loader expands too, so both install paths agree on the contents.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PackageGroup {
+    /// Name recorded in the config file, e.g. `development`
+    pub id: String,
+    /// Display name, e.g. "Development tools"
+    pub name: String,
+    pub description: String,
+    /// Whether the packages come from the multilib repository
+    #[serde(default)]
+    pub requires_multilib: bool,
+    pub packages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PackageGroupFile {
+    groups: Vec<PackageGroup>,
+}
+
+/// All curated package groups, in display order
+pub fn package_groups() -> &'static [PackageGroup] {
+    static GROUPS: OnceLock<Vec<PackageGroup>> = OnceLock::new();
+    GROUPS.get_or_init(|| {
+        serde_json::from_str::<PackageGroupFile>(include_str!("../scripts/package_groups.json"))
+            .expect("scripts/package_groups.json is valid")
+            .groups
+    })
+}
+
+/// Look up a package group by its ID
+pub fn find_package_group(id: &str) -> Option<&'static PackageGroup> {
+    package_groups().iter().find(|group| group.id == id)
+}
+
+/// Parse a space-separated list of group IDs
+pub fn parse_package_groups(list: &str) -> Result<Vec<&'static PackageGroup>, String> {
+    let mut groups: Vec<&'static PackageGroup> = Vec::new();
+    for id in list.split_whitespace() {
+        let group = find_package_group(id).ok_or_else(|| {
+            let known: Vec<&str> = package_groups().iter().map(|g| g.id.as_str()).collect();
+            format!(
+                "Unknown package group '{}' (available: {})",
+                id,
+                known.join(", ")
+            )
+        })?;
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    Ok(groups)
+}
+
+/// Explicit packages followed by the packages of the groups, without duplicates
+///
+/// Unknown group IDs are skipped; validate them with [`parse_package_groups`].
+pub fn merge_packages(explicit: &str, group_ids: &[String]) -> String {
+    let mut packages: Vec<&str> = Vec::new();
+    let grouped = group_ids
+        .iter()
+        .filter_map(|id| find_package_group(id))
+        .flat_map(|group| group.packages.iter().map(String::as_str));
+    for package in explicit.split_whitespace().chain(grouped) {
+        if !packages.contains(&package) {
+            packages.push(package);
+        }
+    }
+    packages.join(" ")
+}
 
 /// Search for pacman packages using pacman -Ss
-pub fn search_pacman_packages(search_term: &str) -> Result<Vec<Package>, String> {
+pub fn search_pacman_packages(search_term: &str, proxy: &Proxy) -> Result<Vec<Package>, String> {
     // Validate search term to prevent command injection
     if search_term.contains(";")
         || search_term.contains("|")
@@ -15,8 +91,10 @@ pub fn search_pacman_packages(search_term: &str) -> Result<Vec<Package>, String>
         return Err("Invalid characters in search term".to_string());
     }
 
-    let output = Command::new("pacman")
-        .args(["-Ss", search_term])
+    let mut command = Command::new("pacman");
+    command.args(["-Ss", search_term]);
+    proxy.apply_to(&mut command);
+    let output = command
         .output()
         .map_err(|e| format!("Failed to run pacman: {}", e))?;
 
@@ -77,7 +155,7 @@ pub fn search_pacman_packages(search_term: &str) -> Result<Vec<Package>, String>
 }
 
 /// Search for AUR packages using curl and AUR RPC API
-pub fn search_aur_packages(search_term: &str) -> Result<Vec<Package>, String> {
+pub fn search_aur_packages(search_term: &str, proxy: &Proxy) -> Result<Vec<Package>, String> {
     // Validate search term to prevent URL injection
     if search_term.contains(";")
         || search_term.contains("|")
@@ -96,8 +174,10 @@ pub fn search_aur_packages(search_term: &str) -> Result<Vec<Package>, String> {
         search_term
     );
 
-    let output = Command::new("curl")
-        .args(["-s", &url])
+    let mut command = Command::new("curl");
+    command.args(["-s", &url]);
+    proxy.apply_to(&mut command);
+    let output = command
         .output()
         .map_err(|e| format!("Failed to run curl: {}", e))?;
 
@@ -138,10 +218,49 @@ pub fn search_aur_packages(search_term: &str) -> Result<Vec<Package>, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_package_groups_are_well_formed() {
+        let groups = package_groups();
+        assert!(!groups.is_empty());
+        for (i, group) in groups.iter().enumerate() {
+            assert!(!group.packages.is_empty(), "{} has no packages", group.id);
+            assert!(
+                group
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c == '-'),
+                "{} is not a valid group ID",
+                group.id
+            );
+            assert!(groups[..i].iter().all(|g| g.id != group.id));
+            // Only multilib groups may pull in 32-bit packages
+            let lib32 = group.packages.iter().any(|p| p.starts_with("lib32-"));
+            assert!(!lib32 || group.requires_multilib, "{} needs multilib", group.id);
+        }
+        assert!(find_package_group("gaming").unwrap().requires_multilib);
+    }
+
+    #[test]
+    fn test_parse_and_merge_package_groups() {
+        let groups = parse_package_groups("development fonts development").unwrap();
+        let ids: Vec<&str> = groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec!["development", "fonts"]);
+        assert!(parse_package_groups("").unwrap().is_empty());
+        let error = parse_package_groups("devel").unwrap_err();
+        assert!(error.contains("Unknown package group 'devel'"));
+
+        let merged = merge_packages("htop git", &["development".to_string()]);
+        let merged: Vec<&str> = merged.split(' ').collect();
+        assert_eq!(&merged[..3], &["htop", "git", "base-devel"]);
+        assert_eq!(merged.iter().filter(|p| **p == "git").count(), 1);
+        assert_eq!(merge_packages("htop", &[]), "htop");
+    }
+
     #[test]
     fn test_search_pacman_packages_invalid_command() {
         // Test with an invalid search term that should return no results
-        let result = search_pacman_packages("this_package_does_not_exist_12345");
+        let result =
+            search_pacman_packages("this_package_does_not_exist_12345", &Proxy::from_env());
         // In test environments, pacman may not be available, so we accept both success and failure
         match result {
             Ok(packages) => {
@@ -158,7 +277,7 @@ mod tests {
     #[test]
     fn test_search_aur_packages_invalid_search() {
         // Test with an invalid search term that should return no results
-        let result = search_aur_packages("this_package_does_not_exist_12345");
+        let result = search_aur_packages("this_package_does_not_exist_12345", &Proxy::from_env());
         assert!(result.is_ok());
         let packages = result.unwrap();
         assert!(packages.is_empty());
@@ -168,7 +287,7 @@ mod tests {
     fn test_search_aur_packages_network_handling() {
         // Test that AUR search handles network errors gracefully
         // This test may fail if network is unavailable, which is expected
-        let result = search_aur_packages("linux");
+        let result = search_aur_packages("linux", &Proxy::from_env());
         // We don't assert success here because network may be unavailable in test environments
         match result {
             Ok(packages) => {
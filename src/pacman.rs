@@ -0,0 +1,270 @@
+//! Pacman configuration tuning
+//!
+//! Settings written to pacman.conf on the live system (so pacstrap uses them)
+//! and on the installed system: parallel downloads, output style, and extra
+//! repositories whose signing keys the installer imports.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// ParallelDownloads used when nothing else is configured
+pub const DEFAULT_PARALLEL_DOWNLOADS: u8 = 5;
+
+/// Highest ParallelDownloads value accepted; more only hammers the mirror
+pub const MAX_PARALLEL_DOWNLOADS: u8 = 50;
+
+/// Section names pacman.conf already uses
+const RESERVED_REPOSITORY_NAMES: &[&str] = &[
+    "options",
+    "core",
+    "extra",
+    "multilib",
+    "core-testing",
+    "extra-testing",
+    "multilib-testing",
+];
+
+/// Trust levels a SigLevel line accepts; each may be narrowed to packages or
+/// databases with a `Package` or `Database` prefix
+const SIG_LEVELS: &[&str] = &["Never", "Optional", "Required", "TrustedOnly", "TrustAll"];
+
+/// Check a ParallelDownloads value
+pub fn validate_parallel_downloads(value: &str) -> Result<u8, String> {
+    match value.trim().parse::<u8>() {
+        Ok(count) if (1..=MAX_PARALLEL_DOWNLOADS).contains(&count) => Ok(count),
+        _ => Err(format!(
+            "Parallel downloads must be a number from 1 to {}",
+            MAX_PARALLEL_DOWNLOADS
+        )),
+    }
+}
+
+/// Check a SigLevel value such as `Required DatabaseOptional`
+pub fn validate_sig_level(value: &str) -> Result<(), String> {
+    if value.split_whitespace().next().is_none() {
+        return Err("SigLevel cannot be empty".to_string());
+    }
+    for level in value.split_whitespace() {
+        let trust = level
+            .strip_prefix("Package")
+            .or_else(|| level.strip_prefix("Database"))
+            .unwrap_or(level);
+        if !SIG_LEVELS.contains(&trust) {
+            return Err(format!(
+                "Unknown SigLevel '{}': use {}, optionally prefixed with Package or Database",
+                level,
+                SIG_LEVELS.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Repository appended to pacman.conf
+///
+/// Written as `name|server[|key_id[|sig_level]]` in the TUI and in the
+/// `CUSTOM_REPOSITORIES` variable the scripts read, entries separated by
+/// spaces and the words of the SigLevel by commas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomRepository {
+    /// Section name, e.g. `chaotic-aur`
+    pub name: String,
+    /// `Server` URL; may use pacman's `$repo` and `$arch` variables
+    pub server: String,
+    /// Signing key to import and locally sign; without one the repository is
+    /// added with `SigLevel = Optional TrustAll`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// `SigLevel` of the section, e.g. `Required DatabaseOptional`; replaces
+    /// the default for repositories with or without a key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig_level: Option<String>,
+}
+
+impl CustomRepository {
+    /// Check the name, server URL and key ID
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Repository name is required".to_string());
+        }
+        if !self
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(format!(
+                "Repository name '{}' may only contain letters, digits, '-', '_' and '.'",
+                self.name
+            ));
+        }
+        if RESERVED_REPOSITORY_NAMES.contains(&self.name.as_str()) {
+            return Err(format!("'{}' is already a pacman.conf section", self.name));
+        }
+
+        let server = self.server.as_str();
+        if !["http://", "https://", "ftp://", "file://"]
+            .iter()
+            .any(|scheme| server.starts_with(scheme))
+        {
+            return Err(format!(
+                "Server for '{}' must start with http://, https://, ftp:// or file://",
+                self.name
+            ));
+        }
+        if server.contains(char::is_whitespace) || server.contains('|') {
+            return Err(format!("Server for '{}' cannot contain spaces or '|'", self.name));
+        }
+
+        if let Some(key_id) = &self.key_id {
+            if !(8..=40).contains(&key_id.len()) || !key_id.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(format!(
+                    "Key for '{}' must be an 8 to 40 digit hexadecimal key ID or fingerprint",
+                    self.name
+                ));
+            }
+        }
+
+        if let Some(sig_level) = &self.sig_level {
+            validate_sig_level(sig_level).map_err(|e| format!("{} for '{}'", e, self.name))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for CustomRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.name, self.server)?;
+        if self.key_id.is_some() || self.sig_level.is_some() {
+            write!(f, "|{}", self.key_id.as_deref().unwrap_or_default())?;
+        }
+        if let Some(sig_level) = &self.sig_level {
+            let words: Vec<&str> = sig_level.split_whitespace().collect();
+            write!(f, "|{}", words.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CustomRepository {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split('|');
+        let name = fields.next().unwrap_or_default().to_string();
+        let Some(server) = fields.next() else {
+            return Err(format!(
+                "'{}' needs a server: name|server[|key[|siglevel]]",
+                s
+            ));
+        };
+        let key_id = fields
+            .next()
+            .filter(|key| !key.is_empty())
+            .map(|key| key.to_string());
+        let sig_level = fields
+            .next()
+            .filter(|level| !level.is_empty())
+            .map(|level| level.replace(',', " "));
+        if fields.next().is_some() {
+            return Err(format!(
+                "'{}' has too many fields: name|server[|key[|siglevel]]",
+                s
+            ));
+        }
+
+        let repository = Self {
+            name,
+            server: server.to_string(),
+            key_id,
+            sig_level,
+        };
+        repository.validate()?;
+        Ok(repository)
+    }
+}
+
+/// Parse a space-separated list of `name|server[|key[|siglevel]]` entries
+pub fn parse_repositories(list: &str) -> Result<Vec<CustomRepository>, String> {
+    let repositories = list
+        .split_whitespace()
+        .map(CustomRepository::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, repository) in repositories.iter().enumerate() {
+        if repositories[..i].iter().any(|r| r.name == repository.name) {
+            return Err(format!("Repository '{}' is listed twice", repository.name));
+        }
+    }
+
+    Ok(repositories)
+}
+
+/// Format repositories the way [`parse_repositories`] reads them
+pub fn format_repositories(repositories: &[CustomRepository]) -> String {
+    repositories
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_downloads_range() {
+        assert_eq!(validate_parallel_downloads("5"), Ok(5));
+        assert_eq!(validate_parallel_downloads(" 50 "), Ok(50));
+        assert!(validate_parallel_downloads("0").is_err());
+        assert!(validate_parallel_downloads("51").is_err());
+        assert!(validate_parallel_downloads("many").is_err());
+    }
+
+    #[test]
+    fn test_parse_repositories_round_trip() {
+        let list = "chaotic-aur|https://cdn-mirror.chaotic.cx/$repo/$arch|3056513887B78AEB \
+                    local|file:///srv/repo \
+                    corp|https://repo.corp.example/$arch||Required,DatabaseOptional";
+        let repositories = parse_repositories(list).unwrap();
+
+        assert_eq!(repositories.len(), 3);
+        assert_eq!(repositories[0].name, "chaotic-aur");
+        assert_eq!(repositories[0].key_id.as_deref(), Some("3056513887B78AEB"));
+        assert_eq!(repositories[1].key_id, None);
+        assert_eq!(repositories[2].key_id, None);
+        assert_eq!(
+            repositories[2].sig_level.as_deref(),
+            Some("Required DatabaseOptional")
+        );
+        assert_eq!(
+            parse_repositories(&format_repositories(&repositories)).unwrap(),
+            repositories
+        );
+        assert!(parse_repositories("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sig_levels() {
+        assert!(validate_sig_level("Optional TrustAll").is_ok());
+        assert!(validate_sig_level("PackageRequired DatabaseNever").is_ok());
+        assert!(validate_sig_level("").is_err());
+        assert!(validate_sig_level("Required Sometimes").is_err());
+        assert!(validate_sig_level("PackageDatabaseNever").is_err());
+    }
+
+    #[test]
+    fn test_parse_repositories_rejects_bad_entries() {
+        assert!(parse_repositories("onlyname").is_err());
+        assert!(parse_repositories("core|https://example.com").is_err());
+        assert!(parse_repositories("my repo|https://example.com").is_err());
+        assert!(parse_repositories("mine|example.com/$arch").is_err());
+        assert!(parse_repositories("mine|https://example.com|not-a-key").is_err());
+        assert!(parse_repositories("mine|https://example.com|ABCD1234|Sometimes").is_err());
+        assert!(parse_repositories("mine|https://example.com|ABCD1234|Never|extra").is_err());
+        assert!(parse_repositories("mine|https://a.example mine|https://b.example").is_err());
+    }
+}
@@ -0,0 +1,723 @@
+//! Validation of manual partition layouts
+//!
+//! With the manual strategy the user partitions the disks, formats the
+//! partitions and mounts them under /mnt; install.sh only checks that
+//! something is mounted there. This module reads the selected disks with
+//! lsblk and lists everything that would keep the result from installing
+//! or booting: a missing or unusable EFI System Partition on UEFI, a GPT
+//! disk without a BIOS boot partition on BIOS, no root, two filesystems on
+//! one mountpoint and filesystems that cannot hold what is mounted on them.
+//! Errors stop the installation from starting; warnings (a root that is not
+//! mounted yet, a small ESP) are shown but do not.
+
+use crate::esp::{ESP_GUID, ESP_MBR_TYPE};
+use crate::tools::mounts;
+use crate::tools::resize::format_size;
+use crate::types::BootMode;
+use serde_json::Value;
+use std::fmt;
+use std::process::{Command, Stdio};
+
+/// Where the installed system is mounted
+pub const TARGET: &str = "/mnt";
+
+/// GPT partition type GUID of a BIOS boot partition
+pub const BIOS_BOOT_GUID: &str = "21686148-6449-6e6f-744e-656564454649";
+
+/// Smallest partition mkfs.fat creates a FAT32 filesystem on
+const MIN_ESP_SIZE: u64 = 32 * 1024 * 1024;
+
+/// ESP size below which kernels and initramfs images may not fit on it
+const SMALL_ESP_SIZE: u64 = 300 * 1024 * 1024;
+
+/// Columns read from lsblk, sizes in bytes
+const LSBLK_COLUMNS: &str = "PATH,TYPE,SIZE,PTTYPE,PARTTYPE,PARTTYPENAME,FSTYPE,MOUNTPOINTS";
+
+/// Filesystems a Linux root (and /home, /var, ...) can live on
+const LINUX_FILESYSTEMS: &[&str] = &[
+    "ext4", "ext3", "ext2", "btrfs", "xfs", "f2fs", "bcachefs", "jfs",
+];
+
+/// Filesystems without Unix permissions, symlinks or device files
+const FOREIGN_FILESYSTEMS: &[&str] = &["vfat", "exfat", "ntfs", "ntfs3", "iso9660"];
+
+/// Where the ESP can be mounted for the bootloaders
+const ESP_MOUNTPOINTS: &[&str] = &["/efi", "/boot", "/boot/efi"];
+
+/// A partition of a selected disk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Partition {
+    pub path: String,
+    /// Size in bytes, 0 when unknown
+    pub size: u64,
+    /// GPT type GUID or MBR type (`0x83`), lowercase
+    pub part_type: String,
+    /// Name of the partition type as lsblk gives it, e.g. "EFI System"
+    pub type_name: String,
+    /// Filesystem or container signature, empty when unformatted
+    pub fstype: String,
+    /// Where the partition is mounted now, anywhere; `[SWAP]` for active swap
+    pub mountpoints: Vec<String>,
+}
+
+impl Partition {
+    pub fn is_esp(&self) -> bool {
+        self.part_type == ESP_GUID || self.part_type == ESP_MBR_TYPE
+    }
+
+    pub fn is_bios_boot(&self) -> bool {
+        self.part_type == BIOS_BOOT_GUID
+    }
+
+    /// Type shown to the user: the lsblk name, or the raw type
+    pub fn type_display(&self) -> &str {
+        if self.type_name.is_empty() {
+            &self.part_type
+        } else {
+            &self.type_name
+        }
+    }
+}
+
+/// A selected disk and its partitions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Disk {
+    pub path: String,
+    /// Partition table: `gpt`, `dos` or empty without one
+    pub table: String,
+    pub partitions: Vec<Partition>,
+}
+
+/// A filesystem mounted below [`TARGET`]: a partition, or a LUKS or LVM
+/// volume on one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mount {
+    pub device: String,
+    pub fstype: String,
+    /// Mountpoint in the installed system, e.g. `/` or `/boot`
+    pub path: String,
+}
+
+impl Mount {
+    /// Mountpoint on the live system
+    pub fn live_path(&self) -> String {
+        if self.path == "/" {
+            TARGET.to_string()
+        } else {
+            format!("{}{}", TARGET, self.path)
+        }
+    }
+}
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The installation cannot work like this
+    Error,
+    /// Worth fixing, but the installation can go ahead
+    Warning,
+}
+
+/// Something wrong with the layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub severity: Severity,
+    /// Disk or partition the problem is about, `None` for the whole layout
+    pub device: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.device {
+            Some(device) => write!(f, "{}: {}", device, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Layout of the selected disks and its problems, errors first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Boot mode the layout was checked for (UEFI or BIOS, never Auto)
+    pub boot_mode: BootMode,
+    pub disks: Vec<Disk>,
+    pub mounts: Vec<Mount>,
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Check a layout for the given boot mode
+    pub fn new(boot_mode: BootMode, disks: Vec<Disk>, mounts: Vec<Mount>) -> Self {
+        let mut problems = check(boot_mode, &disks, &mounts);
+        problems.sort_by_key(|problem| problem.severity);
+        Self {
+            boot_mode,
+            disks,
+            mounts,
+            problems,
+        }
+    }
+
+    /// Number of problems that stop the installation
+    pub fn error_count(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Whether a root filesystem is mounted at [`TARGET`]
+    pub fn is_mounted(&self) -> bool {
+        self.mounts.iter().any(|mount| mount.path == "/")
+    }
+
+    /// Where a device is mounted in the installed system
+    pub fn mountpoint(&self, device: &str) -> Option<&str> {
+        self.mounts
+            .iter()
+            .find(|mount| mount.device == device)
+            .map(|mount| mount.path.as_str())
+    }
+}
+
+/// Boot mode the layout has to suit; Auto is the mode the ISO was booted in
+pub fn resolve_boot_mode(boot_mode: BootMode) -> BootMode {
+    match boot_mode {
+        BootMode::Auto if std::path::Path::new("/sys/firmware/efi").exists() => BootMode::Uefi,
+        BootMode::Auto => BootMode::Bios,
+        mode => mode,
+    }
+}
+
+/// Read the selected disks and check their layout
+///
+/// Mounts below [`TARGET`] are taken from every device, so a /home on
+/// another disk still counts.
+pub fn scan(disk_paths: &[String], boot_mode: BootMode) -> Result<Report, String> {
+    let (disks, mounts) = read_layout(disk_paths)?;
+    Ok(Report::new(resolve_boot_mode(boot_mode), disks, mounts))
+}
+
+/// Read the selected disks and the filesystems mounted below [`TARGET`]
+pub fn read_layout(disk_paths: &[String]) -> Result<(Vec<Disk>, Vec<Mount>), String> {
+    let output = Command::new("lsblk")
+        .args(["-J", "-b", "-o", LSBLK_COLUMNS])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run lsblk: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "lsblk failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let (disks, mounts) = parse_lsblk(&String::from_utf8_lossy(&output.stdout), disk_paths)
+        .map_err(|e| format!("Failed to parse lsblk output: {}", e))?;
+    if let Some(missing) = disk_paths
+        .iter()
+        .find(|path| !disks.iter().any(|disk| &disk.path == *path))
+    {
+        return Err(format!("Disk {} was not found", missing));
+    }
+    Ok((disks, mounts))
+}
+
+/// Parse `lsblk -J -b -o` [`LSBLK_COLUMNS`] into the selected disks and the
+/// filesystems mounted below [`TARGET`]
+pub fn parse_lsblk(
+    json: &str,
+    disk_paths: &[String],
+) -> Result<(Vec<Disk>, Vec<Mount>), serde_json::Error> {
+    let value: Value = serde_json::from_str(json)?;
+    let devices = value["blockdevices"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let text = |dev: &Value, key: &str| dev[key].as_str().unwrap_or_default().trim().to_string();
+
+    let mut disks = Vec::new();
+    let mut mounts = Vec::new();
+    for dev in &devices {
+        collect_mounts(dev, &mut mounts);
+        let path = text(dev, "path");
+        if !disk_paths.contains(&path) {
+            continue;
+        }
+        let partitions = dev["children"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|child| child["type"].as_str() == Some("part"))
+            .map(|child| Partition {
+                path: text(child, "path"),
+                // Older lsblk versions print numbers as strings
+                size: child["size"]
+                    .as_u64()
+                    .or_else(|| child["size"].as_str().and_then(|s| s.parse().ok()))
+                    .unwrap_or(0),
+                part_type: text(child, "parttype").to_lowercase(),
+                type_name: text(child, "parttypename"),
+                fstype: text(child, "fstype"),
+                mountpoints: mounts::mountpoints(child),
+            })
+            .collect();
+        disks.push(Disk {
+            path,
+            table: text(dev, "pttype"),
+            partitions,
+        });
+    }
+    Ok((disks, mounts))
+}
+
+fn collect_mounts(dev: &Value, mounts: &mut Vec<Mount>) {
+    for mountpoint in mounts::mountpoints(dev) {
+        let path = if mountpoint == TARGET {
+            "/".to_string()
+        } else if let Some(path) = mountpoint
+            .strip_prefix(TARGET)
+            .filter(|p| p.starts_with('/'))
+        {
+            path.to_string()
+        } else {
+            continue;
+        };
+        mounts.push(Mount {
+            device: dev["path"].as_str().unwrap_or_default().to_string(),
+            fstype: dev["fstype"].as_str().unwrap_or_default().to_string(),
+            path,
+        });
+    }
+    for child in dev["children"].as_array().into_iter().flatten() {
+        collect_mounts(child, mounts);
+    }
+}
+
+/// Everything wrong with a layout for the (resolved) boot mode
+pub fn check(boot_mode: BootMode, disks: &[Disk], mounts: &[Mount]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut error = |device: Option<&str>, message: String| {
+        problems.push(Problem {
+            severity: Severity::Error,
+            device: device.map(str::to_string),
+            message,
+        })
+    };
+    let partitions: Vec<&Partition> = disks.iter().flat_map(|disk| &disk.partitions).collect();
+    if partitions.is_empty() {
+        error(
+            None,
+            "The selected disks have no partitions; create them with cfdisk first".to_string(),
+        );
+        return problems;
+    }
+
+    let mut warnings = Vec::new();
+    let mut warning = |device: Option<&str>, message: String| {
+        warnings.push(Problem {
+            severity: Severity::Warning,
+            device: device.map(str::to_string),
+            message,
+        })
+    };
+
+    // Boot partitions
+    if boot_mode == BootMode::Uefi {
+        for disk in disks.iter().filter(|disk| disk.table == "dos") {
+            warning(
+                Some(&disk.path),
+                "has an MBR partition table; UEFI installs are expected on GPT".to_string(),
+            );
+        }
+        let esps: Vec<&&Partition> = partitions.iter().filter(|part| part.is_esp()).collect();
+        if esps.is_empty() {
+            error(
+                None,
+                "UEFI needs an EFI System Partition: type EF00 (\"EFI System\" in cfdisk), \
+                 formatted FAT32, 1 GiB recommended"
+                    .to_string(),
+            );
+        }
+        for esp in &esps {
+            let path = Some(esp.path.as_str());
+            if esp.size < MIN_ESP_SIZE {
+                error(
+                    path,
+                    format!(
+                        "EFI System Partition is {}; it needs at least {}",
+                        format_size(esp.size),
+                        format_size(MIN_ESP_SIZE)
+                    ),
+                );
+            }
+            match esp.fstype.as_str() {
+                "vfat" => {}
+                "" => warning(
+                    path,
+                    "EFI System Partition is not formatted yet; run mkfs.fat -F 32 on it"
+                        .to_string(),
+                ),
+                fstype => error(
+                    path,
+                    format!(
+                        "EFI System Partition is formatted {}; UEFI firmware only reads FAT \
+                         (mkfs.fat -F 32)",
+                        fstype
+                    ),
+                ),
+            }
+            let mount = mounts.iter().find(|mount| mount.device == esp.path);
+            match mount {
+                Some(mount) if !ESP_MOUNTPOINTS.contains(&mount.path.as_str()) => error(
+                    path,
+                    format!(
+                        "EFI System Partition is mounted at {}; mount it at {}/efi or {}/boot",
+                        mount.live_path(),
+                        TARGET,
+                        TARGET
+                    ),
+                ),
+                Some(mount) if mount.path == "/boot" && esp.size < SMALL_ESP_SIZE => warning(
+                    path,
+                    format!(
+                        "holds the kernels at /boot but is only {}; 1 GiB is recommended",
+                        format_size(esp.size)
+                    ),
+                ),
+                _ => {}
+            }
+        }
+        if !esps.is_empty()
+            && !ESP_MOUNTPOINTS.iter().any(|path| {
+                mounted_at(mounts, path)
+                    .any(|mount| esps.iter().any(|esp| esp.path == mount.device))
+            })
+        {
+            warning(
+                None,
+                format!(
+                    "No EFI System Partition is mounted yet; mount it at {}/efi (or {}/boot) before installing",
+                    TARGET, TARGET
+                ),
+            );
+        }
+        for path in ["/efi", "/boot/efi"] {
+            for mount in mounted_at(mounts, path) {
+                let is_esp = partitions
+                    .iter()
+                    .any(|part| part.path == mount.device && part.is_esp());
+                if !is_esp {
+                    error(
+                        Some(&mount.device),
+                        format!(
+                            "is mounted at {} but is not an EFI System Partition (type EF00)",
+                            mount.live_path()
+                        ),
+                    );
+                }
+            }
+        }
+    } else {
+        let bootable = disks.iter().any(|disk| {
+            disk.table == "dos"
+                || (disk.table == "gpt" && disk.partitions.iter().any(Partition::is_bios_boot))
+        });
+        if !bootable {
+            for disk in disks.iter().filter(|disk| disk.table == "gpt") {
+                error(
+                    Some(&disk.path),
+                    "BIOS boot from GPT needs a 1 MiB BIOS boot partition (type EF02, \
+                     \"BIOS boot\" in cfdisk) for GRUB"
+                        .to_string(),
+                );
+            }
+        }
+        for part in partitions.iter().filter(|part| part.is_bios_boot()) {
+            if !part.fstype.is_empty() {
+                error(
+                    Some(&part.path),
+                    format!(
+                        "BIOS boot partition is formatted {}; GRUB writes to it directly, \
+                         leave it unformatted",
+                        part.fstype
+                    ),
+                );
+            }
+        }
+    }
+
+    // Root
+    let roots: Vec<&Mount> = mounted_at(mounts, "/").collect();
+    match roots.first() {
+        None => {
+            let has_linux = partitions
+                .iter()
+                .any(|part| !part.is_esp() && !part.is_bios_boot() && part.fstype != "swap");
+            if has_linux {
+                warning(
+                    None,
+                    format!(
+                        "Nothing is mounted at {} yet; format the root partition and mount it there before installing",
+                        TARGET
+                    ),
+                );
+            } else {
+                error(
+                    None,
+                    "No root partition: create a Linux filesystem partition (type 8300) for /"
+                        .to_string(),
+                );
+            }
+            for mount in mounts {
+                error(
+                    Some(&mount.device),
+                    format!(
+                        "is mounted at {} while nothing is mounted at {}; mounting the root now would hide it",
+                        mount.live_path(),
+                        TARGET
+                    ),
+                );
+            }
+        }
+        Some(root) if !LINUX_FILESYSTEMS.contains(&root.fstype.as_str()) => error(
+            Some(&root.device),
+            format!(
+                "/ is formatted {}; use ext4, btrfs, xfs or f2fs",
+                if root.fstype.is_empty() {
+                    "with nothing readable"
+                } else {
+                    &root.fstype
+                }
+            ),
+        ),
+        Some(_) => {}
+    }
+
+    // Mountpoints
+    let mut seen: Vec<&str> = Vec::new();
+    for mount in mounts {
+        if seen.contains(&mount.path.as_str()) {
+            continue;
+        }
+        seen.push(&mount.path);
+        let devices: Vec<&str> = mounted_at(mounts, &mount.path)
+            .map(|mount| mount.device.as_str())
+            .collect();
+        if devices.len() > 1 {
+            error(
+                None,
+                format!(
+                    "{} are all mounted at {}; only the last one is visible",
+                    devices.join(", "),
+                    mount.live_path()
+                ),
+            );
+        }
+    }
+    for mount in mounts.iter().filter(|mount| mount.path != "/") {
+        let foreign = FOREIGN_FILESYSTEMS.contains(&mount.fstype.as_str());
+        let boot = mount.path == "/boot" || ESP_MOUNTPOINTS.contains(&mount.path.as_str());
+        if foreign && !(boot && mount.fstype == "vfat") {
+            error(
+                Some(&mount.device),
+                format!(
+                    "{} is formatted {}, which has no Linux permissions or symlinks",
+                    mount.path, mount.fstype
+                ),
+            );
+        }
+    }
+
+    problems.extend(warnings);
+    problems
+}
+
+fn mounted_at<'a>(mounts: &'a [Mount], path: &'a str) -> impl Iterator<Item = &'a Mount> {
+    mounts.iter().filter(move |mount| mount.path == path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn partition(path: &str, size: u64, part_type: &str, fstype: &str) -> Partition {
+        Partition {
+            path: path.to_string(),
+            size,
+            part_type: part_type.to_string(),
+            type_name: String::new(),
+            fstype: fstype.to_string(),
+            mountpoints: Vec::new(),
+        }
+    }
+
+    fn mount(device: &str, fstype: &str, path: &str) -> Mount {
+        Mount {
+            device: device.to_string(),
+            fstype: fstype.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    fn uefi_disk() -> Disk {
+        Disk {
+            path: "/dev/sda".to_string(),
+            table: "gpt".to_string(),
+            partitions: vec![
+                partition("/dev/sda1", 1024 * MIB, ESP_GUID, "vfat"),
+                partition(
+                    "/dev/sda2",
+                    40_000 * MIB,
+                    "0fc63daf-8483-4772-8e79-3d69d8477de4",
+                    "ext4",
+                ),
+            ],
+        }
+    }
+
+    fn messages(problems: &[Problem]) -> Vec<String> {
+        problems.iter().map(Problem::to_string).collect()
+    }
+
+    #[test]
+    fn test_complete_uefi_layout_passes() {
+        let mounts = [
+            mount("/dev/sda2", "ext4", "/"),
+            mount("/dev/sda1", "vfat", "/boot"),
+        ];
+        assert_eq!(check(BootMode::Uefi, &[uefi_disk()], &mounts), []);
+    }
+
+    #[test]
+    fn test_uefi_needs_a_usable_esp() {
+        let mut disk = uefi_disk();
+        disk.partitions.remove(0);
+        let problems = check(BootMode::Uefi, &[disk], &[mount("/dev/sda2", "ext4", "/")]);
+        assert!(messages(&problems)[0].starts_with("UEFI needs an EFI System Partition"));
+
+        let mut disk = uefi_disk();
+        disk.partitions[0].size = 16 * MIB;
+        disk.partitions[0].fstype = "ext4".to_string();
+        let mounts = [
+            mount("/dev/sda2", "ext4", "/"),
+            mount("/dev/sda1", "ext4", "/boot/loader"),
+        ];
+        let problems = messages(&check(BootMode::Uefi, &[disk], &mounts));
+        assert!(problems[0].contains("at least 32.0 MiB"), "{:?}", problems);
+        assert!(problems[1].contains("formatted ext4"));
+        assert!(problems[2].contains("mounted at /mnt/boot/loader"));
+    }
+
+    #[test]
+    fn test_unmounted_layout_is_only_warned_about() {
+        let problems = check(BootMode::Uefi, &[uefi_disk()], &[]);
+        assert!(problems
+            .iter()
+            .all(|problem| problem.severity == Severity::Warning));
+        assert_eq!(problems.len(), 2);
+        assert!(problems[1]
+            .message
+            .starts_with("Nothing is mounted at /mnt"));
+    }
+
+    #[test]
+    fn test_bios_gpt_needs_a_bios_boot_partition() {
+        let mut disk = uefi_disk();
+        disk.partitions.remove(0);
+        let mounts = [mount("/dev/sda2", "ext4", "/")];
+        let problems = check(BootMode::Bios, &[disk.clone()], &mounts);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].device.as_deref(), Some("/dev/sda"));
+        assert!(problems[0].message.contains("type EF02"));
+
+        disk.partitions
+            .push(partition("/dev/sda3", MIB, BIOS_BOOT_GUID, ""));
+        assert_eq!(check(BootMode::Bios, &[disk.clone()], &mounts), []);
+        disk.partitions[1].fstype = "ext4".to_string();
+        assert!(
+            messages(&check(BootMode::Bios, &[disk.clone()], &mounts))[0]
+                .contains("leave it unformatted")
+        );
+
+        // MBR disks boot from their boot sector
+        disk.table = "dos".to_string();
+        disk.partitions.truncate(1);
+        assert_eq!(check(BootMode::Bios, &[disk], &mounts), []);
+    }
+
+    #[test]
+    fn test_mountpoints_are_checked() {
+        let mounts = [
+            mount("/dev/sda2", "vfat", "/"),
+            mount("/dev/sda1", "vfat", "/efi"),
+            mount("/dev/sdb1", "ntfs3", "/home"),
+            mount("/dev/sdb2", "ext4", "/home"),
+        ];
+        let problems = messages(&check(BootMode::Uefi, &[uefi_disk()], &mounts));
+        assert_eq!(
+            problems,
+            [
+                "/dev/sda2: / is formatted vfat; use ext4, btrfs, xfs or f2fs",
+                "/dev/sdb1, /dev/sdb2 are all mounted at /mnt/home; only the last one is visible",
+                "/dev/sdb1: /home is formatted ntfs3, which has no Linux permissions or symlinks",
+            ]
+        );
+
+        // Mounting the root over what is already below /mnt hides it
+        let problems = check(
+            BootMode::Uefi,
+            &[uefi_disk()],
+            &[mount("/dev/sda1", "vfat", "/efi")],
+        );
+        assert!(messages(&problems)
+            .iter()
+            .any(|problem| problem.contains("mounting the root now would hide it")));
+    }
+
+    #[test]
+    fn test_parse_lsblk() {
+        let json = r#"{"blockdevices": [
+            {"path": "/dev/sda", "type": "disk", "size": 64424509440, "pttype": "gpt",
+             "parttype": null, "parttypename": null, "fstype": null, "mountpoints": [null],
+             "children": [
+                {"path": "/dev/sda1", "type": "part", "size": "1073741824", "pttype": "gpt",
+                 "parttype": "C12A7328-F81F-11D2-BA4B-00A0C93EC93B", "parttypename": "EFI System",
+                 "fstype": "vfat", "mountpoints": ["/mnt/efi"]},
+                {"path": "/dev/sda2", "type": "part", "size": 63350767616, "pttype": "gpt",
+                 "parttype": "ca7d7ccb-63ed-4c53-861c-1742536059cc", "parttypename": "Linux LUKS",
+                 "fstype": "crypto_LUKS", "mountpoints": [null],
+                 "children": [
+                    {"path": "/dev/mapper/root", "type": "crypt", "size": 63333990400,
+                     "fstype": "btrfs", "mountpoints": ["/mnt/home", "/mnt"]}
+                 ]}
+             ]},
+            {"path": "/dev/sdb", "type": "disk", "size": 16000000000, "pttype": "dos",
+             "fstype": null, "mountpoint": "/run/archiso/bootmnt"}
+        ]}"#;
+        let (disks, mounts) = parse_lsblk(json, &["/dev/sda".to_string()]).unwrap();
+        assert_eq!(disks.len(), 1);
+        assert_eq!(disks[0].table, "gpt");
+        assert!(disks[0].partitions[0].is_esp());
+        assert_eq!(disks[0].partitions[0].size, 1 << 30);
+        assert_eq!(disks[0].partitions[1].type_display(), "Linux LUKS");
+        assert_eq!(
+            mounts,
+            [
+                mount("/dev/sda1", "vfat", "/efi"),
+                mount("/dev/mapper/root", "btrfs", "/home"),
+                mount("/dev/mapper/root", "btrfs", "/"),
+            ]
+        );
+
+        let report = Report::new(BootMode::Uefi, disks, mounts);
+        assert!(!report.has_errors(), "{:?}", report.problems);
+        assert_eq!(report.mountpoint("/dev/sda1"), Some("/efi"));
+    }
+}
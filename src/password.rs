@@ -0,0 +1,258 @@
+//! Password strength estimation and policy checks
+//!
+//! Provides a lightweight, zxcvbn-style strength estimate used by the password
+//! dialog, plus the minimum-length policy shared by the TUI and config files.
+
+use strum::Display;
+
+/// Minimum password length when none is configured, in the TUI and in config files
+pub const DEFAULT_MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Passwords that are rejected as trivially guessable regardless of length
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "passw0rd",
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "qwerty",
+    "qwertyuiop",
+    "abc123",
+    "letmein",
+    "welcome",
+    "admin",
+    "root",
+    "toor",
+    "archlinux",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "sunshine",
+    "changeme",
+];
+
+/// Keyboard rows and alphabetic runs used to detect sequential patterns
+const SEQUENCES: &[&str] = &[
+    "abcdefghijklmnopqrstuvwxyz",
+    "0123456789",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+];
+
+/// Estimated password strength, ordered from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+pub enum PasswordStrength {
+    #[strum(serialize = "Very weak")]
+    VeryWeak,
+    #[strum(serialize = "Weak")]
+    Weak,
+    #[strum(serialize = "Fair")]
+    Fair,
+    #[strum(serialize = "Strong")]
+    Strong,
+    #[strum(serialize = "Very strong")]
+    VeryStrong,
+}
+
+impl PasswordStrength {
+    /// Score from 0 (very weak) to 4 (very strong)
+    pub fn score(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Estimate the strength of a password
+///
+/// Starts from the brute-force entropy of the character classes used and
+/// subtracts penalties for dictionary words, repeated characters and
+/// keyboard/alphabet sequences.
+pub fn estimate_strength(password: &str) -> PasswordStrength {
+    if password.is_empty() {
+        return PasswordStrength::VeryWeak;
+    }
+
+    let lower = password.to_lowercase();
+    if COMMON_PASSWORDS.contains(&lower.as_str()) {
+        return PasswordStrength::VeryWeak;
+    }
+
+    let mut pool = 0u32;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii_punctuation() || c == ' ')
+    {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+
+    let chars: Vec<char> = lower.chars().collect();
+    let mut effective_len = chars.len() as f64;
+
+    // Repeated characters ("aaaa") add almost nothing
+    let repeats = chars.windows(2).filter(|w| w[0] == w[1]).count();
+    effective_len -= repeats as f64 * 0.75;
+
+    // Sequential runs ("abcd", "4567", "asdf") add almost nothing
+    let sequential = chars
+        .windows(3)
+        .filter(|w| {
+            let run: String = w.iter().collect();
+            let reversed: String = w.iter().rev().collect();
+            SEQUENCES
+                .iter()
+                .any(|seq| seq.contains(&run) || seq.contains(&reversed))
+        })
+        .count();
+    effective_len -= sequential as f64 * 0.75;
+
+    // A common password with a few characters appended counts as one token
+    if let Some(common) = COMMON_PASSWORDS
+        .iter()
+        .filter(|common| common.len() >= 4 && lower.contains(*common))
+        .max_by_key(|common| common.len())
+    {
+        effective_len -= (common.len() - 1) as f64;
+    }
+
+    let bits = effective_len.max(0.0) * (pool.max(1) as f64).log2();
+    match bits {
+        b if b < 28.0 => PasswordStrength::VeryWeak,
+        b if b < 36.0 => PasswordStrength::Weak,
+        b if b < 60.0 => PasswordStrength::Fair,
+        b if b < 80.0 => PasswordStrength::Strong,
+        _ => PasswordStrength::VeryStrong,
+    }
+}
+
+/// Detect input that looks like it was typed with Caps Lock on
+///
+/// True when the password contains letters and every letter is uppercase.
+pub fn looks_like_caps_lock(password: &str) -> bool {
+    let mut letters = password.chars().filter(|c| c.is_alphabetic()).peekable();
+    letters.peek().is_some() && letters.all(|c| c.is_uppercase())
+}
+
+/// Password policy enforced before a password is accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    /// Minimum number of characters
+    pub min_length: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_PASSWORD_LENGTH,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Create a policy with the given minimum length
+    pub fn new(min_length: usize) -> Self {
+        Self { min_length }
+    }
+
+    /// Read a configured minimum length, a plain number of characters
+    pub fn parse(value: &str) -> Result<Self, String> {
+        value
+            .trim()
+            .parse()
+            .map(Self::new)
+            .map_err(|_| format!("Minimum password length must be a number, not '{}'", value))
+    }
+
+    /// Check a password against the policy
+    pub fn check(&self, password: &str) -> Result<(), String> {
+        let len = password.chars().count();
+        if len < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters (currently {})",
+                self.min_length, len
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_password_is_very_weak() {
+        assert_eq!(estimate_strength(""), PasswordStrength::VeryWeak);
+    }
+
+    #[test]
+    fn test_common_passwords_are_very_weak() {
+        assert_eq!(estimate_strength("password"), PasswordStrength::VeryWeak);
+        assert_eq!(estimate_strength("QWERTY"), PasswordStrength::VeryWeak);
+        assert_eq!(estimate_strength("archlinux"), PasswordStrength::VeryWeak);
+    }
+
+    #[test]
+    fn test_sequences_and_repeats_are_penalized() {
+        assert!(estimate_strength("abcdefgh") <= PasswordStrength::Weak);
+        assert!(estimate_strength("aaaaaaaaaa") <= PasswordStrength::Weak);
+        assert!(estimate_strength("password2024") <= PasswordStrength::Weak);
+    }
+
+    #[test]
+    fn test_strength_grows_with_length_and_variety() {
+        let short = estimate_strength("kx7q");
+        let medium = estimate_strength("kx7qPm2w");
+        let long = estimate_strength("kx7q-Pm2w!Tz9r#Vb");
+        assert!(short < medium);
+        assert!(medium < long);
+        assert_eq!(long, PasswordStrength::VeryStrong);
+    }
+
+    #[test]
+    fn test_score_range() {
+        assert_eq!(PasswordStrength::VeryWeak.score(), 0);
+        assert_eq!(PasswordStrength::VeryStrong.score(), 4);
+    }
+
+    #[test]
+    fn test_caps_lock_detection() {
+        assert!(looks_like_caps_lock("HUNTER2"));
+        assert!(!looks_like_caps_lock("Hunter2"));
+        assert!(!looks_like_caps_lock("12345"));
+        assert!(!looks_like_caps_lock(""));
+    }
+
+    #[test]
+    fn test_policy_min_length() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("short").is_err());
+        assert!(policy.check("longenough").is_ok());
+
+        let relaxed = PasswordPolicy::new(0);
+        assert!(relaxed.check("").is_ok());
+
+        assert_eq!(PasswordPolicy::parse(" 12 "), Ok(PasswordPolicy::new(12)));
+        assert!(PasswordPolicy::parse("twelve").is_err());
+        assert!(PasswordPolicy::parse("-1").is_err());
+    }
+
+    #[test]
+    fn test_policy_counts_characters_not_bytes() {
+        let policy = PasswordPolicy::new(4);
+        assert!(policy.check("äöü").is_err());
+        assert!(policy.check("äöüß").is_ok());
+    }
+}
@@ -0,0 +1,590 @@
+//! Custom installation phases
+//!
+//! Extra steps run against the installed system after the built-in phases,
+//! declared by name in the config file's `custom_phases` list. A phase is an
+//! [`InstallPhase`]; the [`PhaseRegistry`] maps names to phases and knows two
+//! sources:
+//!
+//! - built-in phases compiled into the binary (`ca-certificates`, and
+//!   `aur-helper`, see [`crate::aur_helper`])
+//! - executables in the plugin directory (`/etc/archinstall/phases`, or
+//!   `ARCHINSTALL_PHASES_DIR`), named after their file name
+//!
+//! # Plugin contract
+//!
+//! A plugin executable is started once per action with a JSON request on
+//! stdin:
+//!
+//! ```json
+//! {"phase": "corp-ca", "action": "run", "target_root": "/mnt"}
+//! ```
+//!
+//! `action` is `run`, or `rollback` when a later phase failed. The
+//! installation settings are in the environment (`INSTALL_DISK`, `USERNAME`,
+//! ...). Every line printed on stdout or stderr is logged; exit status 0
+//! means success.
+//!
+//! When a phase fails, it and every phase before it are rolled back in
+//! reverse order, so a half-applied customisation does not stay behind.
+
+use crate::error::ArchInstallError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Directory searched for plugin executables
+pub const DEFAULT_PLUGIN_DIR: &str = "/etc/archinstall/phases";
+
+/// Where certificates for the `ca-certificates` phase are taken from
+pub const DEFAULT_CA_SOURCE_DIR: &str = "/etc/archinstall/ca-certificates";
+
+/// Plugin directory, honouring `ARCHINSTALL_PHASES_DIR`
+pub fn plugin_dir() -> PathBuf {
+    std::env::var_os("ARCHINSTALL_PHASES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_PLUGIN_DIR))
+}
+
+/// Check a phase name: lowercase letters, digits, '-' and '_'
+pub fn validate_phase_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Phase name cannot be empty".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Phase name '{}' may only contain lowercase letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// What a phase runs against
+pub struct PhaseContext<'a> {
+    /// Root of the installed system, normally /mnt
+    pub target_root: PathBuf,
+    log: &'a mut dyn FnMut(String),
+}
+
+impl<'a> PhaseContext<'a> {
+    pub fn new(target_root: impl Into<PathBuf>, log: &'a mut dyn FnMut(String)) -> Self {
+        Self {
+            target_root: target_root.into(),
+            log,
+        }
+    }
+
+    /// Report progress
+    pub fn log(&mut self, line: impl Into<String>) {
+        (self.log)(line.into());
+    }
+}
+
+/// One custom step of the installation
+pub trait InstallPhase {
+    /// Name the phase is declared with in the config file
+    fn name(&self) -> &str;
+
+    /// Apply the phase to the target system
+    fn run(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError>;
+
+    /// Undo what [`run`](Self::run) did, also after it failed half-way
+    fn rollback(&mut self, _ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        Ok(())
+    }
+}
+
+/// Where a registered phase comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhaseSource {
+    Builtin,
+    Plugin(PathBuf),
+}
+
+impl fmt::Display for PhaseSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Builtin => write!(f, "built-in"),
+            Self::Plugin(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+type PhaseFactory = Box<dyn Fn() -> Box<dyn InstallPhase>>;
+
+/// Known phases by name
+#[derive(Default)]
+pub struct PhaseRegistry {
+    phases: BTreeMap<String, (PhaseSource, PhaseFactory)>,
+}
+
+impl PhaseRegistry {
+    /// Empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in phases and the plugins found in `dir`
+    pub fn load(dir: &Path) -> Self {
+        let mut registry = Self::new();
+        registry.register("ca-certificates", || {
+            Box::new(CaCertificatesPhase::new(DEFAULT_CA_SOURCE_DIR))
+        });
+        registry.register(crate::aur_helper::PHASE_NAME, || {
+            Box::new(crate::aur_helper::AurHelperPhase::new(
+                crate::aur_helper::helper_from_env(),
+            ))
+        });
+        registry.discover(dir);
+        registry
+    }
+
+    /// Add a built-in phase; replaces an earlier phase of the same name
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn() -> Box<dyn InstallPhase> + 'static,
+    {
+        self.phases
+            .insert(name.to_string(), (PhaseSource::Builtin, Box::new(factory)));
+    }
+
+    /// Register every executable in `dir` as a plugin phase
+    ///
+    /// Files whose names are not valid phase names, or that would shadow a
+    /// built-in phase, are skipped. A missing directory adds nothing.
+    pub fn discover(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if validate_phase_name(name).is_err() || !is_executable(&path) {
+                continue;
+            }
+            if matches!(self.phases.get(name), Some((PhaseSource::Builtin, _))) {
+                log::warn!(
+                    "Plugin {} shadows a built-in phase, ignored",
+                    path.display()
+                );
+                continue;
+            }
+
+            let (plugin_name, plugin_path) = (name.to_string(), path.clone());
+            self.phases.insert(
+                name.to_string(),
+                (
+                    PhaseSource::Plugin(path.clone()),
+                    Box::new(move || {
+                        Box::new(ExecutablePhase::new(&plugin_name, plugin_path.clone()))
+                    }),
+                ),
+            );
+            found.push(name.to_string());
+        }
+        found.sort();
+        found
+    }
+
+    /// Registered phase names and where they come from
+    pub fn list(&self) -> Vec<(&str, &PhaseSource)> {
+        self.phases
+            .iter()
+            .map(|(name, (source, _))| (name.as_str(), source))
+            .collect()
+    }
+
+    /// Instantiate the named phases in order
+    pub fn resolve(
+        &self,
+        names: &[String],
+    ) -> Result<Vec<Box<dyn InstallPhase>>, ArchInstallError> {
+        names
+            .iter()
+            .map(|name| {
+                self.phases
+                    .get(name)
+                    .map(|(_, factory)| factory())
+                    .ok_or_else(|| {
+                        let known: Vec<&str> = self.phases.keys().map(String::as_str).collect();
+                        ArchInstallError::validation(
+                            "phase",
+                            format!(
+                                "'{}' is not registered (available: {})",
+                                name,
+                                if known.is_empty() {
+                                    "none".to_string()
+                                } else {
+                                    known.join(", ")
+                                }
+                            ),
+                        )
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Run phases in order, rolling back on the first failure
+///
+/// The failed phase and all phases before it are rolled back in reverse
+/// order. Rollback errors are logged; the phase's own error is returned.
+pub fn run_phases(
+    phases: &mut [Box<dyn InstallPhase>],
+    ctx: &mut PhaseContext,
+) -> Result<(), ArchInstallError> {
+    for index in 0..phases.len() {
+        ctx.log(format!("==> Phase {}", phases[index].name()));
+        if let Err(e) = phases[index].run(ctx) {
+            ctx.log(format!("Phase {} failed: {}", phases[index].name(), e));
+            for phase in phases[..=index].iter_mut().rev() {
+                ctx.log(format!("<== Rolling back {}", phase.name()));
+                if let Err(rollback_error) = phase.rollback(ctx) {
+                    ctx.log(format!(
+                        "Rollback of {} failed: {}",
+                        phase.name(),
+                        rollback_error
+                    ));
+                }
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Request written to a plugin's stdin
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    phase: &'a str,
+    action: &'a str,
+    target_root: &'a Path,
+}
+
+/// Phase implemented by an executable following the plugin contract
+pub struct ExecutablePhase {
+    name: String,
+    path: PathBuf,
+}
+
+impl ExecutablePhase {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+        }
+    }
+
+    fn invoke(&self, action: &str, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        let request = serde_json::to_string(&PluginRequest {
+            phase: &self.name,
+            action,
+            target_root: &ctx.target_root,
+        })?;
+
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ArchInstallError::command_not_run(self.path.display().to_string(), e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin that ignores its input may close stdin early
+            let _ = stdin.write_all(request.as_bytes());
+            let _ = stdin.write_all(b"\n");
+        }
+
+        let output = child.wait_with_output()?;
+        for line in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+        {
+            ctx.log(line.to_string());
+        }
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(ArchInstallError::command_failed(
+                format!("{} {}", self.name, action),
+                &output,
+            ))
+        }
+    }
+}
+
+impl InstallPhase for ExecutablePhase {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        self.invoke("run", ctx)
+    }
+
+    fn rollback(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        self.invoke("rollback", ctx)
+    }
+}
+
+/// Built-in phase trusting extra certificate authorities on the new system
+///
+/// Copies `*.crt` and `*.pem` files from the source directory into the
+/// target's trust anchors and rebuilds the trust store with `update-ca-trust`.
+pub struct CaCertificatesPhase {
+    source_dir: PathBuf,
+    installed: Vec<PathBuf>,
+}
+
+impl CaCertificatesPhase {
+    pub fn new(source_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            source_dir: source_dir.into(),
+            installed: Vec::new(),
+        }
+    }
+
+    fn anchors_dir(target_root: &Path) -> PathBuf {
+        target_root.join("etc/ca-certificates/trust-source/anchors")
+    }
+
+    fn update_trust(ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        let output = Command::new("arch-chroot")
+            .arg(&ctx.target_root)
+            .arg("update-ca-trust")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| ArchInstallError::command_not_run("arch-chroot", e))?;
+        if !output.status.success() {
+            return Err(ArchInstallError::command_failed("update-ca-trust", &output));
+        }
+        Ok(())
+    }
+}
+
+impl InstallPhase for CaCertificatesPhase {
+    fn name(&self) -> &str {
+        "ca-certificates"
+    }
+
+    fn run(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        let mut certificates: Vec<PathBuf> = std::fs::read_dir(&self.source_dir)
+            .map_err(|e| {
+                ArchInstallError::system(format!(
+                    "Cannot read certificates from {}: {}",
+                    self.source_dir.display(),
+                    e
+                ))
+            })?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("crt" | "pem"))
+            })
+            .collect();
+        certificates.sort();
+        if certificates.is_empty() {
+            return Err(ArchInstallError::validation(
+                "certificate directory",
+                format!("No .crt or .pem files in {}", self.source_dir.display()),
+            ));
+        }
+
+        let anchors = Self::anchors_dir(&ctx.target_root);
+        std::fs::create_dir_all(&anchors)?;
+        for certificate in &certificates {
+            let Some(file_name) = certificate.file_name() else {
+                continue;
+            };
+            let destination = anchors.join(file_name);
+            std::fs::copy(certificate, &destination)?;
+            ctx.log(format!("Trusting {}", file_name.to_string_lossy()));
+            self.installed.push(destination);
+        }
+
+        Self::update_trust(ctx)
+    }
+
+    fn rollback(&mut self, ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+        if self.installed.is_empty() {
+            return Ok(());
+        }
+        for path in self.installed.drain(..) {
+            std::fs::remove_file(&path)?;
+        }
+        Self::update_trust(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Phase recording its calls into a shared journal
+    struct Recorder {
+        name: String,
+        fail: bool,
+        journal: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl InstallPhase for Recorder {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn run(&mut self, _ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+            self.journal.borrow_mut().push(format!("run {}", self.name));
+            if self.fail {
+                Err(ArchInstallError::system("boom"))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn rollback(&mut self, _ctx: &mut PhaseContext) -> Result<(), ArchInstallError> {
+            self.journal
+                .borrow_mut()
+                .push(format!("rollback {}", self.name));
+            Ok(())
+        }
+    }
+
+    fn registry_with(journal: &Rc<RefCell<Vec<String>>>, failing: &str) -> PhaseRegistry {
+        let mut registry = PhaseRegistry::new();
+        for name in ["first", "second", "third"] {
+            let journal = Rc::clone(journal);
+            let fail = name == failing;
+            registry.register(name, move || {
+                Box::new(Recorder {
+                    name: name.to_string(),
+                    fail,
+                    journal: Rc::clone(&journal),
+                })
+            });
+        }
+        registry
+    }
+
+    #[test]
+    fn test_validate_phase_name() {
+        assert!(validate_phase_name("corp-ca_2").is_ok());
+        assert!(validate_phase_name("").is_err());
+        assert!(validate_phase_name("Corp CA").is_err());
+        assert!(validate_phase_name("../evil").is_err());
+    }
+
+    #[test]
+    fn test_phases_run_in_declared_order() {
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let registry = registry_with(&journal, "");
+        let names = vec!["third".to_string(), "first".to_string()];
+        let mut phases = registry.resolve(&names).unwrap();
+
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new("/mnt", &mut log);
+        run_phases(&mut phases, &mut ctx).unwrap();
+        assert_eq!(*journal.borrow(), vec!["run third", "run first"]);
+    }
+
+    #[test]
+    fn test_failure_rolls_back_in_reverse() {
+        let journal = Rc::new(RefCell::new(Vec::new()));
+        let registry = registry_with(&journal, "second");
+        let names: Vec<String> = ["first", "second", "third"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut phases = registry.resolve(&names).unwrap();
+
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new("/mnt", &mut log);
+        assert!(run_phases(&mut phases, &mut ctx).is_err());
+        assert_eq!(
+            *journal.borrow(),
+            vec![
+                "run first",
+                "run second",
+                "rollback second",
+                "rollback first"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_phase_is_rejected() {
+        let registry = PhaseRegistry::load(Path::new("/nonexistent"));
+        let error = registry
+            .resolve(&["dotfiles".to_string()])
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("'dotfiles' is not registered"));
+        assert!(error.contains("ca-certificates"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_receives_json_request() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("corp-ca.sh");
+        std::fs::write(&plugin, "#!/bin/sh\ncat\n[ \"$1\" != fail ]\n").unwrap();
+        std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Not executable: ignored
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let mut registry = PhaseRegistry::new();
+        assert_eq!(registry.discover(dir.path()), vec!["corp-ca"]);
+        assert_eq!(
+            registry.list(),
+            vec![("corp-ca", &PhaseSource::Plugin(plugin.clone()))]
+        );
+
+        let mut phases = registry.resolve(&["corp-ca".to_string()]).unwrap();
+        let mut lines = Vec::new();
+        let mut log = |line: String| lines.push(line);
+        let mut ctx = PhaseContext::new("/mnt", &mut log);
+        run_phases(&mut phases, &mut ctx).unwrap();
+
+        assert!(lines
+            .contains(&r#"{"phase":"corp-ca","action":"run","target_root":"/mnt"}"#.to_string()));
+    }
+
+    #[test]
+    fn test_ca_certificates_phase_requires_certificates() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let mut phase = CaCertificatesPhase::new(source.path());
+
+        let mut log = |_line: String| {};
+        let mut ctx = PhaseContext::new(target.path(), &mut log);
+        let error = phase.run(&mut ctx).err().unwrap().to_string();
+        assert!(error.contains("No .crt or .pem files"));
+        assert!(phase.rollback(&mut ctx).is_ok());
+    }
+}
@@ -0,0 +1,137 @@
+//! Local package cache and offline repository (`--pkg-cache`)
+//!
+//! The Package Cache option names a directory on the live system, such as a
+//! USB stick, that pacman prefers over the mirrors. A directory holding a
+//! repository database made with `repo-add` (`NAME.db`) is an offline
+//! repository: it is listed before every other repository and installs need
+//! no internet. Any other directory of `.pkg.tar.*` files is a package cache
+//! that pacman checks before downloading. The directory reaches install.sh as
+//! `PKG_CACHE`, and packages found in neither are reported before pacstrap.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable carrying the directory to install.sh
+pub const ENV_VAR: &str = "PKG_CACHE";
+
+/// What a package directory holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSource {
+    /// Packages pacman checks before downloading
+    Cache { packages: usize },
+    /// A repository with its own database, usable without internet
+    Repository { name: String, packages: usize },
+}
+
+impl PackageSource {
+    /// One-line description for the status bar
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Cache { packages } => format!("package cache with {} packages", packages),
+            Self::Repository { name, packages } => {
+                format!("offline repository [{}] with {} packages", name, packages)
+            }
+        }
+    }
+}
+
+/// The directory the installer was started with, from `--pkg-cache` or the environment
+pub fn from_env() -> Option<PathBuf> {
+    env::var_os(ENV_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Check the spelling of a package directory; blank means none
+pub fn validate_path(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(());
+    }
+    if !value.starts_with('/') || value.contains(char::is_whitespace) {
+        return Err(format!(
+            "Package cache '{}' must be an absolute path without spaces",
+            value
+        ));
+    }
+    Ok(())
+}
+
+/// Look at what a package directory on this machine holds
+pub fn inspect(dir: &Path) -> Result<PackageSource, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read package cache {}: {}", dir.display(), e))?;
+    let mut repository = None;
+    let mut packages = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.contains(".pkg.tar") && !name.ends_with(".sig") {
+            packages += 1;
+        } else if let Some(repo) = name.strip_suffix(".db") {
+            repository = Some(repo.to_string());
+        }
+    }
+    if packages == 0 {
+        return Err(format!("{} holds no packages", dir.display()));
+    }
+    Ok(match repository {
+        Some(name) => PackageSource::Repository { name, packages },
+        None => PackageSource::Cache { packages },
+    })
+}
+
+/// Check a package directory named by the option or `--pkg-cache`
+pub fn validate(value: &str) -> Result<(), String> {
+    validate_path(value)?;
+    let value = value.trim();
+    if !value.is_empty() {
+        inspect(Path::new(value))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspects_caches_and_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(inspect(dir.path())
+            .unwrap_err()
+            .contains("holds no packages"));
+
+        for file in [
+            "base-3-2-any.pkg.tar.zst",
+            "base-3-2-any.pkg.tar.zst.sig",
+            "linux-6.9-1-x86_64.pkg.tar.zst",
+        ] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        assert_eq!(
+            inspect(dir.path()).unwrap(),
+            PackageSource::Cache { packages: 2 }
+        );
+
+        fs::write(dir.path().join("offline.db"), "").unwrap();
+        fs::write(dir.path().join("offline.db.tar.gz"), "").unwrap();
+        let source = inspect(dir.path()).unwrap();
+        assert_eq!(
+            source.describe(),
+            "offline repository [offline] with 2 packages"
+        );
+        assert!(validate(&dir.path().display().to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_validates_the_path() {
+        assert!(validate("").is_ok());
+        assert!(validate_path("/run/media/usb/pkg").is_ok());
+        assert!(validate_path("pkg").is_err());
+        assert!(validate_path("/run/media/my usb").is_err());
+        assert!(validate("/nonexistent/archinstall-cache")
+            .unwrap_err()
+            .contains("Cannot read"));
+    }
+}
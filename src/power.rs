@@ -0,0 +1,59 @@
+//! Laptop power management
+//!
+//! On a laptop (see [`crate::hardware::detect_laptop`]) the installer proposes
+//! a power manager: power-profiles-daemon for GNOME and KDE, whose power mode
+//! menus drive it, and TLP elsewhere. chroot_config.sh installs and enables
+//! it and writes the lid close action to a logind drop-in.
+
+use crate::types::{DesktopEnvironment, PowerManagement};
+
+/// Power manager proposed for a laptop running `desktop`
+pub fn proposed(desktop: DesktopEnvironment) -> PowerManagement {
+    match desktop {
+        DesktopEnvironment::Gnome | DesktopEnvironment::Kde => PowerManagement::PowerProfilesDaemon,
+        DesktopEnvironment::Xfce
+        | DesktopEnvironment::Cinnamon
+        | DesktopEnvironment::Mate
+        | DesktopEnvironment::Budgie
+        | DesktopEnvironment::Hyprland
+        | DesktopEnvironment::Sway
+        | DesktopEnvironment::I3
+        | DesktopEnvironment::None => PowerManagement::Tlp,
+    }
+}
+
+/// Check the power manager against the desktop environment
+///
+/// GNOME's power settings use power-profiles-daemon, which the tlp package
+/// conflicts with.
+pub fn validate(power: PowerManagement, desktop: DesktopEnvironment) -> Result<(), String> {
+    if power == PowerManagement::Tlp && desktop == DesktopEnvironment::Gnome {
+        return Err(
+            "TLP conflicts with power-profiles-daemon, which GNOME's power settings use: \
+             choose power-profiles-daemon or none"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposal_and_desktop_compatibility() {
+        assert_eq!(
+            proposed(DesktopEnvironment::Gnome),
+            PowerManagement::PowerProfilesDaemon
+        );
+        assert_eq!(proposed(DesktopEnvironment::Hyprland), PowerManagement::Tlp);
+        for desktop in [DesktopEnvironment::Gnome, DesktopEnvironment::Kde] {
+            assert!(validate(proposed(desktop), desktop).is_ok());
+        }
+
+        assert!(validate(PowerManagement::Tlp, DesktopEnvironment::Gnome).is_err());
+        assert!(validate(PowerManagement::Tlp, DesktopEnvironment::Kde).is_ok());
+        assert!(validate(PowerManagement::None, DesktopEnvironment::Gnome).is_ok());
+    }
+}
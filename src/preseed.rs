@@ -0,0 +1,185 @@
+//! Pre-seeded answers for the guided installer
+//!
+//! `install --config partial.json --interactive-missing` loads whatever a
+//! config file sets, which need not be a complete configuration, and opens
+//! the guided installer's wizard on the rest: it only stops at the options
+//! the file leaves out or sets to a value that does not pass validation. An
+//! organisation can standardise the disk layout, packages and services and
+//! still let each user pick a hostname, a username and passwords.
+//!
+//! The file is the usual JSON config, and every option whose field it omits
+//! is asked for. That includes the optional fields a complete file leaves out,
+//! like `http_proxy`: `null` or `[]` keeps them unset without asking. A field
+//! whose value cannot be read at all, like an unknown kernel, is dropped with
+//! a warning and asked for like a missing one.
+
+use crate::config::options::{self, OptionSpec};
+use crate::config::Configuration;
+use crate::config_file::InstallationConfig;
+use crate::error::ArchInstallError;
+use crate::facts::{self, Facts};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Config file fields not named after the variable of their option
+const FIELDS: &[(&str, &str)] = &[
+    ("Hostname", "hostname"),
+    ("Username", "username"),
+    ("User Password", "user_password"),
+    ("Root Password", "root_password"),
+    ("Package Cache", "package_cache"),
+    ("GRUB Theme", "grub_themes"),
+];
+
+/// Answers read from a partial config file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Preseed {
+    /// Option name and value of every option the file sets
+    pub values: Vec<(String, String)>,
+    /// Why fields of the file were ignored
+    pub warnings: Vec<String>,
+}
+
+/// Config file field an option is read from
+pub fn field(spec: &OptionSpec) -> String {
+    FIELDS
+        .iter()
+        .find(|(name, _)| *name == spec.name)
+        .map(|(_, field)| field.to_string())
+        .unwrap_or_else(|| spec.key.unwrap_or_default().to_lowercase())
+}
+
+/// Read the answers of a partial config file
+///
+/// Template variables are resolved like in a complete file.
+pub fn load_preseed(path: &Path) -> Result<Preseed, ArchInstallError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        ArchInstallError::config(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let content = if facts::is_template(&content) {
+        InstallationConfig::render_template(&content, &Facts::gather())
+            .map_err(|e| ArchInstallError::config(e.to_string()))?
+    } else {
+        content
+    };
+    parse_preseed(&content)
+}
+
+/// Read the answers of the JSON of a partial config file
+pub fn parse_preseed(json: &str) -> Result<Preseed, ArchInstallError> {
+    let file: Map<String, Value> = serde_json::from_str(json)
+        .map_err(|e| ArchInstallError::config(format!("Invalid configuration JSON: {}", e)))?;
+    let defaults = match serde_json::to_value(InstallationConfig::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return Err(ArchInstallError::config("Cannot serialize the defaults")),
+    };
+
+    // Fields are merged one by one so a bad one does not hide the others
+    let mut merged = defaults.clone();
+    let mut warnings = Vec::new();
+    for (key, value) in &file {
+        let mut single = defaults.clone();
+        single.insert(key.clone(), value.clone());
+        match serde_json::from_value::<InstallationConfig>(Value::Object(single)) {
+            Ok(_) => {
+                merged.insert(key.clone(), value.clone());
+            }
+            Err(e) => warnings.push(format!("Ignoring {}: {}", key, e)),
+        }
+    }
+    let config: InstallationConfig = serde_json::from_value(Value::Object(merged.clone()))
+        .map_err(|e| ArchInstallError::config(format!("Invalid configuration: {}", e)))?;
+
+    let values = config
+        .option_values()
+        .into_iter()
+        .filter(|(name, _)| {
+            options::spec(name).is_some_and(|spec| {
+                let field = field(spec);
+                file.contains_key(&field) && merged.get(&field) == file.get(&field)
+            })
+        })
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    Ok(Preseed { values, warnings })
+}
+
+/// Set the pre-seeded values in a configuration, returning the options set
+pub fn apply_preseed(config: &mut Configuration, preseed: &Preseed) -> Vec<String> {
+    preseed
+        .values
+        .iter()
+        .filter_map(|(name, value)| {
+            let option = config.options.iter_mut().find(|o| &o.name == name)?;
+            option.value = value.clone();
+            Some(name.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value<'a>(preseed: &'a Preseed, name: &str) -> Option<&'a str> {
+        preseed
+            .values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_fields_follow_the_option_variables() {
+        assert_eq!(field(options::spec("Kernel").unwrap()), "kernel");
+        assert_eq!(field(options::spec("Disk").unwrap()), "install_disk");
+        assert_eq!(field(options::spec("Hostname").unwrap()), "hostname");
+        assert_eq!(
+            field(options::spec("Root Password").unwrap()),
+            "root_password"
+        );
+    }
+
+    #[test]
+    fn test_only_the_fields_of_the_file_are_preseeded() {
+        let preseed = parse_preseed(
+            r#"{"kernel": "LinuxLts", "root_filesystem": "Ext4", "hostname": "desk-01"}"#,
+        )
+        .unwrap();
+        assert_eq!(value(&preseed, "Kernel"), Some("linux-lts"));
+        assert_eq!(value(&preseed, "Root Filesystem"), Some("ext4"));
+        assert_eq!(value(&preseed, "Hostname"), Some("desk-01"));
+        assert_eq!(value(&preseed, "Username"), None);
+        assert_eq!(value(&preseed, "Bootloader"), None);
+        assert_eq!(preseed.values.len(), 3);
+        assert!(preseed.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_fields_are_left_to_the_user() {
+        let preseed =
+            parse_preseed(r#"{"kernel": "LinuxFoo", "username": "alice", "unknown": 1}"#).unwrap();
+        assert_eq!(value(&preseed, "Kernel"), None);
+        assert_eq!(value(&preseed, "Username"), Some("alice"));
+        assert_eq!(preseed.warnings.len(), 1);
+        assert!(preseed.warnings[0].contains("kernel"));
+
+        assert!(parse_preseed("[]").is_err());
+        assert!(parse_preseed("{").is_err());
+    }
+
+    #[test]
+    fn test_apply_sets_values() {
+        let mut config = Configuration::default();
+        let preseed = Preseed {
+            values: vec![
+                ("Kernel".to_string(), "linux-zen".to_string()),
+                ("Nonexistent".to_string(), "x".to_string()),
+            ],
+            warnings: Vec::new(),
+        };
+        assert_eq!(apply_preseed(&mut config, &preseed), vec!["Kernel"]);
+        let kernel = config.options.iter().find(|o| o.name == "Kernel").unwrap();
+        assert_eq!(kernel.value, "linux-zen");
+    }
+}
@@ -0,0 +1,143 @@
+//! Running without root privileges
+//!
+//! The TUI starts for any user: the guided installer can be configured and
+//! saved, and the read-only tools work. Tools that change disks or the
+//! installed system are marked in the menus; choosing one offers to restart
+//! the TUI through sudo or pkexec with that tool open, instead of letting it
+//! fail halfway with permission errors.
+
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Programs that can run the TUI as root, in order of preference
+const LAUNCHERS: &[&str] = &["sudo", "pkexec"];
+
+/// Tools that only read the system, so they are offered without root
+const UNPRIVILEGED_TOOLS: &[&str] = &[
+    "Check Disk Health",
+    "System Info",
+    "Test Connectivity",
+    "Network Info",
+];
+
+/// Whether the process runs with root privileges (effective UID 0)
+pub fn is_root() -> bool {
+    nix::unistd::geteuid().is_root()
+}
+
+/// Whether the tools menu entry `tool` needs root privileges
+pub fn requires_root(tool: &str) -> bool {
+    !UNPRIVILEGED_TOOLS.contains(&tool)
+}
+
+/// The first of sudo and pkexec found on $PATH
+pub fn launcher() -> Option<&'static str> {
+    let path = std::env::var_os("PATH")?;
+    launcher_in(&path)
+}
+
+fn launcher_in(path: &std::ffi::OsStr) -> Option<&'static str> {
+    LAUNCHERS
+        .iter()
+        .copied()
+        .find(|name| std::env::split_paths(path).any(|dir| is_executable(&dir.join(name))))
+}
+
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Arguments that restart this program with `tool` open
+///
+/// `args` are the original arguments without the program name; a previous
+/// `--open-tool` is replaced. The program path comes first because pkexec
+/// needs an absolute path.
+fn restart_args(program: PathBuf, args: &[OsString], tool: &str) -> Vec<OsString> {
+    let mut restart = vec![program.into_os_string()];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--open-tool" {
+            args.next();
+        } else if !arg.to_string_lossy().starts_with("--open-tool=") {
+            restart.push(arg.clone());
+        }
+    }
+    restart.push("--open-tool".into());
+    restart.push(tool.into());
+    restart
+}
+
+/// Replace this process with the TUI running as root through `launcher`
+///
+/// Only returns if the launcher could not be started. Call after the
+/// terminal has been restored.
+pub fn restart_as_root(launcher: &str, tool: &str) -> std::io::Error {
+    let program = match std::env::current_exe() {
+        Ok(program) => program,
+        Err(e) => return e,
+    };
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    log::info!("Restarting through {} to run {}", launcher, tool);
+    Command::new(launcher)
+        .args(restart_args(program, &args, tool))
+        .exec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_tools_run_unprivileged() {
+        assert!(!requires_root("System Info"));
+        assert!(!requires_root("Check Disk Health"));
+        assert!(requires_root("Wipe Disk"));
+        assert!(requires_root("Chroot into System"));
+    }
+
+    #[test]
+    fn test_launcher_search() {
+        let dir = std::env::temp_dir().join(format!("archinstall-launcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pkexec = dir.join("pkexec");
+        std::fs::write(&pkexec, "#!/bin/sh\n").unwrap();
+        assert_eq!(launcher_in(dir.as_os_str()), None);
+
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&pkexec, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(launcher_in(dir.as_os_str()), Some("pkexec"));
+
+        std::fs::copy(&pkexec, dir.join("sudo")).unwrap();
+        assert_eq!(launcher_in(dir.as_os_str()), Some("sudo"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restart_args_open_the_tool() {
+        let args: Vec<OsString> = ["--ascii", "--open-tool", "Wipe Disk", "--open-tool=x"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        let restart = restart_args(
+            PathBuf::from("/usr/bin/archinstall-tui"),
+            &args,
+            "Format Partition",
+        );
+        assert_eq!(
+            restart,
+            [
+                "/usr/bin/archinstall-tui",
+                "--ascii",
+                "--open-tool",
+                "Format Partition"
+            ]
+            .iter()
+            .map(OsString::from)
+            .collect::<Vec<_>>()
+        );
+    }
+}
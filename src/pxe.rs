@@ -0,0 +1,187 @@
+//! Zero-touch network installs
+//!
+//! `generate pxe` turns a config file into what a PXE server needs to install
+//! machines with nobody at the console: the config file to serve over HTTP,
+//! the kernel command line that boots the Arch ISO from an HTTP server with
+//! `archinstall.config=<url>` added, and an iPXE script booting it.
+//!
+//! When the installer starts without a command on a system booted with that
+//! parameter, it downloads the config and runs an unattended installation
+//! instead of opening the TUI. The installed system does not get the
+//! parameter, so it never installs itself again.
+
+use crate::notify;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// Kernel parameter holding the URL of the config file
+pub const CONFIG_PARAM: &str = "archinstall.config";
+
+/// Extracted image of the latest Arch ISO on the mirrors
+pub const DEFAULT_ISO_URL: &str = "https://geo.mirror.pkgbuild.com/iso/latest";
+
+/// Where a config named on the kernel command line is downloaded to
+pub const BOOT_CONFIG_PATH: &str = "/run/archinstall-tui/pxe-config.json";
+
+/// Seconds the config download may take, retries included
+const DOWNLOAD_TIMEOUT_SECS: u32 = 120;
+
+/// Files written by `generate pxe`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PxeArtifacts {
+    /// Kernel command line of the Arch ISO, for PXELINUX, GRUB or iPXE
+    pub cmdline: String,
+    /// iPXE script fetching the kernel and initramfs from the ISO URL
+    pub ipxe: String,
+}
+
+/// Check an URL the target machines download from
+pub fn validate_url(name: &str, url: &str) -> Result<(), String> {
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("{} cannot contain whitespace", name));
+    }
+    if notify::host(url).is_none() {
+        return Err(format!(
+            "{} '{}' must be an http:// or https:// URL with a host",
+            name, url
+        ));
+    }
+    Ok(())
+}
+
+/// Kernel command line booting the ISO at `iso_url` and installing with the
+/// config at `config_url`
+pub fn kernel_cmdline(iso_url: &str, config_url: &str) -> String {
+    format!(
+        "archisobasedir=arch archiso_http_srv={}/ ip=dhcp {}={}",
+        iso_url.trim_end_matches('/'),
+        CONFIG_PARAM,
+        config_url
+    )
+}
+
+/// Build the artifacts for the ISO at `iso_url` and the config at `config_url`
+pub fn generate(iso_url: &str, config_url: &str) -> Result<PxeArtifacts, String> {
+    validate_url("ISO URL", iso_url)?;
+    validate_url("Config URL", config_url)?;
+    let iso_url = iso_url.trim_end_matches('/');
+    let cmdline = kernel_cmdline(iso_url, config_url);
+    let ipxe = format!(
+        "#!ipxe\n\
+         # Boots the Arch ISO over HTTP; archinstall-tui installs with\n\
+         # {config_url}\n\
+         dhcp\n\
+         kernel {iso_url}/arch/boot/x86_64/vmlinuz-linux initrd=initramfs-linux.img {cmdline}\n\
+         initrd {iso_url}/arch/boot/x86_64/initramfs-linux.img\n\
+         boot\n"
+    );
+    Ok(PxeArtifacts {
+        cmdline: format!("{}\n", cmdline),
+        ipxe,
+    })
+}
+
+/// URL of the config named on a kernel command line
+pub fn config_url(cmdline: &str) -> Option<&str> {
+    cmdline
+        .split_whitespace()
+        .filter_map(|param| param.strip_prefix(CONFIG_PARAM)?.strip_prefix('='))
+        .next_back()
+        .filter(|url| !url.is_empty())
+}
+
+/// URL of the config named on the running kernel's command line
+pub fn boot_config_url() -> Option<String> {
+    let cmdline = fs::read_to_string("/proc/cmdline").ok()?;
+    config_url(&cmdline).map(str::to_string)
+}
+
+/// Download the config at `url` to `dest`, readable by root only
+///
+/// The network may still be coming up this early in the boot, so curl
+/// retries refused connections too.
+pub fn download_config(url: &str, dest: &Path) -> Result<(), String> {
+    validate_url("Config URL", url)?;
+    let output = Command::new("curl")
+        .args([
+            "-fsSL",
+            "--retry",
+            "10",
+            "--retry-connrefused",
+            "--max-time",
+        ])
+        .arg(DOWNLOAD_TIMEOUT_SECS.to_string())
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    if let Some(dir) = dest.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    // The config holds the passwords in clear text
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(dest)
+        .and_then(|mut file| file.write_all(&output.stdout))
+        .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_url_from_cmdline() {
+        let cmdline = "BOOT_IMAGE=/arch/boot/x86_64/vmlinuz-linux archisobasedir=arch \
+                       ip=dhcp archinstall.config=http://10.0.0.1/fleet.json quiet\n";
+        assert_eq!(config_url(cmdline), Some("http://10.0.0.1/fleet.json"));
+        assert_eq!(config_url("archisobasedir=arch quiet"), None);
+        assert_eq!(config_url("archinstall.config= quiet"), None);
+        assert_eq!(config_url("archinstall.configx=http://a/b"), None);
+        // The last one wins, like the kernel's own parameters
+        assert_eq!(
+            config_url("archinstall.config=http://a/1 archinstall.config=http://a/2"),
+            Some("http://a/2")
+        );
+    }
+
+    #[test]
+    fn test_generate_pxe_artifacts() {
+        let artifacts = generate("http://pxe.lan/arch/", "http://pxe.lan/fleet.json").unwrap();
+        assert_eq!(
+            artifacts.cmdline,
+            "archisobasedir=arch archiso_http_srv=http://pxe.lan/arch/ ip=dhcp \
+             archinstall.config=http://pxe.lan/fleet.json\n"
+        );
+        assert!(artifacts.ipxe.starts_with("#!ipxe\n"));
+        assert!(artifacts
+            .ipxe
+            .contains("kernel http://pxe.lan/arch/arch/boot/x86_64/vmlinuz-linux "));
+        assert!(artifacts
+            .ipxe
+            .contains("initrd http://pxe.lan/arch/arch/boot/x86_64/initramfs-linux.img\n"));
+        assert!(artifacts.ipxe.contains(artifacts.cmdline.trim_end()));
+        assert!(artifacts.ipxe.ends_with("boot\n"));
+    }
+
+    #[test]
+    fn test_generate_rejects_bad_urls() {
+        assert!(generate(DEFAULT_ISO_URL, "fleet.json").is_err());
+        assert!(generate("ftp://mirror/iso", "http://pxe.lan/fleet.json").is_err());
+        assert!(generate(DEFAULT_ISO_URL, "http://pxe.lan/my fleet.json").is_err());
+    }
+}
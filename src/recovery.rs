@@ -0,0 +1,232 @@
+//! Recovery from a failed installation phase
+//!
+//! The TUI runs install.sh with `ARCHINSTALL_ON_ERROR=ask`. When a phase
+//! fails, `run_phase` prints an [`MARKER`] line naming the phase and waits
+//! for a decision in the file named by `ARCHINSTALL_RECOVERY_FILE`:
+//!
+//! ```text
+//! ARCHINSTALL_RECOVERY: optional Chroot configuration
+//! ```
+//!
+//! The recovery dialog writes `retry`, `skip` or `abort` there. Opening a
+//! shell and exporting the log happen in the TUI while the script keeps
+//! waiting. Critical phases cannot be skipped, and aborting exits the script
+//! through its cleanup trap, which unmounts the target and closes LUKS, LVM
+//! and RAID devices.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Start of the line install.sh prints when a phase fails
+pub const MARKER: &str = "ARCHINSTALL_RECOVERY:";
+
+/// Name of the recovery shell in the embedded terminal
+pub const SHELL_NAME: &str = "Recovery shell";
+
+/// Shell started by [`RecoveryChoice::Shell`], in the target if it is mounted
+pub const SHELL_COMMAND: &str = "cd /mnt 2>/dev/null; exec bash -i";
+
+/// A phase install.sh could not complete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseFailure {
+    /// Description of the phase, e.g. "Chroot configuration"
+    pub phase: String,
+    /// Whether the installation cannot go on without the phase
+    pub critical: bool,
+}
+
+impl PhaseFailure {
+    /// Parse an `ARCHINSTALL_RECOVERY: critical|optional <phase>` line
+    pub fn from_output_line(line: &str) -> Option<Self> {
+        let rest = line.trim().strip_prefix(MARKER)?.trim_start();
+        let (criticality, phase) = rest.split_once(' ')?;
+        let critical = match criticality {
+            "critical" => true,
+            "optional" => false,
+            _ => return None,
+        };
+        Some(Self {
+            phase: phase.trim().to_string(),
+            critical,
+        })
+    }
+}
+
+/// What the user can do about a failed phase, in dialog order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum RecoveryChoice {
+    /// Run the phase again
+    Retry,
+    /// Record the failure and go on with the next phase
+    Skip,
+    /// Open a shell at /mnt to fix the problem by hand
+    Shell,
+    /// Save the installer output to a file
+    ExportLogs,
+    /// Stop the installation and clean up
+    Abort,
+}
+
+impl RecoveryChoice {
+    /// All choices, in dialog order
+    pub fn all() -> Vec<Self> {
+        Self::iter().collect()
+    }
+
+    /// Shortcut key
+    pub fn key(self) -> char {
+        match self {
+            Self::Retry => 'r',
+            Self::Skip => 's',
+            Self::Shell => 't',
+            Self::ExportLogs => 'e',
+            Self::Abort => 'a',
+        }
+    }
+
+    /// The choice a shortcut key stands for
+    pub fn from_key(key: char) -> Option<Self> {
+        Self::iter().find(|choice| choice.key() == key.to_ascii_lowercase())
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Retry => "Retry the phase",
+            Self::Skip => "Skip the phase",
+            Self::Shell => "Open a shell at /mnt",
+            Self::ExportLogs => "Export the installer log",
+            Self::Abort => "Abort and clean up",
+        }
+    }
+
+    /// Decision written for install.sh; `None` for choices the TUI handles
+    pub fn answer(self) -> Option<&'static str> {
+        match self {
+            Self::Retry => Some("retry"),
+            Self::Skip => Some("skip"),
+            Self::Abort => Some("abort"),
+            Self::Shell | Self::ExportLogs => None,
+        }
+    }
+
+    /// Whether the choice is offered for this failure
+    pub fn is_available(self, failure: &PhaseFailure) -> bool {
+        !(self == Self::Skip && failure.critical)
+    }
+}
+
+/// File install.sh waits on for the decision
+///
+/// It lives in a directory only this user can write to: the runtime
+/// directory when the session has one, else a fresh mkdtemp directory, so
+/// nobody can plant the file or a link in its place.
+pub fn decision_file() -> io::Result<PathBuf> {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    let dir = match DIR.get() {
+        Some(dir) => dir,
+        None => {
+            let dir = private_dir()?;
+            DIR.get_or_init(|| dir)
+        }
+    };
+    Ok(dir.join(format!("archinstall-recovery-{}", std::process::id())))
+}
+
+/// `$XDG_RUNTIME_DIR`, or a new directory in the temporary directory
+fn private_dir() -> io::Result<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => nix::unistd::mkdtemp(&std::env::temp_dir().join("archinstall-recovery-XXXXXX"))
+            .map_err(io::Error::from),
+    }
+}
+
+/// Hand a decision to install.sh
+///
+/// Written to a temporary file and renamed, so the script never reads half
+/// a word.
+pub fn answer(path: &Path, choice: RecoveryChoice) -> io::Result<()> {
+    let Some(answer) = choice.answer() else {
+        return Ok(());
+    };
+    let partial = path.with_extension("partial");
+    fs::write(&partial, format!("{}\n", answer))?;
+    fs::rename(&partial, path)
+}
+
+/// Save installer output to `dir`, returning the file written
+pub fn export_logs<'a>(
+    dir: &Path,
+    lines: impl IntoIterator<Item = &'a str>,
+) -> io::Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let path = dir.join(format!("archinstall-failure-{}.log", stamp));
+    let mut content = String::new();
+    for line in lines {
+        content.push_str(line);
+        content.push('\n');
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_the_failure_marker() {
+        assert_eq!(
+            PhaseFailure::from_output_line("ARCHINSTALL_RECOVERY: optional Chroot configuration"),
+            Some(PhaseFailure {
+                phase: "Chroot configuration".to_string(),
+                critical: false,
+            })
+        );
+        let failure =
+            PhaseFailure::from_output_line("ARCHINSTALL_RECOVERY: critical Disk partitioning")
+                .unwrap();
+        assert!(failure.critical);
+        assert!(!RecoveryChoice::Skip.is_available(&failure));
+        assert!(RecoveryChoice::Retry.is_available(&failure));
+        assert_eq!(
+            PhaseFailure::from_output_line("ARCHINSTALL_RECOVERY: maybe Disk"),
+            None
+        );
+        assert_eq!(
+            PhaseFailure::from_output_line("Phase 4: Partitioning disk..."),
+            None
+        );
+    }
+
+    #[test]
+    fn test_answers_and_exports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recovery");
+        answer(&path, RecoveryChoice::Retry).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "retry\n");
+        fs::remove_file(&path).unwrap();
+        answer(&path, RecoveryChoice::Shell).unwrap();
+        assert!(!path.exists());
+
+        let log = export_logs(&dir.path().join("logs"), ["one", "two"]).unwrap();
+        assert_eq!(fs::read_to_string(log).unwrap(), "one\ntwo\n");
+
+        assert_eq!(RecoveryChoice::from_key('T'), Some(RecoveryChoice::Shell));
+        assert_eq!(RecoveryChoice::from_key('x'), None);
+    }
+
+    #[test]
+    fn test_decision_file_is_not_predictable() {
+        let path = decision_file().unwrap();
+        assert_eq!(decision_file().unwrap(), path);
+        assert_ne!(path.parent(), Some(std::env::temp_dir().as_path()));
+    }
+}
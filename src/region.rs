@@ -0,0 +1,186 @@
+//! Region quick setup
+//!
+//! Most people want the locale, keyboard, timezone and mirrors of the
+//! country they are in. Picking that country in the Region option fills in
+//! all of them from [`REGIONS`]; each stays an ordinary option that can be
+//! changed afterwards, and the wizard no longer stops at them.
+
+/// Settings proposed for a country
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Country name, the value of the Region option
+    pub country: &'static str,
+    pub locale: &'static str,
+    /// Console keymap
+    pub keymap: &'static str,
+    /// Timezone region and city
+    pub timezone: (&'static str, &'static str),
+    /// Country reflector ranks mirrors for
+    pub mirror_country: &'static str,
+}
+
+/// Options filled in by the Region option
+pub const FILLS: &[&str] = &[
+    "Locale",
+    "Keymap",
+    "Timezone Region",
+    "Timezone",
+    "Mirror Country",
+];
+
+const fn region(
+    country: &'static str,
+    locale: &'static str,
+    keymap: &'static str,
+    timezone: (&'static str, &'static str),
+) -> Region {
+    Region {
+        country,
+        locale,
+        keymap,
+        timezone,
+        mirror_country: country,
+    }
+}
+
+/// Countries offered, in display order
+pub const REGIONS: &[Region] = &[
+    region("Australia", "en_AU.UTF-8", "us", ("Australia", "Sydney")),
+    region("Austria", "de_AT.UTF-8", "de", ("Europe", "Vienna")),
+    region(
+        "Belgium",
+        "nl_BE.UTF-8",
+        "be-latin1",
+        ("Europe", "Brussels"),
+    ),
+    region(
+        "Brazil",
+        "pt_BR.UTF-8",
+        "br-abnt2",
+        ("America", "Sao_Paulo"),
+    ),
+    region("Canada", "en_CA.UTF-8", "us", ("America", "Toronto")),
+    region("China", "zh_CN.UTF-8", "us", ("Asia", "Shanghai")),
+    region("Denmark", "da_DK.UTF-8", "dk", ("Europe", "Copenhagen")),
+    region("Finland", "fi_FI.UTF-8", "fi", ("Europe", "Helsinki")),
+    region("France", "fr_FR.UTF-8", "fr", ("Europe", "Paris")),
+    region("Germany", "de_DE.UTF-8", "de", ("Europe", "Berlin")),
+    region("India", "en_IN.UTF-8", "us", ("Asia", "Kolkata")),
+    region("Ireland", "en_IE.UTF-8", "uk", ("Europe", "Dublin")),
+    region("Italy", "it_IT.UTF-8", "it", ("Europe", "Rome")),
+    region("Japan", "ja_JP.UTF-8", "jp", ("Asia", "Tokyo")),
+    region("Korea", "ko_KR.UTF-8", "us", ("Asia", "Seoul")),
+    region("Netherlands", "nl_NL.UTF-8", "us", ("Europe", "Amsterdam")),
+    region("New Zealand", "en_NZ.UTF-8", "us", ("Pacific", "Auckland")),
+    region("Norway", "nb_NO.UTF-8", "no", ("Europe", "Oslo")),
+    region("Poland", "pl_PL.UTF-8", "pl", ("Europe", "Warsaw")),
+    region("Portugal", "pt_PT.UTF-8", "pt", ("Europe", "Lisbon")),
+    region("Russia", "ru_RU.UTF-8", "ru", ("Europe", "Moscow")),
+    region("Spain", "es_ES.UTF-8", "es", ("Europe", "Madrid")),
+    region(
+        "Sweden",
+        "sv_SE.UTF-8",
+        "sv-latin1",
+        ("Europe", "Stockholm"),
+    ),
+    region(
+        "Switzerland",
+        "de_CH.UTF-8",
+        "de_CH-latin1",
+        ("Europe", "Zurich"),
+    ),
+    region("United Kingdom", "en_GB.UTF-8", "uk", ("Europe", "London")),
+    region(
+        "United States",
+        "en_US.UTF-8",
+        "us",
+        ("America", "New_York"),
+    ),
+];
+
+/// Look up a region by its country name
+pub fn find_region(country: &str) -> Option<&'static Region> {
+    REGIONS.iter().find(|region| region.country == country)
+}
+
+/// Countries offered by the Region option
+pub fn countries() -> Vec<String> {
+    REGIONS
+        .iter()
+        .map(|region| region.country.to_string())
+        .collect()
+}
+
+/// Check a value of the Region option; empty when none was picked
+pub fn validate(country: &str) -> Result<(), String> {
+    if country.is_empty() || find_region(country).is_some() {
+        Ok(())
+    } else {
+        Err(format!("No region setup for '{}'", country))
+    }
+}
+
+impl Region {
+    /// Value of each option in [`FILLS`], in the same order
+    pub fn values(&self) -> [&'static str; 5] {
+        [
+            self.locale,
+            self.keymap,
+            self.timezone.0,
+            self.timezone.1,
+            self.mirror_country,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::options;
+    use crate::input::InputHandler;
+
+    #[test]
+    fn test_regions_only_propose_offered_values() {
+        for region in REGIONS {
+            assert!(
+                options::LOCALES.contains(&region.locale),
+                "{}",
+                region.country
+            );
+            assert!(
+                options::KEYMAPS.contains(&region.keymap),
+                "{}",
+                region.country
+            );
+            let (zone_region, city) = region.timezone;
+            assert!(
+                options::TIMEZONE_REGIONS.contains(&zone_region),
+                "{}",
+                region.country
+            );
+            assert!(
+                InputHandler::get_timezones_for_region(zone_region).contains(&city.to_string()),
+                "{}",
+                region.country
+            );
+            assert!(
+                options::MIRROR_COUNTRIES.contains(&region.mirror_country),
+                "{}",
+                region.country
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_region() {
+        let germany = find_region("Germany").unwrap();
+        assert_eq!(
+            germany.values(),
+            ["de_DE.UTF-8", "de", "Europe", "Berlin", "Germany"]
+        );
+        assert!(find_region("Atlantis").is_none());
+        assert!(validate("").is_ok());
+        assert!(validate("Atlantis").is_err());
+        assert_eq!(countries().len(), REGIONS.len());
+    }
+}
@@ -0,0 +1,334 @@
+//! Remote installation over SSH
+//!
+//! Pushes the installer scripts and a configuration file to a machine booted
+//! into the Arch ISO, runs the installation there and streams its output back
+//! into the local progress screen. Uses the system `ssh` and `tar` binaries.
+
+use crate::app::{AppMode, AppState, StateHandle};
+use crate::error::ArchInstallError;
+use crate::facts::{self, Facts};
+use crate::notify::Notifier;
+use crate::process_guard::{ChildRegistry, CommandProcessGroup};
+use crate::script_env::ScriptEnv;
+use crate::types::ErrorPolicy;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Working directory on the target that receives scripts and config
+pub const REMOTE_WORK_DIR: &str = "/tmp/archinstall-remote";
+
+/// A machine reachable over SSH that will receive the installation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTarget {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+    pub identity: Option<PathBuf>,
+}
+
+impl RemoteTarget {
+    /// Parse a `[user@]host` target; the user defaults to root (Arch ISO default)
+    pub fn parse(
+        spec: &str,
+        port: u16,
+        identity: Option<PathBuf>,
+    ) -> Result<Self, ArchInstallError> {
+        let (user, host) = match spec.split_once('@') {
+            Some((user, host)) => (user, host),
+            None => ("root", spec),
+        };
+
+        // Reject anything ssh could interpret as an option or a second argument
+        let valid_host = !host.is_empty()
+            && !host.starts_with('-')
+            && host
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'));
+        if !valid_host {
+            return Err(ArchInstallError::validation(
+                "remote host",
+                format!("'{}' is not a host name or address", host),
+            ));
+        }
+
+        let valid_user = !user.is_empty()
+            && !user.starts_with('-')
+            && user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid_user {
+            return Err(ArchInstallError::validation(
+                "remote user",
+                format!("'{}' is not a user name", user),
+            ));
+        }
+
+        Ok(Self {
+            user: user.to_string(),
+            host: host.to_string(),
+            port,
+            identity,
+        })
+    }
+
+    /// `user@host` as passed to ssh
+    pub fn destination(&self) -> String {
+        format!("{}@{}", self.user, self.host)
+    }
+
+    /// Arguments for a non-interactive ssh invocation (never prompts for input)
+    pub fn ssh_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-p".to_string(),
+            self.port.to_string(),
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            "ConnectTimeout=10".to_string(),
+            "-o".to_string(),
+            "StrictHostKeyChecking=accept-new".to_string(),
+            "-o".to_string(),
+            "ServerAliveInterval=15".to_string(),
+        ];
+        if let Some(ref identity) = self.identity {
+            args.push("-i".to_string());
+            args.push(identity.display().to_string());
+        }
+        args.push(self.destination());
+        args
+    }
+
+    /// Build an ssh command that runs `remote_command` on the target
+    fn ssh(&self, remote_command: &str) -> Command {
+        let mut command = Command::new("ssh");
+        command.args(self.ssh_args()).arg("--").arg(remote_command);
+        command
+    }
+
+    /// Verify the target is reachable and looks like a live Arch ISO
+    pub fn check_connection(&self) -> Result<(), ArchInstallError> {
+        let output = self
+            .ssh("command -v pacstrap >/dev/null && command -v arch-chroot >/dev/null")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| ArchInstallError::command_not_run("ssh", e))?;
+
+        match output.status.code() {
+            Some(0) => Ok(()),
+            Some(255) => Err(ArchInstallError::system(format!(
+                "Cannot connect to {}: {}",
+                self.destination(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))),
+            _ => Err(ArchInstallError::system(format!(
+                "{} is reachable but is not an Arch ISO environment (pacstrap/arch-chroot missing)",
+                self.host
+            ))),
+        }
+    }
+
+    /// Read the target's machine facts, for config templates
+    pub fn facts(&self) -> Result<Facts, ArchInstallError> {
+        let output = self
+            .ssh(&facts::remote_command())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| ArchInstallError::command_not_run("ssh", e))?;
+        if !output.status.success() {
+            return Err(ArchInstallError::command_failed(
+                format!("Reading machine facts from {}", self.destination()),
+                &output,
+            ));
+        }
+        Ok(Facts::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Copy the scripts directory and the configuration (JSON) to the target
+    pub fn push(&self, scripts_dir: &Path, config: &[u8]) -> Result<(), ArchInstallError> {
+        let mut tar = Command::new("tar")
+            .arg("-C")
+            .arg(scripts_dir)
+            .arg("-cz")
+            .arg(".")
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| ArchInstallError::command_not_run("tar", e))?;
+
+        let tar_stdout = tar
+            .stdout
+            .take()
+            .ok_or_else(|| ArchInstallError::system("Failed to capture tar output"))?;
+
+        let unpack = format!(
+            "rm -rf {dir} && mkdir -p {dir}/scripts && chmod 700 {dir} && tar -xz -C {dir}/scripts",
+            dir = REMOTE_WORK_DIR
+        );
+        let status = self
+            .ssh(&unpack)
+            .stdin(Stdio::from(tar_stdout))
+            .status()
+            .map_err(|e| ArchInstallError::command_not_run("ssh", e))?;
+        let tar_status = tar
+            .wait()
+            .map_err(|e| ArchInstallError::system(format!("tar failed: {}", e)))?;
+        if !status.success() || !tar_status.success() {
+            return Err(ArchInstallError::system(format!(
+                "Failed to copy installer scripts to {}",
+                self.destination()
+            )));
+        }
+
+        // Config contains passwords: write it with a private umask over stdin
+        let mut child = self
+            .ssh(&format!(
+                "umask 077 && cat > {}/config.json",
+                REMOTE_WORK_DIR
+            ))
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| ArchInstallError::command_not_run("ssh", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config)?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(ArchInstallError::system(format!(
+                "Failed to copy configuration to {}",
+                self.destination()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Remote shell command that runs the installer unattended and removes the config afterwards
+    pub fn install_command(&self, on_error: ErrorPolicy, retries: u8) -> String {
+        let env = ScriptEnv::new()
+            .config_json_path(format!("{}/config.json", REMOTE_WORK_DIR))
+            .unattended(on_error, retries);
+        format!(
+            "cd {dir} && {env} bash scripts/install.sh; rc=$?; rm -f {dir}/config.json; exit $rc",
+            dir = REMOTE_WORK_DIR,
+            env = env.shell_assignments(),
+        )
+    }
+
+    /// Start the remote installation and stream its output into the progress screen
+    ///
+    /// `state` switches to the progress screen now; the output reaches it
+    /// through `app_state`. `notifier` announces the end here, where the
+    /// terminal is.
+    pub fn start_install(
+        &self,
+        on_error: ErrorPolicy,
+        retries: u8,
+        state: &mut AppState,
+        app_state: StateHandle,
+        notifier: Notifier,
+    ) -> Result<(), ArchInstallError> {
+        {
+            state.transition(AppMode::Installation);
+            state.status.info(format!("Installing on {}...", self.host));
+            state.install.started = Some(std::time::Instant::now());
+            state.install.progress = 5;
+            state
+                .install
+                .output
+                .push("=== REMOTE INSTALLATION STARTED ===".to_string());
+            state.install.output.push(format!(
+                "Target: {} (port {})",
+                self.destination(),
+                self.port
+            ));
+            state
+                .install
+                .output
+                .push("==========================================".to_string());
+        }
+
+        let child = self
+            .ssh(&self.install_command(on_error, retries))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .in_new_process_group()
+            .spawn()
+            .map_err(|e| {
+                ArchInstallError::script(format!("Failed to start remote installer: {}", e))
+            })?;
+
+        if let Ok(mut registry) = ChildRegistry::global().lock() {
+            registry.register(child.id());
+        }
+
+        crate::installer::monitor_installer_output(child, app_state, None, None, None, notifier);
+        Ok(())
+    }
+}
+
+/// Quote a string for safe use in a POSIX shell command line
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defaults_to_root() {
+        let target = RemoteTarget::parse("192.168.1.50", 22, None).unwrap();
+        assert_eq!(target.user, "root");
+        assert_eq!(target.host, "192.168.1.50");
+        assert_eq!(target.destination(), "root@192.168.1.50");
+    }
+
+    #[test]
+    fn test_parse_user_and_host() {
+        let target = RemoteTarget::parse("admin@archiso.lan", 2222, None).unwrap();
+        assert_eq!(target.user, "admin");
+        assert_eq!(target.host, "archiso.lan");
+        assert_eq!(target.port, 2222);
+    }
+
+    #[test]
+    fn test_parse_rejects_option_injection() {
+        assert!(RemoteTarget::parse("-oProxyCommand=evil", 22, None).is_err());
+        assert!(RemoteTarget::parse("root@-oProxyCommand=evil", 22, None).is_err());
+        assert!(RemoteTarget::parse("root@host;rm -rf /", 22, None).is_err());
+        assert!(RemoteTarget::parse("", 22, None).is_err());
+        assert!(RemoteTarget::parse("@host", 22, None).is_err());
+    }
+
+    #[test]
+    fn test_ssh_args_are_non_interactive() {
+        let target =
+            RemoteTarget::parse("root@host", 2200, Some(PathBuf::from("/tmp/id_ed25519"))).unwrap();
+        let args = target.ssh_args();
+        assert!(args.contains(&"BatchMode=yes".to_string()));
+        assert_eq!(args[0], "-p");
+        assert_eq!(args[1], "2200");
+        assert!(args
+            .windows(2)
+            .any(|w| w[0] == "-i" && w[1] == "/tmp/id_ed25519"));
+        assert_eq!(args.last().unwrap(), "root@host");
+    }
+
+    #[test]
+    fn test_install_command_runs_unattended() {
+        let target = RemoteTarget::parse("host", 22, None).unwrap();
+        let command = target.install_command(ErrorPolicy::Retry, 2);
+        assert!(command.contains("ARCHINSTALL_UNATTENDED='1'"));
+        assert!(command.contains("ARCHINSTALL_ON_ERROR='retry'"));
+        assert!(command.contains("ARCHINSTALL_RETRIES='2'"));
+        assert!(command.contains("ARCHINSTALL_CONFIG_JSON='/tmp/archinstall-remote/config.json'"));
+        assert!(command.contains("rm -f /tmp/archinstall-remote/config.json"));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("simple"), "'simple'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,292 @@
+//! Environment handed to the shell installer
+//!
+//! install.sh takes everything it needs from its environment, never from
+//! positional arguments. [`ScriptEnv`] builds that environment:
+//!
+//! | Variable | Meaning |
+//! |---|---|
+//! | `ARCHINSTALL_CONFIG_JSON` | Private file with the whole configuration in config-file format; loaded like `--config` |
+//! | `INSTALL_DISK`, `LOCALE`, ... | The configuration as variables (TUI installs, which pass passwords on stdin) |
+//! | `GUEST_TOOLS_PACKAGES`, `AUDIO_PACKAGES`, `SERVICE_PACKAGES`, ... | Packages and services of the chosen options, also passed with a configuration file |
+//! | `ARCHINSTALL_UNATTENDED` | `1` pre-answers confirmations and closes stdin |
+//! | `ARCHINSTALL_ON_ERROR` | `abort`, `retry`, `continue`, or `ask` for the recovery dialog |
+//! | `ARCHINSTALL_RETRIES` | Attempts of a failed phase under `retry` |
+//! | `ARCHINSTALL_RECOVERY_FILE` | File the recovery dialog writes its choice to |
+//! | `ARCHINSTALL_REPORT_DIR` | External directory the installation report is copied to |
+//! | `ARCHINSTALL_BIN` | This binary, which custom phases are run through |
+//! | `LOW_MEMORY` | `Yes` for the low-memory strategy; install.sh decides when unset |
+//! | `http_proxy`, `HTTPS_PROXY`, ... | The guided installer's proxy, in both spellings |
+//!
+//! A configuration file written by [`ScriptEnv::config_json`] holds passwords:
+//! it is created private and removed when the `ScriptEnv` is dropped, so keep
+//! the builder alive until the script has exited.
+//!
+//! Tool scripts still take `--flag value` arguments; [`ScriptArgs`] builds
+//! them from optional values and switches.
+
+use crate::proxy::Proxy;
+use crate::types::ErrorPolicy;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Variable naming the configuration file install.sh loads
+pub const CONFIG_JSON_VAR: &str = "ARCHINSTALL_CONFIG_JSON";
+
+/// Environment of an install.sh run
+#[derive(Debug, Default)]
+pub struct ScriptEnv {
+    vars: BTreeMap<String, String>,
+    /// Configuration file written by this builder, removed on drop
+    config_file: Option<PathBuf>,
+}
+
+impl ScriptEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass the configuration as variables, e.g. from `Configuration::to_env_vars`
+    pub fn config_vars(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.vars.extend(vars);
+        self
+    }
+
+    /// Write the configuration JSON to a private file and pass its path
+    pub fn config_json(mut self, json: &str) -> std::io::Result<Self> {
+        let path = write_private(json)?;
+        self.vars.insert(
+            CONFIG_JSON_VAR.to_string(),
+            path.to_string_lossy().to_string(),
+        );
+        self.config_file = Some(path);
+        Ok(self)
+    }
+
+    /// Pass a configuration file the caller manages, e.g. on a remote machine
+    pub fn config_json_path(self, path: impl AsRef<Path>) -> Self {
+        self.var(CONFIG_JSON_VAR, path.as_ref().to_string_lossy())
+    }
+
+    /// Run without any prompt, handling failed phases by `on_error`
+    pub fn unattended(self, on_error: ErrorPolicy, retries: u8) -> Self {
+        self.var("ARCHINSTALL_UNATTENDED", "1")
+            .var("ARCHINSTALL_ON_ERROR", on_error.to_string())
+            .var("ARCHINSTALL_RETRIES", retries.to_string())
+    }
+
+    /// Wait for the recovery dialog's choice, written to `path`, after a failed phase
+    pub fn recovery_file(self, path: &Path) -> Self {
+        self.var("ARCHINSTALL_ON_ERROR", "ask")
+            .var("ARCHINSTALL_RECOVERY_FILE", path.to_string_lossy())
+    }
+
+    /// Copy the installation report to `dir` after install (--report)
+    pub fn report_dir(self, dir: Option<&Path>) -> Self {
+        match dir {
+            Some(dir) => self.var("ARCHINSTALL_REPORT_DIR", dir.to_string_lossy()),
+            None => self,
+        }
+    }
+
+    /// Use the low-memory strategy when `low`, else leave it to install.sh
+    pub fn low_memory(self, low: bool) -> Self {
+        if low {
+            self.var(crate::low_memory::LOW_MEMORY_VAR, "Yes")
+        } else {
+            self
+        }
+    }
+
+    /// Download through `proxy`
+    ///
+    /// Blank settings are passed blank, so clearing a proxy option overrides
+    /// the proxy the installer was started with.
+    pub fn proxy(self, proxy: &Proxy) -> Self {
+        proxy
+            .env_vars()
+            .into_iter()
+            .fold(self, |env, (name, value)| env.var(name, value))
+    }
+
+    /// Let custom phases call back into this binary
+    pub fn installer_bin(self) -> Self {
+        match std::env::current_exe() {
+            Ok(bin) => self.var("ARCHINSTALL_BIN", bin.to_string_lossy()),
+            Err(_) => self,
+        }
+    }
+
+    fn var(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.vars.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// The variables, by name
+    #[allow(dead_code)]
+    pub fn vars(&self) -> &BTreeMap<String, String> {
+        &self.vars
+    }
+
+    /// Set the variables on a command
+    pub fn apply(&self, command: &mut Command) {
+        command.envs(&self.vars);
+    }
+
+    /// The variables as quoted `NAME=value` assignments for a shell command line
+    pub fn shell_assignments(&self) -> String {
+        self.vars
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, crate::remote::shell_quote(value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Drop for ScriptEnv {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.config_file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Write the configuration where only this user can read it
+///
+/// The file holds passwords: it is created fresh, never through a link
+/// someone else left in the temporary directory.
+fn write_private(json: &str) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let path = std::env::temp_dir().join(format!("archinstall-config-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(path)
+}
+
+/// `--flag value` arguments of a tool script
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptArgs {
+    args: Vec<String>,
+}
+
+impl ScriptArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `flag value`
+    pub fn value(mut self, flag: &str, value: impl ToString) -> Self {
+        self.args.push(flag.to_string());
+        self.args.push(value.to_string());
+        self
+    }
+
+    /// `flag value` when there is a value
+    pub fn optional<T: ToString>(self, flag: &str, value: Option<T>) -> Self {
+        match value {
+            Some(value) => self.value(flag, value),
+            None => self,
+        }
+    }
+
+    /// `flag` alone when `enabled`
+    pub fn switch(mut self, flag: &str, enabled: bool) -> Self {
+        if enabled {
+            self.args.push(flag.to_string());
+        }
+        self
+    }
+
+    /// `on` or `off` for a tri-state option, nothing when unset
+    pub fn toggle(self, setting: Option<bool>, on: &str, off: &str) -> Self {
+        match setting {
+            Some(true) => self.switch(on, true),
+            Some(false) => self.switch(off, true),
+            None => self,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[String] {
+        &self.args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_config_json_is_private_and_removed_on_drop() {
+        let env = ScriptEnv::new()
+            .config_json("{\"hostname\": \"arch\"}")
+            .unwrap();
+        let path = PathBuf::from(&env.vars()[CONFIG_JSON_VAR]);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"hostname\": \"arch\"}"
+        );
+        drop(env);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_unattended_and_shell_assignments() {
+        let env = ScriptEnv::new()
+            .config_json_path("/tmp/remote/config.json")
+            .unattended(ErrorPolicy::Retry, 2)
+            .report_dir(None);
+        assert_eq!(env.vars()["ARCHINSTALL_ON_ERROR"], "retry");
+        assert!(!env.vars().contains_key("ARCHINSTALL_REPORT_DIR"));
+        assert_eq!(
+            env.shell_assignments(),
+            "ARCHINSTALL_CONFIG_JSON='/tmp/remote/config.json' ARCHINSTALL_ON_ERROR='retry' \
+             ARCHINSTALL_RETRIES='2' ARCHINSTALL_UNATTENDED='1'"
+        );
+    }
+
+    #[test]
+    fn test_proxy_overrides_both_spellings() {
+        let proxy = Proxy {
+            http: "http://proxy:3128".to_string(),
+            ..Proxy::default()
+        };
+        let env = ScriptEnv::new().proxy(&proxy);
+        assert_eq!(env.vars()["https_proxy"], "http://proxy:3128");
+        assert_eq!(env.vars()["HTTP_PROXY"], "http://proxy:3128");
+        assert_eq!(env.vars()["no_proxy"], "");
+
+        let env = ScriptEnv::new().proxy(&Proxy::default());
+        assert_eq!(env.vars()["http_proxy"], "");
+        assert_eq!(env.vars()["HTTPS_PROXY"], "");
+    }
+
+    #[test]
+    fn test_script_args() {
+        let args = ScriptArgs::new()
+            .value("--action", "status")
+            .optional("--port", Some(2222))
+            .optional::<&str>("--host", None)
+            .switch("--allow", false)
+            .toggle(Some(false), "--enable-root-login", "--disable-root-login");
+        assert_eq!(
+            args.as_slice(),
+            [
+                "--action",
+                "status",
+                "--port",
+                "2222",
+                "--disable-root-login"
+            ]
+        );
+    }
+}
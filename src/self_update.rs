@@ -0,0 +1,383 @@
+//! Installer updates from GitHub releases
+//!
+//! When the TUI starts it asks GitHub for the latest release in the
+//! background, and the main menu shows a banner if that release is newer than
+//! the running binary. `archinstall-tui self-update` runs the same check and,
+//! on the Arch live ISO, downloads the release binary and replaces the running
+//! one. Elsewhere the installer belongs to whatever installed it, so only the
+//! release page is shown. Downloads go through curl and so through the proxy;
+//! the binary must match the SHA-256 sum published with it before it is run.
+
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// GitHub API endpoint of the latest release
+pub const RELEASES_URL: &str =
+    "https://api.github.com/repos/live4thamuzik/ArchInstall/releases/latest";
+
+/// Release asset holding the installer binary
+pub const ASSET_NAME: &str = "archinstall-tui";
+
+/// Release asset holding the binary's SHA-256 sum, as `sha256sum` prints it
+pub const CHECKSUM_ASSET_NAME: &str = "archinstall-tui.sha256";
+
+/// Environment variable that turns the startup check off (`--no-update-check`)
+pub const DISABLE_VAR: &str = "ARCHINSTALL_NO_UPDATE_CHECK";
+
+/// Directory that only exists on the Arch live ISO
+const LIVE_ISO_MARKER: &str = "/run/archiso";
+
+/// Release version, compared by its numeric parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parse `1.2.3`, `v1.2` and the like; pre-release and build suffixes are ignored
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix(['v', 'V']).unwrap_or(value);
+        let core = value.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// Version of the running binary
+    pub fn current() -> Self {
+        Self::parse(env!("CARGO_PKG_VERSION")).expect("package version is numeric")
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A published release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: Version,
+    /// Release page, for installs the updater does not replace
+    pub page: String,
+    /// Download URL of the installer binary, if the release has one
+    pub download: Option<String>,
+    /// Download URL of the binary's SHA-256 sum, if the release has one
+    pub checksum: Option<String>,
+}
+
+impl Release {
+    /// Whether this release is newer than the running binary
+    pub fn is_newer(&self) -> bool {
+        self.version > Version::current()
+    }
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Read a release from the GitHub API's JSON
+pub fn parse_release(json: &str) -> Result<Release, String> {
+    let release: GitHubRelease =
+        serde_json::from_str(json).map_err(|e| format!("Unexpected release data: {}", e))?;
+    let version = Version::parse(&release.tag_name)
+        .ok_or_else(|| format!("Release tag '{}' is not a version", release.tag_name))?;
+    let asset = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.clone())
+    };
+    Ok(Release {
+        version,
+        download: asset(ASSET_NAME),
+        checksum: asset(CHECKSUM_ASSET_NAME),
+        page: release.html_url,
+    })
+}
+
+/// Ask GitHub for the latest release
+pub fn fetch_latest() -> Result<Release, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "10"])
+        .args(["-H", "Accept: application/vnd.github+json"])
+        .arg(RELEASES_URL)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not reach GitHub: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_release(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The latest release if it is newer than the running binary
+pub fn check() -> Result<Option<Release>, String> {
+    fetch_latest().map(|release| release.is_newer().then_some(release))
+}
+
+/// Whether the TUI should look for updates on start
+pub fn startup_check_enabled() -> bool {
+    std::env::var_os(DISABLE_VAR).is_none() && !crate::simulate::is_enabled()
+}
+
+/// Whether the installer runs on the Arch live ISO, where it may replace itself
+pub fn is_live_iso() -> bool {
+    Path::new(LIVE_ISO_MARKER).is_dir()
+}
+
+/// Read the sum from a `sha256sum`-style line, lowercased
+pub fn parse_checksum(text: &str) -> Option<String> {
+    let sum = text.split_whitespace().next()?;
+    (sum.len() == 64 && sum.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| sum.to_ascii_lowercase())
+}
+
+/// Download a release's binary and put it in place of `exe`
+///
+/// The download is staged next to `exe`, must match the release's SHA-256
+/// sum before it is made executable, and must report the release's version
+/// before it replaces the old binary, so a failed, truncated or tampered
+/// download leaves the installer untouched. A release without a sum is not
+/// installed. Renaming keeps the running process on the old file.
+pub fn install(release: &Release, exe: &Path) -> Result<(), String> {
+    let missing = |asset: &str| {
+        format!(
+            "Release {} has no {} asset: see {}",
+            release.version, asset, release.page
+        )
+    };
+    let url = release
+        .download
+        .as_deref()
+        .ok_or_else(|| missing(ASSET_NAME))?;
+    let checksum_url = release
+        .checksum
+        .as_deref()
+        .ok_or_else(|| missing(CHECKSUM_ASSET_NAME))?;
+    let expected = fetch(checksum_url).and_then(|text| {
+        parse_checksum(&text).ok_or_else(|| format!("{} holds no SHA-256 sum", CHECKSUM_ASSET_NAME))
+    })?;
+    let staged = exe.with_extension("new");
+    let result = download(url, &staged)
+        .and_then(|_| verify_checksum(&staged, &expected))
+        .and_then(|_| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Cannot make {} executable: {}", staged.display(), e))
+        })
+        .and_then(|_| verify(&staged, release.version));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&staged);
+        return Err(e);
+    }
+    fs::rename(&staged, exe).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        format!("Cannot replace {}: {}", exe.display(), e)
+    })
+}
+
+/// Download a small text asset
+fn fetch(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "10"])
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Download of {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn download(url: &str, path: &Path) -> Result<(), String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "300", "-o"])
+        .arg(path)
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Download of {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Check a downloaded file against the published SHA-256 sum
+fn verify_checksum(path: &Path, expected: &str) -> Result<(), String> {
+    let output = Command::new("sha256sum")
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run sha256sum: {}", e))?;
+    let actual = parse_checksum(&String::from_utf8_lossy(&output.stdout))
+        .filter(|_| output.status.success())
+        .ok_or_else(|| format!("Cannot compute the SHA-256 sum of {}", path.display()))?;
+    if actual != expected {
+        return Err(format!(
+            "Downloaded binary has SHA-256 {}, the release publishes {}",
+            actual, expected
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a downloaded binary runs and is the expected version
+fn verify(path: &Path, version: Version) -> Result<(), String> {
+    let output = Command::new(path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Downloaded binary does not run: {}", e))?;
+    let reported = String::from_utf8_lossy(&output.stdout);
+    let matches = reported
+        .split_whitespace()
+        .filter_map(Version::parse)
+        .any(|reported| reported == version);
+    if !output.status.success() || !matches {
+        return Err(format!(
+            "Downloaded binary reports '{}', expected version {}",
+            reported.trim(),
+            version
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_and_orders_versions() {
+        let version = |value| Version::parse(value).unwrap();
+        assert_eq!(version("v1.2.3").to_string(), "1.2.3");
+        assert_eq!(version("0.4"), version("0.4.0"));
+        assert_eq!(version("2.0.0-rc1"), version("2.0.0"));
+        assert!(version("0.10.0") > version("0.9.9"));
+        assert!(version("1.0.0") > version("0.99.99"));
+        assert_eq!(Version::parse("nightly"), None);
+        assert_eq!(Version::parse("1.2.3.4"), None);
+        assert_eq!(
+            Version::current().to_string(),
+            env!("CARGO_PKG_VERSION").to_string()
+        );
+    }
+
+    #[test]
+    fn test_reads_github_release() {
+        let json = r#"{
+            "tag_name": "v99.0.0",
+            "html_url": "https://github.com/live4thamuzik/ArchInstall/releases/tag/v99.0.0",
+            "assets": [
+                {"name": "archinstall-tui.sha256", "browser_download_url": "https://example.com/sum"},
+                {"name": "archinstall-tui", "browser_download_url": "https://example.com/bin"}
+            ]
+        }"#;
+        let release = parse_release(json).unwrap();
+        assert_eq!(release.version.to_string(), "99.0.0");
+        assert_eq!(release.download.as_deref(), Some("https://example.com/bin"));
+        assert_eq!(release.checksum.as_deref(), Some("https://example.com/sum"));
+        assert!(release.is_newer());
+
+        let old = parse_release(r#"{"tag_name": "0.0.1", "html_url": "https://x"}"#).unwrap();
+        assert_eq!(old.download, None);
+        assert_eq!(old.checksum, None);
+        assert!(!old.is_newer());
+
+        assert!(
+            parse_release(r#"{"tag_name": "latest", "html_url": "https://x"}"#)
+                .unwrap_err()
+                .contains("not a version")
+        );
+        assert!(parse_release("{\"message\": \"Not Found\"}").is_err());
+    }
+
+    #[test]
+    fn test_failed_install_keeps_the_old_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("archinstall-tui");
+        fs::write(&exe, "old").unwrap();
+        let release = Release {
+            version: Version::parse("99.0.0").unwrap(),
+            page: "https://example.com/release".to_string(),
+            download: None,
+            checksum: None,
+        };
+        assert!(install(&release, &exe)
+            .unwrap_err()
+            .contains("https://example.com/release"));
+
+        // Without a published sum the binary is not even downloaded
+        let unsigned = Release {
+            download: Some("https://example.com/bin".to_string()),
+            ..release.clone()
+        };
+        assert!(install(&unsigned, &exe)
+            .unwrap_err()
+            .contains(CHECKSUM_ASSET_NAME));
+        assert!(!exe.with_extension("new").exists());
+
+        // A binary reporting another version is not installed
+        assert!(verify(Path::new("/bin/echo"), release.version)
+            .unwrap_err()
+            .contains("expected version 99.0.0"));
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_checks_the_published_sum() {
+        let sum = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            parse_checksum(&format!("{}  archinstall-tui\n", sum)),
+            Some(sum.to_ascii_lowercase())
+        );
+        assert_eq!(parse_checksum("e3b0c442  archinstall-tui"), None);
+        assert_eq!(parse_checksum(""), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("archinstall-tui.new");
+        fs::write(&empty, "").unwrap();
+        verify_checksum(&empty, &sum.to_ascii_lowercase()).unwrap();
+        fs::write(&empty, "tampered").unwrap();
+        assert!(verify_checksum(&empty, &sum.to_ascii_lowercase())
+            .unwrap_err()
+            .contains("the release publishes"));
+    }
+}
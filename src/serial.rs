@@ -0,0 +1,180 @@
+//! Serial console mode
+//!
+//! `--serial` tunes the TUI for installs over a serial line or IPMI
+//! Serial-over-LAN, where the console is slow and often a dumb terminal:
+//!
+//! - the interface is drawn on the normal screen: many consoles have no
+//!   alternate screen, and one lost on reconnect hides the installer
+//! - frames are drawn after input and otherwise at most every
+//!   [`FRAME_INTERVAL`], so spinners and progress output don't saturate the line
+//! - the character set is always ASCII
+//! - every key the TUI uses has an alternative that any client sends: the
+//!   emacs-style control keys in [`KEY_ALTERNATIVES`] stand in for arrows,
+//!   PgUp/PgDn, Home/End and Esc, and `?` opens the help instead of F1
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::{Duration, Instant};
+
+/// Longest time between frames while nothing is typed
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the main loop waits for input
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Control keys standing in for keys a serial client may not send
+///
+/// Ctrl+G stands in for Esc, which some clients hold back or send only as
+/// the start of a sequence.
+pub const KEY_ALTERNATIVES: &[(char, KeyCode, &str)] = &[
+    ('p', KeyCode::Up, "Up"),
+    ('n', KeyCode::Down, "Down"),
+    ('b', KeyCode::Left, "Left"),
+    ('f', KeyCode::Right, "Right"),
+    ('u', KeyCode::PageUp, "PgUp"),
+    ('v', KeyCode::PageDown, "PgDn"),
+    ('a', KeyCode::Home, "Home"),
+    ('e', KeyCode::End, "End"),
+    ('g', KeyCode::Esc, "Esc"),
+    ('h', KeyCode::Backspace, "Backspace"),
+];
+
+/// Control keys left alone while text is typed
+///
+/// Ctrl+U clears the line in shells and editors, so it only stands in for
+/// PgUp outside text input.
+const TEXT_KEYS: &[char] = &['u'];
+
+/// The key a control-key alternative stands for; other keys are unchanged
+///
+/// `typing` is whether a text field or prompt has the keyboard.
+pub fn translate(key: KeyEvent, typing: bool) -> KeyEvent {
+    let KeyCode::Char(c) = key.code else {
+        return key;
+    };
+    if key.modifiers != KeyModifiers::CONTROL {
+        return key;
+    }
+    let c = c.to_ascii_lowercase();
+    if typing && TEXT_KEYS.contains(&c) {
+        return key;
+    }
+    KEY_ALTERNATIVES
+        .iter()
+        .find(|(letter, _, _)| *letter == c)
+        .map_or(key, |(_, code, _)| KeyEvent::new(*code, KeyModifiers::NONE))
+}
+
+/// Whether every serial client can send the key
+///
+/// Printable characters, Enter, Tab, Backspace, Esc and control letters are
+/// single bytes; arrows, function and editing keys are escape sequences a
+/// dumb terminal may not produce.
+#[allow(dead_code)] // Used by the keyboard reachability tests
+pub fn is_plain(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) if key.modifiers == KeyModifiers::CONTROL => c.is_ascii_alphabetic(),
+        KeyCode::Char(c) => {
+            (key.modifiers - KeyModifiers::SHIFT).is_empty() && (c.is_ascii_graphic() || c == ' ')
+        }
+        KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace | KeyCode::Esc => {
+            key.modifiers.is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Decides when the main loop draws a frame
+///
+/// Outside serial mode every pass of the loop draws.
+#[derive(Debug)]
+pub struct FramePacer {
+    serial: bool,
+    pending: bool,
+    last: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn new(serial: bool) -> Self {
+        Self {
+            serial,
+            pending: true,
+            last: None,
+        }
+    }
+
+    /// Draw the next frame right away, e.g. after a key press
+    pub fn input(&mut self) {
+        self.pending = true;
+    }
+
+    /// Whether to draw at `now`; a `true` counts as drawn
+    pub fn should_draw(&mut self, now: Instant) -> bool {
+        let due = !self.serial
+            || self.pending
+            || self
+                .last
+                .is_none_or(|last| now.duration_since(last) >= FRAME_INTERVAL);
+        if due {
+            self.pending = false;
+            self.last = Some(now);
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn test_control_keys_stand_in_for_sequences() {
+        assert_eq!(translate(ctrl('n'), false).code, KeyCode::Down);
+        assert_eq!(translate(ctrl('V'), false).code, KeyCode::PageDown);
+        assert_eq!(translate(ctrl('g'), false), KeyEvent::from(KeyCode::Esc));
+        // Ctrl+Q quits the embedded terminal and is left alone
+        assert_eq!(translate(ctrl('q'), false), ctrl('q'));
+        let typed = KeyEvent::from(KeyCode::Char('n'));
+        assert_eq!(translate(typed, false), typed);
+        // Ctrl+U is PgUp only outside text input
+        assert_eq!(translate(ctrl('u'), false).code, KeyCode::PageUp);
+        assert_eq!(translate(ctrl('u'), true), ctrl('u'));
+        assert_eq!(translate(ctrl('b'), true).code, KeyCode::Left);
+    }
+
+    #[test]
+    fn test_plain_keys() {
+        assert!(is_plain(&KeyEvent::from(KeyCode::Char('q'))));
+        assert!(is_plain(&KeyEvent::new(
+            KeyCode::Char('R'),
+            KeyModifiers::SHIFT
+        )));
+        assert!(is_plain(&KeyEvent::from(KeyCode::Esc)));
+        assert!(is_plain(&ctrl('p')));
+        assert!(!is_plain(&KeyEvent::from(KeyCode::Up)));
+        assert!(!is_plain(&KeyEvent::from(KeyCode::F(1))));
+        assert!(!is_plain(&KeyEvent::new(
+            KeyCode::Char('x'),
+            KeyModifiers::ALT
+        )));
+    }
+
+    #[test]
+    fn test_frames_are_paced_on_serial_consoles() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(true);
+        assert!(pacer.should_draw(start), "first frame");
+        assert!(!pacer.should_draw(start + Duration::from_millis(100)));
+        pacer.input();
+        assert!(pacer.should_draw(start + Duration::from_millis(150)));
+        assert!(!pacer.should_draw(start + Duration::from_millis(400)));
+        assert!(pacer.should_draw(start + Duration::from_millis(650)));
+
+        let mut local = FramePacer::new(false);
+        assert!(local.should_draw(start));
+        assert!(local.should_draw(start));
+    }
+}
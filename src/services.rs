@@ -0,0 +1,209 @@
+//! Services enabled in the installed system
+//!
+//! The Services option is a space-separated list of systemd units picked
+//! from [`SERVICES`]. chroot_config.sh installs the packages a unit needs
+//! and enables exactly these units; display managers, snapper timers and
+//! the sound server are enabled by the options that select them.
+
+use crate::types::TimeSyncDaemon;
+
+/// A systemd unit the guided installer offers to enable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Service {
+    /// Unit name, e.g. `sshd.service`
+    pub unit: &'static str,
+    /// Display name
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Packages providing the unit that pacstrap does not install
+    pub packages: &'static [&'static str],
+    /// Whether the unit is enabled unless deselected
+    pub default: bool,
+}
+
+/// Option value with every service turned off (empty means the defaults)
+pub const NONE: &str = "none";
+
+/// Unit of the time synchronisation service behind the Time Sync option
+pub const TIMESYNCD: &str = "systemd-timesyncd.service";
+
+/// Services offered, in display order
+pub const SERVICES: &[Service] = &[
+    Service {
+        unit: "NetworkManager.service",
+        name: "NetworkManager",
+        description: "Wired and Wi-Fi networking",
+        packages: &[],
+        default: true,
+    },
+    Service {
+        unit: "sshd.service",
+        name: "OpenSSH server",
+        description: "Remote logins over SSH",
+        packages: &[],
+        default: true,
+    },
+    Service {
+        unit: "bluetooth.service",
+        name: "Bluetooth",
+        description: "Bluetooth devices (BlueZ)",
+        packages: &["bluez", "bluez-utils"],
+        default: false,
+    },
+    Service {
+        unit: "firewalld.service",
+        name: "firewalld",
+        description: "Zone-based firewall",
+        packages: &["firewalld"],
+        default: false,
+    },
+    Service {
+        unit: "fstrim.timer",
+        name: "fstrim",
+        description: "Weekly TRIM of SSDs",
+        packages: &[],
+        default: true,
+    },
+    Service {
+        unit: TIMESYNCD,
+        name: "systemd-timesyncd",
+        description: "Network time synchronisation (SNTP)",
+        packages: &[],
+        default: true,
+    },
+];
+
+/// Look up a service by its unit name
+pub fn find_service(unit: &str) -> Option<&'static Service> {
+    SERVICES.iter().find(|service| service.unit == unit)
+}
+
+/// Units enabled by default, space-separated as stored in the option
+pub fn default_services() -> String {
+    SERVICES
+        .iter()
+        .filter(|service| service.default)
+        .map(|service| service.unit)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse a space-separated list of unit names
+pub fn parse_services(list: &str) -> Result<Vec<&'static Service>, String> {
+    let mut services: Vec<&'static Service> = Vec::new();
+    if list.trim() == NONE {
+        return Ok(services);
+    }
+    for unit in list.split_whitespace() {
+        let service = find_service(unit).ok_or_else(|| {
+            let known: Vec<&str> = SERVICES.iter().map(|service| service.unit).collect();
+            format!(
+                "Unknown service '{}' (available: {})",
+                unit,
+                known.join(", ")
+            )
+        })?;
+        if !services.contains(&service) {
+            services.push(service);
+        }
+    }
+    Ok(services)
+}
+
+/// Add or remove a unit in a space-separated list, keeping display order
+pub fn set_enabled(list: &str, unit: &str, enabled: bool) -> String {
+    let units: Vec<&str> = SERVICES
+        .iter()
+        .map(|service| service.unit)
+        .filter(|u| {
+            if *u == unit {
+                enabled
+            } else {
+                list.split_whitespace().any(|listed| listed == *u)
+            }
+        })
+        .collect();
+    if units.is_empty() {
+        NONE.to_string()
+    } else {
+        units.join(" ")
+    }
+}
+
+/// Packages the listed units need, space-separated, as exported in `SERVICE_PACKAGES`
+///
+/// `time_sync` is the daemon enabled in place of systemd-timesyncd, or
+/// `None` when Time Sync (NTP) is off. Unknown units need no packages.
+pub fn packages(list: &str, time_sync: Option<TimeSyncDaemon>) -> String {
+    let mut packages: Vec<&str> = Vec::new();
+    for unit in list.split_whitespace() {
+        let needed = match find_service(unit) {
+            Some(service) if service.unit == TIMESYNCD => {
+                time_sync.map_or(&[][..], |daemon| daemon.packages())
+            }
+            Some(service) => service.packages,
+            None => &[],
+        };
+        for package in needed {
+            if !packages.contains(package) {
+                packages.push(package);
+            }
+        }
+    }
+    packages.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_services_match_previous_behaviour() {
+        assert_eq!(
+            default_services(),
+            "NetworkManager.service sshd.service fstrim.timer systemd-timesyncd.service"
+        );
+        assert!(find_service("bluetooth.service").is_some_and(|s| !s.packages.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_services() {
+        let services = parse_services("fstrim.timer sshd.service fstrim.timer").unwrap();
+        let units: Vec<&str> = services.iter().map(|s| s.unit).collect();
+        assert_eq!(units, ["fstrim.timer", "sshd.service"]);
+
+        assert!(parse_services("").unwrap().is_empty());
+        assert!(parse_services(NONE).unwrap().is_empty());
+        assert!(parse_services("sshd")
+            .unwrap_err()
+            .contains("Unknown service 'sshd'"));
+    }
+
+    #[test]
+    fn test_set_enabled_keeps_display_order() {
+        assert_eq!(
+            set_enabled("fstrim.timer NetworkManager.service", TIMESYNCD, true),
+            "NetworkManager.service fstrim.timer systemd-timesyncd.service"
+        );
+        assert_eq!(
+            set_enabled(&default_services(), TIMESYNCD, false),
+            "NetworkManager.service sshd.service fstrim.timer"
+        );
+        assert_eq!(set_enabled("fstrim.timer", "fstrim.timer", false), NONE);
+    }
+
+    #[test]
+    fn test_packages_of_the_listed_units() {
+        let list = "bluetooth.service firewalld.service systemd-timesyncd.service";
+        assert_eq!(
+            packages(list, Some(TimeSyncDaemon::Timesyncd)),
+            "bluez bluez-utils firewalld"
+        );
+        assert_eq!(
+            packages(list, Some(TimeSyncDaemon::Chrony)),
+            "bluez bluez-utils firewalld chrony"
+        );
+        assert_eq!(packages(TIMESYNCD, None), "");
+        assert_eq!(packages(NONE, Some(TimeSyncDaemon::Chrony)), "");
+    }
+}
@@ -0,0 +1,113 @@
+//! Shell from any screen
+//!
+//! Ctrl+T suspends the TUI for an interactive shell where the installation
+//! stands: in the live system before the target is mounted, at /mnt once it
+//! is, and inside it through arch-chroot once pacstrap has put a shell
+//! there. Exiting the shell returns to the screen it was opened from. The
+//! installer keeps running in the meantime; its updates are applied on
+//! return, and a failed phase waits for its recovery choice until then.
+
+use crate::tools::cleanup::{self, INSTALL_ROOT};
+use std::fmt;
+use std::path::Path;
+
+/// Where the shell opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellContext {
+    /// The live system, at /
+    Live,
+    /// The mounted target, before it has a shell of its own
+    Target,
+    /// arch-chroot into the target
+    Chroot,
+}
+
+impl ShellContext {
+    /// Context given /proc/self/mounts and whether the target has bash
+    pub fn from_mounts(proc_mounts: &str, target_has_shell: bool) -> Self {
+        let target_mounted = cleanup::parse_mounts(proc_mounts, INSTALL_ROOT)
+            .iter()
+            .any(|mount| mount == INSTALL_ROOT);
+        match (target_mounted, target_has_shell) {
+            (false, _) => Self::Live,
+            (true, false) => Self::Target,
+            (true, true) => Self::Chroot,
+        }
+    }
+
+    /// Context of this machine right now
+    pub fn detect() -> Self {
+        let mounts = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+        let has_shell = Path::new(INSTALL_ROOT).join("usr/bin/bash").exists();
+        Self::from_mounts(&mounts, has_shell)
+    }
+
+    /// Script `bash -c` runs: a banner, then the shell
+    pub fn command(self) -> String {
+        let shell = match self {
+            Self::Live => "cd / && exec bash -i".to_string(),
+            Self::Target => format!("cd {} && exec bash -i", INSTALL_ROOT),
+            Self::Chroot => format!("exec arch-chroot {}", INSTALL_ROOT),
+        };
+        format!(
+            "echo 'Shell in {}; exit to return to the installer'; {}",
+            self, shell
+        )
+    }
+}
+
+impl fmt::Display for ShellContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Live => write!(f, "the live system"),
+            Self::Target => write!(f, "{}", INSTALL_ROOT),
+            Self::Chroot => write!(f, "the chroot at {}", INSTALL_ROOT),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+proc /proc proc rw,nosuid 0 0
+/dev/nvme0n1p2 /mnt ext4 rw,relatime 0 0
+/dev/nvme0n1p1 /mnt/boot vfat rw,relatime 0 0
+";
+
+    #[test]
+    fn test_context_follows_the_installation() {
+        assert_eq!(
+            ShellContext::from_mounts("proc /proc proc rw 0 0\n", true),
+            ShellContext::Live
+        );
+        // Only the ESP mounted is not a target yet
+        assert_eq!(
+            ShellContext::from_mounts("/dev/sda1 /mnt/boot vfat rw 0 0\n", false),
+            ShellContext::Live
+        );
+        assert_eq!(
+            ShellContext::from_mounts(MOUNTS, false),
+            ShellContext::Target
+        );
+        assert_eq!(
+            ShellContext::from_mounts(MOUNTS, true),
+            ShellContext::Chroot
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        assert!(ShellContext::Live
+            .command()
+            .ends_with("cd / && exec bash -i"));
+        assert!(ShellContext::Target
+            .command()
+            .ends_with("cd /mnt && exec bash -i"));
+        assert!(ShellContext::Chroot
+            .command()
+            .ends_with("exec arch-chroot /mnt"));
+        assert!(ShellContext::Target.command().contains("Shell in /mnt;"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Simulation mode for demos and test automation (`--simulate`)
+//!
+//! Runs the whole installer flow on a developer machine without touching its
+//! disks: [`crate::hardware::detect_disks`] returns [`fake_disks`], the
+//! installation runs [`install_script`] instead of install.sh, and tools log
+//! the commands they would run instead of running them. The mode is set for
+//! the whole process by [`enable`] and reaches child processes as
+//! `ARCHINSTALL_SIMULATE=1`.
+
+use crate::hardware::{DiskInfo, DiskKind};
+use std::env;
+
+/// Environment variable switching simulation on
+pub const ENV_VAR: &str = "ARCHINSTALL_SIMULATE";
+
+/// Start of every line standing in for a command that was not run
+pub const PREFIX: &str = "[SIMULATION]";
+
+/// Line install.sh prints at the start of each phase, and the commands the
+/// phase would run; `$VARIABLES` are the installer's environment
+const PHASES: &[(&str, &[&str])] = &[
+    ("Starting Arch Linux installation...", &[]),
+    (
+        "Phase 2: Preparing system...",
+        &["timedatectl set-ntp true"],
+    ),
+    (
+        "Starting disk partitioning...",
+        &[
+            "wipefs -a $INSTALL_DISK",
+            "sgdisk --zap-all $INSTALL_DISK",
+            "mkfs.$ROOT_FILESYSTEM ${INSTALL_DISK}2",
+        ],
+    ),
+    (
+        "Phase 5: Installing base system...",
+        &["pacstrap -K /mnt base $KERNEL linux-firmware"],
+    ),
+    (
+        "Phase 7: Configuring system in chroot...",
+        &["arch-chroot /mnt bash /root/chroot_config.sh"],
+    ),
+    (
+        "Installing packages...",
+        &["arch-chroot /mnt pacman -S --needed ..."],
+    ),
+    (
+        "Configuring bootloader...",
+        &["$BOOTLOADER installation on $INSTALL_DISK"],
+    ),
+    ("Phase 8: Finalizing installation...", &["umount -R /mnt"]),
+];
+
+/// Switch simulation on for this process and everything it spawns
+pub fn enable() {
+    env::set_var(ENV_VAR, "1");
+}
+
+/// Whether the installer runs in simulation
+pub fn is_enabled() -> bool {
+    env::var(ENV_VAR).is_ok_and(|value| value == "1")
+}
+
+/// Line logged in place of running `command`
+pub fn skipped(command: &str) -> String {
+    format!("{} Not run: {}", PREFIX, command)
+}
+
+/// Disks detected in simulation: one with Windows on it and two empty ones
+pub fn fake_disks() -> Vec<DiskInfo> {
+    let disk = |path: &str, size: u64, model: &str, transport: &str, kind| DiskInfo {
+        path: path.to_string(),
+        size,
+        model: model.to_string(),
+        transport: transport.to_string(),
+        kind,
+        partitions: Vec::new(),
+        existing_os: Vec::new(),
+        mounted: false,
+        read_only: false,
+    };
+    vec![
+        DiskInfo {
+            partitions: vec!["/dev/nvme0n1p1".to_string(), "/dev/nvme0n1p2".to_string()],
+            existing_os: vec!["Windows Boot Manager".to_string()],
+            ..disk(
+                "/dev/nvme0n1",
+                512_110_190_592,
+                "Simulated NVMe SSD",
+                "nvme",
+                DiskKind::Nvme,
+            )
+        },
+        disk(
+            "/dev/sda",
+            1_000_204_886_016,
+            "Simulated SATA SSD",
+            "sata",
+            DiskKind::Ssd,
+        ),
+        disk(
+            "/dev/sdb",
+            1_000_204_886_016,
+            "Simulated SATA HDD",
+            "sata",
+            DiskKind::Hdd,
+        ),
+    ]
+}
+
+/// Bash script run instead of install.sh
+///
+/// Prints the phase markers install.sh prints, so progress and status move
+/// as in a real installation, and the commands each phase would run. Each
+/// phase takes `$ARCHINSTALL_SIMULATE_DELAY` seconds (2 by default).
+pub fn install_script() -> String {
+    let mut script =
+        String::from("echo '=== SIMULATED INSTALLATION: nothing is written to disk ==='\n");
+    for (marker, commands) in PHASES {
+        script.push_str(&format!("echo '{}'\n", marker));
+        for command in *commands {
+            script.push_str(&format!("echo \"{}\"\n", skipped(command)));
+        }
+        script.push_str("sleep \"${ARCHINSTALL_SIMULATE_DELAY:-2}\"\n");
+    }
+    script.push_str("echo 'Installation complete!'\n");
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_fake_disks_are_install_targets() {
+        let disks = fake_disks();
+        assert!(disks.iter().all(DiskInfo::is_install_candidate));
+        assert_eq!(disks[0].contents(), "Windows Boot Manager");
+        assert_eq!(disks[1].contents(), "empty");
+    }
+
+    #[test]
+    fn test_install_script_logs_instead_of_running() {
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(install_script())
+            .env("ARCHINSTALL_SIMULATE_DELAY", "0")
+            .env("INSTALL_DISK", "/dev/sda")
+            .env("ROOT_FILESYSTEM", "ext4")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Starting disk partitioning..."));
+        assert!(stdout.contains("[SIMULATION] Not run: wipefs -a /dev/sda\n"));
+        assert!(stdout.contains("[SIMULATION] Not run: mkfs.ext4 /dev/sda2\n"));
+        assert!(stdout.trim_end().ends_with("Installation complete!"));
+    }
+}
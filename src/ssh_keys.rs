@@ -0,0 +1,135 @@
+//! SSH keys authorized for the created user
+//!
+//! The SSH Keys option lists where the public keys come from: `github:USER`
+//! and `gitlab:USER` for the keys published on a GitHub or GitLab account,
+//! or the absolute path of a key file, such as one on a USB stick. install.sh
+//! collects them on the live system, where the stick is mounted and the
+//! network is up, and chroot_config.sh writes them to the user's
+//! `~/.ssh/authorized_keys` with the permissions sshd requires.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where authorized keys come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Keys published on a GitHub account
+    GitHub(String),
+    /// Keys published on a GitLab account
+    GitLab(String),
+    /// A public key file on the live system
+    File(PathBuf),
+}
+
+impl KeySource {
+    /// URL the account's keys are published at, `None` for a file
+    pub fn url(&self) -> Option<String> {
+        match self {
+            Self::GitHub(user) => Some(format!("https://github.com/{}.keys", user)),
+            Self::GitLab(user) => Some(format!("https://gitlab.com/{}.keys", user)),
+            Self::File(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GitHub(user) => write!(f, "github:{}", user),
+            Self::GitLab(user) => write!(f, "gitlab:{}", user),
+            Self::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Parse one source: `github:USER`, `gitlab:USER` or an absolute path
+pub fn parse_source(value: &str) -> Result<KeySource, String> {
+    if let Some((service, user)) = value.split_once(':') {
+        let source = match service.to_ascii_lowercase().as_str() {
+            "github" => KeySource::GitHub(user.to_string()),
+            "gitlab" => KeySource::GitLab(user.to_string()),
+            _ if value.starts_with('/') => return Ok(KeySource::File(PathBuf::from(value))),
+            _ => {
+                return Err(format!(
+                    "SSH key source '{}' must be github:USER, gitlab:USER or an absolute path",
+                    value
+                ))
+            }
+        };
+        let valid = !user.is_empty()
+            && user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!("'{}' is not a valid {} username", user, service));
+        }
+        return Ok(source);
+    }
+    if value.starts_with('/') {
+        Ok(KeySource::File(PathBuf::from(value)))
+    } else {
+        Err(format!(
+            "SSH key file '{}' must be an absolute path, e.g. /run/media/usb/id_ed25519.pub",
+            value
+        ))
+    }
+}
+
+/// Parse sources separated by spaces or commas; empty means no keys
+pub fn parse_sources(value: &str) -> Result<Vec<KeySource>, String> {
+    let mut sources: Vec<KeySource> = Vec::new();
+    for entry in value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+    {
+        let source = parse_source(entry)?;
+        if sources.contains(&source) {
+            return Err(format!("SSH key source '{}' is listed twice", entry));
+        }
+        sources.push(source);
+    }
+    Ok(sources)
+}
+
+/// The option value of a source list, as [`parse_sources`] reads it
+pub fn format_sources(sources: &[String]) -> String {
+    sources.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sources() {
+        let sources = parse_sources("github:alice, GitLab:bob.s /run/media/usb/id.pub").unwrap();
+        assert_eq!(
+            sources,
+            [
+                KeySource::GitHub("alice".to_string()),
+                KeySource::GitLab("bob.s".to_string()),
+                KeySource::File(PathBuf::from("/run/media/usb/id.pub")),
+            ]
+        );
+        assert_eq!(
+            sources[0].url().as_deref(),
+            Some("https://github.com/alice.keys")
+        );
+        assert_eq!(sources[2].url(), None);
+        assert_eq!(sources[1].to_string(), "gitlab:bob.s");
+        assert!(parse_sources(" ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_sources() {
+        assert!(parse_sources("id_ed25519.pub")
+            .unwrap_err()
+            .contains("absolute path"));
+        assert!(parse_sources("github:").unwrap_err().contains("username"));
+        assert!(parse_sources("github:a;rm").is_err());
+        assert!(parse_sources("bitbucket:alice").is_err());
+        assert!(parse_sources("github:alice github:alice")
+            .unwrap_err()
+            .contains("listed twice"));
+    }
+}
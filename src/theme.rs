@@ -57,6 +57,9 @@ impl Colors {
     /// Disabled/inactive text color
     pub const FG_MUTED: Color = Color::DarkGray;
 
+    /// Text of the screen behind a modal dialog
+    pub const FG_DIMMED: Color = Color::DarkGray;
+
     // -------------------------------------------------------------------------
     // Accent Colors (branding, emphasis)
     // -------------------------------------------------------------------------
@@ -542,6 +545,89 @@ impl UiConstants {
     pub const PAGE_SCROLL_SIZE: usize = 10;
 }
 
+// =============================================================================
+// CHARACTER SET
+// =============================================================================
+
+/// Characters the terminal can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    /// Box drawing, block elements and emoji
+    #[default]
+    Unicode,
+    /// Plain ASCII for the Linux console (vt), serial and IPMI terminals
+    Ascii,
+}
+
+impl Charset {
+    /// Detect from the environment ($TERM and the locale)
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        Self::detect_from(std::env::var("TERM").ok().as_deref(), locale.as_deref())
+    }
+
+    /// Decide from a $TERM value and the effective locale
+    ///
+    /// The Linux console, vt* and dumb terminals get ASCII, as does a
+    /// locale without UTF-8. An unset locale does not count against Unicode.
+    pub fn detect_from(term: Option<&str>, locale: Option<&str>) -> Self {
+        let term = term.unwrap_or("").trim();
+        let plain_term =
+            term.is_empty() || term == "dumb" || term == "linux" || term.starts_with("vt");
+        let plain_locale = locale.is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        });
+        if plain_term || plain_locale {
+            Self::Ascii
+        } else {
+            Self::Unicode
+        }
+    }
+}
+
+/// ASCII replacement for one rendered cell symbol
+///
+/// Returns `None` for symbols that are already ASCII. Box drawing becomes
+/// `+-|`, arrows and marks get a look-alike, and emoji are blanked.
+pub fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let c = symbol.chars().next()?;
+    if symbol.is_ascii() {
+        return None;
+    }
+    let replacement = match c {
+        '─' | '━' | '═' | '┄' | '┈' | '╌' => "-",
+        '│' | '┃' | '║' | '┆' | '┊' | '╎' => "|",
+        '┌' | '┐' | '└' | '┘' | '╭' | '╮' | '╰' | '╯' | '╔' | '╗' | '╚' | '╝' | '┏' | '┓'
+        | '┗' | '┛' | '├' | '┤' | '┬' | '┴' | '┼' | '╠' | '╣' | '╦' | '╩' | '╬' => "+",
+        '█' | '▓' | '▒' | '▉' | '▊' | '▋' | '▌' | '▍' | '▎' | '▏' | '▀' | '▄' | '■' => "#",
+        '░' => ".",
+        '↑' | '▲' | '△' => "^",
+        '↓' | '▼' | '▽' => "v",
+        '←' | '◀' | '◄' => "<",
+        '→' | '▶' | '►' | '▸' | '❯' | '›' => ">",
+        '↵' | '⏎' => "<",
+        '✓' | '✔' | '☑' | '✅' => "+",
+        '✗' | '✘' | '✖' | '☒' | '❌' => "x",
+        '⚠' | '❗' | '❕' | '🚨' => "!",
+        'ℹ' => "i",
+        '•' | '●' | '◆' | '★' | '☆' => "*",
+        '○' | '◯' | '◇' | '□' => "o",
+        '·' | '…' => ".",
+        '–' | '—' | '−' => "-",
+        '«' => "<",
+        '»' => ">",
+        '🔒' => "#",
+        // Emoji and other pictographs
+        '\u{1F000}'..='\u{1FAFF}' | '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' => " ",
+        _ => "?",
+    };
+    Some(replacement)
+}
+
 // =============================================================================
 // TEXT CONSTANTS
 // =============================================================================
@@ -591,4 +677,32 @@ mod tests {
         let _ = Theme::log_style(LogLevel::Error);
         let _ = Theme::severity_color(Severity::Warning);
     }
+
+    #[test]
+    fn test_charset_detection() {
+        let utf8 = Some("en_US.UTF-8");
+        assert_eq!(Charset::detect_from(Some("xterm-256color"), utf8), Charset::Unicode);
+        assert_eq!(Charset::detect_from(Some("xterm-256color"), None), Charset::Unicode);
+        assert_eq!(Charset::detect_from(Some("linux"), utf8), Charset::Ascii);
+        assert_eq!(Charset::detect_from(Some("vt220"), utf8), Charset::Ascii);
+        assert_eq!(Charset::detect_from(Some("dumb"), utf8), Charset::Ascii);
+        assert_eq!(Charset::detect_from(None, utf8), Charset::Ascii);
+        assert_eq!(Charset::detect_from(Some("screen"), Some("C")), Charset::Ascii);
+        assert_eq!(Charset::detect_from(Some("tmux"), Some("C.utf8")), Charset::Unicode);
+    }
+
+    #[test]
+    fn test_ascii_symbols() {
+        assert_eq!(ascii_symbol("a"), None);
+        assert_eq!(ascii_symbol("┌"), Some("+"));
+        assert_eq!(ascii_symbol("─"), Some("-"));
+        assert_eq!(ascii_symbol("║"), Some("|"));
+        assert_eq!(ascii_symbol("█"), Some("#"));
+        assert_eq!(ascii_symbol("↑"), Some("^"));
+        assert_eq!(ascii_symbol("✓"), Some("+"));
+        assert_eq!(ascii_symbol("⚠\u{fe0f}"), Some("!"));
+        assert_eq!(ascii_symbol("🔧"), Some(" "));
+        assert_eq!(ascii_symbol("🔒"), Some("#"));
+        assert_eq!(ascii_symbol("é"), Some("?"));
+    }
 }
@@ -0,0 +1,294 @@
+//! Network and disk throughput during installation
+//!
+//! While pacstrap downloads packages and the chroot phase installs and copies
+//! files, a sampler thread reads `/proc/net/dev` and `/proc/diskstats` once a
+//! second and publishes download speed, bytes written to the target disks and
+//! disk write speed to the Installation screen.
+
+use crate::app::StateHandle;
+use crate::disk::DiskClass;
+use crate::tools::resize::format_size;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Network interface counters
+const PROC_NET_DEV: &str = "/proc/net/dev";
+/// Block device I/O counters
+const PROC_DISKSTATS: &str = "/proc/diskstats";
+/// /proc/diskstats counts 512-byte sectors regardless of the device
+const SECTOR_SIZE: u64 = 512;
+/// Time between two samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Installer phases that download or copy files, by the marker they print
+const TRANSFER_MARKERS: &[&str] = &[
+    "Installing base system",
+    "Configuring system",
+    "Installing packages",
+];
+
+/// Phase markers that end a transfer phase
+const OTHER_MARKERS: &[&str] = &[
+    "Starting Arch Linux installation",
+    "Preparing system",
+    "Starting disk partitioning",
+    "Generating fstab",
+    "Configuring bootloader",
+    "Finalizing installation",
+    "Installation complete",
+];
+
+/// Rates shown next to the progress bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Throughput {
+    /// Bytes per second received over the network
+    pub download_rate: u64,
+    /// Bytes written to the target disks since the phase began
+    pub written: u64,
+    /// Bytes per second written to the target disks
+    pub write_rate: u64,
+    /// Disk write counter when the phase began, from its first sample
+    pub written_at_start: Option<u64>,
+}
+
+impl Throughput {
+    /// One-line summary, e.g. "Down 4.2 MiB/s | Written 1.3 GiB (85.0 MiB/s)"
+    pub fn summary(&self) -> String {
+        format!(
+            "Down {}/s | Written {} ({}/s)",
+            format_size(self.download_rate),
+            format_size(self.written),
+            format_size(self.write_rate)
+        )
+    }
+}
+
+/// Whether an installer line starts (`Some(true)`) or ends (`Some(false)`) a
+/// phase that transfers files; `None` for lines that are not phase markers
+pub fn transfer_phase(line: &str) -> Option<bool> {
+    if TRANSFER_MARKERS.iter().any(|marker| line.contains(marker)) {
+        Some(true)
+    } else if OTHER_MARKERS.iter().any(|marker| line.contains(marker)) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Cumulative counters at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counters {
+    /// Bytes received on all interfaces but loopback
+    pub received: u64,
+    /// Bytes written to the watched disks
+    pub written: u64,
+}
+
+impl Counters {
+    /// Read the current counters; `disks` as for [`parse_diskstats`]
+    pub fn read(disks: &[String]) -> Option<Self> {
+        Some(Self {
+            received: parse_net_dev(&fs::read_to_string(PROC_NET_DEV).ok()?),
+            written: parse_diskstats(&fs::read_to_string(PROC_DISKSTATS).ok()?, disks),
+        })
+    }
+
+    /// Rates between `earlier` and `self`, `elapsed` apart
+    ///
+    /// Counters that went backwards (an interface or disk disappeared) count
+    /// as no traffic.
+    pub fn rates_since(&self, earlier: &Counters, elapsed: Duration) -> (u64, u64) {
+        let millis = elapsed.as_millis().max(1) as u64;
+        let rate = |now: u64, before: u64| now.saturating_sub(before) * 1000 / millis;
+        (
+            rate(self.received, earlier.received),
+            rate(self.written, earlier.written),
+        )
+    }
+}
+
+/// Total bytes received, from the contents of /proc/net/dev
+///
+/// Loopback is left out: it carries no downloads.
+pub fn parse_net_dev(content: &str) -> u64 {
+    content
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(interface, _)| interface.trim() != "lo")
+        .filter_map(|(_, counters)| counters.split_whitespace().next()?.parse::<u64>().ok())
+        .sum()
+}
+
+/// Total bytes written, from the contents of /proc/diskstats
+///
+/// Only the named whole disks (`sda`, `nvme0n1`) are counted so that a
+/// write is not counted again on its partition or device-mapper volume.
+/// With no names, every whole disk is counted.
+pub fn parse_diskstats(content: &str, disks: &[String]) -> u64 {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // major minor name reads ... sectors_written is the 10th field
+            let name = *fields.get(2)?;
+            let watched = if disks.is_empty() {
+                DiskClass::of(name).is_some()
+            } else {
+                disks.iter().any(|disk| disk == name)
+            };
+            if !watched {
+                return None;
+            }
+            fields.get(9)?.parse::<u64>().ok()
+        })
+        .map(|sectors| sectors * SECTOR_SIZE)
+        .sum()
+}
+
+/// Background thread publishing [`Throughput`] to the app state
+///
+/// Rates are only written while `InstallState::throughput` is `Some`, which the
+/// installer output monitor sets during transfer phases. The thread stops
+/// when the sampler is dropped.
+pub struct ThroughputSampler {
+    stop: Arc<AtomicBool>,
+}
+
+impl ThroughputSampler {
+    /// Start sampling; `disks` are the target disk paths or names
+    pub fn start(app_state: StateHandle, disks: &[String]) -> Self {
+        let disks: Vec<String> = disks
+            .iter()
+            .map(|disk| disk.trim().trim_start_matches("/dev/").to_string())
+            .filter(|disk| !disk.is_empty())
+            .collect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            let mut previous: Option<(Counters, Instant)> = None;
+
+            while !stopped.load(Ordering::Relaxed) {
+                let Some(counters) = Counters::read(&disks) else {
+                    // Not Linux or /proc is not mounted: nothing to show
+                    break;
+                };
+                let now = Instant::now();
+
+                let sent = app_state.update(move |state| {
+                    let install = &mut state.install;
+                    let Some(throughput) = install.throughput.as_mut() else {
+                        return;
+                    };
+                    let start = *throughput.written_at_start.get_or_insert(counters.written);
+                    throughput.written = counters.written.saturating_sub(start);
+                    if let Some((earlier, at)) = previous {
+                        let (download, write) = counters.rates_since(&earlier, now - at);
+                        throughput.download_rate = download;
+                        throughput.write_rate = write;
+                        install
+                            .metrics
+                            .add_downloaded(counters.received.saturating_sub(earlier.received));
+                    }
+                });
+                if !sent {
+                    break;
+                }
+
+                previous = Some((counters, now));
+                thread::sleep(SAMPLE_INTERVAL);
+            }
+        });
+
+        Self { stop }
+    }
+}
+
+impl Drop for ThroughputSampler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NET_DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 9000000    1000    0    0    0     0          0         0  9000000    1000    0    0    0     0       0          0
+enp1s0: 52428800   40000    0    0    0     0          0         0  1048576    9000    0    0    0     0       0          0
+ wlan0: 1048576     800    0    0    0     0          0         0    20480     100    0    0    0     0       0          0
+";
+
+    const DISKSTATS: &str = "\
+   7       0 loop0 120 0 2400 10 0 0 0 0 0 20 10 0 0 0 0 0 0
+ 259       0 nvme0n1 9000 10 800000 300 4000 200 2048000 900 0 1200 1200 0 0 0 0 0 0
+ 259       1 nvme0n1p1 100 0 4000 10 50 0 2048 10 0 20 20 0 0 0 0 0 0
+ 259       2 nvme0n1p2 8800 10 790000 290 3950 200 2045952 890 0 1180 1180 0 0 0 0 0 0
+   8       0 sda 500 0 40000 50 10 0 80 5 0 40 55 0 0 0 0 0 0
+ 254       0 dm-0 8700 0 780000 280 3900 0 2040000 880 0 1170 1160 0 0 0 0 0 0
+";
+
+    #[test]
+    fn test_parse_net_dev_skips_loopback() {
+        assert_eq!(parse_net_dev(NET_DEV), 52_428_800 + 1_048_576);
+        assert_eq!(parse_net_dev(""), 0);
+    }
+
+    #[test]
+    fn test_parse_diskstats_counts_whole_disks() {
+        let target = vec!["nvme0n1".to_string()];
+        assert_eq!(parse_diskstats(DISKSTATS, &target), 2_048_000 * SECTOR_SIZE);
+        // Without targets every whole disk counts, partitions and dm never
+        assert_eq!(
+            parse_diskstats(DISKSTATS, &[]),
+            (2_048_000 + 80) * SECTOR_SIZE
+        );
+    }
+
+    #[test]
+    fn test_rates_since() {
+        let earlier = Counters {
+            received: 1_000,
+            written: 10_000,
+        };
+        let later = Counters {
+            received: 3_001_000,
+            written: 5_000,
+        };
+        assert_eq!(
+            later.rates_since(&earlier, Duration::from_millis(1500)),
+            (2_000_000, 0)
+        );
+    }
+
+    #[test]
+    fn test_transfer_phase_markers() {
+        assert_eq!(
+            transfer_phase("[INFO] Phase 5: Installing base system..."),
+            Some(true)
+        );
+        assert_eq!(
+            transfer_phase("Phase 7: Configuring system in chroot..."),
+            Some(true)
+        );
+        assert_eq!(transfer_phase("Phase 6: Generating fstab..."), Some(false));
+        assert_eq!(transfer_phase("downloading linux-6.9.zst"), None);
+
+        let throughput = Throughput {
+            download_rate: 4 * 1024 * 1024,
+            written: 3 << 30,
+            write_rate: 512,
+            written_at_start: None,
+        };
+        assert_eq!(
+            throughput.summary(),
+            "Down 4.0 MiB/s | Written 3.0 GiB (512 B/s)"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Time synchronization and the hardware clock
+//!
+//! With Time Sync (NTP) enabled the new system runs systemd-timesyncd or
+//! chrony. Both use the servers their package configures unless NTP servers
+//! are given, which chroot_config.sh writes to a timesyncd drop-in or to
+//! chrony.conf. The services list names timesyncd's unit for whichever
+//! daemon is chosen, so the Time Sync option and the checklist stay in step.
+//!
+//! The hardware clock keeps UTC unless it is shared with Windows (see
+//! [`HardwareClock`](crate::types::HardwareClock)).
+
+use crate::config::validate_hostname;
+use std::net::IpAddr;
+
+/// Parse NTP servers separated by spaces or commas; empty means the defaults
+pub fn parse_servers(value: &str) -> Result<Vec<String>, String> {
+    let mut servers: Vec<String> = Vec::new();
+    for server in value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|server| !server.is_empty())
+    {
+        if server.parse::<IpAddr>().is_err() && validate_hostname(server).is_err() {
+            return Err(format!(
+                "NTP server '{}' is not a hostname or IP address",
+                server
+            ));
+        }
+        if servers
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(server))
+        {
+            return Err(format!("NTP server '{}' is listed twice", server));
+        }
+        servers.push(server.to_string());
+    }
+    Ok(servers)
+}
+
+/// The option value of a server list, as [`parse_servers`] reads it
+pub fn format_servers(servers: &[String]) -> String {
+    servers.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_servers() {
+        assert_eq!(
+            parse_servers("0.pool.ntp.org, 192.168.1.1  ntp.example.com").unwrap(),
+            ["0.pool.ntp.org", "192.168.1.1", "ntp.example.com"]
+        );
+        assert_eq!(parse_servers("2001:db8::1").unwrap(), ["2001:db8::1"]);
+        assert!(parse_servers("  ").unwrap().is_empty());
+        assert_eq!(
+            parse_servers("time.local ntp_1").unwrap_err(),
+            "NTP server 'ntp_1' is not a hostname or IP address"
+        );
+        assert!(parse_servers("a.org A.org")
+            .unwrap_err()
+            .contains("listed twice"));
+    }
+
+    #[test]
+    fn test_servers_round_trip() {
+        let servers = parse_servers("a.org,b.org").unwrap();
+        assert_eq!(parse_servers(&format_servers(&servers)).unwrap(), servers);
+    }
+}
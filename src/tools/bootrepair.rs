@@ -0,0 +1,999 @@
+//! Bootloader repair
+//!
+//! The most common rescue: the installed system is intact but no longer
+//! starts, because its firmware boot entry vanished (a firmware update,
+//! another OS installed next to it), grub.cfg was never generated, or the
+//! root filesystem was recreated and the loader still points at its old
+//! UUID. [`diagnose`] looks at the system mounted at /mnt (boot mode,
+//! bootloader, ESP and root filesystem), lists what is broken and proposes
+//! the fixes; [`run`] applies them through arch-chroot, logging every
+//! command with its output.
+
+use crate::arch::Arch;
+use crate::error::ArchInstallError;
+use crate::tools::cleanup;
+use crate::types::{BootMode, Bootloader};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Where the repaired system is mounted
+pub const REPAIR_ROOT: &str = cleanup::INSTALL_ROOT;
+
+/// Tool name while the system to repair is being chosen or repaired
+pub const TOOL_NAME: &str = "bootloader_repair";
+
+/// Where the installer mounts the ESP, in the order they are checked
+const ESP_MOUNTPOINTS: &[&str] = &["/efi", "/boot/efi", "/boot"];
+
+/// Firmware entry labels, as `--bootloader-id=GRUB` and bootctl create them
+const GRUB_ENTRY: &str = "GRUB";
+const SYSTEMD_BOOT_ENTRY: &str = "Linux Boot Manager";
+
+/// Core image grub-install writes for BIOS boot
+const GRUB_BIOS_CORE: &str = "boot/grub/i386-pc/core.img";
+
+const GRUB_CONFIG: &str = "/boot/grub/grub.cfg";
+
+/// Lines around the entries os-prober adds to grub.cfg
+const OS_PROBER_BEGIN: &str = "### BEGIN /etc/grub.d/30_os-prober ###";
+const OS_PROBER_END: &str = "### END /etc/grub.d/30_os-prober ###";
+
+/// Menu U-Boot's distro boot reads on aarch64 boards
+const EXTLINUX_CONFIG: &str = "/boot/extlinux/extlinux.conf";
+
+/// Something that keeps the system from booting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Neither GRUB, systemd-boot nor U-Boot is set up
+    NoBootloader,
+    /// UEFI boot without an ESP mounted in the system
+    NoEsp,
+    /// BIOS boot without a disk to install GRUB to
+    NoBootDisk,
+    /// systemd-boot cannot start on BIOS firmware
+    SystemdBootOnBios,
+    /// The firmware has no boot entry for the bootloader
+    MissingEfiEntry(Bootloader),
+    /// The loader binary (GRUB's core image on BIOS) is missing
+    MissingLoader(Bootloader),
+    MissingGrubConfig,
+    MissingLoaderEntries,
+    /// A kernel command line points at a root filesystem that is not there
+    WrongRootUuid {
+        /// File in the system, e.g. /boot/grub/grub.cfg
+        file: String,
+        found: String,
+        expected: String,
+    },
+}
+
+impl Problem {
+    /// Whether the repair cannot go ahead with this problem
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self,
+            Self::NoBootloader | Self::NoEsp | Self::NoBootDisk | Self::SystemdBootOnBios
+        )
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoBootloader => write!(f, "Neither GRUB, systemd-boot nor U-Boot is set up"),
+            Self::NoEsp => write!(
+                f,
+                "No EFI System Partition mounted at {}",
+                ESP_MOUNTPOINTS.join(", ")
+            ),
+            Self::NoBootDisk => write!(f, "Disk of the root filesystem not found"),
+            Self::SystemdBootOnBios => write!(f, "systemd-boot needs UEFI firmware"),
+            Self::MissingEfiEntry(bootloader) => {
+                write!(f, "No firmware boot entry for {}", bootloader)
+            }
+            Self::MissingLoader(bootloader) => write!(f, "{} is not installed", bootloader),
+            Self::MissingGrubConfig => write!(f, "{} is missing", GRUB_CONFIG),
+            Self::MissingLoaderEntries => write!(f, "No systemd-boot loader entries"),
+            Self::WrongRootUuid {
+                file,
+                found,
+                expected,
+            } => write!(f, "{} boots UUID {}, root is {}", file, found, expected),
+        }
+    }
+}
+
+/// A step of the repair
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fix {
+    /// grub-install to the ESP (UEFI) or the disk's boot sector (BIOS)
+    InstallGrub { boot_mode: BootMode, target: String },
+    /// grub-mkconfig, which also picks up the current root UUID
+    GenerateGrubConfig,
+    /// bootctl install, which also creates the firmware entry
+    InstallSystemdBoot { esp: String },
+    /// Write loader/entries/arch.conf for `kernel`
+    WriteLoaderEntry {
+        esp: String,
+        kernel: String,
+        root_uuid: String,
+    },
+    /// Point the kernel command line of a loader entry at the root UUID
+    ReplaceRootUuid {
+        file: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl Fix {
+    /// Command run in the system with arch-chroot, for fixes that are one
+    fn chroot_command(&self) -> Option<Vec<String>> {
+        let args: Vec<&str> = match self {
+            Self::InstallGrub {
+                boot_mode: BootMode::Bios,
+                target,
+            } => vec!["grub-install", "--target=i386-pc", target, "--recheck"],
+            Self::InstallGrub { target, .. } => {
+                return Some(vec![
+                    "grub-install".to_string(),
+                    format!("--target={}", Arch::current().grub_efi_target()),
+                    format!("--efi-directory={}", target),
+                    format!("--bootloader-id={}", GRUB_ENTRY),
+                    "--recheck".to_string(),
+                ])
+            }
+            Self::GenerateGrubConfig => vec!["grub-mkconfig", "-o", GRUB_CONFIG],
+            Self::InstallSystemdBoot { esp } => {
+                return Some(vec![
+                    "bootctl".to_string(),
+                    "install".to_string(),
+                    format!("--esp-path={}", esp),
+                ])
+            }
+            Self::WriteLoaderEntry { .. } | Self::ReplaceRootUuid { .. } => return None,
+        };
+        Some(args.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InstallGrub {
+                boot_mode: BootMode::Bios,
+                target,
+            } => write!(f, "Reinstall GRUB to the boot sector of {}", target),
+            Self::InstallGrub { target, .. } => {
+                write!(f, "Reinstall GRUB to {} and add its firmware entry", target)
+            }
+            Self::GenerateGrubConfig => write!(f, "Regenerate {}", GRUB_CONFIG),
+            Self::InstallSystemdBoot { esp } => write!(
+                f,
+                "Reinstall systemd-boot to {} and add its firmware entry",
+                esp
+            ),
+            Self::WriteLoaderEntry { esp, kernel, .. } => {
+                write!(f, "Write {}/loader/entries/arch.conf for {}", esp, kernel)
+            }
+            Self::ReplaceRootUuid { file, to, .. } => {
+                write!(f, "Point {} at root UUID {}", file, to)
+            }
+        }
+    }
+}
+
+/// What the repair reads from outside the system's files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Probe {
+    /// Firmware this machine booted with
+    pub boot_mode: BootMode,
+    /// `efibootmgr` output, `None` when it could not be read
+    pub efi_entries: Option<String>,
+    /// Contents of /proc/self/mounts
+    pub mounts: String,
+    /// Device mounted at the system's root
+    pub root_device: Option<String>,
+    /// UUID of the root filesystem
+    pub root_uuid: Option<String>,
+    /// Disk the root filesystem is on, for BIOS boot
+    pub boot_disk: Option<String>,
+}
+
+impl Probe {
+    /// Read the firmware, mounts and root filesystem of the system at `root`
+    pub fn read(root: &Path) -> Self {
+        let boot_mode = if Path::new("/sys/firmware/efi").exists() {
+            BootMode::Uefi
+        } else {
+            BootMode::Bios
+        };
+        let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+        let root_device = mount_source(&mounts, &root.to_string_lossy());
+        let root_uuid = root_device
+            .as_deref()
+            .and_then(|device| command_output("blkid", &["-s", "UUID", "-o", "value", device]))
+            .map(|uuid| uuid.trim().to_string())
+            .filter(|uuid| !uuid.is_empty());
+        let boot_disk = root_device.as_deref().and_then(|device| {
+            let json = command_output("lsblk", &["-J", "-s", "-o", "PATH,TYPE", device])?;
+            parse_boot_disk(&json).ok().flatten()
+        });
+        Self {
+            boot_mode,
+            efi_entries: (boot_mode == BootMode::Uefi)
+                .then(|| command_output("efibootmgr", &[]))
+                .flatten(),
+            mounts,
+            root_device,
+            root_uuid,
+            boot_disk,
+        }
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// What was found and what it takes to make the system boot again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub boot_mode: BootMode,
+    pub bootloader: Option<Bootloader>,
+    /// ESP mountpoint in the system, e.g. /efi
+    pub esp: Option<String>,
+    pub root_device: Option<String>,
+    pub root_uuid: Option<String>,
+    pub boot_disk: Option<String>,
+    /// Kernel a new loader entry boots
+    pub kernel: String,
+    pub problems: Vec<Problem>,
+}
+
+impl Diagnosis {
+    /// Whether nothing keeps the fixes from running
+    pub fn can_repair(&self) -> bool {
+        !self.problems.iter().any(Problem::is_blocking)
+    }
+
+    /// Steps that fix the problems found, in the order they run
+    pub fn fixes(&self) -> Vec<Fix> {
+        let mut fixes = Vec::new();
+        if !self.can_repair() {
+            return fixes;
+        }
+        let installs = self.problems.iter().any(|problem| {
+            matches!(
+                problem,
+                Problem::MissingEfiEntry(_) | Problem::MissingLoader(_)
+            )
+        });
+        if installs {
+            fixes.extend(self.install_fix());
+        }
+
+        match self.bootloader {
+            Some(Bootloader::Grub) => {
+                let config_broken = self.problems.iter().any(|problem| {
+                    matches!(
+                        problem,
+                        Problem::MissingGrubConfig | Problem::WrongRootUuid { .. }
+                    )
+                });
+                if config_broken {
+                    fixes.push(Fix::GenerateGrubConfig);
+                }
+            }
+            Some(Bootloader::SystemdBoot | Bootloader::UBoot) => {
+                for problem in &self.problems {
+                    match problem {
+                        Problem::MissingLoaderEntries => {
+                            if let (Some(esp), Some(root_uuid)) = (&self.esp, &self.root_uuid) {
+                                fixes.push(Fix::WriteLoaderEntry {
+                                    esp: esp.clone(),
+                                    kernel: self.kernel.clone(),
+                                    root_uuid: root_uuid.clone(),
+                                });
+                            }
+                        }
+                        Problem::WrongRootUuid {
+                            file,
+                            found,
+                            expected,
+                        } => fixes.push(Fix::ReplaceRootUuid {
+                            file: file.clone(),
+                            from: found.clone(),
+                            to: expected.clone(),
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+            None => {}
+        }
+        fixes
+    }
+
+    /// Steps that reinstall a bootloader nothing was found wrong with
+    pub fn reinstall(&self) -> Vec<Fix> {
+        if !self.can_repair() {
+            return Vec::new();
+        }
+        let mut fixes: Vec<Fix> = self.install_fix().into_iter().collect();
+        if self.bootloader == Some(Bootloader::Grub) {
+            fixes.push(Fix::GenerateGrubConfig);
+        }
+        fixes
+    }
+
+    fn install_fix(&self) -> Option<Fix> {
+        match (self.bootloader?, self.boot_mode) {
+            (Bootloader::Grub, BootMode::Bios) => Some(Fix::InstallGrub {
+                boot_mode: BootMode::Bios,
+                target: self.boot_disk.clone()?,
+            }),
+            (Bootloader::Grub, _) => Some(Fix::InstallGrub {
+                boot_mode: BootMode::Uefi,
+                target: self.esp.clone()?,
+            }),
+            (Bootloader::SystemdBoot, _) => Some(Fix::InstallSystemdBoot {
+                esp: self.esp.clone()?,
+            }),
+            // U-Boot is on the board, only its menu is in the system
+            (Bootloader::UBoot, _) => None,
+        }
+    }
+
+    /// Boot mode, bootloader, ESP and root as one line each
+    pub fn summary(&self) -> Vec<String> {
+        let unknown = || "not found".to_string();
+        vec![
+            format!("Boot mode: {}", self.boot_mode),
+            format!(
+                "Bootloader: {}",
+                self.bootloader
+                    .map_or_else(unknown, |bootloader| bootloader.to_string())
+            ),
+            format!("ESP: {}", self.esp.clone().unwrap_or_else(unknown)),
+            format!(
+                "Root: {} (UUID {})",
+                self.root_device.clone().unwrap_or_else(unknown),
+                self.root_uuid.clone().unwrap_or_else(unknown)
+            ),
+        ]
+    }
+}
+
+/// Device mounted at `target` according to /proc/self/mounts
+pub fn mount_source(proc_mounts: &str, target: &str) -> Option<String> {
+    let target = target.trim_end_matches('/');
+    proc_mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (source, mountpoint) = (fields.next()?, fields.next()?);
+        (mountpoint.trim_end_matches('/') == target).then(|| source.to_string())
+    })
+}
+
+/// Whether `target` is a vfat mount according to /proc/self/mounts
+fn is_vfat_mount(proc_mounts: &str, target: &str) -> bool {
+    proc_mounts.lines().any(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        fields.len() >= 3 && fields[1] == target && fields[2] == "vfat"
+    })
+}
+
+/// Labels of the firmware boot entries in `efibootmgr` output
+pub fn parse_efi_entries(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("Boot")?;
+            let (number, label) = rest.split_at_checked(4)?;
+            if !number.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let label = label.trim_start_matches('*').trim_start();
+            // The device path follows a tab, or two spaces in older versions
+            let label = label.split('\t').next().unwrap_or_default();
+            let label = label.split("  ").next().unwrap_or_default().trim();
+            Some(label.to_string())
+        })
+        .collect()
+}
+
+/// `root=UUID=` values on the kernel command lines of `config`, sorted
+///
+/// Entries os-prober added for other installed systems boot their own root,
+/// so its section of grub.cfg is left out.
+pub fn root_uuids(config: &str) -> Vec<String> {
+    let mut in_os_prober = false;
+    let mut uuids: Vec<String> = config
+        .lines()
+        .filter(|line| match line.trim() {
+            OS_PROBER_BEGIN => {
+                in_os_prober = true;
+                false
+            }
+            OS_PROBER_END => {
+                in_os_prober = false;
+                false
+            }
+            _ => !in_os_prober,
+        })
+        .flat_map(str::split_whitespace)
+        .filter_map(|word| word.trim_matches(['\'', '"']).strip_prefix("root=UUID="))
+        .map(|uuid| uuid.trim_matches(['\'', '"']).to_string())
+        .collect();
+    uuids.sort();
+    uuids.dedup();
+    uuids
+}
+
+/// Disk at the bottom of `lsblk -J -s -o PATH,TYPE <device>`
+///
+/// With `-s` the tree runs from the device down to what it is built on,
+/// so LVM and LUKS are looked through to the partition's disk.
+pub fn parse_boot_disk(json: &str) -> Result<Option<String>, serde_json::Error> {
+    fn find(device: &Value) -> Option<String> {
+        if device["type"] == "disk" {
+            return device["path"].as_str().map(str::to_string);
+        }
+        device["children"].as_array()?.iter().find_map(find)
+    }
+    let value: Value = serde_json::from_str(json)?;
+    Ok(value["blockdevices"]
+        .as_array()
+        .and_then(|devices| devices.iter().find_map(find)))
+}
+
+/// Loader binary on the ESP and firmware entry label of a UEFI bootloader
+fn efi_loader(bootloader: Bootloader, arch: Arch) -> Option<(String, &'static str)> {
+    let suffix = arch.efi_suffix();
+    match bootloader {
+        Bootloader::Grub => Some((format!("EFI/GRUB/grub{}.efi", suffix), GRUB_ENTRY)),
+        Bootloader::SystemdBoot => Some((
+            format!("EFI/systemd/systemd-boot{}.efi", suffix),
+            SYSTEMD_BOOT_ENTRY,
+        )),
+        Bootloader::UBoot => None,
+    }
+}
+
+/// Kernel of the first /boot/vmlinuz-* image, "linux" when there is none
+fn installed_kernel(root: &Path) -> String {
+    let mut kernels: Vec<String> = fs::read_dir(root.join("boot"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_prefix("vmlinuz-").map(str::to_string)
+        })
+        .collect();
+    kernels.sort();
+    kernels
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "linux".to_string())
+}
+
+/// `*.conf` files of the loader entries directory, sorted
+fn loader_entries(dir: &Path) -> Vec<String> {
+    let mut entries: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".conf"))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Problems with the kernel command lines in `path` (shown as `file`)
+fn check_root_uuid(path: &Path, file: &str, root_uuid: Option<&str>, problems: &mut Vec<Problem>) {
+    let (Some(expected), Ok(config)) = (root_uuid, fs::read_to_string(path)) else {
+        return;
+    };
+    problems.extend(
+        root_uuids(&config)
+            .into_iter()
+            .filter(|found| found != expected)
+            .map(|found| Problem::WrongRootUuid {
+                file: file.to_string(),
+                found,
+                expected: expected.to_string(),
+            }),
+    );
+}
+
+/// Find what keeps the system mounted at `root` from booting
+pub fn diagnose(root: &Path, probe: &Probe) -> Diagnosis {
+    let root_text = root.to_string_lossy().trim_end_matches('/').to_string();
+    let esp = ESP_MOUNTPOINTS
+        .iter()
+        .find(|mountpoint| is_vfat_mount(&probe.mounts, &format!("{}{}", root_text, mountpoint)))
+        .map(|mountpoint| mountpoint.to_string());
+    let esp_dir = esp
+        .as_ref()
+        .map(|esp| root.join(esp.trim_start_matches('/')));
+
+    let bootloader = if esp_dir
+        .as_ref()
+        .is_some_and(|dir| dir.join("loader/loader.conf").exists())
+    {
+        Some(Bootloader::SystemdBoot)
+    } else if root.join(EXTLINUX_CONFIG.trim_start_matches('/')).exists() {
+        Some(Bootloader::UBoot)
+    } else if root.join("etc/default/grub").exists() || root.join("boot/grub").is_dir() {
+        Some(Bootloader::Grub)
+    } else {
+        None
+    };
+
+    let mut diagnosis = Diagnosis {
+        boot_mode: probe.boot_mode,
+        bootloader,
+        esp: esp.clone(),
+        root_device: probe.root_device.clone(),
+        root_uuid: probe.root_uuid.clone(),
+        boot_disk: probe.boot_disk.clone(),
+        kernel: installed_kernel(root),
+        problems: Vec::new(),
+    };
+    let problems = &mut diagnosis.problems;
+    let Some(bootloader) = bootloader else {
+        problems.push(Problem::NoBootloader);
+        return diagnosis;
+    };
+    let uefi = probe.boot_mode == BootMode::Uefi;
+    if bootloader == Bootloader::SystemdBoot && !uefi {
+        problems.push(Problem::SystemdBootOnBios);
+        return diagnosis;
+    }
+
+    // The loader itself and the firmware's way to it; U-Boot is on the board
+    match (uefi, &esp_dir) {
+        _ if bootloader == Bootloader::UBoot => {}
+        (true, None) => problems.push(Problem::NoEsp),
+        (true, Some(esp_dir)) => {
+            let Some((binary, label)) = efi_loader(bootloader, Arch::current()) else {
+                return diagnosis;
+            };
+            if !esp_dir.join(binary).exists() {
+                problems.push(Problem::MissingLoader(bootloader));
+            }
+            let entries = probe.efi_entries.as_deref().map(parse_efi_entries);
+            if entries.is_some_and(|entries| !entries.iter().any(|entry| entry == label)) {
+                problems.push(Problem::MissingEfiEntry(bootloader));
+            }
+        }
+        (false, _) => {
+            if probe.boot_disk.is_none() {
+                problems.push(Problem::NoBootDisk);
+            }
+            if !root.join(GRUB_BIOS_CORE).exists() {
+                problems.push(Problem::MissingLoader(bootloader));
+            }
+        }
+    }
+
+    // What the loader boots
+    let root_uuid = probe.root_uuid.as_deref();
+    match bootloader {
+        Bootloader::Grub => {
+            let config = root.join(GRUB_CONFIG.trim_start_matches('/'));
+            if config.exists() {
+                check_root_uuid(&config, GRUB_CONFIG, root_uuid, problems);
+            } else {
+                problems.push(Problem::MissingGrubConfig);
+            }
+        }
+        Bootloader::UBoot => check_root_uuid(
+            &root.join(EXTLINUX_CONFIG.trim_start_matches('/')),
+            EXTLINUX_CONFIG,
+            root_uuid,
+            problems,
+        ),
+        Bootloader::SystemdBoot => {
+            if let (Some(esp), Some(esp_dir)) = (&esp, &esp_dir) {
+                let entries = loader_entries(&esp_dir.join("loader/entries"));
+                if entries.is_empty() {
+                    problems.push(Problem::MissingLoaderEntries);
+                }
+                for entry in entries {
+                    let file = format!("{}/loader/entries/{}", esp.trim_end_matches('/'), entry);
+                    check_root_uuid(
+                        &esp_dir.join("loader/entries").join(&entry),
+                        &file,
+                        root_uuid,
+                        problems,
+                    );
+                }
+            }
+        }
+    }
+    diagnosis
+}
+
+/// Apply `fixes` to the system at `root`, stopping at the first that fails
+///
+/// `log` receives each step, the commands run and their output.
+pub fn run_at(
+    root: &Path,
+    fixes: &[Fix],
+    mut log: impl FnMut(String),
+) -> Result<(), ArchInstallError> {
+    for (number, fix) in fixes.iter().enumerate() {
+        log(format!("[{}/{}] {}", number + 1, fixes.len(), fix));
+        apply(root, fix, &mut log)?;
+    }
+    log(format!(
+        "The system stays mounted at {}; Clean Up Failed Install releases it",
+        root.display()
+    ));
+    Ok(())
+}
+
+/// Apply `fixes` to the system at [`REPAIR_ROOT`]
+pub fn run(fixes: &[Fix], log: impl FnMut(String)) -> Result<(), ArchInstallError> {
+    run_at(Path::new(REPAIR_ROOT), fixes, log)
+}
+
+/// Unmount the system mounted at [`REPAIR_ROOT`] for a repair that cannot
+/// go ahead
+pub fn unmount() -> Result<(), ArchInstallError> {
+    if crate::simulate::is_enabled() {
+        log::info!(
+            "{}",
+            crate::simulate::skipped(&format!("umount -R {}", REPAIR_ROOT))
+        );
+        return Ok(());
+    }
+    let output = Command::new("umount")
+        .args(["-R", REPAIR_ROOT])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("umount", e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(
+            format!("umount -R {}", REPAIR_ROOT),
+            &output,
+        ));
+    }
+    Ok(())
+}
+
+fn apply(root: &Path, fix: &Fix, log: &mut impl FnMut(String)) -> Result<(), ArchInstallError> {
+    if let Some(command) = fix.chroot_command() {
+        let root = root.to_string_lossy();
+        let display = format!("arch-chroot {} {}", root, command.join(" "));
+        if crate::simulate::is_enabled() {
+            log(crate::simulate::skipped(&display));
+            return Ok(());
+        }
+        log(format!("$ {}", display));
+        let output = Command::new("arch-chroot")
+            .arg(root.as_ref())
+            .args(&command)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| ArchInstallError::command_not_run("arch-chroot", e))?;
+        for line in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+        {
+            log(format!("  {}", line));
+        }
+        if !output.status.success() {
+            return Err(ArchInstallError::command_failed(&command[0], &output));
+        }
+        return Ok(());
+    }
+
+    let in_root = |file: &str| root.join(file.trim_start_matches('/'));
+    match fix {
+        Fix::WriteLoaderEntry {
+            esp,
+            kernel,
+            root_uuid,
+        } => {
+            let dir = in_root(esp).join("loader/entries");
+            let entry = format!(
+                "title   Arch Linux\nlinux   /vmlinuz-{kernel}\ninitrd  /initramfs-{kernel}.img\n\
+                 options root=UUID={root_uuid} rw\n"
+            );
+            if crate::simulate::is_enabled() {
+                log(crate::simulate::skipped(&format!(
+                    "write {}/arch.conf",
+                    dir.display()
+                )));
+                return Ok(());
+            }
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("arch.conf"), &entry)?;
+            for line in entry.lines() {
+                log(format!("  {}", line));
+            }
+        }
+        Fix::ReplaceRootUuid { file, from, to } => {
+            let path = in_root(file);
+            if crate::simulate::is_enabled() {
+                log(crate::simulate::skipped(&format!(
+                    "edit {}",
+                    path.display()
+                )));
+                return Ok(());
+            }
+            let config = fs::read_to_string(&path)?;
+            let old = format!("root=UUID={}", from);
+            log(format!(
+                "  {} line(s) changed",
+                config.matches(&old).count()
+            ));
+            fs::write(&path, config.replace(&old, &format!("root=UUID={}", to)))?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT_UUID: &str = "3f1c7a2e-9b7d-4f0a-8d2c-5e6f7a8b9c0d";
+
+    /// A system at `dir` with its ESP mounted at `esp`
+    fn probe(dir: &Path, esp: &str, efibootmgr: &str) -> Probe {
+        let root = dir.to_string_lossy();
+        Probe {
+            boot_mode: BootMode::Uefi,
+            efi_entries: Some(efibootmgr.to_string()),
+            mounts: format!("/dev/sda2 {root} ext4 rw 0 0\n/dev/sda1 {root}{esp} vfat rw 0 0\n"),
+            root_device: Some("/dev/sda2".to_string()),
+            root_uuid: Some(ROOT_UUID.to_string()),
+            boot_disk: Some("/dev/sda".to_string()),
+        }
+    }
+
+    fn write(dir: &Path, file: &str, content: &str) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_parse_efi_entries() {
+        let output = "BootCurrent: 0001\nTimeout: 1 seconds\nBootOrder: 0001,0000\n\
+                      Boot0000* Windows Boot Manager\tHD(1,GPT,...)/File(\\EFI\\Microsoft\\Boot\\bootmgfw.efi)\n\
+                      Boot0001* GRUB  HD(1,GPT,...)/File(\\EFI\\GRUB\\grubx64.efi)\n\
+                      Boot0002  Linux Boot Manager\n";
+        assert_eq!(
+            parse_efi_entries(output),
+            ["Windows Boot Manager", "GRUB", "Linux Boot Manager"]
+        );
+    }
+
+    #[test]
+    fn test_root_uuids_and_boot_disk() {
+        let grub = "\tlinux\t/vmlinuz-linux root=UUID=aaaa rw quiet\n\
+                    \tlinux\t/vmlinuz-linux root=UUID=aaaa rw single\n\
+                    options \"root=UUID=bbbb\" rw\n";
+        assert_eq!(root_uuids(grub), ["aaaa", "bbbb"]);
+        let grub = "\tlinux\t/vmlinuz-linux root=UUID=bbbb rw\n\
+                    ### BEGIN /etc/grub.d/30_os-prober ###\n\
+                    \tlinux\t/vmlinuz-linux root=UUID=cccc rw\n\
+                    ### END /etc/grub.d/30_os-prober ###\n\
+                    \tlinux\t/vmlinuz-linux-lts root=UUID=aaaa rw\n\
+                    \tlinux\t/vmlinuz-linux root=UUID=bbbb rw single\n";
+        assert_eq!(root_uuids(grub), ["aaaa", "bbbb"]);
+        assert!(root_uuids("options root=/dev/mapper/cryptroot rw").is_empty());
+
+        let lsblk = r#"{"blockdevices": [
+            {"path":"/dev/mapper/vg-root", "type":"lvm", "children": [
+                {"path":"/dev/mapper/cryptlvm", "type":"crypt", "children": [
+                    {"path":"/dev/nvme0n1p2", "type":"part", "children": [
+                        {"path":"/dev/nvme0n1", "type":"disk"}
+                    ]}
+                ]}
+            ]}
+        ]}"#;
+        assert_eq!(
+            parse_boot_disk(lsblk).unwrap().as_deref(),
+            Some("/dev/nvme0n1")
+        );
+        assert_eq!(
+            mount_source("/dev/sda2 /mnt btrfs rw 0 0\n", "/mnt/").as_deref(),
+            Some("/dev/sda2")
+        );
+    }
+
+    #[test]
+    fn test_grub_without_entry_or_config() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "etc/default/grub", "GRUB_TIMEOUT=5\n");
+        write(dir.path(), "efi/EFI/GRUB/grubx64.efi", "");
+        let diagnosis = diagnose(
+            dir.path(),
+            &probe(dir.path(), "/efi", "Boot0000* Windows Boot Manager\n"),
+        );
+
+        assert_eq!(diagnosis.bootloader, Some(Bootloader::Grub));
+        assert_eq!(diagnosis.esp.as_deref(), Some("/efi"));
+        assert_eq!(
+            diagnosis.problems,
+            [
+                Problem::MissingEfiEntry(Bootloader::Grub),
+                Problem::MissingGrubConfig,
+            ]
+        );
+        assert_eq!(
+            diagnosis.fixes(),
+            [
+                Fix::InstallGrub {
+                    boot_mode: BootMode::Uefi,
+                    target: "/efi".to_string(),
+                },
+                Fix::GenerateGrubConfig,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grub_config_with_old_root_uuid() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "etc/default/grub", "");
+        write(dir.path(), "boot/efi/EFI/GRUB/grubx64.efi", "");
+        write(
+            dir.path(),
+            "boot/grub/grub.cfg",
+            "linux /vmlinuz-linux root=UUID=0000-old rw\n",
+        );
+        let diagnosis = diagnose(
+            dir.path(),
+            &probe(dir.path(), "/boot/efi", "Boot0001* GRUB\n"),
+        );
+
+        assert_eq!(
+            diagnosis.problems,
+            [Problem::WrongRootUuid {
+                file: GRUB_CONFIG.to_string(),
+                found: "0000-old".to_string(),
+                expected: ROOT_UUID.to_string(),
+            }]
+        );
+        assert_eq!(diagnosis.fixes(), [Fix::GenerateGrubConfig]);
+    }
+
+    #[test]
+    fn test_systemd_boot_entries_are_fixed_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "boot/loader/loader.conf", "default arch.conf\n");
+        write(dir.path(), "boot/EFI/systemd/systemd-bootx64.efi", "");
+        write(dir.path(), "boot/vmlinuz-linux-lts", "");
+        write(
+            dir.path(),
+            "boot/loader/entries/arch.conf",
+            "title Arch Linux\noptions root=UUID=0000-old rw quiet\n",
+        );
+        let diagnosis = diagnose(dir.path(), &probe(dir.path(), "/boot", ""));
+
+        assert_eq!(diagnosis.bootloader, Some(Bootloader::SystemdBoot));
+        assert_eq!(diagnosis.kernel, "linux-lts");
+        assert_eq!(
+            diagnosis.problems,
+            [
+                Problem::MissingEfiEntry(Bootloader::SystemdBoot),
+                Problem::WrongRootUuid {
+                    file: "/boot/loader/entries/arch.conf".to_string(),
+                    found: "0000-old".to_string(),
+                    expected: ROOT_UUID.to_string(),
+                },
+            ]
+        );
+        let fixes = diagnosis.fixes();
+        assert_eq!(
+            fixes[0],
+            Fix::InstallSystemdBoot {
+                esp: "/boot".to_string()
+            }
+        );
+
+        // The entry is edited without arch-chroot
+        let mut log = Vec::new();
+        run_at(dir.path(), &fixes[1..], |line| log.push(line)).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("boot/loader/entries/arch.conf")).unwrap(),
+            format!(
+                "title Arch Linux\noptions root=UUID={} rw quiet\n",
+                ROOT_UUID
+            )
+        );
+        assert!(log[0].starts_with("[1/1] Point /boot/loader/entries/arch.conf"));
+    }
+
+    #[test]
+    fn test_missing_loader_entry_is_written() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "efi/loader/loader.conf", "");
+        write(dir.path(), "efi/EFI/systemd/systemd-bootx64.efi", "");
+        let diagnosis = diagnose(
+            dir.path(),
+            &probe(dir.path(), "/efi", "Boot0003* Linux Boot Manager\n"),
+        );
+        assert_eq!(diagnosis.problems, [Problem::MissingLoaderEntries]);
+
+        run_at(dir.path(), &diagnosis.fixes(), |_| {}).unwrap();
+        let entry = fs::read_to_string(dir.path().join("efi/loader/entries/arch.conf")).unwrap();
+        assert!(entry.contains("linux   /vmlinuz-linux\n"));
+        assert!(entry.contains(&format!("options root=UUID={} rw", ROOT_UUID)));
+        assert!(diagnose(
+            dir.path(),
+            &probe(dir.path(), "/efi", "Boot0003* Linux Boot Manager\n")
+        )
+        .problems
+        .is_empty());
+    }
+
+    #[test]
+    fn test_blocking_problems_propose_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "etc/default/grub", "");
+        let mut without_esp = probe(dir.path(), "/efi", "");
+        without_esp.mounts = format!("/dev/sda2 {} ext4 rw 0 0\n", dir.path().display());
+        let diagnosis = diagnose(dir.path(), &without_esp);
+        assert!(diagnosis.problems.contains(&Problem::NoEsp));
+        assert!(!diagnosis.can_repair());
+        assert!(diagnosis.fixes().is_empty());
+        assert!(diagnosis.reinstall().is_empty());
+
+        let empty = tempfile::tempdir().unwrap();
+        let diagnosis = diagnose(empty.path(), &probe(empty.path(), "/efi", ""));
+        assert_eq!(diagnosis.problems, [Problem::NoBootloader]);
+    }
+
+    #[test]
+    fn test_bios_grub_goes_to_the_root_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "etc/default/grub", "");
+        write(dir.path(), "boot/grub/grub.cfg", "");
+        let bios = Probe {
+            boot_mode: BootMode::Bios,
+            efi_entries: None,
+            ..probe(dir.path(), "/efi", "")
+        };
+        let diagnosis = diagnose(dir.path(), &bios);
+        assert_eq!(
+            diagnosis.problems,
+            [Problem::MissingLoader(Bootloader::Grub)]
+        );
+        assert_eq!(
+            diagnosis.fixes(),
+            [Fix::InstallGrub {
+                boot_mode: BootMode::Bios,
+                target: "/dev/sda".to_string(),
+            }]
+        );
+        let fix: Fix =
+            serde_json::from_str(&serde_json::to_string(&diagnosis.fixes()[0]).unwrap()).unwrap();
+        assert_eq!(
+            fix.chroot_command().unwrap(),
+            ["grub-install", "--target=i386-pc", "/dev/sda", "--recheck"]
+        );
+    }
+}
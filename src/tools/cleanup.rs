@@ -0,0 +1,468 @@
+//! Cleanup after a failed installation
+//!
+//! A run that dies half-way leaves the target busy: filesystems mounted under
+//! /mnt, swap enabled, LVM volume groups active, LUKS mappings open and RAID
+//! arrays assembled. Repartitioning fails until all of that is released, so
+//! this finds the leftovers and tears them down in dependency order:
+//!
+//! 1. swap (it may live on LVM or LUKS)
+//! 2. mounts under /mnt, deepest first
+//! 3. LVM volume groups
+//! 4. LUKS mappings
+//! 5. RAID arrays
+//!
+//! Stacks the order does not fit (LVM inside LUKS inside RAID, ...) are
+//! handled by retrying the steps that failed once the others are released.
+//!
+//! Only storage of the installation is touched: the disks it was given and
+//! those with something mounted under /mnt, with everything built on them.
+//! Swap, volume groups, mappings and arrays of other disks stay up.
+
+use crate::error::ArchInstallError;
+use serde_json::Value;
+use std::fmt;
+use std::process::{Command, Stdio};
+
+/// Where the installer mounts the target system
+pub const INSTALL_ROOT: &str = "/mnt";
+
+/// Something a failed installation left active
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Leftover {
+    /// Active swap device
+    Swap(String),
+    /// Mount point under the installation root
+    Mount(String),
+    /// LVM volume group with active logical volumes
+    VolumeGroup(String),
+    /// Open LUKS mapping, by name
+    Luks(String),
+    /// Assembled mdadm array, e.g. /dev/md0
+    RaidArray(String),
+}
+
+impl Leftover {
+    /// Position in the teardown order
+    fn stage(&self) -> u8 {
+        match self {
+            Self::Swap(_) => 0,
+            Self::Mount(_) => 1,
+            Self::VolumeGroup(_) => 2,
+            Self::Luks(_) => 3,
+            Self::RaidArray(_) => 4,
+        }
+    }
+
+    /// Program and arguments that release this leftover
+    pub fn command(&self) -> (&'static str, Vec<String>) {
+        match self {
+            Self::Swap(device) => ("swapoff", vec![device.clone()]),
+            Self::Mount(path) => ("umount", vec![path.clone()]),
+            Self::VolumeGroup(vg) => ("vgchange", vec!["-an".to_string(), vg.clone()]),
+            Self::Luks(name) => ("cryptsetup", vec!["close".to_string(), name.clone()]),
+            Self::RaidArray(device) => ("mdadm", vec!["--stop".to_string(), device.clone()]),
+        }
+    }
+}
+
+impl fmt::Display for Leftover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Swap(device) => write!(f, "Swap on {}", device),
+            Self::Mount(path) => write!(f, "Mount {}", path),
+            Self::VolumeGroup(vg) => write!(f, "LVM volume group {}", vg),
+            Self::Luks(name) => write!(f, "LUKS mapping {}", name),
+            Self::RaidArray(device) => write!(f, "RAID array {}", device),
+        }
+    }
+}
+
+/// Mount points at or below `root` from /proc/mounts, deepest first
+pub fn parse_mounts(proc_mounts: &str, root: &str) -> Vec<String> {
+    let nested = format!("{}/", root.trim_end_matches('/'));
+    let mut mounts: Vec<String> = Vec::new();
+    for line in proc_mounts.lines() {
+        let Some(target) = line.split_whitespace().nth(1) else {
+            continue;
+        };
+        // Spaces and tabs in paths are octal-escaped
+        let target = target
+            .replace("\\040", " ")
+            .replace("\\011", "\t")
+            .replace("\\134", "\\");
+        if (target == root || target.starts_with(&nested)) && !mounts.contains(&target) {
+            mounts.push(target);
+        }
+    }
+    mounts.sort_by(|a, b| {
+        let depth = |path: &str| path.matches('/').count();
+        depth(b).cmp(&depth(a)).then_with(|| b.cmp(a))
+    });
+    mounts
+}
+
+/// Devices of the installation from `lsblk -J -o PATH,MOUNTPOINTS`
+///
+/// The installation's disks are `disks` and those with something mounted at
+/// or below [`INSTALL_ROOT`]; the result holds them and every partition,
+/// array, mapping and logical volume stacked on them.
+pub fn parse_install_devices(lsblk: &str, disks: &[String]) -> Vec<String> {
+    fn walk(device: &Value, paths: &mut Vec<String>, mounted: &mut bool) {
+        if let Some(path) = device["path"].as_str() {
+            paths.push(path.to_string());
+        }
+        let mountpoints = device["mountpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(std::iter::once(&device["mountpoint"]));
+        for mountpoint in mountpoints.filter_map(Value::as_str) {
+            let nested = format!("{}/", INSTALL_ROOT);
+            *mounted |= mountpoint == INSTALL_ROOT || mountpoint.starts_with(&nested);
+        }
+        for child in device["children"].as_array().into_iter().flatten() {
+            walk(child, paths, mounted);
+        }
+    }
+
+    let tree: Value = serde_json::from_str(lsblk).unwrap_or_default();
+    let mut scope: Vec<String> = Vec::new();
+    for disk in tree["blockdevices"].as_array().into_iter().flatten() {
+        let (mut paths, mut mounted) = (Vec::new(), false);
+        walk(disk, &mut paths, &mut mounted);
+        if mounted || paths.first().is_some_and(|path| disks.contains(path)) {
+            for path in paths {
+                if !scope.contains(&path) {
+                    scope.push(path);
+                }
+            }
+        }
+    }
+    scope
+}
+
+/// Whether a device (or swap file) belongs to the installation
+fn in_scope(device: &str, scope: &[String]) -> bool {
+    device.starts_with(&format!("{}/", INSTALL_ROOT)) || scope.iter().any(|path| path == device)
+}
+
+/// Swap devices and files of the installation from /proc/swaps
+pub fn parse_swaps(proc_swaps: &str, scope: &[String]) -> Vec<String> {
+    proc_swaps
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|device| in_scope(device, scope))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Volume groups with a logical volume in `scope`, once each, from
+/// `lvs --noheadings -o vg_name,lv_dm_path`
+pub fn parse_volume_groups(lvs: &str, scope: &[String]) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+    for line in lvs.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(path)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if in_scope(path, scope) && !groups.iter().any(|g| g == name) {
+            groups.push(name.to_string());
+        }
+    }
+    groups
+}
+
+/// Names of the mappings in `scope` from `dmsetup ls --target crypt`
+pub fn parse_crypt_mappings(dmsetup: &str, scope: &[String]) -> Vec<String> {
+    dmsetup
+        .lines()
+        .filter(|line| !line.starts_with("No devices found"))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| in_scope(&format!("/dev/mapper/{}", name), scope))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Array devices in `scope` from /proc/mdstat, active or not
+pub fn parse_mdstat(mdstat: &str, scope: &[String]) -> Vec<String> {
+    mdstat
+        .lines()
+        .filter_map(|line| line.split_once(" : "))
+        .filter(|(name, _)| name.starts_with("md"))
+        .map(|(name, _)| format!("/dev/{}", name.trim()))
+        .filter(|device| in_scope(device, scope))
+        .collect()
+}
+
+/// Find everything a failed installation left behind, in teardown order
+///
+/// `disks` are the disks the installation was given, if known; disks with
+/// something mounted under [`INSTALL_ROOT`] are found on their own.
+pub fn detect(disks: &[String]) -> Vec<Leftover> {
+    let read = |path: &str| std::fs::read_to_string(path).unwrap_or_default();
+    // A missing tool means nothing of its kind can be active
+    let run = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default()
+    };
+
+    let mut scope = parse_install_devices(&run("lsblk", &["-J", "-o", "PATH,MOUNTPOINTS"]), disks);
+    // /proc/swaps may name a mapping by its /dev/dm-N node
+    let canonical: Vec<String> = scope
+        .iter()
+        .filter_map(|path| std::fs::canonicalize(path).ok())
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    scope.extend(canonical);
+
+    let mut leftovers: Vec<Leftover> = parse_swaps(&read("/proc/swaps"), &scope)
+        .into_iter()
+        .map(Leftover::Swap)
+        .collect();
+    leftovers.extend(
+        parse_mounts(&read("/proc/self/mounts"), INSTALL_ROOT)
+            .into_iter()
+            .map(Leftover::Mount),
+    );
+    leftovers.extend(
+        parse_volume_groups(
+            &run(
+                "lvs",
+                &[
+                    "--noheadings",
+                    "-o",
+                    "vg_name,lv_dm_path",
+                    "-S",
+                    "lv_active=active",
+                ],
+            ),
+            &scope,
+        )
+        .into_iter()
+        .map(Leftover::VolumeGroup),
+    );
+    leftovers.extend(
+        parse_crypt_mappings(&run("dmsetup", &["ls", "--target", "crypt"]), &scope)
+            .into_iter()
+            .map(Leftover::Luks),
+    );
+    leftovers.extend(
+        parse_mdstat(&read("/proc/mdstat"), &scope)
+            .into_iter()
+            .map(Leftover::RaidArray),
+    );
+
+    // Stable: mounts keep their deepest-first order
+    leftovers.sort_by_key(Leftover::stage);
+    leftovers
+}
+
+/// Release the leftovers in order, retrying failed steps once
+///
+/// Every command and its output is passed to `log`. Errors when something
+/// is still active after the retry.
+pub fn run(leftovers: &[Leftover], mut log: impl FnMut(String)) -> Result<(), ArchInstallError> {
+    let mut pending: Vec<&Leftover> = leftovers.iter().collect();
+
+    for attempt in 0..2 {
+        if attempt > 0 {
+            log(format!("Retrying {} step(s)...", pending.len()));
+        }
+        let before = pending.len();
+        pending.retain(|leftover| !release(leftover, &mut log));
+        // Nothing was released: another pass would fail the same way
+        if pending.is_empty() || pending.len() == before {
+            break;
+        }
+    }
+
+    if pending.is_empty() {
+        Ok(())
+    } else {
+        let names: Vec<String> = pending.iter().map(|l| l.to_string()).collect();
+        Err(ArchInstallError::system(format!(
+            "Still active: {}",
+            names.join(", ")
+        )))
+    }
+}
+
+/// Run the command for one leftover, returning whether it succeeded
+fn release(leftover: &Leftover, log: &mut impl FnMut(String)) -> bool {
+    let (program, args) = leftover.command();
+    let command = format!("{} {}", program, args.join(" "));
+    if crate::simulate::is_enabled() {
+        log(crate::simulate::skipped(&command));
+        return true;
+    }
+    log(format!("$ {}", command));
+
+    match Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) => {
+            for line in String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .chain(String::from_utf8_lossy(&output.stderr).lines())
+            {
+                log(line.to_string());
+            }
+            output.status.success()
+        }
+        Err(e) => {
+            log(format!("Failed to run {}: {}", program, e));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mounts_deepest_first() {
+        let mounts = "\
+proc /proc proc rw,nosuid 0 0
+airootfs / overlay rw 0 0
+/dev/mapper/cryptroot /mnt ext4 rw 0 0
+/dev/sda1 /mnt/boot vfat rw 0 0
+/dev/sda4 /mnt/home ext4 rw 0 0
+/dev/sdb1 /mnt/home/user/My\\040Files ext4 rw 0 0
+/dev/sdc1 /mnt2 ext4 rw 0 0
+";
+        assert_eq!(
+            parse_mounts(mounts, "/mnt"),
+            vec!["/mnt/home/user/My Files", "/mnt/home", "/mnt/boot", "/mnt"]
+        );
+        assert!(parse_mounts("airootfs / overlay rw 0 0\n", "/mnt").is_empty());
+    }
+
+    /// sda holds the installation, LVM on LUKS; sdb is another disk with a
+    /// volume group and a LUKS mapping of its own; sdc and sdd are a RAID
+    const LSBLK: &str = r#"{"blockdevices": [
+        {"path": "/dev/sda", "mountpoints": [null], "children": [
+            {"path": "/dev/sda1", "mountpoints": ["/mnt/boot"]},
+            {"path": "/dev/sda2", "mountpoints": [null], "children": [
+                {"path": "/dev/mapper/cryptlvm", "mountpoints": [null], "children": [
+                    {"path": "/dev/mapper/vg0-root", "mountpoints": ["/mnt"]},
+                    {"path": "/dev/mapper/vg0-swap", "mountpoints": ["[SWAP]"]}
+                ]}
+            ]}
+        ]},
+        {"path": "/dev/sdb", "mountpoints": [null], "children": [
+            {"path": "/dev/sdb1", "mountpoints": [null], "children": [
+                {"path": "/dev/mapper/crypthome", "mountpoints": [null], "children": [
+                    {"path": "/dev/mapper/data-home", "mountpoints": ["/home"]},
+                    {"path": "/dev/mapper/data-swap", "mountpoints": ["[SWAP]"]}
+                ]}
+            ]}
+        ]},
+        {"path": "/dev/sdc", "mountpoints": [null], "children": [
+            {"path": "/dev/md127", "mountpoints": [null]}
+        ]},
+        {"path": "/dev/sdd", "mountpoints": [null], "children": [
+            {"path": "/dev/md127", "mountpoints": [null]}
+        ]},
+        {"path": "/dev/zram0", "mountpoints": ["[SWAP]"]}
+    ]}"#;
+
+    #[test]
+    fn test_install_devices() {
+        let scope = parse_install_devices(LSBLK, &[]);
+        assert!(scope.contains(&"/dev/mapper/vg0-swap".to_string()));
+        assert!(!scope.contains(&"/dev/sdb1".to_string()));
+        assert!(!scope.contains(&"/dev/md127".to_string()));
+
+        // A disk the installation was given counts before anything is mounted
+        let scope = parse_install_devices(LSBLK, &["/dev/sdc".to_string()]);
+        assert_eq!(scope.iter().filter(|p| *p == "/dev/md127").count(), 1);
+        assert!(parse_install_devices("not json", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_swaps_of_the_installation() {
+        let swaps = "\
+Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority
+/dev/zram0                              partition\t4038652\t\t0\t\t100
+/dev/mapper/vg0-swap                    partition\t8388604\t\t0\t\t-2
+/dev/mapper/data-swap                   partition\t8388604\t\t0\t\t-3
+/mnt/swapfile                           file\t\t2097148\t\t0\t\t-4
+";
+        let scope = parse_install_devices(LSBLK, &[]);
+        assert_eq!(
+            parse_swaps(swaps, &scope),
+            vec!["/dev/mapper/vg0-swap", "/mnt/swapfile"]
+        );
+    }
+
+    #[test]
+    fn test_storage_of_other_disks_survives() {
+        let scope = parse_install_devices(LSBLK, &[]);
+        assert_eq!(
+            parse_volume_groups(
+                "  vg0 /dev/mapper/vg0-root\n  vg0 /dev/mapper/vg0-swap\n  data /dev/mapper/data-home\n",
+                &scope
+            ),
+            vec!["vg0"]
+        );
+        assert_eq!(
+            parse_crypt_mappings("cryptlvm\t(254:0)\ncrypthome\t(254:3)\n", &scope),
+            vec!["cryptlvm"]
+        );
+        assert!(parse_crypt_mappings("No devices found\n", &scope).is_empty());
+
+        let mdstat = "\
+Personalities : [raid1]
+md127 : active raid1 sdd1[1] sdc1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+
+md1 : inactive sde2[0](S)
+unused devices: <none>
+";
+        assert!(parse_mdstat(mdstat, &scope).is_empty());
+        let raid = parse_install_devices(LSBLK, &["/dev/sdc".to_string()]);
+        assert_eq!(parse_mdstat(mdstat, &raid), vec!["/dev/md127"]);
+    }
+
+    #[test]
+    fn test_teardown_order_and_commands() {
+        let mut leftovers = [
+            Leftover::RaidArray("/dev/md0".to_string()),
+            Leftover::Luks("cryptlvm".to_string()),
+            Leftover::Mount("/mnt/boot".to_string()),
+            Leftover::VolumeGroup("vg0".to_string()),
+            Leftover::Mount("/mnt".to_string()),
+            Leftover::Swap("/dev/mapper/vg0-swap".to_string()),
+        ];
+        leftovers.sort_by_key(Leftover::stage);
+
+        let commands: Vec<String> = leftovers
+            .iter()
+            .map(|l| {
+                let (program, args) = l.command();
+                format!("{} {}", program, args.join(" "))
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "swapoff /dev/mapper/vg0-swap",
+                "umount /mnt/boot",
+                "umount /mnt",
+                "vgchange -an vg0",
+                "cryptsetup close cryptlvm",
+                "mdadm --stop /dev/md0",
+            ]
+        );
+        assert_eq!(leftovers[4].to_string(), "LUKS mapping cryptlvm");
+    }
+}
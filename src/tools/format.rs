@@ -0,0 +1,264 @@
+//! Partition formatting options
+//!
+//! `tools disk format` and the Format Partition dialog take a filesystem and
+//! the flags that only make sense for it: reserved blocks on ext4, the
+//! metadata profile on btrfs and transparent compression on F2FS.
+//! [`FormatOptions`] rejects flags meant for another filesystem and labels
+//! the filesystem cannot store before format_partition.sh runs mkfs.
+
+use crate::script_env::ScriptArgs;
+use crate::types::{BtrfsProfile, FormatFilesystem};
+use serde::{Deserialize, Serialize};
+
+/// Highest share of an ext4 filesystem that may be reserved for root
+pub const MAX_EXT4_RESERVED: u8 = 50;
+
+/// Everything needed to format one partition
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatOptions {
+    pub device: String,
+    pub filesystem: FormatFilesystem,
+    pub label: Option<String>,
+    /// Percentage of blocks reserved for root (ext4 only, mkfs default 5)
+    pub ext4_reserved: Option<u8>,
+    /// Metadata profile (btrfs only, mkfs default dup)
+    pub btrfs_metadata: Option<BtrfsProfile>,
+    /// Enable transparent compression (F2FS only)
+    pub f2fs_compression: bool,
+}
+
+impl FormatOptions {
+    /// Read the Format Partition dialog and validate the result
+    ///
+    /// Parameter order: device, filesystem, label, ext4_reserved,
+    /// btrfs_metadata, f2fs_compression. An unknown filesystem or metadata
+    /// profile is an error rather than a silent default.
+    pub fn from_dialog(params: &[String]) -> Result<Self, String> {
+        let param = |index: usize| params.get(index).filter(|value| !value.is_empty()).cloned();
+        let filesystem = param(1).ok_or_else(|| "A filesystem is required".to_string())?;
+        let filesystem = filesystem
+            .parse()
+            .map_err(|_| format!("Unknown filesystem '{}'", filesystem))?;
+        let ext4_reserved = param(3)
+            .map(|reserved| reserved.trim().parse::<u8>())
+            .transpose()
+            .map_err(|_| "Reserved blocks must be a whole percentage".to_string())?;
+        // "default" leaves the metadata profile to mkfs.btrfs
+        let btrfs_metadata = param(4)
+            .filter(|profile| profile != "default")
+            .map(|profile| {
+                profile
+                    .parse()
+                    .map_err(|_| format!("Unknown metadata profile '{}'", profile))
+            })
+            .transpose()?;
+        let options = FormatOptions {
+            device: param(0).unwrap_or_default(),
+            filesystem,
+            label: param(2),
+            ext4_reserved,
+            btrfs_metadata,
+            f2fs_compression: param(5).as_deref() == Some("true"),
+        };
+        options.validate().map(|_| options)
+    }
+
+    /// Check the device, label and per-filesystem flags
+    pub fn validate(&self) -> Result<(), String> {
+        if self.device.trim().is_empty() {
+            return Err("A partition is required".to_string());
+        }
+        if let Some(ref label) = self.label {
+            let limit = label_limit(self.filesystem);
+            if label.chars().count() > limit {
+                return Err(format!(
+                    "{} labels hold at most {} characters",
+                    self.filesystem, limit
+                ));
+            }
+            if label.chars().any(char::is_control) {
+                return Err("The label contains control characters".to_string());
+            }
+        }
+        if let Some(reserved) = self.ext4_reserved {
+            if self.filesystem != FormatFilesystem::Ext4 {
+                return Err(format!(
+                    "Reserved blocks only apply to ext4, not {}",
+                    self.filesystem
+                ));
+            }
+            if reserved > MAX_EXT4_RESERVED {
+                return Err(format!(
+                    "Reserved blocks must be 0-{} percent",
+                    MAX_EXT4_RESERVED
+                ));
+            }
+        }
+        if self.btrfs_metadata.is_some() && self.filesystem != FormatFilesystem::Btrfs {
+            return Err(format!(
+                "A metadata profile only applies to btrfs, not {}",
+                self.filesystem
+            ));
+        }
+        if self.f2fs_compression && self.filesystem != FormatFilesystem::F2fs {
+            return Err(format!(
+                "Compression only applies to f2fs, not {}",
+                self.filesystem
+            ));
+        }
+        Ok(())
+    }
+
+    /// Arguments of format_partition.sh
+    pub fn script_args(&self) -> ScriptArgs {
+        self.append_flags(ScriptArgs::new().value("--device", &self.device))
+    }
+
+    /// Append the filesystem, label and per-filesystem flags to `args`
+    pub fn append_flags(&self, args: ScriptArgs) -> ScriptArgs {
+        args.value("--filesystem", self.filesystem)
+            .optional("--label", self.label.as_ref())
+            .optional("--ext4-reserved", self.ext4_reserved)
+            .optional("--btrfs-metadata", self.btrfs_metadata)
+            .switch("--f2fs-compression", self.f2fs_compression)
+    }
+
+    /// The flags in effect, for the confirmation dialog
+    pub fn describe_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(ref label) = self.label {
+            flags.push(format!("label '{}'", label));
+        }
+        if let Some(reserved) = self.ext4_reserved {
+            flags.push(format!("{}% reserved blocks", reserved));
+        }
+        if let Some(profile) = self.btrfs_metadata {
+            flags.push(format!("{} metadata", profile));
+        }
+        if self.f2fs_compression {
+            flags.push("compression".to_string());
+        }
+        flags
+    }
+}
+
+/// Longest label each filesystem stores, in characters
+pub fn label_limit(filesystem: FormatFilesystem) -> usize {
+    match filesystem {
+        FormatFilesystem::Ext4 => 16,
+        FormatFilesystem::Xfs => 12,
+        FormatFilesystem::Btrfs => 255,
+        FormatFilesystem::F2fs => 512,
+        FormatFilesystem::Fat32 | FormatFilesystem::Exfat => 11,
+        FormatFilesystem::Ntfs => 128,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(filesystem: FormatFilesystem) -> FormatOptions {
+        FormatOptions {
+            device: "/dev/sda2".to_string(),
+            filesystem,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rejects_flags_of_another_filesystem() {
+        let ext4 = FormatOptions {
+            ext4_reserved: Some(1),
+            ..options(FormatFilesystem::Ext4)
+        };
+        assert_eq!(ext4.validate(), Ok(()));
+        let xfs = FormatOptions {
+            ext4_reserved: Some(1),
+            ..options(FormatFilesystem::Xfs)
+        };
+        assert!(xfs.validate().unwrap_err().contains("only apply to ext4"));
+        let exfat = FormatOptions {
+            f2fs_compression: true,
+            ..options(FormatFilesystem::Exfat)
+        };
+        assert!(exfat
+            .validate()
+            .unwrap_err()
+            .contains("only applies to f2fs"));
+        let too_many = FormatOptions {
+            ext4_reserved: Some(60),
+            ..options(FormatFilesystem::Ext4)
+        };
+        assert!(too_many.validate().is_err());
+        let long_label = FormatOptions {
+            label: Some("USB-BACKUP-01".to_string()),
+            ..options(FormatFilesystem::Exfat)
+        };
+        assert_eq!(
+            long_label.validate(),
+            Err("exfat labels hold at most 11 characters".to_string())
+        );
+    }
+
+    #[test]
+    fn test_script_args_carry_the_flags() {
+        let btrfs = FormatOptions {
+            label: Some("data".to_string()),
+            btrfs_metadata: Some(BtrfsProfile::Single),
+            ..options(FormatFilesystem::Btrfs)
+        };
+        assert_eq!(
+            btrfs.script_args().as_slice(),
+            [
+                "--device",
+                "/dev/sda2",
+                "--filesystem",
+                "btrfs",
+                "--label",
+                "data",
+                "--btrfs-metadata",
+                "single",
+            ]
+        );
+        let f2fs = FormatOptions {
+            f2fs_compression: true,
+            ..options(FormatFilesystem::F2fs)
+        };
+        assert_eq!(
+            f2fs.script_args().as_slice(),
+            [
+                "--device",
+                "/dev/sda2",
+                "--filesystem",
+                "f2fs",
+                "--f2fs-compression",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dialog_rejects_an_unknown_filesystem() {
+        let params = |filesystem: &str, profile: &str| -> Vec<String> {
+            ["/dev/sda2", filesystem, "", "", profile, "false"]
+                .map(String::from)
+                .to_vec()
+        };
+        assert_eq!(
+            FormatOptions::from_dialog(&params("btrfs", "default")),
+            Ok(options(FormatFilesystem::Btrfs))
+        );
+        assert_eq!(
+            FormatOptions::from_dialog(&params("zfs", "default")),
+            Err("Unknown filesystem 'zfs'".to_string())
+        );
+        assert_eq!(
+            FormatOptions::from_dialog(&params("", "default")),
+            Err("A filesystem is required".to_string())
+        );
+        assert_eq!(
+            FormatOptions::from_dialog(&params("btrfs", "raid1")),
+            Err("Unknown metadata profile 'raid1'".to_string())
+        );
+    }
+}
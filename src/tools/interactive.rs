@@ -0,0 +1,99 @@
+//! Running tools interactively
+//!
+//! Some tools menu entries wrap a program with an interface of its own.
+//! "Run interactively" (I) starts that program in the embedded terminal
+//! instead of collecting parameters in dialogs, so its screens, prompts and
+//! progress can be used directly. The terminal stays open after the program
+//! exits until a key is pressed, so its last screen can be read.
+
+use crate::privilege;
+
+/// The program behind a tools menu entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interactive {
+    /// Tools menu entry
+    pub tool: &'static str,
+    /// Program started in the embedded terminal
+    pub program: &'static str,
+    /// Arguments before the disk, if any
+    args: &'static [&'static str],
+    /// Whether a disk is chosen first and passed as the last argument
+    pub needs_disk: bool,
+}
+
+/// Tools that can run their program interactively
+const INTERACTIVE: &[Interactive] = &[
+    Interactive {
+        tool: "Partition Disk",
+        program: "cfdisk",
+        args: &[],
+        needs_disk: true,
+    },
+    // Captive mode keeps smartctl in the foreground until the test ends
+    Interactive {
+        tool: "Check Disk Health",
+        program: "smartctl",
+        args: &["-t", "short", "-C"],
+        needs_disk: true,
+    },
+    Interactive {
+        tool: "Configure Network",
+        program: "nmtui",
+        args: &[],
+        needs_disk: false,
+    },
+];
+
+/// The interactive program of the tools menu entry `tool`, if it has one
+pub fn find(tool: &str) -> Option<&'static Interactive> {
+    INTERACTIVE
+        .iter()
+        .find(|interactive| interactive.tool == tool)
+}
+
+impl Interactive {
+    /// Arguments of the program, with `disk` last when one is needed
+    pub fn args(&self, disk: Option<&str>) -> Vec<String> {
+        let mut args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+        if self.needs_disk {
+            args.extend(disk.map(str::to_string));
+        }
+        args
+    }
+
+    /// The command line shown as the embedded terminal's title
+    pub fn command_line(&self, disk: Option<&str>) -> String {
+        std::iter::once(self.program.to_string())
+            .chain(self.args(disk))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether the program is found on $PATH
+    pub fn is_installed(&self) -> bool {
+        std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path)
+                .any(|dir| privilege::is_executable(&dir.join(self.program)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interactive_commands() {
+        let smart = find("Check Disk Health").unwrap();
+        assert_eq!(
+            smart.command_line(Some("/dev/sda")),
+            "smartctl -t short -C /dev/sda"
+        );
+        let partition = find("Partition Disk").unwrap();
+        assert_eq!(partition.args(Some("/dev/nvme0n1")), vec!["/dev/nvme0n1"]);
+        let network = find("Configure Network").unwrap();
+        assert!(!network.needs_disk);
+        assert_eq!(network.command_line(Some("/dev/sda")), "nmtui");
+        assert!(find("Generate fstab").is_none());
+    }
+}
@@ -0,0 +1,18 @@
+//! Native tool implementations
+//!
+//! Tools that need structured output in the TUI are implemented in Rust here
+//! instead of only shelling out to `scripts/tools/`.
+
+pub mod bootrepair;
+pub mod cleanup;
+pub mod format;
+pub mod interactive;
+pub mod mounts;
+pub mod netdiag;
+pub mod rescue;
+pub mod resize;
+pub mod services;
+pub mod smart;
+pub mod snapshots;
+pub mod sysinfo;
+pub mod wipe;
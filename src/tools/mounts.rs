@@ -0,0 +1,327 @@
+//! Mount manager
+//!
+//! Lists the block device tree from `lsblk --json` with the current
+//! mountpoints, and mounts or unmounts a device. The filesystem type is
+//! taken from lsblk (probed by udev) and, when that is empty, from blkid, so
+//! mounting never has to guess. Containers that hold no mountable
+//! filesystem (LUKS, LVM physical volumes, RAID members) are refused with a
+//! hint at what has to be opened first.
+
+use crate::error::ArchInstallError;
+use crate::tools::resize::format_size;
+use serde_json::Value;
+use std::fs;
+use std::process::{Command, Stdio};
+
+/// Columns read from lsblk, in bytes
+const LSBLK_COLUMNS: &str = "NAME,PATH,TYPE,SIZE,FSTYPE,LABEL,MOUNTPOINTS";
+
+/// Where devices are mounted unless the user picks another path
+pub const DEFAULT_MOUNTPOINT: &str = "/mnt";
+
+/// lsblk mountpoint of an active swap device
+const SWAP_MOUNTPOINT: &str = "[SWAP]";
+
+/// A block device or one of its children
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockDevice {
+    /// Kernel name, e.g. sda1 or cryptroot
+    pub name: String,
+    /// Device path, e.g. /dev/sda1
+    pub path: String,
+    /// lsblk type: disk, part, crypt, lvm, raid1, loop, rom, ...
+    pub kind: String,
+    /// Size in bytes, 0 when unknown
+    pub size: u64,
+    /// Filesystem or container signature, empty when none was found
+    pub fstype: String,
+    pub label: String,
+    /// Where the device is mounted; `[SWAP]` for active swap
+    pub mountpoints: Vec<String>,
+    /// Nesting level in the device tree, 0 for whole disks
+    pub depth: usize,
+}
+
+impl BlockDevice {
+    pub fn is_mounted(&self) -> bool {
+        !self.mountpoints.is_empty()
+    }
+
+    /// Whether the device is in use as swap
+    pub fn is_active_swap(&self) -> bool {
+        self.mountpoints.iter().any(|m| m == SWAP_MOUNTPOINT)
+    }
+
+    /// Human-readable size, "-" when unknown
+    pub fn size_display(&self) -> String {
+        if self.size == 0 {
+            "-".to_string()
+        } else {
+            format_size(self.size)
+        }
+    }
+
+    /// Why the device cannot be mounted, `None` when it can
+    ///
+    /// `fstype` is the detected filesystem, which may come from blkid when
+    /// lsblk had none.
+    pub fn mount_refusal(&self, fstype: &str) -> Option<String> {
+        let reason = match fstype {
+            "" if self.kind == "disk" || self.kind == "loop" => {
+                "is a whole disk without a filesystem; mount one of its partitions"
+            }
+            "" => "has no filesystem",
+            "crypto_LUKS" => "is LUKS encrypted; open it with cryptsetup first",
+            "LVM2_member" => "is an LVM physical volume; mount its logical volumes",
+            "linux_raid_member" => "is a RAID member; mount the assembled array",
+            "swap" => "is swap space, which is enabled rather than mounted",
+            _ if self.is_mounted() => "is already mounted",
+            _ => return None,
+        };
+        Some(format!("{} {}", self.path, reason))
+    }
+}
+
+/// lsblk mountpoints of a device
+///
+/// MOUNTPOINTS is a list (null entries for unmounted devices); lsblk older
+/// than 2.37 only has the single MOUNTPOINT.
+pub fn mountpoints(dev: &Value) -> Vec<String> {
+    let single = [dev["mountpoint"].clone()];
+    dev["mountpoints"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&single)
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Append a device and its children to `out`
+fn flatten(dev: &Value, depth: usize, out: &mut Vec<BlockDevice>) {
+    let text = |key: &str| dev[key].as_str().unwrap_or_default().trim().to_string();
+    out.push(BlockDevice {
+        name: text("name"),
+        path: text("path"),
+        kind: text("type"),
+        // Older lsblk versions print numbers as strings
+        size: dev["size"]
+            .as_u64()
+            .or_else(|| dev["size"].as_str().and_then(|s| s.parse().ok()))
+            .unwrap_or(0),
+        fstype: text("fstype"),
+        label: text("label"),
+        mountpoints: mountpoints(dev),
+        depth,
+    });
+    for child in dev["children"].as_array().into_iter().flatten() {
+        flatten(child, depth + 1, out);
+    }
+}
+
+/// Parse `lsblk --json --bytes` output into the device tree, depth first
+pub fn parse_lsblk(json: &str) -> Result<Vec<BlockDevice>, serde_json::Error> {
+    let value: Value = serde_json::from_str(json)?;
+    let mut devices = Vec::new();
+    for dev in value["blockdevices"].as_array().into_iter().flatten() {
+        flatten(dev, 0, &mut devices);
+    }
+    Ok(devices)
+}
+
+/// List the block devices of the running system
+pub fn list_devices() -> Result<Vec<BlockDevice>, ArchInstallError> {
+    let output = Command::new("lsblk")
+        .args(["--json", "--bytes", "--output", LSBLK_COLUMNS])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("lsblk", e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed("lsblk", &output));
+    }
+    Ok(parse_lsblk(&String::from_utf8_lossy(&output.stdout))?)
+}
+
+/// Filesystem of a device: lsblk's, or blkid's when lsblk has none
+///
+/// udev may not have probed a partition formatted moments ago.
+pub fn detect_filesystem(device: &BlockDevice) -> String {
+    if !device.fstype.is_empty() {
+        return device.fstype.clone();
+    }
+    Command::new("blkid")
+        .args(["-o", "value", "-s", "TYPE", &device.path])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Where to mount `device` unless the user says otherwise
+///
+/// [`DEFAULT_MOUNTPOINT`] while nothing is mounted there, otherwise a
+/// directory named after the device below it.
+pub fn suggested_mountpoint(device: &BlockDevice, devices: &[BlockDevice]) -> String {
+    let taken = devices
+        .iter()
+        .any(|dev| dev.mountpoints.iter().any(|m| m == DEFAULT_MOUNTPOINT));
+    if taken {
+        format!("{}/{}", DEFAULT_MOUNTPOINT, device.name)
+    } else {
+        DEFAULT_MOUNTPOINT.to_string()
+    }
+}
+
+/// Reject mountpoints mount would misread or that are not absolute
+fn validate_mountpoint(path: &str) -> Result<(), ArchInstallError> {
+    if !path.starts_with('/') || path.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "mount point",
+            format!("'{}' is not an absolute path without spaces", path),
+        ));
+    }
+    Ok(())
+}
+
+/// Run a command that changes the mount table
+fn run(program: &str, args: &[&str]) -> Result<(), ArchInstallError> {
+    let command = format!("{} {}", program, args.join(" "));
+    if crate::simulate::is_enabled() {
+        log::info!("{}", crate::simulate::skipped(&command));
+        return Ok(());
+    }
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run(program, e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(command, &output));
+    }
+    Ok(())
+}
+
+/// Mount a device at `path`, creating the directory, and describe the result
+pub fn mount(device: &BlockDevice, path: &str) -> Result<String, ArchInstallError> {
+    validate_mountpoint(path)?;
+    let fstype = detect_filesystem(device);
+    if let Some(reason) = device.mount_refusal(&fstype) {
+        return Err(ArchInstallError::validation("device", reason));
+    }
+
+    if !crate::simulate::is_enabled() {
+        fs::create_dir_all(path)?;
+    }
+    run("mount", &["-t", &fstype, &device.path, path])?;
+    Ok(format!("Mounted {} ({}) at {}", device.path, fstype, path))
+}
+
+/// Unmount every mountpoint of a device, or disable it as swap
+///
+/// Mounts nested below a mountpoint are unmounted with it.
+pub fn unmount(device: &BlockDevice) -> Result<String, ArchInstallError> {
+    if !device.is_mounted() {
+        return Err(ArchInstallError::validation(
+            "device",
+            format!("{} is not mounted", device.path),
+        ));
+    }
+    if device.is_active_swap() {
+        run("swapoff", &[&device.path])?;
+        return Ok(format!("Disabled swap on {}", device.path));
+    }
+
+    // Deepest first, so a mountpoint is never busy with another one
+    let mut targets = device.mountpoints.clone();
+    targets.sort_by_key(|target| std::cmp::Reverse(target.len()));
+    for target in &targets {
+        run("umount", &["-R", target])?;
+    }
+    Ok(format!(
+        "Unmounted {} from {}",
+        device.path,
+        targets.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSBLK: &str = r#"{
+       "blockdevices": [
+          {"name":"sda", "path":"/dev/sda", "type":"disk", "size":256060514304, "fstype":null, "label":null, "mountpoints":[null],
+             "children": [
+                {"name":"sda1", "path":"/dev/sda1", "type":"part", "size":536870912, "fstype":"vfat", "label":"EFI", "mountpoints":["/mnt/boot"]},
+                {"name":"sda2", "path":"/dev/sda2", "type":"part", "size":255522586624, "fstype":"crypto_LUKS", "label":null, "mountpoints":[null],
+                   "children": [
+                      {"name":"cryptroot", "path":"/dev/mapper/cryptroot", "type":"crypt", "size":"255506857984", "fstype":"btrfs", "label":null, "mountpoints":["/mnt/home", "/mnt"]}
+                   ]
+                }
+             ]
+          },
+          {"name":"zram0", "path":"/dev/zram0", "type":"disk", "size":4294967296, "fstype":"swap", "label":null, "mountpoint":"[SWAP]"}
+       ]
+    }"#;
+
+    #[test]
+    fn test_parse_lsblk_tree() {
+        let devices = parse_lsblk(LSBLK).unwrap();
+        let names: Vec<(&str, usize)> = devices
+            .iter()
+            .map(|dev| (dev.name.as_str(), dev.depth))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("sda", 0),
+                ("sda1", 1),
+                ("sda2", 1),
+                ("cryptroot", 2),
+                ("zram0", 0)
+            ]
+        );
+
+        assert!(!devices[0].is_mounted());
+        assert_eq!(devices[1].label, "EFI");
+        assert_eq!(devices[3].size, 255506857984);
+        assert_eq!(devices[3].mountpoints, ["/mnt/home", "/mnt"]);
+        // Older lsblk: a single MOUNTPOINT column
+        assert!(devices[4].is_active_swap());
+        assert!(parse_lsblk("not json").is_err());
+    }
+
+    #[test]
+    fn test_mount_refusals() {
+        let devices = parse_lsblk(LSBLK).unwrap();
+        let refusal = |index: usize| {
+            let dev = &devices[index];
+            dev.mount_refusal(&dev.fstype)
+        };
+        assert!(refusal(0).unwrap().contains("whole disk"));
+        assert!(refusal(1).unwrap().ends_with("is already mounted"));
+        assert!(refusal(2).unwrap().contains("cryptsetup"));
+        assert!(refusal(4).unwrap().contains("swap space"));
+
+        let fresh = BlockDevice {
+            path: "/dev/sdb1".to_string(),
+            kind: "part".to_string(),
+            ..BlockDevice::default()
+        };
+        assert_eq!(fresh.mount_refusal("ext4"), None);
+        assert!(fresh.mount_refusal("").unwrap().contains("no filesystem"));
+    }
+
+    #[test]
+    fn test_suggested_mountpoint() {
+        let devices = parse_lsblk(LSBLK).unwrap();
+        assert_eq!(suggested_mountpoint(&devices[2], &devices), "/mnt/sda2");
+        assert_eq!(suggested_mountpoint(&devices[2], &devices[..3]), "/mnt");
+
+        assert!(validate_mountpoint("/mnt/data").is_ok());
+        assert!(validate_mountpoint("mnt").is_err());
+        assert!(validate_mountpoint("/mnt/my data").is_err());
+    }
+}
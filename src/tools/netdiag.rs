@@ -0,0 +1,312 @@
+//! Network diagnostics for scripts
+//!
+//! `tools network diagnostics --json` collects interfaces and routes from
+//! `ip --json`, the resolvers from /etc/resolv.conf, and runs the same ping
+//! and DNS checks as network_diagnostics.sh, reporting each as pass/fail
+//! with its latency or resolved addresses.
+
+use crate::error::ArchInstallError;
+use serde::Serialize;
+use serde_json::Value;
+use std::net::ToSocketAddrs;
+use std::process::{Command, Stdio};
+use strum::{Display, EnumString};
+
+/// Addresses pinged to tell a DNS problem from no connectivity at all
+const PING_ADDRESSES: &[&str] = &["8.8.8.8", "1.1.1.1"];
+/// Host names resolved and pinged
+const TEST_HOSTS: &[&str] = &["archlinux.org", "google.com"];
+
+/// How much the diagnostics check, as `--action` of the tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Depth {
+    /// Ping the test addresses and a host name
+    Basic,
+    /// Also resolve the test hosts
+    Detailed,
+    /// Check the gateways, then the internet with and without DNS
+    Troubleshoot,
+}
+
+/// An address of an interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Address {
+    /// inet or inet6
+    pub family: String,
+    pub address: String,
+    pub prefix: u8,
+}
+
+/// A network interface with its addresses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Interface {
+    pub name: String,
+    /// Kernel operational state: UP, DOWN, UNKNOWN, ...
+    pub state: String,
+    pub mac: Option<String>,
+    pub addresses: Vec<Address>,
+}
+
+/// A default route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Route {
+    pub gateway: String,
+    pub interface: String,
+}
+
+/// What a check tested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CheckKind {
+    /// Ping a default gateway
+    Gateway,
+    Ping,
+    Dns,
+}
+
+/// Result of one check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub kind: CheckKind,
+    pub target: String,
+    pub ok: bool,
+    /// Average round trip of a ping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// Addresses a name resolved to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+}
+
+/// Everything the diagnostics found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostics {
+    pub depth: Depth,
+    pub interfaces: Vec<Interface>,
+    pub default_routes: Vec<Route>,
+    pub nameservers: Vec<String>,
+    pub checks: Vec<Check>,
+}
+
+impl Diagnostics {
+    /// Whether every check passed
+    pub fn all_ok(&self) -> bool {
+        self.checks.iter().all(|check| check.ok)
+    }
+}
+
+/// Interfaces from `ip --json addr show`, without loopback
+pub fn parse_interfaces(json: &str) -> Result<Vec<Interface>, serde_json::Error> {
+    let links: Vec<Value> = serde_json::from_str(json)?;
+    Ok(links
+        .iter()
+        .filter(|link| link["link_type"] != "loopback")
+        .map(|link| Interface {
+            name: link["ifname"].as_str().unwrap_or_default().to_string(),
+            state: link["operstate"].as_str().unwrap_or("UNKNOWN").to_string(),
+            mac: link["address"].as_str().map(str::to_string),
+            addresses: link["addr_info"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|info| {
+                    Some(Address {
+                        family: info["family"].as_str()?.to_string(),
+                        address: info["local"].as_str()?.to_string(),
+                        prefix: info["prefixlen"].as_u64().unwrap_or(0) as u8,
+                    })
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Default routes from `ip --json route show default`
+pub fn parse_routes(json: &str) -> Result<Vec<Route>, serde_json::Error> {
+    let routes: Vec<Value> = serde_json::from_str(json)?;
+    Ok(routes
+        .iter()
+        .filter_map(|route| {
+            Some(Route {
+                gateway: route["gateway"].as_str()?.to_string(),
+                interface: route["dev"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Nameservers from the contents of /etc/resolv.conf
+pub fn parse_resolv_conf(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Average round trip from ping's `rtt min/avg/max/mdev = ...` summary
+pub fn parse_ping_average(output: &str) -> Option<f64> {
+    let (_, values) = output
+        .lines()
+        .find(|line| line.contains("min/avg/max"))?
+        .split_once('=')?;
+    values.trim().split('/').nth(1)?.parse().ok()
+}
+
+/// Run `ip --json` with `args` and return its output
+fn ip_json(args: &[&str]) -> Result<String, ArchInstallError> {
+    let output = Command::new("ip")
+        .arg("--json")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("ip", e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed("ip", &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn ping(kind: CheckKind, target: &str) -> Check {
+    let output = Command::new("ping")
+        .args(["-c", "2", "-W", "3", target])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    let (ok, latency_ms) = match output {
+        Ok(output) => (
+            output.status.success(),
+            parse_ping_average(&String::from_utf8_lossy(&output.stdout)),
+        ),
+        Err(_) => (false, None),
+    };
+    Check {
+        kind,
+        target: target.to_string(),
+        ok,
+        latency_ms,
+        addresses: Vec::new(),
+    }
+}
+
+fn resolve(host: &str) -> Check {
+    let mut addresses: Vec<String> = (host, 0)
+        .to_socket_addrs()
+        .into_iter()
+        .flatten()
+        .map(|addr| addr.ip().to_string())
+        .collect();
+    addresses.dedup();
+    Check {
+        kind: CheckKind::Dns,
+        target: host.to_string(),
+        ok: !addresses.is_empty(),
+        latency_ms: None,
+        addresses,
+    }
+}
+
+/// Inspect the network and run the checks of `depth`
+pub fn run(depth: Depth) -> Result<Diagnostics, ArchInstallError> {
+    let interfaces = parse_interfaces(&ip_json(&["addr", "show"])?)?;
+    let mut default_routes = parse_routes(&ip_json(&["route", "show", "default"])?)?;
+    default_routes.extend(parse_routes(&ip_json(&[
+        "-6", "route", "show", "default",
+    ])?)?);
+    let nameservers =
+        parse_resolv_conf(&std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default());
+
+    let mut checks = Vec::new();
+    if depth == Depth::Troubleshoot {
+        checks.extend(
+            default_routes
+                .iter()
+                .map(|route| ping(CheckKind::Gateway, &route.gateway)),
+        );
+    }
+    checks.extend(
+        PING_ADDRESSES
+            .iter()
+            .map(|addr| ping(CheckKind::Ping, addr)),
+    );
+    if depth != Depth::Basic {
+        checks.extend(TEST_HOSTS.iter().map(|host| resolve(host)));
+    }
+    checks.push(ping(CheckKind::Ping, TEST_HOSTS[0]));
+
+    Ok(Diagnostics {
+        depth,
+        interfaces,
+        default_routes,
+        nameservers,
+        checks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ip_output() {
+        let addr = r#"[
+            {"ifindex":1,"ifname":"lo","operstate":"UNKNOWN","link_type":"loopback","address":"00:00:00:00:00:00",
+             "addr_info":[{"family":"inet","local":"127.0.0.1","prefixlen":8}]},
+            {"ifindex":2,"ifname":"enp3s0","operstate":"UP","link_type":"ether","address":"52:54:00:12:34:56",
+             "addr_info":[{"family":"inet","local":"192.168.1.20","prefixlen":24},
+                          {"family":"inet6","local":"fe80::5054:ff:fe12:3456","prefixlen":64}]},
+            {"ifindex":3,"ifname":"wlan0","operstate":"DOWN","link_type":"ether","address":"a0:b1:c2:d3:e4:f5","addr_info":[]}
+        ]"#;
+        let interfaces = parse_interfaces(addr).unwrap();
+        assert_eq!(interfaces.len(), 2);
+        assert_eq!(interfaces[0].name, "enp3s0");
+        assert_eq!(interfaces[0].state, "UP");
+        assert_eq!(
+            interfaces[0].addresses[0],
+            Address {
+                family: "inet".to_string(),
+                address: "192.168.1.20".to_string(),
+                prefix: 24,
+            }
+        );
+        assert!(interfaces[1].addresses.is_empty());
+
+        let routes = r#"[{"dst":"default","gateway":"192.168.1.1","dev":"enp3s0","protocol":"dhcp","metric":100,"flags":[]}]"#;
+        assert_eq!(
+            parse_routes(routes).unwrap(),
+            [Route {
+                gateway: "192.168.1.1".to_string(),
+                interface: "enp3s0".to_string(),
+            }]
+        );
+        assert_eq!(parse_routes("[]").unwrap(), []);
+    }
+
+    #[test]
+    fn test_parse_resolvers_and_ping() {
+        let resolv = "# Generated by NetworkManager\nsearch lan\nnameserver 192.168.1.1\n\
+                      nameserver 2001:4860:4860::8888\n";
+        assert_eq!(
+            parse_resolv_conf(resolv),
+            ["192.168.1.1", "2001:4860:4860::8888"]
+        );
+
+        let ping = "2 packets transmitted, 2 received, 0% packet loss, time 1001ms\n\
+                    rtt min/avg/max/mdev = 11.203/12.551/13.899/1.348 ms\n";
+        assert_eq!(parse_ping_average(ping), Some(12.551));
+        assert_eq!(
+            parse_ping_average("2 packets transmitted, 0 received"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_depth_parses_actions() {
+        assert_eq!("troubleshoot".parse::<Depth>(), Ok(Depth::Troubleshoot));
+        assert!("full".parse::<Depth>().is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Guided chroot into an installed system
+//!
+//! Lists the filesystems that could be an installed system's root so the
+//! rescue shell can be started without mounting anything by hand. The shell
+//! itself is `chroot_system.sh --device`, run in the embedded terminal: it
+//! unlocks LUKS, mounts the root with /boot and the ESP from the system's
+//! fstab, runs arch-chroot and releases everything when the shell exits.
+//! When the terminal is closed instead, [`release`] cleans up after it.
+
+use crate::error::ArchInstallError;
+use crate::tools::cleanup::{self, Leftover};
+use crate::tools::resize::format_size;
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Where the rescue shell mounts the system
+pub const RESCUE_ROOT: &str = cleanup::INSTALL_ROOT;
+
+/// LUKS mapping chroot_system.sh opens for an encrypted root
+pub const RESCUE_MAPPING: &str = "rescue-root";
+
+/// Embedded terminal name of a shell that mounted the system itself
+pub const TOOL_NAME: &str = "rescue shell";
+
+/// Filesystems a root can be on, and LUKS containers that may hold one
+const ROOT_FSTYPES: &[&str] = &[
+    "ext4",
+    "ext3",
+    "btrfs",
+    "xfs",
+    "f2fs",
+    "bcachefs",
+    "crypto_LUKS",
+];
+
+/// Filesystem that may hold an installed system's root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootCandidate {
+    /// Device path, e.g. /dev/nvme0n1p2 or /dev/mapper/arch-root
+    pub path: String,
+    /// lsblk FSTYPE
+    pub fstype: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Filesystem label, empty if none
+    pub label: String,
+}
+
+impl RootCandidate {
+    /// Whether the shell has to ask for a passphrase first
+    pub fn encrypted(&self) -> bool {
+        self.fstype == "crypto_LUKS"
+    }
+}
+
+impl fmt::Display for RootCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fstype = if self.encrypted() {
+            "LUKS"
+        } else {
+            &self.fstype
+        };
+        write!(f, "{} ({}, {})", self.path, fstype, format_size(self.size))?;
+        if !self.label.is_empty() {
+            write!(f, " {}", self.label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse `lsblk -J -b -o PATH,TYPE,FSTYPE,LABEL,SIZE,MOUNTPOINT`
+///
+/// Partitions, logical volumes, RAID arrays and open LUKS mappings with a
+/// root-capable filesystem qualify unless they are mounted. A LUKS
+/// container that is already open is listed by its mapping instead.
+pub fn parse_lsblk(json: &str) -> Result<Vec<RootCandidate>, serde_json::Error> {
+    let value: Value = serde_json::from_str(json)?;
+    let mut candidates = Vec::new();
+    for device in value["blockdevices"].as_array().into_iter().flatten() {
+        collect(device, &mut candidates);
+    }
+    Ok(candidates)
+}
+
+fn collect(device: &Value, candidates: &mut Vec<RootCandidate>) {
+    let text = |key: &str| device[key].as_str().unwrap_or_default().trim().to_string();
+    let children = device["children"].as_array();
+    let fstype = text("fstype");
+    let mounted = device["mountpoint"].as_str().is_some_and(|m| !m.is_empty());
+    let whole_device = matches!(device["type"].as_str(), Some("disk" | "loop" | "rom"));
+    let open_luks = fstype == "crypto_LUKS" && children.is_some_and(|c| !c.is_empty());
+
+    if ROOT_FSTYPES.contains(&fstype.as_str()) && !mounted && !whole_device && !open_luks {
+        candidates.push(RootCandidate {
+            path: text("path"),
+            fstype,
+            // Older lsblk versions print numbers as strings
+            size: device["size"]
+                .as_u64()
+                .or_else(|| device["size"].as_str().and_then(|s| s.parse().ok()))
+                .unwrap_or(0),
+            label: text("label"),
+        });
+    }
+    for child in children.into_iter().flatten() {
+        collect(child, candidates);
+    }
+}
+
+/// Root filesystem candidates on this machine
+pub fn detect() -> Vec<RootCandidate> {
+    Command::new("lsblk")
+        .args(["-J", "-b", "-o", "PATH,TYPE,FSTYPE,LABEL,SIZE,MOUNTPOINT"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|output| parse_lsblk(&String::from_utf8_lossy(&output.stdout)).ok())
+        .unwrap_or_default()
+}
+
+/// Whether a system is already mounted at the rescue root, e.g. right
+/// after an installation
+pub fn root_mounted() -> bool {
+    Path::new(RESCUE_ROOT).join("etc/passwd").exists()
+}
+
+/// Choice for entering the system already mounted at the rescue root
+pub fn mounted_choice() -> String {
+    format!("{} (already mounted)", RESCUE_ROOT)
+}
+
+/// Arguments for chroot_system.sh given the chosen system
+///
+/// `choice` is [`mounted_choice`] or a [`RootCandidate`] as displayed.
+/// Returns whether the shell mounts the system itself (and so should have
+/// it released afterwards).
+pub fn script_args(choice: &str) -> (Vec<String>, bool) {
+    let root = vec!["--root".to_string(), RESCUE_ROOT.to_string()];
+    match choice.split_whitespace().next() {
+        Some(device) if choice != mounted_choice() => (
+            [vec!["--device".to_string(), device.to_string()], root].concat(),
+            true,
+        ),
+        _ => (root, false),
+    }
+}
+
+/// What a rescue shell left behind: mounts under the rescue root and the
+/// LUKS mapping, given /proc/self/mounts and `dmsetup ls --target crypt`
+pub fn leftovers(proc_mounts: &str, crypt_mappings: &str) -> Vec<Leftover> {
+    let mut leftovers: Vec<Leftover> = cleanup::parse_mounts(proc_mounts, RESCUE_ROOT)
+        .into_iter()
+        .map(Leftover::Mount)
+        .collect();
+    let mapping = format!("/dev/mapper/{}", RESCUE_MAPPING);
+    if !cleanup::parse_crypt_mappings(crypt_mappings, &[mapping]).is_empty() {
+        leftovers.push(Leftover::Luks(RESCUE_MAPPING.to_string()));
+    }
+    leftovers
+}
+
+/// Release whatever a killed rescue shell left mounted or unlocked
+///
+/// Nothing is left when the shell exited normally; its own cleanup ran.
+pub fn release() -> Result<(), ArchInstallError> {
+    let mounts = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    let mappings = Command::new("dmsetup")
+        .args(["ls", "--target", "crypt"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+
+    let leftovers = leftovers(&mounts, &mappings);
+    if leftovers.is_empty() {
+        return Ok(());
+    }
+    cleanup::run(&leftovers, |line| log::info!("rescue cleanup: {}", line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LSBLK: &str = r#"{
+       "blockdevices": [
+          {"path":"/dev/loop0", "type":"loop", "fstype":"squashfs", "label":null, "size":838860800, "mountpoint":"/run/archiso/airootfs"},
+          {"path":"/dev/sda", "type":"disk", "fstype":null, "label":null, "size":500107862016, "mountpoint":null,
+             "children": [
+                {"path":"/dev/sda1", "type":"part", "fstype":"vfat", "label":"EFI", "size":536870912, "mountpoint":null},
+                {"path":"/dev/sda2", "type":"part", "fstype":"btrfs", "label":"arch", "size":"499570991104", "mountpoint":null}
+             ]
+          },
+          {"path":"/dev/nvme0n1", "type":"disk", "fstype":null, "label":null, "size":1024209543168, "mountpoint":null,
+             "children": [
+                {"path":"/dev/nvme0n1p1", "type":"part", "fstype":"ext4", "label":null, "size":1073741824, "mountpoint":"/run/media/boot"},
+                {"path":"/dev/nvme0n1p2", "type":"part", "fstype":"crypto_LUKS", "label":null, "size":107374182400, "mountpoint":null},
+                {"path":"/dev/nvme0n1p3", "type":"part", "fstype":"crypto_LUKS", "label":null, "size":107374182400, "mountpoint":null,
+                   "children": [
+                      {"path":"/dev/mapper/home", "type":"crypt", "fstype":"xfs", "label":null, "size":107357405184, "mountpoint":null}
+                   ]
+                }
+             ]
+          }
+       ]
+    }"#;
+
+    #[test]
+    fn test_parse_lsblk_finds_root_candidates() {
+        let candidates = parse_lsblk(LSBLK).unwrap();
+        let paths: Vec<&str> = candidates.iter().map(|c| c.path.as_str()).collect();
+        // No ESP, live medium, mounted filesystem or already-open container
+        assert_eq!(paths, ["/dev/sda2", "/dev/nvme0n1p2", "/dev/mapper/home"]);
+
+        assert_eq!(
+            candidates[0].to_string(),
+            "/dev/sda2 (btrfs, 465.3 GiB) arch"
+        );
+        assert!(candidates[1].encrypted());
+        assert_eq!(
+            candidates[1].to_string(),
+            "/dev/nvme0n1p2 (LUKS, 100.0 GiB)"
+        );
+        assert_eq!(
+            script_args(&candidates[0].to_string()),
+            (
+                vec![
+                    "--device".to_string(),
+                    "/dev/sda2".to_string(),
+                    "--root".to_string(),
+                    "/mnt".to_string()
+                ],
+                true
+            )
+        );
+        assert_eq!(
+            script_args(&mounted_choice()),
+            (vec!["--root".to_string(), "/mnt".to_string()], false)
+        );
+
+        assert!(parse_lsblk(r#"{"blockdevices": []}"#).unwrap().is_empty());
+        assert!(parse_lsblk("not json").is_err());
+    }
+
+    #[test]
+    fn test_leftovers_of_killed_shell() {
+        let mounts = "\
+/dev/mapper/rescue-root /mnt btrfs rw,subvol=/@ 0 0
+/dev/sda1 /mnt/boot vfat rw 0 0
+proc /mnt/proc proc rw 0 0
+/dev/sdb1 /run/media/usb vfat rw 0 0
+";
+        let mappings = "rescue-root\t(254:0)\ncryptswap\t(254:1)\n";
+        assert_eq!(
+            leftovers(mounts, mappings),
+            [
+                Leftover::Mount("/mnt/proc".to_string()),
+                Leftover::Mount("/mnt/boot".to_string()),
+                Leftover::Mount("/mnt".to_string()),
+                Leftover::Luks("rescue-root".to_string()),
+            ]
+        );
+        assert!(leftovers("", "No devices found\n").is_empty());
+    }
+}
@@ -0,0 +1,769 @@
+//! Partition and filesystem resizing
+//!
+//! Grows or shrinks a partition together with the filesystem on it. Each
+//! filesystem has its own rules for ordering and mount state:
+//!
+//! - ext2/3/4: grows online or offline; shrinking requires it unmounted and
+//!   is checked with e2fsck first. The filesystem shrinks before the partition.
+//! - btrfs: resized online only, so it must be mounted.
+//! - xfs: cannot shrink at all; grows online only.
+//! - ntfs: never resized here. It is treated as read-only; use Windows.
+//!
+//! Growing always resizes the partition first, shrinking the filesystem first,
+//! so the filesystem never extends past the end of its partition.
+
+use crate::error::ArchInstallError;
+use serde_json::Value;
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+/// Partition sizes are aligned down to this boundary
+pub const ALIGNMENT: u64 = 1024 * 1024;
+
+/// Filesystem families with distinct resize rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filesystem {
+    /// ext2, ext3 or ext4
+    Ext,
+    Btrfs,
+    Xfs,
+    Ntfs,
+    /// No filesystem signature; only the partition is resized
+    Empty,
+    /// Anything else (vfat, swap, LUKS, ...)
+    Unsupported,
+}
+
+impl Filesystem {
+    /// Classify an lsblk FSTYPE value
+    pub fn from_fstype(fstype: &str) -> Self {
+        match fstype {
+            "ext2" | "ext3" | "ext4" => Self::Ext,
+            "btrfs" => Self::Btrfs,
+            "xfs" => Self::Xfs,
+            "ntfs" | "ntfs3" => Self::Ntfs,
+            "" => Self::Empty,
+            _ => Self::Unsupported,
+        }
+    }
+}
+
+/// Requested new size, as typed by the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeSpec {
+    /// "40G": exact size
+    Absolute(u64),
+    /// "+5G": current size plus this many bytes
+    Grow(u64),
+    /// "-5G": current size minus this many bytes
+    Shrink(u64),
+    /// "max": all free space after the partition
+    Max,
+}
+
+impl FromStr for SizeSpec {
+    type Err = ArchInstallError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("max") {
+            return Ok(Self::Max);
+        }
+        if let Some(rest) = s.strip_prefix('+') {
+            return parse_bytes(rest).map(Self::Grow);
+        }
+        if let Some(rest) = s.strip_prefix('-') {
+            return parse_bytes(rest).map(Self::Shrink);
+        }
+        parse_bytes(s).map(Self::Absolute)
+    }
+}
+
+impl SizeSpec {
+    /// Resolve to a target size in bytes, aligned down to [`ALIGNMENT`]
+    pub fn resolve(self, current: u64, max: u64) -> Result<u64, ArchInstallError> {
+        let target = match self {
+            Self::Absolute(bytes) => bytes,
+            Self::Grow(bytes) => current.saturating_add(bytes),
+            Self::Shrink(bytes) => current.checked_sub(bytes).ok_or_else(|| {
+                ArchInstallError::validation(
+                    "size",
+                    format!(
+                        "Cannot shrink by {}: partition is only {}",
+                        format_size(bytes),
+                        format_size(current)
+                    ),
+                )
+            })?,
+            Self::Max => return Ok(max),
+        };
+
+        let aligned = target / ALIGNMENT * ALIGNMENT;
+        if aligned == 0 {
+            return Err(ArchInstallError::validation(
+                "size",
+                "New size must be at least 1 MiB",
+            ));
+        }
+        if aligned > max {
+            return Err(ArchInstallError::validation(
+                "size",
+                format!(
+                    "New size {} exceeds the available space ({} max)",
+                    format_size(aligned),
+                    format_size(max)
+                ),
+            ));
+        }
+        Ok(aligned)
+    }
+}
+
+/// Parse a size such as "512M", "40G", "1.5T" or "4096" (bytes)
+///
+/// Units are binary (K = KiB) and may be written as K, KB or KiB.
+fn parse_bytes(s: &str) -> Result<u64, ArchInstallError> {
+    let invalid = || {
+        ArchInstallError::validation(
+            "size",
+            format!("'{}' is not a size (expected e.g. 40G, +5G, -5G or max)", s),
+        )
+    };
+
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+
+    let bytes = number * multiplier as f64;
+    if !bytes.is_finite() || bytes <= 0.0 || bytes > u64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(bytes as u64)
+}
+
+/// Format a byte count with binary units, e.g. "20.0 GiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Current state of the partition being resized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionInfo {
+    /// Partition device, e.g. /dev/sda2
+    pub device: String,
+    /// Parent disk, e.g. /dev/sda
+    pub disk: String,
+    /// Partition number on the disk
+    pub number: u32,
+    /// Current size in bytes
+    pub size: u64,
+    /// Largest possible size (up to the next partition or end of disk)
+    pub max_size: u64,
+    /// lsblk FSTYPE, empty if none
+    pub fstype: String,
+    pub mountpoint: Option<String>,
+}
+
+impl PartitionInfo {
+    pub fn filesystem(&self) -> Filesystem {
+        Filesystem::from_fstype(&self.fstype)
+    }
+}
+
+/// One external command of a resize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResizeStep {
+    pub program: &'static str,
+    pub args: Vec<String>,
+    /// Written to the command's stdin (sfdisk scripts)
+    pub stdin: Option<String>,
+}
+
+impl ResizeStep {
+    fn new(program: &'static str, args: &[&str]) -> Self {
+        Self {
+            program,
+            args: args.iter().map(|a| a.to_string()).collect(),
+            stdin: None,
+        }
+    }
+}
+
+impl fmt::Display for ResizeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.program, self.args.join(" "))?;
+        if let Some(ref stdin) = self.stdin {
+            write!(f, " <<< '{}'", stdin.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// A validated resize: the target size and the commands that get there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResizePlan {
+    pub partition: PartitionInfo,
+    pub new_size: u64,
+    pub steps: Vec<ResizeStep>,
+    pub warnings: Vec<String>,
+}
+
+impl ResizePlan {
+    /// Check the filesystem rules and build the command sequence
+    pub fn new(partition: PartitionInfo, spec: SizeSpec) -> Result<Self, ArchInstallError> {
+        let new_size = spec.resolve(partition.size, partition.max_size)?;
+        if new_size == partition.size {
+            return Err(ArchInstallError::validation(
+                "size",
+                format!("{} is already {}", partition.device, format_size(new_size)),
+            ));
+        }
+        let shrink = new_size < partition.size;
+        let mountpoint = partition.mountpoint.as_deref();
+        let mut warnings = Vec::new();
+
+        // Filesystem command, if the filesystem needs resizing separately
+        let fs_steps = match partition.filesystem() {
+            Filesystem::Ext => {
+                if shrink {
+                    if let Some(mnt) = mountpoint {
+                        return Err(ArchInstallError::validation(
+                            "resize",
+                            format!(
+                                "{} filesystems can only shrink unmounted; unmount {} first",
+                                partition.fstype, mnt
+                            ),
+                        ));
+                    }
+                    let kib = format!("{}K", new_size / 1024);
+                    vec![
+                        ResizeStep::new("e2fsck", &["-f", "-p", &partition.device]),
+                        ResizeStep::new("resize2fs", &[&partition.device, &kib]),
+                    ]
+                } else if mountpoint.is_some() {
+                    vec![ResizeStep::new("resize2fs", &[&partition.device])]
+                } else {
+                    // Offline resize2fs refuses to run on an unchecked filesystem
+                    vec![
+                        ResizeStep::new("e2fsck", &["-f", "-p", &partition.device]),
+                        ResizeStep::new("resize2fs", &[&partition.device]),
+                    ]
+                }
+            }
+            Filesystem::Btrfs => {
+                let Some(mnt) = mountpoint else {
+                    return Err(ArchInstallError::validation(
+                        "resize",
+                        format!(
+                            "btrfs can only be resized while mounted; mount {} first",
+                            partition.device
+                        ),
+                    ));
+                };
+                let size = if shrink {
+                    new_size.to_string()
+                } else {
+                    "max".to_string()
+                };
+                warnings.push("Only device 1 of a multi-device btrfs is resized".to_string());
+                vec![ResizeStep::new(
+                    "btrfs",
+                    &["filesystem", "resize", &size, mnt],
+                )]
+            }
+            Filesystem::Xfs => {
+                if shrink {
+                    return Err(ArchInstallError::validation(
+                        "resize",
+                        "XFS filesystems cannot be shrunk",
+                    ));
+                }
+                let Some(mnt) = mountpoint else {
+                    return Err(ArchInstallError::validation(
+                        "resize",
+                        format!(
+                            "XFS can only grow while mounted; mount {} first",
+                            partition.device
+                        ),
+                    ));
+                };
+                vec![ResizeStep::new("xfs_growfs", &[mnt])]
+            }
+            Filesystem::Ntfs => {
+                return Err(ArchInstallError::validation(
+                    "resize",
+                    format!(
+                        "{} is NTFS, which is treated as read-only here. \
+                         Resize it from Windows (Disk Management) to avoid corrupting it",
+                        partition.device
+                    ),
+                ));
+            }
+            Filesystem::Empty => {
+                warnings.push("No filesystem found; only the partition is resized".to_string());
+                Vec::new()
+            }
+            Filesystem::Unsupported => {
+                return Err(ArchInstallError::validation(
+                    "resize",
+                    format!("Resizing {} filesystems is not supported", partition.fstype),
+                ));
+            }
+        };
+
+        let number = partition.number.to_string();
+        // "+" lets sfdisk use all free space after the partition
+        let sfdisk_size = if spec == SizeSpec::Max {
+            "+".to_string()
+        } else {
+            format!("{}MiB", new_size / ALIGNMENT)
+        };
+        let partition_steps = vec![
+            ResizeStep {
+                stdin: Some(format!(", {}\n", sfdisk_size)),
+                ..ResizeStep::new("sfdisk", &["--no-reread", "-N", &number, &partition.disk])
+            },
+            // Tell the kernel about the new size, even while mounted
+            ResizeStep::new("partx", &["-u", "--nr", &number, &partition.disk]),
+        ];
+
+        let steps = if shrink {
+            warnings.push("Shrinking can fail if the filesystem holds too much data".to_string());
+            fs_steps.into_iter().chain(partition_steps).collect()
+        } else {
+            partition_steps.into_iter().chain(fs_steps).collect()
+        };
+
+        Ok(Self {
+            partition,
+            new_size,
+            steps,
+            warnings,
+        })
+    }
+
+    pub fn is_shrink(&self) -> bool {
+        self.new_size < self.partition.size
+    }
+
+    /// Before/after summary for confirmation
+    pub fn preview(&self) -> Vec<String> {
+        let p = &self.partition;
+        let fstype = if p.fstype.is_empty() {
+            "no filesystem"
+        } else {
+            &p.fstype
+        };
+        let mount = match p.mountpoint {
+            Some(ref mnt) => format!("mounted at {}", mnt),
+            None => "not mounted".to_string(),
+        };
+        let (sign, delta) = if self.is_shrink() {
+            ('-', p.size - self.new_size)
+        } else {
+            ('+', self.new_size - p.size)
+        };
+
+        let mut lines = vec![
+            format!("{}: {}, {}", p.device, fstype, mount),
+            format!("Before: {}", format_size(p.size)),
+            format!(
+                "After:  {} ({}{}, max {})",
+                format_size(self.new_size),
+                sign,
+                format_size(delta),
+                format_size(p.max_size)
+            ),
+        ];
+        lines.extend(self.warnings.iter().cloned());
+        lines
+    }
+}
+
+/// Reject anything that is not a plain block device path
+fn validate_device(device: &str) -> Result<(), ArchInstallError> {
+    if !device.starts_with("/dev/") || device.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("'{}' is not a block device under /dev", device),
+        ));
+    }
+    Ok(())
+}
+
+/// Partition fields read from `lsblk -J -b`
+#[derive(Debug, PartialEq, Eq)]
+struct LsblkPartition {
+    name: String,
+    size: u64,
+    fstype: String,
+    mountpoint: Option<String>,
+    parent: String,
+}
+
+fn parse_lsblk(json: &str) -> Result<LsblkPartition, ArchInstallError> {
+    let value: Value = serde_json::from_str(json)?;
+    let dev = value["blockdevices"]
+        .get(0)
+        .ok_or_else(|| ArchInstallError::system("lsblk returned no device"))?;
+
+    let text = |key: &str| dev[key].as_str().unwrap_or_default().to_string();
+    if text("type") != "part" {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("{} is not a partition", text("path")),
+        ));
+    }
+
+    // Older lsblk versions print numbers as strings
+    let size = dev["size"]
+        .as_u64()
+        .or_else(|| dev["size"].as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| ArchInstallError::system("lsblk did not report a size"))?;
+
+    Ok(LsblkPartition {
+        name: text("name"),
+        size,
+        fstype: text("fstype"),
+        mountpoint: dev["mountpoint"].as_str().map(str::to_string),
+        parent: text("pkname"),
+    })
+}
+
+/// Largest size the partition can grow to, from `sfdisk -J` output
+///
+/// That is the space up to the start of the next partition, or to the end
+/// of the usable area of the disk (`disk_sectors` if the table has no lastlba).
+fn max_size_from_table(json: &str, node: &str, disk_sectors: u64) -> Result<u64, ArchInstallError> {
+    let value: Value = serde_json::from_str(json)?;
+    let table = &value["partitiontable"];
+    let sector_size = table["sectorsize"].as_u64().unwrap_or(512);
+    let partitions = table["partitions"].as_array().cloned().unwrap_or_default();
+
+    let start = partitions
+        .iter()
+        .find(|p| p["node"].as_str() == Some(node))
+        .and_then(|p| p["start"].as_u64())
+        .ok_or_else(|| {
+            ArchInstallError::system(format!("{} not found in the partition table", node))
+        })?;
+
+    let end = partitions
+        .iter()
+        .filter_map(|p| p["start"].as_u64())
+        .filter(|&s| s > start)
+        .min()
+        .or_else(|| table["lastlba"].as_u64().map(|lba| lba + 1))
+        .unwrap_or(disk_sectors);
+
+    Ok(end.saturating_sub(start) * sector_size)
+}
+
+fn run_output(program: &str, args: &[&str]) -> Result<String, ArchInstallError> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run(program, e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(program, &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn read_sys_number(path: &str) -> Result<u64, ArchInstallError> {
+    std::fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|_| ArchInstallError::system(format!("Unexpected contents in {}", path)))
+}
+
+/// Read the current size, limits and filesystem of a partition
+pub fn read_partition(device: &str) -> Result<PartitionInfo, ArchInstallError> {
+    validate_device(device)?;
+
+    let lsblk = run_output(
+        "lsblk",
+        &[
+            "-J",
+            "-b",
+            "-o",
+            "NAME,PATH,TYPE,SIZE,FSTYPE,MOUNTPOINT,PKNAME",
+            device,
+        ],
+    )?;
+    let part = parse_lsblk(&lsblk)?;
+    let disk = format!("/dev/{}", part.parent);
+
+    let number = read_sys_number(&format!("/sys/class/block/{}/partition", part.name))? as u32;
+    let disk_sectors = read_sys_number(&format!("/sys/class/block/{}/size", part.parent))?;
+    let table = run_output("sfdisk", &["-J", &disk])?;
+    let max_size = max_size_from_table(&table, device, disk_sectors)?;
+
+    Ok(PartitionInfo {
+        device: device.to_string(),
+        disk,
+        number,
+        size: part.size,
+        max_size,
+        fstype: part.fstype,
+        mountpoint: part.mountpoint,
+    })
+}
+
+/// Read a partition and plan resizing it to `size` ("40G", "+5G", "-5G", "max")
+pub fn plan(device: &str, size: &str) -> Result<ResizePlan, ArchInstallError> {
+    let spec: SizeSpec = size.parse()?;
+    ResizePlan::new(read_partition(device)?, spec)
+}
+
+/// Run the steps of a plan in order, reporting each command and its output
+///
+/// Stops at the first failing step.
+pub fn apply(plan: &ResizePlan, mut log: impl FnMut(String)) -> Result<(), ArchInstallError> {
+    for step in &plan.steps {
+        if crate::simulate::is_enabled() {
+            log(crate::simulate::skipped(&step.to_string()));
+            continue;
+        }
+        log(format!("$ {}", step));
+
+        let mut child = Command::new(step.program)
+            .args(&step.args)
+            .stdin(if step.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ArchInstallError::command_not_run(step.program, e))?;
+
+        if let (Some(input), Some(mut stdin)) = (&step.stdin, child.stdin.take()) {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        for line in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+        {
+            log(line.to_string());
+        }
+
+        // e2fsck -p exits 1 when it fixed something, which is fine
+        let ok = match output.status.code() {
+            Some(0) => true,
+            Some(1) => step.program == "e2fsck",
+            _ => false,
+        };
+        if !ok {
+            return Err(ArchInstallError::command_failed(step.to_string(), &output));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    fn partition(fstype: &str, mountpoint: Option<&str>) -> PartitionInfo {
+        PartitionInfo {
+            device: "/dev/sda2".to_string(),
+            disk: "/dev/sda".to_string(),
+            number: 2,
+            size: 20 * GIB,
+            max_size: 30 * GIB,
+            fstype: fstype.to_string(),
+            mountpoint: mountpoint.map(str::to_string),
+        }
+    }
+
+    fn programs(plan: &ResizePlan) -> Vec<&str> {
+        plan.steps.iter().map(|s| s.program).collect()
+    }
+
+    #[test]
+    fn test_parse_size_spec() {
+        assert_eq!(
+            "40G".parse::<SizeSpec>().unwrap(),
+            SizeSpec::Absolute(40 * GIB)
+        );
+        assert_eq!(
+            "+512MiB".parse::<SizeSpec>().unwrap(),
+            SizeSpec::Grow(512 << 20)
+        );
+        assert_eq!(
+            "-1.5g".parse::<SizeSpec>().unwrap(),
+            SizeSpec::Shrink(3 * GIB / 2)
+        );
+        assert_eq!("MAX".parse::<SizeSpec>().unwrap(), SizeSpec::Max);
+        assert!("".parse::<SizeSpec>().is_err());
+        assert!("40X".parse::<SizeSpec>().is_err());
+        assert!("+-5G".parse::<SizeSpec>().is_err());
+        assert!("0".parse::<SizeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_aligns_and_bounds() {
+        // Aligned down to 1 MiB
+        assert_eq!(
+            SizeSpec::Absolute(GIB + 12345)
+                .resolve(20 * GIB, 30 * GIB)
+                .unwrap(),
+            GIB
+        );
+        assert_eq!(
+            SizeSpec::Grow(5 * GIB).resolve(20 * GIB, 30 * GIB).unwrap(),
+            25 * GIB
+        );
+        assert_eq!(SizeSpec::Max.resolve(20 * GIB, 30 * GIB).unwrap(), 30 * GIB);
+        assert!(SizeSpec::Grow(11 * GIB)
+            .resolve(20 * GIB, 30 * GIB)
+            .is_err());
+        assert!(SizeSpec::Shrink(21 * GIB)
+            .resolve(20 * GIB, 30 * GIB)
+            .is_err());
+        assert!(SizeSpec::Absolute(1000)
+            .resolve(20 * GIB, 30 * GIB)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ext4_grow_resizes_partition_first() {
+        let plan = ResizePlan::new(partition("ext4", Some("/")), SizeSpec::Grow(5 * GIB)).unwrap();
+        assert_eq!(programs(&plan), ["sfdisk", "partx", "resize2fs"]);
+        assert_eq!(plan.steps[0].stdin.as_deref(), Some(", 25600MiB\n"));
+        assert_eq!(plan.steps[0].args, ["--no-reread", "-N", "2", "/dev/sda"]);
+        assert!(!plan.is_shrink());
+    }
+
+    #[test]
+    fn test_ext4_shrink_checks_and_resizes_filesystem_first() {
+        let plan = ResizePlan::new(partition("ext4", None), SizeSpec::Absolute(10 * GIB)).unwrap();
+        assert_eq!(programs(&plan), ["e2fsck", "resize2fs", "sfdisk", "partx"]);
+        assert_eq!(plan.steps[1].args, ["/dev/sda2", "10485760K"]);
+        assert!(plan.is_shrink());
+    }
+
+    #[test]
+    fn test_ext4_shrink_requires_unmounted() {
+        let err = ResizePlan::new(partition("ext4", Some("/home")), SizeSpec::Shrink(GIB))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unmount /home"));
+    }
+
+    #[test]
+    fn test_btrfs_requires_mount_and_uses_mountpoint() {
+        assert!(ResizePlan::new(partition("btrfs", None), SizeSpec::Max).is_err());
+
+        let plan =
+            ResizePlan::new(partition("btrfs", Some("/mnt")), SizeSpec::Shrink(GIB)).unwrap();
+        assert_eq!(programs(&plan), ["btrfs", "sfdisk", "partx"]);
+        assert_eq!(
+            plan.steps[0].args,
+            ["filesystem", "resize", &(19 * GIB).to_string(), "/mnt"]
+        );
+    }
+
+    #[test]
+    fn test_xfs_cannot_shrink() {
+        assert!(ResizePlan::new(partition("xfs", Some("/")), SizeSpec::Shrink(GIB)).is_err());
+        assert!(ResizePlan::new(partition("xfs", None), SizeSpec::Max).is_err());
+
+        let plan = ResizePlan::new(partition("xfs", Some("/")), SizeSpec::Max).unwrap();
+        assert_eq!(programs(&plan), ["sfdisk", "partx", "xfs_growfs"]);
+        assert_eq!(plan.steps[0].stdin.as_deref(), Some(", +\n"));
+    }
+
+    #[test]
+    fn test_ntfs_is_read_only() {
+        let err = ResizePlan::new(partition("ntfs", None), SizeSpec::Max)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("read-only"));
+    }
+
+    #[test]
+    fn test_unsupported_and_unchanged() {
+        assert!(ResizePlan::new(partition("vfat", None), SizeSpec::Max).is_err());
+        assert!(ResizePlan::new(partition("ext4", None), SizeSpec::Absolute(20 * GIB)).is_err());
+    }
+
+    #[test]
+    fn test_preview_shows_before_and_after() {
+        let plan = ResizePlan::new(partition("ext4", Some("/")), SizeSpec::Grow(5 * GIB)).unwrap();
+        let preview = plan.preview();
+        assert_eq!(preview[0], "/dev/sda2: ext4, mounted at /");
+        assert_eq!(preview[1], "Before: 20.0 GiB");
+        assert_eq!(preview[2], "After:  25.0 GiB (+5.0 GiB, max 30.0 GiB)");
+    }
+
+    #[test]
+    fn test_parse_lsblk() {
+        let json = r#"{"blockdevices": [{"name": "nvme0n1p2", "path": "/dev/nvme0n1p2",
+            "type": "part", "size": 21474836480, "fstype": "btrfs",
+            "mountpoint": "/mnt", "pkname": "nvme0n1"}]}"#;
+        let part = parse_lsblk(json).unwrap();
+        assert_eq!(part.size, 20 * GIB);
+        assert_eq!(part.parent, "nvme0n1");
+        assert_eq!(part.mountpoint.as_deref(), Some("/mnt"));
+
+        let disk = r#"{"blockdevices": [{"name": "sda", "type": "disk", "size": "1000"}]}"#;
+        assert!(parse_lsblk(disk).is_err());
+    }
+
+    #[test]
+    fn test_max_size_from_table() {
+        let json = r#"{"partitiontable": {"label": "gpt", "device": "/dev/sda",
+            "lastlba": 209715166, "sectorsize": 512, "partitions": [
+                {"node": "/dev/sda1", "start": 2048, "size": 1048576},
+                {"node": "/dev/sda2", "start": 1050624, "size": 41943040},
+                {"node": "/dev/sda3", "start": 104857600, "size": 1048576}]}}"#;
+        assert_eq!(
+            max_size_from_table(json, "/dev/sda2", 0).unwrap(),
+            (104857600 - 1050624) * 512
+        );
+        assert_eq!(
+            max_size_from_table(json, "/dev/sda3", 0).unwrap(),
+            (209715167 - 104857600) * 512
+        );
+        assert!(max_size_from_table(json, "/dev/sda9", 0).is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(20 * GIB), "20.0 GiB");
+    }
+}
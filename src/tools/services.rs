@@ -0,0 +1,389 @@
+//! systemd unit management
+//!
+//! Lists units with their unit file and activation state, and enables,
+//! disables, starts, stops, masks or unmasks them through `systemctl`. Works
+//! on the running system or, with `systemctl --root`, on an installed system
+//! mounted under a directory; a system that is not running can only have its
+//! unit files changed, so starting and stopping is refused there.
+
+use crate::error::ArchInstallError;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use strum::{Display, EnumString};
+
+/// Unit types the service manager lists
+pub const UNIT_TYPES: &str = "service,socket,timer,path";
+
+/// File whose presence marks a mounted installed system
+const SYSTEMD_BINARY: &str = "usr/lib/systemd/systemd";
+
+/// Something systemctl can do to a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum UnitAction {
+    Enable,
+    Disable,
+    Start,
+    Stop,
+    Mask,
+    Unmask,
+}
+
+impl UnitAction {
+    /// Whether the action needs the system's service manager to be running
+    pub fn needs_running_system(self) -> bool {
+        matches!(self, Self::Start | Self::Stop)
+    }
+
+    /// Verb for the message after the action succeeded
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            Self::Enable => "Enabled",
+            Self::Disable => "Disabled",
+            Self::Start => "Started",
+            Self::Stop => "Stopped",
+            Self::Mask => "Masked",
+            Self::Unmask => "Unmasked",
+        }
+    }
+}
+
+/// System whose units are managed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The system the toolkit runs on
+    Running,
+    /// An installed system mounted under this directory (chroot target)
+    Root(String),
+}
+
+impl Target {
+    /// Whether the target's service manager is not running
+    pub fn is_offline(&self) -> bool {
+        matches!(self, Self::Root(_))
+    }
+
+    /// systemctl arguments for `args` on this target
+    pub fn systemctl_args<'a>(&self, args: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut all = Vec::new();
+        if let Self::Root(root) = self {
+            all.push(format!("--root={}", root));
+        }
+        all.extend(args.into_iter().map(str::to_string));
+        all
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Running => write!(f, "running system"),
+            Self::Root(root) => write!(f, "installed system at {}", root),
+        }
+    }
+}
+
+/// A systemd unit and its state
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Unit {
+    /// Unit name, e.g. sshd.service
+    pub name: String,
+    /// Unit file state (enabled, disabled, static, masked...); empty when the
+    /// unit has no unit file, e.g. an instance of a template
+    pub enabled: String,
+    /// ACTIVE column (active, inactive, failed...); empty on an offline target
+    pub active: String,
+    /// SUB column (running, exited, dead, waiting...)
+    pub sub: String,
+    /// Unit description; empty on an offline target
+    pub description: String,
+}
+
+impl Unit {
+    /// Whether the unit is enabled in some way
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.starts_with("enabled") || self.enabled == "alias"
+    }
+
+    /// Whether the unit has failed
+    pub fn is_failed(&self) -> bool {
+        self.active == "failed"
+    }
+
+    /// Activation state for display, e.g. "active (running)"
+    pub fn activation(&self) -> String {
+        match (self.active.as_str(), self.sub.as_str()) {
+            ("", _) => "-".to_string(),
+            (active, "") => active.to_string(),
+            (active, sub) => format!("{} ({})", active, sub),
+        }
+    }
+
+    /// Whether the unit matches a filter, case-insensitively
+    ///
+    /// Matches the name, the description and both states, so typing "failed"
+    /// or "masked" lists units in that state. An empty filter matches all.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || [&self.name, &self.description, &self.enabled, &self.active]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&filter))
+    }
+}
+
+/// Units matching a filter, see [`Unit::matches`]
+pub fn filter<'a>(units: &'a [Unit], filter: &str) -> Vec<&'a Unit> {
+    units.iter().filter(|unit| unit.matches(filter)).collect()
+}
+
+/// Parse `systemctl list-unit-files --no-legend` into (name, state) pairs
+pub fn parse_unit_files(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.to_string(), fields.next()?.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `systemctl list-units --all --plain --no-legend`
+pub fn parse_units(output: &str) -> Vec<Unit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // Without --plain failed units are marked with a bullet
+            let line = line.trim_start().trim_start_matches('●').trim_start();
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?.to_string();
+            let _load = fields.next()?;
+            let active = fields.next()?.to_string();
+            let sub = fields.next()?.to_string();
+            Some(Unit {
+                name,
+                enabled: String::new(),
+                active,
+                sub,
+                description: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Combine unit files and loaded units into one list sorted by name
+pub fn merge(unit_files: Vec<(String, String)>, loaded: Vec<Unit>) -> Vec<Unit> {
+    let mut units: BTreeMap<String, Unit> = unit_files
+        .into_iter()
+        .map(|(name, enabled)| {
+            let unit = Unit {
+                name: name.clone(),
+                enabled,
+                ..Unit::default()
+            };
+            (name, unit)
+        })
+        .collect();
+    for unit in loaded {
+        match units.get_mut(&unit.name) {
+            Some(known) => {
+                known.active = unit.active;
+                known.sub = unit.sub;
+                known.description = unit.description;
+            }
+            None => {
+                units.insert(unit.name.clone(), unit);
+            }
+        }
+    }
+    units.into_values().collect()
+}
+
+/// Whether an installed system is mounted under `root`
+pub fn has_installed_system(root: &str) -> bool {
+    Path::new(root).join(SYSTEMD_BINARY).exists()
+}
+
+/// Reject names systemctl would take as an option or several units
+fn validate_unit(unit: &str) -> Result<(), ArchInstallError> {
+    if unit.is_empty() || unit.starts_with('-') || unit.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "unit name",
+            format!("'{}' is not a systemd unit", unit),
+        ));
+    }
+    Ok(())
+}
+
+/// Run systemctl on a target and return its standard output
+fn systemctl(target: &Target, args: &[&str]) -> Result<String, ArchInstallError> {
+    let args = target.systemctl_args(args.iter().copied());
+    let output = Command::new("systemctl")
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("systemctl", e))?;
+
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(
+            format!("systemctl {}", args.join(" ")),
+            &output,
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// List the units of a target
+///
+/// An offline target only has unit files, so its units have no activation
+/// state or description.
+pub fn list_units(target: &Target) -> Result<Vec<Unit>, ArchInstallError> {
+    let type_arg = format!("--type={}", UNIT_TYPES);
+    let unit_files = parse_unit_files(&systemctl(
+        target,
+        &["list-unit-files", "--no-legend", "--no-pager", &type_arg],
+    )?);
+    let loaded = if target.is_offline() {
+        Vec::new()
+    } else {
+        parse_units(&systemctl(
+            target,
+            &[
+                "list-units",
+                "--all",
+                "--plain",
+                "--no-legend",
+                "--no-pager",
+                &type_arg,
+            ],
+        )?)
+    };
+    Ok(merge(unit_files, loaded))
+}
+
+/// Apply an action to a unit and describe the result
+///
+/// In simulation the result is the command that was not run.
+pub fn run(target: &Target, action: UnitAction, unit: &str) -> Result<String, ArchInstallError> {
+    validate_unit(unit)?;
+    if action.needs_running_system() && target.is_offline() {
+        return Err(ArchInstallError::validation(
+            "service action",
+            format!(
+                "cannot {} {} on the {}, only change its unit files",
+                action, unit, target
+            ),
+        ));
+    }
+
+    let action_arg = action.to_string();
+    if crate::simulate::is_enabled() {
+        let args = target.systemctl_args([action_arg.as_str(), unit]);
+        return Ok(crate::simulate::skipped(&format!(
+            "systemctl {}",
+            args.join(" ")
+        )));
+    }
+    systemctl(target, &[&action_arg, unit])?;
+    Ok(match target {
+        Target::Running => format!("{} {}", action.past_tense(), unit),
+        Target::Root(root) => format!("{} {} in {}", action.past_tense(), unit, root),
+    })
+}
+
+/// Print `systemctl status` of a unit on the running system
+///
+/// systemctl exits non-zero for inactive units, so only a failure to run it
+/// is an error.
+pub fn print_status(unit: &str) -> Result<(), ArchInstallError> {
+    validate_unit(unit)?;
+    Command::new("systemctl")
+        .args(["status", "--no-pager", unit])
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| ArchInstallError::command_not_run("systemctl", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIT_FILES: &str = "\
+sshd.service                               disabled        disabled
+getty@.service                             enabled         enabled
+systemd-journald.service                   static          -
+fstrim.timer                               enabled         disabled
+";
+
+    const UNITS: &str = "\
+getty@tty1.service        loaded active   running Getty on tty1
+sshd.service              loaded inactive dead    OpenSSH Daemon
+● systemd-journald.service loaded failed   failed  Journal Service
+fstrim.timer              loaded active   waiting Discard unused filesystem blocks once a week
+";
+
+    #[test]
+    fn test_parse_and_merge() {
+        let unit_files = parse_unit_files(UNIT_FILES);
+        assert_eq!(unit_files.len(), 4);
+        assert_eq!(
+            unit_files[0],
+            ("sshd.service".to_string(), "disabled".to_string())
+        );
+
+        let loaded = parse_units(UNITS);
+        assert_eq!(loaded[2].name, "systemd-journald.service");
+        assert_eq!(loaded[2].description, "Journal Service");
+
+        let units = merge(unit_files, loaded);
+        let names: Vec<&str> = units.iter().map(|unit| unit.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "fstrim.timer",
+                "getty@.service",
+                "getty@tty1.service",
+                "sshd.service",
+                "systemd-journald.service"
+            ]
+        );
+        assert!(units[0].is_enabled());
+        assert_eq!(units[0].activation(), "active (waiting)");
+        // Template instances have no unit file of their own
+        assert_eq!(units[2].enabled, "");
+        assert!(units[4].is_failed());
+        // Unit files alone, as on an offline target
+        assert_eq!(units[1].activation(), "-");
+    }
+
+    #[test]
+    fn test_filter() {
+        let units = merge(parse_unit_files(UNIT_FILES), parse_units(UNITS));
+        assert_eq!(filter(&units, "").len(), 5);
+        assert_eq!(filter(&units, "SSH").len(), 1);
+        assert_eq!(filter(&units, "openssh")[0].name, "sshd.service");
+        assert_eq!(filter(&units, "failed")[0].name, "systemd-journald.service");
+        assert!(filter(&units, "bluetooth").is_empty());
+    }
+
+    #[test]
+    fn test_targets_and_actions() {
+        assert_eq!(
+            Target::Root("/mnt".to_string()).systemctl_args(["enable", "sshd.service"]),
+            ["--root=/mnt", "enable", "sshd.service"]
+        );
+        assert_eq!(
+            Target::Running.systemctl_args(["stop", "sshd.service"]),
+            ["stop", "sshd.service"]
+        );
+        assert_eq!("unmask".parse::<UnitAction>().unwrap(), UnitAction::Unmask);
+
+        let offline = Target::Root("/mnt".to_string());
+        assert!(run(&offline, UnitAction::Start, "sshd.service").is_err());
+        assert!(run(&Target::Running, UnitAction::Enable, "--now").is_err());
+        assert!(run(&Target::Running, UnitAction::Enable, "a b").is_err());
+    }
+}
@@ -0,0 +1,552 @@
+//! SMART disk health reporting
+//!
+//! Runs `smartctl --json` and turns its output into a structured report with
+//! a health level per metric, so the TUI can highlight problems instead of
+//! dumping raw text. Also starts short/long drive self-tests.
+
+use crate::error::ArchInstallError;
+use serde::Serialize;
+use serde_json::Value;
+use std::process::{Command, Stdio};
+use strum::Display;
+
+/// Drive temperature (°C) at which a warning is shown
+pub const TEMP_WARNING_C: i64 = 50;
+/// Drive temperature (°C) considered critical
+pub const TEMP_CRITICAL_C: i64 = 60;
+/// SSD/NVMe wear (% of rated endurance used) at which a warning is shown
+pub const WEAR_WARNING_PERCENT: u64 = 70;
+/// SSD/NVMe wear considered critical
+pub const WEAR_CRITICAL_PERCENT: u64 = 90;
+/// Reallocated sector count considered critical
+pub const REALLOCATED_CRITICAL: u64 = 100;
+
+/// smartctl exit status bits that mean the command itself failed
+/// (bit 0: command line did not parse, bit 1: device open failed)
+const SMARTCTL_FATAL_BITS: i64 = 0b11;
+
+/// ATA attributes whose normalized value is the remaining SSD life in percent
+const ATA_WEAR_ATTRIBUTES: &[u64] = &[
+    177, // Wear_Leveling_Count
+    231, // SSD_Life_Left
+    233, // Media_Wearout_Indicator
+];
+
+/// Kind of drive, which decides which metrics apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+pub enum DriveKind {
+    #[strum(serialize = "HDD")]
+    #[serde(rename = "HDD")]
+    Hdd,
+    #[strum(serialize = "SSD")]
+    #[serde(rename = "SSD")]
+    Ssd,
+    #[strum(serialize = "NVMe")]
+    #[serde(rename = "NVMe")]
+    Nvme,
+    #[strum(serialize = "Unknown")]
+    Unknown,
+}
+
+/// Traffic-light rating of a metric or of the whole drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthLevel {
+    Good,
+    Warning,
+    Critical,
+    /// Metric not reported by the drive
+    Unknown,
+}
+
+/// Drive self-test types supported by smartctl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SelfTest {
+    #[strum(serialize = "short")]
+    Short,
+    #[strum(serialize = "long")]
+    Long,
+}
+
+/// One line of the rendered report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+    pub label: &'static str,
+    pub value: String,
+    pub level: HealthLevel,
+}
+
+/// Parsed SMART data for one drive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SmartReport {
+    pub device: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub kind: DriveKind,
+    /// Whether the drive supports SMART and has it enabled
+    pub smart_available: bool,
+    /// Overall SMART self-assessment (None if not reported)
+    pub passed: Option<bool>,
+    pub temperature_c: Option<i64>,
+    pub power_on_hours: Option<u64>,
+    pub reallocated_sectors: Option<u64>,
+    pub pending_sectors: Option<u64>,
+    /// NVMe media and data integrity errors
+    pub media_errors: Option<u64>,
+    /// Percentage of rated endurance used (SSD/NVMe)
+    pub wear_used_percent: Option<u64>,
+    /// Current or most recent self-test status
+    pub self_test_status: Option<String>,
+    /// Warnings and errors reported by smartctl itself
+    pub messages: Vec<String>,
+}
+
+impl SmartReport {
+    /// Parse the output of `smartctl --json -a <device>`
+    pub fn from_json(device: &str, json: &str) -> Result<Self, ArchInstallError> {
+        let root: Value = serde_json::from_str(json)?;
+
+        let messages: Vec<String> = root
+            .pointer("/smartctl/messages")
+            .and_then(Value::as_array)
+            .map(|msgs| {
+                msgs.iter()
+                    .filter_map(|m| m.get("string").and_then(Value::as_str))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let exit_status = root
+            .pointer("/smartctl/exit_status")
+            .and_then(Value::as_i64)
+            .unwrap_or(0);
+        if exit_status & SMARTCTL_FATAL_BITS != 0 {
+            let detail = if messages.is_empty() {
+                format!("exit status {}", exit_status)
+            } else {
+                messages.join("; ")
+            };
+            return Err(ArchInstallError::system(format!(
+                "smartctl could not read {}: {}",
+                device, detail
+            )));
+        }
+
+        let text = |path: &str| {
+            root.pointer(path)
+                .and_then(Value::as_str)
+                .map(|s| s.trim().to_string())
+        };
+        let number = |path: &str| root.pointer(path).and_then(Value::as_u64);
+
+        let protocol = text("/device/protocol").unwrap_or_default();
+        let kind = if protocol.eq_ignore_ascii_case("nvme") {
+            DriveKind::Nvme
+        } else {
+            match number("/rotation_rate") {
+                Some(0) => DriveKind::Ssd,
+                Some(_) => DriveKind::Hdd,
+                None => DriveKind::Unknown,
+            }
+        };
+
+        let smart_available = match kind {
+            // NVMe health data is always available
+            DriveKind::Nvme => true,
+            _ => root
+                .pointer("/smart_support/enabled")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        };
+
+        let attributes: &[Value] = root
+            .pointer("/ata_smart_attributes/table")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let attribute = |id: u64| {
+            attributes
+                .iter()
+                .find(|attr| attr.get("id").and_then(Value::as_u64) == Some(id))
+        };
+        let raw_value =
+            |id: u64| attribute(id).and_then(|attr| attr.pointer("/raw/value")?.as_u64());
+
+        let wear_used_percent = match kind {
+            DriveKind::Nvme => number("/nvme_smart_health_information_log/percentage_used"),
+            DriveKind::Ssd => ATA_WEAR_ATTRIBUTES.iter().find_map(|&id| {
+                let remaining = attribute(id)?.get("value")?.as_u64()?;
+                Some(100u64.saturating_sub(remaining.min(100)))
+            }),
+            _ => None,
+        };
+
+        let self_test_status = text("/ata_smart_data/self_test/status/string")
+            .or_else(|| text("/nvme_self_test_log/current_self_test_operation/string"))
+            .or_else(|| {
+                text("/ata_smart_self_test_log/standard/table/0/status/string")
+                    .map(|s| format!("Last test: {}", s))
+            });
+
+        Ok(Self {
+            device: device.to_string(),
+            model: text("/model_name"),
+            serial: text("/serial_number"),
+            kind,
+            smart_available,
+            passed: root
+                .pointer("/smart_status/passed")
+                .and_then(Value::as_bool),
+            temperature_c: root.pointer("/temperature/current").and_then(Value::as_i64),
+            power_on_hours: number("/power_on_time/hours"),
+            reallocated_sectors: raw_value(5),
+            pending_sectors: raw_value(197),
+            media_errors: number("/nvme_smart_health_information_log/media_errors"),
+            wear_used_percent,
+            self_test_status,
+            messages,
+        })
+    }
+
+    /// Report rows in display order, each with its own health level
+    pub fn rows(&self) -> Vec<ReportRow> {
+        let mut rows = vec![ReportRow {
+            label: "Overall health",
+            value: match (self.smart_available, self.passed) {
+                (false, _) => "SMART unavailable".to_string(),
+                (true, Some(true)) => "PASSED".to_string(),
+                (true, Some(false)) => "FAILED".to_string(),
+                (true, None) => "Not reported".to_string(),
+            },
+            level: match self.passed {
+                Some(true) => HealthLevel::Good,
+                Some(false) => HealthLevel::Critical,
+                None => HealthLevel::Unknown,
+            },
+        }];
+
+        rows.push(ReportRow {
+            label: "Temperature",
+            value: describe(self.temperature_c, |t| format!("{} °C", t)),
+            level: self
+                .temperature_c
+                .map_or(HealthLevel::Unknown, temperature_level),
+        });
+
+        rows.push(ReportRow {
+            label: "Power-on time",
+            value: describe(self.power_on_hours, |h| {
+                format!("{} h ({:.1} years)", h, h as f64 / 8766.0)
+            }),
+            level: HealthLevel::Unknown,
+        });
+
+        if self.kind != DriveKind::Nvme {
+            rows.push(ReportRow {
+                label: "Reallocated sectors",
+                value: describe(self.reallocated_sectors, |n| n.to_string()),
+                level: self
+                    .reallocated_sectors
+                    .map_or(HealthLevel::Unknown, reallocated_level),
+            });
+            rows.push(ReportRow {
+                label: "Pending sectors",
+                value: describe(self.pending_sectors, |n| n.to_string()),
+                level: self.pending_sectors.map_or(HealthLevel::Unknown, |n| {
+                    if n > 0 {
+                        HealthLevel::Critical
+                    } else {
+                        HealthLevel::Good
+                    }
+                }),
+            });
+        } else {
+            rows.push(ReportRow {
+                label: "Media errors",
+                value: describe(self.media_errors, |n| n.to_string()),
+                level: self.media_errors.map_or(HealthLevel::Unknown, |n| {
+                    if n > 0 {
+                        HealthLevel::Critical
+                    } else {
+                        HealthLevel::Good
+                    }
+                }),
+            });
+        }
+
+        if matches!(self.kind, DriveKind::Ssd | DriveKind::Nvme) {
+            rows.push(ReportRow {
+                label: "Wear level",
+                value: describe(self.wear_used_percent, |p| {
+                    format!("{}% of rated endurance used", p)
+                }),
+                level: self
+                    .wear_used_percent
+                    .map_or(HealthLevel::Unknown, wear_level),
+            });
+        }
+
+        rows.push(ReportRow {
+            label: "Self-test",
+            value: self
+                .self_test_status
+                .clone()
+                .unwrap_or_else(|| "No self-test recorded".to_string()),
+            level: HealthLevel::Unknown,
+        });
+
+        rows
+    }
+
+    /// Worst level across all rated metrics
+    pub fn overall(&self) -> HealthLevel {
+        let levels: Vec<HealthLevel> = self.rows().iter().map(|row| row.level).collect();
+        if levels.contains(&HealthLevel::Critical) {
+            HealthLevel::Critical
+        } else if levels.contains(&HealthLevel::Warning) {
+            HealthLevel::Warning
+        } else if self.passed == Some(true) {
+            HealthLevel::Good
+        } else {
+            HealthLevel::Unknown
+        }
+    }
+
+    /// The report for scripts: every field plus the overall `health`
+    pub fn to_json(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["health"] = serde_json::to_value(self.overall()).unwrap_or_default();
+        value
+    }
+}
+
+/// Rate a drive temperature
+pub fn temperature_level(celsius: i64) -> HealthLevel {
+    if celsius >= TEMP_CRITICAL_C {
+        HealthLevel::Critical
+    } else if celsius >= TEMP_WARNING_C {
+        HealthLevel::Warning
+    } else {
+        HealthLevel::Good
+    }
+}
+
+/// Rate a reallocated sector count
+pub fn reallocated_level(count: u64) -> HealthLevel {
+    if count >= REALLOCATED_CRITICAL {
+        HealthLevel::Critical
+    } else if count > 0 {
+        HealthLevel::Warning
+    } else {
+        HealthLevel::Good
+    }
+}
+
+/// Rate SSD/NVMe wear (percent of endurance used)
+pub fn wear_level(used_percent: u64) -> HealthLevel {
+    if used_percent >= WEAR_CRITICAL_PERCENT {
+        HealthLevel::Critical
+    } else if used_percent >= WEAR_WARNING_PERCENT {
+        HealthLevel::Warning
+    } else {
+        HealthLevel::Good
+    }
+}
+
+fn describe<T>(value: Option<T>, format: impl FnOnce(T) -> String) -> String {
+    value.map_or_else(|| "Not reported".to_string(), format)
+}
+
+/// Reject anything that is not a plain block device path
+fn validate_device(device: &str) -> Result<(), ArchInstallError> {
+    if !device.starts_with("/dev/") || device.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("'{}' is not a block device under /dev", device),
+        ));
+    }
+    Ok(())
+}
+
+/// Run smartctl against a device and parse the result
+pub fn read_report(device: &str) -> Result<SmartReport, ArchInstallError> {
+    validate_device(device)?;
+
+    let output = Command::new("smartctl")
+        .args(["--json", "-a", device])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("smartctl", e))?;
+
+    SmartReport::from_json(device, &String::from_utf8_lossy(&output.stdout))
+}
+
+/// Start a drive self-test; the drive runs it in the background
+pub fn start_self_test(device: &str, test: SelfTest) -> Result<String, ArchInstallError> {
+    validate_device(device)?;
+
+    let output = Command::new("smartctl")
+        .args(["-t", &test.to_string(), device])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("smartctl", e))?;
+
+    if !output.status.success() {
+        // smartctl reports its errors on stdout
+        return Err(ArchInstallError::CommandFailed {
+            cmd: format!("smartctl -t {} {}", test, device),
+            status: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .last()
+                .unwrap_or("unknown error")
+                .to_string(),
+        });
+    }
+
+    Ok(match test {
+        SelfTest::Short => format!(
+            "Short self-test started on {} (usually ~2 minutes). Press r to refresh.",
+            device
+        ),
+        SelfTest::Long => format!(
+            "Long self-test started on {} (can take hours). Press r to refresh.",
+            device
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SATA_SSD: &str = r#"{
+        "smartctl": {"exit_status": 0},
+        "device": {"name": "/dev/sda", "type": "sat", "protocol": "ATA"},
+        "model_name": "Samsung SSD 860 EVO 500GB",
+        "serial_number": "S3Z1NB0K123456",
+        "rotation_rate": 0,
+        "smart_support": {"available": true, "enabled": true},
+        "smart_status": {"passed": true},
+        "ata_smart_data": {"self_test": {"status": {"value": 0, "string": "completed without error", "passed": true}}},
+        "ata_smart_attributes": {"table": [
+            {"id": 5, "name": "Reallocated_Sector_Ct", "value": 100, "raw": {"value": 3, "string": "3"}},
+            {"id": 177, "name": "Wear_Leveling_Count", "value": 25, "raw": {"value": 1650, "string": "1650"}},
+            {"id": 197, "name": "Current_Pending_Sector", "value": 100, "raw": {"value": 0, "string": "0"}}
+        ]},
+        "power_on_time": {"hours": 17532},
+        "temperature": {"current": 38}
+    }"#;
+
+    const NVME: &str = r#"{
+        "smartctl": {"exit_status": 0},
+        "device": {"name": "/dev/nvme0", "type": "nvme", "protocol": "NVMe"},
+        "model_name": "WD_BLACK SN850X 1000GB",
+        "smart_status": {"passed": true},
+        "nvme_smart_health_information_log": {"percentage_used": 3, "media_errors": 0, "temperature": 61},
+        "temperature": {"current": 61},
+        "power_on_time": {"hours": 1200}
+    }"#;
+
+    const HDD_FAILING: &str = r#"{
+        "smartctl": {"exit_status": 8},
+        "device": {"name": "/dev/sdb", "type": "sat", "protocol": "ATA"},
+        "model_name": "ST2000DM001",
+        "rotation_rate": 7200,
+        "smart_support": {"available": true, "enabled": true},
+        "smart_status": {"passed": false},
+        "ata_smart_attributes": {"table": [
+            {"id": 5, "value": 80, "raw": {"value": 512}},
+            {"id": 197, "value": 100, "raw": {"value": 8}}
+        ]}
+    }"#;
+
+    #[test]
+    fn test_parse_sata_ssd() {
+        let report = SmartReport::from_json("/dev/sda", SATA_SSD).unwrap();
+        assert_eq!(report.kind, DriveKind::Ssd);
+        assert_eq!(report.model.as_deref(), Some("Samsung SSD 860 EVO 500GB"));
+        assert_eq!(report.passed, Some(true));
+        assert_eq!(report.temperature_c, Some(38));
+        assert_eq!(report.reallocated_sectors, Some(3));
+        assert_eq!(report.pending_sectors, Some(0));
+        assert_eq!(report.wear_used_percent, Some(75));
+        assert_eq!(
+            report.self_test_status.as_deref(),
+            Some("completed without error")
+        );
+        // Reallocated sectors and 75% wear are warnings, nothing critical
+        assert_eq!(report.overall(), HealthLevel::Warning);
+    }
+
+    #[test]
+    fn test_parse_nvme() {
+        let report = SmartReport::from_json("/dev/nvme0n1", NVME).unwrap();
+        assert_eq!(report.kind, DriveKind::Nvme);
+        assert!(report.smart_available);
+        assert_eq!(report.wear_used_percent, Some(3));
+        assert_eq!(report.media_errors, Some(0));
+
+        let rows = report.rows();
+        assert!(rows.iter().any(|r| r.label == "Media errors"));
+        assert!(!rows.iter().any(|r| r.label == "Reallocated sectors"));
+        // 61 °C is critical
+        assert_eq!(report.overall(), HealthLevel::Critical);
+    }
+
+    #[test]
+    fn test_parse_failing_hdd() {
+        let report = SmartReport::from_json("/dev/sdb", HDD_FAILING).unwrap();
+        assert_eq!(report.kind, DriveKind::Hdd);
+        assert_eq!(report.passed, Some(false));
+        assert_eq!(report.wear_used_percent, None);
+        assert!(!report.rows().iter().any(|r| r.label == "Wear level"));
+        assert_eq!(report.rows()[0].value, "FAILED");
+        assert_eq!(report.overall(), HealthLevel::Critical);
+
+        let json = report.to_json();
+        assert_eq!(json["kind"], "HDD");
+        assert_eq!(json["passed"], false);
+        assert_eq!(json["health"], "critical");
+    }
+
+    #[test]
+    fn test_fatal_exit_status_is_error() {
+        let json = r#"{"smartctl": {"exit_status": 2, "messages": [
+            {"string": "Smartctl open device: /dev/sdz failed: No such device", "severity": "error"}
+        ]}}"#;
+        let err = SmartReport::from_json("/dev/sdz", json).unwrap_err();
+        assert!(err.to_string().contains("No such device"));
+    }
+
+    #[test]
+    fn test_missing_metrics_are_unknown() {
+        let json = r#"{"smartctl": {"exit_status": 4}, "device": {"protocol": "ATA"}}"#;
+        let report = SmartReport::from_json("/dev/vda", json).unwrap();
+        assert!(!report.smart_available);
+        assert_eq!(report.rows()[0].value, "SMART unavailable");
+        assert_eq!(report.overall(), HealthLevel::Unknown);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(temperature_level(45), HealthLevel::Good);
+        assert_eq!(temperature_level(50), HealthLevel::Warning);
+        assert_eq!(temperature_level(60), HealthLevel::Critical);
+        assert_eq!(reallocated_level(0), HealthLevel::Good);
+        assert_eq!(reallocated_level(1), HealthLevel::Warning);
+        assert_eq!(reallocated_level(100), HealthLevel::Critical);
+        assert_eq!(wear_level(10), HealthLevel::Good);
+        assert_eq!(wear_level(70), HealthLevel::Warning);
+        assert_eq!(wear_level(95), HealthLevel::Critical);
+    }
+
+    #[test]
+    fn test_device_validation() {
+        assert!(validate_device("/dev/sda").is_ok());
+        assert!(validate_device("-a").is_err());
+        assert!(validate_device("/dev/sda (500G)").is_err());
+        assert!(read_report("--scan").is_err());
+    }
+}
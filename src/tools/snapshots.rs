@@ -0,0 +1,525 @@
+//! Btrfs snapshot management
+//!
+//! Lists, creates, deletes and rolls back snapshots of an installed system's
+//! root subvolume from outside it, e.g. from the ISO when an update left the
+//! system unbootable. The filesystem's top level (subvolid 5) is mounted in a
+//! temporary directory, so the installer's layout is visible as-is: the root
+//! in `@` and snapshots in `@snapshots`, mounted at `/.snapshots` on the
+//! installed system.
+//!
+//! Snapshots are stored the way snapper stores them, as
+//! `@snapshots/<number>/snapshot` with an `info.xml` next to it, so snapshots
+//! taken here and by snapper show up in both. A rollback moves the current
+//! root aside as `@.broken-<date>` and replaces it with a writable snapshot of
+//! the chosen one; the old root is kept until it is deleted by hand.
+
+use crate::error::ArchInstallError;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Subvolume mounted as `/`
+pub const ROOT_SUBVOLUME: &str = "@";
+
+/// Subvolume holding the snapshots, mounted at `/.snapshots`
+pub const SNAPSHOTS_SUBVOLUME: &str = "@snapshots";
+
+/// A snapshot of the root subvolume
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub number: u32,
+    /// UTC, "YYYY-MM-DD HH:MM:SS" like snapper; empty if info.xml is missing
+    pub date: String,
+    pub description: String,
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let date = if self.date.is_empty() {
+            "(no date)"
+        } else {
+            &self.date
+        };
+        write!(f, "{:>4}  {:<19}  {}", self.number, date, self.description)
+    }
+}
+
+/// Text between `<tag>` and `</tag>`, unescaped
+fn xml_field(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(
+        xml[start..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Read a snapshot's number, date and description from snapper's info.xml
+pub fn parse_info(xml: &str) -> Option<Snapshot> {
+    Some(Snapshot {
+        number: xml_field(xml, "num")?.trim().parse().ok()?,
+        date: xml_field(xml, "date").unwrap_or_default(),
+        description: xml_field(xml, "description").unwrap_or_default(),
+    })
+}
+
+/// snapper's info.xml for a single snapshot
+pub fn info_xml(snapshot: &Snapshot) -> String {
+    format!(
+        "<?xml version=\"1.0\"?>\n\
+         <snapshot>\n  \
+         <type>single</type>\n  \
+         <num>{}</num>\n  \
+         <date>{}</date>\n  \
+         <description>{}</description>\n\
+         </snapshot>\n",
+        snapshot.number,
+        xml_escape(&snapshot.date),
+        xml_escape(&snapshot.description)
+    )
+}
+
+/// Number after the highest one in use; snapper never reuses numbers either
+pub fn next_number(snapshots: &[Snapshot]) -> u32 {
+    snapshots.iter().map(|s| s.number).max().unwrap_or(0) + 1
+}
+
+/// Format seconds since the epoch as "YYYY-MM-DD HH:MM:SS" (UTC)
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+fn now_utc() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_utc(secs)
+}
+
+/// Remove `subvolid=` from fstab mount options
+///
+/// genfstab records both the subvolume path and id; after a rollback `@` is a
+/// new subvolume with a new id, and mount refuses options that disagree.
+pub fn strip_subvolid(fstab: &str) -> String {
+    let mut out = String::with_capacity(fstab.len());
+    for line in fstab.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if line.trim_start().starts_with('#')
+            || fields.len() < 4
+            || !fields[3].contains("subvolid=")
+        {
+            out.push_str(line);
+        } else {
+            let options: Vec<&str> = fields[3]
+                .split(',')
+                .filter(|o| !o.starts_with("subvolid="))
+                .collect();
+            let mut fields = fields.clone();
+            let options = options.join(",");
+            fields[3] = &options;
+            out.push_str(&fields.join("\t"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Reject devices that are not btrfs, with a hint for encrypted ones
+fn validate_device(device: &str) -> Result<(), ArchInstallError> {
+    if !device.starts_with("/dev/") || device.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("'{}' is not a block device under /dev", device),
+        ));
+    }
+    let output = Command::new("blkid")
+        .args(["-o", "value", "-s", "TYPE", device])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run("blkid", e))?;
+    let fstype = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match fstype.as_str() {
+        "btrfs" => Ok(()),
+        "crypto_LUKS" => Err(ArchInstallError::validation(
+            "device",
+            format!(
+                "{} is encrypted; open it with cryptsetup and use /dev/mapper/<name>",
+                device
+            ),
+        )),
+        "" => Err(ArchInstallError::validation(
+            "device",
+            format!("{} has no filesystem", device),
+        )),
+        other => Err(ArchInstallError::validation(
+            "device",
+            format!("{} is {}, snapshots need btrfs", device, other),
+        )),
+    }
+}
+
+/// Run a command that changes the filesystem, unless simulating
+fn run(program: &str, args: &[&str], log: &mut impl FnMut(String)) -> Result<(), ArchInstallError> {
+    let command = format!("{} {}", program, args.join(" "));
+    if crate::simulate::is_enabled() {
+        log(crate::simulate::skipped(&command));
+        return Ok(());
+    }
+    log(format!("$ {}", command));
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run(program, e))?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        log(line.to_string());
+    }
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(command, &output));
+    }
+    Ok(())
+}
+
+/// The btrfs top level mounted in a temporary directory, unmounted on drop
+struct TopLevel {
+    path: PathBuf,
+}
+
+impl TopLevel {
+    /// Mount read-only when simulating, so listing still works
+    fn mount(device: &str) -> Result<Self, ArchInstallError> {
+        validate_device(device)?;
+        let path = std::env::temp_dir().join(format!("archinstall-btrfs-{}", std::process::id()));
+        fs::create_dir_all(&path)?;
+
+        let options = if crate::simulate::is_enabled() {
+            "subvolid=5,ro"
+        } else {
+            "subvolid=5"
+        };
+        let output = Command::new("mount")
+            .args(["-t", "btrfs", "-o", options, device])
+            .arg(&path)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| ArchInstallError::command_not_run("mount", e))?;
+        if !output.status.success() {
+            let _ = fs::remove_dir(&path);
+            return Err(ArchInstallError::command_failed(
+                format!("mount -o {} {}", options, device),
+                &output,
+            ));
+        }
+        Ok(Self { path })
+    }
+
+    fn subvolume(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    fn snapshot_dir(&self, number: u32) -> PathBuf {
+        self.subvolume(SNAPSHOTS_SUBVOLUME).join(number.to_string())
+    }
+
+    fn require_root(&self) -> Result<PathBuf, ArchInstallError> {
+        let root = self.subvolume(ROOT_SUBVOLUME);
+        if !root.is_dir() {
+            return Err(ArchInstallError::validation(
+                "device",
+                format!(
+                    "no {} subvolume; this is not an installer btrfs layout",
+                    ROOT_SUBVOLUME
+                ),
+            ));
+        }
+        Ok(root)
+    }
+
+    fn require_snapshot(&self, number: u32) -> Result<PathBuf, ArchInstallError> {
+        let snapshot = self.snapshot_dir(number).join("snapshot");
+        if !snapshot.is_dir() {
+            return Err(ArchInstallError::validation(
+                "snapshot",
+                format!("snapshot {} does not exist", number),
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    fn snapshots(&self) -> Result<Vec<Snapshot>, ArchInstallError> {
+        let dir = self.subvolume(SNAPSHOTS_SUBVOLUME);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let Some(number) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            if !entry.path().join("snapshot").is_dir() {
+                continue;
+            }
+            let snapshot = fs::read_to_string(entry.path().join("info.xml"))
+                .ok()
+                .and_then(|xml| parse_info(&xml))
+                .unwrap_or_default();
+            snapshots.push(Snapshot { number, ..snapshot });
+        }
+        snapshots.sort_by_key(|s| s.number);
+        Ok(snapshots)
+    }
+}
+
+impl Drop for TopLevel {
+    fn drop(&mut self) {
+        let _ = Command::new("umount")
+            .arg(&self.path)
+            .stdin(Stdio::null())
+            .output();
+        let _ = fs::remove_dir(&self.path);
+    }
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Snapshots of the root subvolume, oldest first
+pub fn list(device: &str) -> Result<Vec<Snapshot>, ArchInstallError> {
+    TopLevel::mount(device)?.snapshots()
+}
+
+/// Take a read-only snapshot of the root subvolume and return it
+pub fn create(
+    device: &str,
+    description: &str,
+    mut log: impl FnMut(String),
+) -> Result<Snapshot, ArchInstallError> {
+    let top = TopLevel::mount(device)?;
+    let root = top.require_root()?;
+    let snapshots_dir = top.subvolume(SNAPSHOTS_SUBVOLUME);
+    if !snapshots_dir.is_dir() {
+        run(
+            "btrfs",
+            &["subvolume", "create", &path_str(&snapshots_dir)],
+            &mut log,
+        )?;
+    }
+
+    let snapshot = Snapshot {
+        number: next_number(&top.snapshots()?),
+        date: now_utc(),
+        description: description.to_string(),
+    };
+    let dir = top.snapshot_dir(snapshot.number);
+    if !crate::simulate::is_enabled() {
+        fs::create_dir_all(&dir)?;
+    }
+    run(
+        "btrfs",
+        &[
+            "subvolume",
+            "snapshot",
+            "-r",
+            &path_str(&root),
+            &path_str(&dir.join("snapshot")),
+        ],
+        &mut log,
+    )?;
+    if !crate::simulate::is_enabled() {
+        fs::write(dir.join("info.xml"), info_xml(&snapshot))?;
+    }
+    Ok(snapshot)
+}
+
+/// Delete a snapshot and its snapper metadata
+pub fn delete(
+    device: &str,
+    number: u32,
+    mut log: impl FnMut(String),
+) -> Result<(), ArchInstallError> {
+    let top = TopLevel::mount(device)?;
+    let snapshot = top.require_snapshot(number)?;
+    run(
+        "btrfs",
+        &["subvolume", "delete", &path_str(&snapshot)],
+        &mut log,
+    )?;
+    if !crate::simulate::is_enabled() {
+        fs::remove_dir_all(top.snapshot_dir(number))?;
+    }
+    Ok(())
+}
+
+/// Replace the root subvolume with a writable copy of a snapshot
+///
+/// The current root is renamed, not deleted; its new name is returned. If
+/// creating the new root fails the old one is moved back.
+pub fn rollback(
+    device: &str,
+    number: u32,
+    mut log: impl FnMut(String),
+) -> Result<String, ArchInstallError> {
+    let top = TopLevel::mount(device)?;
+    let root = top.require_root()?;
+    let snapshot = top.require_snapshot(number)?;
+
+    let broken_name = format!(
+        "{}.broken-{}",
+        ROOT_SUBVOLUME,
+        now_utc().replace([' ', ':'], "-")
+    );
+    let broken = top.subvolume(&broken_name);
+    if crate::simulate::is_enabled() {
+        log(crate::simulate::skipped(&format!(
+            "mv {} {}",
+            path_str(&root),
+            path_str(&broken)
+        )));
+    } else {
+        log(format!(
+            "Moving {} aside as {}",
+            ROOT_SUBVOLUME, broken_name
+        ));
+        fs::rename(&root, &broken)?;
+    }
+
+    let restored = run(
+        "btrfs",
+        &[
+            "subvolume",
+            "snapshot",
+            &path_str(&snapshot),
+            &path_str(&root),
+        ],
+        &mut log,
+    );
+    if let Err(e) = restored {
+        if !crate::simulate::is_enabled() {
+            log(format!("Restoring {}", ROOT_SUBVOLUME));
+            fs::rename(&broken, &root)?;
+        }
+        return Err(e);
+    }
+
+    let fstab = root.join("etc/fstab");
+    if !crate::simulate::is_enabled() {
+        if let Ok(contents) = fs::read_to_string(&fstab) {
+            let stripped = strip_subvolid(&contents);
+            if stripped != contents {
+                fs::write(&fstab, stripped)?;
+                log("Removed subvolid= from /etc/fstab of the new root".to_string());
+            }
+        }
+    }
+    Ok(broken_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &str = "<?xml version=\"1.0\"?>
+<snapshot>
+  <type>pre</type>
+  <num>42</num>
+  <date>2025-03-01 09:15:00</date>
+  <description>pacman -Syu</description>
+  <cleanup>number</cleanup>
+</snapshot>
+";
+
+    #[test]
+    fn test_parse_snapper_info() {
+        assert_eq!(
+            parse_info(INFO),
+            Some(Snapshot {
+                number: 42,
+                date: "2025-03-01 09:15:00".to_string(),
+                description: "pacman -Syu".to_string(),
+            })
+        );
+        assert_eq!(parse_info("<snapshot></snapshot>"), None);
+    }
+
+    #[test]
+    fn test_info_xml_round_trip() {
+        let snapshot = Snapshot {
+            number: 7,
+            date: "2025-01-02 03:04:05".to_string(),
+            description: "before <nvidia> & \"kernel\" update".to_string(),
+        };
+        let xml = info_xml(&snapshot);
+        assert!(xml.contains("&lt;nvidia&gt; &amp; &quot;kernel&quot;"));
+        assert_eq!(parse_info(&xml), Some(snapshot));
+    }
+
+    #[test]
+    fn test_next_number() {
+        assert_eq!(next_number(&[]), 1);
+        let snapshots = [3, 9, 5].map(|number| Snapshot {
+            number,
+            ..Default::default()
+        });
+        assert_eq!(next_number(&snapshots), 10);
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_utc(1_735_689_599), "2024-12-31 23:59:59");
+    }
+
+    #[test]
+    fn test_strip_subvolid() {
+        let fstab = "# /dev/sda2\n\
+                     UUID=abc\t/\tbtrfs\trw,noatime,compress=zstd:3,subvolid=256,subvol=/@\t0 0\n\
+                     UUID=def\t/boot\tvfat\trw,relatime\t0 2\n";
+        let stripped = strip_subvolid(fstab);
+        assert!(stripped.starts_with("# /dev/sda2\n"));
+        assert!(stripped.contains("rw,noatime,compress=zstd:3,subvol=/@\t0\t0"));
+        assert!(stripped.ends_with("UUID=def\t/boot\tvfat\trw,relatime\t0 2\n"));
+        assert!(!stripped.contains("subvolid"));
+    }
+}
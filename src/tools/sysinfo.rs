@@ -0,0 +1,253 @@
+//! System information for scripts
+//!
+//! `tools system info --json` reads the running system straight from /proc
+//! and /sys instead of scraping system_info.sh's text, so numbers come out
+//! as numbers (bytes, seconds, °C) that monitoring scripts can compare.
+
+use crate::types::BootMode;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Filesystems counted as storage; pseudo filesystems are left out
+const DISK_FILESYSTEMS: &[&str] = &[
+    "ext4", "ext3", "ext2", "xfs", "btrfs", "f2fs", "bcachefs", "vfat", "exfat", "ntfs", "ntfs3",
+    "iso9660", "squashfs",
+];
+
+/// Memory or swap usage in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+}
+
+/// A mounted disk filesystem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MountedFilesystem {
+    pub device: String,
+    pub mountpoint: String,
+    pub fstype: String,
+}
+
+/// Snapshot of the running system
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SystemInfo {
+    pub hostname: String,
+    pub kernel: String,
+    pub architecture: String,
+    /// PRETTY_NAME of /etc/os-release
+    pub distribution: Option<String>,
+    pub boot_mode: BootMode,
+    pub uptime_seconds: Option<u64>,
+    /// 1, 5 and 15 minute load averages
+    pub load_average: Option<[f64; 3]>,
+    pub timezone: Option<String>,
+    pub cpu_model: Option<String>,
+    pub cpu_cores: usize,
+    pub memory: Usage,
+    pub swap: Usage,
+    pub temperature_c: Option<f64>,
+    /// Network interfaces other than loopback
+    pub interfaces: Vec<String>,
+    /// Only with `--detailed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filesystems: Option<Vec<MountedFilesystem>>,
+}
+
+/// Memory and swap from the contents of /proc/meminfo
+pub fn parse_meminfo(content: &str) -> (Usage, Usage) {
+    let kib = |key: &str| {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|rest| rest.split_whitespace().next()?.parse::<u64>().ok())
+            .map_or(0, |kib| kib * 1024)
+    };
+    let (total, available) = (kib("MemTotal"), kib("MemAvailable"));
+    let (swap_total, swap_free) = (kib("SwapTotal"), kib("SwapFree"));
+    (
+        Usage {
+            total,
+            used: total.saturating_sub(available),
+            available,
+        },
+        Usage {
+            total: swap_total,
+            used: swap_total.saturating_sub(swap_free),
+            available: swap_free,
+        },
+    )
+}
+
+/// CPU model and number of logical cores from /proc/cpuinfo
+pub fn parse_cpuinfo(content: &str) -> (Option<String>, usize) {
+    let value = |line: &str| line.split_once(':').map(|(_, v)| v.trim().to_string());
+    let model = content
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(value);
+    let cores = content
+        .lines()
+        .filter(|line| line.split(':').next().map(str::trim) == Some("processor"))
+        .count();
+    (model, cores)
+}
+
+/// PRETTY_NAME from the contents of /etc/os-release
+pub fn parse_os_release(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|name| name.trim_matches('"').to_string())
+}
+
+/// Uptime in whole seconds from /proc/uptime
+pub fn parse_uptime(content: &str) -> Option<u64> {
+    let seconds: f64 = content.split_whitespace().next()?.parse().ok()?;
+    Some(seconds as u64)
+}
+
+/// Load averages from /proc/loadavg
+pub fn parse_loadavg(content: &str) -> Option<[f64; 3]> {
+    let mut fields = content.split_whitespace().map(|f| f.parse::<f64>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// Disk filesystems from the contents of /proc/self/mounts
+pub fn parse_mounts(content: &str) -> Vec<MountedFilesystem> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (device, mountpoint, fstype) = (fields.next()?, fields.next()?, fields.next()?);
+            DISK_FILESYSTEMS
+                .contains(&fstype)
+                .then(|| MountedFilesystem {
+                    device: device.to_string(),
+                    // Spaces in mountpoints are escaped as \040
+                    mountpoint: mountpoint.replace("\\040", " "),
+                    fstype: fstype.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Timezone from the target of /etc/localtime
+fn timezone() -> Option<String> {
+    let target = fs::read_link("/etc/localtime").ok()?;
+    let target = target.to_string_lossy();
+    target
+        .split_once("zoneinfo/")
+        .map(|(_, zone)| zone.to_string())
+}
+
+/// Network interfaces listed in /sys/class/net, without loopback
+fn interfaces() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir("/sys/class/net")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != "lo")
+        .collect();
+    names.sort();
+    names
+}
+
+fn read_file(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Read the running system; `detailed` adds the mounted filesystems
+pub fn read(detailed: bool) -> SystemInfo {
+    let text = |path: &str| read_file(path).map(|s| s.trim().to_string());
+    let (memory, swap) = parse_meminfo(&read_file("/proc/meminfo").unwrap_or_default());
+    let (cpu_model, cpu_cores) = parse_cpuinfo(&read_file("/proc/cpuinfo").unwrap_or_default());
+    SystemInfo {
+        hostname: text("/proc/sys/kernel/hostname").unwrap_or_default(),
+        kernel: text("/proc/sys/kernel/osrelease").unwrap_or_default(),
+        architecture: std::env::consts::ARCH.to_string(),
+        distribution: read_file("/etc/os-release")
+            .as_deref()
+            .and_then(parse_os_release),
+        boot_mode: if Path::new("/sys/firmware/efi").exists() {
+            BootMode::Uefi
+        } else {
+            BootMode::Bios
+        },
+        uptime_seconds: read_file("/proc/uptime").as_deref().and_then(parse_uptime),
+        load_average: read_file("/proc/loadavg")
+            .as_deref()
+            .and_then(parse_loadavg),
+        timezone: timezone(),
+        cpu_model,
+        cpu_cores,
+        memory,
+        swap,
+        temperature_c: text("/sys/class/thermal/thermal_zone0/temp")
+            .and_then(|milli| milli.parse::<f64>().ok())
+            .map(|milli| milli / 1000.0),
+        interfaces: interfaces(),
+        filesystems: detailed
+            .then(|| parse_mounts(&read_file("/proc/self/mounts").unwrap_or_default())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let meminfo = "MemTotal:       16384000 kB\nMemFree:         1000000 kB\n\
+                       MemAvailable:   12288000 kB\nSwapTotal:       4096000 kB\n\
+                       SwapFree:        3072000 kB\n";
+        let (memory, swap) = parse_meminfo(meminfo);
+        assert_eq!(memory.total, 16_384_000 * 1024);
+        assert_eq!(memory.used, 4_096_000 * 1024);
+        assert_eq!(swap.used, 1_024_000 * 1024);
+
+        let cpuinfo = "processor\t: 0\nmodel name\t: AMD Ryzen 7 5800X\n\n\
+                       processor\t: 1\nmodel name\t: AMD Ryzen 7 5800X\n";
+        assert_eq!(
+            parse_cpuinfo(cpuinfo),
+            (Some("AMD Ryzen 7 5800X".to_string()), 2)
+        );
+
+        assert_eq!(
+            parse_os_release("NAME=\"Arch Linux\"\nPRETTY_NAME=\"Arch Linux\"\n").as_deref(),
+            Some("Arch Linux")
+        );
+        assert_eq!(parse_uptime("3725.42 14000.10\n"), Some(3725));
+        assert_eq!(
+            parse_loadavg("0.52 0.41 0.30 1/523 4242\n"),
+            Some([0.52, 0.41, 0.30])
+        );
+        assert_eq!(parse_loadavg(""), None);
+    }
+
+    #[test]
+    fn test_parse_mounts_keeps_disk_filesystems() {
+        let mounts = "proc /proc proc rw 0 0\n\
+                      /dev/sda2 / ext4 rw,relatime 0 0\n\
+                      tmpfs /tmp tmpfs rw 0 0\n\
+                      /dev/sda1 /mnt/my\\040efi vfat rw 0 0\n";
+        assert_eq!(
+            parse_mounts(mounts),
+            [
+                MountedFilesystem {
+                    device: "/dev/sda2".to_string(),
+                    mountpoint: "/".to_string(),
+                    fstype: "ext4".to_string(),
+                },
+                MountedFilesystem {
+                    device: "/dev/sda1".to_string(),
+                    mountpoint: "/mnt/my efi".to_string(),
+                    fstype: "vfat".to_string(),
+                },
+            ]
+        );
+    }
+}
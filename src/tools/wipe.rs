@@ -0,0 +1,933 @@
+//! Whole-disk wiping
+//!
+//! Erases a disk either by writing to it or by asking the drive to erase
+//! itself:
+//!
+//! - quick: signatures, the first and last MiB (partition tables, GPT backup)
+//! - zero: every byte overwritten with zeros
+//! - discard: every block discarded (TRIM), SSDs only
+//! - ATA Secure Erase through hdparm, when the drive supports it and the
+//!   firmware has not frozen the security feature set
+//! - NVMe Format with user data (or cryptographic) erase through nvme-cli
+//! - NVMe Sanitize through nvme-cli, when the controller supports it
+//!
+//! Sanitize erases every namespace of the controller, and so does Format on
+//! controllers that apply it to all namespaces; both are refused while the
+//! controller has namespaces other than the disk being wiped.
+//!
+//! Overwriting and sanitizing report progress as bytes done out of the disk
+//! size; the other erase commands run in the drive firmware and only report
+//! when they finish.
+
+use crate::error::ArchInstallError;
+use crate::hardware::{self, DiskInfo, DiskKind};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+/// Size of each write while overwriting
+pub const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Bytes overwritten at each end of the disk by a quick wipe
+pub const QUICK_WIPE_SIZE: u64 = 1024 * 1024;
+
+/// Temporary ATA security password, removed again by the erase itself or,
+/// when the erase fails, by [`recovery_commands`]
+const ATA_PASSWORD: &str = "NULL";
+
+/// nvme sanitize-log reports progress in 65536ths
+const SANITIZE_PROGRESS_SCALE: u64 = 65536;
+
+/// How often a running sanitize is polled
+const SANITIZE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Polls a sanitize may stay unstarted before it is given up on
+const SANITIZE_START_POLLS: u32 = 15;
+
+/// Ways to erase a disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum WipeMethod {
+    Quick,
+    Zero,
+    Discard,
+    AtaSecureErase,
+    NvmeFormat,
+    NvmeSanitize,
+}
+
+impl WipeMethod {
+    /// One-line explanation for the method selection
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Quick => "Remove signatures and partition tables (seconds)",
+            Self::Zero => "Overwrite every byte with zeros (slow on large disks)",
+            Self::Discard => "Discard every block (TRIM), fast on SSDs",
+            Self::AtaSecureErase => "Drive firmware erase through hdparm",
+            Self::NvmeFormat => "NVMe format with user data erase",
+            Self::NvmeSanitize => "NVMe sanitize, also clears caches and spare blocks",
+        }
+    }
+
+    /// Whether the method reports byte progress while it runs
+    pub fn reports_progress(self) -> bool {
+        matches!(self, Self::Quick | Self::Zero | Self::NvmeSanitize)
+    }
+}
+
+/// Bytes done out of the total, passed to the progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// Whole percent done, 100 for an empty total
+    pub fn percent(&self) -> u8 {
+        if self.total == 0 {
+            return 100;
+        }
+        (self.done.min(self.total) * 100 / self.total) as u8
+    }
+}
+
+/// ATA security state from `hdparm -I`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AtaSecurity {
+    pub supported: bool,
+    /// A password is set
+    pub enabled: bool,
+    /// Security commands are refused until the next power cycle
+    pub frozen: bool,
+    pub enhanced_erase: bool,
+    /// Estimated erase time in minutes
+    pub erase_minutes: Option<u32>,
+}
+
+/// Erase features of an NVMe controller from `nvme id-ctrl`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NvmeSupport {
+    /// Format NVM is supported (OACS bit 1)
+    pub format: bool,
+    /// Format, or its secure erase, applies to every namespace (FNA bits 0-1)
+    pub format_all_namespaces: bool,
+    /// Format can erase cryptographically (FNA bit 2)
+    pub crypto_format: bool,
+    /// Sanitize crypto erase, block erase, overwrite (SANICAP bits 0-2)
+    pub sanitize_crypto: bool,
+    pub sanitize_block: bool,
+    pub sanitize_overwrite: bool,
+}
+
+impl NvmeSupport {
+    /// Whether any sanitize action is supported
+    pub fn sanitize(&self) -> bool {
+        self.sanitize_crypto || self.sanitize_block || self.sanitize_overwrite
+    }
+
+    /// `--sanact` value of the fastest supported sanitize action
+    fn sanitize_action(&self) -> Option<&'static str> {
+        if self.sanitize_crypto {
+            Some("4")
+        } else if self.sanitize_block {
+            Some("2")
+        } else if self.sanitize_overwrite {
+            Some("3")
+        } else {
+            None
+        }
+    }
+}
+
+/// A disk to wipe and the erase features it offers
+#[derive(Debug, Clone)]
+pub struct WipeTarget {
+    pub disk: DiskInfo,
+    /// The kernel can discard blocks on the disk
+    pub discard: bool,
+    pub ata: Option<AtaSecurity>,
+    pub nvme: Option<NvmeSupport>,
+    /// Other namespaces of the NVMe controller, erased along by a sanitize
+    pub other_namespaces: Vec<String>,
+}
+
+impl WipeTarget {
+    /// Refuse a controller-wide erase while other namespaces would go too
+    fn check_controller_wide(&self, erase: &str) -> Result<(), String> {
+        if self.other_namespaces.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "NVMe {} of {} would also erase {}",
+            erase,
+            self.disk.path,
+            self.other_namespaces.join(", ")
+        ))
+    }
+
+    /// Check that the disk can be wiped with a method
+    pub fn check(&self, method: WipeMethod) -> Result<(), String> {
+        match method {
+            WipeMethod::Quick | WipeMethod::Zero => Ok(()),
+            WipeMethod::Discard if self.discard => Ok(()),
+            WipeMethod::Discard => Err(format!("{} does not support discard", self.disk.path)),
+            WipeMethod::AtaSecureErase => match self.ata {
+                Some(ata) if !ata.supported => Err(format!(
+                    "{} does not support ATA Secure Erase",
+                    self.disk.path
+                )),
+                Some(ata) if ata.frozen => Err(format!(
+                    "{} has ATA security frozen: suspend and resume the machine, then retry",
+                    self.disk.path
+                )),
+                Some(ata) if ata.enabled => Err(format!(
+                    "{} already has an ATA security password set",
+                    self.disk.path
+                )),
+                Some(_) => Ok(()),
+                None => Err(format!(
+                    "{} is not an ATA drive (or hdparm is missing)",
+                    self.disk.path
+                )),
+            },
+            WipeMethod::NvmeFormat => match self.nvme {
+                Some(nvme) if nvme.format && nvme.format_all_namespaces => {
+                    self.check_controller_wide("Format")
+                }
+                Some(nvme) if nvme.format => Ok(()),
+                Some(_) => Err(format!("{} does not support NVMe Format", self.disk.path)),
+                None => Err(format!(
+                    "{} is not an NVMe drive (or nvme-cli is missing)",
+                    self.disk.path
+                )),
+            },
+            WipeMethod::NvmeSanitize => match self.nvme {
+                Some(nvme) if nvme.sanitize() => self.check_controller_wide("Sanitize"),
+                Some(_) => Err(format!("{} does not support NVMe Sanitize", self.disk.path)),
+                None => Err(format!(
+                    "{} is not an NVMe drive (or nvme-cli is missing)",
+                    self.disk.path
+                )),
+            },
+        }
+    }
+
+    /// Methods the disk supports, the recommended one first
+    pub fn methods(&self) -> Vec<WipeMethod> {
+        let recommended = self.recommended();
+        std::iter::once(recommended)
+            .chain(WipeMethod::iter().filter(|&m| m != recommended && self.check(m).is_ok()))
+            .collect()
+    }
+
+    /// Most thorough method that is fast on this kind of disk
+    ///
+    /// Flash drives erase themselves best: overwriting leaves data in spare
+    /// blocks. Hard disks are overwritten, which shows progress.
+    pub fn recommended(&self) -> WipeMethod {
+        let flash = matches!(self.disk.kind, DiskKind::Nvme | DiskKind::Ssd);
+        [
+            WipeMethod::NvmeSanitize,
+            WipeMethod::NvmeFormat,
+            WipeMethod::AtaSecureErase,
+            WipeMethod::Discard,
+        ]
+        .into_iter()
+        .find(|&m| flash && self.check(m).is_ok())
+        .unwrap_or(WipeMethod::Zero)
+    }
+}
+
+/// Parse the Security section of `hdparm -I` output
+///
+/// Returns `None` when the drive reports no ATA security feature set.
+pub fn parse_hdparm_security(output: &str) -> Option<AtaSecurity> {
+    let mut lines = output.lines().skip_while(|line| line.trim() != "Security:");
+    lines.next()?;
+
+    let mut security = AtaSecurity::default();
+    for line in lines.take_while(|line| line.starts_with(char::is_whitespace)) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["supported"] => security.supported = true,
+            ["enabled"] => security.enabled = true,
+            ["frozen"] => security.frozen = true,
+            ["supported:", "enhanced", "erase"] => security.enhanced_erase = true,
+            [time, "for", "SECURITY", "ERASE", "UNIT.", ..] => {
+                security.erase_minutes = time.trim_end_matches("min").parse().ok();
+            }
+            _ => {}
+        }
+    }
+    Some(security)
+}
+
+/// Parse the erase features from `nvme id-ctrl -o json` output
+pub fn parse_nvme_id_ctrl(json: &str) -> Result<NvmeSupport, ArchInstallError> {
+    let value: Value = serde_json::from_str(json)?;
+    let field = |key: &str| value[key].as_u64().unwrap_or(0);
+    let (oacs, fna, sanicap) = (field("oacs"), field("fna"), field("sanicap"));
+    Ok(NvmeSupport {
+        format: oacs & 0b10 != 0,
+        format_all_namespaces: fna & 0b11 != 0,
+        crypto_format: fna & 0b100 != 0,
+        sanitize_crypto: sanicap & 0b1 != 0,
+        sanitize_block: sanicap & 0b10 != 0,
+        sanitize_overwrite: sanicap & 0b100 != 0,
+    })
+}
+
+/// State of a sanitize operation from `nvme sanitize-log -o json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanitizeStatus {
+    /// No sanitize has run since the controller was made
+    Idle,
+    /// Progress in 65536ths
+    Running(u64),
+    Done,
+    Failed,
+}
+
+/// Parse `nvme sanitize-log -o json` output
+///
+/// nvme-cli 2.x nests the log under the device name, 1.x does not.
+pub fn parse_sanitize_log(json: &str) -> Result<SanitizeStatus, ArchInstallError> {
+    let value: Value = serde_json::from_str(json)?;
+    let log = if value.get("sstat").is_some() {
+        &value
+    } else {
+        value
+            .as_object()
+            .and_then(|object| object.values().next())
+            .unwrap_or(&value)
+    };
+    let sstat = log["sstat"]
+        .as_u64()
+        .ok_or_else(|| ArchInstallError::system("nvme sanitize-log did not report a status"))?;
+    Ok(match sstat & 0b111 {
+        1 | 4 => SanitizeStatus::Done,
+        2 => SanitizeStatus::Running(log["sprog"].as_u64().unwrap_or(0)),
+        3 => SanitizeStatus::Failed,
+        _ => SanitizeStatus::Idle,
+    })
+}
+
+/// Overwrite `length` bytes with zeros, reporting each whole percent
+pub fn write_zeros(
+    writer: &mut impl Write,
+    length: u64,
+    mut progress: impl FnMut(Progress),
+) -> Result<(), ArchInstallError> {
+    let zeros = vec![0u8; CHUNK_SIZE];
+    let mut done = 0u64;
+    let mut reported = None;
+    progress(Progress {
+        done,
+        total: length,
+    });
+    while done < length {
+        let chunk = (length - done).min(CHUNK_SIZE as u64) as usize;
+        writer.write_all(&zeros[..chunk])?;
+        done += chunk as u64;
+
+        let current = Progress {
+            done,
+            total: length,
+        };
+        if reported != Some(current.percent()) {
+            reported = Some(current.percent());
+            progress(current);
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn validate_device(device: &str) -> Result<(), ArchInstallError> {
+    if !device.starts_with("/dev/") || device.contains(char::is_whitespace) {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("'{}' is not a block device under /dev", device),
+        ));
+    }
+    Ok(())
+}
+
+fn run_output(program: &str, args: &[&str]) -> Result<String, ArchInstallError> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run(program, e))?;
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(program, &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Other namespaces of the controller a namespace belongs to
+///
+/// `entries` are the names in /sys/class/nvme/<controller>, where the
+/// namespaces sit next to attribute files.
+pub fn sibling_namespaces(
+    namespace: &str,
+    entries: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let Some(controller) = namespace.rfind('n').map(|end| &namespace[..end]) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}n", controller);
+    let mut siblings: Vec<String> = entries
+        .into_iter()
+        .filter(|entry| entry != namespace)
+        .filter(|entry| {
+            entry
+                .strip_prefix(&prefix)
+                .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|entry| format!("/dev/{}", entry))
+        .collect();
+    siblings.sort();
+    siblings
+}
+
+/// Read a whole disk and the erase features it offers
+///
+/// Fails for partitions and for disks with anything mounted or read-only.
+pub fn inspect(device: &str) -> Result<WipeTarget, ArchInstallError> {
+    validate_device(device)?;
+
+    let disk = if crate::simulate::is_enabled() {
+        crate::simulate::fake_disks()
+            .into_iter()
+            .find(|disk| disk.path == device)
+    } else {
+        let lsblk = run_output(
+            "lsblk",
+            &[
+                "-J",
+                "-b",
+                "-o",
+                "PATH,SIZE,TYPE,RO,TRAN,ROTA,MODEL,FSTYPE,MOUNTPOINT",
+                device,
+            ],
+        )?;
+        hardware::parse_lsblk_disks(&lsblk)?.into_iter().next()
+    }
+    .ok_or_else(|| {
+        ArchInstallError::validation("device path", format!("{} is not a whole disk", device))
+    })?;
+
+    if disk.mounted {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("{} has mounted partitions: unmount them first", device),
+        ));
+    }
+    if disk.read_only {
+        return Err(ArchInstallError::validation(
+            "device path",
+            format!("{} is read-only", device),
+        ));
+    }
+
+    let name = device.trim_start_matches("/dev/");
+    let discard = std::fs::read_to_string(format!("/sys/block/{}/queue/discard_max_bytes", name))
+        .ok()
+        .and_then(|max| max.trim().parse::<u64>().ok())
+        .is_some_and(|max| max > 0);
+
+    let ata = match disk.kind {
+        DiskKind::Hdd | DiskKind::Ssd | DiskKind::Unknown => run_output("hdparm", &["-I", device])
+            .ok()
+            .and_then(|output| parse_hdparm_security(&output)),
+        _ => None,
+    };
+    let nvme = match disk.kind {
+        DiskKind::Nvme => run_output("nvme", &["id-ctrl", device, "-o", "json"])
+            .ok()
+            .and_then(|json| parse_nvme_id_ctrl(&json).ok()),
+        _ => None,
+    };
+    let other_namespaces = match (disk.kind, name.rfind('n')) {
+        (DiskKind::Nvme, Some(end)) => {
+            std::fs::read_dir(format!("/sys/class/nvme/{}", &name[..end]))
+                .map(|entries| {
+                    sibling_namespaces(
+                        name,
+                        entries
+                            .flatten()
+                            .map(|entry| entry.file_name().to_string_lossy().into_owned()),
+                    )
+                })
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(WipeTarget {
+        disk,
+        discard,
+        ata,
+        nvme,
+        other_namespaces,
+    })
+}
+
+/// Commands a method runs, in order
+///
+/// Overwriting is done in-process and has no command of its own; only the
+/// signature wipe before it is listed.
+pub fn commands(target: &WipeTarget, method: WipeMethod) -> Vec<(&'static str, Vec<String>)> {
+    let device = target.disk.path.clone();
+    let args = |list: &[&str]| -> Vec<String> {
+        list.iter()
+            .map(|arg| arg.to_string())
+            .chain(std::iter::once(device.clone()))
+            .collect()
+    };
+    let wipefs = ("wipefs", args(&["--all", "--force"]));
+
+    match method {
+        WipeMethod::Quick | WipeMethod::Zero => vec![wipefs],
+        WipeMethod::Discard => vec![wipefs, ("blkdiscard", args(&["--force"]))],
+        WipeMethod::AtaSecureErase => {
+            let erase = if target.ata.is_some_and(|ata| ata.enhanced_erase) {
+                "--security-erase-enhanced"
+            } else {
+                "--security-erase"
+            };
+            vec![
+                (
+                    "hdparm",
+                    args(&["--user-master", "u", "--security-set-pass", ATA_PASSWORD]),
+                ),
+                ("hdparm", args(&["--user-master", "u", erase, ATA_PASSWORD])),
+            ]
+        }
+        WipeMethod::NvmeFormat => {
+            let ses = if target.nvme.is_some_and(|nvme| nvme.crypto_format) {
+                "--ses=2"
+            } else {
+                "--ses=1"
+            };
+            vec![(
+                "nvme",
+                [vec!["format".to_string()], args(&[ses, "--force"])].concat(),
+            )]
+        }
+        WipeMethod::NvmeSanitize => {
+            let action = target
+                .nvme
+                .and_then(|nvme| nvme.sanitize_action())
+                .unwrap_or("2");
+            vec![(
+                "nvme",
+                [vec!["sanitize".to_string()], args(&["--sanact", action])].concat(),
+            )]
+        }
+    }
+}
+
+/// Commands undoing what a method left behind when its command `failed` (an
+/// index into [`commands`]) did not succeed
+///
+/// Once the ATA password is set, a failed erase would leave the drive locked
+/// at the next power cycle, so the password is removed again.
+pub fn recovery_commands(
+    target: &WipeTarget,
+    method: WipeMethod,
+    failed: usize,
+) -> Vec<(&'static str, Vec<String>)> {
+    match method {
+        WipeMethod::AtaSecureErase if failed > 0 => vec![(
+            "hdparm",
+            ["--user-master", "u", "--security-disable", ATA_PASSWORD]
+                .iter()
+                .map(|arg| arg.to_string())
+                .chain(std::iter::once(target.disk.path.clone()))
+                .collect(),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+fn run_logged(
+    program: &str,
+    args: &[String],
+    log: &mut impl FnMut(String),
+) -> Result<(), ArchInstallError> {
+    log(format!("$ {} {}", program, args.join(" ")));
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ArchInstallError::command_not_run(program, e))?;
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+    {
+        log(line.to_string());
+    }
+    if !output.status.success() {
+        return Err(ArchInstallError::command_failed(program, &output));
+    }
+    Ok(())
+}
+
+/// Overwrite byte ranges of the disk, reporting progress across all of them
+fn overwrite(
+    device: &str,
+    ranges: &[(u64, u64)],
+    progress: &mut impl FnMut(Progress),
+) -> Result<(), ArchInstallError> {
+    let mut file = OpenOptions::new().write(true).open(device)?;
+    let total: u64 = ranges.iter().map(|(_, length)| length).sum();
+    let mut before = 0;
+    for &(offset, length) in ranges {
+        file.seek(SeekFrom::Start(offset))?;
+        write_zeros(&mut file, length, |p| {
+            progress(Progress {
+                done: before + p.done,
+                total,
+            })
+        })?;
+        before += length;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Poll the sanitize log until the controller finishes
+fn wait_for_sanitize(
+    target: &WipeTarget,
+    progress: &mut impl FnMut(Progress),
+) -> Result<(), ArchInstallError> {
+    let total = target.disk.size;
+    let mut idle_polls = 0;
+    loop {
+        let json = run_output("nvme", &["sanitize-log", &target.disk.path, "-o", "json"])?;
+        match parse_sanitize_log(&json)? {
+            SanitizeStatus::Idle if idle_polls >= SANITIZE_START_POLLS => {
+                return Err(ArchInstallError::system(format!(
+                    "NVMe sanitize of {} never started",
+                    target.disk.path
+                )))
+            }
+            SanitizeStatus::Idle => idle_polls += 1,
+            SanitizeStatus::Running(sprog) => progress(Progress {
+                done: total * sprog.min(SANITIZE_PROGRESS_SCALE) / SANITIZE_PROGRESS_SCALE,
+                total,
+            }),
+            SanitizeStatus::Done => {
+                progress(Progress { done: total, total });
+                return Ok(());
+            }
+            SanitizeStatus::Failed => {
+                return Err(ArchInstallError::system(format!(
+                    "NVMe sanitize of {} failed",
+                    target.disk.path
+                )))
+            }
+        }
+        thread::sleep(SANITIZE_POLL_INTERVAL);
+    }
+}
+
+/// Wipe a disk, logging each command and reporting progress
+///
+/// Progress is reported for overwriting and sanitizing; the firmware erase
+/// commands block until the drive is done.
+pub fn wipe(
+    target: &WipeTarget,
+    method: WipeMethod,
+    mut log: impl FnMut(String),
+    mut progress: impl FnMut(Progress),
+) -> Result<(), ArchInstallError> {
+    target
+        .check(method)
+        .map_err(|reason| ArchInstallError::validation("wipe method", reason))?;
+
+    let device = &target.disk.path;
+    let size = target.disk.size;
+    let ranges = match method {
+        WipeMethod::Quick => {
+            let head = size.min(QUICK_WIPE_SIZE);
+            let tail = size.saturating_sub(head).min(QUICK_WIPE_SIZE);
+            vec![(0, head), (size - tail, tail)]
+        }
+        WipeMethod::Zero => vec![(0, size)],
+        _ => Vec::new(),
+    };
+
+    if crate::simulate::is_enabled() {
+        for (program, args) in commands(target, method) {
+            log(crate::simulate::skipped(&format!(
+                "{} {}",
+                program,
+                args.join(" ")
+            )));
+        }
+        if !ranges.is_empty() {
+            log(crate::simulate::skipped(&format!(
+                "write zeros to {}",
+                device
+            )));
+        }
+        return Ok(());
+    }
+
+    if method == WipeMethod::AtaSecureErase {
+        if let Some(minutes) = target.ata.and_then(|ata| ata.erase_minutes) {
+            log(format!(
+                "The drive estimates {} minutes for the erase",
+                minutes
+            ));
+        }
+    }
+    for (index, (program, args)) in commands(target, method).into_iter().enumerate() {
+        if let Err(e) = run_logged(program, &args, &mut log) {
+            for (program, args) in recovery_commands(target, method, index) {
+                if let Err(undo) = run_logged(program, &args, &mut log) {
+                    log(format!("Could not undo the failed step: {}", undo));
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    if !ranges.is_empty() {
+        log(format!(
+            "Writing zeros to {} ({})",
+            device,
+            crate::tools::resize::format_size(ranges.iter().map(|(_, length)| length).sum())
+        ));
+        overwrite(device, &ranges, &mut progress)?;
+    }
+    if method == WipeMethod::NvmeSanitize {
+        wait_for_sanitize(target, &mut progress)?;
+    }
+
+    // Let the kernel drop the partitions it still knows about
+    let _ = run_logged("partprobe", std::slice::from_ref(device), &mut log);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const HDPARM: &str = "\
+ATA device, with non-removable media
+\tModel Number:       Samsung SSD 860 EVO 500GB
+Security:
+\tMaster password revision code = 65534
+\t\tsupported
+\tnot\tenabled
+\tnot\tlocked
+\t\tfrozen
+\tnot\texpired: security count
+\t\tsupported: enhanced erase
+\t2min for SECURITY ERASE UNIT. 8min for ENHANCED SECURITY ERASE UNIT.
+Logical Unit WWN Device Identifier: 5002538e40a1b2c3
+";
+
+    fn target(kind: DiskKind) -> WipeTarget {
+        WipeTarget {
+            disk: DiskInfo {
+                path: "/dev/sda".to_string(),
+                size: 500 << 30,
+                model: String::new(),
+                transport: "sata".to_string(),
+                kind,
+                partitions: Vec::new(),
+                existing_os: Vec::new(),
+                mounted: false,
+                read_only: false,
+            },
+            discard: false,
+            ata: None,
+            nvme: None,
+            other_namespaces: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_hdparm_security() {
+        let security = parse_hdparm_security(HDPARM).unwrap();
+        assert!(security.supported);
+        assert!(!security.enabled);
+        assert!(security.frozen);
+        assert!(security.enhanced_erase);
+        assert_eq!(security.erase_minutes, Some(2));
+
+        let unfrozen = HDPARM.replace("\t\tfrozen", "\tnot\tfrozen");
+        assert!(!parse_hdparm_security(&unfrozen).unwrap().frozen);
+        assert_eq!(parse_hdparm_security("ATA device\n"), None);
+    }
+
+    #[test]
+    fn test_parse_nvme() {
+        let support = parse_nvme_id_ctrl(r#"{"oacs": 23, "fna": 4, "sanicap": 3}"#).unwrap();
+        assert!(support.format && support.crypto_format);
+        assert!(!support.format_all_namespaces);
+        assert!(
+            parse_nvme_id_ctrl(r#"{"oacs": 2, "fna": 1}"#)
+                .unwrap()
+                .format_all_namespaces
+        );
+        assert!(support.sanitize_crypto && support.sanitize_block);
+        assert!(!support.sanitize_overwrite);
+        assert_eq!(support.sanitize_action(), Some("4"));
+        assert!(!parse_nvme_id_ctrl(r#"{"oacs": 0}"#).unwrap().sanitize());
+
+        assert_eq!(
+            parse_sanitize_log(r#"{"nvme0n1": {"sprog": 32768, "sstat": 2}}"#).unwrap(),
+            SanitizeStatus::Running(32768)
+        );
+        assert_eq!(
+            parse_sanitize_log(r#"{"sprog": 65535, "sstat": 257}"#).unwrap(),
+            SanitizeStatus::Done
+        );
+        assert_eq!(
+            parse_sanitize_log(r#"{"sprog": 0, "sstat": 3}"#).unwrap(),
+            SanitizeStatus::Failed
+        );
+        assert_eq!(
+            parse_sanitize_log(r#"{"sprog": 0, "sstat": 0}"#).unwrap(),
+            SanitizeStatus::Idle
+        );
+    }
+
+    #[test]
+    fn test_sibling_namespaces() {
+        let entries = [
+            "nvme0n3",
+            "nvme0n1",
+            "nvme0n2",
+            "nvme0c0n1",
+            "firmware_rev",
+            "nvme1n1",
+        ]
+        .map(String::from);
+        assert_eq!(
+            sibling_namespaces("nvme0n1", entries.clone()),
+            vec!["/dev/nvme0n2", "/dev/nvme0n3"]
+        );
+        assert!(sibling_namespaces("nvme1n1", entries).is_empty());
+    }
+
+    #[test]
+    fn test_controller_wide_erase_spares_other_namespaces() {
+        let mut nvme = target(DiskKind::Nvme);
+        nvme.nvme = Some(NvmeSupport {
+            format: true,
+            sanitize_crypto: true,
+            ..Default::default()
+        });
+        nvme.other_namespaces = vec!["/dev/nvme0n2".to_string()];
+        assert!(nvme
+            .check(WipeMethod::NvmeSanitize)
+            .unwrap_err()
+            .contains("/dev/nvme0n2"));
+        // This controller formats one namespace at a time
+        assert_eq!(nvme.recommended(), WipeMethod::NvmeFormat);
+
+        nvme.nvme = nvme.nvme.map(|support| NvmeSupport {
+            format_all_namespaces: true,
+            ..support
+        });
+        assert!(nvme.check(WipeMethod::NvmeFormat).is_err());
+        assert_eq!(nvme.recommended(), WipeMethod::Zero);
+
+        nvme.other_namespaces.clear();
+        assert_eq!(nvme.recommended(), WipeMethod::NvmeSanitize);
+    }
+
+    #[test]
+    fn test_methods_and_commands() {
+        let mut ssd = target(DiskKind::Ssd);
+        ssd.discard = true;
+        ssd.ata = parse_hdparm_security(HDPARM);
+        // Frozen: secure erase is offered only after a suspend
+        assert!(ssd
+            .check(WipeMethod::AtaSecureErase)
+            .unwrap_err()
+            .contains("frozen"));
+        assert_eq!(ssd.recommended(), WipeMethod::Discard);
+
+        ssd.ata = ssd.ata.map(|ata| AtaSecurity {
+            frozen: false,
+            ..ata
+        });
+        assert_eq!(ssd.recommended(), WipeMethod::AtaSecureErase);
+        let erase = commands(&ssd, WipeMethod::AtaSecureErase);
+        assert_eq!(erase.len(), 2);
+        assert!(erase[1]
+            .1
+            .contains(&"--security-erase-enhanced".to_string()));
+        // A failed erase removes the password it set; a failed set has none
+        assert!(recovery_commands(&ssd, WipeMethod::AtaSecureErase, 0).is_empty());
+        assert_eq!(
+            recovery_commands(&ssd, WipeMethod::AtaSecureErase, 1),
+            vec![(
+                "hdparm",
+                vec![
+                    "--user-master".to_string(),
+                    "u".to_string(),
+                    "--security-disable".to_string(),
+                    "NULL".to_string(),
+                    "/dev/sda".to_string()
+                ]
+            )]
+        );
+        assert!(recovery_commands(&ssd, WipeMethod::Discard, 1).is_empty());
+
+        let hdd = target(DiskKind::Hdd);
+        assert_eq!(hdd.methods(), vec![WipeMethod::Zero, WipeMethod::Quick]);
+        assert!(hdd.check(WipeMethod::NvmeSanitize).is_err());
+
+        let mut nvme = target(DiskKind::Nvme);
+        nvme.nvme = Some(NvmeSupport {
+            format: true,
+            sanitize_block: true,
+            ..Default::default()
+        });
+        assert_eq!(nvme.recommended(), WipeMethod::NvmeSanitize);
+        assert_eq!(
+            commands(&nvme, WipeMethod::NvmeFormat)[0].1,
+            vec!["format", "--ses=1", "--force", "/dev/sda"]
+        );
+        assert_eq!(
+            WipeMethod::from_str("nvme-sanitize").unwrap(),
+            WipeMethod::NvmeSanitize
+        );
+    }
+
+    #[test]
+    fn test_write_zeros_reports_progress() {
+        let length = 3 * CHUNK_SIZE as u64 + 10;
+        let mut written = Vec::new();
+        let mut reports = Vec::new();
+        write_zeros(&mut written, length, |p| reports.push(p)).unwrap();
+
+        assert_eq!(written.len() as u64, length);
+        assert!(written.iter().all(|&b| b == 0));
+        assert_eq!(reports.first().unwrap().done, 0);
+        assert_eq!(
+            *reports.last().unwrap(),
+            Progress {
+                done: length,
+                total: length
+            }
+        );
+        assert_eq!(reports.len(), 5);
+        assert_eq!(Progress { done: 1, total: 0 }.percent(), 100);
+    }
+}
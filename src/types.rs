@@ -34,6 +34,8 @@ pub enum Filesystem {
     Btrfs,
     #[strum(serialize = "f2fs")]
     F2fs,
+    #[strum(serialize = "bcachefs")]
+    Bcachefs,
 }
 
 /// Disk partitioning strategy
@@ -59,6 +61,9 @@ pub enum PartitionScheme {
     AutoRaidLvmLuks,
     #[strum(serialize = "manual")]
     Manual,
+    /// Format and mount partitions that already exist, see [`crate::mount_plan`]
+    #[strum(serialize = "existing")]
+    Existing,
 }
 
 #[allow(dead_code)] // Methods available for future use
@@ -94,14 +99,29 @@ impl PartitionScheme {
 #[strum(serialize_all = "lowercase")]
 pub enum DesktopEnvironment {
     #[default]
+    #[serde(alias = "minimal")]
     #[strum(serialize = "none")]
     None,
     #[strum(serialize = "gnome")]
     Gnome,
+    #[serde(alias = "plasma")]
     #[strum(serialize = "kde")]
     Kde,
+    #[strum(serialize = "xfce")]
+    Xfce,
+    #[strum(serialize = "cinnamon")]
+    Cinnamon,
+    #[strum(serialize = "mate")]
+    Mate,
+    #[strum(serialize = "budgie")]
+    Budgie,
     #[strum(serialize = "hyprland")]
     Hyprland,
+    #[strum(serialize = "sway")]
+    Sway,
+    #[serde(alias = "i3wm")]
+    #[strum(serialize = "i3")]
+    I3,
 }
 
 /// Display manager selection
@@ -116,6 +136,8 @@ pub enum DisplayManager {
     Gdm,
     #[strum(serialize = "sddm")]
     Sddm,
+    #[strum(serialize = "lightdm")]
+    Lightdm,
 }
 
 /// Bootloader selection
@@ -128,6 +150,9 @@ pub enum Bootloader {
     Grub,
     #[strum(serialize = "systemd-boot")]
     SystemdBoot,
+    /// extlinux.conf for the U-Boot of an aarch64 board
+    #[strum(serialize = "u-boot")]
+    UBoot,
 }
 
 /// AUR helper selection
@@ -174,6 +199,322 @@ pub enum GpuDriver {
     Intel,
 }
 
+/// Kernel modules of the NVIDIA driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[serde(rename_all = "lowercase")]
+pub enum NvidiaDriver {
+    /// Open kernel modules, for Turing (GTX 16xx, RTX 20xx) and newer cards
+    #[default]
+    #[strum(serialize = "open")]
+    Open,
+    /// Closed kernel modules, needed by cards older than Turing
+    #[strum(serialize = "proprietary")]
+    Proprietary,
+}
+
+/// CPU microcode loaded before the kernel
+///
+/// Named after the package; Auto leaves the choice to the installer, which
+/// reads the CPU vendor from /proc/cpuinfo.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    EnumIter,
+)]
+pub enum Microcode {
+    #[default]
+    #[serde(rename = "auto")]
+    #[strum(serialize = "auto")]
+    Auto,
+    #[serde(rename = "intel-ucode")]
+    #[strum(serialize = "intel-ucode")]
+    Intel,
+    #[serde(rename = "amd-ucode")]
+    #[strum(serialize = "amd-ucode")]
+    Amd,
+    #[serde(rename = "none")]
+    #[strum(serialize = "none")]
+    None,
+}
+
+impl Microcode {
+    /// Package providing the microcode, `None` for Auto and None
+    pub fn package(&self) -> Option<&'static str> {
+        match self {
+            Self::Intel => Some("intel-ucode"),
+            Self::Amd => Some("amd-ucode"),
+            Self::Auto | Self::None => None,
+        }
+    }
+}
+
+/// Virtual machine guest tools to install
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+pub enum GuestTools {
+    #[default]
+    #[strum(serialize = "None")]
+    None,
+    #[strum(serialize = "QEMU")]
+    Qemu,
+    #[strum(serialize = "VirtualBox")]
+    VirtualBox,
+    #[strum(serialize = "VMware")]
+    Vmware,
+    #[strum(serialize = "Hyper-V")]
+    HyperV,
+}
+
+impl GuestTools {
+    /// Packages providing the guest tools
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            Self::None => &[],
+            Self::Qemu => &["qemu-guest-agent"],
+            Self::VirtualBox => &["virtualbox-guest-utils"],
+            Self::Vmware => &["open-vm-tools"],
+            Self::HyperV => &["hyperv"],
+        }
+    }
+
+    /// Services enabled for the guest tools
+    ///
+    /// qemu-guest-agent is started by udev when the host provides its channel.
+    pub fn services(&self) -> &'static [&'static str] {
+        match self {
+            Self::None | Self::Qemu => &[],
+            Self::VirtualBox => &["vboxservice.service"],
+            Self::Vmware => &["vmtoolsd.service", "vmware-vmblock-fuse.service"],
+            Self::HyperV => &["hv_kvp_daemon.service", "hv_vss_daemon.service"],
+        }
+    }
+
+    /// Environment variables handing the packages and services to chroot_config.sh
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("GUEST_TOOLS_PACKAGES", self.packages().join(" ")),
+            ("GUEST_TOOLS_SERVICES", self.services().join(" ")),
+        ]
+    }
+}
+
+/// Sound server for the installed system
+///
+/// Desktop environments depend on a sound library, not a server, so this
+/// decides which server provides it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioServer {
+    #[default]
+    #[strum(serialize = "pipewire")]
+    PipeWire,
+    #[strum(serialize = "pulseaudio")]
+    PulseAudio,
+    #[strum(serialize = "none")]
+    None,
+}
+
+impl AudioServer {
+    /// Packages providing the sound server and its ALSA/Pulse/JACK clients
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            Self::PipeWire => &[
+                "pipewire",
+                "pipewire-alsa",
+                "pipewire-pulse",
+                "pipewire-jack",
+                "wireplumber",
+            ],
+            Self::PulseAudio => &["pulseaudio", "pulseaudio-alsa", "pulseaudio-bluetooth"],
+            Self::None => &[],
+        }
+    }
+
+    /// User units enabled for every user (`systemctl --global`)
+    pub fn user_services(&self) -> &'static [&'static str] {
+        match self {
+            Self::PipeWire => &[
+                "pipewire.socket",
+                "pipewire-pulse.socket",
+                "wireplumber.service",
+            ],
+            Self::PulseAudio => &["pulseaudio.socket"],
+            Self::None => &[],
+        }
+    }
+
+    /// Installed packages removed first, since pipewire-pulse and pulseaudio conflict
+    pub fn replaces(&self) -> &'static [&'static str] {
+        match self {
+            Self::PipeWire => &["pulseaudio-bluetooth", "pulseaudio-alsa", "pulseaudio"],
+            Self::PulseAudio => &["pipewire-pulse"],
+            Self::None => &[],
+        }
+    }
+
+    /// Environment variables handing the packages and user units to chroot_config.sh
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("AUDIO_PACKAGES", self.packages().join(" ")),
+            ("AUDIO_USER_SERVICES", self.user_services().join(" ")),
+            ("AUDIO_REPLACES", self.replaces().join(" ")),
+        ]
+    }
+}
+
+/// Power management for laptops
+///
+/// TLP and power-profiles-daemon both tune the power settings and conflict
+/// with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+pub enum PowerManagement {
+    #[default]
+    #[serde(rename = "none")]
+    #[strum(serialize = "none")]
+    None,
+    #[serde(rename = "tlp")]
+    #[strum(serialize = "tlp")]
+    Tlp,
+    #[serde(rename = "power-profiles-daemon")]
+    #[strum(serialize = "power-profiles-daemon")]
+    PowerProfilesDaemon,
+}
+
+impl PowerManagement {
+    /// Packages providing the power manager
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            Self::None => &[],
+            Self::Tlp => &["tlp"],
+            Self::PowerProfilesDaemon => &["power-profiles-daemon"],
+        }
+    }
+
+    /// Services enabled for the power manager
+    pub fn services(&self) -> &'static [&'static str] {
+        match self {
+            Self::None => &[],
+            Self::Tlp => &["tlp.service"],
+            Self::PowerProfilesDaemon => &["power-profiles-daemon.service"],
+        }
+    }
+}
+
+/// What closing the laptop lid does (logind's `HandleLidSwitch`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+pub enum LidSwitch {
+    #[default]
+    #[serde(rename = "suspend")]
+    #[strum(serialize = "suspend")]
+    Suspend,
+    #[serde(rename = "suspend-then-hibernate")]
+    #[strum(serialize = "suspend-then-hibernate")]
+    SuspendThenHibernate,
+    #[serde(rename = "hibernate")]
+    #[strum(serialize = "hibernate")]
+    Hibernate,
+    #[serde(rename = "lock")]
+    #[strum(serialize = "lock")]
+    Lock,
+    #[serde(rename = "poweroff")]
+    #[strum(serialize = "poweroff")]
+    PowerOff,
+    #[serde(rename = "ignore")]
+    #[strum(serialize = "ignore")]
+    Ignore,
+}
+
+/// Daemon keeping the clock in sync when Time Sync (NTP) is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+pub enum TimeSyncDaemon {
+    #[default]
+    #[serde(rename = "systemd-timesyncd")]
+    #[strum(serialize = "systemd-timesyncd")]
+    Timesyncd,
+    #[serde(rename = "chrony")]
+    #[strum(serialize = "chrony")]
+    Chrony,
+}
+
+impl TimeSyncDaemon {
+    /// Packages providing the daemon; timesyncd is part of systemd
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            Self::Timesyncd => &[],
+            Self::Chrony => &["chrony"],
+        }
+    }
+
+    /// Unit enabled for the daemon
+    pub fn service(&self) -> &'static str {
+        match self {
+            Self::Timesyncd => crate::services::TIMESYNCD,
+            Self::Chrony => "chronyd.service",
+        }
+    }
+}
+
+/// Time the hardware clock (RTC) keeps
+///
+/// Windows reads the hardware clock as local time, so dual-boot systems
+/// that should agree with it use localtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+pub enum HardwareClock {
+    #[default]
+    #[serde(rename = "UTC")]
+    #[strum(serialize = "UTC")]
+    Utc,
+    #[serde(rename = "localtime")]
+    #[strum(serialize = "localtime")]
+    LocalTime,
+}
+
+/// What to do with operating systems already on the install disk
+///
+/// Protect refuses to touch a disk that holds another system, Alongside
+/// keeps its partitions and installs into free space, Erase wipes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingOsPolicy {
+    #[default]
+    #[strum(serialize = "protect")]
+    Protect,
+    #[strum(serialize = "alongside")]
+    Alongside,
+    #[strum(serialize = "erase")]
+    Erase,
+}
+
+/// Where /tmp lives on the installed system
+///
+/// Tmpfs keeps it in memory, with a size limit written to fstab; Disk masks
+/// systemd's tmp.mount so /tmp is a directory on the root filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[serde(rename_all = "lowercase")]
+pub enum TmpPolicy {
+    #[default]
+    #[strum(serialize = "tmpfs")]
+    Tmpfs,
+    #[strum(serialize = "disk")]
+    Disk,
+}
+
 /// Generic Yes/No toggle for boolean-like options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[derive(Display, EnumString, EnumIter)]
@@ -262,6 +603,96 @@ pub enum SnapshotFrequency {
     Monthly,
 }
 
+/// What an unattended install does when an installation phase fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum ErrorPolicy {
+    /// Stop at the first failed phase
+    #[default]
+    #[strum(serialize = "abort")]
+    Abort,
+    /// Retry the failed phase, then abort
+    #[strum(serialize = "retry")]
+    Retry,
+    /// Record the failure and move on (critical phases still abort)
+    #[strum(serialize = "continue")]
+    Continue,
+}
+
+/// How a headless install reports progress on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable lines
+    #[default]
+    #[strum(serialize = "text")]
+    Text,
+    /// One JSON event per line, for provisioning tools
+    #[strum(serialize = "json")]
+    Json,
+}
+
+/// How the network tool configures IPv6 on an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum Ipv6Mode {
+    /// Addresses from router advertisements (SLAAC)
+    #[default]
+    #[strum(serialize = "ra")]
+    Ra,
+    /// Addresses from a DHCPv6 server
+    #[strum(serialize = "dhcpv6")]
+    Dhcpv6,
+    /// A fixed address and gateway
+    #[strum(serialize = "static")]
+    Static,
+    /// No IPv6 on the interface
+    #[strum(serialize = "off")]
+    Off,
+}
+
+/// Network service the network tool writes a persistent configuration for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum NetworkBackend {
+    /// A keyfile in /etc/NetworkManager/system-connections
+    #[strum(serialize = "networkmanager")]
+    NetworkManager,
+    /// A .network unit in /etc/systemd/network
+    #[strum(serialize = "networkd")]
+    Networkd,
+}
+
+/// Filesystem the format tool can create on a partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum FormatFilesystem {
+    #[default]
+    Ext4,
+    Xfs,
+    Btrfs,
+    F2fs,
+    Fat32,
+    Exfat,
+    Ntfs,
+}
+
+/// Btrfs metadata profile of a single-device filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum BtrfsProfile {
+    /// One copy of the metadata
+    Single,
+    /// Two copies of the metadata on the same device
+    Dup,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,6 +741,15 @@ mod tests {
         assert_eq!(Toggle::from(false), Toggle::No);
     }
 
+    #[test]
+    fn test_error_policy_parsing() {
+        assert_eq!(ErrorPolicy::from_str("abort").unwrap(), ErrorPolicy::Abort);
+        assert_eq!(ErrorPolicy::from_str("retry").unwrap(), ErrorPolicy::Retry);
+        assert_eq!(ErrorPolicy::from_str("continue").unwrap(), ErrorPolicy::Continue);
+        assert!(ErrorPolicy::from_str("ignore").is_err());
+        assert_eq!(ErrorPolicy::Continue.to_string(), "continue");
+    }
+
     #[test]
     fn test_serde_roundtrip() {
         let original = BootMode::Uefi;
@@ -328,4 +768,91 @@ mod tests {
         assert_eq!(Bootloader::default(), Bootloader::Grub);
         assert_eq!(Toggle::default(), Toggle::Yes);
     }
+
+    #[test]
+    fn test_microcode_round_trip() {
+        for microcode in Microcode::iter() {
+            assert_eq!(
+                Microcode::from_str(&microcode.to_string()).unwrap(),
+                microcode
+            );
+            let json = serde_json::to_string(&microcode).unwrap();
+            assert_eq!(json, format!("\"{}\"", microcode));
+        }
+        assert_eq!(Microcode::Amd.package(), Some("amd-ucode"));
+        assert_eq!(Microcode::Auto.package(), None);
+    }
+
+    #[test]
+    fn test_guest_tools_round_trip() {
+        for tools in GuestTools::iter() {
+            assert_eq!(GuestTools::from_str(&tools.to_string()).unwrap(), tools);
+        }
+        assert_eq!(GuestTools::Vmware.packages(), ["open-vm-tools"]);
+        assert!(GuestTools::None.packages().is_empty());
+        assert!(GuestTools::Qemu.services().is_empty());
+    }
+
+    #[test]
+    fn test_audio_server_round_trip() {
+        for audio in AudioServer::iter() {
+            assert_eq!(AudioServer::from_str(&audio.to_string()).unwrap(), audio);
+            let json = serde_json::to_string(&audio).unwrap();
+            assert_eq!(json, format!("\"{}\"", audio));
+        }
+        assert_eq!(AudioServer::default(), AudioServer::PipeWire);
+        assert!(AudioServer::PipeWire.packages().contains(&"wireplumber"));
+        assert_eq!(
+            AudioServer::PulseAudio.user_services(),
+            ["pulseaudio.socket"]
+        );
+        assert!(AudioServer::None.packages().is_empty());
+    }
+
+    #[test]
+    fn test_time_settings_round_trip() {
+        for daemon in TimeSyncDaemon::iter() {
+            assert_eq!(TimeSyncDaemon::from_str(&daemon.to_string()).unwrap(), daemon);
+            let json = serde_json::to_string(&daemon).unwrap();
+            assert_eq!(json, format!("\"{}\"", daemon));
+        }
+        for clock in HardwareClock::iter() {
+            assert_eq!(HardwareClock::from_str(&clock.to_string()).unwrap(), clock);
+            let json = serde_json::to_string(&clock).unwrap();
+            assert_eq!(json, format!("\"{}\"", clock));
+        }
+        assert_eq!(TimeSyncDaemon::Chrony.service(), "chronyd.service");
+        assert!(TimeSyncDaemon::Timesyncd.packages().is_empty());
+        assert_eq!(HardwareClock::default(), HardwareClock::Utc);
+    }
+
+    #[test]
+    fn test_power_settings_round_trip() {
+        for power in PowerManagement::iter() {
+            assert_eq!(PowerManagement::from_str(&power.to_string()).unwrap(), power);
+            let json = serde_json::to_string(&power).unwrap();
+            assert_eq!(json, format!("\"{}\"", power));
+        }
+        for lid in LidSwitch::iter() {
+            assert_eq!(LidSwitch::from_str(&lid.to_string()).unwrap(), lid);
+            let json = serde_json::to_string(&lid).unwrap();
+            assert_eq!(json, format!("\"{}\"", lid));
+        }
+        assert_eq!(PowerManagement::Tlp.services(), ["tlp.service"]);
+        assert!(PowerManagement::None.packages().is_empty());
+        assert_eq!(LidSwitch::default(), LidSwitch::Suspend);
+    }
+
+    #[test]
+    fn test_existing_os_policy_round_trip() {
+        for policy in ExistingOsPolicy::iter() {
+            assert_eq!(
+                ExistingOsPolicy::from_str(&policy.to_string()).unwrap(),
+                policy
+            );
+            let json = serde_json::to_string(&policy).unwrap();
+            assert_eq!(json, format!("\"{}\"", policy));
+        }
+        assert_eq!(ExistingOsPolicy::default(), ExistingOsPolicy::Protect);
+    }
 }
@@ -1,8 +1,12 @@
 //! Tool description text generation
 //!
 //! This module contains functions that generate description text
-//! for various tool categories and individual tools.
+//! for various tool categories, individual tools and guided installer
+//! options.
 
+use super::installer::validity_color;
+use crate::config::{options, ConfigOption, Validity};
+use crate::help;
 use crate::theme::{Colors, Styles};
 use ratatui::{
     style::{Modifier, Style},
@@ -50,6 +54,14 @@ pub fn get_tools_category_description(selection: usize) -> Vec<Line<'static>> {
                 "  • Mount/Unmount     - Manage mount points",
                 Styles::text_secondary(),
             )]),
+            Line::from(vec![Span::styled(
+                "  • Resize Partition  - Grow or shrink filesystems",
+                Styles::text_secondary(),
+            )]),
+            Line::from(vec![Span::styled(
+                "  • Clean Up          - Release a failed install",
+                Styles::text_secondary(),
+            )]),
         ],
         1 => vec![
             Line::from(""),
@@ -70,7 +82,7 @@ pub fn get_tools_category_description(selection: usize) -> Vec<Line<'static>> {
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![Span::styled(
-                "  • Install Bootloader - GRUB/systemd-boot",
+                "  • Repair Bootloader  - GRUB/systemd-boot",
                 Styles::text_secondary(),
             )]),
             Line::from(vec![Span::styled(
@@ -164,6 +176,45 @@ pub fn get_tools_category_description(selection: usize) -> Vec<Line<'static>> {
                 Styles::text_secondary(),
             )]),
         ],
+        4 => vec![
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Btrfs Snapshot Tools",
+                Styles::category(),
+            )]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Manage root snapshots of an installed system.",
+                Styles::text(),
+            )]),
+            Line::from(vec![Span::styled(
+                "  Use them from the ISO when an update broke boot.",
+                Styles::text(),
+            )]),
+            Line::from(""),
+            Line::from(vec![Span::styled(
+                "  Available tools:",
+                Style::default()
+                    .fg(Colors::SUCCESS)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(vec![Span::styled(
+                "  • List Snapshots  - Snapper and manual ones",
+                Styles::text_secondary(),
+            )]),
+            Line::from(vec![Span::styled(
+                "  • Create Snapshot - Read-only copy of @",
+                Styles::text_secondary(),
+            )]),
+            Line::from(vec![Span::styled(
+                "  • Delete Snapshot - Free its space",
+                Styles::text_secondary(),
+            )]),
+            Line::from(vec![Span::styled(
+                "  • Roll Back       - Boot a snapshot as /",
+                Styles::text_secondary(),
+            )]),
+        ],
         _ => vec![
             Line::from(""),
             Line::from(vec![Span::styled(
@@ -183,6 +234,75 @@ pub fn get_tools_category_description(selection: usize) -> Vec<Line<'static>> {
     }
 }
 
+/// Get description for the highlighted guided installer option and what is
+/// wrong with its value, `None` for the start button
+pub fn get_option_description(
+    option: Option<&ConfigOption>,
+    validity: Option<&Validity>,
+) -> Vec<Line<'static>> {
+    let Some(option) = option else {
+        return start_installation_description();
+    };
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            format!("  {}", option.name),
+            Styles::category(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            format!("  {}", option.description),
+            Styles::text(),
+        )]),
+        Line::from(""),
+    ];
+    if let Some((validity, reason)) =
+        validity.and_then(|validity| Some((validity, validity.reason()?)))
+    {
+        lines.push(Line::from(vec![Span::styled(
+            format!("  {} {}", validity.symbol(), reason),
+            Style::default().fg(validity_color(validity)),
+        )]));
+        lines.push(Line::from(""));
+    }
+    if !option.default_value.is_empty() && !options::is_secret(&option.name) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                "  Recommended: ",
+                Style::default()
+                    .fg(Colors::SUCCESS)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "{} (default)",
+                    options::shown_value(&option.name, &option.default_value)
+                ),
+                Styles::text(),
+            ),
+        ]));
+        lines.push(Line::from(""));
+    }
+
+    // The help page lists the values and what each one implies
+    if let Some(page) = help::option_help(&option.name) {
+        lines.extend(
+            help::render_markdown(page.text)
+                .into_iter()
+                .map(|mut line| {
+                    line.spans.insert(0, Span::raw("  "));
+                    line
+                }),
+        );
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::styled(
+            format!("  Arch Wiki: {}", page.wiki),
+            Styles::text_secondary(),
+        )]));
+    }
+    lines
+}
+
 /// Get description for disk tool
 pub fn get_disk_tool_description(selection: usize) -> Vec<Line<'static>> {
     match selection {
@@ -191,6 +311,8 @@ pub fn get_disk_tool_description(selection: usize) -> Vec<Line<'static>> {
         2 => wipe_disk_description(),
         3 => check_disk_health_description(),
         4 => mount_unmount_description(),
+        5 => resize_partition_description(),
+        6 => cleanup_description(),
         _ => back_to_menu_description("Tools Menu"),
     }
 }
@@ -198,7 +320,7 @@ pub fn get_disk_tool_description(selection: usize) -> Vec<Line<'static>> {
 /// Get description for system tool
 pub fn get_system_tool_description(selection: usize) -> Vec<Line<'static>> {
     match selection {
-        0 => install_bootloader_description(),
+        0 => repair_bootloader_description(),
         1 => generate_fstab_description(),
         2 => chroot_description(),
         3 => manage_services_description(),
@@ -230,6 +352,16 @@ pub fn get_network_tool_description(selection: usize) -> Vec<Line<'static>> {
     }
 }
 
+pub fn get_snapshot_tool_description(selection: usize) -> Vec<Line<'static>> {
+    match selection {
+        0 => list_snapshots_description(),
+        1 => create_snapshot_description(),
+        2 => delete_snapshot_description(),
+        3 => rollback_snapshot_description(),
+        _ => back_to_menu_description("Tools Menu"),
+    }
+}
+
 // Individual tool descriptions
 
 fn partition_disk_description() -> Vec<Line<'static>> {
@@ -313,10 +445,27 @@ fn format_partition_description() -> Vec<Line<'static>> {
             "  • xfs     - High-performance filesystem",
             Styles::text_secondary(),
         )]),
+        Line::from(vec![Span::styled(
+            "  • f2fs    - Flash-friendly, optional compression",
+            Styles::text_secondary(),
+        )]),
         Line::from(vec![Span::styled(
             "  • fat32   - For EFI system partitions",
             Styles::text_secondary(),
         )]),
+        Line::from(vec![Span::styled(
+            "  • exfat   - Large files on removable drives",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • ntfs    - Shared with Windows",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Also sets ext4 reserved blocks and the btrfs metadata profile.",
+            Styles::text(),
+        )]),
         Line::from(""),
         Line::from(vec![
             Span::styled("  ⚠️  ", Styles::error()),
@@ -348,17 +497,30 @@ fn wipe_disk_description() -> Vec<Line<'static>> {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![Span::styled(
-            "  • Zero fill    - Fast, single pass of zeros",
+            "  • Quick        - Signatures and partition tables",
             Styles::text_secondary(),
         )]),
         Line::from(vec![Span::styled(
-            "  • Random fill  - More secure, random data",
+            "  • Zero fill    - Every byte, with a progress bar",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • Discard      - TRIM every block (SSD)",
             Styles::text_secondary(),
         )]),
         Line::from(vec![Span::styled(
             "  • ATA Secure   - Hardware-level secure erase",
             Styles::text_secondary(),
         )]),
+        Line::from(vec![Span::styled(
+            "  • NVMe         - Format or Sanitize in firmware",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Only the methods the drive supports are offered.",
+            Styles::text_secondary(),
+        )]),
         Line::from(""),
         Line::from(vec![
             Span::styled("  🚨 ", Styles::error()),
@@ -407,6 +569,15 @@ fn check_disk_health_description() -> Vec<Line<'static>> {
             "  • Temperature readings",
             Styles::text_secondary(),
         )]),
+        Line::from(vec![Span::styled(
+            "  • SSD/NVMe wear level",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Start short or long self-tests from the report.",
+            Styles::text(),
+        )]),
         Line::from(""),
         Line::from(vec![
             Span::styled("  ℹ️  ", Styles::info()),
@@ -430,6 +601,14 @@ fn mount_unmount_description() -> Vec<Line<'static>> {
             "  Mount partitions to access their contents.",
             Styles::text(),
         )]),
+        Line::from(vec![Span::styled(
+            "  Shows the device tree with current mountpoints;",
+            Styles::text(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  the filesystem type is detected automatically.",
+            Styles::text(),
+        )]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Common mount points:",
@@ -465,40 +644,151 @@ fn mount_unmount_description() -> Vec<Line<'static>> {
     ]
 }
 
-fn install_bootloader_description() -> Vec<Line<'static>> {
+fn cleanup_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Clean Up Failed Install",
+            Styles::category(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Release everything a failed run left active,",
+            Styles::text(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  so the install can be retried without a reboot.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Teardown order:",
+            Style::default()
+                .fg(Colors::SUCCESS)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![Span::styled(
+            "  1. Swap on the target",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  2. Mounts under /mnt, deepest first",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  3. LVM volume groups",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  4. LUKS mappings",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  5. mdadm RAID arrays",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "  Note:",
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" No data is erased", Styles::info()),
+        ]),
+    ]
+}
+
+fn resize_partition_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Resize Partition",
+            Styles::category(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Grow or shrink a partition and its filesystem.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Filesystem rules:",
+            Style::default()
+                .fg(Colors::SUCCESS)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • ext4   - Grow anytime, shrink unmounted",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • btrfs  - Grow or shrink while mounted",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • xfs    - Grow only, while mounted",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • ntfs   - Read-only here, resize from Windows",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "  Tip:",
+                Style::default()
+                    .fg(Colors::PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                " Sizes: 40G, +5G, -5G or max",
+                Styles::info(),
+            ),
+        ]),
+    ]
+}
+
+fn repair_bootloader_description() -> Vec<Line<'static>> {
     vec![
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  Install Bootloader",
+            "  Repair Bootloader",
             Styles::category(),
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  Install a bootloader to make your system bootable.",
+            "  Find out why a system no longer boots, fix it.",
             Styles::text(),
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  Available bootloaders:",
+            "  Checks:",
             Style::default()
                 .fg(Colors::SUCCESS)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![Span::styled(
-            "  • GRUB         - Traditional, feature-rich",
+            "  • Firmware boot entry and loader on the ESP",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • grub.cfg or systemd-boot loader entries",
             Styles::text_secondary(),
         )]),
         Line::from(vec![Span::styled(
-            "  • systemd-boot - Simple, fast UEFI boot manager",
+            "  • Root UUID the kernel command line boots",
             Styles::text_secondary(),
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  Requirements:",
-            Styles::title(),
+            "  Fixes are confirmed before they run.",
+            Styles::text_secondary(),
         )]),
         Line::from(vec![Span::styled(
-            "  • Root partition mounted at /mnt",
+            "  Other systems are mounted at /mnt first.",
             Styles::text_secondary(),
         )]),
     ]
@@ -563,6 +853,18 @@ fn chroot_description() -> Vec<Line<'static>> {
             "  Enter an installed system for maintenance.",
             Styles::text(),
         )]),
+        Line::from(vec![Span::styled(
+            "  Pick its root partition: LUKS is unlocked,",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  /boot and the ESP are mounted from its fstab",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  and everything is unmounted on exit.",
+            Styles::text_secondary(),
+        )]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Common uses:",
@@ -607,9 +909,27 @@ fn manage_services_description() -> Vec<Line<'static>> {
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  Enable or disable systemd services.",
+            "  Browse systemd units with their enabled",
+            Styles::text(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  and active state, filter them with /,",
             Styles::text(),
         )]),
+        Line::from(vec![Span::styled(
+            "  and enable, disable, start, stop, mask",
+            Styles::text(),
+        )]),
+        Line::from(vec![Span::styled("  or unmask them.", Styles::text())]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  An installed system mounted at /mnt is",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  managed first; press c for this system.",
+            Styles::text_secondary(),
+        )]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Common services:",
@@ -920,6 +1240,10 @@ fn configure_network_description() -> Vec<Line<'static>> {
             "  • Static IP - Manual setup",
             Styles::text_secondary(),
         )]),
+        Line::from(vec![Span::styled(
+            "  • IPv6      - RA, DHCPv6, static or off",
+            Styles::text_secondary(),
+        )]),
         Line::from(vec![Span::styled(
             "  • WiFi      - Wireless connection",
             Styles::text_secondary(),
@@ -1066,6 +1390,173 @@ fn network_info_description() -> Vec<Line<'static>> {
     ]
 }
 
+fn list_snapshots_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled("  List Snapshots", Styles::category())]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Show the snapshots of the root subvolume (@).",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Information shown:",
+            Style::default()
+                .fg(Colors::SUCCESS)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • Snapshot number",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • Date taken (UTC)",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • Description, e.g. the pacman command",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Snapshots taken by snapper are listed too.",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  ℹ️  ", Styles::info()),
+            Span::styled("Read-only - no changes made", Styles::info()),
+        ]),
+    ]
+}
+
+fn create_snapshot_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled("  Create Snapshot", Styles::category())]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Take a read-only snapshot of the root subvolume.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Stored in @snapshots with snapper's metadata,",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  so snapper on the installed system sees it.",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  💡 ", Styles::info()),
+            Span::styled(
+                "Take one before repairing a system by hand",
+                Styles::info(),
+            ),
+        ]),
+    ]
+}
+
+fn delete_snapshot_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled("  Delete Snapshot", Styles::category())]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Delete a snapshot and free the space it holds.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Use List Snapshots to find its number.",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  ⚠️  ", Styles::error()),
+            Span::styled("A deleted snapshot cannot be restored", Styles::error()),
+        ]),
+    ]
+}
+
+fn rollback_snapshot_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Roll Back to Snapshot",
+            Styles::category(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Make a snapshot the root filesystem again.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  What happens:",
+            Style::default()
+                .fg(Colors::SUCCESS)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • @ is renamed to @.broken-<date>",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • A writable copy of the snapshot becomes @",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  • subvolid= is dropped from its /etc/fstab",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  The old root is kept; delete it once the",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  rolled back system boots.",
+            Styles::text_secondary(),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  ⚠️  ", Styles::error()),
+            Span::styled(
+                "@var and @home are separate and not rolled back",
+                Styles::error(),
+            ),
+        ]),
+    ]
+}
+
+fn start_installation_description() -> Vec<Line<'static>> {
+    vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Start Installation",
+            Styles::category(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  Check every option, then show the installation summary.",
+            Styles::text(),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "  The summary lists the settings and what happens to each disk;",
+            Styles::text_secondary(),
+        )]),
+        Line::from(vec![Span::styled(
+            "  nothing is written until its confirmation is typed.",
+            Styles::text_secondary(),
+        )]),
+    ]
+}
+
 fn back_to_menu_description(menu_name: &str) -> Vec<Line<'static>> {
     vec![
         Line::from(""),
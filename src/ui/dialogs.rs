@@ -3,18 +3,21 @@
 //! This module handles rendering of all dialogs: input dialogs,
 //! confirmation dialogs, embedded terminal, floating output, and file browser.
 
-use crate::app::ToolParameter;
 use crate::app::AppState;
 use crate::components::confirm_dialog::ConfirmDialog;
-use crate::components::floating_window::{FloatingWindow, FloatingWindowConfig};
+use crate::components::floating_window::FloatingWindow;
 use crate::components::pty_terminal::PtyTerminal;
+use crate::components::table::{Column as TableColumn, DataTable, TableCell, TableRow};
+use crate::components::tool_form::ToolForm;
+use crate::hardware::{DiskInfo, DiskKind};
 use crate::input::InputHandler;
 use crate::theme::Colors;
+use crate::types::GrubTheme;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
     Frame,
 };
 
@@ -27,7 +30,17 @@ pub fn render_embedded_terminal(
 ) {
     if let Some(pty) = pty_terminal {
         let title = if let Some(ref term_state) = state.embedded_terminal {
-            format!(" {} - Press Ctrl+Q to exit ", term_state.tool_name)
+            if term_state.finished {
+                let exit = pty
+                    .exit_status()
+                    .map_or_else(|| "Exited".to_string(), |status| status.to_string());
+                format!(
+                    " {} - {} - Press any key to return ",
+                    term_state.tool_name, exit
+                )
+            } else {
+                format!(" {} - Press Ctrl+Q to exit ", term_state.tool_name)
+            }
         } else {
             " Terminal - Press Ctrl+Q to exit ".to_string()
         };
@@ -42,31 +55,19 @@ pub fn render_embedded_terminal(
     }
 }
 
-/// Render floating output window
-pub fn render_floating_output(f: &mut Frame, state: &AppState) {
+/// Render floating output window over `area`, the screen above the nav bar
+pub fn render_floating_output(f: &mut Frame, state: &AppState, area: Rect) {
     if let Some(ref output) = state.floating_output {
-        let config = FloatingWindowConfig {
-            title: output.title.clone(),
-            width_percent: 80,
-            height_percent: 70,
-            ..Default::default()
-        };
-        let window = FloatingWindow::new(config);
+        let window = FloatingWindow::with_layout(&output.title, state.floating_layout);
 
         if let Some(progress) = output.progress {
-            window.render_with_progress(
-                f,
-                f.area(),
-                &output.content,
-                progress,
-                &output.status,
-            );
+            window.render_with_progress(f, area, &output.content, progress, &output.status);
         } else {
             window.render_text(
                 f,
-                f.area(),
+                area,
                 &output.content,
-                Some("Press Esc or Enter to close"),
+                Some("Esc/Enter close · +/- resize · M maximize · D dock"),
             );
         }
     }
@@ -79,6 +80,55 @@ pub fn render_file_browser(f: &mut Frame, state: &AppState) {
     }
 }
 
+/// Render SMART disk health report
+pub fn render_disk_health(f: &mut Frame, state: &AppState) {
+    if let Some(ref health) = state.tools.disk_health {
+        crate::components::disk_health::DiskHealthView::render(f, health);
+    }
+}
+
+/// Render the systemd unit browser
+pub fn render_service_manager(f: &mut Frame, state: &AppState) {
+    if let Some(ref services) = state.tools.services {
+        crate::components::service_manager::ServiceManagerView::render(f, services);
+    }
+}
+
+/// Render the block device mount manager
+pub fn render_mount_manager(f: &mut Frame, state: &AppState) {
+    if let Some(ref mounts) = state.tools.mounts {
+        crate::components::mount_manager::MountManagerView::render(f, mounts);
+    }
+}
+
+/// Render the check of the manual partition layout
+pub fn render_partition_check(f: &mut Frame, state: &AppState) {
+    if let Some(ref check) = state.guided.partition_check {
+        crate::components::partition_check::PartitionCheckView::render(f, check);
+    }
+}
+
+/// Render the Partition Mounts editor
+pub fn render_partition_mounts(f: &mut Frame, state: &AppState) {
+    if let Some(ref mounts) = state.guided.partition_mounts {
+        crate::components::partition_mounts::PartitionMountsView::render(f, mounts);
+    }
+}
+
+/// Render the recovery dialog for a failed installation phase
+pub fn render_recovery_dialog(f: &mut Frame, state: &AppState) {
+    if let Some(ref dialog) = state.install.recovery_dialog {
+        crate::components::recovery_dialog::RecoveryDialog::render(f, dialog);
+    }
+}
+
+/// Render the installation summary in specified area
+pub fn render_install_summary(f: &mut Frame, state: &AppState, area: Rect) {
+    if let Some(ref summary) = state.guided.summary {
+        crate::components::install_summary::InstallSummaryView::render(f, area, summary);
+    }
+}
+
 /// Render tool dialog in specified area
 pub fn render_tool_dialog_in_area(f: &mut Frame, state: &AppState, area: Rect) {
     // Render background
@@ -91,79 +141,8 @@ pub fn render_tool_dialog_in_area(f: &mut Frame, state: &AppState, area: Rect) {
 
 /// Render tool parameter dialog
 pub fn render_tool_dialog(f: &mut Frame, state: &AppState) {
-    if let Some(ref dialog) = state.tool_dialog {
-        let area = f.area();
-
-        // Create a centered dialog box
-        let dialog_width = (area.width * 3 / 4).min(80);
-        let dialog_height = (area.height * 3 / 4).min(20);
-        let dialog_x = (area.width - dialog_width) / 2;
-        let dialog_y = (area.height - dialog_height) / 2;
-
-        let dialog_rect = Rect::new(dialog_x, dialog_y, dialog_width, dialog_height);
-
-        // Draw dialog background
-        f.render_widget(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!("Configure {}", dialog.tool_name))
-                .style(Style::default().bg(Colors::FG_MUTED)),
-            dialog_rect,
-        );
-
-        // Render parameter list
-        let param_area = Rect::new(
-            dialog_x + 2,
-            dialog_y + 2,
-            dialog_width - 4,
-            dialog_height - 6,
-        );
-
-        let mut param_items = Vec::new();
-        for (i, param) in dialog.parameters.iter().enumerate() {
-            let style = if i == dialog.current_param {
-                Style::default().fg(Colors::SECONDARY)
-            } else {
-                Style::default()
-            };
-
-            let raw_value = if i < dialog.param_values.len() {
-                &dialog.param_values[i]
-            } else {
-                ""
-            };
-
-            // Mask password fields with asterisks for security
-            let display_value = if matches!(param.param_type, ToolParameter::Password(_)) {
-                "*".repeat(raw_value.len())
-            } else {
-                raw_value.to_string()
-            };
-
-            param_items.push(ListItem::new(Line::from(vec![
-                Span::styled(format!("{}: ", param.name), Style::default().fg(Colors::PRIMARY)),
-                Span::styled(display_value, style),
-            ])));
-        }
-
-        let param_list =
-            List::new(param_items).highlight_style(Style::default().fg(Colors::SECONDARY));
-
-        f.render_widget(param_list, param_area);
-
-        // Render instructions
-        let instruction_area = Rect::new(
-            dialog_x + 2,
-            dialog_y + dialog_height - 3,
-            dialog_width - 4,
-            1,
-        );
-
-        f.render_widget(
-            Paragraph::new("Enter: Next parameter | b: Back to tools")
-                .style(Style::default().fg(Colors::FG_SECONDARY)),
-            instruction_area,
-        );
+    if let Some(ref dialog) = state.tools.dialog {
+        ToolForm::render(f, dialog);
     }
 }
 
@@ -174,24 +153,36 @@ pub fn render_confirm_dialog(f: &mut Frame, state: &AppState) {
     }
 }
 
+/// Dim everything drawn so far, to sit behind a modal dialog
+fn dim_background(f: &mut Frame) {
+    let area = f.area();
+    f.buffer_mut().set_style(
+        area,
+        Style::default()
+            .fg(Colors::FG_DIMMED)
+            .bg(Color::Reset)
+            .add_modifier(Modifier::DIM)
+            .remove_modifier(Modifier::BOLD | Modifier::REVERSED),
+    );
+}
+
 /// Render input dialog overlay
 pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
     if let Some(ref mut dialog) = input_handler.current_dialog {
         let area = f.area();
 
-        // Fill entire screen with black background
-        let background = Block::default()
-            .borders(Borders::NONE)
-            .style(Style::default().bg(Colors::SELECTED_FG).fg(Colors::SELECTED_FG));
-        f.render_widget(background, area);
+        // Keep the screen behind the dialog visible so the option being
+        // edited stays in view
+        dim_background(f);
 
         // Calculate dialog size and position (centered)
-        let dialog_width = 80;
-        let dialog_height = 25;
+        let dialog_width = 80.min(area.width);
+        let dialog_height = 25.min(area.height);
         let x = (area.width.saturating_sub(dialog_width)) / 2;
         let y = (area.height.saturating_sub(dialog_height)) / 2;
 
         let dialog_area = Rect::new(x, y, dialog_width, dialog_height);
+        f.render_widget(Clear, dialog_area);
 
         // Create dialog layout
         let chunks = Layout::default()
@@ -227,7 +218,8 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
         // Content based on input type
         let selected_index = dialog.input_type.get_selected_index();
         match &mut dialog.input_type {
-            crate::input::InputType::TextInput { .. } => {
+            crate::input::InputType::TextInput { error, .. } => {
+                let error = error.clone();
                 let input_text = dialog.get_display_value();
                 let input_display = if input_text.is_empty() {
                     "Enter value...".to_string()
@@ -235,15 +227,40 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
                     input_text
                 };
 
+                let input_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(0)])
+                    .split(chunks[2]);
+
+                // Red border and the reason underneath while the value is invalid
+                let border_style = if error.is_some() {
+                    Style::default().fg(Colors::ERROR)
+                } else {
+                    Style::default()
+                };
                 let input_widget = Paragraph::new(input_display)
-                    .block(Block::default().borders(Borders::ALL).title("Input"))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(border_style)
+                            .title("Input"),
+                    )
                     .style(Style::default().fg(Colors::SUCCESS));
-                f.render_widget(input_widget, chunks[2]);
+                f.render_widget(input_widget, input_chunks[0]);
+
+                if let Some(err) = error {
+                    let error_widget = Paragraph::new(Line::from(Span::styled(
+                        format!(" ✗ {}", err),
+                        Style::default().fg(Colors::ERROR),
+                    )))
+                    .wrap(ratatui::widgets::Wrap { trim: true });
+                    f.render_widget(error_widget, input_chunks[1]);
+                }
             }
             crate::input::InputType::Selection {
+                field_name,
                 scroll_state,
                 options,
-                ..
             } => {
                 let (start, end) = scroll_state.visible_range();
                 let items: Vec<ListItem> = options
@@ -263,30 +280,41 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
 
                 let list = List::new(items)
                     .block(Block::default().borders(Borders::ALL).title("Options"));
-                f.render_widget(list, chunks[2]);
+
+                // GRUB themes are chosen by their look, shown next to the list
+                let theme = (field_name == "GRUB Theme Selection")
+                    .then(|| options.get(selected_index)?.parse::<GrubTheme>().ok())
+                    .flatten();
+                match theme {
+                    Some(theme) => {
+                        let columns = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Min(0), Constraint::Length(44)])
+                            .split(chunks[2]);
+                        f.render_widget(list, columns[0]);
+                        let block = Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!("Preview: {}", theme));
+                        let inner = block.inner(columns[1]);
+                        f.render_widget(block, columns[1]);
+                        f.render_widget(crate::grub_theme::preview(theme), inner);
+                    }
+                    None => f.render_widget(list, chunks[2]),
+                }
             }
             crate::input::InputType::DiskSelection {
-                available_disks, ..
+                available_disks,
+                table,
+                ..
             } => {
-                let items: Vec<ListItem> = available_disks
-                    .iter()
-                    .enumerate()
-                    .map(|(index, disk)| {
-                        let style = if index == selected_index {
-                            Style::default().fg(Colors::SECONDARY)
-                        } else {
-                            Style::default()
-                        };
-                        ListItem::new(disk.clone()).style(style)
-                    })
-                    .collect();
-
-                let list = List::new(items).block(
+                let columns = disk_columns(false);
+                let rows = disk_rows(available_disks, None);
+                let widget = DataTable::new(&columns, &rows).block(
                     Block::default()
                         .borders(Borders::ALL)
                         .title("Available Disks"),
                 );
-                f.render_widget(list, chunks[2]);
+                f.render_stateful_widget(widget, chunks[2], table);
             }
             crate::input::InputType::PackageSelection {
                 current_input,
@@ -295,7 +323,7 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
                 package_list,
                 show_search_results,
                 search_results,
-                list_state,
+                results,
                 is_pacman,
                 ..
             } => {
@@ -317,42 +345,44 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
                     .style(Style::default().bg(Colors::SELECTED_FG).fg(Colors::FG_PRIMARY));
 
                 if *show_search_results && !search_results.is_empty() {
-                    // Display search results with scrolling
-                    let package_items: Vec<ListItem> = search_results
+                    let columns = [
+                        TableColumn::fixed("", 1).unsorted(),
+                        TableColumn::content("Repo", 10),
+                        TableColumn::content("Name", 32),
+                        TableColumn::content("Version", 20),
+                        TableColumn::fixed("Inst", 4),
+                        TableColumn::fill("Description", 10),
+                    ];
+                    let rows: Vec<TableRow> = search_results
                         .iter()
                         .map(|p| {
-                            let status = if p.installed { "[I]" } else { "[ ]" };
-
                             // Check if this package is already selected in our config
                             let is_selected = package_list.contains(&p.name);
-                            let selection_indicator = if is_selected { "✓" } else { " " };
-
-                            let text = format!(
-                                "{} {} {}/{} ({}) - {}",
-                                status,
-                                selection_indicator,
-                                p.repo,
-                                p.name,
-                                p.version,
-                                p.description
-                            );
+                            let row = TableRow::new([
+                                TableCell::from(if is_selected { "✓" } else { " " }),
+                                TableCell::from(p.repo.as_str()),
+                                TableCell::from(p.name.as_str()),
+                                TableCell::from(p.version.as_str()),
+                                TableCell::from(if p.installed { "[I]" } else { "[ ]" }),
+                                TableCell::from(p.description.as_str()),
+                            ]);
 
                             // Style selected packages differently
-                            let style = if is_selected {
-                                Style::default()
-                                    .fg(Colors::SUCCESS)
-                                    .add_modifier(Modifier::BOLD)
+                            if is_selected {
+                                row.style(
+                                    Style::default()
+                                        .fg(Colors::SUCCESS)
+                                        .add_modifier(Modifier::BOLD),
+                                )
                             } else {
-                                Style::default()
-                            };
-
-                            ListItem::new(text).style(style)
+                                row
+                            }
                         })
                         .collect();
 
-                    let search_list = List::new(package_items)
+                    let search_table = DataTable::new(&columns, &rows)
                         .block(block.title(
-                            "Search Results - ↑↓ Navigate | Enter Toggle Selection | Esc Exit",
+                            "Search Results - ↑↓ Navigate | Tab Sort | Enter Toggle Selection | Esc Exit",
                         ))
                         .highlight_style(
                             Style::default()
@@ -361,7 +391,7 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
                         )
                         .highlight_symbol(">> ");
 
-                    f.render_stateful_widget(search_list, chunks[2], list_state);
+                    f.render_stateful_widget(search_table, chunks[2], results);
                 } else {
                     // Display normal command interface (like old version)
                     let max_visible_lines: usize = 15;
@@ -400,57 +430,53 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
                     .wrap(ratatui::widgets::Wrap { trim: true });
                 f.render_widget(warning_widget, chunks[2]);
             }
+            crate::input::InputType::Error { message } => {
+                let error_widget = Paragraph::new(message.join("\n"))
+                    .block(Block::default().borders(Borders::ALL).title("ERROR"))
+                    .style(Style::default().fg(Colors::ERROR))
+                    .alignment(Alignment::Center)
+                    .wrap(ratatui::widgets::Wrap { trim: true });
+                f.render_widget(error_widget, chunks[2]);
+            }
             crate::input::InputType::PasswordInput { .. } => {
-                let input_text = dialog.get_display_value();
-                let input_display = if input_text.is_empty() {
-                    "Enter password...".to_string()
-                } else {
-                    input_text
-                };
-
-                let input_widget = Paragraph::new(input_display)
-                    .block(Block::default().borders(Borders::ALL).title("Password"))
-                    .style(Style::default().fg(Colors::SUCCESS));
-                f.render_widget(input_widget, chunks[2]);
+                render_password_input(f, chunks[2], &dialog.input_type);
             }
             crate::input::InputType::MultiDiskSelection {
                 selected_disks,
                 available_disks,
-                scroll_state,
+                table,
                 min_disks,
                 max_disks,
                 ..
             } => {
-                // Create list items with selection status
-                let items: Vec<ListItem> = available_disks
-                    .iter()
-                    .enumerate()
-                    .map(|(i, disk)| {
-                        let is_selected = selected_disks.contains(disk);
-                        let status = if is_selected { "[X]" } else { "[ ]" };
-                        let item_text = format!("{} {}", status, disk);
-
-                        ListItem::new(item_text).style(if i == scroll_state.selected_index {
-                            Style::default().fg(Colors::SECONDARY).bg(Colors::FG_MUTED)
-                        } else if is_selected {
-                            Style::default().fg(Colors::SUCCESS)
-                        } else {
-                            Style::default().fg(Colors::FG_PRIMARY)
-                        })
-                    })
-                    .collect();
-
-                let list = List::new(items)
-                    .block(Block::default().borders(Borders::ALL).title(format!(
+                let columns = disk_columns(true);
+                let rows = disk_rows(available_disks, Some(selected_disks));
+                let widget = DataTable::new(&columns, &rows).block(
+                    Block::default().borders(Borders::ALL).title(format!(
                         "Selected: {}/{} (Min: {}, Max: {})",
                         selected_disks.len(),
                         max_disks,
                         min_disks,
                         max_disks
-                    )))
-                    .highlight_style(Style::default().fg(Colors::SECONDARY).bg(Colors::FG_MUTED));
-
-                f.render_widget(list, chunks[2]);
+                    )),
+                );
+                f.render_stateful_widget(widget, chunks[2], table);
+            }
+            crate::input::InputType::PackageGroupSelection {
+                selected,
+                scroll_state,
+                show_packages,
+            } => {
+                render_package_groups(f, chunks[2], selected, scroll_state, *show_packages);
+            }
+            crate::input::InputType::ServiceSelection {
+                selected,
+                scroll_state,
+            } => {
+                render_services(f, chunks[2], selected, scroll_state);
+            }
+            crate::input::InputType::MultiSelect { .. } => {
+                render_multi_select(f, chunks[2], &dialog.input_type);
             }
         }
 
@@ -462,3 +488,350 @@ pub fn render_input_dialog(f: &mut Frame, input_handler: &mut InputHandler) {
         f.render_widget(status, chunks[3]);
     }
 }
+
+/// Checkbox list of package groups, with the highlighted group's packages
+/// alongside when `show_packages` is set
+fn render_package_groups(
+    f: &mut Frame,
+    area: Rect,
+    selected: &[String],
+    scroll_state: &crate::scrolling::ScrollState,
+    show_packages: bool,
+) {
+    let groups = crate::package_utils::package_groups();
+    let area = if show_packages {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        if let Some(group) = groups.get(scroll_state.selected_index) {
+            let packages: Vec<ListItem> = group
+                .packages
+                .iter()
+                .map(|package| ListItem::new(package.as_str()))
+                .collect();
+            let list = List::new(packages).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{} ({})", group.name, group.packages.len())),
+            );
+            f.render_widget(list, columns[1]);
+        }
+        columns[0]
+    } else {
+        area
+    };
+
+    let (start, end) = scroll_state.visible_range();
+    let items: Vec<ListItem> = groups
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+        .map(|(index, group)| {
+            let checked = selected.contains(&group.id);
+            let style = if index == scroll_state.selected_index {
+                Style::default()
+                    .fg(Colors::SECONDARY)
+                    .add_modifier(Modifier::BOLD)
+            } else if checked {
+                Style::default().fg(Colors::SUCCESS)
+            } else {
+                Style::default().fg(Colors::FG_PRIMARY)
+            };
+            let mark = if checked { "[X]" } else { "[ ]" };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} {} - {}", mark, group.name, group.description),
+                    style,
+                ),
+                Span::styled(
+                    format!(" ({} packages)", group.packages.len()),
+                    Style::default().fg(Colors::FG_MUTED),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Groups: {} selected", selected.len())),
+    );
+    f.render_widget(list, area);
+}
+
+/// Checkbox list of the services that can be enabled
+fn render_services(
+    f: &mut Frame,
+    area: Rect,
+    selected: &[String],
+    scroll_state: &crate::scrolling::ScrollState,
+) {
+    let (start, end) = scroll_state.visible_range();
+    let items: Vec<ListItem> = crate::services::SERVICES
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+        .map(|(index, service)| {
+            let checked = selected.iter().any(|unit| unit == service.unit);
+            let style = if index == scroll_state.selected_index {
+                Style::default()
+                    .fg(Colors::SECONDARY)
+                    .add_modifier(Modifier::BOLD)
+            } else if checked {
+                Style::default().fg(Colors::SUCCESS)
+            } else {
+                Style::default().fg(Colors::FG_PRIMARY)
+            };
+            let mark = if checked { "[X]" } else { "[ ]" };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{} {} - {}", mark, service.name, service.description),
+                    style,
+                ),
+                Span::styled(
+                    format!(" ({})", service.unit),
+                    Style::default().fg(Colors::FG_MUTED),
+                ),
+            ];
+            if !service.packages.is_empty() {
+                spans.push(Span::styled(
+                    format!(" +{}", service.packages.join(" ")),
+                    Style::default().fg(Colors::FG_MUTED),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Services: {} enabled", selected.len())),
+    );
+    f.render_widget(list, area);
+}
+
+/// Checkbox list of a multi-select dialog with the checked count and limits,
+/// and the reason the last toggle or submit was refused
+fn render_multi_select(f: &mut Frame, area: Rect, input_type: &crate::input::InputType) {
+    let crate::input::InputType::MultiSelect {
+        options,
+        checked,
+        scroll_state,
+        min,
+        max,
+        error,
+        ..
+    } = input_type
+    else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    let (start, end) = scroll_state.visible_range();
+    let items: Vec<ListItem> = options
+        .iter()
+        .zip(checked)
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+        .map(|(index, (option, &checked))| {
+            let style = if index == scroll_state.selected_index {
+                Style::default()
+                    .fg(Colors::SECONDARY)
+                    .add_modifier(Modifier::BOLD)
+            } else if checked {
+                Style::default().fg(Colors::SUCCESS)
+            } else {
+                Style::default().fg(Colors::FG_PRIMARY)
+            };
+            let mark = if checked { "[X]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", mark, option)).style(style)
+        })
+        .collect();
+
+    let count = checked.iter().filter(|&&c| c).count();
+    let limits = if *max >= options.len() {
+        format!("min {}", min)
+    } else {
+        format!("min {}, max {}", min, max)
+    };
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Selected: {}/{} ({})", count, options.len(), limits)),
+    );
+    f.render_widget(list, chunks[0]);
+
+    if let Some(err) = error {
+        let error_widget = Paragraph::new(Line::from(Span::styled(
+            format!(" ✗ {}", err),
+            Style::default().fg(Colors::ERROR),
+        )));
+        f.render_widget(error_widget, chunks[1]);
+    }
+}
+
+/// Columns of the disk tables; `multi` adds the selection checkbox
+fn disk_columns(multi: bool) -> Vec<TableColumn> {
+    let mut columns = Vec::new();
+    if multi {
+        columns.push(TableColumn::fixed("", 3).unsorted());
+    }
+    columns.extend([
+        TableColumn::content("Device", 16),
+        TableColumn::content("Size", 10),
+        TableColumn::content("Type", 12),
+        TableColumn::fill("Model", 12),
+        TableColumn::content("Parts", 5),
+        TableColumn::fill("Contents", 14),
+    ]);
+    columns
+}
+
+/// One row per disk; `selected` lists the checked disks of a multi-disk dialog
+fn disk_rows(disks: &[DiskInfo], selected: Option<&[String]>) -> Vec<TableRow> {
+    disks
+        .iter()
+        .map(|disk| {
+            let checked = selected.is_some_and(|s| s.contains(&disk.path));
+            let kind = if disk.transport.is_empty()
+                || matches!(disk.kind, DiskKind::Nvme | DiskKind::Mmc)
+            {
+                disk.kind.to_string()
+            } else {
+                format!("{}/{}", disk.kind, disk.transport)
+            };
+            let contents = if disk.mounted {
+                format!("IN USE, {}", disk.contents())
+            } else {
+                disk.contents()
+            };
+            let contents_style = if disk.mounted {
+                Style::default().fg(Colors::ERROR)
+            } else if disk.partitions.is_empty() {
+                Style::default().fg(Colors::SUCCESS)
+            } else {
+                Style::default().fg(Colors::WARNING)
+            };
+
+            let mut cells = Vec::new();
+            if selected.is_some() {
+                cells.push(TableCell::new(if checked { "[X]" } else { "[ ]" }));
+            }
+            cells.extend([
+                TableCell::new(disk.path.as_str()),
+                TableCell::number(disk.size_display(), disk.size),
+                TableCell::new(kind),
+                TableCell::new(disk.model.as_str()),
+                TableCell::number(disk.partitions.len().to_string(), disk.partitions.len() as u64),
+                TableCell::new(contents).style(contents_style),
+            ]);
+            let style = if checked {
+                Style::default().fg(Colors::SUCCESS)
+            } else {
+                Style::default().fg(Colors::FG_PRIMARY)
+            };
+            TableRow::new(cells).style(style)
+        })
+        .collect()
+}
+
+/// Render the password entry, confirmation field, strength meter and warnings
+fn render_password_input(f: &mut Frame, area: Rect, input_type: &crate::input::InputType) {
+    use crate::password::{estimate_strength, looks_like_caps_lock, PasswordStrength};
+
+    let crate::input::InputType::PasswordInput {
+        current_value,
+        placeholder,
+        confirm_value,
+        confirming,
+        min_length,
+        error,
+        ..
+    } = input_type
+    else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Password
+            Constraint::Length(3), // Confirmation
+            Constraint::Length(2), // Strength meter
+            Constraint::Min(0),    // Warnings
+        ])
+        .split(area);
+
+    let masked = |value: &str, empty: &str| {
+        if value.is_empty() {
+            empty.to_string()
+        } else {
+            crate::input::mask(value)
+        }
+    };
+
+    let active = Style::default().fg(Colors::SUCCESS);
+    let inactive = Style::default().fg(Colors::FG_MUTED);
+
+    let password_widget = Paragraph::new(masked(current_value, placeholder))
+        .block(Block::default().borders(Borders::ALL).title("Password"))
+        .style(if *confirming { inactive } else { active });
+    f.render_widget(password_widget, chunks[0]);
+
+    let confirm_widget = Paragraph::new(masked(confirm_value, "Retype password..."))
+        .block(Block::default().borders(Borders::ALL).title("Confirm Password"))
+        .style(if *confirming { active } else { inactive });
+    f.render_widget(confirm_widget, chunks[1]);
+
+    // Strength meter: five segments filled according to the score
+    let strength = estimate_strength(current_value);
+    let color = match strength {
+        PasswordStrength::VeryWeak => Colors::ERROR,
+        PasswordStrength::Weak => Colors::ERROR_LIGHT,
+        PasswordStrength::Fair => Colors::WARNING,
+        PasswordStrength::Strong => Colors::SUCCESS_LIGHT,
+        PasswordStrength::VeryStrong => Colors::SUCCESS,
+    };
+    let filled = strength.score() as usize + 1;
+    let meter = Line::from(vec![
+        Span::styled(" Strength: ", Style::default().fg(Colors::FG_SECONDARY)),
+        Span::styled("■ ".repeat(filled), Style::default().fg(color)),
+        Span::styled("□ ".repeat(5 - filled), Style::default().fg(Colors::FG_MUTED)),
+        Span::styled(
+            strength.to_string(),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    f.render_widget(Paragraph::new(meter), chunks[2]);
+
+    let mut notes = Vec::new();
+    if let Some(err) = error {
+        notes.push(Line::from(Span::styled(
+            format!(" ✗ {}", err),
+            Style::default().fg(Colors::ERROR),
+        )));
+    }
+    let typed = if *confirming { confirm_value } else { current_value };
+    if looks_like_caps_lock(typed) {
+        notes.push(Line::from(Span::styled(
+            " ⚠ All letters are uppercase - is Caps Lock on?",
+            Style::default().fg(Colors::WARNING),
+        )));
+    }
+    if *min_length > 0 {
+        notes.push(Line::from(Span::styled(
+            format!(" Minimum length: {} characters", min_length),
+            Style::default().fg(Colors::FG_MUTED),
+        )));
+    }
+    f.render_widget(Paragraph::new(notes), chunks[3]);
+}
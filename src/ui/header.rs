@@ -1,25 +1,52 @@
 //! Header and common widget rendering
 //!
 //! This module contains the ASCII art header, title rendering,
-//! progress bars, and other common UI elements.
+//! progress bars, and other common UI elements. Terminals without Unicode
+//! get a smaller plain-text header.
+//!
+//! The header follows the size of the screen: the art and a boxed title on
+//! a large terminal, a one-line title once the art would crowd out the
+//! content (an 80x24 console) and nothing at all on a very small one.
 
-use crate::app::AppState;
+use crate::app::{AppMode, AppState};
 use crate::components::help_overlay::HelpOverlay;
 use crate::components::keybindings::KeybindingContext;
-use crate::components::nav_bar::NavBar;
-use crate::theme::Colors;
+use crate::components::status_bar::{format_elapsed, StatusBar};
+use crate::package_progress::PackageProgress;
+use crate::theme::{Charset, Colors};
+use crate::throughput::Throughput;
 use ratatui::{
     layout::{Alignment, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
+    widgets::{Block, Borders, Gauge, Paragraph},
     Frame,
 };
+use std::time::Instant;
+
+/// Rows below which the art gives way to a one-line title
+const FULL_MIN_HEIGHT: u16 = 30;
+
+/// Rows below which screens are drawn without a header
+const TITLE_MIN_HEIGHT: u16 = 12;
+
+/// How much of the header fits on a screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderSize {
+    /// The art and a boxed title
+    Full,
+    /// A one-line title
+    Title,
+    /// Nothing
+    Hidden,
+}
 
 /// Header renderer containing the ASCII art header
 pub struct HeaderRenderer {
     /// ASCII art header lines
     header_lines: Vec<Line<'static>>,
+    /// Columns the widest line of the art takes
+    width: u16,
 }
 
 impl Default for HeaderRenderer {
@@ -31,8 +58,49 @@ impl Default for HeaderRenderer {
 impl HeaderRenderer {
     /// Create a new header renderer
     pub fn new() -> Self {
+        Self::with_charset(Charset::Unicode)
+    }
+
+    /// Create a header renderer for the given character set
+    pub fn with_charset(charset: Charset) -> Self {
+        let header_lines = match charset {
+            Charset::Unicode => Self::create_header(),
+            Charset::Ascii => Self::create_ascii_header(),
+        };
+        let width = header_lines.iter().map(Line::width).max().unwrap_or(0) as u16;
         Self {
-            header_lines: Self::create_header(),
+            header_lines,
+            width,
+        }
+    }
+
+    /// How much of the header a screen drawn in `area` has room for
+    ///
+    /// The art also needs the full width; cut off it would be unreadable.
+    pub fn size(&self, area: Rect) -> HeaderSize {
+        if area.height >= FULL_MIN_HEIGHT && area.width >= self.width {
+            HeaderSize::Full
+        } else if area.height >= TITLE_MIN_HEIGHT {
+            HeaderSize::Title
+        } else {
+            HeaderSize::Hidden
+        }
+    }
+
+    /// Rows the art needs in `area`, including the blank line below it
+    pub fn height(&self, area: Rect) -> u16 {
+        match self.size(area) {
+            HeaderSize::Full => self.header_lines.len() as u16 + 1,
+            HeaderSize::Title | HeaderSize::Hidden => 0,
+        }
+    }
+
+    /// Rows the title needs in `area`
+    pub fn title_height(&self, area: Rect) -> u16 {
+        match self.size(area) {
+            HeaderSize::Full => 3,
+            HeaderSize::Title => 1,
+            HeaderSize::Hidden => 0,
         }
     }
 
@@ -49,12 +117,25 @@ impl HeaderRenderer {
     }
 
     /// Render a title section
+    ///
+    /// The title is boxed when `area` has the rows for it, see
+    /// [`HeaderRenderer::title_height`].
     pub fn render_title(&self, f: &mut Frame, area: Rect, title: &str) {
-        let title_widget = Paragraph::new(title)
-            .block(Block::default().borders(Borders::ALL))
-            .alignment(Alignment::Center)
-            .style(Style::default().fg(Colors::PRIMARY));
-        f.render_widget(title_widget, area);
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let title_widget = Paragraph::new(title).alignment(Alignment::Center);
+        let style = Style::default().fg(Colors::PRIMARY);
+        if area.height >= 3 {
+            f.render_widget(
+                title_widget
+                    .block(Block::default().borders(Borders::ALL))
+                    .style(style),
+                area,
+            );
+        } else {
+            f.render_widget(title_widget.style(style.add_modifier(Modifier::BOLD)), area);
+        }
     }
 
     /// Create the ASCII art header
@@ -86,6 +167,16 @@ impl HeaderRenderer {
             )]),
         ]
     }
+
+    /// Create the plain-text header for ASCII-only terminals
+    fn create_ascii_header() -> Vec<Line<'static>> {
+        let style = Style::default().fg(Colors::PRIMARY);
+        vec![
+            Line::from(Span::styled("+-----------------------------+", style)),
+            Line::from(Span::styled("|   A R C H   I N S T A L L   |", style)),
+            Line::from(Span::styled("+-----------------------------+", style)),
+        ]
+    }
 }
 
 /// Render instructions text
@@ -98,8 +189,11 @@ pub fn render_instructions(f: &mut Frame, area: Rect, text: &str) {
 }
 
 /// Render progress bar
-pub fn render_progress_bar(f: &mut Frame, area: Rect, progress: u16) {
-    let gauge = Gauge::default()
+///
+/// While pacman runs the gauge names the package, e.g.
+/// "40% - Installing package 143/420: firefox".
+pub fn render_progress_bar(f: &mut Frame, area: Rect, progress: u16, packages: &PackageProgress) {
+    let mut gauge = Gauge::default()
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -107,37 +201,81 @@ pub fn render_progress_bar(f: &mut Frame, area: Rect, progress: u16) {
         )
         .gauge_style(Style::default().fg(Colors::INFO))
         .percent(progress);
+    if packages.is_active() {
+        gauge = gauge.label(format!("{}% - {}", progress, packages.summary()));
+    }
     f.render_widget(gauge, area);
 }
 
-/// Render installer output
-pub fn render_installer_output(f: &mut Frame, area: Rect, output: &[String]) {
-    let output_lines: Vec<Line> = output.iter().map(|line| Line::from(line.clone())).collect();
-
-    let output_widget = Paragraph::new(output_lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Installer Output"),
-        )
-        .wrap(Wrap { trim: true });
-    f.render_widget(output_widget, area);
+/// Render download and disk rates, shown beside the progress bar
+pub fn render_throughput(f: &mut Frame, area: Rect, throughput: &Throughput) {
+    let rates = Paragraph::new(throughput.summary())
+        .block(Block::default().borders(Borders::ALL).title("Throughput"))
+        .style(Style::default().fg(Colors::SECONDARY));
+    f.render_widget(rates, area);
 }
 
-/// Render the navigation bar
+/// Render the status bar: keybinding hints, status message, elapsed time
 pub fn render_nav_bar(
     f: &mut Frame,
     state: &AppState,
     keybinding_ctx: &KeybindingContext,
     area: Rect,
 ) {
+    let now = Instant::now();
     let nav_items = keybinding_ctx.get_nav_items(&state.mode);
-    let nav_bar = NavBar::new(nav_items);
-    nav_bar.render(f, area);
+    let right = match state.install.started {
+        Some(started) if state.mode == AppMode::Installation => Some(format!(
+            "Elapsed {}",
+            format_elapsed(now.saturating_duration_since(started))
+        )),
+        _ => state.tools.current.clone(),
+    };
+    StatusBar::new(nav_items)
+        .message(state.status.visible(now))
+        .right(right)
+        .render(f, area);
 }
 
 /// Render the help overlay
 pub fn render_help_overlay(f: &mut Frame, state: &AppState, keybinding_ctx: &KeybindingContext) {
-    let help_overlay = HelpOverlay::new(&state.mode, keybinding_ctx);
+    let help_overlay = HelpOverlay::for_state(state, keybinding_ctx);
     help_overlay.render(f, f.area());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(width: u16, height: u16) -> Rect {
+        Rect::new(0, 0, width, height)
+    }
+
+    #[test]
+    fn test_header_collapses_with_the_screen() {
+        let header = HeaderRenderer::new();
+        assert_eq!(header.size(area(120, 45)), HeaderSize::Full);
+        assert_eq!(header.height(area(120, 45)), 7);
+        assert_eq!(header.title_height(area(120, 45)), 3);
+
+        // An 80x24 console, less the nav bar
+        assert_eq!(header.size(area(80, 23)), HeaderSize::Title);
+        assert_eq!(header.height(area(80, 23)), 0);
+        assert_eq!(header.title_height(area(80, 23)), 1);
+        // Tall but too narrow for the art
+        assert_eq!(header.size(area(80, 45)), HeaderSize::Title);
+
+        assert_eq!(header.size(area(60, 10)), HeaderSize::Hidden);
+        assert_eq!(
+            header.height(area(60, 10)) + header.title_height(area(60, 10)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ascii_art_fits_narrower_screens() {
+        let header = HeaderRenderer::with_charset(Charset::Ascii);
+        assert_eq!(header.size(area(80, 45)), HeaderSize::Full);
+        assert_eq!(header.height(area(80, 45)), 4);
+    }
+}
@@ -1,22 +1,28 @@
 //! Installation UI rendering module
 //!
 //! This module handles rendering of installation-related UI:
-//! - Configuration UI
+//! - Configuration UI (option list with descriptions, and wizard)
 //! - Automated install UI
 //! - Installation progress
 //! - Completion screen
 //! - Tool execution
 
-use super::header::{render_installer_output, render_progress_bar, HeaderRenderer};
+use super::descriptions;
+use super::header::{render_progress_bar, render_throughput, HeaderRenderer};
 use crate::app::AppState;
+use crate::components::output_log::OutputLogView;
+use crate::components::status_bar::format_elapsed;
+use crate::config::Validity;
+use crate::lanes::{LaneState, Lanes};
 use crate::theme::Colors;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Wrap},
     Frame,
 };
+use std::time::Instant;
 
 /// Render configuration UI in specified area
 pub fn render_configuration_ui_in_area(
@@ -28,19 +34,196 @@ pub fn render_configuration_ui_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
-            Constraint::Min(10),   // Configuration options
-            Constraint::Length(3), // Start button
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Min(10),                           // Configuration options
+            Constraint::Length(3),                         // Start button
         ])
         .split(area);
 
     header.render_header(f, chunks[0]);
     header.render_title(f, chunks[1], "Arch Linux Installation Configuration");
-    render_config_options(f, chunks[2], state);
+
+    // Split content into options and a description of the highlighted one
+    let content_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(chunks[2]);
+
+    render_config_options(f, content_chunks[0], state);
+    render_option_description(f, content_chunks[1], state);
     render_start_button(f, chunks[3], state);
 }
 
+/// Render the description panel of the highlighted option
+fn render_option_description(f: &mut Frame, area: Rect, state: &AppState) {
+    let selected = state.guided.scroll.selected_index;
+    let description = descriptions::get_option_description(
+        state.guided.config.options.get(selected),
+        state.guided.validity.get(selected),
+    );
+    let desc_widget = Paragraph::new(description)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Option Information ")
+                .title_style(
+                    Style::default()
+                        .fg(Colors::PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .border_style(Style::default().fg(Colors::PRIMARY)),
+        )
+        .style(Style::default().bg(Colors::BG_PRIMARY))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(desc_widget, area);
+}
+
+/// Render the guided installer's wizard: one option per screen
+pub fn render_wizard_ui_in_area(
+    f: &mut Frame,
+    state: &AppState,
+    area: Rect,
+    header: &HeaderRenderer,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Length(3),                         // Step progress
+            Constraint::Min(8),                            // Question
+            Constraint::Length(3),                         // Prev/Next
+        ])
+        .split(area);
+
+    header.render_header(f, chunks[0]);
+    header.render_title(f, chunks[1], "Arch Linux Installation Wizard");
+
+    let guided = &state.guided;
+    let current = guided.scroll.selected_index;
+    let steps = crate::wizard::steps(&guided.config, &guided.validity, &guided.wizard_skips());
+    let (position, total) = crate::wizard::progress(&steps, current);
+    let option = guided.config.options.get(current);
+    let section = option.map_or("Review", |option| {
+        crate::config::option_category(&option.name)
+    });
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("Progress"))
+        .gauge_style(Style::default().fg(Colors::INFO))
+        .ratio(position as f64 / total.max(1) as f64)
+        .label(format!("Step {} of {} - {}", position, total, section));
+    f.render_widget(gauge, chunks[2]);
+
+    let lines = match option {
+        Some(option) => wizard_question(option, guided.validity.get(current)),
+        None => wizard_review(state),
+    };
+    let question = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(question, chunks[3]);
+
+    let keys = if option.is_some() {
+        "← Prev   Enter Change   → Next   W All options"
+    } else {
+        "← Prev   Enter Review and start   W All options"
+    };
+    let keys = Paragraph::new(keys)
+        .block(Block::default().borders(Borders::ALL))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Colors::SECONDARY));
+    f.render_widget(keys, chunks[4]);
+}
+
+/// Lines of a wizard step: the option, its value and its help page
+fn wizard_question(
+    option: &crate::config::ConfigOption,
+    validity: Option<&Validity>,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            option.name.clone(),
+            Style::default()
+                .fg(Colors::PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            option.description.clone(),
+            Style::default().fg(Colors::FG_SECONDARY),
+        )),
+        Line::from(""),
+    ];
+
+    let marker = if option.is_modified() { " ●" } else { "" };
+    let mut value = vec![
+        Span::styled("Current value: ", Style::default().fg(Colors::FG_MUTED)),
+        Span::styled(
+            format!("{}{}", display_value(option), marker),
+            Style::default().fg(Colors::SECONDARY),
+        ),
+    ];
+    if let Some(validity) = validity {
+        let color = validity_color(validity);
+        value.push(Span::styled(
+            format!(" {}", validity.symbol()),
+            Style::default().fg(color),
+        ));
+        if let Some(reason) = validity.reason() {
+            value.push(Span::styled(
+                format!(" {}", reason),
+                Style::default().fg(color),
+            ));
+        }
+    }
+    lines.push(Line::from(value));
+
+    if let Some(page) = crate::help::option_help(&option.name) {
+        lines.push(Line::from(""));
+        lines.extend(crate::help::render_markdown(page.text));
+    }
+    lines
+}
+
+/// Lines of the wizard's last step, before the summary
+fn wizard_review(state: &AppState) -> Vec<Line<'static>> {
+    let guided = &state.guided;
+    let problems = guided
+        .validity
+        .iter()
+        .filter(|validity| **validity != Validity::Valid)
+        .count();
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Ready to install",
+            Style::default()
+                .fg(Colors::PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "{} options changed from their defaults.",
+            guided.config.modified_count()
+        )),
+    ];
+    lines.push(if problems == 0 {
+        Line::from(Span::styled(
+            "Every option is valid. Press Enter to review the summary and start.",
+            Style::default().fg(Colors::SUCCESS),
+        ))
+    } else {
+        Line::from(Span::styled(
+            format!(
+                "{} options still need attention; go back with ← or press W to see them all.",
+                problems
+            ),
+            Style::default().fg(Colors::WARNING),
+        ))
+    });
+    lines
+}
+
 /// Render automated install UI in specified area
 pub fn render_automated_install_ui_in_area(
     f: &mut Frame,
@@ -51,9 +234,9 @@ pub fn render_automated_install_ui_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
-            Constraint::Min(0),    // Content
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Min(0),                            // Content
         ])
         .split(area);
 
@@ -127,7 +310,7 @@ pub fn render_automated_install_ui_in_area(
         Line::from(vec![
             Span::styled("  📁 ", Style::default().fg(Colors::PRIMARY)),
             Span::styled("Supported formats: ", Style::default().fg(Colors::FG_SECONDARY)),
-            Span::styled(".toml, .json", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(".json", Style::default().fg(Colors::PRIMARY)),
         ]),
     ];
 
@@ -150,46 +333,47 @@ pub fn render_automated_install_ui_in_area(
     let config_lines = vec![
         Line::from(""),
         Line::from(vec![Span::styled(
-            "  # Example config.toml",
+            "  // Example config.json (excerpt)",
             Style::default().fg(Colors::FG_MUTED),
         )]),
         Line::from(""),
+        Line::from(vec![Span::styled("  {", Style::default().fg(Colors::FG_PRIMARY))]),
         Line::from(vec![
-            Span::styled("  hostname", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = ", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"archlinux\"", Style::default().fg(Colors::SUCCESS)),
+            Span::styled("    \"install_disk\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"/dev/sda\",", Style::default().fg(Colors::SUCCESS)),
         ]),
         Line::from(vec![
-            Span::styled("  username", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = ", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"user\"", Style::default().fg(Colors::SUCCESS)),
+            Span::styled("    \"partitioning_strategy\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"AutoSimple\",", Style::default().fg(Colors::SUCCESS)),
         ]),
         Line::from(vec![
-            Span::styled("  install_disk", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = ", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"/dev/sda\"", Style::default().fg(Colors::SUCCESS)),
+            Span::styled("    \"hostname\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"archlinux\",", Style::default().fg(Colors::SUCCESS)),
         ]),
         Line::from(vec![
-            Span::styled("  bootloader", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = ", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"grub\"", Style::default().fg(Colors::SUCCESS)),
+            Span::styled("    \"username\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"user\",", Style::default().fg(Colors::SUCCESS)),
         ]),
         Line::from(vec![
-            Span::styled("  desktop_environment", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = ", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"gnome\"", Style::default().fg(Colors::SUCCESS)),
+            Span::styled("    \"bootloader\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"Grub\",", Style::default().fg(Colors::SUCCESS)),
+        ]),
+        Line::from(vec![
+            Span::styled("    \"desktop_environment\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"Gnome\",", Style::default().fg(Colors::SUCCESS)),
         ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "  [packages]",
-            Style::default().fg(Colors::SECONDARY),
-        )]),
         Line::from(vec![
-            Span::styled("  base", Style::default().fg(Colors::PRIMARY)),
-            Span::styled(" = [", Style::default().fg(Colors::FG_PRIMARY)),
-            Span::styled("\"vim\", \"git\"", Style::default().fg(Colors::SUCCESS)),
-            Span::styled("]", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("    \"additional_packages\"", Style::default().fg(Colors::PRIMARY)),
+            Span::styled(": ", Style::default().fg(Colors::FG_PRIMARY)),
+            Span::styled("\"vim git\"", Style::default().fg(Colors::SUCCESS)),
         ]),
+        Line::from(vec![Span::styled("  }", Style::default().fg(Colors::FG_PRIMARY))]),
         Line::from(""),
         Line::from(vec![
             Span::styled("  Press ", Style::default().fg(Colors::FG_MUTED)),
@@ -232,15 +416,15 @@ pub fn render_tool_execution_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
-            Constraint::Min(0),    // Output
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Min(0),                            // Output
         ])
         .split(area);
 
     header.render_header(f, chunks[0]);
 
-    let title = if let Some(ref tool) = state.current_tool {
+    let title = if let Some(ref tool) = state.tools.current {
         format!("Running: {}", tool)
     } else {
         "Tool Execution".to_string()
@@ -249,7 +433,8 @@ pub fn render_tool_execution_in_area(
 
     // Render tool output
     let output_items: Vec<ListItem> = state
-        .tool_output
+        .tools
+        .output
         .iter()
         .map(|line| ListItem::new(line.as_str()))
         .collect();
@@ -266,67 +451,233 @@ pub fn render_installation_ui_in_area(
     area: Rect,
     header: &HeaderRenderer,
 ) {
+    let watchdog = state.install.watchdog.summary(Instant::now());
+    let lanes = &state.install.lanes;
+    let lanes_height = if lanes.is_active() {
+        lanes.lanes.len() as u16 + 2
+    } else {
+        0
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
-            Constraint::Length(3), // Progress bar
-            Constraint::Min(0),    // Installer output
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Length(3),                         // Progress bar
+            Constraint::Length(watchdog.is_some() as u16), // Quiet installer
+            Constraint::Length(lanes_height),              // Parallel steps
+            Constraint::Min(0),                            // Installer output
         ])
         .split(area);
 
     header.render_header(f, chunks[0]);
     header.render_title(f, chunks[1], "Arch Linux Installation Progress");
-    render_progress_bar(f, chunks[2], state.installation_progress as u16);
-    render_installer_output(f, chunks[3], &state.installer_output);
+    match state.install.throughput {
+        Some(ref throughput) => {
+            let width = throughput.summary().chars().count() as u16 + 2;
+            let progress = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(20), Constraint::Length(width)])
+                .split(chunks[2]);
+            render_progress_bar(
+                f,
+                progress[0],
+                state.install.progress as u16,
+                &state.install.package_progress,
+            );
+            render_throughput(f, progress[1], throughput);
+        }
+        None => render_progress_bar(
+            f,
+            chunks[2],
+            state.install.progress as u16,
+            &state.install.package_progress,
+        ),
+    }
+    if let Some(watchdog) = watchdog {
+        let color = if state.install.watchdog.stalled {
+            Colors::ERROR
+        } else {
+            Colors::WARNING
+        };
+        f.render_widget(
+            Paragraph::new(format!(" {}", watchdog)).style(Style::default().fg(color)),
+            chunks[3],
+        );
+    }
+    if lanes_height > 0 {
+        render_lanes(f, chunks[4], lanes);
+    }
+    OutputLogView::render(f, chunks[5], &state.install.output);
 }
 
-/// Render completion UI in specified area
+/// One line per step running side by side: state, time and last output
+fn render_lanes(f: &mut Frame, area: Rect, lanes: &Lanes) {
+    let now = Instant::now();
+    let name_width = lanes
+        .lanes
+        .iter()
+        .map(|lane| lane.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = lanes
+        .lanes
+        .iter()
+        .map(|lane| {
+            let (symbol, color) = match lane.state {
+                LaneState::Waiting => ("·", Colors::FG_MUTED),
+                LaneState::Running => ("▶", Colors::PRIMARY),
+                LaneState::Done => ("✓", Colors::SUCCESS),
+                LaneState::Failed => ("✗", Colors::ERROR),
+                LaneState::Skipped => ("-", Colors::WARNING),
+            };
+            let elapsed = lane.elapsed(now).map(format_elapsed).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {} ", symbol), Style::default().fg(color)),
+                Span::styled(
+                    format!("{:<width$}", lane.name, width = name_width),
+                    Style::default().fg(Colors::FG_PRIMARY),
+                ),
+                Span::styled(
+                    format!(" {:<8}{:>6}  ", lane.state.label(), elapsed),
+                    Style::default().fg(color),
+                ),
+                Span::styled(
+                    lane.last_line.clone(),
+                    Style::default().fg(Colors::FG_SECONDARY),
+                ),
+            ]))
+        })
+        .collect();
+    f.render_widget(
+        List::new(items).block(Block::default().borders(Borders::ALL).title("Parallel steps")),
+        area,
+    );
+}
+
+/// Render completion UI in specified area: the outcome, where the time went,
+/// what was downloaded and installed, and the warnings of the run
 pub fn render_completion_ui_in_area(
     f: &mut Frame,
     state: &AppState,
     area: Rect,
     header: &HeaderRenderer,
 ) {
+    let metrics = &state.install.metrics;
+    let warnings_height = match metrics.warnings.len() {
+        0 => 0,
+        count => count.min(6) as u16 + 2,
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
-            Constraint::Min(0),    // Completion message
+            Constraint::Length(header.height(area)),       // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Length(7),                         // Totals
+            Constraint::Min(0),                            // Phase durations
+            Constraint::Length(warnings_height),           // Warnings
         ])
         .split(area);
 
     header.render_header(f, chunks[0]);
     header.render_title(f, chunks[1], "Installation Complete");
 
-    let message = Paragraph::new(state.status_message.clone())
-        .block(Block::default().borders(Borders::ALL).title("Status"))
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(Colors::SUCCESS));
-    f.render_widget(message, chunks[2]);
+    let total = metrics
+        .total
+        .map_or_else(|| "-".to_string(), format_elapsed);
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<20}", name),
+                Style::default().fg(Colors::FG_SECONDARY),
+            ),
+            Span::raw(value),
+        ])
+    };
+    let totals = Paragraph::new(vec![
+        Line::from(Span::styled(
+            state.status.text().to_string(),
+            Style::default().fg(Colors::SUCCESS),
+        )),
+        field("Total time", total),
+        field("Downloaded", metrics.downloaded_text()),
+        field("Packages installed", metrics.packages_installed.to_string()),
+        field("Warnings", metrics.warnings.len().to_string()),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("Summary"));
+    f.render_widget(totals, chunks[2]);
+
+    let total_secs = metrics.total.map_or(0, |total| total.as_secs()).max(1);
+    let phases: Vec<ListItem> = metrics
+        .phases
+        .iter()
+        .map(|phase| {
+            let share = phase.duration.as_secs() * 100 / total_secs;
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<28}", phase.name)),
+                Span::styled(
+                    format!("{:>9}", format_elapsed(phase.duration)),
+                    Style::default().fg(Colors::PRIMARY),
+                ),
+                Span::styled(
+                    format!("{:>6}%", share),
+                    Style::default().fg(Colors::FG_MUTED),
+                ),
+            ]))
+        })
+        .collect();
+    let phases = List::new(phases).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Phases (W saves this summary with the installation report)"),
+    );
+    f.render_widget(phases, chunks[3]);
+
+    if warnings_height > 0 {
+        let warnings: Vec<ListItem> = metrics
+            .warnings
+            .iter()
+            .map(|warning| {
+                ListItem::new(Span::styled(
+                    warning.clone(),
+                    Style::default().fg(Colors::WARNING),
+                ))
+            })
+            .collect();
+        let title = format!("Warnings ({})", metrics.warnings.len());
+        let warnings =
+            List::new(warnings).block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(warnings, chunks[4]);
+    }
 }
 
 /// Render configuration options list with scrolling
 fn render_config_options(f: &mut Frame, area: Rect, state: &AppState) {
-    let (start_idx, end_idx) = state.config_scroll.visible_range();
+    let (start_idx, end_idx) = state.guided.scroll.visible_range();
 
     // Create visible items with proper styling
     let visible_items: Vec<ListItem> = state
+        .guided
         .config
         .options
         .iter()
         .enumerate()
         .skip(start_idx)
         .take(end_idx - start_idx)
-        .map(|(index, option)| create_config_item(option, index, state.config_scroll.selected_index))
+        .map(|(index, option)| {
+            create_config_item(
+                option,
+                state.guided.validity.get(index),
+                index,
+                state.guided.scroll.selected_index,
+            )
+        })
         .collect();
 
     // Create title with page info
-    let title = if let Some((current_page, total_pages)) = state.config_scroll.page_info() {
+    let title = if let Some((current_page, total_pages)) = state.guided.scroll.page_info() {
         format!(
-            "Configuration Options (Page {}/{} - ↑↓ Scroll, PgUp/PgDn, Home/End)",
+            "Configuration Options (Page {}/{})",
             current_page, total_pages
         )
     } else {
@@ -337,42 +688,68 @@ fn render_config_options(f: &mut Frame, area: Rect, state: &AppState) {
     f.render_widget(list, area);
 }
 
-/// Create a configuration item with proper styling
-fn create_config_item(
-    option: &crate::config::ConfigOption,
-    index: usize,
-    current_step: usize,
-) -> ListItem<'static> {
-    let display_value = if option.value.is_empty() {
+/// Value of an option as shown in the guided installer
+fn display_value(option: &crate::config::ConfigOption) -> String {
+    if option.value.is_empty() {
         "[Press Enter]".to_string()
     } else {
         // Special display logic for different field types
         match option.name.as_str() {
-            "User Password" | "Root Password" => "***".to_string(),
-            "Additional Pacman Packages" | "Additional AUR Packages" => {
-                if option.value.is_empty() {
-                    "[Press Enter]".to_string()
-                } else {
-                    option.value.clone()
-                }
-            }
-            _ => option.value.clone(),
+            name if crate::config::options::is_secret(name) => "***".to_string(),
+            name => crate::config::options::shown_value(name, &option.value),
         }
-    };
+    }
+}
+
+/// Colour of an option's validity marker
+pub(super) fn validity_color(validity: &Validity) -> ratatui::style::Color {
+    match validity {
+        Validity::Valid => Colors::SUCCESS,
+        Validity::Invalid(_) => Colors::ERROR,
+        Validity::Conflict(_) => Colors::WARNING,
+    }
+}
 
-    let text = format!("{}: {}", option.name, display_value);
+/// Create a configuration item with proper styling
+fn create_config_item(
+    option: &crate::config::ConfigOption,
+    validity: Option<&Validity>,
+    index: usize,
+    current_step: usize,
+) -> ListItem<'static> {
+    // Options changed from their default are marked and highlighted
+    let marker = if option.is_modified() { " ●" } else { "" };
+    let text = format!("{}: {}{}", option.name, display_value(option), marker);
     let style = if index == current_step {
         Style::default().fg(Colors::SECONDARY)
+    } else if option.is_modified() {
+        Style::default().fg(Colors::INFO)
     } else {
         Style::default()
     };
 
-    ListItem::new(text).style(style)
+    let mut spans = vec![Span::styled(text, style)];
+    if let Some(validity) = validity {
+        let color = validity_color(validity);
+        spans.push(Span::styled(
+            format!(" {}", validity.symbol()),
+            Style::default().fg(color),
+        ));
+        // The focused option says what is wrong with it
+        if let Some(reason) = validity.reason().filter(|_| index == current_step) {
+            spans.push(Span::styled(
+                format!(" {}", reason),
+                Style::default().fg(color),
+            ));
+        }
+    }
+
+    ListItem::new(Line::from(spans))
 }
 
 /// Render start button
 fn render_start_button(f: &mut Frame, area: Rect, state: &AppState) {
-    let is_selected = state.config_scroll.selected_index == state.config.options.len();
+    let is_selected = state.guided.scroll.selected_index == state.guided.config.options.len();
     let button_text = if is_selected {
         "  START INSTALLATION (Press Enter)  "
     } else {
@@ -37,7 +37,7 @@ pub fn render_loading_screen(f: &mut Frame, state: &AppState) {
         .split(chunks[1])[1];
 
     // Construct the paragraph for the loading message
-    let loading_message_text = state.status_message.clone();
+    let loading_message_text = state.status.text().to_string();
     let title_text = "Loading Data";
 
     // Create a simple spinner animation
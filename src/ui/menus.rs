@@ -5,10 +5,12 @@
 
 use super::descriptions;
 use super::header::HeaderRenderer;
-use crate::app::AppState;
+use crate::app::{AppMode, AppState};
+use crate::privilege;
+use crate::self_update::Release;
 use crate::theme::Colors;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame,
@@ -21,22 +23,28 @@ pub fn render_main_menu_in_area(
     area: Rect,
     header: &HeaderRenderer,
 ) {
+    let banner_height = if state.update.is_some() { 3 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Length(banner_height), // Update banner
             Constraint::Min(10),   // Menu
         ])
         .split(area);
 
     header.render_header(f, chunks[0]);
     header.render_title(f, chunks[1], "Arch Linux Toolkit");
+    if let Some(release) = &state.update {
+        render_update_banner(f, chunks[2], release);
+    }
 
     let menu_items = [
         " ▶ Guided Installer  (Recommended for new users)",
         " ▶ Automated Install (Run from configuration file)",
         " ▶ Arch Linux Tools  (System repair and administration)",
+        " ▶ Clone System      (Copy this install to a new disk)",
         " ▶ Quit",
     ];
 
@@ -44,7 +52,7 @@ pub fn render_main_menu_in_area(
         .iter()
         .enumerate()
         .map(|(index, item)| {
-            let style = if index == state.main_menu_selection {
+            let style = if index == state.menu.main_selection {
                 Style::default()
                     .fg(Colors::SECONDARY)
                     .add_modifier(Modifier::BOLD)
@@ -65,7 +73,19 @@ pub fn render_main_menu_in_area(
         )
         .highlight_symbol(">> ");
 
-    f.render_widget(menu, chunks[2]);
+    f.render_widget(menu, chunks[3]);
+}
+
+/// Banner announcing a newer installer release
+fn render_update_banner(f: &mut Frame, area: Rect, release: &Release) {
+    let banner = Paragraph::new(format!(
+        "archinstall-tui {} is available: run 'archinstall-tui self-update'",
+        release.version
+    ))
+    .block(Block::default().borders(Borders::ALL).title("Update"))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(Colors::WARNING));
+    f.render_widget(banner, area);
 }
 
 /// Render tools menu in specified area
@@ -78,8 +98,8 @@ pub fn render_tools_menu_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
             Constraint::Min(10),   // Content
         ])
         .split(area);
@@ -98,6 +118,7 @@ pub fn render_tools_menu_in_area(
         ("🔧", "System Tools"),
         ("👥", "User Tools"),
         ("🌐", "Network Tools"),
+        ("📸", "Snapshot Tools"),
         ("◀️ ", "Back to Main Menu"),
     ];
 
@@ -105,14 +126,14 @@ pub fn render_tools_menu_in_area(
         .iter()
         .enumerate()
         .map(|(index, (icon, name))| {
-            let style = if index == state.tools_menu_selection {
+            let style = if index == state.menu.tools_selection {
                 Style::default()
                     .fg(Colors::SECONDARY)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(Colors::FG_PRIMARY)
             };
-            let prefix = if index == state.tools_menu_selection {
+            let prefix = if index == state.menu.tools_selection {
                 "▸ "
             } else {
                 "  "
@@ -138,7 +159,7 @@ pub fn render_tools_menu_in_area(
     f.render_widget(menu, content_chunks[0]);
 
     // Description panel
-    let description = descriptions::get_tools_category_description(state.tools_menu_selection);
+    let description = descriptions::get_tools_category_description(state.menu.tools_selection);
     let desc_widget = Paragraph::new(description)
         .block(
             Block::default()
@@ -157,6 +178,127 @@ pub fn render_tools_menu_in_area(
     f.render_widget(desc_widget, content_chunks[1]);
 }
 
+/// Disk tools menu entries (icon, name); the last one goes back
+const DISK_TOOLS: &[(&str, &str)] = &[
+    ("💾", "Partition Disk"),
+    ("📀", "Format Partition"),
+    ("🗑️ ", "Wipe Disk"),
+    ("🔍", "Check Disk Health"),
+    ("📁", "Mount/Unmount"),
+    ("📐", "Resize Partition"),
+    ("🧹", "Clean Up Failed Install"),
+    ("◀️ ", "Back to Tools Menu"),
+];
+
+/// System tools menu entries (icon, name); the last one goes back
+const SYSTEM_TOOLS: &[(&str, &str)] = &[
+    ("🔧", "Repair Bootloader"),
+    ("📋", "Generate fstab"),
+    ("🖥️ ", "Chroot into System"),
+    ("⚙️ ", "Manage Services"),
+    ("ℹ️ ", "System Info"),
+    ("◀️ ", "Back to Tools Menu"),
+];
+
+/// User tools menu entries (icon, name); the last one goes back
+const USER_TOOLS: &[(&str, &str)] = &[
+    ("👤", "Add User"),
+    ("🔑", "Reset Password"),
+    ("👥", "Manage Groups"),
+    ("🔌", "Configure SSH"),
+    ("🛡️ ", "Security Audit"),
+    ("◀️ ", "Back to Tools Menu"),
+];
+
+/// Network tools menu entries (icon, name); the last one goes back
+const NETWORK_TOOLS: &[(&str, &str)] = &[
+    ("🌐", "Configure Network"),
+    ("📡", "Test Connectivity"),
+    ("🔥", "Firewall Rules"),
+    ("📊", "Network Info"),
+    ("◀️ ", "Back to Tools Menu"),
+];
+
+/// Snapshot tools menu entries (icon, name); the last one goes back
+const SNAPSHOT_TOOLS: &[(&str, &str)] = &[
+    ("📋", "List Snapshots"),
+    ("📸", "Create Snapshot"),
+    ("🗑️ ", "Delete Snapshot"),
+    ("⏪", "Roll Back to Snapshot"),
+    ("◀️ ", "Back to Tools Menu"),
+];
+
+/// Shown after tools that need root when running unprivileged
+const LOCK: &str = "🔒";
+
+fn tool_entries(mode: &AppMode) -> &'static [(&'static str, &'static str)] {
+    match mode {
+        AppMode::DiskTools => DISK_TOOLS,
+        AppMode::SystemTools => SYSTEM_TOOLS,
+        AppMode::UserTools => USER_TOOLS,
+        AppMode::NetworkTools => NETWORK_TOOLS,
+        AppMode::SnapshotTools => SNAPSHOT_TOOLS,
+        _ => &[],
+    }
+}
+
+/// Name of the tool at `selection` in a tools submenu (`None` for Back)
+pub fn tool_name(mode: &AppMode, selection: usize) -> Option<&'static str> {
+    let entries = tool_entries(mode);
+    entries
+        .get(selection)
+        .filter(|_| selection + 1 < entries.len())
+        .map(|(_, name)| *name)
+}
+
+/// Submenu and position of the tool called `name`
+pub fn find_tool(name: &str) -> Option<(AppMode, usize)> {
+    [
+        AppMode::DiskTools,
+        AppMode::SystemTools,
+        AppMode::UserTools,
+        AppMode::NetworkTools,
+        AppMode::SnapshotTools,
+    ]
+    .into_iter()
+    .find_map(|mode| {
+        let index = (0..tool_entries(&mode).len()).find(|&i| tool_name(&mode, i) == Some(name))?;
+        Some((mode, index))
+    })
+}
+
+/// List items of a tools submenu, locking root-only tools when unprivileged
+fn tool_list_items(state: &AppState, menu: AppMode) -> Vec<ListItem<'static>> {
+    tool_entries(&menu)
+        .iter()
+        .enumerate()
+        .map(|(index, (icon, name))| {
+            let locked =
+                !state.privileged && tool_name(&menu, index).is_some_and(privilege::requires_root);
+            let style = if index == state.menu.tools_selection {
+                Style::default()
+                    .fg(Colors::SECONDARY)
+                    .add_modifier(Modifier::BOLD)
+            } else if locked {
+                Style::default().fg(Colors::FG_MUTED)
+            } else {
+                Style::default().fg(Colors::FG_PRIMARY)
+            };
+            let prefix = if index == state.menu.tools_selection {
+                "▸ "
+            } else {
+                "  "
+            };
+            let lock = if locked {
+                format!(" {}", LOCK)
+            } else {
+                String::new()
+            };
+            ListItem::new(format!("{}{} {}{}", prefix, icon, name, lock)).style(style)
+        })
+        .collect()
+}
+
 /// Render disk tools menu in specified area
 pub fn render_disk_tools_menu_in_area(
     f: &mut Frame,
@@ -167,8 +309,8 @@ pub fn render_disk_tools_menu_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
             Constraint::Min(10),   // Content
         ])
         .split(area);
@@ -182,34 +324,7 @@ pub fn render_disk_tools_menu_in_area(
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
         .split(chunks[2]);
 
-    let menu_items = [
-        ("💾", "Partition Disk", "cfdisk"),
-        ("📀", "Format Partition", "mkfs"),
-        ("🗑️ ", "Wipe Disk", "secure erase"),
-        ("🔍", "Check Disk Health", "SMART"),
-        ("📁", "Mount/Unmount", "mount"),
-        ("◀️ ", "Back to Tools Menu", ""),
-    ];
-
-    let menu_items: Vec<ListItem> = menu_items
-        .iter()
-        .enumerate()
-        .map(|(index, (icon, name, _))| {
-            let style = if index == state.tools_menu_selection {
-                Style::default()
-                    .fg(Colors::SECONDARY)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Colors::FG_PRIMARY)
-            };
-            let prefix = if index == state.tools_menu_selection {
-                "▸ "
-            } else {
-                "  "
-            };
-            ListItem::new(format!("{}{} {}", prefix, icon, name)).style(style)
-        })
-        .collect();
+    let menu_items = tool_list_items(state, AppMode::DiskTools);
 
     let menu = List::new(menu_items)
         .block(
@@ -228,7 +343,7 @@ pub fn render_disk_tools_menu_in_area(
     f.render_widget(menu, content_chunks[0]);
 
     // Description panel
-    let description = descriptions::get_disk_tool_description(state.tools_menu_selection);
+    let description = descriptions::get_disk_tool_description(state.menu.tools_selection);
     let desc_widget = Paragraph::new(description)
         .block(
             Block::default()
@@ -257,8 +372,8 @@ pub fn render_system_tools_menu_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
             Constraint::Min(10),   // Content
         ])
         .split(area);
@@ -272,34 +387,7 @@ pub fn render_system_tools_menu_in_area(
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
         .split(chunks[2]);
 
-    let menu_items = [
-        ("🔧", "Install Bootloader"),
-        ("📋", "Generate fstab"),
-        ("🖥️ ", "Chroot into System"),
-        ("⚙️ ", "Manage Services"),
-        ("ℹ️ ", "System Info"),
-        ("◀️ ", "Back to Tools Menu"),
-    ];
-
-    let menu_items: Vec<ListItem> = menu_items
-        .iter()
-        .enumerate()
-        .map(|(index, (icon, name))| {
-            let style = if index == state.tools_menu_selection {
-                Style::default()
-                    .fg(Colors::SECONDARY)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Colors::FG_PRIMARY)
-            };
-            let prefix = if index == state.tools_menu_selection {
-                "▸ "
-            } else {
-                "  "
-            };
-            ListItem::new(format!("{}{} {}", prefix, icon, name)).style(style)
-        })
-        .collect();
+    let menu_items = tool_list_items(state, AppMode::SystemTools);
 
     let menu = List::new(menu_items)
         .block(
@@ -318,7 +406,7 @@ pub fn render_system_tools_menu_in_area(
     f.render_widget(menu, content_chunks[0]);
 
     // Description panel
-    let description = descriptions::get_system_tool_description(state.tools_menu_selection);
+    let description = descriptions::get_system_tool_description(state.menu.tools_selection);
     let desc_widget = Paragraph::new(description)
         .block(
             Block::default()
@@ -347,8 +435,8 @@ pub fn render_user_tools_menu_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
             Constraint::Min(10),   // Content
         ])
         .split(area);
@@ -362,34 +450,7 @@ pub fn render_user_tools_menu_in_area(
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
         .split(chunks[2]);
 
-    let menu_items = [
-        ("👤", "Add User"),
-        ("🔑", "Reset Password"),
-        ("👥", "Manage Groups"),
-        ("🔒", "Configure SSH"),
-        ("🛡️ ", "Security Audit"),
-        ("◀️ ", "Back to Tools Menu"),
-    ];
-
-    let menu_items: Vec<ListItem> = menu_items
-        .iter()
-        .enumerate()
-        .map(|(index, (icon, name))| {
-            let style = if index == state.tools_menu_selection {
-                Style::default()
-                    .fg(Colors::SECONDARY)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Colors::FG_PRIMARY)
-            };
-            let prefix = if index == state.tools_menu_selection {
-                "▸ "
-            } else {
-                "  "
-            };
-            ListItem::new(format!("{}{} {}", prefix, icon, name)).style(style)
-        })
-        .collect();
+    let menu_items = tool_list_items(state, AppMode::UserTools);
 
     let menu = List::new(menu_items)
         .block(
@@ -408,7 +469,7 @@ pub fn render_user_tools_menu_in_area(
     f.render_widget(menu, content_chunks[0]);
 
     // Description panel
-    let description = descriptions::get_user_tool_description(state.tools_menu_selection);
+    let description = descriptions::get_user_tool_description(state.menu.tools_selection);
     let desc_widget = Paragraph::new(description)
         .block(
             Block::default()
@@ -437,8 +498,8 @@ pub fn render_network_tools_menu_in_area(
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // Header
-            Constraint::Length(3), // Title
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
             Constraint::Min(10),   // Content
         ])
         .split(area);
@@ -452,33 +513,70 @@ pub fn render_network_tools_menu_in_area(
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
         .split(chunks[2]);
 
-    let menu_items = [
-        ("🌐", "Configure Network"),
-        ("📡", "Test Connectivity"),
-        ("🔥", "Firewall Rules"),
-        ("📊", "Network Info"),
-        ("◀️ ", "Back to Tools Menu"),
-    ];
+    let menu_items = tool_list_items(state, AppMode::NetworkTools);
 
-    let menu_items: Vec<ListItem> = menu_items
-        .iter()
-        .enumerate()
-        .map(|(index, (icon, name))| {
-            let style = if index == state.tools_menu_selection {
-                Style::default()
-                    .fg(Colors::SECONDARY)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(Colors::FG_PRIMARY)
-            };
-            let prefix = if index == state.tools_menu_selection {
-                "▸ "
-            } else {
-                "  "
-            };
-            ListItem::new(format!("{}{} {}", prefix, icon, name)).style(style)
-        })
-        .collect();
+    let menu = List::new(menu_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Select Tool ")
+                .title_style(
+                    Style::default()
+                        .fg(Colors::PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .border_style(Style::default().fg(Colors::PRIMARY)),
+        )
+        .style(Style::default().bg(Colors::BG_PRIMARY));
+
+    f.render_widget(menu, content_chunks[0]);
+
+    // Description panel
+    let description = descriptions::get_network_tool_description(state.menu.tools_selection);
+    let desc_widget = Paragraph::new(description)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Tool Information ")
+                .title_style(
+                    Style::default()
+                        .fg(Colors::PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .border_style(Style::default().fg(Colors::PRIMARY)),
+        )
+        .style(Style::default().bg(Colors::BG_PRIMARY))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(desc_widget, content_chunks[1]);
+}
+
+/// Render snapshot tools menu in specified area
+pub fn render_snapshot_tools_menu_in_area(
+    f: &mut Frame,
+    state: &AppState,
+    area: Rect,
+    header: &HeaderRenderer,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header.height(area)), // Header
+            Constraint::Length(header.title_height(area)), // Title
+            Constraint::Min(10),   // Content
+        ])
+        .split(area);
+
+    header.render_header(f, chunks[0]);
+    header.render_title(f, chunks[1], "Btrfs Snapshot Tools");
+
+    // Split content into menu and description
+    let content_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(chunks[2]);
+
+    let menu_items = tool_list_items(state, AppMode::SnapshotTools);
 
     let menu = List::new(menu_items)
         .block(
@@ -497,7 +595,7 @@ pub fn render_network_tools_menu_in_area(
     f.render_widget(menu, content_chunks[0]);
 
     // Description panel
-    let description = descriptions::get_network_tool_description(state.tools_menu_selection);
+    let description = descriptions::get_snapshot_tool_description(state.menu.tools_selection);
     let desc_widget = Paragraph::new(description)
         .block(
             Block::default()
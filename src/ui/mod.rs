@@ -5,7 +5,10 @@
 //! - `menus` - Menu rendering (main, tools, categories)
 //! - `installer` - Installation and configuration UI
 //! - `dialogs` - Input and confirmation dialog rendering
-//! - `descriptions` - Tool description text generation
+//! - `descriptions` - Tool and option description text generation
+//!
+//! With [`Charset::Ascii`] every frame is rewritten to plain ASCII after
+//! rendering, so the individual widgets do not need an ASCII variant.
 
 #![allow(dead_code)]
 
@@ -13,14 +16,18 @@ mod descriptions;
 mod dialogs;
 mod header;
 mod installer;
-mod menus;
+pub(crate) mod menus;
 
 use crate::app::{AppMode, AppState};
+use crate::components::ansi_art;
 use crate::components::keybindings::KeybindingContext;
 use crate::components::pty_terminal::PtyTerminal;
 use crate::input::InputHandler;
+use crate::theme::{self, Charset};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Color,
     Frame,
 };
 
@@ -34,6 +41,8 @@ pub use header::HeaderRenderer;
 pub struct UiRenderer {
     /// Header renderer instance
     header: HeaderRenderer,
+    /// Characters the terminal can display
+    charset: Charset,
 }
 
 impl Default for UiRenderer {
@@ -45,11 +54,28 @@ impl Default for UiRenderer {
 impl UiRenderer {
     /// Create a new UI renderer
     pub fn new() -> Self {
+        Self::with_charset(Charset::Unicode)
+    }
+
+    /// Create a UI renderer for the given character set
+    pub fn with_charset(charset: Charset) -> Self {
         Self {
-            header: HeaderRenderer::new(),
+            header: HeaderRenderer::with_charset(charset),
+            charset,
         }
     }
 
+    /// Rows taken by the header and title on screens that show them, in a
+    /// terminal of size `area`
+    pub fn header_height(&self, area: Rect) -> u16 {
+        // Screens are drawn above the nav bar
+        let content = Rect {
+            height: area.height.saturating_sub(1),
+            ..area
+        };
+        self.header.height(content) + self.header.title_height(content)
+    }
+
     /// Render the complete UI based on application state (legacy method for compatibility)
     pub fn render(&self, f: &mut Frame, state: &AppState, input_handler: &mut InputHandler) {
         let keybinding_ctx = KeybindingContext::new();
@@ -65,12 +91,20 @@ impl UiRenderer {
         keybinding_ctx: &KeybindingContext,
         pty_terminal: Option<&mut PtyTerminal>,
     ) {
-        // If dialog is active, render ONLY the dialog - don't render main UI behind it
-        if input_handler.is_dialog_active() {
-            dialogs::render_input_dialog(f, input_handler);
-            return;
+        self.render_frame(f, state, input_handler, keybinding_ctx, pty_terminal);
+        if self.charset == Charset::Ascii {
+            to_ascii(f.buffer_mut());
         }
+    }
 
+    fn render_frame(
+        &self,
+        f: &mut Frame,
+        state: &AppState,
+        input_handler: &mut InputHandler,
+        keybinding_ctx: &KeybindingContext,
+        pty_terminal: Option<&mut PtyTerminal>,
+    ) {
         // Create main layout with nav bar at bottom
         let main_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -88,9 +122,15 @@ impl UiRenderer {
             AppMode::MainMenu => {
                 menus::render_main_menu_in_area(f, state, content_area, &self.header);
             }
+            AppMode::GuidedInstaller if state.guided.wizard => {
+                installer::render_wizard_ui_in_area(f, state, content_area, &self.header);
+            }
             AppMode::GuidedInstaller => {
                 installer::render_configuration_ui_in_area(f, state, content_area, &self.header);
             }
+            AppMode::Summary => {
+                dialogs::render_install_summary(f, state, content_area);
+            }
             AppMode::AutomatedInstall => {
                 installer::render_automated_install_ui_in_area(f, state, content_area, &self.header);
             }
@@ -109,6 +149,9 @@ impl UiRenderer {
             AppMode::NetworkTools => {
                 menus::render_network_tools_menu_in_area(f, state, content_area, &self.header);
             }
+            AppMode::SnapshotTools => {
+                menus::render_snapshot_tools_menu_in_area(f, state, content_area, &self.header);
+            }
             AppMode::ToolDialog => {
                 dialogs::render_tool_dialog_in_area(f, state, content_area);
             }
@@ -127,7 +170,7 @@ impl UiRenderer {
             AppMode::FloatingOutput => {
                 // Render background (previous mode content) then floating window
                 menus::render_tools_menu_in_area(f, state, content_area, &self.header);
-                dialogs::render_floating_output(f, state);
+                dialogs::render_floating_output(f, state, content_area);
             }
             AppMode::FileBrowser => {
                 // Render file browser for config file selection
@@ -138,6 +181,15 @@ impl UiRenderer {
                 // Render background based on pre_dialog_mode, then confirmation dialog
                 if let Some(ref pre_mode) = state.pre_dialog_mode {
                     match pre_mode {
+                        AppMode::MainMenu => {
+                            menus::render_main_menu_in_area(f, state, content_area, &self.header)
+                        }
+                        AppMode::AutomatedInstall => installer::render_automated_install_ui_in_area(
+                            f,
+                            state,
+                            content_area,
+                            &self.header,
+                        ),
                         AppMode::DiskTools => {
                             menus::render_disk_tools_menu_in_area(f, state, content_area, &self.header)
                         }
@@ -150,6 +202,9 @@ impl UiRenderer {
                         AppMode::NetworkTools => {
                             menus::render_network_tools_menu_in_area(f, state, content_area, &self.header)
                         }
+                        AppMode::SnapshotTools => {
+                            menus::render_snapshot_tools_menu_in_area(f, state, content_area, &self.header)
+                        }
                         _ => menus::render_tools_menu_in_area(f, state, content_area, &self.header),
                     }
                 } else {
@@ -158,6 +213,36 @@ impl UiRenderer {
                 // Render the confirmation dialog on top
                 dialogs::render_confirm_dialog(f, state);
             }
+            AppMode::DiskHealth => {
+                // Render disk tools menu behind the report window
+                menus::render_disk_tools_menu_in_area(f, state, content_area, &self.header);
+                dialogs::render_disk_health(f, state);
+            }
+            AppMode::ServiceManager => {
+                // Render system tools menu behind the unit browser
+                menus::render_system_tools_menu_in_area(f, state, content_area, &self.header);
+                dialogs::render_service_manager(f, state);
+            }
+            AppMode::MountManager => {
+                // Render disk tools menu behind the mount manager
+                menus::render_disk_tools_menu_in_area(f, state, content_area, &self.header);
+                dialogs::render_mount_manager(f, state);
+            }
+            AppMode::RecoveryDialog => {
+                // Render the installation behind the dialog
+                installer::render_installation_ui_in_area(f, state, content_area, &self.header);
+                dialogs::render_recovery_dialog(f, state);
+            }
+            AppMode::PartitionCheck => {
+                // Render the configuration behind the check
+                installer::render_configuration_ui_in_area(f, state, content_area, &self.header);
+                dialogs::render_partition_check(f, state);
+            }
+            AppMode::PartitionMounts => {
+                // Render the configuration behind the editor
+                installer::render_configuration_ui_in_area(f, state, content_area, &self.header);
+                dialogs::render_partition_mounts(f, state);
+            }
         }
 
         // Render navigation bar
@@ -167,5 +252,28 @@ impl UiRenderer {
         if state.help_visible {
             header::render_help_overlay(f, state, keybinding_ctx);
         }
+
+        // Input dialogs are modal: the screen stays visible, dimmed, behind them
+        if input_handler.is_dialog_active() {
+            dialogs::render_input_dialog(f, input_handler);
+        }
+    }
+}
+
+/// Replace every non-ASCII symbol in the frame with its ASCII fallback
+fn to_ascii(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        // Half-block pictures keep their brightness instead of true colours
+        if cell.symbol() == ansi_art::HALF_BLOCK {
+            if let Some(shade) = ansi_art::ascii_shade(cell.fg, cell.bg) {
+                cell.set_symbol(shade);
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+                continue;
+            }
+        }
+        if let Some(replacement) = theme::ascii_symbol(cell.symbol()) {
+            cell.set_symbol(replacement);
+        }
     }
 }
@@ -0,0 +1,146 @@
+//! Separate /var partition and /tmp policy
+//!
+//! auto_simple and auto_simple_luks can put /var on a partition of its own,
+//! created before root with a fixed size and the root filesystem, so
+//! databases and container images do not fill root or share its btrfs
+//! subvolume. The LVM strategies size /var as a volume instead, see
+//! [`crate::lvm`].
+//!
+//! /tmp is a tmpfs by default, written to fstab with a size limit and no
+//! SELinux context options, or a plain directory on disk with systemd's
+//! tmp.mount masked.
+
+use crate::types::{PartitionScheme, TmpPolicy};
+
+/// Size of a /var partition that is not created
+pub const NONE: &str = "none";
+
+pub const DEFAULT_VAR_SIZE: &str = NONE;
+pub const DEFAULT_TMP_SIZE: &str = "50%";
+
+/// Smallest /var partition, enough for pacman's cache of one upgrade
+const MIN_VAR_BYTES: u64 = 1024 * MIB;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Whether the strategy can create a separate /var partition
+pub fn has_var_partition(scheme: PartitionScheme) -> bool {
+    matches!(
+        scheme,
+        PartitionScheme::AutoSimple | PartitionScheme::AutoSimpleLuks
+    )
+}
+
+/// Parse a /var partition size such as `20G`; `None` for `none`
+///
+/// Partitions have a fixed size: root takes the rest of the disk.
+pub fn parse_var_size(value: &str) -> Result<Option<u64>, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case(NONE) {
+        return Ok(None);
+    }
+    let invalid = || {
+        format!(
+            "Invalid /var partition size '{}': use a size like 20G, or none",
+            value
+        )
+    };
+    match crate::lvm::parse_size(value).map_err(|_| invalid())? {
+        Some(crate::lvm::Size::Bytes(bytes)) if bytes >= MIN_VAR_BYTES => Ok(Some(bytes)),
+        Some(crate::lvm::Size::Bytes(_)) => Err(format!(
+            "The /var partition needs at least 1G, not '{}'",
+            value
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// Check a tmpfs size: a share of the memory like `50%` or a size like `4G`
+pub fn validate_tmp_size(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    let invalid = || {
+        format!(
+            "Invalid /tmp size '{}': use a share of the memory like 50% or a size like 4G",
+            value
+        )
+    };
+    if let Some(percent) = value.strip_suffix('%') {
+        return match percent.parse::<u8>() {
+            Ok(1..=100) => Ok(()),
+            _ => Err(invalid()),
+        };
+    }
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    match (digits.parse::<u64>(), &value[digits.len()..]) {
+        (Ok(1..), "k" | "K" | "m" | "M" | "g" | "G") => Ok(()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Check the /var and /tmp settings
+///
+/// A /var size is only checked for the strategies that create the
+/// partition; the others ignore it.
+pub fn validate(
+    scheme: PartitionScheme,
+    var_size: &str,
+    tmp_policy: TmpPolicy,
+    tmp_size: &str,
+) -> Result<(), String> {
+    if has_var_partition(scheme) {
+        parse_var_size(var_size)?;
+    }
+    if tmp_policy == TmpPolicy::Tmpfs {
+        validate_tmp_size(tmp_size)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_var_size() {
+        assert_eq!(parse_var_size("none"), Ok(None));
+        assert_eq!(parse_var_size(" 20G "), Ok(Some(20 << 30)));
+        assert_eq!(parse_var_size("2048MiB"), Ok(Some(2048 << 20)));
+        assert!(parse_var_size("512M").unwrap_err().contains("at least 1G"));
+        assert!(parse_var_size("20%FREE").is_err());
+        assert!(parse_var_size("big").is_err());
+        assert!(parse_var_size("").is_err());
+    }
+
+    #[test]
+    fn test_validate_tmp_size() {
+        for size in ["50%", "100%", "4G", "512m", "2048k"] {
+            assert!(validate_tmp_size(size).is_ok(), "{}", size);
+        }
+        for size in ["0%", "150%", "0G", "4GB", "4T", "", "half"] {
+            assert!(validate_tmp_size(size).is_err(), "{}", size);
+        }
+    }
+
+    #[test]
+    fn test_validate_follows_strategy_and_policy() {
+        assert!(validate(PartitionScheme::AutoSimple, "20G", TmpPolicy::Tmpfs, "50%").is_ok());
+        assert!(validate(
+            PartitionScheme::AutoSimpleLuks,
+            "20%FREE",
+            TmpPolicy::Disk,
+            ""
+        )
+        .is_err());
+        // Strategies without the partition ignore its size
+        assert!(validate(PartitionScheme::AutoLvm, "20%FREE", TmpPolicy::Disk, "").is_ok());
+        // The size only limits a tmpfs
+        assert!(validate(PartitionScheme::AutoSimple, "none", TmpPolicy::Disk, "lots").is_ok());
+        assert!(validate(
+            PartitionScheme::AutoSimple,
+            "none",
+            TmpPolicy::Tmpfs,
+            "lots"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! Watchdog for hung installer commands
+//!
+//! A bad mirror or a dying disk can leave a command inside install.sh waiting
+//! forever without printing anything. While the installer runs, a watchdog
+//! thread checks once a second how long its output has been quiet. After
+//! [`QUIET_AFTER`] the Installation screen names the command the installer is
+//! waiting on and when it last printed a line; once the command outlives its
+//! timeout, the line turns red and the status bar says so. `K` kills the
+//! command and `R` kills it and retries the phase. A killed command fails its
+//! phase, so after `K` install.sh offers the usual recovery choices.
+//!
+//! Timeouts come from a [`TimeoutPolicy`]: a default, overridden per phase
+//! (as named in the status bar) or per command with `--timeout NAME=SECONDS`.
+//! The clock stands still while a recovery decision is pending.
+
+use crate::app::StateHandle;
+use crate::components::status_bar::format_elapsed;
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Silence after which the Installation screen shows the running command
+pub const QUIET_AFTER: Duration = Duration::from_secs(30);
+/// Timeout of commands no override names
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+/// Time between two checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Process table
+const PROC: &str = "/proc";
+
+/// How long installer commands may stay quiet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutPolicy {
+    /// Timeout of commands and phases without an override
+    pub default: Duration,
+    /// Phase or command names and their timeouts; later entries win
+    pub overrides: Vec<(String, Duration)>,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_TIMEOUT,
+            overrides: vec![
+                // Ranking mirrors is quick unless a mirror stops answering
+                ("reflector".to_string(), Duration::from_secs(120)),
+                // pacman prints nothing while it downloads into a pipe
+                (
+                    "Installing base system".to_string(),
+                    Duration::from_secs(1800),
+                ),
+                ("Installing packages".to_string(), Duration::from_secs(1800)),
+            ],
+        }
+    }
+}
+
+impl TimeoutPolicy {
+    /// Add overrides after the built-in ones
+    pub fn with_overrides(mut self, overrides: &[(String, Duration)]) -> Self {
+        self.overrides.extend_from_slice(overrides);
+        self
+    }
+
+    /// Timeout of `command` running in `phase`; a command override wins
+    /// over a phase override
+    pub fn limit(&self, phase: Option<&str>, command: Option<&str>) -> Duration {
+        let find = |name: &str| {
+            self.overrides
+                .iter()
+                .rev()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, timeout)| *timeout)
+        };
+        command
+            .and_then(find)
+            .or_else(|| phase.and_then(find))
+            .unwrap_or(self.default)
+    }
+}
+
+/// Parse a `NAME=SECONDS` timeout override
+pub fn parse_override(value: &str) -> Result<(String, Duration), String> {
+    let (name, seconds) = value
+        .rsplit_once('=')
+        .ok_or_else(|| format!("'{}' is not NAME=SECONDS", value))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("'{}' names no phase or command", value));
+    }
+    match seconds.trim().parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Ok((name.to_string(), Duration::from_secs(seconds))),
+        _ => Err(format!("'{}' is not a number of seconds", seconds)),
+    }
+}
+
+/// A command the installer has been waiting on without output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuietCommand {
+    pub pid: u32,
+    /// Start time in clock ticks after boot, which tells the command from a
+    /// later process that reuses its pid
+    pub started: u64,
+    pub name: String,
+    /// Timeout that applies to it
+    pub limit: Duration,
+}
+
+/// What the watchdog knows about the running installer
+#[derive(Debug, Clone, Default)]
+pub struct WatchdogState {
+    /// When the installer last printed a line
+    pub last_output: Option<Instant>,
+    /// Phase running now, as named in the status bar
+    pub phase: Option<String>,
+    /// Command being waited on, once the output has been quiet for [`QUIET_AFTER`]
+    pub quiet: Option<QuietCommand>,
+    /// Whether the quiet command outlived its timeout
+    pub stalled: bool,
+    /// Answer the next recovery prompt with retry
+    pub retry_pending: bool,
+}
+
+impl WatchdogState {
+    /// The installer printed a line at `now`
+    pub fn note_output(&mut self, now: Instant) {
+        self.last_output = Some(now);
+        self.quiet = None;
+        self.stalled = false;
+    }
+
+    /// Whether the installer has been quiet for [`QUIET_AFTER`] at `now`, so
+    /// the command it waits on is worth looking up
+    pub fn is_quiet(&self, now: Instant) -> bool {
+        self.silent_for(now)
+            .is_some_and(|silence| silence >= QUIET_AFTER)
+    }
+
+    /// How long the installer has been quiet at `now`
+    pub fn silent_for(&self, now: Instant) -> Option<Duration> {
+        self.last_output
+            .map(|last| now.saturating_duration_since(last))
+    }
+
+    /// Line for the Installation screen once the installer has been quiet
+    /// for [`QUIET_AFTER`]
+    pub fn summary(&self, now: Instant) -> Option<String> {
+        let silence = self
+            .silent_for(now)
+            .filter(|silence| *silence >= QUIET_AFTER)?;
+        let name = self
+            .quiet
+            .as_ref()
+            .map_or("the installer", |quiet| &quiet.name);
+        Some(match self.quiet {
+            Some(ref quiet) if self.stalled => format!(
+                "{} printed nothing for {}, over its {} timeout: K kill, R kill and retry",
+                quiet.name,
+                format_elapsed(silence),
+                format_elapsed(quiet.limit)
+            ),
+            _ => format!(
+                "Still running {}, last output {} ago",
+                name,
+                format_elapsed(silence)
+            ),
+        })
+    }
+
+    /// Check the installer at `now`; `command` finds the one it is waiting on
+    ///
+    /// Returns whether the command has just outlived its timeout.
+    pub fn check(
+        &mut self,
+        policy: &TimeoutPolicy,
+        now: Instant,
+        command: impl FnOnce() -> Option<(u32, u64, String)>,
+    ) -> bool {
+        let Some(silence) = self.silent_for(now) else {
+            return false;
+        };
+        if silence < QUIET_AFTER {
+            return false;
+        }
+        self.quiet = command().map(|(pid, started, name)| QuietCommand {
+            limit: policy.limit(self.phase.as_deref(), Some(&name)),
+            pid,
+            started,
+            name,
+        });
+        let limit = self.quiet.as_ref().map_or_else(
+            || policy.limit(self.phase.as_deref(), None),
+            |quiet| quiet.limit,
+        );
+        let stalled = silence >= limit;
+        let just_stalled = stalled && !self.stalled;
+        self.stalled = stalled;
+        just_stalled
+    }
+}
+
+/// Process id, parent id and command name from the contents of
+/// `/proc/<pid>/stat`
+///
+/// The name is in parentheses and may itself contain spaces or parentheses.
+pub fn parse_stat(content: &str) -> Option<(u32, u32, String)> {
+    let (pid, rest) = content.split_once(" (")?;
+    let close = rest.rfind(')')?;
+    let name = &rest[..close];
+    // After the name: state, then the parent id
+    let ppid = rest[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((pid.trim().parse().ok()?, ppid, name.to_string()))
+}
+
+/// Start time from the contents of `/proc/<pid>/stat`, in clock ticks after
+/// boot
+pub fn parse_start_time(content: &str) -> Option<u64> {
+    let close = content.rfind(')')?;
+    // After the name: state is the third field, the start time the 22nd
+    content[close + 1..]
+        .split_whitespace()
+        .nth(19)?
+        .parse()
+        .ok()
+}
+
+/// Start time of the process `pid`, `None` once it has exited
+fn start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("{}/{}/stat", PROC, pid)).ok()?;
+    parse_start_time(&stat)
+}
+
+/// The command `root` is waiting on: its newest child, that child's newest
+/// child and so on down to a process without children
+pub fn foreground_command(processes: &[(u32, u32, String)], root: u32) -> Option<(u32, String)> {
+    let mut found = None;
+    let mut current = root;
+    while let Some((pid, _, name)) = processes
+        .iter()
+        .filter(|(_, ppid, _)| *ppid == current)
+        .max_by_key(|(pid, _, _)| *pid)
+    {
+        found = Some((*pid, name.clone()));
+        current = *pid;
+    }
+    found
+}
+
+/// Every process in /proc
+fn processes() -> Vec<(u32, u32, String)> {
+    let Ok(entries) = fs::read_dir(PROC) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+        .filter_map(|stat| parse_stat(&stat))
+        .collect()
+}
+
+/// Terminate a hung command, failing the installer phase that runs it
+///
+/// Nothing is signalled when the command has exited since it was found,
+/// even if another process got its pid.
+pub fn kill(command: &QuietCommand) -> Result<(), String> {
+    if start_time(command.pid) != Some(command.started) {
+        return Err(format!(
+            "{} (pid {}) has already exited",
+            command.name, command.pid
+        ));
+    }
+    signal::kill(Pid::from_raw(command.pid as i32), Signal::SIGTERM)
+        .map_err(|e| format!("Failed to stop process {}: {}", command.pid, e))
+}
+
+/// Handle of the watchdog thread; the thread stops when it is dropped
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    /// Watch the installer process `installer` with `policy`
+    pub fn start(app_state: StateHandle, installer: u32, policy: TimeoutPolicy) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                thread::sleep(CHECK_INTERVAL);
+                let Some(quiet) = app_state.query(|state| {
+                    state.install.recovery_dialog.is_none()
+                        && state.install.watchdog.is_quiet(Instant::now())
+                }) else {
+                    break;
+                };
+                // Scanning /proc takes a while; do it here, not on the UI thread
+                let command = if quiet {
+                    foreground_command(&processes(), installer)
+                        .and_then(|(pid, name)| Some((pid, start_time(pid)?, name)))
+                } else {
+                    None
+                };
+                let policy = policy.clone();
+                let sent = app_state.update(move |state| {
+                    let now = Instant::now();
+                    // install.sh is waiting on the user, not on a command
+                    if state.install.recovery_dialog.is_some() {
+                        state.install.watchdog.note_output(now);
+                        return;
+                    }
+                    let watchdog = &mut state.install.watchdog;
+                    if watchdog.check(&policy, now, || command) {
+                        let name = watchdog.quiet.as_ref().map_or_else(
+                            || "The installer".to_string(),
+                            |quiet| quiet.name.clone(),
+                        );
+                        state.status.warn(format!(
+                            "{} seems hung: K to kill it, R to kill it and retry the phase",
+                            name
+                        ));
+                    }
+                });
+                if !sent {
+                    break;
+                }
+            }
+        });
+
+        Self { stop }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_override_wins_over_phase() {
+        let policy = TimeoutPolicy::default().with_overrides(&[
+            parse_override("pacman=900").unwrap(),
+            parse_override("Installing packages=60").unwrap(),
+        ]);
+        assert_eq!(
+            policy.limit(Some("installing packages"), Some("pacman")),
+            Duration::from_secs(900)
+        );
+        assert_eq!(
+            policy.limit(Some("Installing packages"), Some("curl")),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            policy.limit(Some("Configuring system"), Some("mkinitcpio")),
+            DEFAULT_TIMEOUT
+        );
+        assert!(parse_override("pacman").is_err());
+        assert!(parse_override("pacman=0").is_err());
+        assert!(parse_override("=60").is_err());
+    }
+
+    #[test]
+    fn test_finds_the_command_the_installer_waits_on() {
+        let stat = "4242 (pacman (sync)) S 4200 4242 4242 0 -1 4194560";
+        assert_eq!(
+            parse_stat(stat),
+            Some((4242, 4200, "pacman (sync)".to_string()))
+        );
+
+        let processes = vec![
+            (4100, 4000, "bash".to_string()),
+            (4150, 4100, "sleep".to_string()),
+            (4200, 4100, "pacstrap".to_string()),
+            (4242, 4200, "pacman".to_string()),
+            (5000, 1, "sshd".to_string()),
+        ];
+        assert_eq!(
+            foreground_command(&processes, 4000),
+            Some((4242, "pacman".to_string()))
+        );
+        assert_eq!(foreground_command(&processes, 4242), None);
+    }
+
+    #[test]
+    fn test_reads_the_start_time() {
+        let stat =
+            "4242 (pac man) S 4000 4242 4000 0 -1 4194560 1 0 0 0 0 0 0 0 20 0 1 0 98765 0 0";
+        assert_eq!(parse_start_time(stat), Some(98765));
+        assert_eq!(parse_start_time("4242 (pacman) S 4000"), None);
+
+        let myself = QuietCommand {
+            pid: std::process::id(),
+            started: start_time(std::process::id()).unwrap() + 1,
+            name: "archinstall".to_string(),
+            limit: DEFAULT_TIMEOUT,
+        };
+        // A process started at another time is not the one that was found
+        assert!(kill(&myself).is_err());
+    }
+
+    #[test]
+    fn test_stalls_once_the_timeout_passes() {
+        let policy = TimeoutPolicy::default().with_overrides(&[parse_override("curl=60").unwrap()]);
+        let start = Instant::now();
+        let mut watchdog = WatchdogState::default();
+        watchdog.note_output(start);
+        let curl = || Some((77, 1234, "curl".to_string()));
+
+        assert!(!watchdog.is_quiet(start + Duration::from_secs(10)));
+        assert!(!watchdog.check(&policy, start + Duration::from_secs(10), curl));
+        assert_eq!(watchdog.quiet, None);
+        assert!(watchdog.is_quiet(start + Duration::from_secs(40)));
+        assert!(!watchdog.check(&policy, start + Duration::from_secs(40), curl));
+        assert_eq!(watchdog.quiet.as_ref().map(|quiet| quiet.pid), Some(77));
+        assert_eq!(
+            watchdog.summary(start + Duration::from_secs(40)).as_deref(),
+            Some("Still running curl, last output 0:40 ago")
+        );
+        assert!(watchdog.check(&policy, start + Duration::from_secs(61), curl));
+        assert!(watchdog.stalled);
+        // Reported once
+        assert!(!watchdog.check(&policy, start + Duration::from_secs(62), curl));
+
+        watchdog.note_output(start + Duration::from_secs(63));
+        assert!(!watchdog.stalled);
+        assert_eq!(watchdog.quiet, None);
+    }
+}
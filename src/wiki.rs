@@ -0,0 +1,470 @@
+//! Arch Wiki articles as text for the help browser
+//!
+//! `W` on an option's help page shows the Arch Wiki article it links to,
+//! so the ISO alone is enough to read it. The article is fetched with
+//! `action=render`, which leaves out the wiki's navigation, and its HTML is
+//! reduced to the markdown subset of [`crate::help`]: headings, bullets,
+//! bold and code spans, with tables of contents, edit links and related
+//! article boxes dropped. Converted articles are cached in the user's cache
+//! directory, so each is downloaded once.
+
+use crate::help::WIKI_BASE_URL;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Seconds the download of an article may take
+const FETCH_TIMEOUT_SECS: u32 = 30;
+
+/// Elements left out of the text, by class
+const SKIPPED_CLASSES: &[&str] = &[
+    "toc",
+    "mw-editsection",
+    "noprint",
+    "archwiki-template-meta-related-articles-start",
+];
+
+/// Elements without a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements that start a paragraph of their own
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "ul",
+    "ol",
+    "dl",
+    "table",
+    "blockquote",
+    "figure",
+];
+
+/// Arch Wiki article shown in the help browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Article {
+    /// Being downloaded
+    Fetching(String),
+    /// Converted text in the help markdown subset
+    Ready { title: String, text: String },
+    /// Could not be downloaded
+    Failed { title: String, error: String },
+}
+
+impl Article {
+    /// Title of the article
+    pub fn title(&self) -> &str {
+        match self {
+            Self::Fetching(title) | Self::Ready { title, .. } | Self::Failed { title, .. } => title,
+        }
+    }
+
+    /// The article as shown, in the help markdown subset
+    pub fn to_markdown(&self) -> String {
+        let body = match self {
+            Self::Fetching(_) => "Fetching the article...".to_string(),
+            Self::Ready { text, .. } => text.clone(),
+            Self::Failed { error, .. } => format!(
+                "**Could not fetch the article:** {}\n\nIt needs a network connection; \
+                 see Configure Network in the tools menu.",
+                error
+            ),
+        };
+        format!(
+            "# {}\n\n{}\n\nSource: {}",
+            self.title(),
+            body,
+            article_url(self.title())
+        )
+    }
+}
+
+/// Full Arch Wiki URL of an article
+pub fn article_url(title: &str) -> String {
+    format!("{}{}", WIKI_BASE_URL, title.replace(' ', "_"))
+}
+
+/// Download an article, or read it from the cache
+pub fn fetch(title: &str) -> Article {
+    let cache = crate::session::cache_dir().map(|dir| dir.join("wiki"));
+    match load(cache.as_deref(), title, download) {
+        Ok(text) => Article::Ready {
+            title: title.to_string(),
+            text,
+        },
+        Err(error) => Article::Failed {
+            title: title.to_string(),
+            error,
+        },
+    }
+}
+
+/// Text of an article from the `cache` directory, downloading and caching
+/// it when it is not there yet
+///
+/// A section anchor (`Partitioning#/var`) is not part of the page: the whole
+/// article is downloaded and cached once for all of its sections.
+fn load(
+    cache: Option<&Path>,
+    title: &str,
+    download: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let title = title.split_once('#').map_or(title, |(page, _)| page);
+    let cached = cache.map(|dir| cache_file(dir, title));
+    if let Some(text) = cached
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+    {
+        return Ok(text);
+    }
+
+    let text = html_to_text(&download(&format!("{}?action=render", article_url(title)))?);
+    if text.is_empty() {
+        return Err(format!("The wiki has no article named '{}'", title));
+    }
+    if let Some(path) = cached {
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, &text));
+        if let Err(e) = written {
+            log::warn!("Failed to cache wiki article {:?}: {}", path, e);
+        }
+    }
+    Ok(text)
+}
+
+/// Cache file of an article; subpages get a flat name
+fn cache_file(dir: &Path, title: &str) -> PathBuf {
+    let name: String = title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.md", name))
+}
+
+fn download(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time"])
+        .arg(FETCH_TIMEOUT_SECS.to_string())
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reduce the HTML of a rendered article to the help markdown subset
+pub fn html_to_text(html: &str) -> String {
+    let mut text = TextWriter::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        text.push_tag(&rest[1..end]);
+        rest = &rest[end + 1..];
+    }
+    text.push_text(rest);
+    text.finish()
+}
+
+/// Builds the lines of an article from its tags and text
+#[derive(Default)]
+struct TextWriter {
+    lines: Vec<String>,
+    line: String,
+    /// Heading or bullet marker of the current line
+    prefix: &'static str,
+    /// Skipped element and how deep into it the text is
+    skipping: Option<(String, usize)>,
+    /// Inside a `<pre>` block
+    preformatted: bool,
+}
+
+impl TextWriter {
+    fn push_tag(&mut self, tag: &str) {
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        let attributes = &tag[name_end..];
+
+        if let Some((skipped, depth)) = &mut self.skipping {
+            if *skipped == name && !VOID_ELEMENTS.contains(&name.as_str()) {
+                if closing {
+                    *depth -= 1;
+                } else {
+                    *depth += 1;
+                }
+                if *depth == 0 {
+                    self.skipping = None;
+                }
+            }
+            return;
+        }
+        if !closing && !VOID_ELEMENTS.contains(&name.as_str()) && is_skipped(&name, attributes) {
+            self.skipping = Some((name, 1));
+            return;
+        }
+
+        match name.as_str() {
+            "br" => self.end_line(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.end_paragraph();
+                if !closing {
+                    self.prefix = "## ";
+                }
+            }
+            "li" | "dt" | "dd" | "tr" => {
+                self.end_line();
+                if !closing && name == "li" {
+                    self.prefix = "- ";
+                }
+            }
+            "td" | "th" if !closing && !self.line.is_empty() => self.line.push_str(" | "),
+            "pre" => {
+                self.end_paragraph();
+                self.preformatted = !closing;
+            }
+            "code" | "kbd" | "tt" if !self.preformatted => self.line.push('`'),
+            "b" | "strong" => self.line.push_str("**"),
+            _ if BLOCK_ELEMENTS.contains(&name.as_str()) => self.end_paragraph(),
+            _ => {}
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.skipping.is_some() || text.is_empty() {
+            return;
+        }
+        let text = decode_entities(text);
+        if self.preformatted {
+            let mut lines = text.split('\n');
+            if let Some(first) = lines.next() {
+                self.line.push_str(first);
+            }
+            for line in lines {
+                self.end_line();
+                self.line.push_str(line);
+            }
+            return;
+        }
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !self.line.is_empty() && !self.line.ends_with(' ') {
+                    self.line.push(' ');
+                }
+            } else {
+                self.line.push(c);
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let prefix = std::mem::take(&mut self.prefix);
+        if self.preformatted {
+            self.lines
+                .push(format!("    {}", line).trim_end().to_string());
+            return;
+        }
+        let line = line.trim();
+        // Markers of an element without text
+        if !line.is_empty() && line != "**" && line != "``" {
+            self.lines.push(format!("{}{}", prefix, line));
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        self.end_line();
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.end_line();
+        while self.lines.last().is_some_and(|line| line.is_empty()) {
+            self.lines.pop();
+        }
+        self.lines.join("\n")
+    }
+}
+
+/// Whether an element is left out of the text
+fn is_skipped(name: &str, attributes: &str) -> bool {
+    if matches!(name, "script" | "style") {
+        return true;
+    }
+    attribute(attributes, "class").is_some_and(|classes| {
+        classes
+            .split_whitespace()
+            .any(|class| SKIPPED_CLASSES.contains(&class))
+    }) || attribute(attributes, "id") == Some("toc")
+}
+
+/// Value of a quoted attribute
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let start = attributes
+        .match_indices(&pattern)
+        .find(|(i, _)| *i == 0 || attributes[..*i].ends_with(char::is_whitespace))?
+        .0
+        + pattern.len();
+    let end = attributes[start..].find('"')?;
+    Some(&attributes[start..start + end])
+}
+
+/// Replace the character references the wiki uses
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<div class="mw-parser-output">
+<div class="archwiki-template-meta-related-articles-start"><p>Related articles</p><ul><li><a href="/title/Swap">Swap</a></li></ul></div>
+<p>Zram creates a <b>compressed</b> block device in RAM,
+see <code>zramctl(8)</code> &amp; <a href="/title/Swap">swap</a>.</p>
+<div id="toc" class="toc"><div class="toctitle"><h2>Contents</h2></div><ul><li>1 Usage</li></ul></div>
+<div class="mw-heading mw-heading2"><h2 id="Usage">Usage</h2><span class="mw-editsection">[<a href="?action=edit">edit</a>]</span></div>
+<ul><li>Install <code>zram-generator</code></li>
+<li>Reboot<!-- not <b>needed</b> --></li></ul>
+<pre>[zram0]
+zram-size = ram / 2
+</pre>
+<script>alert("x")</script>
+<table><tr><th>Option</th><th>Meaning</th></tr><tr><td>zram-size</td><td>Size&#160;of &lt;zram0&gt;</td></tr></table>
+</div>"#;
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text(ARTICLE),
+            "Zram creates a **compressed** block device in RAM, see `zramctl(8)` & swap.\n\
+             \n\
+             ## Usage\n\
+             \n\
+             - Install `zram-generator`\n\
+             - Reboot\n\
+             \n    [zram0]\n    zram-size = ram / 2\n\
+             \n\
+             Option | Meaning\n\
+             zram-size | Size of <zram0>"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &amp;&#x41;&#66; &lt;"), "a &AB <");
+        assert_eq!(decode_entities("R&D &unknown; &"), "R&D &unknown; &");
+    }
+
+    #[test]
+    fn test_articles_are_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = load(Some(dir.path()), "Linux console/Keyboard configuration", |url| {
+            assert_eq!(
+                url,
+                "https://wiki.archlinux.org/title/Linux_console/Keyboard_configuration?action=render"
+            );
+            Ok("<p>Keymaps</p>".to_string())
+        })
+        .unwrap();
+        assert_eq!(text, "Keymaps");
+        assert!(dir
+            .path()
+            .join("Linux_console_Keyboard_configuration.md")
+            .is_file());
+
+        let cached = load(
+            Some(dir.path()),
+            "Linux console/Keyboard configuration",
+            |_| panic!("downloaded again"),
+        );
+        assert_eq!(cached.unwrap(), "Keymaps");
+
+        // Sections share the article and its cache file
+        let section = load(Some(dir.path()), "Partitioning#/var", |url| {
+            assert_eq!(
+                url,
+                "https://wiki.archlinux.org/title/Partitioning?action=render"
+            );
+            Ok("<p>Partition scheme</p>".to_string())
+        });
+        assert_eq!(section.unwrap(), "Partition scheme");
+        assert!(dir.path().join("Partitioning.md").is_file());
+        let cached = load(Some(dir.path()), "Partitioning#Swap", |_| {
+            panic!("downloaded again")
+        });
+        assert_eq!(cached.unwrap(), "Partition scheme");
+
+        let empty = load(None, "Missing", |_| Ok(String::new()));
+        assert!(empty.unwrap_err().contains("no article"));
+    }
+
+    #[test]
+    fn test_article_markdown() {
+        let article = Article::Failed {
+            title: "Zram".to_string(),
+            error: "Could not resolve host".to_string(),
+        };
+        let markdown = article.to_markdown();
+        assert!(markdown.starts_with("# Zram\n\n**Could not fetch the article:**"));
+        assert!(markdown.ends_with("Source: https://wiki.archlinux.org/title/Zram"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Step-by-step view of the guided installer
+//!
+//! The wizard asks one option per screen instead of showing the whole list.
+//! Options that cannot matter with the choices made so far are skipped: the
+//! snapshot settings without Btrfs, the swap size without swap, the LVM sizes
+//! with a strategy that has no LVM and so on. An option whose value is invalid
+//! or conflicts with another one is never skipped, so the wizard cannot hide a
+//! problem the installation would stop on.
+//!
+//! Options pre-seeded from a config file (`--interactive-missing`) are
+//! skipped the same way, so the wizard only asks for what the file left out,
+//! and so are the options a Region fills in (see [`crate::region`]).
+
+use crate::config::{options, Configuration, Validity};
+
+/// Whether an option matters with the values of the other options
+///
+/// This is the requirement of its entry in the option table.
+pub fn applies(config: &Configuration, name: &str) -> bool {
+    options::spec(name).is_none_or(|spec| spec.requirement(config).is_ok())
+}
+
+/// Options the wizard stops at, in order, followed by the start step
+///
+/// The start step is `config.options.len()`, the index of the guided
+/// installer's start button. `validity` is the validity of each option; an
+/// option that is not valid is kept even when it does not apply or is one of
+/// the `preseeded` options.
+pub fn steps(config: &Configuration, validity: &[Validity], preseeded: &[String]) -> Vec<usize> {
+    config
+        .options
+        .iter()
+        .enumerate()
+        .filter(|(index, option)| {
+            let valid = validity.get(*index).is_none_or(|v| *v == Validity::Valid);
+            !valid || (applies(config, &option.name) && !preseeded.contains(&option.name))
+        })
+        .map(|(index, _)| index)
+        .chain(std::iter::once(config.options.len()))
+        .collect()
+}
+
+/// Step after (`forward`) or before `current`
+///
+/// `current` does not have to be a step itself, e.g. after an earlier
+/// answer made the option it points at irrelevant. Stays at the first or
+/// last step at either end.
+pub fn neighbour(steps: &[usize], current: usize, forward: bool) -> usize {
+    let found = if forward {
+        steps.iter().find(|&&step| step > current)
+    } else {
+        steps.iter().rev().find(|&&step| step < current)
+    };
+    match (found, forward) {
+        (Some(&step), _) => step,
+        (None, true) => steps.last().copied().unwrap_or(current),
+        (None, false) => steps.first().copied().unwrap_or(current),
+    }
+}
+
+/// One-based position of `current` among the steps, and the number of steps
+pub fn progress(steps: &[usize], current: usize) -> (usize, usize) {
+    let position = steps.iter().filter(|&&step| step <= current).count();
+    (position.max(1), steps.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(config: &mut Configuration, name: &str, value: &str) {
+        if let Some(option) = config.options.iter_mut().find(|o| o.name == name) {
+            option.value = value.to_string();
+        }
+    }
+
+    fn index(config: &Configuration, name: &str) -> usize {
+        config.options.iter().position(|o| o.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_irrelevant_options_are_skipped() {
+        let mut config = Configuration::default();
+        set(&mut config, "Root Filesystem", "ext4");
+        set(&mut config, "Partitioning Strategy", "auto_simple");
+        assert!(!applies(&config, "Btrfs Snapshots"));
+        assert!(!applies(&config, "LVM Root Size"));
+        assert!(applies(&config, "Swap Size"));
+
+        set(&mut config, "Root Filesystem", "btrfs");
+        assert!(applies(&config, "Btrfs Snapshots"));
+        assert!(!applies(&config, "Btrfs Frequency"));
+        set(&mut config, "Btrfs Snapshots", "Yes");
+        assert!(applies(&config, "Btrfs Frequency"));
+
+        set(&mut config, "Boot Mode", "BIOS");
+        assert!(!applies(&config, "Secure Boot"));
+        set(&mut config, "Bootloader", "systemd-boot");
+        assert!(!applies(&config, "GRUB Theme Selection"));
+        assert!(!applies(&config, "Encrypted Boot"));
+    }
+
+    #[test]
+    fn test_steps_keep_invalid_options_and_end_at_start() {
+        let mut config = Configuration::default();
+        set(&mut config, "Swap", "No");
+        let swap_size = index(&config, "Swap Size");
+        let mut validity = vec![Validity::Valid; config.options.len()];
+
+        let all = steps(&config, &validity, &[]);
+        assert!(!all.contains(&swap_size));
+        assert_eq!(all.last(), Some(&config.options.len()));
+
+        validity[swap_size] = Validity::Invalid("Swap Size is invalid".to_string());
+        assert!(steps(&config, &validity, &[]).contains(&swap_size));
+    }
+
+    #[test]
+    fn test_preseeded_options_are_skipped_while_valid() {
+        let config = Configuration::default();
+        let kernel = index(&config, "Kernel");
+        let mut validity = vec![Validity::Valid; config.options.len()];
+        let preseeded = vec!["Kernel".to_string()];
+
+        assert!(steps(&config, &validity, &[]).contains(&kernel));
+        assert!(!steps(&config, &validity, &preseeded).contains(&kernel));
+
+        validity[kernel] = Validity::Invalid("Kernel is invalid".to_string());
+        assert!(steps(&config, &validity, &preseeded).contains(&kernel));
+    }
+
+    #[test]
+    fn test_neighbour_and_progress() {
+        let steps = [0, 2, 5, 9];
+        assert_eq!(neighbour(&steps, 0, true), 2);
+        assert_eq!(neighbour(&steps, 3, true), 5);
+        assert_eq!(neighbour(&steps, 3, false), 2);
+        assert_eq!(neighbour(&steps, 9, true), 9);
+        assert_eq!(neighbour(&steps, 0, false), 0);
+        assert_eq!(progress(&steps, 5), (3, 4));
+        assert_eq!(progress(&steps, 0), (1, 4));
+    }
+}
@@ -0,0 +1,276 @@
+// Round-trip tests for configuration files
+//
+// - every value the guided installer offers survives saving to a config file
+//   and loading it back into the guided installer
+// - tests/fixtures/configs/ holds golden config files; together they use
+//   every value of every option with a fixed set of choices, and each one must
+//   load, validate and serialize back to exactly the same text
+// - each field of a fixture pre-seeds its option on its own
+//   (--interactive-missing)
+//
+// To accept an intentional format change, run with UPDATE_FIXTURES=1 and review the diff.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use archinstall_tui::config::options;
+use archinstall_tui::config::Configuration;
+use archinstall_tui::config_file::InstallationConfig;
+use archinstall_tui::preseed;
+
+
+/// Options whose values are typed or picked from the running system
+const FREE_FORM: &[&str] = &[
+    "Region",
+    "Locale",
+    "Keymap",
+    "Disk",
+    "EFI Partition",
+    "Partition Mounts",
+    "Swap Size",
+    "Btrfs Keep Count",
+    "LVM Volume Group",
+    "LVM Root Size",
+    "LVM Var Size",
+    "Var Partition Size",
+    "Tmp Size",
+    "LVM Home Size",
+    "Timezone Region",
+    "Timezone",
+    "NTP Servers",
+    "HTTP Proxy",
+    "HTTPS Proxy",
+    "No Proxy",
+    "Mirror Country",
+    "Package Cache",
+    "Parallel Downloads",
+    "Custom Repositories",
+    "Additional Pacman Packages",
+    "Package Groups",
+    "Hostname",
+    "Username",
+    "Password Minimum Length",
+    "User Password",
+    "Root Password",
+    "SSH Keys",
+    "Additional AUR Packages",
+    "Initramfs Modules",
+    "Initramfs Hooks",
+    "Services",
+    "Git Repository URL",
+];
+
+fn fixtures() -> Vec<(PathBuf, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/configs");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(&path).unwrap();
+            (path, text)
+        })
+        .collect()
+}
+
+#[test]
+fn every_offered_value_survives_a_save() {
+    let defaults = Configuration::default();
+    for option in &defaults.options {
+        let mut values = if FREE_FORM.contains(&option.name.as_str()) {
+            Vec::new()
+        } else {
+            options::spec(&option.name).unwrap().choices()
+        };
+        values.push(option.get_value());
+        for value in values.into_iter().filter(|value| !value.is_empty()) {
+            let mut config = Configuration::default();
+            config
+                .options
+                .iter_mut()
+                .find(|o| o.name == option.name)
+                .unwrap()
+                .value = value.clone();
+
+            let file_config = InstallationConfig::try_from(&config)
+                .unwrap_or_else(|e| panic!("{} = {:?}: {}", option.name, value, e));
+            let json = file_config.to_json().unwrap();
+            let loaded = Configuration::from(&InstallationConfig::from_json(&json).unwrap());
+            let reloaded = loaded
+                .options
+                .iter()
+                .find(|o| o.name == option.name)
+                .unwrap()
+                .get_value();
+            assert_eq!(
+                reloaded, value,
+                "{} changed on the way through a file",
+                option.name
+            );
+        }
+    }
+}
+
+#[test]
+fn unsaveable_values_are_reported() {
+    let mut config = Configuration::default();
+    let desktop = config
+        .options
+        .iter_mut()
+        .find(|o| o.name == "Desktop Environment")
+        .unwrap();
+    desktop.value = "KDE".to_string();
+
+    let error = InstallationConfig::try_from(&config).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Desktop Environment 'KDE' would be saved as 'none'"));
+}
+
+#[test]
+fn fixtures_are_golden() {
+    let update = std::env::var("UPDATE_FIXTURES").is_ok_and(|v| v == "1");
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty());
+
+    for (path, text) in fixtures {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let config =
+            InstallationConfig::from_json(&text).unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+        config
+            .validate()
+            .unwrap_or_else(|e| panic!("{} does not validate: {}", name, e));
+
+        let serialized = config.to_json().unwrap();
+        if update {
+            std::fs::write(&path, &serialized).unwrap();
+        } else {
+            assert_eq!(
+                serialized, text,
+                "{} does not serialize back to the same text; run with UPDATE_FIXTURES=1 to accept",
+                name
+            );
+        }
+        assert_eq!(InstallationConfig::from_json(&serialized).unwrap(), config);
+    }
+}
+
+#[test]
+fn fixtures_survive_the_guided_installer() {
+    for (path, text) in fixtures() {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let config = InstallationConfig::from_json(&text).unwrap();
+
+        let tui_config = Configuration::from(&config);
+        assert!(
+            tui_config.options.iter().all(|option| option.is_valid()),
+            "{}: {:?}",
+            name,
+            tui_config
+                .options
+                .iter()
+                .filter_map(|option| option.validation_error())
+                .collect::<Vec<_>>()
+        );
+        let saved =
+            InstallationConfig::try_from(&tui_config).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+        // The guided installer has no options for these
+        let expected = InstallationConfig {
+            custom_phases: Vec::new(),
+            notify_url: None,
+            ..config
+        };
+        assert_eq!(
+            saved, expected,
+            "{} lost options in the guided installer",
+            name
+        );
+    }
+}
+
+#[test]
+fn fixtures_cover_every_choice() {
+    let mut used: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    for (_, text) in fixtures() {
+        let config = InstallationConfig::from_json(&text).unwrap();
+        for (name, value) in config.option_values() {
+            used.entry(name).or_default().insert(value);
+        }
+    }
+
+    let mut missing = Vec::new();
+    for option in &Configuration::default().options {
+        if FREE_FORM.contains(&option.name.as_str()) {
+            continue;
+        }
+        for value in options::spec(&option.name).unwrap().choices() {
+            if !used
+                .get(option.name.as_str())
+                .is_some_and(|v| v.contains(&value))
+            {
+                missing.push(format!("{} = {}", option.name, value));
+            }
+        }
+    }
+    assert!(
+        missing.is_empty(),
+        "no fixture uses: {}",
+        missing.join(", ")
+    );
+}
+
+#[test]
+fn every_option_can_be_preseeded_from_its_field() {
+    // A package cache has to exist on this machine to load in the guided
+    // installer, so no fixture can set one
+    let mut fixtures = fixtures();
+    let mut with_cache: serde_json::Value = serde_json::from_str(&fixtures[0].1).unwrap();
+    with_cache["package_cache"] = "/srv/pkg".into();
+    fixtures.push((PathBuf::from("package_cache"), with_cache.to_string()));
+
+    let mut seeded = BTreeSet::new();
+    for (path, text) in fixtures {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let json: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&text).unwrap();
+        let expected = InstallationConfig::from_json(&text)
+            .unwrap()
+            .option_values();
+
+        for (option, value) in expected {
+            let field = preseed::field(options::spec(option).unwrap());
+            let Some(field_value) = json.get(&field) else {
+                continue;
+            };
+            let single = serde_json::json!({ field.clone(): field_value }).to_string();
+            let seeded_values = preseed::parse_preseed(&single)
+                .unwrap_or_else(|e| panic!("{}: {}: {}", name, field, e))
+                .values;
+            assert!(
+                seeded_values.contains(&(option.to_string(), value.clone())),
+                "{}: {} does not pre-seed {} = {:?}",
+                name,
+                field,
+                option,
+                value
+            );
+            seeded.insert(option);
+        }
+    }
+
+    let missing: Vec<String> = Configuration::default()
+        .options
+        .into_iter()
+        .map(|option| option.name)
+        .filter(|name| !seeded.contains(name.as_str()))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "no fixture sets: {}",
+        missing.join(", ")
+    );
+}
@@ -0,0 +1,255 @@
+// Serial console tests for archinstall-tui
+//
+// A serial or IPMI SOL console is often a dumb terminal that sends no escape
+// sequences for arrows, PgUp/PgDn, Home/End or function keys. These tests check
+// that with --serial every dialog is still reachable with the keys such a
+// terminal does send, and that the screens stay ASCII at 80x24.
+
+use std::collections::HashSet;
+
+use archinstall_tui::app::action::Action;
+use archinstall_tui::app::{App, AppMode, AppState};
+use archinstall_tui::serial;
+use archinstall_tui::theme::Charset;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+/// Every mode with a keymap; the embedded terminal passes keys to its shell
+const MODES: &[AppMode] = &[
+    AppMode::MainMenu,
+    AppMode::GuidedInstaller,
+    AppMode::Summary,
+    AppMode::AutomatedInstall,
+    AppMode::ToolsMenu,
+    AppMode::DiskTools,
+    AppMode::SystemTools,
+    AppMode::UserTools,
+    AppMode::NetworkTools,
+    AppMode::SnapshotTools,
+    AppMode::ToolDialog,
+    AppMode::ToolExecution,
+    AppMode::Installation,
+    AppMode::Complete,
+    AppMode::FloatingOutput,
+    AppMode::FileBrowser,
+    AppMode::ConfirmDialog,
+    AppMode::DiskHealth,
+    AppMode::RecoveryDialog,
+    AppMode::PartitionCheck,
+    AppMode::PartitionMounts,
+];
+
+/// Every key a full terminal can send that the TUI might use
+fn all_keys() -> Vec<KeyEvent> {
+    let mut keys: Vec<KeyEvent> = [
+        KeyCode::Up,
+        KeyCode::Down,
+        KeyCode::Left,
+        KeyCode::Right,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::Home,
+        KeyCode::End,
+        KeyCode::Insert,
+        KeyCode::Delete,
+        KeyCode::BackTab,
+    ]
+    .into_iter()
+    .chain((1..=12).map(KeyCode::F))
+    .map(KeyEvent::from)
+    .collect();
+    keys.extend(serial_keys());
+    keys
+}
+
+/// The keys a dumb terminal sends: characters, Enter, Tab, Backspace, Esc and control letters
+fn serial_keys() -> Vec<KeyEvent> {
+    let mut keys: Vec<KeyEvent> = (' '..='~')
+        .map(|c| KeyEvent::from(KeyCode::Char(c)))
+        .chain(
+            [
+                KeyCode::Enter,
+                KeyCode::Tab,
+                KeyCode::Backspace,
+                KeyCode::Esc,
+            ]
+            .map(KeyEvent::from),
+        )
+        .collect();
+    keys.extend(('a'..='z').map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)));
+    assert!(keys.iter().all(serial::is_plain));
+    keys
+}
+
+fn actions(mode: &AppMode, help_visible: bool, keys: &[KeyEvent]) -> HashSet<String> {
+    keys.iter()
+        .filter_map(|key| Action::from_key(mode, help_visible, *key))
+        .map(|action| format!("{:?}", action))
+        .collect()
+}
+
+#[test]
+fn test_every_action_is_reachable_from_a_dumb_terminal() {
+    let full = all_keys();
+    let plain: Vec<KeyEvent> = serial_keys()
+        .into_iter()
+        .map(|key| serial::translate(key, false))
+        .collect();
+    for mode in MODES {
+        for help_visible in [false, true] {
+            let missing: Vec<String> = actions(mode, help_visible, &full)
+                .difference(&actions(mode, help_visible, &plain))
+                .cloned()
+                .collect();
+            assert!(
+                missing.is_empty(),
+                "{:?} (help {}) needs special keys for {:?}",
+                mode,
+                help_visible,
+                missing
+            );
+        }
+    }
+}
+
+#[test]
+fn test_input_dialog_keys_have_alternatives() {
+    // Text and selection dialogs read these raw keys
+    for code in [
+        KeyCode::Up,
+        KeyCode::Down,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::Esc,
+        KeyCode::Backspace,
+    ] {
+        assert!(
+            serial_keys()
+                .into_iter()
+                .any(|key| serial::translate(key, false).code == code),
+            "no serial alternative for {:?}",
+            code
+        );
+    }
+}
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+fn serial_app(mode: AppMode, setup: impl FnOnce(&mut AppState)) -> App {
+    let mut app = App::new(None);
+    app.set_charset(Charset::detect_from(Some("dumb"), None));
+    app.set_serial(true);
+    {
+        let state = app.state_mut();
+        state.mode = mode;
+        setup(state);
+    }
+    app
+}
+
+fn ctrl(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::from(code))
+}
+
+/// Feed the events in, drawing after each, and return the last screen
+fn play(app: &mut App, events: impl IntoIterator<Item = Event>) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+    app.play(&mut terminal, events).expect("events should play");
+    let buffer = terminal.backend().buffer();
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            out.push_str(buffer[(x, y)].symbol());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn option_value(app: &App, name: &str) -> String {
+    let state = app.state();
+    let option = state
+        .guided
+        .config
+        .options
+        .iter()
+        .find(|option| option.name == name)
+        .unwrap();
+    option.value.clone()
+}
+
+fn mode_of(app: &App) -> AppMode {
+    app.state().mode.clone()
+}
+
+#[test]
+fn test_selection_dialog_with_control_keys() {
+    let mut app = serial_app(AppMode::GuidedInstaller, |_| {});
+    // Ctrl+N, Ctrl+P move the list; Enter opens Boot Mode, Ctrl+N picks the next value
+    let screen = play(
+        &mut app,
+        [ctrl('n'), ctrl('p'), key(KeyCode::Enter), ctrl('n')],
+    );
+    assert!(screen.is_ascii(), "non-ASCII output:\n{}", screen);
+    play(&mut app, [key(KeyCode::Enter)]);
+    assert_eq!(option_value(&app, "Boot Mode"), "UEFI");
+
+    // Ctrl+G closes a dialog without changing the value
+    play(&mut app, [key(KeyCode::Enter), ctrl('n'), ctrl('g')]);
+    assert_eq!(option_value(&app, "Boot Mode"), "UEFI");
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+}
+
+#[test]
+fn test_text_dialog_with_control_keys() {
+    let mut app = serial_app(AppMode::GuidedInstaller, |state| {
+        let hostname = state
+            .guided
+            .config
+            .options
+            .iter()
+            .position(|option| option.name == "Hostname")
+            .unwrap();
+        state.guided.scroll.set_selected(hostname);
+    });
+    // Ctrl+H deletes like Backspace
+    let mut events = vec![key(KeyCode::Enter)];
+    events.extend("sol-hostx".chars().map(|c| key(KeyCode::Char(c))));
+    events.push(ctrl('h'));
+    events.push(key(KeyCode::Enter));
+    let screen = play(&mut app, events);
+    assert!(screen.is_ascii(), "non-ASCII output:\n{}", screen);
+    assert_eq!(option_value(&app, "Hostname"), "sol-host");
+}
+
+#[test]
+fn test_help_lists_serial_alternatives() {
+    let mut app = serial_app(AppMode::MainMenu, |_| {});
+    // '?' stands in for F1, Ctrl+E scrolls to the end and Ctrl+G closes the help
+    let screen = play(&mut app, [key(KeyCode::Char('?')), ctrl('e')]);
+    assert!(screen.is_ascii(), "non-ASCII output:\n{}", screen);
+    assert!(screen.contains("Ctrl+N    Down"), "{}", screen);
+    assert!(screen.contains("Ctrl+G    Esc"), "{}", screen);
+    play(&mut app, [ctrl('g')]);
+    assert!(!app.state().help_visible);
+}
+
+#[test]
+fn test_tools_are_reachable_over_serial() {
+    let mut app = serial_app(AppMode::MainMenu, |_| {});
+    // Ctrl+E jumps to the last entry, Ctrl+A back to the first
+    play(&mut app, [ctrl('e'), ctrl('a'), ctrl('n')]);
+    let selection = app.state().menu.main_selection;
+    assert_eq!(selection, 1);
+    let screen = play(&mut app, [key(KeyCode::Enter)]);
+    assert!(screen.is_ascii(), "non-ASCII output:\n{}", screen);
+    assert_ne!(mode_of(&app), AppMode::MainMenu);
+    play(&mut app, [key(KeyCode::Char('b'))]);
+    assert_eq!(mode_of(&app), AppMode::MainMenu);
+}
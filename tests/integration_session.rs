@@ -0,0 +1,168 @@
+// Session persistence tests for archinstall-tui
+//
+// Verifies the guided configuration is auto-saved through App::handle_event
+// and offered for restore on the next launch.
+
+use archinstall_tui::app::{App, AppMode};
+use archinstall_tui::config::Configuration;
+use archinstall_tui::preseed;
+use archinstall_tui::session;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn set_option(app: &mut App, name: &str, value: &str) {
+    let state = app.state_mut();
+    let option = state
+        .guided
+        .config
+        .options
+        .iter_mut()
+        .find(|o| o.name == name)
+        .unwrap();
+    option.value = value.to_string();
+}
+
+fn option_value(app: &App, name: &str) -> String {
+    let state = app.state();
+    state
+        .guided
+        .config
+        .options
+        .iter()
+        .find(|o| o.name == name)
+        .unwrap()
+        .value
+        .clone()
+}
+
+#[test]
+fn test_changes_are_autosaved() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.toml");
+
+    let mut app = App::new(None);
+    app.enable_session_persistence(path.clone());
+    assert_eq!(app.state().mode, AppMode::MainMenu);
+
+    // No changes yet: nothing is written
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    assert!(!path.exists());
+
+    set_option(&mut app, "Kernel", "linux-zen");
+    set_option(&mut app, "Root Password", "supersecret");
+    app.handle_event(key(KeyCode::Up)).unwrap();
+
+    let saved = session::load_session(&path).unwrap();
+    assert_eq!(saved, vec![("Kernel".to_string(), "linux-zen".to_string())]);
+    assert!(!std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("supersecret"));
+}
+
+#[test]
+fn test_saved_session_is_offered_and_restored() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.toml");
+
+    let mut previous = Configuration::default();
+    for option in previous.options.iter_mut() {
+        match option.name.as_str() {
+            "Disk" => option.value = "/dev/vda".to_string(),
+            "Timezone" => option.value = "Europe/Berlin".to_string(),
+            _ => {}
+        }
+    }
+    session::save_session(&previous, &path).unwrap();
+
+    let mut app = App::new(None);
+    app.enable_session_persistence(path.clone());
+    assert_eq!(app.state().mode, AppMode::ConfirmDialog);
+
+    // Select "Yes" and confirm
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+
+    assert_eq!(app.state().mode, AppMode::GuidedInstaller);
+    assert_eq!(option_value(&app, "Disk"), "/dev/vda");
+    assert_eq!(option_value(&app, "Timezone"), "Europe/Berlin");
+}
+
+#[test]
+fn test_declining_restore_keeps_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.toml");
+
+    let mut previous = Configuration::default();
+    for option in previous.options.iter_mut() {
+        if option.name == "Disk" {
+            option.value = "/dev/vda".to_string();
+        }
+    }
+    session::save_session(&previous, &path).unwrap();
+
+    let mut app = App::new(None);
+    app.enable_session_persistence(path.clone());
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+
+    assert_eq!(app.state().mode, AppMode::MainMenu);
+    assert_eq!(option_value(&app, "Disk"), "");
+    // The session is left in place until something changes
+    assert!(path.exists());
+}
+
+#[test]
+fn test_preseed_asks_only_for_missing_options() {
+    let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/configs/01-simple-uefi-gnome.json");
+    let mut json: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(fixture).unwrap()).unwrap();
+    for field in ["hostname", "username", "user_password", "root_password"] {
+        json.remove(field);
+    }
+    // Optional fields a complete file leaves out are set explicitly, or the
+    // wizard asks for them too
+    for field in [
+        "efi_partition",
+        "http_proxy",
+        "https_proxy",
+        "no_proxy",
+        "package_cache",
+        "password_min_length",
+    ] {
+        json.entry(field).or_insert(serde_json::Value::Null);
+    }
+    for field in [
+        "ntp_servers",
+        "custom_repositories",
+        "package_groups",
+        "initramfs_modules",
+        "initramfs_hooks",
+    ] {
+        json.entry(field).or_insert(serde_json::json!([]));
+    }
+    let preseed = preseed::parse_preseed(&serde_json::to_string(&json).unwrap()).unwrap();
+    assert!(preseed.warnings.is_empty(), "{:?}", preseed.warnings);
+
+    let mut app = App::new(None);
+    app.preseed(&preseed);
+    let state = app.state_mut();
+    assert_eq!(state.mode, AppMode::GuidedInstaller);
+    assert!(state.guided.wizard);
+
+    let asked: Vec<String> = state
+        .guided
+        .wizard_steps()
+        .into_iter()
+        .filter_map(|step| state.guided.config.options.get(step))
+        .map(|option| option.name.clone())
+        .collect();
+    assert_eq!(
+        asked,
+        ["Hostname", "Username", "User Password", "Root Password"]
+    );
+    let selected = state.guided.scroll.selected_index;
+    assert_eq!(state.guided.config.options[selected].name, "Hostname");
+}
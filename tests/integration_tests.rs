@@ -88,6 +88,51 @@ fn test_config_structure() {
     assert!(option_names.contains(&&"Root Filesystem".to_string()), "Should have Root Filesystem option");
 }
 
+#[test]
+fn test_guest_tools_proposed_for_detected_hypervisor() {
+    use archinstall_tui::app::App;
+    use archinstall_tui::hardware::Hypervisor;
+
+    let guest_tools = |app: &App| {
+        let state = app.state();
+        state
+            .guided
+            .config
+            .options
+            .iter()
+            .find(|o| o.name == "VM Guest Tools")
+            .map(|o| o.get_value())
+            .unwrap()
+    };
+
+    // Bare metal leaves the option alone
+    let mut app = App::new(None);
+    app.propose_guest_tools(None);
+    assert_eq!(guest_tools(&app), "None");
+
+    let mut app = App::new(None);
+    app.propose_guest_tools(Some(Hypervisor::VirtualBox));
+    assert_eq!(guest_tools(&app), "VirtualBox");
+    let status = app.state().status.text().to_string();
+    assert!(status.contains("virtualbox-guest-utils"), "status: {}", status);
+}
+
+#[test]
+fn test_service_actions_not_run_in_simulation() {
+    let output = Command::new(env!("CARGO_BIN_EXE_archinstall-tui"))
+        .args(["--simulate", "tools", "system", "services"])
+        .args(["--action", "enable", "--service", "sshd.service", "--root", "/mnt"])
+        .output()
+        .expect("Failed to run archinstall-tui");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[SIMULATION] Not run: systemctl --root=/mnt enable sshd.service"),
+        "stdout: {}",
+        stdout
+    );
+}
+
 /// Test async tool execution with threading and output capture
 /// This validates the Sprint 2 async execution pattern works correctly
 #[test]
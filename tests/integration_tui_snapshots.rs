@@ -0,0 +1,1208 @@
+// TUI snapshot tests for archinstall-tui
+//
+// Drives the real App render path through ratatui's TestBackend:
+// - every AppMode is rendered at a fixed 100x40 size and compared against
+//   a text snapshot in tests/snapshots/
+// - a few screens are also rendered at 80x24 and smaller, where the header
+//   collapses to a one-line title or disappears
+// - key events are injected through App::handle_event, exactly as the main loop does
+//
+// To accept intentional UI changes or add a snapshot, run with UPDATE_SNAPSHOTS=1
+// and review the diff.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use archinstall_tui::app::action::{Action, Movement};
+use archinstall_tui::app::{
+    App, AppMode, AppState, ToolDialogState, ToolMessage, ToolParam, ToolParameter,
+};
+use archinstall_tui::components::confirm_dialog::wipe_disk_confirm;
+use archinstall_tui::components::disk_health::DiskHealthState;
+use archinstall_tui::components::file_browser::{FileBrowserState, FileEntry};
+use archinstall_tui::components::floating_window::FloatingOutputState;
+use archinstall_tui::components::install_summary::InstallSummaryState;
+use archinstall_tui::components::mount_manager::MountManagerState;
+use archinstall_tui::components::partition_check::PartitionCheckState;
+use archinstall_tui::components::partition_mounts::PartitionMountsState;
+use archinstall_tui::components::pty_terminal::PtyTerminalState;
+use archinstall_tui::components::recovery_dialog::RecoveryDialogState;
+use archinstall_tui::components::status_bar::StatusLevel;
+use archinstall_tui::keylog::Replay;
+use archinstall_tui::mount_plan::MountPlan;
+use archinstall_tui::partition_check::{self, Report};
+use archinstall_tui::recovery::PhaseFailure;
+use archinstall_tui::self_update::{Release, Version};
+use archinstall_tui::theme::{Charset, Colors};
+use archinstall_tui::throughput::Throughput;
+use archinstall_tui::tools::mounts;
+use archinstall_tui::tools::smart::SmartReport;
+use archinstall_tui::tools::wipe::WipeMethod;
+use archinstall_tui::types::BootMode;
+use archinstall_tui::wiki::Article;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::style::{Color, Modifier};
+use ratatui::Terminal;
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 40;
+
+/// Render the app once and return the screen as plain text (one line per row)
+fn render(app: &mut App) -> String {
+    render_sized(app, WIDTH, HEIGHT)
+}
+
+/// Render the app once on a terminal of the given size
+fn render_sized(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("test terminal");
+    app.draw_to(&mut terminal).expect("draw should succeed");
+
+    let buffer = terminal.backend().buffer();
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        for x in 0..buffer.area.width {
+            line.push_str(buffer[(x, y)].symbol());
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Compare against tests/snapshots/<name>.txt, writing it when UPDATE_SNAPSHOTS=1
+///
+/// A missing file fails the test, so a deleted or misnamed snapshot is not
+/// silently recreated.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+
+    let update = std::env::var("UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1");
+    if update {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "snapshot '{}' cannot be read from {}: {} (run with UPDATE_SNAPSHOTS=1 to create it)",
+            name,
+            path.display(),
+            e
+        )
+    });
+    if expected != actual {
+        let first_diff = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or(0);
+        panic!(
+            "snapshot '{}' differs at line {}\n--- expected\n{}\n--- actual\n{}\n(run with UPDATE_SNAPSHOTS=1 to accept)",
+            name,
+            first_diff + 1,
+            expected,
+            actual
+        );
+    }
+}
+
+/// Build an app with state prepared for the given mode
+fn app_in_mode(mode: AppMode, setup: impl FnOnce(&mut AppState)) -> App {
+    let mut app = App::new(None);
+    {
+        let state = app.state_mut();
+        state.mode = mode;
+        setup(state);
+    }
+    app
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn mode_of(app: &App) -> AppMode {
+    app.state().mode.clone()
+}
+
+#[test]
+fn snapshot_main_menu() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    assert_snapshot("main_menu", &render(&mut app));
+}
+
+#[test]
+fn snapshot_small_terminals_collapse_the_header() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    let screen = render_sized(&mut app, 80, 24);
+    assert!(!screen.contains("█"), "the art does not fit 80x24");
+    assert_snapshot("main_menu_80x24", &screen);
+
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |_| {});
+    assert_snapshot("guided_installer_80x24", &render_sized(&mut app, 80, 24));
+
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    let screen = render_sized(&mut app, 60, 10);
+    assert!(!screen.contains("Arch Linux Toolkit"));
+    assert_snapshot("main_menu_tiny", &screen);
+}
+
+#[test]
+fn snapshot_main_menu_update_banner() {
+    let mut app = app_in_mode(AppMode::MainMenu, |state| {
+        state.update = Some(Release {
+            version: Version::parse("1.2.0").unwrap(),
+            page: "https://github.com/live4thamuzik/ArchInstall/releases/tag/v1.2.0".to_string(),
+            download: None,
+            checksum: None,
+        });
+    });
+    assert_snapshot("main_menu_update_banner", &render(&mut app));
+}
+
+#[test]
+fn snapshot_guided_installer() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |_| {});
+    assert_snapshot("guided_installer", &render(&mut app));
+}
+
+#[test]
+fn snapshot_guided_wizard() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.guided.config.options[15].value = "No".to_string();
+        state.guided.scroll.set_selected(15);
+    });
+    app.handle_event(key(KeyCode::Char('w'))).unwrap();
+    assert_snapshot("guided_wizard", &render(&mut app));
+
+    // Without swap and with ext4 the swap size, Btrfs and LVM steps are skipped
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("Var Partition Size"), "{}", screen);
+
+    app.handle_event(key(KeyCode::End)).unwrap();
+    assert!(render(&mut app).contains("Ready to install"));
+}
+
+#[test]
+fn test_reset_options_to_defaults() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.guided.config.options[3].value = "de_DE.UTF-8".to_string();
+        state.guided.config.options[4].value = "us".to_string();
+        state.guided.config.options[5].value = "/dev/sda".to_string();
+        state.guided.scroll.set_selected(3);
+    });
+    let screen = render(&mut app);
+    assert!(screen.contains("Locale: de_DE.UTF-8 ●"));
+    let keymap = screen
+        .lines()
+        .find(|line| line.contains("Keymap:"))
+        .unwrap();
+    assert!(!keymap.contains('●'), "default value is marked: {}", keymap);
+    assert_snapshot("guided_installer_modified", &screen);
+
+    // 'r' resets the highlighted option
+    app.handle_event(key(KeyCode::Char('r'))).unwrap();
+    {
+        let state = app.state();
+        assert!(!state.guided.config.options[3].is_modified());
+        assert!(state.guided.config.options[5].is_modified());
+        assert!(state
+            .status
+            .text()
+            .contains("Reset Locale to its default: en_US.UTF-8"));
+    }
+
+    // 'R' asks first; the dialog defaults to No
+    app.handle_event(key(KeyCode::Char('R'))).unwrap();
+    assert_eq!(mode_of(&app), AppMode::ConfirmDialog);
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+    assert_eq!(app.state().guided.config.modified_count(), 1);
+
+    for code in [KeyCode::Char('R'), KeyCode::Right, KeyCode::Enter] {
+        app.handle_event(key(code)).unwrap();
+    }
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+    assert_eq!(app.state().guided.config.modified_count(), 0);
+}
+
+#[test]
+fn test_region_fills_in_its_options() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.guided.scroll.set_selected(2);
+    });
+    // Germany is the tenth country
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    for _ in 0..9 {
+        app.handle_event(key(KeyCode::Down)).unwrap();
+    }
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+
+    let state = app.state_mut();
+    let value = |name: &str| state.guided.config.value(name);
+    assert_eq!(value("Region"), "Germany");
+    assert_eq!(value("Locale"), "de_DE.UTF-8");
+    assert_eq!(value("Keymap"), "de");
+    assert_eq!(value("Timezone Region"), "Europe");
+    assert_eq!(value("Timezone"), "Berlin");
+    assert_eq!(value("Mirror Country"), "Germany");
+    assert!(state.status.text().contains("each can still be changed"));
+
+    // The wizard goes on without asking for them
+    let asked: Vec<String> = state
+        .guided
+        .wizard_steps()
+        .into_iter()
+        .filter_map(|step| state.guided.config.options.get(step))
+        .map(|option| option.name.clone())
+        .collect();
+    for name in ["Region", "Locale", "Keymap", "Timezone", "Mirror Country"] {
+        assert_eq!(
+            asked.contains(&name.to_string()),
+            name == "Region",
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_validity_markers_follow_the_values() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.guided.config.options[0].value = "BIOS".to_string();
+        state.guided.config.options[1].value = "Yes".to_string();
+        state.guided.config.options[5].value = "not a disk".to_string();
+        state.guided.scroll.set_selected(1);
+    });
+    let screen = render(&mut app);
+    let line = |screen: &str, name: &str| {
+        screen
+            .lines()
+            .find(|line| line.contains(name))
+            .unwrap()
+            .to_string()
+    };
+    assert!(line(&screen, "Boot Mode:").contains("BIOS ● ⚠"));
+    assert!(!line(&screen, "Boot Mode:").contains("UEFI boot mode"));
+    // Only the focused option explains itself, in the list and its description
+    assert!(line(&screen, "Secure Boot:").contains("Yes ● ⚠ Secure Boot requires"));
+    assert!(screen.contains("│  ⚠ Secure Boot requires UEFI boot mode."));
+    assert!(line(&screen, "Disk:").contains('✗'));
+    assert!(line(&screen, "Existing OS:").contains("[Press Enter] ✓"));
+
+    app.state_mut().guided.config.options[0].value = "UEFI".to_string();
+    let screen = render(&mut app);
+    assert!(line(&screen, "Boot Mode:").contains("UEFI ● ✓"));
+    assert!(line(&screen, "Secure Boot:").contains("Yes ● ✓"));
+}
+
+#[test]
+fn snapshot_ascii_fallback() {
+    for (name, mode) in [
+        ("main_menu_ascii", AppMode::MainMenu),
+        ("guided_installer_ascii", AppMode::GuidedInstaller),
+    ] {
+        let mut app = app_in_mode(mode, |_| {});
+        app.set_charset(Charset::Ascii);
+        let screen = render(&mut app);
+        assert!(screen.is_ascii(), "{} has non-ASCII output", name);
+        assert_snapshot(name, &screen);
+    }
+}
+
+#[test]
+fn snapshot_automated_install() {
+    let mut app = app_in_mode(AppMode::AutomatedInstall, |_| {});
+    assert_snapshot("automated_install", &render(&mut app));
+}
+
+#[test]
+fn snapshot_tool_menus() {
+    for (name, mode) in [
+        ("tools_menu", AppMode::ToolsMenu),
+        ("disk_tools", AppMode::DiskTools),
+        ("system_tools", AppMode::SystemTools),
+        ("user_tools", AppMode::UserTools),
+        ("network_tools", AppMode::NetworkTools),
+        ("snapshot_tools", AppMode::SnapshotTools),
+    ] {
+        let mut app = app_in_mode(mode, |_| {});
+        assert_snapshot(name, &render(&mut app));
+    }
+}
+
+#[test]
+fn snapshot_unprivileged_tools() {
+    let mut app = app_in_mode(AppMode::DiskTools, |state| state.privileged = false);
+    let screen = render(&mut app);
+    assert!(screen.contains("Wipe Disk 🔒"));
+    assert!(!screen.contains("Check Disk Health 🔒"));
+    assert_snapshot("disk_tools_unprivileged", &screen);
+
+    // A root-only tool offers to restart as root instead of running
+    for code in [KeyCode::Down, KeyCode::Down, KeyCode::Enter] {
+        app.handle_event(key(code)).unwrap();
+    }
+    let state = app.state();
+    if archinstall_tui::privilege::launcher().is_some() {
+        assert_eq!(state.mode, AppMode::ConfirmDialog);
+        let dialog = state.confirm_dialog.as_ref().unwrap();
+        assert_eq!(dialog.confirm_action, "restart_as_root");
+        assert_eq!(dialog.action_data.as_deref(), Some("Wipe Disk"));
+    } else {
+        assert_eq!(state.mode, AppMode::DiskTools);
+        assert!(state.status.text().contains("Wipe Disk needs root"));
+    }
+}
+
+#[test]
+fn test_installing_needs_root() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| state.privileged = false);
+    app.dispatch(Action::StartInstall).unwrap();
+    let state = app.state();
+    assert_eq!(state.mode, AppMode::GuidedInstaller);
+    assert!(state.status.text().contains("needs root"));
+}
+
+#[test]
+fn snapshot_tool_dialog() {
+    let mut app = app_in_mode(AppMode::ToolDialog, |state| {
+        state.tools.dialog = Some(ToolDialogState {
+            tool_name: "install_bootloader".to_string(),
+            parameters: vec![
+                ToolParam {
+                    name: "type".to_string(),
+                    description: "Bootloader type (grub or systemd-boot)".to_string(),
+                    param_type: ToolParameter::Selection(
+                        vec!["grub".to_string(), "systemd-boot".to_string()],
+                        0,
+                    ),
+                    required: true,
+                },
+                ToolParam {
+                    name: "disk".to_string(),
+                    description: "Target disk".to_string(),
+                    param_type: ToolParameter::Text("/dev/sda".to_string()),
+                    required: true,
+                },
+            ],
+            current_param: 0,
+            param_values: vec!["grub".to_string(), "/dev/sda".to_string()],
+            is_executing: false,
+        });
+    });
+    assert_snapshot("tool_dialog", &render(&mut app));
+}
+
+#[test]
+fn test_failed_tool_is_reported_as_error() {
+    let mut app = app_in_mode(AppMode::FloatingOutput, |state| {
+        state.floating_output = Some(FloatingOutputState::new("Format Partition"));
+    });
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+    let tool = app.tool_sender();
+    let level = |app: &App| {
+        app.state()
+            .status
+            .visible(Instant::now())
+            .map(|message| message.level)
+    };
+
+    tool.send(ToolMessage::Complete {
+        success: true,
+        exit_code: Some(0),
+    })
+    .unwrap();
+    app.play(&mut terminal, [Event::Resize(WIDTH, HEIGHT)]).unwrap();
+    assert_eq!(level(&app), Some(StatusLevel::Info));
+
+    tool.send(ToolMessage::Complete {
+        success: false,
+        exit_code: Some(2),
+    })
+    .unwrap();
+    app.play(&mut terminal, [Event::Resize(WIDTH, HEIGHT)]).unwrap();
+    assert_eq!(level(&app), Some(StatusLevel::Error));
+    assert_eq!(app.state().status.text(), "Tool failed with exit code: 2");
+}
+
+#[test]
+fn test_key_log_masks_secret_input() {
+    // A password field of a tool form, once it has the focus
+    let mut app = app_in_mode(AppMode::ToolDialog, |state| {
+        state.tools.dialog = Some(ToolDialogState {
+            tool_name: "add_user".to_string(),
+            parameters: vec![
+                ToolParam {
+                    name: "username".to_string(),
+                    description: "Username".to_string(),
+                    param_type: ToolParameter::Text(String::new()),
+                    required: true,
+                },
+                ToolParam {
+                    name: "password".to_string(),
+                    description: "Password".to_string(),
+                    param_type: ToolParameter::Password(String::new()),
+                    required: true,
+                },
+            ],
+            current_param: 0,
+            param_values: vec![String::new(), String::new()],
+            is_executing: false,
+        });
+    });
+    assert!(!app.input_is_secret());
+    app.state_mut().tools.dialog.as_mut().unwrap().current_param = 1;
+    assert!(app.input_is_secret());
+
+    // Everything typed into the embedded terminal, rescue passphrases included
+    let app = app_in_mode(AppMode::EmbeddedTerminal, |_| {});
+    assert!(app.input_is_secret());
+
+    // A proxy URL may carry a password
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        let index = state
+            .guided
+            .config
+            .options
+            .iter()
+            .position(|option| option.name == "HTTP Proxy")
+            .unwrap();
+        state.guided.scroll.set_selected(index);
+    });
+    assert!(!app.input_is_secret());
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert!(app.input_is_secret());
+}
+
+#[test]
+fn test_tool_form_requires_its_fields() {
+    let mut app = app_in_mode(AppMode::ToolDialog, |state| {
+        state.tools.dialog = Some(ToolDialogState::new(
+            "delete_snapshot",
+            vec![
+                ToolParam {
+                    name: "device".to_string(),
+                    description: "Btrfs root device".to_string(),
+                    param_type: ToolParameter::Text(String::new()),
+                    required: true,
+                },
+                ToolParam {
+                    name: "snapshot".to_string(),
+                    description: "Snapshot number".to_string(),
+                    param_type: ToolParameter::Number(1),
+                    required: true,
+                },
+            ],
+        ));
+    });
+
+    // Enter on the last field stops at the first empty required one
+    app.handle_event(key(KeyCode::BackTab)).unwrap();
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    {
+        let state = app.state();
+        assert_eq!(state.mode, AppMode::ToolDialog);
+        assert_eq!(state.tools.dialog.as_ref().unwrap().current_param, 0);
+        assert!(state.status.text().contains("device is required"));
+    }
+
+    for c in "/dev/sda2".chars() {
+        app.handle_event(key(KeyCode::Char(c))).unwrap();
+    }
+    app.handle_event(key(KeyCode::Tab)).unwrap();
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    {
+        let state = app.state();
+        let dialog = state.tools.dialog.as_ref().unwrap();
+        assert_eq!(dialog.param_values, vec!["/dev/sda2", "2"]);
+    }
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::ConfirmDialog);
+}
+
+#[test]
+fn snapshot_tool_execution() {
+    let mut app = app_in_mode(AppMode::ToolExecution, |state| {
+        state.tools.current = Some("check_disk_health".to_string());
+        state.tools.output = vec![
+            "Checking /dev/sda...".to_string(),
+            "SMART overall-health self-assessment test result: PASSED".to_string(),
+        ];
+    });
+    assert_snapshot("tool_execution", &render(&mut app));
+}
+
+#[test]
+fn snapshot_installation() {
+    let mut app = app_in_mode(AppMode::Installation, |state| {
+        state.install.progress = 40;
+        state.status.info("Installing base system");
+        state
+            .install
+            .output
+            .push("=== INSTALLATION ENGINE STARTED ===".to_string());
+        state
+            .install
+            .output
+            .push("Phase 5: Installing base system...".to_string());
+    });
+    assert_snapshot("installation", &render(&mut app));
+}
+
+#[test]
+fn snapshot_installation_packages() {
+    let mut app = app_in_mode(AppMode::Installation, |state| {
+        for line in [
+            "Phase 5: Installing base system...",
+            "  [pacstrap] Packages (420) acl-2.3.2-1  archlinux-keyring-20241015-1",
+            "  [pacstrap] (143/420) installing firefox",
+        ] {
+            state.install.output.push(line.to_string());
+            state.install.package_progress.update(line);
+        }
+        state.install.progress = 53;
+    });
+    assert_snapshot("installation_packages", &render(&mut app));
+}
+
+#[test]
+fn snapshot_installation_throughput() {
+    let mut app = app_in_mode(AppMode::Installation, |state| {
+        state.install.progress = 40;
+        state.install.throughput = Some(Throughput {
+            download_rate: 12_900_000,
+            written: 1_288_490_189,
+            write_rate: 83_886_080,
+            written_at_start: None,
+        });
+        state
+            .install
+            .output
+            .push("Phase 5: Installing base system...".to_string());
+    });
+    assert_snapshot("installation_throughput", &render(&mut app));
+}
+
+#[test]
+fn snapshot_installation_output_search() {
+    let mut app = app_in_mode(AppMode::Installation, |state| {
+        state.install.progress = 75;
+        for i in 0..200 {
+            state
+                .install
+                .output
+                .push(format!("(  {}/200) installing package-{}", i + 1, i));
+            if i == 40 {
+                state
+                    .install
+                    .output
+                    .push("ERROR: [2025-01-01 10:00:00] WARN: No swap configured".to_string());
+            }
+        }
+    });
+    render(&mut app);
+
+    // Scrolling up pauses the pane on the selected line
+    app.handle_event(key(KeyCode::PageUp)).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("paused at"));
+    assert!(!screen.contains("package-199"));
+
+    // '/' searches back from the selection; 'q' is text while typing
+    app.handle_event(key(KeyCode::Char('/'))).unwrap();
+    for c in "package-4q".chars() {
+        app.handle_event(key(KeyCode::Char(c))).unwrap();
+    }
+    app.handle_event(key(KeyCode::Backspace)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::Installation);
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_snapshot("installation_output_search", &render(&mut app));
+
+    // 'f' narrows the pane down to warnings and errors
+    app.handle_event(key(KeyCode::Char('f'))).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("warnings and errors"));
+    assert!(screen.contains("No swap configured"));
+    assert!(!screen.contains("package-49"));
+
+    // End follows new output again
+    app.handle_event(key(KeyCode::Char('f'))).unwrap();
+    app.handle_event(key(KeyCode::Char('f'))).unwrap();
+    app.handle_event(key(KeyCode::End)).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("package-199"));
+    assert!(!screen.contains("paused at"));
+}
+
+#[test]
+fn snapshot_complete() {
+    let mut app = app_in_mode(AppMode::Complete, |state| {
+        state.install.progress = 100;
+        state.status.info("Installation completed successfully!");
+        let start = Instant::now();
+        let metrics = &mut state.install.metrics;
+        for (offset, phase) in [
+            (0, "Validating configuration"),
+            (3, "Partitioning disk"),
+            (15, "Installing base system"),
+            (420, "Configuring system in chroot"),
+        ] {
+            metrics.begin_phase(phase, start + Duration::from_secs(offset));
+        }
+        metrics.follow_line("[2025-01-01 10:00:00] WARN: No swap configured");
+        metrics.add_downloaded(912 << 20);
+        metrics.packages_installed = 642;
+        metrics.finish(start, start + Duration::from_secs(745));
+    });
+    let screen = render(&mut app);
+    assert!(screen.contains("Packages installed  642"));
+    assert_snapshot("complete", &screen);
+}
+
+#[test]
+fn snapshot_embedded_terminal_without_pty() {
+    let mut app = app_in_mode(AppMode::EmbeddedTerminal, |state| {
+        state.embedded_terminal = Some(PtyTerminalState {
+            tool_name: "cfdisk".to_string(),
+            return_mode: AppMode::DiskTools,
+            return_menu_selection: 0,
+            finished: false,
+        });
+    });
+    assert_snapshot("embedded_terminal", &render(&mut app));
+}
+
+#[test]
+fn snapshot_floating_output() {
+    let mut app = app_in_mode(AppMode::FloatingOutput, |state| {
+        let mut output = FloatingOutputState::new("Disk Health");
+        output.append_line("Checking /dev/sda...".to_string());
+        output.append_line("All checks passed".to_string());
+        output.mark_complete();
+        output.status = "Done".to_string();
+        state.floating_output = Some(output);
+    });
+    assert_snapshot("floating_output", &render(&mut app));
+}
+
+#[test]
+fn snapshot_wipe_progress() {
+    let mut app = app_in_mode(AppMode::FloatingOutput, |state| {
+        let mut output = FloatingOutputState::new("Wiping /dev/sdb");
+        output.append_line("$ wipefs --all --force /dev/sdb".to_string());
+        output.append_line("Writing zeros to /dev/sdb (64.0 GiB)".to_string());
+        output.set_progress(42);
+        output.status = "26.9 GiB of 64.0 GiB".to_string();
+        state.floating_output = Some(output);
+    });
+    let screen = render(&mut app);
+    assert!(screen.contains("42%"));
+    assert_snapshot("wipe_progress", &screen);
+}
+
+#[test]
+fn snapshot_floating_output_docked() {
+    let mut app = app_in_mode(AppMode::FloatingOutput, |state| {
+        let mut output = FloatingOutputState::new("Network Test");
+        output.append_line("PING archlinux.org: 56 data bytes".to_string());
+        output.append_line("3 packets transmitted, 3 received".to_string());
+        state.floating_output = Some(output);
+    });
+
+    // Docked to the bottom half, the tools menu stays readable above it
+    app.handle_event(key(KeyCode::Char('d'))).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("Output window docked to the bottom"));
+    assert_snapshot("floating_output_docked", &screen);
+
+    // Esc closes the window back to the tools menu, where M is not a window
+    // key, so the docked layout is kept for the next tool's output
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    app.handle_event(key(KeyCode::Char('m'))).unwrap();
+    assert_eq!(
+        app.state().floating_layout.dock,
+        archinstall_tui::components::floating_window::WindowDock::Bottom
+    );
+}
+
+#[test]
+fn snapshot_file_browser() {
+    let mut app = app_in_mode(AppMode::FileBrowser, |state| {
+        let dir = PathBuf::from("/configs");
+        state.file_browser = Some(FileBrowserState {
+            current_dir: dir.clone(),
+            entries: vec![
+                FileEntry::parent_dir(PathBuf::from("/")),
+                FileEntry {
+                    name: "desktop.json".to_string(),
+                    path: dir.join("desktop.json"),
+                    is_dir: false,
+                    size: 2048,
+                },
+                FileEntry {
+                    name: "server.json".to_string(),
+                    path: dir.join("server.json"),
+                    is_dir: false,
+                    size: 1024,
+                },
+            ],
+            selected: 1,
+            extensions: vec!["json".to_string()],
+            error: None,
+            complete: false,
+            selected_file: None,
+            scroll_offset: 0,
+        });
+    });
+    assert_snapshot("file_browser", &render(&mut app));
+}
+
+#[test]
+fn snapshot_automated_install_preview() {
+    let path = PathBuf::from("tests/fixtures/configs/01-simple-uefi-gnome.json");
+    let mut app = app_in_mode(AppMode::FileBrowser, |state| {
+        state.privileged = false;
+        state.file_browser = Some(FileBrowserState {
+            current_dir: PathBuf::from("tests/fixtures/configs"),
+            entries: vec![FileEntry {
+                name: "01-simple-uefi-gnome.json".to_string(),
+                path: path.clone(),
+                is_dir: false,
+                size: 2048,
+            }],
+            selected: 0,
+            extensions: vec!["json".to_string()],
+            error: None,
+            complete: false,
+            selected_file: None,
+            scroll_offset: 0,
+        });
+    });
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::ConfirmDialog);
+    assert_snapshot("automated_install_preview", &render(&mut app));
+
+    // Confirming starts the install, which needs root like the guided one
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::AutomatedInstall);
+    assert!(app.state().status.text().contains("needs root"));
+}
+
+#[test]
+fn snapshot_confirm_dialog() {
+    let mut app = app_in_mode(AppMode::ConfirmDialog, |state| {
+        state.pre_dialog_mode = Some(AppMode::DiskTools);
+        state.confirm_dialog = Some(wipe_disk_confirm("/dev/sda", WipeMethod::Zero));
+    });
+    assert_snapshot("confirm_dialog", &render(&mut app));
+}
+
+#[test]
+fn snapshot_recovery_dialog() {
+    let mut app = app_in_mode(AppMode::RecoveryDialog, |state| {
+        state.install.progress = 25;
+        state
+            .install
+            .output
+            .push("Phase 4: Partitioning disk...".to_string());
+        state.install.recovery_dialog = Some(RecoveryDialogState::new(PhaseFailure {
+            phase: "Disk partitioning".to_string(),
+            critical: true,
+        }));
+    });
+    // Critical phases cannot be skipped; the dialog stays open and says why
+    app.handle_event(key(KeyCode::Char('s'))).unwrap();
+    assert_eq!(mode_of(&app), AppMode::RecoveryDialog);
+    assert_snapshot("recovery_dialog", &render(&mut app));
+}
+
+#[test]
+fn snapshot_disk_health() {
+    let json = r#"{
+        "smartctl": {"exit_status": 0},
+        "device": {"name": "/dev/sda", "protocol": "ATA"},
+        "model_name": "Samsung SSD 860 EVO 500GB",
+        "serial_number": "S3Z1NB0K123456",
+        "rotation_rate": 0,
+        "smart_support": {"available": true, "enabled": true},
+        "smart_status": {"passed": true},
+        "ata_smart_attributes": {"table": [
+            {"id": 5, "value": 100, "raw": {"value": 3}},
+            {"id": 177, "value": 25, "raw": {"value": 1650}},
+            {"id": 197, "value": 100, "raw": {"value": 0}}
+        ]},
+        "power_on_time": {"hours": 17532},
+        "temperature": {"current": 38}
+    }"#;
+    let mut app = app_in_mode(AppMode::DiskHealth, |state| {
+        let mut health = DiskHealthState::loading("/dev/sda");
+        health.report = Some(Ok(SmartReport::from_json("/dev/sda", json).unwrap()));
+        state.tools.disk_health = Some(health);
+    });
+    assert_snapshot("disk_health", &render(&mut app));
+
+    // Esc closes the report and returns to the disk tools menu
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::DiskTools);
+    assert!(app.state().tools.disk_health.is_none());
+}
+
+#[test]
+fn snapshot_mount_manager() {
+    let json = r#"{"blockdevices": [
+        {"name":"sda", "path":"/dev/sda", "type":"disk", "size":256060514304, "fstype":null, "label":null, "mountpoints":[null],
+         "children": [
+            {"name":"sda1", "path":"/dev/sda1", "type":"part", "size":536870912, "fstype":"vfat", "label":"EFI", "mountpoints":["/mnt/boot"]},
+            {"name":"sda2", "path":"/dev/sda2", "type":"part", "size":255522586624, "fstype":"crypto_LUKS", "label":null, "mountpoints":[null],
+             "children": [
+                {"name":"cryptroot", "path":"/dev/mapper/cryptroot", "type":"crypt", "size":255506857984, "fstype":"btrfs", "label":"arch", "mountpoints":["/mnt/home", "/mnt"]}
+             ]}
+         ]}
+    ]}"#;
+    let mut app = app_in_mode(AppMode::MountManager, |state| {
+        let mut manager = MountManagerState::loading();
+        manager.set_devices(Ok(mounts::parse_lsblk(json).unwrap()));
+        manager.message = Some("Mounted /dev/sda1 (vfat) at /mnt/boot".to_string());
+        state.tools.mounts = Some(manager);
+    });
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    assert_snapshot("mount_manager", &render(&mut app));
+
+    // Esc closes the manager and returns to the disk tools menu
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::DiskTools);
+}
+
+#[test]
+fn snapshot_partition_check() {
+    let json = r#"{"blockdevices": [
+        {"path":"/dev/sda", "type":"disk", "size":64424509440, "pttype":"gpt", "parttype":null, "parttypename":null, "fstype":null, "mountpoints":[null],
+         "children": [
+            {"path":"/dev/sda1", "type":"part", "size":16777216, "pttype":"gpt", "parttype":"c12a7328-f81f-11d2-ba4b-00a0c93ec93b", "parttypename":"EFI System", "fstype":"vfat", "mountpoints":["/mnt/efi"]},
+            {"path":"/dev/sda2", "type":"part", "size":64406683648, "pttype":"gpt", "parttype":"0fc63daf-8483-4772-8e79-3d69d8477de4", "parttypename":"Linux filesystem", "fstype":"ext4", "mountpoints":["/mnt/home"]}
+         ]}
+    ]}"#;
+    let disks = vec!["/dev/sda".to_string()];
+    let (layout, mounts) = partition_check::parse_lsblk(json, &disks).unwrap();
+    let mut app = app_in_mode(AppMode::PartitionCheck, |state| {
+        state.guided.partition_check = Some(PartitionCheckState {
+            disks,
+            boot_mode: BootMode::Uefi,
+            plan: None,
+            report: Ok(Report::new(BootMode::Uefi, layout, mounts)),
+            scroll: 0,
+        });
+    });
+    assert_snapshot("partition_check", &render(&mut app));
+
+    // Esc returns to the settings without starting anything
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+    assert!(app.state().guided.partition_check.is_none());
+}
+
+#[test]
+fn snapshot_partition_mounts() {
+    let json = r#"{"blockdevices": [
+        {"path":"/dev/sda", "type":"disk", "size":64424509440, "pttype":"gpt", "parttype":null, "parttypename":null, "fstype":null, "mountpoints":[null],
+         "children": [
+            {"path":"/dev/sda1", "type":"part", "size":536870912, "pttype":"gpt", "parttype":"c12a7328-f81f-11d2-ba4b-00a0c93ec93b", "parttypename":"EFI System", "fstype":"vfat", "mountpoints":[null]},
+            {"path":"/dev/sda2", "type":"part", "size":32212254720, "pttype":"gpt", "parttype":"0fc63daf-8483-4772-8e79-3d69d8477de4", "parttypename":"Linux filesystem", "fstype":"ext4", "mountpoints":[null]},
+            {"path":"/dev/sda3", "type":"part", "size":31675383808, "pttype":"gpt", "parttype":"0fc63daf-8483-4772-8e79-3d69d8477de4", "parttypename":"Linux filesystem", "fstype":"ext4", "mountpoints":[null]}
+         ]}
+    ]}"#;
+    let (layout, _) = partition_check::parse_lsblk(json, &["/dev/sda".to_string()]).unwrap();
+    let partitions = layout.into_iter().flat_map(|disk| disk.partitions).collect();
+    let mut app = app_in_mode(AppMode::PartitionMounts, |state| {
+        state.guided.partition_mounts = Some(PartitionMountsState::new(
+            Ok(partitions),
+            MountPlan::parse("/dev/sda1=/efi,/dev/sda2=/:ext4").unwrap(),
+            "ext4".to_string(),
+            "ext4".to_string(),
+        ));
+    });
+    assert_snapshot("partition_mounts", &render(&mut app));
+
+    // m on the third partition keeps its data on /home
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    app.handle_event(key(KeyCode::Char('m'))).unwrap();
+    let plan = app
+        .state()
+        .guided
+        .partition_mounts
+        .as_ref()
+        .unwrap()
+        .plan
+        .to_string();
+    assert_eq!(plan, "/dev/sda1=/efi,/dev/sda2=/:ext4,/dev/sda3=/home");
+}
+
+#[test]
+fn snapshot_install_summary() {
+    let mut app = app_in_mode(AppMode::Summary, |state| {
+        for (name, value) in [
+            ("Disk", "/dev/sda"),
+            ("Partitioning Strategy", "auto_luks_lvm"),
+            ("Hostname", "archbox"),
+            ("Username", "alex"),
+            ("User Password", "secret"),
+        ] {
+            if let Some(option) = state
+                .guided
+                .config
+                .options
+                .iter_mut()
+                .find(|o| o.name == name)
+            {
+                option.value = value.to_string();
+            }
+        }
+        state.guided.summary = Some(InstallSummaryState::new(&state.guided.config, &[]));
+    });
+    let screen = render(&mut app);
+    assert!(screen.contains("/dev/sda will be ERASED"));
+    assert!(!screen.contains("secret"));
+    assert_snapshot("install_summary", &screen);
+
+    // A wrong confirmation keeps the summary open and says what to type
+    for c in "sdb".chars() {
+        app.handle_event(key(KeyCode::Char(c))).unwrap();
+    }
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::Summary);
+    assert!(render(&mut app).contains("Type sda or ERASE exactly"));
+
+    // Esc returns to the settings without starting anything
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+    assert!(app.state().guided.summary.is_none());
+}
+
+#[test]
+fn test_key_events_navigate_between_modes() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+
+    // Main menu: first entry is the guided installer
+    assert!(!app.handle_event(key(KeyCode::Enter)).unwrap());
+    assert_eq!(mode_of(&app), AppMode::GuidedInstaller);
+
+    // 'b' goes back to the main menu
+    assert!(!app.handle_event(key(KeyCode::Char('b'))).unwrap());
+    assert_eq!(mode_of(&app), AppMode::MainMenu);
+}
+
+#[test]
+fn test_clone_system_asks_for_the_source() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+
+    // Fourth main menu entry clones a system, starting from "/"
+    for code in [KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Enter] {
+        app.handle_event(key(code)).unwrap();
+    }
+    assert!(render(&mut app).contains("Source System"));
+
+    app.handle_event(key(KeyCode::Backspace)).unwrap();
+    for c in "/nonexistent".chars() {
+        app.handle_event(key(KeyCode::Char(c))).unwrap();
+    }
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+
+    let state = app.state();
+    assert_eq!(state.mode, AppMode::MainMenu);
+    assert!(state.tools.current.is_none());
+    assert!(state
+        .status
+        .text()
+        .contains("/nonexistent does not hold an Arch Linux system"));
+}
+
+#[test]
+fn test_input_dialog_dims_the_screen_behind_it() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |_| {});
+    for code in [KeyCode::Down, KeyCode::Down, KeyCode::Enter] {
+        app.handle_event(key(code)).unwrap();
+    }
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+    app.draw_to(&mut terminal).expect("draw should succeed");
+
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..WIDTH).map(|x| buffer[(x, 11)].symbol()).collect();
+    assert!(
+        screen.contains("Boot Mode"),
+        "option list hidden: {}",
+        screen
+    );
+    assert_eq!(buffer[(1, 11)].fg, Colors::FG_DIMMED);
+    assert!(buffer[(1, 11)].modifier.contains(Modifier::DIM));
+    // The dialog itself is drawn normally
+    assert!(!buffer[(WIDTH / 2, 16)].modifier.contains(Modifier::DIM));
+}
+
+#[test]
+fn snapshot_grub_theme_preview() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        let options = &mut state.guided.config.options;
+        let position = |name: &str| options.iter().position(|o| o.name == name).unwrap();
+        let (enable, selection) = (position("GRUB Theme"), position("GRUB Theme Selection"));
+        options[enable].value = "Yes".to_string();
+        state.guided.scroll.set_selected(selection);
+    });
+    app.handle_event(key(KeyCode::Enter)).unwrap();
+    let screen = render(&mut app);
+    assert!(screen.contains("Preview: PolyDark"));
+    assert!(screen.contains('▀'));
+    assert_snapshot("grub_theme_preview", &screen);
+
+    // The preview follows the highlighted theme, in the theme's colours
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    app.handle_event(key(KeyCode::Down)).unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+    app.draw_to(&mut terminal).expect("draw should succeed");
+    let buffer = terminal.backend().buffer();
+    let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+    assert!(screen.contains("Preview: CyberPunk"));
+    assert!(buffer
+        .content()
+        .iter()
+        .any(|cell| cell.symbol() == "▀" && cell.fg == Color::Rgb(255, 0, 170)));
+}
+
+#[test]
+fn snapshot_replayed_keystrokes() {
+    // Each tests/fixtures/keys/<name>.keys starts at the main menu and ends
+    // on the screen in tests/snapshots/replay_<name>.txt
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/keys");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    for path in files {
+        let replay = Replay::load(&path).unwrap();
+        let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        assert!(!app.play(&mut terminal, replay.events()).unwrap());
+
+        let name = path.file_stem().unwrap().to_string_lossy();
+        assert_snapshot(&format!("replay_{}", name), &render(&mut app));
+    }
+}
+
+#[test]
+fn test_replay_stops_at_quit() {
+    let replay = Replay::parse("0 Down\n5 q\n5 Enter\n").unwrap();
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    assert!(app.play(&mut terminal, replay.events()).unwrap());
+    assert_eq!(mode_of(&app), AppMode::MainMenu);
+}
+
+#[test]
+fn test_dispatched_actions_match_key_presses() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+
+    // Third main menu entry is the tools menu
+    app.dispatch(Action::Navigate(Movement::Down)).unwrap();
+    app.dispatch(Action::Navigate(Movement::Down)).unwrap();
+    app.dispatch(Action::Select).unwrap();
+    assert_eq!(mode_of(&app), AppMode::ToolsMenu);
+    let via_actions = render(&mut app);
+
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    for code in [KeyCode::Down, KeyCode::Down, KeyCode::Enter] {
+        app.handle_event(key(code)).unwrap();
+    }
+    assert_eq!(render(&mut app), via_actions);
+
+    assert!(!app.dispatch(Action::Back).unwrap());
+    assert_eq!(mode_of(&app), AppMode::MainMenu);
+    assert!(app.dispatch(Action::Quit).unwrap());
+}
+
+#[test]
+fn test_help_overlay_toggles_and_renders() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    let before = render(&mut app);
+
+    app.handle_event(key(KeyCode::Char('?'))).unwrap();
+    assert!(app.state().help_visible);
+    let with_help = render(&mut app);
+    assert_ne!(before, with_help);
+    assert_snapshot("main_menu_help", &with_help);
+}
+
+#[test]
+fn test_f1_opens_help_for_highlighted_option() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.guided.scroll.selected_index = 1;
+    });
+
+    app.handle_event(key(KeyCode::F(1))).unwrap();
+    {
+        let state = app.state();
+        assert!(state.help_visible);
+        assert_eq!(state.help_page, Some(1));
+    }
+    let page = render(&mut app);
+    assert!(page.contains("Secure Boot"));
+    assert!(page.contains("wiki.archlinux.org"));
+    assert_snapshot("guided_installer_option_help", &page);
+
+    // Right moves to the next option's page, Esc closes the browser
+    app.handle_event(key(KeyCode::Right)).unwrap();
+    assert!(render(&mut app).contains("Help: Option 3/"));
+    app.handle_event(key(KeyCode::Esc)).unwrap();
+    assert!(!app.state().help_visible);
+}
+
+#[test]
+fn snapshot_option_wiki_article() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |state| {
+        state.help_visible = true;
+        state.help_page = Some(1);
+        state.help_wiki = Some(Article::Ready {
+            title: "Unified Extensible Firmware Interface/Secure Boot".to_string(),
+            text: "Secure Boot is a security feature found in the **UEFI** standard.\n\n\
+                   ## Implementing Secure Boot\n\n\
+                   - Install `sbctl`\n\
+                   - Enroll your keys"
+                .to_string(),
+        });
+    });
+
+    let article = render(&mut app);
+    assert!(article.contains("Arch Wiki"));
+    assert!(article.contains("Implementing Secure Boot"));
+    assert_snapshot("option_wiki_article", &article);
+
+    // W goes back to the option page without fetching anything
+    app.handle_event(key(KeyCode::Char('w'))).unwrap();
+    assert_eq!(app.state().help_wiki, None);
+    assert!(render(&mut app).contains("Help: Option 2/"));
+}
+
+#[test]
+fn test_quit_key_requests_exit() {
+    let mut app = app_in_mode(AppMode::MainMenu, |_| {});
+    assert!(app.handle_event(key(KeyCode::Char('q'))).unwrap());
+}
+
+#[test]
+fn test_resize_event_is_handled() {
+    let mut app = app_in_mode(AppMode::GuidedInstaller, |_| {});
+    assert!(!app.handle_event(Event::Resize(80, 24)).unwrap());
+    let visible = app.state().guided.scroll.visible_items;
+    assert_eq!(visible, 8);
+}